@@ -0,0 +1,222 @@
+//! Tunable combat values and a deterministic balance simulator
+//!
+//! [`CombatTuning`] bundles the damage and XP settings a game ships with in
+//! `assets/data/combat.ron`. The simulator works with expected values instead
+//! of rolling dice, so tools can chart damage and level pacing while the
+//! values are being tweaked.
+
+use crate::damage::{base_damage, CombatStats, DamageConfig, DamageType};
+use crate::progression::ProgressionConfig;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Where a game keeps its tuned combat values
+pub const TUNING_ASSET_PATH: &str = "assets/data/combat.ron";
+
+/// Combat values that are tuned per game
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CombatTuning {
+    pub damage: DamageConfig,
+    pub progression: ProgressionConfig,
+}
+
+impl CombatTuning {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        Ok(ron::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let ron = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, ron)?;
+        Ok(())
+    }
+}
+
+/// Replace the default resources with the game's tuned values, if present
+pub fn load_tuning(mut commands: Commands) {
+    let path = Path::new(TUNING_ASSET_PATH);
+    if !path.exists() {
+        return;
+    }
+    match CombatTuning::load(path) {
+        Ok(tuning) => {
+            commands.insert_resource(tuning.damage);
+            commands.insert_resource(tuning.progression);
+        }
+        Err(e) => warn!("Failed to load {}: {}", TUNING_ASSET_PATH, e),
+    }
+}
+
+/// Average damage of one hit, including critical hits.
+///
+/// Variance is symmetric and averages out; the minimum damage is applied to
+/// the average rather than to each roll.
+pub fn expected_damage(
+    attacker: &CombatStats,
+    target: &CombatStats,
+    damage_type: DamageType,
+    config: &DamageConfig,
+) -> f32 {
+    let crit_bonus = attacker.crit_chance.clamp(0.0, 1.0) * (attacker.crit_multiplier - 1.0);
+    (base_damage(attacker, target, damage_type) * (1.0 + crit_bonus)).max(config.min_damage)
+}
+
+/// Hits needed to bring `hp` to zero on average
+pub fn hits_to_defeat(
+    attacker: &CombatStats,
+    target: &CombatStats,
+    damage_type: DamageType,
+    config: &DamageConfig,
+    hp: f32,
+) -> u32 {
+    let damage = expected_damage(attacker, target, damage_type, config);
+    if damage <= 0.0 {
+        return u32::MAX;
+    }
+    (hp / damage).ceil() as u32
+}
+
+/// One level of a pacing forecast
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelStep {
+    pub level: u32,
+    /// XP needed to reach this level from the previous one
+    pub xp_required: u32,
+    /// Total XP earned when the level is reached
+    pub total_xp: u64,
+    /// Battles fought when the level is reached
+    pub battles: u64,
+}
+
+/// Battles needed to reach each level up to `max_level`, earning
+/// `xp_per_battle` from every fight
+pub fn level_pacing(
+    config: &ProgressionConfig,
+    xp_per_battle: u32,
+    max_level: u32,
+) -> Vec<LevelStep> {
+    let xp_per_battle = xp_per_battle.max(1) as u64;
+    let mut total_xp = 0u64;
+    (2..=max_level)
+        .map(|level| {
+            let xp_required = config.xp_for_level(level - 1);
+            total_xp += xp_required as u64;
+            LevelStep {
+                level,
+                xp_required,
+                total_xp,
+                battles: total_xp.div_ceil(xp_per_battle),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(attack: f32, defense: f32, crit_chance: f32) -> CombatStats {
+        CombatStats {
+            attack,
+            defense,
+            magic_attack: attack,
+            magic_defense: defense,
+            crit_chance,
+            crit_multiplier: 2.0,
+        }
+    }
+
+    #[test]
+    fn expected_damage_averages_in_critical_hits() {
+        let config = DamageConfig::default();
+        let target = stats(0.0, 5.0, 0.0);
+        // 10 * 2 - 5 = 15 per hit
+        let plain = expected_damage(
+            &stats(10.0, 0.0, 0.0),
+            &target,
+            DamageType::Physical,
+            &config,
+        );
+        assert_eq!(plain, 15.0);
+        // A quarter of the hits deal double damage
+        let crits = expected_damage(
+            &stats(10.0, 0.0, 0.25),
+            &target,
+            DamageType::Physical,
+            &config,
+        );
+        assert_eq!(crits, 18.75);
+        // Crit chances above 1 count as always critical
+        let always = expected_damage(
+            &stats(10.0, 0.0, 3.0),
+            &target,
+            DamageType::Magical,
+            &config,
+        );
+        assert_eq!(always, 30.0);
+    }
+
+    #[test]
+    fn expected_damage_is_at_least_the_minimum() {
+        let config = DamageConfig {
+            variance: 0.0,
+            min_damage: 2.0,
+        };
+        let damage = expected_damage(
+            &stats(1.0, 0.0, 0.0),
+            &stats(0.0, 50.0, 0.0),
+            DamageType::Physical,
+            &config,
+        );
+        assert_eq!(damage, 2.0);
+        assert_eq!(
+            hits_to_defeat(
+                &stats(1.0, 0.0, 0.0),
+                &stats(0.0, 50.0, 0.0),
+                DamageType::Physical,
+                &config,
+                9.0
+            ),
+            5
+        );
+    }
+
+    #[test]
+    fn level_pacing_accumulates_the_curve() {
+        let config = ProgressionConfig {
+            base_xp: 100,
+            xp_growth: 1.5,
+        };
+        let steps = level_pacing(&config, 40, 4);
+        assert_eq!(
+            steps,
+            [
+                LevelStep {
+                    level: 2,
+                    xp_required: 100,
+                    total_xp: 100,
+                    battles: 3,
+                },
+                LevelStep {
+                    level: 3,
+                    xp_required: 150,
+                    total_xp: 250,
+                    battles: 7,
+                },
+                LevelStep {
+                    level: 4,
+                    xp_required: 225,
+                    total_xp: 475,
+                    battles: 12,
+                },
+            ]
+        );
+        assert!(level_pacing(&config, 40, 1).is_empty());
+        // No XP per battle is treated as one
+        assert_eq!(level_pacing(&config, 0, 2)[0].battles, 100);
+    }
+}
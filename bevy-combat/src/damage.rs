@@ -73,7 +73,27 @@ pub fn calculate_damage(
     // Check for critical hit
     let is_critical = rand::random::<f32>() < attacker_stats.crit_chance;
 
-    let base_damage = match damage_type {
+    let mut final_damage = base_damage(attacker_stats, target_stats, damage_type);
+
+    // Apply critical multiplier
+    if is_critical {
+        final_damage *= attacker_stats.crit_multiplier;
+    }
+
+    // Apply variance
+    let variance_factor = 1.0 + (rand::random::<f32>() * 2.0 - 1.0) * config.variance;
+    final_damage *= variance_factor;
+
+    (final_damage.max(config.min_damage), is_critical)
+}
+
+/// Damage before critical hits and variance
+pub fn base_damage(
+    attacker_stats: &CombatStats,
+    target_stats: &CombatStats,
+    damage_type: DamageType,
+) -> f32 {
+    match damage_type {
         DamageType::Physical => (attacker_stats.attack * 2.0 - target_stats.defense).max(0.0),
         DamageType::Magical => {
             (attacker_stats.magic_attack * 2.0 - target_stats.magic_defense).max(0.0)
@@ -89,18 +109,5 @@ pub fn calculate_damage(
             (attacker_stats.attack * 1.5 - target_stats.defense * 0.5).max(0.0)
         }
         DamageType::True => attacker_stats.attack,
-    };
-
-    let mut final_damage = base_damage;
-
-    // Apply critical multiplier
-    if is_critical {
-        final_damage *= attacker_stats.crit_multiplier;
     }
-
-    // Apply variance
-    let variance_factor = 1.0 + (rand::random::<f32>() * 2.0 - 1.0) * config.variance;
-    final_damage *= variance_factor;
-
-    (final_damage.max(config.min_damage), is_critical)
 }
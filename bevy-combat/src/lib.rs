@@ -1,3 +1,4 @@
+pub mod balance;
 pub mod damage;
 pub mod effects;
 pub mod progression;
@@ -15,22 +16,26 @@ impl Plugin for CombatPlugin {
             .register_type::<damage::DamageConfig>()
             .register_type::<effects::EffectRegistry>()
             .register_type::<progression::Progression>()
+            .register_type::<progression::ProgressionConfig>()
             .register_type::<state::CombatState>()
             .register_type::<state::CombatManager>()
             // Add states
             .init_state::<state::CombatState>()
             // Add resources
             .init_resource::<damage::DamageConfig>()
+            .init_resource::<progression::ProgressionConfig>()
             .init_resource::<state::CombatManager>()
             // Add events
             .add_event::<damage::DamageEvent>()
             .add_event::<progression::LevelUpEvent>()
             // Add systems
+            .add_systems(Startup, balance::load_tuning)
             .add_systems(
                 Update,
                 (
                     effects::update_effects,
                     effects::handle_madness,
+                    progression::apply_progression_config,
                     state::manage_combat_state,
                 ),
             );
@@ -39,9 +44,10 @@ impl Plugin for CombatPlugin {
 
 /// Prelude for easy access to combat types
 pub mod prelude {
+    pub use crate::balance::CombatTuning;
     pub use crate::damage::{CombatStats, DamageConfig, DamageEvent, DamageType};
     pub use crate::effects::{EffectRegistry, EffectType, StatusEffect};
-    pub use crate::progression::{LevelUpEvent, Progression};
+    pub use crate::progression::{LevelUpEvent, Progression, ProgressionConfig};
     pub use crate::state::{CombatManager, CombatState};
     pub use crate::CombatPlugin;
 }
@@ -11,18 +11,58 @@ pub struct Progression {
 }
 
 impl Default for Progression {
+    fn default() -> Self {
+        Self::with_config(&ProgressionConfig::default())
+    }
+}
+
+/// XP curve shared by all progressing entities
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+pub struct ProgressionConfig {
+    /// XP needed to go from level 1 to 2
+    pub base_xp: u32,
+    /// Factor by which each further level needs more XP
+    pub xp_growth: f32,
+}
+
+impl Default for ProgressionConfig {
     fn default() -> Self {
         Self {
-            level: 1,
-            experience: 0,
-            next_level_xp: 100,
+            base_xp: 100,
+            xp_growth: 1.2,
+        }
+    }
+}
+
+impl ProgressionConfig {
+    /// XP needed to advance from `level` to the next one
+    pub fn xp_for_level(&self, level: u32) -> u32 {
+        let mut xp = self.base_xp;
+        for _ in 1..level {
+            xp = (xp as f32 * self.xp_growth) as u32;
         }
+        xp
     }
 }
 
 impl Progression {
+    /// Level 1 on a tuned curve
+    pub fn with_config(config: &ProgressionConfig) -> Self {
+        Self {
+            level: 1,
+            experience: 0,
+            next_level_xp: config.xp_for_level(1),
+        }
+    }
+
     /// Add XP and return number of levels gained
     pub fn add_xp(&mut self, amount: u32) -> u32 {
+        self.add_xp_with(amount, &ProgressionConfig::default())
+    }
+
+    /// Add XP following a tuned curve and return number of levels gained
+    pub fn add_xp_with(&mut self, amount: u32, config: &ProgressionConfig) -> u32 {
         self.experience += amount;
         let mut levels_gained = 0;
 
@@ -30,8 +70,7 @@ impl Progression {
             self.experience -= self.next_level_xp;
             self.level += 1;
             levels_gained += 1;
-            // Progressive XP curve (example: each level needs 20% more XP)
-            self.next_level_xp = (self.next_level_xp as f32 * 1.2) as u32;
+            self.next_level_xp = config.xp_for_level(self.level);
         }
 
         levels_gained
@@ -45,18 +84,36 @@ pub struct LevelUpEvent {
     pub new_level: u32,
 }
 
+/// System that puts new progressions, and all of them when the curve
+/// changes (e.g. once the tuned values are loaded), on the configured curve
+pub fn apply_progression_config(
+    config: Res<ProgressionConfig>,
+    mut query: Query<&mut Progression>,
+) {
+    for mut progression in query.iter_mut() {
+        if !config.is_changed() && !progression.is_added() {
+            continue;
+        }
+        let next_level_xp = config.xp_for_level(progression.level);
+        if progression.next_level_xp != next_level_xp {
+            progression.next_level_xp = next_level_xp;
+        }
+    }
+}
+
 /// System that handles XP gain from combat
 pub fn handle_xp_gain(
     _commands: Commands,
     mut level_up_events: EventWriter<LevelUpEvent>,
     mut query: Query<(Entity, &mut Progression)>,
+    config: Res<ProgressionConfig>,
 ) {
     for (entity, mut progression) in query.iter_mut() {
         // In a real game, this would be based on actual combat results
         // For the template, we just show how levels are processed
         if progression.experience >= progression.next_level_xp {
             let old_level = progression.level;
-            let levels_gained = progression.add_xp(0);
+            let levels_gained = progression.add_xp_with(0, &config);
             if levels_gained > 0 {
                 info!(
                     "Entity {:?} leveled up: {} -> {}",
@@ -70,3 +127,47 @@ pub fn handle_xp_gain(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_progression_follows_the_default_curve() {
+        let config = ProgressionConfig::default();
+        assert_eq!(Progression::default().next_level_xp, config.xp_for_level(1));
+    }
+
+    #[test]
+    fn add_xp_follows_a_tuned_curve() {
+        let config = ProgressionConfig {
+            base_xp: 50,
+            xp_growth: 2.0,
+        };
+        let mut progression = Progression::with_config(&config);
+        assert_eq!(progression.next_level_xp, 50);
+
+        // 50 to reach level 2, 100 more to reach level 3
+        assert_eq!(progression.add_xp_with(160, &config), 2);
+        assert_eq!(progression.level, 3);
+        assert_eq!(progression.experience, 10);
+        assert_eq!(progression.next_level_xp, config.xp_for_level(3));
+    }
+
+    #[test]
+    fn loaded_curve_applies_to_existing_progressions() {
+        let mut app = App::new();
+        app.init_resource::<ProgressionConfig>()
+            .add_systems(Update, apply_progression_config);
+        let entity = app.world_mut().spawn(Progression::default()).id();
+        app.update();
+
+        app.insert_resource(ProgressionConfig {
+            base_xp: 40,
+            xp_growth: 1.5,
+        });
+        app.update();
+        let progression = app.world().get::<Progression>(entity).unwrap();
+        assert_eq!(progression.next_level_xp, 40);
+    }
+}
@@ -0,0 +1,654 @@
+//! Generated game data loaded through Bevy's asset system
+//!
+//! Items, enemies, quests, encounter tables, boss scripts, town economies, the
+//! map layout, the achievements, the randomizer spec, the speedrun splits and
+//! the optional postgame plan live in `assets/data/*.ron`; the map images are
+//! in `assets/maps/`. The types are registered for reflection so they show up
+//! in inspectors, and with the `file_watcher` feature of Bevy enabled,
+//! editing a RON file while the game runs reloads it in place.
+//!
+//! ```ignore
+//! App::new()
+//!     .add_plugins(DefaultPlugins)
+//!     .add_plugins(GameDataPlugin)
+//!     .run();
+//! ```
+
+use bevy::asset::{AssetLoader, LoadContext, io::Reader};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::marker::PhantomData;
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct Item {
+    pub id: String,
+    pub name: String,
+    pub price: Option<u32>,
+    pub sold_at: Vec<String>,
+    pub found_in: Vec<String>,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct Enemy {
+    pub id: String,
+    pub name: String,
+    pub boss: bool,
+    pub sprite: Option<String>,
+    pub attacks: Vec<String>,
+    pub habitats: Vec<String>,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct QuestObjective {
+    pub description: String,
+    pub objective_type: String,
+    pub location: String,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct Quest {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub main: bool,
+    pub steps: Vec<QuestObjective>,
+    pub rewards: Vec<String>,
+    /// Ids of the quests that must be completed first
+    pub prerequisites: Vec<String>,
+}
+
+#[derive(Asset, Debug, Clone, Reflect, Deserialize)]
+pub struct ItemDatabase {
+    pub items: Vec<Item>,
+}
+
+#[derive(Asset, Debug, Clone, Reflect, Deserialize)]
+pub struct EnemyDatabase {
+    pub enemies: Vec<Enemy>,
+}
+
+#[derive(Asset, Debug, Clone, Reflect, Deserialize)]
+pub struct QuestDatabase {
+    pub quests: Vec<Quest>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect, Deserialize)]
+pub enum DifficultyPreset {
+    Casual,
+    Normal,
+    Hard,
+}
+
+#[derive(Debug, Clone, Copy, Reflect, Deserialize)]
+pub struct LevelRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct EncounterMember {
+    pub enemy: String,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct EncounterGroup {
+    pub members: Vec<EncounterMember>,
+    pub weight: u32,
+    pub levels: LevelRange,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct EncounterTable {
+    pub region: String,
+    pub player_levels: LevelRange,
+    pub groups: Vec<EncounterGroup>,
+}
+
+impl EncounterTable {
+    /// Pick a group for a roll in `0.0..1.0`, weighted by spawn weight
+    pub fn pick(&self, roll: f32) -> Option<&EncounterGroup> {
+        let total: u32 = self.groups.iter().map(|g| g.weight).sum();
+        let mut target = (roll.clamp(0.0, 1.0) * total as f32) as u32;
+        self.groups.iter().find(|group| {
+            if target < group.weight {
+                return true;
+            }
+            target -= group.weight;
+            false
+        })
+    }
+}
+
+#[derive(Asset, Debug, Clone, Reflect, Deserialize)]
+pub struct EncounterTables {
+    pub difficulty: DifficultyPreset,
+    pub tables: Vec<EncounterTable>,
+}
+
+impl EncounterTables {
+    pub fn region(&self, name: &str) -> Option<&EncounterTable> {
+        self.tables.iter().find(|table| table.region == name)
+    }
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct ArenaHazard {
+    pub name: String,
+    pub description: String,
+    pub interval_turns: u32,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct BossPhase {
+    pub hp_threshold: f32,
+    pub rotation: Vec<String>,
+    pub hazards: Vec<ArenaHazard>,
+    pub transition_line: Option<String>,
+}
+
+impl BossPhase {
+    /// Ability used on a turn of this phase, counted from the phase start
+    pub fn ability(&self, turn: usize) -> Option<&str> {
+        (!self.rotation.is_empty()).then(|| self.rotation[turn % self.rotation.len()].as_str())
+    }
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct BossSprite {
+    pub sprite_id: String,
+    pub description: String,
+    pub scale: u32,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct BossMusicCue {
+    pub track: String,
+    pub mood: String,
+    pub tempo: u16,
+    pub intensify_at_phase: Option<usize>,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct PlotBeat {
+    pub quest: String,
+    pub step: usize,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct BossDesign {
+    pub id: String,
+    pub name: String,
+    pub dungeon: String,
+    pub hp: u32,
+    pub phases: Vec<BossPhase>,
+    pub sprite: BossSprite,
+    pub music: BossMusicCue,
+    pub plot_beats: Vec<PlotBeat>,
+}
+
+impl BossDesign {
+    /// Phase active at an HP fraction
+    pub fn phase_at(&self, hp_fraction: f32) -> Option<&BossPhase> {
+        self.phases
+            .iter()
+            .rev()
+            .find(|phase| hp_fraction <= phase.hp_threshold)
+    }
+}
+
+#[derive(Asset, Debug, Clone, Reflect, Deserialize)]
+pub struct BossRoster {
+    pub bosses: Vec<BossDesign>,
+}
+
+impl BossRoster {
+    pub fn boss(&self, id: &str) -> Option<&BossDesign> {
+        self.bosses.iter().find(|boss| boss.id == id)
+    }
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct StockEntry {
+    pub item: String,
+    pub price: u32,
+    /// Units for sale, `None` for unlimited
+    pub stock: Option<u32>,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct Shop {
+    pub name: String,
+    pub entries: Vec<StockEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Reflect, Deserialize)]
+pub enum ServiceKind {
+    Rest,
+    Save,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct Service {
+    pub kind: ServiceKind,
+    pub price: u32,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct Inn {
+    pub price: u32,
+    /// Start node of the innkeeper in `assets/dialogue/*.yarn`
+    pub dialogue: String,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct TownEconomy {
+    pub town: String,
+    pub shops: Vec<Shop>,
+    pub inn: Option<Inn>,
+    pub services: Vec<Service>,
+}
+
+impl TownEconomy {
+    pub fn shop(&self, name: &str) -> Option<&Shop> {
+        self.shops.iter().find(|shop| shop.name == name)
+    }
+}
+
+#[derive(Asset, Debug, Clone, Reflect, Deserialize)]
+pub struct Economy {
+    pub currency: String,
+    pub towns: Vec<TownEconomy>,
+}
+
+impl Economy {
+    pub fn town(&self, name: &str) -> Option<&TownEconomy> {
+        self.towns.iter().find(|town| town.town == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect, Deserialize)]
+pub enum MarkerKind {
+    Town,
+    Dungeon,
+}
+
+/// Region on the world map; coordinates are fractions of `maps/world.png`
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct MapRegion {
+    pub name: String,
+    pub biome: String,
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct MapRoute {
+    pub from: String,
+    pub to: String,
+    pub connection_type: String,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct MapMarker {
+    pub name: String,
+    pub kind: MarkerKind,
+    pub region: String,
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect, Deserialize)]
+pub enum RoomKind {
+    Entrance,
+    Room,
+    Treasure,
+    Stairs,
+    Boss,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct MiniMapRoom {
+    pub x: i32,
+    pub y: i32,
+    pub kind: RoomKind,
+    pub from: Option<usize>,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct MiniMapFloor {
+    pub rooms: Vec<MiniMapRoom>,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct DungeonMiniMap {
+    pub dungeon: String,
+    pub floors: Vec<MiniMapFloor>,
+}
+
+#[derive(Asset, Debug, Clone, Reflect, Deserialize)]
+pub struct WorldMap {
+    pub regions: Vec<MapRegion>,
+    pub routes: Vec<MapRoute>,
+    pub markers: Vec<MapMarker>,
+    pub dungeons: Vec<DungeonMiniMap>,
+}
+
+impl WorldMap {
+    /// Marker under a point of the map, within `radius` map fractions
+    pub fn marker_at(&self, x: f32, y: f32, radius: f32) -> Option<&MapMarker> {
+        self.markers
+            .iter()
+            .find(|marker| (marker.x - x).hypot(marker.y - y) <= radius)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Reflect, Deserialize)]
+pub enum UnlockCondition {
+    CompleteQuest { quest: String },
+    CompleteQuests { quests: Vec<String> },
+    DefeatBoss { boss: String },
+    CollectItems { items: Vec<String> },
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct AchievementIcon {
+    /// Icon in `assets/achievements/`, without extension
+    pub sprite_id: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct Achievement {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub hidden: bool,
+    pub icon: AchievementIcon,
+    pub unlock: UnlockCondition,
+}
+
+#[derive(Asset, Debug, Clone, Reflect, Deserialize)]
+pub struct AchievementSet {
+    pub game: String,
+    pub achievements: Vec<Achievement>,
+}
+
+impl AchievementSet {
+    /// Achievements unlocked by defeating a boss
+    pub fn for_boss<'a>(&'a self, boss: &'a str) -> impl Iterator<Item = &'a Achievement> {
+        self.achievements.iter().filter(move |achievement| {
+            matches!(&achievement.unlock, UnlockCondition::DefeatBoss { boss: id } if id == boss)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct RemixedDungeon {
+    pub dungeon: String,
+    pub name: String,
+    pub description: String,
+    pub gimmick: String,
+    /// Levels added to every encounter of the original dungeon
+    pub level_bonus: u32,
+    pub new_enemies: Vec<String>,
+    pub rewards: Vec<String>,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct CarryOverRules {
+    pub levels: bool,
+    pub equipment: bool,
+    pub items: bool,
+    pub key_items: bool,
+    pub gold_fraction: f32,
+    pub enemy_scaling: f32,
+}
+
+#[derive(Asset, Debug, Clone, Reflect, Deserialize)]
+pub struct PostgamePlan {
+    pub unlock: String,
+    pub remixed_dungeons: Vec<RemixedDungeon>,
+    pub superboss: BossDesign,
+    pub carry_over: CarryOverRules,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Reflect, Deserialize)]
+pub enum Requirement {
+    Item { item: String },
+    Quest { quest: String },
+    Boss { boss: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect, Deserialize)]
+pub enum CheckKind {
+    Treasure,
+    QuestReward,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct Check {
+    pub id: String,
+    pub name: String,
+    pub kind: CheckKind,
+    pub vanilla_item: String,
+    pub requires: Vec<Requirement>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect, Deserialize)]
+pub enum ItemClass {
+    Progression,
+    Filler,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct PoolItem {
+    pub item: String,
+    pub class: ItemClass,
+    pub count: u32,
+}
+
+/// What completing a quest or defeating a boss requires
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct Gate {
+    pub id: String,
+    pub requires: Vec<Requirement>,
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct RandomizerSettings {
+    pub seed: Option<u64>,
+    pub shuffle_treasures: bool,
+    pub shuffle_quest_rewards: bool,
+}
+
+#[derive(Asset, Debug, Clone, Reflect, Deserialize)]
+pub struct RandomizerSpec {
+    pub settings: RandomizerSettings,
+    pub pool: Vec<PoolItem>,
+    pub checks: Vec<Check>,
+    pub quests: Vec<Gate>,
+    pub bosses: Vec<Gate>,
+    pub goal: Vec<Requirement>,
+}
+
+impl RandomizerSpec {
+    pub fn check(&self, id: &str) -> Option<&Check> {
+        self.checks.iter().find(|check| check.id == id)
+    }
+}
+
+/// Game event that ends a speedrun segment
+#[derive(Debug, Clone, PartialEq, Eq, Reflect, Deserialize)]
+pub enum SplitTrigger {
+    BossDefeated { boss: String },
+    QuestCompleted { quest: String },
+}
+
+#[derive(Debug, Clone, Reflect, Deserialize)]
+pub struct Split {
+    pub name: String,
+    pub trigger: SplitTrigger,
+}
+
+#[derive(Asset, Debug, Clone, Reflect, Deserialize)]
+pub struct SplitDefinitions {
+    pub game: String,
+    pub category: String,
+    pub splits: Vec<Split>,
+}
+
+/// Written only when the postgame phase ran
+const POSTGAME_PATH: &str = "data/game.postgame.ron";
+
+/// Handles to the loaded game data
+#[derive(Resource)]
+pub struct GameData {
+    pub items: Handle<ItemDatabase>,
+    pub enemies: Handle<EnemyDatabase>,
+    pub quests: Handle<QuestDatabase>,
+    pub encounters: Handle<EncounterTables>,
+    pub bosses: Handle<BossRoster>,
+    pub economy: Handle<Economy>,
+    pub map: Handle<WorldMap>,
+    pub achievements: Handle<AchievementSet>,
+    pub randomizer: Handle<RandomizerSpec>,
+    pub splits: Handle<SplitDefinitions>,
+    pub postgame: Option<Handle<PostgamePlan>>,
+}
+
+pub struct GameDataPlugin;
+
+impl Plugin for GameDataPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<ItemDatabase>()
+            .init_asset::<EnemyDatabase>()
+            .init_asset::<QuestDatabase>()
+            .init_asset::<EncounterTables>()
+            .init_asset::<BossRoster>()
+            .init_asset::<Economy>()
+            .init_asset::<WorldMap>()
+            .init_asset::<AchievementSet>()
+            .init_asset::<RandomizerSpec>()
+            .init_asset::<SplitDefinitions>()
+            .init_asset::<PostgamePlan>()
+            .register_asset_reflect::<ItemDatabase>()
+            .register_asset_reflect::<EnemyDatabase>()
+            .register_asset_reflect::<QuestDatabase>()
+            .register_asset_reflect::<EncounterTables>()
+            .register_asset_reflect::<BossRoster>()
+            .register_asset_reflect::<Economy>()
+            .register_asset_reflect::<WorldMap>()
+            .register_asset_reflect::<AchievementSet>()
+            .register_asset_reflect::<RandomizerSpec>()
+            .register_asset_reflect::<SplitDefinitions>()
+            .register_asset_reflect::<PostgamePlan>()
+            .register_type::<Item>()
+            .register_type::<Enemy>()
+            .register_type::<Quest>()
+            .register_type::<QuestObjective>()
+            .register_type::<EncounterTable>()
+            .register_type::<BossDesign>()
+            .register_type::<TownEconomy>()
+            .register_type::<MapMarker>()
+            .register_type::<Achievement>()
+            .register_type::<Check>()
+            .register_type::<Split>()
+            .register_type::<RemixedDungeon>()
+            .register_asset_loader(RonAssetLoader::<ItemDatabase>::new(&["items.ron"]))
+            .register_asset_loader(RonAssetLoader::<EnemyDatabase>::new(&["enemies.ron"]))
+            .register_asset_loader(RonAssetLoader::<QuestDatabase>::new(&["quests.ron"]))
+            .register_asset_loader(RonAssetLoader::<EncounterTables>::new(&["encounters.ron"]))
+            .register_asset_loader(RonAssetLoader::<BossRoster>::new(&["bosses.ron"]))
+            .register_asset_loader(RonAssetLoader::<Economy>::new(&["economy.ron"]))
+            .register_asset_loader(RonAssetLoader::<WorldMap>::new(&["map.ron"]))
+            .register_asset_loader(RonAssetLoader::<AchievementSet>::new(&["achievements.ron"]))
+            .register_asset_loader(RonAssetLoader::<RandomizerSpec>::new(&["randomizer.ron"]))
+            .register_asset_loader(RonAssetLoader::<SplitDefinitions>::new(&["splits.ron"]))
+            .register_asset_loader(RonAssetLoader::<PostgamePlan>::new(&["postgame.ron"]))
+            .add_systems(Startup, load_game_data)
+            .add_systems(
+                Update,
+                (
+                    log_reloads::<ItemDatabase>,
+                    log_reloads::<EnemyDatabase>,
+                    log_reloads::<QuestDatabase>,
+                    log_reloads::<EncounterTables>,
+                    log_reloads::<BossRoster>,
+                    log_reloads::<Economy>,
+                    log_reloads::<WorldMap>,
+                    log_reloads::<AchievementSet>,
+                    log_reloads::<RandomizerSpec>,
+                    log_reloads::<SplitDefinitions>,
+                    log_reloads::<PostgamePlan>,
+                ),
+            );
+    }
+}
+
+fn load_game_data(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(GameData {
+        items: asset_server.load("data/game.items.ron"),
+        enemies: asset_server.load("data/game.enemies.ron"),
+        quests: asset_server.load("data/game.quests.ron"),
+        encounters: asset_server.load("data/game.encounters.ron"),
+        bosses: asset_server.load("data/game.bosses.ron"),
+        economy: asset_server.load("data/game.economy.ron"),
+        map: asset_server.load("data/game.map.ron"),
+        achievements: asset_server.load("data/game.achievements.ron"),
+        randomizer: asset_server.load("data/game.randomizer.ron"),
+        splits: asset_server.load("data/game.splits.ron"),
+        postgame: std::path::Path::new("assets")
+            .join(POSTGAME_PATH)
+            .exists()
+            .then(|| asset_server.load(POSTGAME_PATH)),
+    });
+}
+
+fn log_reloads<T: Asset>(mut events: EventReader<AssetEvent<T>>) {
+    for event in events.read() {
+        if let AssetEvent::Modified { .. } = event {
+            info!("Reloaded {}", std::any::type_name::<T>());
+        }
+    }
+}
+
+/// Loads any deserializable asset from RON
+struct RonAssetLoader<T> {
+    extensions: &'static [&'static str],
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> RonAssetLoader<T> {
+    fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> AssetLoader for RonAssetLoader<T>
+where
+    T: Asset + for<'de> Deserialize<'de>,
+{
+    type Asset = T;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<T, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
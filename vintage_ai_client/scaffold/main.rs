@@ -0,0 +1,20 @@
+//! Generated game prototype
+//!
+//! Loads the exported game data from `assets/data/` and runs the speedrun
+//! timer on top of Bevy's default plugins. With the `file_watcher` feature,
+//! edits to the RON files are picked up while the game runs.
+
+pub mod game_data;
+pub mod speedrun;
+
+use bevy::prelude::*;
+
+use game_data::GameDataPlugin;
+use speedrun::SpeedrunPlugin;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins((GameDataPlugin, SpeedrunPlugin))
+        .run();
+}
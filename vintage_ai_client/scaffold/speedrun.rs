@@ -0,0 +1,151 @@
+//! Optional in-game speedrun timer
+//!
+//! Splits on the segments in `assets/data/game.splits.ron`, the same ones
+//! the LiveSplit run in `assets/speedrun/` lists. Gameplay code starts the
+//! run with [`SpeedrunTimer::start`] when a new game begins and sends a
+//! [`SplitReached`] event whenever a boss falls or a quest is completed; the
+//! timer splits when the event matches the next segment and stops after the
+//! last one. Time is real time, so pauses and loading count as they would on
+//! a runner's own timer. F1 shows and hides the overlay, F2 resets the run.
+//!
+//! ```ignore
+//! App::new()
+//!     .add_plugins(DefaultPlugins)
+//!     .add_plugins((GameDataPlugin, SpeedrunPlugin))
+//!     .run();
+//! ```
+
+use bevy::prelude::*;
+use std::time::Duration;
+
+use super::game_data::{GameData, SplitDefinitions, SplitTrigger};
+
+/// Sent by gameplay code when something a segment can end on happens
+#[derive(Event, Debug, Clone)]
+pub struct SplitReached(pub SplitTrigger);
+
+#[derive(Resource, Debug, Default)]
+pub struct SpeedrunTimer {
+    pub running: bool,
+    pub elapsed: Duration,
+    /// Run time at each completed segment
+    pub splits: Vec<Duration>,
+    pub finished: bool,
+}
+
+impl SpeedrunTimer {
+    pub fn start(&mut self) {
+        *self = Self {
+            running: true,
+            ..default()
+        };
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[derive(Component)]
+struct TimerOverlay;
+
+pub struct SpeedrunPlugin;
+
+impl Plugin for SpeedrunPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpeedrunTimer>()
+            .add_event::<SplitReached>()
+            .add_systems(Startup, spawn_overlay)
+            .add_systems(Update, (tick, split, controls, update_overlay).chain());
+    }
+}
+
+fn tick(time: Res<Time<Real>>, mut timer: ResMut<SpeedrunTimer>) {
+    if timer.running {
+        timer.elapsed += time.delta();
+    }
+}
+
+fn split(
+    mut events: EventReader<SplitReached>,
+    mut timer: ResMut<SpeedrunTimer>,
+    game_data: Option<Res<GameData>>,
+    definitions: Res<Assets<SplitDefinitions>>,
+) {
+    let Some(definitions) = game_data.and_then(|data| definitions.get(&data.splits)) else {
+        events.clear();
+        return;
+    };
+    for SplitReached(trigger) in events.read() {
+        if !timer.running {
+            continue;
+        }
+        let Some(next) = definitions.splits.get(timer.splits.len()) else {
+            continue;
+        };
+        if next.trigger != *trigger {
+            continue;
+        }
+        let time = timer.elapsed;
+        timer.splits.push(time);
+        info!("Split {}: {}", next.name, format_time(time));
+        if timer.splits.len() == definitions.splits.len() {
+            timer.running = false;
+            timer.finished = true;
+        }
+    }
+}
+
+fn controls(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut timer: ResMut<SpeedrunTimer>,
+    mut overlay: Query<&mut Visibility, With<TimerOverlay>>,
+) {
+    if keys.just_pressed(KeyCode::F1) {
+        for mut visibility in &mut overlay {
+            visibility.toggle_visible_hidden();
+        }
+    }
+    if keys.just_pressed(KeyCode::F2) {
+        timer.reset();
+    }
+}
+
+fn spawn_overlay(mut commands: Commands) {
+    commands.spawn((
+        TimerOverlay,
+        Text::new(format_time(Duration::ZERO)),
+        TextFont {
+            font_size: 20.0,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            right: Val::Px(8.0),
+            ..default()
+        },
+        Visibility::Visible,
+    ));
+}
+
+fn update_overlay(timer: Res<SpeedrunTimer>, mut overlay: Query<&mut Text, With<TimerOverlay>>) {
+    if !timer.is_changed() {
+        return;
+    }
+    for mut text in &mut overlay {
+        text.0 = format_time(timer.elapsed);
+    }
+}
+
+/// `m:ss.cc`, with hours once the run passes one
+fn format_time(time: Duration) -> String {
+    let centis = time.as_millis() / 10;
+    let (hours, minutes) = (centis / 360_000, centis / 6_000 % 60);
+    let (seconds, centis) = (centis / 100 % 60, centis % 100);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}.{centis:02}")
+    } else {
+        format!("{minutes}:{seconds:02}.{centis:02}")
+    }
+}
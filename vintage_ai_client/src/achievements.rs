@@ -0,0 +1,376 @@
+//! Achievement sets derived from the game content
+//!
+//! Every quest, boss and dungeon's treasure haul becomes an achievement, with
+//! a few completionist achievements on top. Unlock conditions are data that
+//! reference quest, enemy and item ids, so the game checks them against its
+//! own progress. Icons are requested as sprites in the project art style,
+//! and the set is exported as plain JSON and as a Steamworks achievement
+//! schema in VDF.
+
+use anyhow::Result;
+use image::imageops::FilterType;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::consistency::indexed::{self, IndexedPalette};
+use crate::game_assets::{EnemyDatabase, ItemDatabase, QuestDatabase, asset_id};
+use crate::image::ImageGenerator;
+
+/// Size of Steam achievement icons in pixels
+pub const ICON_SIZE: u32 = 64;
+
+/// Steam's public test app, used until the game has an app id of its own
+pub const TEST_APP_ID: u32 = 480;
+
+/// Achievements per Steam stat, each one a bit of it
+const STEAM_BITS_PER_STAT: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnlockCondition {
+    CompleteQuest {
+        quest: String,
+    },
+    /// Complete every listed quest
+    CompleteQuests {
+        quests: Vec<String>,
+    },
+    DefeatBoss {
+        boss: String,
+    },
+    /// Carry or have carried every listed item
+    CollectItems {
+        items: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AchievementIcon {
+    /// Icon file name under `assets/achievements/`, without extension
+    pub sprite_id: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Achievement {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// Hidden achievements only show their name once unlocked
+    pub hidden: bool,
+    pub icon: AchievementIcon,
+    pub unlock: UnlockCondition,
+}
+
+impl Achievement {
+    /// API name of the achievement on Steamworks, e.g. `ACH_DEFEAT_HYDRA`
+    pub fn steam_api_name(&self) -> String {
+        format!("ACH_{}", self.id.to_uppercase())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AchievementSet {
+    pub game: String,
+    pub achievements: Vec<Achievement>,
+}
+
+impl AchievementSet {
+    /// Derive achievements from the quests, the bosses of the enemy database
+    /// and the items only found as dungeon treasure
+    ///
+    /// The last main quest is hidden so the list doesn't spoil the ending.
+    pub fn derive(
+        game: &str,
+        quests: &QuestDatabase,
+        enemies: &EnemyDatabase,
+        items: &ItemDatabase,
+    ) -> Self {
+        let mut set = Self {
+            game: game.to_string(),
+            achievements: Vec::new(),
+        };
+
+        let last_main = quests.quests.iter().rposition(|quest| quest.main);
+        for (index, quest) in quests.quests.iter().enumerate() {
+            let kind = if quest.main { "quest" } else { "side quest" };
+            set.push(Achievement {
+                id: format!("complete_{}", quest.id),
+                name: quest.name.clone(),
+                description: format!("Complete the {kind} {}.", quest.name),
+                hidden: Some(index) == last_main,
+                icon: icon(
+                    &format!("complete_{}", quest.id),
+                    format!(
+                        "emblem for completing {}: {}",
+                        quest.name, quest.description
+                    ),
+                ),
+                unlock: UnlockCondition::CompleteQuest {
+                    quest: quest.id.clone(),
+                },
+            });
+        }
+        let side_quests: Vec<_> = quests
+            .quests
+            .iter()
+            .filter(|quest| !quest.main)
+            .map(|quest| quest.id.clone())
+            .collect();
+        if side_quests.len() > 1 {
+            set.push(Achievement {
+                id: "all_side_quests".to_string(),
+                name: "Helping Hand".to_string(),
+                description: "Complete every side quest.".to_string(),
+                hidden: false,
+                icon: icon(
+                    "all_side_quests",
+                    "open hand holding a bundle of quest scrolls".to_string(),
+                ),
+                unlock: UnlockCondition::CompleteQuests {
+                    quests: side_quests,
+                },
+            });
+        }
+
+        for boss in enemies.enemies.iter().filter(|enemy| enemy.boss) {
+            set.push(Achievement {
+                id: format!("defeat_{}", boss.id),
+                name: format!("{} Vanquished", boss.name),
+                description: format!("Defeat {}.", boss.name),
+                hidden: false,
+                icon: icon(
+                    &format!("defeat_{}", boss.id),
+                    format!("trophy showing the defeated {}", boss.name),
+                ),
+                unlock: UnlockCondition::DefeatBoss {
+                    boss: boss.id.clone(),
+                },
+            });
+        }
+
+        let mut hauls: Vec<(&str, Vec<String>)> = Vec::new();
+        let treasures = items
+            .items
+            .iter()
+            .filter(|item| item.sold_at.is_empty() && !item.found_in.is_empty());
+        for item in treasures {
+            for dungeon in &item.found_in {
+                match hauls.iter_mut().find(|(name, _)| name == dungeon) {
+                    Some((_, items)) => items.push(item.id.clone()),
+                    None => hauls.push((dungeon, vec![item.id.clone()])),
+                }
+            }
+        }
+        for (dungeon, items) in &hauls {
+            let id = asset_id(dungeon);
+            set.push(Achievement {
+                id: format!("treasures_{id}"),
+                name: format!("{dungeon} Treasure Hunter"),
+                description: format!("Find every treasure in {dungeon}."),
+                hidden: false,
+                icon: icon(
+                    &format!("treasures_{id}"),
+                    format!("open treasure chest overflowing with riches from {dungeon}"),
+                ),
+                unlock: UnlockCondition::CollectItems {
+                    items: items.clone(),
+                },
+            });
+        }
+        if hauls.len() > 1 {
+            let mut items: Vec<String> = Vec::new();
+            for id in hauls.into_iter().flat_map(|(_, items)| items) {
+                if !items.contains(&id) {
+                    items.push(id);
+                }
+            }
+            set.push(Achievement {
+                id: "all_treasures".to_string(),
+                name: "Collector".to_string(),
+                description: "Find every dungeon treasure.".to_string(),
+                hidden: false,
+                icon: icon(
+                    "all_treasures",
+                    "crown resting on a pile of gold".to_string(),
+                ),
+                unlock: UnlockCondition::CollectItems { items },
+            });
+        }
+
+        set
+    }
+
+    pub fn achievement(&self, id: &str) -> Option<&Achievement> {
+        self.achievements
+            .iter()
+            .find(|achievement| achievement.id == id)
+    }
+
+    /// Keep the first achievement of an id, names can collide after
+    /// [`asset_id`]
+    fn push(&mut self, achievement: Achievement) {
+        if self.achievement(&achievement.id).is_none() {
+            self.achievements.push(achievement);
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Export as a Steamworks achievement schema
+    ///
+    /// Achievements are packed 32 to a stat in list order, so appending
+    /// achievements keeps the bits of the existing ones. Icons reference the
+    /// files written by [`write_icons`].
+    pub fn to_vdf(&self, app_id: u32) -> String {
+        let mut vdf = String::new();
+        let _ = writeln!(vdf, "\"{app_id}\"\n{{");
+        let _ = writeln!(vdf, "\t\"gamename\"\t\"{}\"", vdf_escape(&self.game));
+        vdf.push_str("\t\"version\"\t\"1\"\n\t\"stats\"\n\t{\n");
+        for (index, chunk) in self.achievements.chunks(STEAM_BITS_PER_STAT).enumerate() {
+            let stat = index + 1;
+            let _ = writeln!(vdf, "\t\t\"{stat}\"\n\t\t{{\n\t\t\t\"bits\"\n\t\t\t{{");
+            for (bit, achievement) in chunk.iter().enumerate() {
+                let token = format!("NEW_ACHIEVEMENT_{stat}_{bit}");
+                let _ = writeln!(vdf, "\t\t\t\t\"{bit}\"\n\t\t\t\t{{");
+                let _ = writeln!(
+                    vdf,
+                    "\t\t\t\t\t\"name\"\t\"{}\"",
+                    achievement.steam_api_name()
+                );
+                vdf.push_str("\t\t\t\t\t\"display\"\n\t\t\t\t\t{\n");
+                let _ = writeln!(
+                    vdf,
+                    "\t\t\t\t\t\t\"name\"\t{{ \"english\"\t\"{}\"\t\"token\"\t\"{token}_NAME\" }}",
+                    vdf_escape(&achievement.name)
+                );
+                let _ = writeln!(
+                    vdf,
+                    "\t\t\t\t\t\t\"desc\"\t{{ \"english\"\t\"{}\"\t\"token\"\t\"{token}_DESC\" }}",
+                    vdf_escape(&achievement.description)
+                );
+                let _ = writeln!(
+                    vdf,
+                    "\t\t\t\t\t\t\"hidden\"\t\"{}\"",
+                    u8::from(achievement.hidden)
+                );
+                let _ = writeln!(
+                    vdf,
+                    "\t\t\t\t\t\t\"icon\"\t\"{}.png\"",
+                    achievement.icon.sprite_id
+                );
+                let _ = writeln!(
+                    vdf,
+                    "\t\t\t\t\t\t\"icon_gray\"\t\"{}_locked.png\"",
+                    achievement.icon.sprite_id
+                );
+                vdf.push_str("\t\t\t\t\t}\n");
+                let _ = writeln!(vdf, "\t\t\t\t\t\"bit\"\t\"{bit}\"\n\t\t\t\t}}");
+            }
+            // Stat type 4 holds achievement bits
+            let _ = writeln!(
+                vdf,
+                "\t\t\t}}\n\t\t\t\"type\"\t\"4\"\n\t\t\t\"id\"\t\"{stat}\"\n\t\t}}"
+            );
+        }
+        vdf.push_str("\t}\n}\n");
+        vdf
+    }
+
+    /// Generate every icon as a sprite in the project art style
+    pub async fn generate_icons(&self, images: &ImageGenerator) -> Result<Vec<(String, Vec<u8>)>> {
+        let mut icons = Vec::with_capacity(self.achievements.len());
+        for achievement in &self.achievements {
+            let data = images
+                .generate_sprite("achievement_icon", &achievement.icon.description, None)
+                .await?;
+            icons.push((achievement.icon.sprite_id.clone(), data));
+        }
+        Ok(icons)
+    }
+}
+
+/// Write icons at Steam's icon size as indexed PNGs in the style palette,
+/// each with a locked variant in its grayscale
+pub fn write_icons(
+    icons: &[(String, Vec<u8>)],
+    palette: &IndexedPalette,
+    dir: &Path,
+) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let locked_palette = palette.grayscale();
+    for (sprite_id, data) in icons {
+        let icon = image::load_from_memory(data)?
+            .resize_exact(ICON_SIZE, ICON_SIZE, FilterType::Nearest)
+            .to_rgba8();
+        indexed::write_png(&icon, palette, &dir.join(format!("{sprite_id}.png")))?;
+        // Keep the indices of the colored icon so both share one layout
+        let mut locked = icon.clone();
+        for (pixel, index) in locked.pixels_mut().zip(palette.index(&icon)) {
+            let gray = locked_palette.colors[index as usize];
+            *pixel = image::Rgba([gray.r, gray.g, gray.b, gray.a]);
+        }
+        indexed::write_png(
+            &locked,
+            &locked_palette,
+            &dir.join(format!("{sprite_id}_locked.png")),
+        )?;
+    }
+    Ok(())
+}
+
+fn icon(id: &str, subject: String) -> AchievementIcon {
+    AchievementIcon {
+        sprite_id: format!("achievement_{id}"),
+        description: format!("Achievement icon, {subject}, centered on a plain background"),
+    }
+}
+
+fn vdf_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consistency::Color;
+
+    #[test]
+    fn icons_are_indexed_with_a_grayscale_locked_variant() {
+        let red = Color::new(200, 40, 40);
+        let palette = IndexedPalette::from_colors(&[red, Color::new(20, 20, 20)]).unwrap();
+        let mut png = Vec::new();
+        image::DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(
+            16,
+            16,
+            image::Rgba([200, 40, 40, 255]),
+        ))
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .unwrap();
+
+        let dir = std::env::temp_dir().join(format!("icons_{}", uuid::Uuid::new_v4()));
+        write_icons(&[("achievement_warden".to_string(), png)], &palette, &dir).unwrap();
+
+        for (file, expected) in [
+            ("achievement_warden.png", [200, 40, 40, 255]),
+            ("achievement_warden_locked.png", {
+                let luma = image::Pixel::to_luma(&image::Rgb([200, 40, 40]))[0];
+                [luma, luma, luma, 255]
+            }),
+        ] {
+            let path = dir.join(file);
+            let decoder =
+                png::Decoder::new(std::io::BufReader::new(std::fs::File::open(&path).unwrap()));
+            assert_eq!(
+                decoder.read_info().unwrap().info().color_type,
+                png::ColorType::Indexed
+            );
+            let icon = image::open(&path).unwrap().to_rgba8();
+            assert_eq!(icon.dimensions(), (ICON_SIZE, ICON_SIZE));
+            assert_eq!(icon.get_pixel(0, 0).0, expected);
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
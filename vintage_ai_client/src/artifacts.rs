@@ -0,0 +1,310 @@
+//! Duplicate detection for generated project artifacts
+//!
+//! Every generated text, image or audio asset is registered with the
+//! embedding of the description it was generated from. Before generating a
+//! new asset the description is embedded and compared against the index, so
+//! a near duplicate can be reused instead of paying for another generation.
+//!
+//! The index is also the project's manifest of user tags ("chapter-2",
+//! "needs-redo", "final"), which organize artifacts in the wizard and
+//! select them for batch operations.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use super::embeddings::EmbeddingsGenerator;
+use super::slugs::{SlugCase, slugify};
+
+/// Similarity above which a new asset is reported as a duplicate
+pub const DEFAULT_DUPLICATE_THRESHOLD: f32 = 0.9;
+
+/// File name of the artifact index inside a project directory
+pub const ARTIFACT_INDEX_FILE: &str = "artifacts.json";
+
+/// Kind of generated asset; only artifacts of the same kind are compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    Text,
+    Image,
+    Audio,
+}
+
+/// A generated asset and the embedding of the description it came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactRecord {
+    /// Asset name, e.g. `forest_tileset_2`
+    pub name: String,
+    pub kind: ArtifactKind,
+    /// Prompt or description the asset was generated from
+    pub description: String,
+    pub embedding: Vec<f32>,
+    /// Where the asset was written, if it was saved to disk
+    pub path: Option<PathBuf>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// An existing artifact that closely matches a new request
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateMatch {
+    pub name: String,
+    pub path: Option<PathBuf>,
+    /// Cosine similarity of the two descriptions (0.0 - 1.0)
+    pub similarity: f32,
+}
+
+impl fmt::Display for DuplicateMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "this is {:.0}% similar to '{}'",
+            self.similarity * 100.0,
+            self.name
+        )
+    }
+}
+
+/// Outcome of checking a description against the index
+#[derive(Debug, Clone)]
+pub struct DuplicateCheck {
+    /// Embedding of the checked description, for registering the new asset
+    pub embedding: Vec<f32>,
+    /// Closest existing artifact above the threshold
+    pub duplicate: Option<DuplicateMatch>,
+}
+
+/// Embedding index of a project's generated artifacts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactIndex {
+    pub artifacts: Vec<ArtifactRecord>,
+    /// Similarity above which a new asset is reported as a duplicate
+    #[serde(default = "default_threshold")]
+    pub threshold: f32,
+    /// Tags by artifact name. Names need not be registered above, so
+    /// artifacts tracked elsewhere, like design steps, can be tagged too.
+    #[serde(default)]
+    pub tags: BTreeMap<String, BTreeSet<String>>,
+}
+
+fn default_threshold() -> f32 {
+    DEFAULT_DUPLICATE_THRESHOLD
+}
+
+impl Default for ArtifactIndex {
+    fn default() -> Self {
+        Self {
+            artifacts: Vec::new(),
+            threshold: DEFAULT_DUPLICATE_THRESHOLD,
+            tags: BTreeMap::new(),
+        }
+    }
+}
+
+impl ArtifactIndex {
+    /// Create an empty index with the default threshold
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load an index from disk, returning an empty one if the file doesn't exist
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read artifact index {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse artifact index {}", path.display()))
+    }
+
+    /// Write the index to disk
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write artifact index {}", path.display()))
+    }
+
+    /// Set the duplicate threshold
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Add or replace an artifact by name
+    pub fn register(&mut self, record: ArtifactRecord) {
+        self.artifacts.retain(|a| a.name != record.name);
+        self.artifacts.push(record);
+    }
+
+    /// Remove an artifact by name, along with its tags
+    pub fn remove(&mut self, name: &str) -> Option<ArtifactRecord> {
+        let idx = self.artifacts.iter().position(|a| a.name == name)?;
+        self.tags.remove(name);
+        Some(self.artifacts.remove(idx))
+    }
+
+    /// Tag an artifact. Tags are kebab-cased, so "Needs redo" and
+    /// "needs-redo" are the same tag. Returns false if the artifact
+    /// already had the tag or the tag is empty.
+    pub fn tag(&mut self, name: &str, tag: &str) -> bool {
+        let tag = slugify(tag, SlugCase::Kebab);
+        if tag.is_empty() {
+            return false;
+        }
+        self.tags.entry(name.to_string()).or_default().insert(tag)
+    }
+
+    /// Remove a tag from an artifact; returns false if it didn't have it
+    pub fn untag(&mut self, name: &str, tag: &str) -> bool {
+        let tag = slugify(tag, SlugCase::Kebab);
+        let Some(tags) = self.tags.get_mut(name) else {
+            return false;
+        };
+        let removed = tags.remove(&tag);
+        if tags.is_empty() {
+            self.tags.remove(name);
+        }
+        removed
+    }
+
+    /// Tags of an artifact, sorted
+    pub fn tags_of(&self, name: &str) -> impl Iterator<Item = &str> {
+        self.tags
+            .get(name)
+            .into_iter()
+            .flat_map(|tags| tags.iter().map(String::as_str))
+    }
+
+    pub fn has_tag(&self, name: &str, tag: &str) -> bool {
+        self.tags
+            .get(name)
+            .is_some_and(|tags| tags.contains(&slugify(tag, SlugCase::Kebab)))
+    }
+
+    /// Names of the artifacts with the tag, sorted
+    pub fn tagged(&self, tag: &str) -> Vec<&str> {
+        let tag = slugify(tag, SlugCase::Kebab);
+        self.tags
+            .iter()
+            .filter(|(_, tags)| tags.contains(&tag))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Every tag in use, sorted
+    pub fn all_tags(&self) -> BTreeSet<&str> {
+        self.tags
+            .values()
+            .flat_map(|tags| tags.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// Artifacts of any kind by similarity to the embedding, most similar first
+    pub fn rank(&self, embedding: &[f32], limit: usize) -> Vec<(&ArtifactRecord, f32)> {
+        let mut ranked: Vec<_> = self
+            .artifacts
+            .iter()
+            .map(|a| {
+                (
+                    a,
+                    EmbeddingsGenerator::cosine_similarity(embedding, &a.embedding),
+                )
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranked.truncate(limit);
+        ranked
+    }
+
+    /// Most similar artifact of the same kind above the threshold
+    pub fn find_duplicate(&self, kind: ArtifactKind, embedding: &[f32]) -> Option<DuplicateMatch> {
+        self.artifacts
+            .iter()
+            .filter(|a| a.kind == kind)
+            .map(|a| {
+                (
+                    a,
+                    EmbeddingsGenerator::cosine_similarity(embedding, &a.embedding),
+                )
+            })
+            .filter(|(_, similarity)| *similarity >= self.threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(a, similarity)| DuplicateMatch {
+                name: a.name.clone(),
+                path: a.path.clone(),
+                similarity,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(name: &str, kind: ArtifactKind, embedding: Vec<f32>) -> ArtifactRecord {
+        ArtifactRecord {
+            name: name.to_string(),
+            kind,
+            description: String::new(),
+            embedding,
+            path: Some(PathBuf::from(format!("{name}.png"))),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn finds_the_closest_artifact_of_the_same_kind() {
+        let mut index = ArtifactIndex::new();
+        index.register(record(
+            "forest_tileset",
+            ArtifactKind::Image,
+            vec![1.0, 0.0],
+        ));
+        index.register(record(
+            "forest_tileset_2",
+            ArtifactKind::Image,
+            vec![0.95, 0.3],
+        ));
+        index.register(record("forest_text", ArtifactKind::Text, vec![1.0, 0.0]));
+
+        let duplicate = index
+            .find_duplicate(ArtifactKind::Image, &[1.0, 0.05])
+            .unwrap();
+        assert_eq!(duplicate.name, "forest_tileset");
+        assert_eq!(duplicate.path, Some(PathBuf::from("forest_tileset.png")));
+        assert!(duplicate.similarity > 0.99);
+        assert!(
+            index
+                .find_duplicate(ArtifactKind::Audio, &[1.0, 0.0])
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn ignores_artifacts_below_the_threshold() {
+        let mut index = ArtifactIndex::new().with_threshold(0.99);
+        index.register(record(
+            "desert_tileset",
+            ArtifactKind::Image,
+            vec![1.0, 0.0],
+        ));
+
+        assert!(
+            index
+                .find_duplicate(ArtifactKind::Image, &[0.9, 0.4])
+                .is_none()
+        );
+        assert!(
+            index
+                .find_duplicate(ArtifactKind::Image, &[2.0, 0.0])
+                .is_some()
+        );
+    }
+}
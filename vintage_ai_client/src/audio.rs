@@ -9,10 +9,9 @@
 use anyhow::{Context, Result};
 use async_openai::{
     Client,
-    config::OpenAIConfig,
     types::chat::{
         ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs,
-        CreateChatCompletionRequestArgs,
+        CreateChatCompletionRequestArgs, CreateChatCompletionResponse,
     },
 };
 use minijinja::Environment;
@@ -25,13 +24,26 @@ use tokio::sync::Mutex;
 use super::{
     AiGenerator,
     cache::{AiCache, CachedData},
+    credentials::{KeyedConfig, retry_keys},
+    prompts::PromptLibrary,
+    quota,
+    telemetry::RequestTimer,
     tokens::TokenCounter,
 };
 
+/// Chat model that writes music and sound effect descriptions
+pub const DESCRIPTION_MODEL: &str = "gpt-4o-mini";
+
+/// Completion limit of a music description
+pub const MAX_MUSIC_TOKENS: u32 = 2000;
+
+/// Completion limit of a sound effect description
+pub const MAX_SOUND_EFFECT_TOKENS: u32 = 1000;
+
 /// Audio generator for music and sound effects
 #[derive(Clone)]
 pub struct AudioGenerator {
-    client: Arc<Client<OpenAIConfig>>,
+    client: Arc<Client<KeyedConfig>>,
     cache: Arc<Mutex<AiCache>>,
     token_counter: Arc<Mutex<TokenCounter>>,
     template_env: Arc<Mutex<Environment<'static>>>,
@@ -121,35 +133,13 @@ impl AudioConfig {
 impl AudioGenerator {
     /// Create a new audio generator
     pub fn new(
-        client: Arc<Client<OpenAIConfig>>,
+        client: Arc<Client<KeyedConfig>>,
         cache: Arc<Mutex<AiCache>>,
         token_counter: Arc<Mutex<TokenCounter>>,
+        prompts: &PromptLibrary,
     ) -> Self {
-        let mut env = Environment::new();
-
-        // Load all audio prompt templates
-        let templates = [
-            (
-                "theme_song",
-                include_str!("../prompts/audio/theme_song.jinja"),
-            ),
-            (
-                "battle_music",
-                include_str!("../prompts/audio/battle_music.jinja"),
-            ),
-            (
-                "victory_fanfare",
-                include_str!("../prompts/audio/victory_fanfare.jinja"),
-            ),
-            (
-                "sound_effect",
-                include_str!("../prompts/audio/sound_effect.jinja"),
-            ),
-        ];
-
-        for (name, template) in templates {
-            env.add_template(name, template).ok();
-        }
+        // Audio prompt templates, overridden by the project's
+        let env = prompts.environment("audio");
 
         Self {
             client,
@@ -165,34 +155,10 @@ impl AudioGenerator {
         music_type: &str,
         config: AudioConfig,
     ) -> Result<MusicDescription> {
-        // Determine which template to use
-        let template_name = match music_type {
-            "battle" => "battle_music",
-            "victory" => "victory_fanfare",
-            "theme" | "main" => "theme_song",
-            _ => "theme_song", // default
-        };
-
-        // Prepare context for template
-        let context = json!({
-            "game_name": "Vintage RPG",
-            "style": config.style,
-            "mood": match music_type {
-                "battle" => "intense and dramatic",
-                "victory" => "triumphant and celebratory",
-                "exploration" => "mysterious and adventurous",
-                _ => "epic and nostalgic"
-            },
-            "tempo": config.tempo,
-            "key": config.key,
-            "time_signature": config.time_signature,
-            "duration": config.duration,
-            "instrumentation": config.instruments.join(", "),
-            "genre": "16-bit RPG",
-            "constraints": "Use only retro synthesizer sounds, chiptune elements",
-        });
+        let prompt = self.music_prompt(music_type, &config).await?;
+        let timer = RequestTimer::start("audio");
 
-        // Generate cache key
+        // Generate cache key from the prompt, so an edited template is requested again
         let mut params = HashMap::new();
         params.insert("type".to_string(), music_type.to_string());
         params.insert("style".to_string(), config.style.clone());
@@ -202,25 +168,18 @@ impl AudioGenerator {
             .cache
             .lock()
             .await
-            .generate_key("audio_music", template_name, &params);
+            .key_for("audio_music", &prompt, &params)
+            .await;
 
         // Check cache
         if let Some(cached) = self.cache.lock().await.get(&cache_key).await
             && let CachedData::Text(data) = &cached.data
             && let Ok(description) = serde_json::from_str::<MusicDescription>(data)
         {
+            timer.cache_hit(self.client.config().provider());
             return Ok(description);
         }
 
-        // Render template
-        let env = self.template_env.lock().await;
-        let template = env
-            .get_template(template_name)
-            .context("Failed to get audio template")?;
-        let prompt = template
-            .render(&context)
-            .context("Failed to render audio template")?;
-
         // Create message
         let messages = vec![
             ChatCompletionRequestSystemMessageArgs::default()
@@ -235,13 +194,16 @@ impl AudioGenerator {
 
         // Make API call
         let request = CreateChatCompletionRequestArgs::default()
-            .model("gpt-4o-mini")
+            .model(DESCRIPTION_MODEL)
             .messages(messages)
             .temperature(0.8)
-            .max_tokens(2000u32)
+            .max_tokens(MAX_MUSIC_TOKENS)
             .build()?;
 
-        let response = self.client.chat().create(request).await?;
+        let response: CreateChatCompletionResponse = retry_keys(&self.client, || {
+            quota::send(&self.client, "/chat/completions", &request)
+        })
+        .await?;
         let content = response
             .choices
             .first()
@@ -264,21 +226,65 @@ impl AudioGenerator {
             .await?;
 
         // Track usage
+        let (mut tokens, mut cost) = (0, 0.0);
         if let Some(usage) = response.usage {
-            self.token_counter
+            tokens = usage.total_tokens as usize;
+            cost = self
+                .token_counter
                 .lock()
                 .await
                 .record_usage(
-                    "gpt-4o-mini",
+                    DESCRIPTION_MODEL,
                     usage.prompt_tokens as usize,
                     usage.completion_tokens as usize,
                 )
                 .await?;
         }
+        timer.finish(self.client.config().provider(), tokens, cost, false);
 
         Ok(description)
     }
 
+    /// Render the music template for a type of track, as sent with
+    /// [`generate_music_description`](Self::generate_music_description)
+    pub async fn music_prompt(&self, music_type: &str, config: &AudioConfig) -> Result<String> {
+        // Determine which template to use
+        let template_name = match music_type {
+            "battle" => "battle_music",
+            "victory" => "victory_fanfare",
+            "theme" | "main" => "theme_song",
+            _ => "theme_song", // default
+        };
+
+        // Prepare context for template
+        let context = json!({
+            "game_name": "Vintage RPG",
+            "style": config.style,
+            "mood": match music_type {
+                "battle" => "intense and dramatic",
+                "victory" => "triumphant and celebratory",
+                "exploration" => "mysterious and adventurous",
+                _ => "epic and nostalgic"
+            },
+            "tempo": config.tempo,
+            "key": config.key,
+            "time_signature": config.time_signature,
+            "duration": config.duration,
+            "instrumentation": config.instruments.join(", "),
+            "genre": "16-bit RPG",
+            "constraints": "Use only retro synthesizer sounds, chiptune elements",
+        });
+
+        // Render template
+        let env = self.template_env.lock().await;
+        let template = env
+            .get_template(template_name)
+            .context("Failed to get audio template")?;
+        template
+            .render(&context)
+            .context("Failed to render audio template")
+    }
+
     /// Parse AI response into structured music description
     fn parse_music_description(
         &self,
@@ -343,16 +349,10 @@ impl AudioGenerator {
         effect_type: &str,
         duration: f32,
     ) -> Result<SoundEffectDescription> {
-        // Prepare context for template
-        let context = json!({
-            "effect_type": effect_type,
-            "duration": duration,
-            "constraints": "16-bit era limitations",
-            "waveforms": ["square", "triangle", "sawtooth", "noise"],
-            "console": "SNES/Genesis era",
-        });
+        let prompt = self.sound_effect_prompt(effect_type, duration).await?;
+        let timer = RequestTimer::start("audio");
 
-        // Generate cache key
+        // Generate cache key from the prompt, so an edited template is requested again
         let mut params = HashMap::new();
         params.insert("type".to_string(), effect_type.to_string());
         params.insert("duration".to_string(), duration.to_string());
@@ -361,25 +361,18 @@ impl AudioGenerator {
             .cache
             .lock()
             .await
-            .generate_key("audio_sfx", effect_type, &params);
+            .key_for("audio_sfx", &prompt, &params)
+            .await;
 
         // Check cache
         if let Some(cached) = self.cache.lock().await.get(&cache_key).await
             && let CachedData::Text(data) = &cached.data
             && let Ok(sfx) = serde_json::from_str::<SoundEffectDescription>(data)
         {
+            timer.cache_hit(self.client.config().provider());
             return Ok(sfx);
         }
 
-        // Render template
-        let env = self.template_env.lock().await;
-        let template = env
-            .get_template("sound_effect")
-            .context("Failed to get sound effect template")?;
-        let prompt = template
-            .render(&context)
-            .context("Failed to render sound effect template")?;
-
         // Create message
         let messages = vec![
             ChatCompletionRequestSystemMessageArgs::default()
@@ -394,13 +387,16 @@ impl AudioGenerator {
 
         // Make API call
         let request = CreateChatCompletionRequestArgs::default()
-            .model("gpt-4o-mini")
+            .model(DESCRIPTION_MODEL)
             .messages(messages)
             .temperature(0.7)
-            .max_tokens(1000u32)
+            .max_tokens(MAX_SOUND_EFFECT_TOKENS)
             .build()?;
 
-        let response = self.client.chat().create(request).await?;
+        let response: CreateChatCompletionResponse = retry_keys(&self.client, || {
+            quota::send(&self.client, "/chat/completions", &request)
+        })
+        .await?;
         let content = response
             .choices
             .first()
@@ -423,21 +419,47 @@ impl AudioGenerator {
             .await?;
 
         // Track usage
+        let (mut tokens, mut cost) = (0, 0.0);
         if let Some(usage) = response.usage {
-            self.token_counter
+            tokens = usage.total_tokens as usize;
+            cost = self
+                .token_counter
                 .lock()
                 .await
                 .record_usage(
-                    "gpt-4o-mini",
+                    DESCRIPTION_MODEL,
                     usage.prompt_tokens as usize,
                     usage.completion_tokens as usize,
                 )
                 .await?;
         }
+        timer.finish(self.client.config().provider(), tokens, cost, false);
 
         Ok(sfx)
     }
 
+    /// Render the sound effect template, as sent with
+    /// [`generate_sound_effect`](Self::generate_sound_effect)
+    pub async fn sound_effect_prompt(&self, effect_type: &str, duration: f32) -> Result<String> {
+        // Prepare context for template
+        let context = json!({
+            "effect_type": effect_type,
+            "duration": duration,
+            "constraints": "16-bit era limitations",
+            "waveforms": ["square", "triangle", "sawtooth", "noise"],
+            "console": "SNES/Genesis era",
+        });
+
+        // Render template
+        let env = self.template_env.lock().await;
+        let template = env
+            .get_template("sound_effect")
+            .context("Failed to get sound effect template")?;
+        template
+            .render(&context)
+            .context("Failed to render sound effect template")
+    }
+
     /// Parse AI response into structured sound effect
     fn parse_sound_effect(
         &self,
@@ -582,13 +604,14 @@ impl AudioGenerator {
 #[async_trait::async_trait]
 impl AiGenerator for AudioGenerator {
     async fn estimate_tokens(&self, request: &str) -> Result<usize> {
-        // Audio generation typically uses less tokens for descriptions
-        Ok(request.len() / 4)
+        // Audio descriptions are produced by a chat model
+        let counter = self.token_counter.lock().await;
+        counter.count_tokens(request, DESCRIPTION_MODEL)
     }
 
-    async fn estimate_cost(&self, _request: &str) -> Result<f64> {
-        // Placeholder cost estimation
-        Ok(0.01)
+    async fn estimate_cost(&self, request: &str) -> Result<f64> {
+        let counter = self.token_counter.lock().await;
+        counter.estimate_cost(DESCRIPTION_MODEL, request, MAX_MUSIC_TOKENS as usize)
     }
 
     async fn is_cached(&self, key: &str) -> bool {
@@ -0,0 +1,109 @@
+//! Azure OpenAI resources
+//!
+//! Azure serves each model from a deployment of the studio's resource, at
+//! `{endpoint}/openai/deployments/{deployment}`, versioned by an
+//! `api-version` parameter and authenticated with an `api-key` header.
+//! [`AzureConfig`] names the deployment of every model the generators ask
+//! for; a model without one is looked for in a deployment of the same name,
+//! which is how most resources name them. In `project.toml` or the AI
+//! configuration:
+//!
+//! ```toml
+//! ai_provider = "azure"
+//!
+//! [azure]
+//! endpoint = "https://my-studio.openai.azure.com"
+//! api_version = "2024-10-21"
+//!
+//! [azure.deployments]
+//! "gpt-4" = "studio-gpt4"
+//! "dall-e-3" = "studio-dalle"
+//! ```
+//!
+//! Keys are read like those of any provider, from `AZURE_OPENAI_API_KEYS`
+//! or `AZURE_OPENAI_API_KEY` unless configured otherwise.
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Provider name selecting Azure OpenAI
+pub const AZURE_PROVIDER: &str = "azure";
+
+/// API version used when none is configured
+pub const DEFAULT_API_VERSION: &str = "2024-10-21";
+
+/// Environment variable read for the endpoint when none is configured
+pub const ENDPOINT_VAR: &str = "AZURE_OPENAI_ENDPOINT";
+
+fn default_api_version() -> String {
+    DEFAULT_API_VERSION.to_string()
+}
+
+/// Azure OpenAI resource and the deployments of its models
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AzureConfig {
+    /// Resource endpoint, e.g. `https://my-studio.openai.azure.com`
+    pub endpoint: String,
+    #[serde(default = "default_api_version")]
+    pub api_version: String,
+    /// Deployment serving each model, by model name
+    #[serde(default)]
+    pub deployments: BTreeMap<String, String>,
+}
+
+impl AzureConfig {
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            api_version: default_api_version(),
+            deployments: BTreeMap::new(),
+        }
+    }
+
+    /// Resource of the `AZURE_OPENAI_ENDPOINT` environment variable
+    pub fn from_env() -> Result<Self> {
+        match std::env::var(ENDPOINT_VAR) {
+            Ok(endpoint) if !endpoint.trim().is_empty() => Ok(Self::new(endpoint.trim())),
+            _ => bail!("Azure OpenAI needs an endpoint: configure [azure] or set {ENDPOINT_VAR}"),
+        }
+    }
+
+    pub fn with_api_version(mut self, api_version: impl Into<String>) -> Self {
+        self.api_version = api_version.into();
+        self
+    }
+
+    /// Serve `model` from `deployment`
+    pub fn with_deployment(
+        mut self,
+        model: impl Into<String>,
+        deployment: impl Into<String>,
+    ) -> Self {
+        self.deployments.insert(model.into(), deployment.into());
+        self
+    }
+
+    /// Deployment serving `model`, the deployment of the same name if none
+    /// is configured
+    pub fn deployment<'a>(&'a self, model: &'a str) -> &'a str {
+        self.deployments
+            .get(model)
+            .map(String::as_str)
+            .unwrap_or(model)
+    }
+
+    /// URL of `path` on the deployment of `model`. Without a model the path
+    /// is taken from the resource itself.
+    pub fn url(&self, model: &str, path: &str) -> String {
+        let endpoint = self.endpoint.trim_end_matches('/');
+        if model.is_empty() {
+            format!("{endpoint}/openai{path}")
+        } else {
+            format!(
+                "{endpoint}/openai/deployments/{}{path}",
+                self.deployment(model)
+            )
+        }
+    }
+}
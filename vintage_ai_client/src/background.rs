@@ -0,0 +1,278 @@
+//! Background removal for generated sprites
+//!
+//! Image models return sprites on a solid backdrop. The backdrop color is
+//! detected by sampling the image corners, then a flood fill from the border
+//! clears every connected pixel within the color distance threshold to real
+//! alpha. Interior pixels that happen to match the backdrop but are enclosed
+//! by the sprite are left alone. Cleared pixels get zero alpha rather than a
+//! magenta color key, so engines can use the result directly.
+
+use image::{DynamicImage, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Settings for removing a solid background
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BackgroundRemoval {
+    /// Largest normalized RGB distance (0.0 - 1.0) from the detected
+    /// background color that is still cleared
+    pub threshold: f32,
+    /// Edge length of the square sampled at each corner
+    pub corner_size: u32,
+}
+
+impl Default for BackgroundRemoval {
+    fn default() -> Self {
+        Self {
+            threshold: 0.12,
+            corner_size: 4,
+        }
+    }
+}
+
+/// Which kinds of generated images get their background removed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackgroundRemovalConfig {
+    pub style_guide: Option<BackgroundRemoval>,
+    pub sprite: Option<BackgroundRemoval>,
+    pub tileset: Option<BackgroundRemoval>,
+    pub ui_element: Option<BackgroundRemoval>,
+    pub background: Option<BackgroundRemoval>,
+}
+
+impl Default for BackgroundRemovalConfig {
+    /// Sprites and UI elements are keyed; style guides, tiles and
+    /// backgrounds are meant to fill their whole canvas
+    fn default() -> Self {
+        Self {
+            style_guide: None,
+            sprite: Some(BackgroundRemoval::default()),
+            tileset: None,
+            ui_element: Some(BackgroundRemoval::default()),
+            background: None,
+        }
+    }
+}
+
+/// Outcome of a background removal pass
+#[derive(Debug, Clone)]
+pub struct RemovalResult {
+    pub image: RgbaImage,
+    /// Detected background color, if the corners agreed on one
+    pub background: Option<[u8; 3]>,
+    pub cleared_pixels: usize,
+}
+
+/// Detect the backdrop color from the image corners.
+///
+/// Each corner patch is averaged; at least three corners must agree within
+/// the threshold. Returns `None` when the corners are already transparent
+/// or disagree, e.g. because the subject touches the image edges.
+pub fn detect_background(img: &RgbaImage, settings: &BackgroundRemoval) -> Option<[u8; 3]> {
+    let (width, height) = img.dimensions();
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    let size = settings.corner_size.clamp(1, width.min(height));
+    let corners = [
+        (0, 0),
+        (width - size, 0),
+        (0, height - size),
+        (width - size, height - size),
+    ];
+
+    let mut samples = Vec::with_capacity(corners.len());
+    for (cx, cy) in corners {
+        let mut sum = [0u32; 3];
+        for y in cy..cy + size {
+            for x in cx..cx + size {
+                let p = img.get_pixel(x, y);
+                if p[3] == 0 {
+                    return None;
+                }
+                for c in 0..3 {
+                    sum[c] += p[c] as u32;
+                }
+            }
+        }
+        let n = size * size;
+        samples.push([(sum[0] / n) as u8, (sum[1] / n) as u8, (sum[2] / n) as u8]);
+    }
+
+    samples.iter().find_map(|candidate| {
+        let agreeing: Vec<&[u8; 3]> = samples
+            .iter()
+            .filter(|s| color_distance(candidate, s) <= settings.threshold)
+            .collect();
+        if agreeing.len() < 3 {
+            return None;
+        }
+        let mut sum = [0u32; 3];
+        for s in &agreeing {
+            for c in 0..3 {
+                sum[c] += s[c] as u32;
+            }
+        }
+        let n = agreeing.len() as u32;
+        Some([(sum[0] / n) as u8, (sum[1] / n) as u8, (sum[2] / n) as u8])
+    })
+}
+
+/// Clear the background connected to the image border
+pub fn remove_background(img: &DynamicImage, settings: &BackgroundRemoval) -> RemovalResult {
+    let mut image = img.to_rgba8();
+    let Some(background) = detect_background(&image, settings) else {
+        return RemovalResult {
+            image,
+            background: None,
+            cleared_pixels: 0,
+        };
+    };
+
+    let (width, height) = image.dimensions();
+    let matches = |p: &Rgba<u8>| {
+        p[3] > 0 && color_distance(&[p[0], p[1], p[2]], &background) <= settings.threshold
+    };
+
+    let mut visited = vec![false; (width * height) as usize];
+    let mut queue = VecDeque::new();
+    let border = (0..width)
+        .flat_map(|x| [(x, 0), (x, height - 1)])
+        .chain((0..height).flat_map(|y| [(0, y), (width - 1, y)]));
+    for (x, y) in border {
+        let idx = (y * width + x) as usize;
+        if !visited[idx] && matches(image.get_pixel(x, y)) {
+            visited[idx] = true;
+            queue.push_back((x, y));
+        }
+    }
+
+    let mut cleared_pixels = 0;
+    while let Some((x, y)) = queue.pop_front() {
+        image.put_pixel(x, y, Rgba([0, 0, 0, 0]));
+        cleared_pixels += 1;
+
+        let neighbors = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+        for (nx, ny) in neighbors {
+            if nx >= width || ny >= height {
+                continue;
+            }
+            let idx = (ny * width + nx) as usize;
+            if !visited[idx] && matches(image.get_pixel(nx, ny)) {
+                visited[idx] = true;
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+
+    RemovalResult {
+        image,
+        background: Some(background),
+        cleared_pixels,
+    }
+}
+
+/// Euclidean RGB distance scaled to 0.0 - 1.0
+fn color_distance(a: &[u8; 3], b: &[u8; 3]) -> f32 {
+    let sum: f32 = (0..3)
+        .map(|c| {
+            let d = a[c] as f32 - b[c] as f32;
+            d * d
+        })
+        .sum();
+    sum.sqrt() / (3.0f32.sqrt() * 255.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GREEN: Rgba<u8> = Rgba([0, 200, 0, 255]);
+    const RED: Rgba<u8> = Rgba([220, 30, 30, 255]);
+
+    /// 16×16 green backdrop with red where `sprite` holds
+    fn sprite(sprite: impl Fn(u32, u32) -> bool) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_fn(16, 16, |x, y| {
+            if sprite(x, y) { RED } else { GREEN }
+        }))
+    }
+
+    fn inside(x: u32, y: u32, from: u32, to: u32) -> bool {
+        (from..to).contains(&x) && (from..to).contains(&y)
+    }
+
+    #[test]
+    fn solid_border_becomes_transparent() {
+        let img = sprite(|x, y| inside(x, y, 6, 10));
+        let result = remove_background(&img, &BackgroundRemoval::default());
+
+        assert_eq!(result.background, Some([0, 200, 0]));
+        assert_eq!(result.cleared_pixels, 16 * 16 - 4 * 4);
+        for (x, y, p) in result.image.enumerate_pixels() {
+            if inside(x, y, 6, 10) {
+                assert_eq!(*p, RED);
+            } else {
+                assert_eq!(p[3], 0, "({x}, {y}) kept");
+            }
+        }
+    }
+
+    #[test]
+    fn enclosed_background_color_is_kept() {
+        // A red ring around a green hole
+        let img = sprite(|x, y| inside(x, y, 4, 12) && !inside(x, y, 6, 10));
+        let result = remove_background(&img, &BackgroundRemoval::default());
+
+        assert_eq!(result.cleared_pixels, 16 * 16 - 8 * 8);
+        for y in 6..10 {
+            for x in 6..10 {
+                assert_eq!(*result.image.get_pixel(x, y), GREEN);
+            }
+        }
+        assert_eq!(result.image.get_pixel(0, 0)[3], 0);
+    }
+
+    #[test]
+    fn threshold_sets_how_far_the_fill_reaches() {
+        // Off-green noise along the top edge, away from the sampled corners
+        let mut img = sprite(|x, y| inside(x, y, 6, 10)).to_rgba8();
+        img.put_pixel(7, 0, Rgba([30, 230, 30, 255]));
+        img.put_pixel(8, 0, Rgba([40, 240, 40, 255]));
+        let img = DynamicImage::ImageRgba8(img);
+
+        let result = remove_background(&img, &BackgroundRemoval::default());
+        assert_eq!(result.image.get_pixel(7, 0)[3], 0);
+        assert_eq!(*result.image.get_pixel(8, 0), Rgba([40, 240, 40, 255]));
+
+        let exact = BackgroundRemoval {
+            threshold: 0.0,
+            ..Default::default()
+        };
+        let result = remove_background(&img, &exact);
+        assert_eq!(result.cleared_pixels, 16 * 16 - 4 * 4 - 2);
+
+        let loose = BackgroundRemoval {
+            threshold: 0.2,
+            ..Default::default()
+        };
+        let result = remove_background(&img, &loose);
+        assert_eq!(result.cleared_pixels, 16 * 16 - 4 * 4);
+    }
+
+    #[test]
+    fn disagreeing_corners_leave_the_image_alone() {
+        // The subject covers two corners
+        let img = sprite(|x, _| x < 8);
+        let result = remove_background(&img, &BackgroundRemoval::default());
+
+        assert_eq!(result.background, None);
+        assert_eq!(result.cleared_pixels, 0);
+        assert_eq!(result.image, img.to_rgba8());
+    }
+}
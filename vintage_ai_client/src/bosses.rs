@@ -0,0 +1,348 @@
+//! Boss designs with phase scripts
+//!
+//! Every dungeon boss gets a battle script split into phases. A phase starts
+//! once the boss's HP drops to its threshold and lists the ability rotation
+//! the boss cycles through and the arena hazards that are active. A design
+//! also requests an oversized battle sprite and a bespoke music cue, and is
+//! cross-referenced with the quest steps (plot beats) where the boss appears.
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::AudioConfig;
+use crate::game_assets::asset_id;
+use crate::game_types::GameConfig;
+use crate::slugs::{AssetCategory, SlugRegistry};
+
+/// Largest sprite scale a boss may request
+pub const MAX_SPRITE_SCALE: u32 = 4;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArenaHazard {
+    pub name: String,
+    pub description: String,
+    /// Turns between two triggers
+    pub interval_turns: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BossPhase {
+    /// HP fraction at which the phase begins, 1.0 for the opening phase
+    pub hp_threshold: f32,
+    /// Abilities used in order, one per turn, repeating
+    pub rotation: Vec<String>,
+    #[serde(default)]
+    pub hazards: Vec<ArenaHazard>,
+    /// Line the boss speaks when the phase begins
+    #[serde(default)]
+    pub transition_line: Option<String>,
+}
+
+impl BossPhase {
+    /// Ability used on a turn of this phase, counted from the phase start
+    pub fn ability(&self, turn: usize) -> Option<&str> {
+        if self.rotation.is_empty() {
+            return None;
+        }
+        Some(&self.rotation[turn % self.rotation.len()])
+    }
+
+    /// Hazards that trigger on a turn of this phase
+    pub fn hazards_on(&self, turn: usize) -> impl Iterator<Item = &ArenaHazard> {
+        self.hazards.iter().filter(move |hazard| {
+            hazard.interval_turns > 0 && (turn + 1).is_multiple_of(hazard.interval_turns as usize)
+        })
+    }
+}
+
+/// Request for the boss's battle sprite
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BossSprite {
+    pub sprite_id: String,
+    pub description: String,
+    /// Multiple of the game's normal sprite size
+    pub scale: u32,
+}
+
+impl BossSprite {
+    /// Sprite size in pixels for the game's normal sprite size
+    pub fn size(&self, sprite_size: u32) -> u32 {
+        sprite_size * self.scale.clamp(1, MAX_SPRITE_SCALE)
+    }
+}
+
+/// Battle music cue of a boss
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BossMusicCue {
+    pub track: String,
+    pub mood: String,
+    pub tempo: u16,
+    /// Phase index that switches to the intense section
+    #[serde(default)]
+    pub intensify_at_phase: Option<usize>,
+}
+
+impl BossMusicCue {
+    /// Audio settings for composing the cue
+    pub fn audio_config(&self, music_style: &str) -> AudioConfig {
+        AudioConfig {
+            style: music_style.to_string(),
+            tempo: self.tempo,
+            ..AudioConfig::default()
+        }
+    }
+}
+
+/// Quest step in which a boss appears
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlotBeat {
+    pub quest: String,
+    /// Index of the step within the quest
+    pub step: usize,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BossDesign {
+    /// Enemy asset id, derived from the name
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub dungeon: String,
+    pub hp: u32,
+    pub phases: Vec<BossPhase>,
+    pub sprite: BossSprite,
+    pub music: BossMusicCue,
+    #[serde(default)]
+    pub plot_beats: Vec<PlotBeat>,
+}
+
+impl BossDesign {
+    /// Name the battle sprite and music cue through the project's slug
+    /// registry, so the exported design references their files by slug.
+    /// The sprite is named apart from a character sheet of the same name.
+    pub fn name_assets(&mut self, slugs: &mut SlugRegistry) {
+        let sprite = format!("{} battle", self.name);
+        self.sprite.sprite_id = slugs.slug(AssetCategory::Sprite, &sprite);
+        let named = slugs
+            .resolve(&self.music.track)
+            .is_some_and(|entry| entry.category == AssetCategory::Music);
+        if !named {
+            self.music.track = slugs.slug(AssetCategory::Music, &self.music.track);
+        }
+    }
+
+    /// Index of the phase active at an HP fraction
+    pub fn phase_index_at(&self, hp_fraction: f32) -> Option<usize> {
+        self.phases
+            .iter()
+            .rposition(|phase| hp_fraction <= phase.hp_threshold)
+    }
+
+    /// Phase active at an HP fraction
+    pub fn phase_at(&self, hp_fraction: f32) -> Option<&BossPhase> {
+        self.phase_index_at(hp_fraction).map(|i| &self.phases[i])
+    }
+}
+
+/// Boss designs of all dungeons
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BossRoster {
+    pub bosses: Vec<BossDesign>,
+}
+
+/// A rule a boss design breaks
+#[derive(Debug, Clone, PartialEq)]
+pub enum BossIssue {
+    NoPhases { boss: String },
+    OpeningThreshold { boss: String, threshold: f32 },
+    ThresholdOrder { boss: String, phase: usize },
+    EmptyRotation { boss: String, phase: usize },
+    SpriteScale { boss: String, scale: u32 },
+    NoPlotBeat { boss: String },
+}
+
+impl std::fmt::Display for BossIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoPhases { boss } => write!(f, "{boss}: no phases"),
+            Self::OpeningThreshold { boss, threshold } => write!(
+                f,
+                "{boss}: opening phase starts at {threshold} HP instead of full HP"
+            ),
+            Self::ThresholdOrder { boss, phase } => write!(
+                f,
+                "{boss}: phase {phase} does not start below the previous phase"
+            ),
+            Self::EmptyRotation { boss, phase } => {
+                write!(f, "{boss}: phase {phase} has no abilities")
+            }
+            Self::SpriteScale { boss, scale } => write!(
+                f,
+                "{boss}: sprite scale {scale} is outside 2..={MAX_SPRITE_SCALE}"
+            ),
+            Self::NoPlotBeat { boss } => write!(f, "{boss}: appears in no quest step"),
+        }
+    }
+}
+
+impl BossRoster {
+    /// Derive enemy ids and link each boss to the quest steps where it
+    /// appears: steps located in the boss's dungeon or naming the boss
+    pub fn cross_reference(&mut self, config: &GameConfig) {
+        let quests = std::iter::once(&config.main_quest).chain(&config.side_quests);
+        let steps: Vec<_> = quests
+            .flat_map(|quest| {
+                quest
+                    .steps
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, step)| (quest, index, step))
+            })
+            .collect();
+
+        for boss in &mut self.bosses {
+            boss.id = asset_id(&boss.name);
+            let name = boss.name.to_lowercase();
+            boss.plot_beats = steps
+                .iter()
+                .filter(|(_, _, step)| {
+                    step.location.eq_ignore_ascii_case(&boss.dungeon)
+                        || step.description.to_lowercase().contains(&name)
+                })
+                .map(|(quest, index, step)| PlotBeat {
+                    quest: quest.name.clone(),
+                    step: *index,
+                    description: step.description.clone(),
+                })
+                .collect();
+        }
+    }
+
+    /// Name every boss's sprite and music cue, see [`BossDesign::name_assets`]
+    pub fn name_assets(&mut self, slugs: &mut SlugRegistry) {
+        for boss in &mut self.bosses {
+            boss.name_assets(slugs);
+        }
+    }
+
+    pub fn boss(&self, id: &str) -> Option<&BossDesign> {
+        self.bosses.iter().find(|boss| boss.id == id)
+    }
+
+    /// Check the phase scripts, sprite requests and plot links
+    pub fn validate(&self) -> Vec<BossIssue> {
+        let mut issues = Vec::new();
+        for boss in &self.bosses {
+            let name = boss.name.clone();
+            match boss.phases.first() {
+                None => issues.push(BossIssue::NoPhases { boss: name.clone() }),
+                Some(opening) if opening.hp_threshold < 1.0 => {
+                    issues.push(BossIssue::OpeningThreshold {
+                        boss: name.clone(),
+                        threshold: opening.hp_threshold,
+                    })
+                }
+                Some(_) => {}
+            }
+            for (index, pair) in boss.phases.windows(2).enumerate() {
+                if pair[1].hp_threshold >= pair[0].hp_threshold {
+                    issues.push(BossIssue::ThresholdOrder {
+                        boss: name.clone(),
+                        phase: index + 1,
+                    });
+                }
+            }
+            for (index, phase) in boss.phases.iter().enumerate() {
+                if phase.rotation.is_empty() {
+                    issues.push(BossIssue::EmptyRotation {
+                        boss: name.clone(),
+                        phase: index,
+                    });
+                }
+            }
+            if !(2..=MAX_SPRITE_SCALE).contains(&boss.sprite.scale) {
+                issues.push(BossIssue::SpriteScale {
+                    boss: name.clone(),
+                    scale: boss.sprite.scale,
+                });
+            }
+            if boss.plot_beats.is_empty() {
+                issues.push(BossIssue::NoPlotBeat { boss: name });
+            }
+        }
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sprite_size_clamps_the_scale() {
+        let sprite = |scale| BossSprite {
+            sprite_id: "warden".to_string(),
+            description: "A towering stone warden".to_string(),
+            scale,
+        };
+        assert_eq!(sprite(3).size(16), 48);
+        assert_eq!(sprite(0).size(16), 16);
+        assert_eq!(sprite(9).size(16), 16 * MAX_SPRITE_SCALE);
+    }
+
+    #[test]
+    fn music_cue_keeps_its_tempo_in_the_project_style() {
+        let cue = BossMusicCue {
+            track: "warden_theme".to_string(),
+            mood: "ominous".to_string(),
+            tempo: 152,
+            intensify_at_phase: Some(1),
+        };
+        let config = cue.audio_config("chiptune");
+        assert_eq!(config.style, "chiptune");
+        assert_eq!(config.tempo, 152);
+    }
+
+    #[test]
+    fn bosses_named_alike_get_their_own_assets() {
+        let boss = |name: &str| BossDesign {
+            id: String::new(),
+            name: name.to_string(),
+            dungeon: "Sunken Vault".to_string(),
+            hp: 900,
+            phases: Vec::new(),
+            sprite: BossSprite {
+                sprite_id: String::new(),
+                description: "A towering stone warden".to_string(),
+                scale: 2,
+            },
+            music: BossMusicCue {
+                track: format!("{name} theme"),
+                mood: "ominous".to_string(),
+                tempo: 152,
+                intensify_at_phase: None,
+            },
+            plot_beats: Vec::new(),
+        };
+        let mut roster = BossRoster {
+            bosses: vec![boss("Warden"), boss("Warden?")],
+        };
+        let mut slugs = SlugRegistry::new();
+        // The character sheet of the same name
+        slugs.slug(AssetCategory::Sprite, "Warden");
+        roster.name_assets(&mut slugs);
+
+        let [first, second] = &roster.bosses[..] else {
+            unreachable!()
+        };
+        assert_eq!(first.sprite.sprite_id, "spr_warden_battle");
+        assert_eq!(second.sprite.sprite_id, "spr_warden_battle_2");
+        assert_eq!(first.music.track, "bgm_warden_theme");
+        assert_eq!(second.music.track, "bgm_warden_theme_2");
+
+        // Naming again keeps the slugs
+        roster.name_assets(&mut slugs);
+        assert_eq!(roster.bosses[1].sprite.sprite_id, "spr_warden_battle_2");
+        assert_eq!(roster.bosses[1].music.track, "bgm_warden_theme_2");
+    }
+}
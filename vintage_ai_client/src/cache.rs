@@ -13,9 +13,19 @@ use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 use tokio::sync::RwLock;
 
+/// Version tag mixed into cache keys; bump when normalization changes
+const KEY_VERSION: &str = "v2";
+
+static WHITESPACE: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"\s+").expect("valid regex"));
+
+static TEMPLATE_TAG: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"(\{\{|\{%|\{#)-?\s*(.*?)\s*-?(\}\}|%\}|#\})").expect("valid regex")
+});
+
 /// Main cache manager for all AI operations
 #[derive(Clone)]
 pub struct AiCache {
@@ -27,6 +37,9 @@ pub struct AiCache {
     config: CacheConfig,
     /// Cache statistics
     stats: Arc<RwLock<CacheStats>>,
+    /// Pre-normalization key of each key handed out by [`AiCache::key_for`],
+    /// migrated if the key misses
+    legacy_keys: Arc<RwLock<HashMap<String, String>>>,
 }
 
 #[derive(Debug, Clone)]
@@ -140,15 +153,51 @@ impl AiCache {
             cache_dir: config.cache_dir.clone(),
             config,
             stats: Arc::new(RwLock::new(CacheStats::default())),
+            legacy_keys: Arc::new(RwLock::new(HashMap::new())),
         })
     }
 
-    /// Generate cache key from request parameters
+    /// Generate cache key from request parameters.
+    ///
+    /// The prompt and parameters are normalized first so that requests that
+    /// only differ in whitespace, parameter order or number formatting share
+    /// an entry. Use [`AiCache::key_for`] to also migrate entries stored
+    /// under the pre-normalization key.
     pub fn generate_key(
         &self,
         prefix: &str,
         content: &str,
         params: &HashMap<String, String>,
+    ) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(KEY_VERSION);
+        hasher.update([0]);
+        hasher.update(prefix);
+        hasher.update([0]);
+        hasher.update(normalize_prompt(content));
+
+        let mut sorted_params: Vec<_> = params
+            .iter()
+            .map(|(k, v)| (k.trim().to_lowercase(), normalize_param(v)))
+            .collect();
+        sorted_params.sort();
+
+        for (key, value) in sorted_params {
+            hasher.update([0]);
+            hasher.update(key);
+            hasher.update([b'=']);
+            hasher.update(value);
+        }
+
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Key produced before prompt normalization was introduced
+    pub fn legacy_key(
+        &self,
+        prefix: &str,
+        content: &str,
+        params: &HashMap<String, String>,
     ) -> String {
         let mut hasher = Sha256::new();
         hasher.update(prefix);
@@ -166,8 +215,49 @@ impl AiCache {
         format!("{:x}", hasher.finalize())
     }
 
+    /// Generate the normalized key. If the next [`AiCache::get`] for it
+    /// misses, an entry stored under the legacy key is moved to it first so
+    /// existing caches keep their hits.
+    pub async fn key_for(
+        &self,
+        prefix: &str,
+        content: &str,
+        params: &HashMap<String, String>,
+    ) -> String {
+        let key = self.generate_key(prefix, content, params);
+        let legacy = self.legacy_key(prefix, content, params);
+        if legacy != key {
+            self.legacy_keys.write().await.insert(key.clone(), legacy);
+        }
+        key
+    }
+
+    /// Move a cache entry to a new key, unless the new key already exists
+    pub async fn migrate_entry(&self, from: &str, to: &str) -> Result<bool> {
+        let from_path = self.cache_path(from);
+        if from == to || !from_path.exists() || self.cache_path(to).exists() {
+            return Ok(false);
+        }
+
+        let mut item = self.load_from_disk(from).await?;
+        item.key = to.to_string();
+        item.metadata.request_hash = to.to_string();
+        self.save_to_disk(&item).await?;
+        tokio::fs::remove_file(&from_path).await?;
+
+        let mut cache = self.memory_cache.write().await;
+        if cache.remove(from).is_some() {
+            cache.insert(to.to_string(), item);
+        }
+
+        tracing::debug!("Migrated cache entry {from} -> {to}");
+        Ok(true)
+    }
+
     /// Get item from cache
     pub async fn get(&self, key: &str) -> Option<CachedItem> {
+        let legacy = self.legacy_keys.write().await.remove(key);
+
         // Check memory cache first
         {
             let mut cache = self.memory_cache.write().await;
@@ -191,11 +281,21 @@ impl AiCache {
             }
         }
 
-        // Check disk cache
-        if let Ok(item) = self.load_from_disk(key).await {
+        // Check disk cache, then an entry left under the legacy key
+        let mut loaded = self.load_from_disk(key).await;
+        if loaded.is_err()
+            && let Some(legacy) = legacy
+        {
+            match self.migrate_entry(&legacy, key).await {
+                Ok(true) => loaded = self.load_from_disk(key).await,
+                Ok(false) => {}
+                Err(e) => tracing::warn!("Failed to migrate cache entry {legacy}: {e}"),
+            }
+        }
+        if let Ok(item) = loaded {
             // Add to memory cache if space available
-            let mut cache = self.memory_cache.write().await;
             if self.can_fit_in_memory(&item).await {
+                let mut cache = self.memory_cache.write().await;
                 cache.insert(key.to_string(), item.clone());
             }
 
@@ -385,6 +485,23 @@ impl AiCache {
     }
 }
 
+/// Canonical form of a prompt for cache keys: template tags lose their inner
+/// padding, whitespace runs collapse to one space, and the ends are trimmed
+pub fn normalize_prompt(prompt: &str) -> String {
+    let tags = TEMPLATE_TAG.replace_all(prompt, "$1 $2 $3");
+    WHITESPACE.replace_all(tags.trim(), " ").into_owned()
+}
+
+/// Canonical form of a parameter value; numbers are reformatted so `0.70`
+/// and `0.7` match
+fn normalize_param(value: &str) -> String {
+    let value = value.trim();
+    match value.parse::<f64>() {
+        Ok(number) if number.is_finite() => number.to_string(),
+        _ => value.to_string(),
+    }
+}
+
 /// Cache-aware wrapper for any async function
 pub async fn cached<F, T>(cache: &AiCache, key: &str, f: F) -> Result<T>
 where
@@ -505,3 +622,139 @@ pub enum ImageFormat {
     WebP,
     Original,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache() -> AiCache {
+        AiCache::with_config(CacheConfig {
+            cache_dir: std::env::temp_dir().join(format!("ai_cache_{}", uuid::Uuid::new_v4())),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn prompts_normalize_whitespace_and_template_tags() {
+        assert_eq!(
+            normalize_prompt("  Draw a\n\tknight  in {{style}} "),
+            "Draw a knight in {{ style }}"
+        );
+        assert_eq!(
+            normalize_prompt("{{- style -}} {%if x%}{#note #}"),
+            "{{ style }} {% if x %}{# note #}"
+        );
+        assert_eq!(normalize_prompt("a  b"), normalize_prompt("a b\n"));
+    }
+
+    #[test]
+    fn numeric_params_normalize_their_formatting() {
+        assert_eq!(normalize_param("0.70"), "0.7");
+        assert_eq!(normalize_param(" 1.0 "), "1");
+        assert_eq!(normalize_param("1e3"), "1000");
+        assert_eq!(normalize_param(" gpt-4o "), "gpt-4o");
+        assert_eq!(normalize_param("NaN"), "NaN");
+    }
+
+    #[test]
+    fn equivalent_requests_share_a_key() {
+        let cache = temp_cache();
+        let key = cache.generate_key(
+            "text",
+            "Draw a  knight",
+            &params(&[("temperature", "0.70"), ("model", "gpt-4o")]),
+        );
+
+        assert_eq!(
+            key,
+            cache.generate_key(
+                "text",
+                " Draw a knight\n",
+                &params(&[(" Model", "gpt-4o"), ("temperature", "0.7")]),
+            )
+        );
+        assert_ne!(
+            key,
+            cache.generate_key(
+                "image",
+                "Draw a knight",
+                &params(&[("temperature", "0.7"), ("model", "gpt-4o")]),
+            )
+        );
+        assert_ne!(
+            key,
+            cache.generate_key(
+                "text",
+                "Draw a knight",
+                &params(&[("temperature", "0.8"), ("model", "gpt-4o")]),
+            )
+        );
+        std::fs::remove_dir_all(&cache.cache_dir).ok();
+    }
+
+    #[tokio::test]
+    async fn legacy_entries_migrate_when_the_new_key_misses() {
+        let cache = temp_cache();
+        let params = params(&[("temperature", "0.7")]);
+        let legacy = cache.legacy_key("text", "Draw a knight", &params);
+        cache
+            .put(
+                legacy.clone(),
+                CachedData::Text("old answer".to_string()),
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+
+        // Handing out the key leaves the disk alone
+        let key = cache.key_for("text", "Draw a knight", &params).await;
+        assert_ne!(key, legacy);
+        assert!(cache.cache_path(&legacy).exists());
+        assert!(!cache.cache_path(&key).exists());
+
+        let item = cache.get(&key).await.expect("migrated entry");
+        assert!(matches!(item.data, CachedData::Text(ref text) if text == "old answer"));
+        assert_eq!(item.key, key);
+        assert_eq!(item.metadata.request_hash, key);
+        assert!(!cache.cache_path(&legacy).exists());
+        assert!(cache.cache_path(&key).exists());
+        assert!(cache.get(&legacy).await.is_none());
+        assert!(cache.legacy_keys.read().await.is_empty());
+        std::fs::remove_dir_all(&cache.cache_dir).ok();
+    }
+
+    #[tokio::test]
+    async fn migration_keeps_existing_entries() {
+        let cache = temp_cache();
+        for (key, text) in [("old", "old answer"), ("new", "new answer")] {
+            cache
+                .put(
+                    key.to_string(),
+                    CachedData::Text(text.to_string()),
+                    HashMap::new(),
+                )
+                .await
+                .unwrap();
+        }
+
+        assert!(!cache.migrate_entry("old", "new").await.unwrap());
+        assert!(!cache.migrate_entry("missing", "other").await.unwrap());
+        let item = cache.get("new").await.unwrap();
+        assert!(matches!(item.data, CachedData::Text(ref text) if text == "new answer"));
+        assert!(cache.cache_path("old").exists());
+
+        assert!(cache.migrate_entry("old", "moved").await.unwrap());
+        assert!(!cache.cache_path("old").exists());
+        let item = cache.get("moved").await.unwrap();
+        assert!(matches!(item.data, CachedData::Text(ref text) if text == "old answer"));
+        std::fs::remove_dir_all(&cache.cache_dir).ok();
+    }
+}
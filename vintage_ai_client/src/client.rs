@@ -3,24 +3,34 @@
 //! This is the main entry point for any part of the application that needs AI functionality.
 //! It manages all AI service instances and provides a clean, consistent interface.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use futures::Stream;
 use futures::StreamExt;
 use minijinja::{Environment, context};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use super::{
     AiConfig, AiGenerator, AiService,
-    audio::{AudioConfig, AudioGenerator},
-    conversation::{ConversationContext, ConversationManager},
+    artifacts::{ARTIFACT_INDEX_FILE, ArtifactIndex, ArtifactKind, ArtifactRecord, DuplicateCheck},
+    audio::{self, AudioConfig, AudioGenerator},
+    conversation::{ConversationContext, ConversationManager, MessageConfig},
+    history::ArtifactHistory,
     image::{ImageConfig, ImageGenerator},
-    text::{TextConfig, TextGenerator},
+    routing::{self, TaskCategory},
+    slugs::{AssetCategory, SLUG_REGISTRY_FILE, SlugRegistry},
+    text::{TextConfig, TextGenerator, with_content_language},
+    tokens::CostEstimate,
 };
 
+/// Directory under the project's `assets/` holding the files of generated
+/// artifacts, so near-duplicates can reuse them
+pub const GENERATED_ASSET_DIR: &str = "generated";
+
 /// The unified AI client - your one-stop shop for all AI services
 #[derive(Clone)]
 pub struct AiClient {
@@ -30,15 +40,44 @@ pub struct AiClient {
     config: Arc<RwLock<AiConfig>>,
     /// Request history for debugging/monitoring
     history: Arc<RwLock<Vec<AiRequest>>>,
+    /// Project that new requests are billed to
+    project: Arc<RwLock<Option<String>>>,
+    /// Embeddings of generated artifacts, for duplicate detection
+    artifacts: Arc<RwLock<ArtifactIndex>>,
+    /// Where the artifact index is persisted, if anywhere
+    artifacts_path: Arc<RwLock<Option<PathBuf>>>,
+    /// Names handed out to generated assets
+    slugs: Arc<RwLock<SlugRegistry>>,
+    /// Where the slug registry is persisted, if anywhere
+    slugs_path: Arc<RwLock<Option<PathBuf>>>,
     /// Template environment for prompts
     templates: Arc<Environment<'static>>,
 }
 
+/// An artifact [`AiClient::generate_unique`] may reuse instead of generating
+struct UniqueArtifact<'a> {
+    kind: ArtifactKind,
+    category: AssetCategory,
+    name: &'a str,
+    /// Prompt the artifact is generated from, compared for duplicates
+    description: &'a str,
+    /// Extension of the written file
+    extension: &'a str,
+    /// Model recorded for a reused artifact
+    model: &'a str,
+}
+
 /// Record of an AI request for monitoring/debugging
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AiRequest {
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub request_type: AiRequestType,
+    /// Model that served the request
+    #[serde(default)]
+    pub model: String,
+    /// Project the request was made for, if one was set
+    #[serde(default)]
+    pub project: Option<String>,
     pub tokens_used: usize,
     pub cost_estimate: f64,
     pub cache_hit: bool,
@@ -53,6 +92,48 @@ pub enum AiRequestType {
     Conversation { context: String },
 }
 
+impl AiRequestType {
+    /// Short name of the request kind
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AiRequestType::Text { .. } => "text",
+            AiRequestType::Image { .. } => "image",
+            AiRequestType::Audio { .. } => "audio",
+            AiRequestType::Conversation { .. } => "conversation",
+        }
+    }
+
+    /// Purpose or context the request was made for
+    pub fn detail(&self) -> &str {
+        match self {
+            AiRequestType::Text { purpose }
+            | AiRequestType::Image { purpose }
+            | AiRequestType::Audio { purpose } => purpose,
+            AiRequestType::Conversation { context } => context,
+        }
+    }
+}
+
+/// File format for exported request history
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HistoryFormat {
+    /// Comma-separated values with a header row
+    Csv,
+    /// One JSON object per line
+    JsonLines,
+}
+
+impl HistoryFormat {
+    /// Pick a format from a file extension (`csv`, `jsonl` or `ndjson`)
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "csv" => Some(HistoryFormat::Csv),
+            "jsonl" | "ndjson" => Some(HistoryFormat::JsonLines),
+            _ => None,
+        }
+    }
+}
+
 /// High-level request types that automatically route to appropriate services
 #[derive(Debug, Clone)]
 pub enum AiTask {
@@ -62,6 +143,8 @@ pub enum AiTask {
         genres: Vec<String>,
         mechanics: Vec<String>,
         themes: Vec<String>,
+        /// Language to write the description in, English when `None`
+        content_language: Option<String>,
     },
     /// Generate concept art for a game
     GenerateConceptArt {
@@ -69,6 +152,12 @@ pub enum AiTask {
         art_style: String,
         subjects: Vec<String>,
     },
+    /// Generate a small preview of a subject, cheaper than concept art
+    GenerateThumbnail {
+        game_name: String,
+        art_style: String,
+        subject: String,
+    },
     /// Generate a theme song or sound effect
     GenerateAudio {
         game_name: String,
@@ -127,13 +216,18 @@ impl AiClient {
             service,
             config,
             history,
+            project: Arc::new(RwLock::new(None)),
+            artifacts: Arc::new(RwLock::new(ArtifactIndex::new())),
+            artifacts_path: Arc::new(RwLock::new(None)),
+            slugs: Arc::new(RwLock::new(SlugRegistry::new())),
+            slugs_path: Arc::new(RwLock::new(None)),
             templates,
         })
     }
 
     /// Create with custom configuration
     pub fn with_config(config: AiConfig) -> Result<Self> {
-        let service = Arc::new(AiService::from_env()?);
+        let service = Arc::new(AiService::with_config(&config)?);
         let config = Arc::new(RwLock::new(config));
         let history = Arc::new(RwLock::new(Vec::new()));
         let templates = Arc::new(Self::create_template_env()?);
@@ -142,6 +236,11 @@ impl AiClient {
             service,
             config,
             history,
+            project: Arc::new(RwLock::new(None)),
+            artifacts: Arc::new(RwLock::new(ArtifactIndex::new())),
+            artifacts_path: Arc::new(RwLock::new(None)),
+            slugs: Arc::new(RwLock::new(SlugRegistry::new())),
+            slugs_path: Arc::new(RwLock::new(None)),
             templates,
         })
     }
@@ -160,26 +259,33 @@ impl AiClient {
         Ok(env)
     }
 
-    /// Execute a high-level AI task
+    /// Execute a high-level AI task on the model its category is routed
+    /// to, falling back to the route's other models when it errors
     pub async fn execute(&self, task: AiTask) -> Result<AiResult> {
         let start = std::time::Instant::now();
 
-        let (result, request_type, tokens, cost, cache_hit) = match task {
+        let (result, request_type, model, tokens, cost, cache_hit) = match task {
             AiTask::GenerateGameDescription {
                 blend_name,
                 genres,
                 mechanics,
                 themes,
+                content_language,
             } => {
-                let prompt =
-                    self.build_game_description_prompt(&blend_name, &genres, &mechanics, &themes);
-                let config = TextConfig::for_game_description();
+                let prompt = with_content_language(
+                    self.build_game_description_prompt(&blend_name, &genres, &mechanics, &themes),
+                    content_language.as_deref(),
+                );
+                let profile = self.config.read().await.profiles.narrative.clone();
+                let config = TextConfig::for_game_description().with_profile(&profile);
                 let text_gen = self.service.text();
 
                 let cache_key = format!("game_desc_{blend_name}");
                 let cache_hit = text_gen.is_cached(&cache_key).await;
 
-                let result = text_gen.generate(&prompt, config).await?;
+                let (result, model) = self
+                    .generate_text(TaskCategory::Description, &prompt, config)
+                    .await?;
                 let tokens = text_gen.estimate_tokens(&prompt).await?;
                 let cost = text_gen.estimate_cost(&prompt).await?;
 
@@ -188,6 +294,7 @@ impl AiClient {
                     AiRequestType::Text {
                         purpose: "game_description".to_string(),
                     },
+                    model,
                     tokens,
                     cost,
                     cache_hit,
@@ -206,8 +313,21 @@ impl AiClient {
                 let cache_key = format!("concept_art_{}_{}", game_name, subjects.join("_"));
                 let cache_hit = image_gen.is_cached(&cache_key).await;
 
-                // Generate image using ImageGenerator's specific method with config
-                let result = image_gen.generate_single(&prompt, config).await?;
+                let name = format!("{game_name} {}", subjects.join(" "));
+                let routed = self.route(TaskCategory::KeyArt).await.model;
+                let (result, model, reused) = self
+                    .generate_unique(
+                        UniqueArtifact {
+                            kind: ArtifactKind::Image,
+                            category: AssetCategory::Background,
+                            name: &name,
+                            description: &prompt,
+                            extension: "png",
+                            model: &routed,
+                        },
+                        || self.generate_image(TaskCategory::KeyArt, &prompt, config),
+                    )
+                    .await?;
                 let tokens = image_gen.estimate_tokens(&prompt).await?;
                 let cost = image_gen.estimate_cost(&prompt).await?;
 
@@ -216,9 +336,58 @@ impl AiClient {
                     AiRequestType::Image {
                         purpose: "concept_art".to_string(),
                     },
+                    model,
                     tokens,
                     cost,
-                    cache_hit,
+                    cache_hit || reused,
+                )
+            }
+
+            AiTask::GenerateThumbnail {
+                game_name,
+                art_style,
+                subject,
+            } => {
+                let subjects = [subject];
+                let prompt = self.build_concept_art_prompt(&game_name, &art_style, &subjects);
+                let image_gen = self.service.image();
+
+                let cache_key = format!("thumbnail_{game_name}_{}", subjects[0]);
+                let cache_hit = image_gen.is_cached(&cache_key).await;
+
+                let name = format!("{game_name} {} thumbnail", subjects[0]);
+                let routed = self.route(TaskCategory::Thumbnail).await.model;
+                let (result, model, reused) = self
+                    .generate_unique(
+                        UniqueArtifact {
+                            kind: ArtifactKind::Image,
+                            category: AssetCategory::Other,
+                            name: &name,
+                            description: &prompt,
+                            extension: "png",
+                            model: &routed,
+                        },
+                        || {
+                            self.generate_image(
+                                TaskCategory::Thumbnail,
+                                &prompt,
+                                ImageConfig::for_thumbnails(),
+                            )
+                        },
+                    )
+                    .await?;
+                let tokens = image_gen.estimate_tokens(&prompt).await?;
+                let cost = image_gen.estimate_cost(&prompt).await?;
+
+                (
+                    AiResult::Image(result),
+                    AiRequestType::Image {
+                        purpose: "thumbnail".to_string(),
+                    },
+                    model,
+                    tokens,
+                    cost,
+                    cache_hit || reused,
                 )
             }
 
@@ -240,20 +409,47 @@ impl AiClient {
                 let cache_hit = audio_gen.is_cached(&cache_key).await;
 
                 // Generate audio description based on type
-                let result = match &audio_type {
-                    AudioType::ThemeSong | AudioType::BattleMusic | AudioType::VictoryFanfare => {
-                        let music_desc = audio_gen
-                            .generate_music_description(&prompt, config)
-                            .await?;
-                        // Convert to bytes (placeholder - would need actual audio generation)
-                        serde_json::to_vec(&music_desc)?
-                    }
+                let (name, category) = match &audio_type {
                     AudioType::SoundEffect(effect) => {
-                        let sound_desc = audio_gen.generate_sound_effect(effect, 0.5).await?;
-                        // Convert to bytes (placeholder - would need actual audio generation)
-                        serde_json::to_vec(&sound_desc)?
+                        (format!("{game_name} {effect} sound"), AssetCategory::Sound)
                     }
+                    _ => (
+                        format!("{game_name} {audio_type:?} {mood}"),
+                        AssetCategory::Music,
+                    ),
                 };
+                let (result, _, reused) = self
+                    .generate_unique(
+                        UniqueArtifact {
+                            kind: ArtifactKind::Audio,
+                            category,
+                            name: &name,
+                            description: &prompt,
+                            extension: "json",
+                            model: audio::DESCRIPTION_MODEL,
+                        },
+                        || async {
+                            let data = match &audio_type {
+                                AudioType::ThemeSong
+                                | AudioType::BattleMusic
+                                | AudioType::VictoryFanfare => {
+                                    let music_desc = audio_gen
+                                        .generate_music_description(&prompt, config)
+                                        .await?;
+                                    // Convert to bytes (placeholder - would need actual audio generation)
+                                    serde_json::to_vec(&music_desc)?
+                                }
+                                AudioType::SoundEffect(effect) => {
+                                    let sound_desc =
+                                        audio_gen.generate_sound_effect(effect, 0.5).await?;
+                                    // Convert to bytes (placeholder - would need actual audio generation)
+                                    serde_json::to_vec(&sound_desc)?
+                                }
+                            };
+                            Ok((data, audio::DESCRIPTION_MODEL.to_string()))
+                        },
+                    )
+                    .await?;
                 let tokens = audio_gen.estimate_tokens(&prompt).await?;
                 let cost = audio_gen.estimate_cost(&prompt).await?;
 
@@ -262,9 +458,10 @@ impl AiClient {
                     AiRequestType::Audio {
                         purpose: format!("{audio_type:?}"),
                     },
+                    audio::DESCRIPTION_MODEL.to_string(),
                     tokens,
                     cost,
-                    cache_hit,
+                    cache_hit || reused,
                 )
             }
 
@@ -279,13 +476,27 @@ impl AiClient {
                     project_config: None,
                 };
 
-                // Start a conversation and send the message
-                let conv_id = conv_manager
-                    .start_conversation("Game Design Discussion".to_string(), conv_context)
-                    .await?;
-                let response = conv_manager
-                    .send_message(&conv_id, question.clone())
-                    .await?;
+                // Each model answers in a conversation of its own, so the
+                // question of a failed attempt isn't sent again as history
+                let route = self.route(TaskCategory::Brainstorm).await;
+                let (response, model) = routing::with_fallback(&route, |model| {
+                    let conv_manager = &conv_manager;
+                    let conv_context = conv_context.clone();
+                    let question = question.clone();
+                    async move {
+                        let conv_id = conv_manager
+                            .start_conversation("Game Design Discussion".to_string(), conv_context)
+                            .await?;
+                        let config = MessageConfig {
+                            model,
+                            ..Default::default()
+                        };
+                        conv_manager
+                            .send_message_with_config(&conv_id, question, Some(config))
+                            .await
+                    }
+                })
+                .await?;
 
                 let tokens = conv_manager.estimate_tokens(&question).await?;
                 let cost = conv_manager.estimate_cost(&question).await?;
@@ -298,6 +509,7 @@ impl AiClient {
                     AiRequestType::Conversation {
                         context: "game_design".to_string(),
                     },
+                    model,
                     tokens,
                     cost,
                     false, // Conversations typically aren't cached
@@ -310,13 +522,16 @@ impl AiClient {
                 specifications,
             } => {
                 let prompt = self.build_code_prompt(&language, &component_type, &specifications);
-                let config = TextConfig::for_code_generation();
+                let profile = self.config.read().await.profiles.code.clone();
+                let config = TextConfig::for_code_generation().with_profile(&profile);
                 let text_gen = self.service.text();
 
                 let cache_key = format!("code_{language}_{component_type}");
                 let cache_hit = text_gen.is_cached(&cache_key).await;
 
-                let result = text_gen.generate(&prompt, config).await?;
+                let (result, model) = self
+                    .generate_text(TaskCategory::Code, &prompt, config)
+                    .await?;
                 let tokens = text_gen.estimate_tokens(&prompt).await?;
                 let cost = text_gen.estimate_cost(&prompt).await?;
 
@@ -325,6 +540,7 @@ impl AiClient {
                     AiRequestType::Text {
                         purpose: "code_generation".to_string(),
                     },
+                    model,
                     tokens,
                     cost,
                     cache_hit,
@@ -332,13 +548,23 @@ impl AiClient {
             }
 
             AiTask::CustomText { prompt, config } => {
-                let config = config.unwrap_or_default();
                 let text_gen = self.service.text();
 
                 let cache_key = format!("custom_text_{}", &prompt[..prompt.len().min(50)]);
                 let cache_hit = text_gen.is_cached(&cache_key).await;
 
-                let result = text_gen.generate(&prompt, config).await?;
+                // Drafts are routed; a configuration of the caller's own
+                // keeps its model
+                let (result, model) = match config {
+                    Some(config) => {
+                        let model = config.model.clone();
+                        (text_gen.generate(&prompt, config).await?, model)
+                    }
+                    None => {
+                        self.generate_text(TaskCategory::Draft, &prompt, TextConfig::default())
+                            .await?
+                    }
+                };
                 let tokens = text_gen.estimate_tokens(&prompt).await?;
                 let cost = text_gen.estimate_cost(&prompt).await?;
 
@@ -347,6 +573,7 @@ impl AiClient {
                     AiRequestType::Text {
                         purpose: "custom".to_string(),
                     },
+                    model,
                     tokens,
                     cost,
                     cache_hit,
@@ -355,6 +582,7 @@ impl AiClient {
 
             AiTask::CustomImage { prompt, config } => {
                 let config = config.unwrap_or_default();
+                let model = config.model_name().to_string();
                 let image_gen = self.service.image();
 
                 let cache_key = format!("custom_image_{}", &prompt[..prompt.len().min(50)]);
@@ -370,6 +598,7 @@ impl AiClient {
                     AiRequestType::Image {
                         purpose: "custom".to_string(),
                     },
+                    model,
                     tokens,
                     cost,
                     cache_hit,
@@ -377,11 +606,15 @@ impl AiClient {
             }
         };
 
-        // Record the request
+        // Record the request in the history; the generators report it to
+        // the metrics themselves
         let duration_ms = start.elapsed().as_millis() as u64;
+
         let request_record = AiRequest {
             timestamp: chrono::Utc::now(),
             request_type,
+            model,
+            project: self.project.read().await.clone(),
             tokens_used: tokens,
             cost_estimate: cost,
             cache_hit,
@@ -393,6 +626,201 @@ impl AiClient {
         Ok(result)
     }
 
+    /// Route serving a category of tasks
+    pub async fn route(&self, category: TaskCategory) -> routing::ModelRoute {
+        self.config.read().await.routing.route(category)
+    }
+
+    /// Generate text with `config` on the models routed for `category`
+    async fn generate_text(
+        &self,
+        category: TaskCategory,
+        prompt: &str,
+        config: TextConfig,
+    ) -> Result<(String, String)> {
+        let route = self.route(category).await;
+        let text_gen = self.service.text();
+        routing::with_fallback(&route, |model| {
+            let text_gen = &text_gen;
+            let config = TextConfig {
+                model,
+                ..config.clone()
+            };
+            async move { text_gen.generate(prompt, config).await }
+        })
+        .await
+    }
+
+    /// Generate an artifact, or reuse the file of a near-duplicate when the
+    /// project's artifact index has one. Generated files are written under
+    /// the project's `assets/generated/` and registered, so later requests
+    /// can reuse them. Without a loaded index this only generates.
+    ///
+    /// Returns the data, the model that served it and whether it was reused.
+    async fn generate_unique<F, Fut>(
+        &self,
+        artifact: UniqueArtifact<'_>,
+        generate: F,
+    ) -> Result<(Vec<u8>, String, bool)>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<(Vec<u8>, String)>>,
+    {
+        let Some(project_dir) = self
+            .artifacts_path
+            .read()
+            .await
+            .as_deref()
+            .and_then(Path::parent)
+            .map(Path::to_path_buf)
+        else {
+            let (data, model) = generate().await?;
+            return Ok((data, model, false));
+        };
+
+        let check = self
+            .check_duplicate(artifact.kind, artifact.description)
+            .await?;
+        if let Some(duplicate) = &check.duplicate {
+            match duplicate.path.as_deref().map(std::fs::read) {
+                Some(Ok(data)) => {
+                    tracing::info!(
+                        "Reusing '{}' for '{}': {duplicate}",
+                        duplicate.name,
+                        artifact.name
+                    );
+                    return Ok((data, artifact.model.to_string(), true));
+                }
+                _ => tracing::warn!(
+                    "Generating '{}' although {duplicate}, which has no file to reuse",
+                    artifact.name
+                ),
+            }
+        }
+
+        let (data, model) = generate().await?;
+        let slug = self.asset_slug(artifact.category, artifact.name).await?;
+        let dir = project_dir.join("assets").join(GENERATED_ASSET_DIR);
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{slug}.{}", artifact.extension));
+        std::fs::write(&path, &data)?;
+        self.register_artifact(
+            slug,
+            artifact.kind,
+            artifact.description,
+            check.embedding,
+            Some(path),
+        )
+        .await?;
+        Ok((data, model, false))
+    }
+
+    /// Generate an image with `config` on the models routed for `category`
+    async fn generate_image(
+        &self,
+        category: TaskCategory,
+        prompt: &str,
+        config: ImageConfig,
+    ) -> Result<(Vec<u8>, String)> {
+        let route = self.route(category).await;
+        let image_gen = self.service.image();
+        routing::with_fallback(&route, |model| {
+            let image_gen = &image_gen;
+            let config = config.clone().with_model(&model, route.is_hd());
+            async move { image_gen.generate_single(prompt, config).await }
+        })
+        .await
+    }
+
+    /// Tokens and cost [`AiClient::execute`] would spend on `task`, without
+    /// making the request. Prompts are rendered as they would be sent, to
+    /// the model the task is routed to.
+    pub async fn estimate(&self, task: &AiTask) -> Result<CostEstimate> {
+        match task {
+            AiTask::GenerateGameDescription {
+                blend_name,
+                genres,
+                mechanics,
+                themes,
+                content_language,
+            } => {
+                let prompt = with_content_language(
+                    self.build_game_description_prompt(blend_name, genres, mechanics, themes),
+                    content_language.as_deref(),
+                );
+                let profile = self.config.read().await.profiles.narrative.clone();
+                let config = TextConfig {
+                    model: self.route(TaskCategory::Description).await.model,
+                    ..TextConfig::for_game_description().with_profile(&profile)
+                };
+                self.service.text().estimate(&prompt, &config).await
+            }
+            AiTask::GenerateConceptArt { .. } => {
+                let route = self.route(TaskCategory::KeyArt).await;
+                let config = ImageConfig::for_sprites().with_model(&route.model, route.is_hd());
+                Ok(self.service.image().estimate(&config, 1).await)
+            }
+            AiTask::GenerateThumbnail { .. } => {
+                let route = self.route(TaskCategory::Thumbnail).await;
+                let config = ImageConfig::for_thumbnails().with_model(&route.model, route.is_hd());
+                Ok(self.service.image().estimate(&config, 1).await)
+            }
+            AiTask::GenerateAudio {
+                game_name,
+                audio_type,
+                mood,
+            } => {
+                let prompt = self.build_audio_prompt(game_name, audio_type, mood);
+                let max_tokens = match audio_type {
+                    AudioType::SoundEffect(_) => audio::MAX_SOUND_EFFECT_TOKENS,
+                    _ => audio::MAX_MUSIC_TOKENS,
+                };
+                let tokens = self.service.audio().estimate_tokens(&prompt).await?;
+                let counter = self.service.token_counter.lock().await;
+                Ok(counter.estimate_chat(audio::DESCRIPTION_MODEL, tokens, max_tokens as usize))
+            }
+            AiTask::DiscussGameDesign { context, question } => {
+                let config = MessageConfig {
+                    model: self.route(TaskCategory::Brainstorm).await.model,
+                    ..Default::default()
+                };
+                let counter = self.service.token_counter.lock().await;
+                let tokens = counter.count_chat_tokens(
+                    &[("system", context.as_str()), ("user", question.as_str())],
+                    &config.model,
+                )?;
+                Ok(counter.estimate_chat(&config.model, tokens, config.max_tokens as usize))
+            }
+            AiTask::GenerateCode {
+                language,
+                component_type,
+                specifications,
+            } => {
+                let prompt = self.build_code_prompt(language, component_type, specifications);
+                let profile = self.config.read().await.profiles.code.clone();
+                let config = TextConfig {
+                    model: self.route(TaskCategory::Code).await.model,
+                    ..TextConfig::for_code_generation().with_profile(&profile)
+                };
+                self.service.text().estimate(&prompt, &config).await
+            }
+            AiTask::CustomText { prompt, config } => {
+                let config = match config {
+                    Some(config) => config.clone(),
+                    None => TextConfig {
+                        model: self.route(TaskCategory::Draft).await.model,
+                        ..Default::default()
+                    },
+                };
+                self.service.text().estimate(prompt, &config).await
+            }
+            AiTask::CustomImage { config, .. } => {
+                let config = config.clone().unwrap_or_default();
+                Ok(self.service.image().estimate(&config, 1).await)
+            }
+        }
+    }
+
     /// Get direct access to text generator for advanced use
     pub fn text(&self) -> TextGenerator {
         self.service.text()
@@ -450,31 +878,192 @@ impl AiClient {
         self.history.write().await.clear();
     }
 
+    /// Set the project that subsequent requests are recorded against
+    pub async fn set_project(&self, project: Option<String>) {
+        *self.project.write().await = project;
+    }
+
+    /// Get the project that requests are currently recorded against
+    pub async fn get_project(&self) -> Option<String> {
+        self.project.read().await.clone()
+    }
+
+    /// Check new assets against a project's artifact index and name them
+    /// through its slug registry, persisting both in the project directory
+    pub async fn open_project(&self, project_dir: impl AsRef<Path>) -> Result<()> {
+        let project_dir = project_dir.as_ref();
+        let artifacts_path = project_dir.join(ARTIFACT_INDEX_FILE);
+        let slugs_path = project_dir.join(SLUG_REGISTRY_FILE);
+        *self.artifacts.write().await = ArtifactIndex::load(&artifacts_path)?;
+        *self.artifacts_path.write().await = Some(artifacts_path);
+        *self.slugs.write().await = SlugRegistry::load(&slugs_path)?;
+        *self.slugs_path.write().await = Some(slugs_path);
+        Ok(())
+    }
+
+    /// The project's slug for an asset, registering it on first request.
+    ///
+    /// Generators name files and artifacts through this so every name
+    /// follows the project's convention and stays the same across runs.
+    pub async fn asset_slug(&self, category: AssetCategory, name: &str) -> Result<String> {
+        let mut slugs = self.slugs.write().await;
+        if let Some(slug) = slugs.get(category, name) {
+            return Ok(slug.to_string());
+        }
+        let slug = slugs.slug(category, name);
+        if let Some(path) = self.slugs_path.read().await.as_ref() {
+            slugs.save(path)?;
+        }
+        Ok(slug)
+    }
+
+    /// Check whether an asset described by `description` already exists.
+    ///
+    /// Call this before generating; if a duplicate is reported the existing
+    /// artifact can be reused instead. Pass the returned embedding to
+    /// [`AiClient::register_artifact`] if a new asset is generated anyway.
+    pub async fn check_duplicate(
+        &self,
+        kind: ArtifactKind,
+        description: &str,
+    ) -> Result<DuplicateCheck> {
+        let config = self.config.read().await.clone();
+        let index = self.artifacts.read().await;
+        self.service
+            .embeddings()
+            .check_duplicate(description, kind, &index, &config)
+            .await
+    }
+
+    /// Record a generated asset so later requests can be checked against it.
+    ///
+    /// With a project index loaded, the asset's file is also kept as its
+    /// next version under the project's `assets/history/`.
+    pub async fn register_artifact(
+        &self,
+        name: impl Into<String>,
+        kind: ArtifactKind,
+        description: &str,
+        embedding: Vec<f32>,
+        path: Option<PathBuf>,
+    ) -> Result<()> {
+        let name = name.into();
+        if self.slugs.read().await.resolve(&name).is_none() {
+            tracing::warn!("Artifact '{name}' was not named through the slug registry");
+        }
+        let index_path = self.artifacts_path.read().await.clone();
+        if let Some(project_dir) = index_path.as_deref().and_then(Path::parent)
+            && let Some(file) = path.as_deref().filter(|file| file.is_file())
+        {
+            ArtifactHistory::for_project(project_dir).record(&name, file, description)?;
+        }
+
+        let mut index = self.artifacts.write().await;
+        index.register(ArtifactRecord {
+            name,
+            kind,
+            description: description.to_string(),
+            embedding,
+            path,
+            created_at: chrono::Utc::now(),
+        });
+
+        if let Some(path) = index_path {
+            index.save(path)?;
+        }
+        Ok(())
+    }
+
     /// Get usage statistics
     pub async fn get_usage_stats(&self) -> UsageStats {
         let history = self.history.read().await;
+        UsageStats::from_requests(history.iter())
+    }
 
-        let total_requests = history.len();
-        let total_tokens: usize = history.iter().map(|r| r.tokens_used).sum();
-        let total_cost: f64 = history.iter().map(|r| r.cost_estimate).sum();
-        let cache_hits = history.iter().filter(|r| r.cache_hit).count();
-        let avg_duration_ms = if total_requests > 0 {
-            history.iter().map(|r| r.duration_ms).sum::<u64>() / total_requests as u64
-        } else {
-            0
-        };
+    /// Get usage statistics per project.
+    ///
+    /// Requests made without a project are grouped under an empty name.
+    pub async fn get_usage_by_project(&self) -> HashMap<String, UsageStats> {
+        let history = self.history.read().await;
 
-        UsageStats {
-            total_requests,
-            total_tokens,
-            total_cost,
-            cache_hit_rate: if total_requests > 0 {
-                cache_hits as f64 / total_requests as f64
-            } else {
-                0.0
-            },
-            avg_duration_ms,
+        let mut groups: HashMap<String, Vec<&AiRequest>> = HashMap::new();
+        for request in history.iter() {
+            groups
+                .entry(request.project.clone().unwrap_or_default())
+                .or_default()
+                .push(request);
+        }
+
+        groups
+            .into_iter()
+            .map(|(project, requests)| (project, UsageStats::from_requests(requests)))
+            .collect()
+    }
+
+    /// Write the request history as a per-request ledger.
+    ///
+    /// Records are ordered by project, then by time. Returns the number of
+    /// records written.
+    pub async fn export_history(
+        &self,
+        path: impl AsRef<Path>,
+        format: HistoryFormat,
+    ) -> Result<usize> {
+        let path = path.as_ref();
+        let mut records = self.get_history().await;
+        records.sort_by(|a, b| {
+            a.project
+                .cmp(&b.project)
+                .then_with(|| a.timestamp.cmp(&b.timestamp))
+        });
+
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut writer = std::io::BufWriter::new(file);
+
+        match format {
+            HistoryFormat::Csv => {
+                writeln!(
+                    writer,
+                    "timestamp,project,type,purpose,model,tokens,cost_usd,cache_hit,duration_ms"
+                )?;
+                for record in &records {
+                    writeln!(
+                        writer,
+                        "{},{},{},{},{},{},{:.6},{},{}",
+                        record.timestamp.to_rfc3339(),
+                        csv_field(record.project.as_deref().unwrap_or_default()),
+                        record.request_type.kind(),
+                        csv_field(record.request_type.detail()),
+                        csv_field(&record.model),
+                        record.tokens_used,
+                        record.cost_estimate,
+                        record.cache_hit,
+                        record.duration_ms,
+                    )?;
+                }
+            }
+            HistoryFormat::JsonLines => {
+                for record in &records {
+                    let line = serde_json::json!({
+                        "timestamp": record.timestamp,
+                        "project": record.project,
+                        "type": record.request_type.kind(),
+                        "purpose": record.request_type.detail(),
+                        "model": record.model,
+                        "tokens": record.tokens_used,
+                        "cost_usd": record.cost_estimate,
+                        "cache_hit": record.cache_hit,
+                        "duration_ms": record.duration_ms,
+                    });
+                    serde_json::to_writer(&mut writer, &line)?;
+                    writeln!(writer)?;
+                }
+            }
         }
+
+        writer.flush()?;
+        Ok(records.len())
     }
 
     // Helper methods for building prompts
@@ -569,6 +1158,15 @@ impl AiClient {
     }
 }
 
+/// Quote a CSV field when it contains a separator, quote or newline
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
 /// Usage statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsageStats {
@@ -579,6 +1177,43 @@ pub struct UsageStats {
     pub avg_duration_ms: u64,
 }
 
+impl UsageStats {
+    /// Aggregate a set of request records
+    pub fn from_requests<'a>(requests: impl IntoIterator<Item = &'a AiRequest>) -> Self {
+        let mut total_requests = 0;
+        let mut total_tokens = 0;
+        let mut total_cost = 0.0;
+        let mut cache_hits = 0;
+        let mut total_duration_ms = 0;
+
+        for request in requests {
+            total_requests += 1;
+            total_tokens += request.tokens_used;
+            total_cost += request.cost_estimate;
+            total_duration_ms += request.duration_ms;
+            if request.cache_hit {
+                cache_hits += 1;
+            }
+        }
+
+        Self {
+            total_requests,
+            total_tokens,
+            total_cost,
+            cache_hit_rate: if total_requests > 0 {
+                cache_hits as f64 / total_requests as f64
+            } else {
+                0.0
+            },
+            avg_duration_ms: if total_requests > 0 {
+                total_duration_ms / total_requests as u64
+            } else {
+                0
+            },
+        }
+    }
+}
+
 /// Data structure to represent a blend result for AI integration
 #[derive(Debug, Clone)]
 pub struct BlendData {
@@ -587,6 +1222,8 @@ pub struct BlendData {
     pub mechanics: Vec<String>,
     pub themes: Vec<String>,
     pub recommended_features: Vec<String>,
+    /// Language of the description and tagline, English when `None`
+    pub content_language: Option<String>,
 }
 
 /// Extension trait for blend integration
@@ -614,6 +1251,7 @@ impl BlendAiIntegration for AiClient {
                 genres: blend.genres.clone(),
                 mechanics: blend.mechanics.clone(),
                 themes: blend.themes.clone(),
+                content_language: blend.content_language.clone(),
             })
             .await?
         {
@@ -628,6 +1266,8 @@ impl BlendAiIntegration for AiClient {
             blend.name,
             blend.genres.join(" meets ")
         );
+        let tagline_prompt =
+            with_content_language(tagline_prompt, blend.content_language.as_deref());
 
         let marketing_tagline = match self
             .execute(AiTask::CustomText {
@@ -652,3 +1292,87 @@ impl BlendAiIntegration for AiClient {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MOCK_PROVIDER;
+
+    fn mock_client() -> AiClient {
+        AiClient::with_config(AiConfig {
+            ai_provider: MOCK_PROVIDER.to_string(),
+            ..AiConfig::default()
+        })
+        .unwrap()
+    }
+
+    fn thumbnail() -> AiTask {
+        AiTask::GenerateThumbnail {
+            game_name: "Chronicles of Eldoria".to_string(),
+            art_style: "16-bit".to_string(),
+            subject: "the Warden".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn near_duplicate_images_reuse_the_generated_file() {
+        let client = mock_client();
+        let dir = std::env::temp_dir().join(format!("dedup_{}", uuid::Uuid::new_v4()));
+        client.open_project(&dir).await.unwrap();
+
+        client.execute(thumbnail()).await.unwrap();
+        let index = ArtifactIndex::load(dir.join(ARTIFACT_INDEX_FILE)).unwrap();
+        let [record] = index.artifacts.as_slice() else {
+            panic!("expected one artifact, got {:?}", index.artifacts);
+        };
+        assert_eq!(record.kind, ArtifactKind::Image);
+        let path = record.path.clone().unwrap();
+        assert!(path.starts_with(dir.join("assets").join(GENERATED_ASSET_DIR)));
+
+        // The second request is served from the file, not generated again
+        std::fs::write(&path, b"kept").unwrap();
+        let AiResult::Image(data) = client.execute(thumbnail()).await.unwrap() else {
+            panic!("expected an image");
+        };
+        assert_eq!(data, b"kept");
+        assert!(client.get_history().await[1].cache_hit);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn asset_slugs_never_collide_and_persist_with_the_project() {
+        let dir = std::env::temp_dir().join(format!("slugs_{}", uuid::Uuid::new_v4()));
+        let client = mock_client();
+        client.open_project(&dir).await.unwrap();
+
+        let enemy = client
+            .asset_slug(AssetCategory::Enemy, "Slime")
+            .await
+            .unwrap();
+        let item = client
+            .asset_slug(AssetCategory::Item, "Slime!")
+            .await
+            .unwrap();
+        assert_eq!(enemy, "slime");
+        assert_eq!(item, "slime_2");
+
+        // Another session on the project hands out the same slugs
+        let reopened = mock_client();
+        reopened.open_project(&dir).await.unwrap();
+        assert_eq!(
+            reopened
+                .asset_slug(AssetCategory::Item, "Slime!")
+                .await
+                .unwrap(),
+            "slime_2"
+        );
+        assert_eq!(
+            reopened
+                .asset_slug(AssetCategory::Npc, "Slime")
+                .await
+                .unwrap(),
+            "slime_3"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
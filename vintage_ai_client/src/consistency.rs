@@ -2,15 +2,55 @@
 //!
 //! Ensures all generated images maintain coherent style, colors, and aesthetics
 //! Optimized for 16-bit nostalgic game art with sprite sheet support
+//!
+//! Besides the built-in presets, styles can be defined in TOML or JSON files.
+//! By convention a project keeps them in `<project>/styles/*.toml`, and the
+//! file stem is the style name. A file is a serialized [`StyleConfig`]:
+//!
+//! ```toml
+//! style_name = "amiga_ocs"
+//!
+//! [palette]
+//! name = "Amiga OCS 32"
+//! max_colors = 32
+//! primary_colors = [{ r = 0, g = 0, b = 0 }, { r = 238, g = 238, b = 238 }]
+//! secondary_colors = [{ r = 102, g = 68, b = 0 }]
+//! accent_colors = [{ r = 255, g = 119, b = 0 }]
+//! transparency_color = { r = 255, g = 0, b = 255, a = 0 }
+//!
+//! [rules]
+//! pixel_size = 1
+//! outline_style = { SinglePixel = { r = 0, g = 0, b = 0 } }
+//! shading_technique = "ThreeTone"   # Flat, TwoTone, ThreeTone, Dithered, Pillow
+//! perspective = "SideScroller"      # TopDown, ThreeQuarterView, Isometric, SideScroller
+//! dithering = "Bayer4x4"            # None, Checkerboard, Bayer2x2, Bayer4x4, Floyd
+//! light_direction = "TopLeft"
+//! constraints = ["Copper gradient skies"]
+//!
+//! [sprite_specs]
+//! character_size = [32, 32]
+//! tile_size = [16, 16]
+//! ui_specs = { button_size = [64, 16], icon_size = [16, 16], font_size = 8, border_width = 1 }
+//! ```
+//!
+//! Color alpha defaults to 255, `rules.dither_opaque_only` defaults to true,
+//! and `secondary_colors`, `accent_colors`, `constraints` and
+//! `sprite_specs.animation_frames` may be omitted.
 
 use anyhow::{Context, Result};
 use image::{DynamicImage, Rgba, RgbaImage};
 use minijinja::{Environment, context};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+use crate::prompts::PromptLibrary;
+
+/// Directory inside a project that holds custom style files
+pub const STYLES_DIR: &str = "styles";
+
 /// Style consistency manager for maintaining visual coherence
 #[derive(Clone)]
 pub struct StyleManager {
@@ -39,8 +79,10 @@ pub struct ColorPalette {
     /// Primary colors (main character, UI elements)
     pub primary_colors: Vec<Color>,
     /// Secondary colors (backgrounds, effects)
+    #[serde(default)]
     pub secondary_colors: Vec<Color>,
     /// Accent colors (highlights, special effects)
+    #[serde(default)]
     pub accent_colors: Vec<Color>,
     /// Transparency color for sprites
     pub transparency_color: Color,
@@ -53,9 +95,14 @@ pub struct Color {
     pub r: u8,
     pub g: u8,
     pub b: u8,
+    #[serde(default = "opaque")]
     pub a: u8,
 }
 
+fn opaque() -> u8 {
+    255
+}
+
 impl Color {
     pub fn new(r: u8, g: u8, b: u8) -> Self {
         Self { r, g, b, a: 255 }
@@ -85,6 +132,16 @@ impl Color {
         let b = (color & 0x1F) << 3;
         Self::new(r as u8, g as u8, b as u8)
     }
+
+    /// Parse `#rrggbb` or `rrggbb`
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.trim().trim_start_matches('#');
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        Some(Self::new(channel(0)?, channel(2)?, channel(4)?))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -99,12 +156,21 @@ pub struct StyleRules {
     pub perspective: Perspective,
     /// Dithering pattern
     pub dithering: DitheringPattern,
+    /// Keep error diffusion inside opaque pixels so sprites don't bleed
+    /// into the background
+    #[serde(default = "default_dither_opaque_only")]
+    pub dither_opaque_only: bool,
     /// Light source direction
     pub light_direction: LightDirection,
     /// Additional constraints
+    #[serde(default)]
     pub constraints: Vec<String>,
 }
 
+fn default_dither_opaque_only() -> bool {
+    true
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum OutlineStyle {
     None,
@@ -162,6 +228,7 @@ pub struct SpriteSpecs {
     /// UI element specifications
     pub ui_specs: UiSpecs,
     /// Animation frame counts
+    #[serde(default = "StyleConfig::default_animation_frames")]
     pub animation_frames: HashMap<String, u32>,
 }
 
@@ -182,15 +249,16 @@ impl Default for StyleManager {
 impl StyleManager {
     /// Create a new style manager
     pub fn new() -> Self {
-        let default_config = StyleConfig::default_16bit_rpg();
+        Self::with_prompts(&PromptLibrary::new())
+    }
 
-        // Initialize template engine
-        let mut env = Environment::new();
+    /// Create a style manager that renders the project's style consistency
+    /// prompt, if it overrides it
+    pub fn with_prompts(prompts: &PromptLibrary) -> Self {
+        let default_config = StyleConfig::default_16bit_rpg();
 
-        // Load style consistency template
-        let style_template = include_str!("../prompts/image/style_consistency.jinja");
-        env.add_template("style_consistency", style_template)
-            .expect("Failed to load style consistency template");
+        // Initialize template engine with the image prompts
+        let env = prompts.environment("image");
 
         Self {
             style_config: Arc::new(Mutex::new(default_config)),
@@ -212,11 +280,78 @@ impl StyleManager {
         Ok(())
     }
 
+    /// Load a custom style from a TOML or JSON file and make it active
+    pub async fn load_style_from_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let config = StyleConfig::from_file(path)?;
+        *self.style_config.lock().await = config;
+        Ok(())
+    }
+
+    /// Load a style by name, checking the project's `styles/` directory
+    /// before the built-in presets
+    pub async fn load_project_style(&self, project_dir: &Path, style_name: &str) -> Result<()> {
+        if let Some((_, path)) = Self::discover_styles(project_dir)?
+            .into_iter()
+            .find(|(name, _)| name == style_name)
+        {
+            return self.load_style_from_file(path).await;
+        }
+        self.load_style(style_name).await
+    }
+
+    /// List custom styles in `<project_dir>/styles` as (name, path) pairs
+    pub fn discover_styles(project_dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+        let dir = project_dir.join(STYLES_DIR);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut styles = Vec::new();
+        for entry in std::fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read styles directory {}", dir.display()))?
+        {
+            let path = entry?.path();
+            let is_style = path
+                .extension()
+                .is_some_and(|ext| ext == "toml" || ext == "json");
+            if is_style && let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+                styles.push((name.to_string(), path.clone()));
+            }
+        }
+        styles.sort();
+        Ok(styles)
+    }
+
     /// Get current style configuration
     pub async fn get_style(&self) -> StyleConfig {
         self.style_config.lock().await.clone()
     }
 
+    /// Adopt the palette and outline/shading hints derived from a style guide
+    pub async fn apply_extracted_style(&self, extracted: &style_extraction::ExtractedStyle) {
+        let mut config = self.style_config.lock().await;
+
+        if !extracted.colors.is_empty() {
+            let name = format!("{} style guide", config.style_name);
+            config.palette = extracted.to_palette(&name, &config.palette);
+        }
+        if let Some(color) = extracted.outline_color {
+            config.rules.outline_style = OutlineStyle::SinglePixel(color);
+        }
+        config.rules.shading_technique = extracted.shading.clone();
+    }
+
+    /// Reduce a generated image to sprite resolution using the active palette
+    pub async fn downscale_to_sprite(
+        &self,
+        img: &DynamicImage,
+        target_size: (u32, u32),
+        method: downscaling::DownscaleMethod,
+    ) -> DynamicImage {
+        let config = self.style_config.lock().await;
+        downscaling::downscale_to_sprite(img, target_size, method, &config.palette)
+    }
+
     /// Create consistent prompt additions for image generation
     pub async fn create_style_prompt(&self, base_prompt: &str) -> Result<String> {
         let config = self.style_config.lock().await;
@@ -255,8 +390,13 @@ impl StyleManager {
     pub async fn enforce_consistency(&self, img: &DynamicImage) -> Result<DynamicImage> {
         let config = self.style_config.lock().await;
 
-        // Step 1: Quantize to palette
-        let quantized = self.quantize_to_palette(img, &config.palette)?;
+        // Step 1: Quantize to palette, diffusing the error for Floyd-Steinberg
+        let quantized = match config.rules.dithering {
+            DitheringPattern::Floyd => {
+                self.floyd_steinberg(img, &config.palette, config.rules.dither_opaque_only)
+            }
+            _ => self.quantize_to_palette(img, &config.palette)?,
+        };
 
         // Step 2: Apply pixel scaling if needed
         let scaled = if config.rules.pixel_size > 1 {
@@ -273,13 +413,26 @@ impl StyleManager {
 
         // Step 4: Apply dithering if needed
         let dithered = match config.rules.dithering {
-            DitheringPattern::None => outlined,
-            _ => self.apply_dithering(&outlined, &config.rules.dithering)?,
+            // Floyd-Steinberg already ran as part of quantization
+            DitheringPattern::None | DitheringPattern::Floyd => outlined,
+            _ => self.apply_dithering(
+                &outlined,
+                &config.rules.dithering,
+                &config.palette,
+                config.rules.dither_opaque_only,
+            )?,
         };
 
         Ok(dithered)
     }
 
+    /// The active palette as PNG color indices, with the transparency color
+    /// as the transparent index. Generated assets are saved in it, snapping
+    /// any colors outside it, so every asset of the style shares them.
+    pub async fn indexed_palette(&self) -> Result<indexed::IndexedPalette> {
+        indexed::IndexedPalette::from_palette(&self.style_config.lock().await.palette)
+    }
+
     /// Quantize image colors to the current palette
     fn quantize_to_palette(
         &self,
@@ -400,6 +553,8 @@ impl StyleManager {
         &self,
         img: &DynamicImage,
         pattern: &DitheringPattern,
+        palette: &ColorPalette,
+        opaque_only: bool,
     ) -> Result<DynamicImage> {
         match pattern {
             DitheringPattern::None => Ok(img.clone()),
@@ -438,11 +593,92 @@ impl StyleManager {
                 ];
                 self.apply_bayer_dithering(img, &bayer_matrix, 4)
             }
-            DitheringPattern::Floyd => {
-                // Floyd-Steinberg dithering - for now, simplified version
-                Ok(img.clone())
+            DitheringPattern::Floyd => Ok(self.floyd_steinberg(img, palette, opaque_only)),
+        }
+    }
+
+    /// Floyd-Steinberg error diffusion against the palette.
+    ///
+    /// Rows are scanned in serpentine order to avoid directional artifacts.
+    /// With `opaque_only`, error is only passed to opaque neighbors (weights
+    /// renormalized), so sprite edges don't leak into transparent background.
+    fn floyd_steinberg(
+        &self,
+        img: &DynamicImage,
+        palette: &ColorPalette,
+        opaque_only: bool,
+    ) -> DynamicImage {
+        const WEIGHTS: [(i32, i32, f32); 4] = [
+            (1, 0, 7.0 / 16.0),
+            (-1, 1, 3.0 / 16.0),
+            (0, 1, 5.0 / 16.0),
+            (1, 1, 1.0 / 16.0),
+        ];
+
+        let rgba = img.to_rgba8();
+        let (width, height) = rgba.dimensions();
+        let (w, h) = (width as i32, height as i32);
+
+        let mut all_colors = Vec::new();
+        all_colors.extend(&palette.primary_colors);
+        all_colors.extend(&palette.secondary_colors);
+        all_colors.extend(&palette.accent_colors);
+
+        let opaque = |x: i32, y: i32| rgba.get_pixel(x as u32, y as u32)[3] >= 128;
+        let mut buffer: Vec<[f32; 3]> = rgba
+            .pixels()
+            .map(|p| [p[0] as f32, p[1] as f32, p[2] as f32])
+            .collect();
+        let mut output = RgbaImage::new(width, height);
+
+        for y in 0..h {
+            let reverse = y % 2 == 1;
+            for i in 0..w {
+                let x = if reverse { w - 1 - i } else { i };
+
+                if !opaque(x, y) {
+                    let t = palette.transparency_color;
+                    output.put_pixel(x as u32, y as u32, Rgba([t.r, t.g, t.b, t.a]));
+                    continue;
+                }
+
+                let [r, g, b] = buffer[(y * w + x) as usize];
+                let old = Color::new(
+                    r.round().clamp(0.0, 255.0) as u8,
+                    g.round().clamp(0.0, 255.0) as u8,
+                    b.round().clamp(0.0, 255.0) as u8,
+                );
+                let new = self.find_nearest_color(old, &all_colors);
+                output.put_pixel(x as u32, y as u32, Rgba([new.r, new.g, new.b, 255]));
+
+                let error = [r - new.r as f32, g - new.g as f32, b - new.b as f32];
+
+                // Mirror the kernel on right-to-left rows
+                let targets: Vec<(i32, i32, f32)> = WEIGHTS
+                    .iter()
+                    .map(|&(dx, dy, weight)| (x + if reverse { -dx } else { dx }, y + dy, weight))
+                    .filter(|&(nx, ny, _)| nx >= 0 && nx < w && ny < h)
+                    .filter(|&(nx, ny, _)| !opaque_only || opaque(nx, ny))
+                    .collect();
+                let total: f32 = if opaque_only {
+                    targets.iter().map(|t| t.2).sum()
+                } else {
+                    1.0
+                };
+                if total <= 0.0 {
+                    continue;
+                }
+
+                for (nx, ny, weight) in targets {
+                    let cell = &mut buffer[(ny * w + nx) as usize];
+                    for c in 0..3 {
+                        cell[c] += error[c] * weight / total;
+                    }
+                }
             }
         }
+
+        DynamicImage::ImageRgba8(output)
     }
 
     /// Apply Bayer matrix dithering
@@ -549,6 +785,45 @@ impl StyleManager {
 }
 
 impl StyleConfig {
+    /// Read a style definition from a TOML or JSON file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read style file {}", path.display()))?;
+
+        let config: Self = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse style file {}", path.display()))?,
+            _ => toml::from_str(&contents)
+                .with_context(|| format!("Failed to parse style file {}", path.display()))?,
+        };
+        config
+            .validate()
+            .with_context(|| format!("Invalid style file {}", path.display()))?;
+        Ok(config)
+    }
+
+    /// Check that the style can be used for generation
+    pub fn validate(&self) -> Result<()> {
+        let color_count = self.palette.primary_colors.len()
+            + self.palette.secondary_colors.len()
+            + self.palette.accent_colors.len();
+        if color_count == 0 {
+            anyhow::bail!("Palette '{}' has no colors", self.palette.name);
+        }
+        if color_count > self.palette.max_colors as usize {
+            anyhow::bail!(
+                "Palette '{}' has {color_count} colors but max_colors is {}",
+                self.palette.name,
+                self.palette.max_colors
+            );
+        }
+        if self.rules.pixel_size == 0 {
+            anyhow::bail!("pixel_size must be at least 1");
+        }
+        Ok(())
+    }
+
     /// Default 16-bit RPG style
     pub fn default_16bit_rpg() -> Self {
         Self::snes_rpg_style()
@@ -587,6 +862,7 @@ impl StyleConfig {
                 shading_technique: ShadingTechnique::ThreeTone,
                 perspective: Perspective::ThreeQuarterView,
                 dithering: DitheringPattern::None,
+                dither_opaque_only: true,
                 light_direction: LightDirection::TopLeft,
                 constraints: vec![
                     "No anti-aliasing".to_string(),
@@ -641,6 +917,7 @@ impl StyleConfig {
                 shading_technique: ShadingTechnique::TwoTone,
                 perspective: Perspective::SideScroller,
                 dithering: DitheringPattern::Checkerboard,
+                dither_opaque_only: true,
                 light_direction: LightDirection::Top,
                 constraints: vec![
                     "High contrast".to_string(),
@@ -685,6 +962,7 @@ impl StyleConfig {
                 shading_technique: ShadingTechnique::TwoTone,
                 perspective: Perspective::TopDown,
                 dithering: DitheringPattern::Bayer2x2,
+                dither_opaque_only: true,
                 light_direction: LightDirection::Top,
                 constraints: vec![
                     "4 colors only".to_string(),
@@ -739,6 +1017,7 @@ impl StyleConfig {
                 shading_technique: ShadingTechnique::Flat,
                 perspective: Perspective::SideScroller,
                 dithering: DitheringPattern::None,
+                dither_opaque_only: true,
                 light_direction: LightDirection::Top,
                 constraints: vec![
                     "3 colors per sprite".to_string(),
@@ -778,8 +1057,25 @@ pub mod sprite_sheets {
     use super::*;
     use image::{GenericImage, GenericImageView, RgbaImage};
 
+    /// A packed sheet and where each sprite sits on it
+    #[derive(Debug, Clone)]
+    pub struct PackedSheet {
+        pub image: DynamicImage,
+        pub metadata: SpriteSheetMetadata,
+    }
+
     /// Pack multiple sprites into a sprite sheet
     pub fn pack_sprites(sprites: Vec<DynamicImage>, padding: u32) -> Result<DynamicImage> {
+        let names = (0..sprites.len()).map(|idx| idx.to_string());
+        Ok(pack_named_sprites(names.zip(sprites).collect(), padding)?.image)
+    }
+
+    /// Pack named sprites into a sprite sheet, recording each sprite's frame
+    /// under its name
+    pub fn pack_named_sprites(
+        sprites: Vec<(String, DynamicImage)>,
+        padding: u32,
+    ) -> Result<PackedSheet> {
         if sprites.is_empty() {
             return Err(anyhow::anyhow!("No sprites to pack"));
         }
@@ -792,7 +1088,7 @@ pub mod sprite_sheets {
         // Get max sprite dimensions
         let (max_width, max_height) = sprites
             .iter()
-            .map(|s| s.dimensions())
+            .map(|(_, s)| s.dimensions())
             .fold((0, 0), |(mw, mh), (w, h)| (mw.max(w), mh.max(h)));
 
         // Create sprite sheet
@@ -806,16 +1102,33 @@ pub mod sprite_sheets {
         }
 
         // Pack sprites
-        for (idx, sprite) in sprites.iter().enumerate() {
+        let mut frames = HashMap::new();
+        for (idx, (name, sprite)) in sprites.into_iter().enumerate() {
             let col = idx as u32 % cols;
             let row = idx as u32 / cols;
             let x = padding + col * (max_width + padding);
             let y = padding + row * (max_height + padding);
 
             sheet.copy_from(&sprite.to_rgba8(), x, y)?;
+            frames.insert(
+                name,
+                SpriteFrame {
+                    x,
+                    y,
+                    width: sprite.width(),
+                    height: sprite.height(),
+                },
+            );
         }
 
-        Ok(DynamicImage::ImageRgba8(sheet))
+        Ok(PackedSheet {
+            image: DynamicImage::ImageRgba8(sheet),
+            metadata: SpriteSheetMetadata {
+                frames,
+                padding,
+                format: "rgba8".to_string(),
+            },
+        })
     }
 
     /// Extract sprites from a sprite sheet
@@ -901,3 +1214,2098 @@ pub struct SpriteFrame {
     pub width: u32,
     pub height: u32,
 }
+
+/// Sprite sheet metadata in the JSON formats engines import directly
+///
+/// Aseprite JSON is read by Godot importers, Phaser and Bevy plugins such as
+/// `bevy_aseprite_ultra`; TexturePacker JSON is Phaser's and PixiJS's native
+/// atlas format. Frames are grouped into animations by name: `walk_0`,
+/// `walk_1`, ... or `character_walk` belong to the `walk` animation when
+/// `walk` is a key of `animation_frames`. Each animation plays one cycle in
+/// [`DEFAULT_CYCLE_MS`], split evenly across its frame count. [`write_sheet`]
+/// saves both next to every scaled variant of a packed sheet.
+pub mod sheet_export {
+    use super::indexed::IndexedPalette;
+    use super::scaling::{ScaledAsset, scale_metadata, write_variants};
+    use super::sprite_sheets::PackedSheet;
+    use super::*;
+    use serde::ser::Serializer;
+    use serde_json::json;
+
+    /// Length of one animation cycle
+    pub const DEFAULT_CYCLE_MS: u32 = 600;
+
+    /// Duration of frames that belong to no animation
+    pub const DEFAULT_FRAME_MS: u32 = 100;
+
+    /// Extension of the Aseprite JSON next to a sheet image
+    pub const ASEPRITE_SUFFIX: &str = "aseprite.json";
+
+    /// Extension of the TexturePacker JSON next to a sheet image
+    pub const TEXTURE_PACKER_SUFFIX: &str = "atlas.json";
+
+    /// Aseprite's two `--format` variants
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum AsepriteLayout {
+        /// `frames` is an object keyed by frame name (`json-hash`)
+        #[default]
+        Hash,
+        /// `frames` is an array with a `filename` per frame (`json-array`)
+        Array,
+    }
+
+    /// Exports [`SpriteSheetMetadata`] for a packed sheet image
+    pub struct SheetExport<'a> {
+        metadata: &'a SpriteSheetMetadata,
+        image: String,
+        size: (u32, u32),
+        animation_frames: HashMap<String, u32>,
+        cycle_ms: u32,
+    }
+
+    impl<'a> SheetExport<'a> {
+        /// `image` is the sheet's file name as referenced from the JSON
+        pub fn new(
+            metadata: &'a SpriteSheetMetadata,
+            image: impl Into<String>,
+            size: (u32, u32),
+        ) -> Self {
+            Self {
+                metadata,
+                image: image.into(),
+                size,
+                animation_frames: HashMap::new(),
+                cycle_ms: DEFAULT_CYCLE_MS,
+            }
+        }
+
+        /// Frame counts per animation, usually `sprite_specs.animation_frames`
+        pub fn with_animation_frames(mut self, animation_frames: HashMap<String, u32>) -> Self {
+            self.animation_frames = animation_frames;
+            self
+        }
+
+        pub fn with_cycle_ms(mut self, cycle_ms: u32) -> Self {
+            self.cycle_ms = cycle_ms;
+            self
+        }
+
+        /// Aseprite JSON with a frame tag per animation
+        pub fn to_aseprite(&self, layout: AsepriteLayout) -> Result<String> {
+            let frames = self.ordered_frames();
+            let frame_tags = animation_ranges(&frames)
+                .into_iter()
+                .map(|(name, from, to)| {
+                    json!({ "name": name, "from": from, "to": to, "direction": "forward" })
+                })
+                .collect::<Vec<_>>();
+            let meta = json!({
+                "app": "https://www.aseprite.org/",
+                "version": "1.3",
+                "image": self.image,
+                "format": "RGBA8888",
+                "size": { "w": self.size.0, "h": self.size.1 },
+                "scale": "1",
+                "frameTags": frame_tags,
+                "layers": [],
+                "slices": [],
+            });
+
+            let json = match layout {
+                AsepriteLayout::Hash => serde_json::to_string_pretty(&HashDocument {
+                    frames: FrameHash(&frames),
+                    animations: None,
+                    meta,
+                })?,
+                AsepriteLayout::Array => serde_json::to_string_pretty(&json!({
+                    "frames": frames
+                        .iter()
+                        .map(|frame| {
+                            let mut entry = frame.entry();
+                            entry["filename"] = json!(frame.name);
+                            entry
+                        })
+                        .collect::<Vec<_>>(),
+                    "meta": meta,
+                }))?,
+            };
+            Ok(json)
+        }
+
+        /// TexturePacker JSON (hash) with an `animations` map for Phaser
+        pub fn to_texture_packer(&self) -> Result<String> {
+            let frames = self.ordered_frames();
+            let animations: serde_json::Map<String, serde_json::Value> = animation_ranges(&frames)
+                .into_iter()
+                .map(|(name, from, to)| {
+                    let names: Vec<&str> = frames[from..=to].iter().map(|f| f.name).collect();
+                    (name.to_string(), json!(names))
+                })
+                .collect();
+
+            Ok(serde_json::to_string_pretty(&HashDocument {
+                frames: FrameHash(&frames),
+                animations: Some(animations),
+                meta: json!({
+                    "app": "https://www.codeandweb.com/texturepacker",
+                    "version": "1.0",
+                    "image": self.image,
+                    "format": "RGBA8888",
+                    "size": { "w": self.size.0, "h": self.size.1 },
+                    "scale": "1",
+                }),
+            })?)
+        }
+
+        /// Frames grouped by animation in playback order, then the rest by name
+        fn ordered_frames(&self) -> Vec<ExportFrame<'a>> {
+            let mut frames: Vec<ExportFrame> = self
+                .metadata
+                .frames
+                .iter()
+                .map(|(name, frame)| {
+                    let (animation, index) = self.animation_of(name);
+                    let duration = animation
+                        .and_then(|a| self.animation_frames.get(a))
+                        .map(|&count| self.cycle_ms / count.max(1))
+                        .unwrap_or(DEFAULT_FRAME_MS);
+                    ExportFrame {
+                        name,
+                        frame,
+                        animation: animation.map(str::to_string),
+                        index,
+                        duration,
+                    }
+                })
+                .collect();
+            frames.sort_by(|a, b| {
+                (a.animation.is_none(), &a.animation, a.index, a.name).cmp(&(
+                    b.animation.is_none(),
+                    &b.animation,
+                    b.index,
+                    b.name,
+                ))
+            });
+            frames
+        }
+
+        /// Split `character_walk_2` into the `walk` animation and index 2
+        fn animation_of<'n>(&'n self, name: &str) -> (Option<&'n str>, u32) {
+            let (stem, index) = match name.rsplit_once('_') {
+                Some((stem, index)) if index.parse::<u32>().is_ok() => {
+                    (stem, index.parse().unwrap_or(0))
+                }
+                _ => (name, 0),
+            };
+            let animation = self
+                .animation_frames
+                .keys()
+                .filter(|key| {
+                    stem == key.as_str()
+                        || stem
+                            .strip_suffix(key.as_str())
+                            .is_some_and(|prefix| prefix.ends_with('_'))
+                })
+                .max_by_key(|key| key.len())
+                .map(String::as_str);
+            (animation, index)
+        }
+    }
+
+    /// File of a sheet image's JSON, e.g. `hero@2x.atlas.json` for `hero@2x.png`
+    pub fn export_file(image_file: &str, suffix: &str) -> String {
+        let stem = image_file.strip_suffix(".png").unwrap_or(image_file);
+        format!("{stem}.{suffix}")
+    }
+
+    /// Save the sheet at every scale, see [`write_variants`], with the
+    /// Aseprite and TexturePacker JSON of each variant next to it
+    pub fn write_sheet(
+        id: &str,
+        sheet: &PackedSheet,
+        animation_frames: &HashMap<String, u32>,
+        palette: &IndexedPalette,
+        dir: &Path,
+    ) -> Result<ScaledAsset> {
+        let asset = write_variants(id, &sheet.image, palette, dir)?;
+        for variant in &asset.variants {
+            let metadata = scale_metadata(&sheet.metadata, variant.scale);
+            let export =
+                SheetExport::new(&metadata, &variant.file, (variant.width, variant.height))
+                    .with_animation_frames(animation_frames.clone());
+            let exports = [
+                (ASEPRITE_SUFFIX, export.to_aseprite(AsepriteLayout::Hash)?),
+                (TEXTURE_PACKER_SUFFIX, export.to_texture_packer()?),
+            ];
+            for (suffix, json) in exports {
+                let file = export_file(&variant.file, suffix);
+                std::fs::write(dir.join(&file), json)
+                    .with_context(|| format!("Failed to write {file}"))?;
+            }
+        }
+        Ok(asset)
+    }
+
+    struct ExportFrame<'a> {
+        name: &'a str,
+        frame: &'a SpriteFrame,
+        animation: Option<String>,
+        index: u32,
+        duration: u32,
+    }
+
+    impl ExportFrame<'_> {
+        fn entry(&self) -> serde_json::Value {
+            let SpriteFrame {
+                x,
+                y,
+                width: w,
+                height: h,
+            } = *self.frame;
+            json!({
+                "frame": { "x": x, "y": y, "w": w, "h": h },
+                "rotated": false,
+                "trimmed": false,
+                "spriteSourceSize": { "x": 0, "y": 0, "w": w, "h": h },
+                "sourceSize": { "w": w, "h": h },
+                "duration": self.duration,
+            })
+        }
+    }
+
+    /// Hash variant document; a typed struct so the frame order survives,
+    /// which `json!` would sort by name
+    #[derive(Serialize)]
+    struct HashDocument<'f, 'a> {
+        frames: FrameHash<'f, 'a>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        animations: Option<serde_json::Map<String, serde_json::Value>>,
+        meta: serde_json::Value,
+    }
+
+    /// Frames keyed by name, serialized in playback order
+    struct FrameHash<'f, 'a>(&'f [ExportFrame<'a>]);
+
+    impl Serialize for FrameHash<'_, '_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+            serializer.collect_map(self.0.iter().map(|frame| (frame.name, frame.entry())))
+        }
+    }
+
+    /// First and last frame index of each animation
+    fn animation_ranges<'f>(frames: &'f [ExportFrame]) -> Vec<(&'f str, usize, usize)> {
+        let mut ranges: Vec<(&str, usize, usize)> = Vec::new();
+        for (idx, frame) in frames.iter().enumerate() {
+            let Some(animation) = frame.animation.as_deref() else {
+                continue;
+            };
+            match ranges.last_mut() {
+                Some((name, _, to)) if *name == animation => *to = idx,
+                _ => ranges.push((animation, idx, idx)),
+            }
+        }
+        ranges
+    }
+}
+
+/// Derive palette and style hints from a generated style guide image
+pub mod style_extraction {
+    use super::*;
+
+    /// Pixels with lower alpha are ignored when sampling colors
+    const MIN_ALPHA: u8 = 128;
+
+    /// Colors darker than this luma can act as an outline color
+    const MAX_OUTLINE_LUMA: f32 = 64.0;
+
+    /// Share of edge pixels a dark color needs before it counts as an outline
+    const MIN_OUTLINE_SHARE: f32 = 0.3;
+
+    /// Number of hue buckets used to group palette ramps
+    const HUE_BUCKETS: usize = 12;
+
+    /// Palette and rendering hints extracted from an image
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct ExtractedStyle {
+        /// Quantized colors, most common first
+        pub colors: Vec<Color>,
+        /// Dark color that dominates shape edges, if any
+        pub outline_color: Option<Color>,
+        /// Shading inferred from how many shades each hue has
+        pub shading: ShadingTechnique,
+    }
+
+    impl ExtractedStyle {
+        /// Analyze an image, reducing it to at most `max_colors` colors
+        pub fn from_image(img: &DynamicImage, max_colors: usize) -> Self {
+            let rgba = img.to_rgba8();
+            let colors = median_cut(&rgba, max_colors.max(1));
+            let outline_color = detect_outline(&rgba, &colors);
+            let shading = infer_shading(&colors);
+
+            Self {
+                colors,
+                outline_color,
+                shading,
+            }
+        }
+
+        /// Split the colors into a palette: the most common half become
+        /// primary colors, the two most saturated of the rest become accents
+        pub fn to_palette(&self, name: &str, base: &ColorPalette) -> ColorPalette {
+            let split = self.colors.len().div_ceil(2);
+            let primary_colors = self.colors[..split].to_vec();
+            let mut rest = self.colors[split..].to_vec();
+
+            rest.sort_by(|a, b| saturation(*b).total_cmp(&saturation(*a)));
+            let accent_count = rest.len().min(2);
+            let accent_colors = rest.drain(..accent_count).collect();
+
+            ColorPalette {
+                name: name.to_string(),
+                primary_colors,
+                secondary_colors: rest,
+                accent_colors,
+                transparency_color: base.transparency_color,
+                max_colors: base.max_colors,
+            }
+        }
+    }
+
+    /// Median-cut quantization over the opaque pixels of an image
+    pub fn median_cut(img: &RgbaImage, max_colors: usize) -> Vec<Color> {
+        let pixels: Vec<[u8; 3]> = img
+            .pixels()
+            .filter(|p| p[3] >= MIN_ALPHA)
+            .map(|p| [p[0], p[1], p[2]])
+            .collect();
+        if pixels.is_empty() {
+            return Vec::new();
+        }
+
+        let mut boxes = vec![pixels];
+        while boxes.len() < max_colors {
+            // Split the box with the widest channel range
+            let Some((idx, channel, range)) = boxes
+                .iter()
+                .enumerate()
+                .filter(|(_, b)| b.len() > 1)
+                .map(|(idx, b)| {
+                    let (channel, range) = widest_channel(b);
+                    (idx, channel, range)
+                })
+                .max_by_key(|(_, _, range)| *range)
+            else {
+                break;
+            };
+            if range == 0 {
+                break;
+            }
+
+            let mut pixels = boxes.swap_remove(idx);
+            pixels.sort_unstable_by_key(|p| p[channel]);
+            let upper = pixels.split_off(pixels.len() / 2);
+            boxes.push(pixels);
+            boxes.push(upper);
+        }
+
+        boxes.sort_by_key(|b| std::cmp::Reverse(b.len()));
+        boxes.iter().map(|b| average(b)).collect()
+    }
+
+    fn widest_channel(pixels: &[[u8; 3]]) -> (usize, u8) {
+        (0..3)
+            .map(|c| {
+                let (min, max) = pixels.iter().fold((u8::MAX, u8::MIN), |(lo, hi), p| {
+                    (lo.min(p[c]), hi.max(p[c]))
+                });
+                (c, max - min)
+            })
+            .max_by_key(|(_, range)| *range)
+            .unwrap_or((0, 0))
+    }
+
+    fn average(pixels: &[[u8; 3]]) -> Color {
+        let mut sum = [0u64; 3];
+        for p in pixels {
+            for c in 0..3 {
+                sum[c] += p[c] as u64;
+            }
+        }
+        let n = pixels.len().max(1) as u64;
+        Color::new((sum[0] / n) as u8, (sum[1] / n) as u8, (sum[2] / n) as u8)
+    }
+
+    fn luma(color: Color) -> f32 {
+        0.299 * color.r as f32 + 0.587 * color.g as f32 + 0.114 * color.b as f32
+    }
+
+    fn saturation(color: Color) -> f32 {
+        let max = color.r.max(color.g).max(color.b) as f32;
+        let min = color.r.min(color.g).min(color.b) as f32;
+        if max == 0.0 { 0.0 } else { (max - min) / max }
+    }
+
+    fn hue_bucket(color: Color) -> Option<usize> {
+        let (r, g, b) = (color.r as f32, color.g as f32, color.b as f32);
+        let max = r.max(g).max(b);
+        let delta = max - r.min(g).min(b);
+        // Grays form their own ramp
+        if delta < 16.0 {
+            return None;
+        }
+        let hue = if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        Some((hue / 360.0 * HUE_BUCKETS as f32) as usize % HUE_BUCKETS)
+    }
+
+    /// Find a dark palette color that dominates pixels on shape boundaries
+    fn detect_outline(img: &RgbaImage, palette: &[Color]) -> Option<Color> {
+        let (width, height) = img.dimensions();
+        if width < 3 || height < 3 || palette.is_empty() {
+            return None;
+        }
+
+        let nearest = |p: &Rgba<u8>| -> usize {
+            palette
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, c)| {
+                    let dr = p[0] as i32 - c.r as i32;
+                    let dg = p[1] as i32 - c.g as i32;
+                    let db = p[2] as i32 - c.b as i32;
+                    dr * dr + dg * dg + db * db
+                })
+                .map(|(i, _)| i)
+                .unwrap_or(0)
+        };
+
+        let mut counts = vec![0usize; palette.len()];
+        let mut edges = 0usize;
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                let pixel = img.get_pixel(x, y);
+                if pixel[3] < MIN_ALPHA {
+                    continue;
+                }
+                let own = nearest(pixel);
+                let is_edge = [(0i32, -1i32), (-1, 0), (1, 0), (0, 1)]
+                    .iter()
+                    .any(|(dx, dy)| {
+                        let n = img.get_pixel((x as i32 + dx) as u32, (y as i32 + dy) as u32);
+                        n[3] < MIN_ALPHA || nearest(n) != own
+                    });
+                if is_edge {
+                    edges += 1;
+                    counts[own] += 1;
+                }
+            }
+        }
+
+        palette
+            .iter()
+            .zip(counts)
+            .filter(|(color, _)| luma(**color) <= MAX_OUTLINE_LUMA)
+            .max_by_key(|(_, count)| *count)
+            .filter(|(_, count)| edges > 0 && *count as f32 / edges as f32 >= MIN_OUTLINE_SHARE)
+            .map(|(color, _)| *color)
+    }
+
+    /// Count shades per hue; more shades per ramp means richer shading
+    fn infer_shading(palette: &[Color]) -> ShadingTechnique {
+        let mut ramps: HashMap<Option<usize>, usize> = HashMap::new();
+        for color in palette {
+            *ramps.entry(hue_bucket(*color)).or_default() += 1;
+        }
+        let chromatic: Vec<usize> = ramps
+            .iter()
+            .filter(|(hue, _)| hue.is_some())
+            .map(|(_, count)| *count)
+            .collect();
+        if chromatic.is_empty() {
+            return ShadingTechnique::Flat;
+        }
+
+        let mean = chromatic.iter().sum::<usize>() as f32 / chromatic.len() as f32;
+        if mean >= 2.5 {
+            ShadingTechnique::ThreeTone
+        } else if mean >= 1.5 {
+            ShadingTechnique::TwoTone
+        } else {
+            ShadingTechnique::Flat
+        }
+    }
+}
+
+/// Reduce large generated images to true sprite resolution
+///
+/// Image models return pictures of pixel art at 1024×1024 rather than actual
+/// 16×24 sprites. [`downscale_to_sprite`] crops to the subject, reduces each
+/// block of source pixels to a single pixel and snaps the result to the
+/// palette, producing an image at game resolution.
+pub mod downscaling {
+    use super::*;
+
+    /// Pixels with lower alpha count as background
+    const MIN_ALPHA: u8 = 128;
+
+    /// Largest per-channel distance from the background color that still
+    /// counts as background on opaque images
+    const BACKGROUND_TOLERANCE: u8 = 24;
+
+    /// Colors darker than this luma are treated as line work
+    const MAX_LINE_LUMA: f32 = 64.0;
+
+    /// Share of a block line work needs to win over the majority color
+    const MIN_LINE_SHARE: f32 = 1.0 / 3.0;
+
+    /// How each block of source pixels becomes one sprite pixel
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+    pub enum DownscaleMethod {
+        /// Sample the pixel at the center of the block
+        Nearest,
+        /// Use the most common color of the block instead of averaging, and
+        /// let dark line work win once it covers a third of the block so
+        /// outlines survive
+        #[default]
+        DominantColor,
+    }
+
+    /// Crop to the content, block-reduce to `target_size` and quantize to
+    /// the palette. Background becomes the palette's transparency color.
+    pub fn downscale_to_sprite(
+        img: &DynamicImage,
+        target_size: (u32, u32),
+        method: DownscaleMethod,
+        palette: &ColorPalette,
+    ) -> DynamicImage {
+        let (target_width, target_height) = (target_size.0.max(1), target_size.1.max(1));
+        let rgba = img.to_rgba8();
+        let background = detect_background(&rgba);
+        let is_background = |p: &Rgba<u8>| {
+            p[3] < MIN_ALPHA
+                || background
+                    .is_some_and(|bg| (0..3).all(|c| p[c].abs_diff(bg[c]) <= BACKGROUND_TOLERANCE))
+        };
+
+        let (x, y, width, height) = content_bounds(&rgba, &is_background)
+            .map(|bounds| fit_aspect(bounds, rgba.dimensions(), (target_width, target_height)))
+            .unwrap_or((0, 0, rgba.width(), rgba.height()));
+
+        let mut all_colors = Vec::new();
+        all_colors.extend(&palette.primary_colors);
+        all_colors.extend(&palette.secondary_colors);
+        all_colors.extend(&palette.accent_colors);
+
+        let transparent = palette.transparency_color;
+        let mut sprite = RgbaImage::new(target_width, target_height);
+        for ty in 0..target_height {
+            for tx in 0..target_width {
+                // Source block covered by this sprite pixel
+                let x0 = x + tx * width / target_width;
+                let x1 = (x + (tx + 1) * width / target_width).max(x0 + 1);
+                let y0 = y + ty * height / target_height;
+                let y1 = (y + (ty + 1) * height / target_height).max(y0 + 1);
+
+                let color = match method {
+                    DownscaleMethod::Nearest => {
+                        let p = rgba.get_pixel((x0 + x1) / 2, (y0 + y1) / 2);
+                        (!is_background(p)).then(|| Color::new(p[0], p[1], p[2]))
+                    }
+                    DownscaleMethod::DominantColor => {
+                        dominant_color(&rgba, (x0, y0, x1, y1), &is_background)
+                    }
+                };
+
+                let color = match color {
+                    Some(color) => nearest(color, &all_colors),
+                    None => transparent,
+                };
+                sprite.put_pixel(tx, ty, Rgba([color.r, color.g, color.b, color.a]));
+            }
+        }
+
+        DynamicImage::ImageRgba8(sprite)
+    }
+
+    /// Background color shared by the image corners, when the image is
+    /// opaque. Transparent images need no color key.
+    fn detect_background(img: &RgbaImage) -> Option<[u8; 3]> {
+        let (width, height) = img.dimensions();
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let corners = [
+            img.get_pixel(0, 0),
+            img.get_pixel(width - 1, 0),
+            img.get_pixel(0, height - 1),
+            img.get_pixel(width - 1, height - 1),
+        ];
+        if corners.iter().any(|p| p[3] < MIN_ALPHA) {
+            return None;
+        }
+
+        // At least three corners must agree to treat it as a backdrop
+        corners.iter().find_map(|candidate| {
+            let matching = corners
+                .iter()
+                .filter(|p| (0..3).all(|c| p[c].abs_diff(candidate[c]) <= BACKGROUND_TOLERANCE))
+                .count();
+            (matching >= 3).then(|| [candidate[0], candidate[1], candidate[2]])
+        })
+    }
+
+    /// Bounding box (x, y, width, height) of the non-background pixels
+    fn content_bounds(
+        img: &RgbaImage,
+        is_background: &impl Fn(&Rgba<u8>) -> bool,
+    ) -> Option<(u32, u32, u32, u32)> {
+        let mut bounds: Option<(u32, u32, u32, u32)> = None;
+        for (x, y, pixel) in img.enumerate_pixels() {
+            if is_background(pixel) {
+                continue;
+            }
+            bounds = Some(match bounds {
+                None => (x, y, x, y),
+                Some((min_x, min_y, max_x, max_y)) => {
+                    (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+                }
+            });
+        }
+        bounds.map(|(min_x, min_y, max_x, max_y)| {
+            (min_x, min_y, max_x - min_x + 1, max_y - min_y + 1)
+        })
+    }
+
+    /// Grow a crop around its center to the target aspect ratio, staying
+    /// inside the image where possible
+    fn fit_aspect(
+        (x, y, width, height): (u32, u32, u32, u32),
+        (image_width, image_height): (u32, u32),
+        (target_width, target_height): (u32, u32),
+    ) -> (u32, u32, u32, u32) {
+        let target_ratio = target_width as f32 / target_height as f32;
+        let (new_width, new_height) = if (width as f32 / height as f32) < target_ratio {
+            ((height as f32 * target_ratio).round() as u32, height)
+        } else {
+            (width, (width as f32 / target_ratio).round() as u32)
+        };
+        let new_width = new_width.clamp(1, image_width);
+        let new_height = new_height.clamp(1, image_height);
+
+        let center_x = x + width / 2;
+        let center_y = y + height / 2;
+        let new_x = center_x
+            .saturating_sub(new_width / 2)
+            .min(image_width - new_width);
+        let new_y = center_y
+            .saturating_sub(new_height / 2)
+            .min(image_height - new_height);
+        (new_x, new_y, new_width, new_height)
+    }
+
+    /// Most common 16-bit color bucket in a block, averaged within the
+    /// bucket. `None` when background covers at least half of the block.
+    fn dominant_color(
+        img: &RgbaImage,
+        (x0, y0, x1, y1): (u32, u32, u32, u32),
+        is_background: &impl Fn(&Rgba<u8>) -> bool,
+    ) -> Option<Color> {
+        let mut buckets: HashMap<u16, (usize, [u64; 3])> = HashMap::new();
+        let mut background = 0usize;
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let p = img.get_pixel(x, y);
+                if is_background(p) {
+                    background += 1;
+                    continue;
+                }
+                let entry = buckets
+                    .entry(Color::new(p[0], p[1], p[2]).to_16bit())
+                    .or_default();
+                entry.0 += 1;
+                for c in 0..3 {
+                    entry.1[c] += p[c] as u64;
+                }
+            }
+        }
+
+        let opaque: usize = buckets.values().map(|(count, _)| count).sum();
+        if opaque <= background {
+            return None;
+        }
+
+        let colors: Vec<(usize, Color)> = buckets
+            .into_values()
+            .map(|(count, sum)| {
+                let n = count as u64;
+                let color = Color::new((sum[0] / n) as u8, (sum[1] / n) as u8, (sum[2] / n) as u8);
+                (count, color)
+            })
+            .collect();
+
+        // Outlines are thinner than a block; keep them when they cover a
+        // good share of it rather than letting the fill color win
+        let line_work = colors
+            .iter()
+            .filter(|(_, color)| luma(*color) <= MAX_LINE_LUMA)
+            .max_by_key(|(count, color)| (*count, color.to_16bit()))
+            .filter(|(count, _)| *count as f32 / opaque as f32 >= MIN_LINE_SHARE);
+
+        line_work
+            .or_else(|| {
+                colors
+                    .iter()
+                    .max_by_key(|(count, color)| (*count, color.to_16bit()))
+            })
+            .map(|(_, color)| *color)
+    }
+
+    fn luma(color: Color) -> f32 {
+        0.299 * color.r as f32 + 0.587 * color.g as f32 + 0.114 * color.b as f32
+    }
+
+    fn nearest(color: Color, palette: &[Color]) -> Color {
+        palette
+            .iter()
+            .min_by_key(|p| {
+                let dr = color.r as i32 - p.r as i32;
+                let dg = color.g as i32 - p.g as i32;
+                let db = color.b as i32 - p.b as i32;
+                dr * dr + dg * dg + db * db
+            })
+            .copied()
+            .unwrap_or(color)
+    }
+}
+
+/// Seam detection and correction for tiles that must wrap
+pub mod tiling {
+    use super::*;
+
+    /// Mean edge difference above which a tile visibly seams
+    pub const MAX_SEAM_DIFFERENCE: f32 = 0.08;
+
+    /// How well opposite edges of a tile continue into each other
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct SeamReport {
+        /// Mean difference between the left and right columns (0.0 - 1.0)
+        pub horizontal: f32,
+        /// Mean difference between the top and bottom rows (0.0 - 1.0)
+        pub vertical: f32,
+    }
+
+    impl SeamReport {
+        pub fn is_seamless(&self) -> bool {
+            self.horizontal <= MAX_SEAM_DIFFERENCE && self.vertical <= MAX_SEAM_DIFFERENCE
+        }
+
+        /// Prompt guidance describing the edges that don't wrap
+        pub fn feedback(&self) -> Vec<String> {
+            let mut feedback = Vec::new();
+            if self.horizontal > MAX_SEAM_DIFFERENCE {
+                feedback.push(
+                    "The left and right edges must match pixel for pixel so the tile repeats \
+                     horizontally without a visible seam"
+                        .to_string(),
+                );
+            }
+            if self.vertical > MAX_SEAM_DIFFERENCE {
+                feedback.push(
+                    "The top and bottom edges must match pixel for pixel so the tile repeats \
+                     vertically without a visible seam"
+                        .to_string(),
+                );
+            }
+            feedback
+        }
+    }
+
+    /// Compare opposite edges of a tile
+    pub fn measure_seams(img: &DynamicImage) -> SeamReport {
+        let rgba = img.to_rgba8();
+        let (width, height) = rgba.dimensions();
+        if width == 0 || height == 0 {
+            return SeamReport {
+                horizontal: 0.0,
+                vertical: 0.0,
+            };
+        }
+
+        let horizontal = (0..height)
+            .map(|y| pixel_difference(rgba.get_pixel(0, y), rgba.get_pixel(width - 1, y)))
+            .sum::<f32>()
+            / height as f32;
+        let vertical = (0..width)
+            .map(|x| pixel_difference(rgba.get_pixel(x, 0), rgba.get_pixel(x, height - 1)))
+            .sum::<f32>()
+            / width as f32;
+
+        SeamReport {
+            horizontal,
+            vertical,
+        }
+    }
+
+    /// Blend each edge band with the opposite edge so the tile wraps.
+    ///
+    /// Pixels on the outermost columns/rows become the average of both edges;
+    /// the influence of the opposite side fades out over `band` pixels.
+    pub fn make_seamless(img: &DynamicImage, band: u32) -> DynamicImage {
+        let mut rgba = img.to_rgba8();
+        let (width, height) = rgba.dimensions();
+
+        let band_x = band.min(width / 2);
+        if band_x > 0 {
+            let source = rgba.clone();
+            for y in 0..height {
+                for i in 0..band_x {
+                    let weight = 0.5 * (1.0 - i as f32 / band_x as f32);
+                    let left = source.get_pixel(i, y);
+                    let right = source.get_pixel(width - 1 - i, y);
+                    rgba.put_pixel(i, y, mix(left, right, weight));
+                    rgba.put_pixel(width - 1 - i, y, mix(right, left, weight));
+                }
+            }
+        }
+
+        let band_y = band.min(height / 2);
+        if band_y > 0 {
+            let source = rgba.clone();
+            for x in 0..width {
+                for i in 0..band_y {
+                    let weight = 0.5 * (1.0 - i as f32 / band_y as f32);
+                    let top = source.get_pixel(x, i);
+                    let bottom = source.get_pixel(x, height - 1 - i);
+                    rgba.put_pixel(x, i, mix(top, bottom, weight));
+                    rgba.put_pixel(x, height - 1 - i, mix(bottom, top, weight));
+                }
+            }
+        }
+
+        DynamicImage::ImageRgba8(rgba)
+    }
+
+    /// Largest channel difference, scaled to 0.0 - 1.0
+    fn pixel_difference(a: &Rgba<u8>, b: &Rgba<u8>) -> f32 {
+        a.0.iter()
+            .zip(b.0.iter())
+            .map(|(x, y)| x.abs_diff(*y))
+            .max()
+            .unwrap_or(0) as f32
+            / 255.0
+    }
+
+    fn mix(a: &Rgba<u8>, b: &Rgba<u8>, weight: f32) -> Rgba<u8> {
+        let blend = |x: u8, y: u8| (x as f32 * (1.0 - weight) + y as f32 * weight).round() as u8;
+        Rgba([
+            blend(a[0], b[0]),
+            blend(a[1], b[1]),
+            blend(a[2], b[2]),
+            blend(a[3], b[3]),
+        ])
+    }
+}
+
+/// Cross-frame checks for the frames of one animation
+///
+/// Frames of a walk cycle should only differ in pose. Each frame is reduced to
+/// a signature of its silhouette (content bounds and a coarse occupancy grid)
+/// and its color usage. The frame closest to all others is the reference;
+/// frames too far from it are reported as outliers to be re-requested.
+pub mod frame_consistency {
+    use super::*;
+    use image::GenericImageView;
+
+    /// Pixels with lower alpha are not part of the silhouette
+    const MIN_ALPHA: u8 = 128;
+
+    /// Edge length of the occupancy grid
+    const GRID: u32 = 16;
+
+    /// How far a frame may drift from the reference frame
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    pub struct FrameTolerance {
+        /// Largest silhouette distance (0.0 - 1.0)
+        pub silhouette: f32,
+        /// Largest palette distance (0.0 - 1.0)
+        pub palette: f32,
+        /// Re-requests per outlier frame before it is kept as is
+        pub max_retries: u32,
+    }
+
+    impl Default for FrameTolerance {
+        fn default() -> Self {
+            Self {
+                silhouette: 0.25,
+                palette: 0.4,
+                max_retries: 2,
+            }
+        }
+    }
+
+    /// A frame that doesn't match the rest of its animation
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct FrameOutlier {
+        pub index: usize,
+        pub silhouette_distance: f32,
+        pub palette_distance: f32,
+    }
+
+    impl FrameOutlier {
+        /// Prompt guidance for re-requesting the frame
+        pub fn feedback(&self, tolerance: &FrameTolerance) -> String {
+            let mut feedback = Vec::new();
+            if self.silhouette_distance > tolerance.silhouette {
+                feedback.push(
+                    "keep exactly the same body proportions, height and size as the other frames",
+                );
+            }
+            if self.palette_distance > tolerance.palette {
+                feedback.push("use exactly the same colors as the other frames");
+            }
+            feedback.join("; ")
+        }
+    }
+
+    /// Silhouette and color summary of a frame
+    #[derive(Debug, Clone)]
+    pub struct FrameSignature {
+        /// Content width / height
+        aspect: f32,
+        /// Content height relative to the frame height
+        height: f32,
+        /// Opaque share of each cell of the content bounds
+        occupancy: Vec<f32>,
+        /// Share of opaque pixels per 4-bit-per-channel color
+        colors: HashMap<(u8, u8, u8), f32>,
+    }
+
+    impl FrameSignature {
+        pub fn of(img: &DynamicImage) -> Self {
+            let rgba = img.to_rgba8();
+            let (width, height) = rgba.dimensions();
+            let opaque = |x: u32, y: u32| rgba.get_pixel(x, y)[3] >= MIN_ALPHA;
+
+            let (mut min_x, mut min_y, mut max_x, mut max_y) = (width, height, 0, 0);
+            let mut colors: HashMap<(u8, u8, u8), f32> = HashMap::new();
+            let mut total = 0.0;
+            for (x, y, p) in rgba.enumerate_pixels() {
+                if p[3] < MIN_ALPHA {
+                    continue;
+                }
+                min_x = min_x.min(x);
+                min_y = min_y.min(y);
+                max_x = max_x.max(x);
+                max_y = max_y.max(y);
+                *colors.entry((p[0] >> 4, p[1] >> 4, p[2] >> 4)).or_default() += 1.0;
+                total += 1.0;
+            }
+            if total == 0.0 {
+                return Self {
+                    aspect: 0.0,
+                    height: 0.0,
+                    occupancy: vec![0.0; (GRID * GRID) as usize],
+                    colors,
+                };
+            }
+            colors.values_mut().for_each(|share| *share /= total);
+
+            let content_width = max_x - min_x + 1;
+            let content_height = max_y - min_y + 1;
+            let mut occupancy = vec![0.0; (GRID * GRID) as usize];
+            let mut counts = vec![0.0f32; (GRID * GRID) as usize];
+            for y in min_y..=max_y {
+                for x in min_x..=max_x {
+                    let cell_x = (x - min_x) * GRID / content_width;
+                    let cell_y = (y - min_y) * GRID / content_height;
+                    let cell = (cell_y * GRID + cell_x) as usize;
+                    counts[cell] += 1.0;
+                    if opaque(x, y) {
+                        occupancy[cell] += 1.0;
+                    }
+                }
+            }
+            for (share, count) in occupancy.iter_mut().zip(&counts) {
+                if *count > 0.0 {
+                    *share /= count;
+                }
+            }
+
+            Self {
+                aspect: content_width as f32 / content_height as f32,
+                height: content_height as f32 / img.dimensions().1 as f32,
+                occupancy,
+                colors,
+            }
+        }
+
+        /// Proportion change or shape mismatch, whichever is larger
+        pub fn silhouette_distance(&self, other: &Self) -> f32 {
+            let relative = |a: f32, b: f32| {
+                if a.max(b) > 0.0 {
+                    (a - b).abs() / a.max(b)
+                } else {
+                    0.0
+                }
+            };
+            let (overlap, union) = self
+                .occupancy
+                .iter()
+                .zip(&other.occupancy)
+                .fold((0.0, 0.0), |(overlap, union), (a, b)| {
+                    (overlap + a.min(*b), union + a.max(*b))
+                });
+            let shape = if union > 0.0 {
+                1.0 - overlap / union
+            } else {
+                0.0
+            };
+
+            relative(self.aspect, other.aspect)
+                .max(relative(self.height, other.height))
+                .max(shape)
+        }
+
+        /// One minus the histogram intersection of the color usage
+        pub fn palette_distance(&self, other: &Self) -> f32 {
+            let shared: f32 = self
+                .colors
+                .iter()
+                .filter_map(|(color, share)| other.colors.get(color).map(|o| share.min(*o)))
+                .sum();
+            (1.0 - shared).clamp(0.0, 1.0)
+        }
+    }
+
+    /// Frames of one animation that drift from the others beyond the tolerance
+    pub fn find_outliers(frames: &[DynamicImage], tolerance: &FrameTolerance) -> Vec<FrameOutlier> {
+        if frames.len() < 2 {
+            return Vec::new();
+        }
+        let signatures: Vec<FrameSignature> = frames.iter().map(FrameSignature::of).collect();
+
+        // The medoid frame, closest to all others, is the reference
+        let combined = |a: &FrameSignature, b: &FrameSignature| {
+            a.silhouette_distance(b) / tolerance.silhouette.max(f32::EPSILON)
+                + a.palette_distance(b) / tolerance.palette.max(f32::EPSILON)
+        };
+        let reference = (0..signatures.len())
+            .map(|i| {
+                let total: f32 = signatures
+                    .iter()
+                    .map(|other| combined(&signatures[i], other))
+                    .sum();
+                (i, total)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+
+        signatures
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != reference)
+            .map(|(index, signature)| FrameOutlier {
+                index,
+                silhouette_distance: signature.silhouette_distance(&signatures[reference]),
+                palette_distance: signature.palette_distance(&signatures[reference]),
+            })
+            .filter(|outlier| {
+                outlier.silhouette_distance > tolerance.silhouette
+                    || outlier.palette_distance > tolerance.palette
+            })
+            .collect()
+    }
+}
+
+/// Integer-scaled variants of game-resolution art
+///
+/// Every sprite and tile is exported at native resolution and at each factor
+/// of [`SCALES`], each source pixel repeated as a block of factor × factor
+/// pixels. A game picks the variant for its [`DisplayMode`] and draws it
+/// unscaled, so no filtering or uneven pixel widths creep in at runtime.
+/// Variants are named `{id}.png`, `{id}@2x.png` and `{id}@4x.png` and listed
+/// with their sizes in a [`ScaleManifest`].
+pub mod scaling {
+    use super::indexed::{IndexedPalette, write_png};
+    use super::*;
+    use std::collections::BTreeMap;
+
+    /// Factors every asset is exported at
+    pub const SCALES: &[u32] = &[1, 2, 4];
+
+    /// File name of the manifest next to the variants
+    pub const MANIFEST_FILE: &str = "scales.json";
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+    pub enum DisplayMode {
+        /// Art at its native resolution
+        Native,
+        /// Doubled pixels, for windows around 2× the native resolution
+        Crisp,
+        /// Quadrupled pixels, for large windows and full screen
+        Chunky,
+    }
+
+    impl DisplayMode {
+        pub fn scale(self) -> u32 {
+            match self {
+                DisplayMode::Native => 1,
+                DisplayMode::Crisp => 2,
+                DisplayMode::Chunky => 4,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct ScaledVariant {
+        pub scale: u32,
+        pub file: String,
+        pub width: u32,
+        pub height: u32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct ScaledAsset {
+        pub id: String,
+        /// Native size in pixels
+        pub width: u32,
+        pub height: u32,
+        pub variants: Vec<ScaledVariant>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct ScaleManifest {
+        pub scales: Vec<u32>,
+        pub modes: BTreeMap<DisplayMode, u32>,
+        pub assets: Vec<ScaledAsset>,
+    }
+
+    impl Default for ScaleManifest {
+        fn default() -> Self {
+            Self {
+                scales: SCALES.to_vec(),
+                modes: [DisplayMode::Native, DisplayMode::Crisp, DisplayMode::Chunky]
+                    .into_iter()
+                    .map(|mode| (mode, mode.scale()))
+                    .collect(),
+                assets: Vec::new(),
+            }
+        }
+    }
+
+    impl ScaleManifest {
+        pub fn to_json(&self) -> Result<String> {
+            Ok(serde_json::to_string_pretty(self)?)
+        }
+
+        /// Write `{dir}/scales.json`
+        pub fn write(&self, dir: &Path) -> Result<()> {
+            std::fs::write(dir.join(MANIFEST_FILE), self.to_json()?)
+                .context("Failed to write scale manifest")
+        }
+    }
+
+    /// Repeat every pixel as a `factor` × `factor` block
+    pub fn upscale(img: &DynamicImage, factor: u32) -> DynamicImage {
+        let factor = factor.max(1);
+        let rgba = img.to_rgba8();
+        let scaled = RgbaImage::from_fn(rgba.width() * factor, rgba.height() * factor, |x, y| {
+            *rgba.get_pixel(x / factor, y / factor)
+        });
+        DynamicImage::ImageRgba8(scaled)
+    }
+
+    /// File name of a variant, e.g. `slime@2x.png`
+    pub fn variant_file(id: &str, scale: u32) -> String {
+        if scale == 1 {
+            format!("{id}.png")
+        } else {
+            format!("{id}@{scale}x.png")
+        }
+    }
+
+    /// The image at every factor of [`SCALES`] as PNG data
+    pub fn encode_variants(img: &DynamicImage) -> Result<Vec<(u32, Vec<u8>)>> {
+        SCALES
+            .iter()
+            .map(|&scale| {
+                let mut buffer = Vec::new();
+                upscale(img, scale).write_to(
+                    &mut std::io::Cursor::new(&mut buffer),
+                    image::ImageFormat::Png,
+                )?;
+                Ok((scale, buffer))
+            })
+            .collect()
+    }
+
+    /// Save the image at every factor of [`SCALES`] into the directory, as
+    /// indexed PNGs in the palette
+    pub fn write_variants(
+        id: &str,
+        img: &DynamicImage,
+        palette: &IndexedPalette,
+        dir: &Path,
+    ) -> Result<ScaledAsset> {
+        let mut variants = Vec::with_capacity(SCALES.len());
+        for &scale in SCALES {
+            let scaled = upscale(img, scale);
+            let file = variant_file(id, scale);
+            write_png(&scaled.to_rgba8(), palette, &dir.join(&file))?;
+            variants.push(ScaledVariant {
+                scale,
+                file,
+                width: scaled.width(),
+                height: scaled.height(),
+            });
+        }
+        Ok(ScaledAsset {
+            id: id.to_string(),
+            width: img.width(),
+            height: img.height(),
+            variants,
+        })
+    }
+
+    /// Frame rectangles of a sheet exported at `factor`
+    pub fn scale_metadata(metadata: &SpriteSheetMetadata, factor: u32) -> SpriteSheetMetadata {
+        SpriteSheetMetadata {
+            frames: metadata
+                .frames
+                .iter()
+                .map(|(name, frame)| {
+                    let frame = SpriteFrame {
+                        x: frame.x * factor,
+                        y: frame.y * factor,
+                        width: frame.width * factor,
+                        height: frame.height * factor,
+                    };
+                    (name.clone(), frame)
+                })
+                .collect(),
+            padding: metadata.padding * factor,
+            format: metadata.format.clone(),
+        }
+    }
+}
+
+/// Time-of-day palette variants of a tileset
+///
+/// A tileset is regraded for each [`TimeOfDay`] without another image
+/// request: every color the tileset uses is pulled toward the hue of the
+/// time of day, desaturated and darkened, then snapped to the nearest color
+/// of the hardware palette. Because each source color maps to exactly one
+/// palette color, the variants are plain palette swaps, and the
+/// [`PaletteVariantSet`] written next to them lists the swaps so a game can
+/// either load the variant images or swap palettes at runtime.
+pub mod palette_variants {
+    use super::indexed::{IndexedPalette, write_png};
+    use super::*;
+    use crate::gradients::TimeOfDay;
+
+    /// Suffix of the metadata file, e.g. `forest.palettes.json`
+    pub const MANIFEST_SUFFIX: &str = "palettes.json";
+
+    /// Pixels with lower alpha are kept as they are
+    const MIN_ALPHA: u8 = 128;
+
+    /// Saturation below which a color is treated as gray and only tinted
+    const GRAY_SATURATION: f32 = 0.08;
+
+    /// Hue, saturation and value adjustment for a time of day
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    pub struct Grade {
+        /// Hue colors are pulled toward, in degrees
+        pub hue: f32,
+        /// How far hues move toward it (0.0 - 1.0)
+        pub hue_pull: f32,
+        pub saturation: f32,
+        pub value: f32,
+    }
+
+    impl Grade {
+        pub fn for_time(time: TimeOfDay) -> Self {
+            let (hue, hue_pull, saturation, value) = match time {
+                TimeOfDay::Dawn => (330.0, 0.15, 0.9, 0.9),
+                TimeOfDay::Day => (0.0, 0.0, 1.0, 1.0),
+                TimeOfDay::Dusk => (25.0, 0.3, 0.95, 0.75),
+                TimeOfDay::Night => (230.0, 0.5, 0.6, 0.45),
+            };
+            Self {
+                hue,
+                hue_pull,
+                saturation,
+                value,
+            }
+        }
+
+        /// The color regraded, before it is snapped to the palette
+        pub fn apply(&self, color: Color) -> Color {
+            let (mut h, mut s, v) = to_hsv(color);
+            if s < GRAY_SATURATION {
+                // Grays have no hue to pull, so they take a faint tint instead
+                h = self.hue;
+                s += self.hue_pull * 0.2;
+            } else {
+                let delta = (self.hue - h + 540.0).rem_euclid(360.0) - 180.0;
+                h = (h + delta * self.hue_pull).rem_euclid(360.0);
+            }
+            let mut graded = from_hsv(h, (s * self.saturation).min(1.0), v * self.value);
+            graded.a = color.a;
+            graded
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct ColorSwap {
+        pub from: Color,
+        pub to: Color,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct PaletteVariant {
+        pub time: TimeOfDay,
+        pub file: String,
+        pub grade: Grade,
+        /// Every opaque color of the base tileset and its replacement
+        pub swaps: Vec<ColorSwap>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct PaletteVariantSet {
+        pub id: String,
+        /// The tileset the variants were derived from
+        pub base: String,
+        pub variants: Vec<PaletteVariant>,
+    }
+
+    impl PaletteVariantSet {
+        pub fn variant(&self, time: TimeOfDay) -> Option<&PaletteVariant> {
+            self.variants.iter().find(|variant| variant.time == time)
+        }
+
+        pub fn to_json(&self) -> Result<String> {
+            Ok(serde_json::to_string_pretty(self)?)
+        }
+    }
+
+    /// File name of a variant, e.g. `forest_night.png`
+    pub fn variant_file(id: &str, time: TimeOfDay) -> String {
+        format!("{id}_{}.png", time.name())
+    }
+
+    /// The swaps taking every opaque color of the tileset to its regraded
+    /// palette color, in order of first appearance. An empty palette keeps
+    /// the regraded colors.
+    pub fn swaps(tileset: &DynamicImage, time: TimeOfDay, palette: &[Color]) -> Vec<ColorSwap> {
+        let grade = Grade::for_time(time);
+        let mut seen = HashMap::new();
+        let mut swaps = Vec::new();
+        for pixel in tileset.to_rgba8().pixels() {
+            if pixel[3] < MIN_ALPHA {
+                continue;
+            }
+            let from = Color::new(pixel[0], pixel[1], pixel[2]);
+            if seen.contains_key(&from) {
+                continue;
+            }
+            let graded = grade.apply(from);
+            let to = nearest(graded, palette).unwrap_or(graded);
+            seen.insert(from, to);
+            swaps.push(ColorSwap { from, to });
+        }
+        swaps
+    }
+
+    /// Apply the swaps, keeping alpha and transparent pixels
+    pub fn apply_swaps(tileset: &DynamicImage, swaps: &[ColorSwap]) -> DynamicImage {
+        let map: HashMap<Color, Color> = swaps.iter().map(|swap| (swap.from, swap.to)).collect();
+        let mut rgba = tileset.to_rgba8();
+        for pixel in rgba.pixels_mut() {
+            if pixel[3] < MIN_ALPHA {
+                continue;
+            }
+            if let Some(to) = map.get(&Color::new(pixel[0], pixel[1], pixel[2])) {
+                *pixel = Rgba([to.r, to.g, to.b, pixel[3]]);
+            }
+        }
+        DynamicImage::ImageRgba8(rgba)
+    }
+
+    /// The tileset at every time of day, with the swaps that produced it
+    pub fn derive_variants(
+        tileset: &DynamicImage,
+        palette: &[Color],
+    ) -> Vec<(TimeOfDay, DynamicImage, Vec<ColorSwap>)> {
+        TimeOfDay::ALL
+            .into_iter()
+            .map(|time| {
+                let swaps = swaps(tileset, time, palette);
+                (time, apply_swaps(tileset, &swaps), swaps)
+            })
+            .collect()
+    }
+
+    /// Save every variant as `{id}_{time}.png`, indexed in the palette, and
+    /// the set as `{id}.palettes.json` in the directory. `base` is the file
+    /// the tileset was saved as.
+    pub fn write_variants(
+        id: &str,
+        base: &str,
+        tileset: &DynamicImage,
+        palette: &[Color],
+        dir: &Path,
+    ) -> Result<PaletteVariantSet> {
+        let indexed = IndexedPalette::from_colors(palette)?;
+        let mut variants = Vec::new();
+        for (time, image, swaps) in derive_variants(tileset, palette) {
+            let file = variant_file(id, time);
+            write_png(&image.to_rgba8(), &indexed, &dir.join(&file))?;
+            variants.push(PaletteVariant {
+                time,
+                file,
+                grade: Grade::for_time(time),
+                swaps,
+            });
+        }
+        let set = PaletteVariantSet {
+            id: id.to_string(),
+            base: base.to_string(),
+            variants,
+        };
+        std::fs::write(dir.join(format!("{id}.{MANIFEST_SUFFIX}")), set.to_json()?)
+            .context("Failed to write palette variants")?;
+        Ok(set)
+    }
+
+    fn nearest(color: Color, palette: &[Color]) -> Option<Color> {
+        palette
+            .iter()
+            .filter(|c| c.a >= MIN_ALPHA)
+            .min_by_key(|c| {
+                // Weighted toward green like the eye, so darkened colors
+                // snap to palette colors of a similar brightness
+                let dr = color.r as i32 - c.r as i32;
+                let dg = color.g as i32 - c.g as i32;
+                let db = color.b as i32 - c.b as i32;
+                3 * dr * dr + 4 * dg * dg + 2 * db * db
+            })
+            .map(|c| Color::new(c.r, c.g, c.b))
+    }
+
+    /// Hue in degrees, saturation and value (0.0 - 1.0)
+    fn to_hsv(color: Color) -> (f32, f32, f32) {
+        let (r, g, b) = (
+            color.r as f32 / 255.0,
+            color.g as f32 / 255.0,
+            color.b as f32 / 255.0,
+        );
+        let max = r.max(g).max(b);
+        let delta = max - r.min(g).min(b);
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        let saturation = if max == 0.0 { 0.0 } else { delta / max };
+        (hue, saturation, max)
+    }
+
+    fn from_hsv(hue: f32, saturation: f32, value: f32) -> Color {
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - ((hue / 60.0).rem_euclid(2.0) - 1.0).abs());
+        let (r, g, b) = match (hue.rem_euclid(360.0) / 60.0) as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = value - chroma;
+        let channel = |c: f32| ((c + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+        Color::new(channel(r), channel(g), channel(b))
+    }
+}
+
+pub mod indexed {
+    //! Indexed-color (PLTE) PNG output
+    //!
+    //! Palette-quantized art is written with a palette chunk instead of
+    //! RGBA, at the smallest bit depth the palette fits (1, 2, 4 or 8 bits
+    //! per pixel). The palette follows the active [`ColorPalette`], so every
+    //! asset of a style shares the same color indices, with the transparency
+    //! color at index 0 marked transparent in the tRNS chunk as retro tools
+    //! expect.
+
+    use super::*;
+    use anyhow::bail;
+
+    /// Most colors an indexed PNG can hold
+    pub const MAX_INDEXED_COLORS: usize = 256;
+
+    /// Pixels with lower alpha use the transparent index
+    const MIN_ALPHA: u8 = 128;
+
+    /// Colors of an indexed image in index order
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct IndexedPalette {
+        pub colors: Vec<Color>,
+        /// Index that transparent pixels are written with
+        pub transparent_index: Option<u8>,
+    }
+
+    impl IndexedPalette {
+        /// The style palette: the transparency color first, then primary,
+        /// secondary and accent colors without repeats
+        pub fn from_palette(palette: &ColorPalette) -> Result<Self> {
+            let mut colors = vec![palette.transparency_color];
+            for color in palette
+                .primary_colors
+                .iter()
+                .chain(&palette.secondary_colors)
+                .chain(&palette.accent_colors)
+            {
+                let color = Color::new(color.r, color.g, color.b);
+                if !colors[1..].contains(&color) {
+                    colors.push(color);
+                }
+            }
+            if colors.len() > MAX_INDEXED_COLORS {
+                bail!(
+                    "palette '{}' has {} colors, more than an indexed PNG holds",
+                    palette.name,
+                    colors.len()
+                );
+            }
+            Ok(Self {
+                colors,
+                transparent_index: Some(0),
+            })
+        }
+
+        /// The colors without repeats after a transparent entry, for art
+        /// drawn in a bare list of colors
+        pub fn from_colors(colors: &[Color]) -> Result<Self> {
+            let mut indexed = vec![Color::transparent()];
+            for color in colors {
+                let color = Color::new(color.r, color.g, color.b);
+                if !indexed[1..].contains(&color) {
+                    indexed.push(color);
+                }
+            }
+            if indexed.len() > MAX_INDEXED_COLORS {
+                bail!(
+                    "{} colors are more than an indexed PNG holds",
+                    indexed.len()
+                );
+            }
+            Ok(Self {
+                colors: indexed,
+                transparent_index: Some(0),
+            })
+        }
+
+        /// The image's own colors in order of first appearance, with fully
+        /// transparent pixels sharing one entry. Fails if there are more
+        /// than fit.
+        pub fn from_image(img: &RgbaImage) -> Result<Self> {
+            let mut colors = Vec::new();
+            let mut seen = HashMap::new();
+            let mut transparent_index = None;
+            for pixel in img.pixels() {
+                let color = if pixel[3] == 0 {
+                    Color::transparent()
+                } else {
+                    Color {
+                        r: pixel[0],
+                        g: pixel[1],
+                        b: pixel[2],
+                        a: pixel[3],
+                    }
+                };
+                if seen.contains_key(&color) {
+                    continue;
+                }
+                if colors.len() == MAX_INDEXED_COLORS {
+                    bail!("image has more than {MAX_INDEXED_COLORS} colors and can't be indexed");
+                }
+                if pixel[3] == 0 {
+                    transparent_index = Some(colors.len() as u8);
+                }
+                seen.insert(color, colors.len() as u8);
+                colors.push(color);
+            }
+            Ok(Self {
+                colors,
+                transparent_index,
+            })
+        }
+
+        /// The same entries in shades of gray, for locked or disabled
+        /// variants of art indexed in this palette. The transparent entry
+        /// keeps its color.
+        pub fn grayscale(&self) -> Self {
+            let colors = self
+                .colors
+                .iter()
+                .enumerate()
+                .map(|(i, &c)| {
+                    if Some(i as u8) == self.transparent_index {
+                        return c;
+                    }
+                    let luma = image::Pixel::to_luma(&image::Rgb([c.r, c.g, c.b]))[0];
+                    Color {
+                        r: luma,
+                        g: luma,
+                        b: luma,
+                        a: c.a,
+                    }
+                })
+                .collect();
+            Self {
+                colors,
+                transparent_index: self.transparent_index,
+            }
+        }
+
+        /// Bits per pixel needed for the palette
+        pub fn bit_depth(&self) -> u8 {
+            match self.colors.len() {
+                0..=2 => 1,
+                3..=4 => 2,
+                5..=16 => 4,
+                _ => 8,
+            }
+        }
+
+        /// The index of every pixel, row by row. Colors not in the palette
+        /// are snapped to the nearest entry and transparent pixels use the
+        /// transparent index.
+        pub fn index(&self, img: &RgbaImage) -> Vec<u8> {
+            let mut cache: HashMap<[u8; 4], u8> = HashMap::new();
+            img.pixels()
+                .map(|pixel| {
+                    *cache
+                        .entry(pixel.0)
+                        .or_insert_with(|| self.nearest(pixel.0))
+                })
+                .collect()
+        }
+
+        fn nearest(&self, [r, g, b, a]: [u8; 4]) -> u8 {
+            if a < MIN_ALPHA
+                && let Some(index) = self.transparent_index
+            {
+                return index;
+            }
+            let exact = self
+                .colors
+                .iter()
+                .position(|c| (c.r, c.g, c.b, c.a) == (r, g, b, a));
+            let index = exact.unwrap_or_else(|| {
+                self.colors
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| Some(*i as u8) != self.transparent_index)
+                    .min_by_key(|(_, c)| {
+                        let dr = r as i32 - c.r as i32;
+                        let dg = g as i32 - c.g as i32;
+                        let db = b as i32 - c.b as i32;
+                        3 * dr * dr + 4 * dg * dg + 2 * db * db
+                    })
+                    .map_or(0, |(i, _)| i)
+            });
+            index as u8
+        }
+
+        /// Alpha of each entry, with the transparent index fully transparent
+        fn alphas(&self) -> Vec<u8> {
+            self.colors
+                .iter()
+                .enumerate()
+                .map(|(i, c)| {
+                    if Some(i as u8) == self.transparent_index {
+                        0
+                    } else {
+                        c.a
+                    }
+                })
+                .collect()
+        }
+    }
+
+    /// The image as an indexed PNG
+    pub fn encode_png(img: &RgbaImage, palette: &IndexedPalette) -> Result<Vec<u8>> {
+        if palette.colors.is_empty() {
+            bail!("cannot index an image with an empty palette");
+        }
+        let depth = palette.bit_depth();
+        let indices = palette.index(img);
+        let width = img.width() as usize;
+        let per_byte = 8 / depth as usize;
+        let row_bytes = width.div_ceil(per_byte);
+        let mut data = vec![0u8; row_bytes * img.height() as usize];
+        for (row, pixels) in indices.chunks(width.max(1)).enumerate() {
+            for (x, &index) in pixels.iter().enumerate() {
+                let shift = 8 - depth as usize * (x % per_byte + 1);
+                data[row * row_bytes + x / per_byte] |= index << shift;
+            }
+        }
+
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, img.width(), img.height());
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(match depth {
+            1 => png::BitDepth::One,
+            2 => png::BitDepth::Two,
+            4 => png::BitDepth::Four,
+            _ => png::BitDepth::Eight,
+        });
+        encoder.set_palette(
+            palette
+                .colors
+                .iter()
+                .flat_map(|c| [c.r, c.g, c.b])
+                .collect::<Vec<_>>(),
+        );
+        let mut alphas = palette.alphas();
+        // tRNS may stop at the last entry that isn't opaque
+        while alphas.last() == Some(&255) {
+            alphas.pop();
+        }
+        if !alphas.is_empty() {
+            encoder.set_trns(alphas);
+        }
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&data)?;
+        writer.finish()?;
+        Ok(png)
+    }
+
+    /// Write the image as an indexed PNG
+    pub fn write_png(img: &RgbaImage, palette: &IndexedPalette, path: &Path) -> Result<()> {
+        std::fs::write(path, encode_png(img, palette)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::indexed::IndexedPalette;
+    use super::scaling::{SCALES, upscale, variant_file};
+    use super::sheet_export::{ASEPRITE_SUFFIX, TEXTURE_PACKER_SUFFIX, export_file, write_sheet};
+    use super::sprite_sheets::pack_named_sprites;
+    use super::*;
+
+    fn sprite(shade: u8) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_fn(4, 6, |x, y| {
+            Rgba([shade, x as u8 * 40, y as u8 * 30, 255])
+        }))
+    }
+
+    fn walk_cycle() -> Vec<(String, DynamicImage)> {
+        vec![
+            ("walk_0".to_string(), sprite(10)),
+            ("walk_1".to_string(), sprite(90)),
+            ("idle_0".to_string(), sprite(170)),
+        ]
+    }
+
+    fn color_type(path: &Path) -> png::ColorType {
+        let decoder =
+            png::Decoder::new(std::io::BufReader::new(std::fs::File::open(path).unwrap()));
+        decoder.read_info().unwrap().info().color_type
+    }
+
+    fn read_json(path: &Path) -> serde_json::Value {
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    /// The sprite a frame entry of either format points at
+    fn crop(sheet: &DynamicImage, entry: &serde_json::Value) -> DynamicImage {
+        let rect = |key: &str| entry["frame"][key].as_u64().unwrap() as u32;
+        sheet.crop_imm(rect("x"), rect("y"), rect("w"), rect("h"))
+    }
+
+    #[test]
+    fn packed_frames_point_at_their_sprites() {
+        let sheet = pack_named_sprites(walk_cycle(), 2).unwrap();
+        assert_eq!(sheet.metadata.frames.len(), 3);
+        for (name, sprite) in walk_cycle() {
+            let frame = &sheet.metadata.frames[&name];
+            let packed = sheet
+                .image
+                .crop_imm(frame.x, frame.y, frame.width, frame.height);
+            assert_eq!(packed.to_rgba8(), sprite.to_rgba8(), "{name}");
+        }
+    }
+
+    #[test]
+    fn sheet_exports_round_trip_at_every_scale() {
+        let dir = std::env::temp_dir().join(format!("sheet_export_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let sheet = pack_named_sprites(walk_cycle(), 2).unwrap();
+        let animation_frames = HashMap::from([("walk".to_string(), 2), ("idle".to_string(), 1)]);
+
+        let palette = IndexedPalette::from_image(&sheet.image.to_rgba8()).unwrap();
+        let asset = write_sheet("hero", &sheet, &animation_frames, &palette, &dir).unwrap();
+        assert_eq!(asset.variants.len(), SCALES.len());
+
+        for &scale in SCALES {
+            let file = variant_file("hero", scale);
+            assert_eq!(color_type(&dir.join(&file)), png::ColorType::Indexed);
+            let image = image::open(dir.join(&file)).unwrap();
+            let aseprite = read_json(&dir.join(export_file(&file, ASEPRITE_SUFFIX)));
+            let atlas = read_json(&dir.join(export_file(&file, TEXTURE_PACKER_SUFFIX)));
+
+            for json in [&aseprite, &atlas] {
+                assert_eq!(json["meta"]["image"], file.as_str());
+                assert_eq!(json["meta"]["size"]["w"], image.width());
+                assert_eq!(json["meta"]["size"]["h"], image.height());
+                for (name, sprite) in walk_cycle() {
+                    let cropped = crop(&image, &json["frames"][&name]);
+                    assert_eq!(
+                        cropped.to_rgba8(),
+                        upscale(&sprite, scale).to_rgba8(),
+                        "{name} at {scale}x"
+                    );
+                }
+            }
+
+            let tags = aseprite["meta"]["frameTags"].as_array().unwrap();
+            let walk = tags.iter().find(|tag| tag["name"] == "walk").unwrap();
+            let order: Vec<&String> = aseprite["frames"].as_object().unwrap().keys().collect();
+            assert_eq!(order[walk["from"].as_u64().unwrap() as usize], "walk_0");
+            assert_eq!(order[walk["to"].as_u64().unwrap() as usize], "walk_1");
+            assert_eq!(
+                atlas["animations"]["walk"],
+                serde_json::json!(["walk_0", "walk_1"])
+            );
+            assert_eq!(aseprite["frames"]["walk_0"]["duration"], 300);
+        }
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn time_of_day_variants_are_indexed_in_the_palette() {
+        let dir = std::env::temp_dir().join(format!("palette_variants_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let palette = [
+            Color::new(16, 24, 32),
+            Color::new(48, 96, 160),
+            Color::new(200, 180, 90),
+        ];
+        let tileset = DynamicImage::ImageRgba8(RgbaImage::from_fn(8, 8, |x, y| {
+            let color = palette[((x + y) % 3) as usize];
+            Rgba([color.r, color.g, color.b, 255])
+        }));
+
+        let set = palette_variants::write_variants("grass", "grass.png", &tileset, &palette, &dir)
+            .unwrap();
+        for variant in &set.variants {
+            let path = dir.join(&variant.file);
+            assert_eq!(color_type(&path), png::ColorType::Indexed);
+            let image = image::open(&path).unwrap().to_rgba8();
+            assert!(
+                image
+                    .pixels()
+                    .all(|p| palette.contains(&Color::new(p[0], p[1], p[2]))),
+                "{} left the palette",
+                variant.file
+            );
+        }
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    fn black_and_white() -> ColorPalette {
+        ColorPalette {
+            name: "1-bit".to_string(),
+            primary_colors: vec![Color::new(0, 0, 0), Color::new(255, 255, 255)],
+            secondary_colors: Vec::new(),
+            accent_colors: Vec::new(),
+            transparency_color: Color {
+                r: 255,
+                g: 0,
+                b: 255,
+                a: 0,
+            },
+            max_colors: 2,
+        }
+    }
+
+    fn gray(value: u8) -> Rgba<u8> {
+        Rgba([value, value, value, 255])
+    }
+
+    fn dither(img: RgbaImage, opaque_only: bool) -> RgbaImage {
+        StyleManager::new()
+            .floyd_steinberg(
+                &DynamicImage::ImageRgba8(img),
+                &black_and_white(),
+                opaque_only,
+            )
+            .to_rgba8()
+    }
+
+    #[test]
+    fn dithering_only_uses_the_palette() {
+        let gradient = RgbaImage::from_fn(16, 8, |x, y| {
+            if x == 0 && y == 0 {
+                Rgba([0, 0, 0, 0])
+            } else {
+                gray((x * 16 + y) as u8)
+            }
+        });
+        let dithered = dither(gradient, true);
+        assert_eq!(*dithered.get_pixel(0, 0), Rgba([255, 0, 255, 0]));
+        for pixel in dithered.pixels().skip(1) {
+            assert!(
+                matches!(pixel.0, [0, 0, 0, 255] | [255, 255, 255, 255]),
+                "{pixel:?}"
+            );
+        }
+        // Mid grays come out as a mix of both colors
+        let white = dithered
+            .pixels()
+            .filter(|p| p[0] == 255 && p[3] == 255)
+            .count();
+        assert!(white > 16 && white < 16 * 8 - 16);
+    }
+
+    #[test]
+    fn flat_palette_colors_stay_undithered() {
+        let flat = RgbaImage::from_pixel(8, 8, gray(255));
+        assert_eq!(dither(flat.clone(), true), flat);
+    }
+
+    #[test]
+    fn odd_rows_pass_the_error_left() {
+        let row = [100, 100, 60];
+        // Row 0 is already in the palette, so only row 1 carries error. The
+        // error below the last row is dropped rather than folded back in.
+        let img = RgbaImage::from_fn(3, 2, |x, y| {
+            if y == 0 {
+                gray(0)
+            } else {
+                gray(row[x as usize])
+            }
+        });
+        let odd: Vec<u8> = (0..3)
+            .map(|x| dither(img.clone(), false).get_pixel(x, 1)[0])
+            .collect();
+        // Scanned right to left: 60 rounds down and pushes its error onto
+        // the middle pixel, which rounds down too and tips the left one up
+        assert_eq!(odd, [255, 0, 0]);
+
+        // The same as the mirrored row scanned left to right on an even row
+        let mirrored = RgbaImage::from_fn(3, 1, |x, _| gray(row[2 - x as usize]));
+        let even: Vec<u8> = (0..3)
+            .rev()
+            .map(|x| dither(mirrored.clone(), false).get_pixel(x, 0)[0])
+            .collect();
+        assert_eq!(odd, even);
+    }
+
+    /// A 6×6 sprite: a transparent border around 4×4 of `fill` with the
+    /// pixels where `inner` holds in `other`
+    fn framed(fill: Rgba<u8>, other: Rgba<u8>, inner: impl Fn(u32, u32) -> bool) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_fn(6, 6, |x, y| {
+            if x == 0 || y == 0 || x == 5 || y == 5 {
+                Rgba([0, 0, 0, 0])
+            } else if inner(x - 1, y - 1) {
+                other
+            } else {
+                fill
+            }
+        }))
+    }
+
+    fn rgb_palette() -> ColorPalette {
+        ColorPalette {
+            primary_colors: vec![
+                Color::new(255, 0, 0),
+                Color::new(0, 0, 255),
+                Color::new(0, 0, 0),
+            ],
+            max_colors: 3,
+            ..black_and_white()
+        }
+    }
+
+    fn downscale(
+        img: &DynamicImage,
+        size: (u32, u32),
+        method: downscaling::DownscaleMethod,
+    ) -> RgbaImage {
+        downscaling::downscale_to_sprite(img, size, method, &rgb_palette()).to_rgba8()
+    }
+
+    #[test]
+    fn downscaling_produces_the_target_size() {
+        use downscaling::DownscaleMethod;
+
+        let art = DynamicImage::ImageRgba8(RgbaImage::from_fn(64, 96, |x, y| {
+            Rgba([(x * 4) as u8, (y * 2) as u8, 90, 255])
+        }));
+        for method in [DownscaleMethod::Nearest, DownscaleMethod::DominantColor] {
+            assert_eq!(downscale(&art, (16, 24), method).dimensions(), (16, 24));
+            assert_eq!(downscale(&art, (5, 3), method).dimensions(), (5, 3));
+            assert_eq!(downscale(&art, (0, 0), method).dimensions(), (1, 1));
+        }
+    }
+
+    #[test]
+    fn dominant_color_wins_over_the_center_pixel() {
+        use downscaling::DownscaleMethod;
+
+        // Mostly dull red, with a light blue center
+        let red = Rgba([200, 40, 40, 255]);
+        let blue = Rgba([60, 60, 220, 255]);
+        let sprite = framed(red, blue, |x, y| (1..3).contains(&x) && (1..3).contains(&y));
+
+        // Both snap to the palette, from different samples of the block
+        let nearest = downscale(&sprite, (1, 1), DownscaleMethod::Nearest);
+        assert_eq!(*nearest.get_pixel(0, 0), Rgba([0, 0, 255, 255]));
+        let dominant = downscale(&sprite, (1, 1), DownscaleMethod::DominantColor);
+        assert_eq!(*dominant.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+
+        // Dark line work on 6 of 16 pixels beats the red majority
+        let outline = framed(red, Rgba([10, 10, 10, 255]), |x, y| {
+            x == 0 || (x == 3 && y < 2)
+        });
+        let dominant = downscale(&outline, (1, 1), DownscaleMethod::DominantColor);
+        assert_eq!(*dominant.get_pixel(0, 0), Rgba([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn background_blocks_become_transparent() {
+        use downscaling::DownscaleMethod;
+
+        // Content in the left half only, cropped to a square
+        let half = DynamicImage::ImageRgba8(RgbaImage::from_fn(8, 8, |x, _| {
+            if x < 4 {
+                Rgba([250, 10, 10, 255])
+            } else {
+                Rgba([0, 0, 0, 0])
+            }
+        }));
+        let sprite = downscale(&half, (2, 1), DownscaleMethod::DominantColor);
+        assert_eq!(*sprite.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+        assert_eq!(*sprite.get_pixel(1, 0), Rgba([255, 0, 255, 0]));
+    }
+}
@@ -3,13 +3,34 @@
 use super::{
     manager::ConversationManager,
     starters,
-    types::{GenerationPhase, GenerationProgress, MessageConfig},
+    types::{AssetGenerators, GenerationPhase, GenerationProgress, MessageConfig},
+};
+use crate::achievements::write_icons;
+use crate::bosses::{BossDesign, BossRoster};
+use crate::consistency::downscaling::DownscaleMethod;
+use crate::consistency::palette_variants;
+use crate::consistency::scaling::{self, ScaleManifest, variant_file};
+use crate::consistency::sheet_export::write_sheet;
+use crate::encounters::DifficultyPreset;
+use crate::game_assets::{
+    ACHIEVEMENT_ASSET_DIR, GameDataAssets, MUSIC_ASSET_DIR, SPRITE_ASSET_DIR, TILESET_ASSET_DIR,
 };
 use crate::game_types::{GameConfig, WorldData};
+use crate::image::sprite_sheets::{generate_character_sheet, generate_tileset};
+use crate::postgame::{PostgamePlan, SUPERBOSS_MIN_HP_RATIO};
+use crate::quests::QuestGraph;
+use crate::slugs::{AssetCategory, SLUG_REGISTRY_FILE, SlugRegistry};
+use crate::text::names::NameGenerator;
+use crate::world::{BiomeTileset, OVERVIEW_TILE_SIZE, WorldGenerator, WorldGraph};
 use anyhow::Result;
+use image::DynamicImage;
 use minijinja::context;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+/// Tiles generated for the tileset of each biome
+const BIOME_TILES: &[&str] = &["ground", "path", "wall", "water"];
+
 /// Extension trait for game generation methods
 #[async_trait::async_trait]
 pub trait GameGenerationExt {
@@ -103,11 +124,14 @@ impl GameGenerationExt for ConversationManager {
                     .send_message_with_config(
                         conversation_id,
                         extraction_prompt,
-                        Some(MessageConfig {
-                            model: "gpt-4-turbo".to_string(),
-                            temperature: 0.3,
-                            max_tokens: 4000,
-                        }),
+                        Some(
+                            MessageConfig {
+                                model: "gpt-4-turbo".to_string(),
+                                max_tokens: 4000,
+                                ..Default::default()
+                            }
+                            .with_profile(&self.profiles.json),
+                        ),
                     )
                     .await?;
 
@@ -131,6 +155,10 @@ impl GameGenerationExt for ConversationManager {
         F: Fn(GenerationProgress) + Send + 'static,
     {
         let project_path = create_project_directory(&config.name)?;
+        // Asset files are named through the project's registry, so slugs
+        // stay the same when a project is generated again
+        let slugs_path = project_path.join(SLUG_REGISTRY_FILE);
+        let mut slugs = SlugRegistry::load(&slugs_path)?;
 
         // Create a new conversation for generation
         let context = starters::game_generation_context(project_config.clone());
@@ -158,7 +186,7 @@ impl GameGenerationExt for ConversationManager {
             message: "Creating regions and connections...".to_string(),
         });
 
-        let world_data = generate_world(
+        let mut world_data = generate_world(
             self,
             &conversation_id,
             config,
@@ -166,8 +194,120 @@ impl GameGenerationExt for ConversationManager {
             project_config.as_ref(),
         )
         .await?;
+        NameGenerator::from_config(config).name_world(&mut world_data);
         save_world_data(&project_path, &world_data)?;
 
+        progress_callback(GenerationProgress {
+            phase: GenerationPhase::WorldGeneration,
+            step: "Designing boss battles".to_string(),
+            progress: 0.25,
+            message: "Scripting boss phases, sprites and music cues...".to_string(),
+        });
+
+        let mut bosses = generate_bosses(
+            self,
+            &conversation_id,
+            config,
+            &world_data,
+            &style_guide,
+            project_config.as_ref(),
+        )
+        .await?;
+        bosses.cross_reference(config);
+        bosses.name_assets(&mut slugs);
+        for issue in bosses.validate() {
+            tracing::warn!("Boss design issue: {issue}");
+        }
+        std::fs::write(
+            project_path.join("world").join("bosses.json"),
+            serde_json::to_string_pretty(&bosses)?,
+        )?;
+
+        let mut game_data = GameDataAssets::from_generated(
+            config,
+            &world_data,
+            encounter_difficulty(project_config.as_ref()),
+        )
+        .with_bosses(bosses);
+        if let Some(generators) = &self.generators {
+            progress_callback(GenerationProgress {
+                phase: GenerationPhase::WorldGeneration,
+                step: "Linking quests".to_string(),
+                progress: 0.26,
+                message: "Typing objectives and quest prerequisites...".to_string(),
+            });
+
+            match link_quests(config, &world_data, &game_data, generators).await {
+                Ok((graph, linked)) => {
+                    std::fs::write(
+                        project_path.join("world").join("quests.json"),
+                        serde_json::to_string_pretty(&graph)?,
+                    )?;
+                    game_data = linked;
+                }
+                Err(e) => {
+                    tracing::warn!("Quest graph not exported, keeping the quest lines: {e:#}")
+                }
+            }
+
+            progress_callback(GenerationProgress {
+                phase: GenerationPhase::WorldGeneration,
+                step: "Placing world zones".to_string(),
+                progress: 0.27,
+                message: "Connecting regions and tiling the overview...".to_string(),
+            });
+
+            match place_world(config, generators).await {
+                Ok((graph, tileset)) => game_data = game_data.with_world(graph, tileset),
+                Err(e) => tracing::warn!("World graph not exported: {e:#}"),
+            }
+        }
+        for issue in game_data.encounters.validate(&game_data.enemies) {
+            tracing::warn!("Encounter table issue: {issue}");
+        }
+
+        // Optional: postgame planning is another large request
+        if feature_enabled(project_config.as_ref(), "postgame") {
+            progress_callback(GenerationProgress {
+                phase: GenerationPhase::WorldGeneration,
+                step: "Planning postgame content".to_string(),
+                progress: 0.28,
+                message: "Remixing dungeons and designing the superboss...".to_string(),
+            });
+
+            let mut postgame =
+                generate_postgame(self, &conversation_id, config, &world_data, &game_data).await?;
+            postgame.cross_reference();
+            postgame.superboss.name_assets(&mut slugs);
+            for issue in postgame.validate(&world_data, &game_data.bosses) {
+                tracing::warn!("Postgame plan issue: {issue}");
+            }
+            std::fs::write(
+                project_path.join("world").join("postgame.json"),
+                serde_json::to_string_pretty(&postgame)?,
+            )?;
+            game_data = game_data.with_postgame(postgame);
+        }
+
+        // Optional: repainting the world map is an image edit request
+        if feature_enabled(project_config.as_ref(), "stylized_maps")
+            && let Some(generators) = &self.generators
+        {
+            progress_callback(GenerationProgress {
+                phase: GenerationPhase::WorldGeneration,
+                step: "Painting the world map".to_string(),
+                progress: 0.29,
+                message: "Repainting the world map in the art style...".to_string(),
+            });
+
+            match stylize_world_map(config, &game_data, generators).await {
+                Ok(map) => game_data = game_data.with_stylized_world(map),
+                Err(e) => tracing::warn!("Stylized world map not exported: {e:#}"),
+            }
+        }
+        game_data.write_to_project(&project_path, &mut slugs)?;
+        slugs.save(&slugs_path)?;
+
         // Phase 3: Generate AI Systems
         progress_callback(GenerationProgress {
             phase: GenerationPhase::AiSystems,
@@ -186,7 +326,13 @@ impl GameGenerationExt for ConversationManager {
                 template.render(context!())?
             };
 
-            let ai_systems = self.send_message(&conversation_id, prompt).await?;
+            let ai_systems = self
+                .send_message_with_config(
+                    &conversation_id,
+                    prompt,
+                    Some(MessageConfig::default().with_profile(&self.profiles.code)),
+                )
+                .await?;
             let ai_systems_path = project_path.join("src").join("npc_ai.rs");
             std::fs::write(&ai_systems_path, ai_systems)?;
         }
@@ -196,10 +342,13 @@ impl GameGenerationExt for ConversationManager {
             phase: GenerationPhase::AssetGeneration,
             step: "Creating game assets".to_string(),
             progress: 0.4,
-            message: "Generating sprites and tilesets...".to_string(),
+            message: "Generating sprites, tilesets and icons...".to_string(),
         });
 
-        // TODO: Implement asset generation
+        if let Some(generators) = &self.generators {
+            generate_assets(config, &game_data, generators, &project_path, &mut slugs).await?;
+            slugs.save(&slugs_path)?;
+        }
 
         // Phase 4: Generate Code
         progress_callback(GenerationProgress {
@@ -229,7 +378,9 @@ impl GameGenerationExt for ConversationManager {
             message: "Creating soundtrack...".to_string(),
         });
 
-        // TODO: Implement music generation
+        if let Some(generators) = &self.generators {
+            compose_boss_music(config, &game_data.bosses, generators, &project_path).await?;
+        }
 
         // Phase 7: Integration
         progress_callback(GenerationProgress {
@@ -328,16 +479,20 @@ async fn generate_style_guide(
         } else {
             template.render(context!(config => config))?
         };
+        let prompt = config.localize_prompt(prompt);
 
         let response = manager
             .send_message_with_config(
                 conversation_id,
                 prompt,
-                Some(MessageConfig {
-                    model: "gpt-4-turbo".to_string(),
-                    temperature: 0.3,
-                    max_tokens: 3000,
-                }),
+                Some(
+                    MessageConfig {
+                        model: "gpt-4-turbo".to_string(),
+                        max_tokens: 3000,
+                        ..Default::default()
+                    }
+                    .with_profile(&manager.profiles.narrative),
+                ),
             )
             .await?;
 
@@ -369,16 +524,20 @@ async fn generate_world(
                 style_guide => style_guide
             ))?
         };
+        let prompt = config.localize_prompt(prompt);
 
         let response = manager
             .send_message_with_config(
                 conversation_id,
                 prompt,
-                Some(MessageConfig {
-                    model: "gpt-4-turbo".to_string(),
-                    temperature: 0.5,
-                    max_tokens: 4000,
-                }),
+                Some(
+                    MessageConfig {
+                        model: "gpt-4-turbo".to_string(),
+                        max_tokens: 4000,
+                        ..Default::default()
+                    }
+                    .with_profile(&manager.profiles.json),
+                ),
             )
             .await?;
 
@@ -392,6 +551,289 @@ async fn generate_world(
     Err(anyhow::anyhow!("World generation template not found"))
 }
 
+async fn generate_bosses(
+    manager: &ConversationManager,
+    conversation_id: &str,
+    config: &GameConfig,
+    world: &WorldData,
+    style_guide: &str,
+    project_config: Option<&serde_json::Value>,
+) -> Result<BossRoster> {
+    if let Some(env) = manager.template_env.lock().await.as_ref()
+        && let Ok(template) = env.get_template("03_bosses")
+    {
+        let prompt = if let Some(project) = project_config {
+            template.render(context!(
+                project => project,
+                config => config,
+                world => world,
+                style_guide => style_guide
+            ))?
+        } else {
+            template.render(context!(
+                config => config,
+                world => world,
+                style_guide => style_guide
+            ))?
+        };
+        let prompt = config.localize_prompt(prompt);
+
+        let response = manager
+            .send_message_with_config(
+                conversation_id,
+                prompt,
+                Some(
+                    MessageConfig {
+                        model: "gpt-4-turbo".to_string(),
+                        max_tokens: 4000,
+                        ..Default::default()
+                    }
+                    .with_profile(&manager.profiles.json),
+                ),
+            )
+            .await?;
+
+        let bosses: Vec<BossDesign> = serde_json::from_str(&response)
+            .map_err(|e| anyhow::anyhow!("Failed to parse boss designs: {e}"))?;
+
+        return Ok(BossRoster { bosses });
+    }
+
+    Err(anyhow::anyhow!("Boss design template not found"))
+}
+
+/// Type the quest lines as a quest graph and the game data with its quests,
+/// failing if the graph doesn't validate against the world
+async fn link_quests(
+    config: &GameConfig,
+    world: &WorldData,
+    game_data: &GameDataAssets,
+    generators: &AssetGenerators,
+) -> Result<(QuestGraph, GameDataAssets)> {
+    let graph = generators.quests.generate(config, world).await?;
+    let game_data = game_data.clone().with_quests(world, &graph)?;
+    Ok((graph, game_data))
+}
+
+/// Place the regions as a zone graph and generate the tiles of its
+/// overview in the project style
+async fn place_world(
+    config: &GameConfig,
+    generators: &AssetGenerators,
+) -> Result<(WorldGraph, BiomeTileset)> {
+    let graph = WorldGenerator::new(generators.text.clone())
+        .generate(config)
+        .await?;
+    let tileset = BiomeTileset::generate(&generators.image, &graph, OVERVIEW_TILE_SIZE).await?;
+    Ok((graph, tileset))
+}
+
+/// Generate a sprite sheet for every character, a battle sprite for every
+/// boss, a tileset for every biome and the achievement icons, and write them
+/// as indexed PNGs in the style palette with the sheets' Aseprite and
+/// TexturePacker JSON, and each tileset's time-of-day variants
+async fn generate_assets(
+    config: &GameConfig,
+    game_data: &GameDataAssets,
+    generators: &AssetGenerators,
+    project_path: &Path,
+    slugs: &mut SlugRegistry,
+) -> Result<()> {
+    let style = generators.image.style().await;
+    let palette = generators.image.indexed_palette().await?;
+    let mut colors = Vec::new();
+    colors.extend(&style.palette.primary_colors);
+    colors.extend(&style.palette.secondary_colors);
+    colors.extend(&style.palette.accent_colors);
+    let animation_frames = style.sprite_specs.animation_frames;
+    let mut animations: Vec<String> = animation_frames.keys().cloned().collect();
+    animations.sort();
+
+    let sprite_dir = project_path.join("assets").join(SPRITE_ASSET_DIR);
+    std::fs::create_dir_all(&sprite_dir)?;
+    let mut scales = ScaleManifest::default();
+    for character in &config.characters {
+        let sheet = generate_character_sheet(
+            &generators.image,
+            &character.name,
+            &character.role,
+            animations.clone(),
+        )
+        .await?;
+        scales.assets.push(write_sheet(
+            &slugs.slug(AssetCategory::Sprite, &character.name),
+            &sheet,
+            &animation_frames,
+            &palette,
+            &sprite_dir,
+        )?);
+    }
+    for boss in &game_data.bosses.bosses {
+        let size = boss.sprite.size(config.art_style.sprite_size);
+        let generated = generators
+            .image
+            .generate_sprite("boss", &boss.sprite.description, None)
+            .await?;
+        let sprite = generators
+            .image
+            .downscale_to_sprite(&generated, (size, size), DownscaleMethod::default())
+            .await?;
+        scales.assets.push(scaling::write_variants(
+            &boss.sprite.sprite_id,
+            &image::load_from_memory(&sprite)?,
+            &palette,
+            &sprite_dir,
+        )?);
+    }
+    scales.write(&sprite_dir)?;
+
+    let mut biomes: Vec<&str> = config
+        .world
+        .regions
+        .iter()
+        .map(|region| region.biome.as_str())
+        .collect();
+    biomes.sort_unstable();
+    biomes.dedup();
+
+    let tileset_dir = project_path.join("assets").join(TILESET_ASSET_DIR);
+    std::fs::create_dir_all(&tileset_dir)?;
+    let mut scales = ScaleManifest::default();
+    for biome in biomes {
+        let tile_types = BIOME_TILES.iter().map(ToString::to_string).collect();
+        let sheet = generate_tileset(&generators.image, biome, tile_types).await?;
+        let id = slugs.slug(AssetCategory::Tileset, biome);
+        let asset = write_sheet(&id, &sheet, &HashMap::new(), &palette, &tileset_dir)?;
+        palette_variants::write_variants(
+            &id,
+            &variant_file(&id, 1),
+            &sheet.image,
+            &colors,
+            &tileset_dir,
+        )?;
+        scales.assets.push(asset);
+    }
+    scales.write(&tileset_dir)?;
+
+    let icons = game_data
+        .achievements
+        .generate_icons(&generators.image)
+        .await?;
+    write_icons(
+        &icons,
+        &palette,
+        &project_path.join("assets").join(ACHIEVEMENT_ASSET_DIR),
+    )
+}
+
+/// Compose the music cue of every boss in the project's music style and
+/// write the descriptions as JSON, named by the cues' track slugs
+async fn compose_boss_music(
+    config: &GameConfig,
+    bosses: &BossRoster,
+    generators: &AssetGenerators,
+    project_path: &Path,
+) -> Result<()> {
+    let music_dir = project_path.join("assets").join(MUSIC_ASSET_DIR);
+    std::fs::create_dir_all(&music_dir)?;
+    for boss in &bosses.bosses {
+        let description = generators
+            .audio
+            .generate_music_description("battle", boss.music.audio_config(&config.music_style))
+            .await?;
+        std::fs::write(
+            music_dir.join(format!("{}.json", boss.music.track)),
+            serde_json::to_string_pretty(&description)?,
+        )?;
+    }
+    Ok(())
+}
+
+/// The project's `gameplay.encounter_difficulty`, the default preset when
+/// it is unset or unknown
+fn encounter_difficulty(project_config: Option<&serde_json::Value>) -> DifficultyPreset {
+    project_config
+        .and_then(|project| project.pointer("/gameplay/encounter_difficulty"))
+        .and_then(|preset| serde_json::from_value(preset.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Whether the project enabled an optional phase with `features.{feature}`
+fn feature_enabled(project_config: Option<&serde_json::Value>, feature: &str) -> bool {
+    project_config
+        .and_then(|project| project.pointer(&format!("/features/{feature}")))
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// The rendered world map repainted in the art style
+async fn stylize_world_map(
+    config: &GameConfig,
+    game_data: &GameDataAssets,
+    generators: &AssetGenerators,
+) -> Result<DynamicImage> {
+    let mut map = Vec::new();
+    game_data
+        .map
+        .render_world(&game_data.palette)
+        .write_to(&mut std::io::Cursor::new(&mut map), image::ImageFormat::Png)?;
+    let regions: Vec<String> = config
+        .world
+        .regions
+        .iter()
+        .map(|region| format!("{} ({})", region.name, region.biome))
+        .collect();
+    let description = format!(
+        "The world of {}, a {} game; regions: {}.",
+        config.world.name,
+        config.genre,
+        regions.join(", ")
+    );
+    let stylized = generators.image.stylize_map(&map, &description).await?;
+    Ok(image::load_from_memory(&stylized)?)
+}
+
+async fn generate_postgame(
+    manager: &ConversationManager,
+    conversation_id: &str,
+    config: &GameConfig,
+    world: &WorldData,
+    game_data: &GameDataAssets,
+) -> Result<PostgamePlan> {
+    if let Some(env) = manager.template_env.lock().await.as_ref()
+        && let Ok(template) = env.get_template("03_postgame")
+    {
+        let prompt = template.render(context!(
+            config => config,
+            world => world,
+            bosses => game_data.bosses,
+            encounters => game_data.encounters,
+            superboss_hp_ratio => SUPERBOSS_MIN_HP_RATIO
+        ))?;
+        let prompt = config.localize_prompt(prompt);
+
+        let response = manager
+            .send_message_with_config(
+                conversation_id,
+                prompt,
+                Some(
+                    MessageConfig {
+                        model: "gpt-4-turbo".to_string(),
+                        max_tokens: 4000,
+                        ..Default::default()
+                    }
+                    .with_profile(&manager.profiles.json),
+                ),
+            )
+            .await?;
+
+        return serde_json::from_str(&response)
+            .map_err(|e| anyhow::anyhow!("Failed to parse postgame plan: {e}"));
+    }
+
+    Err(anyhow::anyhow!("Postgame template not found"))
+}
+
 fn save_world_data(project_path: &Path, world_data: &WorldData) -> Result<()> {
     let world_dir = project_path.join("world");
     std::fs::create_dir_all(&world_dir)?;
@@ -3,11 +3,10 @@
 use anyhow::{Context, Result};
 use async_openai::{
     Client,
-    config::OpenAIConfig,
     types::chat::{
         ChatCompletionRequestAssistantMessageArgs, ChatCompletionRequestMessage,
         ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs,
-        CreateChatCompletionRequestArgs,
+        CreateChatCompletionRequestArgs, CreateChatCompletionResponse,
     },
 };
 use chrono::Utc;
@@ -19,32 +18,61 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-use crate::{AiGenerator, tokens::TokenCounter};
+use crate::credentials::{KeyedConfig, retry_keys};
+use crate::quota;
+use crate::telemetry::RequestTimer;
+use crate::{AiGenerator, ParameterProfiles, tokens::TokenCounter};
 
 use super::types::*;
 
 /// Manages ongoing conversations with context
 #[derive(Clone)]
 pub struct ConversationManager {
-    pub(crate) client: Arc<Client<OpenAIConfig>>,
+    pub(crate) client: Arc<Client<KeyedConfig>>,
     pub(crate) token_counter: Arc<Mutex<TokenCounter>>,
     pub(crate) conversations: Arc<Mutex<HashMap<String, Conversation>>>,
     pub(crate) template_env: Arc<Mutex<Option<Environment<'static>>>>,
     pub(crate) templates_dir: Option<PathBuf>,
+    pub(crate) summarization: SummarizationConfig,
+    pub(crate) profiles: ParameterProfiles,
+    /// Generators of the service the manager belongs to, if any
+    pub(crate) generators: Option<AssetGenerators>,
 }
 
 impl ConversationManager {
     /// Create a new conversation manager
-    pub fn new(client: Arc<Client<OpenAIConfig>>, token_counter: Arc<Mutex<TokenCounter>>) -> Self {
+    pub fn new(client: Arc<Client<KeyedConfig>>, token_counter: Arc<Mutex<TokenCounter>>) -> Self {
         Self {
             client,
             token_counter,
             conversations: Arc::new(Mutex::new(HashMap::new())),
             template_env: Arc::new(Mutex::new(None)),
             templates_dir: None,
+            summarization: SummarizationConfig::default(),
+            profiles: ParameterProfiles::default(),
+            generators: None,
         }
     }
 
+    /// Configure automatic summarization of long conversations
+    pub fn with_summarization(mut self, config: SummarizationConfig) -> Self {
+        self.summarization = config;
+        self
+    }
+
+    /// Configure the per-phase sampling parameters used by game generation
+    pub fn with_profiles(mut self, profiles: ParameterProfiles) -> Self {
+        self.profiles = profiles;
+        self
+    }
+
+    /// Let game generation request sprites, music and structured text
+    /// besides the conversation
+    pub fn with_generators(mut self, generators: AssetGenerators) -> Self {
+        self.generators = Some(generators);
+        self
+    }
+
     /// Initialize template environment for game generation
     pub async fn init_templates(&mut self, templates_dir: PathBuf) -> Result<()> {
         let mut env = Environment::new();
@@ -83,12 +111,11 @@ impl ConversationManager {
 
         // Add system message if provided
         if let Some(system_prompt) = &context.system_prompt {
-            messages.push_back(ConversationMessage {
-                role: MessageRole::System,
-                content: system_prompt.clone(),
-                timestamp: now,
-                tokens: self.estimate_tokens(system_prompt).await?,
-            });
+            messages.push_back(ConversationMessage::new(
+                MessageRole::System,
+                system_prompt.clone(),
+                self.estimate_tokens(system_prompt).await?,
+            ));
         }
 
         let conversation = Conversation {
@@ -99,6 +126,8 @@ impl ConversationManager {
             created_at: now,
             updated_at: now,
             total_tokens: 0,
+            summary: None,
+            branch: None,
         };
 
         self.conversations
@@ -139,32 +168,26 @@ impl ConversationManager {
 
         // Add user message
         let user_tokens = self.estimate_tokens(&message).await?;
-        conversation.messages.push_back(ConversationMessage {
-            role: MessageRole::User,
-            content: message.clone(),
-            timestamp: Utc::now(),
-            tokens: user_tokens,
-        });
+        conversation.messages.push_back(ConversationMessage::new(
+            MessageRole::User,
+            message.clone(),
+            user_tokens,
+        ));
 
         // Prepare messages for API
         let api_messages = self.prepare_api_messages(conversation)?;
 
         // Create request with optional custom config
         let config = config.unwrap_or_default();
-        let request = CreateChatCompletionRequestArgs::default()
-            .model(config.model.as_str())
-            .messages(api_messages)
-            .temperature(config.temperature)
-            .max_tokens(config.max_tokens)
-            .build()?;
+        let request = chat_request(&config.model, &config, api_messages).build()?;
 
         // Make API call
-        let response = self
-            .client
-            .chat()
-            .create(request)
-            .await
-            .context("Failed to get conversation response")?;
+        let timer = RequestTimer::start("conversation");
+        let response: CreateChatCompletionResponse = retry_keys(&self.client, || {
+            quota::send(&self.client, "/chat/completions", &request)
+        })
+        .await
+        .context("Failed to get conversation response")?;
 
         // Extract response
         let assistant_message = response
@@ -175,8 +198,11 @@ impl ConversationManager {
 
         // Track tokens
         let model_name = config.model.as_str();
+        let (mut tokens, mut cost) = (0, 0.0);
         if let Some(usage) = response.usage {
-            self.token_counter
+            tokens = usage.total_tokens as usize;
+            cost = self
+                .token_counter
                 .lock()
                 .await
                 .record_usage(
@@ -186,19 +212,20 @@ impl ConversationManager {
                 )
                 .await?;
 
-            conversation.total_tokens += usage.total_tokens as usize;
+            conversation.total_tokens += tokens;
         }
+        timer.finish(self.client.config().provider(), tokens, cost, false);
 
         // Add assistant message
         let assistant_tokens = self.estimate_tokens(&assistant_message).await?;
-        conversation.messages.push_back(ConversationMessage {
-            role: MessageRole::Assistant,
-            content: assistant_message.clone(),
-            timestamp: Utc::now(),
-            tokens: assistant_tokens,
-        });
-
-        // Trim context if needed
+        conversation.messages.push_back(ConversationMessage::new(
+            MessageRole::Assistant,
+            assistant_message.clone(),
+            assistant_tokens,
+        ));
+
+        // Compact older turns into a digest, then trim context if needed
+        self.compact_context(conversation).await;
         self.trim_context(conversation);
 
         conversation.updated_at = Utc::now();
@@ -220,32 +247,37 @@ impl ConversationManager {
 
         // Add user message
         let user_tokens = self.estimate_tokens(&message).await?;
-        conversation.messages.push_back(ConversationMessage {
-            role: MessageRole::User,
-            content: message.clone(),
-            timestamp: Utc::now(),
-            tokens: user_tokens,
-        });
+        conversation.messages.push_back(ConversationMessage::new(
+            MessageRole::User,
+            message.clone(),
+            user_tokens,
+        ));
 
         // Prepare messages for API
         let api_messages = self.prepare_api_messages(conversation)?;
 
         // Create request with optional custom config
         let config = config.unwrap_or_default();
-        let request = CreateChatCompletionRequestArgs::default()
-            .model(config.model.as_str())
-            .messages(api_messages)
-            .temperature(config.temperature)
-            .max_tokens(config.max_tokens)
-            .stream(true)
-            .build()?;
-
-        let mut stream = self.client.chat().create_stream(request).await?;
+        let request = chat_request(
+            &self.client.config().model_name(&config.model),
+            &config,
+            api_messages,
+        )
+        .stream(true)
+        .build()?;
+
+        let timer = RequestTimer::start("conversation");
+        let client = Client::with_config(self.client.config().for_model(&config.model));
+        quota::pace(&request).await;
+        let mut stream = retry_keys(&client, || async {
+            client.chat().create_stream(request.clone()).await
+        })
+        .await?;
 
         let conversation_id = conversation_id.to_string();
         let conversations_arc = self.conversations.clone();
-        let _token_counter = self.token_counter.clone();
-        let _model_name = config.model.clone();
+        let token_counter = self.token_counter.clone();
+        let model_name = config.model.clone();
 
         Ok(async_stream::try_stream! {
             let mut full_response = String::new();
@@ -268,35 +300,22 @@ impl ConversationManager {
             // After stream completes, update the conversation history
             let mut convs = conversations_arc.lock().await;
             if let Some(conv) = convs.get_mut(&conversation_id) {
-                // Approximate token count (4 chars = 1 token)
-                let assistant_tokens = full_response.len() / 4;
-
-                conv.messages.push_back(ConversationMessage {
-                    role: MessageRole::Assistant,
-                    content: full_response,
-                    timestamp: Utc::now(),
-                    tokens: assistant_tokens,
-                });
-
-                // Trim context
-                let max_messages = conv.context.max_context_messages;
-                let system_count = conv.messages.iter().filter(|m| matches!(m.role, MessageRole::System)).count();
-                let max_non_system = max_messages.saturating_sub(system_count);
-                let non_system_count = conv.messages.iter().filter(|m| !matches!(m.role, MessageRole::System)).count();
-
-                if non_system_count > max_non_system {
-                    let to_remove = non_system_count - max_non_system;
-                    let mut removed = 0;
-                    while removed < to_remove {
-                        for i in 0..conv.messages.len() {
-                            if !matches!(conv.messages[i].role, MessageRole::System) {
-                                conv.messages.remove(i);
-                                removed += 1;
-                                break;
-                            }
-                        }
-                    }
-                }
+                let assistant_tokens = token_counter
+                    .lock()
+                    .await
+                    .count_tokens(&full_response, &model_name)
+                    .unwrap_or_default();
+                // Streams report no usage, so only the reply is counted
+                timer.finish(client.config().provider(), assistant_tokens, 0.0, false);
+
+                conv.messages.push_back(ConversationMessage::new(
+                    MessageRole::Assistant,
+                    full_response,
+                    assistant_tokens,
+                ));
+
+                self.compact_context(conv).await;
+                self.trim_context(conv);
 
                 conv.updated_at = Utc::now();
             }
@@ -338,13 +357,123 @@ impl ConversationManager {
                 updated_at: conv.updated_at,
                 message_count: conv.messages.len(),
                 total_tokens: conv.total_tokens,
+                summary: conv.summary.clone(),
+                parent_id: conv.branch.as_ref().map(|b| b.parent_id.clone()),
             })
             .collect();
 
-        summaries.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        summaries.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
         Ok(summaries)
     }
 
+    /// Fork a conversation into a new branch.
+    ///
+    /// `message_id` is the id of the last message to keep; the system
+    /// prompt, context, summary digest and any later system messages are
+    /// always carried over. Messages already compacted into the digest or
+    /// trimmed can't be forked at. Returns the id of the new branch.
+    pub async fn fork(&self, conversation_id: &str, message_id: &str) -> Result<String> {
+        let mut conversations = self.conversations.lock().await;
+        let parent = conversations
+            .get(conversation_id)
+            .ok_or_else(|| anyhow::anyhow!("Conversation not found"))?;
+
+        let cutoff = parent
+            .messages
+            .iter()
+            .position(|m| m.id == message_id)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Cannot fork at message {message_id}: it is no longer in the conversation"
+                )
+            })?;
+
+        let messages: VecDeque<ConversationMessage> = parent
+            .messages
+            .iter()
+            .enumerate()
+            .filter(|(index, m)| *index <= cutoff || matches!(m.role, MessageRole::System))
+            .map(|(_, m)| m.clone())
+            .collect();
+        let forked_at = messages
+            .iter()
+            .filter(|m| !matches!(m.role, MessageRole::System))
+            .count();
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = Utc::now();
+        let branch = Conversation {
+            id: id.clone(),
+            title: format!("{} (branch)", parent.title),
+            messages,
+            context: parent.context.clone(),
+            created_at: now,
+            updated_at: now,
+            total_tokens: 0,
+            summary: parent.summary.clone(),
+            branch: Some(ConversationBranch {
+                parent_id: parent.id.clone(),
+                forked_at,
+                forked_from: message_id.to_string(),
+            }),
+        };
+
+        conversations.insert(id.clone(), branch);
+        Ok(id)
+    }
+
+    /// Compare two conversations turn by turn
+    pub async fn compare(&self, left_id: &str, right_id: &str) -> Result<BranchComparison> {
+        let conversations = self.conversations.lock().await;
+        let turns = |id: &str| -> Result<Vec<ConversationMessage>> {
+            let conversation = conversations
+                .get(id)
+                .ok_or_else(|| anyhow::anyhow!("Conversation not found: {id}"))?;
+            Ok(conversation
+                .messages
+                .iter()
+                .filter(|m| !matches!(m.role, MessageRole::System))
+                .cloned()
+                .collect())
+        };
+
+        let left = turns(left_id)?;
+        let right = turns(right_id)?;
+
+        let shared_turns = left
+            .iter()
+            .zip(&right)
+            .take_while(|(l, r)| {
+                std::mem::discriminant(&l.role) == std::mem::discriminant(&r.role)
+                    && l.content == r.content
+            })
+            .count();
+
+        Ok(BranchComparison {
+            shared_turns,
+            left_only: left[shared_turns..].to_vec(),
+            right_only: right[shared_turns..].to_vec(),
+        })
+    }
+
+    /// Merge the turns unique to `source_id` into `target_id`.
+    ///
+    /// The divergent turns are appended to the target in order so the next
+    /// exchange sees both design directions; the source branch is left intact.
+    pub async fn merge(&self, target_id: &str, source_id: &str) -> Result<()> {
+        let comparison = self.compare(target_id, source_id).await?;
+
+        let mut conversations = self.conversations.lock().await;
+        let target = conversations
+            .get_mut(target_id)
+            .ok_or_else(|| anyhow::anyhow!("Conversation not found"))?;
+
+        target.messages.extend(comparison.right_only);
+        self.trim_context(target);
+        target.updated_at = Utc::now();
+        Ok(())
+    }
+
     /// Delete a conversation
     pub async fn delete_conversation(&self, conversation_id: &str) -> Result<()> {
         self.conversations.lock().await.remove(conversation_id);
@@ -372,6 +501,16 @@ impl ConversationManager {
             );
         }
 
+        // Add digest of compacted turns
+        if let Some(summary) = &conversation.summary {
+            messages.push(
+                ChatCompletionRequestSystemMessageArgs::default()
+                    .content(format!("Summary of the earlier conversation:\n{summary}").as_str())
+                    .build()?
+                    .into(),
+            );
+        }
+
         // Add context summary if needed
         if let Some(game_context) = &conversation.context.game_concept {
             let context_summary = format!(
@@ -427,6 +566,142 @@ impl ConversationManager {
         Ok(messages)
     }
 
+    /// Summarize the conversation now, regardless of the token threshold
+    pub async fn summarize_conversation(&self, conversation_id: &str) -> Result<()> {
+        let mut conversations = self.conversations.lock().await;
+        let conversation = conversations
+            .get_mut(conversation_id)
+            .ok_or_else(|| anyhow::anyhow!("Conversation not found"))?;
+
+        self.summarize_older_turns(conversation).await?;
+        conversation.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// Compact older turns into a digest once the token threshold is crossed.
+    ///
+    /// Failures are logged rather than propagated so that the regular
+    /// message-count trimming still keeps the conversation usable.
+    async fn compact_context(&self, conversation: &mut Conversation) {
+        if !self.summarization.enabled {
+            return;
+        }
+
+        let turns: Vec<(&str, &str)> = conversation
+            .messages
+            .iter()
+            .filter(|m| !matches!(m.role, MessageRole::System))
+            .map(|m| (m.role.as_str(), m.content.as_str()))
+            .collect();
+        let message_tokens = self
+            .token_counter
+            .lock()
+            .await
+            .count_chat_tokens(&turns, &MessageConfig::default().model)
+            .unwrap_or_default();
+
+        if message_tokens < self.summarization.token_threshold {
+            return;
+        }
+
+        if let Err(e) = self.summarize_older_turns(conversation).await {
+            tracing::warn!(
+                "Failed to summarize conversation {}: {}",
+                conversation.id,
+                e
+            );
+        }
+    }
+
+    /// Fold all but the most recent turns into the conversation digest
+    async fn summarize_older_turns(&self, conversation: &mut Conversation) -> Result<()> {
+        let non_system: Vec<usize> = conversation
+            .messages
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| !matches!(m.role, MessageRole::System))
+            .map(|(i, _)| i)
+            .collect();
+
+        let keep = self.summarization.keep_recent_messages;
+        if non_system.len() <= keep {
+            return Ok(());
+        }
+        let older = &non_system[..non_system.len() - keep];
+
+        let mut transcript = String::new();
+        if let Some(previous) = &conversation.summary {
+            transcript.push_str(&format!("Previous summary:\n{previous}\n\n"));
+        }
+        for &i in older {
+            let msg = &conversation.messages[i];
+            let speaker = match msg.role {
+                MessageRole::User => "User",
+                MessageRole::Assistant => "Assistant",
+                MessageRole::System => "System",
+            };
+            transcript.push_str(&format!("{speaker}: {}\n\n", msg.content));
+        }
+
+        let request = CreateChatCompletionRequestArgs::default()
+            .model(self.summarization.model.as_str())
+            .messages(vec![
+                ChatCompletionRequestSystemMessageArgs::default()
+                    .content(
+                        "Summarize the following game design conversation into a concise digest. \
+                         Preserve every decision, constraint, name and open question; drop small talk.",
+                    )
+                    .build()?
+                    .into(),
+                ChatCompletionRequestUserMessageArgs::default()
+                    .content(transcript.as_str())
+                    .build()?
+                    .into(),
+            ])
+            .temperature(0.2)
+            .max_tokens(self.summarization.max_summary_tokens)
+            .build()?;
+
+        let timer = RequestTimer::start("conversation");
+        let response: CreateChatCompletionResponse = retry_keys(&self.client, || {
+            quota::send(&self.client, "/chat/completions", &request)
+        })
+        .await
+        .context("Failed to summarize conversation")?;
+
+        let (mut tokens, mut cost) = (0, 0.0);
+        if let Some(usage) = &response.usage {
+            tokens = usage.total_tokens as usize;
+            cost = self
+                .token_counter
+                .lock()
+                .await
+                .record_usage(
+                    &self.summarization.model,
+                    usage.prompt_tokens as usize,
+                    usage.completion_tokens as usize,
+                )
+                .await?;
+            conversation.total_tokens += tokens;
+        }
+        timer.finish(self.client.config().provider(), tokens, cost, false);
+
+        let summary = response
+            .choices
+            .first()
+            .and_then(|choice| choice.message.content.clone())
+            .filter(|content| !content.trim().is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Summarization returned no content"))?;
+
+        // Drop the summarized turns, oldest last so indices stay valid
+        for &i in older.iter().rev() {
+            conversation.messages.remove(i);
+        }
+        conversation.summary = Some(summary);
+
+        Ok(())
+    }
+
     /// Trim conversation context to stay within limits
     fn trim_context(&self, conversation: &mut Conversation) {
         let max_messages = conversation.context.max_context_messages;
@@ -474,8 +749,9 @@ impl AiGenerator for ConversationManager {
     }
 
     async fn estimate_cost(&self, request: &str) -> Result<f64> {
+        let config = MessageConfig::default();
         let counter = self.token_counter.lock().await;
-        counter.estimate_cost(request, "gpt-4-turbo", 2000)
+        counter.estimate_cost(&config.model, request, config.max_tokens as usize)
     }
 
     async fn is_cached(&self, _key: &str) -> bool {
@@ -488,3 +764,228 @@ impl AiGenerator for ConversationManager {
         Ok(())
     }
 }
+
+/// A chat request to `model` with the sampling parameters of `config`
+fn chat_request(
+    model: &str,
+    config: &MessageConfig,
+    messages: Vec<ChatCompletionRequestMessage>,
+) -> CreateChatCompletionRequestArgs {
+    let mut request = CreateChatCompletionRequestArgs::default();
+    request
+        .model(model)
+        .messages(messages)
+        .temperature(config.temperature)
+        .top_p(config.top_p)
+        .frequency_penalty(config.frequency_penalty)
+        .presence_penalty(config.presence_penalty)
+        .max_tokens(config.max_tokens);
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AiService;
+    use crate::conversation::game_design_context;
+
+    /// Manager holding a conversation of `turns` user/assistant turns
+    async fn conversation(turns: usize) -> (ConversationManager, String) {
+        let manager = AiService::offline().unwrap().conversation();
+        let id = manager
+            .start_conversation("Design".to_string(), game_design_context())
+            .await
+            .unwrap();
+        let mut conversations = manager.conversations.lock().await;
+        let conversation = conversations.get_mut(&id).unwrap();
+        for turn in 0..turns {
+            let role = if turn % 2 == 0 {
+                MessageRole::User
+            } else {
+                MessageRole::Assistant
+            };
+            conversation.messages.push_back(ConversationMessage::new(
+                role,
+                format!("turn {turn}"),
+                2,
+            ));
+        }
+        drop(conversations);
+        (manager, id)
+    }
+
+    async fn contents(manager: &ConversationManager, id: &str) -> Vec<String> {
+        let conversation = manager.get_conversation(id).await.unwrap();
+        conversation
+            .messages
+            .iter()
+            .map(|m| m.content.clone())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn fork_keeps_messages_up_to_the_id() {
+        let (manager, id) = conversation(4).await;
+        let parent = manager.get_conversation(&id).await.unwrap();
+        let cutoff = parent.messages[2].id.clone();
+
+        let branch = manager.fork(&id, &cutoff).await.unwrap();
+        let forked = manager.get_conversation(&branch).await.unwrap();
+        assert_eq!(forked.messages.len(), 3);
+        assert_eq!(forked.messages[2].content, "turn 1");
+        let origin = forked.branch.unwrap();
+        assert_eq!((origin.parent_id, origin.forked_at), (id, 2));
+        assert_eq!(origin.forked_from, cutoff);
+    }
+
+    #[tokio::test]
+    async fn fork_after_trim_finds_the_same_message() {
+        let (manager, id) = conversation(8).await;
+        let cutoff = {
+            let mut conversations = manager.conversations.lock().await;
+            let conversation = conversations.get_mut(&id).unwrap();
+            let cutoff = conversation.messages[6].id.clone();
+            conversation.context.max_context_messages = 5;
+            manager.trim_context(conversation);
+            conversation.messages.push_back(ConversationMessage::new(
+                MessageRole::System,
+                "Merged decisions".to_string(),
+                2,
+            ));
+            cutoff
+        };
+        assert_eq!(
+            contents(&manager, &id).await[1..],
+            ["turn 4", "turn 5", "turn 6", "turn 7", "Merged decisions"]
+        );
+
+        let branch = manager.fork(&id, &cutoff).await.unwrap();
+        let forked = contents(&manager, &branch).await;
+        assert_eq!(forked[1..], ["turn 4", "turn 5", "Merged decisions"]);
+    }
+
+    #[tokio::test]
+    async fn fork_at_a_trimmed_message_fails() {
+        let (manager, id) = conversation(8).await;
+        let trimmed = {
+            let mut conversations = manager.conversations.lock().await;
+            let conversation = conversations.get_mut(&id).unwrap();
+            let trimmed = conversation.messages[1].id.clone();
+            conversation.context.max_context_messages = 5;
+            manager.trim_context(conversation);
+            trimmed
+        };
+        assert!(manager.fork(&id, &trimmed).await.is_err());
+    }
+
+    /// Manager summarizing past 10 tokens, keeping the last two messages
+    fn compacting(manager: ConversationManager) -> ConversationManager {
+        manager.with_summarization(SummarizationConfig {
+            token_threshold: 10,
+            keep_recent_messages: 2,
+            ..Default::default()
+        })
+    }
+
+    async fn compact(manager: &ConversationManager, id: &str) -> Conversation {
+        let mut conversation = manager.get_conversation(id).await.unwrap();
+        manager.compact_context(&mut conversation).await;
+        conversation
+    }
+
+    #[tokio::test]
+    async fn crossing_the_threshold_summarizes_older_turns() {
+        let (manager, id) = conversation(8).await;
+        let manager = compacting(manager);
+        let before = manager.get_conversation(&id).await.unwrap();
+        let system = before.messages.len() - 8;
+
+        let compacted = compact(&manager, &id).await;
+        assert!(
+            compacted
+                .summary
+                .as_ref()
+                .is_some_and(|s| !s.trim().is_empty())
+        );
+        let kept: Vec<_> = compacted
+            .messages
+            .iter()
+            .map(|m| m.content.as_str())
+            .collect();
+        assert_eq!(kept.len(), system + 2);
+        assert_eq!(kept[system..], ["turn 6", "turn 7"]);
+
+        // One digest message stands in for the older turns in the next
+        // request
+        let request =
+            serde_json::to_string(&manager.prepare_api_messages(&compacted).unwrap()).unwrap();
+        assert_eq!(
+            request
+                .matches("Summary of the earlier conversation")
+                .count(),
+            1
+        );
+        assert!(!request.contains("turn 5") && request.contains("turn 6"));
+    }
+
+    #[tokio::test]
+    async fn short_conversations_are_not_summarized() {
+        let (manager, id) = conversation(2).await;
+        let manager = manager.with_summarization(SummarizationConfig {
+            token_threshold: 10_000,
+            ..Default::default()
+        });
+        let before = manager.get_conversation(&id).await.unwrap();
+
+        let compacted = compact(&manager, &id).await;
+        assert!(compacted.summary.is_none());
+        assert_eq!(compacted.messages.len(), before.messages.len());
+    }
+
+    #[test]
+    fn summaries_use_the_draft_route() {
+        let config = crate::AiConfig::default().with_route(
+            crate::routing::TaskCategory::Draft,
+            crate::routing::ModelRoute::new("gpt-4o"),
+        );
+        assert_eq!(
+            SummarizationConfig::default().model,
+            crate::routing::TaskCategory::Draft.default_route().model
+        );
+        let service = crate::AiService::with_config(&crate::AiConfig {
+            ai_provider: crate::mock::MOCK_PROVIDER.to_string(),
+            ..config
+        })
+        .unwrap();
+        assert_eq!(service.conversation().summarization.model, "gpt-4o");
+    }
+
+    #[test]
+    fn profiled_requests_carry_every_sampling_parameter() {
+        let profile = crate::ParameterProfile {
+            temperature: 0.3,
+            top_p: Some(0.9),
+            frequency_penalty: Some(0.4),
+            presence_penalty: Some(-0.2),
+        };
+        let config = MessageConfig::default().with_profile(&profile);
+        for stream in [false, true] {
+            let request = chat_request("gpt-4o", &config, Vec::new())
+                .stream(stream)
+                .build()
+                .unwrap();
+            assert_eq!(request.temperature, Some(0.3));
+            assert_eq!(request.top_p, Some(0.9));
+            assert_eq!(request.frequency_penalty, Some(0.4));
+            assert_eq!(request.presence_penalty, Some(-0.2));
+        }
+
+        // Unset parameters keep the config's own
+        let config =
+            MessageConfig::default().with_profile(&crate::ParameterProfile::with_temperature(0.0));
+        assert_eq!(
+            (config.temperature, config.top_p, config.frequency_penalty),
+            (0.0, 1.0, 0.0)
+        );
+    }
+}
@@ -18,8 +18,9 @@ pub use starters::{
     technical_assistance_context,
 };
 pub use types::{
-    BlendContext, Conversation, ConversationContext, ConversationMessage, ConversationSummary,
-    GameConceptContext, GenerationPhase, GenerationProgress, MessageConfig, MessageRole,
+    AssetGenerators, BlendContext, BranchComparison, Conversation, ConversationBranch,
+    ConversationContext, ConversationMessage, ConversationSummary, GameConceptContext,
+    GenerationPhase, GenerationProgress, MessageConfig, MessageRole, SummarizationConfig,
 };
 
 // Re-export game generation methods
@@ -1,5 +1,11 @@
 //! Type definitions for conversation management
 
+use crate::ParameterProfile;
+use crate::audio::AudioGenerator;
+use crate::image::ImageGenerator;
+use crate::quests::QuestGenerator;
+use crate::routing::TaskCategory;
+use crate::text::TextGenerator;
 use async_openai::types::chat::Role;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -15,17 +21,66 @@ pub struct Conversation {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub total_tokens: usize,
+    /// Digest of older turns that were compacted out of `messages`
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// Origin of this conversation if it was forked from another one
+    #[serde(default)]
+    pub branch: Option<ConversationBranch>,
+}
+
+/// Where a forked conversation branched off
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationBranch {
+    /// Conversation the branch was forked from
+    pub parent_id: String,
+    /// Number of user/assistant turns copied from the parent
+    pub forked_at: usize,
+    /// Id of the parent's last message the branch shares
+    #[serde(default)]
+    pub forked_from: String,
+}
+
+/// Side-by-side comparison of two conversation branches
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchComparison {
+    /// Number of leading user/assistant turns both branches share
+    pub shared_turns: usize,
+    /// Turns only present in the first branch
+    pub left_only: Vec<ConversationMessage>,
+    /// Turns only present in the second branch
+    pub right_only: Vec<ConversationMessage>,
 }
 
 /// Message in a conversation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConversationMessage {
+    /// Stable id, unchanged when older messages are compacted or trimmed
+    #[serde(default = "new_message_id")]
+    pub id: String,
     pub role: MessageRole,
     pub content: String,
     pub timestamp: DateTime<Utc>,
     pub tokens: usize,
 }
 
+impl ConversationMessage {
+    /// A message sent now
+    pub fn new(role: MessageRole, content: String, tokens: usize) -> Self {
+        Self {
+            id: new_message_id(),
+            role,
+            content,
+            timestamp: Utc::now(),
+            tokens,
+        }
+    }
+}
+
+fn new_message_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
 /// Role in conversation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MessageRole {
@@ -34,6 +89,17 @@ pub enum MessageRole {
     Assistant,
 }
 
+impl MessageRole {
+    /// Role name as sent in the chat format
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageRole::System => "system",
+            MessageRole::User => "user",
+            MessageRole::Assistant => "assistant",
+        }
+    }
+}
+
 impl From<MessageRole> for Role {
     fn from(role: MessageRole) -> Self {
         match role {
@@ -112,6 +178,38 @@ pub struct ConversationSummary {
     pub updated_at: DateTime<Utc>,
     pub message_count: usize,
     pub total_tokens: usize,
+    /// Digest of compacted turns, if the conversation has been summarized
+    pub summary: Option<String>,
+    /// Parent conversation if this one is a fork
+    pub parent_id: Option<String>,
+}
+
+/// Settings for automatic context-window compaction
+#[derive(Debug, Clone)]
+pub struct SummarizationConfig {
+    /// Whether compaction runs automatically after each exchange
+    pub enabled: bool,
+    /// Token count of non-system messages that triggers summarization
+    pub token_threshold: usize,
+    /// Number of most recent messages kept verbatim
+    pub keep_recent_messages: usize,
+    /// Model used to produce the digest, the [`TaskCategory::Draft`]
+    /// route's unless the configuration routes drafts elsewhere
+    pub model: String,
+    /// Maximum tokens for the generated digest
+    pub max_summary_tokens: u32,
+}
+
+impl Default for SummarizationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            token_threshold: 6000,
+            keep_recent_messages: 6,
+            model: TaskCategory::Draft.default_route().model,
+            max_summary_tokens: 600,
+        }
+    }
 }
 
 /// Configuration for message sending
@@ -120,6 +218,10 @@ pub struct MessageConfig {
     pub model: String,
     pub temperature: f32,
     pub max_tokens: u32,
+    /// Top-p nucleus sampling
+    pub top_p: f32,
+    pub frequency_penalty: f32,
+    pub presence_penalty: f32,
 }
 
 impl Default for MessageConfig {
@@ -128,6 +230,36 @@ impl Default for MessageConfig {
             model: "gpt-4-turbo".to_string(),
             temperature: 0.8,
             max_tokens: 2000,
+            top_p: 1.0,
+            frequency_penalty: 0.0,
+            presence_penalty: 0.0,
         }
     }
 }
+
+impl MessageConfig {
+    /// Apply the sampling parameters of a generation profile
+    pub fn with_profile(mut self, profile: &ParameterProfile) -> Self {
+        self.temperature = profile.temperature;
+        if let Some(top_p) = profile.top_p {
+            self.top_p = top_p;
+        }
+        if let Some(penalty) = profile.frequency_penalty {
+            self.frequency_penalty = penalty;
+        }
+        if let Some(penalty) = profile.presence_penalty {
+            self.presence_penalty = penalty;
+        }
+        self
+    }
+}
+
+/// Generators game generation uses for the content the conversation
+/// doesn't write itself
+#[derive(Clone)]
+pub struct AssetGenerators {
+    pub text: TextGenerator,
+    pub image: ImageGenerator,
+    pub audio: AudioGenerator,
+    pub quests: QuestGenerator,
+}
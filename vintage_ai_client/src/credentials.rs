@@ -0,0 +1,633 @@
+//! API keys of the AI providers
+//!
+//! A provider may have several keys, e.g. one per team member sharing the
+//! generator. [`Credentials`] says where each provider's keys are read
+//! from: environment variables or entries of the system keyring. Providers
+//! without any configured read `<PROVIDER>_API_KEYS` (comma-separated), then
+//! `<PROVIDER>_API_KEY`, e.g. `OPENAI_API_KEY`, or `AZURE_OPENAI_API_KEY` for
+//! Azure.
+//!
+//! The keys of a provider form a [`KeyPool`] that hands one out for every
+//! request, as the [`KeyRotation`] says. [`KeyedConfig`] puts the pool behind
+//! the client, and [`retry_keys`] repeats a request with the next key when
+//! the provider rejects the one it used.
+//!
+//! The keyring is the macOS keychain (`security`) or the Secret Service on
+//! Linux (`secret-tool`); keys are stored under [`KEYRING_SERVICE`].
+
+use crate::azure::{AZURE_PROVIDER, AzureConfig};
+use crate::gemini::{GEMINI_PROVIDER, GeminiConfig};
+use anyhow::{Context, Result, bail};
+use async_openai::Client;
+use async_openai::config::{Config, OpenAIConfig};
+use async_openai::error::OpenAIError;
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Keyring service the keys are stored under
+pub const KEYRING_SERVICE: &str = "vintage_game_generator";
+
+/// How the keys of a provider take turns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyRotation {
+    /// Use the first key until the provider rejects it, then the next
+    #[default]
+    Failover,
+    /// Use the keys in turn, spreading requests and rate limits over them
+    RoundRobin,
+}
+
+/// Where a key is read from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum KeySource {
+    /// An environment variable, or several keys in it separated by commas
+    Env { var: String },
+    /// An account of the system keyring
+    Keyring { account: String },
+}
+
+impl KeySource {
+    pub fn env(var: impl Into<String>) -> Self {
+        Self::Env { var: var.into() }
+    }
+
+    pub fn keyring(account: impl Into<String>) -> Self {
+        Self::Keyring {
+            account: account.into(),
+        }
+    }
+
+    /// Keys of the source; an unset variable has none
+    pub fn read(&self) -> Result<Vec<SecretString>> {
+        let value = match self {
+            Self::Env { var } => std::env::var(var).unwrap_or_default(),
+            Self::Keyring { account } => keyring_read(account)?,
+        };
+        Ok(value
+            .split(',')
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(|key| SecretString::from(key.to_string()))
+            .collect())
+    }
+}
+
+/// Where the keys of each provider are read from, and how they rotate
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Credentials {
+    #[serde(default)]
+    pub rotation: KeyRotation,
+    /// Key sources by provider name, e.g. "openai"
+    #[serde(default)]
+    pub providers: BTreeMap<String, Vec<KeySource>>,
+}
+
+impl Credentials {
+    pub fn with_rotation(mut self, rotation: KeyRotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Read a key of `provider` from `source`, after the ones added before
+    pub fn with_source(mut self, provider: &str, source: KeySource) -> Self {
+        self.providers
+            .entry(provider.to_lowercase())
+            .or_default()
+            .push(source);
+        self
+    }
+
+    /// Sources of `provider`, the environment if none are configured
+    pub fn sources(&self, provider: &str) -> Vec<KeySource> {
+        match self.providers.get(&provider.to_lowercase()) {
+            Some(sources) if !sources.is_empty() => sources.clone(),
+            _ => {
+                let prefix = if provider.eq_ignore_ascii_case(AZURE_PROVIDER) {
+                    "AZURE_OPENAI".to_string()
+                } else {
+                    provider.to_uppercase().replace('-', "_")
+                };
+                vec![
+                    KeySource::env(format!("{prefix}_API_KEYS")),
+                    KeySource::env(format!("{prefix}_API_KEY")),
+                ]
+            }
+        }
+    }
+
+    /// Every key of `provider`, without duplicates, in source order
+    pub fn pool(&self, provider: &str) -> Result<KeyPool> {
+        let mut keys: Vec<SecretString> = Vec::new();
+        for source in self.sources(provider) {
+            for key in source.read()? {
+                if !keys
+                    .iter()
+                    .any(|existing| existing.expose_secret() == key.expose_secret())
+                {
+                    keys.push(key);
+                }
+            }
+        }
+        Ok(KeyPool::new(keys, self.rotation))
+    }
+}
+
+/// Keys of a provider handed out in turn
+pub struct KeyPool {
+    keys: Vec<SecretString>,
+    rotation: KeyRotation,
+    /// Key handed out last
+    current: AtomicUsize,
+    /// Keys the provider rejected
+    rejected: Vec<AtomicBool>,
+}
+
+impl std::fmt::Debug for KeyPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyPool")
+            .field("keys", &self.keys.len())
+            .field("rotation", &self.rotation)
+            .field("usable", &self.usable())
+            .finish()
+    }
+}
+
+impl KeyPool {
+    pub fn new(keys: Vec<SecretString>, rotation: KeyRotation) -> Self {
+        let rejected = keys.iter().map(|_| AtomicBool::new(false)).collect();
+        Self {
+            keys,
+            rotation,
+            current: AtomicUsize::new(0),
+            rejected,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Keys the provider hasn't rejected
+    pub fn usable(&self) -> usize {
+        self.rejected
+            .iter()
+            .filter(|rejected| !rejected.load(Ordering::Relaxed))
+            .count()
+    }
+
+    /// Index of the key for the next request. Once every key was
+    /// rejected they are all tried again, in case the rejection was
+    /// temporary.
+    pub fn next_key(&self) -> Option<usize> {
+        if self.keys.is_empty() {
+            return None;
+        }
+        if self.usable() == 0 {
+            for rejected in &self.rejected {
+                rejected.store(false, Ordering::Relaxed);
+            }
+        }
+        let start = match self.rotation {
+            KeyRotation::Failover => self.current.load(Ordering::Relaxed),
+            KeyRotation::RoundRobin => self.current.fetch_add(1, Ordering::Relaxed) + 1,
+        };
+        let index = (0..self.keys.len())
+            .map(|offset| (start + offset) % self.keys.len())
+            .find(|&index| !self.rejected[index].load(Ordering::Relaxed))
+            .unwrap_or(start % self.keys.len());
+        self.current.store(index, Ordering::Relaxed);
+        Some(index)
+    }
+
+    pub fn key(&self, index: usize) -> Option<&SecretString> {
+        self.keys.get(index)
+    }
+
+    /// Mark the key at `index` as rejected. Returns whether another key is
+    /// left to try.
+    pub fn reject(&self, index: usize) -> bool {
+        if let Some(rejected) = self.rejected.get(index) {
+            rejected.store(true, Ordering::Relaxed);
+            tracing::warn!(
+                "API key {} of {} was rejected, {} left",
+                index + 1,
+                self.keys.len(),
+                self.usable()
+            );
+        }
+        self.usable() > 0
+    }
+}
+
+tokio::task_local! {
+    /// Key of the pool the requests of a [`retry_keys`] attempt use
+    static PINNED_KEY: usize;
+}
+
+/// Whether the provider rejected the key itself, rather than the request
+pub fn is_key_error(error: &OpenAIError) -> bool {
+    match error {
+        OpenAIError::ApiError(error) => {
+            error.code.as_deref() == Some("invalid_api_key")
+                || error.r#type.as_deref() == Some("insufficient_quota")
+        }
+        OpenAIError::Reqwest(error) => error.status().is_some_and(|status| status.as_u16() == 401),
+        _ => false,
+    }
+}
+
+/// Provider requests are reported under unless the configuration names one
+const DEFAULT_PROVIDER: &str = "openai";
+
+/// Client configuration that authenticates every request with a key of the
+/// pool. With an empty pool the key of the wrapped configuration is used.
+/// On Azure, requests go to the deployment of the configuration's model; on
+/// Gemini, requests name the Gemini model answering theirs.
+#[derive(Debug, Clone)]
+pub struct KeyedConfig {
+    inner: OpenAIConfig,
+    pool: Arc<KeyPool>,
+    azure: Option<Arc<AzureConfig>>,
+    gemini: Option<Arc<GeminiConfig>>,
+    /// Model of the requests, picking their Azure deployment
+    model: String,
+    /// Provider the requests are reported under
+    provider: String,
+}
+
+impl KeyedConfig {
+    pub fn new(inner: OpenAIConfig, pool: KeyPool) -> Self {
+        Self {
+            inner,
+            pool: Arc::new(pool),
+            azure: None,
+            gemini: None,
+            model: String::new(),
+            provider: DEFAULT_PROVIDER.to_string(),
+        }
+    }
+
+    /// Report the requests under `provider`
+    pub fn with_provider(mut self, provider: &str) -> Self {
+        self.provider = provider.to_string();
+        self
+    }
+
+    /// Send the requests to an Azure OpenAI resource
+    pub fn with_azure(mut self, azure: AzureConfig) -> Self {
+        self.azure = Some(Arc::new(azure));
+        self.with_provider(AZURE_PROVIDER)
+    }
+
+    /// Send the requests to Gemini's OpenAI-compatible API
+    pub fn with_gemini(mut self, gemini: GeminiConfig) -> Self {
+        self.inner = self.inner.with_api_base(&gemini.api_base);
+        self.gemini = Some(Arc::new(gemini));
+        self.with_provider(GEMINI_PROVIDER)
+    }
+
+    /// The same configuration for requests of `model`, sharing the keys
+    pub fn for_model(&self, model: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            ..self.clone()
+        }
+    }
+
+    pub fn pool(&self) -> &KeyPool {
+        &self.pool
+    }
+
+    pub fn azure(&self) -> Option<&AzureConfig> {
+        self.azure.as_deref()
+    }
+
+    pub fn gemini(&self) -> Option<&GeminiConfig> {
+        self.gemini.as_deref()
+    }
+
+    /// Provider the requests are reported under, e.g. in metrics
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    /// Name the provider knows `model` by
+    pub fn model_name(&self, model: &str) -> String {
+        match &self.gemini {
+            Some(gemini) => gemini.model(model),
+            None => model.to_string(),
+        }
+    }
+}
+
+impl Config for KeyedConfig {
+    fn headers(&self) -> HeaderMap {
+        let index = PINNED_KEY
+            .try_with(|index| *index)
+            .ok()
+            .or_else(|| self.pool.next_key());
+        let key = index
+            .and_then(|index| self.pool.key(index))
+            .unwrap_or_else(|| self.inner.api_key())
+            .expose_secret();
+        if self.azure.is_some() {
+            let mut headers = HeaderMap::new();
+            if let Ok(value) = HeaderValue::from_str(key) {
+                headers.insert("api-key", value);
+            }
+            return headers;
+        }
+
+        let mut headers = self.inner.headers();
+        if let Ok(value) = HeaderValue::from_str(&format!("Bearer {key}")) {
+            headers.insert(AUTHORIZATION, value);
+        }
+        headers
+    }
+
+    fn url(&self, path: &str) -> String {
+        match &self.azure {
+            Some(azure) => azure.url(&self.model, path),
+            None => self.inner.url(path),
+        }
+    }
+
+    fn query(&self) -> Vec<(&str, &str)> {
+        match &self.azure {
+            Some(azure) => vec![("api-version", azure.api_version.as_str())],
+            None => self.inner.query(),
+        }
+    }
+
+    fn api_base(&self) -> &str {
+        match &self.azure {
+            Some(azure) => &azure.endpoint,
+            None => self.inner.api_base(),
+        }
+    }
+
+    fn api_key(&self) -> &SecretString {
+        self.inner.api_key()
+    }
+}
+
+/// Send a request, and send it again with the next key while the provider
+/// rejects the key and another is left
+///
+/// Each attempt is pinned to the key it picked, so the key rejected is the
+/// one the attempt sent even while other requests take turns with the
+/// pool.
+pub async fn retry_keys<T, F, Fut>(
+    client: &Client<KeyedConfig>,
+    request: F,
+) -> Result<T, OpenAIError>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, OpenAIError>>,
+{
+    let pool = client.config().pool();
+    let mut attempts = 1;
+    loop {
+        let Some(index) = pool.next_key() else {
+            return request().await;
+        };
+        match PINNED_KEY.scope(index, request()).await {
+            Err(error) if is_key_error(&error) && attempts < pool.len() && pool.reject(index) => {
+                attempts += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Store a key in the system keyring under `account`, replacing the one
+/// stored there before
+///
+/// The key reaches the keyring tool over stdin, never on its command line
+/// where other users could read it from the process list.
+pub fn keyring_store(account: &str, key: &str) -> Result<()> {
+    let (mut command, input) = if cfg!(target_os = "macos") {
+        // In interactive mode `security` reads its commands from stdin
+        let mut command = Command::new("security");
+        command.arg("-i");
+        (command, security_store_command(account, key))
+    } else if cfg!(target_os = "linux") {
+        let mut command = Command::new("secret-tool");
+        command.args([
+            "store",
+            &format!("--label={KEYRING_SERVICE} ({account})"),
+            "service",
+            KEYRING_SERVICE,
+            "account",
+            account,
+        ]);
+        (command, key.to_string())
+    } else {
+        bail!("No keyring is supported on this system");
+    };
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run the keyring tool")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.as_bytes())
+            .context("Failed to pass the key to the keyring")?;
+    }
+    let output = child
+        .wait_with_output()
+        .context("Failed to run the keyring tool")?;
+    if !output.status.success() {
+        bail!(
+            "Failed to store the key of '{account}' in the keyring: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// The `security -i` command line storing `key` under `account`
+fn security_store_command(account: &str, key: &str) -> String {
+    let quote = |value: &str| format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+    format!(
+        "add-generic-password -U -s {} -a {} -w {}\n",
+        quote(KEYRING_SERVICE),
+        quote(account),
+        quote(key)
+    )
+}
+
+/// Key stored in the system keyring under `account`
+pub fn keyring_read(account: &str) -> Result<String> {
+    let output = if cfg!(target_os = "macos") {
+        Command::new("security")
+            .args([
+                "find-generic-password",
+                "-s",
+                KEYRING_SERVICE,
+                "-a",
+                account,
+                "-w",
+            ])
+            .output()
+    } else if cfg!(target_os = "linux") {
+        Command::new("secret-tool")
+            .args(["lookup", "service", KEYRING_SERVICE, "account", account])
+            .output()
+    } else {
+        bail!("No keyring is supported on this system");
+    }
+    .context("Failed to run the keyring tool")?;
+    if !output.status.success() {
+        bail!("No key of '{account}' in the keyring");
+    }
+    let key = String::from_utf8(output.stdout).context("Keyring entry is not text")?;
+    Ok(key.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_openai::error::ApiError;
+    use std::sync::Mutex;
+
+    fn pool(keys: &[&str], rotation: KeyRotation) -> KeyPool {
+        KeyPool::new(
+            keys.iter()
+                .map(|key| SecretString::from(key.to_string()))
+                .collect(),
+            rotation,
+        )
+    }
+
+    fn client(keys: &[&str], rotation: KeyRotation) -> Client<KeyedConfig> {
+        Client::with_config(KeyedConfig::new(OpenAIConfig::new(), pool(keys, rotation)))
+    }
+
+    fn key_error() -> OpenAIError {
+        OpenAIError::ApiError(ApiError {
+            message: "Incorrect API key provided".to_string(),
+            r#type: None,
+            param: None,
+            code: Some("invalid_api_key".to_string()),
+        })
+    }
+
+    /// Key the client's next request is authenticated with
+    fn sent_key(client: &Client<KeyedConfig>) -> String {
+        let headers = client.config().headers();
+        let value = headers[AUTHORIZATION].to_str().unwrap();
+        value.trim_start_matches("Bearer ").to_string()
+    }
+
+    #[test]
+    fn failover_sticks_to_a_key_until_it_is_rejected() {
+        let pool = pool(&["a", "b", "c"], KeyRotation::Failover);
+        assert_eq!(pool.next_key(), Some(0));
+        assert_eq!(pool.next_key(), Some(0));
+        assert!(pool.reject(0));
+        assert_eq!(pool.next_key(), Some(1));
+        assert_eq!(pool.next_key(), Some(1));
+        assert_eq!(pool.usable(), 2);
+    }
+
+    #[test]
+    fn round_robin_takes_turns_and_skips_rejected_keys() {
+        let pool = pool(&["a", "b", "c"], KeyRotation::RoundRobin);
+        let turns: Vec<_> = (0..4).map(|_| pool.next_key().unwrap()).collect();
+        assert_eq!(turns, [1, 2, 0, 1]);
+        assert!(pool.reject(2));
+        let turns: Vec<_> = (0..4).map(|_| pool.next_key().unwrap()).collect();
+        assert!(!turns.contains(&2));
+    }
+
+    #[test]
+    fn rejecting_every_key_tries_them_all_again() {
+        let pool = pool(&["a", "b"], KeyRotation::Failover);
+        assert!(pool.reject(0));
+        assert!(!pool.reject(1));
+        assert_eq!(pool.usable(), 0);
+        assert!(pool.next_key().is_some());
+        assert_eq!(pool.usable(), 2);
+        assert_eq!(
+            KeyPool::new(Vec::new(), KeyRotation::Failover).next_key(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn retry_keys_fails_over_to_the_next_key() {
+        let client = client(&["bad", "good"], KeyRotation::Failover);
+        let sent = Mutex::new(Vec::new());
+        let result = retry_keys(&client, || async {
+            let key = sent_key(&client);
+            sent.lock().unwrap().push(key.clone());
+            if key == "bad" {
+                Err(key_error())
+            } else {
+                Ok(key)
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), "good");
+        assert_eq!(*sent.lock().unwrap(), ["bad", "good"]);
+        assert_eq!(client.config().pool().usable(), 1);
+    }
+
+    #[test]
+    fn requests_are_reported_under_their_provider() {
+        let config = KeyedConfig::new(OpenAIConfig::new(), pool(&[], KeyRotation::default()));
+        assert_eq!(config.provider(), "openai");
+        assert_eq!(config.clone().with_provider("mock").provider(), "mock");
+        let gemini = config.with_gemini(GeminiConfig::default());
+        assert_eq!(gemini.provider(), GEMINI_PROVIDER);
+        assert_eq!(gemini.for_model("gpt-4o").provider(), GEMINI_PROVIDER);
+    }
+
+    #[tokio::test]
+    async fn retry_keys_rejects_the_key_the_attempt_sent() {
+        let client = client(&["a", "bad", "c"], KeyRotation::RoundRobin);
+        let result = retry_keys(&client, || async {
+            let key = sent_key(&client);
+            // Other requests take their turns while this one is in flight
+            client.config().pool().next_key();
+            client.config().pool().next_key();
+            if key == "bad" {
+                Err(key_error())
+            } else {
+                Ok(key)
+            }
+        })
+        .await;
+
+        assert_ne!(result.unwrap(), "bad");
+        let pool = client.config().pool();
+        assert_eq!(pool.usable(), 2);
+        assert!(pool.rejected[1].load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_security_store_command_quotes_the_key() {
+        assert_eq!(
+            security_store_command("openai", r#"sk-a"b\c"#),
+            "add-generic-password -U -s \"vintage_game_generator\" -a \"openai\" \
+             -w \"sk-a\\\"b\\\\c\"\n"
+        );
+    }
+}
@@ -0,0 +1,365 @@
+//! Shops, inns and services per town
+//!
+//! Every generated town gets its shop inventories drawn from the item
+//! database, an inn with innkeeper dialogue if the town has one, and its
+//! services. Innkeepers are named locally by a [`NameGenerator`] seeded
+//! from the game config, clear of the world's NPCs. Prices follow
+//! [`PricingRules`]: a shop's own price wins, then the item's database
+//! price, then a default that grows with each town the player reaches.
+//! Expensive items are sold in limited stock. [`Economy::simulate`]
+//! estimates how many battles the player needs to afford each town's best
+//! gear, and [`Economy::affordability_warnings`] reports the towns where
+//! that takes longer than the rules allow.
+
+use serde::{Deserialize, Serialize};
+
+use crate::game_assets::{ItemDatabase, asset_id};
+use crate::game_types::{GameConfig, WorldData};
+use crate::text::dialogue::{DialogueChoice, DialogueNode, DialogueTree};
+use crate::text::names::{NameGenerator, NameKind};
+
+/// How shop and service prices are set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PricingRules {
+    /// Price of items without a price in the first town
+    pub default_price: u32,
+    /// Price increase of unpriced items and services per town, as a fraction
+    pub price_growth: f32,
+    /// Items at or above this price are sold in limited stock
+    pub rare_price: u32,
+    pub rare_stock: u32,
+    /// Price of a night at the inn in the first town
+    pub inn_price: u32,
+    /// Money a battle earns, for [`Economy::simulate`]
+    pub gold_per_battle: u32,
+    /// Battles a town's best gear may take before it is reported
+    pub max_battles: u32,
+}
+
+impl Default for PricingRules {
+    fn default() -> Self {
+        Self {
+            default_price: 50,
+            price_growth: 0.25,
+            rare_price: 1000,
+            rare_stock: 1,
+            inn_price: 10,
+            gold_per_battle: 30,
+            max_battles: 30,
+        }
+    }
+}
+
+impl PricingRules {
+    /// Scale a first-town price to a later town
+    fn scaled(&self, price: u32, town_index: usize) -> u32 {
+        (price as f32 * (1.0 + self.price_growth * town_index as f32)).round() as u32
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StockEntry {
+    /// Item asset id in the item database
+    pub item: String,
+    pub price: u32,
+    /// Units for sale, `None` for unlimited
+    pub stock: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Shop {
+    pub name: String,
+    pub entries: Vec<StockEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ServiceKind {
+    /// Restore the party at the inn
+    Rest,
+    Save,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Service {
+    pub kind: ServiceKind,
+    pub price: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Inn {
+    pub price: u32,
+    /// Title of the innkeeper's start node in the town's Yarn script
+    pub dialogue: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TownEconomy {
+    pub town: String,
+    pub shops: Vec<Shop>,
+    pub inn: Option<Inn>,
+    pub services: Vec<Service>,
+}
+
+/// Shops and services of all towns, in the order the player reaches them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Economy {
+    pub currency: String,
+    pub towns: Vec<TownEconomy>,
+}
+
+/// Spending needed in a town, from [`Economy::simulate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TownAffordability {
+    pub town: String,
+    /// Cost of the most expensive item of every shop
+    pub best_gear_cost: u32,
+    /// Battles needed to earn the best gear
+    pub battles: u32,
+}
+
+impl Economy {
+    /// Stock the shops of every generated town from the item database
+    ///
+    /// Towns follow the order of the game config; generated towns the config
+    /// doesn't list come last, without an inn or save point.
+    pub fn generate(
+        config: &GameConfig,
+        world: &WorldData,
+        items: &ItemDatabase,
+        rules: &PricingRules,
+    ) -> (Self, Vec<DialogueTree>) {
+        let mut towns: Vec<_> = world.towns.iter().collect();
+        towns.sort_by_key(|town| {
+            config
+                .towns
+                .iter()
+                .position(|t| t.name == town.name)
+                .unwrap_or(usize::MAX)
+        });
+
+        let mut dialogues = Vec::new();
+        let mut names = NameGenerator::from_config(config).with_lore(
+            world
+                .towns
+                .iter()
+                .flat_map(|town| town.npcs.iter().map(|npc| &npc.name)),
+        );
+        let towns = towns
+            .into_iter()
+            .enumerate()
+            .map(|(index, town)| {
+                let shops = town
+                    .shops
+                    .iter()
+                    .map(|shop| Shop {
+                        name: shop.name.clone(),
+                        entries: shop
+                            .inventory
+                            .iter()
+                            .map(|name| {
+                                let id = asset_id(name);
+                                let price = shop.prices.get(name).copied().unwrap_or_else(|| {
+                                    items
+                                        .items
+                                        .iter()
+                                        .find(|item| item.id == id)
+                                        .and_then(|item| item.price)
+                                        .unwrap_or_else(|| rules.scaled(rules.default_price, index))
+                                });
+                                StockEntry {
+                                    item: id,
+                                    price,
+                                    stock: (price >= rules.rare_price).then_some(rules.rare_stock),
+                                }
+                            })
+                            .collect(),
+                    })
+                    .collect();
+
+                let town_config = config.towns.iter().find(|t| t.name == town.name);
+                let mut services = Vec::new();
+                let inn = town_config.filter(|t| t.inn).map(|_| {
+                    let price = rules.scaled(rules.inn_price, index);
+                    let dialogue = innkeeper_dialogue(
+                        &town.name,
+                        &names.name(NameKind::Person),
+                        price,
+                        &config.shop_system.currency,
+                    );
+                    let inn = Inn {
+                        price,
+                        dialogue: dialogue.start.clone(),
+                    };
+                    dialogues.push(dialogue);
+                    services.push(Service {
+                        kind: ServiceKind::Rest,
+                        price,
+                    });
+                    inn
+                });
+                if town_config.is_some_and(|t| t.save_point) {
+                    services.push(Service {
+                        kind: ServiceKind::Save,
+                        price: 0,
+                    });
+                }
+
+                TownEconomy {
+                    town: town.name.clone(),
+                    shops,
+                    inn,
+                    services,
+                }
+            })
+            .collect();
+
+        (
+            Self {
+                currency: config.shop_system.currency.clone(),
+                towns,
+            },
+            dialogues,
+        )
+    }
+
+    /// Battles needed in each town to buy its best gear, earning a fixed
+    /// amount per battle
+    pub fn simulate(&self, gold_per_battle: u32) -> Vec<TownAffordability> {
+        self.towns
+            .iter()
+            .map(|town| {
+                let best_gear_cost = town
+                    .shops
+                    .iter()
+                    .filter_map(|shop| shop.entries.iter().map(|entry| entry.price).max())
+                    .sum();
+                TownAffordability {
+                    town: town.town.clone(),
+                    best_gear_cost,
+                    battles: best_gear_cost.div_ceil(gold_per_battle.max(1)),
+                }
+            })
+            .collect()
+    }
+
+    /// One line per town whose best gear takes more than
+    /// `rules.max_battles` battles at `rules.gold_per_battle`
+    pub fn affordability_warnings(&self, rules: &PricingRules) -> Vec<String> {
+        self.simulate(rules.gold_per_battle)
+            .into_iter()
+            .filter(|town| town.battles > rules.max_battles)
+            .map(|town| {
+                format!(
+                    "The best gear in {} costs {} {}, {} battles at {} per battle",
+                    town.town,
+                    town.best_gear_cost,
+                    self.currency,
+                    town.battles,
+                    rules.gold_per_battle
+                )
+            })
+            .collect()
+    }
+}
+
+/// Greeting, rest and farewell of a town's innkeeper
+fn innkeeper_dialogue(town: &str, keeper: &str, price: u32, currency: &str) -> DialogueTree {
+    let id = format!("{}_inn", asset_id(town));
+    let node = |suffix: &str, text: String| DialogueNode {
+        id: format!("{id}_{suffix}"),
+        speaker: keeper.to_string(),
+        text,
+        set_flags: Vec::new(),
+        choices: Vec::new(),
+        next: None,
+    };
+
+    let mut greeting = node(
+        "greeting",
+        format!(
+            "Welcome to {town}! I'm {keeper}, and a night's rest is {price} {currency}. \
+            Will you stay?"
+        ),
+    );
+    greeting.choices = vec![
+        DialogueChoice {
+            text: "Stay the night".to_string(),
+            target: format!("{id}_rest"),
+            condition: None,
+            set_flags: Vec::new(),
+        },
+        DialogueChoice {
+            text: "Not now".to_string(),
+            target: format!("{id}_farewell"),
+            condition: None,
+            set_flags: Vec::new(),
+        },
+    ];
+    let mut rest = node(
+        "rest",
+        "Sleep well. Your party wakes fully rested.".to_string(),
+    );
+    rest.set_flags = vec![format!("{id}_rested")];
+
+    DialogueTree {
+        id: id.clone(),
+        character: keeper.to_string(),
+        start: greeting.id.clone(),
+        nodes: vec![
+            greeting,
+            rest,
+            node("farewell", "Come back any time, travelers.".to_string()),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encounters::DifficultyPreset;
+    use crate::game_assets::GameDataAssets;
+    use crate::game_types::fixtures;
+
+    fn economy() -> Economy {
+        GameDataAssets::from_generated(
+            &fixtures::config(),
+            &fixtures::world(),
+            DifficultyPreset::default(),
+        )
+        .economy
+    }
+
+    #[test]
+    fn simulate_counts_battles_for_the_best_gear() {
+        let affordability = economy().simulate(100);
+        let port_sylva = affordability
+            .iter()
+            .find(|town| town.town == "Port Sylva")
+            .unwrap();
+        assert_eq!(port_sylva.best_gear_cost, 1200);
+        assert_eq!(port_sylva.battles, 12);
+        // No earnings count as one gold per battle
+        assert_eq!(economy().simulate(0)[1].battles, 1200);
+    }
+
+    #[test]
+    fn warns_about_towns_over_the_battle_budget() {
+        let rules = PricingRules {
+            gold_per_battle: 100,
+            max_battles: 10,
+            ..Default::default()
+        };
+        let warnings = economy().affordability_warnings(&rules);
+        assert_eq!(
+            warnings,
+            ["The best gear in Port Sylva costs 1200 gold, 12 battles at 100 per battle"]
+        );
+
+        let generous = PricingRules {
+            gold_per_battle: 1200,
+            ..rules
+        };
+        assert!(economy().affordability_warnings(&generous).is_empty());
+    }
+}
@@ -7,21 +7,28 @@
 //! - Style consistency
 
 use anyhow::{Context, Result};
-use async_openai::{Client, config::OpenAIConfig, types::embeddings::CreateEmbeddingRequestArgs};
+use async_openai::{
+    Client,
+    types::embeddings::{CreateEmbeddingRequestArgs, CreateEmbeddingResponse},
+};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
 use super::{
     AiConfig, AiGenerator,
+    artifacts::{ArtifactIndex, ArtifactKind, DuplicateCheck},
     cache::{AiCache, CachedData},
+    credentials::{KeyedConfig, retry_keys},
+    quota,
+    telemetry::RequestTimer,
     tokens::TokenCounter,
 };
 
 /// Embeddings generator for semantic similarity
 #[derive(Clone)]
 pub struct EmbeddingsGenerator {
-    client: Arc<Client<OpenAIConfig>>,
+    client: Arc<Client<KeyedConfig>>,
     cache: Arc<Mutex<AiCache>>,
     token_counter: Arc<Mutex<TokenCounter>>,
 }
@@ -35,9 +42,8 @@ impl AiGenerator for EmbeddingsGenerator {
     }
 
     async fn estimate_cost(&self, request: &str) -> Result<f64> {
-        let tokens = self.estimate_tokens(request).await?;
-        // text-embedding-3-small is $0.00002 / 1k tokens
-        Ok((tokens as f64 / 1000.0) * 0.00002)
+        let counter = self.token_counter.lock().await;
+        counter.estimate_embedding_cost("text-embedding-3-small", request)
     }
 
     async fn is_cached(&self, key: &str) -> bool {
@@ -52,7 +58,7 @@ impl AiGenerator for EmbeddingsGenerator {
 impl EmbeddingsGenerator {
     /// Create a new embeddings generator
     pub fn new(
-        client: Arc<Client<OpenAIConfig>>,
+        client: Arc<Client<KeyedConfig>>,
         cache: Arc<Mutex<AiCache>>,
         token_counter: Arc<Mutex<TokenCounter>>,
     ) -> Self {
@@ -65,12 +71,15 @@ impl EmbeddingsGenerator {
 
     /// Generate embeddings for a single text
     pub async fn generate(&self, text: &str, config: &AiConfig) -> Result<Vec<f32>> {
+        let timer = RequestTimer::start("embeddings");
+
         // Check cache first
         let cache_key = format!("embedding:{}:{}", config.embedding_model, text);
 
         if let Some(cached) = self.cache.lock().await.get(&cache_key).await
             && let CachedData::Embedding(embedding) = cached.data
         {
+            timer.cache_hit(self.client.config().provider());
             return Ok(embedding);
         }
 
@@ -87,12 +96,11 @@ impl EmbeddingsGenerator {
             .input(text)
             .build()?;
 
-        let response = self
-            .client
-            .embeddings()
-            .create(request)
-            .await
-            .context("Failed to generate embedding")?;
+        let response: CreateEmbeddingResponse = retry_keys(&self.client, || {
+            quota::send(&self.client, "/embeddings", &request)
+        })
+        .await
+        .context("Failed to generate embedding")?;
 
         // Extract the embedding vector
         let embedding = response
@@ -103,15 +111,16 @@ impl EmbeddingsGenerator {
             .clone();
 
         // Track token usage
-        self.token_counter
+        let tokens = response.usage.prompt_tokens as usize;
+        let cost = self
+            .token_counter
             .lock()
             .await
             .record_usage(
-                model,
-                response.usage.prompt_tokens as usize,
-                0, // No completion tokens for embeddings
+                model, tokens, 0, // No completion tokens for embeddings
             )
             .await?;
+        timer.finish(self.client.config().provider(), tokens, cost, false);
 
         // Cache the result
         self.cache
@@ -133,6 +142,8 @@ impl EmbeddingsGenerator {
         texts: Vec<&str>,
         config: &AiConfig,
     ) -> Result<Vec<Vec<f32>>> {
+        let timer = RequestTimer::start("embeddings");
+
         // OpenAI supports batch embedding requests
         let model = match config.embedding_model.as_str() {
             "text-embedding-3-small" => "text-embedding-3-small",
@@ -146,19 +157,21 @@ impl EmbeddingsGenerator {
             .input(texts.clone())
             .build()?;
 
-        let response = self
-            .client
-            .embeddings()
-            .create(request)
-            .await
-            .context("Failed to generate embeddings batch")?;
+        let response: CreateEmbeddingResponse = retry_keys(&self.client, || {
+            quota::send(&self.client, "/embeddings", &request)
+        })
+        .await
+        .context("Failed to generate embeddings batch")?;
 
         // Track token usage
-        self.token_counter
+        let tokens = response.usage.prompt_tokens as usize;
+        let cost = self
+            .token_counter
             .lock()
             .await
-            .record_usage(model, response.usage.prompt_tokens as usize, 0)
+            .record_usage(model, tokens, 0)
             .await?;
+        timer.finish(self.client.config().provider(), tokens, cost, false);
 
         // Extract all embeddings
         let embeddings: Vec<Vec<f32>> = response.data.into_iter().map(|e| e.embedding).collect();
@@ -208,6 +221,22 @@ impl EmbeddingsGenerator {
         dot_product / (norm_a * norm_b)
     }
 
+    /// Embed a description and look for a near-duplicate artifact in `index`
+    pub async fn check_duplicate(
+        &self,
+        description: &str,
+        kind: ArtifactKind,
+        index: &ArtifactIndex,
+        config: &AiConfig,
+    ) -> Result<DuplicateCheck> {
+        let embedding = self.generate(description, config).await?;
+        let duplicate = index.find_duplicate(kind, &embedding);
+        Ok(DuplicateCheck {
+            embedding,
+            duplicate,
+        })
+    }
+
     /// Find most similar texts from a collection
     pub async fn find_similar(
         &self,
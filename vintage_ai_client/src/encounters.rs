@@ -0,0 +1,273 @@
+//! Random encounter tables per region
+//!
+//! Regions are visited in the order the world lists them, and each one is
+//! meant for a band of player levels. A table lists the enemy groups that can
+//! spawn in a region with their spawn weights and level ranges. Enemies come
+//! from the bestiary, so every name resolves to an enemy asset; bosses never
+//! appear in random encounters. [`EncounterTables::validate`] reports tables
+//! that break these rules, most importantly groups whose levels reach above
+//! the region's player level band.
+
+use serde::{Deserialize, Serialize};
+
+use crate::game_assets::{EnemyDatabase, asset_id};
+use crate::game_types::WorldData;
+
+/// How hard random encounters are
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DifficultyPreset {
+    Casual,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl DifficultyPreset {
+    /// Player levels gained per region
+    pub fn levels_per_region(&self) -> u32 {
+        match self {
+            Self::Casual => 4,
+            Self::Normal => 5,
+            Self::Hard => 6,
+        }
+    }
+
+    /// Largest number of enemies in one group
+    pub fn max_group_size(&self) -> u32 {
+        match self {
+            Self::Casual => 2,
+            Self::Normal => 3,
+            Self::Hard => 4,
+        }
+    }
+
+    /// Levels below the top of the player band that enemies stop at
+    pub fn level_headroom(&self) -> u32 {
+        match self {
+            Self::Casual => 2,
+            Self::Normal => 1,
+            Self::Hard => 0,
+        }
+    }
+}
+
+/// Inclusive range of levels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncounterMember {
+    /// Enemy asset id in the bestiary
+    pub enemy: String,
+    pub count: u32,
+}
+
+/// Enemies that spawn together
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncounterGroup {
+    pub members: Vec<EncounterMember>,
+    /// Relative spawn weight within the region
+    pub weight: u32,
+    pub levels: LevelRange,
+}
+
+impl EncounterGroup {
+    pub fn size(&self) -> u32 {
+        self.members.iter().map(|m| m.count).sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncounterTable {
+    pub region: String,
+    /// Player levels the region is designed for
+    pub player_levels: LevelRange,
+    pub groups: Vec<EncounterGroup>,
+}
+
+/// Encounter tables of all regions
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncounterTables {
+    pub difficulty: DifficultyPreset,
+    pub tables: Vec<EncounterTable>,
+}
+
+/// A rule an encounter table breaks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncounterIssue {
+    UnknownEnemy {
+        region: String,
+        enemy: String,
+    },
+    BossEncounter {
+        region: String,
+        enemy: String,
+    },
+    AboveLevelBand {
+        region: String,
+        group: usize,
+        max_level: u32,
+        band_max: u32,
+    },
+    EmptyGroup {
+        region: String,
+        group: usize,
+    },
+    NoEncounters {
+        region: String,
+    },
+}
+
+impl std::fmt::Display for EncounterIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownEnemy { region, enemy } => {
+                write!(f, "{region}: enemy '{enemy}' is not in the bestiary")
+            }
+            Self::BossEncounter { region, enemy } => {
+                write!(f, "{region}: boss '{enemy}' appears as a random encounter")
+            }
+            Self::AboveLevelBand {
+                region,
+                group,
+                max_level,
+                band_max,
+            } => write!(
+                f,
+                "{region}: group {group} reaches level {max_level}, above the player band maximum {band_max}"
+            ),
+            Self::EmptyGroup { region, group } => {
+                write!(f, "{region}: group {group} has no enemies or zero weight")
+            }
+            Self::NoEncounters { region } => write!(f, "{region}: no encounters"),
+        }
+    }
+}
+
+impl EncounterTables {
+    /// Build tables from each region's encounter list.
+    ///
+    /// Every enemy spawns alone; with larger group sizes it also spawns in
+    /// packs and alongside the next enemy of the region. Bigger groups spawn
+    /// less often and at lower levels, so a full pack stays beatable at the
+    /// bottom of the band.
+    pub fn generate(
+        world: &WorldData,
+        bestiary: &EnemyDatabase,
+        difficulty: DifficultyPreset,
+    ) -> Self {
+        let step = difficulty.levels_per_region();
+        let tables = world
+            .regions
+            .iter()
+            .enumerate()
+            .map(|(index, region)| {
+                let player_levels = LevelRange {
+                    min: 1 + index as u32 * step,
+                    max: (index as u32 + 1) * step,
+                };
+
+                let mut enemies: Vec<String> = Vec::new();
+                for name in &region.encounters {
+                    let id = asset_id(name);
+                    let known = bestiary.enemies.iter().any(|e| e.id == id && !e.boss);
+                    if known && !enemies.contains(&id) {
+                        enemies.push(id);
+                    }
+                }
+
+                let mut groups = Vec::new();
+                for (i, enemy) in enemies.iter().enumerate() {
+                    for count in 1..=difficulty.max_group_size() {
+                        groups.push(group(vec![(enemy, count)], player_levels, difficulty));
+                    }
+                    if difficulty.max_group_size() >= 2
+                        && let Some(partner) = enemies.get(i + 1)
+                    {
+                        groups.push(group(
+                            vec![(enemy, 1), (partner, 1)],
+                            player_levels,
+                            difficulty,
+                        ));
+                    }
+                }
+
+                EncounterTable {
+                    region: region.name.clone(),
+                    player_levels,
+                    groups,
+                }
+            })
+            .collect();
+
+        Self { difficulty, tables }
+    }
+
+    /// Check every table against the bestiary and the level bands
+    pub fn validate(&self, bestiary: &EnemyDatabase) -> Vec<EncounterIssue> {
+        let mut issues = Vec::new();
+        for table in &self.tables {
+            if table.groups.is_empty() {
+                issues.push(EncounterIssue::NoEncounters {
+                    region: table.region.clone(),
+                });
+            }
+            for (index, group) in table.groups.iter().enumerate() {
+                if group.members.is_empty() || group.weight == 0 {
+                    issues.push(EncounterIssue::EmptyGroup {
+                        region: table.region.clone(),
+                        group: index,
+                    });
+                }
+                if group.levels.max > table.player_levels.max {
+                    issues.push(EncounterIssue::AboveLevelBand {
+                        region: table.region.clone(),
+                        group: index,
+                        max_level: group.levels.max,
+                        band_max: table.player_levels.max,
+                    });
+                }
+                for member in &group.members {
+                    match bestiary.enemies.iter().find(|e| e.id == member.enemy) {
+                        None => issues.push(EncounterIssue::UnknownEnemy {
+                            region: table.region.clone(),
+                            enemy: member.enemy.clone(),
+                        }),
+                        Some(enemy) if enemy.boss => issues.push(EncounterIssue::BossEncounter {
+                            region: table.region.clone(),
+                            enemy: member.enemy.clone(),
+                        }),
+                        Some(_) => {}
+                    }
+                }
+            }
+        }
+        issues
+    }
+}
+
+fn group(
+    members: Vec<(&String, u32)>,
+    band: LevelRange,
+    difficulty: DifficultyPreset,
+) -> EncounterGroup {
+    let size: u32 = members.iter().map(|(_, count)| count).sum();
+    let max = band
+        .max
+        .saturating_sub(difficulty.level_headroom() + size - 1)
+        .max(band.min);
+    EncounterGroup {
+        members: members
+            .into_iter()
+            .map(|(enemy, count)| EncounterMember {
+                enemy: enemy.clone(),
+                count,
+            })
+            .collect(),
+        weight: 12 / size,
+        levels: LevelRange { min: band.min, max },
+    }
+}
@@ -0,0 +1,364 @@
+//! Bulk export of a project's artifacts with format conversion
+//!
+//! A [`BulkExport`] selects registered artifacts by tag and by type, writes
+//! each one to an output folder in the chosen format and records the result
+//! in an `export-manifest.json` there. Images can be written as PNG, BMP or
+//! indexed PNG, data as JSON or RON, and audio as WAV or OGG; OGG encoding
+//! needs `ffmpeg` on the path. Artifacts that can't be converted are listed
+//! in the manifest as skipped instead of failing the whole export.
+//!
+//! The [`ExportProfile`] decides the layout of the output folder: a folder
+//! per asset type by default, or the folders and sheet layouts of an RPG
+//! Maker project (see [`rpgmaker`](super::rpgmaker)), in which case the
+//! engine's formats are used instead of the chosen ones.
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::rpgmaker::{RpgMakerAsset, RpgMakerLayout};
+use crate::artifacts::{ArtifactIndex, ArtifactRecord};
+use crate::consistency::indexed::{IndexedPalette, write_png};
+
+/// File name of the manifest written into the output folder
+pub const EXPORT_MANIFEST_FILE: &str = "export-manifest.json";
+
+/// Kind of file an artifact is, by extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetType {
+    Image,
+    Data,
+    Audio,
+    Other,
+}
+
+impl AssetType {
+    pub const ALL: [AssetType; 4] = [
+        AssetType::Image,
+        AssetType::Data,
+        AssetType::Audio,
+        AssetType::Other,
+    ];
+
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "png" | "bmp" | "jpg" | "jpeg" | "webp" | "gif" => AssetType::Image,
+            "json" | "ron" => AssetType::Data,
+            "wav" | "ogg" | "mp3" | "flac" => AssetType::Audio,
+            _ => AssetType::Other,
+        }
+    }
+
+    /// Subfolder of the output folder the type is written to
+    pub fn folder(self) -> &'static str {
+        match self {
+            AssetType::Image => "images",
+            AssetType::Data => "data",
+            AssetType::Audio => "audio",
+            AssetType::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageFormat {
+    #[default]
+    Png,
+    Bmp,
+    /// Palette PNG of the image's own colors, up to 256
+    IndexedPng,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataFormat {
+    #[default]
+    Json,
+    Ron,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioFormat {
+    #[default]
+    Wav,
+    Ogg,
+}
+
+/// Output format per asset type; files of other types are copied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ExportFormats {
+    pub image: ImageFormat,
+    pub data: DataFormat,
+    pub audio: AudioFormat,
+}
+
+/// How the output folder is laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportProfile {
+    /// A folder per asset type, in the chosen formats
+    #[default]
+    Generic,
+    /// The `img/` and `audio/` folders of an RPG Maker MV/MZ project
+    RpgMaker(RpgMakerLayout),
+}
+
+/// An artifact written to the output folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedFile {
+    pub artifact: String,
+    pub asset_type: AssetType,
+    pub source: PathBuf,
+    /// Path relative to the output folder
+    pub file: PathBuf,
+    pub tags: Vec<String>,
+}
+
+/// An artifact that was selected but not written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedExport {
+    pub artifact: String,
+    pub reason: String,
+}
+
+/// Summary written next to the exported files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub exported_at: DateTime<Utc>,
+    #[serde(default)]
+    pub profile: ExportProfile,
+    pub formats: ExportFormats,
+    pub files: Vec<ExportedFile>,
+    pub skipped: Vec<SkippedExport>,
+}
+
+/// Which artifacts to export and in what formats
+#[derive(Debug, Clone, Default)]
+pub struct BulkExport {
+    /// Artifacts with any of these tags; all artifacts if empty
+    pub tags: BTreeSet<String>,
+    /// Artifacts of these types; all types if empty
+    pub types: BTreeSet<AssetType>,
+    pub formats: ExportFormats,
+    pub profile: ExportProfile,
+}
+
+impl BulkExport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tags.insert(tag.to_string());
+        self
+    }
+
+    pub fn with_type(mut self, asset_type: AssetType) -> Self {
+        self.types.insert(asset_type);
+        self
+    }
+
+    pub fn with_formats(mut self, formats: ExportFormats) -> Self {
+        self.formats = formats;
+        self
+    }
+
+    pub fn with_profile(mut self, profile: ExportProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Artifacts saved to disk that match the tags and types
+    pub fn select<'a>(&self, index: &'a ArtifactIndex) -> Vec<&'a ArtifactRecord> {
+        index
+            .artifacts
+            .iter()
+            .filter(|artifact| {
+                artifact.path.as_deref().is_some_and(|path| {
+                    self.types.is_empty() || self.types.contains(&AssetType::from_path(path))
+                })
+            })
+            .filter(|artifact| {
+                self.tags.is_empty()
+                    || self
+                        .tags
+                        .iter()
+                        .any(|tag| index.has_tag(&artifact.name, tag))
+            })
+            .collect()
+    }
+
+    /// Write the selected artifacts to `out_dir` along with the manifest
+    pub fn run(&self, index: &ArtifactIndex, out_dir: &Path) -> Result<ExportManifest> {
+        std::fs::create_dir_all(out_dir)
+            .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+        let mut manifest = ExportManifest {
+            exported_at: Utc::now(),
+            profile: self.profile,
+            formats: self.formats,
+            files: Vec::new(),
+            skipped: Vec::new(),
+        };
+
+        for artifact in self.select(index) {
+            let Some(source) = artifact.path.as_deref() else {
+                continue;
+            };
+            let asset_type = AssetType::from_path(source);
+            let tags: Vec<&str> = index.tags_of(&artifact.name).collect();
+            let written = match self.profile {
+                ExportProfile::Generic => {
+                    let file = Path::new(asset_type.folder())
+                        .join(&artifact.name)
+                        .with_extension(self.extension(asset_type, source));
+                    self.convert(asset_type, source, &out_dir.join(&file))
+                        .map(|()| vec![file])
+                }
+                ExportProfile::RpgMaker(layout) => {
+                    match RpgMakerAsset::classify(asset_type, &artifact.name, &tags) {
+                        Some(asset) => layout.write(asset, &artifact.name, source, out_dir),
+                        None => Err(anyhow::anyhow!(
+                            "RPG Maker has no folder for {asset_type:?} files"
+                        )),
+                    }
+                }
+            };
+            match written {
+                Ok(files) => manifest
+                    .files
+                    .extend(files.into_iter().map(|file| ExportedFile {
+                        artifact: artifact.name.clone(),
+                        asset_type,
+                        source: source.to_path_buf(),
+                        file,
+                        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+                    })),
+                Err(e) => {
+                    tracing::warn!("Skipping {} in export: {e:#}", artifact.name);
+                    manifest.skipped.push(SkippedExport {
+                        artifact: artifact.name.clone(),
+                        reason: format!("{e:#}"),
+                    });
+                }
+            }
+        }
+
+        std::fs::write(
+            out_dir.join(EXPORT_MANIFEST_FILE),
+            serde_json::to_string_pretty(&manifest)?,
+        )
+        .context("Failed to write export manifest")?;
+        Ok(manifest)
+    }
+
+    fn extension(&self, asset_type: AssetType, source: &Path) -> String {
+        match asset_type {
+            AssetType::Image => match self.formats.image {
+                ImageFormat::Png | ImageFormat::IndexedPng => "png".to_string(),
+                ImageFormat::Bmp => "bmp".to_string(),
+            },
+            AssetType::Data => match self.formats.data {
+                DataFormat::Json => "json".to_string(),
+                DataFormat::Ron => "ron".to_string(),
+            },
+            AssetType::Audio => match self.formats.audio {
+                AudioFormat::Wav => "wav".to_string(),
+                AudioFormat::Ogg => "ogg".to_string(),
+            },
+            AssetType::Other => source
+                .extension()
+                .map(|ext| ext.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn convert(&self, asset_type: AssetType, source: &Path, target: &Path) -> Result<()> {
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if !source.is_file() {
+            bail!("{} does not exist", source.display());
+        }
+        let same_extension = source.extension().map(|ext| ext.to_ascii_lowercase())
+            == target.extension().map(|ext| ext.to_ascii_lowercase());
+
+        match asset_type {
+            AssetType::Image => {
+                let image = image::open(source)
+                    .with_context(|| format!("Failed to open {}", source.display()))?;
+                match self.formats.image {
+                    ImageFormat::IndexedPng => {
+                        let image = image.to_rgba8();
+                        write_png(&image, &IndexedPalette::from_image(&image)?, target)?
+                    }
+                    ImageFormat::Png if same_extension => copy(source, target)?,
+                    ImageFormat::Png => image.save_with_format(target, image::ImageFormat::Png)?,
+                    ImageFormat::Bmp => image
+                        .to_rgba8()
+                        .save_with_format(target, image::ImageFormat::Bmp)?,
+                }
+            }
+            AssetType::Data if same_extension => copy(source, target)?,
+            AssetType::Data => {
+                let contents = std::fs::read_to_string(source)
+                    .with_context(|| format!("Failed to read {}", source.display()))?;
+                let converted = match self.formats.data {
+                    DataFormat::Ron => {
+                        let value: serde_json::Value = serde_json::from_str(&contents)
+                            .with_context(|| format!("{} is not valid JSON", source.display()))?;
+                        ron::ser::to_string_pretty(&value, ron::ser::PrettyConfig::default())?
+                    }
+                    DataFormat::Json => {
+                        let value: serde_json::Value = ron::from_str(&contents)
+                            .with_context(|| format!("{} is not valid RON", source.display()))?;
+                        serde_json::to_string_pretty(&value)?
+                    }
+                };
+                std::fs::write(target, converted)
+                    .with_context(|| format!("Failed to write {}", target.display()))?;
+            }
+            AssetType::Audio if same_extension => copy(source, target)?,
+            AssetType::Audio => transcode_audio(source, target)?,
+            AssetType::Other => copy(source, target)?,
+        }
+        Ok(())
+    }
+}
+
+pub(super) fn copy(source: &Path, target: &Path) -> Result<()> {
+    std::fs::copy(source, target)
+        .with_context(|| format!("Failed to copy {}", source.display()))?;
+    Ok(())
+}
+
+/// Convert audio with ffmpeg, which picks the codec from the extension
+pub(super) fn transcode_audio(source: &Path, target: &Path) -> Result<()> {
+    let output = Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-i"])
+        .arg(source)
+        .arg(target)
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                anyhow::anyhow!("ffmpeg is needed to convert audio but was not found")
+            }
+            _ => anyhow::Error::new(e).context("Failed to run ffmpeg"),
+        })?;
+    if !output.status.success() {
+        bail!(
+            "ffmpeg failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,759 @@
+//! GB Studio project export
+//!
+//! A [`GbStudioProject`] collects backgrounds, sprite sheets and scenes and
+//! writes them as a GB Studio 3 project: a `.gbsproj` file next to an
+//! `assets/` folder of PNGs, ready to build into a Game Boy ROM. Images are
+//! reduced by brightness to the four shades the Game Boy shows:
+//!
+//! - backgrounds are padded to whole 8x8 tiles, at least a screen in size,
+//!   and may hold at most [`MAX_UNIQUE_TILES`] different tiles
+//! - sprites are cut into 16x16 frames in the three darker shades a sprite
+//!   can show, with [`TRANSPARENT`] around them
+//!
+//! [`GbStudioProject::add_world`] turns the zones of a [`WorldGraph`] into
+//! one-screen scenes tiled with their biome, joined by triggers on the edge
+//! facing each connected zone, with a sign for every town and dungeon.
+//! [`GbStudioProject::add_dialogue`] puts an NPC into a scene whose script
+//! is a [`DialogueTree`] as text, choice and menu events; story flags become
+//! project variables, and items flags named `has_<item>`.
+
+use anyhow::{Context, Result, bail};
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, Rgba, RgbaImage};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::maps::MarkerKind;
+use crate::slugs::{SlugCase, slugify};
+use crate::text::dialogue::{Condition, DialogueTree};
+use crate::world::{BiomeTileset, DUNGEON_TILE, TOWN_TILE, WorldGraph};
+
+/// Project format version written to the `.gbsproj`
+pub const FORMAT_VERSION: &str = "3.0.0";
+
+/// Size of a background tile in pixels
+pub const TILE_SIZE: u32 = 8;
+
+/// Screen size in tiles; every scene is one screen
+pub const SCREEN_TILES: (u32, u32) = (20, 18);
+
+/// Different tiles a background may use
+pub const MAX_UNIQUE_TILES: usize = 192;
+
+/// Width and height of a sprite frame in pixels
+pub const SPRITE_SIZE: u32 = 16;
+
+/// Frames a sprite sheet may have
+pub const MAX_SPRITE_FRAMES: u32 = 25;
+
+/// Actors and triggers a scene may have
+pub const MAX_ACTORS: usize = 20;
+pub const MAX_TRIGGERS: usize = 30;
+
+/// Characters per line and lines per box of a text event
+pub const TEXT_COLUMNS: usize = 18;
+pub const TEXT_LINES: usize = 2;
+
+/// Options a menu event can offer
+pub const MAX_MENU_OPTIONS: usize = 8;
+
+/// Characters of a choice or menu option
+const OPTION_COLUMNS: usize = 16;
+
+/// The four shades from lightest to darkest, as GB Studio expects them
+pub const SHADES: [[u8; 3]; 4] = [
+    [0xe0, 0xf8, 0xcf],
+    [0x86, 0xc0, 0x6c],
+    [0x30, 0x68, 0x50],
+    [0x07, 0x18, 0x21],
+];
+
+/// Color GB Studio leaves transparent in sprites
+pub const TRANSPARENT: [u8; 3] = [0x65, 0xff, 0x00];
+
+/// Variable menu and choice events store the answer in
+const CHOICE_VARIABLE: &str = "dialogue_choice";
+
+/// Width of the world view the scenes are laid out on, in pixels
+const WORLD_WIDTH: f32 = 1600.0;
+
+#[derive(Debug, Clone)]
+struct Background {
+    id: String,
+    name: String,
+    image: RgbaImage,
+}
+
+#[derive(Debug, Clone)]
+struct SpriteSheet {
+    id: String,
+    name: String,
+    image: RgbaImage,
+}
+
+/// A one-screen scene
+#[derive(Debug, Clone)]
+pub struct Scene {
+    pub id: String,
+    pub name: String,
+    pub background_id: String,
+    /// Position in GB Studio's world view
+    pub x: i32,
+    pub y: i32,
+    actors: Vec<Value>,
+    triggers: Vec<Value>,
+    /// Triggers placed on each edge so far
+    edges: BTreeMap<Edge, u32>,
+}
+
+/// Edge of a scene a trigger sits on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Edge {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Edge {
+    /// Edge facing a scene offset by `dx`, `dy`
+    fn towards(dx: f32, dy: f32) -> Self {
+        if dx.abs() > dy.abs() {
+            if dx > 0.0 { Edge::East } else { Edge::West }
+        } else if dy > 0.0 {
+            Edge::South
+        } else {
+            Edge::North
+        }
+    }
+
+    fn opposite(self) -> Self {
+        match self {
+            Edge::North => Edge::South,
+            Edge::East => Edge::West,
+            Edge::South => Edge::North,
+            Edge::West => Edge::East,
+        }
+    }
+
+    /// Tile of the `slot`th trigger on the edge, and the tile next to it
+    /// inside the scene where a player arriving through it is placed
+    fn slot(self, slot: u32) -> ((u32, u32), (u32, u32), &'static str) {
+        let (width, height) = SCREEN_TILES;
+        let along = |length: u32| 2 + (slot * 4) % (length - 4);
+        match self {
+            Edge::North => ((along(width), 0), (along(width), 2), "down"),
+            Edge::South => ((along(width), height - 1), (along(width), height - 3), "up"),
+            Edge::West => ((0, along(height)), (2, along(height)), "right"),
+            Edge::East => (
+                (width - 1, along(height)),
+                (width - 3, along(height)),
+                "left",
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GbStudioProject {
+    pub title: String,
+    backgrounds: Vec<Background>,
+    sprites: Vec<SpriteSheet>,
+    pub scenes: Vec<Scene>,
+    /// Variable names by id
+    variables: Vec<String>,
+    player_sprite: Option<String>,
+}
+
+impl GbStudioProject {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            backgrounds: Vec::new(),
+            sprites: Vec::new(),
+            scenes: Vec::new(),
+            variables: vec![CHOICE_VARIABLE.to_string()],
+            player_sprite: None,
+        }
+    }
+
+    /// Reduce an image to the four shades as a background and return its id.
+    /// Backgrounds smaller than the screen are scaled up to it, others are
+    /// padded to whole tiles with the lightest shade.
+    pub fn add_background(&mut self, name: &str, image: &DynamicImage) -> Result<String> {
+        let (screen_width, screen_height) =
+            (SCREEN_TILES.0 * TILE_SIZE, SCREEN_TILES.1 * TILE_SIZE);
+        let source = if image.width() < screen_width || image.height() < screen_height {
+            image.resize_exact(
+                image.width().max(screen_width),
+                image.height().max(screen_height),
+                FilterType::Triangle,
+            )
+        } else {
+            image.clone()
+        };
+        let (width, height) = (
+            source.width().div_ceil(TILE_SIZE) * TILE_SIZE,
+            source.height().div_ceil(TILE_SIZE) * TILE_SIZE,
+        );
+        let [r, g, b] = SHADES[0];
+        let mut background = RgbaImage::from_pixel(width, height, Rgba([r, g, b, 255]));
+        imageops::overlay(&mut background, &source.to_rgba8(), 0, 0);
+        for pixel in background.pixels_mut() {
+            let [r, g, b] = SHADES[shade(pixel)];
+            *pixel = Rgba([r, g, b, 255]);
+        }
+
+        let tiles = unique_tiles(&background);
+        if tiles > MAX_UNIQUE_TILES {
+            bail!(
+                "Background '{name}' has {tiles} different tiles, but the Game Boy \
+                holds at most {MAX_UNIQUE_TILES}"
+            );
+        }
+        let id = uuid::Uuid::new_v4().to_string();
+        self.backgrounds.push(Background {
+            id: id.clone(),
+            name: name.to_string(),
+            image: background,
+        });
+        Ok(id)
+    }
+
+    /// Fill a screen with a tile as a background, reusing the one of the
+    /// same name
+    pub fn add_tiled_background(&mut self, name: &str, tile: &DynamicImage) -> Result<String> {
+        if let Some(background) = self.backgrounds.iter().find(|b| b.name == name) {
+            return Ok(background.id.clone());
+        }
+        let tile = tile.resize_exact(TILE_SIZE, TILE_SIZE, FilterType::Triangle);
+        let mut screen = RgbaImage::new(SCREEN_TILES.0 * TILE_SIZE, SCREEN_TILES.1 * TILE_SIZE);
+        for y in 0..SCREEN_TILES.1 {
+            for x in 0..SCREEN_TILES.0 {
+                imageops::replace(
+                    &mut screen,
+                    &tile.to_rgba8(),
+                    (x * TILE_SIZE) as i64,
+                    (y * TILE_SIZE) as i64,
+                );
+            }
+        }
+        self.add_background(name, &DynamicImage::ImageRgba8(screen))
+    }
+
+    /// Reduce an image to a sprite sheet and return its id. An image as wide
+    /// as a whole number of its height is taken as a strip of square frames.
+    pub fn add_sprite(&mut self, name: &str, image: &DynamicImage) -> Result<String> {
+        let (width, height) = (image.width(), image.height());
+        if width == 0 || height == 0 {
+            bail!("Sprite '{name}' is empty");
+        }
+        let frames = if width % height == 0 {
+            width / height
+        } else {
+            1
+        };
+        if frames > MAX_SPRITE_FRAMES {
+            bail!("Sprite '{name}' has {frames} frames, GB Studio allows {MAX_SPRITE_FRAMES}");
+        }
+        let frame_width = width / frames;
+
+        let [r, g, b] = TRANSPARENT;
+        let mut sheet =
+            RgbaImage::from_pixel(SPRITE_SIZE * frames, SPRITE_SIZE, Rgba([r, g, b, 255]));
+        for frame in 0..frames {
+            let source = image
+                .crop_imm(frame * frame_width, 0, frame_width, height)
+                .resize_exact(SPRITE_SIZE, SPRITE_SIZE, FilterType::Triangle)
+                .to_rgba8();
+            for (x, y, pixel) in source.enumerate_pixels() {
+                if pixel[3] < 128 {
+                    continue;
+                }
+                // Sprites can't show the lightest shade
+                let [r, g, b] = SHADES[shade(pixel).max(1)];
+                sheet.put_pixel(frame * SPRITE_SIZE + x, y, Rgba([r, g, b, 255]));
+            }
+        }
+
+        let id = uuid::Uuid::new_v4().to_string();
+        self.sprites.push(SpriteSheet {
+            id: id.clone(),
+            name: name.to_string(),
+            image: sheet,
+        });
+        Ok(id)
+    }
+
+    /// Play as the sprite sheet `sprite_id`
+    pub fn set_player(&mut self, sprite_id: &str) {
+        self.player_sprite = Some(sprite_id.to_string());
+    }
+
+    pub fn scene(&self, name: &str) -> Option<&Scene> {
+        self.scenes.iter().find(|scene| scene.name == name)
+    }
+
+    /// Add a scene for every zone, tiled with its biome's tile from `tiles`
+    /// or the lightest shade, and laid out like the world map. Connected
+    /// zones get a trigger on the edge facing each other that leads to the
+    /// other scene. Towns and dungeons become signs, drawn with the town and
+    /// dungeon tiles; without those tiles they are left out.
+    pub fn add_world(&mut self, graph: &WorldGraph, tiles: &BiomeTileset) -> Result<()> {
+        for zone in &graph.zones {
+            let tile = match tiles.tile(&zone.biome) {
+                Some(tile) => DynamicImage::ImageRgba8(tile.clone()),
+                None => {
+                    let [r, g, b] = SHADES[0];
+                    DynamicImage::ImageRgba8(RgbaImage::from_pixel(
+                        TILE_SIZE,
+                        TILE_SIZE,
+                        Rgba([r, g, b, 255]),
+                    ))
+                }
+            };
+            let background_id = self.add_tiled_background(&zone.biome, &tile)?;
+            self.scenes.push(Scene {
+                id: uuid::Uuid::new_v4().to_string(),
+                name: zone.name.clone(),
+                background_id,
+                x: (zone.x * WORLD_WIDTH) as i32,
+                y: (zone.y * WORLD_WIDTH) as i32,
+                actors: Vec::new(),
+                triggers: Vec::new(),
+                edges: BTreeMap::new(),
+            });
+        }
+
+        for connection in &graph.connections {
+            let (Some(from), Some(to)) = (graph.zone(&connection.from), graph.zone(&connection.to))
+            else {
+                continue;
+            };
+            let edge = Edge::towards(to.x - from.x, to.y - from.y);
+            let from_slot = self.take_slot(&from.name, edge)?;
+            let to_slot = self.take_slot(&to.name, edge.opposite())?;
+            let (from_tile, from_entry, from_facing) = edge.slot(from_slot);
+            let (to_tile, to_entry, to_facing) = edge.opposite().slot(to_slot);
+            self.add_trigger(&from.name, &to.name, from_tile, to_entry, to_facing)?;
+            self.add_trigger(&to.name, &from.name, to_tile, from_entry, from_facing)?;
+        }
+
+        let mut signs = BTreeMap::new();
+        for (key, kind) in [(TOWN_TILE, "Town"), (DUNGEON_TILE, "Dungeon")] {
+            if let Some(tile) = tiles.tile(key) {
+                let id = self.add_sprite(key, &DynamicImage::ImageRgba8(tile.clone()))?;
+                signs.insert(key, (id, kind));
+            }
+        }
+        for node in &graph.nodes {
+            let key = match node.kind {
+                MarkerKind::Town => TOWN_TILE,
+                MarkerKind::Dungeon => DUNGEON_TILE,
+            };
+            let Some((sprite, kind)) = signs.get(key) else {
+                continue;
+            };
+            let script = vec![text_event(&format!("{kind}: {}", node.name))];
+            self.add_actor(&node.zone, &node.name, sprite, script)?;
+        }
+        Ok(())
+    }
+
+    /// Put an NPC drawn with `sprite_id` into `scene` who speaks the
+    /// dialogue when talked to
+    pub fn add_dialogue(
+        &mut self,
+        scene: &str,
+        tree: &DialogueTree,
+        sprite_id: &str,
+    ) -> Result<()> {
+        let script = self.node_events(tree, &tree.start, &mut HashSet::new());
+        self.add_actor(scene, &tree.character, sprite_id, script)
+    }
+
+    /// Write the project into `dir` and return the path of its `.gbsproj`
+    pub fn write(&self, dir: &Path) -> Result<PathBuf> {
+        let mut backgrounds = Vec::new();
+        for background in &self.backgrounds {
+            let filename = format!("{}.png", slugify(&background.name, SlugCase::Kebab));
+            save_png(
+                &background.image,
+                &dir.join("assets/backgrounds").join(&filename),
+            )?;
+            backgrounds.push(json!({
+                "id": background.id,
+                "name": background.name,
+                "filename": filename,
+                "imageWidth": background.image.width(),
+                "imageHeight": background.image.height(),
+                "width": background.image.width() / TILE_SIZE,
+                "height": background.image.height() / TILE_SIZE,
+            }));
+        }
+
+        let mut sprites = Vec::new();
+        for sprite in &self.sprites {
+            let filename = format!("{}.png", slugify(&sprite.name, SlugCase::Kebab));
+            save_png(&sprite.image, &dir.join("assets/sprites").join(&filename))?;
+            sprites.push(json!({
+                "id": sprite.id,
+                "name": sprite.name,
+                "filename": filename,
+                "numFrames": sprite.image.width() / SPRITE_SIZE,
+            }));
+        }
+
+        let scenes: Vec<Value> = self
+            .scenes
+            .iter()
+            .map(|scene| {
+                json!({
+                    "id": scene.id,
+                    "name": scene.name,
+                    "type": "TOPDOWN",
+                    "backgroundId": scene.background_id,
+                    "width": SCREEN_TILES.0,
+                    "height": SCREEN_TILES.1,
+                    "x": scene.x,
+                    "y": scene.y,
+                    "actors": scene.actors,
+                    "triggers": scene.triggers,
+                    "collisions": [],
+                    "script": [],
+                })
+            })
+            .collect();
+
+        let (start_x, start_y) = (SCREEN_TILES.0 / 2, SCREEN_TILES.1 / 2);
+        let project = json!({
+            "name": self.title,
+            "author": "",
+            "notes": "",
+            "_version": FORMAT_VERSION,
+            "backgrounds": backgrounds,
+            "spriteSheets": sprites,
+            "scenes": scenes,
+            "variables": self
+                .variables
+                .iter()
+                .enumerate()
+                .map(|(id, name)| json!({ "id": id.to_string(), "name": name }))
+                .collect::<Vec<_>>(),
+            "palettes": [],
+            "customEvents": [],
+            "music": [],
+            "sounds": [],
+            "fonts": [],
+            "avatars": [],
+            "emotes": [],
+            "settings": {
+                "startSceneId": self.scenes.first().map(|scene| scene.id.as_str()).unwrap_or_default(),
+                "startX": start_x,
+                "startY": start_y,
+                "startDirection": "down",
+                "playerSpriteSheetId": self.player_sprite.as_deref().unwrap_or_default(),
+            },
+        });
+
+        let path = dir.join(format!("{}.gbsproj", slugify(&self.title, SlugCase::Kebab)));
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        std::fs::write(&path, serde_json::to_string_pretty(&project)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    fn scene_mut(&mut self, name: &str) -> Result<&mut Scene> {
+        self.scenes
+            .iter_mut()
+            .find(|scene| scene.name == name)
+            .with_context(|| format!("No scene named '{name}'"))
+    }
+
+    fn take_slot(&mut self, scene: &str, edge: Edge) -> Result<u32> {
+        let slots = self.scene_mut(scene)?.edges.entry(edge).or_default();
+        *slots += 1;
+        Ok(*slots - 1)
+    }
+
+    fn add_trigger(
+        &mut self,
+        scene: &str,
+        target: &str,
+        (x, y): (u32, u32),
+        (entry_x, entry_y): (u32, u32),
+        facing: &str,
+    ) -> Result<()> {
+        let target_id = self.scene_mut(target)?.id.clone();
+        let scene = self.scene_mut(scene)?;
+        if scene.triggers.len() >= MAX_TRIGGERS {
+            bail!(
+                "Scene '{}' has no room for more than {MAX_TRIGGERS} triggers",
+                scene.name
+            );
+        }
+        scene.triggers.push(json!({
+            "id": uuid::Uuid::new_v4().to_string(),
+            "name": format!("To {target}"),
+            "x": x,
+            "y": y,
+            "width": 1,
+            "height": 1,
+            "script": [event("EVENT_SWITCH_SCENE", json!({
+                "sceneId": target_id,
+                "x": entry_x,
+                "y": entry_y,
+                "direction": facing,
+                "fadeSpeed": "2",
+            }))],
+        }));
+        Ok(())
+    }
+
+    /// Place an actor on the next free spot of a scene
+    fn add_actor(
+        &mut self,
+        scene: &str,
+        name: &str,
+        sprite_id: &str,
+        script: Vec<Value>,
+    ) -> Result<()> {
+        let scene = self.scene_mut(scene)?;
+        if scene.actors.len() >= MAX_ACTORS {
+            bail!(
+                "Scene '{}' has no room for more than {MAX_ACTORS} actors",
+                scene.name
+            );
+        }
+        // Rows of spots between the edge triggers, leaving the center to the player
+        let spot = scene.actors.len() as u32;
+        let (x, y) = (4 + spot % 4 * 4, 4 + spot / 4 * 3 % 12);
+        scene.actors.push(json!({
+            "id": uuid::Uuid::new_v4().to_string(),
+            "name": name,
+            "x": x,
+            "y": y,
+            "spriteSheetId": sprite_id,
+            "direction": "down",
+            "moveSpeed": 1,
+            "animSpeed": 15,
+            "script": script,
+        }));
+        Ok(())
+    }
+
+    /// Id of a story flag's variable
+    fn variable(&mut self, name: &str) -> String {
+        let index = match self.variables.iter().position(|variable| variable == name) {
+            Some(index) => index,
+            None => {
+                self.variables.push(name.to_string());
+                self.variables.len() - 1
+            }
+        };
+        index.to_string()
+    }
+
+    /// Events of a node and everything after it. A node already on the
+    /// path ends the dialogue instead of looping.
+    fn node_events(
+        &mut self,
+        tree: &DialogueTree,
+        id: &str,
+        path: &mut HashSet<String>,
+    ) -> Vec<Value> {
+        let Some(node) = tree.node(id) else {
+            return Vec::new();
+        };
+        if !path.insert(node.id.clone()) {
+            return Vec::new();
+        }
+
+        let mut events = vec![text_event(&format!("{}: {}", node.speaker, node.text))];
+        for flag in &node.set_flags {
+            events.push(self.set_flag(flag));
+        }
+
+        let branches: Vec<(String, Vec<Value>)> = node
+            .choices
+            .iter()
+            .take(MAX_MENU_OPTIONS)
+            .map(|choice| {
+                let mut branch: Vec<Value> = choice
+                    .set_flags
+                    .iter()
+                    .map(|flag| self.set_flag(flag))
+                    .collect();
+                branch.extend(self.node_events(tree, &choice.target, path));
+                let branch = match &choice.condition {
+                    Some(condition) => vec![self.guard(condition, branch)],
+                    None => branch,
+                };
+                (option_text(&choice.text), branch)
+            })
+            .collect();
+
+        let choice = self.variable(CHOICE_VARIABLE);
+        match branches.len() {
+            0 => {
+                if let Some(next) = &node.next {
+                    events.extend(self.node_events(tree, next, path));
+                }
+            }
+            1 => events.extend(branches.into_iter().flat_map(|(_, branch)| branch)),
+            2 => {
+                let mut branches = branches.into_iter();
+                let (first_text, first) = branches.next().unwrap_or_default();
+                let (second_text, second) = branches.next().unwrap_or_default();
+                events.push(event(
+                    "EVENT_CHOICE",
+                    json!({ "variable": choice, "trueText": first_text, "falseText": second_text }),
+                ));
+                events.push(event_with_children(
+                    "EVENT_IF_TRUE",
+                    json!({ "variable": choice }),
+                    json!({ "true": first, "false": second }),
+                ));
+            }
+            count => {
+                let mut args = json!({
+                    "variable": choice,
+                    "items": count,
+                    "layout": "menu",
+                    "cancelOnLastOption": false,
+                });
+                let mut cases = json!({ "variable": choice, "choices": count });
+                let mut children = json!({ "else": [] });
+                for (index, (text, branch)) in branches.into_iter().enumerate() {
+                    args[format!("option{}", index + 1)] = json!(text);
+                    cases[format!("value{index}")] = json!(index + 1);
+                    children[format!("true{index}")] = json!(branch);
+                }
+                events.push(event("EVENT_MENU", args));
+                events.push(event_with_children("EVENT_SWITCH", cases, children));
+            }
+        }
+
+        path.remove(&node.id);
+        events
+    }
+
+    fn set_flag(&mut self, flag: &str) -> Value {
+        event("EVENT_SET_TRUE", json!({ "variable": self.variable(flag) }))
+    }
+
+    /// Run `branch` only when the condition holds
+    fn guard(&mut self, condition: &Condition, branch: Vec<Value>) -> Value {
+        let (command, flag) = match condition {
+            Condition::Flag { name } => ("EVENT_IF_TRUE", name.clone()),
+            Condition::NotFlag { name } => ("EVENT_IF_FALSE", name.clone()),
+            Condition::HasItem { item } => (
+                "EVENT_IF_TRUE",
+                format!("has_{}", slugify(item, SlugCase::Snake)),
+            ),
+        };
+        event_with_children(
+            command,
+            json!({ "variable": self.variable(&flag) }),
+            json!({ "true": branch, "false": [] }),
+        )
+    }
+}
+
+fn event(command: &str, args: Value) -> Value {
+    json!({
+        "id": uuid::Uuid::new_v4().to_string(),
+        "command": command,
+        "args": args,
+    })
+}
+
+fn event_with_children(command: &str, args: Value, children: Value) -> Value {
+    let mut event = event(command, args);
+    event["children"] = children;
+    event
+}
+
+/// Text event showing `text` in as many boxes as it needs
+fn text_event(text: &str) -> Value {
+    let lines = wrap(&gb_text(text), TEXT_COLUMNS);
+    let boxes: Vec<String> = lines
+        .chunks(TEXT_LINES)
+        .map(|lines| lines.join("\n"))
+        .collect();
+    event("EVENT_TEXT", json!({ "text": boxes, "avatarId": "" }))
+}
+
+fn option_text(text: &str) -> String {
+    gb_text(text).chars().take(OPTION_COLUMNS).collect()
+}
+
+/// Replace the typographic punctuation models like to write with the ASCII
+/// the default font has
+fn gb_text(text: &str) -> String {
+    text.replace(['‘', '’'], "'")
+        .replace(['“', '”'], "\"")
+        .replace(['–', '—'], "-")
+        .replace('…', "...")
+}
+
+/// Break text into lines of at most `columns` characters, splitting words
+/// longer than a line
+fn wrap(text: &str, columns: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        while word.len() > columns {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            lines.push(word.drain(..columns).collect());
+        }
+        let word: String = word.into_iter().collect();
+        if word.is_empty() {
+            continue;
+        }
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > columns {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Index into [`SHADES`] of a pixel by its brightness
+fn shade(pixel: &Rgba<u8>) -> usize {
+    let [r, g, b, _] = pixel.0.map(f32::from);
+    let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+    3 - (luma as usize * 4 / 256).min(3)
+}
+
+fn unique_tiles(image: &RgbaImage) -> usize {
+    let mut tiles = HashSet::new();
+    for y in (0..image.height()).step_by(TILE_SIZE as usize) {
+        for x in (0..image.width()).step_by(TILE_SIZE as usize) {
+            let tile = imageops::crop_imm(image, x, y, TILE_SIZE, TILE_SIZE).to_image();
+            tiles.insert(tile.into_raw());
+        }
+    }
+    tiles.len()
+}
+
+fn save_png(image: &RgbaImage, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    image
+        .save(path)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
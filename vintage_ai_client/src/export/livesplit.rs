@@ -0,0 +1,121 @@
+//! LiveSplit split definitions
+//!
+//! A speedrun of the generated game is split at its plot beats: every story
+//! dungeon when its boss falls, in the order the main quests lead through
+//! them, and every main quest when it is completed. Dungeons no main quest
+//! mentions are run in world order before the final split. The same
+//! [`SplitDefinitions`] drive the scaffold's in-game timer through
+//! `game.splits.ron` and are written as a LiveSplit `.lss` file for runners.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use super::escape;
+use crate::game_assets::{QuestDatabase, asset_id};
+use crate::game_types::WorldData;
+
+/// LiveSplit run format version written to `.lss` files
+pub const LSS_VERSION: &str = "1.7.0";
+
+/// Category of the generated splits
+pub const DEFAULT_CATEGORY: &str = "Any%";
+
+/// Game event that ends a segment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SplitTrigger {
+    BossDefeated { boss: String },
+    QuestCompleted { quest: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Split {
+    pub name: String,
+    pub trigger: SplitTrigger,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SplitDefinitions {
+    pub game: String,
+    pub category: String,
+    pub splits: Vec<Split>,
+}
+
+impl SplitDefinitions {
+    pub fn derive(game: &str, quests: &QuestDatabase, world: &WorldData) -> Self {
+        let mut splits = Vec::new();
+        let mut visited = vec![false; world.dungeons.len()];
+        let dungeon_split = |index: usize| {
+            let dungeon = &world.dungeons[index];
+            Split {
+                name: dungeon.name.clone(),
+                trigger: SplitTrigger::BossDefeated {
+                    boss: asset_id(&dungeon.boss.name),
+                },
+            }
+        };
+
+        for quest in quests.quests.iter().filter(|quest| quest.main) {
+            for step in &quest.steps {
+                let dungeon = world.dungeons.iter().position(|dungeon| {
+                    dungeon.name.eq_ignore_ascii_case(&step.location)
+                        || dungeon.boss.name.eq_ignore_ascii_case(&step.location)
+                });
+                if let Some(index) = dungeon
+                    && !visited[index]
+                {
+                    visited[index] = true;
+                    splits.push(dungeon_split(index));
+                }
+            }
+            splits.push(Split {
+                name: quest.name.clone(),
+                trigger: SplitTrigger::QuestCompleted {
+                    quest: quest.id.clone(),
+                },
+            });
+        }
+
+        let unvisited: Vec<Split> = (0..world.dungeons.len())
+            .filter(|&index| !visited[index])
+            .map(dungeon_split)
+            .collect();
+        let at = splits.len().saturating_sub(1);
+        splits.splice(at..at, unvisited);
+
+        Self {
+            game: game.to_string(),
+            category: DEFAULT_CATEGORY.to_string(),
+            splits,
+        }
+    }
+
+    /// Serialize as a LiveSplit run without times
+    pub fn to_lss(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(xml, "<Run version=\"{LSS_VERSION}\">");
+        let _ = writeln!(xml, "  <GameIcon />");
+        let _ = writeln!(xml, "  <GameName>{}</GameName>", escape(&self.game));
+        let _ = writeln!(
+            xml,
+            "  <CategoryName>{}</CategoryName>",
+            escape(&self.category)
+        );
+        xml.push_str(
+            "  <Metadata>\n    <Run id=\"\" />\n    <Platform usesEmulator=\"False\">PC</Platform>\n    \
+            <Region />\n    <Variables />\n  </Metadata>\n",
+        );
+        xml.push_str("  <Offset>00:00:00</Offset>\n  <AttemptCount>0</AttemptCount>\n");
+        xml.push_str("  <AttemptHistory />\n  <Segments>\n");
+        for split in &self.splits {
+            let _ = writeln!(
+                xml,
+                "    <Segment>\n      <Name>{}</Name>\n      <Icon />\n      <SplitTimes>\n        \
+                <SplitTime name=\"Personal Best\" />\n      </SplitTimes>\n      \
+                <BestSegmentTime />\n      <SegmentHistory />\n    </Segment>",
+                escape(&split.name)
+            );
+        }
+        xml.push_str("  </Segments>\n  <AutoSplitterSettings />\n</Run>\n");
+        xml
+    }
+}
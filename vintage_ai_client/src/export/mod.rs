@@ -0,0 +1,26 @@
+//! Export of generated content to formats of third-party tools
+//!
+//! - [`bulk`]: selected artifacts in converted formats, with a manifest
+//! - [`gbstudio`]: world scenes, sprites and dialogue scripts as GB Studio
+//!   projects for building Game Boy ROMs
+//! - [`livesplit`]: speedrun splits as LiveSplit runs (`.lss`)
+//! - [`pico8`]: sprites, a tile map and Lua stubs as PICO-8 carts (`.p8`)
+//! - [`rpgmaker`]: artifacts in the folders and sheet layouts of RPG Maker
+//!   MV/MZ projects
+//! - [`tiled`]: dungeon floors as Tiled maps (`.tmx` and `.tmj`)
+
+pub mod bulk;
+pub mod gbstudio;
+pub mod livesplit;
+pub mod pico8;
+pub mod rpgmaker;
+pub mod tiled;
+
+/// Escape text for XML attributes and elements
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,370 @@
+//! PICO-8 cartridge export
+//!
+//! A [`Pico8Cart`] holds the 128x128 sprite sheet, the tile map and the Lua
+//! code of a `.p8` cart. Sprites are cropped, block-reduced to 8x8 or 16x16
+//! and quantized to the fixed 16-color palette; pink is kept as the
+//! transparent color so black outlines survive. Tile maps are filled from a
+//! [`TiledMap`] and may use the lower map half as long as no sprite occupies
+//! the sheet half it shares memory with; sprite flag [`SOLID_FLAG`] marks
+//! the tiles the player can't walk through. [`lua_stub`] writes the
+//! `_init`/`_update`/`_draw` skeleton for the game's core loop.
+
+use anyhow::{Result, bail};
+use image::{DynamicImage, Rgba, RgbaImage};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use super::tiled::TiledMap;
+use crate::consistency::downscaling::{DownscaleMethod, downscale_to_sprite};
+use crate::consistency::{Color, ColorPalette};
+use crate::game_types::GameConfig;
+
+/// Cart format version written to the header
+pub const CART_VERSION: u32 = 41;
+
+/// Width and height of the screen and of the sprite sheet in pixels
+pub const SCREEN_SIZE: u32 = 128;
+
+/// Width and height of one sprite in pixels
+pub const SPRITE_SIZE: u32 = 8;
+
+/// Sprites on the sheet, 16 per row
+pub const SHEET_SPRITES: usize = 256;
+
+/// First sprite whose pixels share memory with the lower map half
+pub const SHARED_SPRITES: usize = 128;
+
+/// Map size in tiles; rows from 32 on share memory with the sprite sheet
+pub const MAP_WIDTH: u32 = 128;
+pub const MAP_HEIGHT: u32 = 64;
+
+/// Map rows stored in the cart's own `__map__` section
+const MAP_ROWS: u32 = 32;
+
+/// Sprite flag bit of tiles that block movement
+pub const SOLID_FLAG: u8 = 1;
+
+/// Color left transparent by the generated `_init`
+pub const TRANSPARENT: u8 = 14;
+
+/// The PICO-8 palette by color index
+pub const PALETTE: [[u8; 3]; 16] = [
+    [0x00, 0x00, 0x00],
+    [0x1d, 0x2b, 0x53],
+    [0x7e, 0x25, 0x53],
+    [0x00, 0x87, 0x51],
+    [0xab, 0x52, 0x36],
+    [0x5f, 0x57, 0x4f],
+    [0xc2, 0xc3, 0xc7],
+    [0xff, 0xf1, 0xe8],
+    [0xff, 0x00, 0x4d],
+    [0xff, 0xa3, 0x00],
+    [0xff, 0xec, 0x27],
+    [0x00, 0xe4, 0x36],
+    [0x29, 0xad, 0xff],
+    [0x83, 0x76, 0x9c],
+    [0xff, 0x77, 0xa8],
+    [0xff, 0xcc, 0xaa],
+];
+
+#[derive(Debug, Clone)]
+pub struct Pico8Cart {
+    pub title: String,
+    pub lua: String,
+    /// First sprite of each added sprite by id
+    pub sprites: BTreeMap<String, u8>,
+    /// Color index of every sheet pixel, row by row
+    gfx: Vec<u8>,
+    used: Vec<bool>,
+    flags: Vec<u8>,
+    /// Sprite index of every map cell, row by row
+    map: Vec<u8>,
+    map_height: u32,
+}
+
+impl Pico8Cart {
+    pub fn new(title: &str) -> Self {
+        let mut used = vec![false; SHEET_SPRITES];
+        // Sprite 0 stays empty, map cells use it for "no tile"
+        used[0] = true;
+        Self {
+            title: title.to_string(),
+            lua: String::new(),
+            sprites: BTreeMap::new(),
+            gfx: vec![0; (SCREEN_SIZE * SCREEN_SIZE) as usize],
+            used,
+            flags: vec![0; SHEET_SPRITES],
+            map: vec![0; (MAP_WIDTH * MAP_HEIGHT) as usize],
+            map_height: 0,
+        }
+    }
+
+    pub fn with_lua(mut self, lua: String) -> Self {
+        self.lua = lua;
+        self
+    }
+
+    /// Quantize an image into the next free block of `size` x `size`
+    /// sprites (1 for 8x8, 2 for 16x16) and return its first sprite
+    pub fn add_sprite(&mut self, id: &str, image: &DynamicImage, size: u32) -> Result<u8> {
+        if !(1..=2).contains(&size) {
+            bail!("PICO-8 sprites are 1 or 2 tiles across, not {size}");
+        }
+        let limit = if self.map_height > MAP_ROWS {
+            SHARED_SPRITES
+        } else {
+            SHEET_SPRITES
+        };
+        let size = size as usize;
+        let Some(first) = (0..limit).find(|&first| {
+            first % 16 + size <= 16
+                && first + (size - 1) * 16 + size <= limit
+                && (0..size).all(|row| (0..size).all(|col| !self.used[first + row * 16 + col]))
+        }) else {
+            bail!("No room left on the PICO-8 sprite sheet for '{id}'");
+        };
+
+        let pixels = size as u32 * SPRITE_SIZE;
+        let sprite = downscale_to_sprite(
+            image,
+            (pixels, pixels),
+            DownscaleMethod::DominantColor,
+            &palette(),
+        )
+        .to_rgba8();
+        let (x0, y0) = (
+            first as u32 % 16 * SPRITE_SIZE,
+            first as u32 / 16 * SPRITE_SIZE,
+        );
+        for (x, y, pixel) in sprite.enumerate_pixels() {
+            self.gfx[((y0 + y) * SCREEN_SIZE + x0 + x) as usize] = color_index(pixel);
+        }
+        for row in 0..size {
+            for col in 0..size {
+                self.used[first + row * 16 + col] = true;
+            }
+        }
+        self.sprites.insert(id.to_string(), first as u8);
+        Ok(first as u8)
+    }
+
+    /// Set the flag bits of a sprite, e.g. [`SOLID_FLAG`] for walls
+    pub fn set_flags(&mut self, sprite: u8, flags: u8) {
+        self.flags[sprite as usize] = flags;
+    }
+
+    /// Fill the map from a Tiled map's tile layers, later layers drawing
+    /// over earlier ones, with `tiles` giving the sprite for each global
+    /// tile id. Unmapped tiles stay empty.
+    pub fn set_map(&mut self, map: &TiledMap, tiles: &BTreeMap<u32, u8>) -> Result<()> {
+        if map.width > MAP_WIDTH || map.height > MAP_HEIGHT {
+            bail!(
+                "{}x{} map doesn't fit the {MAP_WIDTH}x{MAP_HEIGHT} PICO-8 map",
+                map.width,
+                map.height
+            );
+        }
+        if map.height > MAP_ROWS && self.used[SHARED_SPRITES..].iter().any(|&used| used) {
+            bail!(
+                "Map is {} rows tall, but the sheet half shared with map rows \
+                {MAP_ROWS}+ holds sprites",
+                map.height
+            );
+        }
+
+        self.map.fill(0);
+        for layer in &map.layers {
+            for (index, &gid) in layer.data.iter().enumerate() {
+                let (x, y) = (index as u32 % map.width, index as u32 / map.width);
+                if let Some(&sprite) = tiles.get(&gid) {
+                    self.map[(y * MAP_WIDTH + x) as usize] = sprite;
+                }
+            }
+        }
+        self.map_height = map.height;
+        Ok(())
+    }
+
+    /// The sprite sheet in PICO-8 colors, e.g. for a preview
+    pub fn sheet_image(&self) -> RgbaImage {
+        let mut image = RgbaImage::new(SCREEN_SIZE, SCREEN_SIZE);
+        for (index, &color) in self.sheet().iter().enumerate() {
+            let [r, g, b] = PALETTE[color as usize];
+            let (x, y) = (index as u32 % SCREEN_SIZE, index as u32 / SCREEN_SIZE);
+            image.put_pixel(x, y, Rgba([r, g, b, 255]));
+        }
+        image
+    }
+
+    /// Serialize as a `.p8` cart
+    pub fn to_p8(&self) -> String {
+        let mut cart =
+            format!("pico-8 cartridge // http://www.pico-8.com\nversion {CART_VERSION}\n");
+        cart.push_str("__lua__\n");
+        let _ = writeln!(cart, "-- {}", self.title);
+        cart.push_str(&self.lua);
+        if !self.lua.ends_with('\n') {
+            cart.push('\n');
+        }
+
+        cart.push_str("__gfx__\n");
+        for row in self.sheet().chunks(SCREEN_SIZE as usize) {
+            for &color in row {
+                let _ = write!(cart, "{color:x}");
+            }
+            cart.push('\n');
+        }
+
+        cart.push_str("__gff__\n");
+        for row in self.flags.chunks(SHEET_SPRITES / 2) {
+            for &flags in row {
+                let _ = write!(cart, "{flags:02x}");
+            }
+            cart.push('\n');
+        }
+
+        cart.push_str("__map__\n");
+        for row in self.map.chunks(MAP_WIDTH as usize).take(MAP_ROWS as usize) {
+            for &sprite in row {
+                let _ = write!(cart, "{sprite:02x}");
+            }
+            cart.push('\n');
+        }
+        cart
+    }
+
+    /// Sheet pixels with the lower map rows stored in the shared half,
+    /// two tiles per byte pair of pixels, low nibble first
+    fn sheet(&self) -> Vec<u8> {
+        let mut sheet = self.gfx.clone();
+        if self.map_height > MAP_ROWS {
+            let shared = (SCREEN_SIZE * SCREEN_SIZE / 2) as usize;
+            let lower = &self.map[(MAP_ROWS * MAP_WIDTH) as usize..];
+            for (offset, &sprite) in lower.iter().enumerate() {
+                sheet[shared + offset * 2] = sprite & 0x0f;
+                sheet[shared + offset * 2 + 1] = sprite >> 4;
+            }
+        }
+        sheet
+    }
+}
+
+/// Lua skeleton for the core loop of the game: exploration on the map,
+/// battles in the game's combat style and a menu, switched by `mode`
+pub fn lua_stub(config: &GameConfig) -> String {
+    let mut lua = String::new();
+    let _ = writeln!(lua, "-- {}", config.tagline);
+    let _ = writeln!(lua, "-- {} / {}", config.genre, config.setting);
+    if !config.reference_games.is_empty() {
+        let _ = writeln!(lua, "-- inspired by {}", config.reference_games.join(", "));
+    }
+    let _ = writeln!(lua, "-- combat: {}", config.combat_system.style);
+    for feature in &config.combat_system.features {
+        let _ = writeln!(lua, "--   {feature}");
+    }
+    let _ = writeln!(
+        lua,
+        r#"
+function _init()
+ palt(0,false)
+ palt({TRANSPARENT},true)
+ mode="explore"
+ player={{x=8,y=8,spr=1}}
+ party={{}}
+end
+
+function _update()
+ if mode=="explore" then
+  update_explore()
+ elseif mode=="battle" then
+  update_battle()
+ elseif mode=="menu" then
+  update_menu()
+ end
+end
+
+function _draw()
+ cls()
+ if mode=="explore" then
+  draw_explore()
+ elseif mode=="battle" then
+  draw_battle()
+ elseif mode=="menu" then
+  draw_menu()
+ end
+end
+
+function update_explore()
+ local dx,dy=0,0
+ if btn(0) then dx=-1 end
+ if btn(1) then dx=1 end
+ if btn(2) then dy=-1 end
+ if btn(3) then dy=1 end
+ local nx,ny=player.x+dx,player.y+dy
+ if not fget(mget(nx\8,ny\8),0) then
+  player.x,player.y=nx,ny
+ end
+ if btnp(5) then mode="menu" end
+ -- todo: random encounters start a battle
+end
+
+function draw_explore()
+ camera(player.x-64,player.y-64)
+ map(0,0,0,0,128,64)
+ spr(player.spr,player.x,player.y)
+ camera()
+end
+
+-- {} battle loop
+function update_battle()
+ -- todo: pick commands, resolve turns, leave on victory or escape
+ if btnp(5) then mode="explore" end
+end
+
+function draw_battle()
+ print("battle",52,60,7)
+end
+
+function update_menu()
+ if btnp(5) then mode="explore" end
+end
+
+function draw_menu()
+ rectfill(8,8,119,119,1)
+ print("{}",16,16,7)
+ for i,member in ipairs(party) do
+  print(member.name,16,24+i*8,6)
+ end
+end"#,
+        config.combat_system.style,
+        config.name.to_lowercase().replace('"', "'"),
+    );
+    lua
+}
+
+/// The PICO-8 colors to quantize against, without the transparent one
+fn palette() -> ColorPalette {
+    ColorPalette {
+        name: "PICO-8".to_string(),
+        primary_colors: PALETTE
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| index != TRANSPARENT as usize)
+            .map(|(_, &[r, g, b])| Color::new(r, g, b))
+            .collect(),
+        secondary_colors: Vec::new(),
+        accent_colors: Vec::new(),
+        transparency_color: Color::transparent(),
+        max_colors: PALETTE.len() as u32,
+    }
+}
+
+fn color_index(pixel: &Rgba<u8>) -> u8 {
+    if pixel[3] == 0 {
+        return TRANSPARENT;
+    }
+    PALETTE
+        .iter()
+        .position(|color| color[..] == pixel.0[..3])
+        .unwrap_or(0) as u8
+}
@@ -0,0 +1,503 @@
+//! RPG Maker MV/MZ asset layout
+//!
+//! Arranges generated assets the way an RPG Maker MV or MZ project expects
+//! them, so the export folder can be merged into a project as is. Each
+//! artifact is given an [`RpgMakerAsset`] role from an `rpgmaker-` tag
+//! (`rpgmaker-face`, `rpgmaker-a2`, ...) or, failing that, from the words in
+//! its tags and name. Images are laid out on the engine's 48px grid:
+//!
+//! - characters become `$`-prefixed single-character sheets of 3 frames by
+//!   4 directions; a source that already has that layout is only scaled
+//! - faces are fitted into the first 144px slot of a 4x2 face sheet
+//! - tilesets are cut into tiles of the source tile size, scaled to 48px
+//!   and placed on the chosen A1-A5 or B-E sheet; each tile fills a whole
+//!   autotile block on the A sheets
+//! - enemies, parallaxes and pictures are written as PNG unchanged
+//!
+//! Audio is written as OGG into `bgm`, `bgs`, `me` or `se`; MV also gets an
+//! M4A copy for platforms without OGG support. Converting audio needs
+//! `ffmpeg` on the path.
+
+use anyhow::{Context, Result, bail};
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use super::bulk::{AssetType, copy, transcode_audio};
+
+/// Tile size of both engines in pixels
+pub const TILE_SIZE: u32 = 48;
+
+/// Size of one face in a face sheet
+pub const FACE_SIZE: u32 = 144;
+
+/// Faces across and down a face sheet
+pub const FACE_SHEET: (u32, u32) = (4, 2);
+
+/// Frames across and directions down a single-character sheet
+pub const CHARACTER_FRAMES: (u32, u32) = (3, 4);
+
+/// Tag prefix that sets an artifact's role explicitly
+pub const ROLE_TAG_PREFIX: &str = "rpgmaker-";
+
+/// Source tile size assumed for tilesets, the 16-bit era's
+pub const DEFAULT_SOURCE_TILE_SIZE: u32 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RpgMakerVersion {
+    Mv,
+    #[default]
+    Mz,
+}
+
+/// Options of an RPG Maker export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RpgMakerLayout {
+    pub version: RpgMakerVersion,
+    /// Size of one tile in the generated tilesets
+    pub source_tile_size: u32,
+}
+
+impl Default for RpgMakerLayout {
+    fn default() -> Self {
+        Self {
+            version: RpgMakerVersion::default(),
+            source_tile_size: DEFAULT_SOURCE_TILE_SIZE,
+        }
+    }
+}
+
+/// Tileset sheet of the engine's tileset editor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TilesetSheet {
+    /// Animated water and waterfalls
+    A1,
+    /// Ground
+    A2,
+    /// Building walls and roofs
+    A3,
+    /// Walls with their tops
+    A4,
+    /// Plain ground tiles
+    A5,
+    /// Upper layer objects
+    B,
+    C,
+    D,
+    E,
+}
+
+impl TilesetSheet {
+    pub const ALL: [TilesetSheet; 9] = [
+        TilesetSheet::A1,
+        TilesetSheet::A2,
+        TilesetSheet::A3,
+        TilesetSheet::A4,
+        TilesetSheet::A5,
+        TilesetSheet::B,
+        TilesetSheet::C,
+        TilesetSheet::D,
+        TilesetSheet::E,
+    ];
+
+    /// Suffix of the sheet's file name, e.g. `A2` in `Outside_A2.png`
+    pub fn suffix(self) -> &'static str {
+        match self {
+            TilesetSheet::A1 => "A1",
+            TilesetSheet::A2 => "A2",
+            TilesetSheet::A3 => "A3",
+            TilesetSheet::A4 => "A4",
+            TilesetSheet::A5 => "A5",
+            TilesetSheet::B => "B",
+            TilesetSheet::C => "C",
+            TilesetSheet::D => "D",
+            TilesetSheet::E => "E",
+        }
+    }
+
+    /// Sheet size in tiles
+    pub fn size(self) -> (u32, u32) {
+        match self {
+            TilesetSheet::A1 | TilesetSheet::A2 => (16, 12),
+            TilesetSheet::A3 => (16, 8),
+            TilesetSheet::A4 => (16, 15),
+            TilesetSheet::A5 => (8, 16),
+            TilesetSheet::B | TilesetSheet::C | TilesetSheet::D | TilesetSheet::E => (16, 16),
+        }
+    }
+
+    /// Tile rectangles (x, y, width, height) one source tile fills, in the
+    /// order tiles are placed
+    pub fn slots(self) -> Vec<Vec<(u32, u32, u32, u32)>> {
+        match self {
+            // Three animation frames and the waterfall or decoration block
+            // of each kind sit side by side in a half row
+            TilesetSheet::A1 => (0..4)
+                .flat_map(|row| (0..2).map(move |half| (row, half)))
+                .map(|(row, half)| vec![(half * 8, row * 3, 8, 3)])
+                .collect(),
+            TilesetSheet::A2 => blocks(8, 4, 2, 3, 0),
+            TilesetSheet::A3 => blocks(8, 4, 2, 2, 0),
+            // A wall top of 2x3 above its 2x2 side, three rows of them
+            TilesetSheet::A4 => (0..3)
+                .flat_map(|row| (0..8).map(move |column| (row, column)))
+                .map(|(row, column)| {
+                    vec![(column * 2, row * 5, 2, 3), (column * 2, row * 5 + 3, 2, 2)]
+                })
+                .collect(),
+            TilesetSheet::A5 => blocks(8, 16, 1, 1, 0),
+            // The left half is numbered before the right one
+            TilesetSheet::B | TilesetSheet::C | TilesetSheet::D | TilesetSheet::E => {
+                let mut slots = blocks(8, 16, 1, 1, 0);
+                slots.extend(blocks(8, 16, 1, 1, 8));
+                slots
+            }
+        }
+    }
+
+    fn from_suffix(suffix: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|sheet| sheet.suffix().eq_ignore_ascii_case(suffix))
+    }
+}
+
+/// `columns` x `rows` blocks of `width` x `height` tiles, row by row,
+/// starting `offset` tiles from the left
+fn blocks(
+    columns: u32,
+    rows: u32,
+    width: u32,
+    height: u32,
+    offset: u32,
+) -> Vec<Vec<(u32, u32, u32, u32)>> {
+    (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| (row, column)))
+        .map(|(row, column)| vec![(offset + column * width, row * height, width, height)])
+        .collect()
+}
+
+/// What an artifact is to RPG Maker, and so where it goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RpgMakerAsset {
+    Character,
+    Face,
+    Enemy,
+    Tileset(TilesetSheet),
+    Parallax,
+    Picture,
+    Bgm,
+    Bgs,
+    Me,
+    Se,
+}
+
+/// Words in an artifact's name or tags that give away its role
+const CHARACTER_WORDS: &[&str] = &["character", "sprite", "hero", "npc", "player", "walk"];
+const FACE_WORDS: &[&str] = &["face", "portrait"];
+const ENEMY_WORDS: &[&str] = &["enemy", "monster", "boss", "battler"];
+const TILESET_WORDS: &[&str] = &["tileset", "tiles", "tile"];
+const PARALLAX_WORDS: &[&str] = &["background", "parallax", "panorama", "sky"];
+const ME_WORDS: &[&str] = &["fanfare", "victory", "jingle", "gameover"];
+const SE_WORDS: &[&str] = &["sfx", "sound", "effect"];
+const BGS_WORDS: &[&str] = &["ambient", "ambience", "bgs"];
+
+/// Words that pick the tileset sheet when no tag does
+const TILESET_SHEET_WORDS: &[(TilesetSheet, &[&str])] = &[
+    (
+        TilesetSheet::A1,
+        &["water", "sea", "lake", "river", "ocean"],
+    ),
+    (
+        TilesetSheet::A2,
+        &["ground", "grass", "dirt", "sand", "field"],
+    ),
+    (TilesetSheet::A3, &["roof", "building", "house"]),
+    (TilesetSheet::A4, &["wall", "cliff"]),
+    (
+        TilesetSheet::B,
+        &["object", "objects", "decor", "prop", "props", "furniture"],
+    ),
+];
+
+impl RpgMakerAsset {
+    /// Role from an `rpgmaker-` tag, else from the words of the tags and the
+    /// name. Images default to pictures and audio to background music; data
+    /// and other files have no place in the layout.
+    pub fn classify(asset_type: AssetType, name: &str, tags: &[&str]) -> Option<Self> {
+        if let Some(asset) = tags
+            .iter()
+            .filter_map(|tag| tag.strip_prefix(ROLE_TAG_PREFIX))
+            .find_map(Self::from_tag)
+        {
+            return Some(asset);
+        }
+
+        let words: Vec<String> = tags
+            .iter()
+            .copied()
+            .chain([name])
+            .flat_map(|text| text.split(|c: char| !c.is_ascii_alphanumeric()))
+            .map(str::to_ascii_lowercase)
+            .collect();
+        let any =
+            |candidates: &[&str]| words.iter().any(|word| candidates.contains(&word.as_str()));
+
+        match asset_type {
+            AssetType::Image if any(FACE_WORDS) => Some(RpgMakerAsset::Face),
+            AssetType::Image if any(TILESET_WORDS) => {
+                let sheet = TILESET_SHEET_WORDS
+                    .iter()
+                    .find(|(_, candidates)| any(candidates))
+                    .map_or(TilesetSheet::A5, |(sheet, _)| *sheet);
+                Some(RpgMakerAsset::Tileset(sheet))
+            }
+            AssetType::Image if any(ENEMY_WORDS) => Some(RpgMakerAsset::Enemy),
+            AssetType::Image if any(CHARACTER_WORDS) => Some(RpgMakerAsset::Character),
+            AssetType::Image if any(PARALLAX_WORDS) => Some(RpgMakerAsset::Parallax),
+            AssetType::Image => Some(RpgMakerAsset::Picture),
+            AssetType::Audio if any(ME_WORDS) => Some(RpgMakerAsset::Me),
+            AssetType::Audio if any(SE_WORDS) => Some(RpgMakerAsset::Se),
+            AssetType::Audio if any(BGS_WORDS) => Some(RpgMakerAsset::Bgs),
+            AssetType::Audio => Some(RpgMakerAsset::Bgm),
+            AssetType::Data | AssetType::Other => None,
+        }
+    }
+
+    fn from_tag(tag: &str) -> Option<Self> {
+        match tag.to_ascii_lowercase().as_str() {
+            "character" => Some(RpgMakerAsset::Character),
+            "face" => Some(RpgMakerAsset::Face),
+            "enemy" => Some(RpgMakerAsset::Enemy),
+            "tileset" => Some(RpgMakerAsset::Tileset(TilesetSheet::A5)),
+            "parallax" => Some(RpgMakerAsset::Parallax),
+            "picture" => Some(RpgMakerAsset::Picture),
+            "bgm" => Some(RpgMakerAsset::Bgm),
+            "bgs" => Some(RpgMakerAsset::Bgs),
+            "me" => Some(RpgMakerAsset::Me),
+            "se" => Some(RpgMakerAsset::Se),
+            sheet => TilesetSheet::from_suffix(sheet).map(RpgMakerAsset::Tileset),
+        }
+    }
+
+    /// Folder of the project the asset goes into
+    pub fn folder(self) -> &'static str {
+        match self {
+            RpgMakerAsset::Character => "img/characters",
+            RpgMakerAsset::Face => "img/faces",
+            RpgMakerAsset::Enemy => "img/enemies",
+            RpgMakerAsset::Tileset(_) => "img/tilesets",
+            RpgMakerAsset::Parallax => "img/parallaxes",
+            RpgMakerAsset::Picture => "img/pictures",
+            RpgMakerAsset::Bgm => "audio/bgm",
+            RpgMakerAsset::Bgs => "audio/bgs",
+            RpgMakerAsset::Me => "audio/me",
+            RpgMakerAsset::Se => "audio/se",
+        }
+    }
+
+    /// File name the engine finds the asset under, without extension
+    pub fn file_stem(self, name: &str) -> String {
+        match self {
+            // `$` marks a sheet holding a single character
+            RpgMakerAsset::Character => format!("${name}"),
+            RpgMakerAsset::Tileset(sheet) => format!("{name}_{}", sheet.suffix()),
+            _ => name.to_string(),
+        }
+    }
+}
+
+impl RpgMakerLayout {
+    /// Write the artifact `name` from `source` into `out_dir` as `asset`,
+    /// returning the files written relative to `out_dir`
+    pub fn write(
+        &self,
+        asset: RpgMakerAsset,
+        name: &str,
+        source: &Path,
+        out_dir: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        if !source.is_file() {
+            bail!("{} does not exist", source.display());
+        }
+        let stem = Path::new(asset.folder()).join(asset.file_stem(name));
+        let mut files = Vec::new();
+        match asset {
+            RpgMakerAsset::Bgm | RpgMakerAsset::Bgs | RpgMakerAsset::Me | RpgMakerAsset::Se => {
+                files.push(stem.with_extension("ogg"));
+                if self.version == RpgMakerVersion::Mv {
+                    files.push(stem.with_extension("m4a"));
+                }
+                for file in &files {
+                    let target = out_dir.join(file);
+                    create_parent(&target)?;
+                    let same_extension = source.extension().map(|ext| ext.to_ascii_lowercase())
+                        == target.extension().map(|ext| ext.to_ascii_lowercase());
+                    if same_extension {
+                        copy(source, &target)?;
+                    } else {
+                        transcode_audio(source, &target)?;
+                    }
+                }
+            }
+            _ => {
+                let file = stem.with_extension("png");
+                let target = out_dir.join(&file);
+                create_parent(&target)?;
+                let image = image::open(source)
+                    .with_context(|| format!("Failed to open {}", source.display()))?;
+                let image = match asset {
+                    RpgMakerAsset::Character => character_sheet(&image),
+                    RpgMakerAsset::Face => face_sheet(&image),
+                    RpgMakerAsset::Tileset(sheet) => {
+                        tileset_sheet(&image, self.source_tile_size, sheet)?
+                    }
+                    _ => image.to_rgba8(),
+                };
+                image
+                    .save_with_format(&target, image::ImageFormat::Png)
+                    .with_context(|| format!("Failed to write {}", target.display()))?;
+                files.push(file);
+            }
+        }
+        Ok(files)
+    }
+}
+
+fn create_parent(target: &Path) -> Result<()> {
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    Ok(())
+}
+
+/// Single-character sheet. An image shaped like one (3 frames by 4
+/// directions) is scaled to the 48px grid; any other image is taken as a
+/// single pose and stands in every frame.
+pub fn character_sheet(image: &DynamicImage) -> RgbaImage {
+    let (frames, directions) = CHARACTER_FRAMES;
+    let (width, height) = (frames * TILE_SIZE, directions * TILE_SIZE);
+    let rgba = image.to_rgba8();
+    if rgba.width() * directions == rgba.height() * frames {
+        return scale(&rgba, width, height);
+    }
+
+    // Characters stand on the bottom edge of their cell
+    let pose = fit(&rgba, TILE_SIZE, TILE_SIZE);
+    let x = (TILE_SIZE - pose.width()) / 2;
+    let y = TILE_SIZE - pose.height();
+    let mut sheet = RgbaImage::new(width, height);
+    for row in 0..directions {
+        for column in 0..frames {
+            imageops::overlay(
+                &mut sheet,
+                &pose,
+                i64::from(column * TILE_SIZE + x),
+                i64::from(row * TILE_SIZE + y),
+            );
+        }
+    }
+    sheet
+}
+
+/// Face sheet with the image centered in the first slot
+pub fn face_sheet(image: &DynamicImage) -> RgbaImage {
+    let face = fit(&image.to_rgba8(), FACE_SIZE, FACE_SIZE);
+    let mut sheet = RgbaImage::new(FACE_SHEET.0 * FACE_SIZE, FACE_SHEET.1 * FACE_SIZE);
+    imageops::overlay(
+        &mut sheet,
+        &face,
+        i64::from((FACE_SIZE - face.width()) / 2),
+        i64::from((FACE_SIZE - face.height()) / 2),
+    );
+    sheet
+}
+
+/// The tiles of `image`, `tile_size` pixels each, scaled to 48px and laid
+/// out on `sheet` row by row
+pub fn tileset_sheet(
+    image: &DynamicImage,
+    tile_size: u32,
+    sheet: TilesetSheet,
+) -> Result<RgbaImage> {
+    let rgba = image.to_rgba8();
+    if tile_size == 0
+        || !rgba.width().is_multiple_of(tile_size)
+        || !rgba.height().is_multiple_of(tile_size)
+    {
+        bail!(
+            "A {}x{} image doesn't divide into {tile_size}px tiles",
+            rgba.width(),
+            rgba.height()
+        );
+    }
+    let (columns, rows) = (rgba.width() / tile_size, rgba.height() / tile_size);
+    let slots = sheet.slots();
+    if (columns * rows) as usize > slots.len() {
+        bail!(
+            "{} tiles don't fit on the {} sheet, which takes {}",
+            columns * rows,
+            sheet.suffix(),
+            slots.len()
+        );
+    }
+
+    let (width, height) = sheet.size();
+    let mut out = RgbaImage::new(width * TILE_SIZE, height * TILE_SIZE);
+    let tiles = (0..rows).flat_map(|row| (0..columns).map(move |column| (column, row)));
+    for ((column, row), rects) in tiles.zip(slots) {
+        let tile = imageops::crop_imm(
+            &rgba,
+            column * tile_size,
+            row * tile_size,
+            tile_size,
+            tile_size,
+        )
+        .to_image();
+        let tile = scale(&tile, TILE_SIZE, TILE_SIZE);
+        for (x, y, w, h) in rects {
+            for dy in 0..h {
+                for dx in 0..w {
+                    imageops::replace(
+                        &mut out,
+                        &tile,
+                        i64::from((x + dx) * TILE_SIZE),
+                        i64::from((y + dy) * TILE_SIZE),
+                    );
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Scale to fit within `width` x `height` keeping the aspect ratio, by a
+/// whole factor when enlarging so pixel art stays crisp
+fn fit(image: &RgbaImage, width: u32, height: u32) -> RgbaImage {
+    let (source_width, source_height) = image.dimensions();
+    if source_width <= width && source_height <= height {
+        let factor = (width / source_width.max(1)).min(height / source_height.max(1));
+        return scale(image, source_width * factor, source_height * factor);
+    }
+    let ratio = (width as f32 / source_width as f32).min(height as f32 / source_height as f32);
+    let target_width = ((source_width as f32 * ratio).round() as u32).clamp(1, width);
+    let target_height = ((source_height as f32 * ratio).round() as u32).clamp(1, height);
+    scale(image, target_width, target_height)
+}
+
+/// Resize with nearest-neighbour when enlarging and a smoothing filter when
+/// shrinking
+fn scale(image: &RgbaImage, width: u32, height: u32) -> RgbaImage {
+    if image.dimensions() == (width, height) {
+        return image.clone();
+    }
+    let filter = if width >= image.width() && height >= image.height() {
+        FilterType::Nearest
+    } else {
+        FilterType::Triangle
+    };
+    imageops::resize(image, width, height, filter)
+}
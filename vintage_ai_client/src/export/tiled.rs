@@ -0,0 +1,675 @@
+//! Tiled map export
+//!
+//! Generated dungeon floors are turned into [`TiledMap`]s: every mini-map
+//! room becomes a walled block of floor tiles, rooms that lead into each
+//! other get a doorway, and an object layer marks the spawn points (player
+//! start, enemies, chests, stairs and the boss). Tiles reference a packed
+//! sprite sheet through a [`TilesetRef`]. Maps are written as TMX (XML) and
+//! TMJ (JSON) and can be read back from both, so edits made in Tiled can be
+//! re-imported.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use super::escape;
+use crate::consistency::SpriteSheetMetadata;
+use crate::game_types::FloorData;
+use crate::maps::{MiniMapFloor, RoomKind};
+
+/// Map format version written to both formats
+pub const TILED_VERSION: &str = "1.10";
+
+/// Tiles across a room block, including its walls
+pub const ROOM_TILES: u32 = 6;
+
+/// Name of the object layer holding the spawn points
+pub const SPAWN_LAYER: &str = "spawns";
+
+/// Sprite sheet the map's tiles are cut from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TilesetRef {
+    /// Global id of the sheet's first tile
+    pub first_gid: u32,
+    pub name: String,
+    /// Sheet path relative to the map file
+    pub image: String,
+    pub image_width: u32,
+    pub image_height: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub spacing: u32,
+    pub margin: u32,
+    pub columns: u32,
+    pub tile_count: u32,
+}
+
+impl TilesetRef {
+    /// Reference a sheet packed by
+    /// [`pack_sprites`](crate::consistency::sprite_sheets::pack_sprites)
+    ///
+    /// Frames are laid out on a grid of the first frame's size, with the
+    /// packing padding as both spacing and margin. Returns `None` for an
+    /// empty sheet.
+    pub fn from_sheet(
+        name: &str,
+        image: &str,
+        size: (u32, u32),
+        metadata: &SpriteSheetMetadata,
+    ) -> Option<Self> {
+        let frame = metadata.frames.values().next()?;
+        let padding = metadata.padding;
+        let columns = (size.0.saturating_sub(padding)) / (frame.width + padding).max(1);
+        let rows = (size.1.saturating_sub(padding)) / (frame.height + padding).max(1);
+        Some(Self {
+            first_gid: 1,
+            name: name.to_string(),
+            image: image.to_string(),
+            image_width: size.0,
+            image_height: size.1,
+            tile_width: frame.width,
+            tile_height: frame.height,
+            spacing: padding,
+            margin: padding,
+            columns,
+            tile_count: columns * rows,
+        })
+    }
+
+    /// Global id of a tile index within the sheet
+    pub fn gid(&self, index: u32) -> u32 {
+        self.first_gid + index
+    }
+}
+
+/// Sheet tile indices used to build a floor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FloorTiles {
+    pub floor: u32,
+    pub wall: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TileLayer {
+    pub name: String,
+    /// Global tile ids row by row, 0 for no tile
+    pub data: Vec<u32>,
+}
+
+/// Point object on the spawn layer, in pixels
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpawnPoint {
+    pub id: u32,
+    pub name: String,
+    /// Object type, e.g. `player_start`, `enemy` or `chest`
+    pub kind: String,
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TiledMap {
+    /// Size in tiles
+    pub width: u32,
+    pub height: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub tilesets: Vec<TilesetRef>,
+    pub layers: Vec<TileLayer>,
+    pub spawns: Vec<SpawnPoint>,
+}
+
+impl TiledMap {
+    /// Build a map from a dungeon floor's mini-map
+    ///
+    /// Chests take the floor's treasures and enemy spawns its encounters in
+    /// turn.
+    pub fn from_floor(
+        floor: &MiniMapFloor,
+        data: &FloorData,
+        tileset: &TilesetRef,
+        tiles: FloorTiles,
+    ) -> Self {
+        let min_x = floor.rooms.iter().map(|r| r.x).min().unwrap_or(0);
+        let min_y = floor.rooms.iter().map(|r| r.y).min().unwrap_or(0);
+        let max_x = floor.rooms.iter().map(|r| r.x).max().unwrap_or(0);
+        let max_y = floor.rooms.iter().map(|r| r.y).max().unwrap_or(0);
+        let width = (max_x - min_x + 1) as u32 * ROOM_TILES;
+        let height = (max_y - min_y + 1) as u32 * ROOM_TILES;
+
+        let mut ground = vec![0; (width * height) as usize];
+        let mut walls = vec![0; (width * height) as usize];
+        let origin = |x: i32, y: i32| {
+            (
+                (x - min_x) as u32 * ROOM_TILES,
+                (y - min_y) as u32 * ROOM_TILES,
+            )
+        };
+        for room in &floor.rooms {
+            let (left, top) = origin(room.x, room.y);
+            for dy in 0..ROOM_TILES {
+                for dx in 0..ROOM_TILES {
+                    let edge = dx == 0 || dy == 0 || dx == ROOM_TILES - 1 || dy == ROOM_TILES - 1;
+                    let index = ((top + dy) * width + left + dx) as usize;
+                    ground[index] = tileset.gid(tiles.floor);
+                    if edge {
+                        walls[index] = tileset.gid(tiles.wall);
+                    }
+                }
+            }
+        }
+
+        // Open a doorway through both walls between connected rooms
+        for room in &floor.rooms {
+            let Some(from) = room.from.and_then(|i| floor.rooms.get(i)) else {
+                continue;
+            };
+            let (ax, ay) = origin(from.x, from.y);
+            let (bx, by) = origin(room.x, room.y);
+            let middle = ROOM_TILES / 2;
+            let doorway: Vec<(u32, u32)> = if ay == by {
+                let left = ax.max(bx);
+                vec![(left - 1, ay + middle), (left, ay + middle)]
+            } else {
+                let top = ay.max(by);
+                vec![(ax + middle, top - 1), (ax + middle, top)]
+            };
+            for (x, y) in doorway {
+                walls[(y * width + x) as usize] = 0;
+            }
+        }
+
+        let mut spawns = Vec::new();
+        let mut treasures = data.treasures.iter();
+        let mut encounters = data.encounters.iter().cycle();
+        for room in &floor.rooms {
+            let (left, top) = origin(room.x, room.y);
+            let (kind, name) = match room.kind {
+                RoomKind::Entrance => ("player_start", "Entrance".to_string()),
+                RoomKind::Treasure => (
+                    "chest",
+                    treasures
+                        .next()
+                        .cloned()
+                        .unwrap_or_else(|| "Chest".to_string()),
+                ),
+                RoomKind::Stairs => ("stairs", "Stairs down".to_string()),
+                RoomKind::Boss => ("boss", "Boss".to_string()),
+                RoomKind::Room => match encounters.next() {
+                    Some(enemy) => ("enemy", enemy.clone()),
+                    None => continue,
+                },
+            };
+            spawns.push(SpawnPoint {
+                id: spawns.len() as u32 + 1,
+                name,
+                kind: kind.to_string(),
+                x: ((left + ROOM_TILES / 2) * tileset.tile_width) as f32,
+                y: ((top + ROOM_TILES / 2) * tileset.tile_height) as f32,
+            });
+        }
+
+        Self {
+            width,
+            height,
+            tile_width: tileset.tile_width,
+            tile_height: tileset.tile_height,
+            tilesets: vec![tileset.clone()],
+            layers: vec![
+                TileLayer {
+                    name: "ground".to_string(),
+                    data: ground,
+                },
+                TileLayer {
+                    name: "walls".to_string(),
+                    data: walls,
+                },
+            ],
+            spawns,
+        }
+    }
+
+    pub fn to_tmj(&self) -> Result<String> {
+        let mut layers: Vec<TmjLayer> = self
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(index, layer)| TmjLayer::TileLayer {
+                id: index as u32 + 1,
+                name: layer.name.clone(),
+                width: self.width,
+                height: self.height,
+                data: layer.data.clone(),
+            })
+            .collect();
+        layers.push(TmjLayer::ObjectGroup {
+            id: self.layers.len() as u32 + 1,
+            name: SPAWN_LAYER.to_string(),
+            objects: self
+                .spawns
+                .iter()
+                .map(|spawn| TmjObject {
+                    id: spawn.id,
+                    name: spawn.name.clone(),
+                    kind: spawn.kind.clone(),
+                    x: spawn.x,
+                    y: spawn.y,
+                    point: true,
+                })
+                .collect(),
+        });
+
+        let map = Tmj {
+            kind: "map".to_string(),
+            version: TILED_VERSION.to_string(),
+            orientation: "orthogonal".to_string(),
+            renderorder: "right-down".to_string(),
+            infinite: false,
+            width: self.width,
+            height: self.height,
+            tilewidth: self.tile_width,
+            tileheight: self.tile_height,
+            nextlayerid: layers.len() as u32 + 1,
+            nextobjectid: self.next_object_id(),
+            layers,
+            tilesets: self
+                .tilesets
+                .iter()
+                .map(|tileset| TmjTileset {
+                    firstgid: tileset.first_gid,
+                    name: tileset.name.clone(),
+                    image: tileset.image.clone(),
+                    imagewidth: tileset.image_width,
+                    imageheight: tileset.image_height,
+                    tilewidth: tileset.tile_width,
+                    tileheight: tileset.tile_height,
+                    spacing: tileset.spacing,
+                    margin: tileset.margin,
+                    columns: tileset.columns,
+                    tilecount: tileset.tile_count,
+                })
+                .collect(),
+        };
+        Ok(serde_json::to_string_pretty(&map)?)
+    }
+
+    pub fn from_tmj(json: &str) -> Result<Self> {
+        let map: Tmj = serde_json::from_str(json).context("Invalid TMJ map")?;
+        let mut layers = Vec::new();
+        let mut spawns = Vec::new();
+        for layer in map.layers {
+            match layer {
+                TmjLayer::TileLayer { name, data, .. } => layers.push(TileLayer { name, data }),
+                TmjLayer::ObjectGroup { name, objects, .. } if name == SPAWN_LAYER => {
+                    spawns.extend(objects.into_iter().map(|object| SpawnPoint {
+                        id: object.id,
+                        name: object.name,
+                        kind: object.kind,
+                        x: object.x,
+                        y: object.y,
+                    }));
+                }
+                TmjLayer::ObjectGroup { .. } => {}
+            }
+        }
+
+        Ok(Self {
+            width: map.width,
+            height: map.height,
+            tile_width: map.tilewidth,
+            tile_height: map.tileheight,
+            tilesets: map
+                .tilesets
+                .into_iter()
+                .map(|tileset| TilesetRef {
+                    first_gid: tileset.firstgid,
+                    name: tileset.name,
+                    image: tileset.image,
+                    image_width: tileset.imagewidth,
+                    image_height: tileset.imageheight,
+                    tile_width: tileset.tilewidth,
+                    tile_height: tileset.tileheight,
+                    spacing: tileset.spacing,
+                    margin: tileset.margin,
+                    columns: tileset.columns,
+                    tile_count: tileset.tilecount,
+                })
+                .collect(),
+            layers,
+            spawns,
+        })
+    }
+
+    pub fn to_tmx(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<map version=\"{TILED_VERSION}\" orientation=\"orthogonal\" renderorder=\"right-down\" \
+            width=\"{}\" height=\"{}\" tilewidth=\"{}\" tileheight=\"{}\" infinite=\"0\" \
+            nextlayerid=\"{}\" nextobjectid=\"{}\">",
+            self.width,
+            self.height,
+            self.tile_width,
+            self.tile_height,
+            self.layers.len() + 2,
+            self.next_object_id(),
+        );
+        for tileset in &self.tilesets {
+            let _ = writeln!(
+                xml,
+                " <tileset firstgid=\"{}\" name=\"{}\" tilewidth=\"{}\" tileheight=\"{}\" \
+                spacing=\"{}\" margin=\"{}\" tilecount=\"{}\" columns=\"{}\">",
+                tileset.first_gid,
+                escape(&tileset.name),
+                tileset.tile_width,
+                tileset.tile_height,
+                tileset.spacing,
+                tileset.margin,
+                tileset.tile_count,
+                tileset.columns,
+            );
+            let _ = writeln!(
+                xml,
+                "  <image source=\"{}\" width=\"{}\" height=\"{}\"/>",
+                escape(&tileset.image),
+                tileset.image_width,
+                tileset.image_height,
+            );
+            xml.push_str(" </tileset>\n");
+        }
+        for (index, layer) in self.layers.iter().enumerate() {
+            let _ = writeln!(
+                xml,
+                " <layer id=\"{}\" name=\"{}\" width=\"{}\" height=\"{}\">",
+                index + 1,
+                escape(&layer.name),
+                self.width,
+                self.height,
+            );
+            xml.push_str("  <data encoding=\"csv\">\n");
+            let rows: Vec<String> = layer
+                .data
+                .chunks(self.width.max(1) as usize)
+                .map(|row| {
+                    row.iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(",")
+                })
+                .collect();
+            xml.push_str(&rows.join(",\n"));
+            xml.push_str("\n</data>\n </layer>\n");
+        }
+        let _ = writeln!(
+            xml,
+            " <objectgroup id=\"{}\" name=\"{SPAWN_LAYER}\">",
+            self.layers.len() + 1
+        );
+        for spawn in &self.spawns {
+            let _ = writeln!(
+                xml,
+                "  <object id=\"{}\" name=\"{}\" type=\"{}\" x=\"{}\" y=\"{}\">\n   <point/>\n  </object>",
+                spawn.id,
+                escape(&spawn.name),
+                escape(&spawn.kind),
+                spawn.x,
+                spawn.y,
+            );
+        }
+        xml.push_str(" </objectgroup>\n</map>\n");
+        xml
+    }
+
+    /// Read a TMX map with CSV-encoded, orthogonal tile layers
+    pub fn from_tmx(xml: &str) -> Result<Self> {
+        let document = roxmltree::Document::parse(xml).context("Invalid TMX map")?;
+        let map = document.root_element();
+        if !map.has_tag_name("map") {
+            bail!("TMX root element is <{}>, not <map>", map.tag_name().name());
+        }
+
+        let mut tilesets = Vec::new();
+        let mut layers = Vec::new();
+        let mut spawns = Vec::new();
+        for node in map.children().filter(|node| node.is_element()) {
+            match node.tag_name().name() {
+                "tileset" => {
+                    let image = node
+                        .children()
+                        .find(|child| child.has_tag_name("image"))
+                        .context("TMX tileset without an image")?;
+                    tilesets.push(TilesetRef {
+                        first_gid: number(node, "firstgid")?,
+                        name: node.attribute("name").unwrap_or_default().to_string(),
+                        image: image.attribute("source").unwrap_or_default().to_string(),
+                        image_width: number(image, "width")?,
+                        image_height: number(image, "height")?,
+                        tile_width: number(node, "tilewidth")?,
+                        tile_height: number(node, "tileheight")?,
+                        spacing: number(node, "spacing").unwrap_or(0),
+                        margin: number(node, "margin").unwrap_or(0),
+                        columns: number(node, "columns")?,
+                        tile_count: number(node, "tilecount")?,
+                    });
+                }
+                "layer" => {
+                    let data = node
+                        .children()
+                        .find(|child| child.has_tag_name("data"))
+                        .context("TMX layer without data")?;
+                    if data.attribute("encoding") != Some("csv") {
+                        bail!("Only CSV-encoded TMX layers are supported");
+                    }
+                    let data = data
+                        .text()
+                        .unwrap_or_default()
+                        .split(',')
+                        .map(|gid| gid.trim().parse::<u32>())
+                        .collect::<Result<Vec<_>, _>>()
+                        .context("Invalid tile id in TMX layer")?;
+                    layers.push(TileLayer {
+                        name: node.attribute("name").unwrap_or_default().to_string(),
+                        data,
+                    });
+                }
+                "objectgroup" if node.attribute("name") == Some(SPAWN_LAYER) => {
+                    for object in node.children().filter(|child| child.has_tag_name("object")) {
+                        spawns.push(SpawnPoint {
+                            id: number(object, "id")?,
+                            name: object.attribute("name").unwrap_or_default().to_string(),
+                            kind: object.attribute("type").unwrap_or_default().to_string(),
+                            x: number(object, "x")?,
+                            y: number(object, "y")?,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            width: number(map, "width")?,
+            height: number(map, "height")?,
+            tile_width: number(map, "tilewidth")?,
+            tile_height: number(map, "tileheight")?,
+            tilesets,
+            layers,
+            spawns,
+        })
+    }
+
+    fn next_object_id(&self) -> u32 {
+        self.spawns.iter().map(|spawn| spawn.id).max().unwrap_or(0) + 1
+    }
+}
+
+/// TMJ document, limited to what [`TiledMap`] uses
+#[derive(Serialize, Deserialize)]
+struct Tmj {
+    #[serde(rename = "type")]
+    kind: String,
+    version: String,
+    orientation: String,
+    renderorder: String,
+    infinite: bool,
+    width: u32,
+    height: u32,
+    tilewidth: u32,
+    tileheight: u32,
+    nextlayerid: u32,
+    nextobjectid: u32,
+    layers: Vec<TmjLayer>,
+    tilesets: Vec<TmjTileset>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum TmjLayer {
+    #[serde(rename = "tilelayer")]
+    TileLayer {
+        id: u32,
+        name: String,
+        width: u32,
+        height: u32,
+        data: Vec<u32>,
+    },
+    #[serde(rename = "objectgroup")]
+    ObjectGroup {
+        id: u32,
+        name: String,
+        objects: Vec<TmjObject>,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct TmjObject {
+    id: u32,
+    name: String,
+    #[serde(rename = "type", default)]
+    kind: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    point: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TmjTileset {
+    firstgid: u32,
+    name: String,
+    image: String,
+    imagewidth: u32,
+    imageheight: u32,
+    tilewidth: u32,
+    tileheight: u32,
+    #[serde(default)]
+    spacing: u32,
+    #[serde(default)]
+    margin: u32,
+    columns: u32,
+    tilecount: u32,
+}
+
+fn number<T: std::str::FromStr>(node: roxmltree::Node, attribute: &str) -> Result<T> {
+    node.attribute(attribute)
+        .with_context(|| format!("TMX <{}> without {attribute}", node.tag_name().name()))?
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid {attribute} on TMX <{}>", node.tag_name().name()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maps::MiniMapRoom;
+
+    fn floor() -> (MiniMapFloor, FloorData) {
+        let room = |x, y, kind, from| MiniMapRoom { x, y, kind, from };
+        let floor = MiniMapFloor {
+            rooms: vec![
+                room(0, 0, RoomKind::Entrance, None),
+                room(1, 0, RoomKind::Room, Some(0)),
+                room(1, 1, RoomKind::Treasure, Some(1)),
+                room(0, -1, RoomKind::Boss, Some(0)),
+            ],
+        };
+        let data = FloorData {
+            layout: String::new(),
+            encounters: vec!["Slime".to_string()],
+            treasures: vec!["Silver \"Key\" & Map".to_string()],
+        };
+        (floor, data)
+    }
+
+    fn map() -> TiledMap {
+        let tileset = TilesetRef {
+            first_gid: 1,
+            name: "dungeon".to_string(),
+            image: "../sprites/dungeon_sheet.png".to_string(),
+            image_width: 70,
+            image_height: 36,
+            tile_width: 16,
+            tile_height: 16,
+            spacing: 2,
+            margin: 2,
+            columns: 4,
+            tile_count: 8,
+        };
+        let (floor, data) = floor();
+        TiledMap::from_floor(&floor, &data, &tileset, FloorTiles { floor: 0, wall: 3 })
+    }
+
+    #[test]
+    fn floor_layout() {
+        let map = map();
+        assert_eq!((map.width, map.height), (2 * ROOM_TILES, 3 * ROOM_TILES));
+        assert_eq!(map.layers.len(), 2);
+
+        // The corner of the empty grid cell has no ground
+        let cell = |x: u32, y: u32| (y * map.width + x) as usize;
+        assert_eq!(map.layers[0].data[cell(ROOM_TILES, 0)], 0);
+        // Entrance and boss room are joined by a doorway
+        let middle = ROOM_TILES / 2;
+        assert_eq!(map.layers[1].data[cell(middle, ROOM_TILES - 1)], 0);
+        assert_eq!(map.layers[1].data[cell(middle, ROOM_TILES)], 0);
+        assert_eq!(map.layers[1].data[cell(0, ROOM_TILES)], 4);
+
+        let kinds: Vec<&str> = map.spawns.iter().map(|s| s.kind.as_str()).collect();
+        assert_eq!(kinds, ["player_start", "enemy", "chest", "boss"]);
+        assert_eq!(map.spawns[2].name, "Silver \"Key\" & Map");
+    }
+
+    #[test]
+    fn tmj_round_trip() {
+        let map = map();
+        let json = map.to_tmj().unwrap();
+        assert_eq!(TiledMap::from_tmj(&json).unwrap(), map);
+    }
+
+    #[test]
+    fn tmx_round_trip() {
+        let map = map();
+        let xml = map.to_tmx();
+        assert_eq!(TiledMap::from_tmx(&xml).unwrap(), map);
+    }
+
+    #[test]
+    fn tileset_from_packed_sheet() {
+        let metadata = SpriteSheetMetadata {
+            frames: [(
+                "floor".to_string(),
+                crate::consistency::SpriteFrame {
+                    x: 2,
+                    y: 2,
+                    width: 16,
+                    height: 16,
+                },
+            )]
+            .into(),
+            padding: 2,
+            format: "rgba8".to_string(),
+        };
+        let tileset = TilesetRef::from_sheet("dungeon", "sheet.png", (56, 38), &metadata).unwrap();
+        assert_eq!((tileset.columns, tileset.tile_count), (3, 6));
+        assert_eq!(tileset.gid(5), 6);
+    }
+}
@@ -0,0 +1,822 @@
+//! RON game data assets for the exported Bevy project
+//!
+//! The generated world and game config are flattened into item, enemy,
+//! quest, encounter, boss, economy and achievement databases plus a
+//! randomizer spec and speedrun splits, and written as RON next to the JSON
+//! output, along with the postgame plan when that phase ran. Achievements are
+//! also exported as JSON and a Steamworks VDF schema under
+//! `assets/achievements/`, and the splits as a LiveSplit run under
+//! `assets/speedrun/`. Innkeeper dialogue is abbreviated to fit the text box
+//! and written as Yarn Spinner scripts under `assets/dialogue/`, next to the
+//! abbreviations' expansion table, and the world map and dungeon mini-maps
+//! as data and as 1x, 2x and 4x images under `assets/maps/`, next to the
+//! world map repainted in the art style when stylized maps are enabled and
+//! the zone graph and its tiled overview when the world was placed. Dithered
+//! palette skies for each time of day go under `assets/backgrounds/` for
+//! menus and cutscenes. The project also receives `src/game_data.rs`, a
+//! plugin that registers the matching reflected asset types and loaders, so
+//! the prototype loads the data through Bevy's asset server and picks up
+//! edits while play testing, and `src/speedrun.rs`, an optional in-game
+//! timer that splits on the same definitions. Both are registered by the
+//! exported `src/main.rs`, and `Cargo.toml` pulls in Bevy, serde and RON.
+
+use anyhow::{Result, bail};
+use image::DynamicImage;
+use image::imageops::FilterType;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::achievements::{AchievementSet, TEST_APP_ID};
+use crate::bosses::BossRoster;
+use crate::consistency::Color;
+use crate::consistency::indexed::IndexedPalette;
+use crate::consistency::scaling::{ScaleManifest, write_variants};
+use crate::economy::{Economy, PricingRules};
+use crate::encounters::{DifficultyPreset, EncounterTables};
+use crate::export::livesplit::SplitDefinitions;
+use crate::game_types::{GameConfig, QuestLine, WorldData};
+use crate::gradients::{BACKDROP_SIZE, TimeOfDay, render_sky};
+use crate::maps::{WORLD_MAP_SIZE, WorldMap};
+use crate::postgame::PostgamePlan;
+use crate::quests::{ObjectiveKind, QuestDesign, QuestGraph, Reward};
+use crate::randomizer::RandomizerSpec;
+use crate::slugs::{AssetCategory, SlugCase, SlugRegistry, slugify};
+use crate::text::abbreviations::{AbbreviationTable, TextBox};
+use crate::text::dialogue::DialogueTree;
+use crate::world::{BiomeTileset, WorldGraph};
+
+/// Bevy plugin source copied into the exported project
+const GAME_DATA_PLUGIN: &str = include_str!("../scaffold/game_data.rs");
+
+/// Speedrun timer plugin source copied into the exported project
+const SPEEDRUN_PLUGIN: &str = include_str!("../scaffold/speedrun.rs");
+
+/// Entry point of the exported project, registering both plugins
+const GAME_MAIN: &str = include_str!("../scaffold/main.rs");
+
+/// Manifest of the exported project, with `{package}` for its name
+const GAME_MANIFEST: &str = include_str!("../scaffold/Cargo.toml.template");
+
+/// Directory under the project's `assets/` holding the RON files
+pub const DATA_ASSET_DIR: &str = "data";
+
+/// Directory under the project's `assets/` holding the Yarn scripts
+pub const DIALOGUE_ASSET_DIR: &str = "dialogue";
+
+/// Directory under the project's `assets/` holding the map images
+pub const MAP_ASSET_DIR: &str = "maps";
+
+/// Directory under the project's `assets/` holding the achievement exports and icons
+pub const ACHIEVEMENT_ASSET_DIR: &str = "achievements";
+
+/// Directory under the project's `assets/` holding the LiveSplit run
+pub const SPEEDRUN_ASSET_DIR: &str = "speedrun";
+
+/// Directory under the project's `assets/` holding the character sheets
+pub const SPRITE_ASSET_DIR: &str = "sprites";
+
+/// Directory under the project's `assets/` holding the biome tilesets
+pub const TILESET_ASSET_DIR: &str = "tilesets";
+
+/// Directory under the project's `assets/` holding the music descriptions
+pub const MUSIC_ASSET_DIR: &str = "music";
+
+/// Directory under the project's `assets/` holding the menu and cutscene skies
+pub const BACKGROUND_ASSET_DIR: &str = "backgrounds";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Item {
+    pub id: String,
+    pub name: String,
+    pub price: Option<u32>,
+    pub sold_at: Vec<String>,
+    pub found_in: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Enemy {
+    pub id: String,
+    pub name: String,
+    pub boss: bool,
+    pub sprite: Option<String>,
+    pub attacks: Vec<String>,
+    pub habitats: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuestObjective {
+    pub description: String,
+    pub objective_type: String,
+    pub location: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Quest {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub main: bool,
+    pub steps: Vec<QuestObjective>,
+    pub rewards: Vec<String>,
+    /// Ids of the quests that must be completed first
+    #[serde(default)]
+    pub prerequisites: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ItemDatabase {
+    pub items: Vec<Item>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EnemyDatabase {
+    pub enemies: Vec<Enemy>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuestDatabase {
+    pub quests: Vec<Quest>,
+}
+
+/// All data assets of a generated game
+#[derive(Debug, Clone, Default)]
+pub struct GameDataAssets {
+    pub items: ItemDatabase,
+    pub enemies: EnemyDatabase,
+    pub quests: QuestDatabase,
+    pub encounters: EncounterTables,
+    pub bosses: BossRoster,
+    pub economy: Economy,
+    /// Innkeeper dialogue referenced by the economy's inns
+    pub dialogues: Vec<DialogueTree>,
+    pub map: WorldMap,
+    pub achievements: AchievementSet,
+    /// Checks and logic for the exported game's randomizer mode
+    pub randomizer: RandomizerSpec,
+    /// Speedrun splits at the plot beats
+    pub splits: SplitDefinitions,
+    /// Postgame content, only planned when the postgame phase is enabled
+    pub postgame: Option<PostgamePlan>,
+    /// Rules the economy was priced with
+    pub pricing: PricingRules,
+    /// Project palette the maps are drawn in
+    pub palette: Vec<Color>,
+    /// Zone graph of the overworld and the tiles of its overview
+    pub world: Option<(WorldGraph, BiomeTileset)>,
+    /// The world map repainted in the game's art style, at the map's size
+    pub stylized_world: Option<DynamicImage>,
+}
+
+impl GameDataAssets {
+    /// Collect items from shops and treasures, enemies from encounters and
+    /// bosses, and quests from the main and side quest lines. Encounter
+    /// tables use the project's difficulty preset and shops the default
+    /// [`PricingRules`].
+    pub fn from_generated(
+        config: &GameConfig,
+        world: &WorldData,
+        difficulty: DifficultyPreset,
+    ) -> Self {
+        let mut items: BTreeMap<String, Item> = BTreeMap::new();
+        for town in &world.towns {
+            for shop in &town.shops {
+                for name in &shop.inventory {
+                    let entry = item_entry(&mut items, name);
+                    entry.price = entry.price.or_else(|| shop.prices.get(name).copied());
+                    push_unique(&mut entry.sold_at, format!("{} - {}", town.name, shop.name));
+                }
+            }
+        }
+        for dungeon in &world.dungeons {
+            for name in dungeon.floors.iter().flat_map(|f| &f.treasures) {
+                push_unique(
+                    &mut item_entry(&mut items, name).found_in,
+                    dungeon.name.clone(),
+                );
+            }
+        }
+        for dungeon in &config.dungeons {
+            for name in &dungeon.treasures {
+                push_unique(
+                    &mut item_entry(&mut items, name).found_in,
+                    dungeon.name.clone(),
+                );
+            }
+        }
+
+        let mut enemies: BTreeMap<String, Enemy> = BTreeMap::new();
+        for region in &world.regions {
+            for name in &region.encounters {
+                push_unique(
+                    &mut enemy_entry(&mut enemies, name).habitats,
+                    region.name.clone(),
+                );
+            }
+        }
+        for dungeon in &world.dungeons {
+            for name in dungeon.floors.iter().flat_map(|f| &f.encounters) {
+                push_unique(
+                    &mut enemy_entry(&mut enemies, name).habitats,
+                    dungeon.name.clone(),
+                );
+            }
+            let boss = enemy_entry(&mut enemies, &dungeon.boss.name);
+            boss.boss = true;
+            boss.sprite = Some(dungeon.boss.sprite.clone());
+            boss.attacks = dungeon.boss.attacks.clone();
+            push_unique(&mut boss.habitats, dungeon.name.clone());
+        }
+
+        let quests = std::iter::once((&config.main_quest, true))
+            .chain(config.side_quests.iter().map(|quest| (quest, false)))
+            .map(|(quest, main)| Quest::from_quest_line(quest, main))
+            .collect();
+
+        let enemies = EnemyDatabase {
+            enemies: enemies.into_values().collect(),
+        };
+        let encounters = EncounterTables::generate(world, &enemies, difficulty);
+        let items = ItemDatabase {
+            items: items.into_values().collect(),
+        };
+        let (economy, dialogues) =
+            Economy::generate(config, world, &items, &PricingRules::default());
+        let quests = QuestDatabase { quests };
+        let achievements = AchievementSet::derive(&config.name, &quests, &enemies, &items);
+        let randomizer = RandomizerSpec::derive(world, &quests, &enemies);
+        let splits = SplitDefinitions::derive(&config.name, &quests, world);
+
+        Self {
+            items,
+            enemies,
+            quests,
+            encounters,
+            bosses: BossRoster::default(),
+            economy,
+            dialogues,
+            map: WorldMap::generate(config, world),
+            achievements,
+            randomizer,
+            splits,
+            postgame: None,
+            world: None,
+            stylized_world: None,
+            pricing: PricingRules::default(),
+            palette: config
+                .color_palette
+                .primary
+                .iter()
+                .chain(&config.color_palette.secondary)
+                .chain(&config.color_palette.effects)
+                .filter_map(|hex| Color::from_hex(hex))
+                .collect(),
+        }
+    }
+
+    /// Restock the shops and reprice the services with other pricing rules
+    pub fn with_pricing(
+        mut self,
+        config: &GameConfig,
+        world: &WorldData,
+        rules: &PricingRules,
+    ) -> Self {
+        (self.economy, self.dialogues) = Economy::generate(config, world, &self.items, rules);
+        self.pricing = rules.clone();
+        self
+    }
+
+    /// Replace the quests with a typed quest graph and rederive the
+    /// achievements, the randomizer logic and the splits
+    ///
+    /// Fails if the graph has cycles, quests that can never become
+    /// available, or references to content the world doesn't have.
+    pub fn with_quests(mut self, world: &WorldData, graph: &QuestGraph) -> Result<Self> {
+        let issues = graph.validate(world);
+        if !issues.is_empty() {
+            bail!(
+                "Quest graph is invalid: {}",
+                issues
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            );
+        }
+        self.quests = QuestDatabase {
+            quests: graph.order().into_iter().map(Quest::from_design).collect(),
+        };
+        self.achievements = AchievementSet::derive(
+            &self.achievements.game,
+            &self.quests,
+            &self.enemies,
+            &self.items,
+        );
+        self.randomizer = RandomizerSpec::derive(world, &self.quests, &self.enemies);
+        self.splits = SplitDefinitions::derive(&self.splits.game, &self.quests, world);
+        Ok(self)
+    }
+
+    /// Attach the boss designs and point the bosses' enemy entries at their
+    /// battle sprites
+    pub fn with_bosses(mut self, bosses: BossRoster) -> Self {
+        for boss in &bosses.bosses {
+            if let Some(enemy) = self.enemies.enemies.iter_mut().find(|e| e.id == boss.id) {
+                enemy.sprite = Some(boss.sprite.sprite_id.clone());
+            }
+        }
+        self.bosses = bosses;
+        self
+    }
+
+    /// Attach the overworld's zone graph, exported with an overview tiled
+    /// from `tileset`
+    pub fn with_world(mut self, graph: WorldGraph, tileset: BiomeTileset) -> Self {
+        self.world = Some((graph, tileset));
+        self
+    }
+
+    /// Export a repainted world map next to the rendered one, see
+    /// [`crate::image::ImageGenerator::stylize_map`]
+    pub fn with_stylized_world(mut self, map: DynamicImage) -> Self {
+        let (width, height) = WORLD_MAP_SIZE;
+        self.stylized_world = Some(map.resize_exact(width, height, FilterType::Triangle));
+        self
+    }
+
+    /// Attach the postgame plan, adding its superboss to the enemies and
+    /// the achievements
+    pub fn with_postgame(mut self, plan: PostgamePlan) -> Self {
+        let superboss = &plan.superboss;
+        let enemy = enemy_entry_in(&mut self.enemies, &superboss.name);
+        enemy.boss = true;
+        enemy.sprite = Some(superboss.sprite.sprite_id.clone());
+        for ability in superboss.phases.iter().flat_map(|phase| &phase.rotation) {
+            push_unique(&mut enemy.attacks, ability.clone());
+        }
+        push_unique(&mut enemy.habitats, superboss.dungeon.clone());
+        self.achievements = AchievementSet::derive(
+            &self.achievements.game,
+            &self.quests,
+            &self.enemies,
+            &self.items,
+        );
+        self.postgame = Some(plan);
+        self
+    }
+
+    /// Write the RON assets and the loader plugin into a project, logging
+    /// the towns whose gear the economy makes too hard to afford. The map
+    /// and background images are named through the project's `slugs`.
+    pub fn write_to_project(&self, project_path: &Path, slugs: &mut SlugRegistry) -> Result<()> {
+        for warning in self.economy.affordability_warnings(&self.pricing) {
+            tracing::warn!("Economy issue: {warning}");
+        }
+
+        let data_dir = project_path.join("assets").join(DATA_ASSET_DIR);
+        std::fs::create_dir_all(&data_dir)?;
+
+        let pretty = ron::ser::PrettyConfig::default();
+        std::fs::write(
+            data_dir.join("game.items.ron"),
+            ron::ser::to_string_pretty(&self.items, pretty.clone())?,
+        )?;
+        std::fs::write(
+            data_dir.join("game.enemies.ron"),
+            ron::ser::to_string_pretty(&self.enemies, pretty.clone())?,
+        )?;
+        std::fs::write(
+            data_dir.join("game.quests.ron"),
+            ron::ser::to_string_pretty(&self.quests, pretty.clone())?,
+        )?;
+        std::fs::write(
+            data_dir.join("game.encounters.ron"),
+            ron::ser::to_string_pretty(&self.encounters, pretty.clone())?,
+        )?;
+        std::fs::write(
+            data_dir.join("game.bosses.ron"),
+            ron::ser::to_string_pretty(&self.bosses, pretty.clone())?,
+        )?;
+        std::fs::write(
+            data_dir.join("game.economy.ron"),
+            ron::ser::to_string_pretty(&self.economy, pretty.clone())?,
+        )?;
+        std::fs::write(
+            data_dir.join("game.map.ron"),
+            ron::ser::to_string_pretty(&self.map, pretty.clone())?,
+        )?;
+        std::fs::write(
+            data_dir.join("game.achievements.ron"),
+            ron::ser::to_string_pretty(&self.achievements, pretty.clone())?,
+        )?;
+        std::fs::write(
+            data_dir.join("game.randomizer.ron"),
+            ron::ser::to_string_pretty(&self.randomizer, pretty.clone())?,
+        )?;
+        std::fs::write(
+            data_dir.join("game.splits.ron"),
+            ron::ser::to_string_pretty(&self.splits, pretty.clone())?,
+        )?;
+        if let Some(postgame) = &self.postgame {
+            std::fs::write(
+                data_dir.join("game.postgame.ron"),
+                ron::ser::to_string_pretty(postgame, pretty)?,
+            )?;
+        }
+
+        let achievement_dir = project_path.join("assets").join(ACHIEVEMENT_ASSET_DIR);
+        std::fs::create_dir_all(&achievement_dir)?;
+        std::fs::write(
+            achievement_dir.join("achievements.json"),
+            self.achievements.to_json()?,
+        )?;
+        std::fs::write(
+            achievement_dir.join("achievements.vdf"),
+            self.achievements.to_vdf(TEST_APP_ID),
+        )?;
+
+        let speedrun_dir = project_path.join("assets").join(SPEEDRUN_ASSET_DIR);
+        std::fs::create_dir_all(&speedrun_dir)?;
+        std::fs::write(speedrun_dir.join("splits.lss"), self.splits.to_lss())?;
+
+        let palette = IndexedPalette::from_colors(&self.palette)?;
+        let map_dir = project_path.join("assets").join(MAP_ASSET_DIR);
+        std::fs::create_dir_all(&map_dir)?;
+        let mut scales = ScaleManifest::default();
+        scales.assets.push(write_variants(
+            &slugs.slug(AssetCategory::Map, "world"),
+            &DynamicImage::ImageRgba8(self.map.render_world(&self.palette)),
+            &palette,
+            &map_dir,
+        )?);
+        for dungeon in &self.map.dungeons {
+            for (index, floor) in dungeon.floors.iter().enumerate() {
+                let id = slugs.slug(
+                    AssetCategory::Map,
+                    &format!("{} floor {}", dungeon.dungeon, index + 1),
+                );
+                let image = DynamicImage::ImageRgba8(WorldMap::render_floor(floor, &self.palette));
+                scales
+                    .assets
+                    .push(write_variants(&id, &image, &palette, &map_dir)?);
+            }
+        }
+        if let Some(map) = &self.stylized_world {
+            scales.assets.push(write_variants(
+                &slugs.slug(AssetCategory::Map, "world stylized"),
+                map,
+                &palette,
+                &map_dir,
+            )?);
+        }
+        if let Some((graph, tileset)) = &self.world {
+            std::fs::write(map_dir.join("world_graph.json"), graph.to_json()?)?;
+            scales.assets.push(write_variants(
+                &slugs.slug(AssetCategory::Map, "world overview"),
+                &DynamicImage::ImageRgba8(graph.render_overview(tileset, &self.palette)),
+                &palette,
+                &map_dir,
+            )?);
+        }
+        scales.write(&map_dir)?;
+
+        let background_dir = project_path.join("assets").join(BACKGROUND_ASSET_DIR);
+        std::fs::create_dir_all(&background_dir)?;
+        let mut scales = ScaleManifest::default();
+        for time in TimeOfDay::ALL {
+            let sky = render_sky(BACKDROP_SIZE, time.hour(), &self.palette);
+            scales.assets.push(write_variants(
+                &slugs.slug(AssetCategory::Background, &format!("sky {}", time.name())),
+                &DynamicImage::ImageRgba8(sky),
+                &palette,
+                &background_dir,
+            )?);
+        }
+        scales.write(&background_dir)?;
+
+        let dialogue_dir = project_path.join("assets").join(DIALOGUE_ASSET_DIR);
+        std::fs::create_dir_all(&dialogue_dir)?;
+        let abbreviations = AbbreviationTable::build(&self.dialogues, TextBox::default());
+        for (tree, node) in abbreviations.overflowing(&self.dialogues) {
+            tracing::warn!("Dialogue {tree}/{node} overflows the text box");
+        }
+        for dialogue in abbreviations.compress(&self.dialogues) {
+            std::fs::write(
+                dialogue_dir.join(format!("{}.yarn", dialogue.id)),
+                dialogue.to_yarn(),
+            )?;
+        }
+        std::fs::write(
+            dialogue_dir.join("abbreviations.json"),
+            abbreviations.to_json()?,
+        )?;
+
+        let src_dir = project_path.join("src");
+        std::fs::create_dir_all(&src_dir)?;
+        std::fs::write(src_dir.join("game_data.rs"), GAME_DATA_PLUGIN)?;
+        std::fs::write(src_dir.join("speedrun.rs"), SPEEDRUN_PLUGIN)?;
+        std::fs::write(src_dir.join("main.rs"), GAME_MAIN)?;
+        std::fs::write(
+            project_path.join("Cargo.toml"),
+            GAME_MANIFEST.replace("{package}", &asset_id(&self.achievements.game)),
+        )?;
+
+        Ok(())
+    }
+}
+
+impl Quest {
+    fn from_quest_line(quest: &QuestLine, main: bool) -> Self {
+        Self {
+            id: asset_id(&quest.name),
+            name: quest.name.clone(),
+            description: quest.description.clone(),
+            main,
+            steps: quest
+                .steps
+                .iter()
+                .map(|step| QuestObjective {
+                    description: step.description.clone(),
+                    objective_type: step.objective_type.clone(),
+                    location: step.location.clone(),
+                })
+                .collect(),
+            rewards: quest.rewards.clone(),
+            prerequisites: Vec::new(),
+        }
+    }
+
+    fn from_design(quest: &QuestDesign) -> Self {
+        Self {
+            id: quest.id.clone(),
+            name: quest.name.clone(),
+            description: quest.description.clone(),
+            main: quest.main,
+            steps: quest
+                .objectives
+                .iter()
+                .map(|objective| {
+                    let (objective_type, location) = match &objective.kind {
+                        ObjectiveKind::Talk { npc } => ("talk", npc),
+                        ObjectiveKind::Visit { location } => ("visit", location),
+                        ObjectiveKind::Defeat { enemy, .. } => ("defeat", enemy),
+                        ObjectiveKind::Collect { item, .. } => ("collect", item),
+                        ObjectiveKind::Deliver { npc, .. } => ("deliver", npc),
+                    };
+                    QuestObjective {
+                        description: objective.description.clone(),
+                        objective_type: objective_type.to_string(),
+                        location: location.clone(),
+                    }
+                })
+                .collect(),
+            rewards: quest
+                .rewards
+                .iter()
+                .map(|reward| match reward {
+                    Reward::Gold { amount } => format!("{amount} gold"),
+                    Reward::Experience { amount } => format!("{amount} XP"),
+                    Reward::Item { item } => item.clone(),
+                    Reward::Flag { name } => format!("flag:{name}"),
+                })
+                .collect(),
+            prerequisites: quest.prerequisites.iter().map(|p| asset_id(p)).collect(),
+        }
+    }
+}
+
+/// Id of a game data entry derived from its display name, e.g. "Iron Sword"
+/// -> "iron_sword". Entries of the same name are the same entry, so ids only
+/// need to be unique within their database; asset files are named through
+/// the project's [`SlugRegistry`] instead.
+pub(crate) fn asset_id(name: &str) -> String {
+    slugify(name, SlugCase::Snake)
+}
+
+fn item_entry<'a>(items: &'a mut BTreeMap<String, Item>, name: &str) -> &'a mut Item {
+    items.entry(asset_id(name)).or_insert_with(|| Item {
+        id: asset_id(name),
+        name: name.to_string(),
+        price: None,
+        sold_at: Vec::new(),
+        found_in: Vec::new(),
+    })
+}
+
+fn enemy_entry<'a>(enemies: &'a mut BTreeMap<String, Enemy>, name: &str) -> &'a mut Enemy {
+    enemies.entry(asset_id(name)).or_insert_with(|| Enemy {
+        id: asset_id(name),
+        name: name.to_string(),
+        boss: false,
+        sprite: None,
+        attacks: Vec::new(),
+        habitats: Vec::new(),
+    })
+}
+
+/// Enemy entry of an already built database, appended if it's new
+fn enemy_entry_in<'a>(enemies: &'a mut EnemyDatabase, name: &str) -> &'a mut Enemy {
+    let id = asset_id(name);
+    let index = match enemies.enemies.iter().position(|enemy| enemy.id == id) {
+        Some(index) => index,
+        None => {
+            enemies.enemies.push(Enemy {
+                id,
+                name: name.to_string(),
+                boss: false,
+                sprite: None,
+                attacks: Vec::new(),
+                habitats: Vec::new(),
+            });
+            enemies.enemies.len() - 1
+        }
+    };
+    &mut enemies.enemies[index]
+}
+
+fn push_unique(values: &mut Vec<String>, value: String) {
+    if !values.contains(&value) {
+        values.push(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_types::fixtures;
+    use crate::world::{Neighbour, RegionSuggestion};
+
+    /// Empty project directory under the system temp dir
+    fn project_dir() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("game_assets_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn visit_aldwyn(name: &str, prerequisites: &[&str]) -> QuestDesign {
+        QuestDesign {
+            id: asset_id(name),
+            name: name.to_string(),
+            description: String::new(),
+            main: false,
+            giver: None,
+            prerequisites: prerequisites.iter().map(ToString::to_string).collect(),
+            objectives: vec![crate::quests::Objective {
+                description: "Reach Aldwyn".to_string(),
+                kind: ObjectiveKind::Visit {
+                    location: "Aldwyn".to_string(),
+                },
+            }],
+            rewards: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn with_quests_exports_a_valid_graph_in_order() {
+        let (config, world) = (fixtures::config(), fixtures::world());
+        let assets = GameDataAssets::from_generated(&config, &world, DifficultyPreset::default());
+
+        let graph = QuestGraph {
+            quests: vec![
+                visit_aldwyn("Second Errand", &["First Errand"]),
+                visit_aldwyn("First Errand", &[]),
+            ],
+        };
+        let linked = assets.clone().with_quests(&world, &graph).unwrap();
+        let names: Vec<&str> = linked
+            .quests
+            .quests
+            .iter()
+            .map(|q| q.name.as_str())
+            .collect();
+        assert_eq!(names, ["First Errand", "Second Errand"]);
+
+        let cycle = QuestGraph {
+            quests: vec![
+                visit_aldwyn("Chicken", &["Egg"]),
+                visit_aldwyn("Egg", &["Chicken"]),
+            ],
+        };
+        let error = assets.with_quests(&world, &cycle).unwrap_err();
+        assert!(error.to_string().starts_with("Quest graph is invalid"));
+    }
+
+    #[test]
+    fn encounter_tables_use_the_difficulty_preset() {
+        let (config, world) = (fixtures::config(), fixtures::world());
+        let casual = GameDataAssets::from_generated(&config, &world, DifficultyPreset::Casual);
+        let hard = GameDataAssets::from_generated(&config, &world, DifficultyPreset::Hard);
+        assert_eq!(casual.encounters.difficulty, DifficultyPreset::Casual);
+        assert_eq!(hard.encounters.difficulty, DifficultyPreset::Hard);
+        assert_ne!(casual.encounters.tables, hard.encounters.tables);
+    }
+
+    #[test]
+    fn writes_a_manifest_and_main_for_the_plugins() {
+        let (config, world) = (fixtures::config(), fixtures::world());
+        let dir = project_dir();
+        GameDataAssets::from_generated(&config, &world, DifficultyPreset::default())
+            .write_to_project(&dir, &mut SlugRegistry::new())
+            .unwrap();
+
+        let manifest: toml::Table =
+            toml::from_str(&std::fs::read_to_string(dir.join("Cargo.toml")).unwrap()).unwrap();
+        assert_eq!(
+            manifest["package"]["name"].as_str(),
+            Some(asset_id(&config.name).as_str())
+        );
+        for dependency in ["bevy", "serde", "ron"] {
+            assert!(manifest["dependencies"].get(dependency).is_some());
+        }
+        let main = std::fs::read_to_string(dir.join("src").join("main.rs")).unwrap();
+        assert!(main.contains("pub mod game_data;") && main.contains("pub mod speedrun;"));
+        assert!(main.contains("(GameDataPlugin, SpeedrunPlugin)"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn writes_stylized_world_map_at_the_map_size() {
+        let (config, world) = (fixtures::config(), fixtures::world());
+        let painted = DynamicImage::new_rgba8(1024, 1024);
+        let dir = project_dir();
+        GameDataAssets::from_generated(&config, &world, DifficultyPreset::default())
+            .with_stylized_world(painted)
+            .write_to_project(&dir, &mut SlugRegistry::new())
+            .unwrap();
+
+        let map_dir = dir.join("assets").join(MAP_ASSET_DIR);
+        let map = image::open(map_dir.join("world_stylized.png")).unwrap();
+        assert_eq!((map.width(), map.height()), WORLD_MAP_SIZE);
+        let manifest =
+            std::fs::read_to_string(map_dir.join(crate::consistency::scaling::MANIFEST_FILE))
+                .unwrap();
+        assert!(manifest.contains("world_stylized"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn writes_world_graph_and_overview() {
+        let config = fixtures::config();
+        let world = fixtures::world();
+        let graph = WorldGraph::place(&[
+            RegionSuggestion {
+                name: "Greenvale".to_string(),
+                biome: "plains".to_string(),
+                description: String::new(),
+                tags: Vec::new(),
+                towns: vec!["Aldwyn".to_string()],
+                dungeons: Vec::new(),
+                neighbours: vec![Neighbour {
+                    region: "Korin Wastes".to_string(),
+                    connection_type: "road".to_string(),
+                }],
+            },
+            RegionSuggestion {
+                name: "Korin Wastes".to_string(),
+                biome: "desert".to_string(),
+                description: String::new(),
+                tags: Vec::new(),
+                towns: Vec::new(),
+                dungeons: vec!["Sunken Vault".to_string()],
+                neighbours: Vec::new(),
+            },
+        ]);
+        let dir = project_dir();
+        GameDataAssets::from_generated(&config, &world, DifficultyPreset::default())
+            .with_world(graph.clone(), BiomeTileset::new(4))
+            .write_to_project(&dir, &mut SlugRegistry::new())
+            .unwrap();
+
+        let map_dir = dir.join("assets").join(MAP_ASSET_DIR);
+        let json = std::fs::read_to_string(map_dir.join("world_graph.json")).unwrap();
+        assert_eq!(serde_json::from_str::<WorldGraph>(&json).unwrap(), graph);
+        let manifest =
+            std::fs::read_to_string(map_dir.join(crate::consistency::scaling::MANIFEST_FILE))
+                .unwrap();
+        assert!(manifest.contains("world_overview"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn floor_maps_of_dungeons_named_alike_do_not_collide() {
+        let (config, world) = (fixtures::config(), fixtures::world());
+        let mut assets =
+            GameDataAssets::from_generated(&config, &world, DifficultyPreset::default());
+        let mut twin = assets.map.dungeons[0].clone();
+        twin.dungeon = format!("{}!", twin.dungeon);
+        assets.map.dungeons.push(twin);
+
+        let dir = project_dir();
+        let mut slugs = SlugRegistry::new();
+        assets.write_to_project(&dir, &mut slugs).unwrap();
+
+        let map_dir = dir.join("assets").join(MAP_ASSET_DIR);
+        assert!(map_dir.join("sunken_vault_floor_1.png").is_file());
+        assert!(map_dir.join("sunken_vault_floor_1_2.png").is_file());
+        assert_eq!(
+            slugs.resolve("sunken_vault_floor_1_2").unwrap().name,
+            "Sunken Vault! floor 1"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -43,6 +43,41 @@ pub struct GameConfig {
     // Audio
     pub music_style: String,
     pub sound_effects_style: String,
+
+    // Localization
+    /// Language of the generated game's text, e.g. `Japanese` or `es-ES`,
+    /// independent of the language of the wizard. English when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_language: Option<String>,
+
+    // Reproducibility
+    /// Seed of the project's procedural generation, mixed into the seeds of
+    /// dungeon layouts and generated names and sent with text requests.
+    /// Unset, those are seeded by name alone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generation_seed: Option<u64>,
+}
+
+impl GameConfig {
+    /// Ask for the player-facing text of a prompt in the content language
+    pub fn localize_prompt(&self, prompt: String) -> String {
+        crate::text::with_content_language(prompt, self.content_language.as_deref())
+    }
+
+    /// Seed of procedural content named `name`, the same for the same name
+    /// and generation seed
+    pub fn procedural_seed(&self, name: &str) -> u64 {
+        let name_seed = crate::maps::hash(&name.bytes().map(u64::from).collect::<Vec<_>>());
+        match self.generation_seed {
+            Some(seed) => crate::maps::hash(&[seed, name_seed]),
+            None => name_seed,
+        }
+    }
+
+    /// Send the generation seed with a text request
+    pub fn seed_text(&self, config: crate::text::TextConfig) -> crate::text::TextConfig {
+        config.with_seed(self.generation_seed)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -266,3 +301,109 @@ pub struct WorldSetting {
     pub theme: String,
     pub regions: Vec<String>,
 }
+
+/// A small game shared by the unit tests
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::*;
+    use serde_json::json;
+
+    pub fn config() -> GameConfig {
+        let town = |name: &str, inn: bool| {
+            json!({
+                "name": name, "size": "small", "description": "", "shops": ["Item Shop"],
+                "key_npcs": ["Elder Mirren"], "inn": inn, "save_point": inn
+            })
+        };
+        serde_json::from_value(json!({
+            "name": "Chronicles of Eldoria",
+            "tagline": "", "genre": "RPG", "setting": "fantasy", "era": "16-bit",
+            "art_style": {
+                "sprite_size": 16, "tile_size": 16, "animation_frames": {},
+                "perspective": "3/4 top-down", "shading": "flat",
+                "outline": {"enabled": true, "color": "#000000", "thickness": 1}
+            },
+            "color_palette": {
+                "primary": ["#1a1c2c", "#5d275d", "#b13e53"],
+                "secondary": ["#38b764", "#41a6f6"], "ui": [], "effects": ["#ffcd75"]
+            },
+            "reference_games": [],
+            "world": {
+                "name": "Eldoria", "size": "small",
+                "regions": [
+                    {"name": "Greenvale", "biome": "plains", "description": "",
+                     "key_locations": ["Aldwyn"]},
+                    {"name": "Korin Wastes", "biome": "desert", "description": "",
+                     "key_locations": ["Sunken Vault"]}
+                ],
+                "connections": []
+            },
+            "towns": [town("Aldwyn", true), town("Port Sylva", false)],
+            "dungeons": [{
+                "name": "Sunken Vault", "theme": "ruins", "floors": 2, "boss": "Warden",
+                "treasures": ["Frost Blade"], "gimmick": "rising water"
+            }],
+            "party_system": {
+                "max_party_size": 3, "switchable": false, "formation_system": false,
+                "character_classes": []
+            },
+            "combat_system": {"style": "turn-based", "features": []},
+            "dialog_system": {"style": "branching", "portrait_style": "", "text_effects": []},
+            "inventory_system": {
+                "grid_based": false, "capacity": "", "categories": [], "equipment_slots": []
+            },
+            "shop_system": {
+                "currency": "gold", "haggling": false, "shop_types": [], "special_shops": []
+            },
+            "quest_system": {"journal": true, "markers": true, "reward_types": []},
+            "main_quest": {
+                "name": "The Drowned Crown", "description": "",
+                "steps": [{"description": "Clear the vault", "objective_type": "visit",
+                           "location": "Sunken Vault"}],
+                "rewards": ["Frost Blade"]
+            },
+            "side_quests": [],
+            "characters": [{
+                "name": "Lyra", "role": "hero", "personality": "", "backstory": "",
+                "portrait_description": ""
+            }],
+            "music_style": "chiptune",
+            "sound_effects_style": "chiptune",
+            "generation_seed": 42
+        }))
+        .expect("fixture config")
+    }
+
+    pub fn world() -> WorldData {
+        serde_json::from_value(json!({
+            "regions": [
+                {"name": "Greenvale", "map_data": "", "encounters": ["Slime"], "music": ""},
+                {"name": "Korin Wastes", "map_data": "", "encounters": ["Sand Wurm"],
+                 "music": ""}
+            ],
+            "connections": [
+                {"from": "Greenvale", "to": "Korin Wastes", "connection_type": "road"}
+            ],
+            "towns": [
+                {"name": "Aldwyn", "map_data": "", "npcs": [], "shops": [{
+                    "name": "Item Shop", "inventory": ["Potion", "Frost Blade"],
+                    "prices": {"Potion": 20}
+                }]},
+                {"name": "Port Sylva", "map_data": "", "npcs": [], "shops": [{
+                    "name": "Armory", "inventory": ["Iron Helm", "Mythril Mail"],
+                    "prices": {"Mythril Mail": 1200}
+                }]}
+            ],
+            "dungeons": [{
+                "name": "Sunken Vault",
+                "floors": [
+                    {"layout": "", "encounters": ["Slime"], "treasures": ["Ether"]},
+                    {"layout": "", "encounters": ["Drowned Knight"], "treasures": []}
+                ],
+                "boss": {"name": "Warden", "sprite": "warden", "attacks": ["Tidal Slam"],
+                         "dialog": ""}
+            }]
+        }))
+        .expect("fixture world")
+    }
+}
@@ -0,0 +1,110 @@
+//! Google Gemini
+//!
+//! Gemini serves the OpenAI endpoints the generators use from its
+//! OpenAI-compatible API: chat completions with Gemini models, image
+//! generations with Imagen (used for sprite drafts) and embeddings. Only the
+//! model names differ, so [`GeminiConfig`] maps every model the generators
+//! ask for onto the Gemini model answering it; a model without a mapping is
+//! sent as it is, so Gemini models can be asked for by name. In
+//! `project.toml` or the AI configuration:
+//!
+//! ```toml
+//! ai_provider = "gemini"
+//!
+//! [gemini.models]
+//! "gpt-4" = "gemini-2.5-pro"
+//! "dall-e-3" = "imagen-4.0-generate-001"
+//! ```
+//!
+//! Mappings are added to the defaults below. Usage is recorded under the
+//! Gemini model and priced as such, so the token counter's cost by model
+//! compares runs across providers. Keys are read from `GEMINI_API_KEYS` or
+//! `GEMINI_API_KEY` unless configured otherwise. Gemini has no speech or
+//! image edit endpoint.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Provider name selecting Gemini
+pub const GEMINI_PROVIDER: &str = "gemini";
+
+/// OpenAI-compatible API of Gemini
+pub const DEFAULT_API_BASE: &str = "https://generativelanguage.googleapis.com/v1beta/openai";
+
+/// Gemini model answering each model the generators ask for by default
+const DEFAULT_MODELS: &[(&str, &str)] = &[
+    ("gpt-4", "gemini-2.5-pro"),
+    ("gpt-4-32k", "gemini-2.5-pro"),
+    ("gpt-4-turbo", "gemini-2.5-pro"),
+    ("gpt-4o", "gemini-2.5-flash"),
+    ("gpt-4o-mini", "gemini-2.5-flash-lite"),
+    ("gpt-3.5-turbo", "gemini-2.5-flash-lite"),
+    ("dall-e-2", "imagen-3.0-generate-002"),
+    ("dall-e-3", "imagen-3.0-generate-002"),
+    ("gpt-image-1", "imagen-3.0-generate-002"),
+    ("gpt-image-1-mini", "imagen-3.0-generate-002"),
+    ("gpt-image-1.5", "imagen-3.0-generate-002"),
+    ("text-embedding-3-small", "gemini-embedding-001"),
+    ("text-embedding-3-large", "gemini-embedding-001"),
+    ("text-embedding-ada-002", "gemini-embedding-001"),
+];
+
+fn default_api_base() -> String {
+    DEFAULT_API_BASE.to_string()
+}
+
+/// Gemini API and the models answering the generators' requests
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeminiConfig {
+    #[serde(default = "default_api_base")]
+    pub api_base: String,
+    /// Gemini model answering each requested model, over the defaults
+    #[serde(default)]
+    pub models: BTreeMap<String, String>,
+}
+
+impl Default for GeminiConfig {
+    fn default() -> Self {
+        Self {
+            api_base: default_api_base(),
+            models: BTreeMap::new(),
+        }
+    }
+}
+
+impl GeminiConfig {
+    pub fn with_api_base(mut self, api_base: impl Into<String>) -> Self {
+        self.api_base = api_base.into();
+        self
+    }
+
+    /// Answer requests for `model` with `gemini_model`
+    pub fn with_model(mut self, model: impl Into<String>, gemini_model: impl Into<String>) -> Self {
+        self.models.insert(model.into(), gemini_model.into());
+        self
+    }
+
+    /// The configured mappings over the defaults
+    pub fn models(&self) -> BTreeMap<String, String> {
+        let mut models: BTreeMap<String, String> = DEFAULT_MODELS
+            .iter()
+            .map(|(model, gemini)| (model.to_string(), gemini.to_string()))
+            .collect();
+        models.extend(self.models.clone());
+        models
+    }
+
+    /// Gemini model answering `model`. Dated snapshots such as
+    /// `gpt-4o-2024-08-06` are answered like the model they start with.
+    pub fn model(&self, model: &str) -> String {
+        let models = self.models();
+        if let Some(gemini) = models.get(model) {
+            return gemini.clone();
+        }
+        models
+            .iter()
+            .filter(|(name, _)| model.starts_with(&format!("{name}-")))
+            .max_by_key(|(name, _)| name.len())
+            .map_or_else(|| model.to_string(), |(_, gemini)| gemini.clone())
+    }
+}
@@ -0,0 +1,211 @@
+//! Palette-constrained skies and gradients
+//!
+//! Menus and cutscenes need backdrops more than illustrations, and a 16-bit
+//! sky is cheap to draw locally. A gradient is split into horizontal bands;
+//! each band's color is mixed from the two palette colors that bracket it
+//! with a 4×4 Bayer pattern, so the sky stays within the palette, reads as
+//! smooth from a distance and keeps the banding of the era up close. Skies
+//! follow the hour of the day through night, dawn, day and dusk keyframes,
+//! and the night sky gets stars. The same inputs always give the same image.
+
+use image::{Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use crate::consistency::Color;
+use crate::maps::hash;
+
+/// Menu and cutscene backdrop size, the SNES screen
+pub const BACKDROP_SIZE: (u32, u32) = (256, 224);
+
+/// Bands a sky is split into
+pub const DEFAULT_BANDS: u32 = 12;
+
+/// 4×4 Bayer thresholds, in sixteenths
+const BAYER_4X4: [[u32; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// How much the contrast of two mixed colors counts against the pair, so a
+/// pink isn't dithered from red and green
+const DITHER_NOISE_WEIGHT: f32 = 0.5;
+
+/// Zenith luma below which stars come out
+const MAX_STARRY_LUMA: f32 = 48.0;
+
+/// Stars per thousand pixels in the upper half of a night sky
+const STARS_PER_MILLE: u64 = 4;
+
+/// Sky colors from zenith to horizon at an hour of the day
+const KEYFRAMES: &[(f32, [u32; 3])] = &[
+    (0.0, [0x0b0d2a, 0x1b1f4a, 0x2e2a5c]),
+    (5.0, [0x0b0d2a, 0x1b1f4a, 0x2e2a5c]),
+    (6.5, [0x2b3a7a, 0xc06c9a, 0xf5b06a]),
+    (9.0, [0x2a6fd6, 0x5aa0ee, 0xa8d8f8]),
+    (16.0, [0x2a6fd6, 0x5aa0ee, 0xa8d8f8]),
+    (18.5, [0x1e2a6a, 0xb04a6a, 0xf08a3a]),
+    (20.0, [0x0b0d2a, 0x1b1f4a, 0x2e2a5c]),
+    (24.0, [0x0b0d2a, 0x1b1f4a, 0x2e2a5c]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeOfDay {
+    Dawn,
+    Day,
+    Dusk,
+    Night,
+}
+
+impl TimeOfDay {
+    pub const ALL: [TimeOfDay; 4] = [Self::Dawn, Self::Day, Self::Dusk, Self::Night];
+
+    /// Hour the time of day looks most like itself
+    pub fn hour(self) -> f32 {
+        match self {
+            TimeOfDay::Dawn => 6.5,
+            TimeOfDay::Day => 12.0,
+            TimeOfDay::Dusk => 18.5,
+            TimeOfDay::Night => 0.0,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TimeOfDay::Dawn => "dawn",
+            TimeOfDay::Day => "day",
+            TimeOfDay::Dusk => "dusk",
+            TimeOfDay::Night => "night",
+        }
+    }
+}
+
+/// Zenith, middle and horizon colors of the sky at the hour (0.0 - 24.0),
+/// blended between the nearest keyframes
+pub fn sky_colors(hour: f32) -> [Color; 3] {
+    let hour = hour.rem_euclid(24.0);
+    let next = KEYFRAMES
+        .iter()
+        .position(|&(at, _)| at >= hour)
+        .unwrap_or(KEYFRAMES.len() - 1)
+        .max(1);
+    let (from_hour, from) = KEYFRAMES[next - 1];
+    let (to_hour, to) = KEYFRAMES[next];
+    let t = ((hour - from_hour) / (to_hour - from_hour).max(f32::EPSILON)).clamp(0.0, 1.0);
+    std::array::from_fn(|i| mix(rgb(from[i]), rgb(to[i]), t))
+}
+
+/// A sky at the hour, in palette colors
+pub fn render_sky(size: (u32, u32), hour: f32, palette: &[Color]) -> RgbaImage {
+    let stops = sky_colors(hour);
+    let mut image = render_gradient(size, &stops, DEFAULT_BANDS, palette);
+    if luma(stops[0]) < MAX_STARRY_LUMA
+        && let Some(&star) = palette
+            .iter()
+            .max_by(|a, b| luma(**a).total_cmp(&luma(**b)))
+    {
+        let (width, height) = image.dimensions();
+        for y in 0..height / 2 {
+            for x in 0..width {
+                if hash(&[u64::from(x), u64::from(y)]) % 1000 < STARS_PER_MILLE {
+                    image.put_pixel(x, y, Rgba([star.r, star.g, star.b, 255]));
+                }
+            }
+        }
+    }
+    image
+}
+
+/// A vertical gradient through the stops, top to bottom, split into `bands`
+/// and dithered between palette colors. An empty palette keeps the exact
+/// band colors.
+pub fn render_gradient(
+    size: (u32, u32),
+    stops: &[Color],
+    bands: u32,
+    palette: &[Color],
+) -> RgbaImage {
+    let (width, height) = (size.0.max(1), size.1.max(1));
+    let bands = bands.clamp(1, height);
+    let mixes: Vec<(Color, Color, u32)> = (0..bands)
+        .map(|band| {
+            let t = (band as f32 + 0.5) / bands as f32;
+            bracket(sample(stops, t), palette)
+        })
+        .collect();
+
+    RgbaImage::from_fn(width, height, |x, y| {
+        let (low, high, sixteenths) = mixes[(y * bands / height) as usize];
+        let color = if sixteenths > BAYER_4X4[(y % 4) as usize][(x % 4) as usize] {
+            high
+        } else {
+            low
+        };
+        Rgba([color.r, color.g, color.b, 255])
+    })
+}
+
+/// Color of the gradient at `t` (0.0 - 1.0)
+fn sample(stops: &[Color], t: f32) -> Color {
+    match stops {
+        [] => Color::new(0, 0, 0),
+        [only] => *only,
+        _ => {
+            let position = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+            let index = (position as usize).min(stops.len() - 2);
+            mix(stops[index], stops[index + 1], position - index as f32)
+        }
+    }
+}
+
+/// The two palette colors whose mix comes closest to the target without
+/// visible noise, and how many sixteenths of the second to use
+fn bracket(target: Color, palette: &[Color]) -> (Color, Color, u32) {
+    let Some(&low) = palette.iter().min_by_key(|&&c| distance(c, target)) else {
+        return (target, target, 0);
+    };
+    let along = |high: Color| {
+        let span = [
+            high.r as f32 - low.r as f32,
+            high.g as f32 - low.g as f32,
+            high.b as f32 - low.b as f32,
+        ];
+        let offset = [
+            target.r as f32 - low.r as f32,
+            target.g as f32 - low.g as f32,
+            target.b as f32 - low.b as f32,
+        ];
+        let length: f32 = span.iter().map(|s| s * s).sum();
+        let dot: f32 = span.iter().zip(&offset).map(|(s, o)| s * o).sum();
+        (dot / length.max(f32::EPSILON)).clamp(0.0, 1.0)
+    };
+    let best = palette
+        .iter()
+        .filter(|&&c| c != low)
+        .map(|&high| {
+            let f = along(high);
+            let noise = distance(low, high) as f32 * f * (1.0 - f) * DITHER_NOISE_WEIGHT;
+            (high, f, distance(mix(low, high, f), target) + noise as i32)
+        })
+        .min_by_key(|&(_, _, cost)| cost);
+    match best {
+        Some((high, f, _)) => (low, high, (f * 16.0).round() as u32),
+        None => (low, low, 0),
+    }
+}
+
+fn mix(a: Color, b: Color, t: f32) -> Color {
+    let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    Color::new(channel(a.r, b.r), channel(a.g, b.g), channel(a.b, b.b))
+}
+
+fn distance(a: Color, b: Color) -> i32 {
+    let dr = a.r as i32 - b.r as i32;
+    let dg = a.g as i32 - b.g as i32;
+    let db = a.b as i32 - b.b as i32;
+    dr * dr + dg * dg + db * db
+}
+
+fn luma(color: Color) -> f32 {
+    0.299 * color.r as f32 + 0.587 * color.g as f32 + 0.114 * color.b as f32
+}
+
+fn rgb(hex: u32) -> Color {
+    Color::new((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+}
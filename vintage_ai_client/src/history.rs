@@ -0,0 +1,170 @@
+//! Version history of generated artifacts
+//!
+//! Every time an artifact is generated its file is copied into
+//! `assets/history/<artifact>/` as the next version (`v1.png`, `v2.png`, ...)
+//! and listed in that directory's `versions.json`. Regenerating an asset
+//! therefore never loses the earlier results; any version can be compared
+//! with the others and restored over the current file, which itself becomes
+//! a new version first so a rollback can be undone.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Directory under the project's `assets/` holding the versions
+pub const HISTORY_DIR: &str = "history";
+
+/// File listing an artifact's versions inside its history directory
+pub const VERSIONS_FILE: &str = "versions.json";
+
+/// One kept version of an artifact
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactVersion {
+    /// Counting from 1
+    pub version: u32,
+    /// File name inside the artifact's history directory
+    pub file: String,
+    /// Where the artifact lives in the project, for rolling back
+    #[serde(default)]
+    pub source: Option<PathBuf>,
+    /// Prompt, tweak or other note on how the version came about
+    #[serde(default)]
+    pub note: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Versions of every artifact of a project
+#[derive(Debug, Clone)]
+pub struct ArtifactHistory {
+    dir: PathBuf,
+}
+
+impl ArtifactHistory {
+    /// History of the project at `project_dir`, under `assets/history/`
+    pub fn for_project(project_dir: &Path) -> Self {
+        Self {
+            dir: project_dir.join("assets").join(HISTORY_DIR),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Names of the artifacts with at least one version, sorted
+    pub fn artifacts(&self) -> Result<Vec<String>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)
+            .with_context(|| format!("Failed to read {}", self.dir.display()))?
+        {
+            let entry = entry?;
+            if entry.path().join(VERSIONS_FILE).exists() {
+                names.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Versions of an artifact, oldest first
+    pub fn versions(&self, name: &str) -> Result<Vec<ArtifactVersion>> {
+        let path = self.artifact_dir(name).join(VERSIONS_FILE);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Where a version's file is kept
+    pub fn version_path(&self, name: &str, version: &ArtifactVersion) -> PathBuf {
+        self.artifact_dir(name).join(&version.file)
+    }
+
+    /// Keep a copy of the artifact's current file as its next version.
+    /// Nothing is recorded if the file matches the latest version.
+    pub fn record(&self, name: &str, source: &Path, note: &str) -> Result<Option<ArtifactVersion>> {
+        let bytes = std::fs::read(source)
+            .with_context(|| format!("Failed to read {}", source.display()))?;
+        let extension = source
+            .extension()
+            .map(|ext| ext.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "bin".to_string());
+        self.record_bytes(name, &bytes, &extension, Some(source), note)
+    }
+
+    /// Keep the contents as the artifact's next version, e.g. a text
+    /// artifact that only lives in memory or a checkpoint. Nothing is
+    /// recorded if they match the latest version.
+    pub fn record_bytes(
+        &self,
+        name: &str,
+        bytes: &[u8],
+        extension: &str,
+        source: Option<&Path>,
+        note: &str,
+    ) -> Result<Option<ArtifactVersion>> {
+        let mut versions = self.versions(name)?;
+        if let Some(latest) = versions.last()
+            && std::fs::read(self.version_path(name, latest)).is_ok_and(|kept| kept == bytes)
+        {
+            return Ok(None);
+        }
+
+        let number = versions.last().map_or(1, |latest| latest.version + 1);
+        let version = ArtifactVersion {
+            version: number,
+            file: format!("v{number}.{extension}"),
+            source: source
+                .map(Path::to_path_buf)
+                .or_else(|| versions.last().and_then(|latest| latest.source.clone())),
+            note: note.to_string(),
+            created_at: Utc::now(),
+        };
+        let dir = self.artifact_dir(name);
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        std::fs::write(dir.join(&version.file), bytes)
+            .with_context(|| format!("Failed to write version {number} of {name}"))?;
+        versions.push(version.clone());
+        std::fs::write(
+            dir.join(VERSIONS_FILE),
+            serde_json::to_string_pretty(&versions)?,
+        )
+        .with_context(|| format!("Failed to write the version list of {name}"))?;
+        Ok(Some(version))
+    }
+
+    /// Copy a version back over the artifact's file. The file's current
+    /// contents are recorded first, so the rollback can itself be undone.
+    pub fn restore(&self, name: &str, version: u32) -> Result<PathBuf> {
+        let versions = self.versions(name)?;
+        let kept = versions
+            .iter()
+            .find(|v| v.version == version)
+            .with_context(|| format!("{name} has no version {version}"))?;
+        let target = kept
+            .source
+            .clone()
+            .with_context(|| format!("No project file is known for {name}"))?;
+        if target.exists() {
+            self.record(name, &target, "Before rolling back")?;
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(self.version_path(name, kept), &target)
+            .with_context(|| format!("Failed to restore version {version} of {name}"))?;
+        Ok(target)
+    }
+
+    fn artifact_dir(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+}
@@ -6,13 +6,20 @@
 //! - Palette quantization and recoloring
 //! - Batch generation with rate limiting
 //! - Smart caching to reduce costs
+//! - Optional vision model critique feeding retries
 
 use anyhow::{Context, Result};
 use async_openai::{
     Client,
-    config::OpenAIConfig,
+    types::chat::{
+        ChatCompletionRequestMessageContentPartImage, ChatCompletionRequestMessageContentPartText,
+        ChatCompletionRequestUserMessageArgs, ChatCompletionRequestUserMessageContent,
+        ChatCompletionRequestUserMessageContentPart, CreateChatCompletionRequestArgs,
+        CreateChatCompletionResponse, ImageUrl, ResponseFormat,
+    },
     types::images::{
-        CreateImageRequestArgs, Image, ImageModel, ImageQuality, ImageResponseFormat, ImageSize,
+        CreateImageEditRequestArgs, CreateImageRequestArgs, Image, ImageInput, ImageModel,
+        ImageQuality, ImageResponseFormat, ImageSize, ImagesResponse,
     },
 };
 use base64::Engine;
@@ -20,27 +27,64 @@ use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
 use minijinja::Environment;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{Mutex, Semaphore};
 
 use super::{
     AiConfig, AiGenerator,
-    cache::{AiCache, ImageCache},
-    consistency::{Color, ColorPalette, StyleManager},
-    tokens::TokenCounter,
+    background::{BackgroundRemoval, BackgroundRemovalConfig, remove_background},
+    cache::{AiCache, CachedData, ImageCache},
+    consistency::{
+        Color, ColorPalette, StyleConfig, StyleManager,
+        downscaling::DownscaleMethod,
+        frame_consistency::{self, FrameTolerance},
+        indexed::IndexedPalette,
+        scaling,
+        style_extraction::ExtractedStyle,
+        tiling,
+    },
+    credentials::{KeyedConfig, retry_keys},
+    prompts::PromptLibrary,
+    quota::{self, QuotaTracker},
+    refusals::{self, NeedsManualRewrite},
+    telemetry::RequestTimer,
+    tokens::{CostEstimate, TokenCounter},
 };
 
 /// Image generator with style consistency
 #[derive(Clone)]
 pub struct ImageGenerator {
-    client: Arc<Client<OpenAIConfig>>,
+    client: Arc<Client<KeyedConfig>>,
     cache: Arc<Mutex<AiCache>>,
     image_cache: ImageCache,
     token_counter: Arc<Mutex<TokenCounter>>,
     style_manager: Arc<Mutex<StyleManager>>,
     batch_semaphore: Arc<Semaphore>,
     template_env: Arc<Mutex<Environment<'static>>>,
+    background_removal: BackgroundRemovalConfig,
+    frame_tolerance: FrameTolerance,
+    vision_critique: bool,
+}
+
+/// Edge bands blended when correcting a seaming tile, as a fraction of its size
+const TILE_BLEND_FRACTION: u32 = 8;
+
+/// Chat model that describes reference sprites and screenshots and critiques
+/// generated images
+const VISION_MODEL: &str = "gpt-4o";
+
+/// How a previously accepted sprite conditions a new one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ReferenceMode {
+    /// Describe the reference with a vision model and generate from the
+    /// description; works with every image model
+    #[default]
+    Describe,
+    /// Send the reference to the image edit endpoint, which keeps the
+    /// character closest to the original
+    Edit,
 }
 
 /// Configuration for image generation
@@ -117,6 +161,29 @@ impl ImageConfig {
         }
     }
 
+    /// API name of the configured model
+    pub fn model_name(&self) -> &str {
+        match &self.model {
+            ImageModel::DallE2 => "dall-e-2",
+            ImageModel::DallE3 => "dall-e-3",
+            ImageModel::GptImage1 => "gpt-image-1",
+            ImageModel::GptImage1dot5 => "gpt-image-1.5",
+            ImageModel::GptImage1Mini => "gpt-image-1-mini",
+            ImageModel::Other(name) => name.as_str(),
+        }
+    }
+
+    /// Key into the pricing table, e.g. `dall-e-3-1024x1024-standard`
+    pub fn pricing_key(&self) -> String {
+        let model = self.model_name();
+        let (width, height) = Self::get_dimensions(&self.size);
+        let quality = match self.quality {
+            ImageQuality::HD => "hd",
+            _ => "standard",
+        };
+        format!("{model}-{width}x{height}-{quality}")
+    }
+
     /// Configuration for sprite generation
     pub fn for_sprites() -> Self {
         Self {
@@ -129,6 +196,76 @@ impl ImageConfig {
         }
     }
 
+    /// Configuration for edits of a reference sprite
+    pub fn for_reference_edits() -> Self {
+        Self {
+            model: ImageModel::GptImage1,
+            size: ImageSize::S1024x1024,
+            quality: ImageQuality::Medium,
+            n: 1,
+            response_format: ImageResponseFormat::B64Json,
+            enforce_consistency: true,
+        }
+    }
+
+    /// Configuration for small previews
+    pub fn for_thumbnails() -> Self {
+        Self {
+            model: ImageModel::DallE2,
+            size: ImageSize::S512x512,
+            quality: ImageQuality::Standard,
+            n: 1,
+            response_format: ImageResponseFormat::B64Json,
+            enforce_consistency: true,
+        }
+    }
+
+    /// Generate with the model named `model`, in HD when `hd` and the
+    /// model has it. Sizes the model doesn't offer become 1024x1024.
+    pub fn with_model(mut self, model: &str, hd: bool) -> Self {
+        self.model = match model {
+            "dall-e-2" => ImageModel::DallE2,
+            "dall-e-3" => ImageModel::DallE3,
+            "gpt-image-1" => ImageModel::GptImage1,
+            "gpt-image-1.5" => ImageModel::GptImage1dot5,
+            "gpt-image-1-mini" => ImageModel::GptImage1Mini,
+            other => ImageModel::Other(other.to_string()),
+        };
+        match self.model {
+            ImageModel::DallE2 => {
+                self.quality = ImageQuality::Standard;
+                if !matches!(
+                    self.size,
+                    ImageSize::S256x256 | ImageSize::S512x512 | ImageSize::S1024x1024
+                ) {
+                    self.size = ImageSize::S1024x1024;
+                }
+            }
+            ImageModel::DallE3 => {
+                self.quality = if hd {
+                    ImageQuality::HD
+                } else {
+                    ImageQuality::Standard
+                };
+                if !matches!(
+                    self.size,
+                    ImageSize::S1024x1024 | ImageSize::S1792x1024 | ImageSize::S1024x1792
+                ) {
+                    self.size = ImageSize::S1024x1024;
+                }
+            }
+            _ => {
+                if hd {
+                    self.quality = ImageQuality::High;
+                } else if matches!(self.quality, ImageQuality::Standard | ImageQuality::HD) {
+                    // Qualities of the DALL-E models only
+                    self.quality = ImageQuality::Auto;
+                }
+            }
+        }
+        self
+    }
+
     /// Configuration for background/tileset generation
     pub fn for_backgrounds() -> Self {
         Self {
@@ -181,10 +318,11 @@ impl ImageConfig {
 impl ImageGenerator {
     /// Create a new image generator
     pub fn new(
-        client: Arc<Client<OpenAIConfig>>,
+        client: Arc<Client<KeyedConfig>>,
         cache: Arc<Mutex<AiCache>>,
         token_counter: Arc<Mutex<TokenCounter>>,
         style_manager: Arc<Mutex<StyleManager>>,
+        prompts: &PromptLibrary,
     ) -> Self {
         // Extract the inner AiCache from the Mutex
         let inner_cache = cache
@@ -194,21 +332,8 @@ impl ImageGenerator {
             .unwrap_or_else(|| Arc::new(AiCache::new().unwrap()));
         let image_cache = ImageCache::new(inner_cache);
 
-        let mut env = Environment::new();
-
-        // Load all image prompt templates
-        let templates = [
-            (
-                "style_guide",
-                include_str!("../prompts/image/style_guide.jinja"),
-            ),
-            ("sprite", include_str!("../prompts/image/sprite.jinja")),
-            ("tileset", include_str!("../prompts/image/tileset.jinja")),
-        ];
-
-        for (name, template) in templates {
-            env.add_template(name, template).ok();
-        }
+        // Image prompt templates, overridden by the project's
+        let env = prompts.environment("image");
 
         Self {
             client,
@@ -218,11 +343,64 @@ impl ImageGenerator {
             style_manager,
             batch_semaphore: Arc::new(Semaphore::new(3)), // Max 3 concurrent image generations
             template_env: Arc::new(Mutex::new(env)),
+            background_removal: BackgroundRemovalConfig::default(),
+            frame_tolerance: FrameTolerance::default(),
+            vision_critique: false,
         }
     }
 
+    /// Choose which kinds of images get their solid background removed
+    pub fn with_background_removal(mut self, config: BackgroundRemovalConfig) -> Self {
+        self.background_removal = config;
+        self
+    }
+
+    /// How far frames of one animation may drift before they are re-requested
+    pub fn with_frame_tolerance(mut self, tolerance: FrameTolerance) -> Self {
+        self.frame_tolerance = tolerance;
+        self
+    }
+
+    /// Have a vision model critique every attempt in addition to the pixel
+    /// checks; its issues are added to the prompt of the next attempt
+    pub fn with_vision_critique(mut self, enabled: bool) -> Self {
+        self.vision_critique = enabled;
+        self
+    }
+
+    /// The active style, e.g. for the frame counts of its animations
+    pub async fn style(&self) -> StyleConfig {
+        self.style_manager.lock().await.get_style().await
+    }
+
+    /// The active palette as PNG color indices, see
+    /// [`StyleManager::indexed_palette`]
+    pub async fn indexed_palette(&self) -> Result<IndexedPalette> {
+        self.style_manager.lock().await.indexed_palette().await
+    }
+
     /// Generate a style guide that establishes visual consistency
     pub async fn generate_style_guide(&self, concept: &GameConcept) -> Result<Vec<u8>> {
+        let style_guide_prompt = self.style_guide_prompt(concept).await?;
+
+        // Generate with validation
+        let result = self
+            .generate_with_validation(
+                &style_guide_prompt,
+                ImageConfig::for_sprites(),
+                ValidationCriteria::StyleGuide,
+                5, // max attempts
+            )
+            .await?;
+
+        // Extract and store style information
+        self.extract_style_information(&result).await?;
+
+        Ok(result)
+    }
+
+    /// Render the style guide template for a concept in the active style
+    pub async fn style_guide_prompt(&self, concept: &GameConcept) -> Result<String> {
         let style_config = self.style_manager.lock().await.get_style().await;
 
         // Prepare context for template
@@ -237,6 +415,7 @@ impl ImageGenerator {
             "outline_style": self.format_outline(&style_config.rules.outline_style),
             "perspective": self.format_perspective(&style_config.rules.perspective),
             "visual_inspirations": concept.visual_inspirations.join(", "),
+            "reference_screens": concept.reference_screens,
             "mood": concept.mood,
             "style_name": style_config.style_name,
         });
@@ -246,33 +425,465 @@ impl ImageGenerator {
         let template = env
             .get_template("style_guide")
             .context("Failed to get style guide template")?;
-        let style_guide_prompt = template
+        template
             .render(&context)
-            .context("Failed to render style guide template")?;
+            .context("Failed to render style guide template")
+    }
+
+    /// Generate a sprite with enforced consistency
+    pub async fn generate_sprite(
+        &self,
+        sprite_type: &str,
+        description: &str,
+        _style_guide: Option<&[u8]>,
+    ) -> Result<Vec<u8>> {
+        let prompt = self.sprite_prompt(sprite_type, description).await?;
 
         // Generate with validation
-        let result = self
+        let sprite = self
             .generate_with_validation(
-                &style_guide_prompt,
+                &prompt,
                 ImageConfig::for_sprites(),
-                ValidationCriteria::StyleGuide,
-                5, // max attempts
+                ValidationCriteria::Sprite(sprite_type.to_string()),
+                3,
             )
             .await?;
 
-        // Extract and store style information
-        self.extract_style_information(&result).await?;
+        // Post-process for consistency
+        let processed = self.enforce_palette_consistency(&sprite).await?;
 
-        Ok(result)
+        Ok(processed)
     }
 
-    /// Generate a sprite with enforced consistency
-    pub async fn generate_sprite(
+    /// Generate a tile that wraps seamlessly.
+    ///
+    /// Tiles whose opposite edges don't continue into each other are
+    /// regenerated with the failing edges named in the prompt; if the last
+    /// attempt still seams, the edges are wrap-blended.
+    pub async fn generate_tile(&self, tile_type: &str, description: &str) -> Result<Vec<u8>> {
+        let prompt = self
+            .sprite_prompt(&format!("tile_{tile_type}"), description)
+            .await?;
+
+        let tile = self
+            .generate_with_validation(
+                &prompt,
+                ImageConfig::for_sprites(),
+                ValidationCriteria::Tileset(tile_type.to_string()),
+                3,
+            )
+            .await?;
+
+        let img = image::load_from_memory(&tile)?;
+        let seams = tiling::measure_seams(&img);
+        let tile = if seams.is_seamless() {
+            tile
+        } else {
+            tracing::info!(
+                "Wrap-blending tile {} (seams {:.3}/{:.3})",
+                tile_type,
+                seams.horizontal,
+                seams.vertical
+            );
+            let (width, height) = img.dimensions();
+            let band = (width.min(height) / TILE_BLEND_FRACTION).max(1);
+            let mut buffer = Vec::new();
+            tiling::make_seamless(&img, band).write_to(
+                &mut std::io::Cursor::new(&mut buffer),
+                image::ImageFormat::Png,
+            )?;
+            buffer
+        };
+
+        self.enforce_palette_consistency(&tile).await
+    }
+
+    /// Generate a variation of an accepted sprite, e.g. an NPC in another
+    /// outfit, that stays on-model with the reference
+    pub async fn generate_sprite_from_reference(
         &self,
         sprite_type: &str,
-        description: &str,
-        _style_guide: Option<&[u8]>,
+        variation: &str,
+        reference: &[u8],
+        mode: ReferenceMode,
     ) -> Result<Vec<u8>> {
+        match mode {
+            ReferenceMode::Describe => {
+                let reference_description = self.describe_reference(reference).await?;
+                let description = format!(
+                    "{variation}. The character must stay on-model with this reference: {reference_description}"
+                );
+                self.generate_sprite(sprite_type, &description, None).await
+            }
+            ReferenceMode::Edit => {
+                let prompt = self
+                    .sprite_prompt(
+                        sprite_type,
+                        &format!(
+                            "The same character as in the image, with the same proportions, \
+                             face and colors: {variation}"
+                        ),
+                    )
+                    .await?;
+                let sprite = self.edit_reference(&prompt, reference).await?;
+                let sprite = self.remove_solid_background(
+                    sprite,
+                    &ValidationCriteria::Sprite(sprite_type.to_string()),
+                )?;
+                self.enforce_palette_consistency(&sprite).await
+            }
+        }
+    }
+
+    /// Describe a sprite's appearance precisely enough to redraw it
+    pub async fn describe_reference(&self, reference: &[u8]) -> Result<String> {
+        let mut params = HashMap::new();
+        params.insert("model".to_string(), VISION_MODEL.to_string());
+        let reference_hash = format!("{:x}", Sha256::digest(reference));
+
+        let cache_key = self
+            .cache
+            .lock()
+            .await
+            .key_for("reference_description", &reference_hash, &params)
+            .await;
+        if let Some(cached) = self.cache.lock().await.get(&cache_key).await
+            && let CachedData::Text(text) = cached.data
+        {
+            return Ok(text);
+        }
+
+        let description = self
+            .ask_vision(
+                "Describe this pixel art game character so an artist can redraw it on-model: \
+                 body proportions and height in heads, silhouette, face, hair, clothing, \
+                 accessories, and the exact colors of each part. Reply with one dense \
+                 paragraph and nothing else.",
+                reference,
+                400,
+                None,
+            )
+            .await
+            .context("Failed to describe reference sprite")?;
+
+        let cache_params: HashMap<String, serde_json::Value> = params
+            .into_iter()
+            .map(|(k, v)| (k, serde_json::Value::String(v)))
+            .collect();
+        self.cache
+            .lock()
+            .await
+            .put(
+                cache_key,
+                CachedData::Text(description.clone()),
+                cache_params,
+            )
+            .await?;
+
+        Ok(description)
+    }
+
+    /// Review an image against the style rules with a vision model
+    ///
+    /// Catches what the pixel checks can't see, such as a wrong perspective,
+    /// a cropped subject or scenery drawn behind a sprite.
+    pub async fn critique_image(
+        &self,
+        data: &[u8],
+        criteria: &ValidationCriteria,
+    ) -> Result<ImageCritique> {
+        let style = self.style_manager.lock().await.get_style().await;
+        let mut rules = vec![
+            format!(
+                "{} perspective",
+                self.format_perspective(&style.rules.perspective)
+            ),
+            self.format_outline(&style.rules.outline_style).to_string(),
+            format!(
+                "{} shading",
+                self.format_shading(&style.rules.shading_technique)
+            ),
+            format!("light source: {:?}", style.rules.light_direction),
+            format!("at most {} colors", style.palette.max_colors),
+            "crisp pixel edges without anti-aliasing".to_string(),
+        ];
+        rules.extend(style.rules.constraints.iter().cloned());
+        let subject = match criteria {
+            ValidationCriteria::StyleGuide => "a style guide sheet".to_string(),
+            ValidationCriteria::Sprite(sprite_type) => format!("a {sprite_type} sprite"),
+            ValidationCriteria::Tileset(tile_type) => format!("a {tile_type} tile"),
+            ValidationCriteria::UIElement(element) => format!("a {element} UI element"),
+            ValidationCriteria::Background => "a background".to_string(),
+        };
+
+        let mut params = HashMap::new();
+        params.insert("model".to_string(), VISION_MODEL.to_string());
+        params.insert("subject".to_string(), subject.clone());
+        params.insert("rules".to_string(), rules.join("; "));
+        let image_hash = format!("{:x}", Sha256::digest(data));
+
+        let cache_key = self
+            .cache
+            .lock()
+            .await
+            .key_for("image_critique", &image_hash, &params)
+            .await;
+        if let Some(cached) = self.cache.lock().await.get(&cache_key).await
+            && let CachedData::Text(text) = &cached.data
+            && let Ok(critique) = serde_json::from_str::<ImageCritique>(text)
+        {
+            return Ok(critique);
+        }
+
+        let instruction = format!(
+            "You review {subject} for a 16-bit game. The art must follow these rules:\n- {}\n\n\
+             List every way the image breaks a rule or is wrong for its purpose, such as a \
+             wrong perspective, a cropped subject, several subjects, text, or scenery behind a \
+             sprite. Reply with JSON of the form {{\"issues\": [{{\"rule\": \"perspective\", \
+             \"severity\": \"minor|major\", \"description\": \"what is wrong\", \"fix\": \
+             \"one imperative sentence for the artist\"}}]}} and an empty list if the image is \
+             correct.",
+            rules.join("\n- ")
+        );
+        let response = self
+            .ask_vision(&instruction, data, 600, Some(ResponseFormat::JsonObject))
+            .await
+            .context("Failed to critique image")?;
+        let critique: ImageCritique =
+            serde_json::from_str(&response).context("Failed to parse image critique")?;
+
+        let cache_params: HashMap<String, serde_json::Value> = params
+            .into_iter()
+            .map(|(k, v)| (k, serde_json::Value::String(v)))
+            .collect();
+        self.cache
+            .lock()
+            .await
+            .put(cache_key, CachedData::Text(response), cache_params)
+            .await?;
+
+        Ok(critique)
+    }
+
+    /// Read the palette, perspective and interface layout off a screenshot
+    /// of an existing game, so its look can be compared and imitated
+    pub async fn analyze_screenshot(&self, screenshot: &[u8]) -> Result<ScreenshotAnalysis> {
+        let mut params = HashMap::new();
+        params.insert("model".to_string(), VISION_MODEL.to_string());
+        let screenshot_hash = format!("{:x}", Sha256::digest(screenshot));
+
+        let cache_key = self
+            .cache
+            .lock()
+            .await
+            .key_for("screenshot_analysis", &screenshot_hash, &params)
+            .await;
+        if let Some(cached) = self.cache.lock().await.get(&cache_key).await
+            && let CachedData::Text(text) = &cached.data
+            && let Ok(analysis) = serde_json::from_str::<ScreenshotAnalysis>(text)
+        {
+            return Ok(analysis);
+        }
+
+        let response = self
+            .ask_vision(
+                "This is a screenshot of a video game. Describe how it looks. Reply with JSON \
+                 of the form {\"palette\": [\"#rrggbb\"], \"perspective\": \"side view\", \
+                 \"ui_layout\": [\"score and lives along the top edge\"]}, where palette lists \
+                 up to 8 dominant colors, most used first, perspective is one of side view, \
+                 top-down, isometric, three-quarter, first person or pseudo-3D, and ui_layout \
+                 has one short entry per interface element with where it sits on screen, or \
+                 is empty if the screen has no interface.",
+                screenshot,
+                300,
+                Some(ResponseFormat::JsonObject),
+            )
+            .await
+            .context("Failed to analyze screenshot")?;
+        let mut analysis: ScreenshotAnalysis =
+            serde_json::from_str(&response).context("Failed to parse screenshot analysis")?;
+        analysis.palette = analysis
+            .palette
+            .iter()
+            .filter_map(|hex| Color::from_hex(hex))
+            .map(|color| format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b))
+            .collect();
+
+        let cache_params: HashMap<String, serde_json::Value> = params
+            .into_iter()
+            .map(|(k, v)| (k, serde_json::Value::String(v)))
+            .collect();
+        self.cache
+            .lock()
+            .await
+            .put(
+                cache_key,
+                CachedData::Text(serde_json::to_string(&analysis)?),
+                cache_params,
+            )
+            .await?;
+
+        Ok(analysis)
+    }
+
+    /// Ask the vision model about an image and record the token usage
+    async fn ask_vision(
+        &self,
+        instruction: &str,
+        image: &[u8],
+        max_tokens: u32,
+        response_format: Option<ResponseFormat>,
+    ) -> Result<String> {
+        let timer = RequestTimer::start("image");
+        let data_url = format!(
+            "data:image/png;base64,{}",
+            base64::engine::general_purpose::STANDARD.encode(to_png(image)?)
+        );
+        let content = ChatCompletionRequestUserMessageContent::Array(vec![
+            ChatCompletionRequestUserMessageContentPart::Text(
+                ChatCompletionRequestMessageContentPartText {
+                    text: instruction.to_string(),
+                },
+            ),
+            ChatCompletionRequestUserMessageContentPart::ImageUrl(
+                ChatCompletionRequestMessageContentPartImage {
+                    image_url: ImageUrl {
+                        url: data_url,
+                        detail: None,
+                    },
+                },
+            ),
+        ]);
+        let mut request = CreateChatCompletionRequestArgs::default();
+        request
+            .model(VISION_MODEL)
+            .messages(vec![
+                ChatCompletionRequestUserMessageArgs::default()
+                    .content(content)
+                    .build()?
+                    .into(),
+            ])
+            .max_tokens(max_tokens);
+        if let Some(format) = response_format {
+            request.response_format(format);
+        }
+
+        let request = request.build()?;
+        let response: CreateChatCompletionResponse = retry_keys(&self.client, || {
+            quota::send(&self.client, "/chat/completions", &request)
+        })
+        .await?;
+        let (mut tokens, mut cost) = (0, 0.0);
+        if let Some(usage) = &response.usage {
+            tokens = usage.total_tokens as usize;
+            cost = self
+                .token_counter
+                .lock()
+                .await
+                .record_usage(
+                    VISION_MODEL,
+                    usage.prompt_tokens as usize,
+                    usage.completion_tokens as usize,
+                )
+                .await?;
+        }
+        timer.finish(self.client.config().provider(), tokens, cost, false);
+
+        response
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.message.content)
+            .ok_or_else(|| anyhow::anyhow!("No response from the vision model"))
+    }
+
+    /// Repaint a rasterized map in the game's art style, keeping its layout
+    pub async fn stylize_map(&self, map: &[u8], description: &str) -> Result<Vec<u8>> {
+        let prompt = format!(
+            "Repaint this map as a 16-bit RPG map screen with hand-placed pixel art terrain. \
+             Keep every landmass, border, route and marker exactly where it is and keep the \
+             colors of each area. {description}"
+        );
+        let map = self.edit_reference(&prompt, map).await?;
+        self.enforce_palette_consistency(&map).await
+    }
+
+    /// Redraw a reference image through the image edit endpoint
+    async fn edit_reference(&self, prompt: &str, reference: &[u8]) -> Result<Vec<u8>> {
+        let timer = RequestTimer::start("image");
+        let config = ImageConfig::for_reference_edits();
+        let mut params = HashMap::new();
+        params.insert("model".to_string(), config.model_name().to_string());
+        params.insert("size".to_string(), format!("{:?}", config.size));
+        params.insert("quality".to_string(), format!("{:?}", config.quality));
+        params.insert(
+            "reference".to_string(),
+            format!("{:x}", Sha256::digest(reference)),
+        );
+
+        let cache_key = self
+            .cache
+            .lock()
+            .await
+            .key_for("image_edit", prompt, &params)
+            .await;
+        if let Some(cached_data) = self
+            .image_cache
+            .get_image(&cache_key, super::cache::ImageFormat::Png)
+            .await
+        {
+            timer.cache_hit(self.client.config().provider());
+            return Ok(cached_data);
+        }
+
+        let request = CreateImageEditRequestArgs::default()
+            .image(ImageInput::from_vec_u8(
+                "reference.png".to_string(),
+                to_png(reference)?,
+            ))
+            .prompt(prompt)
+            .model(config.model.clone())
+            .n(config.n)
+            .quality(config.quality.clone())
+            .size(config.size)
+            .build()?;
+        let model = config.model.to_string();
+        let client = Client::with_config(self.client.config().for_model(&model));
+        QuotaTracker::shared().pace(&model, 0).await;
+        let response = retry_keys(&client, || async {
+            client.images().edit(request.clone()).await
+        })
+        .await
+        .context("Failed to edit reference sprite")?;
+        let image_bytes = decode_image(
+            response
+                .data
+                .first()
+                .ok_or_else(|| anyhow::anyhow!("No image data in response"))?,
+        )?;
+
+        let (width, height) = ImageConfig::get_dimensions(&config.size);
+        let cost = self
+            .token_counter
+            .lock()
+            .await
+            .record_image_generation(&config.pricing_key(), width, height, 1)
+            .await?;
+        timer.finish(self.client.config().provider(), 0, cost, false);
+
+        let cache_params: HashMap<String, serde_json::Value> = params
+            .into_iter()
+            .map(|(k, v)| (k, serde_json::Value::String(v)))
+            .collect();
+        self.image_cache
+            .put_image(cache_key, image_bytes.clone(), cache_params)
+            .await?;
+
+        Ok(image_bytes)
+    }
+
+    /// Render the sprite template for a description in the active style
+    pub async fn sprite_prompt(&self, sprite_type: &str, description: &str) -> Result<String> {
         let style_config = self.style_manager.lock().await.get_style().await;
 
         // Get style-consistent description
@@ -301,24 +912,9 @@ impl ImageGenerator {
         let template = env
             .get_template("sprite")
             .context("Failed to get sprite template")?;
-        let prompt = template
+        template
             .render(&context)
-            .context("Failed to render sprite template")?;
-
-        // Generate with validation
-        let sprite = self
-            .generate_with_validation(
-                &prompt,
-                ImageConfig::for_sprites(),
-                ValidationCriteria::Sprite(sprite_type.to_string()),
-                3,
-            )
-            .await?;
-
-        // Post-process for consistency
-        let processed = self.enforce_palette_consistency(&sprite).await?;
-
-        Ok(processed)
+            .context("Failed to render sprite template")
     }
 
     /// Generate multiple sprites as a batch
@@ -370,11 +966,19 @@ impl ImageGenerator {
     ) -> Result<Vec<u8>> {
         let mut best_result = None;
         let mut best_score = 0.0;
+        let mut attempt_prompt = prompt.to_string();
 
         for attempt in 0..max_attempts {
-            match self.generate_single(prompt, config.clone()).await {
+            match self.generate_single(&attempt_prompt, config.clone()).await {
                 Ok(data) => {
-                    let validation = self.validate_image(&data, &criteria).await?;
+                    let data = self.remove_solid_background(data, &criteria)?;
+                    let mut validation = self.validate_image(&data, &criteria).await?;
+                    if self.vision_critique {
+                        match self.critique_image(&data, &criteria).await {
+                            Ok(critique) => critique.apply_to(&mut validation),
+                            Err(e) => tracing::warn!("Vision critique failed: {e}"),
+                        }
+                    }
 
                     if validation.passed {
                         return Ok(data);
@@ -392,8 +996,16 @@ impl ImageGenerator {
                             attempt + 1,
                             validation.issues
                         );
+                        if !validation.prompt_feedback.is_empty() {
+                            attempt_prompt = format!(
+                                "{prompt}\n\nThe previous attempt was rejected. {}.",
+                                validation.prompt_feedback.join(". ")
+                            );
+                        }
                     }
                 }
+                // Another attempt would be refused the same way
+                Err(e) if e.is::<NeedsManualRewrite>() => return Err(e),
                 Err(e) => {
                     tracing::error!("Generation attempt {} failed: {}", attempt + 1, e);
                     if attempt == max_attempts - 1 {
@@ -414,8 +1026,29 @@ impl ImageGenerator {
         })
     }
 
-    /// Generate a single image
+    /// Tokens and cost of generating `count` images with `config`, without
+    /// making the request
+    pub async fn estimate(&self, config: &ImageConfig, count: usize) -> CostEstimate {
+        let (width, height) = ImageConfig::get_dimensions(&config.size);
+        self.token_counter
+            .lock()
+            .await
+            .estimate_images(&config.pricing_key(), width, height, count)
+    }
+
+    /// Generate a single image. A refused prompt is retried once
+    /// sanitized, see [`refusals::retry_sanitized`].
     pub async fn generate_single(&self, prompt: &str, config: ImageConfig) -> Result<Vec<u8>> {
+        refusals::retry_sanitized(prompt, |prompt| {
+            let config = config.clone();
+            async move { self.generate_once(&prompt, config).await }
+        })
+        .await
+    }
+
+    async fn generate_once(&self, prompt: &str, config: ImageConfig) -> Result<Vec<u8>> {
+        let timer = RequestTimer::start("image");
+
         // Check cache first
         let mut params = HashMap::new();
         params.insert("model".to_string(), format!("{:?}", config.model));
@@ -426,13 +1059,15 @@ impl ImageGenerator {
             .cache
             .lock()
             .await
-            .generate_key("image", prompt, &params);
+            .key_for("image", prompt, &params)
+            .await;
 
         if let Some(cached_data) = self
             .image_cache
             .get_image(&cache_key, super::cache::ImageFormat::Png)
             .await
         {
+            timer.cache_hit(self.client.config().provider());
             return Ok(cached_data);
         }
 
@@ -447,12 +1082,11 @@ impl ImageGenerator {
             .build()?;
 
         // Make API call
-        let response = self
-            .client
-            .images()
-            .generate(request)
-            .await
-            .context("Failed to generate image")?;
+        let response: ImagesResponse = retry_keys(&self.client, || {
+            quota::send(&self.client, "/images/generations", &request)
+        })
+        .await
+        .context("Failed to generate image")?;
 
         // Extract image data from the response
         let image_data = response
@@ -460,30 +1094,19 @@ impl ImageGenerator {
             .first()
             .ok_or_else(|| anyhow::anyhow!("No image data in response"))?;
 
-        // The async-openai Image type is an enum with Url and B64Json variants
-        let image_bytes = match image_data.as_ref() {
-            Image::B64Json { b64_json, .. } => base64::engine::general_purpose::STANDARD
-                .decode(b64_json.as_ref())
-                .context("Failed to decode base64 image data")?,
-            Image::Url { url, .. } => {
-                // If we got a URL instead, we need to fetch it
-                anyhow::bail!("Expected base64 data but got URL: {url}");
-            }
-        };
+        let image_bytes = decode_image(image_data)?;
 
         // Track usage
         let (width, height) = ImageConfig::get_dimensions(&config.size);
-        let quality_str = match config.quality {
-            ImageQuality::HD => "hd",
-            _ => "standard",
-        };
-        let model_name = format!("dall-e-3-{}x{}-{}", width, height, quality_str);
+        let model_name = config.pricing_key();
 
-        self.token_counter
+        let cost = self
+            .token_counter
             .lock()
             .await
             .record_image_generation(&model_name, width, height, 1)
             .await?;
+        timer.finish(self.client.config().provider(), 0, cost, false);
 
         // Cache result
         let cache_params: HashMap<String, serde_json::Value> = params
@@ -510,6 +1133,7 @@ impl ImageGenerator {
             score: 1.0,
             issues: Vec::new(),
             suggestions: Vec::new(),
+            prompt_feedback: Vec::new(),
         };
 
         // Check dimensions
@@ -528,6 +1152,22 @@ impl ImageGenerator {
             }
         }
 
+        // Tiles must wrap without visible seams
+        if let ValidationCriteria::Tileset(_) = criteria {
+            let seams = tiling::measure_seams(&img);
+            if !seams.is_seamless() {
+                result.issues.push(format!(
+                    "Tile edges don't wrap: left/right {:.2}, top/bottom {:.2}",
+                    seams.horizontal, seams.vertical
+                ));
+                result.score *= 0.6;
+                result
+                    .suggestions
+                    .push("Wrap-blend the tile edges".to_string());
+                result.prompt_feedback.extend(seams.feedback());
+            }
+        }
+
         // Check color count
         let color_count = self.count_unique_colors(&img);
         let style = self.style_manager.lock().await.get_style().await;
@@ -628,6 +1268,37 @@ impl ImageGenerator {
         false
     }
 
+    /// Key out the backdrop if removal is enabled for this kind of image
+    fn remove_solid_background(
+        &self,
+        image_data: Vec<u8>,
+        criteria: &ValidationCriteria,
+    ) -> Result<Vec<u8>> {
+        let Some(settings) = criteria.background_removal(&self.background_removal) else {
+            return Ok(image_data);
+        };
+
+        let img = image::load_from_memory(&image_data)?;
+        let result = remove_background(&img, settings);
+        if result.cleared_pixels == 0 {
+            return Ok(image_data);
+        }
+
+        tracing::debug!(
+            "Removed background {:?} ({} pixels)",
+            result.background,
+            result.cleared_pixels
+        );
+
+        let mut buffer = Vec::new();
+        DynamicImage::ImageRgba8(result.image).write_to(
+            &mut std::io::Cursor::new(&mut buffer),
+            image::ImageFormat::Png,
+        )?;
+
+        Ok(buffer)
+    }
+
     /// Enforce palette consistency
     async fn enforce_palette_consistency(&self, image_data: &[u8]) -> Result<Vec<u8>> {
         let img = image::load_from_memory(image_data)?;
@@ -648,16 +1319,73 @@ impl ImageGenerator {
         Ok(buffer)
     }
 
-    /// Extract style information from generated style guide
-    async fn extract_style_information(&self, _style_guide_data: &[u8]) -> Result<()> {
-        // This would analyze the style guide image and extract:
-        // - Color palette
-        // - Shading patterns
-        // - Outline styles
-        // - Common motifs
+    /// Turn a full-size generated image into a sprite at game resolution.
+    ///
+    /// Crops to the subject, reduces blocks to single pixels and quantizes to
+    /// the active palette, e.g. a 1024×1024 DALL-E image to a 16×24 sprite.
+    pub async fn downscale_to_sprite(
+        &self,
+        image_data: &[u8],
+        target_size: (u32, u32),
+        method: DownscaleMethod,
+    ) -> Result<Vec<u8>> {
+        let img = image::load_from_memory(image_data)?;
+        let sprite = self
+            .style_manager
+            .lock()
+            .await
+            .downscale_to_sprite(&img, target_size, method)
+            .await;
+
+        let mut buffer = Vec::new();
+        sprite.write_to(
+            &mut std::io::Cursor::new(&mut buffer),
+            image::ImageFormat::Png,
+        )?;
+
+        Ok(buffer)
+    }
 
-        // For now, we'll just note that we have a style guide
-        tracing::info!("Style guide generated and stored for consistency enforcement");
+    /// Turn a full-size generated image into a sprite at game resolution and
+    /// its integer-scaled variants, as `(scale, PNG data)` for every factor of
+    /// [`scaling::SCALES`]
+    pub async fn downscale_to_variants(
+        &self,
+        image_data: &[u8],
+        target_size: (u32, u32),
+        method: DownscaleMethod,
+    ) -> Result<Vec<(u32, Vec<u8>)>> {
+        let img = image::load_from_memory(image_data)?;
+        let sprite = self
+            .style_manager
+            .lock()
+            .await
+            .downscale_to_sprite(&img, target_size, method)
+            .await;
+
+        scaling::encode_variants(&sprite)
+    }
+
+    /// Extract style information from generated style guide
+    ///
+    /// Derives the palette by median-cut quantization and infers outline and
+    /// shading hints, then makes them the active style so later sprites
+    /// inherit the guide.
+    async fn extract_style_information(&self, style_guide_data: &[u8]) -> Result<()> {
+        let img = image::load_from_memory(style_guide_data)
+            .context("Failed to decode style guide image")?;
+
+        let style_manager = self.style_manager.lock().await;
+        let max_colors = style_manager.get_style().await.palette.max_colors as usize;
+        let extracted = ExtractedStyle::from_image(&img, max_colors);
+        style_manager.apply_extracted_style(&extracted).await;
+
+        tracing::info!(
+            "Extracted {} colors from style guide (outline: {:?}, shading: {:?})",
+            extracted.colors.len(),
+            extracted.outline_color,
+            extracted.shading
+        );
 
         Ok(())
     }
@@ -709,8 +1437,9 @@ impl AiGenerator for ImageGenerator {
     }
 
     async fn estimate_cost(&self, _request: &str) -> Result<f64> {
-        // DALL-E 3 standard quality 1024x1024
-        Ok(0.04)
+        // Priced per image, independent of prompt length
+        let counter = self.token_counter.lock().await;
+        Ok(counter.estimate_image_cost(&ImageConfig::default().pricing_key(), 1))
     }
 
     async fn is_cached(&self, key: &str) -> bool {
@@ -730,6 +1459,30 @@ pub struct SpriteRequest {
     pub description: String,
 }
 
+/// Bytes of a generated image
+fn decode_image(image: &Image) -> Result<Vec<u8>> {
+    // The async-openai Image type is an enum with Url and B64Json variants
+    match image {
+        Image::B64Json { b64_json, .. } => base64::engine::general_purpose::STANDARD
+            .decode(b64_json.as_ref())
+            .context("Failed to decode base64 image data"),
+        Image::Url { url, .. } => {
+            // If we got a URL instead, we need to fetch it
+            anyhow::bail!("Expected base64 data but got URL: {url}");
+        }
+    }
+}
+
+/// Re-encode an image as PNG, the format the vision and edit endpoints expect
+fn to_png(data: &[u8]) -> Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    image::load_from_memory(data)?.write_to(
+        &mut std::io::Cursor::new(&mut buffer),
+        image::ImageFormat::Png,
+    )?;
+    Ok(buffer)
+}
+
 /// Validation criteria for generated images
 #[derive(Debug, Clone)]
 pub enum ValidationCriteria {
@@ -740,6 +1493,22 @@ pub enum ValidationCriteria {
     Background,
 }
 
+impl ValidationCriteria {
+    /// Background removal settings for this kind of image, if enabled
+    pub fn background_removal<'a>(
+        &self,
+        config: &'a BackgroundRemovalConfig,
+    ) -> Option<&'a BackgroundRemoval> {
+        match self {
+            Self::StyleGuide => config.style_guide.as_ref(),
+            Self::Sprite(_) => config.sprite.as_ref(),
+            Self::Tileset(_) => config.tileset.as_ref(),
+            Self::UIElement(_) => config.ui_element.as_ref(),
+            Self::Background => config.background.as_ref(),
+        }
+    }
+}
+
 /// Validation result
 #[derive(Debug)]
 pub struct ValidationResult {
@@ -747,6 +1516,72 @@ pub struct ValidationResult {
     pub score: f32,
     pub issues: Vec<String>,
     pub suggestions: Vec<String>,
+    /// Instructions added to the prompt when regenerating
+    pub prompt_feedback: Vec<String>,
+}
+
+/// How much a critique issue matters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CritiqueSeverity {
+    Minor,
+    Major,
+}
+
+/// Problem a vision model found in a generated image
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CritiqueIssue {
+    /// Rule the image breaks, e.g. "perspective" or "palette"
+    pub rule: String,
+    pub severity: CritiqueSeverity,
+    pub description: String,
+    /// Instruction that fixes the issue on the next attempt
+    pub fix: String,
+}
+
+/// Structured review of a generated image
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImageCritique {
+    #[serde(default)]
+    pub issues: Vec<CritiqueIssue>,
+}
+
+impl ImageCritique {
+    /// Fold the issues into a validation result; a major issue fails it
+    pub fn apply_to(&self, result: &mut ValidationResult) {
+        for issue in &self.issues {
+            result
+                .issues
+                .push(format!("{}: {}", issue.rule, issue.description));
+            result.prompt_feedback.push(issue.fix.clone());
+            result.score *= match issue.severity {
+                CritiqueSeverity::Major => 0.5,
+                CritiqueSeverity::Minor => 0.9,
+            };
+        }
+        result.passed = result.score >= 0.7;
+    }
+}
+
+/// Visual descriptors a vision model read off a game screenshot
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScreenshotAnalysis {
+    /// Dominant colors as `#rrggbb`, most used first
+    #[serde(default)]
+    pub palette: Vec<String>,
+    /// Camera perspective, e.g. "side view" or "top-down"
+    #[serde(default)]
+    pub perspective: String,
+    /// Where each part of the interface sits, e.g. "score along the top edge"
+    #[serde(default)]
+    pub ui_layout: Vec<String>,
+}
+
+impl ScreenshotAnalysis {
+    /// Whether the model read nothing off the screenshot
+    pub fn is_empty(&self) -> bool {
+        self.palette.is_empty() && self.perspective.is_empty() && self.ui_layout.is_empty()
+    }
 }
 
 /// Game concept for style guide generation
@@ -757,11 +1592,16 @@ pub struct GameConcept {
     pub mood: String,
     pub visual_inspirations: Vec<String>,
     pub color_themes: Vec<String>,
+    /// How the inspirations look on screen, one line per game, e.g.
+    /// "Super Metroid: side view; palette #101820, #3050a0; HUD along the top"
+    #[serde(default)]
+    pub reference_screens: Vec<String>,
 }
 
 /// Sprite sheet generation utilities
 pub mod sprite_sheets {
     use super::*;
+    use crate::consistency::sprite_sheets::{PackedSheet, pack_named_sprites};
 
     /// Generate a complete sprite sheet for a character
     pub async fn generate_character_sheet(
@@ -769,25 +1609,81 @@ pub mod sprite_sheets {
         character_name: &str,
         character_class: &str,
         animations: Vec<String>,
-    ) -> Result<DynamicImage> {
+    ) -> Result<PackedSheet> {
+        let style = generator.style_manager.lock().await.get_style().await;
+        let tolerance = generator.frame_tolerance;
         let mut sprites = Vec::new();
 
-        // Generate each animation frame
+        // Generate the frames of each animation
         for animation in &animations {
-            let description = format!(
-                "{character_name} {character_class} character performing {animation} animation, 16-bit pixel art sprite"
-            );
+            let frame_count = style
+                .sprite_specs
+                .animation_frames
+                .get(animation)
+                .copied()
+                .unwrap_or(1)
+                .max(1);
+            let describe = |frame: u32, feedback: Option<&str>| {
+                let mut description = format!(
+                    "{character_name} {character_class} character performing {animation} animation, frame {} of {frame_count}, 16-bit pixel art sprite",
+                    frame + 1
+                );
+                if let Some(feedback) = feedback {
+                    description.push_str(&format!(", {feedback}"));
+                }
+                description
+            };
 
-            let sprite_data = generator
-                .generate_sprite(&format!("character_{animation}"), &description, None)
-                .await?;
+            let mut frames = Vec::new();
+            for frame in 0..frame_count {
+                let sprite_data = generator
+                    .generate_sprite(
+                        &format!("character_{animation}"),
+                        &describe(frame, None),
+                        None,
+                    )
+                    .await?;
+                frames.push(image::load_from_memory(&sprite_data)?);
+            }
+
+            // Re-request frames whose proportions or colors drift
+            for retry in 0..tolerance.max_retries {
+                let outliers = frame_consistency::find_outliers(&frames, &tolerance);
+                if outliers.is_empty() {
+                    break;
+                }
+                for outlier in outliers {
+                    tracing::info!(
+                        "Frame {} of {animation} is inconsistent (silhouette {:.2}, palette {:.2}), re-requesting ({}/{})",
+                        outlier.index + 1,
+                        outlier.silhouette_distance,
+                        outlier.palette_distance,
+                        retry + 1,
+                        tolerance.max_retries
+                    );
+                    let feedback =
+                        format!("{} (attempt {})", outlier.feedback(&tolerance), retry + 2);
+                    let sprite_data = generator
+                        .generate_sprite(
+                            &format!("character_{animation}"),
+                            &describe(outlier.index as u32, Some(&feedback)),
+                            None,
+                        )
+                        .await?;
+                    frames[outlier.index] = image::load_from_memory(&sprite_data)?;
+                }
+            }
 
-            let sprite = image::load_from_memory(&sprite_data)?;
-            sprites.push(sprite);
+            sprites.extend(
+                frames
+                    .into_iter()
+                    .enumerate()
+                    .map(|(frame, sprite)| (format!("{animation}_{frame}"), sprite)),
+            );
         }
 
-        // Pack into sprite sheet
-        pack_sprites(sprites, 2)
+        // Pack into sprite sheet, one frame per `{animation}_{index}`
+        pack_named_sprites(sprites, 2)
     }
 
     /// Generate tileset for environments
@@ -795,22 +1691,20 @@ pub mod sprite_sheets {
         generator: &ImageGenerator,
         theme: &str,
         tile_types: Vec<String>,
-    ) -> Result<DynamicImage> {
+    ) -> Result<PackedSheet> {
         let mut tiles = Vec::new();
 
         for tile_type in &tile_types {
             let description =
                 format!("{theme} environment tile: {tile_type}, 16-bit pixel art, seamless tiling");
 
-            let tile_data = generator
-                .generate_sprite(&format!("tile_{tile_type}"), &description, None)
-                .await?;
+            let tile_data = generator.generate_tile(tile_type, &description).await?;
 
             let tile = image::load_from_memory(&tile_data)?;
-            tiles.push(tile);
+            tiles.push((tile_type.clone(), tile));
         }
 
-        pack_sprites(tiles, 0)
+        pack_named_sprites(tiles, 0)
     }
 }
 
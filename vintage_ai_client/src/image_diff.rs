@@ -0,0 +1,147 @@
+//! Visual diff between two versions of a generated image
+//!
+//! Produces a heatmap where unchanged pixels are shown as a dimmed grayscale
+//! copy of the original and changed pixels are tinted from yellow (small
+//! change) to red (large change). In palette-bucket mode colors are first
+//! reduced to 16-bit (5-6-5) buckets, so only changes that survive palette
+//! enforcement show up.
+
+use anyhow::Result;
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage, imageops::FilterType};
+use serde::{Deserialize, Serialize};
+
+use super::consistency::Color;
+
+/// How pixel differences are measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DiffMode {
+    /// Largest per-channel difference of the raw RGBA values
+    #[default]
+    Pixel,
+    /// Difference after reducing both images to 16-bit color buckets
+    PaletteBucket,
+}
+
+/// Result of comparing two images
+#[derive(Debug, Clone)]
+pub struct ImageDiff {
+    /// Heatmap the size of the original image
+    pub heatmap: RgbaImage,
+    pub changed_pixels: usize,
+    pub total_pixels: usize,
+    /// Mean difference over all pixels (0.0 - 1.0)
+    pub mean_delta: f32,
+    /// Largest difference of any pixel (0.0 - 1.0)
+    pub max_delta: f32,
+    /// The second image had different dimensions and was rescaled to match
+    pub resized: bool,
+}
+
+impl ImageDiff {
+    /// Fraction of pixels that changed
+    pub fn changed_ratio(&self) -> f32 {
+        if self.total_pixels == 0 {
+            0.0
+        } else {
+            self.changed_pixels as f32 / self.total_pixels as f32
+        }
+    }
+}
+
+/// Compare two encoded images (PNG, JPEG, ...)
+pub fn diff_image_bytes(before: &[u8], after: &[u8], mode: DiffMode) -> Result<ImageDiff> {
+    let before = image::load_from_memory(before)?;
+    let after = image::load_from_memory(after)?;
+    Ok(diff_images(&before, &after, mode))
+}
+
+/// Compare two images, rescaling `after` to the size of `before` if needed
+pub fn diff_images(before: &DynamicImage, after: &DynamicImage, mode: DiffMode) -> ImageDiff {
+    let (width, height) = before.dimensions();
+    let resized = after.dimensions() != (width, height);
+    let after = if resized {
+        // Nearest keeps pixel art edges intact
+        after.resize_exact(width, height, FilterType::Nearest)
+    } else {
+        after.clone()
+    };
+
+    let before = before.to_rgba8();
+    let after = after.to_rgba8();
+
+    let mut heatmap = RgbaImage::new(width, height);
+    let mut changed_pixels = 0;
+    let mut total_delta = 0.0;
+    let mut max_delta: f32 = 0.0;
+
+    for (x, y, a) in before.enumerate_pixels() {
+        let b = after.get_pixel(x, y);
+        let delta = match mode {
+            DiffMode::Pixel => pixel_delta(a, b),
+            DiffMode::PaletteBucket => pixel_delta(&bucket(a), &bucket(b)),
+        };
+
+        if delta > 0.0 {
+            changed_pixels += 1;
+        }
+        total_delta += delta;
+        max_delta = max_delta.max(delta);
+        heatmap.put_pixel(x, y, heat_color(a, delta));
+    }
+
+    let total_pixels = (width * height) as usize;
+    ImageDiff {
+        heatmap,
+        changed_pixels,
+        total_pixels,
+        mean_delta: if total_pixels > 0 {
+            total_delta / total_pixels as f32
+        } else {
+            0.0
+        },
+        max_delta,
+        resized,
+    }
+}
+
+/// Largest channel difference, scaled to 0.0 - 1.0
+fn pixel_delta(a: &Rgba<u8>, b: &Rgba<u8>) -> f32 {
+    a.0.iter()
+        .zip(b.0.iter())
+        .map(|(x, y)| x.abs_diff(*y))
+        .max()
+        .unwrap_or(0) as f32
+        / 255.0
+}
+
+/// Reduce a pixel to its 16-bit color bucket; fully transparent pixels share one bucket
+fn bucket(pixel: &Rgba<u8>) -> Rgba<u8> {
+    let [r, g, b, a] = pixel.0;
+    if a == 0 {
+        return Rgba([0, 0, 0, 0]);
+    }
+    let color = Color::from_16bit(Color::new(r, g, b).to_16bit());
+    Rgba([color.r, color.g, color.b, 255])
+}
+
+/// Dimmed grayscale for unchanged pixels, yellow to red for changed ones
+fn heat_color(original: &Rgba<u8>, delta: f32) -> Rgba<u8> {
+    let [r, g, b, a] = original.0;
+    let luma = (0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32) * (a as f32 / 255.0);
+    let gray = (luma * 0.3) as u8;
+
+    if delta <= 0.0 {
+        return Rgba([gray, gray, gray, 255]);
+    }
+
+    // Keep small changes visible against the dimmed background
+    let t = 0.35 + 0.65 * delta.clamp(0.0, 1.0);
+    let heat = [255.0, 255.0 * (1.0 - delta.clamp(0.0, 1.0)), 0.0];
+    let mix = |from: u8, to: f32| (from as f32 * (1.0 - t) + to * t) as u8;
+    Rgba([
+        mix(gray, heat[0]),
+        mix(gray, heat[1]),
+        mix(gray, heat[2]),
+        255,
+    ])
+}
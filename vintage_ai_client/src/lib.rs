@@ -8,19 +8,49 @@
 //! - Token counting and cost optimization
 //! - Intelligent caching to reduce API calls
 
+pub mod achievements;
+pub mod artifacts;
 pub mod audio;
+pub mod azure;
+pub mod background;
+pub mod bosses;
 pub mod cache;
 pub mod client;
 pub mod consistency;
 pub mod conversation;
+pub mod credentials;
+pub mod economy;
 pub mod embeddings;
+pub mod encounters;
+pub mod export;
+pub mod game_assets;
 pub mod game_types;
+pub mod gemini;
+pub mod gradients;
+pub mod history;
 pub mod image;
+pub mod image_diff;
+pub mod maps;
+pub mod mock;
+pub mod postgame;
+pub mod prompts;
+pub mod quests;
+pub mod quota;
+pub mod randomizer;
+pub mod refusals;
+pub mod routing;
+pub mod slugs;
+pub mod telemetry;
 pub mod text;
+pub mod thumbnails;
 pub mod tokens;
+pub mod warmup;
+pub mod world;
 
 use anyhow::Result;
 use async_openai::{Client, config::OpenAIConfig};
+use credentials::{Credentials, KeyPool, KeyRotation, KeyedConfig};
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
@@ -28,32 +58,198 @@ use tokio::sync::Mutex;
 #[derive(Clone)]
 pub struct AiService {
     /// OpenAI client for API calls
-    pub client: Arc<Client<OpenAIConfig>>,
+    pub client: Arc<Client<KeyedConfig>>,
     /// Cache manager for all AI operations
     pub cache: Arc<Mutex<cache::AiCache>>,
     /// Token counter for cost tracking
     pub token_counter: Arc<Mutex<tokens::TokenCounter>>,
     /// Style consistency manager for visual coherence
     pub style_manager: Arc<Mutex<consistency::StyleManager>>,
+    /// Shared conversation manager so conversations persist across calls
+    conversations: conversation::ConversationManager,
+    /// Per-phase sampling parameters
+    profiles: ParameterProfiles,
+    /// Prompt templates, with the project's overrides
+    prompts: prompts::PromptLibrary,
+    /// Whether image attempts are critiqued by a vision model
+    vision_critique: bool,
+    /// Offline provider answering the requests, if any
+    mock: Option<mock::MockProvider>,
 }
 
 impl AiService {
     /// Create a new AI service instance
     pub fn new() -> Result<Self> {
-        let config = OpenAIConfig::new();
+        Self::with_credentials("openai", &Credentials::default())
+    }
+
+    /// Create a service that authenticates with the keys of `provider`,
+    /// rotating through them as `credentials` configures
+    pub fn with_credentials(provider: &str, credentials: &Credentials) -> Result<Self> {
+        let pricing = tokens::ModelPricing::from_env()?;
+        Self::with_client(
+            KeyedConfig::new(OpenAIConfig::new(), Self::key_pool(provider, credentials)?)
+                .with_provider(provider),
+            pricing,
+            cache::AiCache::new()?,
+            None,
+        )
+    }
+
+    /// Create a service whose requests go to the deployments of an Azure
+    /// OpenAI resource
+    pub fn azure(azure: azure::AzureConfig, credentials: &Credentials) -> Result<Self> {
+        let pool = Self::key_pool(azure::AZURE_PROVIDER, credentials)?;
+        let pricing = tokens::ModelPricing::from_env()?;
+        Self::with_client(
+            KeyedConfig::new(OpenAIConfig::new(), pool).with_azure(azure),
+            pricing,
+            cache::AiCache::new()?,
+            None,
+        )
+    }
+
+    /// Create a service whose requests are answered by Gemini models
+    pub fn gemini(gemini: gemini::GeminiConfig, credentials: &Credentials) -> Result<Self> {
+        let pool = Self::key_pool(gemini::GEMINI_PROVIDER, credentials)?;
+        let pricing = tokens::ModelPricing::from_env()?;
+        Self::with_client(
+            KeyedConfig::new(OpenAIConfig::new(), pool).with_gemini(gemini),
+            pricing,
+            cache::AiCache::new()?,
+            None,
+        )
+    }
+
+    fn key_pool(provider: &str, credentials: &Credentials) -> Result<KeyPool> {
+        let pool = credentials.pool(provider)?;
+        if pool.is_empty() {
+            tracing::warn!("No API key configured for {provider}");
+        }
+        Ok(pool)
+    }
+
+    /// Create a service answered by the mock provider, without an API key
+    /// or a network. Nothing it requests is priced, and it caches apart
+    /// from the other services, so its placeholders are never served to
+    /// them.
+    pub fn offline() -> Result<Self> {
+        let mock = mock::MockProvider::shared()?;
+        let cache = cache::AiCache::with_config(cache::CacheConfig {
+            cache_dir: cache::CacheConfig::default()
+                .cache_dir
+                .join(mock::MOCK_PROVIDER),
+            ..Default::default()
+        })?;
+        let service = Self::with_client(
+            KeyedConfig::new(
+                mock.config(),
+                KeyPool::new(Vec::new(), KeyRotation::default()),
+            )
+            .with_provider(mock::MOCK_PROVIDER),
+            tokens::ModelPricing::free(),
+            cache,
+            Some(mock.clone()),
+        )?;
+        mock.set_style_manager(service.style_manager.clone());
+        Ok(service)
+    }
+
+    fn with_client(
+        config: KeyedConfig,
+        pricing: tokens::ModelPricing,
+        cache: cache::AiCache,
+        mock: Option<mock::MockProvider>,
+    ) -> Result<Self> {
+        let mut token_counter = tokens::TokenCounter::with_pricing(pricing);
+        if let Some(gemini) = config.gemini() {
+            token_counter = token_counter.with_aliases(gemini.models());
+        }
+        let token_counter = Arc::new(Mutex::new(token_counter));
         let client = Arc::new(Client::with_config(config));
 
         Ok(Self {
             client: client.clone(),
-            cache: Arc::new(Mutex::new(cache::AiCache::new()?)),
-            token_counter: Arc::new(Mutex::new(tokens::TokenCounter::new())),
+            cache: Arc::new(Mutex::new(cache)),
+            token_counter: token_counter.clone(),
             style_manager: Arc::new(Mutex::new(consistency::StyleManager::new())),
+            conversations: conversation::ConversationManager::new(client, token_counter),
+            profiles: ParameterProfiles::default(),
+            prompts: prompts::PromptLibrary::new(),
+            vision_critique: false,
+            mock,
         })
     }
 
+    /// Create a service that applies the per-phase profiles, the keys and
+    /// the draft route, which summarizes long conversations, from `config`,
+    /// offline when its provider is [`mock::MOCK_PROVIDER`],
+    /// on Azure when it is [`azure::AZURE_PROVIDER`] and on Gemini when it
+    /// is [`gemini::GEMINI_PROVIDER`]
+    pub fn with_config(config: &AiConfig) -> Result<Self> {
+        let mut service = if config.ai_provider == mock::MOCK_PROVIDER {
+            Self::offline()?
+        } else if config.ai_provider == azure::AZURE_PROVIDER {
+            let azure = match &config.azure {
+                Some(azure) => azure.clone(),
+                None => azure::AzureConfig::from_env()?,
+            };
+            Self::azure(azure, &config.credentials)?
+        } else if config.ai_provider == gemini::GEMINI_PROVIDER {
+            Self::gemini(
+                config.gemini.clone().unwrap_or_default(),
+                &config.credentials,
+            )?
+        } else {
+            Self::with_credentials(&config.ai_provider, &config.credentials)?
+        };
+        service.profiles = config.profiles.clone();
+        service.conversations = service
+            .conversations
+            .with_profiles(config.profiles.clone())
+            .with_summarization(conversation::SummarizationConfig {
+                model: config.routing.route(routing::TaskCategory::Draft).model,
+                ..Default::default()
+            });
+        service.vision_critique = config.vision_critique;
+        Ok(service)
+    }
+
+    /// Per-phase sampling parameters used by this service
+    pub fn profiles(&self) -> &ParameterProfiles {
+        &self.profiles
+    }
+
+    /// Search the `prompts/` directory of `project_dir` for overrides of
+    /// the embedded prompt templates. The style manager starts over with
+    /// the default style. Offline, the project's fixtures are loaded too.
+    pub fn with_prompt_overrides(mut self, project_dir: &Path) -> Self {
+        self.prompts = prompts::PromptLibrary::new().with_project_dir(project_dir);
+        self.style_manager = Arc::new(Mutex::new(consistency::StyleManager::with_prompts(
+            &self.prompts,
+        )));
+        if let Some(mock) = &self.mock {
+            mock.set_style_manager(self.style_manager.clone());
+            if let Err(e) = mock.load_fixtures(&project_dir.join(mock::FIXTURES_DIR)) {
+                tracing::warn!("Failed to load fixtures: {e:#}");
+            }
+        }
+        self
+    }
+
+    /// Whether the mock provider answers this service's requests
+    pub fn is_offline(&self) -> bool {
+        self.mock.is_some()
+    }
+
+    /// Prompt templates used by the generators of this service
+    pub fn prompts(&self) -> &prompts::PromptLibrary {
+        &self.prompts
+    }
+
     /// Initialize from environment variables
     pub fn from_env() -> Result<Self> {
-        // This will use OPENAI_API_KEY from environment
+        // This will use OPENAI_API_KEYS or OPENAI_API_KEY from environment
         Self::new()
     }
 
@@ -66,6 +262,16 @@ impl AiService {
         )
     }
 
+    /// Get a quest generator backed by the text generation service
+    pub fn quests(&self) -> quests::QuestGenerator {
+        quests::QuestGenerator::new(self.text())
+    }
+
+    /// Get an overworld generator backed by the text generation service
+    pub fn world(&self) -> world::WorldGenerator {
+        world::WorldGenerator::new(self.text())
+    }
+
     /// Get a reference to the image generation service
     pub fn image(&self) -> image::ImageGenerator {
         image::ImageGenerator::new(
@@ -73,7 +279,9 @@ impl AiService {
             self.cache.clone(),
             self.token_counter.clone(),
             self.style_manager.clone(),
+            &self.prompts,
         )
+        .with_vision_critique(self.vision_critique)
     }
 
     /// Get a reference to the audio generation service
@@ -82,12 +290,21 @@ impl AiService {
             self.client.clone(),
             self.cache.clone(),
             self.token_counter.clone(),
+            &self.prompts,
         )
     }
 
-    /// Get a reference to the conversation service
+    /// Get a reference to the conversation service, generating game assets
+    /// with this service's generators
     pub fn conversation(&self) -> conversation::ConversationManager {
-        conversation::ConversationManager::new(self.client.clone(), self.token_counter.clone())
+        self.conversations
+            .clone()
+            .with_generators(conversation::AssetGenerators {
+                text: self.text(),
+                image: self.image(),
+                audio: self.audio(),
+                quests: self.quests(),
+            })
     }
 
     /// Get a reference to the embeddings service
@@ -129,6 +346,9 @@ pub struct AiConfig {
     pub audio_model: String,
     /// Embedding model (e.g., text-embedding-3-small, text-embedding-3-large)
     pub embedding_model: String,
+    /// Models of each category of task, overriding the default routes
+    #[serde(default)]
+    pub routing: routing::RoutingPolicy,
 
     // Generation Parameters
     /// Temperature for generation (0.0 - 2.0)
@@ -141,16 +361,34 @@ pub struct AiConfig {
     pub frequency_penalty: f32,
     /// Presence penalty for text generation (-2.0 to 2.0)
     pub presence_penalty: f32,
+    /// Per-phase parameter profiles, used instead of the global values above
+    #[serde(default)]
+    pub profiles: ParameterProfiles,
 
     // Image Parameters
     /// Image quality (standard or hd for DALL-E 3)
     pub image_quality: String,
     /// Image size (1024x1024, 1792x1024, 1024x1792 for DALL-E 3)
     pub image_size: String,
+    /// Have a vision model critique every image attempt in addition to the
+    /// pixel checks, at the cost of a chat request per attempt
+    #[serde(default)]
+    pub vision_critique: bool,
 
     // Provider Settings
-    /// AI provider (openai, anthropic)
+    /// AI provider (openai, azure, gemini, anthropic)
     pub ai_provider: String,
+    /// Azure OpenAI resource, used when the provider is azure. Without it
+    /// the endpoint is read from `AZURE_OPENAI_ENDPOINT`.
+    #[serde(default)]
+    pub azure: Option<azure::AzureConfig>,
+    /// Gemini models answering the requests, used when the provider is
+    /// gemini. Without it the defaults are used.
+    #[serde(default)]
+    pub gemini: Option<gemini::GeminiConfig>,
+    /// Where the API keys of each provider are read from
+    #[serde(default)]
+    pub credentials: Credentials,
 
     // Cache and Performance
     /// Enable AI response caching
@@ -173,6 +411,7 @@ impl Default for AiConfig {
             image_model: "dall-e-3".to_string(),
             audio_model: "tts-1".to_string(),
             embedding_model: "text-embedding-3-small".to_string(),
+            routing: routing::RoutingPolicy::default(),
 
             // Generation defaults
             temperature: 0.8,
@@ -180,13 +419,18 @@ impl Default for AiConfig {
             max_tokens: 2000,
             frequency_penalty: 0.0,
             presence_penalty: 0.0,
+            profiles: ParameterProfiles::default(),
 
             // Image defaults
             image_quality: "standard".to_string(),
             image_size: "1024x1024".to_string(),
+            vision_critique: false,
 
             // Provider defaults
             ai_provider: "openai".to_string(),
+            azure: None,
+            gemini: None,
+            credentials: Credentials::default(),
 
             // Cache and performance defaults
             cache_enabled: true,
@@ -220,6 +464,16 @@ impl AiConfig {
         self
     }
 
+    /// Serve a category of tasks with another route
+    pub fn with_route(
+        mut self,
+        category: routing::TaskCategory,
+        route: routing::ModelRoute,
+    ) -> Self {
+        self.routing = self.routing.with_route(category, route);
+        self
+    }
+
     /// Builder pattern for generation parameters
     pub fn with_temperature(mut self, temp: f32) -> Self {
         self.temperature = temp.clamp(0.0, 2.0);
@@ -231,6 +485,17 @@ impl AiConfig {
         self
     }
 
+    /// Builder pattern for per-phase parameters
+    pub fn with_profile(mut self, kind: ProfileKind, profile: ParameterProfile) -> Self {
+        *self.profiles.get_mut(kind) = profile.validate();
+        self
+    }
+
+    /// Parameters to use for a kind of generation
+    pub fn profile(&self, kind: ProfileKind) -> &ParameterProfile {
+        self.profiles.get(kind)
+    }
+
     /// Validate and clamp configuration values
     pub fn validate(mut self) -> Self {
         self.temperature = self.temperature.clamp(0.0, 2.0);
@@ -238,6 +503,108 @@ impl AiConfig {
         self.frequency_penalty = self.frequency_penalty.clamp(-2.0, 2.0);
         self.presence_penalty = self.presence_penalty.clamp(-2.0, 2.0);
         self.max_tokens = self.max_tokens.min(128000);
+        for kind in ProfileKind::ALL {
+            let profile = self.profiles.get_mut(kind);
+            *profile = profile.clone().validate();
+        }
+        self
+    }
+}
+
+/// Kind of output a generation phase produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileKind {
+    /// Prose: descriptions, lore, dialogue, style guides
+    Narrative,
+    /// Source code
+    Code,
+    /// Structured data that is parsed as JSON
+    Json,
+}
+
+impl ProfileKind {
+    pub const ALL: [ProfileKind; 3] =
+        [ProfileKind::Narrative, ProfileKind::Code, ProfileKind::Json];
+}
+
+/// Sampling parameters for one kind of generation
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ParameterProfile {
+    /// Temperature (0.0 - 2.0)
+    pub temperature: f32,
+    /// Top-p nucleus sampling (0.0-1.0), unset keeps the request's own value
+    pub top_p: Option<f32>,
+    /// Frequency penalty (-2.0 to 2.0), unset keeps the request's own value
+    pub frequency_penalty: Option<f32>,
+    /// Presence penalty (-2.0 to 2.0), unset keeps the request's own value
+    pub presence_penalty: Option<f32>,
+}
+
+impl Default for ParameterProfile {
+    fn default() -> Self {
+        Self {
+            temperature: 0.7,
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+        }
+    }
+}
+
+impl ParameterProfile {
+    /// Profile with the given temperature and default sampling otherwise
+    pub fn with_temperature(temperature: f32) -> Self {
+        Self {
+            temperature,
+            ..Default::default()
+        }
+    }
+
+    /// Clamp values to the ranges accepted by the API
+    pub fn validate(mut self) -> Self {
+        self.temperature = self.temperature.clamp(0.0, 2.0);
+        self.top_p = self.top_p.map(|v| v.clamp(0.0, 1.0));
+        self.frequency_penalty = self.frequency_penalty.map(|v| v.clamp(-2.0, 2.0));
+        self.presence_penalty = self.presence_penalty.map(|v| v.clamp(-2.0, 2.0));
         self
     }
 }
+
+/// Parameter profiles per kind of generation
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ParameterProfiles {
+    pub narrative: ParameterProfile,
+    pub code: ParameterProfile,
+    pub json: ParameterProfile,
+}
+
+impl Default for ParameterProfiles {
+    fn default() -> Self {
+        Self {
+            narrative: ParameterProfile::with_temperature(0.9),
+            code: ParameterProfile::with_temperature(0.2),
+            json: ParameterProfile::with_temperature(0.0),
+        }
+    }
+}
+
+impl ParameterProfiles {
+    pub fn get(&self, kind: ProfileKind) -> &ParameterProfile {
+        match kind {
+            ProfileKind::Narrative => &self.narrative,
+            ProfileKind::Code => &self.code,
+            ProfileKind::Json => &self.json,
+        }
+    }
+
+    pub fn get_mut(&mut self, kind: ProfileKind) -> &mut ParameterProfile {
+        match kind {
+            ProfileKind::Narrative => &mut self.narrative,
+            ProfileKind::Code => &mut self.code,
+            ProfileKind::Json => &mut self.json,
+        }
+    }
+}
@@ -0,0 +1,593 @@
+//! World map and dungeon mini-maps
+//!
+//! The world map is laid out from the region graph: regions are placed by a
+//! force-directed layout over their connections, towns and dungeons are
+//! pinned to the region that lists them as a key location, and routes follow
+//! the connections. Dungeon mini-maps are grown room by room from a seed
+//! derived from the dungeon's name and the project's generation seed, so
+//! regenerating the same world gives the same maps. Both are rasterized at
+//! console resolution in the project palette;
+//! [`ImageGenerator::stylize_map`](crate::image::ImageGenerator::stylize_map)
+//! can repaint the result while keeping its layout. The layout itself is
+//! exported as data for the in-game map screen.
+
+use image::{Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use crate::consistency::Color;
+use crate::game_types::{FloorData, GameConfig, WorldData};
+
+/// World map resolution, the SNES screen size
+pub const WORLD_MAP_SIZE: (u32, u32) = (256, 224);
+
+/// Pixel size of a room on a mini-map, including the gap to its neighbours
+pub const MINIMAP_CELL: u32 = 8;
+
+const LAYOUT_ITERATIONS: usize = 300;
+
+/// Distance kept between region centers and the map edge
+const LAYOUT_MARGIN: f32 = 0.22;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarkerKind {
+    Town,
+    Dungeon,
+}
+
+/// Region on the world map; coordinates are fractions of the map size
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapRegion {
+    pub name: String,
+    pub biome: String,
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapRoute {
+    pub from: String,
+    pub to: String,
+    pub connection_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapMarker {
+    pub name: String,
+    pub kind: MarkerKind,
+    pub region: String,
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoomKind {
+    Entrance,
+    Room,
+    Treasure,
+    /// Stairs down to the next floor
+    Stairs,
+    Boss,
+}
+
+/// Room on a mini-map grid
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MiniMapRoom {
+    pub x: i32,
+    pub y: i32,
+    pub kind: RoomKind,
+    /// Room this one was entered from
+    pub from: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MiniMapFloor {
+    pub rooms: Vec<MiniMapRoom>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DungeonMiniMap {
+    pub dungeon: String,
+    pub floors: Vec<MiniMapFloor>,
+}
+
+/// Layout of the world map and the dungeon mini-maps
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorldMap {
+    pub regions: Vec<MapRegion>,
+    pub routes: Vec<MapRoute>,
+    pub markers: Vec<MapMarker>,
+    pub dungeons: Vec<DungeonMiniMap>,
+}
+
+impl WorldMap {
+    /// Lay out the generated world
+    ///
+    /// Towns and dungeons that no region lists as a key location are spread
+    /// over the regions in turn, so every location appears on the map.
+    pub fn generate(config: &GameConfig, world: &WorldData) -> Self {
+        let positions = layout_regions(world);
+        let regions: Vec<MapRegion> = world
+            .regions
+            .iter()
+            .zip(positions)
+            .map(|(region, (x, y))| MapRegion {
+                name: region.name.clone(),
+                biome: config
+                    .world
+                    .regions
+                    .iter()
+                    .find(|r| r.name == region.name)
+                    .map(|r| r.biome.clone())
+                    .unwrap_or_default(),
+                x,
+                y,
+            })
+            .collect();
+
+        let routes = world
+            .connections
+            .iter()
+            .map(|connection| MapRoute {
+                from: connection.from.clone(),
+                to: connection.to.clone(),
+                connection_type: connection.connection_type.clone(),
+            })
+            .collect();
+
+        let locations = world
+            .towns
+            .iter()
+            .map(|town| (&town.name, MarkerKind::Town))
+            .chain(
+                world
+                    .dungeons
+                    .iter()
+                    .map(|dungeon| (&dungeon.name, MarkerKind::Dungeon)),
+            );
+        let mut markers: Vec<MapMarker> = Vec::new();
+        if !regions.is_empty() {
+            for (index, (name, kind)) in locations.enumerate() {
+                let home = config
+                    .world
+                    .regions
+                    .iter()
+                    .find(|r| {
+                        r.key_locations
+                            .iter()
+                            .any(|location| location.eq_ignore_ascii_case(name))
+                    })
+                    .and_then(|r| regions.iter().find(|region| region.name == r.name))
+                    .unwrap_or(&regions[index % regions.len()]);
+
+                // Circle the region's center, one slot per location
+                let slot = markers.iter().filter(|m| m.region == home.name).count();
+                let angle = slot as f32 * 2.4;
+                markers.push(MapMarker {
+                    name: name.clone(),
+                    kind,
+                    region: home.name.clone(),
+                    x: (home.x + angle.cos() * 0.05).clamp(0.02, 0.98),
+                    y: (home.y + angle.sin() * 0.05).clamp(0.02, 0.98),
+                });
+            }
+        }
+
+        let dungeons = world
+            .dungeons
+            .iter()
+            .map(|dungeon| {
+                DungeonMiniMap::generate(
+                    &dungeon.name,
+                    config.procedural_seed(&dungeon.name),
+                    &dungeon.floors,
+                )
+            })
+            .collect();
+
+        Self {
+            regions,
+            routes,
+            markers,
+            dungeons,
+        }
+    }
+
+    /// Rasterize the world map in the palette
+    pub fn render_world(&self, palette: &[Color]) -> RgbaImage {
+        let (width, height) = WORLD_MAP_SIZE;
+        let colors = MapColors::from_palette(palette);
+        let land_radius = (0.55 / (self.regions.len().max(1) as f32).sqrt()).clamp(0.12, 0.3);
+
+        let mut img = RgbaImage::from_pixel(width, height, colors.sea);
+        let mut owner = vec![None; (width * height) as usize];
+        for y in 0..height {
+            for x in 0..width {
+                let (fx, fy) = (x as f32 / width as f32, y as f32 / height as f32);
+                // Blocky noise roughens the coastline
+                let noise = (hash(&[(x / 4) as u64, (y / 4) as u64]) % 100) as f32 / 100.0 * 0.04;
+                let nearest = self
+                    .regions
+                    .iter()
+                    .enumerate()
+                    .map(|(i, r)| (i, ((fx - r.x).powi(2) + (fy - r.y).powi(2)).sqrt()))
+                    .min_by(|a, b| a.1.total_cmp(&b.1));
+                if let Some((index, distance)) = nearest
+                    && distance + noise < land_radius
+                {
+                    owner[(y * width + x) as usize] = Some(index);
+                    img.put_pixel(x, y, colors.biome(&self.regions[index].biome));
+                }
+            }
+        }
+
+        // Outline coasts and region borders
+        for y in 0..height {
+            for x in 0..width {
+                let here = owner[(y * width + x) as usize];
+                if here.is_none() {
+                    continue;
+                }
+                let neighbour = |dx: i32, dy: i32| {
+                    let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                    (nx >= 0 && ny >= 0 && nx < width as i32 && ny < height as i32)
+                        .then(|| owner[(ny as u32 * width + nx as u32) as usize])
+                };
+                // Coasts on every side, borders once between two regions
+                let coast = [(1, 0), (-1, 0), (0, 1), (0, -1)]
+                    .iter()
+                    .any(|&(dx, dy)| neighbour(dx, dy) == Some(None));
+                let border = [(1, 0), (0, 1)].iter().any(|&(dx, dy)| {
+                    neighbour(dx, dy).is_some_and(|other| other.is_some() && other != here)
+                });
+                if coast || border {
+                    img.put_pixel(x, y, colors.outline);
+                }
+            }
+        }
+
+        let to_pixel = |x: f32, y: f32| ((x * width as f32) as i32, (y * height as f32) as i32);
+        for route in &self.routes {
+            let from = self.regions.iter().find(|r| r.name == route.from);
+            let to = self.regions.iter().find(|r| r.name == route.to);
+            if let (Some(from), Some(to)) = (from, to) {
+                let sea_route = ["ship", "boat", "sea"]
+                    .iter()
+                    .any(|word| route.connection_type.to_lowercase().contains(word));
+                draw_line(
+                    &mut img,
+                    to_pixel(from.x, from.y),
+                    to_pixel(to.x, to.y),
+                    colors.route,
+                    if sea_route { 4 } else { 1 },
+                );
+            }
+        }
+
+        for marker in &self.markers {
+            let (cx, cy) = to_pixel(marker.x, marker.y);
+            let color = match marker.kind {
+                MarkerKind::Town => colors.town,
+                MarkerKind::Dungeon => colors.dungeon,
+            };
+            for dy in -2i32..=2 {
+                for dx in -2i32..=2 {
+                    let edge = dx.abs() == 2 || dy.abs() == 2;
+                    put(
+                        &mut img,
+                        cx + dx,
+                        cy + dy,
+                        if edge { colors.outline } else { color },
+                    );
+                }
+            }
+        }
+
+        img
+    }
+
+    /// Rasterize one dungeon floor in the palette
+    pub fn render_floor(floor: &MiniMapFloor, palette: &[Color]) -> RgbaImage {
+        let colors = MapColors::from_palette(palette);
+        let (Some(min_x), Some(min_y), Some(max_x), Some(max_y)) = (
+            floor.rooms.iter().map(|r| r.x).min(),
+            floor.rooms.iter().map(|r| r.y).min(),
+            floor.rooms.iter().map(|r| r.x).max(),
+            floor.rooms.iter().map(|r| r.y).max(),
+        ) else {
+            return RgbaImage::new(MINIMAP_CELL, MINIMAP_CELL);
+        };
+
+        let width = (max_x - min_x + 1) as u32 * MINIMAP_CELL;
+        let height = (max_y - min_y + 1) as u32 * MINIMAP_CELL;
+        let mut img = RgbaImage::new(width, height);
+        let center = |room: &MiniMapRoom| {
+            (
+                (room.x - min_x) * MINIMAP_CELL as i32 + MINIMAP_CELL as i32 / 2,
+                (room.y - min_y) * MINIMAP_CELL as i32 + MINIMAP_CELL as i32 / 2,
+            )
+        };
+
+        for room in &floor.rooms {
+            if let Some(from) = room.from.and_then(|i| floor.rooms.get(i)) {
+                draw_line(&mut img, center(from), center(room), colors.route, 1);
+            }
+        }
+        for room in &floor.rooms {
+            let (cx, cy) = center(room);
+            let fill = match room.kind {
+                RoomKind::Entrance => colors.town,
+                RoomKind::Room => colors.floor,
+                RoomKind::Treasure => colors.treasure,
+                RoomKind::Stairs => colors.route,
+                RoomKind::Boss => colors.dungeon,
+            };
+            let half = MINIMAP_CELL as i32 / 2 - 1;
+            for dy in -half..half {
+                for dx in -half..half {
+                    let edge = dx == -half || dy == -half || dx == half - 1 || dy == half - 1;
+                    put(
+                        &mut img,
+                        cx + dx,
+                        cy + dy,
+                        if edge { colors.outline } else { fill },
+                    );
+                }
+            }
+        }
+
+        img
+    }
+}
+
+impl DungeonMiniMap {
+    /// Grow each floor from its entrance by a seeded random walk; the last
+    /// room leads down, or holds the boss on the last floor
+    fn generate(dungeon: &str, seed: u64, floors: &[FloorData]) -> Self {
+        let floors = floors
+            .iter()
+            .enumerate()
+            .map(|(index, floor)| {
+                let mut rng = hash(&[seed, index as u64]);
+                let room_count = (4 + index * 2 + floor.treasures.len()).min(16);
+                let mut rooms = vec![MiniMapRoom {
+                    x: 0,
+                    y: 0,
+                    kind: RoomKind::Entrance,
+                    from: None,
+                }];
+                let mut attempts = 0;
+                while rooms.len() < room_count && attempts < room_count * 20 {
+                    attempts += 1;
+                    rng = hash(&[rng]);
+                    let parent = (rng % rooms.len() as u64) as usize;
+                    let (dx, dy) = [(1, 0), (-1, 0), (0, 1), (0, -1)][(rng >> 8) as usize % 4];
+                    let (x, y) = (rooms[parent].x + dx, rooms[parent].y + dy);
+                    if rooms.iter().any(|room| room.x == x && room.y == y) {
+                        continue;
+                    }
+                    rooms.push(MiniMapRoom {
+                        x,
+                        y,
+                        kind: RoomKind::Room,
+                        from: Some(parent),
+                    });
+                }
+
+                for room in rooms
+                    .iter_mut()
+                    .skip(1)
+                    .step_by(2)
+                    .take(floor.treasures.len())
+                {
+                    room.kind = RoomKind::Treasure;
+                }
+                if rooms.len() > 1
+                    && let Some(last) = rooms.last_mut()
+                {
+                    last.kind = if index + 1 == floors.len() {
+                        RoomKind::Boss
+                    } else {
+                        RoomKind::Stairs
+                    };
+                }
+                MiniMapFloor { rooms }
+            })
+            .collect();
+
+        Self {
+            dungeon: dungeon.to_string(),
+            floors,
+        }
+    }
+}
+
+/// Palette colors chosen for each map element
+pub(crate) struct MapColors {
+    palette: Vec<Rgba<u8>>,
+    pub(crate) sea: Rgba<u8>,
+    pub(crate) outline: Rgba<u8>,
+    pub(crate) route: Rgba<u8>,
+    pub(crate) town: Rgba<u8>,
+    pub(crate) dungeon: Rgba<u8>,
+    floor: Rgba<u8>,
+    treasure: Rgba<u8>,
+}
+
+impl MapColors {
+    pub(crate) fn from_palette(palette: &[Color]) -> Self {
+        let palette: Vec<Rgba<u8>> = palette.iter().map(|c| Rgba([c.r, c.g, c.b, 255])).collect();
+        let pick = |target: [u8; 3]| nearest(&palette, target);
+        Self {
+            sea: pick([40, 80, 168]),
+            outline: pick([16, 16, 24]),
+            route: pick([120, 72, 40]),
+            town: pick([248, 248, 240]),
+            dungeon: pick([200, 40, 40]),
+            floor: pick([136, 136, 152]),
+            treasure: pick([240, 200, 40]),
+            palette,
+        }
+    }
+
+    pub(crate) fn biome(&self, biome: &str) -> Rgba<u8> {
+        let biome = biome.to_lowercase();
+        let target = [
+            (["desert", "sand", "dune"], [216, 184, 112]),
+            (["snow", "ice", "tundra"], [232, 240, 248]),
+            (["mountain", "rock", "cliff"], [128, 120, 112]),
+            (["volcan", "lava", "ash"], [152, 56, 32]),
+            (["swamp", "marsh", "bog"], [88, 104, 56]),
+            (["forest", "wood", "jungle"], [40, 112, 48]),
+        ]
+        .iter()
+        .find(|(words, _)| words.iter().any(|word| biome.contains(word)))
+        .map(|(_, color)| *color)
+        .unwrap_or([96, 168, 72]);
+        nearest(&self.palette, target)
+    }
+}
+
+fn nearest(palette: &[Rgba<u8>], target: [u8; 3]) -> Rgba<u8> {
+    palette
+        .iter()
+        .min_by_key(|p| {
+            (0..3)
+                .map(|i| (p[i] as i32 - target[i] as i32).pow(2))
+                .sum::<i32>()
+        })
+        .copied()
+        .unwrap_or(Rgba([target[0], target[1], target[2], 255]))
+}
+
+/// Place regions by a force-directed layout over their connections
+fn layout_regions(world: &WorldData) -> Vec<(f32, f32)> {
+    let edges: Vec<(usize, usize)> = world
+        .connections
+        .iter()
+        .filter_map(|connection| {
+            let from = world
+                .regions
+                .iter()
+                .position(|r| r.name == connection.from)?;
+            let to = world.regions.iter().position(|r| r.name == connection.to)?;
+            (from != to).then_some((from, to))
+        })
+        .collect();
+    layout_graph(world.regions.len(), &edges)
+}
+
+/// Force-directed layout of a graph, fitted inside the map margin
+pub(crate) fn layout_graph(count: usize, edges: &[(usize, usize)]) -> Vec<(f32, f32)> {
+    let mut positions: Vec<(f32, f32)> = (0..count)
+        .map(|i| {
+            let angle = i as f32 / count.max(1) as f32 * std::f32::consts::TAU;
+            (0.5 + angle.cos() * 0.3, 0.5 + angle.sin() * 0.3)
+        })
+        .collect();
+
+    let ideal = 0.6 / (count.max(1) as f32).sqrt();
+    for iteration in 0..LAYOUT_ITERATIONS {
+        let mut forces = vec![(0.0f32, 0.0f32); count];
+        for a in 0..count {
+            for b in 0..count {
+                if a == b {
+                    continue;
+                }
+                let dx = positions[a].0 - positions[b].0;
+                let dy = positions[a].1 - positions[b].1;
+                let distance = (dx * dx + dy * dy).sqrt().max(0.01);
+                let push = ideal * ideal / distance;
+                forces[a].0 += dx / distance * push;
+                forces[a].1 += dy / distance * push;
+            }
+        }
+        for &(a, b) in edges {
+            let dx = positions[a].0 - positions[b].0;
+            let dy = positions[a].1 - positions[b].1;
+            let distance = (dx * dx + dy * dy).sqrt().max(0.01);
+            let pull = distance * distance / ideal;
+            forces[a].0 -= dx / distance * pull;
+            forces[a].1 -= dy / distance * pull;
+            forces[b].0 += dx / distance * pull;
+            forces[b].1 += dy / distance * pull;
+        }
+
+        // Cool down so the layout settles
+        let step = 0.05 * (1.0 - iteration as f32 / LAYOUT_ITERATIONS as f32);
+        for (position, force) in positions.iter_mut().zip(forces) {
+            let length = (force.0 * force.0 + force.1 * force.1).sqrt().max(1e-6);
+            position.0 += force.0 / length * step.min(length);
+            position.1 += force.1 / length * step.min(length);
+        }
+    }
+
+    // Fit the layout to the map, leaving room for the coasts
+    let (min_x, max_x, min_y, max_y) = positions.iter().fold(
+        (f32::MAX, f32::MIN, f32::MAX, f32::MIN),
+        |(min_x, max_x, min_y, max_y), &(x, y)| {
+            (min_x.min(x), max_x.max(x), min_y.min(y), max_y.max(y))
+        },
+    );
+    let fit = |value: f32, min: f32, max: f32| {
+        if max - min < 1e-3 {
+            0.5
+        } else {
+            LAYOUT_MARGIN + (value - min) / (max - min) * (1.0 - 2.0 * LAYOUT_MARGIN)
+        }
+    };
+    positions
+        .into_iter()
+        .map(|(x, y)| (fit(x, min_x, max_x), fit(y, min_y, max_y)))
+        .collect()
+}
+
+/// Bresenham line; `dash` > 1 leaves gaps for dotted lines
+pub(crate) fn draw_line(
+    img: &mut RgbaImage,
+    from: (i32, i32),
+    to: (i32, i32),
+    color: Rgba<u8>,
+    dash: i32,
+) {
+    let (mut x, mut y) = from;
+    let dx = (to.0 - x).abs();
+    let dy = -(to.1 - y).abs();
+    let sx = if x < to.0 { 1 } else { -1 };
+    let sy = if y < to.1 { 1 } else { -1 };
+    let mut error = dx + dy;
+    let mut step = 0;
+    loop {
+        if step % dash.max(1) < (dash.max(1) + 1) / 2 {
+            put(img, x, y, color);
+        }
+        if (x, y) == to {
+            break;
+        }
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += sx;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += sy;
+        }
+        step += 1;
+    }
+}
+
+pub(crate) fn put(img: &mut RgbaImage, x: i32, y: i32, color: Rgba<u8>) {
+    if x >= 0 && y >= 0 && (x as u32) < img.width() && (y as u32) < img.height() {
+        img.put_pixel(x as u32, y as u32, color);
+    }
+}
+
+/// Stable 64-bit mix of the values
+pub(crate) fn hash(values: &[u64]) -> u64 {
+    values.iter().fold(0x9e37_79b9_7f4a_7c15, |state, value| {
+        let mut z = state ^ value.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    })
+}
@@ -0,0 +1,629 @@
+//! Offline provider with canned responses
+//!
+//! With `ai_provider = "mock"` the service talks to a [`MockProvider`]: a
+//! local server that answers the OpenAI endpoints the generators use, so
+//! every generator works unchanged without an API key or a network:
+//!
+//! - chat completions, streamed or not, return the fixture whose keyword
+//!   the request mentions, or a deterministic placeholder
+//! - image generations and edits return a placeholder sprite drawn with
+//!   the active palette, different for every prompt
+//! - embeddings are deterministic unit vectors of the input
+//! - speech is a second of silence
+//!
+//! A project adds fixtures as files in its `fixtures/` directory; the file
+//! stem is the keyword, with underscores for spaces, e.g.
+//! `fixtures/boss_roster.json`. Usage is reported as zero tokens.
+
+use crate::consistency::{Color, StyleManager};
+use crate::maps::hash;
+use anyhow::{Context, Result};
+use async_openai::config::OpenAIConfig;
+use base64::Engine;
+use image::{Rgba, RgbaImage};
+use serde_json::{Value, json};
+use std::path::Path;
+use std::sync::{Arc, LazyLock, RwLock};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+
+/// `AiConfig::ai_provider` that selects the mock provider
+pub const MOCK_PROVIDER: &str = "mock";
+
+/// Directory of a project holding its fixtures
+pub const FIXTURES_DIR: &str = "fixtures";
+
+/// Length of an embedding when the request doesn't ask for one
+const EMBEDDING_DIMENSIONS: usize = 1536;
+
+/// Sample rate of the silence returned for speech
+const SPEECH_SAMPLE_RATE: u32 = 24_000;
+
+/// Cells per side of the pattern of a placeholder image
+const PATTERN_CELLS: u32 = 8;
+
+/// Fixtures every mock provider has, by keyword
+const FIXTURES: &[(&str, &str)] = &[
+    (
+        "sound designer",
+        "Placeholder sound effect: silence. Square wave, start 440 Hz, end 440 Hz, \
+        immediate attack, short release.",
+    ),
+    (
+        "music composer",
+        "Placeholder track: a silent loop.\n\nIntro: rest.\nMain loop: rest.\nOutro: rest.",
+    ),
+];
+
+/// Palette of placeholders when no style is known
+const DEFAULT_PALETTE: [[u8; 3]; 4] = [
+    [0x22, 0x20, 0x34],
+    [0x5b, 0x6e, 0xe1],
+    [0x99, 0xe5, 0x50],
+    [0xfb, 0xf2, 0x36],
+];
+
+static SHARED: LazyLock<std::result::Result<MockProvider, String>> =
+    LazyLock::new(|| MockProvider::start().map_err(|e| format!("{e:#}")));
+
+#[derive(Default)]
+struct MockState {
+    /// Project fixtures by keyword, checked before the built-in ones
+    fixtures: RwLock<Vec<(String, String)>>,
+    style_manager: RwLock<Option<Arc<Mutex<StyleManager>>>>,
+}
+
+/// Local stand-in for the OpenAI API
+#[derive(Clone)]
+pub struct MockProvider {
+    api_base: String,
+    state: Arc<MockState>,
+}
+
+impl MockProvider {
+    /// The provider of this process, started on first use
+    pub fn shared() -> Result<Self> {
+        SHARED.clone().map_err(anyhow::Error::msg)
+    }
+
+    /// Serve on a free local port from a thread of its own, so the provider
+    /// outlives any runtime of the caller
+    fn start() -> Result<Self> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")
+            .context("Failed to bind the mock provider")?;
+        listener.set_nonblocking(true)?;
+        let api_base = format!("http://{}/v1", listener.local_addr()?);
+        let state = Arc::new(MockState::default());
+
+        let server_state = state.clone();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .context("Failed to create the mock provider runtime")?;
+        std::thread::Builder::new()
+            .name("mock-provider".to_string())
+            .spawn(move || {
+                runtime.block_on(async move {
+                    let Ok(listener) = tokio::net::TcpListener::from_std(listener) else {
+                        return;
+                    };
+                    while let Ok((stream, _)) = listener.accept().await {
+                        let state = server_state.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = handle(stream, &state).await {
+                                tracing::warn!("Mock provider request failed: {e:#}");
+                            }
+                        });
+                    }
+                });
+            })
+            .context("Failed to start the mock provider")?;
+
+        tracing::info!("Mock provider serving {api_base}");
+        Ok(Self { api_base, state })
+    }
+
+    pub fn api_base(&self) -> &str {
+        &self.api_base
+    }
+
+    /// Client configuration pointing at the provider
+    pub fn config(&self) -> OpenAIConfig {
+        OpenAIConfig::new()
+            .with_api_base(&self.api_base)
+            .with_api_key(MOCK_PROVIDER)
+    }
+
+    /// Draw placeholders with the palette of this style manager
+    pub fn set_style_manager(&self, style_manager: Arc<Mutex<StyleManager>>) {
+        if let Ok(mut current) = self.state.style_manager.write() {
+            *current = Some(style_manager);
+        }
+    }
+
+    /// Replace the project fixtures with the files of `dir`. Returns how
+    /// many were loaded; a missing directory has none.
+    pub fn load_fixtures(&self, dir: &Path) -> Result<usize> {
+        let mut fixtures = Vec::new();
+        if dir.is_dir() {
+            for entry in std::fs::read_dir(dir)
+                .with_context(|| format!("Failed to read {}", dir.display()))?
+            {
+                let path = entry?.path();
+                let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                    continue;
+                };
+                if !path.is_file() {
+                    continue;
+                }
+                let content = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                fixtures.push((stem.replace('_', " ").to_lowercase(), content));
+            }
+        }
+        // Longer keywords are more specific
+        fixtures.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        let count = fixtures.len();
+        if let Ok(mut current) = self.state.fixtures.write() {
+            *current = fixtures;
+        }
+        Ok(count)
+    }
+}
+
+/// Response to a chat whose messages read `text`
+fn fixture(state: &MockState, text: &str) -> Option<String> {
+    let text = text.to_lowercase();
+    let project = state.fixtures.read().ok().and_then(|fixtures| {
+        fixtures
+            .iter()
+            .find(|(keyword, _)| text.contains(keyword.as_str()))
+            .map(|(_, response)| response.clone())
+    });
+    project.or_else(|| {
+        FIXTURES
+            .iter()
+            .find(|(keyword, _)| text.contains(keyword))
+            .map(|(_, response)| response.to_string())
+    })
+}
+
+/// Answer one request and close the connection
+async fn handle(stream: TcpStream, state: &MockState) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let path = request_line
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or_default()
+        .to_string();
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse().unwrap_or(0);
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await?;
+
+    let (status, content_type, response) = respond(&path, &body, state).await;
+    let head = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+        Connection: close\r\n\r\n",
+        response.len()
+    );
+    let mut stream = reader.into_inner();
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(&response).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+async fn respond(
+    path: &str,
+    body: &[u8],
+    state: &MockState,
+) -> (&'static str, &'static str, Vec<u8>) {
+    let request: Value = serde_json::from_slice(body).unwrap_or(Value::Null);
+    let json_response =
+        |value: Value| ("200 OK", "application/json", value.to_string().into_bytes());
+
+    if path.ends_with("/chat/completions") {
+        let response = chat_response(&request, state);
+        let model = request["model"].as_str().unwrap_or(MOCK_PROVIDER);
+        if request["stream"].as_bool() == Some(true) {
+            return ("200 OK", "text/event-stream", chat_stream(model, &response));
+        }
+        json_response(json!({
+            "id": "chatcmpl-mock",
+            "object": "chat.completion",
+            "created": 0,
+            "model": model,
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": response },
+                "finish_reason": "stop",
+                "logprobs": null,
+            }],
+            "usage": { "prompt_tokens": 0, "completion_tokens": 0, "total_tokens": 0 },
+        }))
+    } else if path.ends_with("/images/generations") || path.ends_with("/images/edits") {
+        let (prompt, size) = if path.ends_with("/edits") {
+            (
+                multipart_field(body, "prompt").unwrap_or_default(),
+                multipart_field(body, "size"),
+            )
+        } else {
+            (
+                request["prompt"].as_str().unwrap_or_default().to_string(),
+                request["size"].as_str().map(str::to_string),
+            )
+        };
+        let (width, height) = size
+            .as_deref()
+            .and_then(|size| size.split_once('x'))
+            .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+            .unwrap_or((1024, 1024));
+        let palette = palette(state).await;
+        let mut png = Vec::new();
+        let encoded = placeholder_image(&prompt, width, height, &palette)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png);
+        if encoded.is_err() {
+            return ("500 Internal Server Error", "text/plain", Vec::new());
+        }
+        json_response(json!({
+            "created": 0,
+            "data": [{ "b64_json": base64::engine::general_purpose::STANDARD.encode(&png) }],
+        }))
+    } else if path.ends_with("/embeddings") {
+        let inputs: Vec<String> = match &request["input"] {
+            Value::Array(items) => items.iter().map(Value::to_string).collect(),
+            input => vec![input.to_string()],
+        };
+        let dimensions = request["dimensions"]
+            .as_u64()
+            .map_or(EMBEDDING_DIMENSIONS, |dimensions| dimensions as usize);
+        json_response(json!({
+            "object": "list",
+            "model": request["model"].as_str().unwrap_or(MOCK_PROVIDER),
+            "data": inputs
+                .iter()
+                .enumerate()
+                .map(|(index, input)| json!({
+                    "index": index,
+                    "object": "embedding",
+                    "embedding": embedding(input, dimensions),
+                }))
+                .collect::<Vec<_>>(),
+            "usage": { "prompt_tokens": 0, "total_tokens": 0 },
+        }))
+    } else if path.ends_with("/audio/speech") {
+        ("200 OK", "audio/wav", silence(SPEECH_SAMPLE_RATE))
+    } else {
+        (
+            "404 Not Found",
+            "application/json",
+            json!({ "error": { "message": format!("The mock provider doesn't serve {path}") } })
+                .to_string()
+                .into_bytes(),
+        )
+    }
+}
+
+/// Fixture or placeholder answering a chat request
+fn chat_response(request: &Value, state: &MockState) -> String {
+    let messages = request["messages"].as_array().cloned().unwrap_or_default();
+    let text: String = messages
+        .iter()
+        .map(|message| match &message["content"] {
+            Value::String(content) => content.clone(),
+            content => content.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if let Some(response) = fixture(state, &text) {
+        return response;
+    }
+
+    let format = request["response_format"]["type"]
+        .as_str()
+        .unwrap_or("text");
+    if format != "text" {
+        return json!({ "placeholder": true }).to_string();
+    }
+    let request_line = messages
+        .iter()
+        .rev()
+        .find(|message| message["role"] == "user")
+        .and_then(|message| message["content"].as_str())
+        .and_then(|content| content.lines().find(|line| !line.trim().is_empty()))
+        .unwrap_or("the request");
+    let request_line: String = request_line.trim().chars().take(120).collect();
+    format!(
+        "# Offline placeholder\n\nThis is a canned response of the mock provider, standing in \
+        for an answer to: {request_line}\n\n- First placeholder point\n- Second placeholder point\n"
+    )
+}
+
+/// Server-sent events streaming the response as one chunk
+fn chat_stream(model: &str, response: &str) -> Vec<u8> {
+    let chunk = |delta: Value, finish_reason: Value| {
+        json!({
+            "id": "chatcmpl-mock",
+            "object": "chat.completion.chunk",
+            "created": 0,
+            "model": model,
+            "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }],
+        })
+    };
+    let events = [
+        chunk(
+            json!({ "role": "assistant", "content": response }),
+            Value::Null,
+        ),
+        chunk(json!({}), json!("stop")),
+    ];
+    let mut stream = String::new();
+    for event in events {
+        stream.push_str(&format!("data: {event}\n\n"));
+    }
+    stream.push_str("data: [DONE]\n\n");
+    stream.into_bytes()
+}
+
+/// Value of a text field of a multipart body
+fn multipart_field(body: &[u8], name: &str) -> Option<String> {
+    let body = String::from_utf8_lossy(body);
+    let start = body.find(&format!("name=\"{name}\""))?;
+    let value = &body[start..];
+    let value = &value[value.find("\r\n\r\n")? + 4..];
+    Some(value[..value.find("\r\n")?].to_string())
+}
+
+/// Opaque colors of the active palette, darkest first
+async fn palette(state: &MockState) -> Vec<[u8; 3]> {
+    let style_manager = state
+        .style_manager
+        .read()
+        .ok()
+        .and_then(|style_manager| style_manager.clone());
+    let mut colors: Vec<[u8; 3]> = match style_manager {
+        Some(style_manager) => {
+            let palette = style_manager.lock().await.get_style().await.palette;
+            palette
+                .primary_colors
+                .iter()
+                .chain(&palette.secondary_colors)
+                .chain(&palette.accent_colors)
+                .filter(|color| color.a > 0)
+                .map(|&Color { r, g, b, .. }| [r, g, b])
+                .collect()
+        }
+        None => Vec::new(),
+    };
+    if colors.len() < 2 {
+        colors = DEFAULT_PALETTE.to_vec();
+    }
+    colors.sort_by_key(|[r, g, b]| u32::from(*r) * 299 + u32::from(*g) * 587 + u32::from(*b) * 114);
+    colors
+}
+
+fn text_hash(text: &str) -> u64 {
+    hash(&text.bytes().map(u64::from).collect::<Vec<_>>())
+}
+
+/// A mirrored pattern of cells in a color picked by the prompt, outlined
+/// in the darkest color, on a transparent background
+fn placeholder_image(prompt: &str, width: u32, height: u32, palette: &[[u8; 3]]) -> RgbaImage {
+    let seed = text_hash(prompt);
+    let outline = palette[0];
+    let fill = palette[1 + (seed % (palette.len() as u64 - 1)) as usize];
+    let cell = (width.min(height) / (PATTERN_CELLS + 2)).max(1);
+    let (left, top) = (
+        (width.saturating_sub(cell * PATTERN_CELLS)) / 2,
+        (height.saturating_sub(cell * PATTERN_CELLS)) / 2,
+    );
+    let filled = |x: i64, y: i64| {
+        if x < 0 || y < 0 || x >= PATTERN_CELLS as i64 || y >= PATTERN_CELLS as i64 {
+            return false;
+        }
+        let column = x.min(PATTERN_CELLS as i64 - 1 - x) as u64;
+        hash(&[seed, column, y as u64]).is_multiple_of(2)
+    };
+
+    let mut image = RgbaImage::new(width, height);
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        if x < left || y < top {
+            continue;
+        }
+        let (cx, cy) = (((x - left) / cell) as i64, ((y - top) / cell) as i64);
+        let (inner_x, inner_y) = ((x - left) % cell, (y - top) % cell);
+        if filled(cx, cy) {
+            *pixel = Rgba([fill[0], fill[1], fill[2], 255]);
+            let border = (cell / 8).max(1);
+            let edge = (inner_x < border && !filled(cx - 1, cy))
+                || (inner_x >= cell - border && !filled(cx + 1, cy))
+                || (inner_y < border && !filled(cx, cy - 1))
+                || (inner_y >= cell - border && !filled(cx, cy + 1));
+            if edge {
+                *pixel = Rgba([outline[0], outline[1], outline[2], 255]);
+            }
+        }
+    }
+    image
+}
+
+/// Unit vector that is the same for the same input
+fn embedding(input: &str, dimensions: usize) -> Vec<f32> {
+    let seed = text_hash(input);
+    let vector: Vec<f32> = (0..dimensions as u64)
+        .map(|i| (hash(&[seed, i]) % 2001) as f32 / 1000.0 - 1.0)
+        .collect();
+    let norm = vector
+        .iter()
+        .map(|v| v * v)
+        .sum::<f32>()
+        .sqrt()
+        .max(f32::EPSILON);
+    vector.into_iter().map(|v| v / norm).collect()
+}
+
+/// Mono 16-bit WAV of one second of silence
+fn silence(sample_rate: u32) -> Vec<u8> {
+    let data_len = sample_rate * 2;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    wav.resize(44 + data_len as usize, 0);
+    wav
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AiService;
+    use crate::image::ImageConfig;
+    use crate::text::TextConfig;
+
+    #[tokio::test]
+    async fn offline_text_is_deterministic() {
+        let service = AiService::offline().unwrap();
+        let prompt = "Describe the harbor town of Port Sylva";
+        let first = service
+            .text()
+            .generate(prompt, TextConfig::default())
+            .await
+            .unwrap();
+        let second = service
+            .text()
+            .generate(prompt, TextConfig::default())
+            .await
+            .unwrap();
+
+        assert_eq!(first, second);
+        assert!(first.starts_with("# Offline placeholder"));
+        assert!(first.contains(prompt));
+
+        let request = json!({ "messages": [{ "role": "user", "content": prompt }] });
+        let state = MockState::default();
+        assert_eq!(
+            chat_response(&request, &state),
+            chat_response(&request, &state)
+        );
+    }
+
+    #[test]
+    fn fixtures_answer_chats_that_mention_them() {
+        let state = MockState::default();
+        *state.fixtures.write().unwrap() = vec![("boss roster".to_string(), "[]".to_string())];
+        let request =
+            |content: &str| json!({ "messages": [{ "role": "user", "content": content }] });
+
+        assert_eq!(
+            chat_response(&request("Design the BOSS ROSTER"), &state),
+            "[]"
+        );
+        assert!(
+            chat_response(&request("You are a music composer"), &state).contains("silent loop")
+        );
+        let structured = json!({
+            "messages": [{ "role": "user", "content": "Anything" }],
+            "response_format": { "type": "json_object" },
+        });
+        assert_eq!(
+            chat_response(&structured, &state),
+            r#"{"placeholder":true}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn offline_sprites_use_only_the_palette() {
+        let service = AiService::offline().unwrap();
+        let png = service
+            .image()
+            .generate_single("a knight with a red cape", ImageConfig::for_sprites())
+            .await
+            .unwrap();
+        let sprite = image::load_from_memory(&png).unwrap().to_rgba8();
+
+        let style = service.style_manager.lock().await.get_style().await;
+        let palette: Vec<[u8; 3]> = style
+            .palette
+            .primary_colors
+            .iter()
+            .chain(&style.palette.secondary_colors)
+            .chain(&style.palette.accent_colors)
+            .map(|&Color { r, g, b, .. }| [r, g, b])
+            .chain(DEFAULT_PALETTE)
+            .collect();
+        let mut opaque = 0;
+        for pixel in sprite.pixels() {
+            match pixel.0 {
+                [_, _, _, 0] => {}
+                [r, g, b, 255] => {
+                    assert!(palette.contains(&[r, g, b]), "{r},{g},{b} is off palette");
+                    opaque += 1;
+                }
+                other => panic!("pixel {other:?} is partly transparent"),
+            }
+        }
+        assert!(opaque > 0);
+    }
+
+    #[test]
+    fn placeholders_differ_by_prompt() {
+        let palette = DEFAULT_PALETTE.to_vec();
+        let knight = placeholder_image("knight", 64, 64, &palette);
+        assert_eq!(knight, placeholder_image("knight", 64, 64, &palette));
+        assert_ne!(knight, placeholder_image("slime", 64, 64, &palette));
+        assert_eq!(knight.dimensions(), (64, 64));
+    }
+
+    #[tokio::test]
+    async fn speech_is_a_second_of_silence() {
+        let provider = MockProvider::shared().unwrap();
+        let wav = reqwest::Client::new()
+            .post(format!("{}/audio/speech", provider.api_base()))
+            .json(&json!({ "model": "tts-1", "input": "Hello", "voice": "alloy" }))
+            .send()
+            .await
+            .unwrap()
+            .bytes()
+            .await
+            .unwrap();
+
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(&wav[8..12], b"WAVE");
+        let data = &wav[44..];
+        assert_eq!(data.len(), SPEECH_SAMPLE_RATE as usize * 2);
+        assert!(data.iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn embeddings_are_deterministic_unit_vectors() {
+        let vector = embedding("Aldwyn", 64);
+        assert_eq!(vector, embedding("Aldwyn", 64));
+        assert_ne!(vector, embedding("Port Sylva", 64));
+        let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-4);
+    }
+}
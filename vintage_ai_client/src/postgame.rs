@@ -0,0 +1,145 @@
+//! New Game+ and postgame content
+//!
+//! An optional generation phase plans what opens up after the credits:
+//! remixes of the story dungeons with stronger enemies and a new gimmick, a
+//! superboss that outclasses every story boss, and the rules for what
+//! carries over into a New Game+. The plan is checked against the generated
+//! world and the boss balance before it is exported.
+
+use serde::{Deserialize, Serialize};
+
+use crate::bosses::{BossDesign, BossRoster};
+use crate::game_assets::asset_id;
+use crate::game_types::WorldData;
+
+/// HP a superboss needs relative to the strongest story boss
+pub const SUPERBOSS_MIN_HP_RATIO: f32 = 1.5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemixedDungeon {
+    /// Story dungeon the remix is built from
+    pub dungeon: String,
+    pub name: String,
+    pub description: String,
+    /// Mechanic that replaces or twists the original gimmick
+    pub gimmick: String,
+    /// Levels added to every encounter of the original dungeon
+    pub level_bonus: u32,
+    #[serde(default)]
+    pub new_enemies: Vec<String>,
+    #[serde(default)]
+    pub rewards: Vec<String>,
+}
+
+/// What a New Game+ keeps from the finished save
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CarryOverRules {
+    pub levels: bool,
+    pub equipment: bool,
+    pub items: bool,
+    /// Story items are usually reset with the plot
+    pub key_items: bool,
+    /// Fraction of the gold kept, 0.0 to 1.0
+    pub gold_fraction: f32,
+    /// Enemy stat multiplier of the New Game+
+    pub enemy_scaling: f32,
+}
+
+impl Default for CarryOverRules {
+    fn default() -> Self {
+        Self {
+            levels: true,
+            equipment: true,
+            items: false,
+            key_items: false,
+            gold_fraction: 0.5,
+            enemy_scaling: 1.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostgamePlan {
+    /// What the player must do to open the postgame, e.g. the final quest
+    pub unlock: String,
+    pub remixed_dungeons: Vec<RemixedDungeon>,
+    pub superboss: BossDesign,
+    #[serde(default)]
+    pub carry_over: CarryOverRules,
+}
+
+/// A rule a postgame plan breaks
+#[derive(Debug, Clone, PartialEq)]
+pub enum PostgameIssue {
+    NoRemixes,
+    UnknownDungeon(String),
+    NoLevelBonus(String),
+    /// The superboss isn't clearly stronger than the story bosses
+    WeakSuperboss {
+        hp: u32,
+        strongest: u32,
+    },
+    GoldFraction(f32),
+    EnemyScaling(f32),
+}
+
+impl std::fmt::Display for PostgameIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoRemixes => write!(f, "no remixed dungeons"),
+            Self::UnknownDungeon(name) => write!(f, "remix of unknown dungeon '{name}'"),
+            Self::NoLevelBonus(name) => write!(f, "remix '{name}' is no stronger than its dungeon"),
+            Self::WeakSuperboss { hp, strongest } => write!(
+                f,
+                "superboss has {hp} HP, needs at least {SUPERBOSS_MIN_HP_RATIO}x the strongest \
+                story boss ({strongest} HP)"
+            ),
+            Self::GoldFraction(fraction) => {
+                write!(f, "gold carry-over {fraction} is outside 0.0..=1.0")
+            }
+            Self::EnemyScaling(scaling) => {
+                write!(f, "New Game+ enemy scaling {scaling} is below 1.0")
+            }
+        }
+    }
+}
+
+impl PostgamePlan {
+    /// Derive the superboss's enemy id from its name
+    pub fn cross_reference(&mut self) {
+        self.superboss.id = asset_id(&self.superboss.name);
+    }
+
+    /// Check the plan against the world and the story bosses
+    pub fn validate(&self, world: &WorldData, bosses: &BossRoster) -> Vec<PostgameIssue> {
+        let mut issues = Vec::new();
+        if self.remixed_dungeons.is_empty() {
+            issues.push(PostgameIssue::NoRemixes);
+        }
+        for remix in &self.remixed_dungeons {
+            if !world.dungeons.iter().any(|d| d.name == remix.dungeon) {
+                issues.push(PostgameIssue::UnknownDungeon(remix.dungeon.clone()));
+            }
+            if remix.level_bonus == 0 {
+                issues.push(PostgameIssue::NoLevelBonus(remix.name.clone()));
+            }
+        }
+
+        let strongest = bosses.bosses.iter().map(|boss| boss.hp).max().unwrap_or(0);
+        if (self.superboss.hp as f32) < strongest as f32 * SUPERBOSS_MIN_HP_RATIO {
+            issues.push(PostgameIssue::WeakSuperboss {
+                hp: self.superboss.hp,
+                strongest,
+            });
+        }
+
+        let rules = &self.carry_over;
+        if !(0.0..=1.0).contains(&rules.gold_fraction) {
+            issues.push(PostgameIssue::GoldFraction(rules.gold_fraction));
+        }
+        if rules.enemy_scaling < 1.0 {
+            issues.push(PostgameIssue::EnemyScaling(rules.enemy_scaling));
+        }
+        issues
+    }
+}
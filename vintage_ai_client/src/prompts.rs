@@ -0,0 +1,532 @@
+//! Prompt templates with per-project overrides
+//!
+//! The image and audio prompts are compiled into the crate. A project can
+//! override any of them with a file of the same name under its `prompts/`
+//! directory, e.g. `prompts/image/sprite.jinja`, which is searched before
+//! the embedded template. Overrides are read when a generator is created,
+//! so an edited override applies from the next generator on. An override
+//! that doesn't parse is skipped with a warning in favour of the embedded
+//! template, so a typo can't stop generation.
+//!
+//! A project can also define variables and macros for its prompts in
+//! `prompts/variables.toml`, e.g. `{{ protagonist_name }}`, which every
+//! prompt's template environment resolves. Variables are plain text that
+//! is never evaluated as a template, and macros render without access to
+//! files, so a project's definitions can only produce prompt text.
+//! [`PromptLibrary::check_variables`] finds the names the project's
+//! prompts use that nothing defines, so a run can refuse to start rather
+//! than send a prompt with a hole in it.
+
+use anyhow::{Context, Result};
+use minijinja::{Environment, UndefinedBehavior};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Directory of a project holding its prompt overrides
+pub const PROMPT_OVERRIDE_DIR: &str = "prompts";
+
+/// File in the override directory defining the project's prompt variables
+/// and macros
+pub const PROMPT_VARIABLES_FILE: &str = "variables.toml";
+
+/// Template the project's macros are compiled into and imported from
+const MACROS_TEMPLATE: &str = "project_macros";
+
+/// A prompt template compiled into the crate
+#[derive(Debug)]
+pub struct EmbeddedPrompt {
+    /// Group and template name, e.g. `image/sprite`
+    pub name: &'static str,
+    pub source: &'static str,
+    /// Names the generator that sends the prompt passes in
+    pub context: &'static [&'static str],
+}
+
+/// Names the music generator passes to each music prompt
+const MUSIC_CONTEXT: &[&str] = &[
+    "game_name",
+    "style",
+    "mood",
+    "tempo",
+    "key",
+    "time_signature",
+    "duration",
+    "instrumentation",
+    "genre",
+    "constraints",
+];
+
+/// Every embedded prompt, by group
+pub const EMBEDDED_PROMPTS: &[EmbeddedPrompt] = &[
+    EmbeddedPrompt {
+        name: "image/style_guide",
+        source: include_str!("../prompts/image/style_guide.jinja"),
+        context: &[
+            "genre",
+            "max_colors",
+            "character_width",
+            "character_height",
+            "tile_width",
+            "tile_height",
+            "shading_technique",
+            "outline_style",
+            "perspective",
+            "visual_inspirations",
+            "reference_screens",
+            "mood",
+            "style_name",
+        ],
+    },
+    EmbeddedPrompt {
+        name: "image/sprite",
+        source: include_str!("../prompts/image/sprite.jinja"),
+        context: &[
+            "sprite_type",
+            "description",
+            "max_width",
+            "max_height",
+            "max_colors",
+            "shading_technique",
+            "outline_style",
+            "perspective",
+            "visual_style",
+        ],
+    },
+    EmbeddedPrompt {
+        name: "image/tileset",
+        source: include_str!("../prompts/image/tileset.jinja"),
+        context: &[],
+    },
+    EmbeddedPrompt {
+        name: "image/style_consistency",
+        source: include_str!("../prompts/image/style_consistency.jinja"),
+        context: &[
+            "base_prompt",
+            "style_name",
+            "palette_name",
+            "max_colors",
+            "color_list",
+            "pixel_size",
+            "outline_style",
+            "shading_technique",
+            "light_direction",
+            "perspective",
+            "character_width",
+            "character_height",
+            "constraints",
+        ],
+    },
+    EmbeddedPrompt {
+        name: "audio/theme_song",
+        source: include_str!("../prompts/audio/theme_song.jinja"),
+        context: MUSIC_CONTEXT,
+    },
+    EmbeddedPrompt {
+        name: "audio/battle_music",
+        source: include_str!("../prompts/audio/battle_music.jinja"),
+        context: MUSIC_CONTEXT,
+    },
+    EmbeddedPrompt {
+        name: "audio/victory_fanfare",
+        source: include_str!("../prompts/audio/victory_fanfare.jinja"),
+        context: MUSIC_CONTEXT,
+    },
+    EmbeddedPrompt {
+        name: "audio/sound_effect",
+        source: include_str!("../prompts/audio/sound_effect.jinja"),
+        context: &[
+            "effect_type",
+            "duration",
+            "constraints",
+            "waveforms",
+            "console",
+        ],
+    },
+];
+
+/// Source of a prompt as a generator would use it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptSource {
+    pub text: String,
+    /// The project's override file, `None` for the embedded template
+    pub override_path: Option<PathBuf>,
+}
+
+/// Looks prompts up in a project's overrides, then in the embedded ones
+#[derive(Debug, Clone, Default)]
+pub struct PromptLibrary {
+    override_dir: Option<PathBuf>,
+}
+
+impl PromptLibrary {
+    /// Library of the embedded prompts only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Search the `prompts/` directory of `project_dir` first
+    pub fn with_project_dir(mut self, project_dir: &Path) -> Self {
+        self.override_dir = Some(project_dir.join(PROMPT_OVERRIDE_DIR));
+        self
+    }
+
+    /// Where overrides are searched, if anywhere
+    pub fn override_dir(&self) -> Option<&Path> {
+        self.override_dir.as_deref()
+    }
+
+    /// File that overrides the prompt `name`, whether or not it exists
+    pub fn override_path(&self, name: &str) -> Option<PathBuf> {
+        self.override_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{name}.jinja")))
+    }
+
+    /// The project's override of `name` if there is one, otherwise the
+    /// embedded template
+    pub fn source(&self, name: &str) -> Result<PromptSource> {
+        if let Some(path) = self.override_path(name)
+            && path.is_file()
+        {
+            let text = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            return Ok(PromptSource {
+                text,
+                override_path: Some(path),
+            });
+        }
+        let embedded =
+            embedded_prompt(name).with_context(|| format!("No prompt template named {name}"))?;
+        Ok(PromptSource {
+            text: embedded.source.to_string(),
+            override_path: None,
+        })
+    }
+
+    /// File holding the project's variables and macros, whether or not it
+    /// exists
+    pub fn variables_path(&self) -> Option<PathBuf> {
+        self.override_dir
+            .as_ref()
+            .map(|dir| dir.join(PROMPT_VARIABLES_FILE))
+    }
+
+    /// The project's variables and macros, none without a project
+    pub fn variables(&self) -> Result<PromptVariables> {
+        match self.variables_path() {
+            Some(path) => PromptVariables::load(&path),
+            None => Ok(PromptVariables::default()),
+        }
+    }
+
+    /// Problems with the project's variables that would break its prompts
+    pub fn check_variables(&self) -> Result<Vec<VariableIssue>> {
+        Ok(self.check_variables_with(&self.variables()?))
+    }
+
+    /// Problems the prompts would have with `variables` in place of the
+    /// project's, e.g. to check edits before saving them: names that can't
+    /// be used in a template, names the generators already set, and names a
+    /// prompt override or macro uses that neither the generator nor the
+    /// project defines. Names the embedded template reads count as defined
+    /// too, as they render the way they do in the built-in prompt.
+    pub fn check_variables_with(&self, variables: &PromptVariables) -> Vec<VariableIssue> {
+        let builtins: HashSet<String> = Environment::new()
+            .globals()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        let mut issues = Vec::new();
+        let mut issue = |location: &str, message: String| {
+            issues.push(VariableIssue {
+                location: location.to_string(),
+                message,
+            });
+        };
+
+        let mut generator_names = BTreeMap::new();
+        for prompt in EMBEDDED_PROMPTS {
+            for name in prompt.context {
+                generator_names.entry(*name).or_insert(prompt.name);
+            }
+        }
+        for name in variables.names() {
+            if !is_identifier(name) {
+                issue(
+                    name,
+                    "is not a valid name; use letters, digits and underscores".to_string(),
+                );
+            } else if builtins.contains(name) {
+                issue(name, format!("hides the built-in `{name}` function"));
+            } else if let Some(prompt) = generator_names.get(name) {
+                issue(
+                    name,
+                    format!("is set by the generator of {prompt}, which ignores this value"),
+                );
+            }
+            if variables.variables.contains_key(name) && variables.macros.contains_key(name) {
+                issue(
+                    name,
+                    "is defined both as a variable and as a macro".to_string(),
+                );
+            }
+        }
+
+        let defined = |name: &str| {
+            builtins.contains(name)
+                || variables.variables.contains_key(name)
+                || variables.macros.contains_key(name)
+        };
+        for (name, definition) in &variables.macros {
+            let location = format!("macro {name}");
+            if let Err(e) = Environment::new().template_from_str(&definition.body) {
+                issue(&location, format!("doesn't parse: {e}"));
+                continue;
+            }
+            for used in undeclared(&definition.body) {
+                if !defined(&used) && !definition.args.contains(&used) {
+                    issue(&location, format!("uses `{used}`, which isn't defined"));
+                }
+            }
+        }
+
+        let import = variables.import_tag();
+        for prompt in EMBEDDED_PROMPTS {
+            let Ok(source) = self.source(prompt.name) else {
+                continue;
+            };
+            if source.override_path.is_none() {
+                continue;
+            }
+            let supplied = undeclared(prompt.source);
+            for used in undeclared(&format!("{import}{}", source.text)) {
+                if !defined(&used)
+                    && !supplied.contains(&used)
+                    && !prompt.context.contains(&used.as_str())
+                {
+                    issue(prompt.name, format!("uses `{used}`, which isn't defined"));
+                }
+            }
+        }
+        issues
+    }
+
+    /// Fail with every problem [`check_variables`](Self::check_variables)
+    /// finds, before anything is generated
+    pub fn ensure_variables_defined(&self) -> Result<()> {
+        let issues = self.check_variables()?;
+        if issues.is_empty() {
+            return Ok(());
+        }
+        let list: Vec<String> = issues.iter().map(|issue| format!("- {issue}")).collect();
+        anyhow::bail!(
+            "The project's prompt variables need fixing before generating:\n{}",
+            list.join("\n")
+        )
+    }
+
+    /// Fill the project's variables and macros into `text` written by the
+    /// user, e.g. a project brief. A name that isn't defined is an error.
+    pub fn expand(&self, text: &str) -> Result<String> {
+        let variables = self.variables()?;
+        let mut env = Environment::new();
+        env.set_keep_trailing_newline(true);
+        env.set_undefined_behavior(UndefinedBehavior::Strict);
+        let import = variables.install(&mut env);
+        env.render_str(&format!("{import}{text}"), ())
+            .context("Failed to fill in the prompt variables")
+    }
+
+    /// Template environment with every prompt of `group`, e.g. `image`,
+    /// registered under the rest of its name (`sprite`), and the project's
+    /// variables and macros. Definitions that can't be read are skipped
+    /// with a warning, like a broken override.
+    pub fn environment(&self, group: &str) -> Environment<'static> {
+        let mut env = Environment::new();
+        let variables = self.variables().unwrap_or_else(|e| {
+            tracing::warn!("Ignoring the project's prompt variables: {e:#}");
+            PromptVariables::default()
+        });
+        let import = variables.install(&mut env);
+        let prefix = format!("{group}/");
+        for prompt in EMBEDDED_PROMPTS {
+            let Some(short_name) = prompt.name.strip_prefix(&prefix) else {
+                continue;
+            };
+            let added = self.source(prompt.name).and_then(|source| {
+                let origin = source.override_path.map_or_else(
+                    || "the template".to_string(),
+                    |path| path.display().to_string(),
+                );
+                env.add_template_owned(short_name, format!("{import}{}", source.text))
+                    .with_context(|| format!("Failed to parse {origin}"))
+            });
+            if let Err(e) = added {
+                tracing::warn!("Using the embedded {} prompt: {e:#}", prompt.name);
+                env.add_template_owned(short_name, format!("{import}{}", prompt.source))
+                    .ok();
+            }
+        }
+        env
+    }
+}
+
+/// The embedded prompt `name`, e.g. `image/sprite`
+pub fn embedded_prompt(name: &str) -> Option<&'static EmbeddedPrompt> {
+    EMBEDDED_PROMPTS.iter().find(|prompt| prompt.name == name)
+}
+
+/// Variables and macros a project defines for all of its prompts, e.g.
+///
+/// ```toml
+/// [variables]
+/// protagonist_name = "Aria"
+/// studio_style = "moody 16-bit pixel art"
+///
+/// [macros.hero]
+/// args = ["pose"]
+/// body = "{{ protagonist_name }} {{ pose }}, drawn in {{ studio_style }}"
+/// ```
+///
+/// used in a prompt as `{{ studio_style }}` or `{{ hero("waving") }}`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PromptVariables {
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+    #[serde(default)]
+    pub macros: BTreeMap<String, PromptMacro>,
+}
+
+/// A template snippet a prompt calls like a function
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PromptMacro {
+    /// Names the arguments are available under in the body
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    pub body: String,
+}
+
+impl PromptVariables {
+    /// Read the definitions at `path`, none when the file doesn't exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Write the definitions to `path`
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let content = toml::to_string_pretty(self).context("Failed to serialize variables")?;
+        std::fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Every variable and macro name
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.variables
+            .keys()
+            .chain(self.macros.keys())
+            .map(String::as_str)
+    }
+
+    /// Register the variables as globals of `env` and the macros as a
+    /// template, returning the tag a prompt starts with to import them.
+    /// Names that can't be used in a template and macros that don't parse
+    /// are left out.
+    fn install(&self, env: &mut Environment<'static>) -> String {
+        for (name, value) in &self.variables {
+            if is_identifier(name) {
+                env.add_global(name.clone(), value.clone());
+            }
+        }
+        let import = self.import_tag();
+        if import.is_empty() {
+            return import;
+        }
+        if let Err(e) = env.add_template_owned(MACROS_TEMPLATE, self.macro_source()) {
+            tracing::warn!("Skipping the project's prompt macros: {e:#}");
+            return String::new();
+        }
+        import
+    }
+
+    /// Tag importing every usable macro, empty without any. It doesn't
+    /// output anything or start a new line, so the lines of an error still
+    /// match the prompt's own.
+    fn import_tag(&self) -> String {
+        let names: Vec<&str> = self
+            .usable_macros()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if names.is_empty() {
+            return String::new();
+        }
+        format!(
+            "{{% from \"{MACROS_TEMPLATE}\" import {} %}}",
+            names.join(", ")
+        )
+    }
+
+    fn macro_source(&self) -> String {
+        self.usable_macros()
+            .map(|(name, definition)| {
+                format!(
+                    "{{% macro {name}({}) %}}{}{{% endmacro %}}\n",
+                    definition.args.join(", "),
+                    definition.body
+                )
+            })
+            .collect()
+    }
+
+    /// Macros that compile on their own, so one broken macro doesn't take
+    /// the others with it
+    fn usable_macros(&self) -> impl Iterator<Item = (&String, &PromptMacro)> {
+        self.macros.iter().filter(|(name, definition)| {
+            is_identifier(name)
+                && definition.args.iter().all(|arg| is_identifier(arg))
+                && Environment::new()
+                    .template_from_str(&definition.body)
+                    .is_ok()
+        })
+    }
+}
+
+/// A problem with a project's prompt variables
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableIssue {
+    /// Variable, macro or prompt the problem is in
+    pub location: String,
+    pub message: String,
+}
+
+impl fmt::Display for VariableIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// Top-level names `source` reads without defining them, none if it
+/// doesn't parse
+fn undeclared(source: &str) -> BTreeSet<String> {
+    let env = Environment::new();
+    env.template_from_str(source)
+        .map(|template| template.undeclared_variables(false).into_iter().collect())
+        .unwrap_or_default()
+}
+
+/// Whether `name` can be used as a template variable
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
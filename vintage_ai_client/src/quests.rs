@@ -0,0 +1,428 @@
+//! Typed quests with dependency graphs
+//!
+//! The game config describes its quest lines in free text. [`QuestGenerator`]
+//! turns them into [`QuestDesign`]s with typed objectives, prerequisite
+//! quests and rewards that name the NPCs, locations, enemies and items of
+//! the generated world. [`QuestGraph::validate`] checks the prerequisites
+//! for cycles and for quests that can never become available, and checks
+//! that the referenced content exists, before the quests are exported.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::game_assets::asset_id;
+use crate::game_types::{GameConfig, WorldData};
+use crate::text::{TextConfig, TextGenerator};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ObjectiveKind {
+    Talk { npc: String },
+    Visit { location: String },
+    Defeat { enemy: String, count: u32 },
+    Collect { item: String, count: u32 },
+    Deliver { item: String, npc: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Objective {
+    pub description: String,
+    #[serde(flatten)]
+    pub kind: ObjectiveKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Reward {
+    Gold {
+        amount: u32,
+    },
+    Experience {
+        amount: u32,
+    },
+    Item {
+        item: String,
+    },
+    /// Story flag set on completion, e.g. to open a new area
+    Flag {
+        name: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuestDesign {
+    /// Quest id, derived from the name
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub main: bool,
+    /// NPC who hands out the quest
+    #[serde(default)]
+    pub giver: Option<String>,
+    /// Names of the quests that must be completed first
+    #[serde(default)]
+    pub prerequisites: Vec<String>,
+    pub objectives: Vec<Objective>,
+    #[serde(default)]
+    pub rewards: Vec<Reward>,
+}
+
+impl QuestDesign {
+    /// NPCs the quest involves, including its giver
+    pub fn npcs(&self) -> Vec<&str> {
+        let mut npcs: Vec<&str> = self.giver.as_deref().into_iter().collect();
+        for objective in &self.objectives {
+            if let ObjectiveKind::Talk { npc } | ObjectiveKind::Deliver { npc, .. } =
+                &objective.kind
+                && !npcs.contains(&npc.as_str())
+            {
+                npcs.push(npc);
+            }
+        }
+        npcs
+    }
+
+    /// Locations the quest sends the player to
+    pub fn locations(&self) -> Vec<&str> {
+        self.objectives
+            .iter()
+            .filter_map(|objective| match &objective.kind {
+                ObjectiveKind::Visit { location } => Some(location.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// A problem of the quest graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuestIssue {
+    DuplicateQuest(String),
+    NoObjectives(String),
+    UnknownPrerequisite {
+        quest: String,
+        prerequisite: String,
+    },
+    /// Quests that require each other, in dependency order
+    Cycle(Vec<String>),
+    /// Quest that depends on a cycle or an unknown quest
+    Unreachable(String),
+    UnknownNpc {
+        quest: String,
+        npc: String,
+    },
+    UnknownLocation {
+        quest: String,
+        location: String,
+    },
+}
+
+impl std::fmt::Display for QuestIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DuplicateQuest(quest) => write!(f, "quest '{quest}' is defined twice"),
+            Self::NoObjectives(quest) => write!(f, "quest '{quest}' has no objectives"),
+            Self::UnknownPrerequisite {
+                quest,
+                prerequisite,
+            } => write!(f, "quest '{quest}' requires unknown quest '{prerequisite}'"),
+            Self::Cycle(quests) => {
+                write!(f, "quests require each other: {}", quests.join(" -> "))
+            }
+            Self::Unreachable(quest) => write!(f, "quest '{quest}' can never become available"),
+            Self::UnknownNpc { quest, npc } => {
+                write!(f, "quest '{quest}' involves unknown NPC '{npc}'")
+            }
+            Self::UnknownLocation { quest, location } => {
+                write!(f, "quest '{quest}' visits unknown location '{location}'")
+            }
+        }
+    }
+}
+
+/// All quests of a game and their prerequisites
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuestGraph {
+    pub quests: Vec<QuestDesign>,
+}
+
+impl QuestGraph {
+    pub fn quest(&self, name_or_id: &str) -> Option<&QuestDesign> {
+        let id = asset_id(name_or_id);
+        self.quests.iter().find(|quest| quest.id == id)
+    }
+
+    /// Quests in an order where every quest follows its prerequisites;
+    /// quests that can never become available are left out
+    pub fn order(&self) -> Vec<&QuestDesign> {
+        let mut done: HashSet<&str> = HashSet::new();
+        let mut order = Vec::new();
+        loop {
+            let available: Vec<&QuestDesign> = self
+                .quests
+                .iter()
+                .filter(|quest| !done.contains(quest.id.as_str()))
+                .filter(|quest| {
+                    quest
+                        .prerequisites
+                        .iter()
+                        .all(|p| done.contains(asset_id(p).as_str()))
+                })
+                .collect();
+            if available.is_empty() {
+                return order;
+            }
+            for quest in available {
+                done.insert(&quest.id);
+                order.push(quest);
+            }
+        }
+    }
+
+    /// Check the graph, and the NPCs and locations against the world
+    pub fn validate(&self, world: &WorldData) -> Vec<QuestIssue> {
+        let mut issues = Vec::new();
+
+        let mut ids = HashSet::new();
+        for quest in &self.quests {
+            if !ids.insert(quest.id.as_str()) {
+                issues.push(QuestIssue::DuplicateQuest(quest.name.clone()));
+            }
+            if quest.objectives.is_empty() {
+                issues.push(QuestIssue::NoObjectives(quest.name.clone()));
+            }
+            for prerequisite in &quest.prerequisites {
+                if !ids_contain(&self.quests, prerequisite) {
+                    issues.push(QuestIssue::UnknownPrerequisite {
+                        quest: quest.name.clone(),
+                        prerequisite: prerequisite.clone(),
+                    });
+                }
+            }
+        }
+
+        let cycles = self.cycles();
+        let in_cycle: HashSet<&str> = cycles.iter().flatten().map(String::as_str).collect();
+        let reachable: HashSet<&str> = self.order().iter().map(|q| q.name.as_str()).collect();
+        for quest in &self.quests {
+            if !reachable.contains(quest.name.as_str()) && !in_cycle.contains(quest.name.as_str()) {
+                issues.push(QuestIssue::Unreachable(quest.name.clone()));
+            }
+        }
+        issues.extend(cycles.iter().cloned().map(QuestIssue::Cycle));
+
+        let npcs: HashSet<String> = world
+            .towns
+            .iter()
+            .flat_map(|town| &town.npcs)
+            .map(|npc| asset_id(&npc.name))
+            .collect();
+        let locations: HashSet<String> = world
+            .regions
+            .iter()
+            .map(|r| &r.name)
+            .chain(world.towns.iter().map(|t| &t.name))
+            .chain(world.dungeons.iter().map(|d| &d.name))
+            .map(|name| asset_id(name))
+            .collect();
+        for quest in &self.quests {
+            for npc in quest.npcs() {
+                if !npcs.contains(&asset_id(npc)) {
+                    issues.push(QuestIssue::UnknownNpc {
+                        quest: quest.name.clone(),
+                        npc: npc.to_string(),
+                    });
+                }
+            }
+            for location in quest.locations() {
+                if !locations.contains(&asset_id(location)) {
+                    issues.push(QuestIssue::UnknownLocation {
+                        quest: quest.name.clone(),
+                        location: location.to_string(),
+                    });
+                }
+            }
+        }
+
+        issues
+    }
+
+    /// Prerequisite cycles, each listed once by quest name
+    fn cycles(&self) -> Vec<Vec<String>> {
+        let index: HashMap<&str, usize> = self
+            .quests
+            .iter()
+            .enumerate()
+            .map(|(i, quest)| (quest.id.as_str(), i))
+            .collect();
+        let edges: Vec<Vec<usize>> = self
+            .quests
+            .iter()
+            .map(|quest| {
+                quest
+                    .prerequisites
+                    .iter()
+                    .filter_map(|p| index.get(asset_id(p).as_str()).copied())
+                    .collect()
+            })
+            .collect();
+
+        // 0 = unvisited, 1 = on the current path, 2 = finished
+        let mut state = vec![0u8; self.quests.len()];
+        let mut cycles = Vec::new();
+        for start in 0..self.quests.len() {
+            if state[start] != 0 {
+                continue;
+            }
+            let mut path = vec![start];
+            let mut next_edge = vec![0usize];
+            state[start] = 1;
+            while let Some(&node) = path.last() {
+                let edge = next_edge.last_mut().expect("edges follow the path");
+                if let Some(&target) = edges[node].get(*edge) {
+                    *edge += 1;
+                    match state[target] {
+                        0 => {
+                            state[target] = 1;
+                            path.push(target);
+                            next_edge.push(0);
+                        }
+                        1 => {
+                            let from = path.iter().position(|&n| n == target).unwrap_or(0);
+                            // Prerequisites point backwards; list in play order
+                            cycles.push(
+                                path[from..]
+                                    .iter()
+                                    .rev()
+                                    .map(|&n| self.quests[n].name.clone())
+                                    .collect(),
+                            );
+                        }
+                        _ => {}
+                    }
+                } else {
+                    state[node] = 2;
+                    path.pop();
+                    next_edge.pop();
+                }
+            }
+        }
+        cycles
+    }
+}
+
+fn ids_contain(quests: &[QuestDesign], name: &str) -> bool {
+    let id = asset_id(name);
+    quests.iter().any(|quest| quest.id == id)
+}
+
+/// Generates typed quests from the game's quest lines and world
+#[derive(Clone)]
+pub struct QuestGenerator {
+    text: TextGenerator,
+}
+
+impl QuestGenerator {
+    pub fn new(text: TextGenerator) -> Self {
+        Self { text }
+    }
+
+    /// Generate the quest graph of a game
+    ///
+    /// A graph with issues is sent back once with the issues listed; if the
+    /// second graph still has issues they are returned as an error.
+    pub async fn generate(&self, config: &GameConfig, world: &WorldData) -> Result<QuestGraph> {
+        let quest_lines = std::iter::once((&config.main_quest, "main"))
+            .chain(config.side_quests.iter().map(|quest| (quest, "side")))
+            .map(|(quest, kind)| {
+                format!(
+                    "- {} ({kind}): {} Steps: {}",
+                    quest.name,
+                    quest.description,
+                    quest
+                        .steps
+                        .iter()
+                        .map(|step| format!("{} at {}", step.description, step.location))
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let npcs: Vec<&str> = world
+            .towns
+            .iter()
+            .flat_map(|town| &town.npcs)
+            .map(|npc| npc.name.as_str())
+            .collect();
+        let locations: Vec<&str> = world
+            .regions
+            .iter()
+            .map(|r| r.name.as_str())
+            .chain(world.towns.iter().map(|t| t.name.as_str()))
+            .chain(world.dungeons.iter().map(|d| d.name.as_str()))
+            .collect();
+
+        let prompt = format!(
+            "Turn the quest lines of the 16-bit RPG {game} into typed quests.\n\n\
+            Quest lines:\n{quest_lines}\n\n\
+            NPCs: {npcs}\nLocations: {locations}\n\n\
+            Use only these NPCs and locations. Every quest names the quests that must be \
+            completed before it as prerequisites; the main quest's chapters may be split into \
+            several quests that follow each other. Side quests should open up along the main \
+            quest, and no quest may require itself through other quests.\n\n\
+            Return a JSON array of quests of the form {{\"name\": \"string\", \"description\": \
+            \"string\", \"main\": true, \"giver\": \"npc or null\", \"prerequisites\": [\"quest \
+            name\"], \"objectives\": [{{\"description\": \"string\", \"type\": \
+            \"talk|visit|defeat|collect|deliver\", \"npc\": \"for talk and deliver\", \
+            \"location\": \"for visit\", \"enemy\": \"for defeat\", \"item\": \"for collect and \
+            deliver\", \"count\": 1}}], \"rewards\": [{{\"type\": \"gold|experience\", \"amount\": \
+            100}} or {{\"type\": \"item\", \"item\": \"string\"}} or {{\"type\": \"flag\", \"name\": \
+            \"string\"}}]}}.",
+            game = config.name,
+            npcs = npcs.join(", "),
+            locations = locations.join(", "),
+        );
+        let prompt = config.localize_prompt(prompt);
+        let config = config.seed_text(TextConfig {
+            max_tokens: 4000,
+            ..TextConfig::for_world_building()
+        });
+
+        let graph = self.request(&prompt, config.clone()).await?;
+        let issues = graph.validate(world);
+        if issues.is_empty() {
+            return Ok(graph);
+        }
+
+        let retry_prompt = format!(
+            "{prompt}\n\nA previous answer had these problems, avoid them: {}.",
+            join_issues(&issues)
+        );
+        let graph = self.request(&retry_prompt, config).await?;
+        let issues = graph.validate(world);
+        if !issues.is_empty() {
+            anyhow::bail!("Quest graph is invalid: {}", join_issues(&issues));
+        }
+        Ok(graph)
+    }
+
+    async fn request(&self, prompt: &str, config: TextConfig) -> Result<QuestGraph> {
+        let mut quests: Vec<QuestDesign> = self.text.generate_structured(prompt, config).await?;
+        for quest in &mut quests {
+            quest.id = asset_id(&quest.name);
+        }
+        Ok(QuestGraph { quests })
+    }
+}
+
+fn join_issues(issues: &[QuestIssue]) -> String {
+    issues
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -0,0 +1,606 @@
+//! Rate limits reported by the provider
+//!
+//! OpenAI reports what is left of the account's per-minute request and
+//! token limits in the `x-ratelimit-*` headers of its responses. [`send`]
+//! posts a request and records those headers per model in the process-wide
+//! [`QuotaTracker`]. Before a request is dispatched it waits in
+//! [`QuotaTracker::pace`] until the model has the requests and tokens left
+//! to take it, so concurrent generators slow down together instead of
+//! running into a storm of 429 responses. Every service of the process
+//! shares the tracker, as they share the account's limits.
+
+use async_openai::Client;
+use async_openai::config::Config;
+use async_openai::error::{ApiError, OpenAIError, WrappedError};
+use reqwest::StatusCode;
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::credentials::KeyedConfig;
+use crate::tokens::TokenCounter;
+
+/// Attempts of a request that is rate limited or hits a server error
+pub const MAX_ATTEMPTS: u32 = 5;
+
+/// Characters per token when estimating the tokens of a request that isn't
+/// a chat
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Time allowed to connect to the provider
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Longest silence of a response before the request fails. A completion
+/// arrives in one piece once it is written, so this allows for long ones.
+const READ_TIMEOUT: Duration = Duration::from_secs(600);
+
+static HTTP: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .read_timeout(READ_TIMEOUT)
+        .build()
+        .expect("Failed to build the HTTP client")
+});
+
+static TOKEN_COUNTER: LazyLock<TokenCounter> = LazyLock::new(TokenCounter::new);
+
+static SHARED: LazyLock<QuotaTracker> = LazyLock::new(QuotaTracker::default);
+
+/// Limits of one model as of a response
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimits {
+    /// Requests per minute
+    pub limit_requests: Option<u64>,
+    pub remaining_requests: Option<u64>,
+    /// Until the requests are replenished
+    pub reset_requests: Option<Duration>,
+    /// Tokens per minute
+    pub limit_tokens: Option<u64>,
+    pub remaining_tokens: Option<u64>,
+    /// Until the tokens are replenished
+    pub reset_tokens: Option<Duration>,
+    /// When the response arrived
+    pub observed_at: Instant,
+}
+
+impl RateLimits {
+    /// Limits of the `x-ratelimit-*` headers, if the response has any
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header = |name: &str| {
+            headers
+                .get(format!("x-ratelimit-{name}"))
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+        };
+        let number = |name: &str| header(name).and_then(|value| value.parse().ok());
+        let limits = Self {
+            limit_requests: number("limit-requests"),
+            remaining_requests: number("remaining-requests"),
+            reset_requests: header("reset-requests").and_then(parse_duration),
+            limit_tokens: number("limit-tokens"),
+            remaining_tokens: number("remaining-tokens"),
+            reset_tokens: header("reset-tokens").and_then(parse_duration),
+            observed_at: Instant::now(),
+        };
+        (limits.remaining_requests.is_some() || limits.remaining_tokens.is_some()).then_some(limits)
+    }
+
+    /// Requests left, counting the ones replenished by `now`
+    pub fn requests_left(&self, now: Instant) -> Option<u64> {
+        if self.is_reset(self.reset_requests, now) {
+            self.limit_requests.or(self.remaining_requests)
+        } else {
+            self.remaining_requests
+        }
+    }
+
+    /// Tokens left, counting the ones replenished by `now`
+    pub fn tokens_left(&self, now: Instant) -> Option<u64> {
+        if self.is_reset(self.reset_tokens, now) {
+            self.limit_tokens.or(self.remaining_tokens)
+        } else {
+            self.remaining_tokens
+        }
+    }
+
+    /// Time from `now` until a request of `tokens` fits the limits
+    pub fn wait_for(&self, tokens: u64, now: Instant) -> Duration {
+        // A request larger than the whole limit only waits for a full one
+        let tokens = self.limit_tokens.map_or(tokens, |limit| tokens.min(limit));
+        let mut wait = Duration::ZERO;
+        if self.requests_left(now) == Some(0) {
+            wait = wait.max(self.until_reset(self.reset_requests, now));
+        }
+        if self.tokens_left(now).is_some_and(|left| left < tokens) {
+            wait = wait.max(self.until_reset(self.reset_tokens, now));
+        }
+        wait
+    }
+
+    fn is_reset(&self, reset: Option<Duration>, now: Instant) -> bool {
+        reset.is_some_and(|reset| now >= self.observed_at + reset)
+    }
+
+    fn until_reset(&self, reset: Option<Duration>, now: Instant) -> Duration {
+        reset.map_or(Duration::ZERO, |reset| {
+            (self.observed_at + reset).saturating_duration_since(now)
+        })
+    }
+
+    /// Take a request of `tokens` off what is left, replenishing first if a
+    /// reset has passed
+    fn consume(&mut self, tokens: u64, now: Instant) {
+        let requests = self.requests_left(now);
+        let left_tokens = self.tokens_left(now);
+        if self.is_reset(self.reset_requests, now) || self.is_reset(self.reset_tokens, now) {
+            // Replenished limits are good for a minute at most
+            self.reset_requests = self.reset_requests.map(|_| Duration::from_secs(60));
+            self.reset_tokens = self.reset_tokens.map(|_| Duration::from_secs(60));
+            self.observed_at = now;
+        }
+        self.remaining_requests = requests.map(|left| left.saturating_sub(1));
+        self.remaining_tokens = left_tokens.map(|left| left.saturating_sub(tokens));
+    }
+}
+
+/// Parse a reset time such as "1s", "6m0s", "20ms" or "1h2m3.5s"
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let mut total = 0.0;
+    let mut rest = text.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let value: f64 = rest[..number_end].parse().ok()?;
+        rest = &rest[number_end..];
+        let unit_end = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let seconds = match &rest[..unit_end] {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" | "" => 1.0,
+            "ms" => 0.001,
+            _ => return None,
+        };
+        total += value * seconds;
+        rest = &rest[unit_end..];
+    }
+    Some(Duration::from_secs_f64(total))
+}
+
+/// Latest rate limits of each model, shared by every request of the process
+#[derive(Debug, Clone, Default)]
+pub struct QuotaTracker {
+    limits: Arc<Mutex<HashMap<String, RateLimits>>>,
+}
+
+impl QuotaTracker {
+    /// The tracker of the process
+    pub fn shared() -> &'static Self {
+        &SHARED
+    }
+
+    pub fn record(&self, model: &str, limits: RateLimits) {
+        if let Ok(mut all) = self.limits.lock() {
+            all.insert(model.to_string(), limits);
+        }
+    }
+
+    pub fn limits(&self, model: &str) -> Option<RateLimits> {
+        self.limits.lock().ok()?.get(model).cloned()
+    }
+
+    /// Limits of every model a response reported them for, by model name
+    pub fn snapshot(&self) -> Vec<(String, RateLimits)> {
+        let mut all: Vec<_> = self
+            .limits
+            .lock()
+            .map(|all| all.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+            .unwrap_or_default();
+        all.sort_by(|a, b| a.0.cmp(&b.0));
+        all
+    }
+
+    /// Longest time any model has to wait before it takes a request again
+    pub fn wait(&self) -> Duration {
+        let now = Instant::now();
+        self.snapshot()
+            .iter()
+            .map(|(_, limits)| limits.wait_for(0, now))
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Claim a request of `tokens` for `model` if it fits the limits now,
+    /// otherwise return how long to wait
+    pub fn reserve(&self, model: &str, tokens: u64) -> Duration {
+        let Ok(mut all) = self.limits.lock() else {
+            return Duration::ZERO;
+        };
+        let Some(limits) = all.get_mut(model) else {
+            return Duration::ZERO;
+        };
+        let now = Instant::now();
+        let wait = limits.wait_for(tokens, now);
+        if wait.is_zero() {
+            limits.consume(tokens, now);
+        }
+        wait
+    }
+
+    /// Wait until a request of `tokens` for `model` fits the limits, and
+    /// claim it
+    pub async fn pace(&self, model: &str, tokens: u64) {
+        loop {
+            let wait = self.reserve(model, tokens);
+            if wait.is_zero() {
+                return;
+            }
+            tracing::debug!("Waiting {wait:?} for the rate limits of {model}");
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Hold every request for `model` back for `wait`, after the provider
+    /// said it is rate limited
+    pub fn throttle(&self, model: &str, wait: Duration) {
+        let Ok(mut all) = self.limits.lock() else {
+            return;
+        };
+        let limits = all.entry(model.to_string()).or_insert_with(|| RateLimits {
+            limit_requests: None,
+            remaining_requests: None,
+            reset_requests: None,
+            limit_tokens: None,
+            remaining_tokens: None,
+            reset_tokens: None,
+            observed_at: Instant::now(),
+        });
+        let now = Instant::now();
+        let until = limits.until_reset(limits.reset_requests, now).max(wait);
+        let tokens_until = limits.until_reset(limits.reset_tokens, now);
+        limits.remaining_requests = Some(0);
+        limits.reset_requests = Some(until);
+        limits.reset_tokens = limits.reset_tokens.map(|_| tokens_until);
+        limits.observed_at = now;
+    }
+}
+
+/// Model and token count of a request body: the counted prompt of a chat,
+/// or a rough count of anything else, plus the completion limit
+fn describe(body: &serde_json::Value) -> (String, u64) {
+    let model = body
+        .get("model")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let completion = ["max_completion_tokens", "max_tokens"]
+        .iter()
+        .find_map(|field| body.get(*field).and_then(serde_json::Value::as_u64))
+        .unwrap_or(0);
+    let prompt = match body.get("messages").and_then(serde_json::Value::as_array) {
+        Some(messages) => {
+            let messages: Vec<(&str, String)> = messages.iter().map(chat_message).collect();
+            TOKEN_COUNTER
+                .count_chat_tokens(&messages, &model)
+                .unwrap_or_default() as u64
+        }
+        None => (body.to_string().len() / CHARS_PER_TOKEN) as u64,
+    };
+    (model, prompt + completion)
+}
+
+/// Role and text of a chat message, joining the text parts of a message
+/// made of parts
+fn chat_message(message: &serde_json::Value) -> (&str, String) {
+    let role = message
+        .get("role")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default();
+    let content = match message.get("content") {
+        Some(serde_json::Value::String(text)) => text.clone(),
+        Some(serde_json::Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(serde_json::Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    };
+    (role, content)
+}
+
+/// Wait until `request` fits the rate limits of its model, for requests
+/// sent through the client rather than [`send`]
+pub async fn pace<I: Serialize>(request: &I) {
+    if let Ok(body) = serde_json::to_value(request) {
+        let (model, tokens) = describe(&body);
+        QuotaTracker::shared().pace(&model, tokens).await;
+    }
+}
+
+/// Wait before retrying, from the `retry-after` headers of a response
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    header("retry-after-ms")
+        .and_then(|ms| ms.parse().ok())
+        .map(Duration::from_millis)
+        .or_else(|| {
+            header("retry-after")
+                .and_then(|secs| secs.parse().ok())
+                .map(Duration::from_secs_f64)
+        })
+}
+
+/// Post a JSON request to `path` of the client's API, paced by the rate
+/// limits of its model, and record the limits of the response. Requests
+/// that are rate limited or hit a server error are retried.
+pub async fn send<I, O>(
+    client: &Client<KeyedConfig>,
+    path: &str,
+    request: &I,
+) -> Result<O, OpenAIError>
+where
+    I: Serialize,
+    O: DeserializeOwned,
+{
+    let mut body =
+        serde_json::to_value(request).map_err(|e| OpenAIError::InvalidArgument(e.to_string()))?;
+    let (model, tokens) = describe(&body);
+    let model = client.config().model_name(&model);
+    if let Some(field) = body.get_mut("model") {
+        *field = serde_json::Value::String(model.clone());
+    }
+    let config = client.config().for_model(&model);
+    let tracker = QuotaTracker::shared();
+
+    let mut attempt = 1;
+    loop {
+        tracker.pace(&model, tokens).await;
+        let response = HTTP
+            .post(config.url(path))
+            .query(&config.query())
+            .headers(config.headers())
+            .json(&body)
+            .send()
+            .await?;
+        let status = response.status();
+        if let Some(limits) = RateLimits::from_headers(response.headers()) {
+            tracker.record(&model, limits);
+        }
+        let retry_after = retry_after(response.headers());
+        let bytes = response.bytes().await?;
+        let text = || String::from_utf8_lossy(&bytes).into_owned();
+
+        if status.is_success() {
+            return serde_json::from_slice(&bytes)
+                .map_err(|e| OpenAIError::JSONDeserialize(e, text()));
+        }
+        let error = if status.is_server_error() {
+            // Server errors aren't always JSON
+            ApiError {
+                message: text(),
+                r#type: None,
+                param: None,
+                code: None,
+            }
+        } else {
+            serde_json::from_slice::<WrappedError>(&bytes)
+                .map_err(|e| OpenAIError::JSONDeserialize(e, text()))?
+                .error
+        };
+
+        let rate_limited = status == StatusCode::TOO_MANY_REQUESTS
+            && error.r#type.as_deref() != Some("insufficient_quota");
+        if attempt >= MAX_ATTEMPTS || !(rate_limited || status.is_server_error()) {
+            return Err(OpenAIError::ApiError(error));
+        }
+        let wait = retry_after.unwrap_or(Duration::from_millis(500 << attempt));
+        if rate_limited {
+            tracing::warn!(
+                "Rate limited on {model}, retrying in {wait:?}: {}",
+                error.message
+            );
+            tracker.throttle(&model, wait);
+        } else {
+            tracing::warn!("Server error {status}, retrying in {wait:?}");
+            tokio::time::sleep(wait).await;
+        }
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use serde_json::json;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    /// Limits of 10 requests and 1000 tokens a minute, as observed at `now`
+    fn limits(now: Instant, requests: u64, tokens: u64) -> RateLimits {
+        RateLimits {
+            limit_requests: Some(10),
+            remaining_requests: Some(requests),
+            reset_requests: Some(Duration::from_secs(6)),
+            limit_tokens: Some(1000),
+            remaining_tokens: Some(tokens),
+            reset_tokens: Some(Duration::from_secs(30)),
+            observed_at: now,
+        }
+    }
+
+    #[test]
+    fn parse_duration_of_every_unit() {
+        assert_eq!(parse_duration("1s"), Some(Duration::from_secs(1)));
+        assert_eq!(parse_duration("6m0s"), Some(Duration::from_secs(360)));
+        assert_eq!(parse_duration("20ms"), Some(Duration::from_millis(20)));
+        assert_eq!(parse_duration(" 7 "), Some(Duration::from_secs(7)));
+        assert_eq!(
+            parse_duration("1h2m3.5s"),
+            Some(Duration::from_secs_f64(3723.5))
+        );
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("5d"), None);
+        assert_eq!(parse_duration("s"), None);
+    }
+
+    #[test]
+    fn rate_limits_from_headers() {
+        let limits = RateLimits::from_headers(&headers(&[
+            ("x-ratelimit-limit-requests", "500"),
+            ("x-ratelimit-remaining-requests", "499"),
+            ("x-ratelimit-reset-requests", "120ms"),
+            ("x-ratelimit-limit-tokens", "30000"),
+            ("x-ratelimit-remaining-tokens", "29000"),
+            ("x-ratelimit-reset-tokens", "2s"),
+        ]))
+        .unwrap();
+        assert_eq!(limits.limit_requests, Some(500));
+        assert_eq!(limits.remaining_requests, Some(499));
+        assert_eq!(limits.reset_requests, Some(Duration::from_millis(120)));
+        assert_eq!(limits.limit_tokens, Some(30000));
+        assert_eq!(limits.remaining_tokens, Some(29000));
+        assert_eq!(limits.reset_tokens, Some(Duration::from_secs(2)));
+
+        assert!(RateLimits::from_headers(&headers(&[])).is_none());
+        assert!(
+            RateLimits::from_headers(&headers(&[("x-ratelimit-limit-tokens", "30000")])).is_none(),
+            "a limit without what is left of it says nothing"
+        );
+    }
+
+    #[test]
+    fn wait_for_the_reset_of_what_ran_out() {
+        let now = Instant::now();
+        let limits = limits(now, 3, 100);
+        assert_eq!(limits.wait_for(50, now), Duration::ZERO);
+        assert_eq!(limits.wait_for(500, now), Duration::from_secs(30));
+        assert_eq!(
+            limits.wait_for(500, now + Duration::from_secs(10)),
+            Duration::from_secs(20)
+        );
+        // Tokens are replenished after their reset
+        assert_eq!(
+            limits.wait_for(500, now + Duration::from_secs(30)),
+            Duration::ZERO
+        );
+        // A request larger than the limit waits for a full one only
+        assert_eq!(
+            limits.wait_for(5000, now + Duration::from_secs(30)),
+            Duration::ZERO
+        );
+
+        let limits = self::limits(now, 0, 1000);
+        assert_eq!(limits.wait_for(0, now), Duration::from_secs(6));
+        assert_eq!(
+            limits.wait_for(0, now + Duration::from_secs(6)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn consume_takes_off_what_is_left() {
+        let now = Instant::now();
+        let mut limits = limits(now, 3, 100);
+        limits.consume(40, now);
+        assert_eq!(limits.remaining_requests, Some(2));
+        assert_eq!(limits.remaining_tokens, Some(60));
+        limits.consume(100, now);
+        assert_eq!(limits.remaining_tokens, Some(0));
+
+        // After the resets it starts over from the full limits
+        let later = now + Duration::from_secs(31);
+        limits.consume(100, later);
+        assert_eq!(limits.remaining_requests, Some(9));
+        assert_eq!(limits.remaining_tokens, Some(900));
+        assert_eq!(limits.observed_at, later);
+        assert_eq!(limits.wait_for(1000, later), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn retry_after_prefers_milliseconds() {
+        assert_eq!(
+            retry_after(&headers(&[("retry-after-ms", "250"), ("retry-after", "3")])),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(
+            retry_after(&headers(&[("retry-after", "1.5")])),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(retry_after(&headers(&[("retry-after", "soon")])), None);
+        assert_eq!(retry_after(&headers(&[])), None);
+    }
+
+    #[test]
+    fn reserve_claims_until_the_limits_run_out() {
+        let tracker = QuotaTracker::default();
+        assert_eq!(tracker.reserve("gpt-4o", 10_000), Duration::ZERO);
+
+        tracker.record("gpt-4o", limits(Instant::now(), 2, 1000));
+        assert_eq!(tracker.reserve("gpt-4o", 600), Duration::ZERO);
+        // The first reservation left too few tokens for a second
+        assert!(!tracker.reserve("gpt-4o", 600).is_zero());
+        assert_eq!(tracker.reserve("gpt-4o", 300), Duration::ZERO);
+        // Both requests are used up
+        assert!(!tracker.reserve("gpt-4o", 0).is_zero());
+    }
+
+    #[test]
+    fn throttle_holds_a_model_back() {
+        let tracker = QuotaTracker::default();
+        tracker.throttle("gpt-4o", Duration::from_secs(5));
+        let wait = tracker.reserve("gpt-4o", 1);
+        assert!(wait > Duration::from_secs(4) && wait <= Duration::from_secs(5));
+        assert_eq!(tracker.reserve("gpt-4o-mini", 1), Duration::ZERO);
+
+        // A longer wait of the limits is kept
+        tracker.record("gpt-4o-mini", limits(Instant::now(), 0, 1000));
+        tracker.throttle("gpt-4o-mini", Duration::from_secs(1));
+        assert!(tracker.reserve("gpt-4o-mini", 1) > Duration::from_secs(5));
+        assert!(tracker.wait() > Duration::from_secs(5));
+    }
+
+    #[test]
+    fn describe_counts_chat_tokens() {
+        let body = json!({
+            "model": "gpt-4o",
+            "max_tokens": 100,
+            "messages": [
+                { "role": "system", "content": "You design games." },
+                { "role": "user", "content": [
+                    { "type": "text", "text": "Name a platformer." },
+                    { "type": "image_url", "image_url": { "url": "data:" } },
+                ] },
+            ],
+        });
+        let prompt = TOKEN_COUNTER
+            .count_chat_tokens(
+                &[
+                    ("system", "You design games."),
+                    ("user", "Name a platformer."),
+                ],
+                "gpt-4o",
+            )
+            .unwrap() as u64;
+        assert_eq!(describe(&body), ("gpt-4o".to_string(), prompt + 100));
+
+        let body = json!({ "model": "text-embedding-3-small", "input": "x".repeat(400) });
+        let (model, tokens) = describe(&body);
+        assert_eq!(model, "text-embedding-3-small");
+        assert!((100..=120).contains(&tokens));
+    }
+}
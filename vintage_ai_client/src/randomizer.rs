@@ -0,0 +1,596 @@
+//! Randomizer spec derived from the quest and item graph
+//!
+//! Every place the player receives an item is a check: dungeon treasures and
+//! item rewards of quests. Each check lists the logic it requires (items,
+//! completed quests, defeated bosses), and quests and bosses list theirs, so
+//! a shuffle can be tested for beatability by sweeping through what becomes
+//! reachable. Dungeons open in world order, each behind the boss of the one
+//! before it, and quests require their prerequisites, the items they ask to
+//! collect and the bosses they ask to defeat. Items some requirement names
+//! are progression; everything else is filler. [`RandomizerSpec::shuffle`]
+//! places the pool with an assumed fill, so every seed it returns can be
+//! finished.
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::game_assets::{EnemyDatabase, QuestDatabase, asset_id};
+use crate::game_types::WorldData;
+use crate::maps::hash;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Requirement {
+    Item { item: String },
+    Quest { quest: String },
+    Boss { boss: String },
+}
+
+/// A place that hands out an item
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Check {
+    pub id: String,
+    pub name: String,
+    pub kind: CheckKind,
+    /// Item id found here in the unrandomized game
+    pub vanilla_item: String,
+    pub requires: Vec<Requirement>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CheckKind {
+    Treasure,
+    QuestReward,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ItemClass {
+    /// Named by a requirement, placement decides what can be reached
+    Progression,
+    Filler,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolItem {
+    pub item: String,
+    pub class: ItemClass,
+    pub count: u32,
+}
+
+/// What completing a quest or defeating a boss requires
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Gate {
+    pub id: String,
+    pub requires: Vec<Requirement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RandomizerSettings {
+    /// Fixed seed, e.g. for races; `None` lets the player roll one
+    pub seed: Option<u64>,
+    pub shuffle_treasures: bool,
+    pub shuffle_quest_rewards: bool,
+}
+
+impl Default for RandomizerSettings {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            shuffle_treasures: true,
+            shuffle_quest_rewards: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RandomizerSpec {
+    pub settings: RandomizerSettings,
+    pub pool: Vec<PoolItem>,
+    pub checks: Vec<Check>,
+    pub quests: Vec<Gate>,
+    pub bosses: Vec<Gate>,
+    /// Requirements for beating the game
+    pub goal: Vec<Requirement>,
+}
+
+/// Item placed at each check, keyed by check id
+pub type Placement = BTreeMap<String, String>;
+
+/// Progress of a sweep through a placement
+#[derive(Debug, Default)]
+struct Progress {
+    items: HashSet<String>,
+    quests: HashSet<String>,
+    bosses: HashSet<String>,
+    checks: HashSet<String>,
+}
+
+impl Progress {
+    fn meets(&self, requires: &[Requirement]) -> bool {
+        requires.iter().all(|requirement| match requirement {
+            Requirement::Item { item } => self.items.contains(item),
+            Requirement::Quest { quest } => self.quests.contains(quest),
+            Requirement::Boss { boss } => self.bosses.contains(boss),
+        })
+    }
+}
+
+impl RandomizerSpec {
+    pub fn derive(world: &WorldData, quests: &QuestDatabase, enemies: &EnemyDatabase) -> Self {
+        let is_boss = |name: &str| {
+            enemies
+                .enemies
+                .iter()
+                .any(|enemy| enemy.boss && enemy.id == asset_id(name))
+        };
+
+        // Each dungeon opens once the previous dungeon's boss is down
+        let mut entry: BTreeMap<String, Vec<Requirement>> = BTreeMap::new();
+        let mut bosses = Vec::new();
+        let mut previous_boss: Option<String> = None;
+        for dungeon in &world.dungeons {
+            let requires: Vec<_> = previous_boss
+                .iter()
+                .map(|boss| Requirement::Boss { boss: boss.clone() })
+                .collect();
+            let boss = asset_id(&dungeon.boss.name);
+            entry.insert(dungeon.name.to_lowercase(), requires.clone());
+            bosses.push(Gate {
+                id: boss.clone(),
+                requires,
+            });
+            previous_boss = Some(boss);
+        }
+
+        let mut checks = Vec::new();
+        for dungeon in &world.dungeons {
+            let requires = &entry[&dungeon.name.to_lowercase()];
+            for (floor, data) in dungeon.floors.iter().enumerate() {
+                for (index, treasure) in data.treasures.iter().enumerate() {
+                    checks.push(Check {
+                        id: format!("{}_floor{}_{index}", asset_id(&dungeon.name), floor + 1),
+                        name: format!("{} {}F - {treasure}", dungeon.name, floor + 1),
+                        kind: CheckKind::Treasure,
+                        vanilla_item: asset_id(treasure),
+                        requires: requires.clone(),
+                    });
+                }
+            }
+        }
+
+        for quest in &quests.quests {
+            let rewards = quest.rewards.iter().filter(|reward| is_item_reward(reward));
+            for (index, reward) in rewards.enumerate() {
+                checks.push(Check {
+                    id: format!("{}_reward_{index}", quest.id),
+                    name: format!("{} - {reward}", quest.name),
+                    kind: CheckKind::QuestReward,
+                    vanilla_item: asset_id(reward),
+                    requires: vec![Requirement::Quest {
+                        quest: quest.id.clone(),
+                    }],
+                });
+            }
+        }
+
+        // Only items some check hands out can gate a quest
+        let pooled: HashSet<String> = checks.iter().map(|c| c.vanilla_item.clone()).collect();
+        let mut quest_gates = Vec::new();
+        for quest in &quests.quests {
+            let mut requires: Vec<Requirement> = quest
+                .prerequisites
+                .iter()
+                .map(|id| Requirement::Quest { quest: id.clone() })
+                .collect();
+            for step in &quest.steps {
+                let step_requires = match step.objective_type.as_str() {
+                    "collect" if pooled.contains(&asset_id(&step.location)) => {
+                        vec![Requirement::Item {
+                            item: asset_id(&step.location),
+                        }]
+                    }
+                    "defeat" if is_boss(&step.location) => vec![Requirement::Boss {
+                        boss: asset_id(&step.location),
+                    }],
+                    _ => entry
+                        .get(&step.location.to_lowercase())
+                        .cloned()
+                        .unwrap_or_default(),
+                };
+                for requirement in step_requires {
+                    if !requires.contains(&requirement) {
+                        requires.push(requirement);
+                    }
+                }
+            }
+            quest_gates.push(Gate {
+                id: quest.id.clone(),
+                requires,
+            });
+        }
+
+        let needed: HashSet<&str> = checks
+            .iter()
+            .flat_map(|check| &check.requires)
+            .chain(quest_gates.iter().flat_map(|gate| &gate.requires))
+            .chain(bosses.iter().flat_map(|gate| &gate.requires))
+            .filter_map(|requirement| match requirement {
+                Requirement::Item { item } => Some(item.as_str()),
+                _ => None,
+            })
+            .collect();
+        let mut pool: Vec<PoolItem> = Vec::new();
+        for check in &checks {
+            match pool
+                .iter_mut()
+                .find(|entry| entry.item == check.vanilla_item)
+            {
+                Some(entry) => entry.count += 1,
+                None => pool.push(PoolItem {
+                    item: check.vanilla_item.clone(),
+                    class: if needed.contains(check.vanilla_item.as_str()) {
+                        ItemClass::Progression
+                    } else {
+                        ItemClass::Filler
+                    },
+                    count: 1,
+                }),
+            }
+        }
+
+        let goal = match quests.quests.iter().rev().find(|quest| quest.main) {
+            Some(quest) => vec![Requirement::Quest {
+                quest: quest.id.clone(),
+            }],
+            None => bosses
+                .iter()
+                .map(|gate| Requirement::Boss {
+                    boss: gate.id.clone(),
+                })
+                .collect(),
+        };
+
+        Self {
+            settings: RandomizerSettings::default(),
+            pool,
+            checks,
+            quests: quest_gates,
+            bosses,
+            goal,
+        }
+    }
+
+    /// Items at their original checks
+    pub fn vanilla(&self) -> Placement {
+        self.checks
+            .iter()
+            .map(|check| (check.id.clone(), check.vanilla_item.clone()))
+            .collect()
+    }
+
+    /// Checks collected in each sphere of a playthrough, or `None` if the
+    /// goal can't be reached
+    pub fn playthrough(&self, placement: &Placement) -> Option<Vec<Vec<String>>> {
+        let mut progress = Progress::default();
+        let mut spheres = Vec::new();
+        loop {
+            self.open_gates(&mut progress);
+            let sphere: Vec<String> = self
+                .checks
+                .iter()
+                .filter(|check| !progress.checks.contains(&check.id))
+                .filter(|check| progress.meets(&check.requires))
+                .map(|check| check.id.clone())
+                .collect();
+            if sphere.is_empty() {
+                break;
+            }
+            for id in &sphere {
+                progress.checks.insert(id.clone());
+                if let Some(item) = placement.get(id) {
+                    progress.items.insert(item.clone());
+                }
+            }
+            spheres.push(sphere);
+        }
+        progress.meets(&self.goal).then_some(spheres)
+    }
+
+    /// Place the pool at random with an assumed fill
+    ///
+    /// Progression items go first, each to a check that is reachable with
+    /// the progression items still to be placed; filler fills the rest.
+    /// Checks the settings don't shuffle keep their vanilla item.
+    pub fn shuffle(&self, seed: u64) -> Result<Placement> {
+        let shuffled = |check: &Check| match check.kind {
+            CheckKind::Treasure => self.settings.shuffle_treasures,
+            CheckKind::QuestReward => self.settings.shuffle_quest_rewards,
+        };
+        let mut placement: Placement = self
+            .checks
+            .iter()
+            .filter(|check| !shuffled(check))
+            .map(|check| (check.id.clone(), check.vanilla_item.clone()))
+            .collect();
+
+        let mut progression = Vec::new();
+        let mut filler = Vec::new();
+        for check in self.checks.iter().filter(|check| shuffled(check)) {
+            let class = self
+                .pool
+                .iter()
+                .find(|entry| entry.item == check.vanilla_item)
+                .map_or(ItemClass::Filler, |entry| entry.class);
+            match class {
+                ItemClass::Progression => progression.push(check.vanilla_item.clone()),
+                ItemClass::Filler => filler.push(check.vanilla_item.clone()),
+            }
+        }
+        let mut rng = hash(&[seed]);
+        shuffle(&mut progression, &mut rng);
+        shuffle(&mut filler, &mut rng);
+
+        while let Some(item) = progression.pop() {
+            let mut progress = Progress {
+                items: progression.iter().cloned().collect(),
+                ..Default::default()
+            };
+            self.sweep(&placement, &mut progress);
+            let open: Vec<&Check> = self
+                .checks
+                .iter()
+                .filter(|check| !placement.contains_key(&check.id))
+                .filter(|check| progress.checks.contains(&check.id))
+                .collect();
+            if open.is_empty() {
+                bail!("No reachable check left for {item} with seed {seed}");
+            }
+            rng = hash(&[rng]);
+            let check = open[(rng % open.len() as u64) as usize];
+            placement.insert(check.id.clone(), item);
+        }
+
+        for check in &self.checks {
+            if !placement.contains_key(&check.id) {
+                let item = filler.pop().unwrap_or_else(|| check.vanilla_item.clone());
+                placement.insert(check.id.clone(), item);
+            }
+        }
+
+        if self.playthrough(&placement).is_none() {
+            bail!("Seed {seed} produced an unbeatable placement");
+        }
+        Ok(placement)
+    }
+
+    /// Collect everything reachable from the progress, using the placed
+    /// items; checks without an item are reached but give nothing
+    fn sweep(&self, placement: &Placement, progress: &mut Progress) {
+        loop {
+            self.open_gates(progress);
+            let mut changed = false;
+            for check in &self.checks {
+                if progress.checks.contains(&check.id) || !progress.meets(&check.requires) {
+                    continue;
+                }
+                progress.checks.insert(check.id.clone());
+                if let Some(item) = placement.get(&check.id) {
+                    progress.items.insert(item.clone());
+                }
+                changed = true;
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    /// Complete every quest and defeat every boss the progress allows
+    fn open_gates(&self, progress: &mut Progress) {
+        loop {
+            let mut changed = false;
+            for gate in &self.quests {
+                if !progress.quests.contains(&gate.id) && progress.meets(&gate.requires) {
+                    progress.quests.insert(gate.id.clone());
+                    changed = true;
+                }
+            }
+            for gate in &self.bosses {
+                if !progress.bosses.contains(&gate.id) && progress.meets(&gate.requires) {
+                    progress.bosses.insert(gate.id.clone());
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+}
+
+/// Whether a quest reward names an item rather than gold, experience or a
+/// story flag
+fn is_item_reward(reward: &str) -> bool {
+    !(reward.starts_with("flag:") || reward.ends_with(" gold") || reward.ends_with(" XP"))
+}
+
+/// Fisher-Yates with the map hash as the random source
+fn shuffle(items: &mut [String], rng: &mut u64) {
+    for i in (1..items.len()).rev() {
+        *rng = hash(&[*rng]);
+        items.swap(i, (*rng % (i as u64 + 1)) as usize);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_assets::{Enemy, Quest, QuestObjective};
+    use crate::game_types::fixtures;
+    use serde_json::json;
+
+    fn step(objective_type: &str, location: &str) -> QuestObjective {
+        QuestObjective {
+            description: String::new(),
+            objective_type: objective_type.to_string(),
+            location: location.to_string(),
+        }
+    }
+
+    fn quest(id: &str, main: bool, prerequisites: &[&str], steps: Vec<QuestObjective>) -> Quest {
+        Quest {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            main,
+            steps,
+            rewards: Vec::new(),
+            prerequisites: prerequisites.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// Three dungeons in a row, a key that unlocks a lantern quest, and a
+    /// finale that needs the last boss and the Fire Rod
+    fn spec() -> RandomizerSpec {
+        let dungeon = |name: &str, treasures: [&str; 2], boss: &str| {
+            json!({
+                "name": name,
+                "floors": [
+                    {"layout": "", "encounters": [], "treasures": [treasures[0]]},
+                    {"layout": "", "encounters": [], "treasures": [treasures[1]]}
+                ],
+                "boss": {"name": boss, "sprite": "", "attacks": [], "dialog": ""}
+            })
+        };
+        let mut world = fixtures::world();
+        world.dungeons = serde_json::from_value(json!([
+            dungeon("Sunken Vault", ["Ether", "Iron Key"], "Warden"),
+            dungeon("Ember Keep", ["Potion", "Fire Rod"], "Salamander"),
+            dungeon("Sky Spire", ["Elixir", "Potion"], "Tempest"),
+        ]))
+        .unwrap();
+
+        let mut lost_key = quest("lost_key", false, &[], vec![step("collect", "Iron Key")]);
+        lost_key.rewards = vec!["Lantern".to_string(), "50 gold".to_string()];
+        let mut dark_cellar = quest(
+            "dark_cellar",
+            false,
+            &["lost_key"],
+            vec![step("collect", "Lantern"), step("visit", "Ember Keep")],
+        );
+        dark_cellar.rewards = vec!["Hi-Potion".to_string()];
+        let finale = quest(
+            "finale",
+            true,
+            &[],
+            vec![step("defeat", "Tempest"), step("collect", "Fire Rod")],
+        );
+        let quests = QuestDatabase {
+            quests: vec![lost_key, dark_cellar, finale],
+        };
+
+        let enemies = EnemyDatabase {
+            enemies: ["Warden", "Salamander", "Tempest"]
+                .into_iter()
+                .map(|name| Enemy {
+                    id: asset_id(name),
+                    name: name.to_string(),
+                    boss: true,
+                    sprite: None,
+                    attacks: Vec::new(),
+                    habitats: Vec::new(),
+                })
+                .collect(),
+        };
+        RandomizerSpec::derive(&world, &quests, &enemies)
+    }
+
+    fn sorted_items(placement: &Placement) -> Vec<&str> {
+        let mut items: Vec<&str> = placement.values().map(String::as_str).collect();
+        items.sort_unstable();
+        items
+    }
+
+    #[test]
+    fn derive_finds_checks_gates_and_progression() {
+        let spec = spec();
+        assert_eq!(spec.checks.len(), 8);
+        assert_eq!(
+            spec.goal,
+            [Requirement::Quest {
+                quest: "finale".to_string()
+            }]
+        );
+
+        let progression: Vec<&str> = spec
+            .pool
+            .iter()
+            .filter(|entry| entry.class == ItemClass::Progression)
+            .map(|entry| entry.item.as_str())
+            .collect();
+        assert_eq!(progression, ["iron_key", "fire_rod", "lantern"]);
+        let potions = spec.pool.iter().find(|entry| entry.item == "potion");
+        assert_eq!(potions.map(|entry| entry.count), Some(2));
+
+        // The second dungeon opens behind the first boss
+        let ember = spec.checks.iter().find(|c| c.id == "ember_keep_floor2_0");
+        assert_eq!(
+            ember.map(|c| c.requires.clone()),
+            Some(vec![Requirement::Boss {
+                boss: "warden".to_string()
+            }])
+        );
+        assert!(spec.playthrough(&spec.vanilla()).is_some());
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_placement() {
+        let spec = spec();
+        let placement = spec.shuffle(7).unwrap();
+        assert_eq!(spec.shuffle(7).unwrap(), placement);
+
+        let distinct: HashSet<Placement> =
+            (0..20).map(|seed| spec.shuffle(seed).unwrap()).collect();
+        assert!(distinct.len() > 1);
+    }
+
+    #[test]
+    fn every_seed_is_beatable() {
+        let spec = spec();
+        let vanilla = spec.vanilla();
+        for seed in 0..200 {
+            let placement = spec.shuffle(seed).unwrap();
+            assert!(placement.keys().eq(vanilla.keys()));
+            assert_eq!(sorted_items(&placement), sorted_items(&vanilla));
+
+            let spheres = spec.playthrough(&placement).expect("beatable");
+            assert_eq!(
+                spheres.iter().map(Vec::len).sum::<usize>(),
+                spec.checks.len()
+            );
+        }
+
+        // The key behind the quest it unlocks, which hands out the Fire Rod
+        let mut locked = vanilla.clone();
+        for (check, item) in [
+            ("sunken_vault_floor2_0", "hi_potion"),
+            ("ember_keep_floor2_0", "lantern"),
+            ("lost_key_reward_0", "fire_rod"),
+            ("dark_cellar_reward_0", "iron_key"),
+        ] {
+            locked.insert(check.to_string(), item.to_string());
+        }
+        assert!(spec.playthrough(&locked).is_none());
+    }
+
+    #[test]
+    fn unshuffled_checks_keep_their_vanilla_item() {
+        let mut spec = spec();
+        spec.settings.shuffle_quest_rewards = false;
+        for seed in 0..20 {
+            let placement = spec.shuffle(seed).unwrap();
+            assert_eq!(placement["lost_key_reward_0"], "lantern");
+            assert_eq!(placement["dark_cellar_reward_0"], "hi_potion");
+        }
+    }
+}
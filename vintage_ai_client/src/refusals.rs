@@ -0,0 +1,624 @@
+//! Content policy refusals
+//!
+//! A model refuses a prompt either with an error (`content_policy_violation`,
+//! the usual answer of the image models) or with a response: the `refusal`
+//! field of a chat message, a `content_filter` finish reason, or an apology
+//! in place of the content ("I'm sorry, but I can't help with that").
+//!
+//! [`retry_sanitized`] runs a request and, when it is refused, guesses the
+//! [`RefusalTrigger`] from the prompt, rewrites the prompt without it and
+//! runs the request once more. When the rewrite is refused too, or the
+//! trigger is one no rewrite should paper over, the request fails with
+//! [`NeedsManualRewrite`] so the task can ask for a new prompt instead of
+//! reporting a generic error.
+
+use anyhow::Result;
+use async_openai::error::OpenAIError;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+/// Sentence added to every rewritten prompt
+const SAFE_FRAMING: &str =
+    "This is stylized, non-graphic, family-friendly content for an original 16-bit video game.";
+
+/// Longest response still read as an apology rather than content
+const MAX_REFUSAL_LEN: usize = 400;
+
+/// Openings of a response that refuses instead of answering
+static REFUSAL_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)^\W*(i'?m sorry|i am sorry|sorry|i apologi[sz]e|unfortunately)?[,.!\s]*(but\s+)?(i|we)\s*('m| am)?\s*(can(no|')t|cannot|am unable to|'m unable to|unable to|won'?t be able to|must decline to|will not)\s+(help|assist|create|generate|provide|comply|write|produce|fulfil|do that|continue)",
+    )
+    .expect("valid refusal pattern")
+});
+
+/// What most likely made the model refuse
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefusalTrigger {
+    /// Graphic violence or gore
+    Violence,
+    /// Sexual or suggestive content
+    Sexual,
+    /// Names of existing games, franchises or their characters
+    Copyright,
+    /// A real, living person
+    RealPerson,
+    /// Self-harm or suicide
+    SelfHarm,
+    /// Slurs or hateful content
+    Hate,
+    /// Nothing in the prompt explains the refusal
+    Unknown,
+}
+
+impl RefusalTrigger {
+    /// Triggers in the order they are checked
+    pub const ALL: [RefusalTrigger; 7] = [
+        RefusalTrigger::SelfHarm,
+        RefusalTrigger::Hate,
+        RefusalTrigger::Sexual,
+        RefusalTrigger::RealPerson,
+        RefusalTrigger::Copyright,
+        RefusalTrigger::Violence,
+        RefusalTrigger::Unknown,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RefusalTrigger::Violence => "violence",
+            RefusalTrigger::Sexual => "sexual content",
+            RefusalTrigger::Copyright => "existing franchise",
+            RefusalTrigger::RealPerson => "real person",
+            RefusalTrigger::SelfHarm => "self-harm",
+            RefusalTrigger::Hate => "hateful content",
+            RefusalTrigger::Unknown => "unknown",
+        }
+    }
+
+    /// Words of a prompt hinting at the trigger
+    fn keywords(self) -> &'static [&'static str] {
+        match self {
+            RefusalTrigger::Violence => &[
+                "blood",
+                "bloody",
+                "gore",
+                "gory",
+                "decapitate",
+                "decapitated",
+                "dismember",
+                "dismembered",
+                "mutilated",
+                "corpse",
+                "corpses",
+                "torture",
+                "tortured",
+                "massacre",
+                "slaughter",
+                "murder",
+                "behead",
+                "beheaded",
+                "guts",
+                "kill",
+                "killing",
+            ],
+            RefusalTrigger::Sexual => &[
+                "nude",
+                "naked",
+                "sexy",
+                "sexual",
+                "erotic",
+                "lingerie",
+                "seductive",
+                "topless",
+                "nsfw",
+            ],
+            RefusalTrigger::Copyright => &[
+                "nintendo",
+                "sega",
+                "capcom",
+                "square enix",
+                "squaresoft",
+                "konami",
+                "disney",
+                "pokemon",
+                "pokémon",
+                "pikachu",
+                "mario",
+                "luigi",
+                "zelda",
+                "hyrule",
+                "sonic",
+                "mega man",
+                "megaman",
+                "final fantasy",
+                "chrono trigger",
+                "earthbound",
+                "metroid",
+                "samus",
+                "castlevania",
+                "kirby",
+                "donkey kong",
+            ],
+            RefusalTrigger::RealPerson => &[
+                "celebrity",
+                "president",
+                "prime minister",
+                "politician",
+                "real person",
+                "photo of",
+                "likeness of",
+            ],
+            RefusalTrigger::SelfHarm => &["suicide", "self-harm", "self harm", "cutting herself"],
+            RefusalTrigger::Hate => &["slur", "slurs", "nazi", "racist", "genocide"],
+            RefusalTrigger::Unknown => &[],
+        }
+    }
+
+    /// Milder words replacing the keywords in a rewrite. Triggers without
+    /// any can't be rewritten automatically.
+    fn replacements(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            RefusalTrigger::Violence => &[
+                ("bloody", "battle-worn"),
+                ("blood", "sparks"),
+                ("gory", "dramatic"),
+                ("gore", "impact effects"),
+                ("decapitated", "defeated"),
+                ("decapitate", "defeat"),
+                ("dismembered", "defeated"),
+                ("dismember", "defeat"),
+                ("mutilated", "battered"),
+                ("corpses", "fallen foes"),
+                ("corpse", "fallen foe"),
+                ("tortured", "imprisoned"),
+                ("torture", "imprisonment"),
+                ("massacre", "battle"),
+                ("slaughter", "battle"),
+                ("murder", "defeat"),
+                ("beheaded", "defeated"),
+                ("behead", "defeat"),
+                ("guts", "courage"),
+                ("killing", "defeating"),
+                ("kill", "defeat"),
+            ],
+            RefusalTrigger::Sexual => &[
+                ("nude", "fully clothed"),
+                ("naked", "fully clothed"),
+                ("sexy", "charismatic"),
+                ("sexual", "romantic"),
+                ("erotic", "romantic"),
+                ("lingerie", "adventuring clothes"),
+                ("seductive", "charming"),
+                ("topless", "armored"),
+                ("nsfw", "all-ages"),
+            ],
+            RefusalTrigger::Copyright => &[
+                ("square enix", "a classic RPG studio"),
+                ("squaresoft", "a classic RPG studio"),
+                ("nintendo", "a classic console studio"),
+                ("sega", "a classic console studio"),
+                ("capcom", "a classic arcade studio"),
+                ("konami", "a classic arcade studio"),
+                ("disney", "a classic animation studio"),
+                ("pokémon", "collectible monsters"),
+                ("pokemon", "collectible monsters"),
+                ("pikachu", "an electric mouse monster"),
+                ("mario", "a cheerful plumber hero"),
+                ("luigi", "a tall plumber hero"),
+                ("zelda", "a wise princess"),
+                ("hyrule", "a fantasy kingdom"),
+                ("sonic", "a speedy hero"),
+                ("mega man", "a robot hero"),
+                ("megaman", "a robot hero"),
+                ("final fantasy", "classic 16-bit JRPGs"),
+                ("chrono trigger", "classic 16-bit JRPGs"),
+                ("earthbound", "quirky 16-bit RPGs"),
+                ("metroid", "16-bit exploration games"),
+                ("samus", "an armored bounty hunter"),
+                ("castlevania", "gothic 16-bit action games"),
+                ("kirby", "a round pink hero"),
+                ("donkey kong", "a barrel-throwing ape"),
+            ],
+            RefusalTrigger::RealPerson
+            | RefusalTrigger::SelfHarm
+            | RefusalTrigger::Hate
+            | RefusalTrigger::Unknown => &[],
+        }
+    }
+
+    /// Trigger the prompt most likely hit. `message` is the provider's
+    /// reason, checked before the prompt when it names one.
+    pub fn classify(prompt: &str, message: &str) -> Self {
+        let named = [
+            (RefusalTrigger::SelfHarm, "self-harm"),
+            (RefusalTrigger::Hate, "hate"),
+            (RefusalTrigger::Sexual, "sexual"),
+            (RefusalTrigger::Violence, "violence"),
+            (RefusalTrigger::Copyright, "copyright"),
+            (RefusalTrigger::Copyright, "trademark"),
+            (RefusalTrigger::RealPerson, "public figure"),
+        ];
+        if let Some((trigger, _)) = named
+            .iter()
+            .find(|(_, word)| word_pattern(word).is_match(message))
+        {
+            return *trigger;
+        }
+
+        Self::ALL
+            .into_iter()
+            .find(|trigger| {
+                trigger
+                    .keywords()
+                    .iter()
+                    .any(|keyword| word_pattern(keyword).is_match(prompt))
+            })
+            .unwrap_or(RefusalTrigger::Unknown)
+    }
+}
+
+impl std::fmt::Display for RefusalTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Case-insensitive pattern of `word` as a whole word
+fn word_pattern(word: &str) -> Regex {
+    Regex::new(&format!(r"(?i)\b{}\b", regex::escape(word))).expect("escaped word")
+}
+
+/// Error a request fails with when the provider refused its prompt
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("The model refused the prompt: {message}")]
+pub struct Refused {
+    /// The provider's or the model's explanation
+    pub message: String,
+}
+
+/// Error a request fails with when its prompt was refused and couldn't be
+/// rewritten into one the model accepts
+#[derive(Debug, Clone, Serialize, Deserialize, thiserror::Error)]
+#[error("The prompt needs a manual rewrite ({trigger}): {message}")]
+pub struct NeedsManualRewrite {
+    pub trigger: RefusalTrigger,
+    /// The refused prompt
+    pub prompt: String,
+    /// The automatic rewrite that was refused as well, if one was tried
+    pub attempted: Option<String>,
+    /// The provider's or the model's explanation
+    pub message: String,
+}
+
+/// Refusal explanation of a chat response, from the message's `refusal`
+/// field, a `content_filter` finish reason or an apology in place of the
+/// content
+pub fn refusal_in_response(
+    content: Option<&str>,
+    refusal: Option<&str>,
+    content_filtered: bool,
+) -> Option<String> {
+    if let Some(refusal) = refusal.map(str::trim).filter(|refusal| !refusal.is_empty()) {
+        return Some(refusal.to_string());
+    }
+    if content_filtered {
+        return Some("The response was withheld by the content filter".to_string());
+    }
+    let content = content?.trim();
+    (content.len() <= MAX_REFUSAL_LEN && REFUSAL_PATTERN.is_match(content))
+        .then(|| content.to_string())
+}
+
+/// Refusal explanation of a failed request: a [`Refused`] response or a
+/// content policy error of the provider
+pub fn refusal_in_error(error: &anyhow::Error) -> Option<String> {
+    error.chain().find_map(|cause| {
+        if let Some(refused) = cause.downcast_ref::<Refused>() {
+            return Some(refused.message.clone());
+        }
+        match cause.downcast_ref::<OpenAIError>()? {
+            OpenAIError::ApiError(error) => {
+                let code = error.code.as_deref().unwrap_or_default();
+                let message = error.message.to_lowercase();
+                (code == "content_policy_violation"
+                    || code == "content_filter"
+                    || message.contains("safety system")
+                    || message.contains("content policy")
+                    || message.contains("content management policy"))
+                .then(|| error.message.clone())
+            }
+            _ => None,
+        }
+    })
+}
+
+/// `prompt` with the words of `trigger` replaced by milder ones and framed
+/// as game content, or `None` if the trigger can't be rewritten away
+pub fn sanitize(prompt: &str, trigger: RefusalTrigger) -> Option<String> {
+    let replacements: Vec<(&str, &str)> = match trigger {
+        // Nothing points at the cause: soften every word that could
+        RefusalTrigger::Unknown => [
+            RefusalTrigger::Violence,
+            RefusalTrigger::Sexual,
+            RefusalTrigger::Copyright,
+        ]
+        .iter()
+        .flat_map(|trigger| trigger.replacements().iter().copied())
+        .collect(),
+        trigger => {
+            let replacements = trigger.replacements();
+            if replacements.is_empty() {
+                return None;
+            }
+            replacements.to_vec()
+        }
+    };
+
+    let mut rewritten = prompt.to_string();
+    for (word, replacement) in replacements {
+        rewritten = word_pattern(word)
+            .replace_all(&rewritten, replacement)
+            .into_owned();
+    }
+    if rewritten == prompt && trigger != RefusalTrigger::Unknown {
+        return None;
+    }
+    Some(format!("{rewritten}\n\n{SAFE_FRAMING}"))
+}
+
+/// Run `request` with `prompt`. When it is refused, run it once more with
+/// the prompt sanitized, and fail with [`NeedsManualRewrite`] if that is
+/// refused too or the prompt can't be sanitized. Other errors are returned
+/// as they are.
+pub async fn retry_sanitized<T, F, Fut>(prompt: &str, request: F) -> Result<T>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let error = match request(prompt.to_string()).await {
+        Err(error) => error,
+        result => return result,
+    };
+    let Some(message) = refusal_in_error(&error) else {
+        return Err(error);
+    };
+
+    let trigger = RefusalTrigger::classify(prompt, &message);
+    let Some(rewritten) = sanitize(prompt, trigger) else {
+        tracing::warn!("Prompt refused ({trigger}), no automatic rewrite: {message}");
+        return Err(NeedsManualRewrite {
+            trigger,
+            prompt: prompt.to_string(),
+            attempted: None,
+            message,
+        }
+        .into());
+    };
+
+    tracing::warn!("Prompt refused ({trigger}), retrying with a sanitized rewrite: {message}");
+    let error = match request(rewritten.clone()).await {
+        Err(error) => error,
+        result => return result,
+    };
+    match refusal_in_error(&error) {
+        Some(message) => Err(NeedsManualRewrite {
+            trigger,
+            prompt: prompt.to_string(),
+            attempted: Some(rewritten),
+            message,
+        }
+        .into()),
+        None => Err(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_openai::error::ApiError;
+    use std::sync::Mutex;
+
+    fn policy_error(code: &str, message: &str) -> anyhow::Error {
+        OpenAIError::ApiError(ApiError {
+            message: message.to_string(),
+            r#type: Some("invalid_request_error".to_string()),
+            param: None,
+            code: Some(code.to_string()),
+        })
+        .into()
+    }
+
+    #[test]
+    fn apologies_read_as_refusals() {
+        for apology in [
+            "I'm sorry, but I can't help with that.",
+            "Sorry, I cannot create that image.",
+            "I apologize, but I am unable to generate this content.",
+            "Unfortunately, I won't be able to write that.",
+            "I must decline to produce that.",
+        ] {
+            assert!(REFUSAL_PATTERN.is_match(apology), "{apology}");
+        }
+        for content in [
+            "I can help with that! Here is the dungeon layout.",
+            "Sorry about the wait: the boss has three phases.",
+            "The knight cannot continue without the key.",
+        ] {
+            assert!(!REFUSAL_PATTERN.is_match(content), "{content}");
+        }
+    }
+
+    #[test]
+    fn classify_prefers_the_provider_reason_then_the_order_of_all() {
+        // The reason names the trigger, whatever the prompt says
+        assert_eq!(
+            RefusalTrigger::classify("a bloody battle", "flagged as sexual content"),
+            RefusalTrigger::Sexual
+        );
+        // Otherwise the earlier trigger of ALL wins
+        assert_eq!(
+            RefusalTrigger::classify("Mario slaughters a nazi", ""),
+            RefusalTrigger::Hate
+        );
+        assert_eq!(
+            RefusalTrigger::classify("Mario covered in blood", "refused"),
+            RefusalTrigger::Copyright
+        );
+        assert_eq!(
+            RefusalTrigger::classify("a bloody battle", "refused"),
+            RefusalTrigger::Violence
+        );
+        // Keywords are whole words
+        assert_eq!(
+            RefusalTrigger::classify("a skill tree for the bloodline", "refused"),
+            RefusalTrigger::Unknown
+        );
+    }
+
+    #[test]
+    fn sanitize_replaces_whole_words_only() {
+        let rewritten = sanitize(
+            "Kill the boss, skill up, then KILL again",
+            RefusalTrigger::Violence,
+        )
+        .unwrap();
+        assert!(rewritten.starts_with("defeat the boss, skill up, then defeat again"));
+        assert!(rewritten.ends_with(SAFE_FRAMING));
+
+        // Longer words are replaced before the words they contain
+        let rewritten = sanitize("a bloody sword", RefusalTrigger::Violence).unwrap();
+        assert!(rewritten.starts_with("a battle-worn sword"));
+
+        // Nothing to replace, or nothing that may be replaced
+        assert_eq!(sanitize("a friendly slime", RefusalTrigger::Violence), None);
+        assert_eq!(
+            sanitize("a portrait of the president", RefusalTrigger::RealPerson),
+            None
+        );
+        // Unknown triggers soften anything they can, or just add the framing
+        assert_eq!(
+            sanitize("a friendly slime", RefusalTrigger::Unknown).unwrap(),
+            format!("a friendly slime\n\n{SAFE_FRAMING}")
+        );
+    }
+
+    #[test]
+    fn refusals_are_found_in_responses() {
+        assert_eq!(
+            refusal_in_response(Some("Here it is"), Some(" Not allowed "), false).as_deref(),
+            Some("Not allowed")
+        );
+        assert!(refusal_in_response(Some("Here it is"), Some("  "), true).is_some());
+        assert_eq!(
+            refusal_in_response(Some("I'm sorry, but I can't help with that."), None, false)
+                .as_deref(),
+            Some("I'm sorry, but I can't help with that.")
+        );
+        assert_eq!(
+            refusal_in_response(Some("A castle on a hill"), None, false),
+            None
+        );
+        assert_eq!(refusal_in_response(None, None, false), None);
+
+        // A long answer opening with an apology is content
+        let long = format!(
+            "I'm sorry, but I can't help with the ending. {}",
+            "Ok. ".repeat(100)
+        );
+        assert_eq!(refusal_in_response(Some(&long), None, false), None);
+    }
+
+    #[test]
+    fn refusals_are_found_in_errors() {
+        let refused = anyhow::Error::from(Refused {
+            message: "No".to_string(),
+        })
+        .context("Failed to generate");
+        assert_eq!(refusal_in_error(&refused).as_deref(), Some("No"));
+
+        let policy = policy_error("content_policy_violation", "Rejected by the safety system");
+        assert_eq!(
+            refusal_in_error(&policy).as_deref(),
+            Some("Rejected by the safety system")
+        );
+        let filtered = policy_error("other", "Blocked by our content management policy");
+        assert!(refusal_in_error(&filtered).is_some());
+
+        assert_eq!(
+            refusal_in_error(&policy_error("rate_limit_exceeded", "Slow down")),
+            None
+        );
+        assert_eq!(refusal_in_error(&anyhow::anyhow!("timed out")), None);
+    }
+
+    /// Run `retry_sanitized` with a request answering `answers` in turn,
+    /// returning the result and the prompts the request was called with
+    async fn retry(
+        prompt: &str,
+        answers: Vec<Result<&'static str>>,
+    ) -> (Result<&'static str>, Vec<String>) {
+        let answers = Mutex::new(answers.into_iter());
+        let prompts = Mutex::new(Vec::new());
+        let result = retry_sanitized(prompt, |prompt| {
+            prompts.lock().unwrap().push(prompt);
+            let answer = answers
+                .lock()
+                .unwrap()
+                .next()
+                .expect("an answer per attempt");
+            async move { answer }
+        })
+        .await;
+        (result, prompts.into_inner().unwrap())
+    }
+
+    fn refused() -> Result<&'static str> {
+        Err(Refused {
+            message: "Refused".to_string(),
+        }
+        .into())
+    }
+
+    #[tokio::test]
+    async fn accepted_prompts_run_once() {
+        let (result, prompts) = retry("a bloody sword", vec![Ok("sprite")]).await;
+        assert_eq!(result.unwrap(), "sprite");
+        assert_eq!(prompts, ["a bloody sword"]);
+
+        let (result, prompts) =
+            retry("a bloody sword", vec![Err(anyhow::anyhow!("timed out"))]).await;
+        assert!(refusal_in_error(&result.unwrap_err()).is_none());
+        assert_eq!(prompts.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn refused_prompts_are_retried_sanitized_once() {
+        let (result, prompts) = retry("a bloody sword", vec![refused(), Ok("sprite")]).await;
+        assert_eq!(result.unwrap(), "sprite");
+        assert_eq!(prompts.len(), 2);
+        assert_eq!(
+            Some(prompts[1].clone()),
+            sanitize("a bloody sword", RefusalTrigger::Violence)
+        );
+
+        let (result, prompts) = retry("a bloody sword", vec![refused(), refused()]).await;
+        let error = result.unwrap_err();
+        let rewrite = error.downcast_ref::<NeedsManualRewrite>().unwrap();
+        assert_eq!(rewrite.trigger, RefusalTrigger::Violence);
+        assert_eq!(rewrite.prompt, "a bloody sword");
+        assert_eq!(rewrite.attempted.as_ref(), Some(&prompts[1]));
+        assert_eq!(prompts.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn unsanitizable_prompts_need_a_manual_rewrite() {
+        let (result, prompts) = retry("a photo of the president", vec![refused()]).await;
+        let error = result.unwrap_err();
+        let rewrite = error.downcast_ref::<NeedsManualRewrite>().unwrap();
+        assert_eq!(rewrite.trigger, RefusalTrigger::RealPerson);
+        assert_eq!(rewrite.attempted, None);
+        assert_eq!(prompts.len(), 1);
+    }
+}
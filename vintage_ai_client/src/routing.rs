@@ -0,0 +1,186 @@
+//! Model routing per kind of task
+//!
+//! Not every request deserves the most capable model: brainstorming and
+//! drafts are thrown away or rewritten, while the final code and key art
+//! ship with the game. [`RoutingPolicy`] picks the model of each
+//! [`TaskCategory`] of [`AiTask`](crate::client::AiTask), and the models to
+//! fall back to when it errors. The defaults can be overridden per category
+//! in the AI configuration:
+//!
+//! ```toml
+//! [routing.code]
+//! model = "gpt-4o"
+//! fallbacks = ["gpt-4-turbo"]
+//!
+//! [routing.key_art]
+//! model = "gpt-image-1"
+//! ```
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::refusals::NeedsManualRewrite;
+
+/// What a task is for, which decides the model it is worth
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskCategory {
+    /// Design discussions and ideas
+    Brainstorm,
+    /// Free-form text without a configuration of its own
+    Draft,
+    /// Game descriptions
+    Description,
+    /// Code that ends up in the game
+    Code,
+    /// Small previews of an image
+    Thumbnail,
+    /// Concept and key art
+    KeyArt,
+}
+
+impl TaskCategory {
+    pub const ALL: [TaskCategory; 6] = [
+        TaskCategory::Brainstorm,
+        TaskCategory::Draft,
+        TaskCategory::Description,
+        TaskCategory::Code,
+        TaskCategory::Thumbnail,
+        TaskCategory::KeyArt,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TaskCategory::Brainstorm => "brainstorm",
+            TaskCategory::Draft => "draft",
+            TaskCategory::Description => "description",
+            TaskCategory::Code => "code",
+            TaskCategory::Thumbnail => "thumbnail",
+            TaskCategory::KeyArt => "key_art",
+        }
+    }
+
+    /// Category of a [`TaskCategory::name`]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.name() == name)
+    }
+
+    /// Route used when the configuration doesn't override it
+    pub fn default_route(self) -> ModelRoute {
+        match self {
+            TaskCategory::Brainstorm | TaskCategory::Draft => {
+                ModelRoute::new("gpt-4o-mini").with_fallbacks(["gpt-3.5-turbo"])
+            }
+            TaskCategory::Description => ModelRoute::new("gpt-4-turbo").with_fallbacks(["gpt-4o"]),
+            TaskCategory::Code => ModelRoute::new("gpt-4").with_fallbacks(["gpt-4-turbo"]),
+            TaskCategory::Thumbnail => ModelRoute::new("dall-e-2").with_fallbacks(["dall-e-3"]),
+            TaskCategory::KeyArt => ModelRoute {
+                quality: Some("hd".to_string()),
+                ..ModelRoute::new("dall-e-3").with_fallbacks(["dall-e-2"])
+            },
+        }
+    }
+}
+
+/// Model serving a category of tasks, and the ones tried after it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelRoute {
+    pub model: String,
+    /// Tried in order when the model before errors
+    #[serde(default)]
+    pub fallbacks: Vec<String>,
+    /// Image quality (standard or hd), for image models
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<String>,
+}
+
+impl ModelRoute {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            fallbacks: Vec::new(),
+            quality: None,
+        }
+    }
+
+    pub fn with_fallbacks<S: Into<String>>(
+        mut self,
+        fallbacks: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.fallbacks = fallbacks.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// The model, then its fallbacks, each once
+    pub fn models(&self) -> Vec<&str> {
+        let mut models: Vec<&str> = Vec::new();
+        for model in std::iter::once(&self.model).chain(&self.fallbacks) {
+            if !models.contains(&model.as_str()) {
+                models.push(model);
+            }
+        }
+        models
+    }
+
+    /// Whether images should be generated in HD
+    pub fn is_hd(&self) -> bool {
+        self.quality.as_deref() == Some("hd")
+    }
+}
+
+/// Routes overriding the defaults, by category
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RoutingPolicy {
+    overrides: BTreeMap<TaskCategory, ModelRoute>,
+}
+
+impl RoutingPolicy {
+    /// Route of a category, the override if there is one
+    pub fn route(&self, category: TaskCategory) -> ModelRoute {
+        self.overrides
+            .get(&category)
+            .cloned()
+            .unwrap_or_else(|| category.default_route())
+    }
+
+    /// Serve a category with another route
+    pub fn with_route(mut self, category: TaskCategory, route: ModelRoute) -> Self {
+        self.overrides.insert(category, route);
+        self
+    }
+
+    /// Go back to the default route of a category
+    pub fn reset(&mut self, category: TaskCategory) {
+        self.overrides.remove(&category);
+    }
+}
+
+/// Run `request` with the model of `route`, then with each fallback while
+/// it errors. Returns the result with the model that produced it, or the
+/// error of the last model. A refused prompt is not retried on other
+/// models, since it needs rewriting rather than another model.
+pub async fn with_fallback<T, F, Fut>(route: &ModelRoute, request: F) -> Result<(T, String)>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let models = route.models();
+    let mut remaining = models.len();
+    for model in models {
+        remaining -= 1;
+        match request(model.to_string()).await {
+            Ok(result) => return Ok((result, model.to_string())),
+            Err(error)
+                if remaining == 0 || error.downcast_ref::<NeedsManualRewrite>().is_some() =>
+            {
+                return Err(error);
+            }
+            Err(error) => tracing::warn!("{model} failed, falling back: {error:#}"),
+        }
+    }
+    unreachable!("a route has at least one model")
+}
@@ -0,0 +1,341 @@
+//! Asset naming convention and slug registry
+//!
+//! Every generated artifact gets its file and cross-reference name from a
+//! [`SlugRegistry`] instead of building one ad hoc. A name is turned into a
+//! slug under the project's [`NamingConvention`] (snake_case or kebab-case,
+//! a prefix per category, a length limit) and recorded. Asking again for the
+//! same category and name returns the same slug, and a different name that
+//! would produce a taken slug gets a numeric suffix (`slime`, `slime_2`), so
+//! slugs never collide. The registry is saved as `slugs.json` in the
+//! project directory, which keeps slugs stable across runs for the
+//! exporters that reference assets by slug.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// File name of the slug registry inside a project directory
+pub const SLUG_REGISTRY_FILE: &str = "slugs.json";
+
+/// Default longest slug, suffix included
+pub const DEFAULT_MAX_LENGTH: usize = 48;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlugCase {
+    #[default]
+    Snake,
+    Kebab,
+}
+
+impl SlugCase {
+    pub fn separator(self) -> char {
+        match self {
+            SlugCase::Snake => '_',
+            SlugCase::Kebab => '-',
+        }
+    }
+}
+
+/// What a slug names; each category can carry its own prefix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetCategory {
+    Item,
+    Enemy,
+    Boss,
+    Npc,
+    Quest,
+    Location,
+    Dungeon,
+    Achievement,
+    Dialogue,
+    Sprite,
+    Tileset,
+    Map,
+    Background,
+    Music,
+    Sound,
+    Other,
+}
+
+/// How slugs are spelled
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamingConvention {
+    #[serde(default)]
+    pub case: SlugCase,
+    /// Prefix per category, joined to the name with the separator.
+    /// Categories without one are not prefixed.
+    #[serde(default)]
+    pub prefixes: BTreeMap<AssetCategory, String>,
+    #[serde(default = "default_max_length")]
+    pub max_length: usize,
+}
+
+fn default_max_length() -> usize {
+    DEFAULT_MAX_LENGTH
+}
+
+impl Default for NamingConvention {
+    /// snake_case, with prefixes on media assets so sprites, tiles and audio
+    /// of the same subject don't collide with each other or with game data
+    fn default() -> Self {
+        let prefixes = [
+            (AssetCategory::Sprite, "spr"),
+            (AssetCategory::Tileset, "tiles"),
+            (AssetCategory::Background, "bg"),
+            (AssetCategory::Music, "bgm"),
+            (AssetCategory::Sound, "sfx"),
+        ];
+        Self {
+            case: SlugCase::Snake,
+            prefixes: prefixes
+                .into_iter()
+                .map(|(category, prefix)| (category, prefix.to_string()))
+                .collect(),
+            max_length: DEFAULT_MAX_LENGTH,
+        }
+    }
+}
+
+impl NamingConvention {
+    pub fn with_case(mut self, case: SlugCase) -> Self {
+        self.case = case;
+        self
+    }
+
+    pub fn with_prefix(mut self, category: AssetCategory, prefix: &str) -> Self {
+        let prefix = slugify(prefix, self.case);
+        if prefix.is_empty() {
+            self.prefixes.remove(&category);
+        } else {
+            self.prefixes.insert(category, prefix);
+        }
+        self
+    }
+
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length.max(8);
+        self
+    }
+
+    /// The slug for a name before collisions are resolved
+    pub fn slug(&self, category: AssetCategory, name: &str) -> String {
+        let separator = self.case.separator();
+        let mut slug = slugify(name, self.case);
+        if slug.is_empty() {
+            slug = format!("{category:?}").to_lowercase();
+        }
+        if let Some(prefix) = self.prefixes.get(&category)
+            && !slug.starts_with(&format!("{prefix}{separator}"))
+        {
+            slug = format!("{prefix}{separator}{slug}");
+        }
+        truncate(&slug, self.max_length, separator)
+    }
+
+    /// Ways the slug breaks the convention
+    pub fn check(&self, category: AssetCategory, slug: &str) -> Vec<SlugIssue> {
+        let mut issues = Vec::new();
+        if slug.is_empty() {
+            issues.push(SlugIssue::Empty);
+            return issues;
+        }
+        if slugify(slug, self.case) != slug {
+            issues.push(SlugIssue::WrongCase(self.case));
+        }
+        if let Some(prefix) = self.prefixes.get(&category)
+            && !slug.starts_with(&format!("{prefix}{}", self.case.separator()))
+        {
+            issues.push(SlugIssue::MissingPrefix(prefix.clone()));
+        }
+        if slug.len() > self.max_length {
+            issues.push(SlugIssue::TooLong(slug.len()));
+        }
+        issues
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlugIssue {
+    Empty,
+    WrongCase(SlugCase),
+    MissingPrefix(String),
+    /// Length in bytes
+    TooLong(usize),
+    /// Registered for another name or category
+    Taken(String),
+}
+
+impl fmt::Display for SlugIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SlugIssue::Empty => write!(f, "slug is empty"),
+            SlugIssue::WrongCase(case) => write!(f, "slug is not {case:?} case"),
+            SlugIssue::MissingPrefix(prefix) => write!(f, "slug lacks the '{prefix}' prefix"),
+            SlugIssue::TooLong(length) => write!(f, "slug is {length} bytes long"),
+            SlugIssue::Taken(name) => write!(f, "slug is already used by '{name}'"),
+        }
+    }
+}
+
+/// A registered slug and what it names
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlugEntry {
+    pub category: AssetCategory,
+    /// Display name the slug was requested for
+    pub name: String,
+}
+
+/// Every slug handed out for a project
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlugRegistry {
+    #[serde(default)]
+    pub convention: NamingConvention,
+    #[serde(default)]
+    pub slugs: BTreeMap<String, SlugEntry>,
+}
+
+impl SlugRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_convention(convention: NamingConvention) -> Self {
+        Self {
+            convention,
+            slugs: BTreeMap::new(),
+        }
+    }
+
+    /// Load a registry from disk, returning an empty one if the file doesn't exist
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read slug registry {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse slug registry {}", path.display()))
+    }
+
+    /// Write the registry to disk
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write slug registry {}", path.display()))
+    }
+
+    /// The slug for the name, registering a new one if needed
+    pub fn slug(&mut self, category: AssetCategory, name: &str) -> String {
+        if let Some(slug) = self.get(category, name) {
+            return slug.to_string();
+        }
+        let base = self.convention.slug(category, name);
+        let separator = self.convention.case.separator();
+        let mut slug = base.clone();
+        let mut n = 2;
+        while self.slugs.contains_key(&slug) {
+            let suffix = format!("{separator}{n}");
+            let stem = truncate(
+                &base,
+                self.convention.max_length.saturating_sub(suffix.len()),
+                separator,
+            );
+            slug = format!("{stem}{suffix}");
+            n += 1;
+        }
+        self.slugs.insert(
+            slug.clone(),
+            SlugEntry {
+                category,
+                name: name.trim().to_string(),
+            },
+        );
+        slug
+    }
+
+    /// The slug registered for the name, if any
+    pub fn get(&self, category: AssetCategory, name: &str) -> Option<&str> {
+        let name = name.trim();
+        self.slugs
+            .iter()
+            .find(|(_, entry)| entry.category == category && entry.name == name)
+            .map(|(slug, _)| slug.as_str())
+    }
+
+    /// What a slug names
+    pub fn resolve(&self, slug: &str) -> Option<&SlugEntry> {
+        self.slugs.get(slug)
+    }
+
+    /// Ways a slug chosen elsewhere breaks the convention or collides
+    pub fn check(&self, category: AssetCategory, name: &str, slug: &str) -> Vec<SlugIssue> {
+        let mut issues = self.convention.check(category, slug);
+        if let Some(entry) = self.slugs.get(slug)
+            && (entry.category != category || entry.name != name.trim())
+        {
+            issues.push(SlugIssue::Taken(entry.name.clone()));
+        }
+        issues
+    }
+}
+
+/// Lowercase the name and join its words with the case's separator,
+/// e.g. "Iron Sword" -> "iron_sword"
+pub fn slugify(name: &str, case: SlugCase) -> String {
+    let separator = case.separator();
+    let mut slug = String::with_capacity(name.len());
+    for c in name.trim().chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.ends_with(separator) && !slug.is_empty() {
+            slug.push(separator);
+        }
+    }
+    slug.trim_end_matches(separator).to_string()
+}
+
+/// Cut the slug to at most `max` bytes, preferring a word boundary
+fn truncate(slug: &str, max: usize, separator: char) -> String {
+    if slug.len() <= max {
+        return slug.to_string();
+    }
+    let mut end = max;
+    while !slug.is_char_boundary(end) {
+        end -= 1;
+    }
+    let cut = &slug[..end];
+    match cut.rfind(separator) {
+        Some(at) if at > max / 2 => cut[..at].to_string(),
+        _ => cut.trim_end_matches(separator).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colliding_slugs_get_a_suffix_within_the_length_limit() {
+        let mut slugs =
+            SlugRegistry::with_convention(NamingConvention::default().with_max_length(12));
+        let first = slugs.slug(AssetCategory::Music, "Boss Battle Theme");
+        let second = slugs.slug(AssetCategory::Music, "Boss battle theme!");
+        assert_eq!(first, "bgm_boss");
+        assert_eq!(second, "bgm_boss_2");
+        assert!(second.len() <= 12);
+
+        assert_eq!(slugs.slug(AssetCategory::Music, "Boss Battle Theme"), first);
+        assert_eq!(
+            slugs.check(AssetCategory::Item, "Potion", &second),
+            [SlugIssue::Taken("Boss battle theme!".to_string())]
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! Request metrics for running the client as a backend service
+//!
+//! With the `metrics` feature enabled, every request of the
+//! [`AiService`](crate::AiService) generators (text, image, audio,
+//! embeddings and conversations) is reported through the `metrics` facade,
+//! labelled by `provider` and `request_type`. Install an exporter
+//! (e.g. `metrics-exporter-prometheus` or an OpenTelemetry bridge) in the
+//! host application to collect them. Without the feature these functions
+//! compile to nothing.
+//!
+//! | Metric | Kind |
+//! |--------|------|
+//! | `vintage_ai_requests_total` | counter |
+//! | `vintage_ai_cache_hits_total` | counter |
+//! | `vintage_ai_tokens_total` | counter |
+//! | `vintage_ai_cost_usd_total` | gauge (cumulative) |
+//! | `vintage_ai_request_duration_seconds` | histogram |
+//! | `vintage_ai_request_cost_usd` | histogram |
+//!
+//! Cache hit rate is `vintage_ai_cache_hits_total / vintage_ai_requests_total`.
+
+use std::time::{Duration, Instant};
+
+pub const REQUESTS_TOTAL: &str = "vintage_ai_requests_total";
+pub const CACHE_HITS_TOTAL: &str = "vintage_ai_cache_hits_total";
+pub const TOKENS_TOTAL: &str = "vintage_ai_tokens_total";
+pub const COST_USD_TOTAL: &str = "vintage_ai_cost_usd_total";
+pub const REQUEST_DURATION_SECONDS: &str = "vintage_ai_request_duration_seconds";
+pub const REQUEST_COST_USD: &str = "vintage_ai_request_cost_usd";
+
+/// One completed request, as reported to the metrics recorder
+#[derive(Debug, Clone)]
+pub struct RequestMetrics<'a> {
+    pub provider: &'a str,
+    pub request_type: &'a str,
+    pub duration: Duration,
+    pub tokens: usize,
+    pub cost: f64,
+    pub cache_hit: bool,
+}
+
+/// Times a generator request until it is reported
+#[derive(Debug)]
+pub struct RequestTimer {
+    request_type: &'static str,
+    start: Instant,
+}
+
+impl RequestTimer {
+    pub fn start(request_type: &'static str) -> Self {
+        Self {
+            request_type,
+            start: Instant::now(),
+        }
+    }
+
+    /// Report the request as served from the cache
+    pub fn cache_hit(self, provider: &str) {
+        self.finish(provider, 0, 0.0, true);
+    }
+
+    /// Report the request with the tokens and cost it spent
+    pub fn finish(self, provider: &str, tokens: usize, cost: f64, cache_hit: bool) {
+        record_request(&RequestMetrics {
+            provider,
+            request_type: self.request_type,
+            duration: self.start.elapsed(),
+            tokens,
+            cost,
+            cache_hit,
+        });
+    }
+}
+
+/// Register descriptions and units with the installed recorder
+#[cfg(feature = "metrics")]
+pub fn describe_metrics() {
+    use metrics::{Unit, describe_counter, describe_gauge, describe_histogram};
+
+    describe_counter!(REQUESTS_TOTAL, "AI requests executed");
+    describe_counter!(CACHE_HITS_TOTAL, "AI requests served from cache");
+    describe_counter!(TOKENS_TOTAL, Unit::Count, "Tokens consumed by AI requests");
+    describe_gauge!(COST_USD_TOTAL, "Estimated AI spend in USD");
+    describe_histogram!(
+        REQUEST_DURATION_SECONDS,
+        Unit::Seconds,
+        "AI request latency"
+    );
+    describe_histogram!(REQUEST_COST_USD, "Estimated cost per AI request in USD");
+}
+
+/// Register descriptions and units with the installed recorder
+#[cfg(not(feature = "metrics"))]
+pub fn describe_metrics() {}
+
+/// Report a completed request
+#[cfg(feature = "metrics")]
+pub fn record_request(request: &RequestMetrics<'_>) {
+    let labels = [
+        ("provider", request.provider.to_string()),
+        ("request_type", request.request_type.to_string()),
+    ];
+
+    metrics::counter!(REQUESTS_TOTAL, &labels).increment(1);
+    if request.cache_hit {
+        metrics::counter!(CACHE_HITS_TOTAL, &labels).increment(1);
+    }
+    metrics::counter!(TOKENS_TOTAL, &labels).increment(request.tokens as u64);
+    metrics::gauge!(COST_USD_TOTAL, &labels).increment(request.cost);
+    metrics::histogram!(REQUEST_DURATION_SECONDS, &labels).record(request.duration.as_secs_f64());
+    metrics::histogram!(REQUEST_COST_USD, &labels).record(request.cost);
+}
+
+/// Report a completed request
+#[cfg(not(feature = "metrics"))]
+pub fn record_request(_request: &RequestMetrics<'_>) {}
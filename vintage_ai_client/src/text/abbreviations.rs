@@ -0,0 +1,429 @@
+//! Abbreviations that fit dialogue into retro text boxes
+//!
+//! Like the localizations of the era, lines that overflow an 8px-font text
+//! box are shortened with a substitution dictionary rather than rewritten.
+//! [`AbbreviationTable::build`] adds abbreviations one at a time, always the
+//! one that saves the most characters in the lines that still overflow:
+//! first the standard RPG shorthands ("Experience" to "Exp."), then long
+//! words with their vowels dropped. Words only ever written capitalized are
+//! taken for names and left alone. The finished table is applied to
+//! every line, so a word reads the same everywhere, and exported as an
+//! expansion table for tooling.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use super::dialogue::DialogueTree;
+
+/// Shorthands players of the era know
+const STANDARD: &[(&str, &str)] = &[
+    ("experience", "exp."),
+    ("hit points", "HP"),
+    ("magic points", "MP"),
+    ("level", "lv."),
+    ("magic", "mgc."),
+    ("attack", "atk."),
+    ("defense", "def."),
+    ("strength", "str."),
+    ("intelligence", "int."),
+    ("agility", "agi."),
+    ("potion", "potn."),
+    ("equipment", "equip."),
+];
+
+/// Words shorter than this are never abbreviated by vowel dropping
+const MIN_WORD_LEN: usize = 7;
+
+/// Size of a dialogue window in characters of the 8px font
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextBox {
+    pub columns: usize,
+    pub lines: usize,
+}
+
+impl Default for TextBox {
+    /// A 256px wide SNES window with its border
+    fn default() -> Self {
+        Self {
+            columns: 28,
+            lines: 3,
+        }
+    }
+}
+
+impl TextBox {
+    /// Lines the text takes when word-wrapped, breaking words longer than a
+    /// line
+    pub fn lines_needed(&self, text: &str) -> usize {
+        let columns = self.columns.max(1);
+        let mut lines = 1;
+        let mut used = 0;
+        for word in text.split_whitespace() {
+            let len = word.chars().count();
+            let needed = if used == 0 { len } else { used + 1 + len };
+            if needed <= columns {
+                used = needed;
+                continue;
+            }
+            if used > 0 {
+                lines += 1;
+            }
+            lines += (len - 1) / columns;
+            used = (len - 1) % columns + 1;
+        }
+        lines
+    }
+
+    pub fn fits(&self, text: &str) -> bool {
+        self.lines_needed(text) <= self.lines
+    }
+
+    /// Choices are listed one per line behind a cursor
+    pub fn fits_choice(&self, text: &str) -> bool {
+        text.chars().count() + 2 <= self.columns
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Abbreviation {
+    /// Lowercase phrase as written out
+    pub full: String,
+    pub short: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbbreviationTable {
+    pub text_box: TextBox,
+    pub entries: Vec<Abbreviation>,
+}
+
+impl AbbreviationTable {
+    /// Collect the abbreviations the dialogue needs to fit the text box
+    pub fn build(dialogues: &[DialogueTree], text_box: TextBox) -> Self {
+        let mut table = Self {
+            text_box,
+            entries: Vec::new(),
+        };
+        let lines: Vec<(&str, bool)> = dialogues
+            .iter()
+            .flat_map(|tree| &tree.nodes)
+            .flat_map(|node| {
+                std::iter::once((node.text.as_str(), false)).chain(
+                    node.choices
+                        .iter()
+                        .map(|choice| (choice.text.as_str(), true)),
+                )
+            })
+            .collect();
+        let words: Vec<&str> = lines
+            .iter()
+            .flat_map(|(text, _)| text.split(|c: char| !c.is_alphanumeric()))
+            .collect();
+        let lowercase: Vec<String> = words.iter().map(|word| word.to_lowercase()).collect();
+
+        let mut candidates: Vec<Abbreviation> = STANDARD
+            .iter()
+            .map(|&(full, short)| Abbreviation {
+                full: full.to_string(),
+                short: short.to_string(),
+            })
+            .collect();
+        for word in &words {
+            if word.chars().count() < MIN_WORD_LEN
+                || !word.starts_with(char::is_lowercase)
+                || candidates.iter().any(|candidate| candidate.full == *word)
+            {
+                continue;
+            }
+            if let Some(short) = drop_vowels(word, &lowercase, &candidates) {
+                candidates.push(Abbreviation {
+                    full: word.to_string(),
+                    short,
+                });
+            }
+        }
+
+        let mut standard = STANDARD.len();
+        loop {
+            let overflowing: Vec<String> = lines
+                .iter()
+                .map(|&(text, choice)| (table.apply(text), choice))
+                .filter(|(text, choice)| {
+                    if *choice {
+                        !text_box.fits_choice(text)
+                    } else {
+                        !text_box.fits(text)
+                    }
+                })
+                .map(|(text, _)| text)
+                .collect();
+            if overflowing.is_empty() {
+                break;
+            }
+            let best = candidates
+                .iter()
+                .enumerate()
+                .map(|(index, candidate)| {
+                    let saved = candidate.full.chars().count() - candidate.short.chars().count();
+                    let uses: usize = overflowing
+                        .iter()
+                        .map(|text| find_words(text, &candidate.full).len())
+                        .sum();
+                    (index, saved * uses)
+                })
+                .filter(|&(_, savings)| savings > 0)
+                // Standard shorthands come first in the list and win
+                .max_by_key(|&(index, savings)| {
+                    (index < standard, savings, std::cmp::Reverse(index))
+                });
+            let Some((index, _)) = best else {
+                break;
+            };
+            if index < standard {
+                standard -= 1;
+            }
+            table.entries.push(candidates.remove(index));
+        }
+        table
+    }
+
+    /// Replace every listed phrase, keeping a leading capital
+    pub fn apply(&self, text: &str) -> String {
+        self.entries.iter().fold(text.to_string(), |text, entry| {
+            replace_words(&text, &entry.full, &entry.short)
+        })
+    }
+
+    /// Write the abbreviations out again
+    pub fn expand(&self, text: &str) -> String {
+        self.entries
+            .iter()
+            .rev()
+            .fold(text.to_string(), |text, entry| {
+                replace_words(&text, &entry.short, &entry.full)
+            })
+    }
+
+    /// The dialogue with the table applied to every line and choice
+    pub fn compress(&self, dialogues: &[DialogueTree]) -> Vec<DialogueTree> {
+        let mut dialogues = dialogues.to_vec();
+        for node in dialogues.iter_mut().flat_map(|tree| &mut tree.nodes) {
+            node.text = self.apply(&node.text);
+            for choice in &mut node.choices {
+                choice.text = self.apply(&choice.text);
+            }
+        }
+        dialogues
+    }
+
+    /// Ids of the nodes that overflow the text box even when compressed, as
+    /// `(tree, node)`
+    pub fn overflowing<'a>(&self, dialogues: &'a [DialogueTree]) -> Vec<(&'a str, &'a str)> {
+        dialogues
+            .iter()
+            .flat_map(|tree| tree.nodes.iter().map(move |node| (tree, node)))
+            .filter(|(_, node)| {
+                !self.text_box.fits(&self.apply(&node.text))
+                    || node
+                        .choices
+                        .iter()
+                        .any(|choice| !self.text_box.fits_choice(&self.apply(&choice.text)))
+            })
+            .map(|(tree, node)| (tree.id.as_str(), node.id.as_str()))
+            .collect()
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Keep the first letter and the consonants, e.g. "mountain" to "mntn.",
+/// unless that spells a word of the text or another abbreviation
+fn drop_vowels(word: &str, words: &[String], taken: &[Abbreviation]) -> Option<String> {
+    let mut chars = word.chars();
+    let first = chars.next()?;
+    let consonants: String = chars
+        .filter(|c| !matches!(c, 'a' | 'e' | 'i' | 'o' | 'u'))
+        .collect();
+    let short = format!("{first}{consonants}.");
+    let clashes = words.iter().any(|w| *w == short[..short.len() - 1])
+        || taken.iter().any(|entry| entry.short == short);
+    (!clashes && short.chars().count() < word.chars().count()).then_some(short)
+}
+
+/// Byte ranges of case-insensitive whole-word matches of the phrase
+fn find_words(text: &str, phrase: &str) -> Vec<(usize, usize)> {
+    let lower = text.to_lowercase();
+    let phrase = phrase.to_lowercase();
+    // Lowercasing can change byte lengths, matches are only mapped back
+    // when it didn't
+    if lower.len() != text.len() || phrase.is_empty() {
+        return Vec::new();
+    }
+    let is_word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+    let mut matches = Vec::new();
+    let mut from = 0;
+    while let Some(offset) = lower[from..].find(&phrase) {
+        let start = from + offset;
+        let end = start + phrase.len();
+        if !is_word(lower[..start].chars().next_back()) && !is_word(lower[end..].chars().next()) {
+            matches.push((start, end));
+        }
+        from = end;
+    }
+    matches
+}
+
+fn replace_words(text: &str, from: &str, to: &str) -> String {
+    let mut result = String::new();
+    let mut last = 0;
+    for (start, end) in find_words(text, from) {
+        result.push_str(&text[last..start]);
+        let capital = text[start..].chars().next().is_some_and(char::is_uppercase);
+        // A full stop after the word already ends the abbreviation
+        let to = match to.strip_suffix('.') {
+            Some(stripped) if text[end..].starts_with('.') => stripped,
+            _ => to,
+        };
+        let mut to_chars = to.chars();
+        match to_chars.next() {
+            Some(first) if capital => result.extend(first.to_uppercase().chain(to_chars)),
+            _ => result.push_str(to),
+        }
+        last = end;
+    }
+    result.push_str(&text[last..]);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::dialogue::DialogueNode;
+
+    fn dialogue(lines: &[&str]) -> Vec<DialogueTree> {
+        let nodes = lines
+            .iter()
+            .enumerate()
+            .map(|(index, text)| DialogueNode {
+                id: format!("n{index}"),
+                speaker: "Sage".to_string(),
+                text: text.to_string(),
+                set_flags: Vec::new(),
+                choices: Vec::new(),
+                next: None,
+            })
+            .collect();
+        vec![DialogueTree {
+            id: "sage".to_string(),
+            character: "Sage".to_string(),
+            start: "n0".to_string(),
+            nodes,
+        }]
+    }
+
+    fn table(entries: &[(&str, &str)]) -> AbbreviationTable {
+        AbbreviationTable {
+            text_box: TextBox::default(),
+            entries: entries
+                .iter()
+                .map(|&(full, short)| Abbreviation {
+                    full: full.to_string(),
+                    short: short.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn word_wrapping() {
+        let text_box = TextBox {
+            columns: 10,
+            lines: 2,
+        };
+        assert_eq!(text_box.lines_needed(""), 1);
+        assert_eq!(text_box.lines_needed("hello you"), 1);
+        assert_eq!(text_box.lines_needed("hello world"), 2);
+        // Words longer than a line are broken, and the rest of the last
+        // piece is filled up
+        assert_eq!(text_box.lines_needed("abcdefghijklmnopqrstuvwxy ab"), 3);
+        assert_eq!(text_box.lines_needed("hi abcdefghijklmnop"), 3);
+        assert!(text_box.fits("hello world"));
+        assert!(!text_box.fits("hello world, hello"));
+        assert!(text_box.fits_choice("12345678"));
+        assert!(!text_box.fits_choice("123456789"));
+    }
+
+    #[test]
+    fn whole_words_only() {
+        assert_eq!(
+            find_words("level levels relevel Level.", "level"),
+            [(0, 5), (21, 26)]
+        );
+        assert!(find_words("level", "").is_empty());
+        // Matches aren't mapped back when lowercasing changes byte lengths
+        assert!(find_words("İlevel level", "level").is_empty());
+    }
+
+    #[test]
+    fn replacement_keeps_capitals_and_full_stops() {
+        assert_eq!(
+            replace_words("Experience is key", "experience", "exp."),
+            "Exp. is key"
+        );
+        assert_eq!(
+            replace_words("Gain experience.", "experience", "exp."),
+            "Gain exp."
+        );
+        assert_eq!(
+            replace_words("inexperience", "experience", "exp."),
+            "inexperience"
+        );
+    }
+
+    #[test]
+    fn apply_and_expand_round_trip() {
+        let table = table(&[("experience", "exp."), ("potion", "potn.")]);
+        let text = "Experience and potion for you";
+        let short = table.apply(text);
+        assert_eq!(short, "Exp. and potn. for you");
+        assert_eq!(table.expand(&short), text);
+    }
+
+    #[test]
+    fn build_prefers_standard_shorthands() {
+        let text_box = TextBox {
+            columns: 20,
+            lines: 1,
+        };
+        let dialogues = dialogue(&["Your experience grows", "Short line"]);
+        let table = AbbreviationTable::build(&dialogues, text_box);
+        assert_eq!(
+            table.entries,
+            self::table(&[("experience", "exp.")]).entries
+        );
+        assert_eq!(
+            table.compress(&dialogues)[0].nodes[0].text,
+            "Your exp. grows"
+        );
+        assert!(table.overflowing(&dialogues).is_empty());
+    }
+
+    #[test]
+    fn build_leaves_names_alone() {
+        let text_box = TextBox {
+            columns: 20,
+            lines: 1,
+        };
+        let dialogues = dialogue(&["Bartholomew Bartholomew Bartholomew"]);
+        let table = AbbreviationTable::build(&dialogues, text_box);
+        assert!(table.entries.is_empty());
+        assert_eq!(table.overflowing(&dialogues), [("sage", "n0")]);
+
+        // Vowel dropping never spells a word of the text
+        let words = ["mntn".to_string()];
+        assert_eq!(drop_vowels("mountain", &words, &[]), None);
+        assert_eq!(drop_vowels("mountain", &[], &[]).as_deref(), Some("mntn."));
+    }
+}
@@ -0,0 +1,319 @@
+//! Branching NPC dialogue trees
+//!
+//! A [`DialogueTree`] is a set of nodes, each a line spoken by a character.
+//! A node either continues to the next node, offers choices that lead to
+//! other nodes, or ends the conversation. Choices can be gated by
+//! [`Condition`]s on story flags and items, and nodes and choices set flags
+//! when they are reached. Trees are generated from a character sheet and
+//! plot context, validated for broken links, and exported as JSON or as a
+//! Yarn Spinner script.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Write;
+
+use super::{TextConfig, TextGenerator, with_content_language};
+use crate::game_types::Character;
+
+/// Requirement for a choice to be offered
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Condition {
+    /// The story flag is set
+    Flag { name: String },
+    /// The story flag is not set
+    NotFlag { name: String },
+    /// The party carries the item
+    HasItem { item: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DialogueChoice {
+    pub text: String,
+    /// Node the choice leads to
+    pub target: String,
+    #[serde(default)]
+    pub condition: Option<Condition>,
+    #[serde(default)]
+    pub set_flags: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DialogueNode {
+    pub id: String,
+    pub speaker: String,
+    pub text: String,
+    /// Flags set when the node is shown
+    #[serde(default)]
+    pub set_flags: Vec<String>,
+    #[serde(default)]
+    pub choices: Vec<DialogueChoice>,
+    /// Node that follows when there are no choices; `None` ends the dialogue
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DialogueTree {
+    pub id: String,
+    /// Character the player talks to
+    pub character: String,
+    /// Id of the opening node
+    pub start: String,
+    pub nodes: Vec<DialogueNode>,
+}
+
+/// A structural problem of a dialogue tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DialogueIssue {
+    MissingStart(String),
+    DuplicateNode(String),
+    BrokenLink { from: String, to: String },
+    Unreachable(String),
+    NodeWithNextAndChoices(String),
+}
+
+impl std::fmt::Display for DialogueIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingStart(id) => write!(f, "start node '{id}' does not exist"),
+            Self::DuplicateNode(id) => write!(f, "node id '{id}' is used twice"),
+            Self::BrokenLink { from, to } => {
+                write!(f, "node '{from}' links to missing node '{to}'")
+            }
+            Self::Unreachable(id) => write!(f, "node '{id}' cannot be reached"),
+            Self::NodeWithNextAndChoices(id) => {
+                write!(f, "node '{id}' has both a next node and choices")
+            }
+        }
+    }
+}
+
+impl DialogueTree {
+    pub fn node(&self, id: &str) -> Option<&DialogueNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    /// Every flag the tree sets or checks, in order of appearance
+    pub fn flags(&self) -> Vec<&str> {
+        let mut flags: Vec<&str> = Vec::new();
+        for node in &self.nodes {
+            let choice_flags = node.choices.iter().flat_map(|choice| {
+                let checked = match &choice.condition {
+                    Some(Condition::Flag { name } | Condition::NotFlag { name }) => {
+                        Some(name.as_str())
+                    }
+                    _ => None,
+                };
+                checked
+                    .into_iter()
+                    .chain(choice.set_flags.iter().map(String::as_str))
+            });
+            for flag in node
+                .set_flags
+                .iter()
+                .map(String::as_str)
+                .chain(choice_flags)
+            {
+                if !flags.contains(&flag) {
+                    flags.push(flag);
+                }
+            }
+        }
+        flags
+    }
+
+    /// Check that every link resolves and every node can be reached
+    pub fn validate(&self) -> Vec<DialogueIssue> {
+        let mut issues = Vec::new();
+
+        let mut ids = HashSet::new();
+        for node in &self.nodes {
+            if !ids.insert(node.id.as_str()) {
+                issues.push(DialogueIssue::DuplicateNode(node.id.clone()));
+            }
+            if node.next.is_some() && !node.choices.is_empty() {
+                issues.push(DialogueIssue::NodeWithNextAndChoices(node.id.clone()));
+            }
+            for target in links(node) {
+                if self.node(target).is_none() {
+                    issues.push(DialogueIssue::BrokenLink {
+                        from: node.id.clone(),
+                        to: target.to_string(),
+                    });
+                }
+            }
+        }
+
+        if self.node(&self.start).is_none() {
+            issues.push(DialogueIssue::MissingStart(self.start.clone()));
+            return issues;
+        }
+
+        let mut reached = HashSet::from([self.start.as_str()]);
+        let mut queue = VecDeque::from([self.start.as_str()]);
+        while let Some(id) = queue.pop_front() {
+            let Some(node) = self.node(id) else {
+                continue;
+            };
+            for target in links(node) {
+                if reached.insert(target) {
+                    queue.push_back(target);
+                }
+            }
+        }
+        for node in &self.nodes {
+            if !reached.contains(node.id.as_str()) {
+                issues.push(DialogueIssue::Unreachable(node.id.clone()));
+            }
+        }
+
+        issues
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Export as a Yarn Spinner 2 script
+    ///
+    /// Flags become boolean variables, declared in the start node. Item
+    /// conditions call a `has_item` function that the game registers with
+    /// its dialogue runner.
+    pub fn to_yarn(&self) -> String {
+        let mut yarn = String::new();
+
+        // Yarn starts at the first node of the file
+        let start = self.nodes.iter().filter(|node| node.id == self.start);
+        let rest = self.nodes.iter().filter(|node| node.id != self.start);
+        for node in start.chain(rest) {
+            let _ = writeln!(yarn, "title: {}", yarn_identifier(&node.id));
+            let _ = writeln!(yarn, "tags: {}", yarn_identifier(&self.character));
+            yarn.push_str("---\n");
+            if node.id == self.start {
+                for flag in self.flags() {
+                    let _ = writeln!(yarn, "<<declare ${} = false>>", yarn_identifier(flag));
+                }
+            }
+            let _ = writeln!(yarn, "{}: {}", node.speaker, node.text);
+            for flag in &node.set_flags {
+                let _ = writeln!(yarn, "<<set ${} to true>>", yarn_identifier(flag));
+            }
+            for choice in &node.choices {
+                let _ = write!(yarn, "-> {}", choice.text);
+                if let Some(condition) = &choice.condition {
+                    let _ = write!(yarn, " <<if {}>>", yarn_condition(condition));
+                }
+                yarn.push('\n');
+                for flag in &choice.set_flags {
+                    let _ = writeln!(yarn, "    <<set ${} to true>>", yarn_identifier(flag));
+                }
+                let _ = writeln!(yarn, "    <<jump {}>>", yarn_identifier(&choice.target));
+            }
+            if let Some(next) = &node.next {
+                let _ = writeln!(yarn, "<<jump {}>>", yarn_identifier(next));
+            }
+            yarn.push_str("===\n");
+        }
+
+        yarn
+    }
+}
+
+fn links(node: &DialogueNode) -> impl Iterator<Item = &str> {
+    node.choices
+        .iter()
+        .map(|choice| choice.target.as_str())
+        .chain(node.next.as_deref())
+}
+
+/// Yarn node titles and variables only allow letters, digits and underscores
+fn yarn_identifier(name: &str) -> String {
+    let mut identifier: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if identifier.is_empty() || identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert(0, '_');
+    }
+    identifier
+}
+
+fn yarn_condition(condition: &Condition) -> String {
+    match condition {
+        Condition::Flag { name } => format!("${}", yarn_identifier(name)),
+        Condition::NotFlag { name } => format!("not ${}", yarn_identifier(name)),
+        Condition::HasItem { item } => format!("has_item(\"{}\")", item.replace('"', "'")),
+    }
+}
+
+/// Generate a dialogue tree for a character at a point of the plot, written
+/// in `content_language` if one is given
+///
+/// A tree with structural issues is sent back once with the issues listed;
+/// if the second tree is still broken the issues are returned as an error.
+pub async fn generate_dialogue_tree(
+    generator: &TextGenerator,
+    character: &Character,
+    plot_context: &str,
+    content_language: Option<&str>,
+) -> Result<DialogueTree> {
+    let prompt = format!(
+        "Write a branching conversation with {name}, the {role}, for a 16-bit RPG.\n\
+        Personality: {personality}\n\
+        Backstory: {backstory}\n\
+        Current plot: {plot_context}\n\n\
+        Keep every line short enough for a classic text box. Offer the player two or \
+        three choices at important moments, let some choices depend on story flags or \
+        items, and set flags for what the player learned or promised.\n\n\
+        Return JSON of the form {{\"id\": \"string\", \"character\": \"{name}\", \
+        \"start\": \"node_id\", \"nodes\": [{{\"id\": \"node_id\", \"speaker\": \"string\", \
+        \"text\": \"string\", \"set_flags\": [\"flag\"], \"choices\": [{{\"text\": \"string\", \
+        \"target\": \"node_id\", \"condition\": {{\"type\": \"flag|not_flag\", \"name\": \
+        \"flag\"}} or {{\"type\": \"has_item\", \"item\": \"item name\"}} or null, \
+        \"set_flags\": []}}], \"next\": \"node_id or null\"}}]}}. A node has either choices \
+        or a next node; both empty ends the conversation.",
+        name = character.name,
+        role = character.role,
+        personality = character.personality,
+        backstory = character.backstory,
+    );
+    let prompt = with_content_language(prompt, content_language);
+    let config = TextConfig {
+        max_tokens: 3000,
+        ..TextConfig::for_dialogue()
+    };
+
+    let tree: DialogueTree = generator
+        .generate_structured(&prompt, config.clone())
+        .await?;
+    let issues = tree.validate();
+    if issues.is_empty() {
+        return Ok(tree);
+    }
+
+    let retry_prompt = format!(
+        "{prompt}\n\nA previous tree had these problems, avoid them: {}.",
+        issues
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ")
+    );
+    let tree: DialogueTree = generator.generate_structured(&retry_prompt, config).await?;
+    let issues = tree.validate();
+    if !issues.is_empty() {
+        anyhow::bail!(
+            "Dialogue tree for {} is broken: {}",
+            character.name,
+            issues
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("; ")
+        );
+    }
+    Ok(tree)
+}
@@ -2,18 +2,20 @@
 //!
 //! Handles generation of:
 //! - Game descriptions and narratives
-//! - Character dialogues and backstories
+//! - Character dialogues and backstories, including branching dialogue trees
+//!   abbreviated to fit retro text boxes
 //! - Quest text and world lore
+//! - Names of minor NPCs, towns and items, generated locally from the lore
 //! - Code generation for game mechanics
 //! - Documentation and tutorials
 
 use anyhow::{Context, Result};
 use async_openai::{
     Client,
-    config::OpenAIConfig,
     types::chat::{
         ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs,
-        CreateChatCompletionRequestArgs,
+        CreateChatCompletionRequestArgs, CreateChatCompletionResponse, FinishReason,
+        ResponseFormat, ResponseFormatJsonSchema,
     },
 };
 use serde::{Deserialize, Serialize};
@@ -22,15 +24,22 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 
 use super::{
-    AiGenerator,
+    AiGenerator, ParameterProfile,
     cache::{AiCache, CachedData},
-    tokens::TokenCounter,
+    credentials::{KeyedConfig, retry_keys},
+    quota, refusals,
+    telemetry::RequestTimer,
+    tokens::{CostEstimate, TokenCounter},
 };
 
+pub mod abbreviations;
+pub mod dialogue;
+pub mod names;
+
 /// Text generator for all text-based content
 #[derive(Clone)]
 pub struct TextGenerator {
-    client: Arc<Client<OpenAIConfig>>,
+    client: Arc<Client<KeyedConfig>>,
     cache: Arc<Mutex<AiCache>>,
     token_counter: Arc<Mutex<TokenCounter>>,
 }
@@ -52,6 +61,10 @@ pub struct TextConfig {
     pub presence_penalty: f32,
     /// System prompt for context
     pub system_prompt: Option<String>,
+    /// Sampling seed sent with the request, so providers that support it
+    /// answer a repeated request the same way as far as they can
+    #[serde(default)]
+    pub seed: Option<u64>,
 }
 
 impl Default for TextConfig {
@@ -64,12 +77,34 @@ impl Default for TextConfig {
             frequency_penalty: 0.0,
             presence_penalty: 0.0,
             system_prompt: None,
+            seed: None,
         }
     }
 }
 
 /// Specialized configurations for different text types
 impl TextConfig {
+    /// Apply the sampling parameters of a generation profile
+    pub fn with_profile(mut self, profile: &ParameterProfile) -> Self {
+        self.temperature = profile.temperature;
+        if let Some(top_p) = profile.top_p {
+            self.top_p = top_p;
+        }
+        if let Some(penalty) = profile.frequency_penalty {
+            self.frequency_penalty = penalty;
+        }
+        if let Some(penalty) = profile.presence_penalty {
+            self.presence_penalty = penalty;
+        }
+        self
+    }
+
+    /// Send `seed` with the request; `None` leaves sampling unseeded
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
     /// Configuration for game descriptions
     pub fn for_game_description() -> Self {
         Self {
@@ -136,10 +171,30 @@ impl TextConfig {
     }
 }
 
+/// Ask for the player-facing text of `prompt` in `language`, e.g.
+/// `Japanese` or `es-ES`. `None` leaves the prompt as it is.
+///
+/// The instruction goes into the prompt rather than the system prompt so
+/// cached answers are kept apart per language. Keys, ids and enumerated
+/// values stay in English so structured answers still parse.
+pub fn with_content_language(prompt: String, language: Option<&str>) -> String {
+    match language
+        .map(str::trim)
+        .filter(|language| !language.is_empty())
+    {
+        Some(language) => format!(
+            "{prompt}\n\nWrite all player-facing text (names, descriptions, dialogue, lore and \
+            taglines) in {language}. Keep JSON keys, ids and enumerated values such as types in \
+            English."
+        ),
+        None => prompt,
+    }
+}
+
 impl TextGenerator {
     /// Create a new text generator
     pub fn new(
-        client: Arc<Client<OpenAIConfig>>,
+        client: Arc<Client<KeyedConfig>>,
         cache: Arc<Mutex<AiCache>>,
         token_counter: Arc<Mutex<TokenCounter>>,
     ) -> Self {
@@ -150,24 +205,70 @@ impl TextGenerator {
         }
     }
 
-    /// Generate text with caching and token tracking
+    /// Generate text with caching and token tracking. A refused prompt is
+    /// retried once sanitized, see [`refusals::retry_sanitized`].
     pub async fn generate(&self, prompt: &str, config: TextConfig) -> Result<String> {
-        // Generate cache key
-        let mut params = HashMap::new();
-        params.insert("model".to_string(), config.model.clone());
-        params.insert("temperature".to_string(), config.temperature.to_string());
-        params.insert("max_tokens".to_string(), config.max_tokens.to_string());
+        refusals::retry_sanitized(prompt, |prompt| {
+            let config = config.clone();
+            async move { self.generate_once(&prompt, config, None).await }
+        })
+        .await
+    }
+
+    /// Generate JSON that follows `schema`, a JSON Schema sent to the model
+    /// as its response format under `name`. The schema isn't enforced
+    /// strictly, so callers still validate what comes back.
+    pub async fn generate_with_schema(
+        &self,
+        prompt: &str,
+        config: TextConfig,
+        name: &str,
+        schema: serde_json::Value,
+    ) -> Result<String> {
+        let format = ResponseFormat::JsonSchema {
+            json_schema: ResponseFormatJsonSchema {
+                description: None,
+                name: name.to_string(),
+                schema: Some(schema),
+                strict: Some(false),
+            },
+        };
+        refusals::retry_sanitized(prompt, |prompt| {
+            let config = config.clone();
+            let format = &format;
+            async move { self.generate_once(&prompt, config, Some(format)).await }
+        })
+        .await
+    }
+
+    async fn generate_once(
+        &self,
+        prompt: &str,
+        config: TextConfig,
+        response_format: Option<&ResponseFormat>,
+    ) -> Result<String> {
+        let timer = RequestTimer::start("text");
 
+        // Generate cache key
+        let mut params = cache_params(&config);
+        if let Some(format) = response_format {
+            params.insert(
+                "response_format".to_string(),
+                serde_json::to_string(format)?,
+            );
+        }
         let cache_key = self
             .cache
             .lock()
             .await
-            .generate_key("text", prompt, &params);
+            .key_for("text", prompt, &params)
+            .await;
 
         // Check cache first
         if let Some(cached) = self.cache.lock().await.get(&cache_key).await
             && let CachedData::Text(text) = cached.data
         {
+            timer.cache_hit(self.client.config().provider());
             return Ok(text);
         }
 
@@ -191,34 +292,49 @@ impl TextGenerator {
         );
 
         // Create request
-        let request = CreateChatCompletionRequestArgs::default()
+        let mut request = CreateChatCompletionRequestArgs::default();
+        request
             .model(&config.model)
             .messages(messages)
             .temperature(config.temperature)
             .max_tokens(config.max_tokens)
             .top_p(config.top_p)
             .frequency_penalty(config.frequency_penalty)
-            .presence_penalty(config.presence_penalty)
-            .build()?;
+            .presence_penalty(config.presence_penalty);
+        if let Some(format) = response_format {
+            request.response_format(format.clone());
+        }
+        if let Some(seed) = config.seed {
+            // The API takes a signed seed; keep the bits of the unsigned one
+            request.seed(seed as i64);
+        }
+        let request = request.build()?;
 
         // Make API call
-        let response = self
-            .client
-            .chat()
-            .create(request)
-            .await
-            .context("Failed to generate text")?;
-
-        // Extract text
-        let text = response
-            .choices
-            .first()
-            .and_then(|choice| choice.message.content.clone())
-            .unwrap_or_default();
+        let response: CreateChatCompletionResponse = retry_keys(&self.client, || {
+            quota::send(&self.client, "/chat/completions", &request)
+        })
+        .await
+        .context("Failed to generate text")?;
+
+        // Extract text, unless the model refused
+        let choice = response.choices.first();
+        let content = choice.and_then(|choice| choice.message.content.clone());
+        if let Some(message) = refusals::refusal_in_response(
+            content.as_deref(),
+            choice.and_then(|choice| choice.message.refusal.as_deref()),
+            choice.is_some_and(|choice| choice.finish_reason == Some(FinishReason::ContentFilter)),
+        ) {
+            return Err(refusals::Refused { message }.into());
+        }
+        let text = content.unwrap_or_default();
 
         // Track tokens
+        let (mut tokens, mut cost) = (0, 0.0);
         if let Some(usage) = response.usage {
-            self.token_counter
+            tokens = usage.total_tokens as usize;
+            cost = self
+                .token_counter
                 .lock()
                 .await
                 .record_usage(
@@ -228,6 +344,7 @@ impl TextGenerator {
                 )
                 .await?;
         }
+        timer.finish(self.client.config().provider(), tokens, cost, false);
 
         // Cache result
         let cache_params: HashMap<String, serde_json::Value> = params
@@ -244,6 +361,29 @@ impl TextGenerator {
         Ok(text)
     }
 
+    /// Tokens and cost of generating `prompt` with `config`, without
+    /// making the request. A prompt the cache answers costs nothing.
+    pub async fn estimate(&self, prompt: &str, config: &TextConfig) -> Result<CostEstimate> {
+        let cache_key = self
+            .cache
+            .lock()
+            .await
+            .key_for("text", prompt, &cache_params(config))
+            .await;
+        if self.cache.lock().await.get(&cache_key).await.is_some() {
+            return Ok(CostEstimate::cached());
+        }
+
+        let mut messages = Vec::new();
+        if let Some(system) = &config.system_prompt {
+            messages.push(("system", system.as_str()));
+        }
+        messages.push(("user", prompt));
+        let counter = self.token_counter.lock().await;
+        let prompt_tokens = counter.count_chat_tokens(&messages, &config.model)?;
+        Ok(counter.estimate_chat(&config.model, prompt_tokens, config.max_tokens as usize))
+    }
+
     /// Generate multiple related texts (e.g., character descriptions)
     pub async fn generate_batch(
         &self,
@@ -333,7 +473,7 @@ impl AiGenerator for TextGenerator {
 
     async fn estimate_cost(&self, request: &str) -> Result<f64> {
         let counter = self.token_counter.lock().await;
-        counter.estimate_cost(request, "gpt-3.5-turbo", 1000)
+        counter.estimate_cost("gpt-3.5-turbo", request, 1000)
     }
 
     async fn is_cached(&self, key: &str) -> bool {
@@ -345,6 +485,18 @@ impl AiGenerator for TextGenerator {
     }
 }
 
+/// Request settings a generated text is cached under
+fn cache_params(config: &TextConfig) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+    params.insert("model".to_string(), config.model.clone());
+    params.insert("temperature".to_string(), config.temperature.to_string());
+    params.insert("max_tokens".to_string(), config.max_tokens.to_string());
+    if let Some(seed) = config.seed {
+        params.insert("seed".to_string(), seed.to_string());
+    }
+    params
+}
+
 /// Specialized generators for game content
 pub mod game_content {
     use super::*;
@@ -0,0 +1,510 @@
+//! Local name generation
+//!
+//! Minor NPCs, towns and items don't need a model call to be named. The
+//! [`NameGenerator`] splits the proper names of the generated lore into
+//! syllables and recombines first, inner and last syllables into new names,
+//! so they sound like the world they come from. Words every setting shares
+//! ("Castle", "of", "Sword") are not mined for syllables: place words seen
+//! in town names become town descriptors, and the last words of item names
+//! become the bases of item names. Generation is seeded and never repeats a
+//! name it has produced or learned. [`NameGenerator::name_world`] fills in
+//! the minor NPCs, towns and treasures the world prompt leaves unnamed.
+
+use std::collections::HashSet;
+
+use crate::game_types::{GameConfig, WorldData};
+use crate::maps::hash;
+
+/// Words that are part of names in every setting
+const GENERIC_WORDS: &str = "\
+    a an and armor bay blade bow bridge castle cave cavern caves city crystal \
+    dark desert east elder fire forest fort gate great helm hills holy ice \
+    island isle keep king lady lake lord lost marsh mountain mountains new \
+    north of old peak plains port potion queen ring river road ruins sea \
+    shield shrine sir south staff swamp sword temple the tower town valley \
+    village west woods";
+
+/// Syllables used while the lore is too thin to draw from
+const FALLBACK_STARTS: &[&str] = &["al", "bra", "cor", "da", "el", "fen", "gar", "ka", "mor"];
+const FALLBACK_ENDS: &[&str] = &["an", "dor", "ia", "is", "on", "ra", "wyn"];
+
+/// Item bases used when no item names were learned
+const FALLBACK_ITEM_NOUNS: &[&str] = &["Amulet", "Blade", "Charm", "Tonic"];
+
+/// Attempts at a name that is new and pronounceable
+const MAX_ATTEMPTS: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameKind {
+    Person,
+    Town,
+    Item,
+}
+
+/// Place word of a town name and whether it comes before the name
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Descriptor {
+    word: String,
+    prefix: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct NameGenerator {
+    starts: Vec<String>,
+    middles: Vec<String>,
+    ends: Vec<String>,
+    descriptors: Vec<Descriptor>,
+    item_nouns: Vec<String>,
+    /// Lowercase names that must not be produced
+    taken: HashSet<String>,
+    rng: u64,
+}
+
+impl NameGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            starts: Vec::new(),
+            middles: Vec::new(),
+            ends: Vec::new(),
+            descriptors: Vec::new(),
+            item_nouns: Vec::new(),
+            taken: HashSet::new(),
+            rng: hash(&[seed]),
+        }
+    }
+
+    /// Seed with the game's name and generation seed and learn from its
+    /// world, towns, dungeons and characters
+    pub fn from_config(config: &GameConfig) -> Self {
+        let seed = config.procedural_seed(&config.name);
+        let world = &config.world;
+        let lore = std::iter::once(&world.name)
+            .chain(world.regions.iter().map(|region| &region.name))
+            .chain(
+                world
+                    .regions
+                    .iter()
+                    .flat_map(|region| &region.key_locations),
+            )
+            .chain(config.towns.iter().flat_map(|town| &town.key_npcs))
+            .chain(config.dungeons.iter().map(|dungeon| &dungeon.name))
+            .chain(config.dungeons.iter().map(|dungeon| &dungeon.boss))
+            .chain(config.characters.iter().map(|character| &character.name));
+        Self::new(seed)
+            .with_lore(lore)
+            .with_towns(config.towns.iter().map(|town| &town.name))
+            .with_items(
+                config
+                    .dungeons
+                    .iter()
+                    .flat_map(|dungeon| &dungeon.treasures),
+            )
+    }
+
+    /// Learn syllables from proper names
+    pub fn with_lore<S: AsRef<str>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        for name in names {
+            self.learn(name.as_ref());
+        }
+        self
+    }
+
+    /// Learn syllables and place words from town names
+    pub fn with_towns<S: AsRef<str>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        for name in names {
+            let name = name.as_ref();
+            self.learn(name);
+            let words: Vec<&str> = name.split_whitespace().collect();
+            for (index, word) in words.iter().enumerate() {
+                if words.len() < 2 || !is_generic(word) || is_article(word) {
+                    continue;
+                }
+                let descriptor = Descriptor {
+                    word: capitalize(&word.to_lowercase()),
+                    prefix: index + 1 < words.len(),
+                };
+                if !self.descriptors.contains(&descriptor) {
+                    self.descriptors.push(descriptor);
+                }
+            }
+        }
+        self
+    }
+
+    /// Learn item bases and the syllables of the other words from item
+    /// names, e.g. "Blade" and "frost" from "Frost Blade of Dawn"
+    pub fn with_items<S: AsRef<str>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        for name in names {
+            let name = name.as_ref();
+            self.taken.insert(name.to_lowercase());
+            let head = name.split(" of ").next().unwrap_or(name);
+            let Some(noun) = head.split_whitespace().last() else {
+                continue;
+            };
+            self.learn(&name.replacen(noun, " ", 1));
+            let noun = capitalize(&noun.to_lowercase());
+            if !self.item_nouns.contains(&noun) {
+                self.item_nouns.push(noun);
+            }
+        }
+        self
+    }
+
+    /// Name the NPCs, towns and dungeon treasures the model left blank,
+    /// keeping clear of the names it did choose
+    pub fn name_world(&mut self, world: &mut WorldData) {
+        let chosen = world
+            .towns
+            .iter()
+            .flat_map(|town| {
+                std::iter::once(&town.name).chain(town.npcs.iter().map(|npc| &npc.name))
+            })
+            .chain(
+                world
+                    .dungeons
+                    .iter()
+                    .flat_map(|dungeon| dungeon.floors.iter().flat_map(|floor| &floor.treasures)),
+            );
+        for name in chosen.filter(|name| !name.trim().is_empty()) {
+            self.taken.insert(name.to_lowercase());
+        }
+
+        for town in &mut world.towns {
+            if town.name.trim().is_empty() {
+                town.name = self.name(NameKind::Town);
+            }
+            for npc in town
+                .npcs
+                .iter_mut()
+                .filter(|npc| npc.name.trim().is_empty())
+            {
+                npc.name = self.name(NameKind::Person);
+            }
+        }
+        let treasures = world
+            .dungeons
+            .iter_mut()
+            .flat_map(|dungeon| &mut dungeon.floors)
+            .flat_map(|floor| &mut floor.treasures);
+        for treasure in treasures.filter(|treasure| treasure.trim().is_empty()) {
+            *treasure = self.name(NameKind::Item);
+        }
+    }
+
+    /// A new name of the kind
+    pub fn name(&mut self, kind: NameKind) -> String {
+        let mut candidate = String::new();
+        for _ in 0..MAX_ATTEMPTS {
+            let core = self.core(kind);
+            candidate = match kind {
+                NameKind::Person => core,
+                NameKind::Town => match self.pick_descriptor() {
+                    Some(Descriptor { word, prefix: true }) => format!("{word} {core}"),
+                    Some(Descriptor {
+                        word,
+                        prefix: false,
+                    }) => format!("{core} {word}"),
+                    None => core,
+                },
+                NameKind::Item => {
+                    let noun = self.pick_item_noun();
+                    if self.next().is_multiple_of(2) {
+                        format!("{core} {noun}")
+                    } else {
+                        format!("{noun} of {core}")
+                    }
+                }
+            };
+            if self.taken.insert(candidate.to_lowercase()) {
+                break;
+            }
+        }
+        candidate
+    }
+
+    /// A pronounceable word of two or three syllables
+    fn core(&mut self, kind: NameKind) -> String {
+        let starts = pool(&self.starts, FALLBACK_STARTS, 2);
+        let ends = pool(&self.ends, FALLBACK_ENDS, 2);
+        let middles = self.middles.clone();
+        let three =
+            kind != NameKind::Person && !middles.is_empty() && self.next().is_multiple_of(3);
+        let mut word = String::new();
+        for _ in 0..MAX_ATTEMPTS {
+            word = self.pick(&starts).clone();
+            if three {
+                let middle: &String = self.pick(&middles);
+                word.push_str(middle);
+            }
+            let end: &String = self.pick(&ends);
+            word.push_str(end);
+            if pronounceable(&word) && !self.taken.contains(&word) {
+                break;
+            }
+        }
+        capitalize(&word)
+    }
+
+    fn learn(&mut self, name: &str) {
+        self.taken.insert(name.to_lowercase());
+        for word in name.split(|c: char| !c.is_alphabetic()) {
+            let word = word.to_lowercase();
+            if word.len() < 3 || is_generic(&word) {
+                continue;
+            }
+            self.taken.insert(word.clone());
+            let syllables = syllables(&word);
+            let last = syllables.len() - 1;
+            for (index, syllable) in syllables.into_iter().enumerate() {
+                let list = match index {
+                    0 => &mut self.starts,
+                    _ if index == last => &mut self.ends,
+                    _ => &mut self.middles,
+                };
+                if !list.contains(&syllable) {
+                    list.push(syllable);
+                }
+            }
+        }
+    }
+
+    fn pick_descriptor(&mut self) -> Option<Descriptor> {
+        // Half of the towns go without a place word
+        if self.descriptors.is_empty() || self.next().is_multiple_of(2) {
+            return None;
+        }
+        let descriptors = self.descriptors.clone();
+        Some(self.pick(&descriptors).clone())
+    }
+
+    fn pick_item_noun(&mut self) -> String {
+        let nouns = pool(&self.item_nouns, FALLBACK_ITEM_NOUNS, 1);
+        self.pick(&nouns).clone()
+    }
+
+    fn pick<'a, T>(&mut self, list: &'a [T]) -> &'a T {
+        &list[(self.next() % list.len() as u64) as usize]
+    }
+
+    fn next(&mut self) -> u64 {
+        self.rng = hash(&[self.rng]);
+        self.rng
+    }
+}
+
+/// Learned syllables, topped up with the fallback while there are fewer
+/// than `min`
+fn pool(learned: &[String], fallback: &[&str], min: usize) -> Vec<String> {
+    let mut pool = learned.to_vec();
+    if pool.len() < min {
+        pool.extend(fallback.iter().map(|s| s.to_string()));
+    }
+    pool
+}
+
+/// Split a lowercase word before each vowel group, keeping all but the
+/// last consonant of a cluster with the syllable before it, e.g. "eldoria"
+/// into "el", "do", "ria"
+fn syllables(word: &str) -> Vec<String> {
+    let chars: Vec<char> = word.chars().collect();
+    let is_vowel = |index: usize| {
+        matches!(chars[index], 'a' | 'e' | 'i' | 'o' | 'u') || (chars[index] == 'y' && index > 0)
+    };
+
+    let mut syllables = Vec::new();
+    let mut start = 0;
+    let mut index = 0;
+    // Skip the leading consonants and the first vowel group
+    while index < chars.len() && !is_vowel(index) {
+        index += 1;
+    }
+    loop {
+        while index < chars.len() && is_vowel(index) {
+            index += 1;
+        }
+        let cluster = index;
+        while index < chars.len() && !is_vowel(index) {
+            index += 1;
+        }
+        if index == chars.len() {
+            break;
+        }
+        // The next syllable starts with the last consonant of the cluster
+        let split = if index > cluster { index - 1 } else { index };
+        syllables.push(chars[start..split].iter().collect());
+        start = split;
+    }
+    syllables.push(chars[start..].iter().collect());
+    syllables
+}
+
+/// No letter three times in a row and no more than two vowels or three
+/// consonants in a row
+fn pronounceable(word: &str) -> bool {
+    let chars: Vec<char> = word.chars().collect();
+    if !(3..=10).contains(&chars.len()) {
+        return false;
+    }
+    let vowel = |c: char| matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+    let (mut vowels, mut consonants) = (0, 0);
+    for (index, &c) in chars.iter().enumerate() {
+        if index >= 2 && chars[index - 1] == c && chars[index - 2] == c {
+            return false;
+        }
+        if vowel(c) {
+            (vowels, consonants) = (vowels + 1, 0);
+        } else {
+            (vowels, consonants) = (0, consonants + 1);
+        }
+        if vowels > 2 || consonants > 3 {
+            return false;
+        }
+    }
+    true
+}
+
+fn is_generic(word: &str) -> bool {
+    let word = word.to_lowercase();
+    GENERIC_WORDS
+        .split_whitespace()
+        .any(|generic| generic == word)
+}
+
+fn is_article(word: &str) -> bool {
+    matches!(
+        word.to_lowercase().as_str(),
+        "a" | "an" | "and" | "of" | "the"
+    )
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_types::{BossData, DungeonData, FloorData, NpcData, TownData};
+
+    const LORE: &[&str] = &["Eldoria", "Valmorath", "Sylvendar", "Korindel"];
+
+    fn generator(seed: u64) -> NameGenerator {
+        NameGenerator::new(seed)
+            .with_lore(LORE)
+            .with_towns(["Port Aldwyn", "Castle Mirren"])
+            .with_items(["Frost Blade of Dawn", "Ember Ring"])
+    }
+
+    #[test]
+    fn syllable_splitting() {
+        assert_eq!(syllables("eldoria"), ["el", "do", "ria"]);
+        assert_eq!(syllables("valmorath"), ["val", "mo", "rath"]);
+        assert_eq!(syllables("strand"), ["strand"]);
+        assert_eq!(syllables("ayla"), ["ay", "la"]);
+    }
+
+    #[test]
+    fn same_seed_same_names() {
+        let kinds = [NameKind::Person, NameKind::Town, NameKind::Item];
+        let mut first = generator(7);
+        let mut second = generator(7);
+        for kind in kinds.iter().cycle().take(30) {
+            assert_eq!(first.name(*kind), second.name(*kind));
+        }
+
+        let mut first = generator(7);
+        let mut other = generator(8);
+        let names: Vec<_> = (0..10).map(|_| first.name(NameKind::Person)).collect();
+        let others: Vec<_> = (0..10).map(|_| other.name(NameKind::Person)).collect();
+        assert_ne!(names, others);
+    }
+
+    #[test]
+    fn names_never_repeat_or_copy_the_lore() {
+        let mut names = generator(3);
+        let mut seen = HashSet::new();
+        for kind in [NameKind::Person, NameKind::Town, NameKind::Item] {
+            for _ in 0..25 {
+                let name = names.name(kind);
+                assert!(seen.insert(name.to_lowercase()), "{name} repeated");
+                assert!(
+                    !LORE.iter().any(|lore| lore.eq_ignore_ascii_case(&name)),
+                    "{name} copies the lore"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn towns_and_items_use_learned_words() {
+        let mut names = generator(11);
+        let towns: Vec<_> = (0..20).map(|_| names.name(NameKind::Town)).collect();
+        assert!(
+            towns
+                .iter()
+                .any(|town| town.starts_with("Port ") || town.starts_with("Castle "))
+        );
+        let items: Vec<_> = (0..20).map(|_| names.name(NameKind::Item)).collect();
+        assert!(
+            items
+                .iter()
+                .all(|item| ["Blade", "Ring"].iter().any(|noun| item.contains(noun)))
+        );
+    }
+
+    #[test]
+    fn name_world_fills_only_blanks() {
+        let npc = |name: &str| NpcData {
+            name: name.to_string(),
+            sprite: String::new(),
+            dialog_tree: String::new(),
+        };
+        let mut world = WorldData {
+            regions: Vec::new(),
+            connections: Vec::new(),
+            towns: vec![
+                TownData {
+                    name: "Eldoria".to_string(),
+                    map_data: String::new(),
+                    npcs: vec![npc("Mira"), npc(""), npc(" ")],
+                    shops: Vec::new(),
+                },
+                TownData {
+                    name: String::new(),
+                    map_data: String::new(),
+                    npcs: Vec::new(),
+                    shops: Vec::new(),
+                },
+            ],
+            dungeons: vec![DungeonData {
+                name: "Korindel Depths".to_string(),
+                floors: vec![FloorData {
+                    layout: String::new(),
+                    encounters: Vec::new(),
+                    treasures: vec!["Ember Ring".to_string(), String::new()],
+                }],
+                boss: BossData {
+                    name: "Warden".to_string(),
+                    sprite: String::new(),
+                    attacks: Vec::new(),
+                    dialog: String::new(),
+                },
+            }],
+        };
+        generator(5).name_world(&mut world);
+
+        assert_eq!(world.towns[0].name, "Eldoria");
+        assert_eq!(world.towns[0].npcs[0].name, "Mira");
+        let npcs: Vec<_> = world.towns[0].npcs.iter().map(|npc| &npc.name).collect();
+        assert!(npcs.iter().all(|name| !name.trim().is_empty()));
+        assert_ne!(npcs[1], npcs[2]);
+        assert!(!world.towns[1].name.is_empty());
+        let treasures = &world.dungeons[0].floors[0].treasures;
+        assert_eq!(treasures[0], "Ember Ring");
+        assert!(!treasures[1].is_empty() && treasures[1] != "Ember Ring");
+    }
+}
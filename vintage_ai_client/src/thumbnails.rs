@@ -0,0 +1,124 @@
+//! Thumbnail cache and buffered image loading for artifact browsing
+//!
+//! Decoding every full-size PNG makes browsing a large project slow, so
+//! previews come from a [`ThumbnailCache`] in `thumbnails/` beside the
+//! project's artifact index. A thumbnail is generated the first time an
+//! image is shown and reused until the file changes; history versions never
+//! change, so each version is scaled down once. Files are decoded as they
+//! are read through a buffer, without holding a second copy of the encoded
+//! bytes.
+
+use anyhow::{Context, Result};
+use image::DynamicImage;
+use image::ImageReader;
+use image::imageops::FilterType;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Directory beside the artifact index holding the thumbnails
+pub const THUMBNAILS_DIR: &str = "thumbnails";
+
+/// Default largest edge of a thumbnail in pixels
+pub const THUMBNAIL_SIZE: u32 = 128;
+
+/// Decode an image while reading its file
+pub fn load_image(path: &Path) -> Result<DynamicImage> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    ImageReader::new(BufReader::new(file))
+        .with_guessed_format()
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .decode()
+        .with_context(|| format!("Failed to decode {}", path.display()))
+}
+
+/// Scaled-down copies of a project's images, generated on first use
+#[derive(Debug, Clone)]
+pub struct ThumbnailCache {
+    dir: PathBuf,
+    size: u32,
+}
+
+impl ThumbnailCache {
+    /// Cache of the project at `project_dir`, under `thumbnails/`
+    pub fn for_project(project_dir: &Path) -> Self {
+        Self {
+            dir: project_dir.join(THUMBNAILS_DIR),
+            size: THUMBNAIL_SIZE,
+        }
+    }
+
+    /// Largest edge of the thumbnails; images already smaller keep their size
+    pub fn with_size(mut self, size: u32) -> Self {
+        self.size = size.max(1);
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Where the thumbnail of the image's current contents is kept. The
+    /// name changes with the file's size and modification time, so a
+    /// regenerated image gets a fresh thumbnail.
+    pub fn thumbnail_path(&self, source: &Path) -> Result<PathBuf> {
+        let metadata = std::fs::metadata(source)
+            .with_context(|| format!("Failed to read {}", source.display()))?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+
+        let mut hasher = Sha256::new();
+        hasher.update(source.to_string_lossy().as_bytes());
+        hasher.update(metadata.len().to_le_bytes());
+        hasher.update(modified.as_nanos().to_le_bytes());
+        hasher.update(self.size.to_le_bytes());
+        let key = format!("{:x}", hasher.finalize());
+
+        let stem = source
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Ok(self.dir.join(format!("{stem}-{}.png", &key[..16])))
+    }
+
+    /// The thumbnail of an image, generating and caching it if needed
+    pub fn get(&self, source: &Path) -> Result<image::RgbaImage> {
+        let path = self.thumbnail_path(source)?;
+        if path.exists() {
+            match load_image(&path) {
+                Ok(thumbnail) => return Ok(thumbnail.to_rgba8()),
+                Err(e) => tracing::warn!("Regenerating thumbnail: {e:#}"),
+            }
+        }
+
+        let image = load_image(source)?;
+        let thumbnail = if image.width().max(image.height()) > self.size {
+            // Nearest keeps pixel art crisp
+            image.resize(self.size, self.size, FilterType::Nearest)
+        } else {
+            image
+        }
+        .to_rgba8();
+
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        thumbnail
+            .save_with_format(&path, image::ImageFormat::Png)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(thumbnail)
+    }
+
+    /// Remove every cached thumbnail
+    pub fn clear(&self) -> Result<()> {
+        if self.dir.exists() {
+            std::fs::remove_dir_all(&self.dir)
+                .with_context(|| format!("Failed to remove {}", self.dir.display()))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,661 @@
+//! Token counting and cost estimation for AI operations
+//!
+//! Uses tiktoken-rs for accurate token counting
+//! Supports all OpenAI models and their pricing
+
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tiktoken_rs::CoreBPE;
+use tiktoken_rs::tokenizer::{Tokenizer, get_tokenizer};
+use tokio::sync::Mutex;
+
+pub mod pricing;
+
+pub use pricing::{ModelCost, ModelPricing};
+
+/// Tokens added to every reply for `<|start|>assistant<|message|>`
+const REPLY_PRIMING_TOKENS: usize = 3;
+
+/// Share of its completion limit a reply is expected to use
+const EXPECTED_COMPLETION_SHARE: f64 = 0.6;
+
+/// Share of its completion limit even a short reply uses
+const MIN_COMPLETION_SHARE: f64 = 0.2;
+
+/// Token counter for tracking usage and costs
+pub struct TokenCounter {
+    /// Usage statistics
+    stats: Arc<Mutex<TokenStats>>,
+    /// Model pricing information
+    pricing: ModelPricing,
+    /// Model serving each requested model, when the provider renames them
+    aliases: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TokenStats {
+    /// Total tokens used for prompts
+    pub prompt_tokens: u64,
+    /// Total tokens used for completions
+    pub completion_tokens: u64,
+    /// Total tokens for embeddings
+    pub embedding_tokens: u64,
+    /// Total tokens for images (estimated)
+    pub image_tokens: u64,
+    /// Total cost in USD
+    pub total_cost: f64,
+    /// Cost breakdown by model
+    pub cost_by_model: HashMap<String, f64>,
+    /// Token breakdown by model
+    pub tokens_by_model: HashMap<String, u64>,
+}
+
+impl Default for TokenCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TokenCounter {
+    /// Create a new token counter
+    pub fn new() -> Self {
+        Self::with_pricing(ModelPricing::default())
+    }
+
+    /// Create a token counter using a custom pricing table
+    pub fn with_pricing(pricing: ModelPricing) -> Self {
+        Self {
+            stats: Arc::new(Mutex::new(TokenStats::default())),
+            pricing,
+            aliases: BTreeMap::new(),
+        }
+    }
+
+    /// Record and price usage of a requested model under the model the
+    /// provider serves it with, e.g. `gpt-4` under a Gemini model
+    pub fn with_aliases(mut self, aliases: BTreeMap<String, String>) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    /// Model usage of `model` is recorded under. Dated snapshots and
+    /// pricing keys such as `dall-e-3-1024x1024-standard` are recorded under
+    /// the alias of the model they start with.
+    fn served<'a>(&'a self, model: &'a str) -> &'a str {
+        if let Some(served) = self.aliases.get(model) {
+            return served;
+        }
+        self.aliases
+            .iter()
+            .filter(|(name, _)| model.starts_with(&format!("{name}-")))
+            .max_by_key(|(name, _)| name.len())
+            .map_or(model, |(_, served)| served)
+    }
+
+    /// Pricing table used for cost calculations
+    pub fn pricing(&self) -> &ModelPricing {
+        &self.pricing
+    }
+
+    /// Mutable access to the pricing table, e.g. to apply overrides
+    pub fn pricing_mut(&mut self) -> &mut ModelPricing {
+        &mut self.pricing
+    }
+
+    /// Count tokens for a given text and model
+    pub fn count_tokens(&self, text: &str, model: &str) -> Result<usize> {
+        let encoder = Self::get_encoder_for_model(model);
+        let tokens = encoder.encode_with_special_tokens(text);
+        Ok(tokens.len())
+    }
+
+    /// Count tokens for a chat request, including the per-message framing
+    /// and reply priming overhead added by the chat format.
+    ///
+    /// Each message is a `(role, content)` pair.
+    pub fn count_chat_tokens<R, C>(&self, messages: &[(R, C)], model: &str) -> Result<usize>
+    where
+        R: AsRef<str>,
+        C: AsRef<str>,
+    {
+        let encoder = Self::get_encoder_for_model(model);
+        // Older 3.5 snapshots wrap each message in <im_start>{role}\n{content}<im_end>\n
+        let tokens_per_message = if model.starts_with("gpt-3.5") { 4 } else { 3 };
+
+        let content_tokens: usize = messages
+            .iter()
+            .map(|(role, content)| {
+                tokens_per_message
+                    + encoder.encode_with_special_tokens(role.as_ref()).len()
+                    + encoder.encode_with_special_tokens(content.as_ref()).len()
+            })
+            .sum();
+
+        Ok(content_tokens + REPLY_PRIMING_TOKENS)
+    }
+
+    /// Estimate tokens for an image
+    pub fn estimate_image_tokens(&self, width: u32, height: u32) -> usize {
+        // Rough estimation based on image dimensions
+        // OpenAI doesn't provide exact token counts for images
+        let pixels = width * height;
+        let base_tokens = 85; // Base tokens for any image
+        let dimension_tokens = (pixels as f64 / 750.0).ceil() as usize;
+        base_tokens + dimension_tokens
+    }
+
+    /// Record token usage, returning its cost
+    pub async fn record_usage(
+        &self,
+        model: &str,
+        prompt_tokens: usize,
+        completion_tokens: usize,
+    ) -> Result<f64> {
+        let model = self.served(model);
+        let mut stats = self.stats.lock().await;
+
+        // Update token counts
+        stats.prompt_tokens += prompt_tokens as u64;
+        stats.completion_tokens += completion_tokens as u64;
+
+        // Update per-model stats
+        *stats.tokens_by_model.entry(model.to_string()).or_insert(0) +=
+            (prompt_tokens + completion_tokens) as u64;
+
+        // Calculate cost
+        if self.pricing.get(model).is_none() {
+            return Ok(0.0);
+        }
+        let total_cost = self
+            .pricing
+            .chat_cost(model, prompt_tokens, completion_tokens);
+
+        stats.total_cost += total_cost;
+        *stats.cost_by_model.entry(model.to_string()).or_insert(0.0) += total_cost;
+
+        Ok(total_cost)
+    }
+
+    /// Record image generation, returning its cost
+    pub async fn record_image_generation(
+        &self,
+        model: &str,
+        width: u32,
+        height: u32,
+        count: usize,
+    ) -> Result<f64> {
+        let model = self.served(model);
+        let mut stats = self.stats.lock().await;
+
+        let Some(image_cost) = self.pricing.get(model).and_then(|p| p.image_cost) else {
+            return Ok(0.0);
+        };
+        let total_cost = image_cost * count as f64;
+        stats.total_cost += total_cost;
+        *stats.cost_by_model.entry(model.to_string()).or_insert(0.0) += total_cost;
+        stats.image_tokens += self.estimate_image_tokens(width, height) as u64 * count as u64;
+
+        Ok(total_cost)
+    }
+
+    /// Record embedding usage, returning its cost
+    pub async fn record_embedding(&self, model: &str, tokens: usize) -> Result<f64> {
+        let model = self.served(model);
+        let mut stats = self.stats.lock().await;
+
+        stats.embedding_tokens += tokens as u64;
+
+        if self
+            .pricing
+            .get(model)
+            .is_none_or(|p| p.embedding_cost_per_1k.is_none())
+        {
+            return Ok(0.0);
+        }
+        let total_cost = self.pricing.embedding_cost(model, tokens);
+        stats.total_cost += total_cost;
+        *stats.cost_by_model.entry(model.to_string()).or_insert(0.0) += total_cost;
+
+        Ok(total_cost)
+    }
+
+    /// Get current statistics
+    pub async fn get_stats(&self) -> TokenStats {
+        self.stats.lock().await.clone()
+    }
+
+    /// Reset statistics
+    pub async fn reset_stats(&self) {
+        *self.stats.lock().await = TokenStats::default();
+    }
+
+    /// Estimate cost for a request before making it
+    pub fn estimate_cost(
+        &self,
+        model: &str,
+        prompt: &str,
+        max_completion_tokens: usize,
+    ) -> Result<f64> {
+        let model = self.served(model);
+        let prompt_tokens = self.count_tokens(prompt, model)?;
+        Ok(self
+            .pricing
+            .chat_cost(model, prompt_tokens, max_completion_tokens))
+    }
+
+    /// Estimate cost of generating images before making the request
+    pub fn estimate_image_cost(&self, model: &str, count: usize) -> f64 {
+        let model = self.served(model);
+        self.pricing.image_cost(model, count)
+    }
+
+    /// Estimate cost of embedding a text before making the request
+    pub fn estimate_embedding_cost(&self, model: &str, text: &str) -> Result<f64> {
+        let model = self.served(model);
+        let tokens = self.count_tokens(text, model)?;
+        Ok(self.pricing.embedding_cost(model, tokens))
+    }
+
+    /// Get the appropriate encoder for a model
+    ///
+    /// Encoders are process-wide singletons, so this is cheap to call per request.
+    /// Unknown models fall back to cl100k_base.
+    fn get_encoder_for_model(model: &str) -> &'static CoreBPE {
+        match get_tokenizer(model).unwrap_or(Tokenizer::Cl100kBase) {
+            Tokenizer::O200kBase => tiktoken_rs::o200k_base_singleton(),
+            Tokenizer::P50kBase => tiktoken_rs::p50k_base_singleton(),
+            Tokenizer::P50kEdit => tiktoken_rs::p50k_edit_singleton(),
+            Tokenizer::R50kBase | Tokenizer::Gpt2 => tiktoken_rs::r50k_base_singleton(),
+            Tokenizer::Cl100kBase => tiktoken_rs::cl100k_base_singleton(),
+        }
+    }
+}
+
+/// Tokens and cost of requests worked out before they are made
+///
+/// Prompts are counted exactly, but the length of a reply isn't known until
+/// it arrives, so the cost is a range: from a short reply to one that uses
+/// the whole completion limit, which it can't exceed. Requests the cache
+/// would answer cost nothing.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CostEstimate {
+    pub requests: usize,
+    /// Requests answered from the cache
+    pub cached: usize,
+    pub prompt_tokens: usize,
+    /// Completion tokens expected
+    pub completion_tokens: usize,
+    /// Expected cost in USD
+    pub cost: f64,
+    pub min_cost: f64,
+    pub max_cost: f64,
+}
+
+impl CostEstimate {
+    /// Estimate for a request the cache already answers
+    pub fn cached() -> Self {
+        Self {
+            requests: 1,
+            cached: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn total_tokens(&self) -> usize {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+impl std::ops::AddAssign for CostEstimate {
+    fn add_assign(&mut self, other: Self) {
+        self.requests += other.requests;
+        self.cached += other.cached;
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.cost += other.cost;
+        self.min_cost += other.min_cost;
+        self.max_cost += other.max_cost;
+    }
+}
+
+impl std::iter::Sum for CostEstimate {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |mut total, estimate| {
+            total += estimate;
+            total
+        })
+    }
+}
+
+impl TokenCounter {
+    /// Estimate a chat request with `prompt_tokens` of messages whose reply
+    /// is limited to `max_completion_tokens`
+    pub fn estimate_chat(
+        &self,
+        model: &str,
+        prompt_tokens: usize,
+        max_completion_tokens: usize,
+    ) -> CostEstimate {
+        let model = self.served(model);
+        let completion = |share: f64| (max_completion_tokens as f64 * share).round() as usize;
+        let completion_tokens = completion(EXPECTED_COMPLETION_SHARE);
+        CostEstimate {
+            requests: 1,
+            cached: 0,
+            prompt_tokens,
+            completion_tokens,
+            cost: self
+                .pricing
+                .chat_cost(model, prompt_tokens, completion_tokens),
+            min_cost: self.pricing.chat_cost(
+                model,
+                prompt_tokens,
+                completion(MIN_COMPLETION_SHARE),
+            ),
+            max_cost: self
+                .pricing
+                .chat_cost(model, prompt_tokens, max_completion_tokens),
+        }
+    }
+
+    /// Estimate generating `count` images, which are priced per image
+    pub fn estimate_images(
+        &self,
+        model: &str,
+        width: u32,
+        height: u32,
+        count: usize,
+    ) -> CostEstimate {
+        let model = self.served(model);
+        let cost = self.pricing.image_cost(model, count);
+        CostEstimate {
+            requests: count,
+            cached: 0,
+            prompt_tokens: self.estimate_image_tokens(width, height) * count,
+            completion_tokens: 0,
+            cost,
+            min_cost: cost,
+            max_cost: cost,
+        }
+    }
+}
+
+/// Token optimization strategies
+pub struct TokenOptimizer {
+    /// Maximum context window sizes by model
+    context_windows: HashMap<String, usize>,
+}
+
+impl Default for TokenOptimizer {
+    fn default() -> Self {
+        let mut context_windows = HashMap::new();
+
+        context_windows.insert("gpt-4".to_string(), 8192);
+        context_windows.insert("gpt-4-32k".to_string(), 32768);
+        context_windows.insert("gpt-4-turbo".to_string(), 128000);
+        context_windows.insert("gpt-3.5-turbo".to_string(), 16384);
+        context_windows.insert("gpt-3.5-turbo-16k".to_string(), 16384);
+
+        Self { context_windows }
+    }
+}
+
+impl TokenOptimizer {
+    /// Optimize a prompt to fit within token limits
+    pub fn optimize_prompt(
+        &self,
+        prompt: &str,
+        model: &str,
+        max_completion_tokens: usize,
+        counter: &TokenCounter,
+    ) -> Result<String> {
+        let max_context = self.context_windows.get(model).copied().unwrap_or(4096);
+        let current_tokens = counter.count_tokens(prompt, model)?;
+
+        if current_tokens + max_completion_tokens <= max_context {
+            return Ok(prompt.to_string());
+        }
+
+        // Need to truncate - implement smart truncation
+        let available_tokens = max_context - max_completion_tokens - 100; // Safety margin
+        self.truncate_to_token_limit(prompt, available_tokens, model, counter)
+    }
+
+    /// Truncate text to fit within token limit
+    fn truncate_to_token_limit(
+        &self,
+        text: &str,
+        max_tokens: usize,
+        model: &str,
+        counter: &TokenCounter,
+    ) -> Result<String> {
+        // Binary search for the right truncation point
+        let chars: Vec<char> = text.chars().collect();
+        let mut left = 0;
+        let mut right = chars.len();
+        let mut best_fit = String::new();
+
+        while left < right {
+            let mid = (left + right).div_ceil(2);
+            let truncated: String = chars[..mid].iter().collect();
+            let tokens = counter.count_tokens(&truncated, model)?;
+
+            if tokens <= max_tokens {
+                best_fit = truncated;
+                left = mid;
+            } else {
+                right = mid - 1;
+            }
+        }
+
+        Ok(best_fit)
+    }
+
+    /// Split text into chunks that fit within token limits
+    pub fn chunk_text(
+        &self,
+        text: &str,
+        max_tokens_per_chunk: usize,
+        model: &str,
+        counter: &TokenCounter,
+    ) -> Result<Vec<String>> {
+        let mut chunks = Vec::new();
+        let paragraphs: Vec<&str> = text.split("\n\n").collect();
+        let mut current_chunk = String::new();
+        let mut current_tokens = 0;
+
+        for paragraph in paragraphs {
+            let paragraph_tokens = counter.count_tokens(paragraph, model)?;
+
+            if current_tokens + paragraph_tokens > max_tokens_per_chunk && !current_chunk.is_empty()
+            {
+                chunks.push(current_chunk.clone());
+                current_chunk.clear();
+                current_tokens = 0;
+            }
+
+            if paragraph_tokens > max_tokens_per_chunk {
+                // Split large paragraph
+                let sub_chunks =
+                    self.split_paragraph(paragraph, max_tokens_per_chunk, model, counter)?;
+                for sub_chunk in sub_chunks {
+                    chunks.push(sub_chunk);
+                }
+            } else {
+                if !current_chunk.is_empty() {
+                    current_chunk.push_str("\n\n");
+                }
+                current_chunk.push_str(paragraph);
+                current_tokens += paragraph_tokens;
+            }
+        }
+
+        if !current_chunk.is_empty() {
+            chunks.push(current_chunk);
+        }
+
+        Ok(chunks)
+    }
+
+    fn split_paragraph(
+        &self,
+        paragraph: &str,
+        max_tokens: usize,
+        model: &str,
+        counter: &TokenCounter,
+    ) -> Result<Vec<String>> {
+        let sentences: Vec<&str> = paragraph.split(". ").collect();
+        let mut chunks = Vec::new();
+        let mut current_chunk = String::new();
+        let mut current_tokens = 0;
+
+        for (i, sentence) in sentences.iter().enumerate() {
+            let sentence_with_period = if i < sentences.len() - 1 {
+                format!("{sentence}. ")
+            } else {
+                sentence.to_string()
+            };
+
+            let sentence_tokens = counter.count_tokens(&sentence_with_period, model)?;
+
+            if current_tokens + sentence_tokens > max_tokens && !current_chunk.is_empty() {
+                chunks.push(current_chunk.trim().to_string());
+                current_chunk.clear();
+                current_tokens = 0;
+            }
+
+            current_chunk.push_str(&sentence_with_period);
+            current_tokens += sentence_tokens;
+        }
+
+        if !current_chunk.is_empty() {
+            chunks.push(current_chunk.trim().to_string());
+        }
+
+        Ok(chunks)
+    }
+}
+
+/// Cost optimization suggestions
+#[derive(Debug, Clone)]
+pub struct CostOptimizationSuggestion {
+    pub description: String,
+    pub potential_savings: f64,
+    pub implementation_difficulty: Difficulty,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+/// Analyze usage and provide cost optimization suggestions
+pub async fn analyze_usage_for_optimizations(
+    stats: &TokenStats,
+) -> Vec<CostOptimizationSuggestion> {
+    let mut suggestions = Vec::new();
+
+    // Check if using expensive models for simple tasks
+    if let Some(gpt4_cost) = stats.cost_by_model.get("gpt-4")
+        && *gpt4_cost > stats.total_cost * 0.5
+    {
+        suggestions.push(CostOptimizationSuggestion {
+            description: "Consider using GPT-3.5-turbo for simpler tasks".to_string(),
+            potential_savings: gpt4_cost * 0.7,
+            implementation_difficulty: Difficulty::Easy,
+        });
+    }
+
+    // Check prompt efficiency
+    let avg_prompt_length = if stats.prompt_tokens > 0 {
+        stats.prompt_tokens as f64 / stats.tokens_by_model.len() as f64
+    } else {
+        0.0
+    };
+
+    if avg_prompt_length > 1000.0 {
+        suggestions.push(CostOptimizationSuggestion {
+            description: "Optimize prompts to be more concise".to_string(),
+            potential_savings: stats.total_cost * 0.2,
+            implementation_difficulty: Difficulty::Medium,
+        });
+    }
+
+    // Suggest caching for repeated requests
+    suggestions.push(CostOptimizationSuggestion {
+        description: "Enable caching for repeated AI requests".to_string(),
+        potential_savings: stats.total_cost * 0.3,
+        implementation_difficulty: Difficulty::Easy,
+    });
+
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_model_family_uses_its_encoder() {
+        let counter = TokenCounter::new();
+        let text = "Привет, мир!";
+        assert_eq!(counter.count_tokens(text, "gpt-4o").unwrap(), 5);
+        assert_eq!(counter.count_tokens(text, "gpt-4").unwrap(), 7);
+        assert_eq!(counter.count_tokens(text, "gpt-3.5-turbo").unwrap(), 7);
+        assert_eq!(counter.count_tokens(text, "text-davinci-003").unwrap(), 13);
+        // Unknown models count like cl100k_base
+        assert_eq!(counter.count_tokens(text, "gemini-1.5-pro").unwrap(), 7);
+
+        // r50k_base has no tokens for runs of spaces
+        let code = "    indented code\n        deeper";
+        assert_eq!(counter.count_tokens(code, "davinci").unwrap(), 15);
+        assert_eq!(counter.count_tokens(code, "text-davinci-003").unwrap(), 7);
+
+        assert!(std::ptr::eq(
+            TokenCounter::get_encoder_for_model("gpt-4o-mini"),
+            tiktoken_rs::o200k_base_singleton()
+        ));
+        assert!(std::ptr::eq(
+            TokenCounter::get_encoder_for_model("unknown-model"),
+            tiktoken_rs::cl100k_base_singleton()
+        ));
+    }
+
+    #[test]
+    fn chat_tokens_include_the_message_framing() {
+        let counter = TokenCounter::new();
+        let none: [(&str, &str); 0] = [];
+        assert_eq!(counter.count_chat_tokens(&none, "gpt-4").unwrap(), 3);
+
+        // 3 framing + 1 role + 2 content, then 3 to prime the reply
+        let hello = [("user", "hello world")];
+        assert_eq!(counter.count_chat_tokens(&hello, "gpt-4").unwrap(), 9);
+        assert_eq!(counter.count_chat_tokens(&hello, "gpt-4o").unwrap(), 9);
+        assert_eq!(
+            counter
+                .count_chat_tokens(&hello, "gpt-3.5-turbo-0301")
+                .unwrap(),
+            10
+        );
+
+        let conversation = [
+            ("system", "You are terse."),
+            ("user", "hello world"),
+            ("assistant", "Hi."),
+        ];
+        let content: usize = conversation
+            .iter()
+            .map(|(role, content)| {
+                counter.count_tokens(role, "gpt-4").unwrap()
+                    + counter.count_tokens(content, "gpt-4").unwrap()
+            })
+            .sum();
+        assert_eq!(
+            counter.count_chat_tokens(&conversation, "gpt-4").unwrap(),
+            content + 3 * 3 + 3
+        );
+    }
+}
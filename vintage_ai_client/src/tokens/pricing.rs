@@ -0,0 +1,295 @@
+//! Per-model pricing table
+//!
+//! Prices are keyed by model name. Image models are keyed by
+//! `{model}-{width}x{height}-{quality}` since size and quality set the price.
+//! The built-in table can be overridden from a TOML file:
+//!
+//! ```toml
+//! [models."gpt-4o"]
+//! prompt_cost_per_1k = 0.0025
+//! completion_cost_per_1k = 0.01
+//!
+//! [models."dall-e-3-1024x1024-standard"]
+//! image_cost = 0.04
+//! ```
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Environment variable pointing at a TOML pricing override file
+pub const PRICING_FILE_ENV: &str = "VINTAGE_AI_PRICING_FILE";
+
+/// Built-in chat prices in USD per 1K tokens: (model, prompt, completion)
+const CHAT_PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4", 0.03, 0.06),
+    ("gpt-4-32k", 0.06, 0.12),
+    ("gpt-4-turbo", 0.01, 0.03),
+    ("gpt-4o", 0.0025, 0.01),
+    ("gpt-4o-mini", 0.00015, 0.0006),
+    ("gpt-3.5-turbo", 0.0005, 0.0015),
+    ("gemini-2.5-pro", 0.00125, 0.01),
+    ("gemini-2.5-flash", 0.0003, 0.0025),
+    ("gemini-2.5-flash-lite", 0.0001, 0.0004),
+    ("gemini-2.0-flash", 0.0001, 0.0004),
+];
+
+/// Built-in image prices in USD per image
+const IMAGE_PRICES: &[(&str, f64)] = &[
+    ("dall-e-3-1024x1024-standard", 0.04),
+    ("dall-e-3-1024x1024-hd", 0.08),
+    ("dall-e-3-1792x1024-standard", 0.08),
+    ("dall-e-3-1792x1024-hd", 0.12),
+    ("dall-e-3-1024x1792-standard", 0.08),
+    ("dall-e-3-1024x1792-hd", 0.12),
+    ("dall-e-2-256x256-standard", 0.016),
+    ("dall-e-2-512x512-standard", 0.018),
+    ("dall-e-2-1024x1024-standard", 0.02),
+    // Imagen costs the same at every size and quality
+    ("imagen-3.0-generate-002", 0.03),
+    ("imagen-4.0-generate-001", 0.04),
+    ("imagen-4.0-fast-generate-001", 0.02),
+];
+
+/// Built-in embedding prices in USD per 1K tokens
+const EMBEDDING_PRICES: &[(&str, f64)] = &[
+    ("text-embedding-3-small", 0.00002),
+    ("text-embedding-3-large", 0.00013),
+    ("text-embedding-ada-002", 0.0001),
+    ("gemini-embedding-001", 0.00015),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPricing {
+    /// Pricing per model
+    #[serde(default)]
+    pub(crate) models: HashMap<String, ModelCost>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelCost {
+    /// Cost per 1K prompt tokens in USD
+    pub prompt_cost_per_1k: f64,
+    /// Cost per 1K completion tokens in USD
+    pub completion_cost_per_1k: f64,
+    /// Cost per image generation (for DALL-E)
+    pub image_cost: Option<f64>,
+    /// Cost per 1K embedding tokens
+    pub embedding_cost_per_1k: Option<f64>,
+}
+
+impl Default for ModelPricing {
+    fn default() -> Self {
+        let mut models = HashMap::new();
+
+        for (model, prompt, completion) in CHAT_PRICES {
+            models.insert(
+                model.to_string(),
+                ModelCost {
+                    prompt_cost_per_1k: *prompt,
+                    completion_cost_per_1k: *completion,
+                    ..Default::default()
+                },
+            );
+        }
+
+        for (model, price) in IMAGE_PRICES {
+            models.insert(
+                model.to_string(),
+                ModelCost {
+                    image_cost: Some(*price),
+                    ..Default::default()
+                },
+            );
+        }
+
+        for (model, price) in EMBEDDING_PRICES {
+            models.insert(
+                model.to_string(),
+                ModelCost {
+                    embedding_cost_per_1k: Some(*price),
+                    ..Default::default()
+                },
+            );
+        }
+
+        Self { models }
+    }
+}
+
+impl ModelPricing {
+    /// A table without prices, for providers that charge nothing
+    pub fn free() -> Self {
+        Self {
+            models: HashMap::new(),
+        }
+    }
+
+    /// Load the built-in table with overrides from a TOML file applied
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let mut pricing = Self::default();
+        pricing.load_overrides(path)?;
+        Ok(pricing)
+    }
+
+    /// Load the built-in table, applying the file named by
+    /// [`PRICING_FILE_ENV`] if it is set
+    pub fn from_env() -> Result<Self> {
+        match std::env::var(PRICING_FILE_ENV) {
+            Ok(path) => Self::from_file(path),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Merge prices from a TOML file over the current table
+    pub fn load_overrides(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read pricing file {}", path.display()))?;
+        let overrides: ModelPricing = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse pricing file {}", path.display()))?;
+        self.models.extend(overrides.models);
+        Ok(())
+    }
+
+    /// Set or replace the price of a single model
+    pub fn set(&mut self, model: impl Into<String>, cost: ModelCost) {
+        self.models.insert(model.into(), cost);
+    }
+
+    /// Look up the price of a model.
+    ///
+    /// Dated snapshots such as `gpt-4o-2024-08-06` fall back to the longest
+    /// known model name they start with.
+    pub fn get(&self, model: &str) -> Option<&ModelCost> {
+        self.models.get(model).or_else(|| {
+            self.models
+                .iter()
+                .filter(|(name, _)| model.starts_with(&format!("{name}-")))
+                .max_by_key(|(name, _)| name.len())
+                .map(|(_, cost)| cost)
+        })
+    }
+
+    /// Cost of a chat request in USD
+    pub fn chat_cost(&self, model: &str, prompt_tokens: usize, completion_tokens: usize) -> f64 {
+        self.get(model)
+            .map(|cost| {
+                (prompt_tokens as f64 / 1000.0) * cost.prompt_cost_per_1k
+                    + (completion_tokens as f64 / 1000.0) * cost.completion_cost_per_1k
+            })
+            .unwrap_or(0.0)
+    }
+
+    /// Cost of generating `count` images in USD
+    pub fn image_cost(&self, model: &str, count: usize) -> f64 {
+        self.get(model)
+            .and_then(|cost| cost.image_cost)
+            .map(|price| price * count as f64)
+            .unwrap_or(0.0)
+    }
+
+    /// Cost of embedding `tokens` tokens in USD
+    pub fn embedding_cost(&self, model: &str, tokens: usize) -> f64 {
+        self.get(model)
+            .and_then(|cost| cost.embedding_cost_per_1k)
+            .map(|price| (tokens as f64 / 1000.0) * price)
+            .unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::ImageConfig;
+    use async_openai::types::images::ImageSize;
+
+    #[test]
+    fn snapshots_fall_back_to_the_longest_prefix() {
+        let pricing = ModelPricing::default();
+        let price = |model| pricing.get(model).map(|cost| cost.prompt_cost_per_1k);
+
+        assert_eq!(price("gpt-4o-2024-08-06"), Some(0.0025));
+        assert_eq!(price("gpt-4o-mini-2024-07-18"), Some(0.00015));
+        assert_eq!(price("gpt-4-0613"), Some(0.03));
+        assert_eq!(price("gemini-2.5-flash-lite-preview"), Some(0.0001));
+        // Prefixes only match whole name segments
+        assert_eq!(price("gpt-4oo"), None);
+        assert_eq!(price("claude-3"), None);
+
+        assert_eq!(pricing.chat_cost("gpt-4-0613", 1000, 500), 0.03 + 0.03);
+        assert_eq!(pricing.chat_cost("unknown", 1000, 500), 0.0);
+    }
+
+    #[test]
+    fn dall_e_3_is_priced_at_every_size_it_generates() {
+        let pricing = ModelPricing::default();
+        let sizes = [
+            ImageSize::S256x256,
+            ImageSize::S1024x1024,
+            ImageSize::S1792x1024,
+            ImageSize::S1024x1792,
+            ImageSize::S1536x1024,
+            ImageSize::S1024x1536,
+        ];
+        for size in sizes {
+            for hd in [false, true] {
+                let config = ImageConfig {
+                    size,
+                    ..Default::default()
+                }
+                .with_model("dall-e-3", hd);
+                let key = config.pricing_key();
+                assert!(pricing.image_cost(&key, 1) > 0.0, "{key} has no price");
+            }
+        }
+    }
+
+    #[test]
+    fn toml_overrides_merge_over_the_built_in_table() {
+        let path = std::env::temp_dir().join(format!("pricing_{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            r#"
+[models."gpt-4o"]
+prompt_cost_per_1k = 0.002
+completion_cost_per_1k = 0.008
+
+[models."local-llm"]
+prompt_cost_per_1k = 0.0001
+
+[models."dall-e-3-1024x1024-standard"]
+image_cost = 0.05
+"#,
+        )
+        .unwrap();
+        let pricing = ModelPricing::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(pricing.chat_cost("gpt-4o", 1000, 1000), 0.002 + 0.008);
+        // Snapshots pick up the overridden price too
+        assert_eq!(pricing.chat_cost("gpt-4o-2024-08-06", 1000, 0), 0.002);
+        assert_eq!(pricing.chat_cost("local-llm", 2000, 1000), 0.0002);
+        assert_eq!(pricing.image_cost("dall-e-3-1024x1024-standard", 2), 0.1);
+        // Models the file leaves out keep their built-in price
+        assert_eq!(pricing.chat_cost("gpt-4", 1000, 0), 0.03);
+        assert_eq!(pricing.image_cost("dall-e-3-1024x1024-hd", 1), 0.08);
+    }
+
+    #[test]
+    fn unreadable_pricing_files_are_errors() {
+        let path = std::env::temp_dir().join(format!("pricing_{}.toml", uuid::Uuid::new_v4()));
+        assert!(ModelPricing::from_file(&path).is_err());
+
+        std::fs::write(
+            &path,
+            "[models.\"gpt-4o\"]\nprompt_cost_per_1k = \"cheap\"\n",
+        )
+        .unwrap();
+        let error = ModelPricing::from_file(&path).unwrap_err();
+        std::fs::remove_file(&path).ok();
+        assert!(error.to_string().contains("Failed to parse pricing file"));
+    }
+}
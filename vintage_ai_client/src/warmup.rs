@@ -0,0 +1,360 @@
+//! Warm-up of the shared cache with generic assets
+//!
+//! Many projects ask for the same generic assets: dialog frames, potion and
+//! key icons, menu blips. A [`WarmupPlan`] lists such assets, and
+//! [`warm_cache`] generates each one into the shared cache, so interactive
+//! sessions that request them again are served from disk. Plans are TOML:
+//!
+//! ```toml
+//! max_cost = 2.5
+//!
+//! [off_peak]
+//! start_hour = 1
+//! end_hour = 5
+//!
+//! [[assets]]
+//! kind = "sprite"
+//! sprite_type = "icon"
+//! description = "red healing potion"
+//!
+//! [[assets]]
+//! kind = "sound_effect"
+//! effect_type = "menu_select"
+//! duration = 0.2
+//! ```
+//!
+//! Assets are requested through the same prompts as during generation, in
+//! the default style, so only sessions in that style reuse them.
+
+use crate::AiService;
+use crate::image::ImageConfig;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Plan file read from the base directory when none is given
+pub const DEFAULT_WARMUP_FILE: &str = "warmup.toml";
+
+/// Generic asset to pre-generate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WarmupAsset {
+    Sprite {
+        sprite_type: String,
+        description: String,
+    },
+    Tile {
+        tile_type: String,
+        description: String,
+    },
+    /// Interface element, generated at UI size
+    Ui {
+        element: String,
+        description: String,
+    },
+    SoundEffect {
+        effect_type: String,
+        duration: f32,
+    },
+}
+
+impl WarmupAsset {
+    fn sprite(sprite_type: &str, description: &str) -> Self {
+        Self::Sprite {
+            sprite_type: sprite_type.to_string(),
+            description: description.to_string(),
+        }
+    }
+
+    fn tile(tile_type: &str, description: &str) -> Self {
+        Self::Tile {
+            tile_type: tile_type.to_string(),
+            description: description.to_string(),
+        }
+    }
+
+    fn ui(element: &str, description: &str) -> Self {
+        Self::Ui {
+            element: element.to_string(),
+            description: description.to_string(),
+        }
+    }
+
+    fn sound_effect(effect_type: &str, duration: f32) -> Self {
+        Self::SoundEffect {
+            effect_type: effect_type.to_string(),
+            duration,
+        }
+    }
+
+    /// Short name for logs and reports
+    pub fn label(&self) -> String {
+        match self {
+            Self::Sprite {
+                sprite_type,
+                description,
+            } => format!("{sprite_type}: {description}"),
+            Self::Tile {
+                tile_type,
+                description,
+            } => format!("tile {tile_type}: {description}"),
+            Self::Ui {
+                element,
+                description,
+            } => format!("{element}: {description}"),
+            Self::SoundEffect {
+                effect_type,
+                duration,
+            } => format!("sfx {effect_type} ({duration}s)"),
+        }
+    }
+}
+
+/// Hours of the local day in which warm-up may run. The window wraps past
+/// midnight when `end_hour` is before `start_hour`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OffPeakWindow {
+    pub start_hour: u32,
+    pub end_hour: u32,
+}
+
+impl OffPeakWindow {
+    pub fn contains<Tz: TimeZone>(&self, time: &DateTime<Tz>) -> bool {
+        let hour = time.hour();
+        if self.start_hour <= self.end_hour {
+            (self.start_hour..self.end_hour).contains(&hour)
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+
+    /// Time from `time` until the window next opens; zero inside it
+    pub fn wait_from<Tz: TimeZone>(&self, time: &DateTime<Tz>) -> Duration {
+        if self.contains(time) {
+            return Duration::zero();
+        }
+        let start = time
+            .with_hour(self.start_hour % 24)
+            .and_then(|start| start.with_minute(0))
+            .and_then(|start| start.with_second(0))
+            .and_then(|start| start.with_nanosecond(0));
+        match start {
+            Some(start) if start > *time => start - time.clone(),
+            Some(start) => start + Duration::days(1) - time.clone(),
+            None => Duration::zero(),
+        }
+    }
+}
+
+/// Assets to warm the cache with, and when
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WarmupPlan {
+    /// Wait for this window before starting, and stop when it closes
+    #[serde(default)]
+    pub off_peak: Option<OffPeakWindow>,
+    /// Stop once this much has been spent, in USD
+    #[serde(default)]
+    pub max_cost: Option<f64>,
+    pub assets: Vec<WarmupAsset>,
+}
+
+impl Default for WarmupPlan {
+    /// Frames, icons, tiles and sound effects nearly every RPG uses
+    fn default() -> Self {
+        Self {
+            off_peak: None,
+            max_cost: None,
+            assets: vec![
+                WarmupAsset::ui("dialog_frame", "dialog box frame with rounded corners"),
+                WarmupAsset::ui("menu_frame", "menu window frame with a cursor hand"),
+                WarmupAsset::ui("status_bar", "health and magic bars in a frame"),
+                WarmupAsset::sprite("icon", "red healing potion"),
+                WarmupAsset::sprite("icon", "blue magic potion"),
+                WarmupAsset::sprite("icon", "iron sword"),
+                WarmupAsset::sprite("icon", "wooden shield"),
+                WarmupAsset::sprite("icon", "brass key"),
+                WarmupAsset::sprite("icon", "gold coin"),
+                WarmupAsset::sprite("icon", "treasure chest"),
+                WarmupAsset::tile("grass", "plain grass field"),
+                WarmupAsset::tile("water", "calm water"),
+                WarmupAsset::tile("floor", "stone dungeon floor"),
+                WarmupAsset::sound_effect("menu_select", 0.2),
+                WarmupAsset::sound_effect("menu_cancel", 0.2),
+                WarmupAsset::sound_effect("hit", 0.3),
+                WarmupAsset::sound_effect("coin", 0.4),
+                WarmupAsset::sound_effect("door", 0.6),
+                WarmupAsset::sound_effect("level_up", 1.5),
+            ],
+        }
+    }
+}
+
+impl WarmupPlan {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let content = toml::to_string_pretty(self).context("Failed to serialize warm-up plan")?;
+        std::fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WarmupStatus {
+    /// Generated now, at this cost
+    Generated(f64),
+    /// Already in the cache
+    Cached,
+    Failed(String),
+    /// Not attempted because the window closed or the budget ran out
+    Skipped,
+}
+
+#[derive(Debug, Clone)]
+pub struct WarmupOutcome {
+    pub asset: WarmupAsset,
+    pub status: WarmupStatus,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WarmupReport {
+    pub outcomes: Vec<WarmupOutcome>,
+}
+
+impl WarmupReport {
+    fn count(&self, matches: impl Fn(&WarmupStatus) -> bool) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|outcome| matches(&outcome.status))
+            .count()
+    }
+
+    pub fn generated(&self) -> usize {
+        self.count(|status| matches!(status, WarmupStatus::Generated(_)))
+    }
+
+    pub fn cached(&self) -> usize {
+        self.count(|status| matches!(status, WarmupStatus::Cached))
+    }
+
+    pub fn failed(&self) -> usize {
+        self.count(|status| matches!(status, WarmupStatus::Failed(_)))
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.count(|status| matches!(status, WarmupStatus::Skipped))
+    }
+
+    pub fn total_cost(&self) -> f64 {
+        self.outcomes
+            .iter()
+            .map(|outcome| match outcome.status {
+                WarmupStatus::Generated(cost) => cost,
+                _ => 0.0,
+            })
+            .sum()
+    }
+}
+
+/// Generate every asset of the plan into the service's cache. Waits for the
+/// off-peak window first; assets left when it closes or the budget is spent
+/// are skipped. Failures are recorded and the rest still run.
+pub async fn warm_cache(service: &AiService, plan: &WarmupPlan) -> WarmupReport {
+    if let Some(window) = &plan.off_peak {
+        let wait = window.wait_from(&Local::now());
+        if wait > Duration::zero() {
+            tracing::info!(
+                "Waiting {} minutes for the off-peak window",
+                wait.num_minutes()
+            );
+            tokio::time::sleep(wait.to_std().unwrap_or_default()).await;
+        }
+    }
+
+    let images = service.image();
+    let audio = service.audio();
+    let mut report = WarmupReport::default();
+    for asset in &plan.assets {
+        let closed = plan
+            .off_peak
+            .is_some_and(|window| !window.contains(&Local::now()));
+        let spent = plan
+            .max_cost
+            .is_some_and(|max_cost| report.total_cost() >= max_cost);
+        if closed || spent {
+            report.outcomes.push(WarmupOutcome {
+                asset: asset.clone(),
+                status: WarmupStatus::Skipped,
+            });
+            continue;
+        }
+
+        let before = service
+            .token_counter
+            .lock()
+            .await
+            .get_stats()
+            .await
+            .total_cost;
+        let result = match asset {
+            WarmupAsset::Sprite {
+                sprite_type,
+                description,
+            } => images
+                .generate_sprite(sprite_type, description, None)
+                .await
+                .map(drop),
+            WarmupAsset::Tile {
+                tile_type,
+                description,
+            } => images.generate_tile(tile_type, description).await.map(drop),
+            WarmupAsset::Ui {
+                element,
+                description,
+            } => match images.sprite_prompt(element, description).await {
+                Ok(prompt) => images
+                    .generate_single(&prompt, ImageConfig::for_ui())
+                    .await
+                    .map(drop),
+                Err(e) => Err(e),
+            },
+            WarmupAsset::SoundEffect {
+                effect_type,
+                duration,
+            } => audio
+                .generate_sound_effect(effect_type, *duration)
+                .await
+                .map(drop),
+        };
+        let cost = service
+            .token_counter
+            .lock()
+            .await
+            .get_stats()
+            .await
+            .total_cost
+            - before;
+
+        let status = match result {
+            Ok(()) if cost > 0.0 => WarmupStatus::Generated(cost),
+            Ok(()) => WarmupStatus::Cached,
+            Err(e) => {
+                tracing::warn!("Failed to warm {}: {e:#}", asset.label());
+                WarmupStatus::Failed(format!("{e:#}"))
+            }
+        };
+        report.outcomes.push(WarmupOutcome {
+            asset: asset.clone(),
+            status,
+        });
+    }
+    report
+}
@@ -0,0 +1,708 @@
+//! Overworld generation
+//!
+//! [`WorldGenerator`] asks the model for the regions of a game: their biome,
+//! tags, description, the towns and dungeons they hold and the regions they
+//! border. [`WorldGraph::place`] turns the suggestions into a zone graph,
+//! joining regions the model left unconnected and placing zones with the same
+//! force-directed layout as the world map, then pins the towns and dungeons
+//! to their zones. The graph is exported as JSON, and rendered as an overview
+//! image tiled with a [`BiomeTileset`] generated in the project style.
+
+use anyhow::Result;
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::consistency::Color;
+use crate::game_types::GameConfig;
+use crate::image::ImageGenerator;
+use crate::maps::{MapColors, MarkerKind, draw_line, hash, layout_graph, put};
+use crate::text::{TextConfig, TextGenerator};
+
+/// Overview size in tiles
+pub const OVERVIEW_TILES: (u32, u32) = (32, 28);
+
+/// Edge of the overview tiles generated for an exported game
+pub const OVERVIEW_TILE_SIZE: u32 = 16;
+
+/// Tileset keys of the tiles that aren't biomes
+pub const SEA_TILE: &str = "sea";
+pub const TOWN_TILE: &str = "town";
+pub const DUNGEON_TILE: &str = "dungeon";
+
+/// Connection type of the roads added between unconnected regions
+const FALLBACK_CONNECTION: &str = "road";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Neighbour {
+    pub region: String,
+    /// How the regions are connected, e.g. "road", "bridge" or "ship"
+    pub connection_type: String,
+}
+
+/// A region as suggested by the model
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegionSuggestion {
+    pub name: String,
+    pub biome: String,
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub towns: Vec<String>,
+    #[serde(default)]
+    pub dungeons: Vec<String>,
+    #[serde(default)]
+    pub neighbours: Vec<Neighbour>,
+}
+
+/// A problem of the suggested regions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorldIssue {
+    NoRegions,
+    DuplicateRegion(String),
+    UnknownNeighbour {
+        region: String,
+        neighbour: String,
+    },
+    /// A town or dungeon listed in more than one region
+    DuplicateLocation(String),
+    /// A town or dungeon of the game config no region holds
+    MissingLocation(String),
+}
+
+impl std::fmt::Display for WorldIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoRegions => write!(f, "the world has no regions"),
+            Self::DuplicateRegion(name) => write!(f, "region '{name}' is listed twice"),
+            Self::UnknownNeighbour { region, neighbour } => {
+                write!(f, "region '{region}' borders unknown region '{neighbour}'")
+            }
+            Self::DuplicateLocation(name) => {
+                write!(f, "'{name}' is placed in more than one region")
+            }
+            Self::MissingLocation(name) => write!(f, "'{name}' is not placed in any region"),
+        }
+    }
+}
+
+/// Check suggested regions against the towns and dungeons of the config
+pub fn validate_regions(config: &GameConfig, regions: &[RegionSuggestion]) -> Vec<WorldIssue> {
+    let mut issues = Vec::new();
+    if regions.is_empty() {
+        issues.push(WorldIssue::NoRegions);
+    }
+
+    let mut names = HashSet::new();
+    let mut locations = HashSet::new();
+    for region in regions {
+        if !names.insert(region.name.as_str()) {
+            issues.push(WorldIssue::DuplicateRegion(region.name.clone()));
+        }
+        for location in region.towns.iter().chain(&region.dungeons) {
+            if !locations.insert(location.as_str()) {
+                issues.push(WorldIssue::DuplicateLocation(location.clone()));
+            }
+        }
+    }
+    for region in regions {
+        for neighbour in &region.neighbours {
+            if !names.contains(neighbour.region.as_str()) {
+                issues.push(WorldIssue::UnknownNeighbour {
+                    region: region.name.clone(),
+                    neighbour: neighbour.region.clone(),
+                });
+            }
+        }
+    }
+    let expected = config
+        .towns
+        .iter()
+        .map(|town| &town.name)
+        .chain(config.dungeons.iter().map(|dungeon| &dungeon.name));
+    for name in expected {
+        if !locations.contains(name.as_str()) {
+            issues.push(WorldIssue::MissingLocation(name.clone()));
+        }
+    }
+
+    issues
+}
+
+/// Zone of the world graph; coordinates are fractions of the overview size
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Zone {
+    pub name: String,
+    pub biome: String,
+    pub tags: Vec<String>,
+    pub description: String,
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ZoneConnection {
+    pub from: String,
+    pub to: String,
+    pub connection_type: String,
+}
+
+/// Town or dungeon pinned to a zone
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorldNode {
+    pub name: String,
+    pub kind: MarkerKind,
+    pub zone: String,
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Zones, their connections and the towns and dungeons placed in them
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorldGraph {
+    pub zones: Vec<Zone>,
+    pub connections: Vec<ZoneConnection>,
+    pub nodes: Vec<WorldNode>,
+}
+
+impl WorldGraph {
+    /// Place suggested regions on the map
+    ///
+    /// Duplicate regions and links to unknown regions are dropped. Groups of
+    /// regions without a path between them are chained together by roads,
+    /// so every zone can be reached.
+    pub fn place(regions: &[RegionSuggestion]) -> Self {
+        let mut suggestions: Vec<&RegionSuggestion> = Vec::new();
+        for region in regions {
+            if !suggestions.iter().any(|s| s.name == region.name) {
+                suggestions.push(region);
+            }
+        }
+        let index_of = |name: &str| suggestions.iter().position(|s| s.name == name);
+
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        let mut connections = Vec::new();
+        for (from, region) in suggestions.iter().enumerate() {
+            for neighbour in &region.neighbours {
+                let Some(to) = index_of(&neighbour.region) else {
+                    continue;
+                };
+                if from == to || edges.contains(&(from, to)) || edges.contains(&(to, from)) {
+                    continue;
+                }
+                edges.push((from, to));
+                connections.push(ZoneConnection {
+                    from: region.name.clone(),
+                    to: neighbour.region.clone(),
+                    connection_type: neighbour.connection_type.clone(),
+                });
+            }
+        }
+
+        // Chain each unconnected group to the one before it
+        let mut visited = vec![false; suggestions.len()];
+        let mut previous: Option<usize> = None;
+        for start in 0..suggestions.len() {
+            if visited[start] {
+                continue;
+            }
+            let mut stack = vec![start];
+            let mut last = start;
+            visited[start] = true;
+            while let Some(zone) = stack.pop() {
+                last = zone;
+                for &(a, b) in &edges {
+                    let next = if a == zone {
+                        b
+                    } else if b == zone {
+                        a
+                    } else {
+                        continue;
+                    };
+                    if !visited[next] {
+                        visited[next] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+            if let Some(previous) = previous {
+                edges.push((previous, start));
+                connections.push(ZoneConnection {
+                    from: suggestions[previous].name.clone(),
+                    to: suggestions[start].name.clone(),
+                    connection_type: FALLBACK_CONNECTION.to_string(),
+                });
+            }
+            previous = Some(last);
+        }
+
+        let zones: Vec<Zone> = suggestions
+            .iter()
+            .zip(layout_graph(suggestions.len(), &edges))
+            .map(|(region, (x, y))| Zone {
+                name: region.name.clone(),
+                biome: region.biome.clone(),
+                tags: region.tags.clone(),
+                description: region.description.clone(),
+                x,
+                y,
+            })
+            .collect();
+
+        let mut nodes = Vec::new();
+        for (region, zone) in suggestions.iter().zip(&zones) {
+            let locations = region
+                .towns
+                .iter()
+                .map(|name| (name, MarkerKind::Town))
+                .chain(
+                    region
+                        .dungeons
+                        .iter()
+                        .map(|name| (name, MarkerKind::Dungeon)),
+                );
+            // Circle the zone's center, one slot per location
+            for (slot, (name, kind)) in locations.enumerate() {
+                let angle = slot as f32 * 2.4;
+                nodes.push(WorldNode {
+                    name: name.clone(),
+                    kind,
+                    zone: zone.name.clone(),
+                    x: (zone.x + angle.cos() * 0.05).clamp(0.02, 0.98),
+                    y: (zone.y + angle.sin() * 0.05).clamp(0.02, 0.98),
+                });
+            }
+        }
+
+        Self {
+            zones,
+            connections,
+            nodes,
+        }
+    }
+
+    pub fn zone(&self, name: &str) -> Option<&Zone> {
+        self.zones.iter().find(|zone| zone.name == name)
+    }
+
+    /// Distinct biomes of the zones, in order of appearance
+    pub fn biomes(&self) -> Vec<&str> {
+        let mut biomes: Vec<&str> = Vec::new();
+        for zone in &self.zones {
+            if !biomes.contains(&zone.biome.as_str()) {
+                biomes.push(&zone.biome);
+            }
+        }
+        biomes
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Render the overview, one tileset tile per map cell
+    ///
+    /// Cells take the tile of the nearest zone's biome, or the sea tile
+    /// beyond the coast. Missing tiles fall back to flat palette colors.
+    pub fn render_overview(&self, tileset: &BiomeTileset, palette: &[Color]) -> RgbaImage {
+        let (columns, rows) = OVERVIEW_TILES;
+        let size = tileset.tile_size;
+        let colors = MapColors::from_palette(palette);
+        let land_radius = (0.55 / (self.zones.len().max(1) as f32).sqrt()).clamp(0.12, 0.3);
+
+        let mut img = RgbaImage::new(columns * size, rows * size);
+        for row in 0..rows {
+            for column in 0..columns {
+                let fx = (column as f32 + 0.5) / columns as f32;
+                let fy = (row as f32 + 0.5) / rows as f32;
+                let noise = (hash(&[column as u64, row as u64]) % 100) as f32 / 100.0 * 0.04;
+                let zone = self
+                    .zones
+                    .iter()
+                    .map(|zone| (zone, (fx - zone.x).hypot(fy - zone.y)))
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .filter(|(_, distance)| distance + noise < land_radius)
+                    .map(|(zone, _)| zone);
+
+                let (x, y) = (column * size, row * size);
+                let key = zone.map_or(SEA_TILE, |zone| zone.biome.as_str());
+                match tileset.tile(key) {
+                    Some(tile) => imageops::replace(&mut img, tile, x.into(), y.into()),
+                    None => {
+                        let color = match zone {
+                            Some(zone) => colors.biome(&zone.biome),
+                            None => colors.sea,
+                        };
+                        fill(&mut img, x as i32, y as i32, size, color);
+                    }
+                }
+            }
+        }
+
+        let (width, height) = (img.width() as f32, img.height() as f32);
+        let to_pixel = |x: f32, y: f32| ((x * width) as i32, (y * height) as i32);
+        for connection in &self.connections {
+            if let (Some(from), Some(to)) = (self.zone(&connection.from), self.zone(&connection.to))
+            {
+                let sea_route = ["ship", "boat", "sea"]
+                    .iter()
+                    .any(|word| connection.connection_type.to_lowercase().contains(word));
+                draw_line(
+                    &mut img,
+                    to_pixel(from.x, from.y),
+                    to_pixel(to.x, to.y),
+                    colors.route,
+                    if sea_route { size as i32 / 2 } else { 1 },
+                );
+            }
+        }
+
+        for node in &self.nodes {
+            let (cx, cy) = to_pixel(node.x, node.y);
+            let (x, y) = (cx - size as i32 / 2, cy - size as i32 / 2);
+            let (key, color) = match node.kind {
+                MarkerKind::Town => (TOWN_TILE, colors.town),
+                MarkerKind::Dungeon => (DUNGEON_TILE, colors.dungeon),
+            };
+            match tileset.tile(key) {
+                Some(tile) => imageops::overlay(&mut img, tile, x.into(), y.into()),
+                None => {
+                    fill(&mut img, x, y, size, colors.outline);
+                    fill(&mut img, x + 1, y + 1, size.saturating_sub(2), color);
+                }
+            }
+        }
+
+        img
+    }
+}
+
+/// Tiles of the overview, keyed by lowercase biome name
+#[derive(Debug, Clone)]
+pub struct BiomeTileset {
+    pub tile_size: u32,
+    tiles: BTreeMap<String, RgbaImage>,
+}
+
+impl BiomeTileset {
+    pub fn new(tile_size: u32) -> Self {
+        Self {
+            tile_size: tile_size.max(1),
+            tiles: BTreeMap::new(),
+        }
+    }
+
+    /// Add a tile, scaled to the tile size
+    pub fn with_tile(mut self, key: &str, tile: &DynamicImage) -> Self {
+        let tile = tile
+            .resize_exact(self.tile_size, self.tile_size, FilterType::Nearest)
+            .to_rgba8();
+        self.tiles.insert(key.to_lowercase(), tile);
+        self
+    }
+
+    pub fn tile(&self, key: &str) -> Option<&RgbaImage> {
+        self.tiles.get(&key.to_lowercase())
+    }
+
+    /// Generate a seamless tile for every biome of the graph and the sea,
+    /// and sprites for the town and dungeon markers, in the project style
+    pub async fn generate(
+        images: &ImageGenerator,
+        graph: &WorldGraph,
+        tile_size: u32,
+    ) -> Result<Self> {
+        let mut tileset = Self::new(tile_size);
+        for biome in graph.biomes().into_iter().chain([SEA_TILE]) {
+            if tileset.tile(biome).is_some() {
+                continue;
+            }
+            let data = images
+                .generate_tile(
+                    &biome.to_lowercase(),
+                    &format!("overworld {biome} terrain seen from above"),
+                )
+                .await?;
+            tileset = tileset.with_tile(biome, &image::load_from_memory(&data)?);
+        }
+        for (key, description) in [
+            (TOWN_TILE, "small overworld town icon with a few roofs"),
+            (DUNGEON_TILE, "small overworld dungeon entrance icon"),
+        ] {
+            let data = images
+                .generate_sprite("map_icon", description, None)
+                .await?;
+            tileset = tileset.with_tile(key, &image::load_from_memory(&data)?);
+        }
+        Ok(tileset)
+    }
+}
+
+pub struct WorldGenerator {
+    text: TextGenerator,
+}
+
+impl WorldGenerator {
+    pub fn new(text: TextGenerator) -> Self {
+        Self { text }
+    }
+
+    /// Suggest the regions of a game and place them
+    pub async fn generate(&self, config: &GameConfig) -> Result<WorldGraph> {
+        let regions = self.suggest_regions(config).await?;
+        Ok(WorldGraph::place(&regions))
+    }
+
+    /// Ask for the regions of a game, placing every town and dungeon of the
+    /// config
+    ///
+    /// Suggestions with issues are sent back once with the issues listed; if
+    /// the second answer still has issues they are returned as an error.
+    pub async fn suggest_regions(&self, config: &GameConfig) -> Result<Vec<RegionSuggestion>> {
+        let hints = config
+            .world
+            .regions
+            .iter()
+            .map(|region| {
+                format!(
+                    "- {} ({}): {}",
+                    region.name, region.biome, region.description
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let towns: Vec<&str> = config.towns.iter().map(|t| t.name.as_str()).collect();
+        let dungeons: Vec<&str> = config.dungeons.iter().map(|d| d.name.as_str()).collect();
+
+        let prompt = format!(
+            "Design the overworld regions of {world}, the world of the 16-bit RPG {game}.\n\
+            Setting: {setting}\nWorld size: {size}\n\
+            Regions planned so far:\n{hints}\n\n\
+            Towns: {towns}\nDungeons: {dungeons}\n\n\
+            Place every town and every dungeon in exactly one region. Give each region one \
+            biome and a few tags for its mood and terrain, and list the regions it borders \
+            and how they connect (road, bridge, cave, ship, ...). The player should be able \
+            to reach every region.\n\n\
+            Return a JSON array of regions of the form {{\"name\": \"string\", \"biome\": \
+            \"string\", \"description\": \"string\", \"tags\": [\"string\"], \"towns\": \
+            [\"town name\"], \"dungeons\": [\"dungeon name\"], \"neighbours\": [{{\"region\": \
+            \"region name\", \"connection_type\": \"string\"}}]}}.",
+            world = config.world.name,
+            game = config.name,
+            setting = config.setting,
+            size = config.world.size,
+            towns = towns.join(", "),
+            dungeons = dungeons.join(", "),
+        );
+        let prompt = config.localize_prompt(prompt);
+        let text_config = config.seed_text(TextConfig {
+            max_tokens: 3000,
+            ..TextConfig::for_world_building()
+        });
+
+        let regions: Vec<RegionSuggestion> = self
+            .text
+            .generate_structured(&prompt, text_config.clone())
+            .await?;
+        let issues = validate_regions(config, &regions);
+        if issues.is_empty() {
+            return Ok(regions);
+        }
+
+        let retry_prompt = format!(
+            "{prompt}\n\nA previous answer had these problems, avoid them: {}.",
+            join_issues(&issues)
+        );
+        let regions: Vec<RegionSuggestion> = self
+            .text
+            .generate_structured(&retry_prompt, text_config)
+            .await?;
+        let issues = validate_regions(config, &regions);
+        if !issues.is_empty() {
+            anyhow::bail!("World regions are invalid: {}", join_issues(&issues));
+        }
+        Ok(regions)
+    }
+}
+
+fn fill(img: &mut RgbaImage, x: i32, y: i32, size: u32, color: Rgba<u8>) {
+    for dy in 0..size as i32 {
+        for dx in 0..size as i32 {
+            put(img, x + dx, y + dy, color);
+        }
+    }
+}
+
+fn join_issues(issues: &[WorldIssue]) -> String {
+    issues
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_types::fixtures;
+
+    fn region(
+        name: &str,
+        towns: &[&str],
+        dungeons: &[&str],
+        neighbours: &[&str],
+    ) -> RegionSuggestion {
+        RegionSuggestion {
+            name: name.to_string(),
+            biome: format!("{name} biome"),
+            description: String::new(),
+            tags: Vec::new(),
+            towns: towns.iter().map(|s| s.to_string()).collect(),
+            dungeons: dungeons.iter().map(|s| s.to_string()).collect(),
+            neighbours: neighbours
+                .iter()
+                .map(|region| Neighbour {
+                    region: region.to_string(),
+                    connection_type: "bridge".to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Zones reachable from the first one over the graph's connections
+    fn reachable(graph: &WorldGraph) -> HashSet<&str> {
+        let mut seen = HashSet::from([graph.zones[0].name.as_str()]);
+        let mut stack = vec![graph.zones[0].name.as_str()];
+        while let Some(zone) = stack.pop() {
+            for connection in &graph.connections {
+                let next = if connection.from == zone {
+                    &connection.to
+                } else if connection.to == zone {
+                    &connection.from
+                } else {
+                    continue;
+                };
+                if seen.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+        seen
+    }
+
+    #[test]
+    fn place_drops_duplicates_and_unknown_neighbours() {
+        let graph = WorldGraph::place(&[
+            region("Greenvale", &["Aldwyn"], &[], &["Korin Wastes", "Nowhere"]),
+            region("Korin Wastes", &[], &["Sunken Vault"], &["Greenvale"]),
+            region("Greenvale", &["Elsewhere"], &[], &[]),
+        ]);
+
+        let zones: Vec<_> = graph.zones.iter().map(|zone| zone.name.as_str()).collect();
+        assert_eq!(zones, ["Greenvale", "Korin Wastes"]);
+        assert_eq!(
+            graph.connections,
+            [ZoneConnection {
+                from: "Greenvale".to_string(),
+                to: "Korin Wastes".to_string(),
+                connection_type: "bridge".to_string(),
+            }]
+        );
+        let nodes: Vec<_> = graph.nodes.iter().map(|node| node.name.as_str()).collect();
+        assert_eq!(nodes, ["Aldwyn", "Sunken Vault"]);
+    }
+
+    #[test]
+    fn place_connects_unconnected_groups() {
+        let graph = WorldGraph::place(&[
+            region("A", &[], &[], &["B"]),
+            region("B", &[], &[], &[]),
+            region("C", &[], &[], &["D"]),
+            region("D", &[], &[], &[]),
+            region("E", &[], &[], &[]),
+        ]);
+
+        assert_eq!(reachable(&graph).len(), 5);
+        let roads = graph
+            .connections
+            .iter()
+            .filter(|connection| connection.connection_type == FALLBACK_CONNECTION)
+            .count();
+        assert_eq!(roads, 2);
+    }
+
+    #[test]
+    fn place_pins_locations_to_their_zones() {
+        let graph = WorldGraph::place(&[
+            region(
+                "Greenvale",
+                &["Aldwyn", "Port Sylva"],
+                &["Old Mine"],
+                &["Korin Wastes"],
+            ),
+            region("Korin Wastes", &[], &["Sunken Vault"], &[]),
+        ]);
+
+        assert_eq!(graph.nodes.len(), 4);
+        for node in &graph.nodes {
+            let zone = graph.zone(&node.zone).unwrap();
+            assert!((0.02..=0.98).contains(&node.x) && (0.02..=0.98).contains(&node.y));
+            assert!((node.x - zone.x).hypot(node.y - zone.y) <= 0.051);
+        }
+        let kinds: Vec<_> = graph.nodes.iter().map(|node| node.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                MarkerKind::Town,
+                MarkerKind::Town,
+                MarkerKind::Dungeon,
+                MarkerKind::Dungeon
+            ]
+        );
+        assert_eq!(graph.nodes[3].zone, "Korin Wastes");
+    }
+
+    #[test]
+    fn validate_regions_reports_every_issue() {
+        let config = fixtures::config();
+        assert_eq!(
+            validate_regions(&config, &[]),
+            [
+                WorldIssue::NoRegions,
+                WorldIssue::MissingLocation("Aldwyn".to_string()),
+                WorldIssue::MissingLocation("Port Sylva".to_string()),
+                WorldIssue::MissingLocation("Sunken Vault".to_string()),
+            ]
+        );
+
+        let regions = [
+            region(
+                "Greenvale",
+                &["Aldwyn", "Port Sylva"],
+                &[],
+                &["Korin Wastes"],
+            ),
+            region("Korin Wastes", &[], &["Sunken Vault"], &["Greenvale"]),
+        ];
+        assert!(validate_regions(&config, &regions).is_empty());
+
+        let regions = [
+            region("Greenvale", &["Aldwyn"], &[], &["Mistwood"]),
+            region("Greenvale", &["Aldwyn"], &["Sunken Vault"], &[]),
+        ];
+        assert_eq!(
+            validate_regions(&config, &regions),
+            [
+                WorldIssue::DuplicateRegion("Greenvale".to_string()),
+                WorldIssue::DuplicateLocation("Aldwyn".to_string()),
+                WorldIssue::UnknownNeighbour {
+                    region: "Greenvale".to_string(),
+                    neighbour: "Mistwood".to_string(),
+                },
+                WorldIssue::MissingLocation("Port Sylva".to_string()),
+            ]
+        );
+    }
+}
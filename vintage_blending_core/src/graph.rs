@@ -1,6 +1,11 @@
 //! Weighted graph implementation for game blending
 //!
-//! Uses petgraph for efficient graph operations
+//! Uses petgraph for efficient graph operations. Besides blend paths the
+//! graph offers analytics over the whole network: communities of related
+//! games by the Louvain method, the chain of most similar games between two
+//! games, centrality scores and the bridge games that link communities.
+//! Distances along the graph are `-ln(similarity)`, so the shortest chain is
+//! the one whose similarities multiply to the most.
 
 use anyhow::Result;
 use petgraph::Undirected;
@@ -9,7 +14,10 @@ use petgraph::data::FromElements;
 use petgraph::graph::{Graph, NodeIndex};
 use petgraph::visit::EdgeRef;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::fmt::Write as _;
+use std::path::Path;
 
 use crate::types::{CompatibilityEdge, Conflict, GameMetadata, Synergy};
 
@@ -64,8 +72,20 @@ impl GameGraph {
         })
     }
 
-    /// Find the best blend path between multiple games
+    /// Find the best blend path between multiple games, all weighted alike
     pub fn find_blend_path(&self, game_ids: &[String]) -> Result<BlendPath> {
+        self.find_weighted_blend_path(game_ids, &HashMap::new())
+    }
+
+    /// Find the best blend path between games that make up unequal shares
+    /// of the blend. `weights` are relative, missing games weigh 1. The
+    /// synergies and conflicts of a pair count in proportion to how evenly
+    /// the pair is weighted, see [`pair_influence`].
+    pub fn find_weighted_blend_path(
+        &self,
+        game_ids: &[String],
+        weights: &HashMap<String, f32>,
+    ) -> Result<BlendPath> {
         if game_ids.len() < 2 {
             anyhow::bail!("Need at least 2 games to blend");
         }
@@ -114,12 +134,15 @@ impl GameGraph {
         let mut synergies = Vec::new();
         let mut conflicts = Vec::new();
 
+        let shares = normalize_weights(game_ids, weights);
+
         for edge in mst_graph.edge_indices() {
             let (src, dst) = mst_graph.edge_endpoints(edge).unwrap();
             let game1_id = &mst_graph[src];
             let game2_id = &mst_graph[dst];
 
-            let edge_analysis = self.analyze_edge(game1_id, game2_id)?;
+            let influence = pair_influence(shares[game1_id], shares[game2_id]);
+            let edge_analysis = self.analyze_edge(game1_id, game2_id)?.weighted(influence);
             synergies.extend(edge_analysis.synergies);
             conflicts.extend(edge_analysis.conflicts);
         }
@@ -129,6 +152,7 @@ impl GameGraph {
             total_compatibility,
             synergies,
             conflicts,
+            weights: shares,
         })
     }
 
@@ -240,6 +264,411 @@ impl GameGraph {
             Vec::new()
         }
     }
+
+    /// Export the similarity network as GraphML (NetworkX, yEd, Gephi)
+    pub fn to_graphml(&self) -> String {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        for (id, attr_type) in [
+            ("name", "string"),
+            ("year", "int"),
+            ("era", "string"),
+            ("genre", "string"),
+            ("mechanic_tags", "string"),
+            ("mood_tags", "string"),
+        ] {
+            let _ = writeln!(
+                out,
+                "  <key id=\"{id}\" for=\"node\" attr.name=\"{id}\" attr.type=\"{attr_type}\"/>"
+            );
+        }
+        out.push_str(
+            "  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>\n",
+        );
+        out.push_str("  <graph id=\"vintage_games\" edgedefault=\"undirected\">\n");
+
+        for (game_id, meta) in self.sorted_metadata() {
+            let _ = writeln!(out, "    <node id=\"{}\">", xml_escape(game_id));
+            for (key, value) in node_attributes(meta) {
+                let _ = writeln!(
+                    out,
+                    "      <data key=\"{key}\">{}</data>",
+                    xml_escape(&value)
+                );
+            }
+            out.push_str("    </node>\n");
+        }
+
+        for (i, (source, target, weight)) in self.sorted_edges().into_iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "    <edge id=\"e{i}\" source=\"{}\" target=\"{}\">",
+                xml_escape(source),
+                xml_escape(target)
+            );
+            let _ = writeln!(out, "      <data key=\"weight\">{weight}</data>");
+            out.push_str("    </edge>\n");
+        }
+
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    /// Export the similarity network as GEXF 1.3 (Gephi)
+    pub fn to_gexf(&self) -> String {
+        const NODE_ATTRIBUTES: [(&str, &str); 5] = [
+            ("year", "integer"),
+            ("era", "string"),
+            ("genre", "string"),
+            ("mechanic_tags", "string"),
+            ("mood_tags", "string"),
+        ];
+
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n");
+        out.push_str("  <meta>\n    <creator>vintage_blending_core</creator>\n");
+        out.push_str("    <description>Vintage game similarity network</description>\n");
+        out.push_str("  </meta>\n");
+        out.push_str("  <graph mode=\"static\" defaultedgetype=\"undirected\">\n");
+        out.push_str("    <attributes class=\"node\">\n");
+        for (id, attr_type) in NODE_ATTRIBUTES {
+            let _ = writeln!(
+                out,
+                "      <attribute id=\"{id}\" title=\"{id}\" type=\"{attr_type}\"/>"
+            );
+        }
+        out.push_str("    </attributes>\n");
+
+        out.push_str("    <nodes>\n");
+        for (game_id, meta) in self.sorted_metadata() {
+            let _ = writeln!(
+                out,
+                "      <node id=\"{}\" label=\"{}\">",
+                xml_escape(game_id),
+                xml_escape(&meta.name)
+            );
+            out.push_str("        <attvalues>\n");
+            for (key, value) in node_attributes(meta).into_iter().skip(1) {
+                let _ = writeln!(
+                    out,
+                    "          <attvalue for=\"{key}\" value=\"{}\"/>",
+                    xml_escape(&value)
+                );
+            }
+            out.push_str("        </attvalues>\n      </node>\n");
+        }
+        out.push_str("    </nodes>\n");
+
+        out.push_str("    <edges>\n");
+        for (i, (source, target, weight)) in self.sorted_edges().into_iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "      <edge id=\"{i}\" source=\"{}\" target=\"{}\" weight=\"{weight}\"/>",
+                xml_escape(source),
+                xml_escape(target)
+            );
+        }
+        out.push_str("    </edges>\n  </graph>\n</gexf>\n");
+        out
+    }
+
+    /// Write the graph to a file, choosing GraphML or GEXF from the extension
+    pub fn export_to_file(&self, path: &Path) -> Result<()> {
+        let content = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("graphml") | Some("xml") => self.to_graphml(),
+            Some("gexf") => self.to_gexf(),
+            other => anyhow::bail!("Unsupported graph export format: {other:?}"),
+        };
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Metadata ordered by game ID so exports are stable
+    fn sorted_metadata(&self) -> Vec<(&String, &GameMetadata)> {
+        let mut games: Vec<_> = self.metadata.iter().collect();
+        games.sort_by(|a, b| a.0.cmp(b.0));
+        games
+    }
+
+    /// Edges as (source, target, weight), ordered by endpoints
+    fn sorted_edges(&self) -> Vec<(&str, &str, f32)> {
+        let mut edges: Vec<_> = self
+            .graph
+            .edge_references()
+            .map(|edge| {
+                let a = self.graph[edge.source()].as_str();
+                let b = self.graph[edge.target()].as_str();
+                let (source, target) = if a <= b { (a, b) } else { (b, a) };
+                (source, target, *edge.weight())
+            })
+            .collect();
+        edges.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+        edges
+    }
+
+    /// Communities of closely related games found by the Louvain method
+    pub fn communities(&self) -> Communities {
+        let (ids, adjacency) = self.adjacency();
+        let membership = louvain(&adjacency);
+
+        let mut groups: Vec<Vec<String>> = Vec::new();
+        let mut group_of: HashMap<usize, usize> = HashMap::new();
+        for (id, community) in ids.iter().zip(&membership) {
+            let group = *group_of.entry(*community).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[group].push(id.to_string());
+        }
+        // IDs are visited in order, so each group is sorted already and
+        // the stable sort keeps groups of equal size in order of their
+        // first game
+        groups.sort_by_key(|group| std::cmp::Reverse(group.len()));
+
+        let membership: HashMap<String, usize> = groups
+            .iter()
+            .enumerate()
+            .flat_map(|(index, group)| group.iter().map(move |id| (id.clone(), index)))
+            .collect();
+        let positions: Vec<usize> = ids.iter().map(|id| membership[*id]).collect();
+        let modularity = modularity(&adjacency, &positions) as f32;
+        Communities {
+            groups,
+            membership,
+            modularity,
+        }
+    }
+
+    /// The chain of games from `from` to `to` whose similarities multiply
+    /// to the most; `None` if either game is unknown or they aren't linked
+    pub fn shortest_path(&self, from: &str, to: &str) -> Option<GamePath> {
+        let (ids, adjacency) = self.adjacency();
+        let source = ids.iter().position(|id| *id == from)?;
+        let target = ids.iter().position(|id| *id == to)?;
+        let paths = shortest_paths(&adjacency, source);
+        if paths.distance[target].is_infinite() {
+            return None;
+        }
+
+        let mut chain = vec![target];
+        while let Some(&previous) = chain
+            .last()
+            .and_then(|node| paths.predecessors[*node].first())
+        {
+            chain.push(previous);
+        }
+        chain.reverse();
+
+        let similarity = chain
+            .windows(2)
+            .map(|pair| {
+                adjacency[pair[0]]
+                    .iter()
+                    .find(|(other, _)| *other == pair[1])
+                    .map_or(0.0, |(_, weight)| *weight as f32)
+            })
+            .product();
+        Some(GamePath {
+            games: chain
+                .into_iter()
+                .map(|node| ids[node].to_string())
+                .collect(),
+            similarity,
+        })
+    }
+
+    /// Degree and betweenness centrality of every game, most between first
+    pub fn centrality(&self) -> Vec<Centrality> {
+        let (ids, adjacency) = self.adjacency();
+        let count = ids.len();
+        let mut betweenness = vec![0.0; count];
+
+        // Brandes' algorithm over the weighted distances
+        for source in 0..count {
+            let paths = shortest_paths(&adjacency, source);
+            let mut dependency = vec![0.0; count];
+            for &node in paths.order.iter().rev() {
+                for &previous in &paths.predecessors[node] {
+                    dependency[previous] +=
+                        paths.sigma[previous] / paths.sigma[node] * (1.0 + dependency[node]);
+                }
+                if node != source {
+                    betweenness[node] += dependency[node];
+                }
+            }
+        }
+
+        // Every pair was counted from both ends
+        let pairs = if count > 2 {
+            ((count - 1) * (count - 2)) as f64
+        } else {
+            1.0
+        };
+        let others = count.saturating_sub(1).max(1) as f64;
+        let mut scores: Vec<Centrality> = ids
+            .iter()
+            .enumerate()
+            .map(|(node, id)| Centrality {
+                game_id: id.to_string(),
+                degree: (adjacency[node].iter().map(|(_, w)| w).sum::<f64>() / others) as f32,
+                betweenness: (betweenness[node] / pairs) as f32,
+            })
+            .collect();
+        scores.sort_by(|a, b| {
+            b.betweenness
+                .total_cmp(&a.betweenness)
+                .then_with(|| b.degree.total_cmp(&a.degree))
+                .then_with(|| a.game_id.cmp(&b.game_id))
+        });
+        scores
+    }
+
+    /// Games whose compatibility is spread the most over other communities
+    /// than their own, strongest bridges first, at most `limit`
+    pub fn bridge_games(&self, communities: &Communities, limit: usize) -> Vec<BridgeGame> {
+        let mut bridges: Vec<BridgeGame> = self
+            .sorted_metadata()
+            .into_iter()
+            .filter_map(|(game_id, _)| {
+                let community = communities.community_of(game_id)?;
+                let mut links: BTreeMap<usize, f32> = BTreeMap::new();
+                for edge in self.graph.edges(self.node_lookup[game_id]) {
+                    let other = if edge.source() == self.node_lookup[game_id] {
+                        edge.target()
+                    } else {
+                        edge.source()
+                    };
+                    if let Some(other_community) = communities.community_of(&self.graph[other]) {
+                        *links.entry(other_community).or_default() += *edge.weight();
+                    }
+                }
+                let total: f32 = links.values().sum();
+                if total <= 0.0 {
+                    return None;
+                }
+
+                let participation = 1.0 - links.values().map(|w| (w / total).powi(2)).sum::<f32>();
+                let mut linked: Vec<(usize, f32)> = links
+                    .into_iter()
+                    .filter(|(other, _)| *other != community)
+                    .map(|(other, weight)| (other, weight / total))
+                    .collect();
+                if linked.is_empty() {
+                    return None;
+                }
+                linked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                Some(BridgeGame {
+                    game_id: game_id.clone(),
+                    community,
+                    participation,
+                    linked,
+                })
+            })
+            .collect();
+        bridges.sort_by(|a, b| {
+            b.participation
+                .total_cmp(&a.participation)
+                .then_with(|| a.game_id.cmp(&b.game_id))
+        });
+        bridges.truncate(limit);
+        bridges
+    }
+
+    /// The most compatible pair of games for every two communities, as
+    /// (game, game, compatibility), most compatible first, at most `limit`
+    pub fn cross_community_blends(
+        &self,
+        communities: &Communities,
+        limit: usize,
+    ) -> Vec<(String, String, f32)> {
+        let mut best: BTreeMap<(usize, usize), (&str, &str, f32)> = BTreeMap::new();
+        for (source, target, weight) in self.sorted_edges() {
+            let (Some(a), Some(b)) = (
+                communities.community_of(source),
+                communities.community_of(target),
+            ) else {
+                continue;
+            };
+            if a == b {
+                continue;
+            }
+            let key = (a.min(b), a.max(b));
+            if best.get(&key).is_none_or(|(_, _, w)| weight > *w) {
+                best.insert(key, (source, target, weight));
+            }
+        }
+
+        let mut blends: Vec<(String, String, f32)> = best
+            .into_values()
+            .map(|(a, b, weight)| (a.to_string(), b.to_string(), weight))
+            .collect();
+        blends.sort_by(|a, b| {
+            b.2.total_cmp(&a.2)
+                .then_with(|| (&a.0, &a.1).cmp(&(&b.0, &b.1)))
+        });
+        blends.truncate(limit);
+        blends
+    }
+
+    /// Game IDs in order and the weighted neighbours of each, by their
+    /// position in that order
+    fn adjacency(&self) -> (Vec<&str>, Adjacency) {
+        let ids: Vec<&str> = self
+            .sorted_metadata()
+            .into_iter()
+            .map(|(id, _)| id.as_str())
+            .collect();
+        let position: HashMap<&str, usize> = ids
+            .iter()
+            .enumerate()
+            .map(|(index, id)| (*id, index))
+            .collect();
+
+        let mut adjacency = vec![Vec::new(); ids.len()];
+        for (source, target, weight) in self.sorted_edges() {
+            let (a, b) = (position[source], position[target]);
+            adjacency[a].push((b, f64::from(weight)));
+            adjacency[b].push((a, f64::from(weight)));
+        }
+        (ids, adjacency)
+    }
+}
+
+/// Node attributes shared by the exporters; `name` comes first
+fn node_attributes(meta: &GameMetadata) -> [(&'static str, String); 6] {
+    // The strongest genre affinity stands in for the game's genre
+    let genre = meta
+        .genre_affinities
+        .iter()
+        .max_by(|a, b| a.1.total_cmp(b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(genre, _)| genre.clone())
+        .unwrap_or_default();
+
+    [
+        ("name", meta.name.clone()),
+        ("year", meta.year.to_string()),
+        ("era", meta.era_category.clone()),
+        ("genre", genre),
+        ("mechanic_tags", meta.mechanic_tags.join(",")),
+        ("mood_tags", meta.mood_tags.join(",")),
+    ]
+}
+
+fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
 }
 
 /// Result of finding an optimal blend path
@@ -249,4 +678,544 @@ pub struct BlendPath {
     pub total_compatibility: f32,
     pub synergies: Vec<Synergy>,
     pub conflicts: Vec<Conflict>,
+    /// Share of the blend each game makes up, summing to 1
+    #[serde(default)]
+    pub weights: HashMap<String, f32>,
+}
+
+/// Communities of the game graph
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Communities {
+    /// Game IDs of each community, largest first, IDs sorted
+    pub groups: Vec<Vec<String>>,
+    /// Index into `groups` by game ID
+    pub membership: HashMap<String, usize>,
+    /// Modularity of the partition, from -0.5 to 1; above 0.3 the
+    /// communities are clearly denser than chance
+    pub modularity: f32,
+}
+
+impl Communities {
+    /// Index of the community a game belongs to
+    pub fn community_of(&self, game_id: &str) -> Option<usize> {
+        self.membership.get(game_id).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+}
+
+/// Chain of games leading from one game to another
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GamePath {
+    /// Game IDs from the first game to the last
+    pub games: Vec<String>,
+    /// Product of the similarities along the chain
+    pub similarity: f32,
+}
+
+/// How central a game is to the graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Centrality {
+    pub game_id: String,
+    /// Mean compatibility with every other game, 0-1
+    pub degree: f32,
+    /// Share of the shortest chains between other games that pass through
+    /// this one, 0-1
+    pub betweenness: f32,
+}
+
+/// A game linking its community to others
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BridgeGame {
+    pub game_id: String,
+    /// Index of the game's own community
+    pub community: usize,
+    /// Participation coefficient: 0 when all of the game's compatibility
+    /// stays in its community, towards 1 as it spreads over many
+    pub participation: f32,
+    /// Other communities the game links to, with the share of its
+    /// compatibility going to each, strongest first
+    pub linked: Vec<(usize, f32)>,
+}
+
+/// Weighted neighbours of each node, by the node's position
+type Adjacency = Vec<Vec<(usize, f64)>>;
+
+/// Passes over the nodes of one Louvain level before giving up on
+/// convergence
+const MAX_LOUVAIN_PASSES: usize = 100;
+
+/// Shortest length of an edge, so even identical games are a step apart
+const MIN_EDGE_LENGTH: f64 = 1e-6;
+
+/// Distances closer than this count as equal
+const DISTANCE_EPSILON: f64 = 1e-9;
+
+/// Community of each node by the Louvain method: nodes move to the
+/// neighbouring community that raises modularity the most, then each
+/// community becomes a node and the moves repeat until none helps. Nodes
+/// are visited in order, so the result is deterministic.
+fn louvain(adjacency: &[Vec<(usize, f64)>]) -> Vec<usize> {
+    let mut membership: Vec<usize> = (0..adjacency.len()).collect();
+    let mut level = adjacency.to_vec();
+    // Weight inside each node of the level, counting both directions
+    let mut internal = vec![0.0; adjacency.len()];
+
+    loop {
+        let Some(communities) = move_nodes(&level, &internal) else {
+            return membership;
+        };
+
+        // Number the communities and merge each into one node
+        let mut numbers: HashMap<usize, usize> = HashMap::new();
+        let renumbered: Vec<usize> = communities
+            .iter()
+            .map(|community| {
+                let next = numbers.len();
+                *numbers.entry(*community).or_insert(next)
+            })
+            .collect();
+        for community in &mut membership {
+            *community = renumbered[*community];
+        }
+
+        let mut merged: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); numbers.len()];
+        let mut merged_internal = vec![0.0; numbers.len()];
+        for (node, edges) in level.iter().enumerate() {
+            let a = renumbered[node];
+            merged_internal[a] += internal[node];
+            for &(other, weight) in edges {
+                let b = renumbered[other];
+                if a == b {
+                    merged_internal[a] += weight;
+                } else {
+                    *merged[a].entry(b).or_default() += weight;
+                }
+            }
+        }
+        level = merged
+            .into_iter()
+            .map(|edges| edges.into_iter().collect())
+            .collect();
+        internal = merged_internal;
+    }
+}
+
+/// One level of the Louvain method: the community of each node after
+/// moving nodes while that raises modularity, or `None` if no node moved
+fn move_nodes(adjacency: &[Vec<(usize, f64)>], internal: &[f64]) -> Option<Vec<usize>> {
+    let degree: Vec<f64> = adjacency
+        .iter()
+        .zip(internal)
+        .map(|(edges, internal)| internal + edges.iter().map(|(_, w)| w).sum::<f64>())
+        .collect();
+    let total: f64 = degree.iter().sum();
+    if total <= 0.0 {
+        return None;
+    }
+
+    let mut community: Vec<usize> = (0..adjacency.len()).collect();
+    let mut community_degree = degree.clone();
+    let mut moved_any = false;
+    for _ in 0..MAX_LOUVAIN_PASSES {
+        let mut moved = false;
+        for node in 0..adjacency.len() {
+            let current = community[node];
+            community_degree[current] -= degree[node];
+
+            let mut links: BTreeMap<usize, f64> = BTreeMap::from([(current, 0.0)]);
+            for &(other, weight) in &adjacency[node] {
+                *links.entry(community[other]).or_default() += weight;
+            }
+            // Modularity gain of joining a community, up to a factor
+            // shared by all of them
+            let gain =
+                |target: usize, link: f64| link - community_degree[target] * degree[node] / total;
+            let mut best = (current, gain(current, links[&current]));
+            for (&target, &link) in &links {
+                let candidate = gain(target, link);
+                if candidate > best.1 + DISTANCE_EPSILON {
+                    best = (target, candidate);
+                }
+            }
+
+            community_degree[best.0] += degree[node];
+            if best.0 != current {
+                community[node] = best.0;
+                moved = true;
+                moved_any = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+    moved_any.then_some(community)
+}
+
+/// Modularity of a partition of the graph into communities
+fn modularity(adjacency: &[Vec<(usize, f64)>], community: &[usize]) -> f64 {
+    let mut inside: HashMap<usize, f64> = HashMap::new();
+    let mut degree: HashMap<usize, f64> = HashMap::new();
+    for (node, edges) in adjacency.iter().enumerate() {
+        for &(other, weight) in edges {
+            *degree.entry(community[node]).or_default() += weight;
+            if community[node] == community[other] {
+                *inside.entry(community[node]).or_default() += weight;
+            }
+        }
+    }
+    let total: f64 = degree.values().sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+    degree
+        .iter()
+        .map(|(c, degree)| inside.get(c).copied().unwrap_or(0.0) / total - (degree / total).powi(2))
+        .sum()
+}
+
+/// Shortest paths from one node of the graph
+struct ShortestPaths {
+    /// Nodes in the order they were reached, nearest first
+    order: Vec<usize>,
+    distance: Vec<f64>,
+    /// Number of shortest paths to each node
+    sigma: Vec<f64>,
+    /// Nodes before each node on its shortest paths
+    predecessors: Vec<Vec<usize>>,
+}
+
+/// Entry of the Dijkstra queue, nearest first
+#[derive(PartialEq)]
+struct Reached {
+    distance: f64,
+    node: usize,
+}
+
+impl Eq for Reached {}
+
+impl Ord for Reached {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .distance
+            .total_cmp(&self.distance)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+impl PartialOrd for Reached {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Dijkstra's algorithm from `source` with edges `-ln(similarity)` long,
+/// counting the shortest paths as Brandes' algorithm needs
+fn shortest_paths(adjacency: &[Vec<(usize, f64)>], source: usize) -> ShortestPaths {
+    let count = adjacency.len();
+    let mut paths = ShortestPaths {
+        order: Vec::with_capacity(count),
+        distance: vec![f64::INFINITY; count],
+        sigma: vec![0.0; count],
+        predecessors: vec![Vec::new(); count],
+    };
+    let mut settled = vec![false; count];
+    paths.distance[source] = 0.0;
+    paths.sigma[source] = 1.0;
+
+    let mut queue = BinaryHeap::from([Reached {
+        distance: 0.0,
+        node: source,
+    }]);
+    while let Some(Reached { distance, node }) = queue.pop() {
+        if settled[node] {
+            continue;
+        }
+        settled[node] = true;
+        paths.order.push(node);
+
+        for &(other, weight) in &adjacency[node] {
+            if settled[other] {
+                continue;
+            }
+            let length = (-weight.clamp(f64::MIN_POSITIVE, 1.0).ln()).max(MIN_EDGE_LENGTH);
+            let candidate = distance + length;
+            if candidate < paths.distance[other] - DISTANCE_EPSILON {
+                paths.distance[other] = candidate;
+                paths.sigma[other] = paths.sigma[node];
+                paths.predecessors[other] = vec![node];
+                queue.push(Reached {
+                    distance: candidate,
+                    node: other,
+                });
+            } else if (candidate - paths.distance[other]).abs() <= DISTANCE_EPSILON {
+                paths.sigma[other] += paths.sigma[node];
+                paths.predecessors[other].push(node);
+            }
+        }
+    }
+    paths
+}
+
+/// Synergies and conflicts weaker than this after weighting are dropped
+pub const MIN_WEIGHTED_STRENGTH: f32 = 0.1;
+
+/// Shares of the blend from relative weights: missing games weigh 1,
+/// negative weights count as 0, and if nothing weighs anything every game
+/// gets the same share
+pub fn normalize_weights(
+    game_ids: &[String],
+    weights: &HashMap<String, f32>,
+) -> HashMap<String, f32> {
+    let raw: Vec<f32> = game_ids
+        .iter()
+        .map(|id| weights.get(id).copied().unwrap_or(1.0).max(0.0))
+        .collect();
+    let total: f32 = raw.iter().sum();
+
+    game_ids
+        .iter()
+        .zip(raw)
+        .map(|(id, weight)| {
+            let share = if total > 0.0 {
+                weight / total
+            } else {
+                1.0 / game_ids.len() as f32
+            };
+            (id.clone(), share)
+        })
+        .collect()
+}
+
+/// How much the synergies and conflicts of two games matter, given their
+/// shares of the blend: 1 for an even pair, falling to 0 as one of them
+/// stops contributing. A 70/30 pair counts for 0.6.
+pub fn pair_influence(share1: f32, share2: f32) -> f32 {
+    let total = share1 + share2;
+    if total <= 0.0 {
+        0.0
+    } else {
+        2.0 * share1.min(share2) / total
+    }
+}
+
+impl CompatibilityEdge {
+    /// Synergy strengths and conflict severities scaled by `influence`,
+    /// without the ones that become negligible
+    pub fn weighted(mut self, influence: f32) -> Self {
+        for synergy in &mut self.synergies {
+            synergy.strength *= influence;
+        }
+        for conflict in &mut self.conflicts {
+            conflict.severity *= influence;
+        }
+        self.synergies
+            .retain(|synergy| synergy.strength >= MIN_WEIGHTED_STRENGTH);
+        self.conflicts
+            .retain(|conflict| conflict.severity >= MIN_WEIGHTED_STRENGTH);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FeatureVector;
+
+    fn game(id: &str, name: &str, genre: &str) -> GameMetadata {
+        GameMetadata {
+            game_id: id.to_string(),
+            name: name.to_string(),
+            year: 1987,
+            feature_vector: FeatureVector {
+                genre_weights: vec![1.0, 0.0],
+                mechanic_flags: vec![true, false],
+                platform_generation: 3,
+                complexity: 0.5,
+                action_strategy_balance: 0.0,
+                single_multi_balance: 0.0,
+                semantic_embedding: None,
+            },
+            common_pairings: HashMap::new(),
+            genre_affinities: HashMap::from([(genre.to_string(), 1.0)]),
+            mechanic_tags: vec!["Combat".to_string()],
+            era_category: "late_80s".to_string(),
+            mood_tags: vec!["heroic".to_string()],
+        }
+    }
+
+    fn graph() -> GameGraph {
+        GameGraph::new(HashMap::from([
+            (
+                "zelda".to_string(),
+                game("zelda", "Zelda & Link", "Adventure"),
+            ),
+            ("metroid".to_string(), game("metroid", "Metroid", "Action")),
+        ]))
+        .unwrap()
+    }
+
+    /// Two clusters of three alike games, "a" and "b", and "m" halfway
+    /// between them
+    fn clustered_graph() -> GameGraph {
+        let variant = |id: &str, genres: Vec<f32>, mechanics: Vec<bool>, complexity, balance| {
+            let mut meta = game(id, id, "Action");
+            meta.feature_vector.genre_weights = genres;
+            meta.feature_vector.mechanic_flags = mechanics;
+            meta.feature_vector.complexity = complexity;
+            meta.feature_vector.action_strategy_balance = balance;
+            (id.to_string(), meta)
+        };
+        let mut games = HashMap::new();
+        for i in 1..=3 {
+            games.extend([
+                variant(
+                    &format!("a{i}"),
+                    vec![1.0, 0.0],
+                    vec![true, false],
+                    0.2,
+                    -1.0,
+                ),
+                variant(
+                    &format!("b{i}"),
+                    vec![0.0, 1.0],
+                    vec![false, true],
+                    0.9,
+                    1.0,
+                ),
+            ]);
+        }
+        games.extend([variant("m", vec![0.7, 0.7], vec![true, true], 0.55, 0.0)]);
+        GameGraph::new(games).unwrap()
+    }
+
+    #[test]
+    fn test_communities() {
+        let graph = clustered_graph();
+        let communities = graph.communities();
+        assert_eq!(communities.len(), 2);
+        assert!(communities.modularity > 0.0);
+        let a = communities.community_of("a1").unwrap();
+        let b = communities.community_of("b1").unwrap();
+        assert_ne!(a, b);
+        for i in 2..=3 {
+            assert_eq!(communities.community_of(&format!("a{i}")), Some(a));
+            assert_eq!(communities.community_of(&format!("b{i}")), Some(b));
+        }
+        assert_eq!(communities.groups[0].len(), 4);
+        assert_eq!(communities.community_of("zelda"), None);
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let graph = clustered_graph();
+        // The way from one cluster to the other leads through the bridge
+        let path = graph.shortest_path("a1", "b1").unwrap();
+        assert_eq!(path.games, ["a1", "m", "b1"]);
+        let direct = graph.analyze_edge("a1", "b1").unwrap().weight;
+        assert!(path.similarity > direct);
+        assert_eq!(graph.shortest_path("a1", "a1").unwrap().games, ["a1"]);
+        assert!(graph.shortest_path("a1", "zelda").is_none());
+    }
+
+    #[test]
+    fn test_centrality() {
+        let centrality = clustered_graph().centrality();
+        assert_eq!(centrality.len(), 7);
+        assert_eq!(centrality[0].game_id, "m");
+        assert!(centrality[0].betweenness > 0.5);
+        assert!(centrality.iter().all(|c| (0.0..=1.0).contains(&c.degree)));
+        assert!(
+            centrality
+                .windows(2)
+                .all(|w| w[0].betweenness >= w[1].betweenness)
+        );
+    }
+
+    #[test]
+    fn test_bridge_games() {
+        let graph = clustered_graph();
+        let communities = graph.communities();
+        let a = communities.community_of("a1").unwrap();
+        let b = communities.community_of("b1").unwrap();
+
+        let bridges = graph.bridge_games(&communities, 3);
+        assert!(bridges.len() <= 3);
+        assert_eq!(bridges[0].game_id, "m");
+        let other = if bridges[0].community == a { b } else { a };
+        assert_eq!(bridges[0].linked[0].0, other);
+        assert!(graph.bridge_games(&communities, 0).is_empty());
+    }
+
+    #[test]
+    fn test_cross_community_blends() {
+        let graph = clustered_graph();
+        let communities = graph.communities();
+        let blends = graph.cross_community_blends(&communities, 5);
+        assert_eq!(blends.len(), 1);
+        assert!(blends[0].0 == "m" || blends[0].1 == "m");
+        assert_ne!(
+            communities.community_of(&blends[0].0),
+            communities.community_of(&blends[0].1)
+        );
+        assert!(graph.cross_community_blends(&communities, 0).is_empty());
+    }
+
+    #[test]
+    fn test_weighted_blend_path() {
+        let ids = ["zelda".to_string(), "metroid".to_string()];
+        let even = graph().find_blend_path(&ids).unwrap();
+        assert_eq!(even.weights["zelda"], 0.5);
+        assert!(!even.synergies.is_empty());
+
+        // A 70/30 blend keeps the synergies at 60% of their strength
+        let weights = HashMap::from([("zelda".to_string(), 70.0), ("metroid".to_string(), 30.0)]);
+        let weighted = graph().find_weighted_blend_path(&ids, &weights).unwrap();
+        assert!((weighted.weights["zelda"] - 0.7).abs() < 1e-6);
+        for (even, weighted) in even.synergies.iter().zip(&weighted.synergies) {
+            assert!((weighted.strength - even.strength * 0.6).abs() < 1e-6);
+        }
+
+        // A game that makes up none of the blend adds no synergies
+        let weights = HashMap::from([("metroid".to_string(), 0.0)]);
+        let lopsided = graph().find_weighted_blend_path(&ids, &weights).unwrap();
+        assert_eq!(lopsided.weights["zelda"], 1.0);
+        assert!(lopsided.synergies.is_empty());
+
+        assert_eq!(pair_influence(0.0, 0.0), 0.0);
+        let equal = normalize_weights(
+            &ids,
+            &HashMap::from([("zelda".to_string(), 0.0), ("metroid".to_string(), 0.0)]),
+        );
+        assert_eq!(equal["metroid"], 0.5);
+    }
+
+    #[test]
+    fn test_graphml_export() {
+        let graphml = graph().to_graphml();
+
+        assert!(graphml.contains("<node id=\"metroid\">"));
+        assert!(graphml.contains("<data key=\"name\">Zelda &amp; Link</data>"));
+        assert!(graphml.contains("<data key=\"genre\">Adventure</data>"));
+        assert!(graphml.contains("<data key=\"mood_tags\">heroic</data>"));
+        assert!(graphml.contains("<edge id=\"e0\" source=\"metroid\" target=\"zelda\">"));
+        assert!(graphml.contains("<data key=\"weight\">"));
+    }
+
+    #[test]
+    fn test_gexf_export() {
+        let gexf = graph().to_gexf();
+
+        assert!(gexf.contains("<node id=\"zelda\" label=\"Zelda &amp; Link\">"));
+        assert!(gexf.contains("<attvalue for=\"year\" value=\"1987\"/>"));
+        assert!(gexf.contains("source=\"metroid\" target=\"zelda\" weight="));
+        assert!(!gexf.contains("for=\"name\""));
+    }
 }
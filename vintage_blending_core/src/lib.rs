@@ -5,11 +5,13 @@
 
 pub mod graph;
 pub mod metadata;
+pub mod rating;
 pub mod similarity;
 pub mod types;
 
 pub use graph::GameGraph;
 pub use metadata::MetadataBuilder;
+pub use rating::{BlendRating, RatingModel};
 pub use similarity::SimilarityEngine;
 pub use types::*;
 
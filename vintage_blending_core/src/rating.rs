@@ -0,0 +1,274 @@
+//! User rating model for personalized blend recommendations
+//!
+//! Thumbs-up/down ratings are stored alongside the feature description of the
+//! rated blend. A small logistic model is fitted over those features so that
+//! candidate blends can be ranked by how likely the user is to enjoy them.
+
+use crate::types::FeatureVector;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Gradient descent iterations used when fitting
+const TRAINING_EPOCHS: usize = 200;
+/// Learning rate for gradient descent
+const LEARNING_RATE: f32 = 0.1;
+/// L2 regularization strength, keeps weights small with few ratings
+const L2_PENALTY: f32 = 0.01;
+
+/// A single rating given by the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlendRating {
+    /// Ids of the games that made up the rated blend
+    pub game_ids: Vec<String>,
+    /// What was rated, e.g. "blend" or "concept"
+    pub subject: String,
+    /// Thumbs up (`true`) or down (`false`)
+    pub liked: bool,
+    /// Features of the blend at rating time
+    pub features: Vec<f32>,
+    /// Seconds since the Unix epoch
+    pub rated_at: u64,
+}
+
+/// Logistic ranking model fitted on the user's ratings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RatingModel {
+    pub ratings: Vec<BlendRating>,
+    pub weights: Vec<f32>,
+    pub bias: f32,
+}
+
+impl RatingModel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a model from a JSON file, returning an empty model if it does not exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Save the model and its ratings as JSON
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Forget all ratings and learned weights
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Record a rating for a blend and refit the model
+    pub fn rate(
+        &mut self,
+        game_ids: Vec<String>,
+        subject: impl Into<String>,
+        vectors: &[&FeatureVector],
+        liked: bool,
+    ) {
+        let rated_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        self.ratings.push(BlendRating {
+            game_ids,
+            subject: subject.into(),
+            liked,
+            features: blend_features(vectors),
+            rated_at,
+        });
+        self.fit();
+    }
+
+    /// Whether the model has seen both positive and negative examples
+    pub fn is_trained(&self) -> bool {
+        self.ratings.iter().any(|r| r.liked) && self.ratings.iter().any(|r| !r.liked)
+    }
+
+    /// Refit the weights on all stored ratings
+    pub fn fit(&mut self) {
+        let Some(dim) = self.ratings.first().map(|r| r.features.len()) else {
+            self.weights.clear();
+            self.bias = 0.0;
+            return;
+        };
+
+        let samples: Vec<&BlendRating> = self
+            .ratings
+            .iter()
+            .filter(|r| r.features.len() == dim)
+            .collect();
+
+        self.weights = vec![0.0; dim];
+        self.bias = 0.0;
+        let n = samples.len() as f32;
+
+        for _ in 0..TRAINING_EPOCHS {
+            let mut grad_w = vec![0.0f32; dim];
+            let mut grad_b = 0.0f32;
+
+            for sample in &samples {
+                let target = if sample.liked { 1.0 } else { 0.0 };
+                let error = self.predict(&sample.features) - target;
+                for (g, x) in grad_w.iter_mut().zip(&sample.features) {
+                    *g += error * x;
+                }
+                grad_b += error;
+            }
+
+            for (w, g) in self.weights.iter_mut().zip(&grad_w) {
+                *w -= LEARNING_RATE * (g / n + L2_PENALTY * *w);
+            }
+            self.bias -= LEARNING_RATE * grad_b / n;
+        }
+    }
+
+    /// Probability that the user likes a blend with these features
+    pub fn predict(&self, features: &[f32]) -> f32 {
+        if self.weights.len() != features.len() {
+            return 0.5;
+        }
+        let z: f32 = self
+            .weights
+            .iter()
+            .zip(features)
+            .map(|(w, x)| w * x)
+            .sum::<f32>()
+            + self.bias;
+        1.0 / (1.0 + (-z).exp())
+    }
+
+    /// Score a candidate blend; 0.5 means no preference signal
+    pub fn score(&self, vectors: &[&FeatureVector]) -> f32 {
+        if !self.is_trained() {
+            return 0.5;
+        }
+        self.predict(&blend_features(vectors))
+    }
+
+    /// Sort candidates by predicted preference, best first
+    pub fn rank<T>(&self, candidates: Vec<(T, Vec<&FeatureVector>)>) -> Vec<(T, f32)> {
+        let mut scored: Vec<(T, f32)> = candidates
+            .into_iter()
+            .map(|(item, vectors)| {
+                let score = self.score(&vectors);
+                (item, score)
+            })
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored
+    }
+}
+
+/// Describe a blend as a fixed-size feature vector.
+///
+/// The first half is the mean of the members' features; the second half is the
+/// mean absolute pairwise delta, which captures how far apart the games are.
+pub fn blend_features(vectors: &[&FeatureVector]) -> Vec<f32> {
+    let flat: Vec<Vec<f32>> = vectors.iter().map(|v| flatten(v)).collect();
+    let Some(dim) = flat.iter().map(Vec::len).max() else {
+        return Vec::new();
+    };
+
+    let mut mean = vec![0.0f32; dim];
+    for v in &flat {
+        for (m, x) in mean.iter_mut().zip(v) {
+            *m += x / flat.len() as f32;
+        }
+    }
+
+    let mut delta = vec![0.0f32; dim];
+    let mut pairs = 0;
+    for i in 0..flat.len() {
+        for j in (i + 1)..flat.len() {
+            for (d, (a, b)) in delta.iter_mut().zip(flat[i].iter().zip(&flat[j])) {
+                *d += (a - b).abs();
+            }
+            pairs += 1;
+        }
+    }
+    if pairs > 0 {
+        delta.iter_mut().for_each(|d| *d /= pairs as f32);
+    }
+
+    mean.extend(delta);
+    mean
+}
+
+fn flatten(vector: &FeatureVector) -> Vec<f32> {
+    let mut values = vector.genre_weights.clone();
+    values.extend(
+        vector
+            .mechanic_flags
+            .iter()
+            .map(|&f| if f { 1.0 } else { 0.0 }),
+    );
+    values.push(vector.platform_generation as f32 / 5.0);
+    values.push(vector.complexity);
+    values.push(vector.action_strategy_balance);
+    values.push(vector.single_multi_balance);
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vector(complexity: f32, action: f32) -> FeatureVector {
+        FeatureVector {
+            genre_weights: vec![1.0, 0.0],
+            mechanic_flags: vec![true, false],
+            platform_generation: 3,
+            complexity,
+            action_strategy_balance: action,
+            single_multi_balance: 0.0,
+            semantic_embedding: None,
+        }
+    }
+
+    #[test]
+    fn test_blend_features_include_deltas() {
+        let a = vector(0.2, -1.0);
+        let b = vector(0.8, 1.0);
+        let features = blend_features(&[&a, &b]);
+
+        // 2 genres + 2 mechanics + 4 scalars, doubled for mean and delta
+        assert_eq!(features.len(), 16);
+        assert!((features[5] - 0.5).abs() < 1e-6); // mean complexity
+        assert!((features[8 + 5] - 0.6).abs() < 1e-6); // complexity delta
+    }
+
+    #[test]
+    fn test_model_learns_preference() {
+        let calm = vector(0.9, 1.0);
+        let frantic = vector(0.1, -1.0);
+
+        let mut model = RatingModel::new();
+        for _ in 0..3 {
+            model.rate(vec!["1".into(), "2".into()], "blend", &[&calm, &calm], true);
+            model.rate(
+                vec!["3".into(), "4".into()],
+                "blend",
+                &[&frantic, &frantic],
+                false,
+            );
+        }
+
+        assert!(model.is_trained());
+        assert!(model.score(&[&calm, &calm]) > model.score(&[&frantic, &frantic]));
+
+        model.reset();
+        assert!(model.ratings.is_empty());
+        assert_eq!(model.score(&[&calm, &calm]), 0.5);
+    }
+}
@@ -1,17 +1,51 @@
 //! AI-powered game analysis during build time
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
-use tiktoken_rs::{CoreBPE, get_bpe_from_model};
+use std::fs;
+use std::path::Path;
+use tokio::sync::Semaphore;
 use vintage_ai_client::{
     AiService,
     text::{TextConfig, TextGenerator},
+    tokens::TokenCounter,
 };
 
+/// Model used for batch analysis requests. The requests send a JSON Schema
+/// as the response format, so it must support structured outputs.
+pub(crate) const ANALYSIS_MODEL: &str = "gpt-4o";
+
+/// System prompt of the analysis requests
+pub(crate) const ANALYSIS_SYSTEM_PROMPT: &str = "You are a video game historian and design analyst. Analyze vintage games with deep insight into their design, cultural impact, and innovations. Always respond with valid JSON.";
+
+/// Template of the prompt asking for one batch's analyses
+pub(crate) const BATCH_ANALYSIS_TEMPLATE: &str =
+    include_str!("../templates/ai_analysis/batch_analysis.jinja");
+
+/// Completion tokens allowed per analysis request, the most the analysis
+/// model returns
+const ANALYSIS_MAX_TOKENS: u16 = 16384;
+
+/// Batches analyzed at once unless [`AIAnalyzer::with_concurrency`] says
+/// otherwise
+pub const DEFAULT_CONCURRENCY: usize = 3;
+
+/// Where the build keeps the checkpoints of an interrupted analysis
+pub const ANALYSIS_CHECKPOINT_DIR: &str = "assets/wizard/dataset/checkpoints";
+
+/// Metadata of one completed batch, saved so a restarted analysis can skip it
+#[derive(Debug, Serialize, Deserialize)]
+struct BatchCheckpoint {
+    /// IDs of the batch's games, so a checkpoint of other games is not reused
+    game_ids: Vec<u32>,
+    enriched: Vec<EnrichedGameMetadata>,
+}
+
 /// AI-analyzed game metadata
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EnrichedGameMetadata {
     pub id: u32,
     pub name: String,
@@ -20,6 +54,9 @@ pub struct EnrichedGameMetadata {
     pub platforms: Vec<String>,
     pub developer: Option<String>,
     pub deck: Option<String>,
+    /// Data sources the game came from, e.g. `["giantbomb", "igdb"]`
+    #[serde(default)]
+    pub sources: Vec<String>,
 
     // AI-analyzed fields
     pub themes: Vec<String>,
@@ -48,6 +85,16 @@ pub struct EnrichedGameMetadata {
     pub social_features: Vec<String>,
     pub accessibility_notes: Vec<String>,
 
+    // Read off the screenshot by a vision model
+    /// Dominant colors as `#rrggbb`, most used first
+    #[serde(default)]
+    pub palette: Vec<String>,
+    #[serde(default)]
+    pub perspective: String,
+    /// Interface elements and where they sit on screen
+    #[serde(default)]
+    pub ui_layout: Vec<String>,
+
     // Semantic embeddings for similarity
     pub theme_embeddings: Vec<f32>,
     pub mechanic_embeddings: Vec<f32>,
@@ -55,7 +102,7 @@ pub struct EnrichedGameMetadata {
     pub overall_embedding: Vec<f32>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct GameMechanic {
     pub name: String,
     pub description: String,
@@ -63,9 +110,163 @@ pub struct GameMechanic {
     pub innovation_level: f32, // 0.0 to 1.0
 }
 
+/// A genre a game blends, with its weight from 0.0 to 1.0
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct GenreWeight {
+    pub genre: String,
+    pub weight: f32,
+}
+
+/// One game's analysis as the model returns it
+///
+/// Its JSON Schema is sent with the request as the response format, and
+/// missing fields deserialize empty so [`invalid_fields`](Self::invalid_fields)
+/// can name them for the repair request.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct GameAnalysis {
+    /// ID of the analyzed game, as given in the prompt
+    pub id: u32,
+    pub themes: Vec<String>,
+    pub narrative_elements: Vec<String>,
+    pub mechanics: Vec<GameMechanic>,
+    pub mood_tags: Vec<String>,
+    pub innovation_aspects: Vec<String>,
+    pub cultural_impact: String,
+    pub design_philosophy: String,
+    pub player_experience: String,
+    pub difficulty_curve: String,
+    pub replayability_factors: Vec<String>,
+    pub artistic_style: String,
+    pub audio_design: String,
+    pub pacing: String,
+    pub target_audience: Vec<String>,
+    pub unique_features: Vec<String>,
+    pub influenced_by: Vec<String>,
+    pub influenced_games: Vec<String>,
+    pub genre_blend: Vec<GenreWeight>,
+    pub era_significance: String,
+    pub technical_achievements: Vec<String>,
+    pub memorable_moments: Vec<String>,
+    pub core_loop: String,
+    pub progression_system: String,
+    pub social_features: Vec<String>,
+    pub accessibility_notes: Vec<String>,
+}
+
+/// The response to a batch analysis request
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BatchAnalysis {
+    pub games: Vec<GameAnalysis>,
+}
+
+impl GameAnalysis {
+    /// Every analyzed field, in the order of the prompt
+    pub const FIELDS: &[&str] = &[
+        "themes",
+        "narrative_elements",
+        "mechanics",
+        "mood_tags",
+        "innovation_aspects",
+        "cultural_impact",
+        "design_philosophy",
+        "player_experience",
+        "difficulty_curve",
+        "replayability_factors",
+        "artistic_style",
+        "audio_design",
+        "pacing",
+        "target_audience",
+        "unique_features",
+        "influenced_by",
+        "influenced_games",
+        "genre_blend",
+        "era_significance",
+        "technical_achievements",
+        "memorable_moments",
+        "core_loop",
+        "progression_system",
+        "social_features",
+        "accessibility_notes",
+    ];
+
+    /// Names of the fields that are missing or out of range. Lists a game
+    /// may rightly leave empty, such as `social_features`, are never invalid.
+    pub fn invalid_fields(&self) -> Vec<&'static str> {
+        let unit = |value: f32| (0.0..=1.0).contains(&value);
+        let mut invalid = Vec::new();
+
+        for (field, text) in [
+            ("cultural_impact", &self.cultural_impact),
+            ("design_philosophy", &self.design_philosophy),
+            ("player_experience", &self.player_experience),
+            ("difficulty_curve", &self.difficulty_curve),
+            ("artistic_style", &self.artistic_style),
+            ("audio_design", &self.audio_design),
+            ("pacing", &self.pacing),
+            ("era_significance", &self.era_significance),
+            ("core_loop", &self.core_loop),
+            ("progression_system", &self.progression_system),
+        ] {
+            if text.trim().is_empty() {
+                invalid.push(field);
+            }
+        }
+        for (field, list) in [
+            ("themes", &self.themes),
+            ("narrative_elements", &self.narrative_elements),
+            ("mood_tags", &self.mood_tags),
+            ("target_audience", &self.target_audience),
+            ("unique_features", &self.unique_features),
+        ] {
+            if list.iter().all(|item| item.trim().is_empty()) {
+                invalid.push(field);
+            }
+        }
+
+        if self.mechanics.is_empty()
+            || self.mechanics.iter().any(|m| {
+                m.name.trim().is_empty() || !unit(m.importance) || !unit(m.innovation_level)
+            })
+        {
+            invalid.push("mechanics");
+        }
+        if self.genre_blend.is_empty()
+            || self
+                .genre_blend
+                .iter()
+                .any(|g| g.genre.trim().is_empty() || !unit(g.weight))
+        {
+            invalid.push("genre_blend");
+        }
+
+        // Keep the order of the prompt
+        invalid.sort_by_key(|field| Self::FIELDS.iter().position(|f| f == field));
+        invalid
+    }
+
+    /// Take `fields` from `other`, an analysis of the same game
+    pub fn replace_fields(&mut self, other: &GameAnalysis, fields: &[&str]) {
+        let (Ok(mut current), Ok(other)) =
+            (serde_json::to_value(&*self), serde_json::to_value(other))
+        else {
+            return;
+        };
+        for field in fields {
+            if let Some(value) = other.get(*field) {
+                current[*field] = value.clone();
+            }
+        }
+        if let Ok(replaced) = serde_json::from_value(current) {
+            *self = replaced;
+        }
+    }
+}
+
 pub struct AIAnalyzer {
-    bpe: CoreBPE,
+    token_counter: TokenCounter,
     text_generator: TextGenerator,
+    concurrency: usize,
 }
 
 impl AIAnalyzer {
@@ -82,43 +283,129 @@ impl AIAnalyzer {
             }
         }
 
-        let bpe = get_bpe_from_model("gpt-4")?;
         let ai_service = AiService::from_env()?;
         let text_generator = ai_service.text();
         Ok(Self {
-            bpe,
+            token_counter: TokenCounter::new(),
             text_generator,
+            concurrency: DEFAULT_CONCURRENCY,
         })
     }
 
+    /// Analyze up to `concurrency` batches at once instead of the default
+    /// [`DEFAULT_CONCURRENCY`]
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
     /// Analyze all games in batches using intelligent prompt chunking
     pub async fn analyze_games(
         &self,
         games: &[Value],
         batch_size: usize,
     ) -> Result<Vec<EnrichedGameMetadata>> {
-        println!("Starting AI analysis of {} games...", games.len());
+        self.analyze_in_batches(games, batch_size, None).await
+    }
 
-        let mut all_enriched = Vec::new();
+    /// Analyze all games in batches like [`analyze_games`](Self::analyze_games),
+    /// saving each completed batch to `checkpoint_dir`
+    ///
+    /// A batch whose checkpoint holds the same games is loaded instead of
+    /// analyzed, so a run that failed halfway picks up at the failed batch.
+    /// The checkpoints are kept; remove them with
+    /// [`clear_checkpoints`](Self::clear_checkpoints) once the results are saved.
+    pub async fn analyze_games_resumable(
+        &self,
+        games: &[Value],
+        batch_size: usize,
+        checkpoint_dir: impl AsRef<Path>,
+    ) -> Result<Vec<EnrichedGameMetadata>> {
+        let checkpoint_dir = checkpoint_dir.as_ref();
+        fs::create_dir_all(checkpoint_dir).with_context(|| {
+            format!(
+                "Failed to create checkpoint directory {}",
+                checkpoint_dir.display()
+            )
+        })?;
+        self.analyze_in_batches(games, batch_size, Some(checkpoint_dir))
+            .await
+    }
 
-        // Process games in batches
-        for (batch_idx, batch) in games.chunks(batch_size).enumerate() {
-            println!(
-                "Processing batch {}/{}",
-                batch_idx + 1,
-                games.len().div_ceil(batch_size)
-            );
+    /// Analyze the batches concurrently, at most `concurrency` at a time,
+    /// keeping the games' order. With a checkpoint directory, completed
+    /// batches are restored from and saved to it.
+    async fn analyze_in_batches(
+        &self,
+        games: &[Value],
+        batch_size: usize,
+        checkpoint_dir: Option<&Path>,
+    ) -> Result<Vec<EnrichedGameMetadata>> {
+        println!(
+            "Starting AI analysis of {} games, {} batches at a time...",
+            games.len(),
+            self.concurrency
+        );
+
+        let batch_count = games.len().div_ceil(batch_size);
+        let semaphore = Semaphore::new(self.concurrency);
+
+        let batches = games
+            .chunks(batch_size)
+            .enumerate()
+            .map(|(batch_idx, batch)| {
+                let semaphore = &semaphore;
+                async move {
+                    let path =
+                        checkpoint_dir.map(|dir| dir.join(format!("batch_{batch_idx:04}.json")));
+                    let game_ids: Vec<u32> = batch
+                        .iter()
+                        .filter_map(|g| g.get("id").and_then(|v| v.as_u64()))
+                        .map(|id| id as u32)
+                        .collect();
+
+                    if let Some(path) = &path
+                        && let Some(checkpoint) = load_checkpoint(path)
+                        && checkpoint.game_ids == game_ids
+                    {
+                        println!(
+                            "Skipping batch {}/{}, restored from checkpoint",
+                            batch_idx + 1,
+                            batch_count
+                        );
+                        return Ok(checkpoint.enriched);
+                    }
+
+                    let _permit = semaphore.acquire().await?;
+                    println!("Processing batch {}/{}", batch_idx + 1, batch_count);
+                    let enriched = self.analyze_batch(batch).await?;
+
+                    let Some(path) = path else {
+                        return Ok(enriched);
+                    };
+                    let checkpoint = BatchCheckpoint { game_ids, enriched };
+                    fs::write(&path, serde_json::to_string(&checkpoint)?).with_context(|| {
+                        format!("Failed to write checkpoint {}", path.display())
+                    })?;
+                    Ok::<_, anyhow::Error>(checkpoint.enriched)
+                }
+            });
 
-            let enriched_batch = self.analyze_batch(batch).await?;
-            all_enriched.extend(enriched_batch);
+        // The first failure cancels the batches still running; the ones
+        // that completed are in their checkpoints
+        let results = futures::future::try_join_all(batches).await?;
+        Ok(results.into_iter().flatten().collect())
+    }
 
-            // Rate limiting pause between batches
-            if batch_idx < games.chunks(batch_size).len() - 1 {
-                tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-            }
+    /// Remove the checkpoints of a finished analysis
+    pub fn clear_checkpoints(checkpoint_dir: impl AsRef<Path>) -> Result<()> {
+        let checkpoint_dir = checkpoint_dir.as_ref();
+        if checkpoint_dir.exists() {
+            fs::remove_dir_all(checkpoint_dir).with_context(|| {
+                format!("Failed to remove checkpoints {}", checkpoint_dir.display())
+            })?;
         }
-
-        Ok(all_enriched)
+        Ok(())
     }
 
     /// Analyze a batch of games in a single prompt
@@ -146,11 +433,13 @@ impl AIAnalyzer {
                 return Ok(results);
             }
 
-            // Send to AI for analysis
+            // Send to AI for analysis. The request claims its tokens in the
+            // shared QuotaTracker before it goes out, so concurrent batches
+            // wait for room in the rate limits in turn.
             let response = self.send_analysis_request(&prompt).await?;
 
             // Parse the structured response
-            let enriched = self.parse_analysis_response(&response, games)?;
+            let enriched = self.parse_analysis_response(&response, games).await?;
 
             Ok(enriched)
         })
@@ -158,10 +447,8 @@ impl AIAnalyzer {
 
     /// Build a comprehensive analysis prompt for a batch of games
     fn build_batch_prompt(&self, games: &[Value]) -> Result<String> {
-        let template = include_str!("../templates/ai_analysis/batch_analysis.jinja");
-
         let mut env = minijinja::Environment::new();
-        env.add_template("batch_analysis", template)?;
+        env.add_template("batch_analysis", BATCH_ANALYSIS_TEMPLATE)?;
 
         let tmpl = env.get_template("batch_analysis")?;
 
@@ -212,70 +499,70 @@ impl AIAnalyzer {
 
     /// Count tokens in a string
     fn count_tokens(&self, text: &str) -> usize {
-        self.bpe.encode_with_special_tokens(text).len()
+        self.token_counter
+            .count_tokens(text, ANALYSIS_MODEL)
+            .unwrap_or_default()
     }
 
-    /// Send analysis request to AI
+    /// Send analysis request to AI, asking for JSON of [`BatchAnalysis`]'s
+    /// schema
     async fn send_analysis_request(&self, prompt: &str) -> Result<String> {
-        let system_prompt = "You are a video game historian and design analyst. Analyze vintage games with deep insight into their design, cultural impact, and innovations. Always respond with valid JSON.";
-
         let config = TextConfig {
-            model: "gpt-4-turbo".to_string(),
-            system_prompt: Some(system_prompt.to_string()),
+            model: ANALYSIS_MODEL.to_string(),
+            system_prompt: Some(ANALYSIS_SYSTEM_PROMPT.to_string()),
             temperature: 0.7,
-            max_tokens: 50000,
+            max_tokens: ANALYSIS_MAX_TOKENS,
             ..Default::default()
         };
 
         // Add JSON instruction to the prompt
         let json_prompt = format!("{prompt}\n\nIMPORTANT: Respond ONLY with valid JSON.");
 
-        let response = self.text_generator.generate(&json_prompt, config).await?;
+        let response = self
+            .text_generator
+            .generate_with_schema(&json_prompt, config, "game_analyses", batch_schema()?)
+            .await?;
         Ok(response)
     }
 
-    /// Parse the AI analysis response
-    fn parse_analysis_response(
+    /// Parse the AI analysis response, re-asking for the games and fields
+    /// that fail validation, and merge it with the original games
+    async fn parse_analysis_response(
         &self,
         response: &str,
         original_games: &[Value],
     ) -> Result<Vec<EnrichedGameMetadata>> {
-        let analysis: Value = serde_json::from_str(response)?;
-
-        let games_analysis = analysis["games"]
-            .as_array()
-            .ok_or_else(|| anyhow::anyhow!("No games array in response"))?;
+        let mut analyses = parse_analyses(response)?;
 
-        // Create a map of original games by ID for efficient lookup
-        let original_map: std::collections::HashMap<u32, &Value> = original_games
+        let repairs: Vec<(&Value, Vec<&'static str>)> = original_games
             .iter()
-            .filter_map(|g| {
-                let id = g.get("id").and_then(|v| v.as_u64())? as u32;
-                Some((id, g))
+            .filter_map(|original| {
+                let id = game_id(original)?;
+                let invalid = match analyses.get(&id) {
+                    Some(analysis) => analysis.invalid_fields(),
+                    None => GameAnalysis::FIELDS.to_vec(),
+                };
+                (!invalid.is_empty()).then_some((original, invalid))
             })
             .collect();
+        if !repairs.is_empty() {
+            self.repair_analyses(&repairs, &mut analyses).await;
+        }
 
         let mut enriched_games = Vec::new();
-
-        // Match by game ID instead of index position
-        for game_analysis in games_analysis.iter() {
-            // Try to get the game ID from the analysis response
-            let analysis_id = game_analysis
-                .get("id")
-                .and_then(|v| v.as_u64())
-                .or_else(|| game_analysis.get("game_id").and_then(|v| v.as_u64()))
-                .map(|id| id as u32);
-
-            if let Some(id) = analysis_id {
-                if let Some(original) = original_map.get(&id) {
-                    let enriched = self.merge_analysis_with_original(original, game_analysis)?;
-                    enriched_games.push(enriched);
-                } else {
-                    eprintln!("Warning: AI returned analysis for unknown game ID {id}");
-                }
-            } else {
-                eprintln!("Warning: AI response missing game ID, skipping entry");
+        for original in original_games {
+            let Some(analysis) = game_id(original).and_then(|id| analyses.remove(&id)) else {
+                continue;
+            };
+            let invalid = analysis.invalid_fields();
+            if !invalid.is_empty() {
+                eprintln!(
+                    "Warning: Analysis of {} still has invalid fields: {}",
+                    analysis_name(original),
+                    invalid.join(", ")
+                );
             }
+            enriched_games.push(self.merge_analysis_with_original(original, analysis));
         }
 
         // Warn if we didn't get all games back
@@ -290,241 +577,213 @@ impl AIAnalyzer {
         Ok(enriched_games)
     }
 
+    /// Ask the model once more for the invalid fields of each game, or the
+    /// whole analysis of a game it left out, and take the valid answers.
+    /// A failed repair request keeps the analyses as they are.
+    async fn repair_analyses(
+        &self,
+        repairs: &[(&Value, Vec<&'static str>)],
+        analyses: &mut HashMap<u32, GameAnalysis>,
+    ) {
+        println!("  Repairing the analyses of {} games", repairs.len());
+
+        let mut prompt = String::from(
+            "Some game analyses were missing fields or had invalid values. \
+             Provide the listed fields for each game; the other fields may be left empty.\n",
+        );
+        for (original, fields) in repairs {
+            let id = game_id(original).unwrap_or_default();
+            prompt.push_str(&format!(
+                "\nGame ID {id}: {} ({})\nFields: {}\n",
+                analysis_name(original),
+                original.get("year").and_then(|v| v.as_i64()).unwrap_or(0),
+                fields.join(", ")
+            ));
+            if let Some(analysis) = analyses.get(&id)
+                && let Ok(current) = serde_json::to_string(analysis)
+            {
+                prompt.push_str(&format!("Current analysis: {current}\n"));
+            }
+        }
+        prompt.push_str(
+            "\nRules: string fields must not be empty; importance, innovation_level \
+             and weight are between 0.0 and 1.0.",
+        );
+
+        let repaired = match self.send_analysis_request(&prompt).await {
+            Ok(response) => parse_analyses(&response),
+            Err(e) => Err(e),
+        };
+        let mut repaired = match repaired {
+            Ok(repaired) => repaired,
+            Err(e) => {
+                eprintln!("Warning: Failed to repair analyses: {e:#}");
+                return;
+            }
+        };
+
+        for (original, fields) in repairs {
+            let Some(id) = game_id(original) else {
+                continue;
+            };
+            let Some(replacement) = repaired.remove(&id) else {
+                continue;
+            };
+            match analyses.get_mut(&id) {
+                Some(analysis) => analysis.replace_fields(&replacement, fields),
+                None => {
+                    analyses.insert(id, replacement);
+                }
+            }
+        }
+    }
+
     /// Merge AI analysis with original game data
     fn merge_analysis_with_original(
         &self,
         original: &Value,
-        analysis: &Value,
-    ) -> Result<EnrichedGameMetadata> {
-        // Extract original fields
-        let id = original.get("id").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
-
-        let name = original
-            .get("name")
-            .and_then(|v| v.as_str())
-            .unwrap_or("Unknown")
-            .to_string();
-
-        let year = original
-            .get("year")
-            .and_then(|v| v.as_i64())
-            .unwrap_or(1980) as i32;
-
-        let original_genre = original
-            .get("genre")
-            .and_then(|v| v.as_str())
-            .unwrap_or("Unknown")
-            .to_string();
-
-        let platforms = original
-            .get("platforms")
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|p| p.as_str())
-                    .map(|s| s.to_string())
-                    .collect()
-            })
-            .unwrap_or_default();
-
-        let developer = original
-            .get("developer")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string());
-
-        let deck = original
-            .get("deck")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string());
-
-        // Extract AI-analyzed fields
-        let themes = self.extract_string_array(analysis, "themes");
-        let narrative_elements = self.extract_string_array(analysis, "narrative_elements");
-        let mood_tags = self.extract_string_array(analysis, "mood_tags");
-        let innovation_aspects = self.extract_string_array(analysis, "innovation_aspects");
-        let replayability_factors = self.extract_string_array(analysis, "replayability_factors");
-        let target_audience = self.extract_string_array(analysis, "target_audience");
-        let unique_features = self.extract_string_array(analysis, "unique_features");
-        let influenced_by = self.extract_string_array(analysis, "influenced_by");
-        let influenced_games = self.extract_string_array(analysis, "influenced_games");
-        let technical_achievements = self.extract_string_array(analysis, "technical_achievements");
-        let memorable_moments = self.extract_string_array(analysis, "memorable_moments");
-        let social_features = self.extract_string_array(analysis, "social_features");
-        let accessibility_notes = self.extract_string_array(analysis, "accessibility_notes");
-
-        // Extract mechanics
-        let mechanics = self.extract_mechanics(analysis);
-
-        // Extract genre blend
-        let genre_blend = self.extract_genre_blend(analysis);
-
-        // Extract single string fields
-        let cultural_impact = analysis
-            .get("cultural_impact")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-
-        let design_philosophy = analysis
-            .get("design_philosophy")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-
-        let player_experience = analysis
-            .get("player_experience")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-
-        let difficulty_curve = analysis
-            .get("difficulty_curve")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-
-        let artistic_style = analysis
-            .get("artistic_style")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-
-        let audio_design = analysis
-            .get("audio_design")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-
-        let pacing = analysis
-            .get("pacing")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-
-        let era_significance = analysis
-            .get("era_significance")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-
-        let core_loop = analysis
-            .get("core_loop")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-
-        let progression_system = analysis
-            .get("progression_system")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-
-        // Extract embeddings
-        let theme_embeddings = self.extract_float_array(analysis, "theme_embeddings");
-        let mechanic_embeddings = self.extract_float_array(analysis, "mechanic_embeddings");
-        let narrative_embeddings = self.extract_float_array(analysis, "narrative_embeddings");
-        let overall_embedding = self.extract_float_array(analysis, "overall_embedding");
-
-        Ok(EnrichedGameMetadata {
-            id,
-            name,
-            year,
-            original_genre,
-            platforms,
-            developer,
-            deck,
-            themes,
-            narrative_elements,
-            mechanics,
-            mood_tags,
-            innovation_aspects,
-            cultural_impact,
-            design_philosophy,
-            player_experience,
-            difficulty_curve,
-            replayability_factors,
-            artistic_style,
-            audio_design,
-            pacing,
-            target_audience,
-            unique_features,
-            influenced_by,
-            influenced_games,
-            genre_blend,
-            era_significance,
-            technical_achievements,
-            memorable_moments,
-            core_loop,
-            progression_system,
-            social_features,
-            accessibility_notes,
-            theme_embeddings,
-            mechanic_embeddings,
-            narrative_embeddings,
-            overall_embedding,
-        })
+        analysis: GameAnalysis,
+    ) -> EnrichedGameMetadata {
+        let string = |key: &str| {
+            original
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+        };
+        let strings = |key: &str| {
+            original
+                .get(key)
+                .and_then(|v| v.as_array())
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str())
+                        .map(|s| s.to_string())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        EnrichedGameMetadata {
+            id: game_id(original).unwrap_or(0),
+            name: string("name").unwrap_or_else(|| "Unknown".to_string()),
+            year: original
+                .get("year")
+                .and_then(|v| v.as_i64())
+                .unwrap_or(1980) as i32,
+            original_genre: string("genre").unwrap_or_else(|| "Unknown".to_string()),
+            platforms: strings("platforms"),
+            developer: string("developer"),
+            deck: string("deck"),
+            sources: strings("sources"),
+            themes: analysis.themes,
+            narrative_elements: analysis.narrative_elements,
+            mechanics: analysis.mechanics,
+            mood_tags: analysis.mood_tags,
+            innovation_aspects: analysis.innovation_aspects,
+            cultural_impact: analysis.cultural_impact,
+            design_philosophy: analysis.design_philosophy,
+            player_experience: analysis.player_experience,
+            difficulty_curve: analysis.difficulty_curve,
+            replayability_factors: analysis.replayability_factors,
+            artistic_style: analysis.artistic_style,
+            audio_design: analysis.audio_design,
+            pacing: analysis.pacing,
+            target_audience: analysis.target_audience,
+            unique_features: analysis.unique_features,
+            influenced_by: analysis.influenced_by,
+            influenced_games: analysis.influenced_games,
+            genre_blend: analysis
+                .genre_blend
+                .into_iter()
+                .map(|blend| (blend.genre, blend.weight))
+                .collect(),
+            era_significance: analysis.era_significance,
+            technical_achievements: analysis.technical_achievements,
+            memorable_moments: analysis.memorable_moments,
+            core_loop: analysis.core_loop,
+            progression_system: analysis.progression_system,
+            social_features: analysis.social_features,
+            accessibility_notes: analysis.accessibility_notes,
+            palette: Vec::new(),
+            perspective: String::new(),
+            ui_layout: Vec::new(),
+            theme_embeddings: Vec::new(),
+            mechanic_embeddings: Vec::new(),
+            narrative_embeddings: Vec::new(),
+            overall_embedding: Vec::new(),
+        }
     }
+}
 
-    fn extract_string_array(&self, obj: &Value, key: &str) -> Vec<String> {
-        obj.get(key)
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.as_str())
-                    .map(|s| s.to_string())
-                    .collect()
-            })
-            .unwrap_or_default()
+/// JSON Schema of [`BatchAnalysis`] for the response format, with every
+/// field required so the model fills them all
+fn batch_schema() -> Result<Value> {
+    let mut schema = serde_json::to_value(schemars::schema_for!(BatchAnalysis))?;
+    if let Some(object) = schema.as_object_mut() {
+        object.remove("$schema");
     }
+    require_all_properties(&mut schema);
+    Ok(schema)
+}
 
-    fn extract_float_array(&self, obj: &Value, key: &str) -> Vec<f32> {
-        obj.get(key)
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.as_f64())
-                    .map(|f| f as f32)
-                    .collect()
-            })
-            .unwrap_or_default()
+fn require_all_properties(schema: &mut Value) {
+    match schema {
+        Value::Object(object) => {
+            if let Some(Value::Object(properties)) = object.get("properties") {
+                let required = properties.keys().cloned().map(Value::String).collect();
+                object.insert("required".to_string(), Value::Array(required));
+            }
+            object.values_mut().for_each(require_all_properties);
+        }
+        Value::Array(items) => items.iter_mut().for_each(require_all_properties),
+        _ => {}
     }
+}
 
-    fn extract_mechanics(&self, obj: &Value) -> Vec<GameMechanic> {
-        obj.get("mechanics")
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|m| {
-                        let name = m.get("name")?.as_str()?.to_string();
-                        let description = m.get("description")?.as_str()?.to_string();
-                        let importance = m.get("importance")?.as_f64()? as f32;
-                        let innovation_level = m.get("innovation_level")?.as_f64()? as f32;
-
-                        Some(GameMechanic {
-                            name,
-                            description,
-                            importance,
-                            innovation_level,
-                        })
-                    })
-                    .collect()
-            })
-            .unwrap_or_default()
+/// Analyses of a response by game ID. An entry without an ID or that
+/// doesn't deserialize is skipped, so its game counts as missing.
+fn parse_analyses(response: &str) -> Result<HashMap<u32, GameAnalysis>> {
+    let analysis: Value =
+        serde_json::from_str(response).context("Analysis response is not valid JSON")?;
+    let games = analysis["games"]
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("No games array in response"))?;
+
+    let mut analyses = HashMap::new();
+    for game in games {
+        match serde_json::from_value::<GameAnalysis>(game.clone()) {
+            Ok(analysis) if analysis.id != 0 => {
+                analyses.insert(analysis.id, analysis);
+            }
+            Ok(_) => eprintln!("Warning: AI response missing game ID, skipping entry"),
+            Err(e) => eprintln!("Warning: Skipping malformed game analysis: {e}"),
+        }
     }
+    Ok(analyses)
+}
 
-    fn extract_genre_blend(&self, obj: &Value) -> Vec<(String, f32)> {
-        obj.get("genre_blend")
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|g| {
-                        let genre = g.get("genre")?.as_str()?.to_string();
-                        let weight = g.get("weight")?.as_f64()? as f32;
-                        Some((genre, weight))
-                    })
-                    .collect()
-            })
-            .unwrap_or_default()
+fn game_id(game: &Value) -> Option<u32> {
+    game.get("id").and_then(|v| v.as_u64()).map(|id| id as u32)
+}
+
+fn analysis_name(game: &Value) -> &str {
+    game.get("name")
+        .and_then(|v| v.as_str())
+        .unwrap_or("Unknown")
+}
+
+/// A batch checkpoint, or `None` if there is none or it can't be read
+fn load_checkpoint(path: &Path) -> Option<BatchCheckpoint> {
+    let content = fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(checkpoint) => Some(checkpoint),
+        Err(e) => {
+            eprintln!(
+                "Warning: Ignoring unreadable checkpoint {}: {e}",
+                path.display()
+            );
+            None
+        }
     }
 }
 
@@ -546,3 +805,151 @@ pub async fn generate_embeddings_batch(
         .generate_batch(texts, &Default::default())
         .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn complete_analysis(id: u32) -> GameAnalysis {
+        let text = || "Described".to_string();
+        let list = || vec!["Listed".to_string()];
+        GameAnalysis {
+            id,
+            themes: list(),
+            narrative_elements: list(),
+            mechanics: vec![GameMechanic {
+                name: "Jumping".to_string(),
+                description: text(),
+                importance: 0.9,
+                innovation_level: 0.4,
+            }],
+            mood_tags: list(),
+            cultural_impact: text(),
+            design_philosophy: text(),
+            player_experience: text(),
+            difficulty_curve: text(),
+            artistic_style: text(),
+            audio_design: text(),
+            pacing: text(),
+            target_audience: list(),
+            unique_features: list(),
+            genre_blend: vec![GenreWeight {
+                genre: "Platformer".to_string(),
+                weight: 1.0,
+            }],
+            era_significance: text(),
+            core_loop: text(),
+            progression_system: text(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_analyses_by_id() {
+        let response = json!({
+            "games": [
+                { "id": 7, "themes": ["Rescue"] },
+                { "themes": ["No ID"] },
+                { "id": 9, "mechanics": "not a list" },
+                { "id": 12, "pacing": "Brisk" },
+            ]
+        })
+        .to_string();
+
+        let analyses = parse_analyses(&response).unwrap();
+        assert_eq!(analyses.len(), 2);
+        assert_eq!(analyses[&7].themes, ["Rescue"]);
+        assert_eq!(analyses[&12].pacing, "Brisk");
+        assert!(analyses[&12].themes.is_empty());
+    }
+
+    #[test]
+    fn test_parse_analyses_rejects_malformed_responses() {
+        assert!(parse_analyses("not json").is_err());
+        assert!(parse_analyses(r#"{"analyses": []}"#).is_err());
+    }
+
+    #[test]
+    fn test_invalid_fields_of_a_complete_analysis() {
+        assert!(complete_analysis(1).invalid_fields().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_fields_in_prompt_order() {
+        let mut analysis = complete_analysis(1);
+        analysis.pacing = "  ".to_string();
+        analysis.themes = vec![String::new()];
+        analysis.genre_blend[0].weight = 1.5;
+        analysis.mechanics[0].name.clear();
+        // Lists a game may leave empty stay valid
+        analysis.social_features.clear();
+        analysis.influenced_by.clear();
+
+        assert_eq!(
+            analysis.invalid_fields(),
+            ["themes", "mechanics", "pacing", "genre_blend"]
+        );
+        assert_eq!(
+            GameAnalysis::default().invalid_fields().len(),
+            17,
+            "every required field of an empty analysis is invalid"
+        );
+    }
+
+    #[test]
+    fn test_replace_fields_takes_only_the_named_fields() {
+        let mut analysis = complete_analysis(3);
+        let mut repair = GameAnalysis {
+            id: 3,
+            pacing: "Relentless".to_string(),
+            themes: vec!["Revenge".to_string()],
+            ..Default::default()
+        };
+        repair.core_loop = "Ignored".to_string();
+
+        analysis.replace_fields(&repair, &["pacing", "themes", "unknown_field"]);
+        assert_eq!(analysis.pacing, "Relentless");
+        assert_eq!(analysis.themes, ["Revenge"]);
+        assert_eq!(analysis.core_loop, "Described");
+        assert!(analysis.invalid_fields().is_empty());
+    }
+
+    #[test]
+    fn test_batch_schema_requires_every_property() {
+        let schema = batch_schema().unwrap();
+        assert!(schema.get("$schema").is_none());
+        assert_eq!(schema["required"], json!(["games"]));
+
+        let text = schema.to_string();
+        for definition in ["GameAnalysis", "GameMechanic", "GenreWeight"] {
+            assert!(
+                text.contains(definition),
+                "{definition} is not in the schema"
+            );
+        }
+        let analysis = schema
+            .pointer("/$defs/GameAnalysis")
+            .or_else(|| schema.pointer("/definitions/GameAnalysis"))
+            .expect("GameAnalysis definition");
+        let required = analysis["required"].as_array().unwrap();
+        assert_eq!(required.len(), GameAnalysis::FIELDS.len() + 1);
+        for field in GameAnalysis::FIELDS {
+            assert!(required.contains(&json!(field)), "{field} is not required");
+        }
+    }
+
+    #[test]
+    fn test_require_all_properties_reaches_nested_objects() {
+        let mut schema = json!({
+            "properties": { "a": {}, "b": {} },
+            "items": [{ "properties": { "c": {} } }],
+            "nested": { "properties": { "d": {} }, "required": [] },
+        });
+        require_all_properties(&mut schema);
+
+        assert_eq!(schema["required"], json!(["a", "b"]));
+        assert_eq!(schema["items"][0]["required"], json!(["c"]));
+        assert_eq!(schema["nested"]["required"], json!(["d"]));
+    }
+}
@@ -1,4 +1,12 @@
-//! GiantBomb API client
+//! Game data sources
+//!
+//! The timeline can be built from GiantBomb, from IGDB ([`igdb`]), or from
+//! both merged ([`merge`]), behind the [`GameDataSource`] trait. Every game
+//! records the sources its data came from in [`Game::sources`], which is
+//! kept in the generated data.
+
+pub mod igdb;
+pub mod merge;
 
 use crate::types::*;
 use anyhow::{Context, Result};
@@ -6,9 +14,94 @@ use std::collections::{HashMap, HashSet};
 use std::thread;
 use std::time::Duration;
 
+pub use igdb::{IgdbClient, IgdbCredentials};
+pub use merge::MergedSource;
+
+/// Source name of GiantBomb data
+pub const GIANTBOMB_SOURCE: &str = "giantbomb";
+
+/// Timeline games by year and primary genre
+pub type Timeline = HashMap<i32, HashMap<String, Game>>;
+
+/// An API the timeline's games and platforms are fetched from
+pub trait GameDataSource {
+    /// Name recorded in [`Game::sources`], e.g. `giantbomb`
+    fn name(&self) -> &str;
+
+    /// Fetch the vintage platforms
+    fn fetch_platforms(&self) -> Result<Vec<PlatformInfo>>;
+
+    /// Fetch the best reviewed game of up to [`TOP_GENRES_PER_YEAR`]
+    /// genres of each year of the period
+    fn fetch_timeline_games(&self, start_year: i32, end_year: i32) -> Result<Timeline>;
+
+    /// Fill in a game's images where the timeline query left them out
+    fn enhance_game_images(&self, game: &Game) -> Result<Game> {
+        Ok(game.clone())
+    }
+
+    /// Enhance every game of the timeline with detailed images
+    fn enhance_games_with_images(&self, timeline: Timeline) -> Result<Vec<(i32, String, Game)>> {
+        println!("Fetching detailed images for games...");
+        let mut games_with_images = Vec::new();
+
+        for (year, year_games) in timeline {
+            for (genre, game) in year_games {
+                let enhanced_game = self.enhance_game_images(&game)?;
+                games_with_images.push((year, genre, enhanced_game));
+            }
+        }
+
+        Ok(games_with_images)
+    }
+}
+
+/// Which sources the timeline is built from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DataSourceKind {
+    #[default]
+    GiantBomb,
+    Igdb,
+    /// GiantBomb, with IGDB filling its gaps
+    Both,
+}
+
+impl DataSourceKind {
+    /// Kind named `giantbomb`, `igdb` or `both`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "giantbomb" => Some(Self::GiantBomb),
+            "igdb" => Some(Self::Igdb),
+            "both" => Some(Self::Both),
+            _ => None,
+        }
+    }
+
+    pub fn uses_giantbomb(self) -> bool {
+        self != Self::Igdb
+    }
+
+    pub fn uses_igdb(self) -> bool {
+        self != Self::GiantBomb
+    }
+}
+
+/// Whether a platform name is one of the [`VINTAGE_PLATFORMS`]
+pub fn is_vintage_platform(name: &str) -> bool {
+    is_platform_of(name, VINTAGE_PLATFORMS)
+}
+
+/// Whether a platform name is one of `platforms`, e.g. an era pack's
+pub fn is_platform_of(name: &str, platforms: &[&str]) -> bool {
+    platforms
+        .iter()
+        .any(|vp| name.contains(vp) || vp.contains(name))
+}
+
 pub struct GiantBombClient {
     client: reqwest::blocking::Client,
     api_key: String,
+    platforms: &'static [&'static str],
 }
 
 impl GiantBombClient {
@@ -18,7 +111,17 @@ impl GiantBombClient {
             .timeout(Duration::from_secs(30))
             .build()?;
 
-        Ok(Self { client, api_key })
+        Ok(Self {
+            client,
+            api_key,
+            platforms: VINTAGE_PLATFORMS,
+        })
+    }
+
+    /// Keep games of `platforms` instead of the [`VINTAGE_PLATFORMS`]
+    pub fn with_platforms(mut self, platforms: &'static [&'static str]) -> Self {
+        self.platforms = platforms;
+        self
     }
 
     /// Fetch platform information
@@ -52,11 +155,7 @@ impl GiantBombClient {
         let vintage_platforms: Vec<PlatformInfo> = platform_response
             .results
             .into_iter()
-            .filter(|p| {
-                VINTAGE_PLATFORMS
-                    .iter()
-                    .any(|vp| p.name.contains(vp) || vp.contains(p.name.as_str()))
-            })
+            .filter(|p| is_platform_of(&p.name, self.platforms))
             .collect();
 
         println!("  Found {} vintage platforms", vintage_platforms.len());
@@ -64,12 +163,8 @@ impl GiantBombClient {
     }
 
     /// Fetch games for a timeline period
-    pub fn fetch_timeline_games(
-        &self,
-        start_year: i32,
-        end_year: i32,
-    ) -> Result<HashMap<i32, HashMap<String, Game>>> {
-        let mut timeline: HashMap<i32, HashMap<String, Game>> = HashMap::new();
+    pub fn fetch_timeline_games(&self, start_year: i32, end_year: i32) -> Result<Timeline> {
+        let mut timeline = Timeline::new();
         let mut processed_ids = HashSet::new();
 
         for year in start_year..=end_year {
@@ -137,7 +232,7 @@ impl GiantBombClient {
         let mut games_without_platforms = 0;
         let mut games_without_vintage_platforms = 0;
 
-        for game in gb_response.results {
+        for mut game in gb_response.results {
             // Skip if we've already processed this game
             if !processed_ids.insert(game.id) {
                 continue;
@@ -153,11 +248,9 @@ impl GiantBombClient {
             };
 
             // Filter by vintage platforms
-            let has_vintage_platform = platforms.iter().any(|p| {
-                VINTAGE_PLATFORMS
-                    .iter()
-                    .any(|vp| p.name.contains(vp) || vp.contains(p.name.as_str()))
-            });
+            let has_vintage_platform = platforms
+                .iter()
+                .any(|p| is_platform_of(&p.name, self.platforms));
 
             if !has_vintage_platform {
                 games_without_vintage_platforms += 1;
@@ -175,6 +268,7 @@ impl GiantBombClient {
             // Only keep if it's the first game we've seen for this genre this year
             use std::collections::hash_map::Entry;
             if let Entry::Vacant(e) = year_games.entry(primary_genre.clone()) {
+                game.sources = vec![GIANTBOMB_SOURCE.to_string()];
                 eprintln!(
                     "    Added: {} ({}) - {}",
                     game.name,
@@ -203,29 +297,6 @@ impl GiantBombClient {
         Ok(year_games)
     }
 
-    /// Enhance games with detailed image information
-    pub fn enhance_games_with_images(
-        &self,
-        timeline: HashMap<i32, HashMap<String, Game>>,
-    ) -> Result<Vec<(i32, String, Game)>> {
-        println!("Fetching detailed images for games...");
-        let mut games_with_images = Vec::new();
-
-        for year in timeline.keys().cloned().collect::<Vec<_>>() {
-            if let Some(year_games) = timeline.get(&year) {
-                for (genre, game) in year_games {
-                    let enhanced_game = self.enhance_game_images(game)?;
-                    games_with_images.push((year, genre.clone(), enhanced_game));
-
-                    // Rate limit
-                    thread::sleep(Duration::from_millis(200));
-                }
-            }
-        }
-
-        Ok(games_with_images)
-    }
-
     /// Enhance a single game with detailed images
     fn enhance_game_images(&self, game: &Game) -> Result<Game> {
         let mut enhanced_game = game.clone();
@@ -263,8 +334,29 @@ impl GiantBombClient {
                     });
                 }
             }
+
+            // Rate limit
+            thread::sleep(Duration::from_millis(200));
         }
 
         Ok(enhanced_game)
     }
 }
+
+impl GameDataSource for GiantBombClient {
+    fn name(&self) -> &str {
+        GIANTBOMB_SOURCE
+    }
+
+    fn fetch_platforms(&self) -> Result<Vec<PlatformInfo>> {
+        GiantBombClient::fetch_platforms(self)
+    }
+
+    fn fetch_timeline_games(&self, start_year: i32, end_year: i32) -> Result<Timeline> {
+        GiantBombClient::fetch_timeline_games(self, start_year, end_year)
+    }
+
+    fn enhance_game_images(&self, game: &Game) -> Result<Game> {
+        GiantBombClient::enhance_game_images(self, game)
+    }
+}
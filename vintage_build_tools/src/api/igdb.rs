@@ -0,0 +1,377 @@
+//! IGDB API client
+//!
+//! IGDB authenticates through Twitch: the client ID and secret of a Twitch
+//! application are exchanged for an app access token, which is sent with the
+//! client ID on every request. Queries are written in IGDB's Apicalypse
+//! syntax and POSTed to the endpoint.
+//!
+//! IGDB's IDs overlap GiantBomb's, so the IDs of IGDB games and platforms
+//! are offset by [`IGDB_ID_OFFSET`] to keep them apart in a merged timeline.
+
+use super::{GameDataSource, Timeline, is_platform_of};
+use crate::types::*;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet};
+use std::thread;
+use std::time::Duration;
+
+/// Source name of IGDB data
+pub const IGDB_SOURCE: &str = "igdb";
+
+/// Added to IGDB IDs so they don't collide with GiantBomb IDs
+pub const IGDB_ID_OFFSET: u32 = 10_000_000;
+
+/// IGDB allows 4 requests per second
+const REQUEST_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Fields of a game the timeline needs
+const GAME_FIELDS: &str = "name,slug,summary,first_release_date,url,genres.name,\
+    platforms.name,platforms.abbreviation,involved_companies.developer,\
+    involved_companies.company.name,cover.image_id,screenshots.image_id";
+
+/// Twitch application credentials for IGDB
+#[derive(Debug, Clone)]
+pub struct IgdbCredentials {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+impl IgdbCredentials {
+    /// Credentials from `TWITCH_CLIENT_ID` and `TWITCH_CLIENT_SECRET`, if
+    /// both are set
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            client_id: std::env::var("TWITCH_CLIENT_ID").ok()?,
+            client_secret: std::env::var("TWITCH_CLIENT_SECRET").ok()?,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TwitchToken {
+    access_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct IgdbNamed {
+    id: u32,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct IgdbPlatform {
+    id: u32,
+    name: String,
+    #[serde(default)]
+    abbreviation: Option<String>,
+    #[serde(default)]
+    summary: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IgdbInvolvedCompany {
+    #[serde(default)]
+    developer: bool,
+    company: IgdbNamed,
+}
+
+#[derive(Debug, Deserialize)]
+struct IgdbImage {
+    image_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct IgdbGame {
+    id: u32,
+    name: String,
+    #[serde(default)]
+    slug: Option<String>,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    first_release_date: Option<i64>,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    genres: Vec<IgdbNamed>,
+    #[serde(default)]
+    platforms: Vec<IgdbPlatform>,
+    #[serde(default)]
+    involved_companies: Vec<IgdbInvolvedCompany>,
+    #[serde(default)]
+    cover: Option<IgdbImage>,
+    #[serde(default)]
+    screenshots: Vec<IgdbImage>,
+}
+
+pub struct IgdbClient {
+    client: reqwest::blocking::Client,
+    client_id: String,
+    access_token: String,
+    platforms: &'static [&'static str],
+}
+
+impl IgdbClient {
+    /// Create a client with an app access token for the credentials
+    pub fn new(credentials: &IgdbCredentials) -> Result<Self> {
+        let client = reqwest::blocking::Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(Duration::from_secs(30))
+            .build()?;
+
+        let response = client
+            .post(TWITCH_TOKEN_URL)
+            .query(&[
+                ("client_id", credentials.client_id.as_str()),
+                ("client_secret", credentials.client_secret.as_str()),
+                ("grant_type", "client_credentials"),
+            ])
+            .send()
+            .context("Failed to request a Twitch access token")?;
+        if !response.status().is_success() {
+            anyhow::bail!("Twitch OAuth returned status: {}", response.status());
+        }
+        let token: TwitchToken = response
+            .json()
+            .context("Failed to parse the Twitch access token")?;
+
+        Ok(Self {
+            client,
+            client_id: credentials.client_id.clone(),
+            access_token: token.access_token,
+            platforms: VINTAGE_PLATFORMS,
+        })
+    }
+
+    /// Keep games of `platforms` instead of the [`VINTAGE_PLATFORMS`]
+    pub fn with_platforms(mut self, platforms: &'static [&'static str]) -> Self {
+        self.platforms = platforms;
+        self
+    }
+
+    /// IGDB names most consoles in full, so the abbreviation is matched too
+    fn is_wanted_platform(&self, name: &str, abbreviation: Option<&str>) -> bool {
+        is_platform_of(name, self.platforms)
+            || abbreviation.is_some_and(|abbreviation| is_platform_of(abbreviation, self.platforms))
+    }
+
+    /// POST an Apicalypse query to an endpoint, e.g. `games`
+    fn query<T: DeserializeOwned>(&self, endpoint: &str, query: String) -> Result<Vec<T>> {
+        let response = self
+            .client
+            .post(format!("{IGDB_API_BASE}/{endpoint}"))
+            .header("Client-ID", &self.client_id)
+            .bearer_auth(&self.access_token)
+            .body(query)
+            .send()
+            .with_context(|| format!("Failed to query IGDB {endpoint}"))?;
+        thread::sleep(REQUEST_INTERVAL);
+
+        if !response.status().is_success() {
+            anyhow::bail!("IGDB {endpoint} returned status: {}", response.status());
+        }
+        response
+            .json()
+            .with_context(|| format!("Failed to parse IGDB {endpoint} response"))
+    }
+
+    /// Fetch games for a specific year
+    fn fetch_year_games(
+        &self,
+        year: i32,
+        processed_ids: &mut HashSet<u32>,
+    ) -> Result<HashMap<String, Game>> {
+        let (Some(start), Some(end)) = (year_start(year), year_start(year + 1)) else {
+            return Ok(HashMap::new());
+        };
+        let query = format!(
+            "fields {GAME_FIELDS}; \
+             where first_release_date >= {start} & first_release_date < {end} \
+             & version_parent = null; \
+             sort total_rating_count desc; limit 100;"
+        );
+        let results: Vec<IgdbGame> = match self.query("games", query) {
+            Ok(results) => results,
+            Err(e) => {
+                eprintln!("    Warning: Failed to fetch {year} games from IGDB: {e:#}");
+                return Ok(HashMap::new());
+            }
+        };
+
+        // Group games by genre and pick the best rated one
+        let mut year_games: HashMap<String, Game> = HashMap::new();
+        for igdb_game in results {
+            if !processed_ids.insert(igdb_game.id) {
+                continue;
+            }
+            let has_vintage_platform = igdb_game
+                .platforms
+                .iter()
+                .any(|p| self.is_wanted_platform(&p.name, p.abbreviation.as_deref()));
+            if !has_vintage_platform {
+                continue;
+            }
+
+            let game = to_game(igdb_game);
+            let primary_genre = game
+                .genres
+                .as_ref()
+                .and_then(|genres| genres.first())
+                .map(|g| g.name.clone())
+                .unwrap_or_else(|| "Action".to_string());
+
+            use std::collections::hash_map::Entry;
+            if let Entry::Vacant(e) = year_games.entry(primary_genre.clone()) {
+                eprintln!("    Added: {} ({}) from IGDB", game.name, &primary_genre);
+                e.insert(game);
+            }
+
+            if year_games.len() >= TOP_GENRES_PER_YEAR {
+                break;
+            }
+        }
+
+        Ok(year_games)
+    }
+}
+
+impl GameDataSource for IgdbClient {
+    fn name(&self) -> &str {
+        IGDB_SOURCE
+    }
+
+    fn fetch_platforms(&self) -> Result<Vec<PlatformInfo>> {
+        println!("Fetching platform information from IGDB...");
+
+        let platforms: Vec<IgdbPlatform> = self.query(
+            "platforms",
+            "fields name,abbreviation,summary; limit 500;".to_string(),
+        )?;
+        let vintage_platforms: Vec<PlatformInfo> = platforms
+            .into_iter()
+            .filter(|p| self.is_wanted_platform(&p.name, p.abbreviation.as_deref()))
+            .map(|p| PlatformInfo {
+                id: p.id + IGDB_ID_OFFSET,
+                name: p.name,
+                abbreviation: p.abbreviation,
+                deck: p.summary,
+                install_base: None,
+                original_price: None,
+                release_date: None,
+                online_support: None,
+            })
+            .collect();
+
+        println!("  Found {} vintage platforms", vintage_platforms.len());
+        Ok(vintage_platforms)
+    }
+
+    fn fetch_timeline_games(&self, start_year: i32, end_year: i32) -> Result<Timeline> {
+        let mut timeline = Timeline::new();
+        let mut processed_ids = HashSet::new();
+
+        for year in start_year..=end_year {
+            println!("  Fetching games from {year} from IGDB...");
+
+            let year_games = self.fetch_year_games(year, &mut processed_ids)?;
+            if !year_games.is_empty() {
+                timeline.insert(year, year_games);
+            }
+        }
+
+        Ok(timeline)
+    }
+}
+
+/// Unix timestamp of the start of a year
+fn year_start(year: i32) -> Option<i64> {
+    Some(
+        chrono::NaiveDate::from_ymd_opt(year, 1, 1)?
+            .and_hms_opt(0, 0, 0)?
+            .and_utc()
+            .timestamp(),
+    )
+}
+
+/// URL of an IGDB image in one of its sizes, e.g. `cover_big`
+fn image_url(size: &str, image_id: &str) -> String {
+    format!("{IGDB_IMAGE_BASE}/t_{size}/{image_id}.jpg")
+}
+
+/// Convert an IGDB game to the timeline's game type
+fn to_game(game: IgdbGame) -> Game {
+    let cover = game.cover.map(|image| image.image_id);
+    let screenshot = game
+        .screenshots
+        .into_iter()
+        .next()
+        .map(|image| image.image_id);
+    let image = cover.as_ref().or(screenshot.as_ref()).map(|fallback| {
+        let cover = cover.as_ref().unwrap_or(fallback);
+        let screen = screenshot.as_ref().unwrap_or(fallback);
+        ImageInfo {
+            icon_url: Some(image_url("micro", cover)),
+            medium_url: Some(image_url("cover_big", cover)),
+            screen_url: Some(image_url("screenshot_med", screen)),
+            screen_large_url: Some(image_url("screenshot_big", screen)),
+            small_url: Some(image_url("cover_small", cover)),
+            super_url: Some(image_url("screenshot_huge", screen)),
+            thumb_url: Some(image_url("thumb", cover)),
+            tiny_url: Some(image_url("micro", cover)),
+            original_url: image_url("original", cover),
+            image_tags: None,
+        }
+    });
+
+    let developers: Vec<Developer> = game
+        .involved_companies
+        .into_iter()
+        .filter(|company| company.developer)
+        .map(|company| Developer {
+            id: company.company.id + IGDB_ID_OFFSET,
+            name: company.company.name,
+        })
+        .collect();
+
+    Game {
+        id: game.id + IGDB_ID_OFFSET,
+        guid: format!("igdb-{}", game.slug.unwrap_or_else(|| game.id.to_string())),
+        name: game.name,
+        aliases: None,
+        deck: game.summary,
+        description: None,
+        image,
+        original_release_date: game
+            .first_release_date
+            .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+            .map(|date| date.format("%Y-%m-%d").to_string()),
+        platforms: Some(
+            game.platforms
+                .into_iter()
+                .map(|p| Platform {
+                    id: p.id + IGDB_ID_OFFSET,
+                    name: p.name,
+                    abbreviation: p.abbreviation,
+                    install_base: None,
+                    original_price: None,
+                    release_date: None,
+                })
+                .collect(),
+        ),
+        genres: Some(
+            game.genres
+                .into_iter()
+                .map(|genre| Genre {
+                    id: genre.id + IGDB_ID_OFFSET,
+                    name: genre.name,
+                })
+                .collect(),
+        ),
+        themes: None,
+        developers: (!developers.is_empty()).then_some(developers),
+        site_detail_url: game.url,
+        sources: vec![IGDB_SOURCE.to_string()],
+    }
+}
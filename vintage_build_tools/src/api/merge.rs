@@ -0,0 +1,150 @@
+//! Timeline merged from two data sources
+//!
+//! The primary source picks the timeline; the secondary fills the genres a
+//! year is missing and the fields a game is missing, such as platforms the
+//! primary source doesn't list. A game both sources know is matched by its
+//! name and lists both in its [`Game::sources`].
+
+use super::{GameDataSource, Timeline};
+use crate::types::*;
+use anyhow::Result;
+
+pub struct MergedSource {
+    primary: Box<dyn GameDataSource>,
+    secondary: Box<dyn GameDataSource>,
+    name: String,
+}
+
+impl MergedSource {
+    pub fn new(primary: Box<dyn GameDataSource>, secondary: Box<dyn GameDataSource>) -> Self {
+        let name = format!("{}+{}", primary.name(), secondary.name());
+        Self {
+            primary,
+            secondary,
+            name,
+        }
+    }
+}
+
+impl GameDataSource for MergedSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn fetch_platforms(&self) -> Result<Vec<PlatformInfo>> {
+        let mut platforms = self.primary.fetch_platforms()?;
+        match self.secondary.fetch_platforms() {
+            Ok(secondary) => {
+                for platform in secondary {
+                    if !platforms.iter().any(|p| same_name(&p.name, &platform.name)) {
+                        platforms.push(platform);
+                    }
+                }
+            }
+            Err(e) => eprintln!(
+                "Warning: Failed to fetch platforms from {}: {e:#}",
+                self.secondary.name()
+            ),
+        }
+        Ok(platforms)
+    }
+
+    fn fetch_timeline_games(&self, start_year: i32, end_year: i32) -> Result<Timeline> {
+        let mut timeline = self.primary.fetch_timeline_games(start_year, end_year)?;
+        let secondary = match self.secondary.fetch_timeline_games(start_year, end_year) {
+            Ok(secondary) => secondary,
+            Err(e) => {
+                eprintln!(
+                    "Warning: Failed to fetch games from {}: {e:#}",
+                    self.secondary.name()
+                );
+                return Ok(timeline);
+            }
+        };
+
+        merge_timelines(&mut timeline, secondary);
+        Ok(timeline)
+    }
+
+    fn enhance_game_images(&self, game: &Game) -> Result<Game> {
+        // Each source enhances the games it found
+        match game.sources.first() {
+            Some(source) if source == self.secondary.name() => {
+                self.secondary.enhance_game_images(game)
+            }
+            _ => self.primary.enhance_game_images(game),
+        }
+    }
+}
+
+/// Merge the games of `secondary` into `timeline`: a game the timeline
+/// already has, in any year, gets its missing fields filled, and any other
+/// game takes its genre's slot in its year if the slot is free
+pub fn merge_timelines(timeline: &mut Timeline, secondary: Timeline) {
+    let mut secondary: Vec<(i32, String, Game)> = secondary
+        .into_iter()
+        .flat_map(|(year, games)| {
+            games
+                .into_iter()
+                .map(move |(genre, game)| (year, genre, game))
+        })
+        .collect();
+    secondary.sort_by_key(|(year, genre, _)| (*year, genre.clone()));
+
+    for (year, genre, game) in secondary {
+        let existing = timeline
+            .values_mut()
+            .flat_map(|games| games.values_mut())
+            .find(|existing| same_name(&existing.name, &game.name));
+        if let Some(existing) = existing {
+            fill_missing(existing, game);
+            continue;
+        }
+
+        let year_games = timeline.entry(year).or_default();
+        if year_games.len() < TOP_GENRES_PER_YEAR && !year_games.contains_key(&genre) {
+            year_games.insert(genre, game);
+        }
+    }
+}
+
+/// Fill the fields `game` lacks from `other`, the same game from another
+/// source, and credit the other source
+fn fill_missing(game: &mut Game, other: Game) {
+    game.deck = game.deck.take().or(other.deck);
+    game.description = game.description.take().or(other.description);
+    game.image = game.image.take().or(other.image);
+    game.original_release_date = game
+        .original_release_date
+        .take()
+        .or(other.original_release_date);
+    game.genres = game.genres.take().or(other.genres);
+    game.developers = game.developers.take().or(other.developers);
+    game.site_detail_url = game.site_detail_url.take().or(other.site_detail_url);
+
+    if let Some(other_platforms) = other.platforms {
+        let platforms = game.platforms.get_or_insert_with(Vec::new);
+        for platform in other_platforms {
+            if !platforms.iter().any(|p| same_name(&p.name, &platform.name)) {
+                platforms.push(platform);
+            }
+        }
+    }
+
+    for source in other.sources {
+        if !game.sources.contains(&source) {
+            game.sources.push(source);
+        }
+    }
+}
+
+/// Whether two names are the same, ignoring case and punctuation
+fn same_name(a: &str, b: &str) -> bool {
+    let normalize = |name: &str| {
+        name.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+    };
+    normalize(a) == normalize(b)
+}
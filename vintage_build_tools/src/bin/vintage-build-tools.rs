@@ -0,0 +1,121 @@
+//! Command line entry point for the build tools
+//!
+//! ```text
+//! vintage-build-tools generate [--incremental] [--concurrency N] [START END]
+//! vintage-build-tools era-pack <ID>... | --all
+//! vintage-build-tools bundle --version 1.0.0 [--source DIR] [--output DIR] [--license SPDX]
+//! ```
+
+use anyhow::{Context, Result};
+use vintage_build_tools::{
+    DatasetBundler, ERA_PACKS, VintageBuildTools, dataset::DATASET_SOURCE_DIR,
+};
+
+const USAGE: &str = "Usage:
+  vintage-build-tools generate [--incremental] [--concurrency <N>] [START END]
+  vintage-build-tools era-pack <ID>... | --all
+  vintage-build-tools bundle --version <VERSION> [--source <DIR>] [--output <DIR>] [--license <SPDX>]";
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        Some("generate") => {
+            let mut incremental = false;
+            let mut concurrency = None;
+            let mut years = Vec::new();
+            let mut rest = args[1..].iter();
+            while let Some(arg) = rest.next() {
+                match arg.as_str() {
+                    "--incremental" => incremental = true,
+                    "--concurrency" => {
+                        let value = rest
+                            .next()
+                            .with_context(|| format!("Missing value for {arg}\n{USAGE}"))?;
+                        concurrency = Some(value.parse::<usize>().with_context(|| {
+                            format!("--concurrency takes a number, not {value}")
+                        })?);
+                    }
+                    _ => years.push(arg),
+                }
+            }
+            let start = years
+                .first()
+                .map(|s| s.parse())
+                .transpose()?
+                .unwrap_or(1980);
+            let end = years.get(1).map(|s| s.parse()).transpose()?.unwrap_or(1995);
+            let mut tools = VintageBuildTools::from_env(start, end)?;
+            if let Some(concurrency) = concurrency {
+                tools = tools.with_concurrency(concurrency);
+            }
+            if incremental {
+                tools.build_incremental().await
+            } else {
+                tools.build().await
+            }
+        }
+        Some("era-pack") => era_packs(&args[1..]),
+        Some("bundle") => bundle(&args[1..]),
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    }
+}
+
+fn era_packs(args: &[String]) -> Result<()> {
+    let ids: Vec<&str> = if args.iter().any(|arg| arg == "--all") {
+        ERA_PACKS.iter().map(|spec| spec.id).collect()
+    } else {
+        args.iter().map(String::as_str).collect()
+    };
+    if ids.is_empty() {
+        anyhow::bail!("Name an era pack or pass --all\n{USAGE}");
+    }
+
+    // The period comes from each pack
+    let tools = VintageBuildTools::from_env(1980, 1995)?;
+    for id in ids {
+        tools.build_era_pack(id)?;
+    }
+    Ok(())
+}
+
+fn bundle(args: &[String]) -> Result<()> {
+    let mut version = None;
+    let mut source = DATASET_SOURCE_DIR.to_string();
+    let mut output = "dist".to_string();
+    let mut license = None;
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .with_context(|| format!("Missing value for {flag}\n{USAGE}"))?
+            .clone();
+        match flag.as_str() {
+            "--version" => version = Some(value),
+            "--source" => source = value,
+            "--output" => output = value,
+            "--license" => license = Some(value),
+            other => anyhow::bail!("Unknown option {other}\n{USAGE}"),
+        }
+    }
+    let version = version.with_context(|| format!("--version is required\n{USAGE}"))?;
+
+    let mut bundler = DatasetBundler::new(source);
+    if let Some(license) = license {
+        bundler = bundler.with_license(license);
+    }
+    let manifest = bundler.bundle(&output, &version)?;
+
+    for file in &manifest.files {
+        println!(
+            "  {} ({} bytes, sha256 {})",
+            file.path, file.bytes, file.sha256
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,262 @@
+//! Versioned dataset bundles of the vintage game knowledge base
+//!
+//! The full build talks to Giant Bomb and OpenAI, which is slow and costs
+//! money. After a build, the enriched metadata and similarity graph are kept
+//! in [`DATASET_SOURCE_DIR`]; [`DatasetBundler`] packages them into a
+//! self-contained directory other crates and apps can consume:
+//!
+//! ```text
+//! vintage-games-<version>/
+//!   manifest.json    version, counts and SHA-256 of every file
+//!   games.json       enriched metadata without embeddings
+//!   embeddings.json  int8-quantized embeddings per game
+//!   graph.json       similarity graph
+//!   LICENSE
+//! ```
+
+use crate::ai_analysis::EnrichedGameMetadata;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where a build leaves the inputs for dataset bundles
+pub const DATASET_SOURCE_DIR: &str = "assets/wizard/dataset";
+
+/// Bumped whenever the layout of the bundle files changes
+pub const DATASET_FORMAT_VERSION: u32 = 1;
+
+const ENRICHED_FILE: &str = "enriched_metadata.json";
+const GRAPH_FILE: &str = "graph.json";
+const MANIFEST_FILE: &str = "manifest.json";
+const LICENSE_FILE: &str = "LICENSE";
+
+/// Description of a bundle, written as `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetManifest {
+    pub name: String,
+    pub version: String,
+    pub format_version: u32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub license: String,
+    pub game_count: usize,
+    pub year_range: Option<(i32, i32)>,
+    /// Dimensions of each embedding kind, e.g. `overall` -> 1536
+    pub embedding_dimensions: Vec<(String, usize)>,
+    pub files: Vec<ManifestFile>,
+}
+
+/// A file in the bundle with its checksum
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestFile {
+    pub path: String,
+    pub bytes: u64,
+    pub sha256: String,
+}
+
+/// Embedding stored as signed bytes; `value = byte * scale`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantizedEmbedding {
+    pub scale: f32,
+    pub values: Vec<i8>,
+}
+
+impl QuantizedEmbedding {
+    /// Symmetric int8 quantization scaled to the largest magnitude
+    pub fn quantize(embedding: &[f32]) -> Self {
+        let max = embedding.iter().fold(0.0f32, |max, v| max.max(v.abs()));
+        let scale = if max > 0.0 { max / 127.0 } else { 1.0 };
+        let values = embedding
+            .iter()
+            .map(|v| (v / scale).round().clamp(-127.0, 127.0) as i8)
+            .collect();
+        Self { scale, values }
+    }
+
+    pub fn dequantize(&self) -> Vec<f32> {
+        self.values.iter().map(|&v| v as f32 * self.scale).collect()
+    }
+}
+
+/// All embeddings of one game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameEmbeddings {
+    pub id: u32,
+    pub overall: Option<QuantizedEmbedding>,
+    pub theme: Option<QuantizedEmbedding>,
+    pub mechanic: Option<QuantizedEmbedding>,
+    pub narrative: Option<QuantizedEmbedding>,
+}
+
+impl GameEmbeddings {
+    fn from_metadata(game: &EnrichedGameMetadata) -> Self {
+        let quantize =
+            |values: &[f32]| (!values.is_empty()).then(|| QuantizedEmbedding::quantize(values));
+        Self {
+            id: game.id,
+            overall: quantize(&game.overall_embedding),
+            theme: quantize(&game.theme_embeddings),
+            mechanic: quantize(&game.mechanic_embeddings),
+            narrative: quantize(&game.narrative_embeddings),
+        }
+    }
+}
+
+/// Packages build outputs into versioned dataset bundles
+pub struct DatasetBundler {
+    source_dir: PathBuf,
+    license: String,
+}
+
+impl DatasetBundler {
+    pub fn new(source_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            source_dir: source_dir.into(),
+            license: env!("CARGO_PKG_LICENSE").to_string(),
+        }
+    }
+
+    /// SPDX expression recorded in the manifest and LICENSE file
+    pub fn with_license(mut self, license: impl Into<String>) -> Self {
+        self.license = license.into();
+        self
+    }
+
+    /// Keep the expensive build outputs so bundles can be made later
+    pub fn save_sources(
+        source_dir: impl AsRef<Path>,
+        enriched: &[EnrichedGameMetadata],
+        graph: &Value,
+    ) -> Result<()> {
+        let dir = source_dir.as_ref();
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join(ENRICHED_FILE), serde_json::to_string(enriched)?)?;
+        fs::write(dir.join(GRAPH_FILE), serde_json::to_string(graph)?)?;
+        Ok(())
+    }
+
+    /// Enriched metadata kept by the last build
+    pub fn load_enriched(source_dir: impl AsRef<Path>) -> Result<Vec<EnrichedGameMetadata>> {
+        let enriched_path = source_dir.as_ref().join(ENRICHED_FILE);
+        serde_json::from_str(&fs::read_to_string(&enriched_path).with_context(|| {
+            format!(
+                "Failed to read {}; run the full build first",
+                enriched_path.display()
+            )
+        })?)
+        .with_context(|| format!("Failed to parse {}", enriched_path.display()))
+    }
+
+    /// Write `<output_dir>/vintage-games-<version>` and return its manifest
+    pub fn bundle(&self, output_dir: impl AsRef<Path>, version: &str) -> Result<DatasetManifest> {
+        let enriched = Self::load_enriched(&self.source_dir)?;
+
+        let graph_path = self.source_dir.join(GRAPH_FILE);
+        let graph: Value = serde_json::from_str(
+            &fs::read_to_string(&graph_path)
+                .with_context(|| format!("Failed to read {}", graph_path.display()))?,
+        )?;
+
+        let name = format!("vintage-games-{version}");
+        let bundle_dir = output_dir.as_ref().join(&name);
+        fs::create_dir_all(&bundle_dir)?;
+
+        let embeddings: Vec<GameEmbeddings> =
+            enriched.iter().map(GameEmbeddings::from_metadata).collect();
+        let games: Vec<Value> = enriched
+            .iter()
+            .map(strip_embeddings)
+            .collect::<Result<_>>()?;
+
+        let mut files = Vec::new();
+        for (file, content) in [
+            ("games.json", serde_json::to_vec_pretty(&games)?),
+            ("embeddings.json", serde_json::to_vec(&embeddings)?),
+            ("graph.json", serde_json::to_vec_pretty(&graph)?),
+            (LICENSE_FILE, self.license_text().into_bytes()),
+        ] {
+            fs::write(bundle_dir.join(file), &content)?;
+            files.push(ManifestFile {
+                path: file.to_string(),
+                bytes: content.len() as u64,
+                sha256: format!("{:x}", Sha256::digest(&content)),
+            });
+        }
+
+        let years = enriched.iter().map(|g| g.year);
+        let year_range = years.clone().min().zip(years.max());
+
+        let manifest = DatasetManifest {
+            name,
+            version: version.to_string(),
+            format_version: DATASET_FORMAT_VERSION,
+            created_at: chrono::Utc::now(),
+            license: self.license.clone(),
+            game_count: enriched.len(),
+            year_range,
+            embedding_dimensions: embedding_dimensions(&enriched),
+            files,
+        };
+        fs::write(
+            bundle_dir.join(MANIFEST_FILE),
+            serde_json::to_string_pretty(&manifest)?,
+        )?;
+
+        println!(
+            "Bundled {} games into {}",
+            manifest.game_count,
+            bundle_dir.display()
+        );
+        Ok(manifest)
+    }
+
+    fn license_text(&self) -> String {
+        format!(
+            "This dataset is licensed under {}.\n\n\
+             Game facts were retrieved from the Giant Bomb API (https://www.giantbomb.com/api/)\n\
+             and remain subject to its terms of use. Analysis fields and embeddings were\n\
+             generated with OpenAI models.\n",
+            self.license
+        )
+    }
+}
+
+/// Serialize a game without its embedding vectors
+fn strip_embeddings(game: &EnrichedGameMetadata) -> Result<Value> {
+    let mut value = serde_json::to_value(game)?;
+    if let Some(object) = value.as_object_mut() {
+        for field in [
+            "theme_embeddings",
+            "mechanic_embeddings",
+            "narrative_embeddings",
+            "overall_embedding",
+        ] {
+            object.remove(field);
+        }
+    }
+    Ok(value)
+}
+
+fn embedding_dimensions(enriched: &[EnrichedGameMetadata]) -> Vec<(String, usize)> {
+    let dimension = |get: fn(&EnrichedGameMetadata) -> &Vec<f32>| {
+        enriched
+            .iter()
+            .map(|g| get(g).len())
+            .find(|&len| len > 0)
+            .unwrap_or(0)
+    };
+    vec![
+        ("overall".to_string(), dimension(|g| &g.overall_embedding)),
+        ("theme".to_string(), dimension(|g| &g.theme_embeddings)),
+        (
+            "mechanic".to_string(),
+            dimension(|g| &g.mechanic_embeddings),
+        ),
+        (
+            "narrative".to_string(),
+            dimension(|g| &g.narrative_embeddings),
+        ),
+    ]
+}
@@ -0,0 +1,190 @@
+//! Era packs: timelines of other periods, shipped as data files
+//!
+//! The generated modules embed the 1980-1995 timeline. An era pack holds the
+//! timeline of another period, such as the Atari era, as JSON in
+//! [`ERA_PACKS_DIR`], and the game generator loads whichever packs are
+//! present at startup. Packs skip the AI analysis, so building one only
+//! costs the data source's requests.
+//!
+//! ```text
+//! assets/wizard/era_packs/
+//!   atari.json    1977-1983
+//!   32-bit.json   1996-2001
+//! ```
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where era packs are written
+pub const ERA_PACKS_DIR: &str = "assets/wizard/era_packs";
+
+/// Bumped whenever the layout of a pack changes
+pub const ERA_PACK_FORMAT_VERSION: u32 = 1;
+
+/// A period an era pack can be built for
+#[derive(Debug, Clone, Copy)]
+pub struct EraPackSpec {
+    /// File name of the pack, e.g. `atari`
+    pub id: &'static str,
+    pub name: &'static str,
+    pub start_year: i32,
+    pub end_year: i32,
+    /// Platforms the pack's games are picked from
+    pub platforms: &'static [&'static str],
+}
+
+/// The era packs the build tools know how to build
+pub const ERA_PACKS: &[EraPackSpec] = &[
+    EraPackSpec {
+        id: "atari",
+        name: "Atari Era",
+        start_year: 1977,
+        end_year: 1983,
+        platforms: &[
+            "Atari 2600",
+            "Atari 5200",
+            "Atari 8-bit",
+            "Intellivision",
+            "ColecoVision",
+            "Odyssey",
+            "Arcade",
+            "Apple II",
+            "VIC-20",
+        ],
+    },
+    EraPackSpec {
+        id: "32-bit",
+        name: "32-bit Era",
+        start_year: 1996,
+        end_year: 2001,
+        platforms: &[
+            "PlayStation",
+            "Saturn",
+            "Nintendo 64",
+            "Dreamcast",
+            "Game Boy Color",
+            "Arcade",
+            "PC",
+        ],
+    },
+];
+
+/// The pack with this ID
+pub fn era_pack_spec(id: &str) -> Option<&'static EraPackSpec> {
+    ERA_PACKS.iter().find(|spec| spec.id == id)
+}
+
+/// An era pack file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EraPack {
+    pub format_version: u32,
+    pub id: String,
+    pub name: String,
+    pub start_year: i32,
+    pub end_year: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub games: Vec<PackGame>,
+}
+
+/// A game of a pack, laid out like the generated `TimelineGame`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackGame {
+    pub id: u32,
+    pub year: i32,
+    pub genre: String,
+    pub name: String,
+    pub deck: Option<String>,
+    pub platforms: Vec<String>,
+    pub developer: Option<String>,
+    pub image_urls: PackImageUrls,
+    pub site_url: String,
+    pub sources: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackImageUrls {
+    pub icon: Option<String>,
+    pub medium: Option<String>,
+    pub screen: Option<String>,
+    pub screen_large: Option<String>,
+    pub small: Option<String>,
+    pub super_url: Option<String>,
+    pub thumb: Option<String>,
+    pub tiny: Option<String>,
+    pub original: String,
+}
+
+impl PackGame {
+    /// A game of the JSON timeline the generator builds for the templates
+    pub fn from_timeline_json(game: &Value) -> Option<Self> {
+        let string = |key: &str| game.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        let strings = |key: &str| -> Vec<String> {
+            game.get(key)
+                .and_then(|v| v.as_array())
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        Some(Self {
+            id: game.get("id")?.as_u64()? as u32,
+            year: game.get("year")?.as_i64()? as i32,
+            genre: string("genre")?,
+            name: string("name")?,
+            deck: string("deck"),
+            platforms: strings("platforms"),
+            developer: string("developers"),
+            image_urls: PackImageUrls {
+                icon: string("image_icon_url"),
+                medium: string("image_medium_url"),
+                screen: string("image_screen_url"),
+                screen_large: string("image_screen_large_url"),
+                small: string("image_small_url"),
+                super_url: string("image_super_url"),
+                thumb: string("image_thumb_url"),
+                tiny: string("image_tiny_url"),
+                original: string("image_original_url").unwrap_or_default(),
+            },
+            site_url: string("site_url").unwrap_or_default(),
+            sources: strings("sources"),
+        })
+    }
+}
+
+impl EraPack {
+    /// Pack the timeline games fetched for `spec`
+    pub fn new(spec: &EraPackSpec, timeline_games: &[Value]) -> Self {
+        let mut games: Vec<PackGame> = timeline_games
+            .iter()
+            .filter_map(PackGame::from_timeline_json)
+            .collect();
+        games.sort_by(|a, b| a.year.cmp(&b.year).then_with(|| a.genre.cmp(&b.genre)));
+
+        Self {
+            format_version: ERA_PACK_FORMAT_VERSION,
+            id: spec.id.to_string(),
+            name: spec.name.to_string(),
+            start_year: spec.start_year,
+            end_year: spec.end_year,
+            created_at: chrono::Utc::now(),
+            games,
+        }
+    }
+
+    /// Write the pack to `<dir>/<id>.json` and return the path
+    pub fn save(&self, dir: impl AsRef<Path>) -> Result<PathBuf> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(format!("{}.json", self.id));
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+}
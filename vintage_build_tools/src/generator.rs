@@ -1,21 +1,32 @@
 //! Main game data generator orchestrator
 
 use crate::{
-    ai_analysis::{AIAnalyzer, EnrichedGameMetadata},
-    api::GiantBombClient,
+    ai_analysis::{AIAnalyzer, ANALYSIS_CHECKPOINT_DIR, DEFAULT_CONCURRENCY, EnrichedGameMetadata},
+    api::{
+        DataSourceKind, GameDataSource, GiantBombClient, IgdbClient, IgdbCredentials, MergedSource,
+    },
+    dataset::{DATASET_SOURCE_DIR, DatasetBundler},
+    era_packs::{ERA_PACKS_DIR, EraPack, EraPackSpec},
     graph::GraphBuilder,
     images::ImageDownloader,
+    incremental::BuildManifest,
+    screenshots::ScreenshotAnalyzer,
     templates::TemplateProcessor,
     types::*,
 };
 use anyhow::Result;
+use std::collections::HashMap;
 use std::path::Path;
 
 pub struct GameDataGenerator {
     api_key: String,
     openai_api_key: Option<String>,
+    igdb_credentials: Option<IgdbCredentials>,
+    data_source: DataSourceKind,
     timeline_start: i32,
     timeline_end: i32,
+    /// Analysis batches sent at once
+    concurrency: usize,
 }
 
 impl GameDataGenerator {
@@ -26,8 +37,11 @@ impl GameDataGenerator {
         Self {
             api_key,
             openai_api_key,
+            igdb_credentials: IgdbCredentials::from_env(),
+            data_source: DataSourceKind::default(),
             timeline_start,
             timeline_end,
+            concurrency: DEFAULT_CONCURRENCY,
         }
     }
 
@@ -41,11 +55,58 @@ impl GameDataGenerator {
         Self {
             api_key,
             openai_api_key: Some(openai_api_key),
+            igdb_credentials: IgdbCredentials::from_env(),
+            data_source: DataSourceKind::default(),
             timeline_start,
             timeline_end,
+            concurrency: DEFAULT_CONCURRENCY,
         }
     }
 
+    /// Pick the sources the timeline is built from
+    pub fn with_data_source(mut self, data_source: DataSourceKind) -> Self {
+        self.data_source = data_source;
+        self
+    }
+
+    /// Analyze up to `concurrency` batches of games at once
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Authenticate with IGDB with these credentials instead of the
+    /// environment's
+    pub fn with_igdb_credentials(mut self, credentials: IgdbCredentials) -> Self {
+        self.igdb_credentials = Some(credentials);
+        self
+    }
+
+    /// Client of the configured data source, keeping games of `platforms`
+    fn data_source(&self, platforms: &'static [&'static str]) -> Result<Box<dyn GameDataSource>> {
+        let igdb = || -> Result<Box<dyn GameDataSource>> {
+            let credentials = self.igdb_credentials.as_ref().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "TWITCH_CLIENT_ID and TWITCH_CLIENT_SECRET are required to fetch games from IGDB."
+                )
+            })?;
+            Ok(Box::new(
+                IgdbClient::new(credentials)?.with_platforms(platforms),
+            ))
+        };
+        let giantbomb = || -> Result<Box<dyn GameDataSource>> {
+            Ok(Box::new(
+                GiantBombClient::new(self.api_key.clone())?.with_platforms(platforms),
+            ))
+        };
+
+        Ok(match self.data_source {
+            DataSourceKind::GiantBomb => giantbomb()?,
+            DataSourceKind::Igdb => igdb()?,
+            DataSourceKind::Both => Box::new(MergedSource::new(giantbomb()?, igdb()?)),
+        })
+    }
+
     /// Run the complete generation process
     pub async fn generate(&self) -> Result<()> {
         // Check if we need to generate
@@ -59,37 +120,157 @@ impl GameDataGenerator {
             self.timeline_start, self.timeline_end
         );
 
+        let (platforms, timeline_games) =
+            self.fetch_timeline(self.timeline_start, self.timeline_end, VINTAGE_PLATFORMS)?;
+
+        // 5. AI Analysis (REQUIRED)
+        let enriched_metadata = self.analyze(&timeline_games).await?;
+
+        self.finish(platforms, timeline_games, enriched_metadata)
+    }
+
+    /// Regenerate, re-analyzing only the games whose source data changed
+    /// since the last build
+    ///
+    /// The timeline is always fetched again; a game keeps its enriched
+    /// metadata, embeddings included, when its source data and metadata
+    /// hash the same as in the last build's [`BuildManifest`]. Without a
+    /// manifest every game is analyzed, as in [`generate`](Self::generate).
+    pub async fn generate_incremental(&self) -> Result<()> {
+        println!(
+            "Incrementally building vintage game timeline ({}-{})...",
+            self.timeline_start, self.timeline_end
+        );
+
+        let (platforms, timeline_games) =
+            self.fetch_timeline(self.timeline_start, self.timeline_end, VINTAGE_PLATFORMS)?;
+
+        let (mut enriched_metadata, changed_games) = match BuildManifest::load(DATASET_SOURCE_DIR)?
+        {
+            Some(manifest) => {
+                let previous =
+                    DatasetBundler::load_enriched(DATASET_SOURCE_DIR).unwrap_or_else(|e| {
+                        eprintln!("Warning: Ignoring the last build's metadata: {e:#}");
+                        Vec::new()
+                    });
+                manifest.partition(&timeline_games, previous)?
+            }
+            None => {
+                println!("  No usable build manifest found, analyzing every game");
+                (Vec::new(), timeline_games.clone())
+            }
+        };
+
+        println!(
+            "  {} games unchanged, {} to analyze",
+            enriched_metadata.len(),
+            changed_games.len()
+        );
+        if !changed_games.is_empty() {
+            enriched_metadata.extend(self.analyze(&changed_games).await?);
+        }
+
+        // Keep the timeline's order
+        let order: HashMap<u64, usize> = timeline_games
+            .iter()
+            .enumerate()
+            .filter_map(|(index, game)| Some((game.get("id")?.as_u64()?, index)))
+            .collect();
+        enriched_metadata.sort_by_key(|e| order.get(&(e.id as u64)).copied());
+
+        self.finish(platforms, timeline_games, enriched_metadata)
+    }
+
+    /// Build the era pack of `spec` into [`ERA_PACKS_DIR`]: its period's
+    /// timeline with cover images, without AI analysis
+    pub fn generate_era_pack(&self, spec: &EraPackSpec) -> Result<EraPack> {
+        println!(
+            "Building the {} pack ({}-{})...",
+            spec.name, spec.start_year, spec.end_year
+        );
+
+        let (_, timeline_games) =
+            self.fetch_timeline(spec.start_year, spec.end_year, spec.platforms)?;
+
+        let image_downloader = ImageDownloader::new("assets/wizard/game_covers")?;
+        image_downloader.download_game_covers(&timeline_games)?;
+
+        let pack = EraPack::new(spec, &timeline_games);
+        let path = pack.save(ERA_PACKS_DIR)?;
+        println!("Wrote {} games to {}", pack.games.len(), path.display());
+        Ok(pack)
+    }
+
+    /// Fetch the platforms and the timeline's games of a period, converted
+    /// to JSON for the templates
+    fn fetch_timeline(
+        &self,
+        start_year: i32,
+        end_year: i32,
+        platforms: &'static [&'static str],
+    ) -> Result<(Vec<PlatformInfo>, Vec<serde_json::Value>)> {
         // Create API client
-        let client = GiantBombClient::new(self.api_key.clone())?;
+        let client = self.data_source(platforms)?;
+        println!("  Fetching games from {}", client.name());
 
         // 1. Fetch platform information
         let platforms = client.fetch_platforms()?;
 
         // 2. Fetch games timeline
-        let timeline = client.fetch_timeline_games(self.timeline_start, self.timeline_end)?;
+        let timeline = client.fetch_timeline_games(start_year, end_year)?;
 
         // 3. Enhance games with detailed images
         let enhanced_games = client.enhance_games_with_images(timeline)?;
 
         // 4. Convert to JSON format for templates
-        let mut timeline_games = self.convert_to_json(&enhanced_games)?;
+        let timeline_games = self.convert_to_json(&enhanced_games)?;
 
         println!(
             "Built timeline with {} exemplar games",
             timeline_games.len()
         );
+        Ok((platforms, timeline_games))
+    }
 
-        // 5. AI Analysis (REQUIRED)
+    /// Analyze games with the text model and read their screenshots
+    async fn analyze(&self, games: &[serde_json::Value]) -> Result<Vec<EnrichedGameMetadata>> {
         let openai_key = self.openai_api_key.as_ref()
             .ok_or_else(|| anyhow::anyhow!(
                 "OPENAI_API_KEY not found in environment. AI analysis is REQUIRED for high-quality game metadata. Please set it in your .env or .env.local file."
             ))?;
 
         println!("Running AI analysis on game collection...");
-        let analyzer = AIAnalyzer::new(openai_key.clone())?;
+        let analyzer = AIAnalyzer::new(openai_key.clone())?.with_concurrency(self.concurrency);
 
-        // Analyze games in batches
-        let enriched_metadata = analyzer.analyze_games(&timeline_games, 10).await?;
+        // Analyze games in batches, resuming from the checkpoints of a
+        // failed run
+        let mut enriched_metadata = analyzer
+            .analyze_games_resumable(games, 10, ANALYSIS_CHECKPOINT_DIR)
+            .await?;
+
+        // Read palette, perspective and UI layout off the screenshots
+        ScreenshotAnalyzer::new()?
+            .analyze_games(games, &mut enriched_metadata)
+            .await?;
+
+        Ok(enriched_metadata)
+    }
+
+    /// Download covers, build the graph and generate the modules from the
+    /// analyzed games, then record the build manifest
+    fn finish(
+        &self,
+        platforms: Vec<PlatformInfo>,
+        mut timeline_games: Vec<serde_json::Value>,
+        enriched_metadata: Vec<EnrichedGameMetadata>,
+    ) -> Result<()> {
+        // Fingerprint the games as fetched, before the metadata is merged in
+        let manifest = BuildManifest::new(
+            self.timeline_start,
+            self.timeline_end,
+            &timeline_games,
+            &enriched_metadata,
+        )?;
 
         // Merge enriched metadata back into timeline_games
         self.merge_enriched_metadata(&mut timeline_games, &enriched_metadata)?;
@@ -102,6 +283,10 @@ impl GameDataGenerator {
         let graph_data =
             GraphBuilder::build_enriched_game_graph(&timeline_games, &enriched_metadata)?;
 
+        // Keep the expensive outputs around for dataset bundles and
+        // incremental builds
+        DatasetBundler::save_sources(DATASET_SOURCE_DIR, &enriched_metadata, &graph_data)?;
+
         // 8. Generate Rust modules from templates
         let template_processor =
             TemplateProcessor::new("templates/giantbomb", "src/vintage_games")?;
@@ -117,6 +302,9 @@ impl GameDataGenerator {
         // Validate that all required files were generated
         self.validate_generation()?;
 
+        manifest.save(DATASET_SOURCE_DIR)?;
+        AIAnalyzer::clear_checkpoints(ANALYSIS_CHECKPOINT_DIR)?;
+
         println!("Vintage game timeline successfully generated!");
         Ok(())
     }
@@ -130,6 +318,7 @@ impl GameDataGenerator {
             "src/vintage_games/platforms.rs",
             "src/vintage_games/eras.rs",
             "src/vintage_games/graph.rs",
+            "src/vintage_games/visuals.rs",
         ];
 
         for module in &required_modules {
@@ -169,6 +358,7 @@ impl GameDataGenerator {
             "src/vintage_games/platforms.rs",
             "src/vintage_games/eras.rs",
             "src/vintage_games/graph.rs",
+            "src/vintage_games/visuals.rs",
         ];
 
         for module in &required_modules {
@@ -292,6 +482,7 @@ impl GameDataGenerator {
                 "site_url".to_string(),
                 serde_json::json!(game.site_detail_url.as_ref().unwrap_or(&"".to_string())),
             );
+            game_data.insert("sources".to_string(), serde_json::json!(game.sources));
 
             timeline_games.push(serde_json::Value::Object(game_data));
         }
@@ -329,6 +520,7 @@ impl GameDataGenerator {
                         "platforms",
                         "developer",
                         "deck",
+                        "sources",
                     ];
 
                     for (key, value) in enriched_obj {
@@ -1,6 +1,7 @@
 //! Game similarity graph pre-computation
 
 use crate::ai_analysis::EnrichedGameMetadata;
+use crate::screenshots::{VISUAL_SIMILARITY_WEIGHT, visual_similarity};
 use anyhow::Result;
 use serde_json::Value;
 use std::collections::HashMap;
@@ -23,10 +24,12 @@ impl GraphBuilder {
             enriched_metadata.iter().map(|e| (e.id, e)).collect();
 
         let mut metadata_list = Vec::new();
+        let mut enriched_list = Vec::new();
 
         // Convert JSON games to GameMetadata with enriched data
         for game in timeline_games {
             let game_id = game.get("id").and_then(|v| v.as_u64()).map(|id| id as u32);
+            enriched_list.push(game_id.and_then(|id| enriched_map.get(&id).copied()));
 
             let mut metadata = Self::json_to_metadata(game)?;
 
@@ -64,7 +67,7 @@ impl GraphBuilder {
         }
 
         // Calculate similarities with enhanced data
-        Self::compute_similarity_graph(metadata_list, timeline_games)
+        Self::compute_similarity_graph(metadata_list, &enriched_list, timeline_games)
     }
 
     /// Pre-compute game similarity graph (without AI enrichment)
@@ -477,6 +480,7 @@ impl GraphBuilder {
     /// Common similarity computation logic
     fn compute_similarity_graph(
         metadata_list: Vec<GameMetadata>,
+        enriched_list: &[Option<&EnrichedGameMetadata>],
         timeline_games: &[Value],
     ) -> Result<Value> {
         // Handle empty game list
@@ -505,9 +509,17 @@ impl GraphBuilder {
 
         for i in 0..metadata_list.len() {
             for j in (i + 1)..metadata_list.len() {
-                let similarity = metadata_list[i]
+                let mut similarity = metadata_list[i]
                     .feature_vector
                     .similarity(&metadata_list[j].feature_vector);
+
+                // Games that look alike on screen are closer
+                if let (Some(a), Some(b)) = (enriched_list[i], enriched_list[j])
+                    && let Some(visual) = visual_similarity(a, b)
+                {
+                    similarity = (1.0 - VISUAL_SIMILARITY_WEIGHT) * similarity
+                        + VISUAL_SIMILARITY_WEIGHT * visual;
+                }
                 similarities[i][j] = similarity;
                 similarities[j][i] = similarity;
 
@@ -0,0 +1,293 @@
+//! Build manifest for incremental builds
+//!
+//! Every build records, per game, a SHA-256 of the source data the game was
+//! built from and of the enriched metadata the analysis produced. The next
+//! incremental build fetches the timeline again and only re-analyzes and
+//! re-embeds the games whose source hash changed, that are new, or whose
+//! kept metadata no longer matches its recorded hash; the rest reuse the
+//! enriched metadata of [`DATASET_SOURCE_DIR`](crate::dataset::DATASET_SOURCE_DIR).
+//! A manifest written with another analysis model, system prompt or prompt
+//! template is not used, so changing any of them analyzes every game again.
+
+use crate::ai_analysis::{
+    ANALYSIS_MODEL, ANALYSIS_SYSTEM_PROMPT, BATCH_ANALYSIS_TEMPLATE, EnrichedGameMetadata,
+};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const BUILD_MANIFEST_FILE: &str = "build_manifest.json";
+
+/// Bumped whenever hashing changes, so older manifests rebuild everything
+pub const BUILD_MANIFEST_VERSION: u32 = 2;
+
+/// Hashes of one game's build inputs and outputs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameFingerprint {
+    pub source_hash: String,
+    pub enriched_hash: String,
+}
+
+/// What the last build was made from, written next to the dataset sources
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildManifest {
+    pub version: u32,
+    /// [`analysis_hash`] of the analysis the metadata came from
+    pub analysis_hash: String,
+    pub built_at: chrono::DateTime<chrono::Utc>,
+    pub timeline_start: i32,
+    pub timeline_end: i32,
+    /// Fingerprints by game ID
+    pub games: HashMap<u32, GameFingerprint>,
+}
+
+impl BuildManifest {
+    /// Fingerprint every game of a finished build. `timeline_games` are the
+    /// games as fetched, before the enriched metadata was merged into them.
+    pub fn new(
+        timeline_start: i32,
+        timeline_end: i32,
+        timeline_games: &[Value],
+        enriched: &[EnrichedGameMetadata],
+    ) -> Result<Self> {
+        let source_hashes: HashMap<u32, String> = timeline_games
+            .iter()
+            .filter_map(|game| Some((game_id(game)?, source_hash(game))))
+            .collect();
+
+        let mut games = HashMap::new();
+        for metadata in enriched {
+            if let Some(source_hash) = source_hashes.get(&metadata.id) {
+                games.insert(
+                    metadata.id,
+                    GameFingerprint {
+                        source_hash: source_hash.clone(),
+                        enriched_hash: enriched_hash(metadata)?,
+                    },
+                );
+            }
+        }
+
+        Ok(Self {
+            version: BUILD_MANIFEST_VERSION,
+            analysis_hash: analysis_hash(),
+            built_at: chrono::Utc::now(),
+            timeline_start,
+            timeline_end,
+            games,
+        })
+    }
+
+    /// The manifest of the last build in `dir`, if there is a usable one
+    pub fn load(dir: impl AsRef<Path>) -> Result<Option<Self>> {
+        let path = dir.as_ref().join(BUILD_MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let manifest: Self = serde_json::from_str(
+            &fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?,
+        )
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+        Ok(manifest.is_current().then_some(manifest))
+    }
+
+    /// Whether the manifest was written by this version with the current
+    /// analysis
+    pub fn is_current(&self) -> bool {
+        self.version == BUILD_MANIFEST_VERSION && self.analysis_hash == analysis_hash()
+    }
+
+    pub fn save(&self, dir: impl AsRef<Path>) -> Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        fs::write(
+            dir.join(BUILD_MANIFEST_FILE),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+
+    /// Split the fetched games into the enriched metadata that can be kept
+    /// from `previous` and the games that need analyzing
+    pub fn partition(
+        &self,
+        timeline_games: &[Value],
+        previous: Vec<EnrichedGameMetadata>,
+    ) -> Result<(Vec<EnrichedGameMetadata>, Vec<Value>)> {
+        let mut previous: HashMap<u32, EnrichedGameMetadata> =
+            previous.into_iter().map(|e| (e.id, e)).collect();
+
+        let mut unchanged = Vec::new();
+        let mut changed = Vec::new();
+        for game in timeline_games {
+            let kept = game_id(game)
+                .and_then(|id| Some((self.games.get(&id)?, previous.remove(&id)?)))
+                .filter(|(fingerprint, _)| fingerprint.source_hash == source_hash(game));
+            match kept {
+                Some((fingerprint, metadata))
+                    if fingerprint.enriched_hash == enriched_hash(&metadata)? =>
+                {
+                    unchanged.push(metadata)
+                }
+                _ => changed.push(game.clone()),
+            }
+        }
+
+        Ok((unchanged, changed))
+    }
+}
+
+/// SHA-256 of what the analysis asks with: its model, system prompt and
+/// prompt template
+pub fn analysis_hash() -> String {
+    let mut hasher = Sha256::new();
+    for part in [
+        ANALYSIS_MODEL,
+        ANALYSIS_SYSTEM_PROMPT,
+        BATCH_ANALYSIS_TEMPLATE,
+    ] {
+        // Length-prefixed, so moving text between parts changes the hash
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// SHA-256 of a game's source data as fetched from the API
+pub fn source_hash(game: &Value) -> String {
+    // Games are built field by field in a fixed order, so equal data
+    // serializes, and hashes, equally
+    format!("{:x}", Sha256::digest(game.to_string()))
+}
+
+/// SHA-256 of a game's enriched metadata, embeddings included
+pub fn enriched_hash(metadata: &EnrichedGameMetadata) -> Result<String> {
+    Ok(format!(
+        "{:x}",
+        Sha256::digest(serde_json::to_vec(metadata)?)
+    ))
+}
+
+fn game_id(game: &Value) -> Option<u32> {
+    game.get("id").and_then(|v| v.as_u64()).map(|id| id as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn games() -> Vec<Value> {
+        vec![
+            json!({ "id": 1, "name": "Pitfall!", "year": 1982 }),
+            json!({ "id": 2, "name": "Metroid", "year": 1986 }),
+        ]
+    }
+
+    fn enriched(id: u32) -> EnrichedGameMetadata {
+        EnrichedGameMetadata {
+            id,
+            themes: vec!["Exploration".to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn manifest() -> BuildManifest {
+        BuildManifest::new(1980, 1990, &games(), &[enriched(1), enriched(2)]).unwrap()
+    }
+
+    fn ids(metadata: &[EnrichedGameMetadata]) -> Vec<u32> {
+        metadata.iter().map(|m| m.id).collect()
+    }
+
+    #[test]
+    fn test_partition_keeps_unchanged_games() {
+        let (unchanged, changed) = manifest()
+            .partition(&games(), vec![enriched(1), enriched(2)])
+            .unwrap();
+        assert_eq!(ids(&unchanged), vec![1, 2]);
+        assert!(changed.is_empty());
+    }
+
+    #[test]
+    fn test_partition_reanalyzes_changed_source() {
+        let mut timeline = games();
+        timeline[1]["year"] = json!(1987);
+        let (unchanged, changed) = manifest()
+            .partition(&timeline, vec![enriched(1), enriched(2)])
+            .unwrap();
+        assert_eq!(ids(&unchanged), vec![1]);
+        assert_eq!(changed, vec![timeline[1].clone()]);
+    }
+
+    #[test]
+    fn test_partition_reanalyzes_tampered_metadata() {
+        let mut tampered = enriched(2);
+        tampered.themes.push("Edited by hand".to_string());
+        let (unchanged, changed) = manifest()
+            .partition(&games(), vec![enriched(1), tampered])
+            .unwrap();
+        assert_eq!(ids(&unchanged), vec![1]);
+        assert_eq!(changed, vec![games()[1].clone()]);
+    }
+
+    #[test]
+    fn test_partition_analyzes_new_games() {
+        let mut timeline = games();
+        timeline.push(json!({ "id": 3, "name": "Zelda", "year": 1986 }));
+        let (unchanged, changed) = manifest()
+            .partition(&timeline, vec![enriched(1), enriched(2)])
+            .unwrap();
+        assert_eq!(ids(&unchanged), vec![1, 2]);
+        assert_eq!(changed, vec![timeline[2].clone()]);
+    }
+
+    #[test]
+    fn test_partition_reanalyzes_games_without_kept_metadata() {
+        let (unchanged, changed) = manifest().partition(&games(), vec![enriched(1)]).unwrap();
+        assert_eq!(ids(&unchanged), vec![1]);
+        assert_eq!(changed, vec![games()[1].clone()]);
+    }
+
+    #[test]
+    fn test_hashes_follow_the_content() {
+        let game = &games()[0];
+        assert_eq!(source_hash(game), source_hash(&game.clone()));
+        assert_ne!(source_hash(game), source_hash(&games()[1]));
+        assert_eq!(source_hash(game).len(), 64);
+
+        assert_eq!(
+            enriched_hash(&enriched(1)).unwrap(),
+            enriched_hash(&enriched(1)).unwrap()
+        );
+        assert_ne!(
+            enriched_hash(&enriched(1)).unwrap(),
+            enriched_hash(&enriched(2)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_manifest_of_another_analysis_is_not_loaded() {
+        let dir = std::env::temp_dir().join(format!("build_manifest_{}", std::process::id()));
+        let mut manifest = manifest();
+        manifest.save(&dir).unwrap();
+        assert!(BuildManifest::load(&dir).unwrap().is_some());
+
+        manifest.analysis_hash = "an older prompt".to_string();
+        manifest.save(&dir).unwrap();
+        assert!(BuildManifest::load(&dir).unwrap().is_none());
+
+        let mut manifest = self::manifest();
+        manifest.version = BUILD_MANIFEST_VERSION - 1;
+        manifest.save(&dir).unwrap();
+        assert!(BuildManifest::load(&dir).unwrap().is_none());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,20 +1,30 @@
 //! Build tools for vintage game data generation
 
 use anyhow::Result;
+use api::IgdbCredentials;
 use dotenv::dotenv;
 use std::env;
 use std::path::PathBuf;
 
 pub mod ai_analysis;
 pub mod api;
+pub mod dataset;
+pub mod era_packs;
 pub mod generator;
 pub mod graph;
 pub mod images;
+pub mod incremental;
+pub mod screenshots;
 pub mod templates;
 pub mod types;
 
 pub use ai_analysis::{AIAnalyzer, EnrichedGameMetadata, GameMechanic};
+pub use api::{DataSourceKind, GameDataSource};
+pub use dataset::{DatasetBundler, DatasetManifest};
+pub use era_packs::{ERA_PACKS, EraPack, EraPackSpec};
 pub use generator::GameDataGenerator;
+pub use incremental::BuildManifest;
+pub use screenshots::ScreenshotAnalyzer;
 
 /// Build tools configuration
 pub struct VintageBuildTools {
@@ -30,6 +40,10 @@ impl VintageBuildTools {
     }
 
     /// Create from environment (loads .env file from repository root)
+    ///
+    /// `VINTAGE_DATA_SOURCE` picks the source: `giantbomb`, the default,
+    /// needs `GIANTBOMB_API_KEY`, `igdb` needs `TWITCH_CLIENT_ID` and
+    /// `TWITCH_CLIENT_SECRET`, and `both` needs all three.
     pub fn from_env(timeline_start: i32, timeline_end: i32) -> Result<Self> {
         // Find repository root by looking for .git directory or workspace Cargo.toml
         let repo_root = find_repository_root()?;
@@ -47,19 +61,59 @@ impl VintageBuildTools {
             dotenv().ok();
         }
 
+        // Pick the data source, GiantBomb unless VINTAGE_DATA_SOURCE says otherwise
+        let data_source = match env::var("VINTAGE_DATA_SOURCE") {
+            Ok(name) => DataSourceKind::from_name(&name).ok_or_else(|| {
+                anyhow::anyhow!("VINTAGE_DATA_SOURCE must be giantbomb, igdb or both, not {name}")
+            })?,
+            Err(_) => DataSourceKind::default(),
+        };
+
         // Get API key from environment
-        let api_key = env::var("GIANTBOMB_API_KEY")
-            .map_err(|_| anyhow::anyhow!(
+        let api_key = match env::var("GIANTBOMB_API_KEY") {
+            Ok(api_key) => api_key,
+            Err(_) if !data_source.uses_giantbomb() => String::new(),
+            Err(_) => anyhow::bail!(
                 "GIANTBOMB_API_KEY not found in environment. Please set it in your .env or .env.local file."
-            ))?;
+            ),
+        };
+        if data_source.uses_igdb() && IgdbCredentials::from_env().is_none() {
+            anyhow::bail!(
+                "TWITCH_CLIENT_ID and TWITCH_CLIENT_SECRET not found in environment. IGDB requires a Twitch application's credentials."
+            );
+        }
+
+        Ok(Self {
+            generator: GameDataGenerator::new(api_key, timeline_start, timeline_end)
+                .with_data_source(data_source),
+        })
+    }
 
-        Ok(Self::new(api_key, timeline_start, timeline_end))
+    /// Analyze up to `concurrency` batches of games at once
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.generator = self.generator.with_concurrency(concurrency);
+        self
     }
 
     /// Run the build process
     pub async fn build(&self) -> Result<()> {
         self.generator.generate().await
     }
+
+    /// Run the build process, re-analyzing only the games that changed
+    /// since the last build
+    pub async fn build_incremental(&self) -> Result<()> {
+        self.generator.generate_incremental().await
+    }
+
+    /// Build the era pack with this ID, one of the [`ERA_PACKS`]
+    pub fn build_era_pack(&self, id: &str) -> Result<EraPack> {
+        let spec = era_packs::era_pack_spec(id).ok_or_else(|| {
+            let known: Vec<&str> = ERA_PACKS.iter().map(|spec| spec.id).collect();
+            anyhow::anyhow!("Unknown era pack {id}; known packs: {}", known.join(", "))
+        })?;
+        self.generator.generate_era_pack(spec)
+    }
 }
 
 /// Find the repository root by looking for .git directory or workspace Cargo.toml
@@ -0,0 +1,271 @@
+//! Vision analysis of the timeline's screenshots
+//!
+//! The guided mode only knows how a game looks from its GiantBomb
+//! screenshot. Each screenshot is read by a vision model at build time, and
+//! the palette, perspective and interface layout it describes are stored in
+//! the game's [`EnrichedGameMetadata`], where they weigh into the similarity
+//! graph and describe the reference games of generated style guides.
+
+use crate::ai_analysis::EnrichedGameMetadata;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use vintage_ai_client::{
+    AiService,
+    image::{ImageGenerator, ScreenshotAnalysis},
+};
+
+/// Share of the visual similarity in the similarity of two games that both
+/// have visual descriptors
+pub const VISUAL_SIMILARITY_WEIGHT: f32 = 0.25;
+
+/// Screenshot fields of a timeline game, largest first
+const SCREENSHOT_FIELDS: &[&str] = &[
+    "image_screen_large_url",
+    "image_screen_url",
+    "image_original_url",
+];
+
+/// Screen edges a UI layout entry can mention, as whole words
+const SCREEN_REGIONS: &[&str] = &["top", "bottom", "left", "right", "center", "centre"];
+
+pub struct ScreenshotAnalyzer {
+    client: reqwest::Client,
+    image_generator: ImageGenerator,
+}
+
+impl ScreenshotAnalyzer {
+    /// Create an analyzer using the OpenAI key of the environment
+    pub fn new() -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        Ok(Self {
+            client,
+            image_generator: AiService::from_env()?.image(),
+        })
+    }
+
+    /// Read the visual descriptors off each game's screenshot into its
+    /// enriched metadata. Games without a screenshot, or whose screenshot
+    /// can't be fetched or read, keep empty descriptors.
+    pub async fn analyze_games(
+        &self,
+        timeline_games: &[Value],
+        enriched: &mut [EnrichedGameMetadata],
+    ) -> Result<()> {
+        println!("Analyzing game screenshots...");
+
+        let screenshots: HashMap<u32, &str> = timeline_games
+            .iter()
+            .filter_map(|game| {
+                let id = game.get("id").and_then(|v| v.as_u64())? as u32;
+                let url = SCREENSHOT_FIELDS
+                    .iter()
+                    .find_map(|field| game.get(*field).and_then(|v| v.as_str()))
+                    .filter(|url| !url.is_empty())?;
+                Some((id, url))
+            })
+            .collect();
+
+        let (mut attempted, mut analyzed) = (0, 0);
+        for metadata in enriched.iter_mut() {
+            let Some(url) = screenshots.get(&metadata.id) else {
+                continue;
+            };
+            attempted += 1;
+            match self.analyze_screenshot(url).await {
+                Ok(analysis) => {
+                    metadata.palette = analysis.palette;
+                    metadata.perspective = analysis.perspective;
+                    metadata.ui_layout = analysis.ui_layout;
+                    analyzed += 1;
+                }
+                Err(e) => eprintln!(
+                    "Warning: Failed to analyze the screenshot of {}: {e:#}",
+                    metadata.name
+                ),
+            }
+        }
+
+        println!("  Analyzed {analyzed}/{attempted} screenshots");
+        Ok(())
+    }
+
+    async fn analyze_screenshot(&self, url: &str) -> Result<ScreenshotAnalysis> {
+        let screenshot = self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await
+            .with_context(|| format!("Failed to download {url}"))?;
+        self.image_generator.analyze_screenshot(&screenshot).await
+    }
+}
+
+/// Whether a vision model described the game's screenshot
+pub fn has_visual_descriptors(metadata: &EnrichedGameMetadata) -> bool {
+    !metadata.palette.is_empty() || !metadata.perspective.is_empty()
+}
+
+/// How alike two games look on screen, from 0.0 to 1.0, or `None` unless
+/// both have visual descriptors. Palette closeness counts for half,
+/// a shared perspective for 30% and interface elements on the same screen
+/// edges for the rest.
+pub fn visual_similarity(a: &EnrichedGameMetadata, b: &EnrichedGameMetadata) -> Option<f32> {
+    if !has_visual_descriptors(a) || !has_visual_descriptors(b) {
+        return None;
+    }
+
+    let palette = palette_similarity(&a.palette, &b.palette);
+    let perspective = if a.perspective.eq_ignore_ascii_case(&b.perspective) {
+        1.0
+    } else {
+        0.0
+    };
+    let (regions_a, regions_b) = (screen_regions(&a.ui_layout), screen_regions(&b.ui_layout));
+    let layout = if regions_a.is_empty() && regions_b.is_empty() {
+        1.0
+    } else {
+        regions_a.intersection(&regions_b).count() as f32
+            / regions_a.union(&regions_b).count() as f32
+    };
+
+    Some(0.5 * palette + 0.3 * perspective + 0.2 * layout)
+}
+
+/// One minus the mean distance from each color to the closest color of
+/// the other palette, both ways, relative to the black-white distance
+fn palette_similarity(a: &[String], b: &[String]) -> f32 {
+    let (a, b) = (rgb_colors(a), rgb_colors(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let max_distance = (3.0f32 * 255.0 * 255.0).sqrt();
+    let mean_closest = |from: &[[f32; 3]], to: &[[f32; 3]]| {
+        from.iter()
+            .map(|color| {
+                to.iter()
+                    .map(|other| {
+                        color
+                            .iter()
+                            .zip(other)
+                            .map(|(x, y)| (x - y).powi(2))
+                            .sum::<f32>()
+                            .sqrt()
+                    })
+                    .fold(f32::MAX, f32::min)
+            })
+            .sum::<f32>()
+            / from.len() as f32
+    };
+    1.0 - (mean_closest(&a, &b) + mean_closest(&b, &a)) / (2.0 * max_distance)
+}
+
+fn rgb_colors(palette: &[String]) -> Vec<[f32; 3]> {
+    palette
+        .iter()
+        .filter_map(|hex| {
+            let hex = hex.trim_start_matches('#');
+            let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+            Some([channel(0)? as f32, channel(2)? as f32, channel(4)? as f32])
+        })
+        .collect()
+}
+
+fn screen_regions(ui_layout: &[String]) -> HashSet<&'static str> {
+    ui_layout
+        .iter()
+        .flat_map(|entry| entry.split(|c: char| !c.is_alphanumeric()))
+        .filter_map(|word| {
+            SCREEN_REGIONS
+                .iter()
+                .find(|region| word.eq_ignore_ascii_case(region))
+        })
+        .map(|&region| if region == "centre" { "center" } else { region })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn described(palette: &[&str], perspective: &str, ui_layout: &[&str]) -> EnrichedGameMetadata {
+        EnrichedGameMetadata {
+            palette: palette.iter().map(|c| c.to_string()).collect(),
+            perspective: perspective.to_string(),
+            ui_layout: ui_layout.iter().map(|e| e.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn strings(entries: &[&str]) -> Vec<String> {
+        entries.iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn test_screen_regions_match_whole_words() {
+        let regions = screen_regions(&strings(&[
+            "Score along the Top",
+            "top-left lives counter",
+            "minimap in the centre",
+        ]));
+        assert_eq!(regions, HashSet::from(["top", "left", "center"]));
+
+        // Words that merely contain a region name
+        assert!(
+            screen_regions(&strings(&["stop watch", "desktop icons", "bright HUD"])).is_empty()
+        );
+        assert!(screen_regions(&strings(&["leftover ammo", "copyright notice"])).is_empty());
+    }
+
+    #[test]
+    fn test_palette_similarity() {
+        let warm = strings(&["#ff0000", "#ffff00"]);
+        assert_eq!(palette_similarity(&warm, &warm), 1.0);
+        assert_eq!(
+            palette_similarity(&strings(&["#000000"]), &strings(&["#ffffff"])),
+            0.0
+        );
+
+        // Closer palettes score higher, in either order
+        let orange = strings(&["#ff8000"]);
+        let blue = strings(&["#0000ff"]);
+        assert!(palette_similarity(&warm, &orange) > palette_similarity(&warm, &blue));
+        assert_eq!(
+            palette_similarity(&warm, &orange),
+            palette_similarity(&orange, &warm)
+        );
+
+        // Entries that aren't hex colors are skipped
+        assert_eq!(
+            palette_similarity(&strings(&["ff0000", "crimson"]), &strings(&["#FF0000"])),
+            1.0
+        );
+        assert_eq!(palette_similarity(&strings(&["crimson"]), &warm), 0.0);
+    }
+
+    #[test]
+    fn test_visual_similarity() {
+        let side_scroller = described(&["#ff0000"], "side-scrolling", &["score at the top"]);
+        assert_eq!(visual_similarity(&side_scroller, &side_scroller), Some(1.0));
+        assert_eq!(
+            visual_similarity(&side_scroller, &EnrichedGameMetadata::default()),
+            None
+        );
+
+        // Same palette, other perspective and interface edge
+        let top_down = described(&["#ff0000"], "Top-down", &["status bar at the bottom"]);
+        assert_eq!(visual_similarity(&side_scroller, &top_down), Some(0.5));
+
+        // Perspectives compare case-insensitively; no layout on either side
+        // counts as the same layout
+        let a = described(&["#000000"], "isometric", &[]);
+        let b = described(&["#ffffff"], "Isometric", &[]);
+        assert_eq!(visual_similarity(&a, &b), Some(0.5));
+    }
+}
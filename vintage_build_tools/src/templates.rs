@@ -39,6 +39,7 @@ impl TemplateProcessor {
             ("platforms.rs.jinja", "platforms.rs"),
             ("eras.rs.jinja", "eras.rs"),
             ("graph.rs.jinja", "graph.rs"),
+            ("visuals.rs.jinja", "visuals.rs"),
         ];
 
         // Create template context
@@ -3,6 +3,9 @@
 use serde::{Deserialize, Serialize};
 
 pub const GIANTBOMB_API_BASE: &str = "https://www.giantbomb.com/api";
+pub const IGDB_API_BASE: &str = "https://api.igdb.com/v4";
+pub const IGDB_IMAGE_BASE: &str = "https://images.igdb.com/igdb/image/upload";
+pub const TWITCH_TOKEN_URL: &str = "https://id.twitch.tv/oauth2/token";
 pub const USER_AGENT: &str = "VintageGameGenerator/1.0";
 pub const RESULTS_PER_PAGE: u32 = 100;
 pub const TOP_GENRES_PER_YEAR: usize = 3;
@@ -55,6 +58,9 @@ pub struct Game {
     pub developers: Option<Vec<Developer>>,
     #[serde(default)]
     pub site_detail_url: Option<String>,
+    /// Data sources the game's data came from, e.g. `["giantbomb", "igdb"]`
+    #[serde(default)]
+    pub sources: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
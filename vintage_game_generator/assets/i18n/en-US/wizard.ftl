@@ -0,0 +1,675 @@
+# Wizard UI strings, English baseline. Every id used by the wizard must be
+# defined here; other languages fall back to these.
+
+## Wizard frame
+
+app-title = 🎮 Vintage Game Generator
+step-welcome = Welcome
+step-select-language = Select Language
+step-guided = Browse & Blend Vintage Games
+step-freeform = AI-Assisted Game Creation
+step-review = Review & Generate
+step-complete = Generation Complete
+nav-back = ← Back
+nav-exit = Exit
+nav-settings = ⚙ Settings
+button-cancel = Cancel
+exit-title = Exit Confirmation
+exit-question = Are you sure you want to exit?
+exit-unsaved = Any unsaved progress will be lost.
+loading-guided = Loading guided mode...
+loading-freeform = Loading freeform mode...
+guided-title = 🎮 Guided Mode - Blend Vintage Games
+complete-title = 🎉 Export Complete!
+complete-exported = Successfully exported game configuration
+complete-blend = Blend: { $name }
+complete-new-blend = Start New Blend
+complete-done = Export completed successfully!
+complete-return = Return to Welcome
+
+## Welcome step
+
+welcome-subtitle = Choose your creation mode
+welcome-click-side = Click on either side to choose your path
+welcome-choose = Choose your creation mode:
+welcome-guided = GUIDED MODE
+welcome-guided-tagline = Browse & Blend Classic Games
+welcome-guided-subtitle = Browse Gaming History
+welcome-guided-body = Select from classic games
+    spanning three decades:
+welcome-guided-70s = • 1970s - Dawn of Gaming
+welcome-guided-80s = • 1980s - Arcade Golden Age
+welcome-guided-90s = • 1990s - Console Revolution
+welcome-guided-choose = Choose Guided
+welcome-freeform = FREEFORM MODE
+welcome-freeform-tagline = AI-Powered Game Creation
+welcome-freeform-subtitle = Create Your Vision
+welcome-freeform-body = Design from scratch with
+    AI-powered assistance:
+welcome-freeform-describe = • Describe your game idea
+welcome-freeform-suggestions = • Get intelligent suggestions
+welcome-freeform-control = • Full creative control
+welcome-freeform-choose = Choose Freeform
+welcome-or = OR
+welcome-same-engine = Both modes lead to the same powerful game generation engine
+welcome-tutorial = 🎓 New here? Take the tutorial
+welcome-tutorial-hint = Walk through a sample project with a mock provider - no API costs
+
+## Settings
+
+settings-title = ⚙ Settings
+settings-ui-language = Interface language
+settings-ui-language-hint = Language of the wizard itself. Generated content has its own language setting.
+settings-content-language = Generated content language
+settings-content-language-default = English (default)
+settings-content-language-hint = Language the game's story, dialogue and text are written in. Saved with the open project.
+settings-content-language-no-project = Open a project to choose the language of its generated content.
+settings-era = Era of the hardware limits
+settings-era-auto = { $era } (from the reference games)
+settings-authenticity = Authenticity
+settings-authenticity-limits = { $strictness }: { $colors } colors, { $sprites } sprites, { $channels } sound channels, { $chars } characters per text box, { $beats } story beats
+settings-authenticity-hint = From loose, nostalgia-flavored output to strict, hardware-accurate output. Applies to every design step. Saved with the open project.
+
+## List mode
+
+list-title = Browse Projects
+list-found = Found { $count ->
+        [one] one project
+       *[other] { $count } projects
+    } in: { $dir }
+list-previous = ◀ Previous
+list-next = Next ▶
+list-edit = Edit
+list-no-project = No project selected
+list-error = Error: { $error }
+list-load-failed = Failed to load projects: { $error }
+list-templates-failed = Failed to load templates: { $error }
+list-tab-details = Project Details
+list-tab-files = Files
+list-tab-preview = Preview
+list-search-hint = Name, genre or source game
+list-any-genre = Any genre
+list-created = Created
+list-from = from
+list-to = to
+list-date-format = Use YYYY-MM-DD
+list-clear = ✖ Clear
+list-matching = { $shown } of { $total }
+list-new-from-template = New from template:
+list-no-templates = No templates
+list-template-saved = { $name } (saved)
+list-project-name = Project name
+list-create = ✨ Create
+list-template-name = Template name
+list-save-template = 💾 Save as template
+list-duplicate = 📄 Duplicate
+list-without-generated = without generated files
+list-without-generated-hint = Leave out assets, checkpoints and exports
+list-archive = 🗜 Archive
+list-delete = 🗑 Delete
+list-restore-archive = Restore archive:
+list-restore-hint = Path to a project .zip
+list-restore = 📂 Restore
+list-duplicated = Duplicated { $name }
+list-archived = Archived { $name } to { $path }
+list-restored = Restored { $path }
+list-deleted = Deleted { $name }
+list-uuid = UUID: { $uuid }
+list-path = Path: { $path }
+list-genre = Genre: { $genre }
+list-source-games = Source Games: { $games }
+list-created-at = Created: { $date }
+list-last-generated = Last Generated: { $date }
+list-gameplay = Gameplay
+list-core-mechanics = Core Mechanics: { $value }
+list-progression = Progression Type: { $value }
+list-gameplay-loop = Gameplay Loop: { $value }
+list-motivation = Player Motivation: { $value }
+list-visual-style = Visual Style
+list-reference-games = Reference Games: { $value }
+list-color-mood = Color Mood: { $value }
+list-sprite-size = Sprite Size: { $value } px
+list-use-outline = Use Outline: { $value }
+list-technical = Technical
+list-world-size = World Size: { $value }
+list-target-platforms = Target Platforms: { $value }
+list-files-title = Project Files
+list-files-unreadable = Could not read project directory
+list-preview-title = Preview
+list-preview-intro = This project would generate:
+list-preview-genre = • A { $genre } game
+list-preview-inspired = • Inspired by { $games }
+list-preview-gameplay = • { $value } gameplay
+list-preview-mood = • { $value } mood visuals
+
+## Delete confirmation
+
+delete-title = Delete Project
+delete-question = Delete { $name }?
+delete-warning = { $path } and everything generated for it will be removed.
+delete-archive-first = Archive the project first to keep a copy.
+delete-confirm = Delete
+
+## Language step
+
+language-title = Select Target Language
+language-subtitle = Choose the programming language for your game
+language-best-for = 🎯 Best for:
+language-strengths = 💪 Strengths:
+language-python-beginners = • Beginners
+language-python-prototypes = • Quick prototypes
+language-python-education = • Educational games
+language-python-easy = • Easy to learn
+language-python-rapid = • Rapid development
+language-python-pygame = • PyGame library
+language-rust-performance = • Performance games
+language-rust-browser = • Browser games
+language-rust-systems = • Complex systems
+language-rust-fast = • Blazing fast
+language-rust-safe = • Memory safe
+language-rust-wasm = • WASM support
+language-coming-soon = 🚧 Coming Soon! 🚧
+language-ruby-in-progress = Ruby support is
+    under development
+language-image-failed = Failed to load language selection image
+language-ruby-coming-soon = Ruby 💎 (Coming Soon)
+language-footer-games = All languages will generate complete, playable retro-style games
+language-footer-libraries = with appropriate libraries and frameworks for each language
+
+## Main window
+
+main-wizard-completed = Wizard completed! Starting AI conversation...
+main-title = AI RPG Generator
+main-phase = Phase: { $phase }
+main-api-key-set = ✓ API Key Set
+main-api-key-missing = ✗ API Key Missing
+main-api-key-hint = Set OPENAI_API_KEY environment variable
+main-pause = ⏸ Pause
+main-start = ▶ Start
+main-browse-projects = 📂 Browse Projects
+main-tab-conversation = 💬 Conversation
+main-tab-prompts = 📝 Prompts
+main-tab-assets = 🎨 Assets
+main-tab-build = 🔨 Build
+main-tab-logs = 📋 Logs
+main-conversation-title = Metaprompt Conversation
+main-game = Game: { $name }
+main-genre = Genre: { $genre }
+main-style = Style: { $style }
+main-conversation-placeholder = Conversation history will appear here...
+main-user-input = User input: { $input }
+main-prompts-title = Prompt Management
+main-validation-queue = Validation Queue:
+main-errors = Errors
+main-browse-prompts = Browse Prompts
+main-opening-prompt = Opening prompt: { $file }
+main-assets-title = Generated Assets
+main-assets-placeholder = Generated assets will appear here...
+main-build-title = Build Output
+main-open-build-dir = 📁 Open Build Directory
+main-open-build-dir-failed = Failed to open build directory: { $error }
+main-run-game = 🎮 Run Game
+main-launching-game = Launching generated game...
+main-build-contents = Build directory contents:
+main-logs-title = Logs
+main-clear-logs = Clear
+
+## Gaming timeline
+
+timeline-title = 🎮 Gaming Timeline
+timeline-pick-decade = Select a decade to explore gaming history
+timeline-decade-hint = Choose from the golden age of arcade games (1980s)
+    or the 16-bit renaissance (1990s)
+timeline-search-hint = Search games, typos welcome
+timeline-any-genre = Any genre
+timeline-any-platform = Any platform
+timeline-years = Years
+timeline-clear-search = ✖ Clear
+timeline-filters = Filters
+timeline-genre = Genre:
+timeline-platform = Platform:
+timeline-developer = Developer:
+timeline-clear-filters = ✖ Clear filters
+timeline-no-matches = No games match the search
+timeline-best-matches = Showing the best { $shown } of { $count } matches
+timeline-matches = { $count ->
+        [one] 1 match
+       *[other] { $count } matches
+    }
+timeline-games = { $count ->
+        [one] 1 game
+       *[other] { $count } games
+    }
+timeline-platforms = { $count ->
+        [one] 1 platform
+       *[other] { $count } platforms
+    }
+timeline-year = Year:
+timeline-platforms-label = Platforms:
+
+## Game cards
+
+card-platforms = Platforms:
+card-selected-title = 📚 Selected Games
+card-none-selected = No games selected yet
+card-selection-hint = Click on games from the timeline to add them
+    Select at least 2 games to create a blend
+card-selected-count = { $count ->
+        [one] 1 game selected
+       *[other] { $count } games selected
+    }
+card-reblend = 🔄 Re-blend
+card-genre = Genre:
+card-era = Era:
+card-era-arcade = Arcade Golden Age
+card-era-early-console = Early Console
+card-era-transition = 8-bit/16-bit Transition
+card-era-16-bit-peak = 16-bit Peak
+card-era-unknown = Unknown Era
+card-complexity = Complexity:
+
+## Guided mode
+
+guided-browse-hint = Browse games by decade and select ones to blend:
+guided-era-stats = 📊 Era Statistics
+guided-selected-count = { $count ->
+        [one] Selected 1 game
+       *[other] Selected { $count } games
+    }
+guided-blend = 🔀 Blend Games
+guided-blend-needs-two = (Select at least 2 games to blend)
+guided-back-to-selection = ⬅ Back to Selection
+guided-continue = Continue ➡
+guided-continue-unresolved = Pick a resolution for every conflict
+guided-skip-resolution = Skip for now
+guided-resolve-conflicts = ⚖ Resolve Conflicts
+guided-export = ✅ Export Configuration
+guided-invalid-step = Invalid step
+guided-selected-games = Selected Games
+guided-weight-hint = How much of the blend this game makes up
+guided-blend-share = → { $percent }% of blend
+surprise-button = 🎲 Surprise Me
+surprise-hint = Blend 2-3 games from different eras and genres
+surprise-seed = Seed
+surprise-seed-hint = Keep the seed to get the same surprise again
+similar-title = 🔗 Similar to { $game }
+similar-score = { $genre }, { $percent }% similar
+clusters-title = 🧭 Game clusters
+clusters-none = The games are too few to form clusters
+clusters-cluster = Cluster { $number }: mostly { $genre }, { $count } games
+clusters-more = { $count } more
+clusters-blends = Blends across clusters
+clusters-blend-hint = { $percent }% compatible; games of different clusters rarely meet
+stats-title = 📊 Era Statistics
+stats-genres = Games by genre per year
+stats-platforms = Platform lifecycles
+stats-platforms-hint = Line thickness reflects how many timeline games the platform hosts
+stats-mechanics = Mechanic prevalence over time
+
+## Ratings and suggestions
+
+rating-blend = Rate this blend:
+rating-concept = Rate the concept:
+rating-more = More like this
+rating-less = Less like this
+rating-recorded = { $count ->
+        [one] 1 rating recorded
+       *[other] { $count } ratings recorded
+    }
+suggestions-title = ⭐ Suggested for you
+suggestions-match = { $percent }% match
+preferences-title = ⚙ Preference Settings
+preferences-ratings = { $count ->
+        [one] 1 rating
+       *[other] { $count } ratings
+    } ({ $liked } 👍 / { $disliked } 👎)
+preferences-untrained = Rate at least one blend up and one down to personalize suggestions
+preferences-export = 📤 Export Ratings
+preferences-exported = Exported to { $path }
+preferences-export-failed = Export failed: { $error }
+preferences-reset = 🗑 Reset Ratings
+preferences-cleared = Ratings cleared
+
+## Blend
+
+blend-genres = Genre Distribution
+blend-mechanics = Combined Mechanics
+blend-balance = Game Balance
+blend-synergies = ✨ Synergies
+blend-conflicts = ⚠️ Conflicts to Resolve
+blend-conflict-games = { $first } vs { $second }
+blend-features = 💡 Recommended Features
+blend-visual-style = 🎨 Visual Style
+blend-export = 📥 Export Configuration
+blend-modify = 🔄 Modify Selection
+blend-create = 🧪 Create Blend
+blend-create-hint = Click to analyze game compatibility and generate blend
+blend-complexity = Complexity:
+blend-simple = Simple
+blend-complex = Complex
+blend-gameplay = Gameplay:
+blend-strategic = Strategic
+blend-action = Action
+export-title = 📤 Export Options
+export-no-blend = No blend result to export. Create a blend first!
+export-copy-toml = 📄 Copy TOML
+export-copy-json = 📋 Copy JSON
+export-copy-prompt = 🤖 Copy AI Prompt
+export-preview-toml = Preview TOML Export
+
+## Conflict resolution
+
+resolution-title = ⚖ Resolve Conflicts
+resolution-intro = The blended games pull in different directions. Pick how each conflict is settled:
+resolution-asking = Asking the AI for more ideas…
+resolution-unavailable = AI suggestions unavailable ({ $error }); showing the blend analysis' ideas
+resolution-from-ai = Suggested by the AI
+
+## Blend explanation
+
+explain-button = 💬 Explain this blend
+explain-button-hint = Ask the AI why the blend scored the way it did
+explain-title = 💬 Explain this blend
+explain-you = You
+explain-ai = AI
+explain-tell-changes = 🔄 Tell it about my changes
+explain-tell-changes-hint = The blend changed since the conversation last heard of it
+explain-input-hint = Ask about the blend…
+explain-send = Send
+
+## Freeform mode
+
+freeform-step-introduction = Introduction
+freeform-step-basic-info = Basic Info
+freeform-step-gameplay = Gameplay Design
+freeform-step-visual-style = Visual Style
+freeform-step-features = Features
+freeform-step-technical = Technical Settings
+freeform-step-review = Review
+freeform-under-construction = { $step } - Under Construction
+freeform-being-rebuilt = This step is currently being rebuilt.
+freeform-title = 🤖 AI Game Design Conversation
+freeform-subtitle = Let's bring your game to life!
+freeform-context = Current Context:
+freeform-fork = ⑂ Fork here
+freeform-fork-hint = Explore a different design direction from this point
+freeform-thinking = AI is thinking...
+freeform-error = Error: { $error }
+freeform-send = Send
+freeform-export = Export
+freeform-back-to-review = ← Back to Review
+freeform-generate = Generate Game →
+freeform-topic = Topic: { $topic }
+freeform-decisions = Decisions made:
+freeform-branches = Branches:
+freeform-switch-branch = Switch to branch
+freeform-hide-comparison = Hide comparison
+freeform-compare = Compare
+freeform-merge = Merge into current
+freeform-shared-messages = { $count ->
+        [one] 1 shared message before the branches diverge
+       *[other] { $count } shared messages before the branches diverge
+    }
+freeform-not-started = Conversation has not started yet
+freeform-no-generator = AI Generator not initialized
+freeform-fork-summarized = This message was summarized away and can't be forked at
+freeform-main-branch = Main
+freeform-branch = Branch { $number }
+freeform-unknown-branch = Unknown branch
+
+## Generation run
+
+run-title = Generate
+run-idle = Generation hasn't started yet.
+run-cancelled = Generation was cancelled. Completed steps were kept.
+run-needs-rewrite = ⚠ Needs manual rewrite: the model refused a prompt ({ $trigger })
+run-refused-prompt = Refused prompt
+run-rewrite-refused = Automatic rewrite, refused as well:
+run-reword-hint = Reword the project description, then resume; completed steps are kept.
+run-complete = Design document written to { $path }
+run-regenerated = Regenerated { $step }
+run-resume = ▶ Resume
+run-resumed-log = Generation resumed
+run-pause = ⏸ Pause
+run-paused-log = Generation will pause after the current step
+run-cancel = ⏹ Cancel
+run-cancelled-log = Generation cancelled
+run-no-config = No project configuration loaded
+run-resume-generation = ⟳ Resume generation
+run-resume-generation-hint = Skip the steps an earlier run completed
+run-start = Start generation
+run-preview-costs = 💰 Preview costs
+run-preview-costs-hint = Estimate tokens and cost without requesting anything
+run-metaprompt-errors = Fix the metaprompt errors before generating
+run-resuming-log = Resuming generation
+run-started-log = Generation started
+rate-limits-paused = { $limits } · paused { $seconds }s
+rate-limits-paused-hint = Requests wait until the limits reset
+estimate-total = Total
+disk-title = Estimated disk space
+disk-too-little = Only { $available } free, { $required } needed with headroom: generation won't start here
+disk-free = { $available } free
+disk-unknown = Free space of the project's disk is unknown
+cost-title = Estimated cost
+cost-step = Step
+cost-phase = Phase
+cost-tokens = Tokens
+cost-cost = Cost
+cost-resume = Resuming reuses { $reused } completed steps: { $cost }
+cost-note = Prompts are counted exactly; the range covers replies from short to the full length limit
+cost-cached = cached
+cost-tokens-summary = { $prompt } in + ~{ $completion } out
+
+## Generated artifacts
+
+generated-title = Generated
+generated-tag = Tag
+generated-all-tags = All
+generated-regenerate-tagged = ⟳ Regenerate all tagged { $tag }
+generated-none-tagged = No generated artifacts are tagged { $tag }
+generated-regenerating-tagged = Regenerating { $count ->
+        [one] 1 artifact
+       *[other] { $count } artifacts
+    } tagged { $tag }
+generated-tweak-hint = Optional tweak, e.g. "make the villain more sympathetic"
+generated-regenerate = ⟳ Regenerate
+generated-regenerating = Regenerating { $step }
+generated-tags-save-failed = Failed to save tags: { $error }
+tags-label = Tags:
+tags-remove = Remove tag
+tags-add = add tag
+
+## Bulk export
+
+bulk-export-title = 📦 Bulk Export
+bulk-export-done-log = Exported { $count ->
+        [one] 1 artifact
+       *[other] { $count } artifacts
+    } to { $dir } ({ $skipped } skipped)
+bulk-export-failed = Export failed: { $error }
+bulk-export-no-tags = none in this project
+bulk-export-types = Types:
+bulk-export-select-hint = Nothing selected means everything
+bulk-export-layout = Layout:
+bulk-export-folder-per-type = Folder per type
+bulk-export-tile-size = Tile size:
+bulk-export-tile-size-hint = Tile size of the generated tilesets, scaled to 48 px
+bulk-export-role-tags = Tag artifacts { $prefix }face, { $prefix }a2, ... to place them
+bulk-export-images = Images:
+bulk-export-indexed-png = Indexed PNG
+bulk-export-data = Data:
+bulk-export-audio = Audio:
+bulk-export-ogg-hint = Needs ffmpeg on the path
+bulk-export-folder = Folder:
+bulk-export-button = Export { $count ->
+        [one] 1 artifact
+       *[other] { $count } artifacts
+    }
+bulk-export-done = Exported { $count ->
+        [one] 1 artifact
+       *[other] { $count } artifacts
+    }, summary in { $manifest }
+bulk-export-skipped = Skipped { $artifact }: { $reason }
+
+## Configuration reload
+
+reload-title = Configuration Reloaded
+reload-prompts = Prompts
+reload-dismiss = Dismiss
+reload-added = (added)
+reload-removed = (removed)
+reload-no-steps = None of the changes reach the design prompts.
+reload-renamed = The game was renamed, so every design step needs a fresh run.
+reload-affected-steps = Design steps that read the changed settings:
+reload-rerun = ⟳ Re-run selected steps
+reload-nothing-generated = Nothing has been generated for this project yet
+reload-regenerating = Regenerating { $steps } after a config change
+reload-valid = ✔ valid
+reload-invalid = ✖ invalid
+reload-queue = Queue for validation
+
+## Artifact history and asset comparison
+
+window-refresh = 🔄 Refresh
+history-title = 🕘 Artifact History
+history-select-artifact = Select artifact
+history-no-versions = No versions have been kept for this project yet
+history-roll-back = ↩ Roll back
+history-roll-back-hint = Make this the artifact's current version
+history-rolled-back = Rolled { $name } back to version { $version }
+history-version-missing = Version { $version } is missing
+compare-title = 🖼 Asset Compare
+compare-before-label = Before:
+compare-after-label = After:
+compare-select-image = Select image
+compare-diff = Diff:
+compare-per-pixel = Per pixel
+compare-palette-buckets = Palette buckets
+compare-palette-buckets-hint = Only count changes that alter a pixel's 16-bit color
+compare-no-images = No images found in the project's assets directory
+compare-load-failed = Failed to load images: { $error }
+compare-pixels-changed = { $changed } of { $total } pixels changed ({ $percent }%)
+compare-deltas = mean Δ { $mean }%, max Δ { $max }%
+compare-resized = ⚠ sizes differ, after was rescaled
+compare-before = Before
+compare-after = After
+compare-heatmap = Heatmap
+
+## Prompt tools
+
+tokens = { $count ->
+        [one] 1 token
+       *[other] { $count } tokens
+    }
+inspector-title = 🔬 Prompt Inspector
+inspector-select-template = Select template
+inspector-no-project = No project loaded - rendering with an empty context
+inspector-render-failed = Failed to render template: { $error }
+inspector-total = Total: { $count ->
+        [one] 1 token
+       *[other] { $count } tokens
+    }
+inspector-template-text = template text
+preview-title = 👁 Prompt Preview
+preview-render = 🔄 Render
+preview-subject-sprite = Sprite description
+preview-subject-asset = Asset description
+preview-subject-effect = Effect type
+preview-overridden = Overridden by { $path }
+preview-built-in = Built-in template
+preview-copy-to-project = 📄 Copy to project
+preview-no-project = No project loaded - rendering with default settings
+preview-tokens = { $prompt }: { $count ->
+        [one] 1 token
+       *[other] { $count } tokens
+    } ({ $model })
+preview-render-failed = Failed to render { $prompt }: { $error }
+saved = Saved { $path }
+save-failed = Failed to save { $path }: { $error }
+window-reload = Reload
+variables-title = 🔤 Prompt Variables
+variables-hint = Use a variable as { "{{" } name { "}}" } and a macro as { "{{" } name(args) { "}}" } in any prompt override
+variables-variables = Variables
+variables-macros = Macros
+variables-arguments = Arguments
+variables-name = Name
+variables-add-variable = ➕ Variable
+variables-add-macro = ➕ Macro
+variables-all-defined = ✓ Every name the prompts use is defined
+variables-save = 💾 Save
+
+## Project search
+
+search-title = 🔍 Project Search
+search-hint = Search text, prompts and artifact names
+search-button = Search
+search-semantic = Semantic
+search-semantic-hint = Also compare the query's meaning with artifact descriptions
+search-semantic-failed = Semantic search failed: { $error }
+search-no-matches = No matches
+search-select-match = Select a match to open it
+search-kind-text = text
+search-kind-prompt = prompt
+search-kind-name = name
+search-kind-similar = similar
+
+## Combat tuning
+
+tuning-title = ⚔ Combat Tuning
+tuning-no-export = No Bevy export configured for this project
+tuning-defaults = Export has no combat.ron yet, showing defaults
+tuning-loaded = Loaded { $path }
+tuning-load-failed = Failed to load { $path }: { $error }
+tuning-damage = Damage
+tuning-variance = Variance
+tuning-min-damage = Minimum damage
+tuning-progression = Progression
+tuning-base-xp = XP for level 2
+tuning-xp-growth = XP growth per level
+tuning-preview = Preview
+tuning-attack = Attack
+tuning-magic-attack = Magic attack
+tuning-crit-chance = Crit chance
+tuning-crit-multiplier = Crit multiplier
+tuning-xp-per-battle = XP per battle
+tuning-max-level = Max level
+tuning-damage-chart = Expected damage per hit by target defense
+tuning-pacing-chart = Battles needed to reach each level
+tuning-save = Save to export
+tuning-battles = Battles
+tuning-pacing-summary = Level { $level } after { $battles } battles ({ $xp } XP)
+
+## Failed tasks
+
+failed-tasks-title = ⚠ Failed Tasks ({ $count })
+failed-tasks-description = These tasks were quarantined after repeated failures. Fix the prompt and retry, or dismiss to skip them.
+failed-tasks-task = { $phase }/{ $name } - { $attempts ->
+        [one] { $attempts } attempt
+       *[other] { $attempts } attempts
+    }
+failed-tasks-retry = 🔁 Retry
+failed-tasks-retry-hint = Save the edited prompt and queue it again
+failed-tasks-dismiss = ✖ Dismiss
+failed-tasks-retrying = Retrying { $path } after edit
+failed-tasks-dismissed = Dismissed failed task { $path }
+
+## Tutorial
+
+tutorial-step-of = Step { $step } of { $total }
+tutorial-next = Next ▶
+tutorial-finish = Finish
+tutorial-skip = Skip tutorial
+tutorial-welcome-title = Welcome to the tutorial
+tutorial-welcome-body = We'll build a tiny sample project end to end with the offline mock provider. Nothing is sent to the AI service, so this costs nothing.
+tutorial-language-title = Pick a language
+tutorial-language-body = Every project targets a programming language. The tutorial uses Rust.
+tutorial-select-games-title = Choose source games
+tutorial-select-games-body = Browse the timeline and select at least two classics to blend. Press Next and we'll pick two for you.
+tutorial-blend-title = Blend them
+tutorial-blend-body = The blend engine finds a path between the games and merges their genres, mechanics and art styles.
+tutorial-generate-title = Generate assets
+tutorial-generate-body = Normally each phase calls the AI provider. Here the offline mock provider answers instead, with a placeholder sprite and design document.
+tutorial-export-title = Export the configuration
+tutorial-export-body = Exporting saves the blend so it can seed a full generation run.
+tutorial-done-title = All done!
+tutorial-done-body = You've seen the whole blend → generate → export flow. The sample project is in the tutorial folder.
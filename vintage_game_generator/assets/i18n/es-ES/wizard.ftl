@@ -0,0 +1,674 @@
+# Textos del asistente en español. Los ids que falten se muestran en inglés.
+
+## Marco del asistente
+
+app-title = 🎮 Generador de Juegos Clásicos
+step-welcome = Bienvenida
+step-select-language = Elegir lenguaje
+step-guided = Explorar y mezclar juegos clásicos
+step-freeform = Creación asistida por IA
+step-review = Revisar y generar
+step-complete = Generación completada
+nav-back = ← Atrás
+nav-exit = Salir
+nav-settings = ⚙ Ajustes
+button-cancel = Cancelar
+exit-title = Confirmar salida
+exit-question = ¿Seguro que quieres salir?
+exit-unsaved = Se perderá el progreso no guardado.
+loading-guided = Cargando el modo guiado...
+loading-freeform = Cargando el modo libre...
+guided-title = 🎮 Modo guiado - Mezcla juegos clásicos
+complete-title = 🎉 ¡Exportación completada!
+complete-exported = La configuración del juego se exportó correctamente
+complete-blend = Mezcla: { $name }
+complete-new-blend = Empezar otra mezcla
+complete-done = ¡Exportación completada!
+complete-return = Volver al inicio
+
+## Bienvenida
+
+welcome-subtitle = Elige cómo quieres crear
+welcome-click-side = Haz clic en un lado para elegir tu camino
+welcome-choose = Elige cómo quieres crear:
+welcome-guided = MODO GUIADO
+welcome-guided-tagline = Explora y mezcla juegos clásicos
+welcome-guided-subtitle = Recorre la historia de los videojuegos
+welcome-guided-body = Elige entre juegos clásicos
+    de tres décadas:
+welcome-guided-70s = • Años 70 - Los inicios
+welcome-guided-80s = • Años 80 - La edad de oro del arcade
+welcome-guided-90s = • Años 90 - La revolución de las consolas
+welcome-guided-choose = Elegir guiado
+welcome-freeform = MODO LIBRE
+welcome-freeform-tagline = Creación de juegos con IA
+welcome-freeform-subtitle = Crea tu propia visión
+welcome-freeform-body = Diseña desde cero con
+    ayuda de la IA:
+welcome-freeform-describe = • Describe la idea de tu juego
+welcome-freeform-suggestions = • Recibe sugerencias inteligentes
+welcome-freeform-control = • Control creativo total
+welcome-freeform-choose = Elegir libre
+welcome-or = O
+welcome-same-engine = Ambos modos usan el mismo motor de generación
+welcome-tutorial = 🎓 ¿Eres nuevo? Haz el tutorial
+welcome-tutorial-hint = Recorre un proyecto de ejemplo con un proveedor simulado, sin coste de API
+
+## Ajustes
+
+settings-title = ⚙ Ajustes
+settings-ui-language = Idioma de la interfaz
+settings-ui-language-hint = Idioma del propio asistente. El contenido generado tiene su propio ajuste de idioma.
+settings-content-language = Idioma del contenido generado
+settings-content-language-default = Inglés (predeterminado)
+settings-content-language-hint = Idioma en que se escriben la historia, los diálogos y los textos del juego. Se guarda con el proyecto abierto.
+settings-content-language-no-project = Abre un proyecto para elegir el idioma de su contenido generado.
+settings-era = Época de los límites de hardware
+settings-era-auto = { $era } (según los juegos de referencia)
+settings-authenticity = Autenticidad
+settings-authenticity-limits = { $strictness }: { $colors } colores, { $sprites } sprites, { $channels } canales de sonido, { $chars } caracteres por cuadro de texto, { $beats } momentos de la trama
+settings-authenticity-hint = Desde un resultado libre con sabor nostálgico hasta uno estricto y fiel al hardware. Se aplica a cada paso del diseño. Se guarda con el proyecto abierto.
+
+## Lista de proyectos
+
+list-title = Explorar proyectos
+list-found = { $count ->
+        [one] Un proyecto
+       *[other] { $count } proyectos
+    } en: { $dir }
+list-previous = ◀ Anterior
+list-next = Siguiente ▶
+list-edit = Editar
+list-no-project = Ningún proyecto seleccionado
+list-error = Error: { $error }
+list-load-failed = No se pudieron cargar los proyectos: { $error }
+list-templates-failed = No se pudieron cargar las plantillas: { $error }
+list-tab-details = Detalles del proyecto
+list-tab-files = Archivos
+list-tab-preview = Vista previa
+list-search-hint = Nombre, género o juego de origen
+list-any-genre = Cualquier género
+list-created = Creado
+list-from = desde
+list-to = hasta
+list-date-format = Usa AAAA-MM-DD
+list-clear = ✖ Limpiar
+list-matching = { $shown } de { $total }
+list-new-from-template = Nuevo desde plantilla:
+list-no-templates = Sin plantillas
+list-template-saved = { $name } (guardada)
+list-project-name = Nombre del proyecto
+list-create = ✨ Crear
+list-template-name = Nombre de la plantilla
+list-save-template = 💾 Guardar como plantilla
+list-duplicate = 📄 Duplicar
+list-without-generated = sin archivos generados
+list-without-generated-hint = Omite recursos, puntos de control y exportaciones
+list-archive = 🗜 Archivar
+list-delete = 🗑 Eliminar
+list-restore-archive = Restaurar archivo:
+list-restore-hint = Ruta a un .zip de proyecto
+list-restore = 📂 Restaurar
+list-duplicated = { $name } duplicado
+list-archived = { $name } archivado en { $path }
+list-restored = { $path } restaurado
+list-deleted = { $name } eliminado
+list-uuid = UUID: { $uuid }
+list-path = Ruta: { $path }
+list-genre = Género: { $genre }
+list-source-games = Juegos de origen: { $games }
+list-created-at = Creado: { $date }
+list-last-generated = Última generación: { $date }
+list-gameplay = Jugabilidad
+list-core-mechanics = Mecánicas principales: { $value }
+list-progression = Tipo de progresión: { $value }
+list-gameplay-loop = Bucle de juego: { $value }
+list-motivation = Motivación del jugador: { $value }
+list-visual-style = Estilo visual
+list-reference-games = Juegos de referencia: { $value }
+list-color-mood = Ambiente de color: { $value }
+list-sprite-size = Tamaño de sprite: { $value } px
+list-use-outline = Usar contorno: { $value }
+list-technical = Técnico
+list-world-size = Tamaño del mundo: { $value }
+list-target-platforms = Plataformas objetivo: { $value }
+list-files-title = Archivos del proyecto
+list-files-unreadable = No se pudo leer el directorio del proyecto
+list-preview-title = Vista previa
+list-preview-intro = Este proyecto generaría:
+list-preview-genre = • Un juego de tipo { $genre }
+list-preview-inspired = • Inspirado en { $games }
+list-preview-gameplay = • Jugabilidad { $value }
+list-preview-mood = • Visuales de ambiente { $value }
+
+## Confirmación de borrado
+
+delete-title = Eliminar proyecto
+delete-question = ¿Eliminar { $name }?
+delete-warning = Se borrarán { $path } y todo lo generado para él.
+delete-archive-first = Archiva el proyecto antes para conservar una copia.
+delete-confirm = Eliminar
+
+## Paso de lenguaje
+
+language-title = Elige el lenguaje de destino
+language-subtitle = Elige el lenguaje de programación de tu juego
+language-best-for = 🎯 Ideal para:
+language-strengths = 💪 Puntos fuertes:
+language-python-beginners = • Principiantes
+language-python-prototypes = • Prototipos rápidos
+language-python-education = • Juegos educativos
+language-python-easy = • Fácil de aprender
+language-python-rapid = • Desarrollo rápido
+language-python-pygame = • Biblioteca PyGame
+language-rust-performance = • Juegos exigentes
+language-rust-browser = • Juegos de navegador
+language-rust-systems = • Sistemas complejos
+language-rust-fast = • Rapidísimo
+language-rust-safe = • Seguro en memoria
+language-rust-wasm = • Soporte WASM
+language-coming-soon = 🚧 ¡Próximamente! 🚧
+language-ruby-in-progress = El soporte de Ruby
+    está en desarrollo
+language-image-failed = No se pudo cargar la imagen de selección de lenguaje
+language-ruby-coming-soon = Ruby 💎 (Próximamente)
+language-footer-games = Todos los lenguajes generan juegos retro completos y jugables
+language-footer-libraries = con las bibliotecas y frameworks adecuados para cada lenguaje
+
+## Ventana principal
+
+main-wizard-completed = ¡Asistente completado! Iniciando la conversación con la IA...
+main-title = Generador de RPG con IA
+main-phase = Fase: { $phase }
+main-api-key-set = ✓ Clave de API configurada
+main-api-key-missing = ✗ Falta la clave de API
+main-api-key-hint = Define la variable de entorno OPENAI_API_KEY
+main-pause = ⏸ Pausar
+main-start = ▶ Iniciar
+main-browse-projects = 📂 Ver proyectos
+main-tab-conversation = 💬 Conversación
+main-tab-prompts = 📝 Prompts
+main-tab-assets = 🎨 Recursos
+main-tab-build = 🔨 Compilación
+main-tab-logs = 📋 Registro
+main-conversation-title = Conversación de metaprompts
+main-game = Juego: { $name }
+main-genre = Género: { $genre }
+main-style = Estilo: { $style }
+main-conversation-placeholder = El historial de la conversación aparecerá aquí...
+main-user-input = Entrada del usuario: { $input }
+main-prompts-title = Gestión de prompts
+main-validation-queue = Cola de validación:
+main-errors = Errores
+main-browse-prompts = Ver prompts
+main-opening-prompt = Abriendo el prompt: { $file }
+main-assets-title = Recursos generados
+main-assets-placeholder = Los recursos generados aparecerán aquí...
+main-build-title = Resultado de la compilación
+main-open-build-dir = 📁 Abrir carpeta de compilación
+main-open-build-dir-failed = No se pudo abrir la carpeta de compilación: { $error }
+main-run-game = 🎮 Ejecutar juego
+main-launching-game = Lanzando el juego generado...
+main-build-contents = Contenido de la carpeta de compilación:
+main-logs-title = Registro
+main-clear-logs = Limpiar
+
+## Línea temporal
+
+timeline-title = 🎮 Historia de los videojuegos
+timeline-pick-decade = Elige una década para explorar la historia de los videojuegos
+timeline-decade-hint = Elige entre la edad de oro de los arcades (años 80)
+    o el renacimiento de los 16 bits (años 90)
+timeline-search-hint = Busca juegos, se admiten erratas
+timeline-any-genre = Cualquier género
+timeline-any-platform = Cualquier plataforma
+timeline-years = Años
+timeline-clear-search = ✖ Limpiar
+timeline-filters = Filtros
+timeline-genre = Género:
+timeline-platform = Plataforma:
+timeline-developer = Desarrolladora:
+timeline-clear-filters = ✖ Quitar filtros
+timeline-no-matches = Ningún juego coincide con la búsqueda
+timeline-best-matches = Mostrando los { $shown } mejores de { $count } resultados
+timeline-matches = { $count ->
+        [one] 1 resultado
+       *[other] { $count } resultados
+    }
+timeline-games = { $count ->
+        [one] 1 juego
+       *[other] { $count } juegos
+    }
+timeline-platforms = { $count ->
+        [one] 1 plataforma
+       *[other] { $count } plataformas
+    }
+timeline-year = Año:
+timeline-platforms-label = Plataformas:
+
+## Fichas de juego
+
+card-platforms = Plataformas:
+card-selected-title = 📚 Juegos elegidos
+card-none-selected = Aún no has elegido ningún juego
+card-selection-hint = Haz clic en los juegos de la línea temporal para añadirlos
+    Elige al menos 2 juegos para crear una mezcla
+card-selected-count = { $count ->
+        [one] 1 juego elegido
+       *[other] { $count } juegos elegidos
+    }
+card-reblend = 🔄 Volver a mezclar
+card-genre = Género:
+card-era = Época:
+card-era-arcade = Edad de oro de los arcades
+card-era-early-console = Primeras consolas
+card-era-transition = Transición de 8 a 16 bits
+card-era-16-bit-peak = Apogeo de los 16 bits
+card-era-unknown = Época desconocida
+card-complexity = Complejidad:
+
+## Modo guiado
+
+guided-browse-hint = Explora los juegos por década y elige los que quieras mezclar:
+guided-era-stats = 📊 Estadísticas de la época
+guided-selected-count = { $count ->
+        [one] 1 juego elegido
+       *[other] { $count } juegos elegidos
+    }
+guided-blend = 🔀 Mezclar juegos
+guided-blend-needs-two = (Elige al menos 2 juegos para mezclar)
+guided-back-to-selection = ⬅ Volver a la selección
+guided-continue = Continuar ➡
+guided-continue-unresolved = Elige una solución para cada conflicto
+guided-skip-resolution = Omitir por ahora
+guided-resolve-conflicts = ⚖ Resolver conflictos
+guided-export = ✅ Exportar configuración
+guided-invalid-step = Paso no válido
+guided-selected-games = Juegos elegidos
+guided-weight-hint = Qué parte de la mezcla aporta este juego
+guided-blend-share = → { $percent }% de la mezcla
+surprise-button = 🎲 Sorpréndeme
+surprise-hint = Mezcla 2-3 juegos de épocas y géneros distintos
+surprise-seed = Semilla
+surprise-seed-hint = Conserva la semilla para repetir la misma sorpresa
+similar-title = 🔗 Parecidos a { $game }
+similar-score = { $genre }, { $percent }% de parecido
+clusters-title = 🧭 Grupos de juegos
+clusters-none = Hay muy pocos juegos para formar grupos
+clusters-cluster = Grupo { $number }: sobre todo { $genre }, { $count } juegos
+clusters-more = { $count } más
+clusters-blends = Mezclas entre grupos
+clusters-blend-hint = { $percent }% compatibles; los juegos de grupos distintos rara vez se encuentran
+stats-title = 📊 Estadísticas de la época
+stats-genres = Juegos por género y año
+stats-platforms = Vida de las plataformas
+stats-platforms-hint = El grosor de la línea indica cuántos juegos de la línea temporal tiene la plataforma
+stats-mechanics = Presencia de las mecánicas a lo largo del tiempo
+
+## Valoraciones y sugerencias
+
+rating-blend = Valora esta mezcla:
+rating-concept = Valora el concepto:
+rating-more = Más como esto
+rating-less = Menos como esto
+rating-recorded = { $count ->
+        [one] 1 valoración guardada
+       *[other] { $count } valoraciones guardadas
+    }
+suggestions-title = ⭐ Sugerencias para ti
+suggestions-match = { $percent }% de afinidad
+preferences-title = ⚙ Preferencias
+preferences-ratings = { $count ->
+        [one] 1 valoración
+       *[other] { $count } valoraciones
+    } ({ $liked } 👍 / { $disliked } 👎)
+preferences-untrained = Valora al menos una mezcla a favor y otra en contra para personalizar las sugerencias
+preferences-export = 📤 Exportar valoraciones
+preferences-exported = Exportadas a { $path }
+preferences-export-failed = Error al exportar: { $error }
+preferences-reset = 🗑 Borrar valoraciones
+preferences-cleared = Valoraciones borradas
+
+## Mezcla
+
+blend-genres = Reparto de géneros
+blend-mechanics = Mecánicas combinadas
+blend-balance = Equilibrio del juego
+blend-synergies = ✨ Sinergias
+blend-conflicts = ⚠️ Conflictos por resolver
+blend-conflict-games = { $first } contra { $second }
+blend-features = 💡 Características recomendadas
+blend-visual-style = 🎨 Estilo visual
+blend-export = 📥 Exportar configuración
+blend-modify = 🔄 Cambiar la selección
+blend-create = 🧪 Crear mezcla
+blend-create-hint = Haz clic para analizar la compatibilidad de los juegos y generar la mezcla
+blend-complexity = Complejidad:
+blend-simple = Sencillo
+blend-complex = Complejo
+blend-gameplay = Jugabilidad:
+blend-strategic = Estratégico
+blend-action = Acción
+export-title = 📤 Opciones de exportación
+export-no-blend = No hay ninguna mezcla que exportar. ¡Crea una primero!
+export-copy-toml = 📄 Copiar TOML
+export-copy-json = 📋 Copiar JSON
+export-copy-prompt = 🤖 Copiar prompt para la IA
+export-preview-toml = Vista previa del TOML exportado
+
+## Resolución de conflictos
+
+resolution-title = ⚖ Resolver conflictos
+resolution-intro = Los juegos mezclados tiran en direcciones distintas. Elige cómo se resuelve cada conflicto:
+resolution-asking = Pidiendo más ideas a la IA…
+resolution-unavailable = Sugerencias de la IA no disponibles ({ $error }); se muestran las ideas del análisis de la mezcla
+resolution-from-ai = Sugerido por la IA
+
+## Explicación de la mezcla
+
+explain-button = 💬 Explícame esta mezcla
+explain-button-hint = Pregunta a la IA por qué la mezcla obtuvo esa puntuación
+explain-title = 💬 Explícame esta mezcla
+explain-you = Tú
+explain-ai = IA
+explain-tell-changes = 🔄 Cuéntale mis cambios
+explain-tell-changes-hint = La mezcla ha cambiado desde la última vez que la conversación la vio
+explain-input-hint = Pregunta sobre la mezcla…
+explain-send = Enviar
+
+## Modo libre
+
+freeform-step-introduction = Introducción
+freeform-step-basic-info = Datos básicos
+freeform-step-gameplay = Diseño de la jugabilidad
+freeform-step-visual-style = Estilo visual
+freeform-step-features = Características
+freeform-step-technical = Ajustes técnicos
+freeform-step-review = Revisión
+freeform-under-construction = { $step } - En construcción
+freeform-being-rebuilt = Este paso se está rehaciendo.
+freeform-title = 🤖 Conversación de diseño con la IA
+freeform-subtitle = ¡Demos vida a tu juego!
+freeform-context = Contexto actual:
+freeform-fork = ⑂ Bifurcar aquí
+freeform-fork-hint = Explora otra dirección de diseño a partir de este punto
+freeform-thinking = La IA está pensando...
+freeform-error = Error: { $error }
+freeform-send = Enviar
+freeform-export = Exportar
+freeform-back-to-review = ← Volver a la revisión
+freeform-generate = Generar el juego →
+freeform-topic = Tema: { $topic }
+freeform-decisions = Decisiones tomadas:
+freeform-branches = Ramas:
+freeform-switch-branch = Cambiar a esta rama
+freeform-hide-comparison = Ocultar comparación
+freeform-compare = Comparar
+freeform-merge = Fusionar con la actual
+freeform-shared-messages = { $count ->
+        [one] 1 mensaje en común antes de que las ramas se separen
+       *[other] { $count } mensajes en común antes de que las ramas se separen
+    }
+freeform-not-started = La conversación aún no ha empezado
+freeform-no-generator = El generador de IA no está inicializado
+freeform-fork-summarized = Este mensaje se resumió y ya no se puede bifurcar desde él
+freeform-main-branch = Principal
+freeform-branch = Rama { $number }
+freeform-unknown-branch = Rama desconocida
+
+## Generación
+
+run-title = Generar
+run-idle = La generación aún no ha empezado.
+run-cancelled = Se canceló la generación. Se conservan los pasos completados.
+run-needs-rewrite = ⚠ Hace falta reescribirlo a mano: el modelo rechazó un prompt ({ $trigger })
+run-refused-prompt = Prompt rechazado
+run-rewrite-refused = Reescritura automática, rechazada también:
+run-reword-hint = Reformula la descripción del proyecto y reanuda; se conservan los pasos completados.
+run-complete = Documento de diseño guardado en { $path }
+run-regenerated = Regenerado { $step }
+run-resume = ▶ Reanudar
+run-resumed-log = Generación reanudada
+run-pause = ⏸ Pausar
+run-paused-log = La generación se pausará tras el paso actual
+run-cancel = ⏹ Cancelar
+run-cancelled-log = Generación cancelada
+run-no-config = No hay ninguna configuración de proyecto cargada
+run-resume-generation = ⟳ Reanudar la generación
+run-resume-generation-hint = Omite los pasos que completó una ejecución anterior
+run-start = Empezar la generación
+run-preview-costs = 💰 Ver costes
+run-preview-costs-hint = Estima tokens y coste sin hacer ninguna petición
+run-metaprompt-errors = Corrige los errores de los metaprompts antes de generar
+run-resuming-log = Reanudando la generación
+run-started-log = Generación iniciada
+rate-limits-paused = { $limits } · en pausa { $seconds } s
+rate-limits-paused-hint = Las peticiones esperan a que se restablezcan los límites
+estimate-total = Total
+disk-title = Espacio en disco estimado
+disk-too-little = Solo hay { $available } libres y hacen falta { $required } con margen: la generación no empezará aquí
+disk-free = { $available } libres
+disk-unknown = Se desconoce el espacio libre del disco del proyecto
+cost-title = Coste estimado
+cost-step = Paso
+cost-phase = Fase
+cost-tokens = Tokens
+cost-cost = Coste
+cost-resume = Reanudar reutiliza { $reused } pasos completados: { $cost }
+cost-note = Los prompts se cuentan con exactitud; el rango va de respuestas cortas al límite de longitud
+cost-cached = en caché
+cost-tokens-summary = { $prompt } de entrada + ~{ $completion } de salida
+
+## Artefactos generados
+
+generated-title = Generado
+generated-tag = Etiqueta
+generated-all-tags = Todas
+generated-regenerate-tagged = ⟳ Regenerar todo lo etiquetado { $tag }
+generated-none-tagged = No hay artefactos generados con la etiqueta { $tag }
+generated-regenerating-tagged = Regenerando { $count ->
+        [one] 1 artefacto
+       *[other] { $count } artefactos
+    } con la etiqueta { $tag }
+generated-tweak-hint = Ajuste opcional, p. ej. "haz al villano más entrañable"
+generated-regenerate = ⟳ Regenerar
+generated-regenerating = Regenerando { $step }
+generated-tags-save-failed = No se pudieron guardar las etiquetas: { $error }
+tags-label = Etiquetas:
+tags-remove = Quitar etiqueta
+tags-add = añadir etiqueta
+
+## Exportación masiva
+
+bulk-export-title = 📦 Exportación masiva
+bulk-export-done-log = { $count ->
+        [one] 1 artefacto exportado
+       *[other] { $count } artefactos exportados
+    } a { $dir } ({ $skipped } omitidos)
+bulk-export-failed = Error al exportar: { $error }
+bulk-export-no-tags = ninguna en este proyecto
+bulk-export-types = Tipos:
+bulk-export-select-hint = No elegir nada equivale a elegirlo todo
+bulk-export-layout = Estructura:
+bulk-export-folder-per-type = Una carpeta por tipo
+bulk-export-tile-size = Tamaño de tile:
+bulk-export-tile-size-hint = Tamaño de tile de los tilesets generados, escalado a 48 px
+bulk-export-role-tags = Etiqueta los artefactos con { $prefix }face, { $prefix }a2, ... para colocarlos
+bulk-export-images = Imágenes:
+bulk-export-indexed-png = PNG indexado
+bulk-export-data = Datos:
+bulk-export-audio = Audio:
+bulk-export-ogg-hint = Necesita ffmpeg en el PATH
+bulk-export-folder = Carpeta:
+bulk-export-button = Exportar { $count ->
+        [one] 1 artefacto
+       *[other] { $count } artefactos
+    }
+bulk-export-done = { $count ->
+        [one] 1 artefacto exportado
+       *[other] { $count } artefactos exportados
+    }, resumen en { $manifest }
+bulk-export-skipped = Omitido { $artifact }: { $reason }
+
+## Recarga de la configuración
+
+reload-title = Configuración recargada
+reload-prompts = Prompts
+reload-dismiss = Descartar
+reload-added = (añadido)
+reload-removed = (eliminado)
+reload-no-steps = Ninguno de los cambios afecta a los prompts de diseño.
+reload-renamed = El juego cambió de nombre, así que todos los pasos de diseño deben ejecutarse de nuevo.
+reload-affected-steps = Pasos de diseño que leen los ajustes cambiados:
+reload-rerun = ⟳ Repetir los pasos elegidos
+reload-nothing-generated = Aún no se ha generado nada para este proyecto
+reload-regenerating = Regenerando { $steps } tras un cambio de configuración
+reload-valid = ✔ válido
+reload-invalid = ✖ no válido
+reload-queue = Poner en cola de validación
+
+## Historial de artefactos y comparación de recursos
+
+window-refresh = 🔄 Actualizar
+history-title = 🕘 Historial de artefactos
+history-select-artifact = Elige un artefacto
+history-no-versions = Aún no se ha guardado ninguna versión en este proyecto
+history-roll-back = ↩ Restaurar
+history-roll-back-hint = Convierte esta en la versión actual del artefacto
+history-rolled-back = { $name } restaurado a la versión { $version }
+history-version-missing = Falta la versión { $version }
+compare-title = 🖼 Comparar recursos
+compare-before-label = Antes:
+compare-after-label = Después:
+compare-select-image = Elige una imagen
+compare-diff = Diferencia:
+compare-per-pixel = Por píxel
+compare-palette-buckets = Grupos de paleta
+compare-palette-buckets-hint = Cuenta solo los cambios que alteran el color de 16 bits de un píxel
+compare-no-images = No hay imágenes en la carpeta de recursos del proyecto
+compare-load-failed = No se pudieron cargar las imágenes: { $error }
+compare-pixels-changed = { $changed } de { $total } píxeles cambiados ({ $percent }%)
+compare-deltas = Δ media { $mean }%, Δ máxima { $max }%
+compare-resized = ⚠ los tamaños difieren, se reescaló la imagen de después
+compare-before = Antes
+compare-after = Después
+compare-heatmap = Mapa de calor
+
+## Herramientas de prompts
+
+tokens = { $count ->
+        [one] 1 token
+       *[other] { $count } tokens
+    }
+inspector-title = 🔬 Inspector de prompts
+inspector-select-template = Elige una plantilla
+inspector-no-project = No hay ningún proyecto cargado: se renderiza con un contexto vacío
+inspector-render-failed = No se pudo renderizar la plantilla: { $error }
+inspector-total = Total: { $count ->
+        [one] 1 token
+       *[other] { $count } tokens
+    }
+inspector-template-text = texto de la plantilla
+preview-title = 👁 Vista previa del prompt
+preview-render = 🔄 Renderizar
+preview-subject-sprite = Descripción del sprite
+preview-subject-asset = Descripción del recurso
+preview-subject-effect = Tipo de efecto
+preview-overridden = Sustituido por { $path }
+preview-built-in = Plantilla integrada
+preview-copy-to-project = 📄 Copiar al proyecto
+preview-no-project = No hay ningún proyecto cargado: se renderiza con los ajustes por defecto
+preview-tokens = { $prompt }: { $count ->
+        [one] 1 token
+       *[other] { $count } tokens
+    } ({ $model })
+preview-render-failed = No se pudo renderizar { $prompt }: { $error }
+saved = Guardado { $path }
+save-failed = No se pudo guardar { $path }: { $error }
+window-reload = Recargar
+variables-title = 🔤 Variables de los prompts
+variables-hint = Usa una variable como { "{{" } nombre { "}}" } y una macro como { "{{" } nombre(args) { "}}" } en cualquier prompt sustituido
+variables-variables = Variables
+variables-macros = Macros
+variables-arguments = Argumentos
+variables-name = Nombre
+variables-add-variable = ➕ Variable
+variables-add-macro = ➕ Macro
+variables-all-defined = ✓ Todos los nombres que usan los prompts están definidos
+variables-save = 💾 Guardar
+
+## Búsqueda en el proyecto
+
+search-title = 🔍 Buscar en el proyecto
+search-hint = Busca en textos, prompts y nombres de artefactos
+search-button = Buscar
+search-semantic = Semántica
+search-semantic-hint = Compara también el significado de la búsqueda con las descripciones de los artefactos
+search-semantic-failed = Falló la búsqueda semántica: { $error }
+search-no-matches = Sin resultados
+search-select-match = Elige un resultado para abrirlo
+search-kind-text = texto
+search-kind-prompt = prompt
+search-kind-name = nombre
+search-kind-similar = parecido
+
+## Ajuste de combate
+
+tuning-title = ⚔ Ajuste de combate
+tuning-no-export = Este proyecto no tiene una exportación de Bevy configurada
+tuning-defaults = La exportación aún no tiene combat.ron; se muestran los valores por defecto
+tuning-loaded = Cargado { $path }
+tuning-load-failed = No se pudo cargar { $path }: { $error }
+tuning-damage = Daño
+tuning-variance = Variación
+tuning-min-damage = Daño mínimo
+tuning-progression = Progresión
+tuning-base-xp = XP para el nivel 2
+tuning-xp-growth = Crecimiento de XP por nivel
+tuning-preview = Vista previa
+tuning-attack = Ataque
+tuning-magic-attack = Ataque mágico
+tuning-crit-chance = Probabilidad de crítico
+tuning-crit-multiplier = Multiplicador de crítico
+tuning-xp-per-battle = XP por batalla
+tuning-max-level = Nivel máximo
+tuning-damage-chart = Daño esperado por golpe según la defensa del objetivo
+tuning-pacing-chart = Batallas necesarias para alcanzar cada nivel
+tuning-save = Guardar en la exportación
+tuning-battles = Batallas
+tuning-pacing-summary = Nivel { $level } tras { $battles } batallas ({ $xp } XP)
+
+## Tareas fallidas
+
+failed-tasks-title = ⚠ Tareas fallidas ({ $count })
+failed-tasks-description = Estas tareas se pusieron en cuarentena tras fallar varias veces. Corrige el prompt y reinténtalo, o descártalas para omitirlas.
+failed-tasks-task = { $phase }/{ $name } - { $attempts ->
+        [one] { $attempts } intento
+       *[other] { $attempts } intentos
+    }
+failed-tasks-retry = 🔁 Reintentar
+failed-tasks-retry-hint = Guarda el prompt editado y vuelve a ponerlo en cola
+failed-tasks-dismiss = ✖ Descartar
+failed-tasks-retrying = Reintentando { $path } tras la edición
+failed-tasks-dismissed = Tarea fallida descartada: { $path }
+
+## Tutorial
+
+tutorial-step-of = Paso { $step } de { $total }
+tutorial-next = Siguiente ▶
+tutorial-finish = Terminar
+tutorial-skip = Saltar tutorial
+tutorial-welcome-title = Bienvenido al tutorial
+tutorial-welcome-body = Crearemos de principio a fin un pequeño proyecto de ejemplo con el proveedor simulado sin conexión. No se envía nada al servicio de IA, así que no cuesta nada.
+tutorial-language-title = Elige un lenguaje
+tutorial-language-body = Cada proyecto usa un lenguaje de programación. El tutorial usa Rust.
+tutorial-select-games-title = Elige los juegos de origen
+tutorial-select-games-body = Recorre la línea temporal y selecciona al menos dos clásicos para mezclar. Pulsa Siguiente y elegiremos dos por ti.
+tutorial-blend-title = Mézclalos
+tutorial-blend-body = El motor de mezcla encuentra un camino entre los juegos y combina sus géneros, mecánicas y estilos artísticos.
+tutorial-generate-title = Genera los recursos
+tutorial-generate-body = Normalmente cada fase llama al proveedor de IA. Aquí responde el proveedor simulado sin conexión, con un sprite y un documento de diseño provisionales.
+tutorial-export-title = Exporta la configuración
+tutorial-export-body = Al exportar se guarda la mezcla para iniciar una generación completa.
+tutorial-done-title = ¡Listo!
+tutorial-done-body = Has visto todo el flujo de mezclar → generar → exportar. El proyecto de ejemplo está en la carpeta del tutorial.
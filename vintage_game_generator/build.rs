@@ -12,6 +12,7 @@ async fn main() -> Result<()> {
     println!("cargo:rerun-if-changed=templates/giantbomb/platforms.rs.jinja");
     println!("cargo:rerun-if-changed=templates/giantbomb/eras.rs.jinja");
     println!("cargo:rerun-if-changed=templates/giantbomb/graph.rs.jinja");
+    println!("cargo:rerun-if-changed=templates/giantbomb/visuals.rs.jinja");
 
     // Build the vintage game data - the build tools handle all validation
     match VintageBuildTools::from_env(TIMELINE_START, TIMELINE_END) {
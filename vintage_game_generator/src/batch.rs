@@ -0,0 +1,254 @@
+//! Headless batch generation over a directory of projects
+//!
+//! `--batch <dir>` finds every `project.toml` below the directory and
+//! generates each project from its configuration without the GUI, at most
+//! `jobs` at a time. A project's design document is written next to its
+//! config, and a config without a generation seed gets the one the run used
+//! recorded, so the batch can be rerun the same way. Projects that fail are
+//! reported and don't stop the batch. When
+//! all have run, `batch-report.json` and `batch-report.md` in the batch
+//! directory list every project with its status, duration and cost, and the
+//! totals across the batch.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use vintage_ai_client::AiConfig;
+
+use crate::footprint::{AssetChecklist, ensure_disk_space};
+use crate::metaprompts::GameGenerator;
+use crate::wizard::config::ProjectConfig;
+
+/// File name of the project configs the batch looks for
+pub const PROJECT_FILE: &str = "project.toml";
+
+/// Written next to each project config
+pub const DESIGN_FILE: &str = "design.md";
+
+pub const REPORT_JSON: &str = "batch-report.json";
+pub const REPORT_MARKDOWN: &str = "batch-report.md";
+
+/// Directories never searched for projects
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules", "assets"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectReport {
+    pub name: String,
+    pub config_file: PathBuf,
+    /// Design document, when generation succeeded
+    pub output: Option<PathBuf>,
+    pub error: Option<String>,
+    pub duration_secs: f64,
+    pub tokens: u64,
+    /// Cost in USD
+    pub cost: f64,
+    pub cost_by_model: BTreeMap<String, f64>,
+}
+
+impl ProjectReport {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchReport {
+    pub batch_dir: PathBuf,
+    pub started_at: DateTime<Utc>,
+    pub jobs: usize,
+    pub duration_secs: f64,
+    pub projects: Vec<ProjectReport>,
+    pub total_tokens: u64,
+    /// Cost in USD across all projects
+    pub total_cost: f64,
+    pub cost_by_model: BTreeMap<String, f64>,
+}
+
+impl BatchReport {
+    pub fn succeeded(&self) -> usize {
+        self.projects.iter().filter(|p| p.succeeded()).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.projects.len() - self.succeeded()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::from("# Batch Generation Report\n\n");
+        let _ = writeln!(md, "- **Directory**: {}", self.batch_dir.display());
+        let _ = writeln!(md, "- **Started**: {}", self.started_at.to_rfc3339());
+        let _ = writeln!(md, "- **Parallel jobs**: {}", self.jobs);
+        let _ = writeln!(md, "- **Duration**: {:.1}s", self.duration_secs);
+        let _ = writeln!(
+            md,
+            "- **Projects**: {} ({} succeeded, {} failed)",
+            self.projects.len(),
+            self.succeeded(),
+            self.failed()
+        );
+        let _ = writeln!(md, "- **Tokens**: {}", self.total_tokens);
+        let _ = writeln!(md, "- **Total cost**: ${:.4}", self.total_cost);
+
+        md.push_str("\n## Projects\n\n");
+        md.push_str("| Project | Status | Duration | Tokens | Cost |\n");
+        md.push_str("|---------|--------|----------|--------|------|\n");
+        for project in &self.projects {
+            let status = match &project.error {
+                None => "ok".to_string(),
+                Some(error) => format!("failed: {}", error.replace('|', "\\|")),
+            };
+            let _ = writeln!(
+                md,
+                "| {} | {status} | {:.1}s | {} | ${:.4} |",
+                project.name, project.duration_secs, project.tokens, project.cost
+            );
+        }
+
+        if !self.cost_by_model.is_empty() {
+            md.push_str("\n## Cost by Model\n\n");
+            md.push_str("| Model | Cost |\n|-------|------|\n");
+            for (model, cost) in &self.cost_by_model {
+                let _ = writeln!(md, "| {model} | ${cost:.4} |");
+            }
+        }
+        md
+    }
+
+    /// Write the JSON and markdown reports into the batch directory
+    pub fn write(&self) -> Result<()> {
+        std::fs::write(
+            self.batch_dir.join(REPORT_JSON),
+            serde_json::to_string_pretty(self)?,
+        )
+        .context("Failed to write batch report")?;
+        std::fs::write(self.batch_dir.join(REPORT_MARKDOWN), self.to_markdown())
+            .context("Failed to write batch report")?;
+        Ok(())
+    }
+}
+
+/// Every project config below the directory, in path order
+pub fn discover_projects(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut projects = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = std::fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read directory {}", dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            let file_name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default();
+            if path.is_dir() {
+                if !file_name.starts_with('.') && !SKIPPED_DIRS.contains(&file_name) {
+                    pending.push(path);
+                }
+            } else if file_name == PROJECT_FILE {
+                projects.push(path);
+            }
+        }
+    }
+    projects.sort();
+    Ok(projects)
+}
+
+/// Generate every project below the directory, `jobs` at a time
+pub async fn run_batch(dir: &Path, ai_config: &AiConfig, jobs: usize) -> Result<BatchReport> {
+    let started_at = Utc::now();
+    let start = Instant::now();
+    let jobs = jobs.max(1);
+    let configs = discover_projects(dir)?;
+    if configs.is_empty() {
+        anyhow::bail!("No {PROJECT_FILE} found in {}", dir.display());
+    }
+    tracing::info!("Batch: {} projects, {jobs} at a time", configs.len());
+
+    let projects: Vec<ProjectReport> = futures::stream::iter(configs)
+        .map(|config_file| run_project(config_file, ai_config))
+        .buffered(jobs)
+        .collect()
+        .await;
+
+    let mut cost_by_model = BTreeMap::new();
+    for (model, cost) in projects.iter().flat_map(|p| &p.cost_by_model) {
+        *cost_by_model.entry(model.clone()).or_insert(0.0) += cost;
+    }
+    Ok(BatchReport {
+        batch_dir: dir.to_path_buf(),
+        started_at,
+        jobs,
+        duration_secs: start.elapsed().as_secs_f64(),
+        total_tokens: projects.iter().map(|p| p.tokens).sum(),
+        total_cost: projects.iter().map(|p| p.cost).sum(),
+        cost_by_model,
+        projects,
+    })
+}
+
+async fn run_project(config_file: PathBuf, ai_config: &AiConfig) -> ProjectReport {
+    let start = Instant::now();
+    let project_dir = config_file
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let mut report = ProjectReport {
+        name: project_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| config_file.display().to_string()),
+        config_file: config_file.clone(),
+        output: None,
+        error: None,
+        duration_secs: 0.0,
+        tokens: 0,
+        cost: 0.0,
+        cost_by_model: BTreeMap::new(),
+    };
+
+    let result = async {
+        let config = ProjectConfig::load(&config_file)?;
+        config.record_generation_seed(&config_file)?;
+        if let Some(name) = config.name.as_ref().filter(|name| !name.is_empty()) {
+            report.name = name.clone();
+        }
+        ensure_disk_space(&AssetChecklist::for_project(&config), &project_dir)?;
+        let mut generator = GameGenerator::with_ai_config(ai_config).await?;
+        generator.set_project_config(config);
+        // A batch that is run again picks up where a crashed run stopped
+        generator.set_checkpoint_dir(project_dir.clone(), true);
+
+        let name = report.name.clone();
+        let design = generator
+            .generate_from_project(move |progress| {
+                tracing::info!("[{name}] {:?}: {}", progress.phase, progress.message);
+            })
+            .await;
+
+        let usage = generator.usage().await;
+        report.tokens = usage.prompt_tokens
+            + usage.completion_tokens
+            + usage.embedding_tokens
+            + usage.image_tokens;
+        report.cost = usage.total_cost;
+        report.cost_by_model = usage.cost_by_model.into_iter().collect();
+
+        let output = project_dir.join(DESIGN_FILE);
+        std::fs::write(&output, design?).context("Failed to write design document")?;
+        report.output = Some(output);
+        Ok::<(), anyhow::Error>(())
+    }
+    .await;
+
+    if let Err(e) = result {
+        tracing::warn!("Batch project {} failed: {e:#}", config_file.display());
+        report.error = Some(format!("{e:#}"));
+    }
+    report.duration_secs = start.elapsed().as_secs_f64();
+    report
+}
@@ -0,0 +1,272 @@
+//! Disk footprint of a project, checked before generating it
+//!
+//! A run that fills the disk used to fail late, with an IO error from
+//! whichever write came last. [`AssetChecklist::for_project`] lists what a
+//! run of the project writes: the design documents with their checkpoint
+//! and history copies, the source images of its sprites, tilesets and
+//! interface, and its music and sound effects. Images are sized from their
+//! dimensions and audio from its duration, rounding up, so the estimate
+//! errs on the large side.
+//!
+//! [`ensure_disk_space`] compares the estimate with the space left on the
+//! disk of the project directory, and refuses to start when it doesn't fit,
+//! naming another location that has room when there is one.
+
+use anyhow::Result;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use sysinfo::Disks;
+
+use crate::metaprompts::DESIGN_STEPS;
+use crate::wizard::config::ProjectConfig;
+
+/// Side of the images requested from the image model
+const SOURCE_IMAGE_SIZE: u32 = 1024;
+
+/// Bytes of a design document, generously
+const DOCUMENT_BYTES: u64 = 32 * 1024;
+
+/// Copies kept of every design document: the document, its checkpoint
+/// entry and its history version
+const DOCUMENT_COPIES: u32 = 3;
+
+/// Audio is written as 16-bit stereo WAV at this rate
+const AUDIO_SAMPLE_RATE: u64 = 44_100;
+
+/// Length of a music track in seconds
+const TRACK_SECONDS: f32 = 90.0;
+
+/// Length of a sound effect in seconds
+const EFFECT_SECONDS: f32 = 1.0;
+
+/// Sound effects of a project, e.g. menu blips, hits and pickups
+const SOUND_EFFECTS: u32 = 24;
+
+/// Interface elements of a project: dialog and menu frames, status bars
+const UI_ELEMENTS: u32 = 6;
+
+/// Characters and level themes assumed when the project names fewer
+const MIN_CHARACTERS: u32 = 4;
+const MIN_LEVEL_THEMES: u32 = 3;
+
+/// Headroom kept on top of the estimate, for the cache and temporary files
+const HEADROOM: f64 = 1.25;
+
+/// What a checklist item is, and how large one of it is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AssetKind {
+    Document,
+    Image { width: u32, height: u32 },
+    Audio { seconds: f32 },
+}
+
+impl AssetKind {
+    /// Bytes one asset of the kind takes on disk. PNG images are counted at
+    /// half their RGBA size, which generated art rarely compresses below.
+    pub fn bytes(self) -> u64 {
+        match self {
+            AssetKind::Document => DOCUMENT_BYTES,
+            AssetKind::Image { width, height } => u64::from(width) * u64::from(height) * 4 / 2,
+            AssetKind::Audio { seconds } => {
+                (f64::from(seconds) * (AUDIO_SAMPLE_RATE * 2 * 2) as f64).ceil() as u64 + 44
+            }
+        }
+    }
+}
+
+/// Assets of one sort a run writes
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChecklistItem {
+    pub name: String,
+    pub count: u32,
+    pub kind: AssetKind,
+}
+
+impl ChecklistItem {
+    pub fn new(name: impl Into<String>, count: u32, kind: AssetKind) -> Self {
+        Self {
+            name: name.into(),
+            count,
+            kind,
+        }
+    }
+
+    pub fn bytes(&self) -> u64 {
+        u64::from(self.count) * self.kind.bytes()
+    }
+}
+
+/// Everything a run of a project writes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AssetChecklist {
+    pub items: Vec<ChecklistItem>,
+}
+
+impl AssetChecklist {
+    /// Assets of a run of the project: a sprite per character, with a
+    /// portrait when the game has dialogue, a tileset and a music track per
+    /// level theme, plus a title track, the style guide, the interface and
+    /// the sound effects every game has
+    pub fn for_project(config: &ProjectConfig) -> Self {
+        let characters = (config.ai_context.character_concepts.len() as u32).max(MIN_CHARACTERS);
+        let themes = (config.ai_context.level_themes.len() as u32).max(MIN_LEVEL_THEMES);
+        let source = AssetKind::Image {
+            width: SOURCE_IMAGE_SIZE,
+            height: SOURCE_IMAGE_SIZE,
+        };
+
+        let mut items = vec![
+            ChecklistItem::new(
+                "Design documents",
+                DESIGN_STEPS.len() as u32 * DOCUMENT_COPIES,
+                AssetKind::Document,
+            ),
+            ChecklistItem::new("Style guide", 1, source),
+            ChecklistItem::new("Character sprites", characters, source),
+        ];
+        if config.features.dialogue_system.is_some() {
+            items.push(ChecklistItem::new("Portraits", characters, source));
+        }
+        items.extend([
+            ChecklistItem::new("Tilesets", themes, source),
+            ChecklistItem::new("Interface", UI_ELEMENTS, source),
+            ChecklistItem::new(
+                "Music",
+                themes + 1,
+                AssetKind::Audio {
+                    seconds: TRACK_SECONDS,
+                },
+            ),
+            ChecklistItem::new(
+                "Sound effects",
+                SOUND_EFFECTS,
+                AssetKind::Audio {
+                    seconds: EFFECT_SECONDS,
+                },
+            ),
+        ]);
+        Self { items }
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.items.iter().map(ChecklistItem::bytes).sum()
+    }
+
+    /// Space to have free before starting: the total with some headroom
+    pub fn required_bytes(&self) -> u64 {
+        (self.total_bytes() as f64 * HEADROOM).ceil() as u64
+    }
+}
+
+/// Size in bytes for people, e.g. "12.3 MB"
+pub struct ByteSize(pub u64);
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+        let mut size = self.0 as f64;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        if unit == 0 {
+            write!(f, "{} {}", self.0, UNITS[0])
+        } else {
+            write!(f, "{size:.1} {}", UNITS[unit])
+        }
+    }
+}
+
+/// Error a run refuses to start with when its project doesn't fit on disk
+#[derive(Debug, Clone, thiserror::Error)]
+pub struct InsufficientSpace {
+    pub dir: PathBuf,
+    pub required: u64,
+    pub available: u64,
+    /// A location with enough free space, if any was found
+    pub suggestion: Option<PathBuf>,
+}
+
+impl fmt::Display for InsufficientSpace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Not enough disk space for {}: the project needs about {} but only {} is free",
+            self.dir.display(),
+            ByteSize(self.required),
+            ByteSize(self.available)
+        )?;
+        match &self.suggestion {
+            Some(suggestion) => write!(
+                f,
+                ". Free up space or choose another base directory, e.g. under {}",
+                suggestion.display()
+            ),
+            None => write!(f, ". Free up space or choose another base directory"),
+        }
+    }
+}
+
+/// Disk holding `dir`, the one with the longest mount point above it
+fn disk_of<'a>(disks: &'a Disks, dir: &Path) -> Option<&'a sysinfo::Disk> {
+    // The directory may not exist yet; its nearest existing ancestor is on
+    // the same disk
+    let existing = dir.ancestors().find(|dir| dir.exists())?;
+    let dir = existing.canonicalize().ok()?;
+    disks
+        .list()
+        .iter()
+        .filter(|disk| dir.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+}
+
+/// Bytes free on the disk of `dir`, or `None` when the disk isn't known
+pub fn available_space(dir: &Path) -> Option<u64> {
+    let disks = Disks::new_with_refreshed_list();
+    disk_of(&disks, dir).map(sysinfo::Disk::available_space)
+}
+
+/// Fail with [`InsufficientSpace`] unless the disk of `dir` has room for
+/// the checklist. A disk that can't be looked up isn't checked.
+pub fn ensure_disk_space(checklist: &AssetChecklist, dir: &Path) -> Result<()> {
+    let disks = Disks::new_with_refreshed_list();
+    let required = checklist.required_bytes();
+    let Some(disk) = disk_of(&disks, dir) else {
+        tracing::warn!(
+            "Couldn't find the disk of {} to check for {} of free space",
+            dir.display(),
+            ByteSize(required)
+        );
+        return Ok(());
+    };
+    let available = disk.available_space();
+    if available >= required {
+        return Ok(());
+    }
+
+    // The user's own directories first, then any other writable disk
+    let suggestion = [dirs::data_dir(), dirs::document_dir(), dirs::home_dir()]
+        .into_iter()
+        .flatten()
+        .chain(
+            disks
+                .list()
+                .iter()
+                .filter(|disk| !disk.is_read_only())
+                .map(|disk| disk.mount_point().to_path_buf()),
+        )
+        .find(|candidate| {
+            disk_of(&disks, candidate).is_some_and(|candidate_disk| {
+                candidate_disk.mount_point() != disk.mount_point()
+                    && candidate_disk.available_space() >= required
+            })
+        });
+    Err(InsufficientSpace {
+        dir: dir.to_path_buf(),
+        required,
+        available,
+        suggestion,
+    }
+    .into())
+}
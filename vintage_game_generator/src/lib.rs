@@ -1,10 +1,17 @@
 // lib.rs
+pub mod batch;
+pub mod footprint;
 pub mod metaprompts;
+pub mod progress;
+#[cfg(feature = "server")]
+pub mod server;
 pub mod vintage_games;
 pub mod wizard;
 
 pub use metaprompts::{GameConfig, GameGenerator, GenerationPhase, GenerationProgress};
 
+pub use progress::{ProgressBroadcast, ProgressEvent};
+
 pub use wizard::{AppDirectories, AppMode, AppState, WizardPlugin};
 
 // Re-export common types for Tauri frontend
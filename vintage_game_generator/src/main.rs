@@ -1,9 +1,18 @@
 use bevy::prelude::*;
 use bevy_egui::EguiPlugin;
 use clap::Parser;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
-use vintage_ai_client::AiConfig;
+use vintage_ai_client::azure::{self, AzureConfig};
+use vintage_ai_client::credentials::{self, Credentials, KeyRotation, KeySource};
+use vintage_ai_client::gemini::{self, GeminiConfig};
+use vintage_ai_client::mock::MOCK_PROVIDER;
+use vintage_ai_client::routing::{ModelRoute, RoutingPolicy, TaskCategory};
+use vintage_ai_client::warmup::{self, WarmupPlan, WarmupStatus};
+use vintage_ai_client::{AiConfig, AiService};
+use vintage_game_generator::batch;
+use vintage_game_generator::vintage_games::database::{self, GameDatabase, USER_GAMES_FILE};
+use vintage_game_generator::wizard::legacy_import;
 use vintage_game_generator::wizard::{AppDirectories, AppMode, WizardPlugin};
 
 #[derive(Parser, Debug)]
@@ -29,6 +38,34 @@ struct Args {
     #[arg(short = 'g', long = "generate", conflicts_with = "list_mode")]
     generate_mode: bool,
 
+    /// Generate every project.toml found below this directory without the GUI
+    #[arg(long = "batch", conflicts_with_all = &["list_mode", "project_dir", "config_file"])]
+    batch: Option<PathBuf>,
+
+    /// Projects generated at the same time in batch mode
+    #[arg(short = 'j', long = "jobs", default_value = "1", requires = "batch")]
+    jobs: usize,
+
+    /// Pre-generate common assets into the shared cache without the GUI, from
+    /// this plan or warmup.toml in the base directory (built-in list if absent)
+    #[arg(long = "warm-cache", conflicts_with_all = &["list_mode", "batch", "project_dir", "config_file"])]
+    warm_cache: Option<Option<PathBuf>>,
+
+    /// Import a project made before the rename, or every one of them below
+    /// this directory, into the base directory, then exit
+    #[arg(long = "import-legacy", value_name = "DIR", conflicts_with_all = &["list_mode", "batch", "project_dir", "config_file"])]
+    import_legacy: Option<PathBuf>,
+
+    /// Game database to blend from in place of the embedded timeline; the
+    /// era packs and the games added in user_games.json are still loaded
+    #[arg(long = "game-database", value_name = "FILE")]
+    game_database: Option<PathBuf>,
+
+    /// Serve the HTTP API on this address instead of opening the GUI
+    #[cfg(feature = "server")]
+    #[arg(long = "serve", conflicts_with_all = &["list_mode", "batch"])]
+    serve: Option<std::net::SocketAddr>,
+
     // AI Configuration
     /// Text generation model (e.g., gpt-4, gpt-3.5-turbo, claude-3-opus)
     #[arg(long = "text-model", default_value = "gpt-4")]
@@ -70,10 +107,59 @@ struct Args {
     #[arg(long = "image-size", default_value = "1024x1024")]
     image_size: String,
 
-    /// AI provider (openai, anthropic)
+    /// Have a vision model critique every image attempt, at the cost of a
+    /// chat request per attempt
+    #[arg(long = "vision-critique")]
+    vision_critique: bool,
+
+    /// AI provider (openai, azure, gemini, anthropic)
     #[arg(long = "ai-provider", default_value = "openai")]
     ai_provider: String,
 
+    /// Azure OpenAI resource endpoint; selects the azure provider
+    #[arg(long = "azure-endpoint", conflicts_with_all = &["ai_provider", "offline"])]
+    azure_endpoint: Option<String>,
+
+    /// Azure OpenAI API version
+    #[arg(long = "azure-api-version", default_value = azure::DEFAULT_API_VERSION)]
+    azure_api_version: String,
+
+    /// Azure deployment serving a model, as MODEL=DEPLOYMENT; repeat for
+    /// each model. Unmapped models use the deployment of the same name.
+    #[arg(long = "azure-deployment", value_name = "MODEL=DEPLOYMENT", value_parser = parse_model_mapping)]
+    azure_deployment: Vec<(String, String)>,
+
+    /// Gemini model answering requests for a model, as MODEL=GEMINI_MODEL;
+    /// repeat for each model. Applies to the gemini provider.
+    #[arg(long = "gemini-model", value_name = "MODEL=GEMINI_MODEL", value_parser = parse_model_mapping)]
+    gemini_model: Vec<(String, String)>,
+
+    /// Model serving a category of tasks (brainstorm, draft, description,
+    /// code, thumbnail, key_art), as CATEGORY=MODEL[,FALLBACK...]; repeat
+    /// for each category. Unrouted categories keep their default models.
+    #[arg(long = "route", value_name = "CATEGORY=MODEL[,FALLBACK...]", value_parser = parse_route)]
+    route: Vec<(TaskCategory, ModelRoute)>,
+
+    /// Answer with canned fixtures and placeholder assets instead of an AI
+    /// provider, so no API key is needed
+    #[arg(long = "offline", conflicts_with = "ai_provider")]
+    offline: bool,
+
+    /// Read an API key of the provider from this keyring account instead of
+    /// the environment; repeat to rotate through several keys
+    #[arg(long = "keyring", value_name = "ACCOUNT", conflicts_with = "offline")]
+    keyring: Vec<String>,
+
+    /// How several API keys take turns: failover uses the next key once one
+    /// is rejected, round-robin spreads requests over all of them
+    #[arg(long = "key-rotation", default_value = "failover", value_parser = ["failover", "round-robin"])]
+    key_rotation: String,
+
+    /// Store an API key read from stdin in the keyring under this account,
+    /// then exit
+    #[arg(long = "store-key", value_name = "ACCOUNT")]
+    store_key: Option<String>,
+
     /// Enable AI response caching (default: true)
     #[arg(long = "cache", default_value = "true")]
     cache: bool,
@@ -83,14 +169,81 @@ struct Args {
     ai_timeout: u64,
 }
 
+/// `MODEL=NAME` of `--azure-deployment` and `--gemini-model`
+fn parse_model_mapping(text: &str) -> Result<(String, String), String> {
+    match text.split_once('=') {
+        Some((model, name)) if !model.is_empty() && !name.is_empty() => {
+            Ok((model.to_string(), name.to_string()))
+        }
+        _ => Err(format!("expected MODEL=NAME, got '{text}'")),
+    }
+}
+
+/// `CATEGORY=MODEL[,FALLBACK...]` of `--route`
+fn parse_route(text: &str) -> Result<(TaskCategory, ModelRoute), String> {
+    let (category, models) = parse_model_mapping(text)?;
+    let category = TaskCategory::from_name(&category)
+        .ok_or_else(|| format!("unknown task category '{category}'"))?;
+    let mut models = models
+        .split(',')
+        .map(str::trim)
+        .filter(|model| !model.is_empty());
+    let model = models
+        .next()
+        .ok_or_else(|| format!("expected a model for '{}'", category.name()))?;
+    // Keep the default quality, so key art stays HD on another model
+    let route = ModelRoute {
+        model: model.to_string(),
+        fallbacks: models.map(str::to_string).collect(),
+        ..category.default_route()
+    };
+    Ok((category, route))
+}
+
 // Create AiConfig from command line args
 fn create_ai_config(args: &Args) -> AiConfig {
+    let rotation = if args.key_rotation == "round-robin" {
+        KeyRotation::RoundRobin
+    } else {
+        KeyRotation::Failover
+    };
+    let azure = args.azure_endpoint.as_ref().map(|endpoint| {
+        args.azure_deployment.iter().fold(
+            AzureConfig::new(endpoint).with_api_version(&args.azure_api_version),
+            |azure, (model, deployment)| azure.with_deployment(model, deployment),
+        )
+    });
+    let ai_provider = if args.offline {
+        MOCK_PROVIDER.to_string()
+    } else if azure.is_some() {
+        azure::AZURE_PROVIDER.to_string()
+    } else {
+        args.ai_provider.clone()
+    };
+    let gemini = (ai_provider == gemini::GEMINI_PROVIDER).then(|| {
+        args.gemini_model
+            .iter()
+            .fold(GeminiConfig::default(), |gemini, (model, gemini_model)| {
+                gemini.with_model(model, gemini_model)
+            })
+    });
+    let credentials = args.keyring.iter().fold(
+        Credentials::default().with_rotation(rotation),
+        |credentials, account| credentials.with_source(&ai_provider, KeySource::keyring(account)),
+    );
+
     AiConfig {
         // Model Selection
         text_model: args.text_model.clone(),
         image_model: args.image_model.clone(),
         audio_model: args.audio_model.clone(),
         embedding_model: "text-embedding-3-small".to_string(),
+        routing: args
+            .route
+            .iter()
+            .fold(RoutingPolicy::default(), |routing, (category, route)| {
+                routing.with_route(*category, route.clone())
+            }),
 
         // Generation Parameters
         temperature: args.temperature,
@@ -98,13 +251,18 @@ fn create_ai_config(args: &Args) -> AiConfig {
         max_tokens: args.max_tokens,
         frequency_penalty: args.frequency_penalty,
         presence_penalty: args.presence_penalty,
+        profiles: Default::default(),
 
         // Image Parameters
         image_quality: args.image_quality.clone(),
         image_size: args.image_size.clone(),
+        vision_critique: args.vision_critique,
 
         // Provider Settings
-        ai_provider: args.ai_provider.clone(),
+        ai_provider,
+        azure,
+        gemini,
+        credentials,
 
         // Cache and Performance
         cache_enabled: args.cache,
@@ -115,13 +273,175 @@ fn create_ai_config(args: &Args) -> AiConfig {
     }
 }
 
+/// Run batch mode and return the process exit code
+fn run_batch(batch_dir: &Path, ai_config: &AiConfig, jobs: usize) -> i32 {
+    tracing_subscriber::fmt::init();
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to create Tokio runtime");
+
+    let report = match runtime.block_on(batch::run_batch(batch_dir, ai_config, jobs)) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Batch failed: {e:#}");
+            return 1;
+        }
+    };
+    if let Err(e) = report.write() {
+        eprintln!("{e:#}");
+    }
+
+    let total = report.projects.len();
+    let succeeded = report.succeeded();
+    let cost = report.total_cost;
+    println!("Batch complete: {succeeded}/{total} projects generated, total cost ${cost:.4}");
+    let report_display = batch_dir.join(batch::REPORT_MARKDOWN);
+    println!("Report: {}", report_display.display());
+    if report.failed() > 0 { 1 } else { 0 }
+}
+
+/// Store the API key given on stdin in the keyring and return the process
+/// exit code
+fn run_store_key(account: &str) -> i32 {
+    let mut key = String::new();
+    if let Err(e) = std::io::stdin().read_line(&mut key) {
+        eprintln!("Failed to read the key: {e}");
+        return 1;
+    }
+    let key = key.trim();
+    if key.is_empty() {
+        eprintln!("No key given on stdin");
+        return 1;
+    }
+    match credentials::keyring_store(account, key) {
+        Ok(()) => {
+            println!("Stored the key of '{account}' in the keyring");
+            0
+        }
+        Err(e) => {
+            eprintln!("{e:#}");
+            1
+        }
+    }
+}
+
+/// Import legacy projects into `base_dir` and return the process exit code
+/// Install the database `--game-database` names, if any, with the games the
+/// user added
+fn load_game_database(file: Option<&Path>, base_dir: &Path) -> anyhow::Result<()> {
+    let mut database = match file {
+        Some(file) => GameDatabase::from_file(file)?,
+        None => GameDatabase::embedded(),
+    };
+    database.load_user_games(base_dir.join(USER_GAMES_FILE))?;
+    database::install(database);
+    Ok(())
+}
+
+fn run_import_legacy(legacy_dir: &Path, base_dir: &Path) -> i32 {
+    let projects = match legacy_import::find_legacy_projects(legacy_dir) {
+        Ok(projects) => projects,
+        Err(e) => {
+            eprintln!("{e:#}");
+            return 1;
+        }
+    };
+    if projects.is_empty() {
+        eprintln!("No legacy project found in {}", legacy_dir.display());
+        return 1;
+    }
+    let mut failed = 0;
+    for project in &projects {
+        match legacy_import::import_legacy_project(project, base_dir) {
+            Ok(config) => println!("Imported {} to {}", project.display(), config.display()),
+            Err(e) => {
+                eprintln!("Failed to import {}: {e:#}", project.display());
+                failed += 1;
+            }
+        }
+    }
+    i32::from(failed > 0)
+}
+
+/// Warm the shared cache and return the process exit code
+fn run_warm_cache(plan_file: Option<&Path>, base_dir: &Path, ai_config: &AiConfig) -> i32 {
+    tracing_subscriber::fmt::init();
+    let default_file = base_dir.join(warmup::DEFAULT_WARMUP_FILE);
+    let plan = match plan_file {
+        Some(path) => WarmupPlan::load(path),
+        None if default_file.exists() => WarmupPlan::load(&default_file),
+        None => Ok(WarmupPlan::default()),
+    };
+    let plan = match plan {
+        Ok(plan) => plan,
+        Err(e) => {
+            eprintln!("{e:#}");
+            return 1;
+        }
+    };
+    let service = match AiService::with_config(ai_config) {
+        Ok(service) => service,
+        Err(e) => {
+            eprintln!("Failed to create AI service: {e:#}");
+            return 1;
+        }
+    };
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to create Tokio runtime");
+
+    let report = runtime.block_on(warmup::warm_cache(&service, &plan));
+    for outcome in &report.outcomes {
+        if let WarmupStatus::Failed(error) = &outcome.status {
+            eprintln!("Failed: {}: {error}", outcome.asset.label());
+        }
+    }
+    println!(
+        "Cache warm-up complete: {} generated, {} already cached, {} failed, {} skipped, cost ${:.4}",
+        report.generated(),
+        report.cached(),
+        report.failed(),
+        report.skipped(),
+        report.total_cost()
+    );
+    if report.failed() > 0 { 1 } else { 0 }
+}
+
+/// Run the API server and return the process exit code
+#[cfg(feature = "server")]
+fn run_server(addr: std::net::SocketAddr, base_dir: PathBuf, ai_config: &AiConfig) -> i32 {
+    tracing_subscriber::fmt::init();
+    let pipeline =
+        vintage_game_generator::wizard::GenerationPipeline::new().with_ai_config(ai_config.clone());
+    let runtime = pipeline.runtime.clone();
+    match runtime.block_on(vintage_game_generator::server::serve(
+        addr, base_dir, pipeline, ai_config,
+    )) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Server failed: {e:#}");
+            1
+        }
+    }
+}
+
 fn main() {
     // Parse CLI arguments
     let args = Args::parse();
 
+    if let Some(account) = &args.store_key {
+        std::process::exit(run_store_key(account));
+    }
+
     // Create AI configuration from args
     let ai_config = create_ai_config(&args);
 
+    if let Some(batch_dir) = &args.batch {
+        std::process::exit(run_batch(batch_dir, &ai_config, args.jobs));
+    }
+
     // Determine mode
     let mode = if args.list_mode {
         AppMode::List
@@ -136,6 +456,24 @@ fn main() {
             .join("vintage_game_generator")
     });
 
+    if let Err(e) = load_game_database(args.game_database.as_deref(), &base_dir) {
+        eprintln!("Failed to load the game database: {e:#}");
+        std::process::exit(1);
+    }
+
+    if let Some(legacy_dir) = &args.import_legacy {
+        std::process::exit(run_import_legacy(legacy_dir, &base_dir));
+    }
+
+    if let Some(plan_file) = &args.warm_cache {
+        std::process::exit(run_warm_cache(plan_file.as_deref(), &base_dir, &ai_config));
+    }
+
+    #[cfg(feature = "server")]
+    if let Some(addr) = args.serve {
+        std::process::exit(run_server(addr, base_dir, &ai_config));
+    }
+
     // For generate mode, determine project directory
     let (project_dir, config_file) = match mode {
         AppMode::Generate => {
@@ -194,6 +532,11 @@ fn main() {
     println!("  Provider: {provider}");
     println!("  Text Model: {text_model}");
     println!("  Temperature: {temperature}");
+    let profiles = &ai_config.profiles;
+    println!(
+        "  Profiles: narrative {} / code {} / json {}",
+        profiles.narrative.temperature, profiles.code.temperature, profiles.json.temperature
+    );
     println!("  Cache: {cache_status}");
     println!();
 
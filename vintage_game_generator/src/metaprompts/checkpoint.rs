@@ -0,0 +1,169 @@
+//! Pausing, cancelling and resuming generation
+//!
+//! A [`GenerationControl`] is shared between whoever drives a run and the
+//! run itself. The run checks it before every step, waits there while the
+//! run is paused, and races every request against cancellation, so a cancel
+//! takes effect without waiting for the model to answer.
+//!
+//! Each completed step's output is recorded in a [`GenerationCheckpoint`]
+//! in the project directory as soon as it finishes. A run started with
+//! resume enabled reuses the recorded outputs and only requests the steps
+//! that are missing, so a cancelled or crashed run loses at most the step
+//! that was in flight.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// File name of the checkpoint inside a project directory
+pub const CHECKPOINT_FILE: &str = "generation-checkpoint.json";
+
+/// Error a run stops with when it is cancelled
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("Generation was cancelled")]
+pub struct Cancelled;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunState {
+    Running,
+    Paused,
+    Cancelled,
+}
+
+#[derive(Debug, Clone)]
+pub struct GenerationControl {
+    state: Arc<watch::Sender<RunState>>,
+}
+
+impl Default for GenerationControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GenerationControl {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(watch::Sender::new(RunState::Running)),
+        }
+    }
+
+    pub fn state(&self) -> RunState {
+        *self.state.borrow()
+    }
+
+    pub fn pause(&self) {
+        self.state.send_if_modified(|state| {
+            let pause = *state == RunState::Running;
+            if pause {
+                *state = RunState::Paused;
+            }
+            pause
+        });
+    }
+
+    pub fn resume(&self) {
+        self.state.send_if_modified(|state| {
+            let resume = *state == RunState::Paused;
+            if resume {
+                *state = RunState::Running;
+            }
+            resume
+        });
+    }
+
+    pub fn cancel(&self) {
+        self.state.send_replace(RunState::Cancelled);
+    }
+
+    /// Clear a cancel or pause before the next run
+    pub fn reset(&self) {
+        self.state.send_replace(RunState::Running);
+    }
+
+    /// Wait while paused; fails with [`Cancelled`] once cancelled
+    pub async fn proceed(&self) -> Result<()> {
+        let mut state = self.state.subscribe();
+        let state = state
+            .wait_for(|state| *state != RunState::Paused)
+            .await
+            .map_err(|_| Cancelled)?;
+        match *state {
+            RunState::Cancelled => Err(Cancelled.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Run the future unless the run is cancelled first
+    pub async fn run<T>(&self, future: impl Future<Output = Result<T>>) -> Result<T> {
+        let mut state = self.state.subscribe();
+        tokio::select! {
+            result = future => result,
+            _ = state.wait_for(|state| *state == RunState::Cancelled) => Err(Cancelled.into()),
+        }
+    }
+}
+
+/// Outputs of the steps a run has completed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationCheckpoint {
+    /// Game the outputs belong to
+    pub name: String,
+    pub completed: BTreeMap<String, String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl GenerationCheckpoint {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            completed: BTreeMap::new(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// The checkpoint in the project directory, if there is one
+    pub fn load(project_dir: &Path) -> Result<Option<Self>> {
+        let path = project_dir.join(CHECKPOINT_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read checkpoint {}", path.display()))?;
+        let checkpoint = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse checkpoint {}", path.display()))?;
+        Ok(Some(checkpoint))
+    }
+
+    pub fn save(&self, project_dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(project_dir)?;
+        std::fs::write(
+            project_dir.join(CHECKPOINT_FILE),
+            serde_json::to_string_pretty(self)?,
+        )
+        .context("Failed to write generation checkpoint")
+    }
+
+    /// Delete the checkpoint so the next run starts over
+    pub fn remove(project_dir: &Path) -> Result<()> {
+        let path = project_dir.join(CHECKPOINT_FILE);
+        if path.exists() {
+            std::fs::remove_file(&path).context("Failed to remove generation checkpoint")?;
+        }
+        Ok(())
+    }
+
+    pub fn output(&self, step: &str) -> Option<&str> {
+        self.completed.get(step).map(String::as_str)
+    }
+
+    pub fn record(&mut self, step: &str, output: String) {
+        self.completed.insert(step.to_string(), output);
+        self.updated_at = Utc::now();
+    }
+}
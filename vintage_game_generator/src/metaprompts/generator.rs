@@ -1,19 +1,111 @@
 use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::{Path, PathBuf};
 
-use crate::wizard::config::ProjectConfig;
+use crate::metaprompts::checkpoint::{GenerationCheckpoint, GenerationControl};
+use crate::vintage_games::Limit;
+use crate::wizard::config::{CharacterConcept, LevelTheme, ProjectConfig};
 use futures::{Stream, StreamExt};
 
 // Import from vintage_ai_client - updated to new API
 use vintage_ai_client::{
-    AiService, conversation::ConversationContext, game_types::GameConfig, text::TextConfig,
+    AiConfig, AiService,
+    conversation::{BranchComparison, ConversationContext},
+    game_assets::{MUSIC_ASSET_DIR, SPRITE_ASSET_DIR},
+    game_types::GameConfig,
+    history::ArtifactHistory,
+    image::ImageConfig,
+    slugs::{AssetCategory, SLUG_REGISTRY_FILE, SlugRegistry},
+    text::{TextConfig, with_content_language},
+    tokens::{CostEstimate, TokenStats},
 };
 
+/// Steps of a design run in order, by the id their output is
+/// checkpointed and regenerated under
+pub const DESIGN_STEPS: [&str; 4] = ["core_design", "asset_descriptions", "dialogue", "music"];
+
+/// Artifact kind of the sprites a run draws of the project's characters
+pub const SPRITE_ARTIFACT: &str = "sprite";
+
+/// Artifact kind of the music tracks a run describes for the project's
+/// level themes
+pub const TRACK_ARTIFACT: &str = "track";
+
+/// Stands in for the core design, and the musical themes, when estimating
+/// the requests written from an excerpt of them before they exist
+const SAMPLE_DESIGN: &str = "The hero sets out from a quiet village to explore ruined castles, \
+    gather allies and face the guardians of the old dungeons. ";
+
+/// Tokens and cost of one design step, worked out without requesting it
+#[derive(Debug, Clone)]
+pub struct StepEstimate {
+    pub step: &'static str,
+    pub phase: GenerationPhase,
+    pub estimate: CostEstimate,
+    /// The run reuses the step's checkpointed output
+    pub checkpointed: bool,
+}
+
+/// Estimate of a whole design run, step by step
+#[derive(Debug, Clone, Default)]
+pub struct RunEstimate {
+    pub steps: Vec<StepEstimate>,
+}
+
+impl RunEstimate {
+    pub fn total(&self) -> CostEstimate {
+        self.steps.iter().map(|step| step.estimate).sum()
+    }
+}
+
+/// Phase a design step runs in
+pub fn step_phase(step: &str) -> GenerationPhase {
+    match step {
+        "core_design" => GenerationPhase::DesigningCore,
+        "asset_descriptions" | SPRITE_ARTIFACT => GenerationPhase::GeneratingAssets,
+        "dialogue" => GenerationPhase::WritingDialogue,
+        "music" | TRACK_ARTIFACT => GenerationPhase::ComposingMusic,
+        _ => GenerationPhase::Design,
+    }
+}
+
+/// Up to `count` resolutions from a one-per-line answer, without the list
+/// markers and quotes models tend to add anyway
+pub fn parse_resolutions(text: &str, count: usize) -> Vec<String> {
+    text.lines()
+        .map(|line| {
+            line.trim()
+                .trim_start_matches(|c: char| {
+                    c.is_ascii_digit() || matches!(c, '-' | '*' | '•' | '.' | ')')
+                })
+                .trim()
+                .trim_matches('"')
+                .trim()
+        })
+        .filter(|line| !line.is_empty())
+        .take(count)
+        .map(str::to_string)
+        .collect()
+}
+
 /// Progress tracking for game generation
 #[derive(Debug, Clone)]
 pub struct GenerationProgress {
     pub phase: GenerationPhase,
     pub progress: f32, // 0.0 to 1.0
     pub message: String,
+    /// File the step has just written, if any
+    pub artifact: Option<WrittenArtifact>,
+}
+
+/// An asset file a run wrote into the project
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrittenArtifact {
+    /// What the file is, e.g. [`SPRITE_ARTIFACT`]
+    pub kind: &'static str,
+    /// Slug the asset is checkpointed and regenerated under
+    pub id: String,
+    pub path: PathBuf,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -69,16 +161,29 @@ impl ConversationState {
 
 pub struct GameGenerator {
     ai_service: AiService,
+    ai_config: AiConfig,
     project_config: Option<ProjectConfig>,
+    control: GenerationControl,
+    /// Where completed steps are checkpointed, and whether an existing
+    /// checkpoint is resumed
+    checkpoint: Option<(PathBuf, bool)>,
 }
 
 impl GameGenerator {
     pub async fn new() -> anyhow::Result<Self> {
-        let ai_service = AiService::from_env()?;
+        Self::with_ai_config(&AiConfig::default()).await
+    }
+
+    /// Create a generator whose phases use the parameter profiles in `config`
+    pub async fn with_ai_config(config: &AiConfig) -> anyhow::Result<Self> {
+        let ai_service = AiService::with_config(config)?;
 
         Ok(Self {
             ai_service,
+            ai_config: config.clone(),
             project_config: None,
+            control: GenerationControl::new(),
+            checkpoint: None,
         })
     }
 
@@ -87,6 +192,26 @@ impl GameGenerator {
         self.project_config = Some(config);
     }
 
+    /// Use a control shared with whoever drives the run, so they can
+    /// pause and cancel it
+    pub fn set_control(&mut self, control: GenerationControl) {
+        self.control = control;
+    }
+
+    pub fn control(&self) -> GenerationControl {
+        self.control.clone()
+    }
+
+    /// Checkpoint completed steps into the project directory, and use the
+    /// prompt overrides in its `prompts/`. With `resume`, steps already in
+    /// the checkpoint are not requested again; without it the checkpoint
+    /// starts over.
+    pub fn set_checkpoint_dir(&mut self, project_dir: impl Into<PathBuf>, resume: bool) {
+        let project_dir = project_dir.into();
+        self.ai_service = self.ai_service.clone().with_prompt_overrides(&project_dir);
+        self.checkpoint = Some((project_dir, resume));
+    }
+
     /// Start a game design conversation
     pub async fn start_game_design_conversation(
         &self,
@@ -161,6 +286,89 @@ impl GameGenerator {
         })
     }
 
+    /// Start a conversation about a blend with `context`, e.g. a blend
+    /// exploration context carrying the blend's analysis, and ask the first
+    /// question. Returns the conversation id and the answer.
+    pub async fn start_blend_conversation(
+        &self,
+        title: &str,
+        context: ConversationContext,
+        question: &str,
+    ) -> anyhow::Result<(String, String)> {
+        let conversation_manager = self.ai_service.conversation();
+        let conversation_id = conversation_manager
+            .start_conversation(title.to_string(), context)
+            .await?;
+        let response = conversation_manager
+            .send_message(&conversation_id, question.to_string())
+            .await?;
+        Ok((conversation_id, response))
+    }
+
+    /// Ask a follow-up question in a blend conversation
+    pub async fn continue_blend_conversation(
+        &self,
+        conversation_id: &str,
+        question: &str,
+    ) -> anyhow::Result<String> {
+        self.ai_service
+            .conversation()
+            .send_message(conversation_id, question.to_string())
+            .await
+    }
+
+    /// Fork a conversation into a new branch after the message with the id
+    pub async fn fork_conversation(
+        &self,
+        conversation_id: &str,
+        message_id: &str,
+    ) -> anyhow::Result<String> {
+        self.ai_service
+            .conversation()
+            .fork(conversation_id, message_id)
+            .await
+    }
+
+    /// Ids of the user and assistant turns the conversation still holds,
+    /// oldest first
+    pub async fn turn_ids(&self, conversation_id: &str) -> anyhow::Result<Vec<String>> {
+        let conversation = self
+            .ai_service
+            .conversation()
+            .get_conversation(conversation_id)
+            .await?;
+        Ok(conversation
+            .messages
+            .iter()
+            .filter(|m| !matches!(m.role, vintage_ai_client::conversation::MessageRole::System))
+            .map(|m| m.id.clone())
+            .collect())
+    }
+
+    /// Compare two conversation branches
+    pub async fn compare_conversations(
+        &self,
+        left_id: &str,
+        right_id: &str,
+    ) -> anyhow::Result<BranchComparison> {
+        self.ai_service
+            .conversation()
+            .compare(left_id, right_id)
+            .await
+    }
+
+    /// Merge the turns unique to `source_id` into `target_id`
+    pub async fn merge_conversations(
+        &self,
+        target_id: &str,
+        source_id: &str,
+    ) -> anyhow::Result<()> {
+        self.ai_service
+            .conversation()
+            .merge(target_id, source_id)
+            .await
+    }
+
     /// Generate full game with progress tracking
     pub async fn generate_full_game<F>(
         &self,
@@ -170,14 +378,233 @@ impl GameGenerator {
     where
         F: Fn(GenerationProgress) + Send + 'static,
     {
+        self.generate_phases(
+            &config.name,
+            None,
+            config.content_language.as_deref(),
+            progress_callback,
+        )
+        .await
+    }
+
+    /// Generate the game described by the project configuration without the
+    /// wizard conversation, as batch mode does
+    pub async fn generate_from_project<F>(&self, progress_callback: F) -> anyhow::Result<String>
+    where
+        F: Fn(GenerationProgress) + Send + 'static,
+    {
+        let (name, brief, language) = self.project_brief()?;
+        self.generate_phases(&name, Some(brief.as_str()), language, progress_callback)
+            .await
+    }
+
+    /// Tokens and cost [`GameGenerator::generate_from_project`] would spend,
+    /// without requesting anything. Every step's prompt is rendered as it
+    /// would be sent; steps written from the core design use the
+    /// checkpointed one, or a sample of the same length before it exists.
+    /// Steps a resumed run would reuse cost nothing.
+    pub async fn estimate_from_project(&self) -> anyhow::Result<RunEstimate> {
+        let (name, brief, language) = self.project_brief()?;
+        self.ai_service.prompts().ensure_variables_defined()?;
+        let checkpoint = self.open_checkpoint(&name)?;
+        let sample = SAMPLE_DESIGN.repeat(10);
+        let core_design = checkpoint.output("core_design").unwrap_or(sample.as_str());
+        let text_generator = self.ai_service.text();
+
+        let mut steps = Vec::new();
+        for step in DESIGN_STEPS {
+            let checkpointed = checkpoint.output(step).is_some();
+            let estimate = if checkpointed {
+                CostEstimate::default()
+            } else {
+                let (prompt, config) =
+                    self.step_prompt(step, &name, Some(brief.as_str()), language, core_design)?;
+                text_generator.estimate(&prompt, &config).await?
+            };
+            steps.push(StepEstimate {
+                step,
+                phase: step_phase(step),
+                estimate,
+                checkpointed,
+            });
+            match step {
+                "asset_descriptions" => steps.extend(self.estimate_sprites(&checkpoint).await?),
+                "music" => steps.extend(self.estimate_tracks(&checkpoint).await?),
+                _ => {}
+            }
+        }
+        Ok(RunEstimate { steps })
+    }
+
+    /// Cost of the sprites a run would draw, `None` when it draws none
+    async fn estimate_sprites(
+        &self,
+        checkpoint: &GenerationCheckpoint,
+    ) -> anyhow::Result<Option<StepEstimate>> {
+        let characters = self.characters();
+        let Some((dir, _)) = &self.checkpoint else {
+            return Ok(None);
+        };
+        if characters.is_empty() {
+            return Ok(None);
+        }
+        let slugs = SlugRegistry::load(dir.join(SLUG_REGISTRY_FILE))?;
+        let missing = characters
+            .iter()
+            .filter(|character| {
+                slugs
+                    .get(AssetCategory::Sprite, &character.name)
+                    .is_none_or(|id| checkpoint.output(id).is_none())
+            })
+            .count();
+        Ok(Some(StepEstimate {
+            step: SPRITE_ARTIFACT,
+            phase: step_phase(SPRITE_ARTIFACT),
+            estimate: self
+                .ai_service
+                .image()
+                .estimate(&ImageConfig::for_sprites(), missing)
+                .await,
+            checkpointed: missing == 0,
+        }))
+    }
+
+    /// Cost of the tracks a run would describe, `None` when it describes none
+    async fn estimate_tracks(
+        &self,
+        checkpoint: &GenerationCheckpoint,
+    ) -> anyhow::Result<Option<StepEstimate>> {
+        let themes = self.level_themes();
+        let Some((dir, _)) = &self.checkpoint else {
+            return Ok(None);
+        };
+        if themes.is_empty() {
+            return Ok(None);
+        }
+        let slugs = SlugRegistry::load(dir.join(SLUG_REGISTRY_FILE))?;
+        let sample = SAMPLE_DESIGN.repeat(10);
+        let music = checkpoint.output("music").unwrap_or(sample.as_str());
+        let mut estimate = CostEstimate::default();
+        let mut checkpointed = true;
+        for theme in themes {
+            if slugs
+                .get(AssetCategory::Music, &theme.name)
+                .is_some_and(|id| checkpoint.output(id).is_some())
+            {
+                continue;
+            }
+            checkpointed = false;
+            let (prompt, config) = self.track_prompt(theme, music);
+            estimate += self.ai_service.text().estimate(&prompt, &config).await?;
+        }
+        Ok(Some(StepEstimate {
+            step: TRACK_ARTIFACT,
+            phase: step_phase(TRACK_ARTIFACT),
+            estimate,
+            checkpointed,
+        }))
+    }
+
+    /// Character concepts of the project, each drawn as a sprite
+    fn characters(&self) -> &[CharacterConcept] {
+        self.project_config
+            .as_ref()
+            .map(|config| config.ai_context.character_concepts.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Level themes of the project, each given a music track
+    fn level_themes(&self) -> &[LevelTheme] {
+        self.project_config
+            .as_ref()
+            .map(|config| config.ai_context.level_themes.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Seed of the project's generation, sent with every design request
+    fn generation_seed(&self) -> Option<u64> {
+        self.project_config
+            .as_ref()
+            .map(|config| config.generation_seed)
+    }
+
+    /// Name, brief and content language of the game the project describes
+    fn project_brief(&self) -> anyhow::Result<(String, String, Option<&str>)> {
+        let config = self
+            .project_config
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No project configuration set"))?;
+        let name = config
+            .name
+            .clone()
+            .unwrap_or_else(|| config.basic_info.name.clone());
+        Ok((
+            name,
+            config.to_ai_summary(),
+            config.basic_info.content_language.as_deref(),
+        ))
+    }
+
+    /// Embedding of the text, e.g. a search query to compare against the
+    /// project's artifact index
+    pub async fn embed(&self, text: &str) -> anyhow::Result<Vec<f32>> {
+        self.ai_service
+            .embeddings()
+            .generate(text, &self.ai_config)
+            .await
+    }
+
+    /// Two or three ways to settle a conflict between the games of a blend,
+    /// e.g. "turn-based overworld, real-time bosses"
+    pub async fn suggest_conflict_resolutions(
+        &self,
+        blend: &str,
+        conflict: &str,
+        count: usize,
+    ) -> anyhow::Result<Vec<String>> {
+        let prompt = format!(
+            "We are designing a game that blends {blend}. The blend has this conflict: \
+             {conflict}\n\nSuggest {count} different design decisions that resolve it, \
+             such as \"turn-based overworld, real-time bosses\". Answer with one \
+             decision per line, each under 20 words, without numbering or commentary."
+        );
+        let config = TextConfig::for_game_description()
+            .with_profile(&self.ai_service.profiles().narrative)
+            .with_seed(self.generation_seed());
+        let output = self.ai_service.text().generate(&prompt, config).await?;
+        let resolutions = parse_resolutions(&output, count);
+        if resolutions.is_empty() {
+            anyhow::bail!("The model suggested no resolutions for {conflict}");
+        }
+        Ok(resolutions)
+    }
+
+    /// Token usage and cost of every request this generator made
+    pub async fn usage(&self) -> TokenStats {
+        self.ai_service.token_counter.lock().await.get_stats().await
+    }
+
+    async fn generate_phases<F>(
+        &self,
+        name: &str,
+        brief: Option<&str>,
+        language: Option<&str>,
+        progress_callback: F,
+    ) -> anyhow::Result<String>
+    where
+        F: Fn(GenerationProgress) + Send + 'static,
+    {
+        // Refuse to start while a prompt uses a variable nobody defines
+        self.ai_service.prompts().ensure_variables_defined()?;
         let text_generator = self.ai_service.text();
-        let text_config = TextConfig::for_game_description();
+        let mut checkpoint = self.open_checkpoint(name)?;
 
         // Initialize
         progress_callback(GenerationProgress {
             phase: GenerationPhase::Initializing,
             progress: 0.0,
             message: "Starting game generation...".to_string(),
+            artifact: None,
         });
 
         // Design core
@@ -185,14 +612,16 @@ impl GameGenerator {
             phase: GenerationPhase::DesigningCore,
             progress: 0.1,
             message: "Designing core game mechanics...".to_string(),
+            artifact: None,
         });
 
-        let core_prompt = format!(
-            "Generate the core game design document for: {}. Include mechanics, story outline, and character descriptions.",
-            config.name
-        );
-        let core_design = text_generator
-            .generate(&core_prompt, text_config.clone())
+        let (core_prompt, config) = self.step_prompt("core_design", name, brief, language, "")?;
+        let core_design = self
+            .run_step(
+                &mut checkpoint,
+                "core_design",
+                text_generator.generate(&core_prompt, config),
+            )
             .await?;
 
         // Generate assets descriptions
@@ -200,14 +629,19 @@ impl GameGenerator {
             phase: GenerationPhase::GeneratingAssets,
             progress: 0.3,
             message: "Generating asset descriptions...".to_string(),
+            artifact: None,
         });
 
-        let assets_prompt = format!(
-            "Based on this design: {}\n\nDescribe the visual assets needed: sprites, tilesets, UI elements.",
-            core_design.chars().take(1000).collect::<String>()
-        );
-        let _assets_desc = text_generator
-            .generate(&assets_prompt, text_config.clone())
+        let (assets_prompt, config) =
+            self.step_prompt("asset_descriptions", name, brief, language, &core_design)?;
+        let assets_desc = self
+            .run_step(
+                &mut checkpoint,
+                "asset_descriptions",
+                text_generator.generate(&assets_prompt, config),
+            )
+            .await?;
+        self.draw_sprites(&mut checkpoint, &assets_desc, &progress_callback)
             .await?;
 
         // Writing dialogue
@@ -215,15 +649,17 @@ impl GameGenerator {
             phase: GenerationPhase::WritingDialogue,
             progress: 0.5,
             message: "Writing character dialogue...".to_string(),
+            artifact: None,
         });
 
-        let dialogue_config = TextConfig::for_dialogue();
-        let dialogue_prompt = format!(
-            "Write sample dialogue for key characters in: {}",
-            config.name
-        );
-        let _dialogue = text_generator
-            .generate(&dialogue_prompt, dialogue_config)
+        let (dialogue_prompt, config) =
+            self.step_prompt("dialogue", name, brief, language, &core_design)?;
+        let _dialogue = self
+            .run_step(
+                &mut checkpoint,
+                "dialogue",
+                text_generator.generate(&dialogue_prompt, config),
+            )
             .await?;
 
         // Composing music descriptions
@@ -231,19 +667,28 @@ impl GameGenerator {
             phase: GenerationPhase::ComposingMusic,
             progress: 0.7,
             message: "Describing musical themes...".to_string(),
+            artifact: None,
         });
 
-        let music_prompt = format!(
-            "Describe the musical themes and sound design for: {}",
-            config.name
-        );
-        let _music = text_generator.generate(&music_prompt, text_config).await?;
+        let (music_prompt, config) =
+            self.step_prompt("music", name, brief, language, &core_design)?;
+        let music = self
+            .run_step(
+                &mut checkpoint,
+                "music",
+                text_generator.generate(&music_prompt, config),
+            )
+            .await?;
+        self.compose_tracks(&mut checkpoint, &music, &progress_callback)
+            .await?;
 
         // Finalize
+        self.control.proceed().await?;
         progress_callback(GenerationProgress {
             phase: GenerationPhase::Finalizing,
             progress: 0.9,
             message: "Finalizing game package...".to_string(),
+            artifact: None,
         });
 
         // Complete
@@ -251,11 +696,414 @@ impl GameGenerator {
             phase: GenerationPhase::Complete,
             progress: 1.0,
             message: "Game generation complete!".to_string(),
+            artifact: None,
         });
 
         Ok(core_design)
     }
 
+    /// Run one step of an earlier run again, e.g. to redo the dialogue,
+    /// without repeating the others. The core design it builds on is taken
+    /// from the checkpoint rather than requested again. A `tweak` is added
+    /// to the step's prompt together with the output it replaces. The new
+    /// output replaces the old one in the checkpoint, and both are kept in
+    /// the project's artifact history; later steps are not updated.
+    pub async fn regenerate_step(&self, step: &str, tweak: Option<&str>) -> anyhow::Result<String> {
+        let (dir, _) = self
+            .checkpoint
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No project directory to regenerate in"))?;
+        let mut checkpoint = GenerationCheckpoint::load(dir)?
+            .ok_or_else(|| anyhow::anyhow!("Nothing has been generated for this project yet"))?;
+        let prompts = self.ai_service.prompts();
+        prompts.ensure_variables_defined()?;
+        let brief = self
+            .project_config
+            .as_ref()
+            .map(ProjectConfig::to_ai_summary);
+        let language = self
+            .project_config
+            .as_ref()
+            .and_then(|config| config.basic_info.content_language.as_deref());
+        let core_design = checkpoint.output("core_design").unwrap_or_default();
+        let (mut prompt, config) = self.step_prompt(
+            step,
+            &checkpoint.name,
+            brief.as_deref(),
+            language,
+            core_design,
+        )?;
+        if let Some(tweak) = tweak.map(str::trim).filter(|tweak| !tweak.is_empty()) {
+            if let Some(previous) = checkpoint.output(step) {
+                prompt.push_str(&format!("\n\nPrevious version:\n{previous}"));
+            }
+            let tweak = prompts.expand(tweak)?;
+            prompt.push_str(&format!("\n\nRevise it as follows: {tweak}"));
+        }
+
+        self.control.proceed().await?;
+        tracing::info!("Regenerating {step} of {}", checkpoint.name);
+        let output = self
+            .control
+            .run(self.ai_service.text().generate(&prompt, config))
+            .await?;
+        let history = ArtifactHistory::for_project(dir);
+        if let Some(previous) = checkpoint.output(step) {
+            history.record_bytes(step, previous.as_bytes(), "md", None, "")?;
+        }
+        history.record_bytes(
+            step,
+            output.as_bytes(),
+            "md",
+            None,
+            tweak.unwrap_or_default(),
+        )?;
+        checkpoint.record(step, output.clone());
+        checkpoint.save(dir)?;
+        Ok(output)
+    }
+
+    /// Draw a sprite of every character concept of the project, described
+    /// with an excerpt of the asset descriptions. Sprites are named through
+    /// the project's slug registry, written under `assets/sprites/` and
+    /// checkpointed like the design steps, so a resumed run only draws the
+    /// missing ones. Runs without a project directory have nowhere to write
+    /// them and draw none.
+    async fn draw_sprites<F>(
+        &self,
+        checkpoint: &mut GenerationCheckpoint,
+        asset_descriptions: &str,
+        progress_callback: &F,
+    ) -> anyhow::Result<()>
+    where
+        F: Fn(GenerationProgress),
+    {
+        let Some((dir, _)) = &self.checkpoint else {
+            return Ok(());
+        };
+        let characters = self.characters();
+        let slugs_path = dir.join(SLUG_REGISTRY_FILE);
+        let mut slugs = SlugRegistry::load(&slugs_path)?;
+
+        for (n, character) in characters.iter().enumerate() {
+            self.control.proceed().await?;
+            let id = slugs.slug(AssetCategory::Sprite, &character.name);
+            slugs.save(&slugs_path)?;
+            let path = asset_path(dir, SPRITE_ASSET_DIR, &id, "png");
+            if checkpoint.output(&id).is_none() || !path.is_file() {
+                let sprite = self
+                    .draw_sprite(character, asset_descriptions, None)
+                    .await?;
+                write_asset(dir, checkpoint, &path, &id, &sprite, "")?;
+            }
+            progress_callback(GenerationProgress {
+                phase: GenerationPhase::GeneratingAssets,
+                progress: 0.3 + 0.2 * (n + 1) as f32 / characters.len() as f32,
+                message: format!("Drew {}", character.name),
+                artifact: Some(WrittenArtifact {
+                    kind: SPRITE_ARTIFACT,
+                    id,
+                    path,
+                }),
+            });
+        }
+        Ok(())
+    }
+
+    /// Draw a character's sprite unless cancelled, revised by the `tweak`
+    /// when there is one
+    async fn draw_sprite(
+        &self,
+        character: &CharacterConcept,
+        asset_descriptions: &str,
+        tweak: Option<&str>,
+    ) -> anyhow::Result<Vec<u8>> {
+        let mut description = format!(
+            "{}, the {}: {}\n\nFrom the game's asset notes: {}",
+            character.name,
+            character.role,
+            character.description,
+            asset_descriptions.chars().take(500).collect::<String>()
+        );
+        if let Some(tweak) = tweak {
+            description.push_str(&format!("\n\nRevise it as follows: {tweak}"));
+        }
+        self.control
+            .run(
+                self.ai_service
+                    .image()
+                    .generate_sprite("character", &description, None),
+            )
+            .await
+    }
+
+    /// Describe a music track for every level theme of the project, written
+    /// from an excerpt of the musical themes. Tracks are named through the
+    /// project's slug registry, written under `assets/music/` and
+    /// checkpointed like the sprites.
+    async fn compose_tracks<F>(
+        &self,
+        checkpoint: &mut GenerationCheckpoint,
+        music: &str,
+        progress_callback: &F,
+    ) -> anyhow::Result<()>
+    where
+        F: Fn(GenerationProgress),
+    {
+        let Some((dir, _)) = &self.checkpoint else {
+            return Ok(());
+        };
+        let themes = self.level_themes();
+        let slugs_path = dir.join(SLUG_REGISTRY_FILE);
+        let mut slugs = SlugRegistry::load(&slugs_path)?;
+
+        for (n, theme) in themes.iter().enumerate() {
+            self.control.proceed().await?;
+            let id = slugs.slug(AssetCategory::Music, &theme.name);
+            slugs.save(&slugs_path)?;
+            let path = asset_path(dir, MUSIC_ASSET_DIR, &id, "md");
+            if checkpoint.output(&id).is_none() || !path.is_file() {
+                let (prompt, config) = self.track_prompt(theme, music);
+                let track = self
+                    .control
+                    .run(self.ai_service.text().generate(&prompt, config))
+                    .await?;
+                write_asset(dir, checkpoint, &path, &id, track.as_bytes(), "")?;
+            }
+            progress_callback(GenerationProgress {
+                phase: GenerationPhase::ComposingMusic,
+                progress: 0.7 + 0.2 * (n + 1) as f32 / themes.len() as f32,
+                message: format!("Composed {}", theme.name),
+                artifact: Some(WrittenArtifact {
+                    kind: TRACK_ARTIFACT,
+                    id,
+                    path,
+                }),
+            });
+        }
+        Ok(())
+    }
+
+    /// Prompt and text settings of a level theme's track
+    fn track_prompt(&self, theme: &LevelTheme, music: &str) -> (String, TextConfig) {
+        let prompt = format!(
+            "Describe the music track for the {} level ({}): its melody, tempo, instrumentation and how it loops.\n\nFrom the game's musical themes: {}{}",
+            theme.name,
+            theme.atmosphere,
+            music.chars().take(1000).collect::<String>(),
+            self.limit_rules(&[Limit::MusicChannels])
+        );
+        let config = TextConfig::for_game_description()
+            .with_profile(&self.ai_service.profiles().narrative)
+            .with_seed(self.generation_seed());
+        (prompt, config)
+    }
+
+    /// Draw a sprite or describe a track of an earlier run again, by the
+    /// slug it was written under, without repeating the rest of the run.
+    /// The asset descriptions or musical themes it is made from are taken
+    /// from the checkpoint rather than requested again, and a `tweak` is
+    /// added to its prompt. The new file replaces the old one, and both are
+    /// kept in the project's artifact history.
+    pub async fn regenerate_asset(
+        &self,
+        id: &str,
+        tweak: Option<&str>,
+    ) -> anyhow::Result<WrittenArtifact> {
+        let (dir, _) = self
+            .checkpoint
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No project directory to regenerate in"))?;
+        let mut checkpoint = GenerationCheckpoint::load(dir)?
+            .ok_or_else(|| anyhow::anyhow!("Nothing has been generated for this project yet"))?;
+        let slugs = SlugRegistry::load(dir.join(SLUG_REGISTRY_FILE))?;
+        let entry = slugs
+            .resolve(id)
+            .ok_or_else(|| anyhow::anyhow!("Nothing named {id} has been generated"))?;
+        let tweak = tweak
+            .map(str::trim)
+            .filter(|tweak| !tweak.is_empty())
+            .map(|tweak| self.ai_service.prompts().expand(tweak))
+            .transpose()?;
+        let upstream = |step: &str| {
+            checkpoint
+                .output(step)
+                .map(str::to_string)
+                .ok_or_else(|| anyhow::anyhow!("Regenerate {step} before {id}"))
+        };
+
+        self.control.proceed().await?;
+        tracing::info!("Regenerating {id} of {}", checkpoint.name);
+        let (kind, path, output) = match entry.category {
+            AssetCategory::Sprite => {
+                let character = self
+                    .characters()
+                    .iter()
+                    .find(|character| character.name == entry.name)
+                    .ok_or_else(|| anyhow::anyhow!("{} is no longer in the project", entry.name))?;
+                let sprite = self
+                    .draw_sprite(
+                        character,
+                        &upstream("asset_descriptions")?,
+                        tweak.as_deref(),
+                    )
+                    .await?;
+                let path = asset_path(dir, SPRITE_ASSET_DIR, id, "png");
+                (SPRITE_ARTIFACT, path, sprite)
+            }
+            AssetCategory::Music => {
+                let theme = self
+                    .level_themes()
+                    .iter()
+                    .find(|theme| theme.name == entry.name)
+                    .ok_or_else(|| anyhow::anyhow!("{} is no longer in the project", entry.name))?;
+                let path = asset_path(dir, MUSIC_ASSET_DIR, id, "md");
+                let (mut prompt, config) = self.track_prompt(theme, &upstream("music")?);
+                if let Some(tweak) = &tweak {
+                    if let Ok(previous) = std::fs::read_to_string(&path) {
+                        prompt.push_str(&format!("\n\nPrevious version:\n{previous}"));
+                    }
+                    prompt.push_str(&format!("\n\nRevise it as follows: {tweak}"));
+                }
+                let track = self
+                    .control
+                    .run(self.ai_service.text().generate(&prompt, config))
+                    .await?;
+                (TRACK_ARTIFACT, path, track.into_bytes())
+            }
+            category => anyhow::bail!("{id} is a {category:?} asset, which design runs never make"),
+        };
+        write_asset(
+            dir,
+            &mut checkpoint,
+            &path,
+            id,
+            &output,
+            tweak.as_deref().unwrap_or_default(),
+        )?;
+        Ok(WrittenArtifact {
+            kind,
+            id: id.to_string(),
+            path,
+        })
+    }
+
+    /// The project's hardware limits among `kept`, as rules for a prompt
+    fn limit_rules(&self, kept: &[Limit]) -> String {
+        self.project_config
+            .as_ref()
+            .map(|config| format!("\n\n{}", config.authentic_limits().rules(kept)))
+            .unwrap_or_default()
+    }
+
+    /// Prompt and text settings of a design step. Steps after the core
+    /// design are written from an excerpt of it. The text that ends up in
+    /// the game, the design document and the dialogue, is asked for in the
+    /// content `language`; asset and music descriptions only feed the asset
+    /// generators and stay in English. The project's prompt variables are
+    /// filled into the brief. Each step is held to the project's hardware
+    /// limits it touches, as firmly as its authenticity level asks, and
+    /// sent with the project's generation seed.
+    fn step_prompt(
+        &self,
+        step: &str,
+        name: &str,
+        brief: Option<&str>,
+        language: Option<&str>,
+        core_design: &str,
+    ) -> anyhow::Result<(String, TextConfig)> {
+        let profiles = self.ai_service.profiles();
+        let seed = self.generation_seed();
+        let text_config = TextConfig::for_game_description()
+            .with_profile(&profiles.narrative)
+            .with_seed(seed);
+        let rules = |kept: &[Limit]| self.limit_rules(kept);
+        Ok(match step {
+            "core_design" => {
+                let mut prompt = format!(
+                    "Generate the core game design document for: {name}. Include mechanics, story outline, and character descriptions."
+                );
+                if let Some(brief) = brief {
+                    let brief = self.ai_service.prompts().expand(brief)?;
+                    prompt.push_str(&format!("\n\n{brief}"));
+                }
+                prompt.push_str(&rules(&Limit::ALL));
+                (with_content_language(prompt, language), text_config)
+            }
+            "asset_descriptions" => (
+                format!(
+                    "Based on this design: {}\n\nDescribe the visual assets needed: sprites, tilesets, UI elements.{}",
+                    core_design.chars().take(1000).collect::<String>(),
+                    rules(&[Limit::Palette, Limit::Sprites])
+                ),
+                text_config,
+            ),
+            "dialogue" => (
+                with_content_language(
+                    format!(
+                        "Write sample dialogue for key characters in: {name}{}",
+                        rules(&[Limit::DialogueLength])
+                    ),
+                    language,
+                ),
+                TextConfig::for_dialogue()
+                    .with_profile(&profiles.narrative)
+                    .with_seed(seed),
+            ),
+            "music" => (
+                format!(
+                    "Describe the musical themes and sound design for: {name}{}",
+                    rules(&[Limit::MusicChannels])
+                ),
+                text_config,
+            ),
+            _ => anyhow::bail!("Unknown generation step '{step}'"),
+        })
+    }
+
+    /// The checkpoint to record steps in, resumed when asked to and when
+    /// it belongs to the same game
+    fn open_checkpoint(&self, name: &str) -> anyhow::Result<GenerationCheckpoint> {
+        if let Some((dir, true)) = &self.checkpoint
+            && let Some(checkpoint) = GenerationCheckpoint::load(dir)?
+            && checkpoint.name == name
+        {
+            tracing::info!(
+                "Resuming {name} with {} completed steps",
+                checkpoint.completed.len()
+            );
+            return Ok(checkpoint);
+        }
+        Ok(GenerationCheckpoint::new(name))
+    }
+
+    /// Wait out a pause, then reuse the step's checkpointed output or run
+    /// the request unless cancelled and checkpoint its output
+    async fn run_step(
+        &self,
+        checkpoint: &mut GenerationCheckpoint,
+        step: &str,
+        request: impl Future<Output = anyhow::Result<String>>,
+    ) -> anyhow::Result<String> {
+        self.control.proceed().await?;
+        if let Some(output) = checkpoint.output(step) {
+            return Ok(output.to_string());
+        }
+        let output = self.control.run(request).await?;
+        checkpoint.record(step, output.clone());
+        if let Some((dir, _)) = &self.checkpoint {
+            checkpoint.save(dir)?;
+            ArtifactHistory::for_project(dir).record_bytes(
+                step,
+                output.as_bytes(),
+                "md",
+                None,
+                "",
+            )?;
+        }
+        Ok(output)
+    }
+
     /// Load a game template
     pub async fn load_template(&self, name: &str) -> anyhow::Result<GameConfig> {
         let templates_dir = dirs::config_dir()
@@ -333,8 +1181,12 @@ impl GameGenerator {
             {
                 // Use get() for safe UTF-8 string slicing to avoid panics
                 if let Some(json_str) = response.get(start..=end)
-                    && let Ok(config) = serde_json::from_str::<GameConfig>(json_str)
+                    && let Ok(mut config) = serde_json::from_str::<GameConfig>(json_str)
                 {
+                    // Procedural content follows the project's seed
+                    if config.generation_seed.is_none() {
+                        config.generation_seed = self.generation_seed();
+                    }
                     return (true, Some(config));
                 }
             }
@@ -342,3 +1194,30 @@ impl GameGenerator {
         (false, None)
     }
 }
+
+/// Where a run writes the asset with the slug, under `assets/{asset_dir}/`
+fn asset_path(project_dir: &Path, asset_dir: &str, id: &str, extension: &str) -> PathBuf {
+    project_dir
+        .join("assets")
+        .join(asset_dir)
+        .join(format!("{id}.{extension}"))
+}
+
+/// Write an asset, keep it in the artifact history and checkpoint its path
+fn write_asset(
+    project_dir: &Path,
+    checkpoint: &mut GenerationCheckpoint,
+    path: &Path,
+    id: &str,
+    contents: &[u8],
+    note: &str,
+) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, contents)?;
+    ArtifactHistory::for_project(project_dir).record(id, path, note)?;
+    let relative = path.strip_prefix(project_dir).unwrap_or(path);
+    checkpoint.record(id, relative.display().to_string());
+    checkpoint.save(project_dir)
+}
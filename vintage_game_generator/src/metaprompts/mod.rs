@@ -1,4 +1,5 @@
 // Module declarations for metaprompts
+pub mod checkpoint;
 pub mod conversation;
 pub mod generator;
 pub mod types;
@@ -6,10 +7,16 @@ pub mod validation;
 pub mod watcher;
 
 // Re-exports for convenience
+pub use checkpoint::{Cancelled, GenerationCheckpoint, GenerationControl, RunState};
 pub use conversation::{SimpleMessage, WizardConversationState};
 pub use generator::{
-    ConversationMessage, ConversationState, GameGenerator, GenerationPhase, GenerationProgress,
+    ConversationMessage, ConversationState, DESIGN_STEPS, GameGenerator, GenerationPhase,
+    GenerationProgress, RunEstimate, SPRITE_ARTIFACT, StepEstimate, TRACK_ARTIFACT,
+    WrittenArtifact, parse_resolutions, step_phase,
 };
 pub use types::{ArtStyle, ColorPalette, GameConfig, WorldConfig};
-pub use validation::{PromptValidator, ValidationResult};
+pub use validation::{
+    IssueKind, METAPROMPT_DIRS, PromptValidator, Severity, TEMPLATE_CONTEXTS, ValidationIssue,
+    ValidationResult,
+};
 pub use watcher::{GenerationQueue, PromptWatcher};
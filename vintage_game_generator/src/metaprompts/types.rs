@@ -36,6 +36,11 @@ pub struct GameConfig {
     // Audio
     pub music_style: String,
     pub sound_effects_style: String,
+
+    // Localization
+    /// Language of the generated game's text, English when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_language: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,9 +1,135 @@
+//! Validation of metaprompt templates
+//!
+//! Besides checking a prompt's syntax and the JSON and Rust blocks it
+//! contains, the validator lints the variables of the metaprompts the
+//! generator renders. [`TEMPLATE_CONTEXTS`] documents the context each one
+//! is rendered with; a template using a name outside its context is an
+//! error, since it would render empty, and a context variable the template
+//! never reads is a warning.
+
 use anyhow::{Context, Result};
 use minijinja::Environment;
 use serde_json::Value;
-use std::path::Path;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 use syn::{File as SynFile, parse_str};
 
+/// Bundled metaprompt directories, relative to the working directory
+pub const METAPROMPT_DIRS: &[&str] = &[
+    "metaprompts",
+    "vintage_game_generator/metaprompts",
+    "crates/vintage_game_generator/metaprompts",
+];
+
+/// A variable a metaprompt is rendered with
+#[derive(Debug, Clone, Copy)]
+pub struct ContextVariable {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Only passed when it's available, so templates may ignore it
+    pub optional: bool,
+}
+
+/// The context a metaprompt is rendered with
+#[derive(Debug, Clone, Copy)]
+pub struct TemplateContext {
+    /// File stem of the template
+    pub template: &'static str,
+    pub variables: &'static [ContextVariable],
+}
+
+const fn var(name: &'static str, description: &'static str) -> ContextVariable {
+    ContextVariable {
+        name,
+        description,
+        optional: false,
+    }
+}
+
+const fn optional(name: &'static str, description: &'static str) -> ContextVariable {
+    ContextVariable {
+        name,
+        description,
+        optional: true,
+    }
+}
+
+const PROJECT: &str = "The wizard's project configuration";
+const CONFIG: &str = "Game configuration extracted from the design conversation";
+const STYLE_GUIDE: &str = "Style guide written by the style step";
+const WORLD: &str = "World design written by the world step";
+
+/// Context of every metaprompt the generator renders
+pub const TEMPLATE_CONTEXTS: &[TemplateContext] = &[
+    TemplateContext {
+        template: "01_design",
+        variables: &[optional("project", PROJECT)],
+    },
+    TemplateContext {
+        template: "02_style",
+        variables: &[optional("project", PROJECT), var("config", CONFIG)],
+    },
+    TemplateContext {
+        template: "03_world",
+        variables: &[
+            optional("project", PROJECT),
+            var("config", CONFIG),
+            var("style_guide", STYLE_GUIDE),
+        ],
+    },
+    TemplateContext {
+        template: "03_bosses",
+        variables: &[
+            optional("project", PROJECT),
+            var("config", CONFIG),
+            var("world", WORLD),
+            var("style_guide", STYLE_GUIDE),
+        ],
+    },
+    TemplateContext {
+        template: "03_postgame",
+        variables: &[
+            var("config", CONFIG),
+            var("world", WORLD),
+            var("bosses", "Bosses designed by the bosses step"),
+            var("encounters", "Encounter tables balanced for the world"),
+            var(
+                "superboss_hp_ratio",
+                "Minimum HP of a superboss relative to the strongest story boss",
+            ),
+        ],
+    },
+    TemplateContext {
+        template: "04_ai_systems",
+        variables: &[optional("config", CONFIG)],
+    },
+    TemplateContext {
+        template: "blend_game_design",
+        variables: &[
+            var(
+                "blend",
+                "Genres, mechanics and styles blended from the source games",
+            ),
+            var("source_games", "The games the blend was made from"),
+            optional(
+                "conflict_resolutions",
+                "How the designer chose to settle the blend's conflicts",
+            ),
+        ],
+    },
+    TemplateContext {
+        template: "code_combat",
+        variables: &[var("config", CONFIG), var("style_guide", STYLE_GUIDE)],
+    },
+];
+
+/// The context the template `name` is rendered with, if it's a known metaprompt
+pub fn template_context(name: &str) -> Option<&'static TemplateContext> {
+    TEMPLATE_CONTEXTS
+        .iter()
+        .find(|context| context.template == name)
+}
+
 pub struct PromptValidator {
     template_env: Environment<'static>,
 }
@@ -23,52 +149,148 @@ impl PromptValidator {
 
     pub async fn validate_prompt(&self, prompt_path: &Path) -> Result<ValidationResult> {
         let content = std::fs::read_to_string(prompt_path).context("Failed to read prompt file")?;
+        Ok(self.validate_source(prompt_path, &content))
+    }
 
-        let mut result = ValidationResult {
-            path: prompt_path.to_path_buf(),
-            valid: true,
-            errors: Vec::new(),
-            warnings: Vec::new(),
-        };
+    /// Validate the template `content` read from `prompt_path`, whose file
+    /// stem names its context
+    pub fn validate_source(&self, prompt_path: &Path, content: &str) -> ValidationResult {
+        let mut result = ValidationResult::new(prompt_path);
 
-        // Check if it's valid Jinja2 syntax
-        match self.template_env.render_str(&content, ()) {
-            Ok(_) => {}
+        // Check if it's valid Jinja2 syntax, and lint its variables
+        match self.template_env.template_from_str(content) {
+            Ok(template) => {
+                let used: BTreeSet<String> =
+                    template.undeclared_variables(false).into_iter().collect();
+                let context = prompt_path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .and_then(template_context);
+                if let Some(context) = context {
+                    self.lint_variables(context, &used, &mut result);
+                }
+            }
             Err(e) => {
-                result.valid = false;
-                result.errors.push(format!("Template syntax error: {e}"));
+                result.push(
+                    Severity::Error,
+                    IssueKind::Syntax,
+                    None,
+                    format!("Template syntax error: {e}"),
+                );
             }
         }
 
         // Check for required variables
         if !content.contains("{{") && !content.contains("{%") {
-            result
-                .warnings
-                .push("No template variables found - is this a static prompt?".to_string());
+            result.push(
+                Severity::Warning,
+                IssueKind::Content,
+                None,
+                "No template variables found - is this a static prompt?",
+            );
         }
 
         // Check prompt length
         let word_count = content.split_whitespace().count();
         if word_count > 4000 {
-            result.warnings.push(format!(
-                "Prompt is very long ({word_count} words) - consider splitting"
-            ));
+            result.push(
+                Severity::Warning,
+                IssueKind::Content,
+                None,
+                format!("Prompt is very long ({word_count} words) - consider splitting"),
+            );
         }
 
         // Check for common issues
         if content.contains("TODO") || content.contains("FIXME") {
-            result
-                .warnings
-                .push("Contains TODO/FIXME markers".to_string());
+            result.push(
+                Severity::Warning,
+                IssueKind::Content,
+                None,
+                "Contains TODO/FIXME markers",
+            );
         }
 
         // Validate JSON blocks if present
-        self.validate_json_blocks(&content, &mut result);
+        self.validate_json_blocks(content, &mut result);
+
+        // Validate Rust blocks if present
+        self.validate_rust_blocks(content, &mut result);
+
+        result
+    }
 
-        // Validate Rust code blocks if present
-        self.validate_rust_blocks(&content, &mut result);
+    /// Compare the names a template uses against its documented context
+    fn lint_variables(
+        &self,
+        context: &TemplateContext,
+        used: &BTreeSet<String>,
+        result: &mut ValidationResult,
+    ) {
+        let globals: BTreeSet<String> = self
+            .template_env
+            .globals()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        for name in used {
+            if globals.contains(name) || context.variables.iter().any(|v| v.name == name) {
+                continue;
+            }
+            let known: Vec<&str> = context.variables.iter().map(|v| v.name).collect();
+            result.push(
+                Severity::Error,
+                IssueKind::UndefinedVariable,
+                Some(name.clone()),
+                format!(
+                    "`{name}` isn't in the context of {} (available: {})",
+                    context.template,
+                    known.join(", ")
+                ),
+            );
+        }
+        for variable in context.variables {
+            if !variable.optional && !used.contains(variable.name) {
+                result.push(
+                    Severity::Warning,
+                    IssueKind::UnusedVariable,
+                    Some(variable.name.to_string()),
+                    format!(
+                        "`{}` is never used ({})",
+                        variable.name, variable.description
+                    ),
+                );
+            }
+        }
+    }
 
-        Ok(result)
+    /// Validate the metaprompts the generator renders, taking each from the
+    /// first of `dirs` that has it
+    pub fn validate_metaprompts(&self, dirs: &[PathBuf]) -> Vec<ValidationResult> {
+        let mut results = Vec::new();
+        for context in TEMPLATE_CONTEXTS {
+            let file_name = format!("{}.jinja", context.template);
+            let Some(path) = dirs
+                .iter()
+                .map(|dir| dir.join(&file_name))
+                .find(|path| path.is_file())
+            else {
+                continue;
+            };
+            match std::fs::read_to_string(&path) {
+                Ok(content) => results.push(self.validate_source(&path, &content)),
+                Err(e) => {
+                    let mut result = ValidationResult::new(&path);
+                    result.push(
+                        Severity::Error,
+                        IssueKind::Content,
+                        None,
+                        format!("Failed to read prompt file: {e}"),
+                    );
+                    results.push(result);
+                }
+            }
+        }
+        results
     }
 
     fn validate_json_blocks(&self, content: &str, result: &mut ValidationResult) {
@@ -84,7 +306,12 @@ impl PromptValidator {
                 in_json = false;
                 // Validate the JSON
                 if let Err(e) = serde_json::from_str::<Value>(&json_content) {
-                    result.errors.push(format!("Invalid JSON block: {e}"));
+                    result.push(
+                        Severity::Error,
+                        IssueKind::Content,
+                        None,
+                        format!("Invalid JSON block: {e}"),
+                    );
                 }
             } else if in_json {
                 json_content.push_str(line);
@@ -126,11 +353,16 @@ impl PromptValidator {
                                     }
                                     Err(_) => {
                                         // Report the original file parsing error
-                                        result.warnings.push(format!(
-                                            "Rust code block at line {} has syntax issues: {}",
-                                            line_num - rust_content.lines().count(),
-                                            file_err
-                                        ));
+                                        result.push(
+                                            Severity::Warning,
+                                            IssueKind::Content,
+                                            None,
+                                            format!(
+                                                "Rust code block at line {} has syntax issues: {}",
+                                                line_num - rust_content.lines().count(),
+                                                file_err
+                                            ),
+                                        );
                                     }
                                 }
                             }
@@ -140,19 +372,29 @@ impl PromptValidator {
 
                 // Check for common Rust issues
                 if rust_content.contains("unwrap()") {
-                    result.warnings.push("Rust code uses unwrap() - consider using ? operator or proper error handling".to_string());
+                    result.push(
+                        Severity::Warning,
+                        IssueKind::Content,
+                        None,
+                        "Rust code uses unwrap() - consider using ? operator or proper error handling",
+                    );
                 }
 
                 if rust_content.contains("panic!") {
-                    result
-                        .warnings
-                        .push("Rust code contains panic! - ensure this is intentional".to_string());
+                    result.push(
+                        Severity::Warning,
+                        IssueKind::Content,
+                        None,
+                        "Rust code contains panic! - ensure this is intentional",
+                    );
                 }
 
                 if rust_content.contains("unsafe") {
-                    result.warnings.push(
-                        "Rust code contains unsafe block - document safety requirements"
-                            .to_string(),
+                    result.push(
+                        Severity::Warning,
+                        IssueKind::Content,
+                        None,
+                        "Rust code contains unsafe block - document safety requirements",
                     );
                 }
             } else if in_rust {
@@ -181,15 +423,83 @@ impl PromptValidator {
     }
 }
 
+/// How serious a validation issue is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The prompt won't render as intended
+    Error,
+    Warning,
+}
+
+/// What a validation issue is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueKind {
+    Syntax,
+    /// A name outside the template's context
+    UndefinedVariable,
+    /// A context variable the template never reads
+    UnusedVariable,
+    /// The prompt's text or embedded code blocks
+    Content,
+}
+
+/// One problem found in a prompt
+#[derive(Debug, Clone)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub kind: IssueKind,
+    /// The variable the issue is about
+    pub variable: Option<String>,
+    pub message: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct ValidationResult {
-    pub path: std::path::PathBuf,
+    pub path: PathBuf,
     pub valid: bool,
-    pub errors: Vec<String>,
-    pub warnings: Vec<String>,
+    pub issues: Vec<ValidationIssue>,
 }
 
 impl ValidationResult {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            valid: true,
+            issues: Vec::new(),
+        }
+    }
+
+    /// Record an issue; any error makes the prompt invalid
+    pub fn push(
+        &mut self,
+        severity: Severity,
+        kind: IssueKind,
+        variable: Option<String>,
+        message: impl Into<String>,
+    ) {
+        if severity == Severity::Error {
+            self.valid = false;
+        }
+        self.issues.push(ValidationIssue {
+            severity,
+            kind,
+            variable,
+            message: message.into(),
+        });
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Warning)
+    }
+
     pub fn to_markdown(&self) -> String {
         let mut md = format!("## Validation Result: {}\n\n", self.path.display());
 
@@ -199,18 +509,18 @@ impl ValidationResult {
             md.push_str("❌ **Invalid**\n\n");
         }
 
-        if !self.errors.is_empty() {
-            md.push_str("### Errors\n");
-            for error in &self.errors {
-                md.push_str(&format!("- {error}\n"));
+        for (heading, severity) in [("Errors", Severity::Error), ("Warnings", Severity::Warning)] {
+            let mut issues = self
+                .issues
+                .iter()
+                .filter(|issue| issue.severity == severity)
+                .peekable();
+            if issues.peek().is_none() {
+                continue;
             }
-            md.push('\n');
-        }
-
-        if !self.warnings.is_empty() {
-            md.push_str("### Warnings\n");
-            for warning in &self.warnings {
-                md.push_str(&format!("- {warning}\n"));
+            md.push_str(&format!("### {heading}\n"));
+            for issue in issues {
+                md.push_str(&format!("- {}\n", issue.message));
             }
             md.push('\n');
         }
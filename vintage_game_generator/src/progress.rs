@@ -0,0 +1,158 @@
+//! Progress events for subscribers outside Bevy
+//!
+//! [`ProgressUpdate`]s are published on a [`ProgressBroadcast`], a tokio
+//! broadcast channel held by the [`GenerationPipeline`], tagged with the
+//! project they belong to. Anything on the pipeline's runtime can
+//! subscribe; with the `server` feature, `GET /ws` forwards the events to
+//! WebSocket clients as JSON. Image artifacts carry a small PNG preview,
+//! base64 encoded, so a subscriber can show them without reading the
+//! project directory.
+//!
+//! [`GenerationPipeline`]: crate::wizard::pipeline::GenerationPipeline
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use image::imageops::FilterType;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::sync::broadcast;
+
+use crate::metaprompts::{GenerationProgress, WrittenArtifact};
+use crate::{GeneratedArtifact, ProgressUpdate};
+
+/// Events a subscriber may fall behind by before it starts missing them
+pub const CHANNEL_CAPACITY: usize = 256;
+
+/// Longest edge of an image preview, in pixels
+pub const PREVIEW_SIZE: u32 = 128;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressEvent {
+    /// Project id, empty for the wizard's own project
+    pub project: String,
+    #[serde(flatten)]
+    pub update: ProgressUpdate,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProgressBroadcast {
+    sender: broadcast::Sender<ProgressEvent>,
+}
+
+impl Default for ProgressBroadcast {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressBroadcast {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+
+    /// Send the update to every current subscriber. Updates published
+    /// while nobody listens are dropped.
+    pub fn publish(&self, project: &str, update: ProgressUpdate) {
+        let _ = self.sender.send(ProgressEvent {
+            project: project.to_string(),
+            update,
+        });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ProgressEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn subscribers(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl ProgressUpdate {
+    pub fn new(phase: impl Into<String>, progress: f32, message: impl Into<String>) -> Self {
+        Self {
+            phase: phase.into(),
+            step: String::new(),
+            progress,
+            message: message.into(),
+            artifact: None,
+        }
+    }
+
+    pub fn with_step(mut self, step: impl Into<String>) -> Self {
+        self.step = step.into();
+        self
+    }
+
+    pub fn with_artifact(mut self, artifact: GeneratedArtifact) -> Self {
+        self.artifact = Some(artifact);
+        self
+    }
+
+    /// The update for a run's progress, with the file the run wrote
+    pub fn from_run(update: &GenerationProgress) -> Self {
+        let progress = Self::new(
+            format!("{:?}", update.phase),
+            update.progress,
+            &update.message,
+        );
+        match &update.artifact {
+            Some(artifact) => progress
+                .with_step(&artifact.id)
+                .with_artifact(GeneratedArtifact::written(artifact)),
+            None => progress,
+        }
+    }
+}
+
+impl GeneratedArtifact {
+    /// An artifact without a preview, e.g. a document or prompt
+    pub fn file(artifact_type: &str, name: &str, path: &Path) -> Self {
+        Self {
+            artifact_type: artifact_type.to_string(),
+            name: name.to_string(),
+            path: Some(path.display().to_string()),
+            preview: None,
+        }
+    }
+
+    /// An image artifact with a base64 PNG preview of the image on disk
+    pub fn image(artifact_type: &str, name: &str, path: &Path) -> Result<Self> {
+        let image = image::open(path)
+            .with_context(|| format!("Failed to open image {}", path.display()))?;
+        Ok(Self {
+            preview: Some(encode_preview(&image)?),
+            ..Self::file(artifact_type, name, path)
+        })
+    }
+
+    /// An artifact a run wrote, with a preview when it is a PNG image. An
+    /// image that can't be read is published without one.
+    pub fn written(artifact: &WrittenArtifact) -> Self {
+        let is_png = artifact
+            .path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+        if is_png {
+            match Self::image(artifact.kind, &artifact.id, &artifact.path) {
+                Ok(image) => return image,
+                Err(e) => tracing::warn!("Publishing {} without a preview: {e:#}", artifact.id),
+            }
+        }
+        Self::file(artifact.kind, &artifact.id, &artifact.path)
+    }
+}
+
+/// The image scaled to fit [`PREVIEW_SIZE`], as base64 PNG. Pixel art is
+/// scaled with nearest neighbour and small images are kept as they are.
+pub fn encode_preview(image: &image::DynamicImage) -> Result<String> {
+    let preview = if image.width().max(image.height()) > PREVIEW_SIZE {
+        image.resize(PREVIEW_SIZE, PREVIEW_SIZE, FilterType::Nearest)
+    } else {
+        image.clone()
+    };
+    let mut png = Vec::new();
+    preview.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(png))
+}
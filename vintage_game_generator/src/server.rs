@@ -0,0 +1,308 @@
+//! HTTP API for driving generation remotely
+//!
+//! Enabled with the `server` feature and started with `--serve <addr>`. The
+//! API wraps a [`GenerationPipeline`] and runs on the pipeline's runtime, so
+//! a web frontend or chat bot can do what the wizard does:
+//!
+//! - `POST /projects` takes a project config as JSON, writes it to a new
+//!   project directory under the base directory and returns the project id
+//! - `POST /projects/{id}/generate` starts generation in the background and
+//!   answers `202 Accepted`, or `409 Conflict` while a run is in progress
+//! - `GET /projects/{id}/progress` streams [`ProgressUpdate`]s as
+//!   server-sent events until the run completes or fails
+//! - `GET /ws` upgrades to a WebSocket that receives every
+//!   [`ProgressEvent`] published on the pipeline as a JSON text message,
+//!   or only one project's with `?project={id}`
+//!
+//! Projects share the pipeline's generator, so runs are processed one at a
+//! time in the order they were started.
+
+use anyhow::Context;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path as UrlPath, Query, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{Mutex, watch};
+use vintage_ai_client::AiConfig;
+
+use crate::batch::{DESIGN_FILE, PROJECT_FILE};
+use crate::footprint::{AssetChecklist, ensure_disk_space};
+use crate::metaprompts::{GameGenerator, GenerationPhase};
+use crate::progress::{ProgressBroadcast, ProgressEvent};
+use crate::wizard::config::{ConfigManager, ProjectConfig};
+use crate::wizard::pipeline::GenerationPipeline;
+use crate::{GeneratedArtifact, ProgressUpdate};
+
+/// Phase reported when a run ends with an error
+pub const FAILED_PHASE: &str = "Failed";
+
+/// Phase reported when a run finishes
+pub const COMPLETE_PHASE: &str = "Complete";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedProject {
+    pub id: String,
+    pub project_dir: PathBuf,
+}
+
+struct ApiProject {
+    dir: PathBuf,
+    progress: watch::Sender<ProgressUpdate>,
+    running: bool,
+}
+
+#[derive(Clone)]
+struct ApiState {
+    pipeline: GenerationPipeline,
+    base_dir: PathBuf,
+    projects: Arc<Mutex<HashMap<String, ApiProject>>>,
+}
+
+/// Serve the API until the process is stopped
+pub async fn serve(
+    addr: SocketAddr,
+    base_dir: PathBuf,
+    pipeline: GenerationPipeline,
+    ai_config: &AiConfig,
+) -> anyhow::Result<()> {
+    {
+        let mut generator = pipeline.generator.lock().await;
+        if generator.is_none() {
+            *generator = Some(GameGenerator::with_ai_config(ai_config).await?);
+        }
+    }
+
+    let state = ApiState {
+        pipeline,
+        base_dir,
+        projects: Arc::new(Mutex::new(HashMap::new())),
+    };
+    let app = Router::new()
+        .route("/projects", post(create_project))
+        .route("/projects/{id}/generate", post(start_generation))
+        .route("/projects/{id}/progress", get(progress))
+        .route("/ws", get(websocket))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind {addr}"))?;
+    tracing::info!("Generator API listening on http://{addr}");
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn create_project(
+    State(state): State<ApiState>,
+    Json(mut config): Json<ProjectConfig>,
+) -> Response {
+    let id = uuid::Uuid::new_v4().to_string();
+    let dir = state.base_dir.join(&id);
+    if config.basic_info.name.is_empty()
+        && let Some(name) = &config.name
+    {
+        config.basic_info.name = name.clone();
+    }
+    config.metadata.id = id.clone();
+
+    let project_name = PROJECT_FILE.trim_end_matches(".toml");
+    let saved = ConfigManager::new(&dir, Some(project_name)).and_then(|mut manager| {
+        manager.config = config;
+        manager.save()
+    });
+    if let Err(e) = saved {
+        return error(StatusCode::INTERNAL_SERVER_ERROR, format!("{e:#}"));
+    }
+
+    let created = ProgressUpdate::new("Created", 0.0, "Waiting for generation to start");
+    state.pipeline.progress.publish(&id, created.clone());
+    let (progress, _) = watch::channel(created);
+    state.projects.lock().await.insert(
+        id.clone(),
+        ApiProject {
+            dir: dir.clone(),
+            progress,
+            running: false,
+        },
+    );
+    (
+        StatusCode::CREATED,
+        Json(CreatedProject {
+            id,
+            project_dir: dir,
+        }),
+    )
+        .into_response()
+}
+
+async fn start_generation(State(state): State<ApiState>, UrlPath(id): UrlPath<String>) -> Response {
+    let (dir, progress) = {
+        let mut projects = state.projects.lock().await;
+        let Some(project) = projects.get_mut(&id) else {
+            return error(StatusCode::NOT_FOUND, format!("Unknown project {id}"));
+        };
+        if project.running {
+            return error(StatusCode::CONFLICT, "Generation is already running".into());
+        }
+        project.running = true;
+        let reporter = Reporter {
+            id: id.clone(),
+            project: project.progress.clone(),
+            broadcast: state.pipeline.progress.clone(),
+        };
+        reporter.send(ProgressUpdate::new(
+            "Queued",
+            0.0,
+            "Waiting for the generator",
+        ));
+        (project.dir.clone(), reporter)
+    };
+
+    tokio::spawn(async move {
+        let result = generate(&state.pipeline, &dir, progress.clone()).await;
+        if let Err(e) = result {
+            tracing::warn!("Generation of project {id} failed: {e:#}");
+            progress.send(ProgressUpdate::new(FAILED_PHASE, 1.0, format!("{e:#}")));
+        }
+        if let Some(project) = state.projects.lock().await.get_mut(&id) {
+            project.running = false;
+        }
+    });
+    StatusCode::ACCEPTED.into_response()
+}
+
+async fn generate(
+    pipeline: &GenerationPipeline,
+    dir: &Path,
+    progress: Reporter,
+) -> anyhow::Result<()> {
+    let config = ProjectConfig::load(&dir.join(PROJECT_FILE))?;
+    // Refuse up front rather than fail on a full disk halfway; the error
+    // is reported as the run's Failed update
+    ensure_disk_space(&AssetChecklist::for_project(&config), dir)?;
+    let mut generator = pipeline.generator.lock().await;
+    let generator = generator.as_mut().context("Generator is not initialized")?;
+    generator.set_project_config(config);
+    generator.set_checkpoint_dir(dir.to_path_buf(), true);
+
+    let reporter = progress.clone();
+    let design = generator
+        .generate_from_project(move |update| {
+            // Subscribers stop at completion, which is reported once the
+            // design document is on disk
+            if update.phase == GenerationPhase::Complete {
+                return;
+            }
+            reporter.send(ProgressUpdate::from_run(&update));
+        })
+        .await?;
+    let design_file = dir.join(DESIGN_FILE);
+    std::fs::write(&design_file, design).context("Failed to write design document")?;
+    progress.send(
+        ProgressUpdate::new(COMPLETE_PHASE, 1.0, "Design document written")
+            .with_artifact(GeneratedArtifact::file("design", DESIGN_FILE, &design_file)),
+    );
+    Ok(())
+}
+
+/// Sends a project's updates to its SSE stream and to the broadcast
+#[derive(Clone)]
+struct Reporter {
+    id: String,
+    project: watch::Sender<ProgressUpdate>,
+    broadcast: ProgressBroadcast,
+}
+
+impl Reporter {
+    fn send(&self, update: ProgressUpdate) {
+        self.broadcast.publish(&self.id, update.clone());
+        self.project.send_replace(update);
+    }
+}
+
+async fn progress(
+    State(state): State<ApiState>,
+    UrlPath(id): UrlPath<String>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, Response> {
+    let mut receiver = match state.projects.lock().await.get(&id) {
+        Some(project) => project.progress.subscribe(),
+        None => {
+            return Err(error(
+                StatusCode::NOT_FOUND,
+                format!("Unknown project {id}"),
+            ));
+        }
+    };
+
+    let stream = async_stream::stream! {
+        loop {
+            let update = receiver.borrow_and_update().clone();
+            let done = update.phase == COMPLETE_PHASE || update.phase == FAILED_PHASE;
+            if let Ok(event) = Event::default().event("progress").json_data(&update) {
+                yield Ok(event);
+            }
+            if done || receiver.changed().await.is_err() {
+                break;
+            }
+        }
+    };
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+#[derive(Debug, Deserialize)]
+struct WebSocketQuery {
+    project: Option<String>,
+}
+
+async fn websocket(
+    State(state): State<ApiState>,
+    Query(query): Query<WebSocketQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let events = state.pipeline.progress.subscribe();
+    upgrade.on_upgrade(move |socket| forward_events(socket, events, query.project))
+}
+
+async fn forward_events(
+    mut socket: WebSocket,
+    mut events: tokio::sync::broadcast::Receiver<ProgressEvent>,
+    project: Option<String>,
+) {
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(missed)) => {
+                tracing::warn!("WebSocket subscriber fell behind by {missed} progress events");
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+        if project
+            .as_ref()
+            .is_some_and(|project| *project != event.project)
+        {
+            continue;
+        }
+        let Ok(json) = serde_json::to_string(&event) else {
+            continue;
+        };
+        if socket.send(Message::Text(json.into())).await.is_err() {
+            break;
+        }
+    }
+}
+
+fn error(status: StatusCode, message: String) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
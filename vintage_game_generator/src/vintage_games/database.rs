@@ -0,0 +1,301 @@
+//! The game database the timeline is queried from
+//!
+//! At startup the database holds the embedded timeline, or the games of a
+//! user-supplied database file, plus the era packs. Users can add their own
+//! games on top, which are kept in a user games file next to their projects
+//! so they can be blended like any other timeline game.
+//!
+//! Games are leaked when they join the database, so queries hand out
+//! `&'static TimelineGame` like the generated timeline does.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use super::era_packs::{ERA_PACKS_DIR, EraPackInfo, LoadedTimeline, PackGame, load_era_packs};
+use super::games::TimelineGame;
+use super::search::{SearchFilters, SearchHit, rank_games};
+
+/// File in the base directory holding the games users added
+pub const USER_GAMES_FILE: &str = "user_games.json";
+
+/// Layout of database and user games files this version reads
+pub const DATABASE_FORMAT_VERSION: u32 = 1;
+
+/// IDs handed out to user games start here, well above the data sources' IDs
+pub const USER_GAME_ID_START: u32 = 1 << 30;
+
+static DATABASE: LazyLock<RwLock<GameDatabase>> =
+    LazyLock::new(|| RwLock::new(GameDatabase::embedded()));
+
+/// Counts the times the database was handed out for changes
+static REVISION: AtomicU64 = AtomicU64::new(0);
+
+/// A database or user games file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameDatabaseFile {
+    pub format_version: u32,
+    pub games: Vec<PackGame>,
+}
+
+impl GameDatabaseFile {
+    /// Read a file, rejecting other format versions
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let file: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        if file.format_version != DATABASE_FORMAT_VERSION {
+            anyhow::bail!(
+                "{} has format version {}, expected {DATABASE_FORMAT_VERSION}",
+                path.display(),
+                file.format_version
+            );
+        }
+        Ok(file)
+    }
+}
+
+/// The games of the timeline, sorted by year
+#[derive(Debug, Default)]
+pub struct GameDatabase {
+    games: Vec<&'static TimelineGame>,
+    packs: Vec<EraPackInfo>,
+    user_games: Vec<&'static TimelineGame>,
+}
+
+impl GameDatabase {
+    /// The embedded timeline and the era packs
+    pub fn embedded() -> Self {
+        Self::from_timeline(LoadedTimeline::new(embedded_games(), era_packs()))
+    }
+
+    /// The games of a user-supplied database file, in place of the embedded
+    /// timeline, and the era packs
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let games: Vec<TimelineGame> = GameDatabaseFile::load(path)?
+            .games
+            .into_iter()
+            .map(PackGame::into_timeline_game)
+            .collect();
+        Ok(Self::from_timeline(LoadedTimeline::new(
+            &games,
+            era_packs(),
+        )))
+    }
+
+    pub fn from_timeline(timeline: LoadedTimeline) -> Self {
+        let games: &'static [TimelineGame] = Box::leak(timeline.games.into_boxed_slice());
+        Self {
+            games: games.iter().collect(),
+            packs: timeline.packs,
+            user_games: Vec::new(),
+        }
+    }
+
+    /// Every game, sorted by year
+    pub fn games(&self) -> &[&'static TimelineGame] {
+        &self.games
+    }
+
+    /// The games users added
+    pub fn user_games(&self) -> &[&'static TimelineGame] {
+        &self.user_games
+    }
+
+    /// The era packs that were loaded
+    pub fn packs(&self) -> &[EraPackInfo] {
+        &self.packs
+    }
+
+    pub fn len(&self) -> usize {
+        self.games.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.games.is_empty()
+    }
+
+    pub fn get(&self, id: u32) -> Option<&'static TimelineGame> {
+        self.games.iter().copied().find(|game| game.id == id)
+    }
+
+    pub fn games_by_year(&self, year: i32) -> Vec<&'static TimelineGame> {
+        self.filter(|game| game.year == year)
+    }
+
+    /// Games of a genre, ignoring case
+    pub fn games_by_genre(&self, genre: &str) -> Vec<&'static TimelineGame> {
+        self.filter(|game| game.genre.eq_ignore_ascii_case(genre))
+    }
+
+    /// Games matching the query, best match first, see [`super::search`]
+    pub fn search_games(&self, query: &str) -> Vec<&'static TimelineGame> {
+        self.search(query, &SearchFilters::default())
+            .into_iter()
+            .map(|hit| hit.game)
+            .collect()
+    }
+
+    /// Games matching the query and the filters, best match first
+    pub fn search(&self, query: &str, filters: &SearchFilters) -> Vec<SearchHit> {
+        rank_games(self.games.iter().copied(), query, filters)
+    }
+
+    fn filter(&self, keep: impl Fn(&TimelineGame) -> bool) -> Vec<&'static TimelineGame> {
+        self.games
+            .iter()
+            .copied()
+            .filter(|game| keep(game))
+            .collect()
+    }
+
+    /// First and last year of the games, if there are any
+    pub fn span(&self) -> Option<(i32, i32)> {
+        let years = self.games.iter().map(|game| game.year);
+        years.clone().min().zip(years.max())
+    }
+
+    /// The lowest free ID for a user game
+    pub fn next_user_game_id(&self) -> u32 {
+        self.games
+            .iter()
+            .map(|game| game.id)
+            .filter(|&id| id >= USER_GAME_ID_START)
+            .max()
+            .map_or(USER_GAME_ID_START, |id| id + 1)
+    }
+
+    /// Add a game of the user's. Its ID must not be taken yet, see
+    /// [`Self::next_user_game_id`].
+    pub fn add_game(&mut self, game: PackGame) -> Result<&'static TimelineGame> {
+        if game.name.trim().is_empty() {
+            anyhow::bail!("A game needs a name");
+        }
+        if game.genre.trim().is_empty() {
+            anyhow::bail!("{} needs a genre", game.name);
+        }
+        if let Some(existing) = self.get(game.id) {
+            anyhow::bail!("ID {} is already taken by {}", game.id, existing.name);
+        }
+
+        let game: &'static TimelineGame = Box::leak(Box::new(game.into_timeline_game()));
+        let index = self.games.partition_point(|other| other.year <= game.year);
+        self.games.insert(index, game);
+        self.user_games.push(game);
+        Ok(game)
+    }
+
+    /// Remove a game the user added. Games of the timeline and the era
+    /// packs can't be removed.
+    pub fn remove_game(&mut self, id: u32) -> Result<&'static TimelineGame> {
+        let index = self
+            .user_games
+            .iter()
+            .position(|game| game.id == id)
+            .with_context(|| format!("No user game has ID {id}"))?;
+        let game = self.user_games.remove(index);
+        self.games.retain(|other| other.id != id);
+        Ok(game)
+    }
+
+    /// Add the games of a user games file and return how many were added.
+    /// A missing file adds nothing; games whose ID is taken are skipped.
+    pub fn load_user_games(&mut self, path: impl AsRef<Path>) -> Result<usize> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(0);
+        }
+
+        let mut added = 0;
+        for game in GameDatabaseFile::load(path)?.games {
+            let name = game.name.clone();
+            match self.add_game(game) {
+                Ok(_) => added += 1,
+                Err(e) => tracing::warn!("Skipping user game {name}: {e:#}"),
+            }
+        }
+        Ok(added)
+    }
+
+    /// Write the games users added to a user games file
+    pub fn save_user_games(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let file = GameDatabaseFile {
+            format_version: DATABASE_FORMAT_VERSION,
+            games: self
+                .user_games
+                .iter()
+                .map(|game| PackGame::from(*game))
+                .collect(),
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&file)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// The timeline compiled into the binary
+fn embedded_games() -> &'static [TimelineGame] {
+    if cfg!(feature = "embedded-timeline") {
+        super::games::TIMELINE_GAMES
+    } else {
+        &[]
+    }
+}
+
+/// The packs in [`ERA_PACKS_DIR`], or in the directory
+/// `VINTAGE_ERA_PACKS_DIR` names
+fn era_packs() -> Vec<super::era_packs::EraPack> {
+    let dir = std::env::var_os("VINTAGE_ERA_PACKS_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(ERA_PACKS_DIR));
+    load_era_packs(dir)
+}
+
+/// The database, loaded with the embedded timeline unless [`install`] was
+/// called first
+pub fn database() -> RwLockReadGuard<'static, GameDatabase> {
+    DATABASE.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The database, to add or remove games
+pub fn database_mut() -> RwLockWriteGuard<'static, GameDatabase> {
+    let database = DATABASE.write().unwrap_or_else(PoisonError::into_inner);
+    REVISION.fetch_add(1, Ordering::Relaxed);
+    database
+}
+
+/// Revision of the database, which changes whenever it may have changed,
+/// so results computed from its games know when to be computed again
+pub fn database_revision() -> u64 {
+    REVISION.load(Ordering::Relaxed)
+}
+
+/// Replace the database, e.g. with one loaded from a user-supplied file
+pub fn install(database: GameDatabase) {
+    *database_mut() = database;
+}
+
+/// Every game of the database
+pub fn all_games() -> Vec<&'static TimelineGame> {
+    database().games().to_vec()
+}
+
+/// The era packs that were loaded
+pub fn loaded_era_packs() -> Vec<EraPackInfo> {
+    database().packs().to_vec()
+}
+
+/// First and last year of the database, the embedded span if it is empty
+pub fn timeline_span() -> (i32, i32) {
+    database()
+        .span()
+        .unwrap_or((super::TIMELINE_START, super::TIMELINE_END))
+}
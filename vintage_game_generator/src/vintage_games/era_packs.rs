@@ -0,0 +1,224 @@
+//! Era packs loaded at startup
+//!
+//! The build tools write the timelines of other periods, such as the Atari
+//! era or the 32-bit era, to [`ERA_PACKS_DIR`] as JSON. The
+//! [`GameDatabase`](super::database::GameDatabase) adds the games of every
+//! pack found in that directory, or in the directory `VINTAGE_ERA_PACKS_DIR`
+//! names, to the embedded timeline. A game in both keeps its embedded entry.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use super::games::{ImageUrls, TimelineGame};
+
+/// Where the build tools write era packs
+pub const ERA_PACKS_DIR: &str = "assets/wizard/era_packs";
+
+/// Pack layout this version reads
+pub const ERA_PACK_FORMAT_VERSION: u32 = 1;
+
+/// An era pack as written by the build tools
+#[derive(Debug, Clone, Deserialize)]
+pub struct EraPack {
+    pub format_version: u32,
+    pub id: String,
+    pub name: String,
+    pub start_year: i32,
+    pub end_year: i32,
+    pub games: Vec<PackGame>,
+}
+
+/// A game of a pack, or of a database or user games file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackGame {
+    pub id: u32,
+    pub year: i32,
+    pub genre: String,
+    pub name: String,
+    #[serde(default)]
+    pub deck: Option<String>,
+    #[serde(default)]
+    pub platforms: Vec<String>,
+    #[serde(default)]
+    pub developer: Option<String>,
+    #[serde(default)]
+    pub image_urls: PackImageUrls,
+    #[serde(default)]
+    pub site_url: String,
+    #[serde(default)]
+    pub sources: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackImageUrls {
+    pub icon: Option<String>,
+    pub medium: Option<String>,
+    pub screen: Option<String>,
+    pub screen_large: Option<String>,
+    pub small: Option<String>,
+    pub super_url: Option<String>,
+    pub thumb: Option<String>,
+    pub tiny: Option<String>,
+    #[serde(default)]
+    pub original: String,
+}
+
+/// What a loaded pack covers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EraPackInfo {
+    pub id: String,
+    pub name: String,
+    pub start_year: i32,
+    pub end_year: i32,
+    /// Games the pack added, not counting ones the timeline already had
+    pub game_count: usize,
+}
+
+/// The embedded timeline and the loaded packs
+#[derive(Debug)]
+pub struct LoadedTimeline {
+    pub games: Vec<TimelineGame>,
+    pub packs: Vec<EraPackInfo>,
+}
+
+impl LoadedTimeline {
+    /// `embedded` followed by the games of `packs` it doesn't have yet
+    pub fn new(embedded: &[TimelineGame], packs: Vec<EraPack>) -> Self {
+        let mut games = embedded.to_vec();
+        let mut ids: HashSet<u32> = games.iter().map(|game| game.id).collect();
+        let mut infos = Vec::new();
+
+        for pack in packs {
+            let mut info = EraPackInfo {
+                id: pack.id,
+                name: pack.name,
+                start_year: pack.start_year,
+                end_year: pack.end_year,
+                game_count: 0,
+            };
+            for game in pack.games {
+                if ids.insert(game.id) {
+                    games.push(game.into_timeline_game());
+                    info.game_count += 1;
+                }
+            }
+            infos.push(info);
+        }
+
+        games.sort_by_key(|game| game.year);
+        Self {
+            games,
+            packs: infos,
+        }
+    }
+
+    /// First and last year of the games, if there are any
+    pub fn span(&self) -> Option<(i32, i32)> {
+        let years = self.games.iter().map(|game| game.year);
+        years.clone().min().zip(years.max())
+    }
+}
+
+impl PackGame {
+    /// The game as a timeline entry. Its strings are leaked, as packs are
+    /// loaded once and live as long as the process.
+    pub fn into_timeline_game(self) -> TimelineGame {
+        fn leak(text: String) -> &'static str {
+            Box::leak(text.into_boxed_str())
+        }
+        fn leak_all(texts: Vec<String>) -> &'static [&'static str] {
+            Box::leak(texts.into_iter().map(leak).collect())
+        }
+
+        let images = self.image_urls;
+        TimelineGame {
+            id: self.id,
+            year: self.year,
+            genre: leak(self.genre),
+            name: leak(self.name),
+            deck: self.deck.map(leak),
+            platforms: leak_all(self.platforms),
+            developer: self.developer.map(leak),
+            image_urls: ImageUrls {
+                icon: images.icon.map(leak),
+                medium: images.medium.map(leak),
+                screen: images.screen.map(leak),
+                screen_large: images.screen_large.map(leak),
+                small: images.small.map(leak),
+                super_url: images.super_url.map(leak),
+                thumb: images.thumb.map(leak),
+                tiny: images.tiny.map(leak),
+                original: leak(images.original),
+            },
+            site_url: leak(self.site_url),
+            sources: leak_all(self.sources),
+        }
+    }
+}
+
+impl From<&TimelineGame> for PackGame {
+    fn from(game: &TimelineGame) -> Self {
+        let images = &game.image_urls;
+        Self {
+            id: game.id,
+            year: game.year,
+            genre: game.genre.to_string(),
+            name: game.name.to_string(),
+            deck: game.deck.map(str::to_string),
+            platforms: game.platforms.iter().map(|p| p.to_string()).collect(),
+            developer: game.developer.map(str::to_string),
+            image_urls: PackImageUrls {
+                icon: images.icon.map(str::to_string),
+                medium: images.medium.map(str::to_string),
+                screen: images.screen.map(str::to_string),
+                screen_large: images.screen_large.map(str::to_string),
+                small: images.small.map(str::to_string),
+                super_url: images.super_url.map(str::to_string),
+                thumb: images.thumb.map(str::to_string),
+                tiny: images.tiny.map(str::to_string),
+                original: images.original.to_string(),
+            },
+            site_url: game.site_url.to_string(),
+            sources: game.sources.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+/// Read every `*.json` pack in `dir`, in file name order. Packs that can't
+/// be read or have another format version are skipped with a warning.
+pub fn load_era_packs(dir: impl AsRef<Path>) -> Vec<EraPack> {
+    let Ok(entries) = std::fs::read_dir(dir.as_ref()) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let pack: EraPack = match std::fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| Ok(serde_json::from_str(&content)?))
+            {
+                Ok(pack) => pack,
+                Err(e) => {
+                    tracing::warn!("Skipping era pack {}: {e:#}", path.display());
+                    return None;
+                }
+            };
+            if pack.format_version != ERA_PACK_FORMAT_VERSION {
+                tracing::warn!(
+                    "Skipping era pack {}: format version {} is not {ERA_PACK_FORMAT_VERSION}",
+                    path.display(),
+                    pack.format_version
+                );
+                return None;
+            }
+            Some(pack)
+        })
+        .collect()
+}
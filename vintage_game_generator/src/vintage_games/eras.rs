@@ -1,8 +1,11 @@
 //! Era definitions and functions
 
-use super::games::{TIMELINE_GAMES, TimelineGame};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+use super::database::all_games;
+use super::games::TimelineGame;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Era {
     ArcadeGoldenAge, // 1980-1983
     EarlyConsole,    // 1984-1987
@@ -65,8 +68,8 @@ pub fn era_description(era: Era) -> &'static str {
 pub fn games_by_era(era: Era) -> Vec<&'static TimelineGame> {
     let (start, end) = era.year_range();
 
-    TIMELINE_GAMES
-        .iter()
+    all_games()
+        .into_iter()
         .filter(|game| game.year >= start && game.year <= end)
         .collect()
 }
@@ -91,7 +94,7 @@ pub fn era_genres(era: Era) -> Vec<String> {
     }
 
     let mut genres: Vec<(String, usize)> = genre_counts.into_iter().collect();
-    genres.sort_by(|a, b| b.1.cmp(&a.1));
+    genres.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
 
     genres.into_iter().map(|(genre, _)| genre).take(5).collect()
 }
@@ -0,0 +1,292 @@
+//! Faceted filtering of the timeline by genre, platform family and developer
+//!
+//! Values picked within a facet widen the selection, picks across facets
+//! narrow it: Platformer or Shooter, on a Nintendo or Sega platform. The
+//! counts of a facet take the picks of the other facets into account, so
+//! they tell how many games picking the value would add.
+
+use std::collections::{BTreeSet, HashMap};
+
+use super::database::all_games;
+use super::games::TimelineGame;
+
+/// Maker or kind of a platform
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PlatformFamily {
+    Nintendo,
+    Sega,
+    Sony,
+    Nec,
+    Atari,
+    Arcade,
+    Computer,
+    Other,
+}
+
+impl PlatformFamily {
+    pub const ALL: [PlatformFamily; 8] = [
+        PlatformFamily::Nintendo,
+        PlatformFamily::Sega,
+        PlatformFamily::Sony,
+        PlatformFamily::Nec,
+        PlatformFamily::Atari,
+        PlatformFamily::Arcade,
+        PlatformFamily::Computer,
+        PlatformFamily::Other,
+    ];
+
+    /// Family of a platform name as the data sources spell it
+    pub fn of(platform: &str) -> Self {
+        // Consoles of computer makers and computers of console makers are
+        // listed before the families their maker's name would suggest
+        const FAMILIES: &[(PlatformFamily, &[&str])] = &[
+            (
+                PlatformFamily::Computer,
+                &[
+                    "atari st",
+                    "atari 8 bit",
+                    "atari falcon",
+                    "pc 8801",
+                    "pc 9801",
+                ],
+            ),
+            (
+                PlatformFamily::Nintendo,
+                &[
+                    "nintendo",
+                    "nes",
+                    "snes",
+                    "famicom",
+                    "game boy",
+                    "virtual boy",
+                    "game watch",
+                ],
+            ),
+            (
+                PlatformFamily::Sega,
+                &[
+                    "sega",
+                    "genesis",
+                    "mega drive",
+                    "master system",
+                    "game gear",
+                    "saturn",
+                    "dreamcast",
+                    "32x",
+                ],
+            ),
+            (PlatformFamily::Sony, &["playstation"]),
+            (
+                PlatformFamily::Nec,
+                &["pc engine", "turbografx", "supergrafx", "pc fx"],
+            ),
+            (PlatformFamily::Atari, &["atari", "lynx", "jaguar"]),
+            (PlatformFamily::Arcade, &["arcade", "neo geo"]),
+            (
+                PlatformFamily::Computer,
+                &[
+                    "pc",
+                    "dos",
+                    "windows",
+                    "mac",
+                    "macintosh",
+                    "amiga",
+                    "commodore",
+                    "vic 20",
+                    "apple",
+                    "amstrad",
+                    "msx",
+                    "zx spectrum",
+                    "bbc micro",
+                    "acorn",
+                    "trs 80",
+                    "x68000",
+                    "fm towns",
+                ],
+            ),
+        ];
+
+        let name = format!(" {} ", words(platform));
+        FAMILIES
+            .iter()
+            .find(|(_, keywords)| {
+                keywords
+                    .iter()
+                    .any(|keyword| name.contains(&format!(" {keyword} ")))
+            })
+            .map_or(PlatformFamily::Other, |(family, _)| *family)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlatformFamily::Nintendo => "Nintendo",
+            PlatformFamily::Sega => "Sega",
+            PlatformFamily::Sony => "Sony",
+            PlatformFamily::Nec => "NEC",
+            PlatformFamily::Atari => "Atari",
+            PlatformFamily::Arcade => "Arcade",
+            PlatformFamily::Computer => "Computer",
+            PlatformFamily::Other => "Other",
+        }
+    }
+}
+
+/// Lowercase words of a name, separated by single spaces
+fn words(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Families of the game's platforms
+pub fn platform_families(game: &TimelineGame) -> BTreeSet<PlatformFamily> {
+    game.platforms
+        .iter()
+        .map(|platform| PlatformFamily::of(platform))
+        .collect()
+}
+
+/// The values picked in each facet; an empty facet allows every game
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Facets {
+    pub genres: BTreeSet<String>,
+    pub families: BTreeSet<PlatformFamily>,
+    pub developers: BTreeSet<String>,
+}
+
+/// How many games each value of a facet has, most first
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FacetCounts {
+    pub genres: Vec<(String, usize)>,
+    pub families: Vec<(PlatformFamily, usize)>,
+    pub developers: Vec<(String, usize)>,
+}
+
+impl Facets {
+    pub fn is_empty(&self) -> bool {
+        self.genres.is_empty() && self.families.is_empty() && self.developers.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn toggle_genre(&mut self, genre: &str) {
+        toggle(&mut self.genres, genre.to_string());
+    }
+
+    pub fn toggle_family(&mut self, family: PlatformFamily) {
+        toggle(&mut self.families, family);
+    }
+
+    pub fn toggle_developer(&mut self, developer: &str) {
+        toggle(&mut self.developers, developer.to_string());
+    }
+
+    pub fn matches(&self, game: &TimelineGame) -> bool {
+        self.matches_genre(game) && self.matches_family(game) && self.matches_developer(game)
+    }
+
+    fn matches_genre(&self, game: &TimelineGame) -> bool {
+        self.genres.is_empty() || self.genres.contains(game.genre)
+    }
+
+    fn matches_family(&self, game: &TimelineGame) -> bool {
+        self.families.is_empty()
+            || game
+                .platforms
+                .iter()
+                .any(|platform| self.families.contains(&PlatformFamily::of(platform)))
+    }
+
+    fn matches_developer(&self, game: &TimelineGame) -> bool {
+        self.developers.is_empty()
+            || game
+                .developer
+                .is_some_and(|developer| self.developers.contains(developer))
+    }
+
+    /// Counts of every facet value among `games`, each facet counted over
+    /// the games the other facets allow
+    pub fn counts<'a>(&self, games: impl IntoIterator<Item = &'a TimelineGame>) -> FacetCounts {
+        let mut genres: HashMap<String, usize> = HashMap::new();
+        let mut families: HashMap<PlatformFamily, usize> = HashMap::new();
+        let mut developers: HashMap<String, usize> = HashMap::new();
+
+        for game in games {
+            let genre = self.matches_genre(game);
+            let family = self.matches_family(game);
+            let developer = self.matches_developer(game);
+
+            if family && developer {
+                *genres.entry(game.genre.to_string()).or_default() += 1;
+            }
+            if genre && developer {
+                for family in platform_families(game) {
+                    *families.entry(family).or_default() += 1;
+                }
+            }
+            if genre
+                && family
+                && let Some(name) = game.developer
+            {
+                *developers.entry(name.to_string()).or_default() += 1;
+            }
+        }
+
+        FacetCounts {
+            genres: by_count(genres),
+            families: by_count(families),
+            developers: by_count(developers),
+        }
+    }
+}
+
+fn toggle<T: Ord>(set: &mut BTreeSet<T>, value: T) {
+    if !set.remove(&value) {
+        set.insert(value);
+    }
+}
+
+/// Values with the most games first, ties in value order
+fn by_count<T: Ord>(counts: HashMap<T, usize>) -> Vec<(T, usize)> {
+    let mut counts: Vec<(T, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// Games on a platform of the family
+pub fn games_by_platform_family(family: PlatformFamily) -> Vec<&'static TimelineGame> {
+    all_games()
+        .into_iter()
+        .filter(|game| platform_families(game).contains(&family))
+        .collect()
+}
+
+/// Games made by the developer
+pub fn games_by_developer(developer: &str) -> Vec<&'static TimelineGame> {
+    all_games()
+        .into_iter()
+        .filter(|game| game.developer == Some(developer))
+        .collect()
+}
+
+/// Every developer in the timeline, sorted
+pub fn all_developers() -> Vec<String> {
+    all_games()
+        .into_iter()
+        .filter_map(|game| game.developer.map(str::to_string))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Games the facets allow
+pub fn faceted_games(facets: &Facets) -> Vec<&'static TimelineGame> {
+    all_games()
+        .into_iter()
+        .filter(|game| facets.matches(game))
+        .collect()
+}
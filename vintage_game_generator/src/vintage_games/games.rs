@@ -3,6 +3,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::database::{all_games, database};
+use super::search::{SearchFilters, SearchHit};
+
 #[derive(Debug, Clone)]
 pub struct TimelineGame {
     pub id: u32,
@@ -14,6 +17,8 @@ pub struct TimelineGame {
     pub developer: Option<&'static str>,
     pub image_urls: ImageUrls,
     pub site_url: &'static str,
+    /// Data sources the game came from, e.g. `["giantbomb", "igdb"]`
+    pub sources: &'static [&'static str],
 }
 
 // Manual Serialize/Deserialize implementation for TimelineGame
@@ -23,7 +28,7 @@ impl Serialize for TimelineGame {
         S: serde::Serializer,
     {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("TimelineGame", 9)?;
+        let mut state = serializer.serialize_struct("TimelineGame", 10)?;
         state.serialize_field("id", &self.id)?;
         state.serialize_field("year", &self.year)?;
         state.serialize_field("genre", &self.genre)?;
@@ -33,6 +38,7 @@ impl Serialize for TimelineGame {
         state.serialize_field("developer", &self.developer)?;
         state.serialize_field("image_urls", &self.image_urls)?;
         state.serialize_field("site_url", &self.site_url)?;
+        state.serialize_field("sources", &self.sources.to_vec())?;
         state.end()
     }
 }
@@ -99,6 +105,7 @@ pub const TIMELINE_GAMES: &[TimelineGame] = &[
             original: r#"https://www.giantbomb.com/a/uploads/original/16/165930/2365659-balloonfight_nes__usa_front.jpg"#,
         },
         site_url: r#"https://www.giantbomb.com/balloon-fight/3030-86/"#,
+        sources: &["giantbomb"],
     },
     TimelineGame {
         id: 101,
@@ -145,6 +152,7 @@ pub const TIMELINE_GAMES: &[TimelineGame] = &[
             original: r#"https://www.giantbomb.com/a/uploads/original/0/7465/1708641-corsarios_01.png"#,
         },
         site_url: r#"https://www.giantbomb.com/corsarios/3030-101/"#,
+        sources: &["giantbomb"],
     },
     TimelineGame {
         id: 15,
@@ -190,6 +198,7 @@ pub const TIMELINE_GAMES: &[TimelineGame] = &[
             original: r#"https://www.giantbomb.com/a/uploads/original/0/238/709028-kampfgruppe_2.jpg"#,
         },
         site_url: r#"https://www.giantbomb.com/kampfgruppe/3030-15/"#,
+        sources: &["giantbomb"],
     },
     TimelineGame {
         id: 33,
@@ -229,6 +238,7 @@ pub const TIMELINE_GAMES: &[TimelineGame] = &[
             original: r#"https://www.giantbomb.com/a/uploads/original/8/87790/2958020-box_slamcity.png"#,
         },
         site_url: r#"https://www.giantbomb.com/slam-city-with-scottie-pippen/3030-33/"#,
+        sources: &["giantbomb"],
     },
     TimelineGame {
         id: 4,
@@ -279,6 +289,7 @@ pub const TIMELINE_GAMES: &[TimelineGame] = &[
             original: r#"https://www.giantbomb.com/a/uploads/original/7/73970/3436007-chessmaster_2000_a800_1_1.jpg"#,
         },
         site_url: r#"https://www.giantbomb.com/the-chessmaster-2000/3030-4/"#,
+        sources: &["giantbomb"],
     },
     TimelineGame {
         id: 1,
@@ -328,6 +339,7 @@ pub const TIMELINE_GAMES: &[TimelineGame] = &[
             original: r#"https://www.giantbomb.com/a/uploads/original/9/93770/2370498-genesis_desertstrike_2__1_.jpg"#,
         },
         site_url: r#"https://www.giantbomb.com/desert-strike-return-to-the-gulf/3030-1/"#,
+        sources: &["giantbomb"],
     },
     TimelineGame {
         id: 30,
@@ -365,6 +377,7 @@ pub const TIMELINE_GAMES: &[TimelineGame] = &[
             original: r#"https://www.giantbomb.com/a/uploads/original/10/103881/1796224-re.jpg"#,
         },
         site_url: r#"https://www.giantbomb.com/romantic-encounters-at-the-dome/3030-30/"#,
+        sources: &["giantbomb"],
     },
     TimelineGame {
         id: 78,
@@ -414,6 +427,7 @@ pub const TIMELINE_GAMES: &[TimelineGame] = &[
             original: r#"https://www.giantbomb.com/a/uploads/original/7/73970/3116917-deflektor_amiga_1_1.jpg"#,
         },
         site_url: r#"https://www.giantbomb.com/deflektor/3030-78/"#,
+        sources: &["giantbomb"],
     },
     TimelineGame {
         id: 80,
@@ -458,6 +472,7 @@ pub const TIMELINE_GAMES: &[TimelineGame] = &[
             original: r#"https://www.giantbomb.com/a/uploads/original/16/164924/2999465-6664166472-87863.jpg"#,
         },
         site_url: r#"https://www.giantbomb.com/ruff-and-reddy-in-the-space-adventure/3030-80/"#,
+        sources: &["giantbomb"],
     },
     TimelineGame {
         id: 311,
@@ -520,6 +535,7 @@ pub const TIMELINE_GAMES: &[TimelineGame] = &[
             original: r#"https://www.giantbomb.com/a/uploads/original/9/93770/2361668-nes_donkeykong.jpg"#,
         },
         site_url: r#"https://www.giantbomb.com/donkey-kong/3030-311/"#,
+        sources: &["giantbomb"],
     },
     TimelineGame {
         id: 197,
@@ -564,6 +580,7 @@ pub const TIMELINE_GAMES: &[TimelineGame] = &[
             original: r#"https://www.giantbomb.com/a/uploads/original/0/5768/812031-carnival.jpg"#,
         },
         site_url: r#"https://www.giantbomb.com/carnival/3030-197/"#,
+        sources: &["giantbomb"],
     },
     TimelineGame {
         id: 102,
@@ -601,6 +618,7 @@ pub const TIMELINE_GAMES: &[TimelineGame] = &[
             original: r#"https://www.giantbomb.com/a/uploads/original/11/110673/3026329-gb_default-16_9.png"#,
         },
         site_url: r#"https://www.giantbomb.com/jabbertalky/3030-102/"#,
+        sources: &["giantbomb"],
     },
     TimelineGame {
         id: 2,
@@ -640,6 +658,7 @@ pub const TIMELINE_GAMES: &[TimelineGame] = &[
             original: r#"https://www.giantbomb.com/a/uploads/original/0/1940/621001-1056454795_00.gif"#,
         },
         site_url: r#"https://www.giantbomb.com/breakfree/3030-2/"#,
+        sources: &["giantbomb"],
     },
     TimelineGame {
         id: 20,
@@ -679,6 +698,7 @@ pub const TIMELINE_GAMES: &[TimelineGame] = &[
             original: r#"https://www.giantbomb.com/a/uploads/original/0/9408/723295-23343_boxshot_1.jpg"#,
         },
         site_url: r#"https://www.giantbomb.com/burntime/3030-20/"#,
+        sources: &["giantbomb"],
     },
     TimelineGame {
         id: 11,
@@ -716,6 +736,7 @@ pub const TIMELINE_GAMES: &[TimelineGame] = &[
             original: r#"https://www.giantbomb.com/a/uploads/original/0/238/707629-gothmog_s_lair.jpg"#,
         },
         site_url: r#"https://www.giantbomb.com/gothmogs-lair/3030-11/"#,
+        sources: &["giantbomb"],
     },
     TimelineGame {
         id: 54,
@@ -760,29 +781,23 @@ pub const TIMELINE_GAMES: &[TimelineGame] = &[
             original: r#"https://www.giantbomb.com/a/uploads/original/9/93770/2362258-nes_starwars.jpg"#,
         },
         site_url: r#"https://www.giantbomb.com/star-wars/3030-54/"#,
+        sources: &["giantbomb"],
     },
 ];
 
 /// Get games for a specific year
 pub fn games_by_year(year: i32) -> Vec<&'static TimelineGame> {
-    TIMELINE_GAMES
-        .iter()
-        .filter(|game| game.year == year)
-        .collect()
+    database().games_by_year(year)
 }
 
 /// Get games for a specific genre across all years
 pub fn games_by_genre(genre: &str) -> Vec<&'static TimelineGame> {
-    let genre_lower = genre.to_lowercase();
-    TIMELINE_GAMES
-        .iter()
-        .filter(|game| game.genre.to_lowercase() == genre_lower)
-        .collect()
+    database().games_by_genre(genre)
 }
 
 /// Get all unique genres in the timeline
 pub fn all_genres() -> Vec<String> {
-    let mut genres: Vec<String> = TIMELINE_GAMES
+    let mut genres: Vec<String> = all_games()
         .iter()
         .map(|game| game.genre.to_string())
         .collect::<std::collections::HashSet<_>>()
@@ -792,9 +807,21 @@ pub fn all_genres() -> Vec<String> {
     genres
 }
 
+/// Get all unique platforms in the timeline
+pub fn all_platforms() -> Vec<String> {
+    let mut platforms: Vec<String> = all_games()
+        .iter()
+        .flat_map(|game| game.platforms.iter().map(|p| p.to_string()))
+        .collect::<std::collections::HashSet<_>>()
+        .into_iter()
+        .collect();
+    platforms.sort();
+    platforms
+}
+
 /// Get all years that have games
 pub fn timeline_years() -> Vec<i32> {
-    let mut years: Vec<i32> = TIMELINE_GAMES
+    let mut years: Vec<i32> = all_games()
         .iter()
         .map(|game| game.year)
         .collect::<std::collections::HashSet<_>>()
@@ -808,46 +835,40 @@ pub fn timeline_years() -> Vec<i32> {
 pub fn build_timeline_index() -> HashMap<i32, Vec<&'static TimelineGame>> {
     let mut index: HashMap<i32, Vec<&'static TimelineGame>> = HashMap::new();
 
-    for game in TIMELINE_GAMES.iter() {
+    for game in all_games() {
         index.entry(game.year).or_default().push(game);
     }
 
     index
 }
 
-/// Get a random exemplar game (useful for inspiration)
-pub fn random_exemplar() -> &'static TimelineGame {
+/// Get a random exemplar game (useful for inspiration), if the database
+/// has any
+pub fn random_exemplar() -> Option<&'static TimelineGame> {
     // Use a simple deterministic "random" based on current time
-    let index = (std::time::SystemTime::now()
+    let games = all_games();
+    let seconds = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as usize)
-        % TIMELINE_GAMES.len();
+        .map_or(0, |elapsed| elapsed.as_secs() as usize);
 
-    &TIMELINE_GAMES[index]
+    games.get(seconds.checked_rem(games.len())?).copied()
 }
 
-/// Find games that match a search query
+/// Find games that match a search query, tolerating typos, best match first
 pub fn search_games(query: &str) -> Vec<&'static TimelineGame> {
-    let query_lower = query.to_lowercase();
-    TIMELINE_GAMES
-        .iter()
-        .filter(|game| {
-            game.name.to_lowercase().contains(&query_lower)
-                || game.genre.to_lowercase().contains(&query_lower)
-                || game
-                    .deck
-                    .as_ref()
-                    .is_some_and(|d| d.to_lowercase().contains(&query_lower))
-        })
-        .collect()
+    database().search_games(query)
+}
+
+/// Find games that match a search query and filters, best match first
+pub fn search_games_filtered(query: &str, filters: &SearchFilters) -> Vec<SearchHit> {
+    database().search(query, filters)
 }
 
 /// Group games by their primary platform
 pub fn games_by_platform() -> HashMap<String, Vec<&'static TimelineGame>> {
     let mut platform_games: HashMap<String, Vec<&'static TimelineGame>> = HashMap::new();
 
-    for game in TIMELINE_GAMES.iter() {
+    for game in all_games() {
         if let Some(platform) = game.platforms.first() {
             platform_games
                 .entry(platform.to_string())
@@ -881,4 +902,10 @@ mod tests {
         assert!(!years.is_empty());
         assert!(years.windows(2).all(|w| w[0] < w[1])); // Sorted
     }
+
+    #[test]
+    fn test_random_exemplar_comes_from_the_database() {
+        let exemplar = random_exemplar().expect("the database has games");
+        assert!(database().get(exemplar.id).is_some());
+    }
 }
@@ -1,7 +1,8 @@
 //! Graph building for vintage_blending_core integration
 
+use super::database::all_games;
 use super::eras::{Era, era_for_year};
-use super::games::{TIMELINE_GAMES, TimelineGame};
+use super::games::TimelineGame;
 
 use petgraph::graph::Graph;
 use std::collections::HashMap;
@@ -20,7 +21,7 @@ pub fn build_game_graph() -> Graph<GameNode, f32> {
     let mut nodes = Vec::new();
 
     // First pass: Create nodes for all games
-    for game in TIMELINE_GAMES.iter() {
+    for game in all_games() {
         let metadata = game_to_metadata(game);
         let node = GameNode { game, metadata };
         nodes.push(node);
@@ -242,16 +243,17 @@ fn calculate_similarity(game1: &GameMetadata, game2: &GameMetadata) -> f32 {
 
 /// Find the most similar games to a given game
 pub fn find_similar_games(game_id: u32, count: usize) -> Vec<(&'static TimelineGame, f32)> {
-    let target_game = match TIMELINE_GAMES.iter().find(|g| g.id == game_id) {
-        Some(game) => game,
+    let games = all_games();
+    let target_game = match games.iter().find(|g| g.id == game_id) {
+        Some(game) => *game,
         None => return Vec::new(),
     };
 
     let target_metadata = game_to_metadata(target_game);
     let sim_engine = SimilarityEngine::new();
 
-    let mut similarities: Vec<(&'static TimelineGame, f32)> = TIMELINE_GAMES
-        .iter()
+    let mut similarities: Vec<(&'static TimelineGame, f32)> = games
+        .into_iter()
         .filter(|g| g.id != game_id)
         .map(|game| {
             let metadata = game_to_metadata(game);
@@ -272,8 +274,8 @@ pub fn build_era_subgraph(eras: &[Era]) -> Graph<GameNode, f32> {
     let mut nodes = Vec::new();
 
     // Filter games by era
-    let era_games: Vec<_> = TIMELINE_GAMES
-        .iter()
+    let era_games: Vec<_> = all_games()
+        .into_iter()
         .filter(|game| {
             if let Some(game_era) = era_for_year(game.year) {
                 eras.contains(&game_era)
@@ -284,7 +286,7 @@ pub fn build_era_subgraph(eras: &[Era]) -> Graph<GameNode, f32> {
         .collect();
 
     // Create nodes
-    for game in era_games.iter() {
+    for game in era_games {
         let metadata = game_to_metadata(game);
         let node = GameNode { game, metadata };
         nodes.push(node);
@@ -320,7 +322,7 @@ mod tests {
 
     #[test]
     fn test_metadata_conversion() {
-        if let Some(game) = TIMELINE_GAMES.first() {
+        if let Some(game) = all_games().first() {
             let metadata = game_to_metadata(game);
             assert_eq!(metadata.name, game.name);
             assert_eq!(metadata.game_id, game.id.to_string());
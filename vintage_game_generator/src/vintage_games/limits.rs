@@ -0,0 +1,241 @@
+//! Hardware limits of each era, and how strictly generation keeps to them
+//!
+//! Every era came with hard limits: colors on screen, sprites, the voices of
+//! the sound chip, the text a dialogue box held, and the story a cartridge
+//! had room for. A project's authenticity level, from loose (0.0) to strict
+//! (1.0), slides every limit from [`LOOSE_LIMITS`], which only evoke the
+//! era, to the era's own. The limits are interpolated geometrically, so the
+//! middle of the slider lands halfway in proportion rather than in count.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::database::all_games;
+use super::eras::Era;
+
+/// What a game may use of each limited resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HardwareLimits {
+    /// Colors on screen at once
+    pub palette_colors: u32,
+    /// Distinct character sprites
+    pub sprites: u32,
+    /// Voices the sound chip plays at once
+    pub music_channels: u32,
+    /// Characters of text a dialogue box holds
+    pub dialogue_chars: u32,
+    /// Story beats of the main plot
+    pub plot_beats: u32,
+}
+
+/// Limits of nostalgia-flavored output, generous enough for any era
+pub const LOOSE_LIMITS: HardwareLimits = HardwareLimits {
+    palette_colors: 256,
+    sprites: 128,
+    music_channels: 16,
+    dialogue_chars: 240,
+    plot_beats: 16,
+};
+
+impl Era {
+    /// What the typical hardware of the era allowed
+    pub fn hardware_limits(self) -> HardwareLimits {
+        match self {
+            // Arcade boards of the early eighties
+            Era::ArcadeGoldenAge => HardwareLimits {
+                palette_colors: 16,
+                sprites: 8,
+                music_channels: 3,
+                dialogue_chars: 32,
+                plot_beats: 1,
+            },
+            // NES and Master System
+            Era::EarlyConsole => HardwareLimits {
+                palette_colors: 25,
+                sprites: 16,
+                music_channels: 5,
+                dialogue_chars: 48,
+                plot_beats: 3,
+            },
+            // Game Boy, Genesis and PC Engine
+            Era::Late8BitEarly16 => HardwareLimits {
+                palette_colors: 61,
+                sprites: 32,
+                music_channels: 10,
+                dialogue_chars: 72,
+                plot_beats: 5,
+            },
+            // SNES
+            Era::Peak16Bit => HardwareLimits {
+                palette_colors: 256,
+                sprites: 64,
+                music_channels: 8,
+                dialogue_chars: 96,
+                plot_beats: 8,
+            },
+        }
+    }
+}
+
+/// How firmly the prompts hold generation to the limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strictness {
+    Loose,
+    Faithful,
+    Strict,
+}
+
+impl Strictness {
+    pub fn for_level(level: f32) -> Self {
+        if level < 1.0 / 3.0 {
+            Strictness::Loose
+        } else if level < 2.0 / 3.0 {
+            Strictness::Faithful
+        } else {
+            Strictness::Strict
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Strictness::Loose => "Loose",
+            Strictness::Faithful => "Faithful",
+            Strictness::Strict => "Strict",
+        }
+    }
+}
+
+impl fmt::Display for Strictness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// A limit a design step is asked to keep to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Palette,
+    Sprites,
+    MusicChannels,
+    DialogueLength,
+    PlotComplexity,
+}
+
+impl Limit {
+    pub const ALL: [Limit; 5] = [
+        Limit::Palette,
+        Limit::Sprites,
+        Limit::MusicChannels,
+        Limit::DialogueLength,
+        Limit::PlotComplexity,
+    ];
+}
+
+/// The limits of an era at an authenticity level
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AuthenticLimits {
+    pub era: Era,
+    /// 0.0 loose to 1.0 strict
+    pub level: f32,
+    pub limits: HardwareLimits,
+}
+
+impl AuthenticLimits {
+    pub fn new(era: Era, level: f32) -> Self {
+        let level = level.clamp(0.0, 1.0);
+        let strict = era.hardware_limits();
+        let scale = |loose: u32, strict: u32| -> u32 {
+            let loose = f64::from(loose.max(1));
+            let strict = f64::from(strict.max(1));
+            (loose * (strict / loose).powf(f64::from(level))).round() as u32
+        };
+        Self {
+            era,
+            level,
+            limits: HardwareLimits {
+                palette_colors: scale(LOOSE_LIMITS.palette_colors, strict.palette_colors),
+                sprites: scale(LOOSE_LIMITS.sprites, strict.sprites),
+                music_channels: scale(LOOSE_LIMITS.music_channels, strict.music_channels),
+                dialogue_chars: scale(LOOSE_LIMITS.dialogue_chars, strict.dialogue_chars),
+                plot_beats: scale(LOOSE_LIMITS.plot_beats, strict.plot_beats),
+            },
+        }
+    }
+
+    pub fn strictness(&self) -> Strictness {
+        Strictness::for_level(self.level)
+    }
+
+    /// Instructions holding a prompt to the `kept` limits, worded as firmly
+    /// as the level asks
+    pub fn rules(&self, kept: &[Limit]) -> String {
+        let limits = &self.limits;
+        let lead = match self.strictness() {
+            Strictness::Loose => format!(
+                "Evoke the {} without being bound by its hardware; as a rough guide:",
+                self.era.name()
+            ),
+            Strictness::Faithful => format!(
+                "Stay close to the hardware limits of the {}:",
+                self.era.name()
+            ),
+            Strictness::Strict => format!(
+                "Keep strictly to the hardware limits of the {}; never exceed them:",
+                self.era.name()
+            ),
+        };
+        let mut rules = vec![lead];
+        for limit in kept {
+            rules.push(match limit {
+                Limit::Palette => format!(
+                    "- At most {} colors on screen at once",
+                    limits.palette_colors
+                ),
+                Limit::Sprites => {
+                    format!("- At most {} distinct character sprites", limits.sprites)
+                }
+                Limit::MusicChannels => format!(
+                    "- Music and sound effects share {} sound channels",
+                    limits.music_channels
+                ),
+                Limit::DialogueLength => format!(
+                    "- Every line of dialogue fits a text box of {} characters",
+                    limits.dialogue_chars
+                ),
+                Limit::PlotComplexity => format!(
+                    "- The main plot has at most {} story beats",
+                    limits.plot_beats
+                ),
+            });
+        }
+        rules.join("\n")
+    }
+}
+
+/// Era most of the named games come from, matched by name against the
+/// timeline
+pub fn era_of_games<S: AsRef<str>>(names: &[S]) -> Option<Era> {
+    let mut counts: Vec<(Era, usize)> = Vec::new();
+    for name in names {
+        let name = name.as_ref().trim().to_lowercase();
+        let Some(game) = all_games()
+            .into_iter()
+            .find(|game| game.name.to_lowercase() == name)
+        else {
+            continue;
+        };
+        let Some(era) = super::eras::era_for_year(game.year) else {
+            continue;
+        };
+        match counts.iter_mut().find(|(counted, _)| *counted == era) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((era, 1)),
+        }
+    }
+    // The first era named wins a tie
+    counts
+        .into_iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|(era, _)| era)
+}
@@ -3,20 +3,42 @@
 //! This module contains a curated timeline of exemplar games from the golden and retro eras.
 //! Each year features the highest-rated game from up to 3 different genres.
 //! Games are selected to serve as creative inspiration for the AI RPG generator.
+//! Era packs extend the timeline to other periods at startup, see [`era_packs`],
+//! and users can add their own games, see [`database`].
 
+pub mod database;
+pub mod era_packs;
 pub mod eras;
+pub mod facets;
 pub mod games;
 pub mod graph;
+pub mod limits;
 pub mod platforms;
+pub mod search;
+pub mod surprise;
+pub mod visuals;
 
 // Re-export commonly used items
+pub use database::{
+    GameDatabase, all_games, database, database_mut, database_revision, loaded_era_packs,
+    timeline_span,
+};
+pub use era_packs::EraPackInfo;
 pub use eras::{Era, era_description, era_for_year, games_by_era};
+pub use facets::{
+    FacetCounts, Facets, PlatformFamily, all_developers, faceted_games, games_by_developer,
+    games_by_platform_family,
+};
 pub use games::{
-    TIMELINE_GAMES, TimelineGame, all_genres, games_by_genre, games_by_year, search_games,
+    TIMELINE_GAMES, TimelineGame, all_genres, all_platforms, games_by_genre, games_by_year,
+    search_games, search_games_filtered,
 };
 pub use graph::{GameNode, build_game_graph};
+pub use limits::{AuthenticLimits, HardwareLimits, Limit, Strictness};
 pub use platforms::{PLATFORM_INFO, PlatformInfo, get_platform_info};
+pub use search::{SearchFilters, SearchHit};
+pub use visuals::{GAME_VISUALS, GameVisuals, visuals_for_game};
 
-/// Timeline span
+/// Span of the embedded timeline; [`timeline_span`] includes the era packs
 pub const TIMELINE_START: i32 = 1980;
 pub const TIMELINE_END: i32 = 1995;
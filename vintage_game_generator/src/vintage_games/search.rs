@@ -0,0 +1,204 @@
+//! Fuzzy, ranked search over the game database
+//!
+//! A query matches a game when it is part of the name, developer, genre or
+//! description, or when each of its words is close to a word of the name,
+//! so "zeda" still finds The Legend of Zelda. Hits are ranked by how well
+//! they match and then by how prominent the game is, and can be narrowed
+//! down to a year range, a platform and a genre.
+
+use std::ops::RangeInclusive;
+
+use super::games::TimelineGame;
+
+/// What a search is narrowed down to
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchFilters {
+    pub years: Option<RangeInclusive<i32>>,
+    /// A platform the game is on, ignoring case
+    pub platform: Option<String>,
+    /// Genre of the game, ignoring case
+    pub genre: Option<String>,
+}
+
+impl SearchFilters {
+    pub fn is_empty(&self) -> bool {
+        self.years.is_none() && self.platform.is_none() && self.genre.is_none()
+    }
+
+    pub fn matches(&self, game: &TimelineGame) -> bool {
+        self.years
+            .as_ref()
+            .is_none_or(|years| years.contains(&game.year))
+            && self.platform.as_ref().is_none_or(|platform| {
+                game.platforms
+                    .iter()
+                    .any(|p| p.eq_ignore_ascii_case(platform))
+            })
+            && self
+                .genre
+                .as_ref()
+                .is_none_or(|genre| game.genre.eq_ignore_ascii_case(genre))
+    }
+}
+
+/// A game that matched a search
+#[derive(Debug, Clone, Copy)]
+pub struct SearchHit {
+    pub game: &'static TimelineGame,
+    /// Higher is better; match quality first, prominence second
+    pub score: f32,
+}
+
+/// The games that match `query` and `filters`, best first. An empty query
+/// keeps every game the filters allow, in their original order.
+pub fn rank_games(
+    games: impl IntoIterator<Item = &'static TimelineGame>,
+    query: &str,
+    filters: &SearchFilters,
+) -> Vec<SearchHit> {
+    let query = normalize(query);
+    let games = games.into_iter().filter(|game| filters.matches(game));
+
+    if query.is_empty() {
+        return games
+            .map(|game| SearchHit {
+                game,
+                score: prominence(game),
+            })
+            .collect();
+    }
+
+    let mut hits: Vec<SearchHit> = games
+        .filter_map(|game| {
+            let quality = match_quality(&query, game)?;
+            Some(SearchHit {
+                game,
+                score: quality + prominence(game),
+            })
+        })
+        .collect();
+    hits.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.game.year.cmp(&b.game.year))
+            .then_with(|| a.game.name.cmp(b.game.name))
+    });
+    hits
+}
+
+/// How well a normalized query matches the game, from 0 to 1
+fn match_quality(query: &str, game: &TimelineGame) -> Option<f32> {
+    let name = normalize(game.name);
+    if name == query {
+        return Some(1.0);
+    }
+    if name.starts_with(query) {
+        return Some(0.9);
+    }
+    if name.contains(query) {
+        return Some(0.8);
+    }
+    if let Some(quality) = fuzzy_words(query, &name) {
+        return Some(0.5 + 0.2 * quality);
+    }
+
+    let contains = |text: &str| normalize(text).contains(query);
+    if game.developer.is_some_and(contains) {
+        Some(0.45)
+    } else if contains(game.genre) {
+        Some(0.4)
+    } else if game.deck.is_some_and(contains) {
+        Some(0.3)
+    } else {
+        None
+    }
+}
+
+/// How closely every word of the query matches some word of the name, from
+/// 0 to 1, or `None` if a word has no match within its typo tolerance
+fn fuzzy_words(query: &str, name: &str) -> Option<f32> {
+    let words: Vec<&str> = name.split(' ').collect();
+    let mut total = 0.0;
+    let mut count = 0;
+
+    for query_word in query.split(' ') {
+        let tolerance = typo_tolerance(query_word);
+        let best = words
+            .iter()
+            .filter_map(|word| {
+                if *word == query_word {
+                    return Some(1.0);
+                }
+                if query_word.len() >= 2 && word.starts_with(query_word) {
+                    return Some(0.9);
+                }
+                let distance = edit_distance(query_word, word);
+                (distance <= tolerance)
+                    .then(|| 1.0 - distance as f32 / (query_word.chars().count() as f32 + 1.0))
+            })
+            .fold(None, |best: Option<f32>, quality| {
+                Some(best.map_or(quality, |best| best.max(quality)))
+            })?;
+        total += best;
+        count += 1;
+    }
+
+    (count > 0).then(|| total / count as f32)
+}
+
+/// Typos forgiven in a query word: none in very short words, so "of" does
+/// not match every "on"
+fn typo_tolerance(word: &str) -> usize {
+    match word.chars().count() {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    }
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions
+/// and swaps of neighbouring characters each count as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// A small bonus for games released on many platforms, found in several
+/// data sources, or with a description, so well-known games rank first
+/// among equally good matches. Never more than the gap between match tiers.
+fn prominence(game: &TimelineGame) -> f32 {
+    let platforms = game.platforms.len().min(5) as f32 / 5.0;
+    let sources = game.sources.len().saturating_sub(1).min(1) as f32;
+    let deck = if game.deck.is_some() { 1.0 } else { 0.0 };
+    0.03 * platforms + 0.01 * sources + 0.01 * deck
+}
+
+/// Lowercase words separated by single spaces, without punctuation
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,141 @@
+//! "Surprise me" picks of games to blend
+//!
+//! Two or three games from different eras and genres, each drawn with a
+//! preference for games neither close to nor far from the ones drawn
+//! before: too similar and the blend brings nothing new, too different and
+//! it falls apart. Games are drawn with a seeded RNG, so a seed always
+//! picks the same games from the same database, and a project's surprises
+//! come in the same order every time it is opened.
+
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use rand::distr::Distribution;
+use rand::distr::weighted::WeightedIndex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::database::all_games;
+use super::eras::{Era, era_for_year};
+use super::games::TimelineGame;
+use super::graph::find_similar_games;
+
+/// Number of games in a surprise blend
+pub const SURPRISE_GAMES: RangeInclusive<usize> = 2..=3;
+
+/// Similarity to the games drawn before that is the most likely to be drawn
+pub const TARGET_SIMILARITY: f32 = 0.5;
+
+/// How quickly the chance of a game falls off away from the target
+const SIMILARITY_SPREAD: f32 = 0.2;
+
+/// Chance left to a game at any similarity, relative to one on target
+const MIN_CHANCE: f64 = 0.01;
+
+/// Era of a game, or its decade for years outside the eras
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Period {
+    Era(Era),
+    Decade(i32),
+}
+
+impl Period {
+    fn of(game: &TimelineGame) -> Self {
+        era_for_year(game.year).map_or(Period::Decade(game.year.div_euclid(10)), Period::Era)
+    }
+}
+
+/// Games for a surprise blend drawn with `seed`, two or three as the seed
+/// has it
+pub fn surprise_games(seed: u64) -> Vec<&'static TimelineGame> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let count = rng.random_range(SURPRISE_GAMES);
+    draw_games(&mut rng, count)
+}
+
+/// Seed of the surprise drawn after `draws` others in a project with the
+/// given generation seed: the generation seed first, then the seeds after it
+pub fn project_surprise_seed(generation_seed: u64, draws: u64) -> u64 {
+    generation_seed.wrapping_add(draws)
+}
+
+/// Up to `count` games drawn with `rng`: the first at random, each next one
+/// from another period and genre when the database has one, favoring a
+/// medium similarity to the games drawn before
+pub fn draw_games(rng: &mut impl Rng, count: usize) -> Vec<&'static TimelineGame> {
+    let games = all_games();
+    if games.is_empty() || count == 0 {
+        return Vec::new();
+    }
+
+    let mut picked = vec![games[rng.random_range(0..games.len())]];
+    // Summed similarity of every game to the picked ones
+    let mut similarity: HashMap<u32, f32> = HashMap::new();
+    while picked.len() < count {
+        let last = picked[picked.len() - 1];
+        for (game, score) in find_similar_games(last.id, usize::MAX) {
+            *similarity.entry(game.id).or_default() += score;
+        }
+
+        let candidates = candidates(&games, &picked);
+        if candidates.is_empty() {
+            break;
+        }
+        let chances: Vec<f64> = candidates
+            .iter()
+            .map(|game| {
+                let mean = similarity.get(&game.id).copied().unwrap_or(0.0) / picked.len() as f32;
+                chance(mean)
+            })
+            .collect();
+        let Ok(distribution) = WeightedIndex::new(&chances) else {
+            break;
+        };
+        picked.push(candidates[distribution.sample(rng)]);
+    }
+    picked
+}
+
+/// Games not drawn yet, keeping those from another period and genre than
+/// every drawn game if there are any, else those differing in either
+fn candidates(
+    games: &[&'static TimelineGame],
+    picked: &[&'static TimelineGame],
+) -> Vec<&'static TimelineGame> {
+    let new_period = |game: &TimelineGame| {
+        picked
+            .iter()
+            .all(|other| Period::of(other) != Period::of(game))
+    };
+    let new_genre = |game: &TimelineGame| picked.iter().all(|other| other.genre != game.genre);
+
+    let unpicked: Vec<&'static TimelineGame> = games
+        .iter()
+        .copied()
+        .filter(|game| picked.iter().all(|other| other.id != game.id))
+        .collect();
+    let both: Vec<_> = unpicked
+        .iter()
+        .copied()
+        .filter(|game| new_period(game) && new_genre(game))
+        .collect();
+    if !both.is_empty() {
+        return both;
+    }
+    let either: Vec<_> = unpicked
+        .iter()
+        .copied()
+        .filter(|game| new_period(game) || new_genre(game))
+        .collect();
+    if !either.is_empty() {
+        return either;
+    }
+    unpicked
+}
+
+/// Relative chance of drawing a game with the given mean similarity to the
+/// games drawn before, highest on the target
+fn chance(similarity: f32) -> f64 {
+    let distance = f64::from((similarity - TARGET_SIMILARITY) / SIMILARITY_SPREAD);
+    (-(distance * distance)).exp().max(MIN_CHANCE)
+}
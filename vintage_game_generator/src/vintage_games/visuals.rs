@@ -0,0 +1,56 @@
+//! How the timeline's games look on screen
+//!
+//! Read off each game's screenshot by a vision model when the timeline is
+//! generated. Games whose screenshot couldn't be read have no entry.
+
+use super::database::database;
+
+/// Palette, perspective and interface layout of a game's screenshot
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameVisuals {
+    pub game_id: u32,
+    /// Dominant colors as `#rrggbb`, most used first
+    pub palette: &'static [&'static str],
+    pub perspective: &'static str,
+    /// Interface elements and where they sit on screen
+    pub ui_layout: &'static [&'static str],
+}
+
+impl GameVisuals {
+    /// The descriptors as one line, e.g. "side view; palette #101820,
+    /// #3050a0; interface: score along the top edge"
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.perspective.is_empty() {
+            parts.push(self.perspective.to_string());
+        }
+        if !self.palette.is_empty() {
+            parts.push(format!("palette {}", self.palette.join(", ")));
+        }
+        if !self.ui_layout.is_empty() {
+            parts.push(format!("interface: {}", self.ui_layout.join(", ")));
+        }
+        parts.join("; ")
+    }
+}
+
+/// Visual descriptors of the timeline's games
+pub const GAME_VISUALS: &[GameVisuals] = &[];
+
+/// Visual descriptors of a game by ID
+pub fn visuals_for_game(game_id: u32) -> Option<&'static GameVisuals> {
+    GAME_VISUALS
+        .iter()
+        .find(|visuals| visuals.game_id == game_id)
+}
+
+/// "Name: descriptors" for the database game called `name`, ignoring case
+pub fn describe_game(name: &str) -> Option<String> {
+    let game = database()
+        .games()
+        .iter()
+        .copied()
+        .find(|game| game.name.eq_ignore_ascii_case(name.trim()))?;
+    let visuals = visuals_for_game(game.id)?;
+    Some(format!("{}: {}", game.name, visuals.describe()))
+}
@@ -0,0 +1,286 @@
+//! Side-by-side comparison of two versions of a generated image
+//!
+//! Pick a before and after image from the project's assets and the panel
+//! shows both next to a diff heatmap, so subtle changes such as palette
+//! enforcement effects are visible at a glance. Toggle the panel with F11.
+
+use crate::wizard::AppDirectories;
+use crate::wizard::i18n::Localizer;
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use std::path::{Path, PathBuf};
+use vintage_ai_client::image_diff::{DiffMode, ImageDiff, diff_images};
+
+/// Image extensions offered for comparison
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp"];
+
+/// Largest edge of each preview in the panel
+const PREVIEW_SIZE: f32 = 256.0;
+
+/// Textures and statistics for the current comparison
+struct Comparison {
+    before: egui::TextureHandle,
+    after: egui::TextureHandle,
+    heatmap: egui::TextureHandle,
+    diff: ImageDiff,
+}
+
+/// State of the asset comparison panel
+#[derive(Resource, Default)]
+pub struct AssetCompareState {
+    pub open: bool,
+    pub images: Vec<PathBuf>,
+    pub before: Option<usize>,
+    pub after: Option<usize>,
+    pub mode: DiffMode,
+    comparison: Option<Comparison>,
+    pub error: Option<String>,
+}
+
+impl AssetCompareState {
+    /// Collect images under the project's assets directory
+    fn refresh_images(&mut self, directories: &AppDirectories) {
+        let mut images = Vec::new();
+        collect_images(&directories.assets_dir, &mut images);
+        images.sort();
+
+        self.images = images;
+        self.before = self.before.filter(|&i| i < self.images.len());
+        self.after = self.after.filter(|&i| i < self.images.len());
+    }
+}
+
+fn collect_images(dir: &Path, images: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_images(&path, images);
+        } else if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+        {
+            images.push(path);
+        }
+    }
+}
+
+/// Toggle the comparison panel with F11
+pub fn toggle_asset_compare(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<AssetCompareState>,
+    directories: Res<AppDirectories>,
+) {
+    if keys.just_pressed(KeyCode::F11) {
+        state.open = !state.open;
+        if state.open {
+            state.refresh_images(&directories);
+        }
+    }
+}
+
+/// Draw the comparison window
+pub fn draw_asset_compare(
+    mut contexts: EguiContexts,
+    mut state: ResMut<AssetCompareState>,
+    directories: Res<AppDirectories>,
+    localizer: Res<Localizer>,
+) {
+    if !state.open {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let mut open = state.open;
+    let mut compare = false;
+
+    egui::Window::new(localizer.t("compare-title"))
+        .id(egui::Id::new("asset_compare"))
+        .open(&mut open)
+        .default_size([840.0, 420.0])
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                for (label, id) in [
+                    ("compare-before-label", "compare_before"),
+                    ("compare-after-label", "compare_after"),
+                ] {
+                    ui.label(localizer.t(label));
+                    let mut selected = if id == "compare_before" {
+                        state.before
+                    } else {
+                        state.after
+                    };
+                    let selected_text = selected
+                        .and_then(|i| state.images.get(i))
+                        .map(|p| display_name(p, &directories.assets_dir))
+                        .unwrap_or_else(|| localizer.t("compare-select-image"));
+
+                    egui::ComboBox::from_id_salt(id)
+                        .selected_text(selected_text)
+                        .show_ui(ui, |ui| {
+                            for (idx, path) in state.images.iter().enumerate() {
+                                let name = display_name(path, &directories.assets_dir);
+                                if ui
+                                    .selectable_value(&mut selected, Some(idx), name)
+                                    .changed()
+                                {
+                                    compare = true;
+                                }
+                            }
+                        });
+
+                    if id == "compare_before" {
+                        state.before = selected;
+                    } else {
+                        state.after = selected;
+                    }
+                }
+
+                if ui.button(localizer.t("window-refresh")).clicked() {
+                    state.refresh_images(&directories);
+                    compare = true;
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(localizer.t("compare-diff"));
+                let mut mode = state.mode;
+                compare |= ui
+                    .selectable_value(&mut mode, DiffMode::Pixel, localizer.t("compare-per-pixel"))
+                    .changed();
+                compare |= ui
+                    .selectable_value(
+                        &mut mode,
+                        DiffMode::PaletteBucket,
+                        localizer.t("compare-palette-buckets"),
+                    )
+                    .on_hover_text(localizer.t("compare-palette-buckets-hint"))
+                    .changed();
+                state.mode = mode;
+            });
+
+            if state.images.is_empty() {
+                ui.label(
+                    egui::RichText::new(localizer.t("compare-no-images"))
+                        .small()
+                        .weak(),
+                );
+            }
+
+            if let Some(error) = &state.error {
+                ui.colored_label(egui::Color32::from_rgb(255, 100, 100), error);
+            }
+
+            if let Some(comparison) = &state.comparison {
+                ui.separator();
+                render_comparison(ui, comparison, &localizer);
+            }
+        });
+
+    state.open = open;
+
+    if compare {
+        run_comparison(ctx, &mut state, &localizer);
+    }
+}
+
+fn display_name(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+fn run_comparison(ctx: &egui::Context, state: &mut AssetCompareState, localizer: &Localizer) {
+    let (Some(before), Some(after)) = (
+        state.before.and_then(|i| state.images.get(i)),
+        state.after.and_then(|i| state.images.get(i)),
+    ) else {
+        state.comparison = None;
+        return;
+    };
+
+    let loaded = image::open(before).and_then(|b| image::open(after).map(|a| (b, a)));
+    let (before, after) = match loaded {
+        Ok(images) => images,
+        Err(e) => {
+            state.comparison = None;
+            state.error =
+                Some(localizer.t_args("compare-load-failed", &[("error", e.to_string().into())]));
+            return;
+        }
+    };
+
+    let diff = diff_images(&before, &after, state.mode);
+    let before = to_texture(ctx, "compare_before", &before.to_rgba8());
+    let after = to_texture(ctx, "compare_after", &after.to_rgba8());
+    let heatmap = to_texture(ctx, "compare_heatmap", &diff.heatmap);
+
+    state.comparison = Some(Comparison {
+        before,
+        after,
+        heatmap,
+        diff,
+    });
+    state.error = None;
+}
+
+fn to_texture(ctx: &egui::Context, name: &str, image: &image::RgbaImage) -> egui::TextureHandle {
+    let size = [image.width() as usize, image.height() as usize];
+    let color_image = egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+    // Nearest filtering so individual pixels stay visible when scaled up
+    ctx.load_texture(name, color_image, egui::TextureOptions::NEAREST)
+}
+
+fn render_comparison(ui: &mut egui::Ui, comparison: &Comparison, localizer: &Localizer) {
+    let diff = &comparison.diff;
+    ui.horizontal(|ui| {
+        ui.label(localizer.t_args(
+            "compare-pixels-changed",
+            &[
+                ("changed", diff.changed_pixels.into()),
+                ("total", diff.total_pixels.into()),
+                (
+                    "percent",
+                    format!("{:.1}", diff.changed_ratio() * 100.0).into(),
+                ),
+            ],
+        ));
+        ui.separator();
+        ui.label(localizer.t_args(
+            "compare-deltas",
+            &[
+                ("mean", format!("{:.1}", diff.mean_delta * 100.0).into()),
+                ("max", format!("{:.1}", diff.max_delta * 100.0).into()),
+            ],
+        ));
+        if diff.resized {
+            ui.separator();
+            ui.label(
+                egui::RichText::new(localizer.t("compare-resized"))
+                    .color(egui::Color32::from_rgb(255, 200, 100)),
+            );
+        }
+    });
+
+    ui.horizontal(|ui| {
+        for (label, texture) in [
+            ("compare-before", &comparison.before),
+            ("compare-after", &comparison.after),
+            ("compare-heatmap", &comparison.heatmap),
+        ] {
+            ui.vertical(|ui| {
+                ui.label(localizer.t(label));
+                ui.add(
+                    egui::Image::new(texture)
+                        .fit_to_exact_size(egui::vec2(PREVIEW_SIZE, PREVIEW_SIZE)),
+                );
+            });
+        }
+    });
+}
@@ -0,0 +1,333 @@
+//! Bulk export of the project's artifacts
+//!
+//! Select artifacts by tag and type, pick an output format for images, data
+//! and audio, and export them to a folder for use in another engine or
+//! tool, or lay them out for an RPG Maker MV/MZ project. The export runs in
+//! the background and writes a manifest listing what was exported and what
+//! was skipped. Toggle the dialog with F7.
+
+use crate::wizard::AppDirectories;
+use crate::wizard::i18n::Localizer;
+use crate::wizard::pipeline::GenerationPipeline;
+use crate::wizard::state::{AppState, LogLevel};
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use tokio::sync::oneshot;
+use vintage_ai_client::artifacts::{ARTIFACT_INDEX_FILE, ArtifactIndex};
+use vintage_ai_client::export::bulk::{
+    AssetType, AudioFormat, BulkExport, DataFormat, EXPORT_MANIFEST_FILE, ExportFormats,
+    ExportManifest, ExportProfile, ImageFormat,
+};
+use vintage_ai_client::export::rpgmaker::{ROLE_TAG_PREFIX, RpgMakerLayout, RpgMakerVersion};
+
+/// Default output folder inside the project directory
+const EXPORT_DIR: &str = "export";
+
+/// State of the bulk export dialog
+#[derive(Resource, Default)]
+pub struct BulkExportState {
+    pub open: bool,
+    pub index: ArtifactIndex,
+    pub tags: BTreeSet<String>,
+    pub types: BTreeSet<AssetType>,
+    pub formats: ExportFormats,
+    pub profile: ExportProfile,
+    pub out_dir: String,
+    pub manifest: Option<ExportManifest>,
+    pub error: Option<String>,
+    pending: Option<oneshot::Receiver<anyhow::Result<ExportManifest>>>,
+}
+
+impl BulkExportState {
+    fn refresh(&mut self, directories: &AppDirectories) {
+        match ArtifactIndex::load(directories.project_dir.join(ARTIFACT_INDEX_FILE)) {
+            Ok(index) => {
+                self.index = index;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(format!("{e:#}")),
+        }
+        let tags = self.index.all_tags();
+        self.tags.retain(|tag| tags.contains(tag.as_str()));
+        if self.out_dir.is_empty() {
+            self.out_dir = directories
+                .project_dir
+                .join(EXPORT_DIR)
+                .display()
+                .to_string();
+        }
+    }
+
+    fn export(&self) -> BulkExport {
+        BulkExport {
+            tags: self.tags.clone(),
+            types: self.types.clone(),
+            formats: self.formats,
+            profile: self.profile,
+        }
+    }
+}
+
+/// Toggle the dialog with F7
+pub fn toggle_bulk_export(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<BulkExportState>,
+    directories: Res<AppDirectories>,
+) {
+    if keys.just_pressed(KeyCode::F7) {
+        state.open = !state.open;
+        if state.open {
+            state.refresh(&directories);
+        }
+    }
+}
+
+/// Draw the export dialog
+pub fn draw_bulk_export(
+    mut contexts: EguiContexts,
+    mut state: ResMut<BulkExportState>,
+    mut app_state: ResMut<AppState>,
+    pipeline: Res<GenerationPipeline>,
+    localizer: Res<Localizer>,
+) {
+    if !state.open {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+    let state = &mut *state;
+
+    if let Some(pending) = state.pending.as_mut() {
+        match pending.try_recv() {
+            Ok(Ok(manifest)) => {
+                state.pending = None;
+                app_state.add_log(
+                    LogLevel::Success,
+                    localizer.t_args(
+                        "bulk-export-done-log",
+                        &[
+                            ("count", manifest.files.len().into()),
+                            ("dir", state.out_dir.clone().into()),
+                            ("skipped", manifest.skipped.len().into()),
+                        ],
+                    ),
+                );
+                state.manifest = Some(manifest);
+            }
+            Ok(Err(e)) => {
+                state.pending = None;
+                state.error = Some(
+                    localizer.t_args("bulk-export-failed", &[("error", format!("{e:#}").into())]),
+                );
+            }
+            Err(oneshot::error::TryRecvError::Empty) => {}
+            Err(oneshot::error::TryRecvError::Closed) => state.pending = None,
+        }
+    }
+
+    let mut open = state.open;
+    let mut export = false;
+
+    egui::Window::new(localizer.t("bulk-export-title"))
+        .id(egui::Id::new("bulk_export"))
+        .open(&mut open)
+        .default_size([560.0, 480.0])
+        .show(ctx, |ui| {
+            let all_tags = state.index.all_tags();
+            ui.horizontal_wrapped(|ui| {
+                ui.label(localizer.t("tags-label"));
+                if all_tags.is_empty() {
+                    ui.label(egui::RichText::new(localizer.t("bulk-export-no-tags")).weak());
+                }
+                for tag in all_tags {
+                    let mut selected = state.tags.contains(tag);
+                    if ui.toggle_value(&mut selected, tag).changed() {
+                        if selected {
+                            state.tags.insert(tag.to_string());
+                        } else {
+                            state.tags.remove(tag);
+                        }
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label(localizer.t("bulk-export-types"));
+                for asset_type in AssetType::ALL {
+                    let mut selected = state.types.contains(&asset_type);
+                    if ui
+                        .toggle_value(&mut selected, format!("{asset_type:?}"))
+                        .changed()
+                    {
+                        if selected {
+                            state.types.insert(asset_type);
+                        } else {
+                            state.types.remove(&asset_type);
+                        }
+                    }
+                }
+            });
+            ui.label(
+                egui::RichText::new(localizer.t("bulk-export-select-hint"))
+                    .small()
+                    .weak(),
+            );
+            ui.separator();
+
+            egui::Grid::new("export_formats").show(ui, |ui| {
+                ui.label(localizer.t("bulk-export-layout"));
+                ui.horizontal(|ui| {
+                    let profile = &mut state.profile;
+                    ui.selectable_value(
+                        profile,
+                        ExportProfile::Generic,
+                        localizer.t("bulk-export-folder-per-type"),
+                    );
+                    for (version, label) in [
+                        (RpgMakerVersion::Mv, "RPG Maker MV"),
+                        (RpgMakerVersion::Mz, "RPG Maker MZ"),
+                    ] {
+                        let selected = matches!(
+                            profile, ExportProfile::RpgMaker(layout) if layout.version == version
+                        );
+                        if ui.selectable_label(selected, label).clicked() {
+                            let source_tile_size = match profile {
+                                ExportProfile::RpgMaker(layout) => layout.source_tile_size,
+                                ExportProfile::Generic => {
+                                    RpgMakerLayout::default().source_tile_size
+                                }
+                            };
+                            *profile = ExportProfile::RpgMaker(RpgMakerLayout {
+                                version,
+                                source_tile_size,
+                            });
+                        }
+                    }
+                });
+                ui.end_row();
+                if let ExportProfile::RpgMaker(layout) = &mut state.profile {
+                    ui.label(localizer.t("bulk-export-tile-size"));
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut layout.source_tile_size)
+                                .range(8..=64)
+                                .suffix(" px"),
+                        )
+                        .on_hover_text(localizer.t("bulk-export-tile-size-hint"));
+                        ui.label(
+                            egui::RichText::new(role_tag_hint(&localizer))
+                                .small()
+                                .weak(),
+                        );
+                    });
+                    ui.end_row();
+                }
+                // RPG Maker takes PNG and OGG only
+                if state.profile == ExportProfile::Generic {
+                    ui.label(localizer.t("bulk-export-images"));
+                    ui.horizontal(|ui| {
+                        let image = &mut state.formats.image;
+                        ui.selectable_value(image, ImageFormat::Png, "PNG");
+                        ui.selectable_value(image, ImageFormat::Bmp, "BMP");
+                        ui.selectable_value(
+                            image,
+                            ImageFormat::IndexedPng,
+                            localizer.t("bulk-export-indexed-png"),
+                        );
+                    });
+                    ui.end_row();
+                    ui.label(localizer.t("bulk-export-data"));
+                    ui.horizontal(|ui| {
+                        let data = &mut state.formats.data;
+                        ui.selectable_value(data, DataFormat::Json, "JSON");
+                        ui.selectable_value(data, DataFormat::Ron, "RON");
+                    });
+                    ui.end_row();
+                    ui.label(localizer.t("bulk-export-audio"));
+                    ui.horizontal(|ui| {
+                        let audio = &mut state.formats.audio;
+                        ui.selectable_value(audio, AudioFormat::Wav, "WAV");
+                        ui.selectable_value(audio, AudioFormat::Ogg, "OGG")
+                            .on_hover_text(localizer.t("bulk-export-ogg-hint"));
+                    });
+                    ui.end_row();
+                }
+                ui.label(localizer.t("bulk-export-folder"));
+                ui.add(egui::TextEdit::singleline(&mut state.out_dir).desired_width(360.0));
+                ui.end_row();
+            });
+            ui.separator();
+
+            let selected = state.export().select(&state.index).len();
+            ui.horizontal(|ui| {
+                let ready = selected > 0 && state.pending.is_none() && !state.out_dir.is_empty();
+                export = ui
+                    .add_enabled(
+                        ready,
+                        egui::Button::new(
+                            localizer.t_args("bulk-export-button", &[("count", selected.into())]),
+                        ),
+                    )
+                    .clicked();
+                if state.pending.is_some() {
+                    ui.spinner();
+                }
+            });
+
+            if let Some(error) = &state.error {
+                ui.colored_label(egui::Color32::from_rgb(255, 100, 100), error);
+            }
+            if let Some(manifest) = &state.manifest {
+                ui.separator();
+                ui.label(localizer.t_args(
+                    "bulk-export-done",
+                    &[
+                        ("count", manifest.files.len().into()),
+                        ("manifest", EXPORT_MANIFEST_FILE.into()),
+                    ],
+                ));
+                egui::ScrollArea::vertical()
+                    .id_salt("export_skipped")
+                    .max_height(160.0)
+                    .show(ui, |ui| {
+                        for skipped in &manifest.skipped {
+                            ui.colored_label(
+                                egui::Color32::from_rgb(255, 200, 100),
+                                localizer.t_args(
+                                    "bulk-export-skipped",
+                                    &[
+                                        ("artifact", skipped.artifact.as_str().into()),
+                                        ("reason", skipped.reason.as_str().into()),
+                                    ],
+                                ),
+                            );
+                        }
+                    });
+            }
+        });
+
+    state.open = open;
+
+    if export {
+        let (sender, receiver) = oneshot::channel();
+        state.pending = Some(receiver);
+        state.error = None;
+        state.manifest = None;
+        let export = state.export();
+        let index = state.index.clone();
+        let out_dir = PathBuf::from(state.out_dir.trim());
+        pipeline.runtime.spawn_blocking(move || {
+            let _ = sender.send(export.run(&index, &out_dir));
+        });
+    }
+}
+
+/// How to place artifacts in an RPG Maker layout explicitly
+fn role_tag_hint(localizer: &Localizer) -> String {
+    localizer.t_args(
+        "bulk-export-role-tags",
+        &[("prefix", ROLE_TAG_PREFIX.into())],
+    )
+}
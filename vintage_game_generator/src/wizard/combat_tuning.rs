@@ -0,0 +1,297 @@
+//! Combat tuning for the project's Bevy export
+//!
+//! Loads the export's `assets/data/combat.ron`, offers sliders for the damage
+//! and XP curve values, and charts the result with the bevy-combat balance
+//! simulator: expected damage against rising defense, and how many battles
+//! each level takes. Saving writes the values back into the export, where
+//! `CombatPlugin` picks them up on the next start. Toggle the panel with F10.
+
+use crate::wizard::AppDirectories;
+use crate::wizard::i18n::Localizer;
+use bevy::prelude::*;
+use bevy_combat::balance::{self, CombatTuning, TUNING_ASSET_PATH};
+use bevy_combat::damage::{CombatStats, DamageType};
+use bevy_egui::{EguiContexts, egui};
+use egui_plot::{Legend, Line, Plot, PlotPoints};
+use std::path::PathBuf;
+
+/// Damage types charted against defense
+const CHARTED_DAMAGE_TYPES: [DamageType; 4] = [
+    DamageType::Physical,
+    DamageType::Magical,
+    DamageType::Corrupted,
+    DamageType::True,
+];
+
+/// State of the combat tuning panel
+#[derive(Resource)]
+pub struct CombatTuningState {
+    pub open: bool,
+    /// `combat.ron` of the export, `None` without an export
+    pub path: Option<PathBuf>,
+    pub tuning: CombatTuning,
+    /// Sample attacker for the damage preview
+    pub attacker: CombatStats,
+    pub xp_per_battle: u32,
+    pub max_level: u32,
+    pub status: Option<String>,
+}
+
+impl Default for CombatTuningState {
+    fn default() -> Self {
+        Self {
+            open: false,
+            path: None,
+            tuning: CombatTuning::default(),
+            attacker: CombatStats::default(),
+            xp_per_battle: 25,
+            max_level: 30,
+            status: None,
+        }
+    }
+}
+
+impl CombatTuningState {
+    /// Read the tuned values from the export, falling back to the defaults
+    fn reload(&mut self, directories: &AppDirectories, localizer: &Localizer) {
+        self.path = directories
+            .bevy_export_dir()
+            .map(|dir| dir.join(TUNING_ASSET_PATH));
+
+        let Some(path) = &self.path else {
+            self.status = Some(localizer.t("tuning-no-export"));
+            return;
+        };
+        if !path.exists() {
+            self.tuning = CombatTuning::default();
+            self.status = Some(localizer.t("tuning-defaults"));
+            return;
+        }
+        match CombatTuning::load(path) {
+            Ok(tuning) => {
+                self.tuning = tuning;
+                self.status = Some(localizer.t_args(
+                    "tuning-loaded",
+                    &[("path", path.display().to_string().into())],
+                ));
+            }
+            Err(e) => {
+                self.status = Some(localizer.t_args(
+                    "tuning-load-failed",
+                    &[
+                        ("path", path.display().to_string().into()),
+                        ("error", e.to_string().into()),
+                    ],
+                ))
+            }
+        }
+    }
+
+    fn save(&mut self, localizer: &Localizer) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        self.status = Some(match self.tuning.save(path) {
+            Ok(()) => localizer.t_args("saved", &[("path", path.display().to_string().into())]),
+            Err(e) => localizer.t_args(
+                "save-failed",
+                &[
+                    ("path", path.display().to_string().into()),
+                    ("error", e.to_string().into()),
+                ],
+            ),
+        });
+    }
+}
+
+/// Toggle the tuning panel with F10
+pub fn toggle_combat_tuning(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<CombatTuningState>,
+    directories: Res<AppDirectories>,
+    localizer: Res<Localizer>,
+) {
+    if keys.just_pressed(KeyCode::F10) {
+        state.open = !state.open;
+        if state.open {
+            state.reload(&directories, &localizer);
+        }
+    }
+}
+
+/// Draw the tuning window
+pub fn draw_combat_tuning(
+    mut contexts: EguiContexts,
+    mut state: ResMut<CombatTuningState>,
+    directories: Res<AppDirectories>,
+    localizer: Res<Localizer>,
+) {
+    if !state.open {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let mut open = state.open;
+    let mut reload = false;
+    let mut save = false;
+
+    egui::Window::new(localizer.t("tuning-title"))
+        .id(egui::Id::new("combat_tuning"))
+        .open(&mut open)
+        .default_size([720.0, 560.0])
+        .show(ctx, |ui| {
+            let state = &mut *state;
+
+            ui.columns(2, |columns| {
+                let ui = &mut columns[0];
+                ui.heading(localizer.t("tuning-damage"));
+                ui.add(
+                    egui::Slider::new(&mut state.tuning.damage.variance, 0.0..=0.5)
+                        .text(localizer.t("tuning-variance")),
+                );
+                ui.add(
+                    egui::Slider::new(&mut state.tuning.damage.min_damage, 0.0..=20.0)
+                        .text(localizer.t("tuning-min-damage")),
+                );
+
+                ui.heading(localizer.t("tuning-progression"));
+                ui.add(
+                    egui::Slider::new(&mut state.tuning.progression.base_xp, 10..=1000)
+                        .text(localizer.t("tuning-base-xp")),
+                );
+                ui.add(
+                    egui::Slider::new(&mut state.tuning.progression.xp_growth, 1.0..=2.0)
+                        .text(localizer.t("tuning-xp-growth")),
+                );
+
+                let ui = &mut columns[1];
+                ui.heading(localizer.t("tuning-preview"));
+                ui.add(
+                    egui::Slider::new(&mut state.attacker.attack, 1.0..=100.0)
+                        .text(localizer.t("tuning-attack")),
+                );
+                ui.add(
+                    egui::Slider::new(&mut state.attacker.magic_attack, 1.0..=100.0)
+                        .text(localizer.t("tuning-magic-attack")),
+                );
+                ui.add(
+                    egui::Slider::new(&mut state.attacker.crit_chance, 0.0..=1.0)
+                        .text(localizer.t("tuning-crit-chance")),
+                );
+                ui.add(
+                    egui::Slider::new(&mut state.attacker.crit_multiplier, 1.0..=4.0)
+                        .text(localizer.t("tuning-crit-multiplier")),
+                );
+                ui.add(
+                    egui::Slider::new(&mut state.xp_per_battle, 1..=500)
+                        .text(localizer.t("tuning-xp-per-battle")),
+                );
+                ui.add(
+                    egui::Slider::new(&mut state.max_level, 5..=99)
+                        .text(localizer.t("tuning-max-level")),
+                );
+            });
+
+            ui.separator();
+            ui.label(localizer.t("tuning-damage-chart"));
+            render_damage_chart(ui, state);
+
+            ui.label(localizer.t("tuning-pacing-chart"));
+            render_pacing_chart(ui, state, &localizer);
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button(localizer.t("window-reload")).clicked() {
+                    reload = true;
+                }
+                if ui
+                    .add_enabled(
+                        state.path.is_some(),
+                        egui::Button::new(localizer.t("tuning-save")),
+                    )
+                    .clicked()
+                {
+                    save = true;
+                }
+                if let Some(status) = &state.status {
+                    ui.label(egui::RichText::new(status).small().weak());
+                }
+            });
+        });
+
+    state.open = open;
+    if reload {
+        state.reload(&directories, &localizer);
+    }
+    if save {
+        state.save(&localizer);
+    }
+}
+
+fn render_damage_chart(ui: &mut egui::Ui, state: &CombatTuningState) {
+    let max_defense = (state.attacker.attack.max(state.attacker.magic_attack) * 2.5).ceil() as u32;
+
+    Plot::new("combat_damage")
+        .legend(Legend::default())
+        .height(180.0)
+        .include_y(0.0)
+        .allow_scroll(false)
+        .show(ui, |plot_ui| {
+            for damage_type in CHARTED_DAMAGE_TYPES {
+                let points: PlotPoints = (0..=max_defense)
+                    .map(|defense| {
+                        let target = CombatStats {
+                            defense: defense as f32,
+                            magic_defense: defense as f32,
+                            ..CombatStats::default()
+                        };
+                        let damage = balance::expected_damage(
+                            &state.attacker,
+                            &target,
+                            damage_type,
+                            &state.tuning.damage,
+                        );
+                        [defense as f64, damage as f64]
+                    })
+                    .collect();
+                plot_ui.line(Line::new(format!("{damage_type:?}"), points));
+            }
+        });
+}
+
+fn render_pacing_chart(ui: &mut egui::Ui, state: &CombatTuningState, localizer: &Localizer) {
+    let pacing = balance::level_pacing(
+        &state.tuning.progression,
+        state.xp_per_battle,
+        state.max_level,
+    );
+
+    Plot::new("combat_pacing")
+        .height(180.0)
+        .include_y(0.0)
+        .allow_scroll(false)
+        .show(ui, |plot_ui| {
+            let points: PlotPoints = pacing
+                .iter()
+                .map(|step| [step.level as f64, step.battles as f64])
+                .collect();
+            plot_ui.line(Line::new(localizer.t("tuning-battles"), points));
+        });
+
+    if let Some(last) = pacing.last() {
+        ui.label(
+            egui::RichText::new(localizer.t_args(
+                "tuning-pacing-summary",
+                &[
+                    ("level", last.level.into()),
+                    ("battles", last.battles.into()),
+                    ("xp", last.total_xp.into()),
+                ],
+            ))
+            .small()
+            .weak(),
+        );
+    }
+}
@@ -4,6 +4,10 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
+use crate::vintage_games::Era;
+use crate::vintage_games::limits::{self, AuthenticLimits};
+use vintage_ai_client::encounters::DifficultyPreset;
+
 /// Project configuration built through wizard and enriched by AI conversation
 /// This represents the user's preferences and constraints, not the full game specification
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +18,11 @@ pub struct ProjectConfig {
     pub author: Option<String>,
     #[serde(default = "default_version")]
     pub version: String,
+    /// Seed of all randomness that doesn't come from the AI: surprise
+    /// blends, dungeon layouts and generated names. Also sent with every
+    /// text request, so a rerun is as reproducible as the provider allows.
+    #[serde(default = "new_generation_seed")]
+    pub generation_seed: u64,
 
     #[serde(default)]
     pub metadata: ProjectMetadata,
@@ -33,6 +42,9 @@ pub struct ProjectConfig {
     #[serde(default)]
     pub technical: TechnicalSettings,
 
+    #[serde(default)]
+    pub authenticity: AuthenticitySettings,
+
     #[serde(default)]
     pub ai_context: AiContext,
 
@@ -47,6 +59,12 @@ fn default_version() -> String {
     "0.1.0".to_string()
 }
 
+/// A random generation seed for a new project, kept within the integers
+/// TOML can hold
+pub fn new_generation_seed() -> u64 {
+    rand::random::<u64>() >> 1
+}
+
 /// Simplified game specification for list mode display
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameSpecification {
@@ -84,6 +102,10 @@ pub struct BasicInfo {
     pub genre: String,
     pub target_audience: String,
     pub inspiration_notes: String, // Added for AI context
+    /// Language the game's text is generated in, e.g. `Japanese`,
+    /// independent of the wizard's UI language. English when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_language: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -93,6 +115,9 @@ pub struct GameplayDesign {
     pub progression_type: String,
     pub victory_conditions: Vec<String>,
     pub difficulty_curve: DifficultyCurve,
+    /// Preset the random encounter tables of the exported game use
+    #[serde(default)]
+    pub encounter_difficulty: DifficultyPreset,
     pub unique_mechanics: Vec<String>, // AI can expand on these
     pub player_motivation: String,     // AI can help define
 }
@@ -141,6 +166,13 @@ pub struct Features {
     pub weather_effects: bool,
     pub minimap: bool,
     pub achievements: bool,
+    /// Plan New Game+ and postgame content; adds a large generation request
+    #[serde(default)]
+    pub postgame: bool,
+    /// Repaint the exported world map in the game's art style; adds an
+    /// image edit request
+    #[serde(default)]
+    pub stylized_maps: bool,
     pub custom_features: Vec<CustomFeature>, // AI can add unique features
 }
 
@@ -190,6 +222,21 @@ pub struct TechnicalSettings {
     pub performance_target: String,
     pub target_platforms: Vec<String>,
     pub multiplayer: Option<MultiplayerConfig>,
+    /// Bevy project exported from this game, relative to the project directory.
+    /// Regenerated assets are mirrored into it while the wizard runs.
+    #[serde(default)]
+    pub bevy_export: Option<PathBuf>,
+}
+
+/// How closely generation keeps to the hardware limits of the game's era
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct AuthenticitySettings {
+    /// 0.0 (loose, nostalgia-flavored) to 1.0 (strict, hardware-accurate)
+    pub level: f32,
+    /// Era whose limits apply, the era of the reference games when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub era: Option<Era>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -317,6 +364,12 @@ impl ProjectConfig {
         if self.features.day_night_cycle {
             features.push("- 🌅 Day/Night Cycle".to_string());
         }
+        if self.features.postgame {
+            features.push("- 🏆 Postgame / New Game+".to_string());
+        }
+        if self.features.stylized_maps {
+            features.push("- 🗺️ Stylized World Map".to_string());
+        }
 
         for custom in &self.features.custom_features {
             features.push(format!("- 🎯 {}", custom.name));
@@ -325,6 +378,22 @@ impl ProjectConfig {
         features.join("\n")
     }
 
+    /// Era of most of the reference games, the 16-bit era when none of
+    /// them is on the timeline
+    pub fn reference_era(&self) -> Era {
+        limits::era_of_games(&self.visual_style.reference_games).unwrap_or(Era::Peak16Bit)
+    }
+
+    /// Limits generation keeps to, those of the configured era or else of
+    /// the reference era
+    pub fn authentic_limits(&self) -> AuthenticLimits {
+        let era = self
+            .authenticity
+            .era
+            .unwrap_or_else(|| self.reference_era());
+        AuthenticLimits::new(era, self.authenticity.level)
+    }
+
     /// Add a conversation entry
     pub fn add_conversation(&mut self, role: &str, content: &str, phase: &str) {
         self.ai_context
@@ -357,12 +426,14 @@ impl Default for ProjectConfig {
             description: None,
             author: None,
             version: default_version(),
+            generation_seed: new_generation_seed(),
             metadata: ProjectMetadata::default(),
             basic_info: BasicInfo::default(),
             gameplay: GameplayDesign::default(),
             visual_style: VisualStyle::default(),
             features: Features::default(),
             technical: TechnicalSettings::default(),
+            authenticity: AuthenticitySettings::default(),
             ai_context: AiContext::default(),
             wizard_state: WizardState::default(),
             game_specification: None,
@@ -475,6 +546,12 @@ impl ConfigManager {
         if self.config.features.day_night_cycle {
             features.push("Day/Night Cycle".to_string());
         }
+        if self.config.features.postgame {
+            features.push("Postgame / New Game+".to_string());
+        }
+        if self.config.features.stylized_maps {
+            features.push("Stylized World Map".to_string());
+        }
 
         for custom in &self.config.features.custom_features {
             features.push(custom.name.clone());
@@ -501,4 +578,19 @@ impl ProjectConfig {
 
         Ok(config)
     }
+
+    /// Write the generation seed into the project file at `path` if it has
+    /// none yet, as files from before seeds were recorded don't, so the
+    /// next run uses the same seed. The rest of the file is left as it is.
+    pub fn record_generation_seed(&self, path: &Path) -> Result<()> {
+        let content = std::fs::read_to_string(path).context("Failed to read project config")?;
+        let table: toml::Table =
+            toml::from_str(&content).context("Failed to parse project config")?;
+        if table.contains_key("generation_seed") {
+            return Ok(());
+        }
+        // Top-level keys go before the first table
+        let content = format!("generation_seed = {}\n{content}", self.generation_seed);
+        std::fs::write(path, content).context("Failed to record the generation seed")
+    }
 }
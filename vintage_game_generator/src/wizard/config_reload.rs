@@ -0,0 +1,409 @@
+//! Hot reload of the project configuration and prompt files
+//!
+//! When `project.toml` changes on disk the watchers reload it and compare
+//! it with the configuration loaded before, and a changed prompt template
+//! is run through the [`PromptValidator`](crate::metaprompts::PromptValidator).
+//! The reload panel lists what changed and offers to run again only the
+//! design steps whose prompts read the changed settings, and to queue the
+//! changed prompts for validation in their phase.
+
+use crate::metaprompts::{DESIGN_STEPS, ValidationResult};
+use crate::wizard::AppDirectories;
+use crate::wizard::config::ProjectConfig;
+use crate::wizard::i18n::Localizer;
+use crate::wizard::pipeline::GenerationPipeline;
+use crate::wizard::state::{AppState, LogLevel};
+use crate::wizard::watchers::FileEventType;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// A setting that differs between two configurations, by its dotted path
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigChange {
+    pub path: String,
+    /// `None` when the setting was added
+    pub before: Option<String>,
+    /// `None` when the setting was removed
+    pub after: Option<String>,
+}
+
+/// Settings that differ between `old` and `new`, sorted by path
+pub fn diff_configs(old: &ProjectConfig, new: &ProjectConfig) -> Result<Vec<ConfigChange>> {
+    let old = flatten_config(old)?;
+    let mut new = flatten_config(new)?;
+    let mut changes = Vec::new();
+    for (path, before) in old {
+        match new.remove(&path) {
+            Some(after) if after == before => {}
+            after => changes.push(ConfigChange {
+                path,
+                before: Some(before),
+                after,
+            }),
+        }
+    }
+    changes.extend(new.into_iter().map(|(path, after)| ConfigChange {
+        path,
+        before: None,
+        after: Some(after),
+    }));
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(changes)
+}
+
+/// Design steps whose prompts read settings that differ between `old` and
+/// `new`. The game's name goes into every step and keys the checkpoint, so
+/// renaming needs all of them. The rest of the configuration reaches the
+/// prompts through the brief, which only the core design reads; the asset
+/// descriptions are written from the core design, so they follow it. The
+/// content language also reaches the dialogue, and the hardware limits
+/// reach every step.
+pub fn affected_steps(old: &ProjectConfig, new: &ProjectConfig) -> Vec<&'static str> {
+    if run_name(old) != run_name(new) || old.authentic_limits() != new.authentic_limits() {
+        DESIGN_STEPS.to_vec()
+    } else if old.basic_info.content_language != new.basic_info.content_language {
+        // core_design, asset_descriptions and dialogue
+        DESIGN_STEPS[..3].to_vec()
+    } else if old.to_ai_summary() != new.to_ai_summary() {
+        // core_design and asset_descriptions
+        DESIGN_STEPS[..2].to_vec()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Name a design run is started under
+fn run_name(config: &ProjectConfig) -> &str {
+    config.name.as_deref().unwrap_or(&config.basic_info.name)
+}
+
+fn flatten_config(config: &ProjectConfig) -> Result<BTreeMap<String, String>> {
+    let value = toml::Value::try_from(config).context("Failed to serialize config")?;
+    let mut settings = BTreeMap::new();
+    flatten(String::new(), &value, &mut settings);
+    Ok(settings)
+}
+
+fn flatten(path: String, value: &toml::Value, settings: &mut BTreeMap<String, String>) {
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                flatten(join(key), value, settings);
+            }
+        }
+        toml::Value::Array(items) if items.iter().any(toml::Value::is_table) => {
+            for (i, item) in items.iter().enumerate() {
+                flatten(format!("{path}[{i}]"), item, settings);
+            }
+        }
+        toml::Value::String(text) => {
+            settings.insert(path, text.clone());
+        }
+        other => {
+            settings.insert(path, other.to_string());
+        }
+    }
+}
+
+/// A prompt template that changed on disk, with its validation result
+#[derive(Debug, Clone)]
+pub struct PromptChange {
+    pub path: PathBuf,
+    /// Phase directory the prompt belongs to
+    pub phase: String,
+    pub event_type: FileEventType,
+    pub validation: Option<ValidationResult>,
+    /// Why the prompt couldn't be validated
+    pub error: Option<String>,
+}
+
+/// Changes since the panel was last dismissed
+#[derive(Resource, Default)]
+pub struct ConfigReloadState {
+    pub open: bool,
+    pub changes: Vec<ConfigChange>,
+    /// Affected design steps, and whether to run each again
+    pub steps: Vec<(&'static str, bool)>,
+    /// The game was renamed, so the steps need a fresh run
+    pub full_run: bool,
+    /// The configuration the changes are listed against: the one before
+    /// the first change, or the one the steps last ran with
+    pub baseline: Option<ProjectConfig>,
+    /// The configuration as reloaded, to run the steps with
+    pub config: Option<ProjectConfig>,
+    pub prompts: Vec<PromptChange>,
+}
+
+impl ConfigReloadState {
+    /// Note a reload of the configuration from `old` to `new`. Changes are
+    /// listed against the baseline, so a setting changed back drops out
+    /// again. Opens the panel if anything changed.
+    pub fn record_config(&mut self, old: &ProjectConfig, new: &ProjectConfig) -> Result<()> {
+        let baseline = self.baseline.get_or_insert_with(|| old.clone());
+        self.changes = diff_configs(baseline, new)?;
+        let steps = affected_steps(baseline, new);
+        self.full_run = steps.len() == DESIGN_STEPS.len();
+        // Keep the selection of steps that stay affected
+        self.steps = steps
+            .into_iter()
+            .map(|step| {
+                let selected = self
+                    .steps
+                    .iter()
+                    .find(|(existing, _)| *existing == step)
+                    .is_none_or(|(_, selected)| *selected);
+                (step, selected)
+            })
+            .collect();
+
+        self.config = Some(new.clone());
+        self.open |= !self.changes.is_empty();
+        Ok(())
+    }
+
+    /// Add a changed prompt, replacing an earlier change to the same file
+    pub fn record_prompt(&mut self, change: PromptChange) {
+        self.prompts.retain(|prompt| prompt.path != change.path);
+        self.prompts.push(change);
+        self.open = true;
+    }
+
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Panel listing reloaded changes, opened when the watchers find some
+pub fn draw_config_reload(
+    mut contexts: EguiContexts,
+    mut state: ResMut<ConfigReloadState>,
+    mut app_state: ResMut<AppState>,
+    pipeline: Res<GenerationPipeline>,
+    directories: Res<AppDirectories>,
+    localizer: Res<Localizer>,
+) {
+    if !state.open {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let mut open = state.open;
+    egui::Window::new(localizer.t("reload-title"))
+        .id(egui::Id::new("config_reload"))
+        .open(&mut open)
+        .default_width(560.0)
+        .show(ctx, |ui| {
+            if !state.changes.is_empty() {
+                ui.heading("project.toml");
+                draw_changes(ui, &state.changes, &localizer);
+                ui.add_space(8.0);
+                draw_affected_steps(
+                    ui,
+                    &mut state,
+                    &mut app_state,
+                    &pipeline,
+                    &directories,
+                    &localizer,
+                );
+                ui.separator();
+            }
+
+            if !state.prompts.is_empty() {
+                ui.heading(localizer.t("reload-prompts"));
+                draw_prompt_changes(ui, &mut state, &mut app_state, &localizer);
+                ui.separator();
+            }
+
+            if ui.button(localizer.t("reload-dismiss")).clicked() {
+                state.clear();
+            }
+        });
+    state.open &= open;
+}
+
+fn draw_changes(ui: &mut egui::Ui, changes: &[ConfigChange], localizer: &Localizer) {
+    let removed = egui::Color32::from_rgb(255, 100, 100);
+    let added = egui::Color32::from_rgb(100, 200, 100);
+    egui::ScrollArea::vertical()
+        .id_salt("config_changes")
+        .max_height(240.0)
+        .show(ui, |ui| {
+            egui::Grid::new("config_changes_grid")
+                .striped(true)
+                .num_columns(3)
+                .show(ui, |ui| {
+                    for change in changes {
+                        ui.monospace(&change.path);
+                        match &change.before {
+                            Some(before) => ui.colored_label(removed, format!("- {before}")),
+                            None => ui.weak(localizer.t("reload-added")),
+                        };
+                        match &change.after {
+                            Some(after) => ui.colored_label(added, format!("+ {after}")),
+                            None => ui.weak(localizer.t("reload-removed")),
+                        };
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+/// The design steps the changes affect, with a button to run them again
+fn draw_affected_steps(
+    ui: &mut egui::Ui,
+    state: &mut ConfigReloadState,
+    app_state: &mut AppState,
+    pipeline: &GenerationPipeline,
+    directories: &AppDirectories,
+    localizer: &Localizer,
+) {
+    if state.steps.is_empty() {
+        ui.label(localizer.t("reload-no-steps"));
+        return;
+    }
+    let running = pipeline.run_status().is_running();
+    let Some(config) = state.config.clone() else {
+        return;
+    };
+    let project_dir = directories.project_dir.clone();
+
+    if state.full_run {
+        ui.label(localizer.t("reload-renamed"));
+        if ui
+            .add_enabled(!running, egui::Button::new(localizer.t("run-start")))
+            .clicked()
+        {
+            pipeline.start_run(config.clone(), project_dir, false);
+            app_state.add_log(LogLevel::Info, localizer.t("run-started-log"));
+            state.steps.clear();
+            state.full_run = false;
+            state.baseline = Some(config);
+        }
+        return;
+    }
+
+    ui.label(localizer.t("reload-affected-steps"));
+    ui.horizontal_wrapped(|ui| {
+        for (step, selected) in &mut state.steps {
+            ui.checkbox(selected, *step);
+        }
+    });
+    let selected: Vec<String> = state
+        .steps
+        .iter()
+        .filter(|(_, selected)| *selected)
+        .map(|(step, _)| step.to_string())
+        .collect();
+    let can_resume = GenerationPipeline::can_resume(&project_dir);
+    let response = ui.add_enabled(
+        !running && can_resume && !selected.is_empty(),
+        egui::Button::new(localizer.t("reload-rerun")),
+    );
+    let response = if can_resume {
+        response
+    } else {
+        response.on_disabled_hover_text(localizer.t("reload-nothing-generated"))
+    };
+    if response.clicked() {
+        app_state.add_log(
+            LogLevel::Info,
+            localizer.t_args(
+                "reload-regenerating",
+                &[("steps", selected.join(", ").into())],
+            ),
+        );
+        pipeline.regenerate_steps(selected, None, config.clone(), project_dir);
+        state.steps.clear();
+        state.baseline = Some(config);
+    }
+}
+
+/// Validation results of the changed prompts, with a button to queue them
+/// for validation in their phase
+fn draw_prompt_changes(
+    ui: &mut egui::Ui,
+    state: &mut ConfigReloadState,
+    app_state: &mut AppState,
+    localizer: &Localizer,
+) {
+    for prompt in &state.prompts {
+        let name = prompt
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        ui.horizontal(|ui| {
+            ui.monospace(format!("{}/{name}", prompt.phase));
+            ui.weak(format!("{:?}", prompt.event_type));
+            match (&prompt.validation, &prompt.error) {
+                (_, Some(error)) => {
+                    ui.colored_label(egui::Color32::from_rgb(255, 100, 100), error);
+                }
+                (Some(result), None) if result.valid => {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(100, 200, 100),
+                        localizer.t("reload-valid"),
+                    );
+                }
+                (Some(_), None) => {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(255, 100, 100),
+                        localizer.t("reload-invalid"),
+                    );
+                }
+                (None, None) => {}
+            }
+        });
+        if let Some(result) = &prompt.validation {
+            for error in result.errors() {
+                ui.colored_label(
+                    egui::Color32::from_rgb(255, 100, 100),
+                    format!("  {}", error.message),
+                );
+            }
+            for warning in result.warnings() {
+                ui.colored_label(
+                    egui::Color32::from_rgb(230, 180, 80),
+                    format!("  {}", warning.message),
+                );
+            }
+        }
+    }
+
+    let queueable: Vec<&PromptChange> = state
+        .prompts
+        .iter()
+        .filter(|prompt| prompt.event_type != FileEventType::Removed)
+        .collect();
+    if !queueable.is_empty() && ui.button(localizer.t("reload-queue")).clicked() {
+        for prompt in queueable {
+            let Ok(content) = std::fs::read_to_string(&prompt.path) else {
+                continue;
+            };
+            let name = prompt
+                .path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            app_state.add_prompt_to_validate(
+                prompt.phase.clone(),
+                name,
+                content,
+                prompt.path.clone(),
+            );
+        }
+        state.prompts.clear();
+    }
+}
@@ -0,0 +1,195 @@
+// dev_bridge.rs - Mirrors regenerated assets into a running Bevy export
+//
+// When the project config names a Bevy export (`technical.bevy_export`), the
+// project's assets directory is watched and every new or changed sprite,
+// tileset or audio file is copied to the same relative path under the
+// export's `assets/`. A prototype running with Bevy's `file_watcher` feature
+// reloads the file in place, so artists can iterate with the game open.
+// The kept versions of the artifact history are not part of the game and
+// stay behind.
+
+use crate::wizard::{
+    directories::AppDirectories,
+    state::{AppState, LogLevel},
+};
+use bevy::prelude::*;
+use crossbeam_channel::{Receiver, bounded};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use vintage_ai_client::history::HISTORY_DIR;
+
+/// Extensions of assets the prototype can hot-reload
+const RELOADABLE_EXTENSIONS: &[&str] = &["png", "ogg", "wav", "mp3", "flac", "ron"];
+
+/// Generators write files in several chunks; wait until a file has been
+/// quiet this long before copying it
+const SETTLE_TIME: Duration = Duration::from_millis(250);
+
+#[derive(Resource)]
+pub struct AssetBridge {
+    _watcher: notify::RecommendedWatcher,
+    rx: Receiver<Result<Event, notify::Error>>,
+    source_dir: PathBuf,
+    export_assets_dir: PathBuf,
+    /// Changed files and when they last changed
+    pending: HashMap<PathBuf, Instant>,
+}
+
+impl AssetBridge {
+    pub fn new(source_dir: PathBuf, export_assets_dir: PathBuf) -> Result<Self, notify::Error> {
+        let (tx, rx) = bounded(256);
+
+        let mut watcher = notify::recommended_watcher(move |res| {
+            let _ = tx.send(res);
+        })?;
+        watcher.watch(&source_dir, RecursiveMode::Recursive)?;
+
+        Ok(Self {
+            _watcher: watcher,
+            rx,
+            source_dir,
+            export_assets_dir,
+            pending: HashMap::new(),
+        })
+    }
+
+    /// Collect changed files that have settled
+    fn poll_settled(&mut self) -> Vec<PathBuf> {
+        let now = Instant::now();
+        while let Ok(Ok(event)) = self.rx.try_recv() {
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                for path in event.paths {
+                    if is_reloadable(&self.source_dir, &path) {
+                        self.pending.insert(path, now);
+                    }
+                }
+            }
+        }
+
+        let settled: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, changed)| now.duration_since(**changed) >= SETTLE_TIME)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &settled {
+            self.pending.remove(path);
+        }
+        settled
+    }
+
+    /// Copy an asset into the export, replacing the old file atomically so the
+    /// prototype never reads a half-written file
+    fn mirror(&self, path: &Path) -> anyhow::Result<PathBuf> {
+        let relative = path.strip_prefix(&self.source_dir)?;
+        let target = self.export_assets_dir.join(relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let staging = target.with_extension("bridge.tmp");
+        std::fs::copy(path, &staging)?;
+        std::fs::rename(&staging, &target)?;
+        Ok(target)
+    }
+}
+
+/// Whether `path`, under the watched `source_dir`, is an asset of the game
+fn is_reloadable(source_dir: &Path, path: &Path) -> bool {
+    let in_history = path
+        .strip_prefix(source_dir)
+        .ok()
+        .and_then(|relative| relative.components().next())
+        .is_some_and(|first| first.as_os_str() == HISTORY_DIR);
+    !in_history
+        && path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| RELOADABLE_EXTENSIONS.contains(&ext))
+}
+
+/// Start the bridge for the current project and forward settled changes
+pub fn sync_exported_assets(
+    mut commands: Commands,
+    mut app_state: ResMut<AppState>,
+    directories: Res<AppDirectories>,
+    bridge: Option<ResMut<AssetBridge>>,
+    mut checked_dir: Local<Option<PathBuf>>,
+) {
+    let Some(mut bridge) = bridge else {
+        // Look for an export once per project
+        if checked_dir.as_ref() == Some(&directories.assets_dir) {
+            return;
+        }
+        *checked_dir = Some(directories.assets_dir.clone());
+
+        let Some(export_assets) = directories.bevy_export_dir().map(|dir| dir.join("assets"))
+        else {
+            return;
+        };
+
+        match AssetBridge::new(directories.assets_dir.clone(), export_assets.clone()) {
+            Ok(bridge) => {
+                app_state.add_log(
+                    LogLevel::Info,
+                    format!("Hot-reloading assets into {}", export_assets.display()),
+                );
+                commands.insert_resource(bridge);
+            }
+            Err(e) => {
+                app_state.add_log(
+                    LogLevel::Error,
+                    format!("Failed to watch assets for the Bevy export: {e}"),
+                );
+            }
+        }
+        return;
+    };
+
+    // Switched to another project; the next frame looks for its export
+    if bridge.source_dir != directories.assets_dir {
+        commands.remove_resource::<AssetBridge>();
+        return;
+    }
+
+    for path in bridge.poll_settled() {
+        match bridge.mirror(&path) {
+            Ok(target) => {
+                app_state.add_log(
+                    LogLevel::Success,
+                    format!("Sent {} to the running prototype", target.display()),
+                );
+            }
+            Err(e) => {
+                app_state.add_log(
+                    LogLevel::Error,
+                    format!("Failed to hot-reload {}: {e}", path.display()),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_versions_are_not_mirrored() {
+        let assets = Path::new("/projects/quest/assets");
+        assert!(is_reloadable(assets, &assets.join("sprites/hero.png")));
+        assert!(is_reloadable(assets, &assets.join("audio/theme.ogg")));
+        assert!(!is_reloadable(assets, &assets.join("design.md")));
+        assert!(!is_reloadable(
+            assets,
+            &assets.join(HISTORY_DIR).join("hero/0001.png")
+        ));
+        // Only the history directory at the top of the assets is skipped
+        assert!(is_reloadable(
+            assets,
+            &assets.join("sprites").join(HISTORY_DIR).join("hero.png")
+        ));
+    }
+}
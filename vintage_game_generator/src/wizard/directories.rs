@@ -1,4 +1,4 @@
-use crate::wizard::mode::AppMode;
+use crate::wizard::{config::ProjectConfig, mode::AppMode};
 use anyhow::{Context, Result};
 use bevy::prelude::*;
 use std::path::PathBuf;
@@ -103,6 +103,18 @@ impl AppDirectories {
         value.get("name")?.as_str().map(|s| s.to_string())
     }
 
+    /// Bevy project exported from this game, if the config names one that exists
+    pub fn bevy_export_dir(&self) -> Option<PathBuf> {
+        let config_file = self
+            .config_file
+            .clone()
+            .unwrap_or_else(|| self.project_dir.join("project.toml"));
+        let config = ProjectConfig::load(&config_file).ok()?;
+        let export = self.project_dir.join(config.technical.bevy_export?);
+
+        export.join("Cargo.toml").exists().then_some(export)
+    }
+
     pub fn get_generated_prompt_path(&self, phase: &str, name: &str) -> PathBuf {
         self.prompts_dir
             .join("generated")
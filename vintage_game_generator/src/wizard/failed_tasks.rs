@@ -0,0 +1,127 @@
+//! Panel listing quarantined pipeline tasks
+//!
+//! Tasks that fail validation too often are set aside so the rest of the run
+//! can continue. This panel shows why each one failed and lets the user edit
+//! the prompt and send it back into the queue.
+
+use crate::wizard::i18n::Localizer;
+use crate::wizard::state::{AppState, LogLevel};
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use std::path::PathBuf;
+
+/// Draw the failed tasks window while any task is quarantined
+pub fn draw_failed_tasks(
+    mut contexts: EguiContexts,
+    mut app_state: ResMut<AppState>,
+    localizer: Res<Localizer>,
+) {
+    if app_state.quarantined_tasks.is_empty() {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let mut retry: Option<PathBuf> = None;
+    let mut dismiss: Option<PathBuf> = None;
+
+    egui::Window::new(localizer.t_args(
+        "failed-tasks-title",
+        &[("count", app_state.quarantined_tasks.len().into())],
+    ))
+    .id(egui::Id::new("failed_tasks"))
+    .default_size([560.0, 400.0])
+    .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+    .show(ctx, |ui| {
+        ui.label(
+            egui::RichText::new(localizer.t("failed-tasks-description"))
+                .small()
+                .weak(),
+        );
+        ui.separator();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for task in app_state.quarantined_tasks.iter_mut() {
+                let id = task.path.display().to_string();
+                egui::CollapsingHeader::new(localizer.t_args(
+                    "failed-tasks-task",
+                    &[
+                        ("phase", task.phase.clone().into()),
+                        ("name", task.name.clone().into()),
+                        ("attempts", task.attempts.into()),
+                    ],
+                ))
+                .id_salt(&id)
+                .show(ui, |ui| {
+                    for error in &task.errors {
+                        ui.colored_label(egui::Color32::from_rgb(255, 120, 120), error);
+                    }
+
+                    ui.add(
+                        egui::TextEdit::multiline(&mut task.content)
+                            .code_editor()
+                            .desired_rows(8)
+                            .desired_width(f32::INFINITY),
+                    );
+
+                    ui.horizontal(|ui| {
+                        if ui
+                            .button(localizer.t("failed-tasks-retry"))
+                            .on_hover_text(localizer.t("failed-tasks-retry-hint"))
+                            .clicked()
+                        {
+                            retry = Some(task.path.clone());
+                        }
+                        if ui.button(localizer.t("failed-tasks-dismiss")).clicked() {
+                            dismiss = Some(task.path.clone());
+                        }
+                    });
+                });
+            }
+        });
+    });
+
+    if let Some(path) = retry {
+        let content = app_state
+            .quarantined_tasks
+            .iter()
+            .find(|t| t.path == path)
+            .map(|t| t.content.clone())
+            .unwrap_or_default();
+
+        match std::fs::write(&path, &content) {
+            Ok(()) => {
+                app_state.retry_quarantined(&path);
+                app_state.add_log(
+                    LogLevel::Info,
+                    localizer.t_args(
+                        "failed-tasks-retrying",
+                        &[("path", path.display().to_string().into())],
+                    ),
+                );
+            }
+            Err(e) => app_state.add_log(
+                LogLevel::Error,
+                localizer.t_args(
+                    "save-failed",
+                    &[
+                        ("path", path.display().to_string().into()),
+                        ("error", e.to_string().into()),
+                    ],
+                ),
+            ),
+        }
+    }
+
+    if let Some(path) = dismiss {
+        app_state.quarantined_tasks.retain(|t| t.path != path);
+        app_state.add_log(
+            LogLevel::Warning,
+            localizer.t_args(
+                "failed-tasks-dismissed",
+                &[("path", path.display().to_string().into())],
+            ),
+        );
+    }
+}
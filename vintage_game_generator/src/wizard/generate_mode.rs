@@ -1,18 +1,29 @@
-use crate::wizard::pipeline::GenerationPipeline;
+use crate::footprint::{AssetChecklist, ByteSize, available_space};
+use crate::metaprompts::{
+    DESIGN_STEPS, GenerationCheckpoint, METAPROMPT_DIRS, PromptValidator, RunEstimate, RunState,
+    Severity, ValidationResult,
+};
+use crate::wizard::pipeline::{GenerationPipeline, RunStatus};
 use crate::wizard::{
     AppDirectories, SwitchModeEvent,
     config::ConfigManager,
+    i18n::Localizer,
     state::{AppState, LogLevel, WizardStep},
     steps::{
-        draw_welcome_step,
+        draw_language_step, draw_welcome_step,
         freeform::{
             ConversationStream, FreeformModeState, render_freeform_mode, setup_freeform_mode,
         },
         guided::{GuidedModeState, render_guided_mode, setup_guided_mode},
     },
+    tutorial::{ANCHOR_COMPLETE, mark_anchor},
 };
 use bevy::prelude::*;
 use bevy_egui::{EguiContexts, egui};
+use std::path::PathBuf;
+use vintage_ai_client::artifacts::{ARTIFACT_INDEX_FILE, ArtifactIndex};
+use vintage_ai_client::quota::RateLimits;
+use vintage_ai_client::tokens::CostEstimate;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SelectedTab {
@@ -42,6 +53,7 @@ pub fn draw_generate_ui(
     stream_res: Option<ResMut<ConversationStream>>,
     commands: Commands,
     mut exit_events: EventWriter<AppExit>,
+    localizer: Res<Localizer>,
 ) {
     trace!("draw_generate_ui called");
 
@@ -68,25 +80,25 @@ pub fn draw_generate_ui(
 
     // Handle exit dialog
     if app_state.show_exit_dialog {
-        egui::Window::new("Exit Confirmation")
+        egui::Window::new(localizer.t("exit-title"))
             .collapsible(false)
             .resizable(false)
             .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
             .show(ctx, |ui| {
-                ui.heading("Are you sure you want to exit?");
+                ui.heading(localizer.t("exit-question"));
                 ui.add_space(10.0);
-                ui.label("Any unsaved progress will be lost.");
+                ui.label(localizer.t("exit-unsaved"));
                 ui.add_space(20.0);
 
                 ui.horizontal(|ui| {
-                    if ui.button("Cancel").clicked() {
+                    if ui.button(localizer.t("button-cancel")).clicked() {
                         app_state.show_exit_dialog = false;
                     }
 
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         if ui
                             .button(
-                                egui::RichText::new("Exit")
+                                egui::RichText::new(localizer.t("nav-exit"))
                                     .color(egui::Color32::from_rgb(255, 100, 100)),
                             )
                             .clicked()
@@ -98,33 +110,46 @@ pub fn draw_generate_ui(
             });
     }
 
-    // Draw wizard steps based on current state - ONLY WELCOME → GUIDED flow
+    // Status bar goes before the central panel of the step
+    draw_rate_limits(ctx, &pipeline.rate_limits(), &localizer);
+
+    // Draw wizard steps based on current state
     match &app_state.wizard_step {
         WizardStep::Welcome => {
             debug!("Drawing welcome step");
-            draw_wizard_frame_with_state(ctx, &mut app_state, |ui, state| {
-                let wizard_mode_action = draw_welcome_step(ui, &mut state.config_manager);
+            draw_wizard_frame_with_state(ctx, &mut app_state, &localizer, |ui, state| {
+                let wizard_mode_action =
+                    draw_welcome_step(ui, &mut state.config_manager, &localizer);
                 if let Some(action) = wizard_mode_action {
                     info!("Welcome action selected: {:?}", action);
                     state.set_wizard_mode(action);
                 }
             });
         }
+        WizardStep::SelectLanguage => {
+            draw_wizard_frame_with_state(ctx, &mut app_state, &localizer, |ui, state| {
+                if let Some(choice) = draw_language_step(ui, &mut state.config_manager, &localizer)
+                {
+                    info!("Language selected: {:?}", choice);
+                    state.set_language(choice);
+                }
+            });
+        }
         WizardStep::GuidedMode => {
             debug!("Drawing guided mode step");
             // Guided mode handles its own UI completely
             // This is where the user browses and blends vintage games
             if let Some(guided_state) = guided_state {
                 debug!("Guided state exists, rendering guided mode");
-                render_guided_mode(contexts, app_state, guided_state);
+                render_guided_mode(contexts, app_state, guided_state, &localizer, &pipeline);
             } else {
                 // Need to setup guided mode resources
                 warn!("No guided state found, setting up guided mode");
-                setup_guided_mode(commands);
+                setup_guided_mode(commands, &directories);
 
                 // Show loading state for this frame
-                draw_wizard_frame(ctx, &mut app_state, |ui| {
-                    ui.label("Loading guided mode...");
+                draw_wizard_frame(ctx, &mut app_state, &localizer, |ui| {
+                    ui.label(localizer.t("loading-guided"));
                 });
             }
         }
@@ -138,72 +163,631 @@ pub fn draw_generate_ui(
                     commands,
                     pipeline,
                     stream_res,
+                    &localizer,
                 );
             } else {
                 warn!("No freeform state found, setting up freeform mode");
                 setup_freeform_mode(commands);
 
-                draw_wizard_frame(ctx, &mut app_state, |ui| {
-                    ui.label("Loading freeform mode...");
+                draw_wizard_frame(ctx, &mut app_state, &localizer, |ui| {
+                    ui.label(localizer.t("loading-freeform"));
                 });
             }
         }
+        WizardStep::Review => {
+            draw_wizard_frame_with_state(ctx, &mut app_state, &localizer, |ui, state| {
+                draw_generation_run(ui, state, &pipeline, &directories, &localizer);
+            });
+        }
         WizardStep::Complete => {
-            draw_wizard_frame_with_state(ctx, &mut app_state, |ui, state| {
-                ui.heading("🎉 Export Complete!");
+            draw_wizard_frame_with_state(ctx, &mut app_state, &localizer, |ui, state| {
+                ui.heading(localizer.t("complete-title"));
                 ui.separator();
 
                 if let Some(export) = &state.guided_export {
-                    ui.label("Successfully exported game configuration");
-                    ui.label(format!("Blend: {}", export.blend_name));
+                    let summary = ui.scope(|ui| {
+                        ui.label(localizer.t("complete-exported"));
+                        ui.label(localizer.t_args(
+                            "complete-blend",
+                            &[("name", export.blend_name.clone().into())],
+                        ));
+                    });
+                    mark_anchor(ui.ctx(), ANCHOR_COMPLETE, summary.response.rect);
 
                     ui.separator();
 
-                    if ui.button("Start New Blend").clicked() {
+                    if ui.button(localizer.t("complete-new-blend")).clicked() {
                         state.set_wizard_step(WizardStep::Welcome);
                         state.guided_export = None;
                     }
                 } else {
-                    ui.label("Export completed successfully!");
+                    ui.label(localizer.t("complete-done"));
 
-                    if ui.button("Return to Welcome").clicked() {
+                    if ui.button(localizer.t("complete-return")).clicked() {
                         state.set_wizard_step(WizardStep::Welcome);
                     }
                 }
             });
         }
-        _ => {
-            warn!("Unhandled wizard step: {:?}", app_state.wizard_step);
-            // Any other step that isn't implemented yet
-            draw_wizard_frame_with_state(ctx, &mut app_state, |ui, state| {
-                ui.heading("🚧 Under Construction");
-                ui.label("This feature is not implemented yet.");
-                ui.add_space(20.0);
+    }
+}
+
+/// Start, pause, resume and cancel the design run of the project
+fn draw_generation_run(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    pipeline: &GenerationPipeline,
+    directories: &AppDirectories,
+    localizer: &Localizer,
+) {
+    ui.heading(localizer.t("run-title"));
+    ui.add_space(10.0);
+
+    let status = pipeline.run_status();
+    match &status {
+        RunStatus::Idle => {
+            ui.label(localizer.t("run-idle"));
+        }
+        RunStatus::Running(progress) => {
+            ui.add(egui::ProgressBar::new(progress.progress).show_percentage());
+            ui.label(format!("{:?}: {}", progress.phase, progress.message));
+        }
+        RunStatus::Cancelled => {
+            ui.label(localizer.t("run-cancelled"));
+        }
+        RunStatus::Failed(error) => {
+            ui.colored_label(egui::Color32::from_rgb(255, 100, 100), error);
+        }
+        RunStatus::NeedsRewrite(refusal) => {
+            ui.colored_label(
+                egui::Color32::from_rgb(255, 165, 0),
+                localizer.t_args(
+                    "run-needs-rewrite",
+                    &[("trigger", refusal.trigger.to_string().into())],
+                ),
+            );
+            ui.label(&refusal.message);
+            egui::CollapsingHeader::new(localizer.t("run-refused-prompt"))
+                .id_salt("refused_prompt")
+                .show(ui, |ui| {
+                    ui.label(egui::RichText::new(&refusal.prompt).monospace());
+                    if let Some(attempted) = &refusal.attempted {
+                        ui.separator();
+                        ui.label(localizer.t("run-rewrite-refused"));
+                        ui.label(egui::RichText::new(attempted).monospace());
+                    }
+                });
+            ui.label(
+                egui::RichText::new(localizer.t("run-reword-hint"))
+                    .small()
+                    .weak(),
+            );
+        }
+        RunStatus::Complete(design_file) => {
+            ui.label(localizer.t_args(
+                "run-complete",
+                &[("path", design_file.display().to_string().into())],
+            ));
+        }
+        RunStatus::Regenerated(step) => {
+            ui.label(localizer.t_args("run-regenerated", &[("step", step.as_str().into())]));
+        }
+    }
+    ui.add_space(10.0);
+
+    // Metaprompt lint from the last attempt to start a run, and the last
+    // cost preview
+    let lint_id = ui.id().with("metaprompt_lint");
+    let estimate_id = ui.id().with("cost_preview");
+    let disk_id = ui.id().with("disk_preview");
+    ui.horizontal(|ui| {
+        if status.is_running() {
+            if pipeline.control.state() == RunState::Paused {
+                if ui.button(localizer.t("run-resume")).clicked() {
+                    pipeline.control.resume();
+                    state.add_log(LogLevel::Info, localizer.t("run-resumed-log"));
+                }
+            } else if ui.button(localizer.t("run-pause")).clicked() {
+                pipeline.control.pause();
+                state.add_log(LogLevel::Info, localizer.t("run-paused-log"));
+            }
+            if ui.button(localizer.t("run-cancel")).clicked() {
+                pipeline.control.cancel();
+                state.add_log(LogLevel::Warning, localizer.t("run-cancelled-log"));
+            }
+            return;
+        }
 
-                // Show pipeline status for debugging
+        let Some(config) = state.config_manager.as_ref().map(|m| m.config.clone()) else {
+            ui.label(localizer.t("run-no-config"));
+            return;
+        };
+        let project_dir = directories.project_dir.clone();
+        let mut resume = None;
+        if GenerationPipeline::can_resume(&project_dir)
+            && ui
+                .button(localizer.t("run-resume-generation"))
+                .on_hover_text(localizer.t("run-resume-generation-hint"))
+                .clicked()
+        {
+            resume = Some(true);
+        }
+        if ui.button(localizer.t("run-start")).clicked() {
+            resume = Some(false);
+        }
+        if ui
+            .button(localizer.t("run-preview-costs"))
+            .on_hover_text(localizer.t("run-preview-costs-hint"))
+            .clicked()
+        {
+            let resumable = GenerationPipeline::can_resume(&project_dir);
+            let estimate = pipeline
+                .estimate_run(config.clone(), project_dir.clone(), false)
+                .and_then(|full| {
+                    let resumed = resumable
+                        .then(|| pipeline.estimate_run(config.clone(), project_dir.clone(), true))
+                        .transpose()?;
+                    Ok((full, resumed))
+                })
+                .map_err(|e| format!("{e:#}"));
+            ui.data_mut(|data| data.insert_temp(estimate_id, estimate));
+            let disk: DiskPreview = (
+                AssetChecklist::for_project(&config),
+                available_space(&project_dir),
+            );
+            ui.data_mut(|data| data.insert_temp(disk_id, disk));
+        }
+        let Some(resume) = resume else {
+            return;
+        };
+
+        let lint = lint_metaprompts(directories);
+        let valid = lint.iter().all(|result| result.valid);
+        ui.data_mut(|data| data.insert_temp(lint_id, lint));
+        if !valid {
+            state.add_log(LogLevel::Error, localizer.t("run-metaprompt-errors"));
+        } else if resume {
+            pipeline.start_run(config, project_dir, true);
+            state.add_log(LogLevel::Info, localizer.t("run-resuming-log"));
+        } else {
+            pipeline.start_run(config, project_dir, false);
+            state.add_log(LogLevel::Info, localizer.t("run-started-log"));
+        }
+    });
+
+    if let Some(lint) = ui.data_mut(|data| data.get_temp::<Vec<ValidationResult>>(lint_id)) {
+        draw_metaprompt_lint(ui, &lint);
+    }
+
+    if !status.is_running() {
+        match ui.data_mut(|data| data.get_temp::<CostPreview>(estimate_id)) {
+            Some(Ok((full, resumed))) => draw_cost_estimate(ui, &full, resumed.as_ref(), localizer),
+            Some(Err(error)) => {
+                ui.colored_label(egui::Color32::from_rgb(255, 100, 100), error);
+            }
+            None => {}
+        }
+        if let Some((checklist, available)) =
+            ui.data_mut(|data| data.get_temp::<DiskPreview>(disk_id))
+        {
+            draw_disk_estimate(ui, &checklist, available, localizer);
+        }
+        draw_generated_steps(ui, state, pipeline, directories, localizer);
+    }
+}
+
+/// Status bar with what is left of the provider's rate limits, once a
+/// response reported them
+fn draw_rate_limits(ctx: &egui::Context, limits: &[(String, RateLimits)], localizer: &Localizer) {
+    if limits.is_empty() {
+        return;
+    }
+    let now = std::time::Instant::now();
+    egui::TopBottomPanel::bottom("rate_limits").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            for (model, limits) in limits {
+                let text = format!(
+                    "{model}: {} RPM · {} TPM",
+                    remaining(limits.requests_left(now), limits.limit_requests),
+                    remaining(limits.tokens_left(now), limits.limit_tokens)
+                );
+                let wait = limits.wait_for(0, now);
+                if wait.is_zero() {
+                    ui.label(text);
+                } else {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(255, 180, 80),
+                        localizer.t_args(
+                            "rate-limits-paused",
+                            &[
+                                ("limits", text.into()),
+                                ("seconds", (wait.as_secs() + 1).into()),
+                            ],
+                        ),
+                    )
+                    .on_hover_text(localizer.t("rate-limits-paused-hint"));
+                }
                 ui.separator();
-                ui.label("Pipeline initialized");
+            }
+        });
+    });
+}
+
+/// What is left of a limit, out of the whole
+fn remaining(left: Option<u64>, limit: Option<u64>) -> String {
+    match (left, limit) {
+        (Some(left), Some(limit)) => format!("{left}/{limit}"),
+        (Some(left), None) => left.to_string(),
+        (None, _) => "?".to_string(),
+    }
+}
+
+/// Estimate of a fresh run, and of resuming the last one if it can be
+/// resumed, or why it couldn't be worked out
+type CostPreview = Result<(RunEstimate, Option<RunEstimate>), String>;
 
-                if ui.button("← Back to Welcome").clicked() {
-                    state.set_wizard_step(WizardStep::Welcome);
+/// Assets a run writes, and the space free on the project's disk if known
+type DiskPreview = (AssetChecklist, Option<u64>);
+
+/// Disk footprint of a run by asset, against the space free
+fn draw_disk_estimate(
+    ui: &mut egui::Ui,
+    checklist: &AssetChecklist,
+    available: Option<u64>,
+    localizer: &Localizer,
+) {
+    ui.add_space(10.0);
+    ui.label(egui::RichText::new(localizer.t("disk-title")).strong());
+    egui::Grid::new("disk_preview_grid")
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            for item in &checklist.items {
+                ui.label(&item.name);
+                ui.label(item.count.to_string());
+                ui.label(ByteSize(item.bytes()).to_string());
+                ui.end_row();
+            }
+            ui.strong(localizer.t("estimate-total"));
+            ui.label("");
+            ui.strong(ByteSize(checklist.total_bytes()).to_string());
+            ui.end_row();
+        });
+    let required = checklist.required_bytes();
+    match available {
+        Some(available) if available < required => {
+            ui.colored_label(
+                egui::Color32::from_rgb(255, 100, 100),
+                localizer.t_args(
+                    "disk-too-little",
+                    &[
+                        ("available", ByteSize(available).to_string().into()),
+                        ("required", ByteSize(required).to_string().into()),
+                    ],
+                ),
+            );
+        }
+        Some(available) => {
+            ui.label(localizer.t_args(
+                "disk-free",
+                &[("available", ByteSize(available).to_string().into())],
+            ));
+        }
+        None => {
+            ui.label(localizer.t("disk-unknown"));
+        }
+    }
+}
+
+/// Per-step and total estimate of a run
+fn draw_cost_estimate(
+    ui: &mut egui::Ui,
+    full: &RunEstimate,
+    resumed: Option<&RunEstimate>,
+    localizer: &Localizer,
+) {
+    ui.add_space(10.0);
+    ui.label(egui::RichText::new(localizer.t("cost-title")).strong());
+    egui::Grid::new("cost_preview_grid")
+        .num_columns(4)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong(localizer.t("cost-step"));
+            ui.strong(localizer.t("cost-phase"));
+            ui.strong(localizer.t("cost-tokens"));
+            ui.strong(localizer.t("cost-cost"));
+            ui.end_row();
+            for step in &full.steps {
+                ui.monospace(step.step);
+                ui.label(format!("{:?}", step.phase));
+                ui.label(token_summary(&step.estimate, localizer));
+                ui.label(cost_range(&step.estimate));
+                ui.end_row();
+            }
+            let total = full.total();
+            ui.strong(localizer.t("estimate-total"));
+            ui.label("");
+            ui.strong(token_summary(&total, localizer));
+            ui.strong(cost_range(&total));
+            ui.end_row();
+        });
+    if let Some(resumed) = resumed {
+        let total = resumed.total();
+        let reused = resumed
+            .steps
+            .iter()
+            .filter(|step| step.checkpointed)
+            .count();
+        ui.label(localizer.t_args(
+            "cost-resume",
+            &[
+                ("reused", reused.into()),
+                ("cost", cost_range(&total).into()),
+            ],
+        ));
+    }
+    ui.label(egui::RichText::new(localizer.t("cost-note")).small().weak());
+}
+
+fn token_summary(estimate: &CostEstimate, localizer: &Localizer) -> String {
+    if estimate.requests > 0 && estimate.cached == estimate.requests {
+        return localizer.t("cost-cached");
+    }
+    localizer.t_args(
+        "cost-tokens-summary",
+        &[
+            ("prompt", estimate.prompt_tokens.into()),
+            ("completion", estimate.completion_tokens.into()),
+        ],
+    )
+}
+
+fn cost_range(estimate: &CostEstimate) -> String {
+    format!(
+        "${:.4} (${:.4} – ${:.4})",
+        estimate.cost, estimate.min_cost, estimate.max_cost
+    )
+}
+
+/// Validate the metaprompts a run renders, preferring the project's copies
+/// over the bundled ones
+fn lint_metaprompts(directories: &AppDirectories) -> Vec<ValidationResult> {
+    let mut dirs = vec![directories.prompts_dir.clone()];
+    dirs.extend(METAPROMPT_DIRS.iter().map(PathBuf::from));
+    PromptValidator::new().validate_metaprompts(&dirs)
+}
+
+/// Issues found in the metaprompts; errors keep the run from starting
+fn draw_metaprompt_lint(ui: &mut egui::Ui, lint: &[ValidationResult]) {
+    for result in lint.iter().filter(|result| !result.issues.is_empty()) {
+        ui.monospace(result.path.display().to_string());
+        for issue in &result.issues {
+            let color = match issue.severity {
+                Severity::Error => egui::Color32::from_rgb(255, 100, 100),
+                Severity::Warning => egui::Color32::from_rgb(230, 180, 80),
+            };
+            ui.colored_label(color, format!("  {}", issue.message));
+        }
+    }
+}
+
+/// Outputs of the last run and the project's other artifacts, with their
+/// tags. Design steps, sprites and tracks have a Regenerate action that
+/// makes only that one again, optionally with a tweak to its prompt, and
+/// the list can be filtered by tag to regenerate everything with it at once.
+fn draw_generated_steps(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    pipeline: &GenerationPipeline,
+    directories: &AppDirectories,
+    localizer: &Localizer,
+) {
+    let project_dir = &directories.project_dir;
+    let checkpoint = GenerationCheckpoint::load(project_dir).ok().flatten();
+    // Design steps first, then the sprites and tracks by slug
+    let steps: Vec<(&str, &str)> = checkpoint
+        .iter()
+        .flat_map(|checkpoint| {
+            let assets = checkpoint
+                .completed
+                .keys()
+                .map(String::as_str)
+                .filter(|id| !DESIGN_STEPS.contains(id));
+            DESIGN_STEPS
+                .into_iter()
+                .chain(assets)
+                .filter_map(|step| Some((step, checkpoint.output(step)?)))
+        })
+        .collect();
+    let index_path = project_dir.join(ARTIFACT_INDEX_FILE);
+    let mut index = match ArtifactIndex::load(&index_path) {
+        Ok(index) => index,
+        Err(e) => {
+            ui.colored_label(egui::Color32::from_rgb(255, 100, 100), format!("{e:#}"));
+            return;
+        }
+    };
+    if steps.is_empty() && index.artifacts.is_empty() {
+        return;
+    }
+    ui.add_space(10.0);
+    ui.heading(localizer.t("generated-title"));
+
+    // The tag filter is kept in egui's memory, "" for all artifacts
+    let filter_id = ui.id().with("tag_filter");
+    let mut filter = ui
+        .data_mut(|data| data.get_temp::<String>(filter_id))
+        .unwrap_or_default();
+    let all = localizer.t("generated-all-tags");
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_label(localizer.t("generated-tag"))
+            .selected_text(if filter.is_empty() {
+                all.as_str()
+            } else {
+                filter.as_str()
+            })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut filter, String::new(), all.as_str());
+                for tag in index.all_tags() {
+                    ui.selectable_value(&mut filter, tag.to_string(), tag);
                 }
             });
+        if !filter.is_empty()
+            && ui
+                .button(localizer.t_args(
+                    "generated-regenerate-tagged",
+                    &[("tag", filter.as_str().into())],
+                ))
+                .clicked()
+            && let Some(config) = state.config_manager.as_ref().map(|m| m.config.clone())
+        {
+            match pipeline.regenerate_tagged(&filter, config, project_dir.clone()) {
+                Ok(0) => state.add_log(
+                    LogLevel::Warning,
+                    localizer.t_args("generated-none-tagged", &[("tag", filter.as_str().into())]),
+                ),
+                Ok(count) => state.add_log(
+                    LogLevel::Info,
+                    localizer.t_args(
+                        "generated-regenerating-tagged",
+                        &[("count", count.into()), ("tag", filter.as_str().into())],
+                    ),
+                ),
+                Err(e) => state.add_log(LogLevel::Error, format!("{e:#}")),
+            }
+        }
+    });
+    ui.data_mut(|data| data.insert_temp(filter_id, filter.clone()));
+
+    let mut changed = false;
+    for (step, output) in steps {
+        if !filter.is_empty() && !index.has_tag(step, &filter) {
+            continue;
         }
+        egui::CollapsingHeader::new(step)
+            .id_salt(("generated_step", step))
+            .show(ui, |ui| {
+                // Sprites and tracks are checkpointed by the file they were
+                // written to
+                if DESIGN_STEPS.contains(&step) {
+                    egui::ScrollArea::vertical()
+                        .id_salt(("generated_output", step))
+                        .max_height(200.0)
+                        .show(ui, |ui| ui.label(output));
+                } else {
+                    ui.label(egui::RichText::new(output).weak());
+                }
+                changed |= draw_tags(ui, &mut index, step, localizer);
+
+                // Tweak text is kept in egui's memory per step
+                let tweak_id = ui.id().with("tweak");
+                let mut tweak = ui
+                    .data_mut(|data| data.get_temp::<String>(tweak_id))
+                    .unwrap_or_default();
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut tweak)
+                            .hint_text(localizer.t("generated-tweak-hint"))
+                            .desired_width(360.0),
+                    );
+                    if ui.button(localizer.t("generated-regenerate")).clicked() {
+                        let Some(config) = state.config_manager.as_ref().map(|m| m.config.clone())
+                        else {
+                            return;
+                        };
+                        let tweak = Some(tweak.trim().to_string()).filter(|t| !t.is_empty());
+                        pipeline.regenerate(step, tweak, config, project_dir.clone());
+                        state.add_log(
+                            LogLevel::Info,
+                            localizer.t_args("generated-regenerating", &[("step", step.into())]),
+                        );
+                    }
+                });
+                ui.data_mut(|data| data.insert_temp(tweak_id, tweak));
+            });
     }
+
+    let artifacts: Vec<_> = index
+        .artifacts
+        .iter()
+        .filter(|a| filter.is_empty() || index.has_tag(&a.name, &filter))
+        .map(|a| (a.name.clone(), a.description.clone(), a.path.clone()))
+        .collect();
+    for (name, description, path) in artifacts {
+        egui::CollapsingHeader::new(&name)
+            .id_salt(("generated_artifact", &name))
+            .show(ui, |ui| {
+                if let Some(path) = path {
+                    ui.label(egui::RichText::new(path.display().to_string()).weak());
+                }
+                ui.label(description);
+                changed |= draw_tags(ui, &mut index, &name, localizer);
+            });
+    }
+
+    if changed && let Err(e) = index.save(&index_path) {
+        state.add_log(
+            LogLevel::Error,
+            localizer.t_args(
+                "generated-tags-save-failed",
+                &[("error", format!("{e:#}").into())],
+            ),
+        );
+    }
+}
+
+/// An artifact's tags, each removable, and a field to add one.
+/// Returns whether the tags changed.
+fn draw_tags(
+    ui: &mut egui::Ui,
+    index: &mut ArtifactIndex,
+    name: &str,
+    localizer: &Localizer,
+) -> bool {
+    let mut changed = false;
+    ui.horizontal_wrapped(|ui| {
+        ui.label(localizer.t("tags-label"));
+        let tags: Vec<String> = index.tags_of(name).map(str::to_string).collect();
+        for tag in tags {
+            if ui
+                .small_button(format!("{tag} ✕"))
+                .on_hover_text(localizer.t("tags-remove"))
+                .clicked()
+            {
+                changed |= index.untag(name, &tag);
+            }
+        }
+        let new_tag_id = ui.id().with(("new_tag", name));
+        let mut new_tag = ui
+            .data_mut(|data| data.get_temp::<String>(new_tag_id))
+            .unwrap_or_default();
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut new_tag)
+                .hint_text(localizer.t("tags-add"))
+                .desired_width(100.0),
+        );
+        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            changed |= index.tag(name, &new_tag);
+            new_tag.clear();
+        }
+        ui.data_mut(|data| data.insert_temp(new_tag_id, new_tag));
+    });
+    changed
 }
 
 fn draw_wizard_frame<T>(
     ctx: &egui::Context,
     app_state: &mut AppState,
+    localizer: &Localizer,
     content: impl FnOnce(&mut egui::Ui) -> T,
 ) -> T {
     let mut result = None;
     egui::CentralPanel::default().show(ctx, |ui| {
         // Header
         ui.horizontal(|ui| {
-            ui.heading("🎮 Vintage Game Generator");
+            ui.heading(localizer.t("app-title"));
             ui.separator();
-            ui.label(app_state.get_step_title());
+            ui.label(localizer.t(app_state.step_title_id()));
         });
         ui.separator();
 
@@ -224,14 +808,17 @@ fn draw_wizard_frame<T>(
 
         // Navigation
         ui.horizontal(|ui| {
-            if app_state.can_go_back() && ui.button("← Back").clicked() {
+            if app_state.can_go_back() && ui.button(localizer.t("nav-back")).clicked() {
                 app_state.go_back();
             }
 
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                if ui.button("Exit").clicked() {
+                if ui.button(localizer.t("nav-exit")).clicked() {
                     app_state.show_exit_dialog = true;
                 }
+                if ui.button(localizer.t("nav-settings")).clicked() {
+                    app_state.show_settings = !app_state.show_settings;
+                }
             });
         });
     });
@@ -241,14 +828,15 @@ fn draw_wizard_frame<T>(
 fn draw_wizard_frame_with_state(
     ctx: &egui::Context,
     app_state: &mut AppState,
+    localizer: &Localizer,
     content: impl FnOnce(&mut egui::Ui, &mut AppState),
 ) {
     egui::CentralPanel::default().show(ctx, |ui| {
         // Header
         ui.horizontal(|ui| {
-            ui.heading("🎮 Vintage Game Generator");
+            ui.heading(localizer.t("app-title"));
             ui.separator();
-            ui.label(app_state.get_step_title());
+            ui.label(localizer.t(app_state.step_title_id()));
         });
         ui.separator();
 
@@ -269,14 +857,17 @@ fn draw_wizard_frame_with_state(
 
         // Navigation
         ui.horizontal(|ui| {
-            if app_state.can_go_back() && ui.button("← Back").clicked() {
+            if app_state.can_go_back() && ui.button(localizer.t("nav-back")).clicked() {
                 app_state.go_back();
             }
 
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                if ui.button("Exit").clicked() {
+                if ui.button(localizer.t("nav-exit")).clicked() {
                     app_state.show_exit_dialog = true;
                 }
+                if ui.button(localizer.t("nav-settings")).clicked() {
+                    app_state.show_settings = !app_state.show_settings;
+                }
             });
         });
     });
@@ -0,0 +1,418 @@
+//! Gallery of kept artifact versions
+//!
+//! Lists the artifacts with a history under `assets/history/` and shows
+//! every version of the selected one. Any two versions can be compared side
+//! by side (with a diff heatmap for images), and any version can be rolled
+//! back to. The version strip shows cached thumbnails, loaded as they scroll
+//! into view; full-size images are only decoded for a comparison. Toggle
+//! the panel with F8.
+
+use crate::wizard::AppDirectories;
+use crate::wizard::i18n::Localizer;
+use crate::wizard::pipeline::GenerationPipeline;
+use crate::wizard::state::{AppState, LogLevel};
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use std::path::Path;
+use vintage_ai_client::history::{ArtifactHistory, ArtifactVersion};
+use vintage_ai_client::image_diff::{DiffMode, ImageDiff, diff_images};
+use vintage_ai_client::thumbnails::{ThumbnailCache, load_image};
+
+/// Files that are shown as images
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp"];
+
+/// Largest edge of a thumbnail in the version strip
+const THUMBNAIL_SIZE: f32 = 96.0;
+
+/// Width of a version's column in the strip
+const COLUMN_WIDTH: f32 = THUMBNAIL_SIZE + 40.0;
+
+/// Largest edge of each image in a comparison
+const PREVIEW_SIZE: f32 = 256.0;
+
+/// A version loaded for display
+enum Preview {
+    Image(egui::TextureHandle),
+    Text(String),
+    Unavailable(String),
+}
+
+/// Two versions next to each other
+struct Comparison {
+    left: (u32, Preview),
+    right: (u32, Preview),
+    heatmap: Option<(egui::TextureHandle, ImageDiff)>,
+}
+
+/// State of the history gallery
+#[derive(Resource, Default)]
+pub struct HistoryGalleryState {
+    pub open: bool,
+    pub artifacts: Vec<String>,
+    pub selected: Option<String>,
+    pub versions: Vec<ArtifactVersion>,
+    /// Versions picked for comparison
+    pub left: Option<u32>,
+    pub right: Option<u32>,
+    pub error: Option<String>,
+    /// Thumbnails of the versions, loaded when first scrolled into view
+    previews: Vec<Option<Preview>>,
+    comparison: Option<Comparison>,
+}
+
+impl HistoryGalleryState {
+    fn refresh(&mut self, directories: &AppDirectories) {
+        let history = ArtifactHistory::for_project(&directories.project_dir);
+        match history.artifacts() {
+            Ok(artifacts) => self.artifacts = artifacts,
+            Err(e) => self.error = Some(format!("{e:#}")),
+        }
+        if self
+            .selected
+            .as_ref()
+            .is_some_and(|name| !self.artifacts.contains(name))
+        {
+            self.selected = None;
+        }
+        self.versions.clear();
+        self.previews.clear();
+        self.comparison = None;
+    }
+
+    fn select(&mut self, directories: &AppDirectories, name: &str) {
+        let history = ArtifactHistory::for_project(&directories.project_dir);
+        self.selected = Some(name.to_string());
+        self.left = None;
+        self.right = None;
+        self.comparison = None;
+        self.versions = match history.versions(name) {
+            Ok(versions) => versions,
+            Err(e) => {
+                self.error = Some(format!("{e:#}"));
+                Vec::new()
+            }
+        };
+        self.previews = self.versions.iter().map(|_| None).collect();
+    }
+
+    /// Load the thumbnail of the `i`th version if it isn't loaded yet
+    fn load_thumbnail(&mut self, ctx: &egui::Context, directories: &AppDirectories, i: usize) {
+        let (Some(name), Some(version)) = (&self.selected, self.versions.get(i)) else {
+            return;
+        };
+        if self.previews.get(i).is_none_or(Option::is_some) {
+            return;
+        }
+        let path =
+            ArtifactHistory::for_project(&directories.project_dir).version_path(name, version);
+        let preview = if is_image(&path) {
+            match ThumbnailCache::for_project(&directories.project_dir).get(&path) {
+                Ok(thumbnail) => {
+                    Preview::Image(to_texture(ctx, &path.display().to_string(), &thumbnail))
+                }
+                Err(e) => Preview::Unavailable(format!("{e:#}")),
+            }
+        } else {
+            load_preview(ctx, &path)
+        };
+        self.previews[i] = Some(preview);
+    }
+
+    fn compare(
+        &mut self,
+        ctx: &egui::Context,
+        directories: &AppDirectories,
+        localizer: &Localizer,
+    ) {
+        let (Some(name), Some(left), Some(right)) = (&self.selected, self.left, self.right) else {
+            self.comparison = None;
+            return;
+        };
+        let history = ArtifactHistory::for_project(&directories.project_dir);
+        let path = |number: u32| {
+            self.versions
+                .iter()
+                .find(|v| v.version == number)
+                .map(|v| history.version_path(name, v))
+        };
+        let heatmap = path(left)
+            .zip(path(right))
+            .filter(|(before, _)| is_image(before))
+            .and_then(|(before, after)| Some((load_image(&before).ok()?, load_image(&after).ok()?)))
+            .map(|(before, after)| {
+                let diff = diff_images(&before, &after, DiffMode::Pixel);
+                (to_texture(ctx, "history_heatmap", &diff.heatmap), diff)
+            });
+        let preview = |number: u32| match path(number) {
+            Some(path) => load_preview(ctx, &path),
+            None => Preview::Unavailable(
+                localizer.t_args("history-version-missing", &[("version", number.into())]),
+            ),
+        };
+        self.comparison = Some(Comparison {
+            left: (left, preview(left)),
+            right: (right, preview(right)),
+            heatmap,
+        });
+    }
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+fn load_preview(ctx: &egui::Context, path: &Path) -> Preview {
+    if is_image(path) {
+        return match load_image(path) {
+            Ok(image) => Preview::Image(to_texture(
+                ctx,
+                &path.display().to_string(),
+                &image.to_rgba8(),
+            )),
+            Err(e) => Preview::Unavailable(format!("{e:#}")),
+        };
+    }
+    match std::fs::read_to_string(path) {
+        Ok(text) => Preview::Text(text),
+        Err(e) => Preview::Unavailable(e.to_string()),
+    }
+}
+
+fn to_texture(ctx: &egui::Context, name: &str, image: &image::RgbaImage) -> egui::TextureHandle {
+    let size = [image.width() as usize, image.height() as usize];
+    let color_image = egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+    // Nearest filtering so individual pixels stay visible when scaled up
+    ctx.load_texture(name, color_image, egui::TextureOptions::NEAREST)
+}
+
+/// Toggle the gallery with F8
+pub fn toggle_history_gallery(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<HistoryGalleryState>,
+    directories: Res<AppDirectories>,
+) {
+    if keys.just_pressed(KeyCode::F8) {
+        state.open = !state.open;
+        if state.open {
+            state.refresh(&directories);
+        }
+    }
+}
+
+/// Draw the gallery window
+pub fn draw_history_gallery(
+    mut contexts: EguiContexts,
+    mut state: ResMut<HistoryGalleryState>,
+    mut app_state: ResMut<AppState>,
+    directories: Res<AppDirectories>,
+    localizer: Res<Localizer>,
+) {
+    if !state.open {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+    let state = &mut *state;
+
+    let mut open = state.open;
+    let mut select = None;
+    let mut compare = false;
+    let mut restore = None;
+    let mut refresh = false;
+    let mut visible = Vec::new();
+
+    egui::Window::new(localizer.t("history-title"))
+        .id(egui::Id::new("history_gallery"))
+        .open(&mut open)
+        .default_size([860.0, 520.0])
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let selected_text = state
+                    .selected
+                    .clone()
+                    .unwrap_or_else(|| localizer.t("history-select-artifact"));
+                egui::ComboBox::from_id_salt("history_artifact")
+                    .selected_text(selected_text)
+                    .show_ui(ui, |ui| {
+                        for name in &state.artifacts {
+                            if ui
+                                .selectable_label(state.selected.as_ref() == Some(name), name)
+                                .clicked()
+                            {
+                                select = Some(name.clone());
+                            }
+                        }
+                    });
+                if ui.button(localizer.t("window-refresh")).clicked() {
+                    refresh = true;
+                }
+            });
+
+            if state.artifacts.is_empty() {
+                ui.label(
+                    egui::RichText::new(localizer.t("history-no-versions"))
+                        .small()
+                        .weak(),
+                );
+            }
+            if let Some(error) = &state.error {
+                ui.colored_label(egui::Color32::from_rgb(255, 100, 100), error);
+            }
+            ui.separator();
+
+            egui::ScrollArea::horizontal()
+                .id_salt("history_versions")
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        for (i, (version, preview)) in
+                            state.versions.iter().zip(&state.previews).enumerate()
+                        {
+                            let column = egui::Rect::from_min_size(
+                                ui.cursor().min,
+                                egui::vec2(COLUMN_WIDTH, THUMBNAIL_SIZE),
+                            );
+                            if preview.is_none() && ui.is_rect_visible(column) {
+                                visible.push(i);
+                            }
+                            ui.vertical(|ui| {
+                                ui.set_width(COLUMN_WIDTH);
+                                ui.strong(format!("v{}", version.version));
+                                ui.label(
+                                    egui::RichText::new(
+                                        version.created_at.format("%Y-%m-%d %H:%M").to_string(),
+                                    )
+                                    .small()
+                                    .weak(),
+                                );
+                                match preview {
+                                    Some(preview) => draw_preview(ui, preview, THUMBNAIL_SIZE),
+                                    None => {
+                                        ui.add_sized(
+                                            [THUMBNAIL_SIZE, THUMBNAIL_SIZE],
+                                            egui::Spinner::new(),
+                                        );
+                                    }
+                                }
+                                if !version.note.is_empty() {
+                                    ui.label(egui::RichText::new(&version.note).small())
+                                        .on_hover_text(&version.note);
+                                }
+                                ui.horizontal(|ui| {
+                                    let number = Some(version.version);
+                                    compare |=
+                                        ui.selectable_value(&mut state.left, number, "A").changed();
+                                    compare |= ui
+                                        .selectable_value(&mut state.right, number, "B")
+                                        .changed();
+                                });
+                                if ui
+                                    .button(localizer.t("history-roll-back"))
+                                    .on_hover_text(localizer.t("history-roll-back-hint"))
+                                    .clicked()
+                                {
+                                    restore = Some(version.version);
+                                }
+                            });
+                        }
+                    });
+                });
+
+            if let Some(comparison) = &state.comparison {
+                ui.separator();
+                draw_comparison(ui, comparison, &localizer);
+            }
+        });
+
+    state.open = open;
+
+    if !visible.is_empty() {
+        for i in visible {
+            state.load_thumbnail(ctx, &directories, i);
+        }
+        ctx.request_repaint();
+    }
+    if refresh {
+        state.refresh(&directories);
+        if let Some(name) = state.selected.clone() {
+            state.select(&directories, &name);
+        }
+    }
+    if let Some(name) = select {
+        state.select(&directories, &name);
+    }
+    if compare {
+        state.compare(ctx, &directories, &localizer);
+    }
+    if let (Some(version), Some(name)) = (restore, state.selected.clone()) {
+        match GenerationPipeline::restore_version(&directories.project_dir, &name, version) {
+            Ok(()) => {
+                app_state.add_log(
+                    LogLevel::Success,
+                    localizer.t_args(
+                        "history-rolled-back",
+                        &[("name", name.as_str().into()), ("version", version.into())],
+                    ),
+                );
+                state.error = None;
+            }
+            Err(e) => state.error = Some(format!("{e:#}")),
+        }
+        // The rollback keeps the replaced contents as a new version
+        state.select(&directories, &name);
+    }
+}
+
+fn draw_preview(ui: &mut egui::Ui, preview: &Preview, size: f32) {
+    match preview {
+        Preview::Image(texture) => {
+            ui.add(egui::Image::new(texture).fit_to_exact_size(egui::vec2(size, size)));
+        }
+        Preview::Text(text) => {
+            egui::ScrollArea::vertical()
+                .id_salt(ui.next_auto_id())
+                .max_height(size * 2.0)
+                .show(ui, |ui| ui.label(egui::RichText::new(text).small()));
+        }
+        Preview::Unavailable(error) => {
+            ui.colored_label(egui::Color32::from_rgb(255, 100, 100), error);
+        }
+    }
+}
+
+fn draw_comparison(ui: &mut egui::Ui, comparison: &Comparison, localizer: &Localizer) {
+    if let Some((_, diff)) = &comparison.heatmap {
+        ui.label(localizer.t_args(
+            "compare-pixels-changed",
+            &[
+                ("changed", diff.changed_pixels.into()),
+                ("total", diff.total_pixels.into()),
+                (
+                    "percent",
+                    format!("{:.1}", diff.changed_ratio() * 100.0).into(),
+                ),
+            ],
+        ));
+    }
+    ui.columns(
+        if comparison.heatmap.is_some() { 3 } else { 2 },
+        |columns| {
+            for (column, (number, preview)) in [&comparison.left, &comparison.right]
+                .into_iter()
+                .enumerate()
+            {
+                columns[column].strong(format!("v{number}"));
+                draw_preview(&mut columns[column], preview, PREVIEW_SIZE);
+            }
+            if let Some((heatmap, _)) = &comparison.heatmap {
+                columns[2].strong(localizer.t("compare-heatmap"));
+                columns[2].add(
+                    egui::Image::new(heatmap)
+                        .fit_to_exact_size(egui::vec2(PREVIEW_SIZE, PREVIEW_SIZE)),
+                );
+            }
+        },
+    );
+}
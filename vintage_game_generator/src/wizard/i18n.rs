@@ -0,0 +1,161 @@
+//! Translations of the wizard UI
+//!
+//! Wizard strings are looked up by id in Fluent catalogs under
+//! `assets/i18n/<language>/wizard.ftl`, which are compiled into the binary.
+//! English is the baseline: an id missing from another catalog falls back
+//! to English, and one missing from both is shown as the id itself. The UI
+//! language is picked in the settings window and is independent of the
+//! language generated content is written in.
+
+use bevy::prelude::*;
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use unic_langid::LanguageIdentifier;
+
+/// Language the UI starts in and falls back to
+pub const DEFAULT_LANGUAGE: &str = "en-US";
+
+/// A language the wizard UI is translated into
+#[derive(Debug)]
+pub struct UiLanguage {
+    /// BCP 47 identifier, e.g. `en-US`
+    pub id: &'static str,
+    /// Name of the language in itself, for the language picker
+    pub name: &'static str,
+    source: &'static str,
+}
+
+/// Languages with a catalog, the baseline first
+pub const LANGUAGES: &[UiLanguage] = &[
+    UiLanguage {
+        id: "en-US",
+        name: "English",
+        source: include_str!("../../assets/i18n/en-US/wizard.ftl"),
+    },
+    UiLanguage {
+        id: "es-ES",
+        name: "Español",
+        source: include_str!("../../assets/i18n/es-ES/wizard.ftl"),
+    },
+];
+
+type Bundle = FluentBundle<FluentResource>;
+
+/// Looks up wizard strings in the current UI language
+#[derive(Resource)]
+pub struct Localizer {
+    language: &'static UiLanguage,
+    bundle: Bundle,
+    fallback: Bundle,
+}
+
+impl Default for Localizer {
+    fn default() -> Self {
+        Self::new(DEFAULT_LANGUAGE)
+    }
+}
+
+impl Localizer {
+    /// Localizer for `language`, or English if there is no catalog for it
+    pub fn new(language: &str) -> Self {
+        let baseline = &LANGUAGES[0];
+        let language = find_language(language).unwrap_or_else(|| {
+            warn!("No translation for {language}, using {}", baseline.id);
+            baseline
+        });
+        Self {
+            language,
+            bundle: build_bundle(language),
+            fallback: build_bundle(baseline),
+        }
+    }
+
+    /// Identifier of the current language
+    pub fn language(&self) -> &'static str {
+        self.language.id
+    }
+
+    /// Switch the UI language; returns false if there is no catalog for it
+    pub fn set_language(&mut self, language: &str) -> bool {
+        let Some(found) = find_language(language) else {
+            return false;
+        };
+        if found.id != self.language.id {
+            self.language = found;
+            self.bundle = build_bundle(found);
+        }
+        true
+    }
+
+    /// The string with this id
+    pub fn t(&self, id: &str) -> String {
+        self.format(id, None)
+    }
+
+    /// The string with this id, filling in its `{ $name }` placeholders
+    pub fn t_args(&self, id: &str, args: &[(&str, FluentValue)]) -> String {
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, value.clone());
+        }
+        self.format(id, Some(&fluent_args))
+    }
+
+    fn format(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        for bundle in [&self.bundle, &self.fallback] {
+            let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) else {
+                continue;
+            };
+            let mut errors = Vec::new();
+            let text = bundle.format_pattern(pattern, args, &mut errors);
+            if !errors.is_empty() {
+                warn!("Errors formatting {id}: {errors:?}");
+            }
+            return text.into_owned();
+        }
+        id.to_string()
+    }
+}
+
+fn find_language(language: &str) -> Option<&'static UiLanguage> {
+    LANGUAGES
+        .iter()
+        .find(|candidate| candidate.id.eq_ignore_ascii_case(language))
+        .or_else(|| {
+            // "es" or "es-MX" pick the first catalog of the same language
+            let primary = language.split(['-', '_']).next()?;
+            LANGUAGES.iter().find(|candidate| {
+                candidate
+                    .id
+                    .split('-')
+                    .next()
+                    .is_some_and(|other| other.eq_ignore_ascii_case(primary))
+            })
+        })
+}
+
+fn build_bundle(language: &UiLanguage) -> Bundle {
+    let id: LanguageIdentifier = language
+        .id
+        .parse()
+        .expect("built-in language ids are valid");
+    let mut bundle = FluentBundle::new_concurrent(vec![id]);
+    // Unicode isolation marks show up as boxes in egui's fonts
+    bundle.set_use_isolating(false);
+    match FluentResource::try_new(language.source.to_string()) {
+        Ok(resource) => {
+            if let Err(errors) = bundle.add_resource(resource) {
+                warn!(
+                    "Duplicate strings in the {} catalog: {errors:?}",
+                    language.id
+                );
+            }
+        }
+        Err((resource, errors)) => {
+            warn!("Errors in the {} catalog: {errors:?}", language.id);
+            // Keep whatever parsed
+            let _ = bundle.add_resource(resource);
+        }
+    }
+    bundle
+}
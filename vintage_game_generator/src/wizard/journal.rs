@@ -0,0 +1,74 @@
+//! Change journal of a project
+//!
+//! Changes made to a project from outside the wizard's own steps, such as
+//! importing it from another layout, are appended to `journal.jsonl` in the
+//! project directory, one JSON object per line, so what happened to a
+//! project can be read back after the fact.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+
+/// File in the project directory holding the journal
+pub const JOURNAL_FILE: &str = "journal.jsonl";
+
+/// One change to a project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub timestamp: DateTime<Utc>,
+    /// Kind of change, e.g. `legacy_import`
+    pub kind: String,
+    pub summary: String,
+    /// One line per detail of the change
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<String>,
+}
+
+impl JournalEntry {
+    pub fn new(kind: impl Into<String>, summary: impl Into<String>) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            kind: kind.into(),
+            summary: summary.into(),
+            details: Vec::new(),
+        }
+    }
+
+    pub fn with_details(mut self, details: Vec<String>) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// Append an entry to the journal of the project in `project_dir`
+pub fn append(project_dir: &Path, entry: &JournalEntry) -> Result<()> {
+    let path = project_dir.join(JOURNAL_FILE);
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let line = serde_json::to_string(entry)?;
+    writeln!(file, "{line}").with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Entries of the journal of the project in `project_dir`, oldest first.
+/// A project without a journal has none.
+pub fn read(project_dir: &Path) -> Result<Vec<JournalEntry>> {
+    let path = project_dir.join(JOURNAL_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line)
+                .with_context(|| format!("Malformed entry in {}", path.display()))
+        })
+        .collect()
+}
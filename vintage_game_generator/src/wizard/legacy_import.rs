@@ -0,0 +1,216 @@
+//! Importing projects made by rust-vintage-game-generator
+//!
+//! Before the rename, the tool kept each project in a directory named after
+//! the game:
+//!
+//! ```text
+//! <game>/
+//! ├── game.toml           # flat config: name, genre, art_style, ...
+//! ├── conversation.json   # design conversation, [{"role", "content"}]
+//! ├── prompts/            # generated prompts
+//! └── output/             # generated files, design.md among them
+//! ```
+//!
+//! Some versions wrote the same flat config as `project.toml`, which is told
+//! apart from the current schema by its missing `[basic_info]` table.
+//! [`import_legacy_project`] leaves the old directory untouched and creates
+//! a project in the current layout in the base directory: a UUID directory
+//! with `project.toml`, the prompts under `prompts/generated/`, the
+//! generated files under `assets/` and the design as `design.md`. The import
+//! is recorded in the new project's change journal.
+
+use crate::batch::{DESIGN_FILE, PROJECT_FILE};
+use crate::wizard::config::{ConfigManager, ConversationEntry, CustomFeature, ProjectMetadata};
+use crate::wizard::journal::{self, JournalEntry};
+use crate::wizard::projects::project_files;
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Config file of a legacy project
+pub const LEGACY_CONFIG_FILE: &str = "game.toml";
+
+/// Design conversation of a legacy project
+const LEGACY_CONVERSATION_FILE: &str = "conversation.json";
+
+/// Directories of a legacy project and where their files go
+const LEGACY_DIRS: &[(&str, &str)] = &[("prompts", "prompts/generated"), ("output", "assets")];
+
+/// Journal kind of an import
+pub const IMPORT_JOURNAL_KIND: &str = "legacy_import";
+
+/// The flat config schema of the old tool
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct LegacyConfig {
+    #[serde(alias = "game_name", alias = "title")]
+    name: String,
+    tagline: String,
+    description: String,
+    genre: String,
+    target_audience: String,
+    #[serde(alias = "style")]
+    art_style: String,
+    sprite_size: u32,
+    reference_games: Vec<String>,
+    #[serde(alias = "core_mechanics")]
+    mechanics: Vec<String>,
+    features: Vec<String>,
+    created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LegacyMessage {
+    role: String,
+    content: String,
+}
+
+/// Config file of `dir` if it is a legacy project
+pub fn legacy_config_path(dir: &Path) -> Option<PathBuf> {
+    let legacy = dir.join(LEGACY_CONFIG_FILE);
+    if legacy.is_file() && !dir.join(PROJECT_FILE).exists() {
+        return Some(legacy);
+    }
+    let project = dir.join(PROJECT_FILE);
+    let value: toml::Table = toml::from_str(&std::fs::read_to_string(&project).ok()?).ok()?;
+    let flat = !value.contains_key("basic_info")
+        && ["genre", "game_name", "art_style"]
+            .iter()
+            .any(|key| value.contains_key(*key));
+    flat.then_some(project)
+}
+
+/// `dir` if it is a legacy project, or else the legacy projects directly
+/// below it, sorted
+pub fn find_legacy_projects(dir: &Path) -> Result<Vec<PathBuf>> {
+    if legacy_config_path(dir).is_some() {
+        return Ok(vec![dir.to_path_buf()]);
+    }
+    let mut projects = Vec::new();
+    for entry in
+        std::fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_dir() && legacy_config_path(&path).is_some() {
+            projects.push(path);
+        }
+    }
+    projects.sort();
+    Ok(projects)
+}
+
+/// Import the legacy project in `legacy_dir` into a new UUID directory of
+/// `base_dir` and return the path of its `project.toml`
+pub fn import_legacy_project(legacy_dir: &Path, base_dir: &Path) -> Result<PathBuf> {
+    let Some(config_path) = legacy_config_path(legacy_dir) else {
+        bail!("{} is not a legacy project", legacy_dir.display());
+    };
+    let content = std::fs::read_to_string(&config_path)
+        .with_context(|| format!("Failed to read {}", config_path.display()))?;
+    let legacy: LegacyConfig = toml::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", config_path.display()))?;
+
+    let project_dir = base_dir.join(Uuid::new_v4().to_string());
+    let mut details = vec![format!(
+        "Config {} migrated to {PROJECT_FILE}",
+        relative_name(legacy_dir, &config_path)
+    )];
+
+    for file in project_files(legacy_dir)? {
+        let relative = file.strip_prefix(legacy_dir)?;
+        if file == config_path || relative == Path::new(LEGACY_CONVERSATION_FILE) {
+            continue;
+        }
+        let target = migrated_path(relative);
+        if let Some(parent) = project_dir.join(&target).parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(&file, project_dir.join(&target))
+            .with_context(|| format!("Failed to copy {}", file.display()))?;
+        details.push(format!("{} -> {}", relative.display(), target.display()));
+    }
+
+    let mut manager = ConfigManager::new(&project_dir, Some("project"))?;
+    let config = &mut manager.config;
+    config.metadata = ProjectMetadata::default();
+    if let Some(created_at) = legacy.created_at {
+        config.metadata.created_at = created_at;
+    }
+    config.basic_info.name = if legacy.name.is_empty() {
+        legacy_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    } else {
+        legacy.name
+    };
+    config.basic_info.tagline = legacy.tagline;
+    config.basic_info.description = legacy.description;
+    config.basic_info.genre = legacy.genre;
+    config.basic_info.target_audience = legacy.target_audience;
+    config.visual_style.art_direction_notes = legacy.art_style;
+    config.visual_style.sprite_size = legacy.sprite_size;
+    config.visual_style.reference_games = legacy.reference_games;
+    config.gameplay.core_mechanics = legacy.mechanics;
+    config.features.custom_features = legacy
+        .features
+        .into_iter()
+        .map(|name| CustomFeature {
+            name,
+            description: String::new(),
+            complexity: String::new(),
+        })
+        .collect();
+
+    let conversation = legacy_dir.join(LEGACY_CONVERSATION_FILE);
+    if conversation.is_file() {
+        let content = std::fs::read_to_string(&conversation)
+            .with_context(|| format!("Failed to read {}", conversation.display()))?;
+        let messages: Vec<LegacyMessage> = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", conversation.display()))?;
+        details.push(format!(
+            "{} messages of {LEGACY_CONVERSATION_FILE} copied into the project's AI context",
+            messages.len()
+        ));
+        let created_at = config.metadata.created_at;
+        config.ai_context.conversation_history = messages
+            .into_iter()
+            .map(|message| ConversationEntry {
+                timestamp: created_at,
+                role: message.role,
+                content: message.content,
+                phase: "legacy".to_string(),
+            })
+            .collect();
+    }
+    manager.save()?;
+
+    journal::append(
+        &project_dir,
+        &JournalEntry::new(
+            IMPORT_JOURNAL_KIND,
+            format!("Imported from the legacy project {}", legacy_dir.display()),
+        )
+        .with_details(details),
+    )?;
+    Ok(project_dir.join(PROJECT_FILE))
+}
+
+/// Where a file of a legacy project goes in the current layout
+fn migrated_path(relative: &Path) -> PathBuf {
+    if relative == Path::new("output").join(DESIGN_FILE) {
+        return PathBuf::from(DESIGN_FILE);
+    }
+    for (legacy, current) in LEGACY_DIRS {
+        if let Ok(rest) = relative.strip_prefix(legacy) {
+            return Path::new(current).join(rest);
+        }
+    }
+    relative.to_path_buf()
+}
+
+fn relative_name(dir: &Path, file: &Path) -> String {
+    file.strip_prefix(dir).unwrap_or(file).display().to_string()
+}
@@ -1,14 +1,97 @@
-use crate::wizard::{AppDirectories, AppMode, SwitchModeEvent, config::ProjectConfig};
+use crate::wizard::i18n::Localizer;
+use crate::wizard::project_index::{ProjectFilter, ProjectIndex};
+use crate::wizard::projects::{
+    archive_project, delete_project, duplicate_project, restore_archive,
+};
+use crate::wizard::templates::{ProjectTemplate, load_templates};
+use crate::wizard::{AppDirectories, AppMode, AppState, SwitchModeEvent, config::ProjectConfig};
 use bevy::prelude::*;
 use bevy_egui::{EguiContexts, egui};
-use std::path::PathBuf;
+use chrono::NaiveDate;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
+/// Directory under the base directory archives are written to by default
+const ARCHIVES_DIR: &str = "archives";
+
 #[derive(Resource, Default)]
 pub struct ListModeState {
     pub projects: Vec<(Uuid, ProjectConfig, PathBuf)>,
     pub selected_index: usize,
     pub error: Option<String>,
+    /// Built-in presets followed by saved templates
+    pub templates: Vec<ProjectTemplate>,
+    pub template_index: usize,
+    /// Name for a project created from a template
+    pub new_project_name: String,
+    /// Name for the selected project saved as a template
+    pub template_name: String,
+    /// Leave generated files out of duplicates
+    pub clear_generated: bool,
+    /// Zip file to archive the selected project to
+    pub archive_path: String,
+    /// Zip file to restore a project from
+    pub restore_path: String,
+    /// Project directory waiting for the delete to be confirmed
+    pub confirm_delete: Option<PathBuf>,
+    /// Outcome of the last project action
+    pub message: Option<String>,
+    /// Searchable summaries of the projects
+    pub index: ProjectIndex,
+    pub filter: ProjectFilter,
+    /// Creation date range as typed, `YYYY-MM-DD`
+    pub created_from: String,
+    pub created_to: String,
+}
+
+impl ListModeState {
+    /// Reload the projects, selecting the one at `select` if given
+    fn reload(&mut self, base_dir: &Path, select: Option<&Path>, localizer: &Localizer) {
+        match load_projects(base_dir) {
+            Ok(projects) => self.projects = projects,
+            Err(e) => {
+                self.error =
+                    Some(localizer.t_args("list-load-failed", &[("error", e.to_string().into())]))
+            }
+        }
+        if let Some(index) =
+            select.and_then(|select| self.projects.iter().position(|(_, _, path)| path == select))
+        {
+            self.selected_index = index;
+        }
+        self.selected_index = self
+            .selected_index
+            .min(self.projects.len().saturating_sub(1));
+
+        match ProjectIndex::load(base_dir) {
+            Ok(index) => self.index = index,
+            Err(e) => warn!("Rebuilding the project index: {e:#}"),
+        }
+        self.index.sync(
+            self.projects
+                .iter()
+                .map(|(_, config, path)| (path.parent().unwrap_or(path), config)),
+        );
+        if let Err(e) = self.index.save(base_dir) {
+            warn!("Failed to save the project index: {e:#}");
+        }
+    }
+
+    /// Indices of the projects matching the filter
+    fn visible(&self) -> Vec<usize> {
+        self.projects
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, _, path))| {
+                self.filter.is_empty()
+                    || self
+                        .index
+                        .get(path.parent().unwrap_or(path))
+                        .is_some_and(|entry| self.filter.matches(entry))
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
 }
 
 pub fn draw_list_ui(
@@ -16,12 +99,22 @@ pub fn draw_list_ui(
     directories: Res<AppDirectories>,
     mut state: ResMut<ListModeState>,
     mut switch_mode_events: EventWriter<SwitchModeEvent>,
+    mut app_state: ResMut<AppState>,
+    localizer: Res<Localizer>,
 ) {
     // Load projects on first run
     if state.projects.is_empty() {
-        match load_projects(&directories.base_dir) {
-            Ok(projects) => state.projects = projects,
-            Err(e) => state.error = Some(format!("Failed to load projects: {e}")),
+        state.reload(&directories.base_dir, None, &localizer);
+    }
+    if state.templates.is_empty() {
+        match load_templates(&directories.base_dir) {
+            Ok(templates) => state.templates = templates,
+            Err(e) => {
+                state.error = Some(localizer.t_args(
+                    "list-templates-failed",
+                    &[("error", format!("{e:#}").into())],
+                ))
+            }
         }
     }
 
@@ -31,13 +124,30 @@ pub fn draw_list_ui(
     // Top panel
     if let Ok(ctx) = contexts.ctx_mut() {
         egui::TopBottomPanel::top("header").show(ctx, |ui| {
-            ui.heading("Browse Projects");
+            ui.horizontal(|ui| {
+                ui.heading(localizer.t("list-title"));
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button(localizer.t("nav-settings")).clicked() {
+                        app_state.show_settings = !app_state.show_settings;
+                    }
+                });
+            });
             ui.separator();
-            ui.label(format!(
-                "Found {} projects in: {}",
-                state.projects.len(),
-                directories.base_dir.display()
+            ui.label(localizer.t_args(
+                "list-found",
+                &[
+                    ("count", state.projects.len().into()),
+                    ("dir", directories.base_dir.display().to_string().into()),
+                ],
             ));
+            draw_new_from_template(
+                ui,
+                &mut state,
+                &directories,
+                &mut switch_mode_events,
+                &localizer,
+            );
+            draw_restore_archive(ui, &mut state, &directories, &localizer);
         });
     }
 
@@ -45,13 +155,17 @@ pub fn draw_list_ui(
     if let Ok(ctx) = contexts.ctx_mut() {
         egui::TopBottomPanel::bottom("navigation").show(ctx, |ui| {
             ui.horizontal(|ui| {
-                if ui.button("◀ Previous").clicked() && state.selected_index > 0 {
-                    state.selected_index -= 1;
+                let visible = state.visible();
+                if ui.button(localizer.t("list-previous")).clicked()
+                    && let Some(&previous) =
+                        visible.iter().rev().find(|&&i| i < state.selected_index)
+                {
+                    state.selected_index = previous;
                 }
 
                 ui.separator();
 
-                if ui.button("Edit").clicked()
+                if ui.button(localizer.t("list-edit")).clicked()
                     && let Some((uuid, _, path)) = state.projects.get(state.selected_index)
                 {
                     info!("Switching to edit mode for project: {}", uuid);
@@ -64,22 +178,30 @@ pub fn draw_list_ui(
 
                 ui.separator();
 
-                if ui.button("Next ▶").clicked()
-                    && state.selected_index < state.projects.len().saturating_sub(1)
+                if ui.button(localizer.t("list-next")).clicked()
+                    && let Some(&next) = visible.iter().find(|&&i| i > state.selected_index)
                 {
-                    state.selected_index += 1;
+                    state.selected_index = next;
                 }
+
+                ui.separator();
+
+                draw_save_as_template(ui, &mut state, &directories, &localizer);
             });
+            draw_project_actions(ui, &mut state, &directories, &localizer);
         });
     }
 
     // Project list on top
     if let Ok(ctx) = contexts.ctx_mut() {
         egui::TopBottomPanel::top("project_list").show(ctx, |ui| {
+            draw_search(ui, &mut state, &localizer);
+            let visible = state.visible();
             egui::ScrollArea::horizontal().show(ui, |ui| {
                 ui.horizontal(|ui| {
                     let mut new_index = None;
-                    for (i, (_, config, _)) in state.projects.iter().enumerate() {
+                    for &i in &visible {
+                        let config = &state.projects[i].1;
                         let is_selected = i == state.selected_index;
                         if ui
                             .selectable_label(is_selected, &config.basic_info.name)
@@ -102,19 +224,19 @@ pub fn draw_list_ui(
             // Tab bar
             ui.horizontal(|ui| unsafe {
                 if ui
-                    .selectable_label(SELECTED_TAB == "details", "Project Details")
+                    .selectable_label(SELECTED_TAB == "details", localizer.t("list-tab-details"))
                     .clicked()
                 {
                     SELECTED_TAB = "details";
                 }
                 if ui
-                    .selectable_label(SELECTED_TAB == "files", "Files")
+                    .selectable_label(SELECTED_TAB == "files", localizer.t("list-tab-files"))
                     .clicked()
                 {
                     SELECTED_TAB = "files";
                 }
                 if ui
-                    .selectable_label(SELECTED_TAB == "preview", "Preview")
+                    .selectable_label(SELECTED_TAB == "preview", localizer.t("list-tab-preview"))
                     .clicked()
                 {
                     SELECTED_TAB = "preview";
@@ -126,80 +248,476 @@ pub fn draw_list_ui(
             // Tab content
             unsafe {
                 match SELECTED_TAB {
-                    "details" => draw_project_details(ui, &state),
-                    "files" => draw_project_files(ui, &state),
-                    "preview" => draw_project_preview(ui, &state),
+                    "details" => draw_project_details(ui, &state, &localizer),
+                    "files" => draw_project_files(ui, &state, &localizer),
+                    "preview" => draw_project_preview(ui, &state, &localizer),
                     _ => {}
                 }
             }
 
             // Error display
             if let Some(error) = &state.error {
-                ui.colored_label(egui::Color32::RED, format!("Error: {error}"));
+                ui.colored_label(
+                    egui::Color32::RED,
+                    localizer.t_args("list-error", &[("error", error.as_str().into())]),
+                );
             }
         });
     }
+
+    if let Ok(ctx) = contexts.ctx_mut() {
+        draw_delete_confirmation(ctx, &mut state, &directories, &localizer);
+    }
 }
 
-fn draw_project_details(ui: &mut egui::Ui, state: &ListModeState) {
+/// Search box and filters over the project index
+fn draw_search(ui: &mut egui::Ui, state: &mut ListModeState, localizer: &Localizer) {
+    ui.horizontal(|ui| {
+        ui.label("🔍");
+        ui.add(
+            egui::TextEdit::singleline(&mut state.filter.query)
+                .hint_text(localizer.t("list-search-hint"))
+                .desired_width(220.0),
+        );
+
+        let genres = state.index.genres();
+        let any_genre = localizer.t("list-any-genre");
+        egui::ComboBox::from_id_salt("project_genre")
+            .selected_text(state.filter.genre.as_deref().unwrap_or(&any_genre))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut state.filter.genre, None, any_genre.as_str());
+                for genre in genres {
+                    let label = genre.clone();
+                    ui.selectable_value(&mut state.filter.genre, Some(genre), label);
+                }
+            });
+
+        ui.label(localizer.t("list-created"));
+        for (text, date, hint) in [
+            (
+                &mut state.created_from,
+                &mut state.filter.created_from,
+                localizer.t("list-from"),
+            ),
+            (
+                &mut state.created_to,
+                &mut state.filter.created_to,
+                localizer.t("list-to"),
+            ),
+        ] {
+            let response = ui.add(
+                egui::TextEdit::singleline(text)
+                    .hint_text(hint)
+                    .desired_width(90.0),
+            );
+            if response.changed() {
+                *date = NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d").ok();
+            }
+            if !text.trim().is_empty() && date.is_none() {
+                response.on_hover_text(localizer.t("list-date-format"));
+            }
+        }
+
+        if !state.filter.is_empty() && ui.button(localizer.t("list-clear")).clicked() {
+            state.filter = ProjectFilter::default();
+            state.created_from.clear();
+            state.created_to.clear();
+        }
+        ui.label(
+            egui::RichText::new(localizer.t_args(
+                "list-matching",
+                &[
+                    ("shown", state.visible().len().into()),
+                    ("total", state.projects.len().into()),
+                ],
+            ))
+            .weak(),
+        );
+    });
+}
+
+/// Duplicate, archive or delete the selected project
+fn draw_project_actions(
+    ui: &mut egui::Ui,
+    state: &mut ListModeState,
+    directories: &AppDirectories,
+    localizer: &Localizer,
+) {
+    let Some((uuid, config, path)) = state.projects.get(state.selected_index) else {
+        return;
+    };
+    let project_dir = path.parent().unwrap_or(path).to_path_buf();
+    let uuid = *uuid;
+    let name = config.basic_info.name.clone();
+
+    ui.horizontal(|ui| {
+        if ui.button(localizer.t("list-duplicate")).clicked() {
+            match duplicate_project(&project_dir, state.clear_generated) {
+                Ok(copy) => {
+                    state.message = Some(
+                        localizer.t_args("list-duplicated", &[("name", name.as_str().into())]),
+                    );
+                    state.reload(&directories.base_dir, Some(&copy), localizer);
+                }
+                Err(e) => state.error = Some(format!("{e:#}")),
+            }
+        }
+        ui.checkbox(
+            &mut state.clear_generated,
+            localizer.t("list-without-generated"),
+        )
+        .on_hover_text(localizer.t("list-without-generated-hint"));
+
+        ui.separator();
+
+        ui.add(
+            egui::TextEdit::singleline(&mut state.archive_path)
+                .hint_text(format!("{ARCHIVES_DIR}/{uuid}.zip"))
+                .desired_width(220.0),
+        );
+        if ui.button(localizer.t("list-archive")).clicked() {
+            let archive_path = if state.archive_path.trim().is_empty() {
+                directories
+                    .base_dir
+                    .join(ARCHIVES_DIR)
+                    .join(format!("{uuid}.zip"))
+            } else {
+                directories.base_dir.join(state.archive_path.trim())
+            };
+            match archive_project(&project_dir, &archive_path) {
+                Ok(()) => {
+                    state.message = Some(localizer.t_args(
+                        "list-archived",
+                        &[
+                            ("name", name.as_str().into()),
+                            ("path", archive_path.display().to_string().into()),
+                        ],
+                    ))
+                }
+                Err(e) => state.error = Some(format!("{e:#}")),
+            }
+        }
+
+        ui.separator();
+
+        if ui
+            .button(
+                egui::RichText::new(localizer.t("list-delete"))
+                    .color(egui::Color32::from_rgb(255, 100, 100)),
+            )
+            .clicked()
+        {
+            state.confirm_delete = Some(project_dir.clone());
+        }
+    });
+
+    if let Some(message) = &state.message {
+        ui.label(egui::RichText::new(message).small().weak());
+    }
+}
+
+/// Restore a project from a zip archive
+fn draw_restore_archive(
+    ui: &mut egui::Ui,
+    state: &mut ListModeState,
+    directories: &AppDirectories,
+    localizer: &Localizer,
+) {
+    ui.horizontal(|ui| {
+        ui.label(localizer.t("list-restore-archive"));
+        ui.add(
+            egui::TextEdit::singleline(&mut state.restore_path)
+                .hint_text(localizer.t("list-restore-hint"))
+                .desired_width(280.0),
+        );
+        if ui
+            .add_enabled(
+                !state.restore_path.trim().is_empty(),
+                egui::Button::new(localizer.t("list-restore")),
+            )
+            .clicked()
+        {
+            let archive_path = directories.base_dir.join(state.restore_path.trim());
+            match restore_archive(&archive_path, &directories.base_dir) {
+                Ok(path) => {
+                    state.message = Some(localizer.t_args(
+                        "list-restored",
+                        &[("path", archive_path.display().to_string().into())],
+                    ));
+                    state.restore_path.clear();
+                    state.reload(&directories.base_dir, Some(&path), localizer);
+                }
+                Err(e) => state.error = Some(format!("{e:#}")),
+            }
+        }
+    });
+}
+
+fn draw_delete_confirmation(
+    ctx: &egui::Context,
+    state: &mut ListModeState,
+    directories: &AppDirectories,
+    localizer: &Localizer,
+) {
+    let Some(project_dir) = state.confirm_delete.clone() else {
+        return;
+    };
+    let name = state
+        .projects
+        .iter()
+        .find(|(_, _, path)| path.parent() == Some(project_dir.as_path()))
+        .map(|(_, config, _)| config.basic_info.name.clone())
+        .unwrap_or_else(|| project_dir.display().to_string());
+
+    egui::Window::new(localizer.t("delete-title"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.heading(localizer.t_args("delete-question", &[("name", name.as_str().into())]));
+            ui.add_space(10.0);
+            ui.label(localizer.t_args(
+                "delete-warning",
+                &[("path", project_dir.display().to_string().into())],
+            ));
+            ui.label(localizer.t("delete-archive-first"));
+            ui.add_space(20.0);
+
+            ui.horizontal(|ui| {
+                if ui.button(localizer.t("button-cancel")).clicked() {
+                    state.confirm_delete = None;
+                }
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui
+                        .button(
+                            egui::RichText::new(localizer.t("delete-confirm"))
+                                .color(egui::Color32::from_rgb(255, 100, 100)),
+                        )
+                        .clicked()
+                    {
+                        state.confirm_delete = None;
+                        match delete_project(&project_dir) {
+                            Ok(()) => {
+                                state.message = Some(
+                                    localizer
+                                        .t_args("list-deleted", &[("name", name.as_str().into())]),
+                                );
+                                state.reload(&directories.base_dir, None, localizer);
+                            }
+                            Err(e) => state.error = Some(format!("{e:#}")),
+                        }
+                    }
+                });
+            });
+        });
+}
+
+/// Pick a template and create a new project from it, then open the project
+fn draw_new_from_template(
+    ui: &mut egui::Ui,
+    state: &mut ListModeState,
+    directories: &AppDirectories,
+    switch_mode_events: &mut EventWriter<SwitchModeEvent>,
+    localizer: &Localizer,
+) {
+    ui.horizontal(|ui| {
+        ui.label(localizer.t("list-new-from-template"));
+        let selected_text = state
+            .templates
+            .get(state.template_index)
+            .map(|template| template.template.name.clone())
+            .unwrap_or_else(|| localizer.t("list-no-templates"));
+        egui::ComboBox::from_id_salt("project_template")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                for (i, template) in state.templates.iter().enumerate() {
+                    let label = if template.is_builtin() {
+                        template.template.name.clone()
+                    } else {
+                        localizer.t_args(
+                            "list-template-saved",
+                            &[("name", template.template.name.as_str().into())],
+                        )
+                    };
+                    ui.selectable_value(&mut state.template_index, i, label)
+                        .on_hover_text(&template.template.description);
+                }
+            });
+        ui.add(
+            egui::TextEdit::singleline(&mut state.new_project_name)
+                .hint_text(localizer.t("list-project-name"))
+                .desired_width(200.0),
+        );
+
+        let Some(template) = state.templates.get(state.template_index) else {
+            return;
+        };
+        if ui.button(localizer.t("list-create")).clicked() {
+            match template.create_project(&directories.base_dir, &state.new_project_name) {
+                Ok(path) => {
+                    info!("Created project from template {}", template.template.name);
+                    state.new_project_name.clear();
+                    // Reload the list so the new project shows up when coming back
+                    state.projects.clear();
+                    switch_mode_events.write(SwitchModeEvent {
+                        new_mode: AppMode::Generate,
+                        project_path: Some(path),
+                    });
+                }
+                Err(e) => state.error = Some(format!("{e:#}")),
+            }
+        }
+    });
+}
+
+/// Save the selected project as a reusable template
+fn draw_save_as_template(
+    ui: &mut egui::Ui,
+    state: &mut ListModeState,
+    directories: &AppDirectories,
+    localizer: &Localizer,
+) {
+    ui.add(
+        egui::TextEdit::singleline(&mut state.template_name)
+            .hint_text(localizer.t("list-template-name"))
+            .desired_width(160.0),
+    );
+    let Some((_, config, _)) = state.projects.get(state.selected_index) else {
+        return;
+    };
+    if ui
+        .add_enabled(
+            !state.template_name.trim().is_empty(),
+            egui::Button::new(localizer.t("list-save-template")),
+        )
+        .clicked()
+    {
+        let template = ProjectTemplate::from_project(
+            state.template_name.trim(),
+            &config.basic_info.tagline,
+            config,
+        );
+        match template.save(&directories.base_dir) {
+            Ok(path) => {
+                info!("Saved template to {}", path.display());
+                state.template_name.clear();
+                // Reload so the new template can be picked right away
+                state.templates.clear();
+            }
+            Err(e) => state.error = Some(format!("{e:#}")),
+        }
+    }
+}
+
+fn draw_project_details(ui: &mut egui::Ui, state: &ListModeState, localizer: &Localizer) {
+    let field = |id: &str, name: &str, value: String| localizer.t_args(id, &[(name, value.into())]);
+
     if let Some((uuid, config, path)) = state.projects.get(state.selected_index) {
         ui.heading(&config.basic_info.name);
         ui.label(&config.basic_info.tagline);
         ui.separator();
 
         ui.group(|ui| {
-            ui.label(format!("UUID: {uuid}"));
-            ui.label(format!("Path: {}", path.display()));
-            ui.label(format!("Genre: {}", config.basic_info.genre));
+            ui.label(field("list-uuid", "uuid", uuid.to_string()));
+            ui.label(field("list-path", "path", path.display().to_string()));
+            ui.label(field(
+                "list-genre",
+                "genre",
+                config.basic_info.genre.clone(),
+            ));
             // Theme and tone fields don't exist in BasicInfo
+            if let Some(entry) = state.index.get(path.parent().unwrap_or(path)) {
+                if !entry.source_games.is_empty() {
+                    ui.label(field(
+                        "list-source-games",
+                        "games",
+                        entry.source_games.join(", "),
+                    ));
+                }
+                ui.label(field(
+                    "list-created-at",
+                    "date",
+                    entry.created_at.format("%Y-%m-%d %H:%M").to_string(),
+                ));
+                if let Some(generated) = entry.last_generated {
+                    ui.label(field(
+                        "list-last-generated",
+                        "date",
+                        generated.format("%Y-%m-%d %H:%M").to_string(),
+                    ));
+                }
+            }
         });
 
         ui.separator();
 
-        ui.collapsing("Gameplay", |ui| {
-            ui.label(format!(
-                "Core Mechanics: {}",
-                config.gameplay.core_mechanics.join(", ")
+        ui.collapsing(localizer.t("list-gameplay"), |ui| {
+            ui.label(field(
+                "list-core-mechanics",
+                "value",
+                config.gameplay.core_mechanics.join(", "),
+            ));
+            ui.label(field(
+                "list-progression",
+                "value",
+                config.gameplay.progression_type.clone(),
             ));
-            ui.label(format!(
-                "Progression Type: {}",
-                config.gameplay.progression_type
+            ui.label(field(
+                "list-gameplay-loop",
+                "value",
+                config.gameplay.gameplay_loop.clone(),
             ));
-            ui.label(format!("Gameplay Loop: {}", config.gameplay.gameplay_loop));
-            ui.label(format!(
-                "Player Motivation: {}",
-                config.gameplay.player_motivation
+            ui.label(field(
+                "list-motivation",
+                "value",
+                config.gameplay.player_motivation.clone(),
             ));
         });
 
-        ui.collapsing("Visual Style", |ui| {
-            ui.label(format!(
-                "Reference Games: {}",
-                config.visual_style.reference_games.join(", ")
+        ui.collapsing(localizer.t("list-visual-style"), |ui| {
+            ui.label(field(
+                "list-reference-games",
+                "value",
+                config.visual_style.reference_games.join(", "),
+            ));
+            ui.label(field(
+                "list-color-mood",
+                "value",
+                config.visual_style.color_mood.clone(),
             ));
-            ui.label(format!("Color Mood: {}", config.visual_style.color_mood));
-            ui.label(format!(
-                "Sprite Size: {} px",
-                config.visual_style.sprite_size
+            ui.label(field(
+                "list-sprite-size",
+                "value",
+                config.visual_style.sprite_size.to_string(),
+            ));
+            ui.label(field(
+                "list-use-outline",
+                "value",
+                config.visual_style.use_outline.to_string(),
             ));
-            ui.label(format!("Use Outline: {}", config.visual_style.use_outline));
         });
 
-        ui.collapsing("Technical", |ui| {
-            ui.label(format!("World Size: {}", config.technical.world_size));
-            ui.label(format!(
-                "Target Platforms: {}",
-                config.technical.target_platforms.join(", ")
+        ui.collapsing(localizer.t("list-technical"), |ui| {
+            ui.label(field(
+                "list-world-size",
+                "value",
+                config.technical.world_size.clone(),
+            ));
+            ui.label(field(
+                "list-target-platforms",
+                "value",
+                config.technical.target_platforms.join(", "),
             ));
         });
     } else {
-        ui.label("No project selected");
+        ui.label(localizer.t("list-no-project"));
     }
 }
 
-fn draw_project_files(ui: &mut egui::Ui, state: &ListModeState) {
+fn draw_project_files(ui: &mut egui::Ui, state: &ListModeState, localizer: &Localizer) {
     if let Some((_, _, path)) = state.projects.get(state.selected_index) {
-        ui.heading("Project Files");
+        ui.heading(localizer.t("list-files-title"));
         ui.separator();
 
         // List files in project directory
@@ -210,31 +728,46 @@ fn draw_project_files(ui: &mut egui::Ui, state: &ListModeState) {
                 }
             }
         } else {
-            ui.label("Could not read project directory");
+            ui.label(localizer.t("list-files-unreadable"));
         }
     } else {
-        ui.label("No project selected");
+        ui.label(localizer.t("list-no-project"));
     }
 }
 
-fn draw_project_preview(ui: &mut egui::Ui, state: &ListModeState) {
+fn draw_project_preview(ui: &mut egui::Ui, state: &ListModeState, localizer: &Localizer) {
+    let field = |id: &str, name: &str, value: String| localizer.t_args(id, &[(name, value.into())]);
+
     if let Some((_, config, _)) = state.projects.get(state.selected_index) {
-        ui.heading("Preview");
+        ui.heading(localizer.t("list-preview-title"));
         ui.separator();
 
         // Show a preview of what would be generated
-        ui.label("This project would generate:");
+        ui.label(localizer.t("list-preview-intro"));
         ui.group(|ui| {
-            ui.label(format!("• A {} game", config.basic_info.genre));
-            ui.label(format!(
-                "• Inspired by {}",
-                config.visual_style.reference_games.join(", ")
+            ui.label(field(
+                "list-preview-genre",
+                "genre",
+                config.basic_info.genre.clone(),
+            ));
+            ui.label(field(
+                "list-preview-inspired",
+                "games",
+                config.visual_style.reference_games.join(", "),
+            ));
+            ui.label(field(
+                "list-preview-gameplay",
+                "value",
+                config.gameplay.progression_type.clone(),
+            ));
+            ui.label(field(
+                "list-preview-mood",
+                "value",
+                config.visual_style.color_mood.clone(),
             ));
-            ui.label(format!("• {} gameplay", config.gameplay.progression_type));
-            ui.label(format!("• {} mood visuals", config.visual_style.color_mood));
         });
     } else {
-        ui.label("No project selected");
+        ui.label(localizer.t("list-no-project"));
     }
 }
 
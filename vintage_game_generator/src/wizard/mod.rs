@@ -3,18 +3,38 @@
 
 use bevy::prelude::*;
 use bevy_egui::EguiContexts;
+use vintage_ai_client::AiConfig;
 
 // Submodules in wizard/ directory
+pub mod asset_compare;
+pub mod bulk_export;
+pub mod combat_tuning;
 pub mod config;
+pub mod config_reload;
+pub mod dev_bridge;
 pub mod directories;
+pub mod failed_tasks;
 pub mod generate_mode;
+pub mod history_gallery;
+pub mod i18n;
 pub mod image_loader;
+pub mod journal;
+pub mod legacy_import;
 pub mod list_mode;
 pub mod mode;
 pub mod overlay;
 pub mod pipeline;
+pub mod project_index;
+pub mod project_search;
+pub mod projects;
+pub mod prompt_inspector;
+pub mod prompt_preview;
+pub mod prompt_variables;
+pub mod settings;
 pub mod state;
 pub mod steps;
+pub mod templates;
+pub mod tutorial;
 pub mod watchers;
 
 pub use directories::AppDirectories;
@@ -28,10 +48,28 @@ impl Plugin for WizardPlugin {
     fn build(&self, app: &mut App) {
         info!("Initializing WizardPlugin");
 
-        // Add app state and pipeline
+        // Add app state and pipeline, generating with the configuration from
+        // the command line
+        let ai_config = app
+            .world()
+            .get_resource::<AiConfig>()
+            .cloned()
+            .unwrap_or_default();
         app.insert_resource(AppState::new())
-            .insert_resource(GenerationPipeline::new())
+            .insert_resource(GenerationPipeline::new().with_ai_config(ai_config))
             .insert_resource(watchers::ConfigModificationTracker::default())
+            .init_resource::<tutorial::TutorialState>()
+            .init_resource::<prompt_inspector::PromptInspectorState>()
+            .init_resource::<prompt_preview::PromptPreviewState>()
+            .init_resource::<prompt_variables::PromptVariablesState>()
+            .init_resource::<asset_compare::AssetCompareState>()
+            .init_resource::<combat_tuning::CombatTuningState>()
+            .init_resource::<project_search::ProjectSearchState>()
+            .init_resource::<history_gallery::HistoryGalleryState>()
+            .init_resource::<bulk_export::BulkExportState>()
+            .init_resource::<config_reload::ConfigReloadState>()
+            .init_resource::<i18n::Localizer>()
+            .init_resource::<settings::WizardSettings>()
             .add_event::<SwitchModeEvent>()
             .add_systems(Startup, setup_app)
             .add_systems(Update, handle_mode_switch);
@@ -44,12 +82,146 @@ impl Plugin for WizardPlugin {
                 apply_theme,
                 generate_mode::draw_generate_ui.run_if(in_mode(AppMode::Generate)),
                 watchers::check_prompt_changes.run_if(in_mode(AppMode::Generate)),
+                watchers::check_config_changes.run_if(in_mode(AppMode::Generate)),
                 pipeline::process_generation_queue.run_if(in_mode(AppMode::Generate)),
+                dev_bridge::sync_exported_assets.run_if(in_mode(AppMode::Generate)),
                 steps::freeform::process_conversation_stream.run_if(in_mode(AppMode::Generate)),
                 list_mode::draw_list_ui.run_if(in_mode(AppMode::List)),
             ),
         );
 
+        // Tutorial callouts draw on top of the generate UI
+        app.add_systems(
+            Update,
+            tutorial::draw_tutorial_overlay
+                .after(generate_mode::draw_generate_ui)
+                .run_if(in_mode(AppMode::Generate)),
+        );
+
+        // Quarantined pipeline tasks
+        app.add_systems(
+            Update,
+            failed_tasks::draw_failed_tasks
+                .after(generate_mode::draw_generate_ui)
+                .run_if(in_mode(AppMode::Generate)),
+        );
+
+        // Changes reloaded from project.toml and the prompt files
+        app.add_systems(
+            Update,
+            config_reload::draw_config_reload
+                .after(generate_mode::draw_generate_ui)
+                .run_if(in_mode(AppMode::Generate)),
+        );
+
+        // Developer panel for template authors, toggled with F12
+        app.add_systems(
+            Update,
+            (
+                prompt_inspector::toggle_prompt_inspector,
+                prompt_inspector::draw_prompt_inspector
+                    .after(generate_mode::draw_generate_ui)
+                    .after(prompt_inspector::toggle_prompt_inspector),
+            )
+                .run_if(in_mode(AppMode::Generate)),
+        );
+
+        // Built-in prompts as they will be sent, toggled with F5
+        app.add_systems(
+            Update,
+            (
+                prompt_preview::toggle_prompt_preview,
+                prompt_preview::draw_prompt_preview
+                    .after(generate_mode::draw_generate_ui)
+                    .after(prompt_preview::toggle_prompt_preview),
+            )
+                .run_if(in_mode(AppMode::Generate)),
+        );
+
+        // Project prompt variables and macros, toggled with F4
+        app.add_systems(
+            Update,
+            (
+                prompt_variables::toggle_prompt_variables,
+                prompt_variables::draw_prompt_variables
+                    .after(generate_mode::draw_generate_ui)
+                    .after(prompt_variables::toggle_prompt_variables),
+            )
+                .run_if(in_mode(AppMode::Generate)),
+        );
+
+        // Image version comparison, toggled with F11
+        app.add_systems(
+            Update,
+            (
+                asset_compare::toggle_asset_compare,
+                asset_compare::draw_asset_compare
+                    .after(generate_mode::draw_generate_ui)
+                    .after(asset_compare::toggle_asset_compare),
+            )
+                .run_if(in_mode(AppMode::Generate)),
+        );
+
+        // Combat value tuning for the Bevy export, toggled with F10
+        app.add_systems(
+            Update,
+            (
+                combat_tuning::toggle_combat_tuning,
+                combat_tuning::draw_combat_tuning
+                    .after(generate_mode::draw_generate_ui)
+                    .after(combat_tuning::toggle_combat_tuning),
+            )
+                .run_if(in_mode(AppMode::Generate)),
+        );
+
+        // Project-wide search across generated artifacts, toggled with F9
+        app.add_systems(
+            Update,
+            (
+                project_search::toggle_project_search,
+                project_search::draw_project_search
+                    .after(generate_mode::draw_generate_ui)
+                    .after(project_search::toggle_project_search),
+            )
+                .run_if(in_mode(AppMode::Generate)),
+        );
+
+        // Version history of generated artifacts, toggled with F8
+        app.add_systems(
+            Update,
+            (
+                history_gallery::toggle_history_gallery,
+                history_gallery::draw_history_gallery
+                    .after(generate_mode::draw_generate_ui)
+                    .after(history_gallery::toggle_history_gallery),
+            )
+                .run_if(in_mode(AppMode::Generate)),
+        );
+
+        // Bulk export of artifacts in converted formats, toggled with F7
+        app.add_systems(
+            Update,
+            (
+                bulk_export::toggle_bulk_export,
+                bulk_export::draw_bulk_export
+                    .after(generate_mode::draw_generate_ui)
+                    .after(bulk_export::toggle_bulk_export),
+            )
+                .run_if(in_mode(AppMode::Generate)),
+        );
+
+        // Wizard settings, toggled with F6 in either mode
+        app.add_systems(
+            Update,
+            (
+                settings::toggle_settings,
+                settings::draw_settings
+                    .after(generate_mode::draw_generate_ui)
+                    .after(list_mode::draw_list_ui)
+                    .after(settings::toggle_settings),
+            ),
+        );
+
         info!("WizardPlugin setup complete");
     }
 }
@@ -100,7 +272,13 @@ fn handle_mode_switch(
     }
 }
 
-fn setup_app(_commands: Commands, directories: Res<AppDirectories>, mode: Res<AppMode>) {
+fn setup_app(
+    _commands: Commands,
+    directories: Res<AppDirectories>,
+    mode: Res<AppMode>,
+    mut wizard_settings: ResMut<settings::WizardSettings>,
+    mut localizer: ResMut<i18n::Localizer>,
+) {
     info!("AI RPG Generator starting up in {:?} mode", mode);
     info!("Base dir: {:?}", directories.base_dir);
 
@@ -119,6 +297,18 @@ fn setup_app(_commands: Commands, directories: Res<AppDirectories>, mode: Res<Ap
     if let Err(e) = directories.ensure_directories_exist() {
         error!("Failed to create directories: {}", e);
     }
+
+    match settings::WizardSettings::load(&directories.base_dir) {
+        Ok(loaded) => *wizard_settings = loaded,
+        Err(e) => warn!("Using default settings: {e:#}"),
+    }
+    if !localizer.set_language(&wizard_settings.ui_language) {
+        warn!(
+            "No translation for {}, keeping {}",
+            wizard_settings.ui_language,
+            localizer.language()
+        );
+    }
 }
 
 fn apply_theme(mut contexts: EguiContexts) {
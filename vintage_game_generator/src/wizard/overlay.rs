@@ -6,7 +6,7 @@
 //! - Clickable hotspots with hover effects
 //! - Layered rendering with proper interaction handling
 
-use crate::wizard::image_loader;
+use crate::wizard::{image_loader, tutorial};
 use bevy::prelude::*;
 use bevy_egui::egui;
 use serde::{Deserialize, Serialize};
@@ -477,6 +477,8 @@ pub fn show_image_with_overlays(
     // Process hotspots using ui.interact for proper event handling
     for hotspot in &config.hotspots {
         let rect = hotspot.bounds.to_rect(config.image_size, drawn_rect);
+        // The tutorial can point at any hotspot by its id
+        tutorial::mark_anchor(ui.ctx(), &hotspot.id, rect);
         let is_disabled = disabled_hotspots.contains(&hotspot.id.as_str());
 
         if is_disabled {
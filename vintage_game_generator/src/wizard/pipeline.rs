@@ -1,20 +1,46 @@
-use crate::metaprompts::{GameGenerator, GenerationPhase};
+use crate::batch::DESIGN_FILE;
+use crate::footprint::{AssetChecklist, ensure_disk_space};
+use crate::metaprompts::{
+    Cancelled, DESIGN_STEPS, GameGenerator, GenerationCheckpoint, GenerationControl,
+    GenerationPhase, GenerationProgress, RunEstimate,
+};
+use crate::progress::ProgressBroadcast;
 use crate::wizard::{
+    config::ProjectConfig,
     directories::AppDirectories,
+    project_index,
     state::{AppState, LogLevel},
 };
-use anyhow::Result;
+use crate::{GeneratedArtifact, ProgressUpdate};
+use anyhow::{Context, Result};
 use bevy::prelude::*;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::runtime::Runtime;
 use tokio::sync::Mutex;
+use vintage_ai_client::AiConfig;
+use vintage_ai_client::artifacts::{ARTIFACT_INDEX_FILE, ArtifactIndex};
+use vintage_ai_client::history::ArtifactHistory;
+use vintage_ai_client::quota::{QuotaTracker, RateLimits};
+use vintage_ai_client::refusals::NeedsManualRewrite;
+
+/// Failed attempts before a task is quarantined
+pub const MAX_TASK_ATTEMPTS: u32 = 3;
 
 #[derive(Clone, Resource)]
 pub struct GenerationPipeline {
     pub runtime: Arc<Runtime>,
     pub generator: Arc<Mutex<Option<GameGenerator>>>,
+    /// Progress for subscribers outside Bevy
+    pub progress: ProgressBroadcast,
+    /// Pause and cancel for the design run in progress
+    pub control: GenerationControl,
+    /// Status of the latest design run, for the UI
+    pub run: Arc<std::sync::Mutex<RunStatus>>,
     pub current_task: Option<GenerationTask>,
     pub rate_limiter: RateLimiter,
+    /// Configuration the generator is created with
+    pub ai_config: AiConfig,
 }
 
 #[derive(Debug, Clone)]
@@ -24,6 +50,26 @@ pub struct GenerationTask {
     pub started_at: std::time::Instant,
 }
 
+/// Where the design run of generate mode stands
+#[derive(Debug, Clone)]
+pub enum RunStatus {
+    Idle,
+    Running(GenerationProgress),
+    Cancelled,
+    Failed(String),
+    /// A prompt was refused, also after an automatic rewrite
+    NeedsRewrite(NeedsManualRewrite),
+    Complete(PathBuf),
+    /// A single step was run again
+    Regenerated(String),
+}
+
+impl RunStatus {
+    pub fn is_running(&self) -> bool {
+        matches!(self, RunStatus::Running(_))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RateLimiter {
     pub last_request: Option<std::time::Instant>,
@@ -48,23 +94,34 @@ impl GenerationPipeline {
         Self {
             runtime,
             generator: Arc::new(Mutex::new(None)),
+            progress: ProgressBroadcast::new(),
+            control: GenerationControl::new(),
+            run: Arc::new(std::sync::Mutex::new(RunStatus::Idle)),
             current_task: None,
             rate_limiter: RateLimiter {
                 last_request: None,
                 min_delay_ms: 1000, // 1 second between requests
             },
+            ai_config: AiConfig::default(),
         }
     }
 
+    /// Create the generator with `config`, e.g. the one from the command line
+    pub fn with_ai_config(mut self, config: AiConfig) -> Self {
+        self.ai_config = config;
+        self
+    }
+
     pub fn initialize_generator(
         &self,
         _api_key: String,
         _directories: &AppDirectories,
+        ai_config: &AiConfig,
     ) -> Result<()> {
         let generator_arc = self.generator.clone();
 
         self.runtime.block_on(async move {
-            let new_generator = GameGenerator::new().await?;
+            let new_generator = GameGenerator::with_ai_config(ai_config).await?;
             let mut generator_lock = generator_arc.lock().await;
             *generator_lock = Some(new_generator);
             Ok::<(), anyhow::Error>(())
@@ -73,7 +130,262 @@ impl GenerationPipeline {
         Ok(())
     }
 
+    /// Generate the project's design document and character sprites in the
+    /// background, checkpointing each step into the project directory and
+    /// publishing the progress, with previews of the sprites. With
+    /// `resume`, steps completed by an earlier run are skipped.
+    pub fn start_run(&self, config: ProjectConfig, project_dir: PathBuf, resume: bool) {
+        let Some(set_status) = self.begin_run("Waiting for the generator...") else {
+            return;
+        };
+        let control = self.control.clone();
+        let generator = self.generator.clone();
+        let ai_config = self.ai_config.clone();
+        let broadcast = self.progress.clone();
+        self.runtime.spawn(async move {
+            let progress = set_status.clone();
+            let result = async move {
+                // Refuse up front rather than fail on a full disk halfway
+                ensure_disk_space(&AssetChecklist::for_project(&config), &project_dir)?;
+                let mut generator = generator.lock().await;
+                let generator =
+                    ready_generator(&mut generator, &ai_config, config, control).await?;
+                generator.set_checkpoint_dir(project_dir.clone(), resume);
+
+                let design = generator
+                    .generate_from_project(move |update| {
+                        broadcast.publish("", ProgressUpdate::from_run(&update));
+                        progress(RunStatus::Running(update))
+                    })
+                    .await?;
+                let design_file = project_dir.join(DESIGN_FILE);
+                std::fs::write(&design_file, design).context("Failed to write design document")?;
+                update_project_index(&project_dir);
+                Ok::<_, anyhow::Error>(design_file)
+            }
+            .await;
+
+            set_status(match result {
+                Ok(design_file) => RunStatus::Complete(design_file),
+                Err(e) if e.is::<Cancelled>() => RunStatus::Cancelled,
+                Err(e) => match e.downcast_ref::<NeedsManualRewrite>() {
+                    Some(refusal) => RunStatus::NeedsRewrite(refusal.clone()),
+                    None => RunStatus::Failed(format!("{e:#}")),
+                },
+            });
+        });
+    }
+
+    /// Tokens and cost a design run of the project would spend, step by
+    /// step, without requesting anything. Only call it while no run is
+    /// going, since it waits for the generator.
+    pub fn estimate_run(
+        &self,
+        config: ProjectConfig,
+        project_dir: PathBuf,
+        resume: bool,
+    ) -> Result<RunEstimate> {
+        let control = self.control.clone();
+        let generator = self.generator.clone();
+        let ai_config = self.ai_config.clone();
+        self.runtime.block_on(async move {
+            let mut generator = generator.lock().await;
+            let generator = ready_generator(&mut generator, &ai_config, config, control).await?;
+            generator.set_checkpoint_dir(project_dir, resume);
+            generator.estimate_from_project().await
+        })
+    }
+
+    /// Run one artifact of the last design run again in the background,
+    /// leaving the others as they are. `artifact_id` is one of
+    /// [`DESIGN_STEPS`] or the slug of a sprite or track the run wrote; the
+    /// upstream context comes from the checkpoint, and a `tweak` adjusts the
+    /// artifact's prompt.
+    pub fn regenerate(
+        &self,
+        artifact_id: &str,
+        tweak: Option<String>,
+        config: ProjectConfig,
+        project_dir: PathBuf,
+    ) {
+        self.regenerate_steps(vec![artifact_id.to_string()], tweak, config, project_dir);
+    }
+
+    /// Regenerate every artifact of the last run with the tag, e.g.
+    /// "needs-redo", design steps first. Returns how many were started.
+    pub fn regenerate_tagged(
+        &self,
+        tag: &str,
+        config: ProjectConfig,
+        project_dir: PathBuf,
+    ) -> Result<usize> {
+        let index = ArtifactIndex::load(project_dir.join(ARTIFACT_INDEX_FILE))?;
+        let checkpoint = GenerationCheckpoint::load(&project_dir)?;
+        let assets = checkpoint
+            .iter()
+            .flat_map(|checkpoint| checkpoint.completed.keys())
+            .map(String::as_str)
+            .filter(|id| !DESIGN_STEPS.contains(id));
+        let steps: Vec<String> = DESIGN_STEPS
+            .into_iter()
+            .chain(assets)
+            .filter(|step| index.has_tag(step, tag))
+            .map(str::to_string)
+            .collect();
+        let count = steps.len();
+        if count > 0 {
+            self.regenerate_steps(steps, None, config, project_dir);
+        }
+        Ok(count)
+    }
+
+    /// Run several artifacts of the last run again, in order, in the
+    /// background. Sprites and tracks are published with the file they were
+    /// written to.
+    pub fn regenerate_steps(
+        &self,
+        steps: Vec<String>,
+        tweak: Option<String>,
+        config: ProjectConfig,
+        project_dir: PathBuf,
+    ) {
+        let names = steps.join(", ");
+        let Some(set_status) = self.begin_run(&format!("Regenerating {names}...")) else {
+            return;
+        };
+        let control = self.control.clone();
+        let generator = self.generator.clone();
+        let ai_config = self.ai_config.clone();
+        let broadcast = self.progress.clone();
+        self.runtime.spawn(async move {
+            let progress = set_status.clone();
+            let result = async move {
+                let mut generator = generator.lock().await;
+                let generator =
+                    ready_generator(&mut generator, &ai_config, config, control).await?;
+                generator.set_checkpoint_dir(project_dir.clone(), true);
+
+                for (n, step) in steps.iter().enumerate() {
+                    progress(RunStatus::Running(GenerationProgress {
+                        phase: GenerationPhase::Design,
+                        progress: n as f32 / steps.len() as f32,
+                        message: format!("Regenerating {step}..."),
+                        artifact: None,
+                    }));
+                    if !DESIGN_STEPS.contains(&step.as_str()) {
+                        let artifact = generator.regenerate_asset(step, tweak.as_deref()).await?;
+                        broadcast.publish(
+                            "",
+                            ProgressUpdate::new("Regenerate", 1.0, format!("Regenerated {step}"))
+                                .with_step(step)
+                                .with_artifact(GeneratedArtifact::written(&artifact)),
+                        );
+                        continue;
+                    }
+                    let output = generator.regenerate_step(step, tweak.as_deref()).await?;
+                    // The design document is the core design
+                    if step == DESIGN_STEPS[0] {
+                        let design_file = project_dir.join(DESIGN_FILE);
+                        std::fs::write(&design_file, output)
+                            .context("Failed to write design document")?;
+                    }
+                    broadcast.publish(
+                        "",
+                        ProgressUpdate::new("Regenerate", 1.0, format!("Regenerated {step}"))
+                            .with_step(step),
+                    );
+                }
+                update_project_index(&project_dir);
+                Ok::<_, anyhow::Error>(())
+            }
+            .await;
+
+            set_status(match result {
+                Ok(()) => RunStatus::Regenerated(names),
+                Err(e) if e.is::<Cancelled>() => RunStatus::Cancelled,
+                Err(e) => match e.downcast_ref::<NeedsManualRewrite>() {
+                    Some(refusal) => RunStatus::NeedsRewrite(refusal.clone()),
+                    None => RunStatus::Failed(format!("{e:#}")),
+                },
+            });
+        });
+    }
+
+    /// Mark a run as started and return a setter for its status, or
+    /// `None` while another run is still going
+    fn begin_run(&self, message: &str) -> Option<impl Fn(RunStatus) + Clone + Send + 'static> {
+        let status = self.run.clone();
+        if status.lock().is_ok_and(|status| status.is_running()) {
+            return None;
+        }
+        let set_status = move |new: RunStatus| {
+            if let Ok(mut status) = status.lock() {
+                *status = new;
+            }
+        };
+        set_status(RunStatus::Running(GenerationProgress {
+            phase: GenerationPhase::Initializing,
+            progress: 0.0,
+            message: message.to_string(),
+            artifact: None,
+        }));
+        self.control.reset();
+        Some(set_status)
+    }
+
+    /// Roll an artifact back to a kept version. Design steps are restored
+    /// into the checkpoint (and the design document for the core design),
+    /// other artifacts over their file in the project.
+    pub fn restore_version(project_dir: &std::path::Path, name: &str, version: u32) -> Result<()> {
+        let history = ArtifactHistory::for_project(project_dir);
+        if !DESIGN_STEPS.contains(&name) {
+            history.restore(name, version)?;
+            return Ok(());
+        }
+
+        let kept = history
+            .versions(name)?
+            .into_iter()
+            .find(|v| v.version == version)
+            .with_context(|| format!("{name} has no version {version}"))?;
+        let text = std::fs::read_to_string(history.version_path(name, &kept))
+            .with_context(|| format!("Failed to read version {version} of {name}"))?;
+        let mut checkpoint = GenerationCheckpoint::load(project_dir)?
+            .context("Nothing has been generated for this project yet")?;
+        if let Some(current) = checkpoint.output(name) {
+            history.record_bytes(name, current.as_bytes(), "md", None, "Before rolling back")?;
+        }
+        if name == DESIGN_STEPS[0] {
+            std::fs::write(project_dir.join(DESIGN_FILE), &text)
+                .context("Failed to write design document")?;
+        }
+        checkpoint.record(name, text);
+        checkpoint.save(project_dir)
+    }
+
+    /// Whether an earlier run left completed steps to resume from
+    pub fn can_resume(project_dir: &std::path::Path) -> bool {
+        GenerationCheckpoint::load(project_dir).is_ok_and(|checkpoint| checkpoint.is_some())
+    }
+
+    pub fn run_status(&self) -> RunStatus {
+        self.run
+            .lock()
+            .map(|status| status.clone())
+            .unwrap_or(RunStatus::Idle)
+    }
+
+    /// Rate limits the provider last reported, by model
+    pub fn rate_limits(&self) -> Vec<(String, RateLimits)> {
+        QuotaTracker::shared().snapshot()
+    }
+
+    /// Whether the next task may be dispatched: the minimum delay has passed
+    /// and no model is out of requests until its limits reset
     pub fn can_make_request(&self) -> bool {
+        if !QuotaTracker::shared().wait().is_zero() {
+            return false;
+        }
         match self.rate_limiter.last_request {
             None => true,
             Some(last) => {
@@ -88,6 +400,30 @@ impl GenerationPipeline {
     }
 }
 
+/// Note the finished generation in list mode's project index
+fn update_project_index(project_dir: &std::path::Path) {
+    if let Err(e) = project_index::record_generation(project_dir) {
+        warn!("Failed to update the project index: {e:#}");
+    }
+}
+
+/// The wizard's generator, created on first use, set up for a run of the
+/// project
+async fn ready_generator<'a>(
+    generator: &'a mut Option<GameGenerator>,
+    ai_config: &AiConfig,
+    config: ProjectConfig,
+    control: GenerationControl,
+) -> Result<&'a mut GameGenerator> {
+    if generator.is_none() {
+        *generator = Some(GameGenerator::with_ai_config(ai_config).await?);
+    }
+    let generator = generator.as_mut().context("Generator is not initialized")?;
+    generator.set_project_config(config);
+    generator.set_control(control);
+    Ok(generator)
+}
+
 /// Process the generation queue
 pub fn process_generation_queue(
     mut pipeline: ResMut<GenerationPipeline>,
@@ -126,26 +462,54 @@ pub fn process_generation_queue(
                     LogLevel::Success,
                     format!("Validated prompt: {}/{}", prompt.phase, prompt.name),
                 );
+                pipeline.progress.publish(
+                    "",
+                    ProgressUpdate::new(
+                        &prompt.phase,
+                        app_state.get_progress(),
+                        format!("Validated prompt {}", prompt.name),
+                    )
+                    .with_step(&prompt.name)
+                    .with_artifact(GeneratedArtifact::file(
+                        "prompt",
+                        &prompt.name,
+                        &validated_path,
+                    )),
+                );
             }
+            app_state.mark_prompt_validated(&prompt.path, Vec::new());
         } else {
             app_state.add_log(
                 LogLevel::Warning,
                 format!(
-                    "Validation errors for {}: {:?}",
-                    prompt.name, validation_errors
+                    "Validation errors for {} (attempt {}/{MAX_TASK_ATTEMPTS}): {:?}",
+                    prompt.name,
+                    prompt.attempts + 1,
+                    validation_errors
                 ),
             );
+
+            if app_state.record_prompt_failure(&prompt.path, validation_errors, MAX_TASK_ATTEMPTS) {
+                app_state.add_log(
+                    LogLevel::Error,
+                    format!(
+                        "Quarantined {}/{} after {MAX_TASK_ATTEMPTS} failed attempts, continuing without it",
+                        prompt.phase, prompt.name
+                    ),
+                );
+            }
         }
 
-        app_state.mark_prompt_validated(&prompt.path, validation_errors);
+        pipeline.mark_request_made();
         return;
     }
 
-    // If all prompts are validated for current phase, advance
+    // If all remaining prompts are validated for current phase, advance.
+    // Quarantined tasks don't hold the phase back.
     let all_validated = app_state
         .prompt_validation_queue
         .iter()
-        .all(|p| p.validated);
+        .all(|p| p.validated || p.quarantined);
 
     if all_validated && !app_state.prompt_validation_queue.is_empty() {
         let current_phase = app_state.current_phase;
@@ -155,6 +519,14 @@ pub fn process_generation_queue(
         );
         app_state.advance_phase();
         app_state.prompt_validation_queue.clear();
+        pipeline.progress.publish(
+            "",
+            ProgressUpdate::new(
+                format!("{:?}", app_state.current_phase),
+                app_state.get_progress(),
+                format!("Phase {current_phase:?} complete"),
+            ),
+        );
 
         // Start next phase generation
         if app_state.current_phase != GenerationPhase::Packaging {
@@ -0,0 +1,206 @@
+//! Searchable index of the projects in the base directory
+//!
+//! Project directories are named by UUID, so list mode keeps a summary of
+//! every project in `projects-index.json` in the base directory: its name,
+//! genre, the games it blends and when it was created and last generated.
+//! The index is refreshed whenever list mode scans the projects and updated
+//! after each generation run, and [`ProjectFilter`] searches it.
+
+use crate::batch::PROJECT_FILE;
+use crate::wizard::config::ProjectConfig;
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// File in the base directory holding the index
+pub const PROJECT_INDEX_FILE: &str = "projects-index.json";
+
+/// Blend written by the guided wizard, read for its source games
+const BLEND_FILE: &str = "blend.toml";
+
+/// What the index knows about one project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectIndexEntry {
+    pub project_dir: PathBuf,
+    pub name: String,
+    #[serde(default)]
+    pub tagline: String,
+    #[serde(default)]
+    pub genre: String,
+    /// Games the project blends or takes after
+    #[serde(default)]
+    pub source_games: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub last_generated: Option<DateTime<Utc>>,
+}
+
+impl ProjectIndexEntry {
+    pub fn from_config(project_dir: &Path, config: &ProjectConfig) -> Self {
+        let mut source_games = config.visual_style.reference_games.clone();
+        for game in blend_source_games(project_dir) {
+            if !source_games.contains(&game) {
+                source_games.push(game);
+            }
+        }
+        Self {
+            project_dir: project_dir.to_path_buf(),
+            name: config.basic_info.name.clone(),
+            tagline: config.basic_info.tagline.clone(),
+            genre: config.basic_info.genre.clone(),
+            source_games,
+            created_at: config.metadata.created_at,
+            last_generated: None,
+        }
+    }
+}
+
+/// Summaries of the projects under a base directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectIndex {
+    pub projects: Vec<ProjectIndexEntry>,
+}
+
+impl ProjectIndex {
+    /// Load the index of `base_dir`, or an empty one if there is none yet
+    pub fn load(base_dir: &Path) -> Result<Self> {
+        let path = base_dir.join(PROJECT_INDEX_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, base_dir: &Path) -> Result<()> {
+        let path = base_dir.join(PROJECT_INDEX_FILE);
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn get(&self, project_dir: &Path) -> Option<&ProjectIndexEntry> {
+        self.projects
+            .iter()
+            .find(|entry| entry.project_dir == project_dir)
+    }
+
+    /// Add or replace a project's entry, keeping when it was last generated
+    pub fn upsert(&mut self, mut entry: ProjectIndexEntry) {
+        match self
+            .projects
+            .iter_mut()
+            .find(|existing| existing.project_dir == entry.project_dir)
+        {
+            Some(existing) => {
+                entry.last_generated = entry.last_generated.or(existing.last_generated);
+                *existing = entry;
+            }
+            None => self.projects.push(entry),
+        }
+    }
+
+    /// Bring the index in line with the projects found by a scan, dropping
+    /// entries of projects that no longer exist
+    pub fn sync<'a>(&mut self, projects: impl IntoIterator<Item = (&'a Path, &'a ProjectConfig)>) {
+        let mut seen = Vec::new();
+        for (project_dir, config) in projects {
+            self.upsert(ProjectIndexEntry::from_config(project_dir, config));
+            seen.push(project_dir.to_path_buf());
+        }
+        self.projects
+            .retain(|entry| seen.contains(&entry.project_dir));
+    }
+
+    /// Genres of the indexed projects, sorted and without duplicates
+    pub fn genres(&self) -> Vec<String> {
+        let mut genres: Vec<String> = self
+            .projects
+            .iter()
+            .map(|entry| entry.genre.clone())
+            .filter(|genre| !genre.is_empty())
+            .collect();
+        genres.sort();
+        genres.dedup();
+        genres
+    }
+}
+
+/// Note in the index of `project_dir`'s base directory that the project was
+/// just generated
+pub fn record_generation(project_dir: &Path) -> Result<()> {
+    let base_dir = project_dir
+        .parent()
+        .context("Project directory has no parent")?;
+    let config = ProjectConfig::load(&project_dir.join(PROJECT_FILE))?;
+    let mut index = ProjectIndex::load(base_dir)?;
+    let mut entry = ProjectIndexEntry::from_config(project_dir, &config);
+    entry.last_generated = Some(Utc::now());
+    index.upsert(entry);
+    index.save(base_dir)
+}
+
+/// Search over the index; empty criteria match every project
+#[derive(Debug, Clone, Default)]
+pub struct ProjectFilter {
+    /// Words that must all appear in the name, tagline, genre or source games
+    pub query: String,
+    pub genre: Option<String>,
+    pub created_from: Option<NaiveDate>,
+    pub created_to: Option<NaiveDate>,
+}
+
+impl ProjectFilter {
+    pub fn is_empty(&self) -> bool {
+        self.query.trim().is_empty()
+            && self.genre.is_none()
+            && self.created_from.is_none()
+            && self.created_to.is_none()
+    }
+
+    pub fn matches(&self, entry: &ProjectIndexEntry) -> bool {
+        if self
+            .genre
+            .as_ref()
+            .is_some_and(|genre| !entry.genre.eq_ignore_ascii_case(genre))
+        {
+            return false;
+        }
+        let created = entry.created_at.date_naive();
+        if self.created_from.is_some_and(|from| created < from)
+            || self.created_to.is_some_and(|to| created > to)
+        {
+            return false;
+        }
+
+        let haystack = [&entry.name, &entry.tagline, &entry.genre]
+            .into_iter()
+            .chain(&entry.source_games)
+            .map(|field| field.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.query
+            .split_whitespace()
+            .all(|word| haystack.contains(&word.to_lowercase()))
+    }
+}
+
+/// Names of the source games in the project's `blend.toml`, if any
+fn blend_source_games(project_dir: &Path) -> Vec<String> {
+    let Ok(contents) = std::fs::read_to_string(project_dir.join(BLEND_FILE)) else {
+        return Vec::new();
+    };
+    let Ok(blend) = toml::from_str::<toml::Table>(&contents) else {
+        return Vec::new();
+    };
+    blend
+        .get("source_games")
+        .and_then(|games| games.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|game| game.get("name")?.as_str())
+        .map(str::to_string)
+        .collect()
+}
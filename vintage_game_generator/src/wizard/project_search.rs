@@ -0,0 +1,513 @@
+//! Search across everything generated for the project
+//!
+//! Finds a query in the text of generated documents and data, in prompt
+//! templates and in artifact and file names, case-insensitively. With
+//! semantic search on, the query is also embedded and compared against the
+//! project's artifact index, so "the key that opens the silver door" finds
+//! the artifact described as "ornate silver key" too. Selecting a hit opens
+//! its detail view: the file with the matching line highlighted, or the
+//! image. Toggle the panel with F9.
+
+use crate::wizard::AppDirectories;
+use crate::wizard::i18n::Localizer;
+use crate::wizard::pipeline::GenerationPipeline;
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use std::path::{Path, PathBuf};
+use tokio::sync::oneshot;
+use vintage_ai_client::artifacts::{ARTIFACT_INDEX_FILE, ArtifactIndex};
+use vintage_ai_client::thumbnails::load_image;
+
+/// Files whose text is searched
+const TEXT_EXTENSIONS: &[&str] = &[
+    "md", "txt", "toml", "json", "ron", "yarn", "jinja", "j2", "lss", "vdf",
+];
+
+/// Files that are shown as images in the detail view
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp"];
+
+/// Directories never searched
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules"];
+
+/// Larger files are not searched for text
+const MAX_TEXT_FILE_BYTES: u64 = 2 * 1024 * 1024;
+
+/// Most hits shown per search
+const MAX_HITS: usize = 200;
+
+/// Most artifacts returned by semantic search
+const MAX_SEMANTIC_HITS: usize = 10;
+
+/// Characters of context around a match in the hit list
+const SNIPPET_CONTEXT: usize = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitKind {
+    /// Text inside a generated file
+    Content,
+    /// Text inside a prompt template
+    Prompt,
+    /// Artifact or file name
+    Name,
+    /// Artifact whose description is close in meaning to the query
+    Semantic,
+}
+
+impl HitKind {
+    /// Message id of the kind's label
+    fn label(self) -> &'static str {
+        match self {
+            HitKind::Content => "search-kind-text",
+            HitKind::Prompt => "search-kind-prompt",
+            HitKind::Name => "search-kind-name",
+            HitKind::Semantic => "search-kind-similar",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub kind: HitKind,
+    /// Artifact name or path relative to the project
+    pub title: String,
+    pub path: Option<PathBuf>,
+    /// Line of the match, counting from 1
+    pub line: Option<usize>,
+    pub snippet: String,
+    /// Similarity for semantic hits (0.0 - 1.0)
+    pub score: Option<f32>,
+}
+
+/// Contents of the selected hit
+enum Detail {
+    Text {
+        title: String,
+        lines: Vec<String>,
+        line: Option<usize>,
+    },
+    Image {
+        title: String,
+        texture: egui::TextureHandle,
+    },
+    Artifact {
+        title: String,
+        description: String,
+    },
+}
+
+/// State of the search panel
+#[derive(Resource, Default)]
+pub struct ProjectSearchState {
+    pub open: bool,
+    pub query: String,
+    pub semantic: bool,
+    pub hits: Vec<SearchHit>,
+    pub selected: Option<usize>,
+    pub error: Option<String>,
+    pending: Option<oneshot::Receiver<anyhow::Result<Vec<SearchHit>>>>,
+    detail: Option<Detail>,
+    focus_query: bool,
+}
+
+/// Hits for the query in the project's files, prompts and artifact names
+pub fn search_project(
+    project_dir: &Path,
+    prompts_dir: &Path,
+    index: &ArtifactIndex,
+    query: &str,
+) -> Vec<SearchHit> {
+    let needle = query.trim().to_lowercase();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    let mut hits = Vec::new();
+    for artifact in &index.artifacts {
+        if artifact.name.to_lowercase().contains(&needle)
+            || artifact.description.to_lowercase().contains(&needle)
+        {
+            hits.push(SearchHit {
+                kind: HitKind::Name,
+                title: artifact.name.clone(),
+                path: artifact.path.clone(),
+                line: None,
+                snippet: snippet(&artifact.description, &needle),
+                score: None,
+            });
+        }
+    }
+
+    let mut files = Vec::new();
+    collect_files(project_dir, &mut files);
+    if !prompts_dir.starts_with(project_dir) {
+        collect_files(prompts_dir, &mut files);
+    }
+    files.sort();
+    files.dedup();
+
+    for path in files {
+        let in_prompts = path.starts_with(prompts_dir);
+        let title = relative(&path, project_dir);
+        if path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().to_lowercase().contains(&needle))
+        {
+            hits.push(SearchHit {
+                kind: HitKind::Name,
+                title: title.clone(),
+                path: Some(path.clone()),
+                line: None,
+                snippet: String::new(),
+                score: None,
+            });
+        }
+        if !has_extension(&path, TEXT_EXTENSIONS)
+            || path
+                .file_name()
+                .is_some_and(|name| name == ARTIFACT_INDEX_FILE)
+            || !std::fs::metadata(&path).is_ok_and(|m| m.len() <= MAX_TEXT_FILE_BYTES)
+        {
+            continue;
+        }
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        for (number, line) in contents.lines().enumerate() {
+            if line.to_lowercase().contains(&needle) {
+                hits.push(SearchHit {
+                    kind: if in_prompts {
+                        HitKind::Prompt
+                    } else {
+                        HitKind::Content
+                    },
+                    title: title.clone(),
+                    path: Some(path.clone()),
+                    line: Some(number + 1),
+                    snippet: snippet(line, &needle),
+                    score: None,
+                });
+            }
+        }
+        if hits.len() >= MAX_HITS {
+            break;
+        }
+    }
+    hits.truncate(MAX_HITS);
+    hits
+}
+
+/// Artifacts of the index ranked by similarity to the query embedding
+pub fn semantic_hits(index: &ArtifactIndex, embedding: &[f32]) -> Vec<SearchHit> {
+    index
+        .rank(embedding, MAX_SEMANTIC_HITS)
+        .into_iter()
+        .map(|(artifact, similarity)| SearchHit {
+            kind: HitKind::Semantic,
+            title: artifact.name.clone(),
+            path: artifact.path.clone(),
+            line: None,
+            snippet: artifact
+                .description
+                .chars()
+                .take(SNIPPET_CONTEXT * 2)
+                .collect(),
+            score: Some(similarity),
+        })
+        .collect()
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if path.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+                collect_files(&path, files);
+            }
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| extensions.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+fn relative(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// The line around the first match, trimmed to a few words either side
+fn snippet(text: &str, needle: &str) -> String {
+    let lower = text.to_lowercase();
+    // Lowercasing can change byte lengths, so fall back to the start
+    let at = lower
+        .find(needle)
+        .filter(|_| lower.len() == text.len())
+        .unwrap_or(0);
+    let mut start = at.saturating_sub(SNIPPET_CONTEXT);
+    while !text.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (at + needle.len() + SNIPPET_CONTEXT).min(text.len());
+    while !text.is_char_boundary(end) {
+        end += 1;
+    }
+    let mut snippet = text[start..end].trim().to_string();
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if end < text.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+/// Toggle the search panel with F9
+pub fn toggle_project_search(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<ProjectSearchState>,
+) {
+    if keys.just_pressed(KeyCode::F9) {
+        state.open = !state.open;
+        state.focus_query = state.open;
+    }
+}
+
+/// Draw the search window
+pub fn draw_project_search(
+    mut contexts: EguiContexts,
+    mut state: ResMut<ProjectSearchState>,
+    directories: Res<AppDirectories>,
+    pipeline: Res<GenerationPipeline>,
+    localizer: Res<Localizer>,
+) {
+    if !state.open {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+    let state = &mut *state;
+
+    if let Some(pending) = state.pending.as_mut() {
+        match pending.try_recv() {
+            Ok(Ok(mut semantic)) => {
+                state.pending = None;
+                // Similar artifacts go first; keep any selection on the same hit
+                state.selected = state.selected.map(|index| index + semantic.len());
+                semantic.append(&mut state.hits);
+                state.hits = semantic;
+            }
+            Ok(Err(e)) => {
+                state.pending = None;
+                state.error = Some(localizer.t_args(
+                    "search-semantic-failed",
+                    &[("error", format!("{e:#}").into())],
+                ));
+            }
+            Err(oneshot::error::TryRecvError::Empty) => {}
+            Err(oneshot::error::TryRecvError::Closed) => state.pending = None,
+        }
+    }
+
+    let mut open = state.open;
+    let mut search = false;
+    let mut select = None;
+
+    egui::Window::new(localizer.t("search-title"))
+        .id(egui::Id::new("project_search"))
+        .open(&mut open)
+        .default_size([760.0, 480.0])
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut state.query)
+                        .hint_text(localizer.t("search-hint"))
+                        .desired_width(360.0),
+                );
+                if state.focus_query {
+                    response.request_focus();
+                    state.focus_query = false;
+                }
+                search |= response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                search |= ui.button(localizer.t("search-button")).clicked();
+                ui.checkbox(&mut state.semantic, localizer.t("search-semantic"))
+                    .on_hover_text(localizer.t("search-semantic-hint"));
+                if state.pending.is_some() {
+                    ui.spinner();
+                }
+            });
+
+            if let Some(error) = &state.error {
+                ui.colored_label(egui::Color32::from_rgb(255, 100, 100), error);
+            }
+            ui.separator();
+
+            ui.columns(2, |columns| {
+                egui::ScrollArea::vertical()
+                    .id_salt("search_hits")
+                    .show(&mut columns[0], |ui| {
+                        if state.hits.is_empty() && !state.query.trim().is_empty() {
+                            ui.label(egui::RichText::new(localizer.t("search-no-matches")).weak());
+                        }
+                        for (index, hit) in state.hits.iter().enumerate() {
+                            let mut heading =
+                                format!("[{}] {}", localizer.t(hit.kind.label()), hit.title);
+                            if let Some(line) = hit.line {
+                                heading.push_str(&format!(":{line}"));
+                            }
+                            if let Some(score) = hit.score {
+                                heading.push_str(&format!(" ({:.0}%)", score * 100.0));
+                            }
+                            let selected = state.selected == Some(index);
+                            if ui.selectable_label(selected, heading).clicked() {
+                                select = Some(index);
+                            }
+                            if !hit.snippet.is_empty() {
+                                ui.label(egui::RichText::new(&hit.snippet).small().weak());
+                            }
+                        }
+                    });
+                draw_detail(&mut columns[1], state.detail.as_ref(), &localizer);
+            });
+        });
+
+    state.open = open;
+
+    if search {
+        run_search(state, &directories, &pipeline, &localizer);
+    }
+    if let Some(index) = select {
+        state.selected = Some(index);
+        state.detail = state
+            .hits
+            .get(index)
+            .cloned()
+            .map(|hit| load_detail(ctx, &hit, &directories.project_dir));
+    }
+}
+
+fn run_search(
+    state: &mut ProjectSearchState,
+    directories: &AppDirectories,
+    pipeline: &GenerationPipeline,
+    localizer: &Localizer,
+) {
+    state.error = None;
+    state.selected = None;
+    state.detail = None;
+
+    let index = match ArtifactIndex::load(directories.project_dir.join(ARTIFACT_INDEX_FILE)) {
+        Ok(index) => index,
+        Err(e) => {
+            state.error = Some(format!("{e:#}"));
+            ArtifactIndex::new()
+        }
+    };
+    state.hits = search_project(
+        &directories.project_dir,
+        &directories.prompts_dir,
+        &index,
+        &state.query,
+    );
+
+    if !state.semantic || index.artifacts.is_empty() || state.query.trim().is_empty() {
+        state.pending = None;
+        return;
+    }
+    let (sender, receiver) = oneshot::channel();
+    state.pending = Some(receiver);
+    let generator = pipeline.generator.clone();
+    let query = state.query.trim().to_string();
+    let not_initialized = localizer.t("freeform-no-generator");
+    pipeline.runtime.spawn(async move {
+        let result = async {
+            let generator = generator.lock().await;
+            let Some(generator) = generator.as_ref() else {
+                anyhow::bail!(not_initialized);
+            };
+            let embedding = generator.embed(&query).await?;
+            Ok(semantic_hits(&index, &embedding))
+        }
+        .await;
+        let _ = sender.send(result);
+    });
+}
+
+fn load_detail(ctx: &egui::Context, hit: &SearchHit, project_dir: &Path) -> Detail {
+    let Some(path) = &hit.path else {
+        return Detail::Artifact {
+            title: hit.title.clone(),
+            description: hit.snippet.clone(),
+        };
+    };
+    let title = relative(path, project_dir);
+    if has_extension(path, IMAGE_EXTENSIONS)
+        && let Ok(image) = load_image(path)
+    {
+        let image = image.to_rgba8();
+        let size = [image.width() as usize, image.height() as usize];
+        let color_image = egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+        // Nearest filtering so individual pixels stay visible when scaled up
+        let texture = ctx.load_texture("search_detail", color_image, egui::TextureOptions::NEAREST);
+        return Detail::Image { title, texture };
+    }
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Detail::Text {
+            title,
+            lines: contents.lines().map(str::to_string).collect(),
+            line: hit.line,
+        },
+        Err(_) => Detail::Artifact {
+            title,
+            description: hit.snippet.clone(),
+        },
+    }
+}
+
+fn draw_detail(ui: &mut egui::Ui, detail: Option<&Detail>, localizer: &Localizer) {
+    let Some(detail) = detail else {
+        ui.label(egui::RichText::new(localizer.t("search-select-match")).weak());
+        return;
+    };
+    match detail {
+        Detail::Text { title, lines, line } => {
+            ui.strong(title);
+            egui::ScrollArea::vertical()
+                .id_salt("search_detail")
+                .show(ui, |ui| {
+                    for (index, text) in lines.iter().enumerate() {
+                        let number = index + 1;
+                        let text = egui::RichText::new(format!("{number:>5}  {text}")).monospace();
+                        if Some(number) == *line {
+                            let response = ui
+                                .label(text.background_color(egui::Color32::from_rgb(90, 80, 20)));
+                            response.scroll_to_me(Some(egui::Align::Center));
+                        } else {
+                            ui.label(text);
+                        }
+                    }
+                });
+        }
+        Detail::Image { title, texture } => {
+            ui.strong(title);
+            let size = texture.size_vec2();
+            let scale = (256.0 / size.x.max(size.y)).max(1.0).floor();
+            ui.image((texture.id(), size * scale));
+        }
+        Detail::Artifact { title, description } => {
+            ui.strong(title);
+            ui.label(description);
+        }
+    }
+}
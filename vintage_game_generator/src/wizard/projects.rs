@@ -0,0 +1,179 @@
+//! Duplicating, archiving, restoring and deleting projects from list mode
+//!
+//! Projects are directories named by UUID under the base directory. A
+//! duplicate gets a new UUID and can leave the generated files behind so it
+//! starts from the design alone. Archives are zip files holding the project
+//! directory; restoring one keeps its UUID unless a project with that UUID
+//! already exists.
+
+use crate::batch::{DESIGN_FILE, PROJECT_FILE};
+use crate::metaprompts::checkpoint::CHECKPOINT_FILE;
+use crate::wizard::config::{ConfigManager, ProjectMetadata};
+use anyhow::{Context, Result, bail};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use vintage_ai_client::artifacts::ARTIFACT_INDEX_FILE;
+use vintage_ai_client::thumbnails::THUMBNAILS_DIR;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Files and directories produced by generation rather than the wizard
+const GENERATED: &[&str] = &[
+    "assets",
+    "build",
+    "export",
+    THUMBNAILS_DIR,
+    ARTIFACT_INDEX_FILE,
+    CHECKPOINT_FILE,
+    DESIGN_FILE,
+];
+
+/// Copy a project into a new UUID directory beside it and return the path of
+/// the copy's `project.toml`. With `clear_generated` only the design inputs
+/// are copied, not generated assets, checkpoints or exports.
+pub fn duplicate_project(project_dir: &Path, clear_generated: bool) -> Result<PathBuf> {
+    let base_dir = project_dir
+        .parent()
+        .context("Project directory has no parent")?;
+    let copy_dir = base_dir.join(Uuid::new_v4().to_string());
+
+    for file in project_files(project_dir)? {
+        let relative = file.strip_prefix(project_dir)?;
+        if clear_generated && is_generated(relative) {
+            continue;
+        }
+        let target = copy_dir.join(relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(&file, &target)
+            .with_context(|| format!("Failed to copy {}", file.display()))?;
+    }
+
+    let mut manager = ConfigManager::new(&copy_dir, Some("project"))?;
+    if !manager.config_loaded() {
+        bail!("{} has no {PROJECT_FILE}", project_dir.display());
+    }
+    manager.config.metadata = ProjectMetadata::default();
+    manager.config.basic_info.name = format!("{} (copy)", manager.config.basic_info.name);
+    manager.save()?;
+    Ok(copy_dir.join(PROJECT_FILE))
+}
+
+/// Zip the project directory to `archive_path`
+pub fn archive_project(project_dir: &Path, archive_path: &Path) -> Result<()> {
+    let root = project_dir
+        .file_name()
+        .context("Project directory has no name")?
+        .to_string_lossy()
+        .into_owned();
+    if let Some(parent) = archive_path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let archive = File::create(archive_path)
+        .with_context(|| format!("Failed to create {}", archive_path.display()))?;
+    let mut writer = ZipWriter::new(BufWriter::new(archive));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for file in project_files(project_dir)? {
+        let relative = file.strip_prefix(project_dir)?;
+        // Zip entry names always use forward slashes
+        let name = Path::new(&root)
+            .join(relative)
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        writer.start_file(name, options)?;
+        let mut source =
+            File::open(&file).with_context(|| format!("Failed to read {}", file.display()))?;
+        std::io::copy(&mut source, &mut writer)?;
+    }
+    writer
+        .finish()
+        .with_context(|| format!("Failed to write {}", archive_path.display()))?;
+    Ok(())
+}
+
+/// Unpack an archive made by [`archive_project`] into `base_dir` and return
+/// the path of the restored `project.toml`
+pub fn restore_archive(archive_path: &Path, base_dir: &Path) -> Result<PathBuf> {
+    let file = File::open(archive_path)
+        .with_context(|| format!("Failed to open {}", archive_path.display()))?;
+    let mut archive = ZipArchive::new(BufReader::new(file))
+        .with_context(|| format!("{} is not a zip archive", archive_path.display()))?;
+
+    let root = (0..archive.len())
+        .filter_map(|i| archive.by_index(i).ok()?.enclosed_name())
+        .find(|name| name.ends_with(PROJECT_FILE) && name.components().count() == 2)
+        .and_then(|name| Some(name.components().next()?.as_os_str().to_os_string()))
+        .with_context(|| format!("{} does not contain a project", archive_path.display()))?;
+
+    let mut project_dir = base_dir.join(&root);
+    if Uuid::parse_str(&root.to_string_lossy()).is_err() || project_dir.exists() {
+        project_dir = base_dir.join(Uuid::new_v4().to_string());
+    }
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let Some(name) = entry.enclosed_name() else {
+            tracing::warn!("Skipping unsafe archive entry {}", entry.name());
+            continue;
+        };
+        let Ok(relative) = name.strip_prefix(&root) else {
+            continue;
+        };
+        let target = project_dir.join(relative);
+        if entry.is_dir() {
+            std::fs::create_dir_all(&target)?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut output = File::create(&target)
+            .with_context(|| format!("Failed to write {}", target.display()))?;
+        std::io::copy(&mut entry, &mut output)?;
+    }
+    Ok(project_dir.join(PROJECT_FILE))
+}
+
+/// Remove a project directory. Refuses directories that don't hold a
+/// `project.toml`, so a wrong path can't wipe anything else.
+pub fn delete_project(project_dir: &Path) -> Result<()> {
+    if !project_dir.join(PROJECT_FILE).is_file() {
+        bail!("{} is not a project directory", project_dir.display());
+    }
+    std::fs::remove_dir_all(project_dir)
+        .with_context(|| format!("Failed to delete {}", project_dir.display()))
+}
+
+fn is_generated(relative: &Path) -> bool {
+    relative
+        .components()
+        .next()
+        .is_some_and(|first| GENERATED.iter().any(|name| first.as_os_str() == *name))
+}
+
+/// Every file below `dir`, sorted
+pub(crate) fn project_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in
+            std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
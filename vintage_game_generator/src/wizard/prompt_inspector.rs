@@ -0,0 +1,380 @@
+//! Developer panel for inspecting rendered metaprompt templates
+//!
+//! Renders a selected jinja template with the current project context and
+//! shows how many tokens each top-level context section contributes. A
+//! section's share is measured by rendering the template again without that
+//! section; the lines that disappear are highlighted in the final prompt.
+//! Toggle the panel with F12.
+
+use crate::metaprompts::METAPROMPT_DIRS;
+use crate::wizard::AppDirectories;
+use crate::wizard::i18n::Localizer;
+use crate::wizard::state::AppState;
+use anyhow::Result;
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use minijinja::{Environment, UndefinedBehavior};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use vintage_ai_client::tokens::TokenCounter;
+
+/// Models offered for token counting
+const MODELS: &[&str] = &["gpt-4-turbo", "gpt-4o", "gpt-4o-mini", "gpt-3.5-turbo"];
+
+/// Token share of one context section
+#[derive(Debug, Clone)]
+pub struct SectionShare {
+    pub name: String,
+    pub tokens: usize,
+    /// Lines of the final prompt that only appear because of this section
+    pub lines: Vec<usize>,
+}
+
+/// Result of rendering a template against a context
+#[derive(Debug, Clone, Default)]
+pub struct PromptAnalysis {
+    pub prompt: String,
+    pub total_tokens: usize,
+    pub sections: Vec<SectionShare>,
+}
+
+impl PromptAnalysis {
+    /// Tokens not attributed to any context section
+    pub fn template_tokens(&self) -> usize {
+        let attributed: usize = self.sections.iter().map(|s| s.tokens).sum();
+        self.total_tokens.saturating_sub(attributed)
+    }
+}
+
+/// State of the prompt inspector panel
+#[derive(Resource)]
+pub struct PromptInspectorState {
+    pub open: bool,
+    pub templates: Vec<(String, PathBuf)>,
+    pub selected: Option<usize>,
+    pub model: String,
+    pub analysis: Option<PromptAnalysis>,
+    pub error: Option<String>,
+}
+
+impl Default for PromptInspectorState {
+    fn default() -> Self {
+        Self {
+            open: false,
+            templates: Vec::new(),
+            selected: None,
+            model: MODELS[0].to_string(),
+            analysis: None,
+            error: None,
+        }
+    }
+}
+
+impl PromptInspectorState {
+    /// Collect templates from the project's prompt directory and the bundled metaprompts
+    fn refresh_templates(&mut self, directories: &AppDirectories) {
+        let mut dirs = vec![directories.prompts_dir.clone()];
+        dirs.extend(METAPROMPT_DIRS.iter().map(PathBuf::from));
+
+        let mut templates = Vec::new();
+        for dir in dirs {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().is_some_and(|ext| ext == "jinja")
+                    && let Some(name) = path.file_stem().and_then(|s| s.to_str())
+                    && !templates.iter().any(|(n, _)| n == name)
+                {
+                    templates.push((name.to_string(), path));
+                }
+            }
+        }
+        templates.sort();
+
+        self.templates = templates;
+        self.selected = self.selected.filter(|&i| i < self.templates.len());
+    }
+}
+
+/// Render `source` with `context` and attribute tokens to each section of `context[root]`
+pub fn analyze_template(
+    source: &str,
+    context: &serde_json::Value,
+    root: &str,
+    model: &str,
+    counter: &TokenCounter,
+) -> Result<PromptAnalysis> {
+    let mut env = Environment::new();
+    // Sections are removed one at a time, so missing attributes must render empty
+    env.set_undefined_behavior(UndefinedBehavior::Chainable);
+    env.add_template("inspected", source)?;
+    let template = env.get_template("inspected")?;
+
+    let prompt = template.render(context)?;
+    let total_tokens = counter.count_tokens(&prompt, model)?;
+
+    let mut sections = Vec::new();
+    if let Some(root_value) = context.get(root).and_then(|v| v.as_object()) {
+        for key in root_value.keys() {
+            let mut ablated = context.clone();
+            if let Some(obj) = ablated.get_mut(root).and_then(|v| v.as_object_mut()) {
+                obj.remove(key);
+            }
+            let without = template.render(&ablated)?;
+            let tokens = total_tokens.saturating_sub(counter.count_tokens(&without, model)?);
+
+            sections.push(SectionShare {
+                name: key.clone(),
+                tokens,
+                lines: removed_lines(&prompt, &without),
+            });
+        }
+    }
+    sections.sort_by_key(|section| std::cmp::Reverse(section.tokens));
+
+    Ok(PromptAnalysis {
+        prompt,
+        total_tokens,
+        sections,
+    })
+}
+
+/// Indices of lines in `full` that have no counterpart in `reduced`
+fn removed_lines(full: &str, reduced: &str) -> Vec<usize> {
+    let mut available: HashMap<&str, usize> = HashMap::new();
+    for line in reduced.lines() {
+        *available.entry(line).or_default() += 1;
+    }
+
+    full.lines()
+        .enumerate()
+        .filter_map(|(idx, line)| match available.get_mut(line) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                None
+            }
+            _ if line.trim().is_empty() => None,
+            _ => Some(idx),
+        })
+        .collect()
+}
+
+/// Highlight color for the n-th section
+fn section_color(index: usize) -> egui::Color32 {
+    const PALETTE: [(u8, u8, u8); 6] = [
+        (200, 80, 80),
+        (80, 160, 220),
+        (120, 200, 100),
+        (220, 180, 60),
+        (180, 100, 220),
+        (80, 200, 180),
+    ];
+    let (r, g, b) = PALETTE[index % PALETTE.len()];
+    egui::Color32::from_rgb(r, g, b)
+}
+
+/// Toggle the inspector with F12
+pub fn toggle_prompt_inspector(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<PromptInspectorState>,
+    directories: Res<AppDirectories>,
+) {
+    if keys.just_pressed(KeyCode::F12) {
+        state.open = !state.open;
+        if state.open {
+            state.refresh_templates(&directories);
+        }
+    }
+}
+
+/// Draw the inspector window
+pub fn draw_prompt_inspector(
+    mut contexts: EguiContexts,
+    mut state: ResMut<PromptInspectorState>,
+    app_state: Res<AppState>,
+    directories: Res<AppDirectories>,
+    localizer: Res<Localizer>,
+) {
+    if !state.open {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let mut open = state.open;
+    let mut analyze = false;
+
+    egui::Window::new(localizer.t("inspector-title"))
+        .id(egui::Id::new("prompt_inspector"))
+        .open(&mut open)
+        .default_size([720.0, 560.0])
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let selected_name = state
+                    .selected
+                    .and_then(|i| state.templates.get(i))
+                    .map(|(name, _)| name.clone())
+                    .unwrap_or_else(|| localizer.t("inspector-select-template"));
+
+                let mut selected = state.selected;
+                egui::ComboBox::from_id_salt("inspector_template")
+                    .selected_text(selected_name)
+                    .show_ui(ui, |ui| {
+                        for (idx, (name, _)) in state.templates.iter().enumerate() {
+                            if ui
+                                .selectable_value(&mut selected, Some(idx), name)
+                                .changed()
+                            {
+                                analyze = true;
+                            }
+                        }
+                    });
+                state.selected = selected;
+
+                let mut model = state.model.clone();
+                egui::ComboBox::from_id_salt("inspector_model")
+                    .selected_text(&model)
+                    .show_ui(ui, |ui| {
+                        for candidate in MODELS {
+                            if ui
+                                .selectable_value(&mut model, candidate.to_string(), *candidate)
+                                .changed()
+                            {
+                                analyze = true;
+                            }
+                        }
+                    });
+                state.model = model;
+
+                if ui.button(localizer.t("window-refresh")).clicked() {
+                    state.refresh_templates(&directories);
+                    analyze = true;
+                }
+            });
+
+            if app_state.config_manager.is_none() {
+                ui.label(
+                    egui::RichText::new(localizer.t("inspector-no-project"))
+                        .small()
+                        .weak(),
+                );
+            }
+
+            if let Some(error) = &state.error {
+                ui.colored_label(egui::Color32::from_rgb(255, 100, 100), error);
+            }
+
+            if let Some(analysis) = &state.analysis {
+                ui.separator();
+                render_token_shares(ui, analysis, &localizer);
+                ui.separator();
+                render_prompt(ui, analysis);
+            }
+        });
+
+    state.open = open;
+
+    if analyze {
+        run_analysis(&mut state, &app_state, &localizer);
+    }
+}
+
+fn run_analysis(state: &mut PromptInspectorState, app_state: &AppState, localizer: &Localizer) {
+    let Some((_, path)) = state.selected.and_then(|i| state.templates.get(i)) else {
+        return;
+    };
+
+    let project = app_state
+        .config_manager
+        .as_ref()
+        .and_then(|manager| serde_json::to_value(&manager.config).ok())
+        .unwrap_or_else(|| serde_json::json!({}));
+    let context = serde_json::json!({ "project": project });
+
+    let counter = TokenCounter::new();
+    let result = std::fs::read_to_string(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|source| analyze_template(&source, &context, "project", &state.model, &counter));
+
+    match result {
+        Ok(analysis) => {
+            state.analysis = Some(analysis);
+            state.error = None;
+        }
+        Err(e) => {
+            state.analysis = None;
+            state.error = Some(localizer.t_args(
+                "inspector-render-failed",
+                &[("error", e.to_string().into())],
+            ));
+        }
+    }
+}
+
+/// Per-section token breakdown as proportional bars
+fn render_token_shares(ui: &mut egui::Ui, analysis: &PromptAnalysis, localizer: &Localizer) {
+    ui.label(localizer.t_args(
+        "inspector-total",
+        &[("count", analysis.total_tokens.into())],
+    ));
+    let total = analysis.total_tokens.max(1) as f32;
+
+    egui::Grid::new("inspector_shares")
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            for (idx, section) in analysis.sections.iter().enumerate() {
+                ui.colored_label(section_color(idx), &section.name);
+                ui.add(
+                    egui::ProgressBar::new(section.tokens as f32 / total)
+                        .fill(section_color(idx))
+                        .desired_width(240.0),
+                );
+                ui.label(localizer.t_args("tokens", &[("count", section.tokens.into())]));
+                ui.end_row();
+            }
+
+            ui.label(localizer.t("inspector-template-text"));
+            ui.add(
+                egui::ProgressBar::new(analysis.template_tokens() as f32 / total)
+                    .desired_width(240.0),
+            );
+            ui.label(localizer.t_args("tokens", &[("count", analysis.template_tokens().into())]));
+            ui.end_row();
+        });
+}
+
+/// Final prompt with lines tinted by the section that produced them
+fn render_prompt(ui: &mut egui::Ui, analysis: &PromptAnalysis) {
+    let mut owners: HashMap<usize, usize> = HashMap::new();
+    for (idx, section) in analysis.sections.iter().enumerate() {
+        for line in &section.lines {
+            owners.entry(*line).or_insert(idx);
+        }
+    }
+
+    let font = egui::FontId::monospace(12.0);
+    let mut job = egui::text::LayoutJob::default();
+    for (idx, line) in analysis.prompt.lines().enumerate() {
+        let mut format = egui::TextFormat::simple(font.clone(), ui.visuals().text_color());
+        if let Some(owner) = owners.get(&idx) {
+            format.background = section_color(*owner).gamma_multiply(0.35);
+        }
+        job.append(line, 0.0, format);
+        job.append(
+            "\n",
+            0.0,
+            egui::TextFormat::simple(font.clone(), ui.visuals().text_color()),
+        );
+    }
+
+    egui::ScrollArea::vertical()
+        .id_salt("inspector_prompt")
+        .max_height(320.0)
+        .show(ui, |ui| {
+            ui.label(job);
+        });
+}
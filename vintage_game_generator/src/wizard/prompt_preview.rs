@@ -0,0 +1,324 @@
+//! Preview of the built-in image and audio prompts as they will be sent
+//!
+//! Lists the prompts compiled into the AI client and marks the ones the
+//! project overrides in its `prompts/` directory. The selected prompt is
+//! rendered by the same generator code that sends it, with the project's
+//! overrides and settings, without requesting anything from the API. An
+//! embedded prompt can be copied into the project to start an override.
+//! Toggle the panel with F5.
+
+use crate::vintage_games::visuals;
+use crate::wizard::AppDirectories;
+use crate::wizard::config::ProjectConfig;
+use crate::wizard::i18n::Localizer;
+use crate::wizard::pipeline::GenerationPipeline;
+use crate::wizard::state::AppState;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use std::path::{Path, PathBuf};
+use vintage_ai_client::AiService;
+use vintage_ai_client::audio::AudioConfig;
+use vintage_ai_client::image::GameConcept;
+use vintage_ai_client::prompts::{EMBEDDED_PROMPTS, PromptLibrary, embedded_prompt};
+use vintage_ai_client::tokens::TokenCounter;
+
+/// Model the preview's tokens are counted for
+const TOKEN_MODEL: &str = "gpt-4o";
+
+/// Sound effects are previewed at this length, in seconds
+const SOUND_EFFECT_DURATION: f32 = 0.5;
+
+/// A prompt rendered for the preview
+#[derive(Debug, Clone)]
+pub struct PromptPreview {
+    pub name: &'static str,
+    pub prompt: String,
+    pub tokens: usize,
+}
+
+/// State of the prompt preview panel
+#[derive(Resource)]
+pub struct PromptPreviewState {
+    pub open: bool,
+    /// Index into [`EMBEDDED_PROMPTS`]
+    pub selected: usize,
+    /// What the prompt is for, where it takes a subject
+    pub subject: String,
+    pub preview: Option<PromptPreview>,
+    pub error: Option<String>,
+}
+
+impl Default for PromptPreviewState {
+    fn default() -> Self {
+        Self {
+            open: false,
+            selected: 0,
+            subject: "hero".to_string(),
+            preview: None,
+            error: None,
+        }
+    }
+}
+
+/// Message id of the label of the subject a prompt takes, for prompts that
+/// take one
+pub fn subject_label(name: &str) -> Option<&'static str> {
+    match name {
+        "image/sprite" => Some("preview-subject-sprite"),
+        "image/style_consistency" => Some("preview-subject-asset"),
+        "audio/sound_effect" => Some("preview-subject-effect"),
+        _ => None,
+    }
+}
+
+/// Render the embedded prompt `name` with `service`'s templates through the
+/// generator that sends it
+pub async fn render_prompt(
+    service: &AiService,
+    name: &str,
+    subject: &str,
+    config: &ProjectConfig,
+) -> Result<String> {
+    match name {
+        "image/style_guide" => service.image().style_guide_prompt(&concept(config)).await,
+        "image/sprite" => service.image().sprite_prompt("character", subject).await,
+        "image/style_consistency" => {
+            service
+                .style_manager
+                .lock()
+                .await
+                .create_style_prompt(subject)
+                .await
+        }
+        "audio/theme_song" => {
+            service
+                .audio()
+                .music_prompt("theme", &AudioConfig::default())
+                .await
+        }
+        "audio/battle_music" => {
+            service
+                .audio()
+                .music_prompt("battle", &AudioConfig::default())
+                .await
+        }
+        "audio/victory_fanfare" => {
+            service
+                .audio()
+                .music_prompt("victory", &AudioConfig::default())
+                .await
+        }
+        "audio/sound_effect" => {
+            service
+                .audio()
+                .sound_effect_prompt(subject, SOUND_EFFECT_DURATION)
+                .await
+        }
+        _ => anyhow::bail!("No generator sends the {name} prompt yet"),
+    }
+}
+
+/// The game concept the style guide is drawn for
+fn concept(config: &ProjectConfig) -> GameConcept {
+    GameConcept {
+        title: config
+            .name
+            .clone()
+            .unwrap_or_else(|| config.basic_info.name.clone()),
+        genre: config.basic_info.genre.clone(),
+        mood: config.visual_style.color_mood.clone(),
+        visual_inspirations: config.visual_style.reference_games.clone(),
+        color_themes: Vec::new(),
+        reference_screens: config
+            .visual_style
+            .reference_games
+            .iter()
+            .filter_map(|name| visuals::describe_game(name))
+            .collect(),
+    }
+}
+
+/// Copy the embedded prompt `name` into the project as an override, unless
+/// the project already overrides it
+pub fn create_override(project_dir: &Path, name: &str) -> Result<PathBuf> {
+    let library = PromptLibrary::new().with_project_dir(project_dir);
+    let path = library
+        .override_path(name)
+        .context("No override directory")?;
+    if path.exists() {
+        return Ok(path);
+    }
+    let embedded = embedded_prompt(name).with_context(|| format!("No prompt named {name}"))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    std::fs::write(&path, embedded.source)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Toggle the preview with F5
+pub fn toggle_prompt_preview(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<PromptPreviewState>,
+) {
+    if keys.just_pressed(KeyCode::F5) {
+        state.open = !state.open;
+        state.preview = None;
+    }
+}
+
+/// Draw the preview window
+pub fn draw_prompt_preview(
+    mut contexts: EguiContexts,
+    mut state: ResMut<PromptPreviewState>,
+    app_state: Res<AppState>,
+    pipeline: Res<GenerationPipeline>,
+    directories: Res<AppDirectories>,
+    localizer: Res<Localizer>,
+) {
+    if !state.open {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let library = PromptLibrary::new().with_project_dir(&directories.project_dir);
+    let mut open = state.open;
+    let mut render = state.preview.is_none() && state.error.is_none();
+
+    egui::Window::new(localizer.t("preview-title"))
+        .id(egui::Id::new("prompt_preview"))
+        .open(&mut open)
+        .default_size([680.0, 520.0])
+        .show(ctx, |ui| {
+            let name = EMBEDDED_PROMPTS[state.selected].name;
+            ui.horizontal(|ui| {
+                let mut selected = state.selected;
+                egui::ComboBox::from_id_salt("preview_prompt")
+                    .selected_text(name)
+                    .show_ui(ui, |ui| {
+                        for (index, prompt) in EMBEDDED_PROMPTS.iter().enumerate() {
+                            render |= ui
+                                .selectable_value(&mut selected, index, prompt.name)
+                                .changed();
+                        }
+                    });
+                state.selected = selected;
+                render |= ui.button(localizer.t("preview-render")).clicked();
+            });
+
+            if let Some(label) = subject_label(name) {
+                ui.horizontal(|ui| {
+                    ui.label(localizer.t(label));
+                    render |= ui.text_edit_singleline(&mut state.subject).lost_focus();
+                });
+            }
+
+            match library.override_path(name).filter(|path| path.is_file()) {
+                Some(path) => {
+                    ui.label(localizer.t_args(
+                        "preview-overridden",
+                        &[("path", path.display().to_string().into())],
+                    ));
+                }
+                None => {
+                    ui.horizontal(|ui| {
+                        ui.weak(localizer.t("preview-built-in"));
+                        if ui.button(localizer.t("preview-copy-to-project")).clicked() {
+                            match create_override(&directories.project_dir, name) {
+                                Ok(_) => render = true,
+                                Err(e) => state.error = Some(format!("{e:#}")),
+                            }
+                        }
+                    });
+                }
+            }
+            if app_state.config_manager.is_none() {
+                ui.label(
+                    egui::RichText::new(localizer.t("preview-no-project"))
+                        .small()
+                        .weak(),
+                );
+            }
+
+            if let Some(error) = &state.error {
+                ui.colored_label(egui::Color32::from_rgb(255, 100, 100), error);
+            }
+            if let Some(preview) = &state.preview {
+                ui.separator();
+                ui.label(localizer.t_args(
+                    "preview-tokens",
+                    &[
+                        ("prompt", preview.name.into()),
+                        ("count", preview.tokens.into()),
+                        ("model", TOKEN_MODEL.into()),
+                    ],
+                ));
+                egui::ScrollArea::vertical()
+                    .id_salt("preview_prompt_text")
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut preview.prompt.as_str())
+                                .code_editor()
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+            }
+        });
+    state.open = open;
+
+    if render && state.open {
+        let config = app_state
+            .config_manager
+            .as_ref()
+            .map(|manager| manager.config.clone())
+            .unwrap_or_default();
+        run_preview(
+            &mut state,
+            &pipeline,
+            &directories.project_dir,
+            &config,
+            &localizer,
+        );
+    }
+}
+
+fn run_preview(
+    state: &mut PromptPreviewState,
+    pipeline: &GenerationPipeline,
+    project_dir: &Path,
+    config: &ProjectConfig,
+    localizer: &Localizer,
+) {
+    let name = EMBEDDED_PROMPTS[state.selected].name;
+    let result = pipeline.runtime.block_on(async {
+        let service = AiService::new()?.with_prompt_overrides(project_dir);
+        render_prompt(&service, name, &state.subject, config).await
+    });
+    let result = result.and_then(|prompt| {
+        let tokens = TokenCounter::new().count_tokens(&prompt, TOKEN_MODEL)?;
+        Ok(PromptPreview {
+            name,
+            prompt,
+            tokens,
+        })
+    });
+
+    match result {
+        Ok(preview) => {
+            state.preview = Some(preview);
+            state.error = None;
+        }
+        Err(e) => {
+            state.preview = None;
+            state.error = Some(localizer.t_args(
+                "preview-render-failed",
+                &[("prompt", name.into()), ("error", format!("{e:#}").into())],
+            ));
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! Management of the project's prompt variables and macros
+//!
+//! Edits the variables and macros in the project's `prompts/variables.toml`,
+//! which every prompt can use, e.g. `{{ protagonist_name }}`. The edits are
+//! checked as they are made against the project's prompt overrides; a run
+//! won't start while a prompt uses a name nothing defines. Toggle the panel
+//! with F4.
+
+use crate::wizard::AppDirectories;
+use crate::wizard::i18n::Localizer;
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use std::collections::BTreeMap;
+use vintage_ai_client::prompts::{PromptLibrary, PromptMacro, PromptVariables, VariableIssue};
+
+/// State of the prompt variables panel
+#[derive(Resource, Default)]
+pub struct PromptVariablesState {
+    pub open: bool,
+    /// Definitions being edited
+    pub variables: PromptVariables,
+    /// Comma-separated arguments of each macro as typed
+    pub macro_args: BTreeMap<String, String>,
+    /// Name typed for a new variable or macro
+    pub new_name: String,
+    pub issues: Vec<VariableIssue>,
+    pub status: Option<String>,
+}
+
+impl PromptVariablesState {
+    /// Read the project's definitions
+    fn reload(&mut self, library: &PromptLibrary) {
+        match library.variables() {
+            Ok(variables) => {
+                self.variables = variables;
+                self.status = None;
+            }
+            Err(e) => {
+                self.variables = PromptVariables::default();
+                self.status = Some(format!("{e:#}"));
+            }
+        }
+        self.macro_args = self
+            .variables
+            .macros
+            .iter()
+            .map(|(name, definition)| (name.clone(), definition.args.join(", ")))
+            .collect();
+        self.check(library);
+    }
+
+    fn check(&mut self, library: &PromptLibrary) {
+        self.issues = library.check_variables_with(&self.variables);
+    }
+
+    fn save(&mut self, library: &PromptLibrary, localizer: &Localizer) {
+        let Some(path) = library.variables_path() else {
+            return;
+        };
+        self.status = Some(match self.variables.save(&path) {
+            Ok(()) => localizer.t_args("saved", &[("path", path.display().to_string().into())]),
+            Err(e) => format!("{e:#}"),
+        });
+    }
+
+    /// The typed name, if it's free
+    fn take_new_name(&mut self) -> Option<String> {
+        let name = self.new_name.trim().to_string();
+        if name.is_empty() || self.variables.names().any(|existing| existing == name) {
+            return None;
+        }
+        self.new_name.clear();
+        Some(name)
+    }
+}
+
+/// Toggle the panel with F4
+pub fn toggle_prompt_variables(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<PromptVariablesState>,
+    directories: Res<AppDirectories>,
+) {
+    if keys.just_pressed(KeyCode::F4) {
+        state.open = !state.open;
+        if state.open {
+            state.reload(&PromptLibrary::new().with_project_dir(&directories.project_dir));
+        }
+    }
+}
+
+/// Draw the variables window
+pub fn draw_prompt_variables(
+    mut contexts: EguiContexts,
+    mut state: ResMut<PromptVariablesState>,
+    directories: Res<AppDirectories>,
+    localizer: Res<Localizer>,
+) {
+    if !state.open {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let library = PromptLibrary::new().with_project_dir(&directories.project_dir);
+    let mut open = state.open;
+    let mut changed = false;
+    let mut reload = false;
+    let mut save = false;
+
+    egui::Window::new(localizer.t("variables-title"))
+        .id(egui::Id::new("prompt_variables"))
+        .open(&mut open)
+        .default_size([620.0, 520.0])
+        .show(ctx, |ui| {
+            let state = &mut *state;
+            ui.label(
+                egui::RichText::new(localizer.t("variables-hint"))
+                    .small()
+                    .weak(),
+            );
+
+            egui::ScrollArea::vertical()
+                .id_salt("prompt_variables")
+                .max_height(360.0)
+                .show(ui, |ui| {
+                    ui.heading(localizer.t("variables-variables"));
+                    let mut removed = None;
+                    egui::Grid::new("prompt_variables_grid")
+                        .num_columns(3)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (name, value) in state.variables.variables.iter_mut() {
+                                ui.monospace(name);
+                                changed |= ui.text_edit_singleline(value).changed();
+                                if ui.small_button("🗑").clicked() {
+                                    removed = Some(name.clone());
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    if let Some(name) = removed {
+                        state.variables.variables.remove(&name);
+                        changed = true;
+                    }
+
+                    ui.separator();
+                    ui.heading(localizer.t("variables-macros"));
+                    let mut removed = None;
+                    for (name, definition) in state.variables.macros.iter_mut() {
+                        ui.horizontal(|ui| {
+                            ui.monospace(name);
+                            ui.label(localizer.t("variables-arguments"));
+                            let args = state.macro_args.entry(name.clone()).or_default();
+                            if ui.text_edit_singleline(args).changed() {
+                                definition.args = args
+                                    .split(',')
+                                    .map(str::trim)
+                                    .filter(|arg| !arg.is_empty())
+                                    .map(str::to_string)
+                                    .collect();
+                                changed = true;
+                            }
+                            if ui.small_button("🗑").clicked() {
+                                removed = Some(name.clone());
+                            }
+                        });
+                        changed |= ui
+                            .add(
+                                egui::TextEdit::multiline(&mut definition.body)
+                                    .code_editor()
+                                    .desired_rows(2)
+                                    .desired_width(f32::INFINITY),
+                            )
+                            .changed();
+                    }
+                    if let Some(name) = removed {
+                        state.variables.macros.remove(&name);
+                        state.macro_args.remove(&name);
+                        changed = true;
+                    }
+                });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label(localizer.t("variables-name"));
+                ui.text_edit_singleline(&mut state.new_name);
+                if ui.button(localizer.t("variables-add-variable")).clicked()
+                    && let Some(name) = state.take_new_name()
+                {
+                    state.variables.variables.insert(name, String::new());
+                    changed = true;
+                }
+                if ui.button(localizer.t("variables-add-macro")).clicked()
+                    && let Some(name) = state.take_new_name()
+                {
+                    state.variables.macros.insert(name, PromptMacro::default());
+                    changed = true;
+                }
+            });
+
+            if state.issues.is_empty() {
+                ui.colored_label(
+                    egui::Color32::from_rgb(100, 200, 100),
+                    localizer.t("variables-all-defined"),
+                );
+            } else {
+                for issue in &state.issues {
+                    ui.colored_label(egui::Color32::from_rgb(255, 100, 100), issue.to_string());
+                }
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                reload = ui.button(localizer.t("window-reload")).clicked();
+                save = ui.button(localizer.t("variables-save")).clicked();
+                if let Some(status) = &state.status {
+                    ui.label(egui::RichText::new(status).small().weak());
+                }
+            });
+        });
+
+    state.open = open;
+    if changed {
+        state.check(&library);
+    }
+    if reload {
+        state.reload(&library);
+    }
+    if save {
+        state.save(&library, &localizer);
+    }
+}
@@ -0,0 +1,229 @@
+//! Wizard settings, kept in `settings.toml` in the base directory
+//!
+//! For now this is the language of the wizard UI, switched at runtime from
+//! the settings window (F6, or the ⚙ Settings button). It only affects the
+//! wizard's own strings. The language of generated content is part of each
+//! project's configuration; the same window sets it for the open project,
+//! along with how closely its generation keeps to the hardware limits of
+//! its era.
+
+use crate::vintage_games::eras::all_eras;
+use crate::wizard::AppDirectories;
+use crate::wizard::config::ConfigManager;
+use crate::wizard::i18n::{DEFAULT_LANGUAGE, LANGUAGES, Localizer};
+use crate::wizard::state::AppState;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// File in the base directory holding the settings
+pub const SETTINGS_FILE: &str = "settings.toml";
+
+/// Languages offered for generated content besides the English default,
+/// by the name that goes into the prompts
+pub const CONTENT_LANGUAGES: &[&str] = &[
+    "Japanese",
+    "Spanish",
+    "French",
+    "German",
+    "Italian",
+    "Portuguese",
+    "Korean",
+    "Chinese",
+];
+
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WizardSettings {
+    /// Language of the wizard UI, e.g. `en-US`
+    pub ui_language: String,
+}
+
+impl Default for WizardSettings {
+    fn default() -> Self {
+        Self {
+            ui_language: DEFAULT_LANGUAGE.to_string(),
+        }
+    }
+}
+
+impl WizardSettings {
+    /// Load the settings of `base_dir`, or the defaults if there are none yet
+    pub fn load(base_dir: &Path) -> Result<Self> {
+        let path = base_dir.join(SETTINGS_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, base_dir: &Path) -> Result<()> {
+        let path = base_dir.join(SETTINGS_FILE);
+        std::fs::write(&path, toml::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Toggle the settings window with F6
+pub fn toggle_settings(keys: Res<ButtonInput<KeyCode>>, mut app_state: ResMut<AppState>) {
+    if keys.just_pressed(KeyCode::F6) {
+        app_state.show_settings = !app_state.show_settings;
+    }
+}
+
+pub fn draw_settings(
+    mut contexts: EguiContexts,
+    mut app_state: ResMut<AppState>,
+    mut settings: ResMut<WizardSettings>,
+    mut localizer: ResMut<Localizer>,
+    directories: Res<AppDirectories>,
+) {
+    if !app_state.show_settings {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let mut open = app_state.show_settings;
+    egui::Window::new(localizer.t("settings-title"))
+        .id(egui::Id::new("wizard_settings"))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let mut language = localizer.language();
+            let current = LANGUAGES
+                .iter()
+                .find(|candidate| candidate.id == language)
+                .map_or(language, |found| found.name);
+            egui::ComboBox::from_label(localizer.t("settings-ui-language"))
+                .selected_text(current)
+                .show_ui(ui, |ui| {
+                    for candidate in LANGUAGES {
+                        ui.selectable_value(&mut language, candidate.id, candidate.name);
+                    }
+                });
+            ui.label(
+                egui::RichText::new(localizer.t("settings-ui-language-hint"))
+                    .small()
+                    .weak(),
+            );
+
+            if language != localizer.language() && localizer.set_language(language) {
+                settings.ui_language = language.to_string();
+                if let Err(e) = settings.save(&directories.base_dir) {
+                    warn!("Failed to save settings: {e:#}");
+                }
+            }
+
+            ui.separator();
+            match app_state.config_manager.as_mut() {
+                Some(config_manager) => {
+                    draw_content_language(ui, config_manager, &localizer);
+                    ui.separator();
+                    draw_authenticity(ui, config_manager, &localizer);
+                }
+                None => {
+                    ui.label(
+                        egui::RichText::new(localizer.t("settings-content-language-no-project"))
+                            .small()
+                            .weak(),
+                    );
+                }
+            }
+        });
+    app_state.show_settings = open;
+}
+
+/// Picker for the language the open project's game text is generated in
+fn draw_content_language(
+    ui: &mut egui::Ui,
+    config_manager: &mut ConfigManager,
+    localizer: &Localizer,
+) {
+    let current = config_manager.config.basic_info.content_language.clone();
+    let mut selected = current.clone();
+    let default = localizer.t("settings-content-language-default");
+    egui::ComboBox::from_label(localizer.t("settings-content-language"))
+        .selected_text(selected.clone().unwrap_or_else(|| default.clone()))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut selected, None, default.clone());
+            for language in CONTENT_LANGUAGES {
+                ui.selectable_value(&mut selected, Some(language.to_string()), *language);
+            }
+        });
+    ui.label(
+        egui::RichText::new(localizer.t("settings-content-language-hint"))
+            .small()
+            .weak(),
+    );
+
+    if selected != current {
+        config_manager.config.basic_info.content_language = selected;
+        if let Err(e) = config_manager.save() {
+            warn!("Failed to save content language: {e:#}");
+        }
+    }
+}
+
+/// Slider for how closely the open project keeps to the hardware limits of
+/// its era, with the era and the limits it comes to
+fn draw_authenticity(ui: &mut egui::Ui, config_manager: &mut ConfigManager, localizer: &Localizer) {
+    let current = config_manager.config.authenticity.clone();
+    let mut selected = current.clone();
+    let limits = config_manager.config.authentic_limits();
+
+    let automatic = localizer.t_args(
+        "settings-era-auto",
+        &[("era", config_manager.config.reference_era().name().into())],
+    );
+    egui::ComboBox::from_label(localizer.t("settings-era"))
+        .selected_text(
+            selected
+                .era
+                .map_or(automatic.clone(), |era| era.name().to_string()),
+        )
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut selected.era, None, automatic.clone());
+            for era in all_eras() {
+                ui.selectable_value(&mut selected.era, Some(era), era.name());
+            }
+        });
+
+    let slider = ui.add(
+        egui::Slider::new(&mut selected.level, 0.0..=1.0)
+            .show_value(false)
+            .text(localizer.t("settings-authenticity")),
+    );
+    ui.label(localizer.t_args(
+        "settings-authenticity-limits",
+        &[
+            ("strictness", limits.strictness().label().into()),
+            ("colors", limits.limits.palette_colors.into()),
+            ("sprites", limits.limits.sprites.into()),
+            ("channels", limits.limits.music_channels.into()),
+            ("chars", limits.limits.dialogue_chars.into()),
+            ("beats", limits.limits.plot_beats.into()),
+        ],
+    ));
+    ui.label(
+        egui::RichText::new(localizer.t("settings-authenticity-hint"))
+            .small()
+            .weak(),
+    );
+
+    // The limits follow the slider while it is dragged; the level is saved
+    // once it is let go
+    let changed = selected != current;
+    config_manager.config.authenticity = selected;
+    if ((changed && !slider.dragged()) || slider.drag_stopped())
+        && let Err(e) = config_manager.save()
+    {
+        warn!("Failed to save authenticity level: {e:#}");
+    }
+}
@@ -32,16 +32,22 @@ pub struct AppState {
     pub generation_status: GenerationStatus,
     pub error_message: Option<String>,
     pub show_exit_dialog: bool,
+    pub show_settings: bool,
     pub guided_export: Option<GuidedModeExport>,
 
     // Generation pipeline fields
     pub generation_active: bool,
     pub prompt_validation_queue: Vec<PromptValidation>,
+    /// Tasks that kept failing and were set aside so the run can continue
+    pub quarantined_tasks: Vec<PromptValidation>,
     pub current_phase: GenerationPhase,
     pub generation_logs: Vec<(LogLevel, String)>,
 
     // Configuration manager for persisting wizard state
     pub config_manager: Option<ConfigManager>,
+
+    // Set when the user asks for the tutorial; consumed by the tutorial system
+    pub tutorial_requested: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -52,6 +58,10 @@ pub struct PromptValidation {
     pub content: String,
     pub validated: bool,
     pub errors: Vec<String>,
+    /// Failed validation attempts so far
+    pub attempts: u32,
+    /// Set aside after too many failures; doesn't block the phase
+    pub quarantined: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -86,12 +96,15 @@ impl AppState {
             generation_status: GenerationStatus::Idle,
             error_message: None,
             show_exit_dialog: false,
+            show_settings: false,
             guided_export: None,
             generation_active: false,
             prompt_validation_queue: Vec::new(),
+            quarantined_tasks: Vec::new(),
             current_phase: GenerationPhase::Design,
             generation_logs: Vec::new(),
             config_manager: None,
+            tutorial_requested: false,
         }
     }
 
@@ -109,6 +122,9 @@ impl AppState {
                 self.wizard_mode = WizardMode::Freeform;
                 self.wizard_step = WizardStep::SelectLanguage;
             }
+            WelcomeAction::Tutorial => {
+                self.tutorial_requested = true;
+            }
         }
     }
 
@@ -168,14 +184,15 @@ impl AppState {
         }
     }
 
-    pub fn get_step_title(&self) -> &str {
+    /// Id of the current step's title in the UI catalogs
+    pub fn step_title_id(&self) -> &'static str {
         match &self.wizard_step {
-            WizardStep::Welcome => "Welcome",
-            WizardStep::SelectLanguage => "Select Language",
-            WizardStep::GuidedMode => "Browse & Blend Vintage Games",
-            WizardStep::FreeformMode => "AI-Assisted Game Creation",
-            WizardStep::Review => "Review & Generate",
-            WizardStep::Complete => "Generation Complete",
+            WizardStep::Welcome => "step-welcome",
+            WizardStep::SelectLanguage => "step-select-language",
+            WizardStep::GuidedMode => "step-guided",
+            WizardStep::FreeformMode => "step-freeform",
+            WizardStep::Review => "step-review",
+            WizardStep::Complete => "step-complete",
         }
     }
 
@@ -217,7 +234,7 @@ impl AppState {
     pub fn get_next_prompt_to_validate(&self) -> Option<&PromptValidation> {
         self.prompt_validation_queue
             .iter()
-            .find(|p| !p.validated && p.errors.is_empty())
+            .find(|p| !p.validated && !p.quarantined)
     }
 
     pub fn add_log(&mut self, level: LogLevel, message: String) {
@@ -235,6 +252,52 @@ impl AppState {
         }
     }
 
+    /// Record a failed attempt; after `max_attempts` failures the task is
+    /// moved to quarantine. Returns true if it was quarantined.
+    pub fn record_prompt_failure(
+        &mut self,
+        path: &PathBuf,
+        errors: Vec<String>,
+        max_attempts: u32,
+    ) -> bool {
+        let Some(prompt) = self
+            .prompt_validation_queue
+            .iter_mut()
+            .find(|p| &p.path == path)
+        else {
+            return false;
+        };
+
+        prompt.attempts += 1;
+        prompt.errors = errors;
+        if prompt.attempts < max_attempts {
+            // Pick up any fixes written to disk before the next attempt
+            if let Ok(content) = std::fs::read_to_string(&prompt.path) {
+                prompt.content = content;
+            }
+            return false;
+        }
+
+        prompt.quarantined = true;
+        let prompt = prompt.clone();
+        self.quarantined_tasks.retain(|p| p.path != prompt.path);
+        self.quarantined_tasks.push(prompt);
+        true
+    }
+
+    /// Move a quarantined task back into the queue with a fresh attempt count
+    pub fn retry_quarantined(&mut self, path: &PathBuf) {
+        if let Some(idx) = self.quarantined_tasks.iter().position(|p| &p.path == path) {
+            let mut prompt = self.quarantined_tasks.remove(idx);
+            prompt.attempts = 0;
+            prompt.errors.clear();
+            prompt.validated = false;
+            prompt.quarantined = false;
+            self.prompt_validation_queue.retain(|p| &p.path != path);
+            self.prompt_validation_queue.push(prompt);
+        }
+    }
+
     pub fn advance_phase(&mut self) {
         self.current_phase = match self.current_phase {
             GenerationPhase::Initializing => GenerationPhase::Design,
@@ -271,6 +334,9 @@ impl AppState {
         content: String,
         path: PathBuf,
     ) {
+        // A changed file gets a fresh start, even if it was quarantined
+        self.quarantined_tasks.retain(|p| p.path != path);
+        self.prompt_validation_queue.retain(|p| p.path != path);
         self.prompt_validation_queue.push(PromptValidation {
             path,
             phase,
@@ -278,6 +344,8 @@ impl AppState {
             content,
             validated: false,
             errors: Vec::new(),
+            attempts: 0,
+            quarantined: false,
         });
     }
 
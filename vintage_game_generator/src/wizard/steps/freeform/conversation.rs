@@ -1,9 +1,10 @@
 //! AI conversation interface for freeform mode
 
 use super::{
-    ConversationEntry, ConversationRole, ConversationStream, ConversationStreamEvent,
-    FreeformModeState,
+    ConversationBranchView, ConversationEntry, ConversationRole, ConversationStream,
+    ConversationStreamEvent, FreeformModeState,
 };
+use crate::wizard::i18n::Localizer;
 use crate::wizard::pipeline::GenerationPipeline;
 use crate::wizard::state::AppState;
 use bevy::prelude::*;
@@ -18,6 +19,7 @@ pub fn render_conversation(
     _commands: Commands,
     pipeline: Res<GenerationPipeline>,
     mut stream_res: ResMut<ConversationStream>,
+    localizer: &Localizer,
 ) {
     let Ok(ctx) = contexts.ctx_mut() else {
         return;
@@ -26,28 +28,50 @@ pub fn render_conversation(
     egui::CentralPanel::default().show(ctx, |ui| {
         // Header
         ui.horizontal(|ui| {
-            ui.heading("🤖 AI Game Design Conversation");
+            ui.heading(localizer.t("freeform-title"));
             ui.separator();
-            ui.label("Let's bring your game to life!");
+            ui.label(localizer.t("freeform-subtitle"));
         });
         ui.separator();
 
         // Context summary
         if !freeform_state.conversation.context_summary.is_empty() {
             ui.group(|ui| {
-                ui.label("Current Context:");
+                ui.label(localizer.t("freeform-context"));
                 ui.label(&freeform_state.conversation.context_summary);
             });
             ui.separator();
         }
 
+        // Branch selector, comparison and merge
+        if !freeform_state.conversation.branches.is_empty() {
+            render_branch_bar(ui, &mut freeform_state, &pipeline, localizer);
+            ui.separator();
+        }
+
+        if let Some(other) = freeform_state.conversation.comparing_with {
+            render_branch_comparison(ui, &freeform_state, other, localizer);
+            ui.separator();
+        }
+
         // Conversation history
+        let mut fork_at = None;
+        let can_fork = freeform_state.conversation.conversation_id.is_some()
+            && !freeform_state.conversation.is_processing;
         egui::ScrollArea::vertical()
             .auto_shrink([false; 2])
             .max_height(ui.available_height() - 100.0)
             .show(ui, |ui| {
-                for entry in &freeform_state.conversation.history {
-                    render_conversation_entry(ui, entry);
+                for (idx, entry) in freeform_state.conversation.history.iter().enumerate() {
+                    render_conversation_entry(ui, entry, localizer);
+                    if can_fork
+                        && ui
+                            .small_button(localizer.t("freeform-fork"))
+                            .on_hover_text(localizer.t("freeform-fork-hint"))
+                            .clicked()
+                    {
+                        fork_at = Some(idx);
+                    }
                     ui.add_space(10.0);
                 }
 
@@ -57,16 +81,25 @@ pub fn render_conversation(
                 {
                     ui.horizontal(|ui| {
                         ui.spinner();
-                        ui.label("AI is thinking...");
+                        ui.label(localizer.t("freeform-thinking"));
                     });
                 }
 
                 // Show error if any
                 if let Some(error) = &freeform_state.conversation.error_message {
-                    ui.colored_label(egui::Color32::RED, format!("Error: {}", error));
+                    ui.colored_label(
+                        egui::Color32::RED,
+                        localizer.t_args("freeform-error", &[("error", error.as_str().into())]),
+                    );
                 }
             });
 
+        if let Some(idx) = fork_at
+            && let Err(e) = fork_conversation(&mut freeform_state, &pipeline, idx, localizer)
+        {
+            freeform_state.conversation.error_message = Some(e.to_string());
+        }
+
         ui.separator();
 
         // Input area
@@ -79,23 +112,33 @@ pub fn render_conversation(
                 && !freeform_state.conversation.current_input.trim().is_empty()
                 && !freeform_state.conversation.is_processing
             {
-                send_message(&mut freeform_state, &pipeline, stream_res.reborrow());
+                send_message(
+                    &mut freeform_state,
+                    &pipeline,
+                    stream_res.reborrow(),
+                    localizer,
+                );
             }
 
             ui.vertical(|ui| {
                 if freeform_state.conversation.is_processing {
-                    if ui.button("Cancel").clicked() {
+                    if ui.button(localizer.t("button-cancel")).clicked() {
                         freeform_state.conversation.is_processing = false;
                         freeform_state.conversation.is_streaming = false;
                         stream_res.receiver = None;
                     }
-                } else if ui.button("Send").clicked()
+                } else if ui.button(localizer.t("freeform-send")).clicked()
                     && !freeform_state.conversation.current_input.trim().is_empty()
                 {
-                    send_message(&mut freeform_state, &pipeline, stream_res.reborrow());
+                    send_message(
+                        &mut freeform_state,
+                        &pipeline,
+                        stream_res.reborrow(),
+                        localizer,
+                    );
                 }
 
-                if ui.button("Export").clicked() {
+                if ui.button(localizer.t("freeform-export")).clicked() {
                     // TODO: Export conversation and config
                     info!("Exporting freeform configuration...");
                 }
@@ -105,12 +148,12 @@ pub fn render_conversation(
         // Navigation
         ui.separator();
         ui.horizontal(|ui| {
-            if ui.button("← Back to Review").clicked() {
+            if ui.button(localizer.t("freeform-back-to-review")).clicked() {
                 freeform_state.current_step = super::FreeformStep::Review;
             }
 
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                if ui.button("Generate Game →").clicked() {
+                if ui.button(localizer.t("freeform-generate")).clicked() {
                     // TODO: Start generation
                     app_state.set_wizard_step(crate::wizard::state::WizardStep::Complete);
                 }
@@ -119,7 +162,7 @@ pub fn render_conversation(
     });
 }
 
-fn render_conversation_entry(ui: &mut egui::Ui, entry: &ConversationEntry) {
+fn render_conversation_entry(ui: &mut egui::Ui, entry: &ConversationEntry, localizer: &Localizer) {
     let (icon, color) = match entry.role {
         ConversationRole::User => ("👤", egui::Color32::from_rgb(100, 150, 255)),
         ConversationRole::Assistant => ("🤖", egui::Color32::from_rgb(100, 255, 150)),
@@ -133,9 +176,12 @@ fn render_conversation_entry(ui: &mut egui::Ui, entry: &ConversationEntry) {
 
     if let Some(metadata) = &entry.metadata {
         ui.indent("metadata", |ui| {
-            ui.label(format!("Topic: {}", metadata.topic));
+            ui.label(localizer.t_args(
+                "freeform-topic",
+                &[("topic", metadata.topic.as_str().into())],
+            ));
             if !metadata.decisions_made.is_empty() {
-                ui.label("Decisions made:");
+                ui.label(localizer.t("freeform-decisions"));
                 for decision in &metadata.decisions_made {
                     ui.label(format!("  • {}", decision));
                 }
@@ -144,10 +190,198 @@ fn render_conversation_entry(ui: &mut egui::Ui, entry: &ConversationEntry) {
     }
 }
 
+fn render_branch_bar(
+    ui: &mut egui::Ui,
+    freeform_state: &mut FreeformModeState,
+    pipeline: &GenerationPipeline,
+    localizer: &Localizer,
+) {
+    let mut switch_to = None;
+    let mut merge_from = None;
+
+    ui.horizontal_wrapped(|ui| {
+        ui.label(localizer.t("freeform-branches"));
+        let _ = ui.selectable_label(true, &freeform_state.conversation.branch_label);
+
+        let conversation = &mut freeform_state.conversation;
+        for (idx, branch) in conversation.branches.iter().enumerate() {
+            ui.menu_button(&branch.label, |ui| {
+                if ui.button(localizer.t("freeform-switch-branch")).clicked() {
+                    switch_to = Some(idx);
+                    ui.close_menu();
+                }
+                let comparing = conversation.comparing_with == Some(idx);
+                if ui
+                    .button(if comparing {
+                        localizer.t("freeform-hide-comparison")
+                    } else {
+                        localizer.t("freeform-compare")
+                    })
+                    .clicked()
+                {
+                    conversation.comparing_with = (!comparing).then_some(idx);
+                    ui.close_menu();
+                }
+                if ui.button(localizer.t("freeform-merge")).clicked() {
+                    merge_from = Some(idx);
+                    ui.close_menu();
+                }
+            });
+        }
+    });
+
+    if freeform_state.conversation.is_processing {
+        return;
+    }
+
+    if let Some(idx) = switch_to {
+        let conversation = &mut freeform_state.conversation;
+        let target = conversation.branches.remove(idx);
+        conversation.branches.push(ConversationBranchView {
+            conversation_id: conversation.conversation_id.take(),
+            label: std::mem::take(&mut conversation.branch_label),
+            history: std::mem::take(&mut conversation.history),
+        });
+        conversation.conversation_id = target.conversation_id;
+        conversation.branch_label = target.label;
+        conversation.history = target.history;
+        conversation.comparing_with = None;
+    }
+
+    if let Some(idx) = merge_from
+        && let Err(e) = merge_branch(freeform_state, pipeline, idx, localizer)
+    {
+        freeform_state.conversation.error_message = Some(e.to_string());
+    }
+}
+
+fn render_branch_comparison(
+    ui: &mut egui::Ui,
+    freeform_state: &FreeformModeState,
+    other: usize,
+    localizer: &Localizer,
+) {
+    let conversation = &freeform_state.conversation;
+    let Some(branch) = conversation.branches.get(other) else {
+        return;
+    };
+
+    let shared = shared_prefix_len(&conversation.history, &branch.history);
+
+    ui.group(|ui| {
+        ui.label(localizer.t_args("freeform-shared-messages", &[("count", shared.into())]));
+        ui.columns(2, |columns| {
+            columns[0].strong(&conversation.branch_label);
+            for entry in &conversation.history[shared..] {
+                render_conversation_entry(&mut columns[0], entry, localizer);
+            }
+
+            columns[1].strong(&branch.label);
+            for entry in &branch.history[shared..] {
+                render_conversation_entry(&mut columns[1], entry, localizer);
+            }
+        });
+    });
+}
+
+/// Number of leading entries two branch histories have in common
+fn shared_prefix_len(left: &[ConversationEntry], right: &[ConversationEntry]) -> usize {
+    left.iter()
+        .zip(right)
+        .take_while(|(l, r)| l.role == r.role && l.content == r.content)
+        .count()
+}
+
+fn fork_conversation(
+    freeform_state: &mut FreeformModeState,
+    pipeline: &GenerationPipeline,
+    at_message: usize,
+    localizer: &Localizer,
+) -> anyhow::Result<()> {
+    let conversation = &mut freeform_state.conversation;
+    let conversation_id = conversation
+        .conversation_id
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!(localizer.t("freeform-not-started")))?;
+
+    let generator_arc = pipeline.generator.clone();
+    let new_id = pipeline.runtime.block_on(async {
+        let generator = generator_arc.lock().await;
+        let generator = generator
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!(localizer.t("freeform-no-generator")))?;
+        // The history and the conversation end with the same turns, but
+        // the conversation may have compacted or trimmed the oldest ones
+        let turns_after = conversation.history.len() - 1 - at_message;
+        let ids = generator.turn_ids(&conversation_id).await?;
+        let message_id = ids
+            .len()
+            .checked_sub(turns_after + 1)
+            .map(|index| &ids[index])
+            .ok_or_else(|| anyhow::anyhow!(localizer.t("freeform-fork-summarized")))?;
+        generator
+            .fork_conversation(&conversation_id, message_id)
+            .await
+    })?;
+
+    if conversation.branch_label.is_empty() {
+        conversation.branch_label = localizer.t("freeform-main-branch");
+    }
+    conversation.branches.push(ConversationBranchView {
+        conversation_id: Some(conversation_id),
+        label: conversation.branch_label.clone(),
+        history: conversation.history.clone(),
+    });
+
+    conversation.history.truncate(at_message + 1);
+    conversation.conversation_id = Some(new_id);
+    conversation.branch_label = localizer.t_args(
+        "freeform-branch",
+        &[("number", conversation.branches.len().into())],
+    );
+    conversation.comparing_with = None;
+
+    Ok(())
+}
+
+fn merge_branch(
+    freeform_state: &mut FreeformModeState,
+    pipeline: &GenerationPipeline,
+    source: usize,
+    localizer: &Localizer,
+) -> anyhow::Result<()> {
+    let conversation = &mut freeform_state.conversation;
+    let branch = conversation
+        .branches
+        .get(source)
+        .ok_or_else(|| anyhow::anyhow!(localizer.t("freeform-unknown-branch")))?;
+
+    if let (Some(target_id), Some(source_id)) = (
+        conversation.conversation_id.clone(),
+        branch.conversation_id.clone(),
+    ) {
+        let generator_arc = pipeline.generator.clone();
+        pipeline.runtime.block_on(async move {
+            let generator = generator_arc.lock().await;
+            let generator = generator
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!(localizer.t("freeform-no-generator")))?;
+            generator.merge_conversations(&target_id, &source_id).await
+        })?;
+    }
+
+    let shared = shared_prefix_len(&conversation.history, &branch.history);
+    let merged: Vec<ConversationEntry> = branch.history[shared..].to_vec();
+    conversation.history.extend(merged);
+
+    Ok(())
+}
+
 fn send_message(
     freeform_state: &mut FreeformModeState,
     pipeline: &GenerationPipeline,
     mut stream_res: Mut<ConversationStream>,
+    localizer: &Localizer,
 ) {
     let message = freeform_state.conversation.current_input.trim().to_string();
 
@@ -174,6 +408,7 @@ fn send_message(
     let generator_arc = pipeline.generator.clone();
     let runtime = pipeline.runtime.clone();
     let conversation_id = freeform_state.conversation.conversation_id.clone();
+    let not_initialized = localizer.t("freeform-no-generator");
 
     // Spawn async task for streaming
     runtime.spawn(async move {
@@ -208,7 +443,8 @@ fn send_message(
             } else {
                 // If no conversation ID, start a new one first
                 match generator.start_game_design_conversation(&message).await {
-                    Ok((_new_id, initial_response)) => {
+                    Ok((new_id, initial_response)) => {
+                        let _ = tx.send(ConversationStreamEvent::Started(new_id));
                         // Send the initial response
                         let _ = tx.send(ConversationStreamEvent::Token(initial_response));
                         let _ = tx.send(ConversationStreamEvent::Finished);
@@ -219,9 +455,7 @@ fn send_message(
                 }
             }
         } else {
-            let _ = tx.send(ConversationStreamEvent::Error(
-                "AI Generator not initialized".to_string(),
-            ));
+            let _ = tx.send(ConversationStreamEvent::Error(not_initialized));
         }
     });
 }
@@ -238,6 +472,9 @@ pub fn process_conversation_stream(
 
     while let Ok(event) = receiver_ref.try_recv() {
         match event {
+            ConversationStreamEvent::Started(conversation_id) => {
+                freeform_state.conversation.conversation_id = Some(conversation_id);
+            }
             ConversationStreamEvent::Token(token) => {
                 // If the last message is from Assistant and we are streaming, append to it
                 // Otherwise, create a new Assistant message
@@ -3,6 +3,7 @@
 //! This mode provides an interactive conversation interface where users can
 //! describe their game ideas and the AI helps design and generate the game.
 
+use crate::wizard::i18n::Localizer;
 use crate::wizard::pipeline::GenerationPipeline;
 use crate::wizard::state::AppState;
 use bevy::prelude::*;
@@ -22,29 +23,30 @@ pub fn render_freeform_mode(
     commands: Commands,
     pipeline: Res<GenerationPipeline>,
     stream_res: ResMut<ConversationStream>,
+    localizer: &Localizer,
 ) {
     // Route to appropriate sub-step
     match &freeform_state.current_step {
         FreeformStep::Introduction => {
-            ui_placeholder(contexts, "Introduction");
+            ui_placeholder(contexts, localizer, "freeform-step-introduction");
         }
         FreeformStep::BasicInfo => {
-            ui_placeholder(contexts, "Basic Info");
+            ui_placeholder(contexts, localizer, "freeform-step-basic-info");
         }
         FreeformStep::GameplayDesign => {
-            ui_placeholder(contexts, "Gameplay Design");
+            ui_placeholder(contexts, localizer, "freeform-step-gameplay");
         }
         FreeformStep::VisualStyle => {
-            ui_placeholder(contexts, "Visual Style");
+            ui_placeholder(contexts, localizer, "freeform-step-visual-style");
         }
         FreeformStep::Features => {
-            ui_placeholder(contexts, "Features");
+            ui_placeholder(contexts, localizer, "freeform-step-features");
         }
         FreeformStep::TechnicalSettings => {
-            ui_placeholder(contexts, "Technical Settings");
+            ui_placeholder(contexts, localizer, "freeform-step-technical");
         }
         FreeformStep::Review => {
-            ui_placeholder(contexts, "Review");
+            ui_placeholder(contexts, localizer, "freeform-step-review");
         }
         FreeformStep::Conversation => {
             conversation::render_conversation(
@@ -54,18 +56,22 @@ pub fn render_freeform_mode(
                 commands,
                 pipeline,
                 stream_res,
+                localizer,
             );
         }
     }
 }
 
-fn ui_placeholder(mut contexts: EguiContexts, name: &str) {
+fn ui_placeholder(mut contexts: EguiContexts, localizer: &Localizer, name_id: &str) {
     let Ok(ctx) = contexts.ctx_mut() else {
         return;
     };
     egui::CentralPanel::default().show(ctx, |ui| {
-        ui.heading(format!("{} - Under Construction", name));
-        ui.label("This step is currently being rebuilt.");
+        ui.heading(localizer.t_args(
+            "freeform-under-construction",
+            &[("step", localizer.t(name_id).into())],
+        ));
+        ui.label(localizer.t("freeform-being-rebuilt"));
     });
 }
 
@@ -77,6 +77,20 @@ pub struct ConversationState {
     pub is_streaming: bool,
     pub error_message: Option<String>,
     pub context_summary: String,
+    /// Label of the branch currently shown
+    pub branch_label: String,
+    /// Other branches of this conversation, not currently shown
+    pub branches: Vec<ConversationBranchView>,
+    /// Index into `branches` being compared with the current branch
+    pub comparing_with: Option<usize>,
+}
+
+/// A stashed conversation branch
+#[derive(Clone)]
+pub struct ConversationBranchView {
+    pub conversation_id: Option<String>,
+    pub label: String,
+    pub history: Vec<ConversationEntry>,
 }
 
 #[derive(Clone)]
@@ -103,6 +117,7 @@ pub struct ConversationMetadata {
 
 /// Events for streaming conversation
 pub enum ConversationStreamEvent {
+    Started(String),
     Token(String),
     Finished,
     Error(String),
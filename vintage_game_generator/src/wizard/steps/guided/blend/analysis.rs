@@ -197,6 +197,7 @@ pub fn extract_conflicts(
                 game1: game1_name.to_string(),
                 game2: game2_name.to_string(),
                 conflict_type: conf.type_name.clone(),
+                description: conf.description.clone(),
                 resolution: conf.resolution_hint.clone(),
             });
         }
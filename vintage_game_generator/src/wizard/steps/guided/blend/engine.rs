@@ -2,14 +2,15 @@ use crate::wizard::steps::guided::types::{BlendResult, GuidedModeState};
 use std::collections::{HashMap, HashSet};
 use vintage_blending_core::{
     CompatibilityEdge,
-    graph::{BlendPath, GameGraph},
+    graph::{BlendPath, GameGraph, normalize_weights, pair_influence},
     similarity::SimilarityEngine,
 };
 
 use super::analysis::{analyze_conflicts, analyze_synergies, generate_recommendations};
 use super::metadata::{build_game_metadata, determine_art_styles};
 
-/// Create a blend from selected games using the blending core
+/// Create a blend from selected games using the blending core, each game
+/// counting as much as its weight slider says
 pub fn create_blend(state: &mut GuidedModeState) {
     let selected_games: Vec<_> = state.selected_games.values().cloned().collect();
 
@@ -17,6 +18,13 @@ pub fn create_blend(state: &mut GuidedModeState) {
         return;
     }
 
+    let game_ids: Vec<String> = selected_games.iter().map(|g| g.id.to_string()).collect();
+    let weights: HashMap<String, f32> = selected_games
+        .iter()
+        .map(|g| (g.id.to_string(), state.game_weight(g.id)))
+        .collect();
+    let shares = normalize_weights(&game_ids, &weights);
+
     // Build metadata for each game
     let mut game_metadata = HashMap::new();
 
@@ -40,52 +48,92 @@ pub fn create_blend(state: &mut GuidedModeState) {
             let meta2 = &game_metadata[&game2.id.to_string()];
 
             let compatibility = engine.compute_similarity(meta1, meta2);
+            let influence = pair_influence(shares[&game_ids[i]], shares[&game_ids[j]]);
 
-            edges.push(CompatibilityEdge {
-                weight: compatibility,
-                synergies: analyze_synergies(game1, game2, meta1, meta2),
-                conflicts: analyze_conflicts(game1, game2, meta1, meta2),
-            });
+            edges.push(
+                CompatibilityEdge {
+                    weight: compatibility,
+                    synergies: analyze_synergies(game1, game2, meta1, meta2),
+                    conflicts: analyze_conflicts(game1, game2, meta1, meta2),
+                }
+                .weighted(influence),
+            );
         }
     }
 
     // Find optimal blend path
-    let game_ids: Vec<String> = selected_games.iter().map(|g| g.id.to_string()).collect();
-    let blend_path = graph.find_blend_path(&game_ids).unwrap_or_else(|_| {
-        // Fallback: create a simple path through all games
-        BlendPath {
-            games: game_ids.clone(),
-            total_compatibility: edges.iter().map(|e| e.weight).sum(),
-            synergies: edges.iter().flat_map(|e| e.synergies.clone()).collect(),
-            conflicts: edges.iter().flat_map(|e| e.conflicts.clone()).collect(),
-        }
-    });
+    let blend_path = graph
+        .find_weighted_blend_path(&game_ids, &weights)
+        .unwrap_or_else(|_| {
+            // Fallback: create a simple path through all games
+            BlendPath {
+                games: game_ids.clone(),
+                total_compatibility: edges.iter().map(|e| e.weight).sum(),
+                synergies: edges.iter().flat_map(|e| e.synergies.clone()).collect(),
+                conflicts: edges.iter().flat_map(|e| e.conflicts.clone()).collect(),
+                weights: shares,
+            }
+        });
 
     // Generate blend result
     let blend_result = generate_blend_result(&selected_games, &game_metadata, &blend_path);
+    state.resolutions.update(&blend_result.conflicts);
     state.blend_result = Some(blend_result);
 }
 
+/// Replace the selection with the surprise games drawn with `seed` and
+/// blend them at equal weights. Returns whether there were enough games to
+/// blend.
+pub fn create_surprise_blend(state: &mut GuidedModeState, seed: u64) -> bool {
+    let games = crate::vintage_games::surprise::surprise_games(seed);
+    if games.len() < 2 {
+        return false;
+    }
+
+    state.selected_games = games.into_iter().map(|game| (game.id, game)).collect();
+    state.game_weights.clear();
+    state.ui_state.similar_to = None;
+    create_blend(state);
+    state.blend_result.is_some()
+}
+
 /// Generate the final blend result
 fn generate_blend_result(
     games: &[&crate::vintage_games::TimelineGame],
     metadata: &HashMap<String, vintage_blending_core::GameMetadata>,
     blend_path: &BlendPath,
 ) -> BlendResult {
+    let share = |game: &crate::vintage_games::TimelineGame| {
+        blend_path
+            .weights
+            .get(&game.id.to_string())
+            .copied()
+            .unwrap_or(1.0 / games.len() as f32)
+    };
+
+    // Heaviest games first, so they lead the name; games with no share
+    // add nothing but are still listed as sources
+    let mut games = games.to_vec();
+    games.sort_by(|a, b| {
+        share(b)
+            .total_cmp(&share(a))
+            .then_with(|| a.name.cmp(b.name))
+    });
+    let contributing: Vec<_> = games.iter().copied().filter(|g| share(g) > 0.0).collect();
+
     // Aggregate genres with weights
     let mut genre_weights = HashMap::new();
-    for game in games {
+    for game in &contributing {
         let meta = &metadata[&game.id.to_string()];
-        // For now, we'll use the game's genre affinity as a simple weight
-        // Use the genre affinities from metadata
+        // Use the genre affinities from metadata, scaled by the game's share
         for (genre, weight) in &meta.genre_affinities {
-            *genre_weights.entry(genre.clone()).or_insert(0.0) += weight;
+            *genre_weights.entry(genre.clone()).or_insert(0.0) += weight * share(game);
         }
 
         // If no affinities, use primary genre
         if meta.genre_affinities.is_empty() {
             // Fallback: use the game's primary genre
-            *genre_weights.entry(game.genre.to_string()).or_insert(0.0) += 1.0;
+            *genre_weights.entry(game.genre.to_string()).or_insert(0.0) += share(game);
         }
     }
 
@@ -97,20 +145,19 @@ fn generate_blend_result(
 
     // Collect all mechanics
     let mut all_mechanics = HashSet::new();
-    for game in games {
+    for game in &contributing {
         let meta = &metadata[&game.id.to_string()];
         all_mechanics.extend(meta.mechanic_tags.iter().cloned());
     }
 
     // Generate blend name
-    let blend_name = generate_blend_name(games);
+    let blend_name = generate_blend_name(&games);
 
-    // Calculate average complexity and balance
+    // Calculate weighted average complexity and balance
     let avg_complexity = games
         .iter()
-        .map(|g| metadata[&g.id.to_string()].feature_vector.complexity)
-        .sum::<f32>()
-        / games.len() as f32;
+        .map(|g| metadata[&g.id.to_string()].feature_vector.complexity * share(g))
+        .sum::<f32>();
 
     let avg_balance = games
         .iter()
@@ -118,9 +165,9 @@ fn generate_blend_result(
             metadata[&g.id.to_string()]
                 .feature_vector
                 .action_strategy_balance
+                * share(g)
         })
-        .sum::<f32>()
-        / games.len() as f32;
+        .sum::<f32>();
 
     // Extract synergies and conflicts from the blend path
     let synergies = blend_path
@@ -144,6 +191,7 @@ fn generate_blend_result(
                 game1: c.type_name.clone(), // We'll use type_name as a placeholder
                 game2: String::new(),
                 conflict_type: c.type_name.clone(),
+                description: c.description.clone(),
                 resolution: c.resolution_hint.clone(),
             }
         })
@@ -153,11 +201,21 @@ fn generate_blend_result(
     let recommendations = generate_recommendations(&genre_weights, &all_mechanics, avg_complexity);
 
     // Determine art styles
-    let art_styles = determine_art_styles(games);
+    let art_styles = determine_art_styles(&contributing);
+
+    let mut description = generate_blend_description(&games, &genre_weights);
+    let shares: Vec<f32> = games.iter().map(|g| share(g)).collect();
+    if shares.iter().any(|s| (s - shares[0]).abs() > 0.005) {
+        let mix: Vec<String> = games
+            .iter()
+            .map(|g| format!("{:.0}% {}", share(g) * 100.0, g.name))
+            .collect();
+        description.push_str(&format!(" ({})", mix.join(", ")));
+    }
 
     BlendResult {
         name: blend_name,
-        description: generate_blend_description(games, &genre_weights),
+        description,
         blend_path: blend_path.clone(),
         genres: genre_weights,
         mechanics: all_mechanics,
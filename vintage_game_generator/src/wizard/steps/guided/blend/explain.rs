@@ -0,0 +1,384 @@
+//! "Explain this blend" conversation
+//!
+//! Opens a blend exploration conversation that knows the concrete blend:
+//! its source games and their shares, genres, mechanics, synergies,
+//! conflicts and the resolutions picked for them. The user can ask why a
+//! synergy scored high, change the blend, and tell the conversation about
+//! the changes before exporting.
+
+use crate::metaprompts::ConversationMessage;
+use crate::wizard::i18n::Localizer;
+use crate::wizard::pipeline::GenerationPipeline;
+use crate::wizard::steps::guided::types::GuidedModeState;
+use bevy_egui::egui;
+use tokio::sync::oneshot;
+use vintage_ai_client::conversation::{ConversationContext, blend_exploration_context};
+
+/// The question the conversation is opened with
+pub const EXPLAIN_QUESTION: &str = "Explain this blend: why do its synergies score the way they \
+    do, what do its conflicts mean for the design, and what would make it stronger?";
+
+/// Genres and mechanics named as the blend's dominant attributes
+const DOMINANT_ATTRIBUTES: usize = 3;
+
+/// Reply to a message of the blend conversation, with the conversation's id
+type Reply = anyhow::Result<(String, String)>;
+
+/// A question to the conversation about the blend
+enum Request {
+    Start {
+        title: String,
+        context: Box<ConversationContext>,
+    },
+    Continue(String),
+}
+
+/// The conversation about the current blend
+#[derive(Debug, Default)]
+pub struct BlendExplanation {
+    pub open: bool,
+    pub conversation_id: Option<String>,
+    pub messages: Vec<ConversationMessage>,
+    pub input: String,
+    pub error: Option<String>,
+    /// The blend as last described to the conversation
+    described: Option<String>,
+    pending: Option<oneshot::Receiver<Reply>>,
+}
+
+impl BlendExplanation {
+    pub fn is_waiting(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Whether the blend changed since the conversation last heard of it
+    pub fn is_outdated(&self, state_description: Option<&str>) -> bool {
+        self.conversation_id.is_some() && self.described.as_deref() != state_description
+    }
+
+    /// Take the answer once it arrived
+    pub fn poll(&mut self) {
+        let Some(pending) = &mut self.pending else {
+            return;
+        };
+        match pending.try_recv() {
+            Ok(Ok((conversation_id, response))) => {
+                self.pending = None;
+                self.conversation_id = Some(conversation_id);
+                self.messages.push(ConversationMessage {
+                    role: "assistant".to_string(),
+                    content: response,
+                });
+            }
+            Ok(Err(e)) => {
+                self.pending = None;
+                self.error = Some(format!("{e:#}"));
+            }
+            Err(oneshot::error::TryRecvError::Empty) => {}
+            Err(oneshot::error::TryRecvError::Closed) => self.pending = None,
+        }
+    }
+
+    /// Send `question`, starting the conversation with the blend's context
+    /// if there is none yet
+    fn ask(&mut self, question: String, state: &GuidedModeState, pipeline: &GenerationPipeline) {
+        let request = match (&self.conversation_id, &state.blend_result) {
+            (Some(id), _) => Request::Continue(id.clone()),
+            (None, Some(blend)) => {
+                let Some(context) = blend_conversation_context(state) else {
+                    return;
+                };
+                self.described = describe_blend(state);
+                Request::Start {
+                    title: blend.name.clone(),
+                    context: Box::new(context),
+                }
+            }
+            (None, None) => return,
+        };
+
+        self.error = None;
+        self.messages.push(ConversationMessage {
+            role: "user".to_string(),
+            content: question.clone(),
+        });
+
+        let (sender, receiver) = oneshot::channel();
+        self.pending = Some(receiver);
+        let generator = pipeline.generator.clone();
+        pipeline.runtime.spawn(async move {
+            let result = async {
+                let generator = generator.lock().await;
+                let Some(generator) = generator.as_ref() else {
+                    anyhow::bail!("the AI generator is not initialized");
+                };
+                match request {
+                    Request::Start { title, context } => {
+                        generator
+                            .start_blend_conversation(&title, *context, &question)
+                            .await
+                    }
+                    Request::Continue(id) => {
+                        let response = generator
+                            .continue_blend_conversation(&id, &question)
+                            .await?;
+                        Ok((id, response))
+                    }
+                }
+            }
+            .await;
+            let _ = sender.send(result);
+        });
+    }
+}
+
+/// The blend in words, as the conversation is told about it
+pub fn describe_blend(state: &GuidedModeState) -> Option<String> {
+    let blend = state.blend_result.as_ref()?;
+    let mut text = format!("Blend: {}\n{}\n", blend.name, blend.description);
+
+    text.push_str("\nSource games:\n");
+    for game_id in &blend.blend_path.games {
+        let Some(game) = game_id
+            .parse::<u32>()
+            .ok()
+            .and_then(|id| state.selected_games.get(&id))
+        else {
+            continue;
+        };
+        let share = blend
+            .blend_path
+            .weights
+            .get(game_id)
+            .copied()
+            .unwrap_or(0.0);
+        text.push_str(&format!(
+            "- {} ({}, {}): {:.0}% of the blend\n",
+            game.name,
+            game.year,
+            game.genre,
+            share * 100.0
+        ));
+    }
+
+    text.push_str("\nGenres:\n");
+    for (genre, weight) in sorted_genres(state) {
+        text.push_str(&format!("- {genre}: {:.0}%\n", weight * 100.0));
+    }
+
+    let mut mechanics: Vec<&String> = blend.mechanics.iter().collect();
+    mechanics.sort();
+    if !mechanics.is_empty() {
+        let mechanics: Vec<&str> = mechanics.into_iter().map(String::as_str).collect();
+        text.push_str(&format!("\nMechanics: {}\n", mechanics.join(", ")));
+    }
+    text.push_str(&format!(
+        "Complexity: {:.0}%, action/strategy balance: {:.2}\n",
+        blend.complexity_score * 100.0,
+        blend.action_strategy_balance
+    ));
+
+    if !blend.synergies.is_empty() {
+        text.push_str("\nSynergies, with the strength they scored:\n");
+        for synergy in &blend.synergies {
+            text.push_str(&format!(
+                "- {}: {} (strength {:.2})\n",
+                synergy.game1, synergy.description, synergy.strength
+            ));
+        }
+    }
+    if !blend.conflicts.is_empty() {
+        text.push_str("\nConflicts:\n");
+        for conflict in &blend.conflicts {
+            text.push_str(&format!(
+                "- {}: {} (suggested: {})\n",
+                conflict.conflict_type, conflict.description, conflict.resolution
+            ));
+        }
+    }
+    let resolutions = state.resolutions.resolutions();
+    if !resolutions.is_empty() {
+        text.push_str("\nResolutions the designer picked:\n");
+        for resolution in resolutions {
+            text.push_str(&format!(
+                "- {}: {}\n",
+                resolution.conflict_type, resolution.resolution
+            ));
+        }
+    }
+    Some(text)
+}
+
+/// A blend exploration context for the current blend, with its games,
+/// shares and analysis
+pub fn blend_conversation_context(state: &GuidedModeState) -> Option<ConversationContext> {
+    let blend = state.blend_result.as_ref()?;
+    let description = describe_blend(state)?;
+
+    let games: Vec<(String, f32)> = blend
+        .blend_path
+        .games
+        .iter()
+        .filter_map(|game_id| {
+            let game = state.selected_games.get(&game_id.parse::<u32>().ok()?)?;
+            let share = blend
+                .blend_path
+                .weights
+                .get(game_id)
+                .copied()
+                .unwrap_or(0.0);
+            Some((game.name.to_string(), share))
+        })
+        .collect();
+    let genres = sorted_genres(state);
+
+    let mut context =
+        blend_exploration_context(games.iter().map(|(name, _)| name.clone()).collect());
+    if let Some(concept) = &mut context.game_concept {
+        concept.title = blend.name.clone();
+        if let Some((genre, _)) = genres.first() {
+            concept.genre = genre.to_string();
+        }
+        if let Some(current) = &mut concept.current_blend {
+            current.blend_weights = games.into_iter().collect();
+            let mut mechanics: Vec<&String> = blend.mechanics.iter().collect();
+            mechanics.sort();
+            current.dominant_attributes = genres
+                .iter()
+                .take(DOMINANT_ATTRIBUTES)
+                .map(|(genre, _)| genre.to_string())
+                .chain(mechanics.into_iter().take(DOMINANT_ATTRIBUTES).cloned())
+                .collect();
+        }
+    }
+    context.system_prompt = Some(format!(
+        "{}\n\nThe blend being discussed:\n{description}",
+        context.system_prompt.unwrap_or_default()
+    ));
+    Some(context)
+}
+
+/// Genres of the blend, heaviest first
+fn sorted_genres(state: &GuidedModeState) -> Vec<(&str, f32)> {
+    let Some(blend) = &state.blend_result else {
+        return Vec::new();
+    };
+    let mut genres: Vec<(&str, f32)> = blend
+        .genres
+        .iter()
+        .map(|(genre, weight)| (genre.as_str(), *weight))
+        .collect();
+    genres.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    genres
+}
+
+/// Button that opens the conversation, asking for the explanation the
+/// first time
+pub fn render_explain_button(
+    ui: &mut egui::Ui,
+    state: &mut GuidedModeState,
+    localizer: &Localizer,
+    pipeline: &GenerationPipeline,
+) {
+    if ui
+        .button(localizer.t("explain-button"))
+        .on_hover_text(localizer.t("explain-button-hint"))
+        .clicked()
+    {
+        let mut explanation = std::mem::take(&mut state.explanation);
+        explanation.open = true;
+        if explanation.messages.is_empty() && !explanation.is_waiting() {
+            explanation.ask(EXPLAIN_QUESTION.to_string(), state, pipeline);
+        }
+        state.explanation = explanation;
+    }
+}
+
+/// Window with the conversation about the blend
+pub fn render_explanation_window(
+    ctx: &egui::Context,
+    state: &mut GuidedModeState,
+    localizer: &Localizer,
+    pipeline: &GenerationPipeline,
+) {
+    let mut explanation = std::mem::take(&mut state.explanation);
+    explanation.poll();
+    if !explanation.open {
+        state.explanation = explanation;
+        return;
+    }
+
+    let description = describe_blend(state);
+    let mut open = true;
+    let mut question = None;
+    let mut tells_changes = false;
+    egui::Window::new(localizer.t("explain-title"))
+        .id(egui::Id::new("blend_explanation_window"))
+        .open(&mut open)
+        .default_width(480.0)
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical()
+                .id_salt("blend_explanation")
+                .max_height(360.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for message in &explanation.messages {
+                        let who = if message.role == "user" {
+                            localizer.t("explain-you")
+                        } else {
+                            localizer.t("explain-ai")
+                        };
+                        ui.label(egui::RichText::new(who).strong());
+                        ui.label(&message.content);
+                        ui.add_space(6.0);
+                    }
+                    if explanation.is_waiting() {
+                        ui.spinner();
+                    }
+                });
+
+            if let Some(error) = &explanation.error {
+                ui.colored_label(egui::Color32::from_rgb(255, 100, 100), error);
+            }
+            if explanation.is_outdated(description.as_deref())
+                && !explanation.is_waiting()
+                && let Some(description) = &description
+                && ui
+                    .button(localizer.t("explain-tell-changes"))
+                    .on_hover_text(localizer.t("explain-tell-changes-hint"))
+                    .clicked()
+            {
+                question = Some(format!(
+                    "I changed the blend. It is now:\n{description}\nWhat changed for the better or worse?"
+                ));
+                tells_changes = true;
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut explanation.input)
+                        .hint_text(localizer.t("explain-input-hint"))
+                        .desired_width(360.0),
+                );
+                let send = ui
+                    .add_enabled(!explanation.is_waiting(), egui::Button::new(localizer.t("explain-send")))
+                    .clicked()
+                    || (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)));
+                let input = explanation.input.trim();
+                if send && !input.is_empty() && !explanation.is_waiting() {
+                    question = Some(input.to_string());
+                    explanation.input.clear();
+                }
+            });
+        });
+
+    explanation.open = open;
+    if let Some(question) = question {
+        if tells_changes {
+            explanation.described = description;
+        }
+        explanation.ask(question, state, pipeline);
+    }
+    state.explanation = explanation;
+}
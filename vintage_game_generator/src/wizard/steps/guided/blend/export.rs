@@ -1,3 +1,4 @@
+use crate::wizard::i18n::Localizer;
 use crate::wizard::steps::guided::types::{GuidedModeExport, SourceGame};
 use bevy_egui::egui;
 use minijinja::{Environment, context};
@@ -23,6 +24,7 @@ pub fn export_blend_to_config(
                     year: game.year,
                     genre: game.genre.to_string(),
                     developer: game.developer.map(|s| s.to_string()),
+                    weight: blend.blend_path.weights.get(game_id).copied(),
                 })
             })
         })
@@ -38,6 +40,7 @@ pub fn export_blend_to_config(
         complexity: blend.complexity_score,
         action_strategy_balance: blend.action_strategy_balance,
         recommended_features: blend.recommended_features.clone(),
+        conflict_resolutions: state.resolutions.resolutions(),
     })
 }
 
@@ -70,9 +73,23 @@ pub fn export_to_toml(state: &crate::wizard::steps::guided::GuidedModeState) ->
         if let Some(dev) = &game.developer {
             toml.push_str(&format!("developer = \"{dev}\"\n"));
         }
+        if let Some(weight) = game.weight {
+            toml.push_str(&format!("weight = {weight:.2}\n"));
+        }
         toml.push('\n');
     }
 
+    // Resolutions picked for the blend's conflicts
+    for resolution in &export.conflict_resolutions {
+        toml.push_str("[[conflict_resolutions]]\n");
+        toml.push_str(&format!(
+            "conflict_type = \"{}\"\n",
+            resolution.conflict_type
+        ));
+        toml.push_str(&format!("description = \"{}\"\n", resolution.description));
+        toml.push_str(&format!("resolution = \"{}\"\n\n", resolution.resolution));
+    }
+
     // Genre weights
     toml.push_str("[genres]\n");
     let mut sorted_genres: Vec<_> = export.genre_weights.iter().collect();
@@ -131,6 +148,7 @@ pub fn export_to_json(
         "mechanics": export.mechanics,
         "art_styles": export.art_styles,
         "recommended_features": export.recommended_features,
+        "conflict_resolutions": export.conflict_resolutions,
         "synergies": blend.synergies.iter().map(|s| {
             serde_json::json!({
                 "games": [s.game1.clone(), s.game2.clone()],
@@ -184,6 +202,8 @@ pub fn generate_ai_prompt(
         mechanics: Vec<String>,
         art_styles: Vec<String>,
         complexity_score: f32,
+        action_strategy_balance: f32,
+        recommended_features: Vec<String>,
         synergies: Vec<SerializableSynergy>,
         conflicts: Vec<SerializableConflict>,
     }
@@ -211,6 +231,8 @@ pub fn generate_ai_prompt(
         mechanics: blend.mechanics.iter().cloned().collect(),
         art_styles: blend.art_styles.clone(),
         complexity_score: blend.complexity_score,
+        action_strategy_balance: blend.action_strategy_balance,
+        recommended_features: blend.recommended_features.clone(),
         synergies: blend
             .synergies
             .iter()
@@ -235,38 +257,43 @@ pub fn generate_ai_prompt(
 
     let rendered = tmpl.render(context!(
         blend => serializable_blend,
-        source_games => export.source_games
+        source_games => export.source_games,
+        conflict_resolutions => export.conflict_resolutions
     ))?;
 
     Ok(rendered)
 }
 
 /// Export UI for showing export options
-pub fn render_export_ui(ui: &mut egui::Ui, state: &crate::wizard::steps::guided::GuidedModeState) {
-    ui.heading("📤 Export Options");
+pub fn render_export_ui(
+    ui: &mut egui::Ui,
+    state: &crate::wizard::steps::guided::GuidedModeState,
+    localizer: &Localizer,
+) {
+    ui.heading(localizer.t("export-title"));
     ui.separator();
 
     if state.blend_result.is_none() {
-        ui.label("No blend result to export. Create a blend first!");
+        ui.label(localizer.t("export-no-blend"));
         return;
     }
 
     ui.horizontal(|ui| {
-        if ui.button("📄 Copy TOML").clicked()
+        if ui.button(localizer.t("export-copy-toml")).clicked()
             && let Some(toml) = export_to_toml(state)
         {
             // TODO: Copy to clipboard
             ui.ctx().copy_text(toml);
         }
 
-        if ui.button("📋 Copy JSON").clicked()
+        if ui.button(localizer.t("export-copy-json")).clicked()
             && let Some(json) = export_to_json(state)
         {
             // TODO: Copy to clipboard
             ui.ctx().copy_text(json.to_string());
         }
 
-        if ui.button("🤖 Copy AI Prompt").clicked() {
+        if ui.button(localizer.t("export-copy-prompt")).clicked() {
             match generate_ai_prompt(state) {
                 Ok(prompt) => {
                     ui.ctx().copy_text(prompt);
@@ -280,7 +307,7 @@ pub fn render_export_ui(ui: &mut egui::Ui, state: &crate::wizard::steps::guided:
 
     // Show export preview
     ui.separator();
-    ui.collapsing("Preview TOML Export", |ui| {
+    ui.collapsing(localizer.t("export-preview-toml"), |ui| {
         if let Some(toml) = export_to_toml(state) {
             ui.monospace(&toml);
         }
@@ -1,28 +1,33 @@
 pub mod analysis;
 pub mod engine;
+pub mod explain;
 pub mod export;
 pub mod metadata;
+pub mod resolution;
 pub mod visualization;
 
 // Re-export key functions
-pub use engine::create_blend;
+pub use engine::{create_blend, create_surprise_blend};
+pub use explain::{render_explain_button, render_explanation_window};
 pub use export::{export_blend_to_config, render_export_ui};
+pub use resolution::render_conflict_resolution;
 pub use visualization::render_blend_visualization;
 
+use crate::wizard::i18n::Localizer;
 use crate::wizard::steps::guided::GuidedModeState;
 use bevy_egui::egui;
 
 /// Main blend UI that combines visualization and export
-pub fn render_blend_ui(ui: &mut egui::Ui, state: &mut GuidedModeState) {
+pub fn render_blend_ui(ui: &mut egui::Ui, state: &mut GuidedModeState, localizer: &Localizer) {
     ui.columns(2, |columns| {
         // Left column - visualization
         columns[0].group(|ui| {
-            render_blend_visualization(ui, state);
+            render_blend_visualization(ui, state, localizer);
         });
 
         // Right column - export options
         columns[1].group(|ui| {
-            render_export_ui(ui, state);
+            render_export_ui(ui, state, localizer);
         });
     });
 }
@@ -0,0 +1,341 @@
+//! Conflict resolution step of guided mode
+//!
+//! Every conflict found between the blended games comes with two or three
+//! ways to settle it. The blend analysis' suggestions are shown right away
+//! and replaced by the AI's once it answers, unless one was picked already.
+//! The picked resolutions are exported with the blend and written into the
+//! blend design prompt.
+
+use super::analysis::suggest_resolution;
+use crate::wizard::i18n::Localizer;
+use crate::wizard::pipeline::GenerationPipeline;
+use crate::wizard::steps::guided::types::{
+    BlendResult, Conflict, ConflictResolution, GuidedModeState,
+};
+use bevy_egui::egui;
+use tokio::sync::oneshot;
+
+/// Resolutions offered per conflict
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// A conflict of the blend and the ways offered to settle it
+#[derive(Debug, Clone)]
+pub struct ConflictChoice {
+    pub conflict: Conflict,
+    pub suggestions: Vec<String>,
+    /// Index of the picked suggestion
+    pub chosen: Option<usize>,
+    /// Whether the suggestions came from the AI
+    pub from_ai: bool,
+}
+
+impl ConflictChoice {
+    fn new(conflict: Conflict) -> Self {
+        Self {
+            suggestions: offline_suggestions(&conflict),
+            conflict,
+            chosen: None,
+            from_ai: false,
+        }
+    }
+
+    fn is_same_conflict(&self, conflict: &Conflict) -> bool {
+        self.conflict.conflict_type == conflict.conflict_type
+            && self.conflict.description == conflict.description
+    }
+
+    pub fn resolution(&self) -> Option<ConflictResolution> {
+        let resolution = self.suggestions.get(self.chosen?)?;
+        Some(ConflictResolution {
+            conflict_type: self.conflict.conflict_type.clone(),
+            description: self.conflict.description.clone(),
+            resolution: resolution.clone(),
+        })
+    }
+}
+
+/// The conflicts of the current blend and the resolutions picked for them
+#[derive(Debug, Default)]
+pub struct ResolutionState {
+    pub choices: Vec<ConflictChoice>,
+    /// Why the AI couldn't suggest resolutions
+    pub error: Option<String>,
+    /// Whether the AI was asked for the current conflicts
+    requested: bool,
+    pending: Option<oneshot::Receiver<anyhow::Result<Vec<Vec<String>>>>>,
+}
+
+impl ResolutionState {
+    /// Take over the conflicts of a new blend. A conflict the previous blend
+    /// had as well keeps its suggestions and pick, e.g. when only a weight
+    /// was changed, and an unchanged set of conflicts keeps the request in
+    /// flight; a blend with new conflicts is sent to the AI again.
+    pub fn update(&mut self, conflicts: &[Conflict]) {
+        let mut choices: Vec<ConflictChoice> = Vec::new();
+        for conflict in conflicts {
+            if choices
+                .iter()
+                .any(|choice| choice.is_same_conflict(conflict))
+            {
+                continue;
+            }
+            let choice = self
+                .choices
+                .iter()
+                .find(|choice| choice.is_same_conflict(conflict))
+                .cloned()
+                .unwrap_or_else(|| ConflictChoice::new(conflict.clone()));
+            choices.push(choice);
+        }
+
+        // The AI's answer lists suggestions by conflict position, so only a
+        // changed set of conflicts makes the request in flight useless
+        let changed = choices.len() != self.choices.len()
+            || choices
+                .iter()
+                .zip(&self.choices)
+                .any(|(choice, previous)| !choice.is_same_conflict(&previous.conflict));
+        if changed {
+            self.pending = None;
+            self.requested = choices.iter().all(|choice| choice.from_ai);
+        }
+        self.choices = choices;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.choices.is_empty()
+    }
+
+    /// Whether every conflict has a resolution picked
+    pub fn is_resolved(&self) -> bool {
+        self.choices.iter().all(|choice| choice.chosen.is_some())
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// The picked resolutions, in conflict order
+    pub fn resolutions(&self) -> Vec<ConflictResolution> {
+        self.choices
+            .iter()
+            .filter_map(ConflictChoice::resolution)
+            .collect()
+    }
+
+    /// Ask the AI for resolutions of the conflicts it hasn't suggested any
+    /// for yet, in the background
+    pub fn request_suggestions(&mut self, blend: &BlendResult, pipeline: &GenerationPipeline) {
+        self.requested = true;
+        self.error = None;
+        let conflicts: Vec<String> = self
+            .choices
+            .iter()
+            .map(|choice| {
+                format!(
+                    "{}: {}",
+                    choice.conflict.conflict_type, choice.conflict.description
+                )
+            })
+            .collect();
+        let summary = format!("{}. {}", blend.name, blend.description);
+
+        let (sender, receiver) = oneshot::channel();
+        self.pending = Some(receiver);
+        let generator = pipeline.generator.clone();
+        pipeline.runtime.spawn(async move {
+            let result = async {
+                let generator = generator.lock().await;
+                let Some(generator) = generator.as_ref() else {
+                    anyhow::bail!("the AI generator is not initialized");
+                };
+                let mut suggestions = Vec::with_capacity(conflicts.len());
+                for conflict in &conflicts {
+                    suggestions.push(
+                        generator
+                            .suggest_conflict_resolutions(&summary, conflict, MAX_SUGGESTIONS)
+                            .await?,
+                    );
+                }
+                Ok(suggestions)
+            }
+            .await;
+            let _ = sender.send(result);
+        });
+    }
+
+    /// Take the AI's suggestions once they arrived
+    pub fn poll(&mut self) {
+        let Some(pending) = &mut self.pending else {
+            return;
+        };
+        match pending.try_recv() {
+            Ok(Ok(suggestions)) => {
+                self.pending = None;
+                self.apply_suggestions(suggestions);
+            }
+            Ok(Err(e)) => {
+                self.pending = None;
+                self.error = Some(format!("{e:#}"));
+            }
+            Err(oneshot::error::TryRecvError::Empty) => {}
+            Err(oneshot::error::TryRecvError::Closed) => self.pending = None,
+        }
+    }
+
+    /// Offer the AI's suggestions, one list per conflict, for the conflicts
+    /// nothing was picked for yet. Answers with fewer than two suggestions
+    /// leave the analysis' in place.
+    pub fn apply_suggestions(&mut self, suggestions: Vec<Vec<String>>) {
+        for (choice, suggestions) in self.choices.iter_mut().zip(suggestions) {
+            if choice.chosen.is_none() && suggestions.len() >= 2 {
+                choice.suggestions = suggestions;
+                choice.suggestions.truncate(MAX_SUGGESTIONS);
+                choice.from_ai = true;
+            }
+        }
+    }
+}
+
+/// Two or three ways to settle a conflict that need no AI, from the kind of
+/// conflict and the blend analysis' hint
+pub fn offline_suggestions(conflict: &Conflict) -> Vec<String> {
+    let ideas: &[&str] = match conflict.conflict_type.as_str() {
+        "Complexity Mismatch" => &[
+            "Simple core loop with optional deep systems for those who want them",
+            "Difficulty modes that unlock the deeper mechanics step by step",
+        ],
+        "Gameplay Style Conflict" => &[
+            "Turn-based overworld, real-time boss fights",
+            "Real-time action with a pause to plan and give orders",
+        ],
+        "Era Gap" => &[
+            "Keep the older game's look with the newer game's controls",
+            "Start in the older game's style and modernize as the story advances",
+        ],
+        "Genre Conflict" => &[
+            "Alternate the genres from chapter to chapter",
+            "Let the player's build lean toward either genre",
+        ],
+        _ => &[],
+    };
+
+    let mut suggestions: Vec<String> = ideas.iter().map(|idea| idea.to_string()).collect();
+    let fallbacks = [
+        conflict.resolution.clone(),
+        suggest_resolution(&conflict.description.to_lowercase()),
+        "Let the player choose between both approaches".to_string(),
+    ];
+    for fallback in fallbacks {
+        if suggestions.len() >= MAX_SUGGESTIONS {
+            break;
+        }
+        if !fallback.is_empty() && !suggestions.contains(&fallback) {
+            suggestions.push(fallback);
+        }
+    }
+    suggestions
+}
+
+/// The conflicts of the blend, each with its suggestions to pick from
+pub fn render_conflict_resolution(
+    ui: &mut egui::Ui,
+    state: &mut GuidedModeState,
+    localizer: &Localizer,
+    pipeline: &GenerationPipeline,
+) {
+    let Some(blend) = &state.blend_result else {
+        return;
+    };
+    let resolutions = &mut state.resolutions;
+    resolutions.poll();
+    if !resolutions.requested {
+        resolutions.request_suggestions(blend, pipeline);
+    }
+
+    ui.heading(localizer.t("resolution-title"));
+    ui.label(localizer.t("resolution-intro"));
+    if resolutions.is_loading() {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.label(localizer.t("resolution-asking"));
+        });
+    } else if let Some(error) = &resolutions.error {
+        ui.colored_label(
+            egui::Color32::from_rgb(255, 165, 0),
+            localizer.t_args(
+                "resolution-unavailable",
+                &[("error", error.as_str().into())],
+            ),
+        );
+    }
+    ui.separator();
+
+    for (index, choice) in resolutions.choices.iter_mut().enumerate() {
+        ui.group(|ui| {
+            ui.label(
+                egui::RichText::new(&choice.conflict.conflict_type)
+                    .strong()
+                    .color(egui::Color32::from_rgb(255, 100, 100)),
+            );
+            ui.label(&choice.conflict.description);
+            ui.push_id(index, |ui| {
+                for (option, suggestion) in choice.suggestions.iter().enumerate() {
+                    ui.radio_value(&mut choice.chosen, Some(option), suggestion);
+                }
+            });
+            if choice.from_ai {
+                ui.weak(localizer.t("resolution-from-ai"));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conflict(conflict_type: &str) -> Conflict {
+        Conflict {
+            game1: "Metroid".to_string(),
+            game2: "Final Fantasy".to_string(),
+            conflict_type: conflict_type.to_string(),
+            description: format!("{conflict_type} between the games"),
+            resolution: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_update_with_unchanged_conflicts_keeps_the_request() {
+        let conflicts = [conflict("Genre Conflict"), conflict("Era Gap")];
+        let mut state = ResolutionState::default();
+        state.update(&conflicts);
+        state.choices[1].chosen = Some(0);
+
+        let (_sender, receiver) = oneshot::channel();
+        state.requested = true;
+        state.pending = Some(receiver);
+
+        // A dragged weight slider re-blends every frame with the same conflicts
+        for _ in 0..3 {
+            state.update(&conflicts);
+        }
+        assert!(state.requested);
+        assert!(state.is_loading());
+        assert_eq!(state.choices.len(), 2);
+        assert_eq!(state.choices[1].chosen, Some(0));
+    }
+
+    #[test]
+    fn test_update_with_new_conflicts_asks_again() {
+        let mut state = ResolutionState::default();
+        state.update(&[conflict("Genre Conflict")]);
+        let (_sender, receiver) = oneshot::channel();
+        state.requested = true;
+        state.pending = Some(receiver);
+
+        state.update(&[conflict("Genre Conflict"), conflict("Era Gap")]);
+        assert!(!state.requested);
+        assert!(!state.is_loading());
+    }
+}
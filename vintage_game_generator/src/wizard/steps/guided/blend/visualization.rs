@@ -1,9 +1,15 @@
 use super::engine::create_blend;
+use crate::wizard::i18n::Localizer;
+use crate::wizard::steps::guided::preferences::render_rating_controls;
 use crate::wizard::steps::guided::types::GuidedModeState;
 use bevy_egui::egui;
 
 /// Render the blend visualization UI
-pub fn render_blend_visualization(ui: &mut egui::Ui, state: &mut GuidedModeState) {
+pub fn render_blend_visualization(
+    ui: &mut egui::Ui,
+    state: &mut GuidedModeState,
+    localizer: &Localizer,
+) {
     let mut clear_blend = false;
 
     if let Some(blend) = &state.blend_result {
@@ -18,23 +24,28 @@ pub fn render_blend_visualization(ui: &mut egui::Ui, state: &mut GuidedModeState
             ui.separator();
 
             // Genre distribution
-            ui.collapsing("Genre Distribution", |ui| {
+            ui.collapsing(localizer.t("blend-genres"), |ui| {
                 render_genre_chart(ui, &blend.genres);
             });
 
             // Mechanics
-            ui.collapsing("Combined Mechanics", |ui| {
+            ui.collapsing(localizer.t("blend-mechanics"), |ui| {
                 render_mechanics_cloud(ui, &blend.mechanics);
             });
 
             // Complexity and balance
-            ui.collapsing("Game Balance", |ui| {
-                render_balance_metrics(ui, blend.complexity_score, blend.action_strategy_balance);
+            ui.collapsing(localizer.t("blend-balance"), |ui| {
+                render_balance_metrics(
+                    ui,
+                    blend.complexity_score,
+                    blend.action_strategy_balance,
+                    localizer,
+                );
             });
 
             // Synergies
             if !blend.synergies.is_empty() {
-                ui.collapsing("✨ Synergies", |ui| {
+                ui.collapsing(localizer.t("blend-synergies"), |ui| {
                     for synergy in &blend.synergies {
                         ui.group(|ui| {
                             ui.label(format!("{} + {}", synergy.game1, synergy.game2));
@@ -50,10 +61,16 @@ pub fn render_blend_visualization(ui: &mut egui::Ui, state: &mut GuidedModeState
 
             // Conflicts
             if !blend.conflicts.is_empty() {
-                ui.collapsing("⚠️ Conflicts to Resolve", |ui| {
+                ui.collapsing(localizer.t("blend-conflicts"), |ui| {
                     for conflict in &blend.conflicts {
                         ui.group(|ui| {
-                            ui.label(format!("{} vs {}", conflict.game1, conflict.game2));
+                            ui.label(localizer.t_args(
+                                "blend-conflict-games",
+                                &[
+                                    ("first", conflict.game1.as_str().into()),
+                                    ("second", conflict.game2.as_str().into()),
+                                ],
+                            ));
                             ui.label(
                                 egui::RichText::new(&conflict.conflict_type)
                                     .small()
@@ -70,14 +87,14 @@ pub fn render_blend_visualization(ui: &mut egui::Ui, state: &mut GuidedModeState
             }
 
             // Recommendations
-            ui.collapsing("💡 Recommended Features", |ui| {
+            ui.collapsing(localizer.t("blend-features"), |ui| {
                 for feature in &blend.recommended_features {
                     ui.label(format!("• {feature}"));
                 }
             });
 
             // Art styles
-            ui.collapsing("🎨 Visual Style", |ui| {
+            ui.collapsing(localizer.t("blend-visual-style"), |ui| {
                 for style in &blend.art_styles {
                     ui.label(format!("• {style}"));
                 }
@@ -86,11 +103,11 @@ pub fn render_blend_visualization(ui: &mut egui::Ui, state: &mut GuidedModeState
             // Export button
             ui.separator();
             ui.horizontal(|ui| {
-                if ui.button("📥 Export Configuration").clicked() {
+                if ui.button(localizer.t("blend-export")).clicked() {
                     // Export will be handled by the export module
                 }
 
-                if ui.button("🔄 Modify Selection").clicked() {
+                if ui.button(localizer.t("blend-modify")).clicked() {
                     clear_blend = true;
                 }
             });
@@ -100,16 +117,21 @@ pub fn render_blend_visualization(ui: &mut egui::Ui, state: &mut GuidedModeState
         ui.vertical_centered(|ui| {
             ui.add_space(20.0);
             if ui
-                .button(egui::RichText::new("🧪 Create Blend").size(20.0))
+                .button(egui::RichText::new(localizer.t("blend-create")).size(20.0))
                 .clicked()
             {
                 create_blend(state);
             }
             ui.add_space(10.0);
-            ui.label("Click to analyze game compatibility and generate blend");
+            ui.label(localizer.t("blend-create-hint"));
         });
     }
 
+    // Rating controls need mutable access to the state
+    if state.blend_result.is_some() && !clear_blend {
+        ui.group(|ui| render_rating_controls(ui, state, localizer));
+    }
+
     // Apply deferred state changes
     if clear_blend {
         state.blend_result = None;
@@ -173,18 +195,33 @@ fn render_mechanics_cloud(ui: &mut egui::Ui, mechanics: &std::collections::HashS
 }
 
 /// Render balance metrics with visual bars
-fn render_balance_metrics(ui: &mut egui::Ui, complexity: f32, action_balance: f32) {
+fn render_balance_metrics(
+    ui: &mut egui::Ui,
+    complexity: f32,
+    action_balance: f32,
+    localizer: &Localizer,
+) {
     ui.group(|ui| {
         // Complexity meter
         ui.horizontal(|ui| {
-            ui.label("Complexity:");
-            render_meter(ui, complexity, "Simple", "Complex");
+            ui.label(localizer.t("blend-complexity"));
+            render_meter(
+                ui,
+                complexity,
+                &localizer.t("blend-simple"),
+                &localizer.t("blend-complex"),
+            );
         });
 
         // Action/Strategy balance
         ui.horizontal(|ui| {
-            ui.label("Gameplay:");
-            render_meter(ui, action_balance, "Strategic", "Action");
+            ui.label(localizer.t("blend-gameplay"));
+            render_meter(
+                ui,
+                action_balance,
+                &localizer.t("blend-strategic"),
+                &localizer.t("blend-action"),
+            );
         });
     });
 }
@@ -0,0 +1,185 @@
+//! Clusters of related games and blends across them
+//!
+//! The blending core's game graph groups the database into communities of
+//! closely related games. The panel lists them and suggests the most
+//! compatible pair of games of every two clusters, blends of games that
+//! rarely meet otherwise. Both are computed once per database revision.
+
+use super::blend::metadata::build_game_metadata;
+use super::types::GuidedModeState;
+use crate::vintage_games::{TimelineGame, all_games, database_revision};
+use crate::wizard::i18n::Localizer;
+use bevy_egui::egui;
+use std::collections::HashMap;
+use vintage_blending_core::graph::GameGraph;
+
+/// Clusters listed in the panel
+const MAX_CLUSTERS: usize = 6;
+
+/// Games named per cluster before the rest are counted
+const NAMED_GAMES: usize = 4;
+
+/// Cross-cluster blends suggested
+const MAX_BLENDS: usize = 5;
+
+/// The database's clusters and the blends suggested across them
+#[derive(Debug, Clone, Default)]
+pub struct GameClusters {
+    /// Database revision they were computed for
+    pub revision: u64,
+    /// Games of each cluster of two or more games, largest first, each
+    /// sorted by year
+    pub clusters: Vec<Vec<&'static TimelineGame>>,
+    /// Pairs of games of different clusters with their compatibility,
+    /// most compatible first
+    pub blends: Vec<(&'static TimelineGame, &'static TimelineGame, f32)>,
+}
+
+impl GameClusters {
+    /// Clusters and blends of the games in the database
+    pub fn compute() -> Self {
+        let revision = database_revision();
+        let games: HashMap<String, &'static TimelineGame> = all_games()
+            .into_iter()
+            .map(|game| (game.id.to_string(), game))
+            .collect();
+        let metadata = games
+            .iter()
+            .map(|(id, game)| (id.clone(), build_game_metadata(game)))
+            .collect();
+        let Ok(graph) = GameGraph::new(metadata) else {
+            return Self {
+                revision,
+                ..Self::default()
+            };
+        };
+
+        let communities = graph.communities();
+        let clusters = communities
+            .groups
+            .iter()
+            .filter(|group| group.len() > 1)
+            .map(|group| {
+                let mut cluster: Vec<_> = group
+                    .iter()
+                    .filter_map(|id| games.get(id))
+                    .copied()
+                    .collect();
+                cluster.sort_by_key(|game| (game.year, game.id));
+                cluster
+            })
+            .collect();
+        let blends = graph
+            .cross_community_blends(&communities, MAX_BLENDS)
+            .into_iter()
+            .filter_map(|(a, b, compatibility)| {
+                Some((
+                    *games.get(&a)?,
+                    *games.get(&b)?,
+                    compatibility.clamp(0.0, 1.0),
+                ))
+            })
+            .collect();
+
+        Self {
+            revision,
+            clusters,
+            blends,
+        }
+    }
+
+    pub fn is_current(&self) -> bool {
+        self.revision == database_revision()
+    }
+}
+
+/// Most common genre of a cluster, the earlier game's on a tie
+fn main_genre(cluster: &[&'static TimelineGame]) -> &'static str {
+    let mut counts: Vec<(&'static str, usize)> = Vec::new();
+    for game in cluster {
+        match counts.iter_mut().find(|(genre, _)| *genre == game.genre) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((game.genre, 1)),
+        }
+    }
+    counts
+        .iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map_or("", |(genre, _)| genre)
+}
+
+/// Clusters of the database and blends across them, a suggested blend
+/// picked with one click
+pub fn render_game_clusters(ui: &mut egui::Ui, state: &mut GuidedModeState, localizer: &Localizer) {
+    let mut to_add = None;
+    egui::CollapsingHeader::new(localizer.t("clusters-title"))
+        .id_salt("game_clusters")
+        .show(ui, |ui| {
+            if !state
+                .ui_state
+                .game_clusters
+                .as_ref()
+                .is_some_and(GameClusters::is_current)
+            {
+                state.ui_state.game_clusters = Some(GameClusters::compute());
+            }
+            let Some(clusters) = &state.ui_state.game_clusters else {
+                return;
+            };
+            if clusters.clusters.is_empty() {
+                ui.label(egui::RichText::new(localizer.t("clusters-none")).weak());
+                return;
+            }
+
+            for (index, cluster) in clusters.clusters.iter().take(MAX_CLUSTERS).enumerate() {
+                let mut names: Vec<&str> = cluster
+                    .iter()
+                    .take(NAMED_GAMES)
+                    .map(|game| game.name)
+                    .collect();
+                let more = cluster.len().saturating_sub(NAMED_GAMES);
+                let more = (more > 0)
+                    .then(|| localizer.t_args("clusters-more", &[("count", more.into())]));
+                names.extend(more.as_deref());
+                ui.label(
+                    egui::RichText::new(localizer.t_args(
+                        "clusters-cluster",
+                        &[
+                            ("number", (index + 1).into()),
+                            ("genre", main_genre(cluster).into()),
+                            ("count", cluster.len().into()),
+                        ],
+                    ))
+                    .strong(),
+                );
+                ui.label(egui::RichText::new(names.join(", ")).small());
+            }
+
+            if clusters.blends.is_empty() {
+                return;
+            }
+            ui.separator();
+            ui.label(localizer.t("clusters-blends"));
+            ui.horizontal_wrapped(|ui| {
+                for (a, b, compatibility) in &clusters.blends {
+                    let response = ui
+                        .button(format!("🔀 {} × {}", a.name, b.name))
+                        .on_hover_text(localizer.t_args(
+                            "clusters-blend-hint",
+                            &[("percent", (compatibility * 100.0).round().into())],
+                        ));
+                    if response.clicked() {
+                        to_add = Some((*a, *b));
+                    }
+                }
+            });
+        });
+
+    if let Some((a, b)) = to_add {
+        state.selected_games.insert(a.id, a);
+        state.selected_games.insert(b.id, b);
+        state.ui_state.similar_to = Some(b.id);
+        state.blend_result = None;
+    }
+}
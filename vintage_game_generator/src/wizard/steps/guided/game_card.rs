@@ -1,5 +1,6 @@
 use super::types::{GameCardStyle, GuidedModeState};
 use crate::vintage_games::TimelineGame;
+use crate::wizard::i18n::Localizer;
 use bevy_egui::egui;
 
 /// Render a detailed game card for the selection panel
@@ -8,6 +9,7 @@ pub fn render_game_card(
     game: &'static TimelineGame,
     state: &mut GuidedModeState,
     can_remove: bool,
+    localizer: &Localizer,
 ) -> bool {
     let mut removed = false;
     let style = GameCardStyle::default();
@@ -66,7 +68,7 @@ pub fn render_game_card(
         if !game.platforms.is_empty() {
             ui.separator();
             ui.horizontal_wrapped(|ui| {
-                ui.label("Platforms:");
+                ui.label(localizer.t("card-platforms"));
                 for platform in game.platforms {
                     ui.label(
                         egui::RichText::new(&**platform)
@@ -82,30 +84,38 @@ pub fn render_game_card(
 }
 
 /// Render the selected games panel
-pub fn render_selected_games(ui: &mut egui::Ui, state: &mut GuidedModeState) {
+pub fn render_selected_games(
+    ui: &mut egui::Ui,
+    state: &mut GuidedModeState,
+    localizer: &Localizer,
+) {
     ui.group(|ui| {
-        ui.heading("📚 Selected Games");
+        ui.heading(localizer.t("card-selected-title"));
 
         if state.selected_games.is_empty() {
             ui.separator();
             ui.vertical_centered(|ui| {
                 ui.add_space(20.0);
                 ui.label(
-                    egui::RichText::new("No games selected yet")
+                    egui::RichText::new(localizer.t("card-none-selected"))
                         .size(16.0)
                         .color(egui::Color32::from_gray(150)),
                 );
                 ui.add_space(10.0);
-                ui.label("Click on games from the timeline to add them");
-                ui.label("Select at least 2 games to create a blend");
+                ui.label(localizer.t("card-selection-hint"));
             });
         } else {
             ui.horizontal(|ui| {
-                ui.label(format!("{} games selected", state.selected_games.len()));
+                ui.label(localizer.t_args(
+                    "card-selected-count",
+                    &[("count", state.selected_games.len().into())],
+                ));
 
                 if state.selected_games.len() >= 2 {
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                        if state.blend_result.is_some() && ui.button("🔄 Re-blend").clicked() {
+                        if state.blend_result.is_some()
+                            && ui.button(localizer.t("card-reblend")).clicked()
+                        {
                             state.blend_result = None;
                         }
                     });
@@ -179,7 +189,7 @@ pub fn render_selected_games(ui: &mut egui::Ui, state: &mut GuidedModeState) {
                                 if !game.platforms.is_empty() {
                                     ui.separator();
                                     ui.horizontal_wrapped(|ui| {
-                                        ui.label("Platforms:");
+                                        ui.label(localizer.t("card-platforms"));
                                         for platform in game.platforms {
                                             ui.label(
                                                 egui::RichText::new(&**platform)
@@ -212,13 +222,13 @@ pub fn render_selected_games(ui: &mut egui::Ui, state: &mut GuidedModeState) {
 }
 
 /// Render game attributes for blending visualization
-pub fn render_game_attributes(ui: &mut egui::Ui, game: &TimelineGame) {
+pub fn render_game_attributes(ui: &mut egui::Ui, game: &TimelineGame, localizer: &Localizer) {
     ui.group(|ui| {
         ui.label(egui::RichText::new(game.name).strong());
 
         // Genre badge
         ui.horizontal(|ui| {
-            ui.label("Genre:");
+            ui.label(localizer.t("card-genre"));
             ui.label(
                 egui::RichText::new(game.genre)
                     .background_color(egui::Color32::from_rgb(70, 130, 180))
@@ -228,22 +238,22 @@ pub fn render_game_attributes(ui: &mut egui::Ui, game: &TimelineGame) {
 
         // Year era
         let era = match game.year {
-            1980..=1983 => "Arcade Golden Age",
-            1984..=1987 => "Early Console",
-            1988..=1991 => "8-bit/16-bit Transition",
-            1992..=1995 => "16-bit Peak",
-            _ => "Unknown Era",
+            1980..=1983 => "card-era-arcade",
+            1984..=1987 => "card-era-early-console",
+            1988..=1991 => "card-era-transition",
+            1992..=1995 => "card-era-16-bit-peak",
+            _ => "card-era-unknown",
         };
 
         ui.horizontal(|ui| {
-            ui.label("Era:");
-            ui.label(egui::RichText::new(era).italics().small());
+            ui.label(localizer.t("card-era"));
+            ui.label(egui::RichText::new(localizer.t(era)).italics().small());
         });
 
         // Complexity estimate based on year and genre
         let complexity = estimate_game_complexity(game);
         ui.horizontal(|ui| {
-            ui.label("Complexity:");
+            ui.label(localizer.t("card-complexity"));
             render_complexity_bar(ui, complexity);
         });
     });
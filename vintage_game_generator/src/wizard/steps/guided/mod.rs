@@ -1,25 +1,46 @@
 // Re-export the comprehensive implementation modules
 pub mod blend;
+pub mod clusters;
 pub mod game_card;
+pub mod preferences;
+pub mod similar;
+pub mod stats;
 pub mod timeline;
 pub mod types;
 
 // Re-export key types and functions
 pub use blend::{
-    create_blend, export_blend_to_config, render_blend_ui, render_blend_visualization,
-    render_export_ui,
+    create_blend, create_surprise_blend, export_blend_to_config, render_blend_ui,
+    render_blend_visualization, render_conflict_resolution, render_explain_button,
+    render_explanation_window, render_export_ui,
 };
+pub use clusters::{GameClusters, render_game_clusters};
 pub use game_card::render_game_card;
+pub use preferences::{render_preference_settings, render_rating_controls, render_suggestions};
+pub use similar::{SimilarGames, render_similar_games};
+pub use stats::{TimelineStats, render_stats_dashboard};
 pub use timeline::render_timeline;
-pub use types::{BlendResult, Conflict, GuidedModeExport, GuidedModeState, SourceGame, Synergy};
+pub use types::{
+    BlendResult, Conflict, ConflictResolution, GuidedModeExport, GuidedModeState, SourceGame,
+    Synergy,
+};
 
+use crate::vintage_games::surprise::project_surprise_seed;
+use crate::wizard::AppDirectories;
+use crate::wizard::i18n::Localizer;
+use crate::wizard::pipeline::GenerationPipeline;
 use crate::wizard::state::{AppState, WizardStep};
+use crate::wizard::tutorial::{
+    ANCHOR_BLEND, ANCHOR_BLEND_RESULT, ANCHOR_EXPORT, ANCHOR_TIMELINE, mark_anchor,
+};
 use bevy::prelude::*;
 use bevy_egui::{EguiContexts, egui};
 
 /// Set up guided mode resources when entering this step
-pub fn setup_guided_mode(mut commands: Commands) {
-    commands.insert_resource(GuidedModeState::default());
+pub fn setup_guided_mode(mut commands: Commands, directories: &AppDirectories) {
+    let mut state = GuidedModeState::default();
+    preferences::load_preferences(&mut state, &directories.base_dir);
+    commands.insert_resource(state);
 }
 
 /// Clean up guided mode resources when leaving this step
@@ -32,17 +53,23 @@ pub fn render_guided_mode(
     mut contexts: EguiContexts,
     mut app_state: ResMut<AppState>,
     mut guided_state: ResMut<GuidedModeState>,
+    localizer: &Localizer,
+    pipeline: &GenerationPipeline,
 ) {
     let Ok(ctx) = contexts.ctx_mut() else {
         return;
     };
+    let generation_seed = app_state
+        .config_manager
+        .as_ref()
+        .map(|manager| manager.config.generation_seed);
 
     egui::CentralPanel::default().show(ctx, |ui| {
         // Header
         ui.horizontal(|ui| {
-            ui.heading("🎮 Guided Mode - Blend Vintage Games");
+            ui.heading(localizer.t("guided-title"));
             ui.separator();
-            ui.label(app_state.get_step_title());
+            ui.label(localizer.t(app_state.step_title_id()));
         });
         ui.separator();
 
@@ -60,44 +87,115 @@ pub fn render_guided_mode(
             match guided_state.current_step {
                 0 => {
                     // Timeline browsing
-                    ui.label("Browse games by decade and select ones to blend:");
+                    ui.horizontal(|ui| {
+                        ui.label(localizer.t("guided-browse-hint"));
+                        ui.toggle_value(
+                            &mut guided_state.ui_state.show_stats,
+                            localizer.t("guided-era-stats"),
+                        );
+                        render_surprise_controls(ui, &mut guided_state, generation_seed, localizer);
+                    });
                     ui.separator();
 
-                    render_timeline(ui, &mut guided_state);
+                    if guided_state.ui_state.show_stats {
+                        render_stats_dashboard(ui, &mut guided_state, localizer);
+                        ui.separator();
+                    }
+
+                    let timeline = ui.scope(|ui| render_timeline(ui, &mut guided_state, localizer));
+                    mark_anchor(ui.ctx(), ANCHOR_TIMELINE, timeline.response.rect);
+                    render_similar_games(ui, &mut guided_state, localizer);
+                    render_game_clusters(ui, &mut guided_state, localizer);
+                    render_suggestions(ui, &mut guided_state, localizer);
+                    render_preference_settings(ui, &mut guided_state, localizer);
 
                     // Show selected games count
                     if !guided_state.selected_games.is_empty() {
                         ui.separator();
                         ui.horizontal(|ui| {
-                            ui.label(format!(
-                                "Selected {} games",
-                                guided_state.selected_games.len()
+                            ui.label(localizer.t_args(
+                                "guided-selected-count",
+                                &[("count", guided_state.selected_games.len().into())],
                             ));
 
                             if guided_state.selected_games.len() >= 2 {
-                                if ui.button("🔀 Blend Games").clicked() {
-                                    // Create the blend
+                                let blend = ui.button(localizer.t("guided-blend"));
+                                mark_anchor(ui.ctx(), ANCHOR_BLEND, blend.rect);
+                                if blend.clicked() {
+                                    // Create the blend, then settle its
+                                    // conflicts if it has any
                                     create_blend(&mut guided_state);
-                                    guided_state.current_step = 1;
+                                    guided_state.current_step =
+                                        if guided_state.resolutions.is_empty() {
+                                            2
+                                        } else {
+                                            1
+                                        };
                                 }
                             } else {
-                                ui.label("(Select at least 2 games to blend)");
+                                ui.label(localizer.t("guided-blend-needs-two"));
                             }
                         });
                     }
                 }
                 1 => {
+                    // Conflict resolution
+                    if guided_state.blend_result.is_some() {
+                        render_conflict_resolution(ui, &mut guided_state, localizer, pipeline);
+
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            if ui.button(localizer.t("guided-back-to-selection")).clicked() {
+                                guided_state.current_step = 0;
+                                guided_state.blend_result = None;
+                            }
+
+                            let resolved = guided_state.resolutions.is_resolved();
+                            if ui
+                                .add_enabled(
+                                    resolved,
+                                    egui::Button::new(localizer.t("guided-continue")),
+                                )
+                                .on_disabled_hover_text(localizer.t("guided-continue-unresolved"))
+                                .clicked()
+                            {
+                                guided_state.current_step = 2;
+                            }
+                            if !resolved
+                                && ui.button(localizer.t("guided-skip-resolution")).clicked()
+                            {
+                                guided_state.current_step = 2;
+                            }
+                        });
+                    } else {
+                        // No blend result, go back
+                        guided_state.current_step = 0;
+                    }
+                }
+                2 => {
                     // Blend visualization and export
                     if guided_state.blend_result.is_some() {
-                        render_blend_ui(ui, &mut guided_state);
+                        let blend =
+                            ui.scope(|ui| render_blend_ui(ui, &mut guided_state, localizer));
+                        mark_anchor(ui.ctx(), ANCHOR_BLEND_RESULT, blend.response.rect);
 
                         ui.separator();
-                        if ui.button("⬅ Back to Selection").clicked() {
+                        if ui.button(localizer.t("guided-back-to-selection")).clicked() {
                             guided_state.current_step = 0;
                             guided_state.blend_result = None;
                         }
 
-                        if ui.button("✅ Export Configuration").clicked()
+                        if !guided_state.resolutions.is_empty()
+                            && ui.button(localizer.t("guided-resolve-conflicts")).clicked()
+                        {
+                            guided_state.current_step = 1;
+                        }
+
+                        render_explain_button(ui, &mut guided_state, localizer, pipeline);
+
+                        let export = ui.button(localizer.t("guided-export"));
+                        mark_anchor(ui.ctx(), ANCHOR_EXPORT, export.rect);
+                        if export.clicked()
                             && let Some(export) = export_blend_to_config(&guided_state)
                         {
                             // Store the export in app state
@@ -110,7 +208,7 @@ pub fn render_guided_mode(
                     }
                 }
                 _ => {
-                    ui.label("Invalid step");
+                    ui.label(localizer.t("guided-invalid-step"));
                 }
             }
 
@@ -118,14 +216,17 @@ pub fn render_guided_mode(
             if !guided_state.selected_games.is_empty() {
                 ui.separator();
                 let mut games_to_remove = Vec::new();
-                egui::CollapsingHeader::new("Selected Games")
+                let mut weights_changed = false;
+                egui::CollapsingHeader::new(localizer.t("guided-selected-games"))
                     .default_open(true)
                     .show(ui, |ui| {
-                        let game_list: Vec<(u32, String, i32)> = guided_state
+                        let mut game_list: Vec<(u32, String, i32)> = guided_state
                             .selected_games
                             .iter()
                             .map(|(id, game)| (*id, game.name.to_string(), game.year))
                             .collect();
+                        game_list.sort_by(|a, b| a.1.cmp(&b.1));
+                        let shares = guided_state.game_shares();
 
                         for (id, name, year) in game_list {
                             ui.horizontal(|ui| {
@@ -134,6 +235,21 @@ pub fn render_guided_mode(
                                 }
                                 ui.label(&name);
                                 ui.label(format!("({year})"));
+
+                                // Weight of the game, with the share of the
+                                // blend it works out to
+                                let mut weight = guided_state.game_weight(id);
+                                let response = ui
+                                    .add(egui::Slider::new(&mut weight, 0.0..=100.0).suffix("%"))
+                                    .on_hover_text(localizer.t("guided-weight-hint"));
+                                if response.changed() {
+                                    guided_state.game_weights.insert(id, weight);
+                                    weights_changed = true;
+                                }
+                                ui.weak(localizer.t_args(
+                                    "guided-blend-share",
+                                    &[("percent", (shares[&id] * 100.0).round().into())],
+                                ));
                             });
                         }
                     });
@@ -142,6 +258,14 @@ pub fn render_guided_mode(
                 for id in games_to_remove {
                     guided_state.toggle_game_selection(id);
                 }
+
+                // Reweigh the blend on screen
+                if weights_changed
+                    && guided_state.blend_result.is_some()
+                    && guided_state.selected_games.len() >= 2
+                {
+                    create_blend(&mut guided_state);
+                }
             }
         });
 
@@ -149,17 +273,60 @@ pub fn render_guided_mode(
 
         // Navigation
         ui.horizontal(|ui| {
-            if app_state.can_go_back() && ui.button("← Back").clicked() {
+            if app_state.can_go_back() && ui.button(localizer.t("nav-back")).clicked() {
                 app_state.go_back();
                 // Cleanup is handled by the cleanup_guided_mode system
                 // We just need to switch the wizard step
             }
 
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                if ui.button("Exit").clicked() {
+                if ui.button(localizer.t("nav-exit")).clicked() {
                     app_state.show_exit_dialog = true;
                 }
+                if ui.button(localizer.t("nav-settings")).clicked() {
+                    app_state.show_settings = !app_state.show_settings;
+                }
             });
         });
     });
+
+    // Conversation about the blend, kept open while the blend is reworked
+    render_explanation_window(ctx, &mut guided_state, localizer, pipeline);
+}
+
+/// "Surprise me" button, which blends a random pick of games and shows the
+/// blend right away, with the seed to replay the pick with. Unless the seed
+/// is fixed, the picks follow from the project's generation seed.
+fn render_surprise_controls(
+    ui: &mut egui::Ui,
+    state: &mut GuidedModeState,
+    generation_seed: Option<u64>,
+    localizer: &Localizer,
+) {
+    ui.separator();
+    if ui
+        .button(localizer.t("surprise-button"))
+        .on_hover_text(localizer.t("surprise-hint"))
+        .clicked()
+    {
+        if !state.ui_state.fixed_surprise_seed {
+            state.ui_state.surprise_seed = match generation_seed {
+                Some(seed) => project_surprise_seed(seed, state.ui_state.surprise_draws),
+                None => rand::random(),
+            };
+            state.ui_state.surprise_draws += 1;
+        }
+        if create_surprise_blend(state, state.ui_state.surprise_seed) {
+            state.current_step = 2;
+        }
+    }
+    ui.checkbox(
+        &mut state.ui_state.fixed_surprise_seed,
+        localizer.t("surprise-seed"),
+    )
+    .on_hover_text(localizer.t("surprise-seed-hint"));
+    ui.add_enabled(
+        state.ui_state.fixed_surprise_seed,
+        egui::DragValue::new(&mut state.ui_state.surprise_seed),
+    );
 }
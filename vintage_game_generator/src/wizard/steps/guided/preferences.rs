@@ -0,0 +1,279 @@
+//! Personal blend preferences learned from thumbs-up/down ratings
+//!
+//! Ratings are stored in the base directory so they persist across projects.
+//! Once both liked and disliked blends exist, the fitted model re-ranks
+//! suggested additions to the current selection. The games' metadata is
+//! built once per database revision and the ranking once per selection and
+//! set of ratings.
+
+use super::blend::metadata::build_game_metadata;
+use super::types::GuidedModeState;
+use crate::vintage_games::{TimelineGame, all_games, database_revision};
+use crate::wizard::i18n::Localizer;
+use bevy::prelude::*;
+use bevy_egui::egui;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use vintage_blending_core::rating::RatingModel;
+use vintage_blending_core::{FeatureVector, GameMetadata};
+
+/// File name of the rating store inside the base directory
+pub const RATINGS_FILE: &str = "blend_ratings.json";
+
+/// Number of suggested additions shown at once
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Metadata of the database's games and the suggestions ranked from it
+#[derive(Debug, Clone, Default)]
+pub struct SuggestionCache {
+    /// Database revision the metadata was built for
+    pub revision: u64,
+    /// Metadata of every game by ID
+    pub metadata: HashMap<u32, GameMetadata>,
+    /// Sorted IDs of the selection the ranking was made for
+    pub selection: Vec<u32>,
+    /// Games outside the selection with their score, best first. `None`
+    /// once the ratings changed.
+    pub ranked: Option<Vec<(&'static TimelineGame, f32)>>,
+}
+
+impl SuggestionCache {
+    /// Drop the ranking, after the ratings changed
+    pub fn invalidate_ranking(&mut self) {
+        self.ranked = None;
+    }
+}
+
+/// Load the rating model for the given base directory into the guided state
+pub fn load_preferences(state: &mut GuidedModeState, base_dir: &Path) {
+    let path = base_dir.join(RATINGS_FILE);
+    state.ui_state.rating_model = RatingModel::load(&path).unwrap_or_else(|e| {
+        warn!("Failed to load blend ratings from {}: {e}", path.display());
+        RatingModel::default()
+    });
+    state.ui_state.ratings_path = Some(path);
+    state.ui_state.suggestions.invalidate_ranking();
+}
+
+/// Games to add to the selection, ranked by the rating model, rebuilding
+/// only what the database, the selection or the ratings changed
+pub fn ranked_suggestions(state: &mut GuidedModeState) -> &[(&'static TimelineGame, f32)] {
+    let cache = &mut state.ui_state.suggestions;
+    let revision = database_revision();
+    if cache.revision != revision || cache.metadata.is_empty() {
+        cache.metadata = all_games()
+            .into_iter()
+            .map(|game| (game.id, build_game_metadata(game)))
+            .collect();
+        cache.revision = revision;
+        cache.ranked = None;
+    }
+
+    let mut selection: Vec<u32> = state.selected_games.keys().copied().collect();
+    selection.sort_unstable();
+    if cache.selection != selection {
+        cache.selection = selection;
+        cache.ranked = None;
+    }
+
+    let metadata = &cache.metadata;
+    let selected_games = &state.selected_games;
+    let model = &state.ui_state.rating_model;
+    cache.ranked.get_or_insert_with(|| {
+        let selected: Vec<&FeatureVector> = selected_games
+            .keys()
+            .filter_map(|id| metadata.get(id))
+            .map(|m| &m.feature_vector)
+            .collect();
+        model.rank(
+            all_games()
+                .into_iter()
+                .filter(|game| !selected_games.contains_key(&game.id))
+                .filter_map(|game| {
+                    let mut vectors = selected.clone();
+                    vectors.push(&metadata.get(&game.id)?.feature_vector);
+                    Some((game, vectors))
+                })
+                .collect(),
+        )
+    })
+}
+
+/// Record a rating for the selected games and persist it
+fn record_rating(state: &mut GuidedModeState, subject: &str, liked: bool) {
+    let games: Vec<&TimelineGame> = state.selected_games.values().copied().collect();
+    let metadata: Vec<_> = games.iter().map(|g| build_game_metadata(g)).collect();
+    let vectors: Vec<&FeatureVector> = metadata.iter().map(|m| &m.feature_vector).collect();
+    let game_ids = games.iter().map(|g| g.id.to_string()).collect();
+
+    state
+        .ui_state
+        .rating_model
+        .rate(game_ids, subject, &vectors, liked);
+    state.ui_state.suggestions.invalidate_ranking();
+    save_preferences(state);
+}
+
+fn save_preferences(state: &GuidedModeState) {
+    if let Some(path) = &state.ui_state.ratings_path
+        && let Err(e) = state.ui_state.rating_model.save(path)
+    {
+        warn!("Failed to save blend ratings to {}: {e}", path.display());
+    }
+}
+
+/// Thumbs-up/down controls for the current blend and its generated concept
+pub fn render_rating_controls(
+    ui: &mut egui::Ui,
+    state: &mut GuidedModeState,
+    localizer: &Localizer,
+) {
+    let mut rating = None;
+
+    for (subject, label) in [("blend", "rating-blend"), ("concept", "rating-concept")] {
+        ui.horizontal(|ui| {
+            ui.label(localizer.t(label));
+            if ui
+                .small_button("👍")
+                .on_hover_text(localizer.t("rating-more"))
+                .clicked()
+            {
+                rating = Some((subject, true));
+            }
+            if ui
+                .small_button("👎")
+                .on_hover_text(localizer.t("rating-less"))
+                .clicked()
+            {
+                rating = Some((subject, false));
+            }
+        });
+    }
+
+    if let Some((subject, liked)) = rating {
+        record_rating(state, subject, liked);
+    }
+
+    let count = state.ui_state.rating_model.ratings.len();
+    if count > 0 {
+        ui.label(
+            egui::RichText::new(localizer.t_args("rating-recorded", &[("count", count.into())]))
+                .small()
+                .weak(),
+        );
+    }
+}
+
+/// Suggest games to add to the selection, ranked by the user's taste
+pub fn render_suggestions(ui: &mut egui::Ui, state: &mut GuidedModeState, localizer: &Localizer) {
+    if !state.ui_state.rating_model.is_trained() || state.selected_games.is_empty() {
+        return;
+    }
+    let ranked: Vec<_> = ranked_suggestions(state)
+        .iter()
+        .take(MAX_SUGGESTIONS)
+        .copied()
+        .collect();
+
+    let mut to_add = None;
+    egui::CollapsingHeader::new(localizer.t("suggestions-title"))
+        .default_open(true)
+        .show(ui, |ui| {
+            for (game, score) in ranked {
+                ui.horizontal(|ui| {
+                    if ui.small_button("➕").clicked() {
+                        to_add = Some(game);
+                    }
+                    ui.label(format!("{} ({})", game.name, game.year));
+                    ui.label(
+                        egui::RichText::new(localizer.t_args(
+                            "suggestions-match",
+                            &[("percent", (score * 100.0).round().into())],
+                        ))
+                        .small()
+                        .weak(),
+                    );
+                });
+            }
+        });
+
+    if let Some(game) = to_add {
+        state.selected_games.insert(game.id, game);
+    }
+}
+
+/// Export and reset controls for stored ratings
+pub fn render_preference_settings(
+    ui: &mut egui::Ui,
+    state: &mut GuidedModeState,
+    localizer: &Localizer,
+) {
+    egui::CollapsingHeader::new(localizer.t("preferences-title"))
+        .default_open(false)
+        .show(ui, |ui| {
+            let model = &state.ui_state.rating_model;
+            let liked = model.ratings.iter().filter(|r| r.liked).count();
+            ui.label(localizer.t_args(
+                "preferences-ratings",
+                &[
+                    ("count", model.ratings.len().into()),
+                    ("liked", liked.into()),
+                    ("disliked", (model.ratings.len() - liked).into()),
+                ],
+            ));
+            if !model.is_trained() {
+                ui.label(
+                    egui::RichText::new(localizer.t("preferences-untrained"))
+                        .small()
+                        .weak(),
+                );
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button(localizer.t("preferences-export")).clicked() {
+                    match export_preferences(state) {
+                        Ok(path) => {
+                            state.ui_state.preferences_status = Some(localizer.t_args(
+                                "preferences-exported",
+                                &[("path", path.display().to_string().into())],
+                            ))
+                        }
+                        Err(e) => {
+                            state.ui_state.preferences_status = Some(localizer.t_args(
+                                "preferences-export-failed",
+                                &[("error", e.to_string().into())],
+                            ))
+                        }
+                    }
+                }
+
+                if ui.button(localizer.t("preferences-reset")).clicked() {
+                    state.ui_state.rating_model.reset();
+                    state.ui_state.suggestions.invalidate_ranking();
+                    save_preferences(state);
+                    state.ui_state.preferences_status = Some(localizer.t("preferences-cleared"));
+                }
+            });
+
+            if let Some(status) = &state.ui_state.preferences_status {
+                ui.label(egui::RichText::new(status).small());
+            }
+        });
+}
+
+/// Write a timestamped copy of the ratings next to the rating store
+fn export_preferences(state: &GuidedModeState) -> anyhow::Result<PathBuf> {
+    let dir = state
+        .ui_state
+        .ratings_path
+        .as_ref()
+        .and_then(|p| p.parent())
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let path = dir.join(format!(
+        "blend_ratings_{}.json",
+        chrono::Local::now().format("%Y%m%d_%H%M%S")
+    ));
+    state.ui_state.rating_model.save(&path)?;
+    Ok(path)
+}
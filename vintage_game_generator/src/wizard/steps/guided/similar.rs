@@ -0,0 +1,103 @@
+//! "Similar games" strip for the game picked last
+//!
+//! Similarities come from the blending core's `SimilarityEngine` over the
+//! games' feature vectors, semantic embeddings included once games carry
+//! them. They are computed once per picked game and kept until another one
+//! is picked.
+
+use super::types::GuidedModeState;
+use crate::vintage_games::{TimelineGame, graph::find_similar_games};
+use crate::wizard::i18n::Localizer;
+use bevy_egui::egui;
+
+/// Similar games shown in the strip
+const MAX_SIMILAR: usize = 8;
+
+/// Games similar to the one picked last, best match first, with the
+/// similarity they have to it
+#[derive(Debug, Clone)]
+pub struct SimilarGames {
+    pub game_id: u32,
+    pub games: Vec<(&'static TimelineGame, f32)>,
+}
+
+impl SimilarGames {
+    pub fn for_game(game_id: u32, count: usize) -> Self {
+        // Rounding can push identical games a hair past 1
+        let games = find_similar_games(game_id, count)
+            .into_iter()
+            .map(|(game, score)| (game, score.clamp(0.0, 1.0)))
+            .collect();
+        Self { game_id, games }
+    }
+}
+
+/// Strip of games similar to the one picked last, each added to the blend
+/// with one click
+pub fn render_similar_games(ui: &mut egui::Ui, state: &mut GuidedModeState, localizer: &Localizer) {
+    let Some(game_id) = state.ui_state.similar_to else {
+        return;
+    };
+    let Some(&focus) = state.selected_games.get(&game_id) else {
+        return;
+    };
+
+    // Ask for enough games that the selected ones can be left out
+    if state
+        .ui_state
+        .similar_games
+        .as_ref()
+        .is_none_or(|similar| similar.game_id != game_id)
+    {
+        state.ui_state.similar_games = Some(SimilarGames::for_game(
+            game_id,
+            MAX_SIMILAR + state.selected_games.len(),
+        ));
+    }
+    let Some(similar) = &state.ui_state.similar_games else {
+        return;
+    };
+
+    let candidates: Vec<(&'static TimelineGame, f32)> = similar
+        .games
+        .iter()
+        .filter(|(game, _)| !state.selected_games.contains_key(&game.id))
+        .take(MAX_SIMILAR)
+        .copied()
+        .collect();
+    if candidates.is_empty() {
+        return;
+    }
+
+    let mut to_add = None;
+    egui::CollapsingHeader::new(localizer.t_args("similar-title", &[("game", focus.name.into())]))
+        .id_salt("similar_games")
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::ScrollArea::horizontal()
+                .id_salt("similar_games_scroll")
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        for (game, score) in candidates {
+                            let response = ui
+                                .button(format!("➕ {} ({})", game.name, game.year))
+                                .on_hover_text(localizer.t_args(
+                                    "similar-score",
+                                    &[
+                                        ("genre", game.genre.into()),
+                                        ("percent", (score * 100.0).round().into()),
+                                    ],
+                                ));
+                            if response.clicked() {
+                                to_add = Some(game);
+                            }
+                        }
+                    });
+                });
+        });
+
+    if let Some(game) = to_add {
+        state.selected_games.insert(game.id, game);
+        state.blend_result = None;
+    }
+}
@@ -0,0 +1,222 @@
+//! Era statistics dashboard over the timeline dataset
+//!
+//! Gives context when picking blend sources: how genres are distributed per
+//! year, which platforms were active when, and how inferred mechanics spread
+//! through the timeline. The statistics are computed again whenever the
+//! database changes.
+
+use super::blend::metadata::build_game_metadata;
+use super::types::GuidedModeState;
+use crate::vintage_games::{TimelineGame, all_games, database_revision, timeline_span};
+use crate::wizard::i18n::Localizer;
+use bevy_egui::egui;
+use egui_plot::{Bar, BarChart, Legend, Line, Plot, PlotPoints};
+use std::collections::{BTreeMap, HashMap};
+
+/// Number of mechanics shown in the prevalence chart
+const TOP_MECHANICS: usize = 6;
+
+/// Aggregated statistics over a set of timeline games
+#[derive(Debug, Clone, Default)]
+pub struct TimelineStats {
+    /// Database revision the statistics were computed for, by
+    /// [`from_timeline`](Self::from_timeline)
+    pub revision: u64,
+    /// Genre -> year -> number of games
+    pub genre_counts: BTreeMap<String, BTreeMap<i32, usize>>,
+    /// Platform -> (first year, last year, game count)
+    pub platform_lifecycles: BTreeMap<String, (i32, i32, usize)>,
+    /// Mechanic -> year -> share of that year's games using it
+    pub mechanic_prevalence: BTreeMap<String, BTreeMap<i32, f32>>,
+    /// Number of games per year
+    pub games_per_year: BTreeMap<i32, usize>,
+}
+
+impl TimelineStats {
+    /// Compute statistics over the whole timeline
+    pub fn from_timeline() -> Self {
+        Self {
+            revision: database_revision(),
+            ..Self::from_games(all_games())
+        }
+    }
+
+    /// Whether the statistics are of the database as it is now
+    pub fn is_current(&self) -> bool {
+        self.revision == database_revision()
+    }
+
+    /// Compute statistics over an arbitrary set of games
+    pub fn from_games<'a>(games: impl IntoIterator<Item = &'a TimelineGame>) -> Self {
+        let mut stats = Self::default();
+        let mut mechanic_counts: HashMap<String, BTreeMap<i32, usize>> = HashMap::new();
+
+        for game in games {
+            *stats.games_per_year.entry(game.year).or_default() += 1;
+
+            *stats
+                .genre_counts
+                .entry(game.genre.to_string())
+                .or_default()
+                .entry(game.year)
+                .or_default() += 1;
+
+            for platform in game.platforms {
+                let entry = stats
+                    .platform_lifecycles
+                    .entry(platform.to_string())
+                    .or_insert((game.year, game.year, 0));
+                entry.0 = entry.0.min(game.year);
+                entry.1 = entry.1.max(game.year);
+                entry.2 += 1;
+            }
+
+            let metadata = build_game_metadata(game);
+            for mechanic in metadata.mechanic_tags {
+                *mechanic_counts
+                    .entry(mechanic)
+                    .or_default()
+                    .entry(game.year)
+                    .or_default() += 1;
+            }
+        }
+
+        // Keep the most common mechanics, normalized by games per year
+        let mut totals: Vec<(String, usize)> = mechanic_counts
+            .iter()
+            .map(|(name, years)| (name.clone(), years.values().sum()))
+            .collect();
+        totals.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        for (name, _) in totals.into_iter().take(TOP_MECHANICS) {
+            let years = &mechanic_counts[&name];
+            let prevalence = years
+                .iter()
+                .map(|(year, count)| {
+                    let total = stats.games_per_year.get(year).copied().unwrap_or(1);
+                    (*year, *count as f32 / total as f32)
+                })
+                .collect();
+            stats.mechanic_prevalence.insert(name, prevalence);
+        }
+
+        stats
+    }
+}
+
+/// Render the statistics dashboard
+pub fn render_stats_dashboard(
+    ui: &mut egui::Ui,
+    state: &mut GuidedModeState,
+    localizer: &Localizer,
+) {
+    if !state
+        .ui_state
+        .timeline_stats
+        .as_ref()
+        .is_some_and(TimelineStats::is_current)
+    {
+        state.ui_state.timeline_stats = Some(TimelineStats::from_timeline());
+    }
+    let Some(stats) = &state.ui_state.timeline_stats else {
+        return;
+    };
+
+    ui.group(|ui| {
+        ui.heading(localizer.t("stats-title"));
+        ui.separator();
+
+        egui::CollapsingHeader::new(localizer.t("stats-genres"))
+            .default_open(true)
+            .show(ui, |ui| render_genre_chart(ui, stats));
+
+        egui::CollapsingHeader::new(localizer.t("stats-platforms"))
+            .default_open(false)
+            .show(ui, |ui| render_platform_chart(ui, stats, localizer));
+
+        egui::CollapsingHeader::new(localizer.t("stats-mechanics"))
+            .default_open(false)
+            .show(ui, |ui| render_mechanic_chart(ui, stats));
+    });
+}
+
+/// Stacked bar chart of game counts per year, one stack segment per genre
+fn render_genre_chart(ui: &mut egui::Ui, stats: &TimelineStats) {
+    let (start, end) = timeline_span();
+    let mut charts: Vec<BarChart> = Vec::new();
+
+    for (genre, years) in &stats.genre_counts {
+        let bars = (start..=end)
+            .map(|year| {
+                let count = years.get(&year).copied().unwrap_or(0);
+                Bar::new(year as f64, count as f64).width(0.7)
+            })
+            .collect();
+
+        let others: Vec<&BarChart> = charts.iter().collect();
+        let chart = BarChart::new(genre.as_str(), bars).stack_on(&others);
+        charts.push(chart);
+    }
+
+    Plot::new("genre_per_year")
+        .legend(Legend::default())
+        .height(220.0)
+        .allow_scroll(false)
+        .show(ui, |plot_ui| {
+            for chart in charts {
+                plot_ui.bar_chart(chart);
+            }
+        });
+}
+
+/// One horizontal line per platform spanning its first and last game year
+fn render_platform_chart(ui: &mut egui::Ui, stats: &TimelineStats, localizer: &Localizer) {
+    let mut platforms: Vec<_> = stats.platform_lifecycles.iter().collect();
+    platforms.sort_by_key(|(_, (first, last, _))| (*first, *last));
+
+    let height = (platforms.len() as f32 * 18.0).clamp(120.0, 400.0);
+
+    Plot::new("platform_lifecycles")
+        .height(height)
+        .allow_scroll(false)
+        .show_axes([true, false])
+        .show(ui, |plot_ui| {
+            for (idx, (platform, (first, last, count))) in platforms.iter().enumerate() {
+                let y = idx as f64;
+                let points =
+                    PlotPoints::from(vec![[*first as f64 - 0.4, y], [*last as f64 + 0.4, y]]);
+                plot_ui.line(
+                    Line::new(format!("{platform} ({count})"), points)
+                        .width((1.0 + *count as f32).min(8.0)),
+                );
+            }
+        });
+
+    ui.label(
+        egui::RichText::new(localizer.t("stats-platforms-hint"))
+            .small()
+            .weak(),
+    );
+}
+
+/// Share of each year's games exhibiting the most common mechanics
+fn render_mechanic_chart(ui: &mut egui::Ui, stats: &TimelineStats) {
+    let (start, end) = timeline_span();
+    Plot::new("mechanic_prevalence")
+        .legend(Legend::default())
+        .height(220.0)
+        .include_y(0.0)
+        .include_y(1.0)
+        .allow_scroll(false)
+        .show(ui, |plot_ui| {
+            for (mechanic, years) in &stats.mechanic_prevalence {
+                let points: PlotPoints = (start..=end)
+                    .map(|year| {
+                        let share = years.get(&year).copied().unwrap_or(0.0);
+                        [year as f64, share as f64]
+                    })
+                    .collect();
+                plot_ui.line(Line::new(mechanic.as_str(), points));
+            }
+        });
+}
@@ -1,46 +1,49 @@
 use super::types::{Decade, GuidedModeState};
-use crate::vintage_games::{self, TimelineGame};
+use crate::vintage_games::{self, Facets, PlatformFamily, SearchFilters, TimelineGame};
+use crate::wizard::i18n::Localizer;
 use bevy_egui::egui;
 
+/// Search results shown at once; refine the query to see others
+const MAX_SEARCH_RESULTS: usize = 60;
+
+/// Developer chips shown besides the picked ones, those with most games
+const MAX_DEVELOPER_CHIPS: usize = 12;
+
 /// Render the timeline browser UI
-pub fn render_timeline(ui: &mut egui::Ui, state: &mut GuidedModeState) {
+pub fn render_timeline(ui: &mut egui::Ui, state: &mut GuidedModeState, localizer: &Localizer) {
     ui.group(|ui| {
-        ui.heading("🎮 Gaming Timeline");
+        ui.heading(localizer.t("timeline-title"));
         ui.separator();
 
-        // Decade selector buttons
-        ui.horizontal(|ui| {
-            // 1980s button
-            let eighties_selected = state.selected_decade == Some(Decade::Eighties);
-            let eighties_response = ui.add_sized(
-                [120.0, 60.0],
-                egui::Button::new(
-                    egui::RichText::new(format!("{} 1980s", Decade::Eighties.icon())).size(18.0),
-                )
-                .selected(eighties_selected),
-            );
+        render_search_bar(ui, state, localizer);
+        render_facets(ui, &mut state.facets, localizer);
+        ui.separator();
 
-            if eighties_response.clicked() {
-                state.selected_decade = Some(Decade::Eighties);
-            }
+        if !state.search_query.trim().is_empty() || !state.search_filters.is_empty() {
+            render_search_results(ui, state, localizer);
+            return;
+        }
 
-            eighties_response.on_hover_text(Decade::Eighties.description());
+        // Decade selector buttons, for the decades the timeline and its
+        // era packs have games in
+        ui.horizontal(|ui| {
+            for decade in Decade::ALL.into_iter().filter(Decade::has_games) {
+                let selected = state.selected_decade == Some(decade);
+                let response = ui.add_sized(
+                    [120.0, 60.0],
+                    egui::Button::new(
+                        egui::RichText::new(format!("{} {}", decade.icon(), decade.name()))
+                            .size(18.0),
+                    )
+                    .selected(selected),
+                );
 
-            // 1990s button
-            let nineties_selected = state.selected_decade == Some(Decade::Nineties);
-            let nineties_response = ui.add_sized(
-                [120.0, 60.0],
-                egui::Button::new(
-                    egui::RichText::new(format!("{} 1990s", Decade::Nineties.icon())).size(18.0),
-                )
-                .selected(nineties_selected),
-            );
+                if response.clicked() {
+                    state.selected_decade = Some(decade);
+                }
 
-            if nineties_response.clicked() {
-                state.selected_decade = Some(Decade::Nineties);
+                response.on_hover_text(decade.description());
             }
-
-            nineties_response.on_hover_text(Decade::Nineties.description());
         });
 
         ui.separator();
@@ -64,7 +67,7 @@ pub fn render_timeline(ui: &mut egui::Ui, state: &mut GuidedModeState) {
                 .scroll_bar_visibility(egui::scroll_area::ScrollBarVisibility::AlwaysVisible)
                 .show(ui, |ui| {
                     for year in start_year..=end_year {
-                        render_year_section(ui, state, year);
+                        render_year_section(ui, state, year, localizer);
                     }
                 });
         } else {
@@ -72,21 +75,191 @@ pub fn render_timeline(ui: &mut egui::Ui, state: &mut GuidedModeState) {
             ui.vertical_centered(|ui| {
                 ui.add_space(50.0);
                 ui.label(
-                    egui::RichText::new("Select a decade to explore gaming history")
+                    egui::RichText::new(localizer.t("timeline-pick-decade"))
                         .size(20.0)
                         .color(egui::Color32::from_gray(150)),
                 );
                 ui.add_space(20.0);
-                ui.label("Choose from the golden age of arcade games (1980s)");
-                ui.label("or the 16-bit renaissance (1990s)");
+                ui.label(localizer.t("timeline-decade-hint"));
             });
         }
     });
 }
 
+/// Search box with the genre, platform and year filters
+fn render_search_bar(ui: &mut egui::Ui, state: &mut GuidedModeState, localizer: &Localizer) {
+    ui.horizontal_wrapped(|ui| {
+        ui.label("🔍");
+        ui.add(
+            egui::TextEdit::singleline(&mut state.search_query)
+                .hint_text(localizer.t("timeline-search-hint"))
+                .desired_width(220.0),
+        );
+
+        let filters = &mut state.search_filters;
+        filter_combo(
+            ui,
+            "timeline_search_genre",
+            &localizer.t("timeline-any-genre"),
+            &mut filters.genre,
+            get_all_genres(),
+        );
+        filter_combo(
+            ui,
+            "timeline_search_platform",
+            &localizer.t("timeline-any-platform"),
+            &mut filters.platform,
+            vintage_games::all_platforms(),
+        );
+
+        let (first_year, last_year) = vintage_games::timeline_span();
+        let mut limit_years = filters.years.is_some();
+        if ui
+            .checkbox(&mut limit_years, localizer.t("timeline-years"))
+            .changed()
+        {
+            filters.years = limit_years.then_some(first_year..=last_year);
+        }
+        if let Some(years) = &mut filters.years {
+            let (mut start, mut end) = years.clone().into_inner();
+            ui.add(egui::DragValue::new(&mut start).range(first_year..=end));
+            ui.label("–");
+            ui.add(egui::DragValue::new(&mut end).range(start..=last_year));
+            *years = start..=end;
+        }
+
+        if (!state.search_query.is_empty() || !state.search_filters.is_empty())
+            && ui.button(localizer.t("timeline-clear-search")).clicked()
+        {
+            state.search_query.clear();
+            state.search_filters = SearchFilters::default();
+        }
+    });
+}
+
+/// Chips narrowing the timeline down by genre, platform family and developer
+fn render_facets(ui: &mut egui::Ui, facets: &mut Facets, localizer: &Localizer) {
+    let counts = facets.counts(vintage_games::all_games());
+
+    egui::CollapsingHeader::new(localizer.t("timeline-filters"))
+        .id_salt("timeline_facets")
+        .default_open(!facets.is_empty())
+        .show(ui, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                ui.label(localizer.t("timeline-genre"));
+                for (genre, count) in &counts.genres {
+                    if facet_chip(ui, facets.genres.contains(genre), genre, *count) {
+                        facets.toggle_genre(genre);
+                    }
+                }
+            });
+
+            ui.horizontal_wrapped(|ui| {
+                ui.label(localizer.t("timeline-platform"));
+                for family in PlatformFamily::ALL {
+                    let count = counts
+                        .families
+                        .iter()
+                        .find(|(counted, _)| *counted == family)
+                        .map_or(0, |(_, count)| *count);
+                    let picked = facets.families.contains(&family);
+                    if (count > 0 || picked) && facet_chip(ui, picked, family.name(), count) {
+                        facets.toggle_family(family);
+                    }
+                }
+            });
+
+            ui.horizontal_wrapped(|ui| {
+                ui.label(localizer.t("timeline-developer"));
+                // Picked developers stay visible even when they have few games
+                for (rank, (developer, count)) in counts.developers.iter().enumerate() {
+                    let picked = facets.developers.contains(developer);
+                    if (rank < MAX_DEVELOPER_CHIPS || picked)
+                        && facet_chip(ui, picked, developer, *count)
+                    {
+                        facets.toggle_developer(developer);
+                    }
+                }
+            });
+
+            if !facets.is_empty() && ui.button(localizer.t("timeline-clear-filters")).clicked() {
+                facets.clear();
+            }
+        });
+}
+
+/// A toggleable chip with the number of games it stands for; true if clicked
+fn facet_chip(ui: &mut egui::Ui, picked: bool, label: &str, count: usize) -> bool {
+    ui.selectable_label(picked, format!("{label} ({count})"))
+        .clicked()
+}
+
+/// Combo box picking one of `options`, or none of them
+fn filter_combo(
+    ui: &mut egui::Ui,
+    id: &str,
+    any: &str,
+    value: &mut Option<String>,
+    options: Vec<String>,
+) {
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(value.as_deref().unwrap_or(any))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(value, None, any);
+            for option in options {
+                let label = option.clone();
+                ui.selectable_value(value, Some(option), label);
+            }
+        });
+}
+
+/// Games matching the search, best match first
+fn render_search_results(ui: &mut egui::Ui, state: &mut GuidedModeState, localizer: &Localizer) {
+    let mut games = get_filtered_games(&state.search_query, &state.search_filters);
+    games.retain(|game| state.facets.matches(game));
+
+    if games.is_empty() {
+        ui.label(
+            egui::RichText::new(localizer.t("timeline-no-matches"))
+                .italics()
+                .color(egui::Color32::from_gray(150)),
+        );
+        return;
+    }
+
+    if games.len() > MAX_SEARCH_RESULTS {
+        ui.label(localizer.t_args(
+            "timeline-best-matches",
+            &[
+                ("shown", MAX_SEARCH_RESULTS.into()),
+                ("count", games.len().into()),
+            ],
+        ));
+    } else {
+        ui.label(localizer.t_args("timeline-matches", &[("count", games.len().into())]));
+    }
+
+    egui::ScrollArea::vertical()
+        .id_salt("timeline_search_scroll")
+        .max_height(ui.available_height() - 20.0)
+        .show(ui, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for game in games.into_iter().take(MAX_SEARCH_RESULTS) {
+                    render_timeline_game_card(ui, state, game, localizer);
+                }
+            });
+        });
+}
+
 /// Render games for a specific year
-fn render_year_section(ui: &mut egui::Ui, state: &mut GuidedModeState, year: i32) {
-    let games = vintage_games::games_by_year(year);
+fn render_year_section(
+    ui: &mut egui::Ui,
+    state: &mut GuidedModeState,
+    year: i32,
+    localizer: &Localizer,
+) {
+    let mut games = vintage_games::games_by_year(year);
+    games.retain(|game| state.facets.matches(game));
 
     if games.is_empty() {
         return;
@@ -96,7 +269,7 @@ fn render_year_section(ui: &mut egui::Ui, state: &mut GuidedModeState, year: i32
     ui.group(|ui| {
         ui.horizontal(|ui| {
             ui.heading(format!("{year}"));
-            ui.label(format!("{} games", games.len()));
+            ui.label(localizer.t_args("timeline-games", &[("count", games.len().into())]));
         });
 
         ui.separator();
@@ -104,7 +277,7 @@ fn render_year_section(ui: &mut egui::Ui, state: &mut GuidedModeState, year: i32
         // Games grid
         ui.horizontal_wrapped(|ui| {
             for game in games {
-                render_timeline_game_card(ui, state, game);
+                render_timeline_game_card(ui, state, game, localizer);
             }
         });
     });
@@ -117,6 +290,7 @@ fn render_timeline_game_card(
     ui: &mut egui::Ui,
     state: &mut GuidedModeState,
     game: &'static TimelineGame,
+    localizer: &Localizer,
 ) {
     let is_selected = state.selected_games.contains_key(&game.id);
     let is_hovered = state.ui_state.hovered_game == Some(game.id);
@@ -147,6 +321,7 @@ fn render_timeline_game_card(
             state.selected_games.remove(&game.id);
         } else {
             state.selected_games.insert(game.id, game);
+            state.ui_state.similar_to = Some(game.id);
         }
     }
 
@@ -195,9 +370,12 @@ fn render_timeline_game_card(
     // Platform count
     if !game.platforms.is_empty() {
         child_ui.label(
-            egui::RichText::new(format!("{} platforms", game.platforms.len()))
-                .size(8.0)
-                .color(egui::Color32::from_gray(140)),
+            egui::RichText::new(localizer.t_args(
+                "timeline-platforms",
+                &[("count", game.platforms.len().into())],
+            ))
+            .size(8.0)
+            .color(egui::Color32::from_gray(140)),
         );
     }
 
@@ -217,25 +395,25 @@ fn render_timeline_game_card(
 
     // Tooltip on hover
     response.on_hover_ui(|ui| {
-        render_game_tooltip(ui, game);
+        render_game_tooltip(ui, game, localizer);
     });
 }
 
 /// Render detailed tooltip for a game
-fn render_game_tooltip(ui: &mut egui::Ui, game: &TimelineGame) {
+fn render_game_tooltip(ui: &mut egui::Ui, game: &TimelineGame, localizer: &Localizer) {
     ui.heading(game.name);
 
     ui.horizontal(|ui| {
-        ui.label("Year:");
+        ui.label(localizer.t("timeline-year"));
         ui.strong(game.year.to_string());
         ui.separator();
-        ui.label("Genre:");
+        ui.label(localizer.t("timeline-genre"));
         ui.strong(game.genre);
     });
 
     if let Some(developer) = game.developer {
         ui.horizontal(|ui| {
-            ui.label("Developer:");
+            ui.label(localizer.t("timeline-developer"));
             ui.label(developer);
         });
     }
@@ -247,7 +425,7 @@ fn render_game_tooltip(ui: &mut egui::Ui, game: &TimelineGame) {
 
     if !game.platforms.is_empty() {
         ui.separator();
-        ui.label("Platforms:");
+        ui.label(localizer.t("timeline-platforms-label"));
         ui.horizontal_wrapped(|ui| {
             for platform in game.platforms {
                 ui.label(
@@ -265,17 +443,10 @@ pub fn get_all_genres() -> Vec<String> {
     vintage_games::all_genres()
 }
 
-/// Get games filtered by search query and genre
-pub fn get_filtered_games(search: &str, genre_filter: Option<&str>) -> Vec<&'static TimelineGame> {
-    let mut games = if search.is_empty() {
-        vintage_games::TIMELINE_GAMES.iter().collect()
-    } else {
-        vintage_games::search_games(search)
-    };
-
-    if let Some(genre) = genre_filter {
-        games.retain(|game| game.genre == genre);
-    }
-
-    games
+/// Get games matching the search query and filters, best match first
+pub fn get_filtered_games(search: &str, filters: &SearchFilters) -> Vec<&'static TimelineGame> {
+    vintage_games::search_games_filtered(search, filters)
+        .into_iter()
+        .map(|hit| hit.game)
+        .collect()
 }
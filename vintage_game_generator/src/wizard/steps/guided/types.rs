@@ -4,15 +4,26 @@ use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use vintage_blending_core::graph::BlendPath;
 
+/// Weight slider position of a selected game that hasn't been moved
+pub const DEFAULT_GAME_WEIGHT: f32 = 50.0;
+
 /// State for the guided mode workflow
 #[derive(Debug, Default, Resource)]
 pub struct GuidedModeState {
     pub selected_decade: Option<Decade>,
     pub selected_games: HashMap<u32, &'static crate::vintage_games::TimelineGame>,
+    /// Relative weight of each selected game in the blend, 0-100 as set on
+    /// its slider
+    pub game_weights: HashMap<u32, f32>,
     pub blend_result: Option<BlendResult>,
+    /// Suggested and picked resolutions of the blend's conflicts
+    pub resolutions: super::blend::resolution::ResolutionState,
+    /// Conversation explaining the blend
+    pub explanation: super::blend::explain::BlendExplanation,
     pub ui_state: GuiState,
     pub search_query: String,
-    pub genre_filter: Option<String>,
+    pub search_filters: crate::vintage_games::SearchFilters,
+    pub facets: crate::vintage_games::Facets,
     pub current_step: u32,
 }
 
@@ -20,11 +31,41 @@ impl GuidedModeState {
     pub fn toggle_game_selection(&mut self, game_id: u32) {
         if self.selected_games.contains_key(&game_id) {
             self.selected_games.remove(&game_id);
+            self.game_weights.remove(&game_id);
         } else {
             // This would need to be called with the actual game reference
             // For now, we'll handle this in the UI code
         }
     }
+
+    /// Slider position of a selected game
+    pub fn game_weight(&self, game_id: u32) -> f32 {
+        self.game_weights
+            .get(&game_id)
+            .copied()
+            .unwrap_or(DEFAULT_GAME_WEIGHT)
+    }
+
+    /// Share of the blend each selected game makes up, summing to 1; equal
+    /// shares if every slider is at 0
+    pub fn game_shares(&self) -> HashMap<u32, f32> {
+        let total: f32 = self
+            .selected_games
+            .keys()
+            .map(|id| self.game_weight(*id))
+            .sum();
+        self.selected_games
+            .keys()
+            .map(|id| {
+                let share = if total > 0.0 {
+                    self.game_weight(*id) / total
+                } else {
+                    1.0 / self.selected_games.len() as f32
+                };
+                (*id, share)
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Default)]
@@ -33,49 +74,92 @@ pub struct GuiState {
     pub show_blend_details: bool,
     pub scroll_position: f32,
     pub timeline_scroll: f32,
+    pub show_stats: bool,
+    pub timeline_stats: Option<super::stats::TimelineStats>,
+    pub rating_model: vintage_blending_core::rating::RatingModel,
+    pub ratings_path: Option<std::path::PathBuf>,
+    pub preferences_status: Option<String>,
+    pub suggestions: super::preferences::SuggestionCache,
+    /// Game the "similar games" strip is shown for, the one picked last
+    pub similar_to: Option<u32>,
+    pub similar_games: Option<super::similar::SimilarGames>,
+    pub game_clusters: Option<super::clusters::GameClusters>,
+    /// Seed of the last "surprise me" blend, reused while it is fixed
+    pub surprise_seed: u64,
+    pub fixed_surprise_seed: bool,
+    /// Surprise blends drawn so far, counting the seeds taken from the
+    /// project's generation seed
+    pub surprise_draws: u64,
 }
 
 /// Decades for timeline browsing
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Decade {
-    Eighties, // 1980-1989
-    Nineties, // 1990-1995
+    Seventies, // 1970-1979, from era packs
+    Eighties,  // 1980-1989
+    Nineties,  // 1990-1999
+    Noughties, // 2000-2009, from era packs
 }
 
 impl Decade {
+    /// Every decade, in order
+    pub const ALL: [Decade; 4] = [
+        Decade::Seventies,
+        Decade::Eighties,
+        Decade::Nineties,
+        Decade::Noughties,
+    ];
+
     pub fn from_year(year: i32) -> Option<Self> {
-        match year {
-            1980..=1989 => Some(Decade::Eighties),
-            1990..=1995 => Some(Decade::Nineties),
-            _ => None,
-        }
+        Self::ALL.into_iter().find(|decade| {
+            let (start, end) = decade.year_range();
+            (start..=end).contains(&year)
+        })
     }
 
     pub fn year_range(&self) -> (i32, i32) {
         match self {
+            Decade::Seventies => (1970, 1979),
             Decade::Eighties => (1980, 1989),
-            Decade::Nineties => (1990, 1995),
+            Decade::Nineties => (1990, 1999),
+            Decade::Noughties => (2000, 2009),
         }
     }
 
+    /// Whether the timeline, era packs included, has games of the decade
+    pub fn has_games(&self) -> bool {
+        let (start, end) = self.year_range();
+        crate::vintage_games::all_games()
+            .iter()
+            .any(|game| (start..=end).contains(&game.year))
+    }
+
     pub fn name(&self) -> &'static str {
         match self {
+            Decade::Seventies => "1970s",
             Decade::Eighties => "1980s",
             Decade::Nineties => "1990s",
+            Decade::Noughties => "2000s",
         }
     }
 
     pub fn description(&self) -> &'static str {
         match self {
+            Decade::Seventies => {
+                "The Atari age: Pong's heirs, home cartridges and the first arcade hits"
+            }
             Decade::Eighties => "The birth of gaming: From arcade classics to the NES revolution",
             Decade::Nineties => "The 16-bit golden age: RPGs flourish and genres mature",
+            Decade::Noughties => "The 32-bit leap: polygons, CD audio and the last of the sprites",
         }
     }
 
     pub fn icon(&self) -> &'static str {
         match self {
+            Decade::Seventies => "👾",
             Decade::Eighties => "🕹️",
             Decade::Nineties => "🎮",
+            Decade::Noughties => "💿",
         }
     }
 }
@@ -109,6 +193,15 @@ pub struct Conflict {
     pub game1: String,
     pub game2: String,
     pub conflict_type: String,
+    pub description: String,
+    pub resolution: String,
+}
+
+/// The way the user chose to settle a conflict of the blend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConflictResolution {
+    pub conflict_type: String,
+    pub description: String,
     pub resolution: String,
 }
 
@@ -149,6 +242,10 @@ pub struct GuidedModeExport {
     pub complexity: f32,
     pub action_strategy_balance: f32,
     pub recommended_features: Vec<String>,
+    /// Resolutions picked in the conflict step; absent in exports made
+    /// before conflicts could be resolved
+    #[serde(default)]
+    pub conflict_resolutions: Vec<ConflictResolution>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -157,4 +254,8 @@ pub struct SourceGame {
     pub year: i32,
     pub genre: String,
     pub developer: Option<String>,
+    /// Share of the blend the game makes up; absent in exports made before
+    /// blends were weighted
+    #[serde(default)]
+    pub weight: Option<f32>,
 }
@@ -1,7 +1,9 @@
 use crate::wizard::config::ConfigManager;
+use crate::wizard::i18n::Localizer;
 use crate::wizard::overlay::{
     ClickableAreaConfig as ClickableImageConfig, show_image_with_overlays,
 };
+use crate::wizard::tutorial::{ANCHOR_RUST, mark_anchor};
 use bevy_egui::egui;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -33,6 +35,7 @@ impl LanguageChoice {
 pub fn draw_language_step(
     ui: &mut egui::Ui,
     config_manager: &mut Option<ConfigManager>,
+    localizer: &Localizer,
 ) -> Option<LanguageChoice> {
     let mut selected = None;
 
@@ -40,9 +43,9 @@ pub fn draw_language_step(
         ui.add_space(20.0);
 
         // Title
-        ui.heading(egui::RichText::new("Select Target Language").size(28.0).strong());
+        ui.heading(egui::RichText::new(localizer.t("language-title")).size(28.0).strong());
         ui.add_space(10.0);
-        ui.label(egui::RichText::new("Choose the programming language for your game").size(16.0));
+        ui.label(egui::RichText::new(localizer.t("language-subtitle")).size(16.0));
         ui.add_space(40.0);
 
         // Load the language icons configuration
@@ -88,30 +91,30 @@ pub fn draw_language_step(
                     columns[0].vertical_centered(|ui| {
                         ui.label(egui::RichText::new("Python").size(20.0).strong().color(egui::Color32::from_rgb(53, 114, 165)));
                         ui.add_space(10.0);
-                        ui.label("🎯 Best for:");
-                        ui.label("• Beginners");
-                        ui.label("• Quick prototypes");
-                        ui.label("• Educational games");
+                        ui.label(localizer.t("language-best-for"));
+                        ui.label(localizer.t("language-python-beginners"));
+                        ui.label(localizer.t("language-python-prototypes"));
+                        ui.label(localizer.t("language-python-education"));
                         ui.add_space(10.0);
-                        ui.label("💪 Strengths:");
-                        ui.label("• Easy to learn");
-                        ui.label("• Rapid development");
-                        ui.label("• PyGame library");
+                        ui.label(localizer.t("language-strengths"));
+                        ui.label(localizer.t("language-python-easy"));
+                        ui.label(localizer.t("language-python-rapid"));
+                        ui.label(localizer.t("language-python-pygame"));
                     });
 
                     // Rust column
                     columns[1].vertical_centered(|ui| {
                         ui.label(egui::RichText::new("Rust").size(20.0).strong().color(egui::Color32::from_rgb(255, 106, 0)));
                         ui.add_space(10.0);
-                        ui.label("🎯 Best for:");
-                        ui.label("• Performance games");
-                        ui.label("• Browser games");
-                        ui.label("• Complex systems");
+                        ui.label(localizer.t("language-best-for"));
+                        ui.label(localizer.t("language-rust-performance"));
+                        ui.label(localizer.t("language-rust-browser"));
+                        ui.label(localizer.t("language-rust-systems"));
                         ui.add_space(10.0);
-                        ui.label("💪 Strengths:");
-                        ui.label("• Blazing fast");
-                        ui.label("• Memory safe");
-                        ui.label("• WASM support");
+                        ui.label(localizer.t("language-strengths"));
+                        ui.label(localizer.t("language-rust-fast"));
+                        ui.label(localizer.t("language-rust-safe"));
+                        ui.label(localizer.t("language-rust-wasm"));
                     });
 
                     // Ruby column
@@ -133,17 +136,16 @@ pub fn draw_language_step(
                                 ui.image(&overlay_texture);
                             }
                         } else {
-                            ui.label(egui::RichText::new("🚧 Coming Soon! 🚧").size(16.0));
+                            ui.label(egui::RichText::new(localizer.t("language-coming-soon")).size(16.0));
                             ui.add_space(10.0);
-                            ui.label("Ruby support is");
-                            ui.label("under development");
+                            ui.label(localizer.t("language-ruby-in-progress"));
                         }
                     });
                 });
 
             } else {
                 // Fallback if image loading fails
-                ui.label("Failed to load language selection image");
+                ui.label(localizer.t("language-image-failed"));
             }
         } else {
             // Fallback UI without images
@@ -152,12 +154,14 @@ pub fn draw_language_step(
                     selected = Some(LanguageChoice::Python);
                 }
                 ui.add_space(20.0);
-                if ui.button(egui::RichText::new("Rust 🦀").size(20.0)).clicked() {
+                let rust = ui.button(egui::RichText::new("Rust 🦀").size(20.0));
+                mark_anchor(ui.ctx(), ANCHOR_RUST, rust.rect);
+                if rust.clicked() {
                     selected = Some(LanguageChoice::Rust);
                 }
                 ui.add_space(20.0);
                 ui.add_enabled_ui(false, |ui| {
-                    let _ = ui.button(egui::RichText::new("Ruby 💎 (Coming Soon)").size(20.0));
+                    let _ = ui.button(egui::RichText::new(localizer.t("language-ruby-coming-soon")).size(20.0));
                 });
             });
         }
@@ -167,8 +171,8 @@ pub fn draw_language_step(
         ui.add_space(10.0);
 
         // Additional info
-        ui.label(egui::RichText::new("All languages will generate complete, playable retro-style games").size(14.0).weak());
-        ui.label(egui::RichText::new("with appropriate libraries and frameworks for each language").size(14.0).weak());
+        ui.label(egui::RichText::new(localizer.t("language-footer-games")).size(14.0).weak());
+        ui.label(egui::RichText::new(localizer.t("language-footer-libraries")).size(14.0).weak());
     });
 
     // Update config manager if a selection was made
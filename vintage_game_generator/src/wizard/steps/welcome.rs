@@ -1,19 +1,23 @@
 use crate::wizard::config::ConfigManager;
+use crate::wizard::i18n::Localizer;
 use crate::wizard::image_loader;
 use crate::wizard::overlay::{
     ClickableAreaConfig as ClickableImageConfig, show_image_with_overlays,
 };
+use crate::wizard::tutorial::{ANCHOR_GUIDED_MODE, mark_anchor};
 use bevy_egui::egui;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum WelcomeAction {
     GuidedMode,
     FreeformMode,
+    Tutorial,
 }
 
 pub fn draw_welcome_step(
     ui: &mut egui::Ui,
     config_manager: &mut Option<ConfigManager>,
+    localizer: &Localizer,
 ) -> Option<WelcomeAction> {
     let mut action = None;
 
@@ -21,9 +25,9 @@ pub fn draw_welcome_step(
         ui.add_space(20.0);
 
         // Title
-        ui.heading(egui::RichText::new("🎮 Vintage Game Generator").size(36.0).strong());
+        ui.heading(egui::RichText::new(localizer.t("app-title")).size(36.0).strong());
         ui.add_space(10.0);
-        ui.label(egui::RichText::new("Choose your creation mode").size(20.0));
+        ui.label(egui::RichText::new(localizer.t("welcome-subtitle")).size(20.0));
         ui.add_space(40.0);
 
         // Try to load and display the mode selection image using pure egui
@@ -58,12 +62,17 @@ pub fn draw_welcome_step(
                         let font_subheading = egui::FontId::proportional(18.0);
                         let font_cta = egui::FontId::proportional(20.0);
 
+                        let guided = localizer.t("welcome-guided");
+                        let guided_tagline = localizer.t("welcome-guided-tagline");
+                        let freeform = localizer.t("welcome-freeform");
+                        let freeform_tagline = localizer.t("welcome-freeform-tagline");
+                        let or = localizer.t("welcome-or");
                         let text_overlays = vec![
-                            ("left_top", "GUIDED MODE", font_heading.clone()),
-                            ("left_bottom", "Browse & Blend Classic Games", font_cta.clone()),
-                            ("right_top", "FREEFORM MODE", font_heading.clone()),
-                            ("right_bottom", "AI-Powered Game Creation", font_cta.clone()),
-                            ("center_divider", "OR", font_subheading),
+                            ("left_top", guided.as_str(), font_heading.clone()),
+                            ("left_bottom", guided_tagline.as_str(), font_cta.clone()),
+                            ("right_top", freeform.as_str(), font_heading.clone()),
+                            ("right_bottom", freeform_tagline.as_str(), font_cta.clone()),
+                            ("center_divider", or.as_str(), font_subheading),
                         ];
 
                         // Show the clickable image with text
@@ -102,7 +111,7 @@ pub fn draw_welcome_step(
                         ui.add_space(20.0);
                         ui.separator();
                         ui.add_space(10.0);
-                        ui.label(egui::RichText::new("Click on either side to choose your path").size(14.0).weak());
+                        ui.label(egui::RichText::new(localizer.t("welcome-click-side")).size(14.0).weak());
                         break;
                     }
                 }
@@ -110,7 +119,7 @@ pub fn draw_welcome_step(
 
         // If image couldn't be loaded, show a simple selection UI
         if !image_loaded {
-            ui.label(egui::RichText::new("Choose your creation mode:").size(16.0));
+            ui.label(egui::RichText::new(localizer.t("welcome-choose")).size(16.0));
             ui.add_space(20.0);
         }
 
@@ -131,9 +140,9 @@ pub fn draw_welcome_step(
                         ui.add_space(20.0);
 
                         // Mode title
-                        ui.label(egui::RichText::new("GUIDED MODE").size(24.0).strong().color(egui::Color32::from_rgb(100, 149, 237)));
+                        ui.label(egui::RichText::new(localizer.t("welcome-guided")).size(24.0).strong().color(egui::Color32::from_rgb(100, 149, 237)));
                         ui.add_space(10.0);
-                        ui.label(egui::RichText::new("Browse Gaming History").size(16.0));
+                        ui.label(egui::RichText::new(localizer.t("welcome-guided-subtitle")).size(16.0));
                         ui.add_space(20.0);
 
                         // Icon with text overlay - try multiple paths
@@ -170,18 +179,18 @@ pub fn draw_welcome_step(
                         ui.add_space(20.0);
 
                         // Description
-                        ui.label("Select from classic games");
-                        ui.label("spanning three decades:");
+                        ui.label(localizer.t("welcome-guided-body"));
                         ui.add_space(10.0);
-                        ui.label("• 1970s - Dawn of Gaming");
-                        ui.label("• 1980s - Arcade Golden Age");
-                        ui.label("• 1990s - Console Revolution");
+                        ui.label(localizer.t("welcome-guided-70s"));
+                        ui.label(localizer.t("welcome-guided-80s"));
+                        ui.label(localizer.t("welcome-guided-90s"));
 
                         ui.add_space(30.0);
 
                         let button_response = ui.button(
-                            egui::RichText::new("Choose Guided").size(18.0)
+                            egui::RichText::new(localizer.t("welcome-guided-choose")).size(18.0)
                         );
+                        mark_anchor(ui.ctx(), ANCHOR_GUIDED_MODE, button_response.rect);
 
                         button_response.clicked()
                     });
@@ -210,9 +219,9 @@ pub fn draw_welcome_step(
                         ui.add_space(20.0);
 
                         // Mode title
-                        ui.label(egui::RichText::new("FREEFORM MODE").size(24.0).strong().color(egui::Color32::from_rgb(255, 140, 90)));
+                        ui.label(egui::RichText::new(localizer.t("welcome-freeform")).size(24.0).strong().color(egui::Color32::from_rgb(255, 140, 90)));
                         ui.add_space(10.0);
-                        ui.label(egui::RichText::new("Create Your Vision").size(16.0));
+                        ui.label(egui::RichText::new(localizer.t("welcome-freeform-subtitle")).size(16.0));
                         ui.add_space(20.0);
 
                         // Icon - try multiple paths
@@ -249,17 +258,16 @@ pub fn draw_welcome_step(
                         ui.add_space(20.0);
 
                         // Description
-                        ui.label("Design from scratch with");
-                        ui.label("AI-powered assistance:");
+                        ui.label(localizer.t("welcome-freeform-body"));
                         ui.add_space(10.0);
-                        ui.label("• Describe your game idea");
-                        ui.label("• Get intelligent suggestions");
-                        ui.label("• Full creative control");
+                        ui.label(localizer.t("welcome-freeform-describe"));
+                        ui.label(localizer.t("welcome-freeform-suggestions"));
+                        ui.label(localizer.t("welcome-freeform-control"));
 
                         ui.add_space(30.0);
 
                         let button_response = ui.button(
-                            egui::RichText::new("Choose Freeform").size(18.0)
+                            egui::RichText::new(localizer.t("welcome-freeform-choose")).size(18.0)
                         );
 
                         button_response.clicked()
@@ -281,7 +289,16 @@ pub fn draw_welcome_step(
         // Info text at bottom
         ui.separator();
         ui.add_space(10.0);
-        ui.label(egui::RichText::new("Both modes lead to the same powerful game generation engine").size(14.0).weak());
+        ui.label(egui::RichText::new(localizer.t("welcome-same-engine")).size(14.0).weak());
+        ui.add_space(10.0);
+
+        if ui
+            .button(egui::RichText::new(localizer.t("welcome-tutorial")).size(16.0))
+            .on_hover_text(localizer.t("welcome-tutorial-hint"))
+            .clicked()
+        {
+            action = Some(WelcomeAction::Tutorial);
+        }
     });
 
     action
@@ -0,0 +1,168 @@
+//! Project templates for starting new projects in list mode
+//!
+//! A template is a TOML file with a `[template]` table naming and describing
+//! it and a `[project]` table holding the project configuration to start
+//! from. A few presets are built in; projects saved as templates are kept in
+//! `templates/` under the base directory and listed after them.
+
+use crate::wizard::config::{
+    AiContext, ConfigManager, ProjectConfig, ProjectMetadata, WizardState, new_generation_seed,
+};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Directory under the base directory holding saved templates
+pub const TEMPLATES_DIR: &str = "templates";
+
+/// Presets shipped with the generator
+const BUILTIN_TEMPLATES: &[&str] = &[
+    include_str!("../../templates/presets/snes_jrpg.toml"),
+    include_str!("../../templates/presets/arcade_shooter.toml"),
+    include_str!("../../templates/presets/gb_puzzle.toml"),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateInfo {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// A project configuration to start new projects from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTemplate {
+    pub template: TemplateInfo,
+    pub project: ProjectConfig,
+    /// Where a saved template lives; `None` for the built-in presets
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+impl ProjectTemplate {
+    pub fn parse(contents: &str) -> Result<Self> {
+        toml::from_str(contents).context("Failed to parse project template")
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut template =
+            Self::parse(&contents).with_context(|| format!("In {}", path.display()))?;
+        template.path = Some(path.to_path_buf());
+        Ok(template)
+    }
+
+    /// Template of an existing project, without its identity, wizard
+    /// progress or conversation
+    pub fn from_project(name: &str, description: &str, project: &ProjectConfig) -> Self {
+        let mut project = project.clone();
+        project.metadata = ProjectMetadata::default();
+        project.wizard_state = WizardState::default();
+        project.ai_context = AiContext {
+            conversation_history: Vec::new(),
+            ..project.ai_context
+        };
+        project.technical.bevy_export = None;
+        Self {
+            template: TemplateInfo {
+                name: name.to_string(),
+                description: description.to_string(),
+            },
+            project,
+            path: None,
+        }
+    }
+
+    pub fn is_builtin(&self) -> bool {
+        self.path.is_none()
+    }
+
+    /// Create a new project from the template in its own directory under
+    /// `base_dir` and return the path of its `project.toml`
+    pub fn create_project(&self, base_dir: &Path, name: &str) -> Result<PathBuf> {
+        let project_dir = base_dir.join(Uuid::new_v4().to_string());
+        let mut manager = ConfigManager::new(&project_dir, Some("project"))?;
+        manager.config = self.project.clone();
+        manager.config.metadata = ProjectMetadata::default();
+        manager.config.metadata.version = manager.config.version.clone();
+        // Projects made from the same template still differ where randomness
+        // decides
+        manager.config.generation_seed = new_generation_seed();
+        if !name.trim().is_empty() {
+            manager.config.basic_info.name = name.trim().to_string();
+        }
+        manager
+            .save()
+            .with_context(|| format!("Failed to create project from {}", self.template.name))?;
+        Ok(project_dir.join("project.toml"))
+    }
+
+    /// Write the template to `templates/` under `base_dir`
+    pub fn save(&self, base_dir: &Path) -> Result<PathBuf> {
+        let dir = base_dir.join(TEMPLATES_DIR);
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(format!("{}.toml", file_stem(&self.template.name)));
+        let contents = toml::to_string_pretty(self).context("Failed to serialize template")?;
+        std::fs::write(&path, contents)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// The built-in presets
+pub fn builtin_templates() -> Vec<ProjectTemplate> {
+    BUILTIN_TEMPLATES
+        .iter()
+        .map(|contents| ProjectTemplate::parse(contents).expect("built-in templates are valid"))
+        .collect()
+}
+
+/// Built-in presets followed by the templates saved under `base_dir`.
+/// Saved templates that fail to load are skipped with a warning.
+pub fn load_templates(base_dir: &Path) -> Result<Vec<ProjectTemplate>> {
+    let mut templates = builtin_templates();
+    let dir = base_dir.join(TEMPLATES_DIR);
+    if !dir.exists() {
+        return Ok(templates);
+    }
+
+    let mut saved = Vec::new();
+    for entry in
+        std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "toml") {
+            continue;
+        }
+        match ProjectTemplate::load(&path) {
+            Ok(template) => saved.push(template),
+            Err(e) => tracing::warn!("Skipping template: {e:#}"),
+        }
+    }
+    saved.sort_by(|a, b| a.template.name.cmp(&b.template.name));
+    templates.extend(saved);
+    Ok(templates)
+}
+
+/// File name for a template, from its name
+fn file_stem(name: &str) -> String {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if stem.trim_matches('_').is_empty() {
+        "template".to_string()
+    } else {
+        stem
+    }
+}
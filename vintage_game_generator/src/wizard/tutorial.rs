@@ -0,0 +1,492 @@
+//! Interactive tutorial walking through a complete blend → generate → export run
+//!
+//! The tutorial drives a scripted mini-project: it picks two timeline games,
+//! blends them with the regular blend engine and generates a few placeholder
+//! assets with the offline service, whose mock provider answers in place of
+//! the AI provider, so new users can learn the flow without spending money.
+//! Each step is presented as a callout pointing at the widget it is about:
+//! widgets record where they were drawn with [`mark_anchor`] under the id of
+//! a step's hotspot, and the hotspot's bounds are used while the widget is
+//! not on screen.
+
+use crate::vintage_games::database;
+use crate::wizard::AppDirectories;
+use crate::wizard::i18n::Localizer;
+use crate::wizard::overlay::{
+    Bounds, Hotspot, ImageSize, OverlayConfig, OverlayContent, render_overlay,
+};
+use crate::wizard::pipeline::GenerationPipeline;
+use crate::wizard::state::{AppState, WizardStep};
+use crate::wizard::steps::guided::blend::export::export_to_toml;
+use crate::wizard::steps::guided::{GuidedModeState, create_blend, export_blend_to_config};
+use crate::wizard::steps::{LanguageChoice, WelcomeAction};
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, egui};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::runtime::Runtime;
+use vintage_ai_client::AiService;
+use vintage_ai_client::image::ImageConfig;
+use vintage_ai_client::text::TextConfig;
+
+/// Anchor of the welcome screen's guided mode button
+pub const ANCHOR_GUIDED_MODE: &str = "welcome_guided_mode";
+
+/// Anchor of the Rust choice, the id of its hotspot on the language screen
+pub const ANCHOR_RUST: &str = "rust";
+
+/// Anchor of guided mode's timeline of games
+pub const ANCHOR_TIMELINE: &str = "guided_timeline";
+
+/// Anchor of guided mode's blend button
+pub const ANCHOR_BLEND: &str = "guided_blend";
+
+/// Anchor of the blend guided mode shows once blended
+pub const ANCHOR_BLEND_RESULT: &str = "guided_blend_result";
+
+/// Anchor of guided mode's export button
+pub const ANCHOR_EXPORT: &str = "guided_export";
+
+/// Anchor of the summary of the exported blend
+pub const ANCHOR_COMPLETE: &str = "complete_summary";
+
+/// Widgets drawn this frame by anchor, kept in egui's temporary data
+type Anchors = HashMap<String, egui::Rect>;
+
+fn anchors_id() -> egui::Id {
+    egui::Id::new("tutorial_anchors")
+}
+
+/// Record where a widget the tutorial can point at was drawn this frame
+pub fn mark_anchor(ctx: &egui::Context, anchor: &str, rect: egui::Rect) {
+    ctx.data_mut(|data| {
+        data.get_temp_mut_or_default::<Anchors>(anchors_id())
+            .insert(anchor.to_string(), rect);
+    });
+}
+
+/// Stage of the scripted flow a tutorial step belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TutorialStage {
+    Welcome,
+    Language,
+    SelectGames,
+    Blend,
+    Generate,
+    Export,
+    Done,
+}
+
+/// A single callout in the tutorial script
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TutorialStep {
+    pub stage: TutorialStage,
+    /// Id of the callout's title in the UI catalogs
+    pub title_id: String,
+    /// Id of the callout's text in the UI catalogs
+    pub body_id: String,
+    /// Widget to highlight: its id is the anchor the widget is marked with,
+    /// its label the catalog id of its tooltip and its bounds, in script
+    /// coordinates, the region highlighted while the widget is not drawn
+    pub hotspot: Hotspot,
+}
+
+/// Tutorial script loaded from RON
+///
+/// Hotspot bounds are expressed against `canvas`, which is scaled to the
+/// window the same way clickable image configs are scaled to their image.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TutorialScript {
+    pub canvas: ImageSize,
+    pub steps: Vec<TutorialStep>,
+}
+
+impl TutorialScript {
+    /// Load the tutorial script from a RON file
+    pub fn from_ron_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(ron::from_str(&contents)?)
+    }
+
+    /// Load the bundled script, falling back to the built-in one
+    pub fn load() -> Self {
+        let ron_paths = [
+            "assets/wizard/tutorial.ron",
+            "crates/vintage_game_generator/assets/wizard/tutorial.ron",
+        ];
+
+        ron_paths
+            .iter()
+            .find_map(|path| Self::from_ron_file(path).ok())
+            .unwrap_or_default()
+    }
+}
+
+impl Default for TutorialScript {
+    fn default() -> Self {
+        let step = |stage, id: &str, anchor: &str, bounds| TutorialStep {
+            stage,
+            title_id: format!("tutorial-{id}-title"),
+            body_id: format!("tutorial-{id}-body"),
+            hotspot: Hotspot {
+                id: anchor.to_string(),
+                bounds,
+                label: format!("tutorial-{id}-title"),
+            },
+        };
+        let content = Bounds {
+            x_min: 50,
+            y_min: 120,
+            x_max: 950,
+            y_max: 880,
+        };
+        let footer = Bounds {
+            x_min: 20,
+            y_min: 900,
+            x_max: 980,
+            y_max: 980,
+        };
+
+        Self {
+            canvas: ImageSize {
+                width: 1000,
+                height: 1000,
+            },
+            steps: vec![
+                step(
+                    TutorialStage::Welcome,
+                    "welcome",
+                    ANCHOR_GUIDED_MODE,
+                    content,
+                ),
+                step(TutorialStage::Language, "language", ANCHOR_RUST, content),
+                step(
+                    TutorialStage::SelectGames,
+                    "select-games",
+                    ANCHOR_TIMELINE,
+                    content,
+                ),
+                step(TutorialStage::Blend, "blend", ANCHOR_BLEND, footer),
+                step(
+                    TutorialStage::Generate,
+                    "generate",
+                    ANCHOR_BLEND_RESULT,
+                    content,
+                ),
+                step(TutorialStage::Export, "export", ANCHOR_EXPORT, footer),
+                step(TutorialStage::Done, "done", ANCHOR_COMPLETE, content),
+            ],
+        }
+    }
+}
+
+/// Runtime state of the tutorial
+#[derive(Resource, Default)]
+pub struct TutorialState {
+    pub active: bool,
+    pub current: usize,
+    pub script: Option<TutorialScript>,
+    pub project_dir: Option<PathBuf>,
+    pub generated_assets: Vec<PathBuf>,
+    pub error: Option<String>,
+}
+
+impl TutorialState {
+    /// Start the tutorial from the first step
+    pub fn start(&mut self, directories: &AppDirectories) {
+        self.active = true;
+        self.current = 0;
+        self.script = Some(TutorialScript::load());
+        self.project_dir = Some(directories.base_dir.join("tutorial"));
+        self.generated_assets.clear();
+        self.error = None;
+    }
+
+    /// Stop the tutorial
+    pub fn stop(&mut self) {
+        self.active = false;
+        self.script = None;
+    }
+
+    pub fn current_step(&self) -> Option<&TutorialStep> {
+        self.script.as_ref()?.steps.get(self.current)
+    }
+}
+
+/// Draw the callout for the current tutorial step and perform its scripted action
+pub fn draw_tutorial_overlay(
+    mut contexts: EguiContexts,
+    mut tutorial: ResMut<TutorialState>,
+    mut app_state: ResMut<AppState>,
+    guided_state: Option<ResMut<GuidedModeState>>,
+    directories: Res<AppDirectories>,
+    pipeline: Res<GenerationPipeline>,
+    localizer: Res<Localizer>,
+) {
+    if app_state.tutorial_requested {
+        app_state.tutorial_requested = false;
+        tutorial.start(&directories);
+        info!("Starting tutorial");
+    }
+
+    if !tutorial.active {
+        return;
+    }
+
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+    // Anchors are marked anew every frame, so a widget no longer drawn is
+    // not pointed at
+    let anchors = ctx
+        .data_mut(|data| data.remove_temp::<Anchors>(anchors_id()))
+        .unwrap_or_default();
+
+    let Some((step, canvas)) = tutorial
+        .current_step()
+        .cloned()
+        .zip(tutorial.script.as_ref().map(|s| s.canvas))
+    else {
+        tutorial.stop();
+        return;
+    };
+
+    let highlight = anchors
+        .get(&step.hotspot.id)
+        .copied()
+        .unwrap_or_else(|| step.hotspot.bounds.to_rect(canvas, ctx.screen_rect()));
+    let highlight_color = egui::Color32::from_rgb(249, 226, 175);
+
+    // Persistent highlight around the region the step refers to
+    ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("tutorial_highlight"),
+    ))
+    .rect_stroke(
+        highlight,
+        6.0,
+        egui::Stroke::new(3.0, highlight_color),
+        egui::epaint::StrokeKind::Outside,
+    );
+
+    let mut advance = false;
+    let mut skip = false;
+
+    egui::Window::new(format!("🎓 {}", localizer.t(&step.title_id)))
+        .id(egui::Id::new("tutorial_callout"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_TOP, [-20.0, 60.0])
+        .show(ctx, |ui| {
+            ui.set_max_width(320.0);
+            ui.label(localizer.t(&step.body_id));
+
+            if let Some(error) = &tutorial.error {
+                ui.add_space(5.0);
+                ui.colored_label(egui::Color32::from_rgb(255, 100, 100), error);
+            }
+
+            if step.stage == TutorialStage::Generate && !tutorial.generated_assets.is_empty() {
+                ui.add_space(5.0);
+                for asset in &tutorial.generated_assets {
+                    ui.label(format!("✔ {}", asset.display()));
+                }
+            }
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                let total = tutorial.script.as_ref().map_or(0, |s| s.steps.len());
+                ui.label(localizer.t_args(
+                    "tutorial-step-of",
+                    &[
+                        ("step", (tutorial.current + 1).into()),
+                        ("total", total.into()),
+                    ],
+                ));
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let next_label = if step.stage == TutorialStage::Done {
+                        "tutorial-finish"
+                    } else {
+                        "tutorial-next"
+                    };
+                    if ui.button(localizer.t(next_label)).clicked() {
+                        advance = true;
+                    }
+                    if step.stage != TutorialStage::Done
+                        && ui.button(localizer.t("tutorial-skip")).clicked()
+                    {
+                        skip = true;
+                    }
+                });
+            });
+
+            // Hover hint over the highlighted region, reusing the overlay hotspot
+            render_overlay(
+                ui,
+                &OverlayConfig {
+                    content: OverlayContent::Hotspot {
+                        id: step.hotspot.id.clone(),
+                        hover_color: highlight_color,
+                        hover_stroke_width: 1.0,
+                        tooltip: Some(localizer.t(&step.hotspot.label)),
+                    },
+                    rect: highlight,
+                    block_interaction: false,
+                    opacity: 1.0,
+                    z_order: 1,
+                },
+            );
+        });
+
+    if skip {
+        tutorial.stop();
+        return;
+    }
+
+    if advance {
+        match run_scripted_action(
+            step.stage,
+            &mut tutorial,
+            &mut app_state,
+            guided_state.map(|s| s.into_inner()),
+            &pipeline.runtime,
+        ) {
+            Ok(true) => {
+                tutorial.error = None;
+                tutorial.current += 1;
+            }
+            Ok(false) => {}
+            Err(e) => {
+                error!("Tutorial step failed: {}", e);
+                tutorial.error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+/// Perform the scripted action for a stage; returns whether to move on
+fn run_scripted_action(
+    stage: TutorialStage,
+    tutorial: &mut TutorialState,
+    app_state: &mut AppState,
+    guided_state: Option<&mut GuidedModeState>,
+    runtime: &Runtime,
+) -> Result<bool> {
+    match stage {
+        TutorialStage::Welcome => {
+            app_state.set_wizard_mode(WelcomeAction::GuidedMode);
+        }
+        TutorialStage::Language => {
+            app_state.set_language(LanguageChoice::Rust);
+        }
+        TutorialStage::SelectGames => {
+            // Guided mode resources are created on the next frame
+            let Some(guided) = guided_state else {
+                return Ok(false);
+            };
+            if guided.selected_games.len() < 2 {
+                for game in tutorial_games() {
+                    guided.selected_games.insert(game.id, game);
+                }
+            }
+        }
+        TutorialStage::Blend => {
+            let guided = guided_state.context("Guided mode is not active")?;
+            create_blend(guided);
+            guided.current_step = 2;
+        }
+        TutorialStage::Generate => {
+            let guided = guided_state.context("Guided mode is not active")?;
+            let project_dir = tutorial
+                .project_dir
+                .clone()
+                .context("Tutorial project directory not set")?;
+            tutorial.generated_assets = generate_sample_assets(&project_dir, guided, runtime)?;
+        }
+        TutorialStage::Export => {
+            let guided = guided_state.context("Guided mode is not active")?;
+            let export = export_blend_to_config(guided).context("No blend to export")?;
+
+            if let Some(project_dir) = &tutorial.project_dir
+                && let Some(toml) = export_to_toml(guided)
+            {
+                std::fs::write(project_dir.join("blend.toml"), toml)?;
+            }
+
+            app_state.set_guided_export(export);
+            app_state.set_wizard_step(WizardStep::Complete);
+        }
+        TutorialStage::Done => {
+            tutorial.stop();
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Two timeline games from different genres used by the scripted project
+pub fn tutorial_games() -> Vec<&'static crate::vintage_games::TimelineGame> {
+    let database = database();
+    let games = database.games();
+    let Some(&first) = games.first() else {
+        return Vec::new();
+    };
+
+    let second = games
+        .iter()
+        .find(|g| g.genre != first.genre)
+        .or_else(|| games.get(1))
+        .copied();
+
+    std::iter::once(first).chain(second).collect()
+}
+
+/// Generate the sample project's player sprite and design document with
+/// the offline service, which answers without an API key or a network
+pub fn generate_sample_assets(
+    project_dir: &Path,
+    guided: &GuidedModeState,
+    runtime: &Runtime,
+) -> Result<Vec<PathBuf>> {
+    let blend = guided
+        .blend_result
+        .as_ref()
+        .context("Blend the games before generating")?;
+
+    let assets_dir = project_dir.join("assets");
+    std::fs::create_dir_all(&assets_dir).context("Failed to create tutorial assets directory")?;
+
+    let mut mechanics: Vec<_> = blend.mechanics.iter().map(String::as_str).collect();
+    mechanics.sort();
+    let sprite_prompt = format!("Player character sprite for {}", blend.name);
+    let design_prompt = format!(
+        "Write a short design document for {}: {}\nMechanics: {}",
+        blend.name,
+        blend.description,
+        mechanics.join(", ")
+    );
+
+    let service = AiService::offline()?;
+    let (sprite, design) = runtime.block_on(async {
+        let sprite = service
+            .image()
+            .generate_single(&sprite_prompt, ImageConfig::for_sprites())
+            .await?;
+        let design = service
+            .text()
+            .generate(&design_prompt, TextConfig::default())
+            .await?;
+        anyhow::Ok((sprite, design))
+    })?;
+
+    let sprite_path = assets_dir.join("player.png");
+    std::fs::write(&sprite_path, sprite)?;
+    let design_path = project_dir.join("DESIGN.md");
+    std::fs::write(&design_path, design)?;
+
+    Ok(vec![sprite_path, design_path])
+}
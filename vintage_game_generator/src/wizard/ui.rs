@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 use bevy_egui::{EguiContexts, egui};
 use crate::wizard::{AppState, AppDirectories, GenerationPipeline, state::{SelectedTab, LogLevel}, SwitchModeEvent, AppMode};
+use crate::wizard::i18n::Localizer;
 use crate::wizard::generate_flow::{WizardUI, WizardAction, WizardStep};
 use async_openai::types::{ChatCompletionRequestMessage, ChatCompletionRequestAssistantMessageArgs};
 
@@ -19,6 +20,7 @@ pub fn draw_app_ui(
     pipeline: Res<GenerationPipeline>,
     mut switch_mode_events: EventWriter<SwitchModeEvent>,
     mut ui_state: ResMut<UIState>,
+    localizer: Res<Localizer>,
 ) {
     // Initialize wizard if needed
     if ui_state.wizard_ui.is_none() {
@@ -88,7 +90,7 @@ pub fn draw_app_ui(
 
                                 app_state.add_log(
                                     LogLevel::Success,
-                                    "Wizard completed! Starting AI conversation...".to_string()
+                                    localizer.t("main-wizard-completed")
                                 );
                             }
                         }
@@ -105,30 +107,30 @@ pub fn draw_app_ui(
         if let Ok(ctx) = contexts.ctx_mut() {
             egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
                 ui.horizontal(|ui| {
-                    ui.heading("AI RPG Generator");
+                    ui.heading(localizer.t("main-title"));
                     ui.separator();
 
                     // Phase indicator
-                    ui.label(format!("Phase: {:?}", app_state.current_phase));
+                    ui.label(localizer.t_args("main-phase", &[("phase", format!("{:?}", app_state.current_phase).into())]));
                     ui.separator();
 
                     // API Key status
                     if app_state.api_key_set {
-                        ui.colored_label(egui::Color32::GREEN, "✓ API Key Set");
+                        ui.colored_label(egui::Color32::GREEN, localizer.t("main-api-key-set"));
                     } else {
-                        ui.colored_label(egui::Color32::RED, "✗ API Key Missing");
-                        ui.label("Set OPENAI_API_KEY environment variable");
+                        ui.colored_label(egui::Color32::RED, localizer.t("main-api-key-missing"));
+                        ui.label(localizer.t("main-api-key-hint"));
                     }
 
                     ui.separator();
 
                     // Generation controls
                     if app_state.generation_active {
-                        if ui.button("⏸ Pause").clicked() {
+                        if ui.button(localizer.t("main-pause")).clicked() {
                             app_state.set_generation_active(false);
                         }
                     } else {
-                        if ui.button("▶ Start").clicked() {
+                        if ui.button(localizer.t("main-start")).clicked() {
                             app_state.set_generation_active(true);
                         }
                     }
@@ -136,7 +138,7 @@ pub fn draw_app_ui(
                     ui.separator();
 
                     // Mode switch button
-                    if ui.button("📂 Browse Projects").clicked() {
+                    if ui.button(localizer.t("main-browse-projects")).clicked() {
                         switch_mode_events.write(SwitchModeEvent {
                             new_mode: AppMode::List,
                             project_path: None,
@@ -150,38 +152,38 @@ pub fn draw_app_ui(
         if let Ok(ctx) = contexts.ctx_mut() {
             egui::TopBottomPanel::top("tab_bar").show(ctx, |ui| {
         ui.horizontal(|ui| {
-            ui.selectable_value(&mut ui_state.selected_tab, SelectedTab::Conversation, "💬 Conversation");
-            ui.selectable_value(&mut ui_state.selected_tab, SelectedTab::Prompts, "📝 Prompts");
-            ui.selectable_value(&mut ui_state.selected_tab, SelectedTab::Assets, "🎨 Assets");
-            ui.selectable_value(&mut ui_state.selected_tab, SelectedTab::Build, "🔨 Build");
-            ui.selectable_value(&mut ui_state.selected_tab, SelectedTab::Logs, "📋 Logs");
+            ui.selectable_value(&mut ui_state.selected_tab, SelectedTab::Conversation, localizer.t("main-tab-conversation"));
+            ui.selectable_value(&mut ui_state.selected_tab, SelectedTab::Prompts, localizer.t("main-tab-prompts"));
+            ui.selectable_value(&mut ui_state.selected_tab, SelectedTab::Assets, localizer.t("main-tab-assets"));
+            ui.selectable_value(&mut ui_state.selected_tab, SelectedTab::Build, localizer.t("main-tab-build"));
+            ui.selectable_value(&mut ui_state.selected_tab, SelectedTab::Logs, localizer.t("main-tab-logs"));
         });
             });
 
             // Main panel with selected tab content
             egui::CentralPanel::default().show(ctx, |ui| {
                 match ui_state.selected_tab {
-                    SelectedTab::Conversation => conversation_tab(ui, &mut app_state, &mut ui_state.input_text),
-                    SelectedTab::Prompts => prompts_tab(ui, &mut app_state, &directories),
-                    SelectedTab::Assets => assets_tab(ui, &mut app_state),
-                    SelectedTab::Build => build_tab(ui, &mut app_state, &directories),
-                    SelectedTab::Logs => logs_tab(ui, &mut app_state),
+                    SelectedTab::Conversation => conversation_tab(ui, &mut app_state, &mut ui_state.input_text, &localizer),
+                    SelectedTab::Prompts => prompts_tab(ui, &mut app_state, &directories, &localizer),
+                    SelectedTab::Assets => assets_tab(ui, &mut app_state, &localizer),
+                    SelectedTab::Build => build_tab(ui, &mut app_state, &directories, &localizer),
+                    SelectedTab::Logs => logs_tab(ui, &mut app_state, &localizer),
                 }
             });
         }
 }
 
-fn conversation_tab(ui: &mut egui::Ui, app_state: &mut AppState, input_text: &mut String) {
+fn conversation_tab(ui: &mut egui::Ui, app_state: &mut AppState, input_text: &mut String, localizer: &Localizer) {
     ui.vertical(|ui| {
-        ui.heading("Metaprompt Conversation");
+        ui.heading(localizer.t("main-conversation-title"));
         ui.separator();
 
         // Game config display
         if let Some(config) = &app_state.game_specification {
             ui.group(|ui| {
-                ui.label(format!("Game: {}", config.name));
-                ui.label(format!("Genre: {}", config.genre));
-                ui.label(format!("Style: {:?}", config.art_style));
+                ui.label(localizer.t_args("main-game", &[("name", config.name.clone().into())]));
+                ui.label(localizer.t_args("main-genre", &[("genre", config.genre.clone().into())]));
+                ui.label(localizer.t_args("main-style", &[("style", format!("{:?}", config.art_style).into())]));
             });
         }
 
@@ -192,7 +194,7 @@ fn conversation_tab(ui: &mut egui::Ui, app_state: &mut AppState, input_text: &mu
             .max_height(400.0)
             .show(ui, |ui| {
                 // TODO: Show conversation messages from ConversationState
-                ui.label("Conversation history will appear here...");
+                ui.label(localizer.t("main-conversation-placeholder"));
             });
 
         ui.separator();
@@ -204,7 +206,7 @@ fn conversation_tab(ui: &mut egui::Ui, app_state: &mut AppState, input_text: &mu
                 // TODO: Send message
                 app_state.add_log(
                     LogLevel::Info,
-                    format!("User input: {}", input_text)
+                    localizer.t_args("main-user-input", &[("input", input_text.clone().into())])
                 );
                 input_text.clear();
             }
@@ -212,14 +214,14 @@ fn conversation_tab(ui: &mut egui::Ui, app_state: &mut AppState, input_text: &mu
     });
 }
 
-fn prompts_tab(ui: &mut egui::Ui, app_state: &mut AppState, directories: &AppDirectories) {
+fn prompts_tab(ui: &mut egui::Ui, app_state: &mut AppState, directories: &AppDirectories, localizer: &Localizer) {
     ui.vertical(|ui| {
-        ui.heading("Prompt Management");
+        ui.heading(localizer.t("main-prompts-title"));
         ui.separator();
 
         // Show validation queue
         ui.group(|ui| {
-            ui.label("Validation Queue:");
+            ui.label(localizer.t("main-validation-queue"));
             for prompt in &app_state.prompt_validation_queue {
                 ui.horizontal(|ui| {
                     if prompt.validated {
@@ -235,7 +237,7 @@ fn prompts_tab(ui: &mut egui::Ui, app_state: &mut AppState, directories: &AppDir
                     ui.label(format!("{}/{}", prompt.phase, prompt.name));
 
                     if !prompt.errors.is_empty() {
-                        ui.collapsing("Errors", |ui| {
+                        ui.collapsing(localizer.t("main-errors"), |ui| {
                             for error in &prompt.errors {
                                 ui.label(format!("• {}", error));
                             }
@@ -248,7 +250,7 @@ fn prompts_tab(ui: &mut egui::Ui, app_state: &mut AppState, directories: &AppDir
         ui.separator();
 
         // Browse prompts by phase
-        ui.collapsing("Browse Prompts", |ui| {
+        ui.collapsing(localizer.t("main-browse-prompts"), |ui| {
             let phases = [
                 "01_design",
                 "02_style",
@@ -270,7 +272,7 @@ fn prompts_tab(ui: &mut egui::Ui, app_state: &mut AppState, directories: &AppDir
                                     // TODO: Open prompt in editor
                                     app_state.add_log(
                                         LogLevel::Info,
-                                        format!("Opening prompt: {:?}", file_name)
+                                        localizer.t_args("main-opening-prompt", &[("file", file_name.to_string_lossy().into_owned().into())])
                                     );
                                 }
                             }
@@ -282,13 +284,13 @@ fn prompts_tab(ui: &mut egui::Ui, app_state: &mut AppState, directories: &AppDir
     });
 }
 
-fn assets_tab(ui: &mut egui::Ui, _app_state: &mut AppState) {
+fn assets_tab(ui: &mut egui::Ui, _app_state: &mut AppState, localizer: &Localizer) {
     ui.vertical(|ui| {
-        ui.heading("Generated Assets");
+        ui.heading(localizer.t("main-assets-title"));
         ui.separator();
 
         // Asset gallery
-        ui.label("Generated assets will appear here...");
+        ui.label(localizer.t("main-assets-placeholder"));
 
         // TODO: Display generated images using egui::Image
         // TODO: List audio files
@@ -296,27 +298,27 @@ fn assets_tab(ui: &mut egui::Ui, _app_state: &mut AppState) {
     });
 }
 
-fn build_tab(ui: &mut egui::Ui, app_state: &mut AppState, directories: &AppDirectories) {
+fn build_tab(ui: &mut egui::Ui, app_state: &mut AppState, directories: &AppDirectories, localizer: &Localizer) {
     ui.vertical(|ui| {
-        ui.heading("Build Output");
+        ui.heading(localizer.t("main-build-title"));
         ui.separator();
 
         ui.horizontal(|ui| {
-            if ui.button("📁 Open Build Directory").clicked() {
+            if ui.button(localizer.t("main-open-build-dir")).clicked() {
                 let build_dir = directories.project_dir.join("build");
                 if let Err(e) = open::that(&build_dir) {
                     app_state.add_log(
                         LogLevel::Error,
-                        format!("Failed to open build directory: {}", e)
+                        localizer.t_args("main-open-build-dir-failed", &[("error", e.to_string().into())])
                     );
                 }
             }
 
-            if ui.button("🎮 Run Game").clicked() {
+            if ui.button(localizer.t("main-run-game")).clicked() {
                 // TODO: Launch the generated game
                 app_state.add_log(
                     LogLevel::Info,
-                    "Launching generated game...".to_string()
+                    localizer.t("main-launching-game")
                 );
             }
         });
@@ -324,16 +326,16 @@ fn build_tab(ui: &mut egui::Ui, app_state: &mut AppState, directories: &AppDirec
         ui.separator();
 
         // File tree of build directory
-        ui.label("Build directory contents:");
+        ui.label(localizer.t("main-build-contents"));
         // TODO: Show file tree
     });
 }
 
-fn logs_tab(ui: &mut egui::Ui, app_state: &mut AppState) {
+fn logs_tab(ui: &mut egui::Ui, app_state: &mut AppState, localizer: &Localizer) {
     ui.vertical(|ui| {
         ui.horizontal(|ui| {
-            ui.heading("Logs");
-            if ui.button("Clear").clicked() {
+            ui.heading(localizer.t("main-logs-title"));
+            if ui.button(localizer.t("main-clear-logs")).clicked() {
                 app_state.clear_logs();
             }
         });
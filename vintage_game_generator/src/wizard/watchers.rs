@@ -1,5 +1,10 @@
+use crate::batch::PROJECT_FILE;
+use crate::metaprompts::PromptValidator;
 use crate::wizard::{
+    config::ProjectConfig,
+    config_reload::{ConfigReloadState, PromptChange},
     directories::AppDirectories,
+    pipeline::GenerationPipeline,
     state::{AppState, LogLevel},
 };
 use bevy::prelude::*;
@@ -93,23 +98,27 @@ fn is_prompt_file(path: &std::path::Path) -> bool {
         .unwrap_or(false)
 }
 
-/// Check for prompt file changes
+/// Check for prompt file changes, validating changed prompts for the
+/// reload panel
 pub fn check_prompt_changes(
+    mut commands: Commands,
     mut app_state: ResMut<AppState>,
     directories: Res<AppDirectories>,
     watcher_option: Option<ResMut<PromptWatcher>>,
+    pipeline: Res<GenerationPipeline>,
+    mut reload: ResMut<ConfigReloadState>,
 ) {
     let mut watcher = match watcher_option {
         Some(w) => w,
         None => {
             // Try to create watcher if it doesn't exist
             match PromptWatcher::new(&directories) {
-                Ok(_new_watcher) => {
+                Ok(new_watcher) => {
                     app_state.add_log(
                         LogLevel::Info,
                         "Started watching prompts directory for changes".to_string(),
                     );
-                    // We can't insert the resource from here, so just return
+                    commands.insert_resource(new_watcher);
                     return;
                 }
                 Err(e) => {
@@ -124,8 +133,10 @@ pub fn check_prompt_changes(
     };
 
     let events = watcher.poll_events();
+    let validator = PromptValidator::new();
 
     for event in events {
+        reload.record_prompt(validate_changed_prompt(&validator, &pipeline, &event));
         match event.event_type {
             FileEventType::Created => {
                 app_state.add_log(
@@ -189,22 +200,69 @@ pub fn check_prompt_changes(
     }
 }
 
+/// Run a changed prompt through the validator
+fn validate_changed_prompt(
+    validator: &PromptValidator,
+    pipeline: &GenerationPipeline,
+    event: &FileChangeEvent,
+) -> PromptChange {
+    let phase = event
+        .path
+        .parent()
+        .and_then(|p| p.file_name())
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+    let mut change = PromptChange {
+        path: event.path.clone(),
+        phase,
+        event_type: event.event_type.clone(),
+        validation: None,
+        error: None,
+    };
+    if event.event_type != FileEventType::Removed {
+        match pipeline
+            .runtime
+            .block_on(validator.validate_prompt(&event.path))
+        {
+            Ok(result) => change.validation = Some(result),
+            Err(e) => change.error = Some(format!("{e:#}")),
+        }
+    }
+    change
+}
+
 #[derive(Resource, Default)]
 pub struct ConfigModificationTracker {
+    /// File being tracked; another project being opened starts over
+    path: Option<PathBuf>,
     last_modified: Option<SystemTime>,
+    /// The configuration as last loaded, to diff the next change against
+    config: Option<ProjectConfig>,
 }
 
-/// Watch for config file changes
+/// Watch for config file changes. The reloaded configuration replaces the
+/// one in memory, and what changed goes to the reload panel.
 pub fn check_config_changes(
     mut app_state: ResMut<AppState>,
     directories: Res<AppDirectories>,
     mut tracker: ResMut<ConfigModificationTracker>,
+    mut reload: ResMut<ConfigReloadState>,
 ) {
-    let config_file = directories.project_dir.join("project.toml");
+    let config_file = directories
+        .config_file
+        .clone()
+        .unwrap_or_else(|| directories.project_dir.join(PROJECT_FILE));
 
     if !config_file.exists() {
         return;
     }
+    if tracker.path.as_ref() != Some(&config_file) {
+        *tracker = ConfigModificationTracker {
+            path: Some(config_file.clone()),
+            ..Default::default()
+        };
+    }
 
     // Check modification time
     if let Ok(metadata) = std::fs::metadata(&config_file)
@@ -213,6 +271,7 @@ pub fn check_config_changes(
         // Compare with last known modification time
         if tracker.last_modified.is_none() {
             tracker.last_modified = Some(modified);
+            tracker.config = ProjectConfig::load(&config_file).ok();
         } else if tracker.last_modified != Some(modified) {
             tracker.last_modified = Some(modified);
 
@@ -222,20 +281,26 @@ pub fn check_config_changes(
             );
 
             // Reload config
-            if let Ok(content) = std::fs::read_to_string(&config_file) {
-                match toml::from_str::<crate::metaprompts::GameConfig>(&content) {
-                    Ok(config) => {
-                        app_state.set_game_specification(config);
-                        app_state.add_log(
-                            LogLevel::Success,
-                            "Configuration reloaded successfully".to_string(),
-                        );
-                    }
-                    Err(e) => {
-                        app_state.add_log(LogLevel::Error, format!("Failed to parse config: {e}"));
-                    }
+            let config = match ProjectConfig::load(&config_file) {
+                Ok(config) => config,
+                Err(e) => {
+                    app_state.add_log(LogLevel::Error, format!("Failed to parse config: {e:#}"));
+                    return;
                 }
+            };
+            if let Some(previous) = &tracker.config
+                && let Err(e) = reload.record_config(previous, &config)
+            {
+                app_state.add_log(LogLevel::Warning, format!("Failed to diff config: {e:#}"));
             }
+            if let Some(manager) = app_state.config_manager.as_mut() {
+                manager.config = config.clone();
+            }
+            tracker.config = Some(config);
+            app_state.add_log(
+                LogLevel::Success,
+                "Configuration reloaded successfully".to_string(),
+            );
         }
     }
 }
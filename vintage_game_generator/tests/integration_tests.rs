@@ -424,5 +424,1700 @@ fn test_wizard_state_transitions() {
     );
 }
 
+/// Test creating projects from templates and saving projects as templates
+#[test]
+fn test_project_templates() {
+    use vintage_game_generator::wizard::config::ProjectConfig;
+    use vintage_game_generator::wizard::templates::{
+        ProjectTemplate, builtin_templates, load_templates,
+    };
+
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let base_dir = temp_dir.path();
+
+    let builtin = builtin_templates();
+    assert_eq!(builtin.len(), 3);
+
+    let config_path = builtin[0]
+        .create_project(base_dir, "My Quest")
+        .expect("Failed to create project from template");
+    let config = ProjectConfig::load(&config_path).expect("Failed to load created project");
+    assert_eq!(config.basic_info.name, "My Quest");
+    assert_eq!(config.basic_info.genre, builtin[0].project.basic_info.genre);
+
+    ProjectTemplate::from_project("My Quest", "", &config)
+        .save(base_dir)
+        .expect("Failed to save template");
+    let templates = load_templates(base_dir).expect("Failed to load templates");
+    assert_eq!(templates.len(), 4);
+    assert!(!templates[3].is_builtin());
+    assert_eq!(templates[3].project.basic_info.name, "My Quest");
+}
+
+/// Test duplicating, archiving, restoring and deleting a project
+#[test]
+fn test_project_duplicate_and_archive() {
+    use vintage_game_generator::wizard::config::ProjectConfig;
+    use vintage_game_generator::wizard::projects::{
+        archive_project, delete_project, duplicate_project, restore_archive,
+    };
+    use vintage_game_generator::wizard::templates::builtin_templates;
+
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let base_dir = temp_dir.path();
+    let config_path = builtin_templates()[0]
+        .create_project(base_dir, "Original")
+        .expect("Failed to create project");
+    let project_dir = config_path.parent().unwrap().to_path_buf();
+    std::fs::create_dir_all(project_dir.join("assets")).unwrap();
+    std::fs::write(project_dir.join("assets/hero.png"), b"png").unwrap();
+
+    let copy = duplicate_project(&project_dir, true).expect("Failed to duplicate project");
+    let copy_config = ProjectConfig::load(&copy).expect("Failed to load duplicate");
+    assert_eq!(copy_config.basic_info.name, "Original (copy)");
+    assert!(!copy.parent().unwrap().join("assets").exists());
+
+    let archive = base_dir.join("archives/original.zip");
+    archive_project(&project_dir, &archive).expect("Failed to archive project");
+    delete_project(&project_dir).expect("Failed to delete project");
+    assert!(!project_dir.exists());
+
+    let restored = restore_archive(&archive, base_dir).expect("Failed to restore archive");
+    assert_eq!(restored, config_path);
+    assert!(project_dir.join("assets/hero.png").exists());
+}
+
+/// Test searching the project index by name, genre, source game and date
+#[test]
+fn test_project_index_filter() {
+    use vintage_game_generator::wizard::config::ProjectConfig;
+    use vintage_game_generator::wizard::project_index::{
+        ProjectFilter, ProjectIndex, record_generation,
+    };
+    use vintage_game_generator::wizard::templates::builtin_templates;
+
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let base_dir = temp_dir.path();
+    let mut projects = Vec::new();
+    for template in builtin_templates() {
+        let config_path = template
+            .create_project(base_dir, "")
+            .expect("Failed to create project");
+        let config = ProjectConfig::load(&config_path).expect("Failed to load project");
+        projects.push((config_path.parent().unwrap().to_path_buf(), config));
+    }
+
+    let mut index = ProjectIndex::default();
+    index.sync(projects.iter().map(|(dir, config)| (dir.as_path(), config)));
+    index.save(base_dir).expect("Failed to save index");
+    record_generation(&projects[0].0).expect("Failed to record generation");
+    let index = ProjectIndex::load(base_dir).expect("Failed to load index");
+    assert_eq!(index.projects.len(), 3);
+    assert!(index.get(&projects[0].0).unwrap().last_generated.is_some());
+
+    let matching = |filter: &ProjectFilter| {
+        index
+            .projects
+            .iter()
+            .filter(|entry| filter.matches(entry))
+            .count()
+    };
+    let by_query = |query: &str| ProjectFilter {
+        query: query.to_string(),
+        ..Default::default()
+    };
+    assert_eq!(matching(&ProjectFilter::default()), 3);
+    assert_eq!(matching(&by_query("raiden")), 1);
+    assert_eq!(matching(&by_query("untitled jrpg")), 1);
+    assert_eq!(
+        matching(&ProjectFilter {
+            genre: Some("puzzle".to_string()),
+            ..Default::default()
+        }),
+        1
+    );
+    let tomorrow = chrono::Utc::now().date_naive() + chrono::Days::new(1);
+    assert_eq!(
+        matching(&ProjectFilter {
+            created_from: Some(tomorrow),
+            ..Default::default()
+        }),
+        0
+    );
+}
+
+#[test]
+fn test_wizard_translations() {
+    use vintage_game_generator::wizard::i18n::{LANGUAGES, Localizer};
+
+    let message_ids = |language: &str| -> Vec<String> {
+        let path = format!(
+            "{}/assets/i18n/{language}/wizard.ftl",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let contents = std::fs::read_to_string(&path).expect("Failed to read catalog");
+        contents
+            .lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_lowercase()))
+            .filter_map(|line| Some(line.split_once(" =")?.0.to_string()))
+            .collect()
+    };
+    let baseline = message_ids("en-US");
+    for language in LANGUAGES {
+        for id in message_ids(language.id) {
+            assert!(
+                baseline.contains(&id),
+                "{id} in {} is missing from en-US",
+                language.id
+            );
+        }
+    }
+
+    let mut localizer = Localizer::new("fr-FR");
+    assert_eq!(localizer.language(), "en-US");
+    assert_eq!(localizer.t("list-edit"), "Edit");
+    assert_eq!(localizer.t("no-such-message"), "no-such-message");
+    assert_eq!(
+        localizer.t_args("list-found", &[("count", 1.into()), ("dir", "base".into())]),
+        "Found one project in: base"
+    );
+
+    assert!(localizer.set_language("es"));
+    assert_eq!(localizer.language(), "es-ES");
+    assert_eq!(localizer.t("list-edit"), "Editar");
+    assert!(!localizer.set_language("xx-YY"));
+    assert_eq!(localizer.language(), "es-ES");
+}
+
+#[test]
+fn test_config_reload_diff() {
+    use vintage_game_generator::metaprompts::DESIGN_STEPS;
+    use vintage_game_generator::wizard::config::ProjectConfig;
+    use vintage_game_generator::wizard::config_reload::{
+        ConfigReloadState, affected_steps, diff_configs,
+    };
+
+    let mut old = ProjectConfig::default();
+    old.basic_info.name = "Moonlit Quest".to_string();
+    old.basic_info.genre = "RPG".to_string();
+
+    let mut cosmetic = old.clone();
+    cosmetic.wizard_state.current_step = "review".to_string();
+    let changes = diff_configs(&old, &cosmetic).expect("Failed to diff");
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].path, "wizard_state.current_step");
+    assert!(affected_steps(&old, &cosmetic).is_empty());
+
+    let mut regenre = old.clone();
+    regenre.basic_info.genre = "Action RPG".to_string();
+    regenre
+        .visual_style
+        .reference_games
+        .push("Secret of Mana".to_string());
+    let changes = diff_configs(&old, &regenre).expect("Failed to diff");
+    let genre = changes
+        .iter()
+        .find(|change| change.path == "basic_info.genre")
+        .expect("Genre change missing");
+    assert_eq!(genre.before.as_deref(), Some("RPG"));
+    assert_eq!(genre.after.as_deref(), Some("Action RPG"));
+    assert!(
+        changes
+            .iter()
+            .any(|change| change.path == "visual_style.reference_games")
+    );
+    assert_eq!(affected_steps(&old, &regenre), DESIGN_STEPS[..2].to_vec());
+
+    let mut renamed = old.clone();
+    renamed.basic_info.name = "Sunlit Quest".to_string();
+    assert_eq!(affected_steps(&old, &renamed), DESIGN_STEPS.to_vec());
+
+    // Changing a setting back drops it from the panel
+    let mut state = ConfigReloadState::default();
+    state.record_config(&old, &regenre).unwrap();
+    assert!(state.open);
+    state.record_config(&regenre, &old).unwrap();
+    assert!(state.changes.is_empty());
+    assert!(!state.full_run);
+    assert!(state.steps.is_empty());
+}
+
+#[test]
+fn test_content_language() {
+    use vintage_ai_client::text::with_content_language;
+    use vintage_game_generator::metaprompts::DESIGN_STEPS;
+    use vintage_game_generator::wizard::config::ProjectConfig;
+    use vintage_game_generator::wizard::config_reload::affected_steps;
+
+    // Projects saved before the setting existed load as English
+    let config = ProjectConfig::default();
+    let saved = toml::to_string(&config).expect("Failed to serialize");
+    assert!(!saved.contains("content_language"));
+    let loaded: ProjectConfig = toml::from_str(&saved).expect("Failed to parse");
+    assert_eq!(loaded.basic_info.content_language, None);
+
+    assert_eq!(with_content_language("Write".to_string(), None), "Write");
+    assert_eq!(
+        with_content_language("Write".to_string(), Some(" ")),
+        "Write"
+    );
+    let prompt = with_content_language("Write".to_string(), Some("Japanese"));
+    assert!(prompt.starts_with("Write\n\n"));
+    assert!(prompt.contains("in Japanese"));
+
+    // Switching the language redoes the steps that write game text
+    let mut japanese = config.clone();
+    japanese.basic_info.content_language = Some("Japanese".to_string());
+    assert_eq!(
+        affected_steps(&config, &japanese),
+        DESIGN_STEPS[..3].to_vec()
+    );
+}
+
+#[test]
+fn test_prompt_override_preview() {
+    use vintage_ai_client::AiService;
+    use vintage_ai_client::prompts::PromptLibrary;
+    use vintage_game_generator::wizard::config::ProjectConfig;
+    use vintage_game_generator::wizard::prompt_preview::{create_override, render_prompt};
+
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path();
+    let path = create_override(project_dir, "audio/sound_effect").expect("Failed to copy prompt");
+    assert_eq!(path, project_dir.join("prompts/audio/sound_effect.jinja"));
+    let library = PromptLibrary::new().with_project_dir(project_dir);
+    assert_eq!(
+        library.source("audio/sound_effect").unwrap().override_path,
+        Some(path.clone())
+    );
+
+    // Copying again keeps the edited override
+    std::fs::write(&path, "Sound of a {{ effect_type }}").unwrap();
+    create_override(project_dir, "audio/sound_effect").unwrap();
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let prompt = runtime
+        .block_on(async {
+            let service = AiService::new()?.with_prompt_overrides(project_dir);
+            render_prompt(
+                &service,
+                "audio/sound_effect",
+                "jump",
+                &ProjectConfig::default(),
+            )
+            .await
+        })
+        .expect("Failed to render prompt");
+    assert_eq!(prompt, "Sound of a jump");
+}
+
+#[test]
+fn test_prompt_variables() {
+    use vintage_ai_client::AiService;
+    use vintage_ai_client::prompts::{PromptLibrary, PromptMacro, PromptVariables};
+    use vintage_game_generator::wizard::config::ProjectConfig;
+    use vintage_game_generator::wizard::prompt_preview::{create_override, render_prompt};
+
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path();
+    let library = PromptLibrary::new().with_project_dir(project_dir);
+
+    let mut variables = PromptVariables::default();
+    variables
+        .variables
+        .insert("studio_style".to_string(), "moody pixel art".to_string());
+    variables
+        .variables
+        .insert("protagonist_name".to_string(), "Aria".to_string());
+    variables.macros.insert(
+        "hero".to_string(),
+        PromptMacro {
+            args: vec!["pose".to_string()],
+            body: "{{ protagonist_name }} {{ pose }}".to_string(),
+        },
+    );
+    variables
+        .save(&library.variables_path().unwrap())
+        .expect("Failed to save variables");
+    assert_eq!(library.variables().unwrap(), variables);
+
+    let path = create_override(project_dir, "audio/sound_effect").unwrap();
+    std::fs::write(
+        &path,
+        "{{ hero(\"leaping\") }} makes a {{ effect_type }} in {{ studio_style }}",
+    )
+    .unwrap();
+    library
+        .ensure_variables_defined()
+        .expect("Every variable is defined");
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let prompt = runtime
+        .block_on(async {
+            let service = AiService::new()?.with_prompt_overrides(project_dir);
+            render_prompt(
+                &service,
+                "audio/sound_effect",
+                "jump",
+                &ProjectConfig::default(),
+            )
+            .await
+        })
+        .expect("Failed to render prompt");
+    assert_eq!(prompt, "Aria leaping makes a jump in moody pixel art");
+    assert_eq!(
+        library.expand("A tale of {{ protagonist_name }}").unwrap(),
+        "A tale of Aria"
+    );
+
+    // An undefined name stops generation before it starts
+    std::fs::write(&path, "{{ rival_name }} makes a {{ effect_type }}").unwrap();
+    let issues = library.check_variables().unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].location, "audio/sound_effect");
+    assert!(issues[0].message.contains("rival_name"));
+    assert!(library.ensure_variables_defined().is_err());
+    assert!(library.expand("{{ rival_name }}").is_err());
+}
+
+#[test]
+fn test_metaprompt_variable_lint() {
+    use vintage_game_generator::metaprompts::{
+        IssueKind, METAPROMPT_DIRS, PromptValidator, Severity,
+    };
+
+    let validator = PromptValidator::new();
+
+    // The bundled metaprompts only use names from their contexts
+    let dirs: Vec<PathBuf> = METAPROMPT_DIRS.iter().map(PathBuf::from).collect();
+    let bundled = validator.validate_metaprompts(&dirs);
+    assert!(!bundled.is_empty(), "No bundled metaprompts found");
+    for result in &bundled {
+        assert!(result.valid, "{}", result.to_markdown());
+    }
+
+    // A project copy takes precedence and is linted against the same context
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::write(
+        temp_dir.path().join("03_world.jinja"),
+        "{{ config.name }} in {{ wrld.name }}{% for town in config.towns %}{{ town.name }}{% endfor %}",
+    )
+    .unwrap();
+    let mut dirs = dirs;
+    dirs.insert(0, temp_dir.path().to_path_buf());
+    let results = validator.validate_metaprompts(&dirs);
+    let world = results
+        .iter()
+        .find(|result| result.path.starts_with(temp_dir.path()))
+        .expect("The project's copy is validated");
+    assert!(!world.valid);
+
+    let undefined: Vec<_> = world.errors().collect();
+    assert_eq!(undefined.len(), 1);
+    assert_eq!(undefined[0].kind, IssueKind::UndefinedVariable);
+    assert_eq!(undefined[0].variable.as_deref(), Some("wrld"));
+
+    // The optional project context may go unused, the style guide may not
+    let unused: Vec<_> = world
+        .issues
+        .iter()
+        .filter(|issue| issue.kind == IssueKind::UnusedVariable)
+        .collect();
+    assert_eq!(unused.len(), 1);
+    assert_eq!(unused[0].severity, Severity::Warning);
+    assert_eq!(unused[0].variable.as_deref(), Some("style_guide"));
+
+    // Syntax errors are reported without rendering the template
+    let broken = validator.validate_source(
+        &temp_dir.path().join("02_style.jinja"),
+        "{% for dungeon in config.dungeons %}{{ dungeon.name }}",
+    );
+    assert!(!broken.valid);
+    assert_eq!(broken.issues[0].kind, IssueKind::Syntax);
+}
+
+#[test]
+fn test_run_cost_estimate() {
+    use vintage_game_generator::GameGenerator;
+    use vintage_game_generator::metaprompts::{DESIGN_STEPS, GenerationCheckpoint};
+    use vintage_game_generator::wizard::config::ProjectConfig;
+
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path();
+    let mut config = ProjectConfig::default();
+    config.basic_info.name = "Estimate Quest".to_string();
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let (full, resumed) = runtime
+        .block_on(async {
+            let mut generator = GameGenerator::new().await?;
+            generator.set_project_config(config);
+            generator.set_checkpoint_dir(project_dir, false);
+            let full = generator.estimate_from_project().await?;
+
+            // A resumed run reuses the checkpointed core design
+            let mut checkpoint = GenerationCheckpoint::new("Estimate Quest");
+            checkpoint.record("core_design", "A short design.".to_string());
+            checkpoint.save(project_dir)?;
+            generator.set_checkpoint_dir(project_dir, true);
+            let resumed = generator.estimate_from_project().await?;
+            Ok::<_, anyhow::Error>((full, resumed))
+        })
+        .expect("Failed to estimate the run");
+
+    let steps: Vec<&str> = full.steps.iter().map(|step| step.step).collect();
+    assert_eq!(steps, DESIGN_STEPS);
+    for step in &full.steps {
+        assert!(!step.checkpointed);
+        assert_eq!(step.estimate.requests, 1);
+        assert!(step.estimate.prompt_tokens > 0);
+        assert!(step.estimate.min_cost <= step.estimate.cost);
+        assert!(step.estimate.cost <= step.estimate.max_cost);
+    }
+    let total = full.total();
+    assert_eq!(total.requests, DESIGN_STEPS.len());
+    assert!(total.max_cost > 0.0);
+
+    assert!(resumed.steps[0].checkpointed);
+    assert_eq!(resumed.steps[0].estimate.requests, 0);
+    assert!(resumed.total().cost < total.cost);
+}
+
+#[test]
+fn test_design_run_draws_character_sprites() {
+    use std::sync::{Arc, Mutex};
+    use vintage_ai_client::AiConfig;
+    use vintage_ai_client::mock::MOCK_PROVIDER;
+    use vintage_game_generator::metaprompts::{GenerationCheckpoint, SPRITE_ARTIFACT};
+    use vintage_game_generator::wizard::config::{CharacterConcept, ProjectConfig};
+    use vintage_game_generator::{GameGenerator, ProgressUpdate};
+
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path();
+    let mut config = ProjectConfig::default();
+    config.basic_info.name = "Sprite Quest".to_string();
+    config.ai_context.character_concepts = vec![CharacterConcept {
+        name: "Slime King".to_string(),
+        role: "boss".to_string(),
+        description: "A crowned blob of green goo".to_string(),
+        abilities: Vec::new(),
+    }];
+    let ai_config = AiConfig {
+        ai_provider: MOCK_PROVIDER.to_string(),
+        ..AiConfig::default()
+    };
+
+    let updates = Arc::new(Mutex::new(Vec::new()));
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let estimate = runtime
+        .block_on(async {
+            let mut generator = GameGenerator::with_ai_config(&ai_config).await?;
+            generator.set_project_config(config);
+            generator.set_checkpoint_dir(project_dir, false);
+            let estimate = generator.estimate_from_project().await?;
+            let sink = updates.clone();
+            generator
+                .generate_from_project(move |update| sink.lock().unwrap().push(update))
+                .await?;
+            Ok::<_, anyhow::Error>(estimate)
+        })
+        .expect("Failed to run the design");
+
+    let sprites = estimate
+        .steps
+        .iter()
+        .find(|step| step.step == SPRITE_ARTIFACT)
+        .expect("The estimate includes the sprites");
+    assert_eq!(sprites.estimate.requests, 1);
+
+    let path = project_dir.join("assets/sprites/spr_slime_king.png");
+    assert!(path.exists(), "The sprite is written to the project");
+    let checkpoint = GenerationCheckpoint::load(project_dir)
+        .unwrap()
+        .expect("The run is checkpointed");
+    assert_eq!(
+        checkpoint.output("spr_slime_king"),
+        Some("assets/sprites/spr_slime_king.png")
+    );
+
+    let updates = updates.lock().unwrap();
+    let drawn = updates
+        .iter()
+        .find(|update| update.artifact.is_some())
+        .expect("The sprite is reported with the progress");
+    let published = ProgressUpdate::from_run(drawn);
+    assert_eq!(published.step, "spr_slime_king");
+    let artifact = published.artifact.expect("The update carries the sprite");
+    assert_eq!(artifact.artifact_type, SPRITE_ARTIFACT);
+    assert!(artifact.preview.is_some_and(|preview| !preview.is_empty()));
+}
+
+#[test]
+fn test_regenerate_single_assets() {
+    use vintage_ai_client::AiConfig;
+    use vintage_ai_client::history::ArtifactHistory;
+    use vintage_ai_client::mock::MOCK_PROVIDER;
+    use vintage_game_generator::GameGenerator;
+    use vintage_game_generator::metaprompts::{SPRITE_ARTIFACT, TRACK_ARTIFACT};
+    use vintage_game_generator::wizard::config::{CharacterConcept, LevelTheme, ProjectConfig};
+
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path();
+    let mut config = ProjectConfig::default();
+    config.basic_info.name = "Redo Quest".to_string();
+    config.ai_context.character_concepts = vec![CharacterConcept {
+        name: "Slime King".to_string(),
+        role: "boss".to_string(),
+        description: "A crowned blob of green goo".to_string(),
+        abilities: Vec::new(),
+    }];
+    config.ai_context.level_themes = vec![LevelTheme {
+        name: "Sunken Vault".to_string(),
+        atmosphere: "damp and echoing".to_string(),
+        key_mechanics: Vec::new(),
+        visual_elements: Vec::new(),
+    }];
+    let ai_config = AiConfig {
+        ai_provider: MOCK_PROVIDER.to_string(),
+        ..AiConfig::default()
+    };
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let (estimate, sprite, track, unknown) = runtime
+        .block_on(async {
+            let mut generator = GameGenerator::with_ai_config(&ai_config).await?;
+            generator.set_project_config(config);
+            generator.set_checkpoint_dir(project_dir, false);
+            let estimate = generator.estimate_from_project().await?;
+            generator.generate_from_project(|_| {}).await?;
+
+            let sprite = generator
+                .regenerate_asset("spr_slime_king", Some("a taller crown"))
+                .await?;
+            let track = generator
+                .regenerate_asset("bgm_sunken_vault", Some("slower"))
+                .await?;
+            let unknown = generator.regenerate_asset("spr_nobody", None).await;
+            Ok::<_, anyhow::Error>((estimate, sprite, track, unknown))
+        })
+        .expect("Failed to regenerate");
+
+    let tracks = estimate
+        .steps
+        .iter()
+        .find(|step| step.step == TRACK_ARTIFACT)
+        .expect("The estimate includes the tracks");
+    assert_eq!(tracks.estimate.requests, 1);
+
+    // Each asset is redone on its own, and the history keeps the version
+    // it replaces
+    assert_eq!(sprite.kind, SPRITE_ARTIFACT);
+    assert_eq!(
+        sprite.path,
+        project_dir.join("assets/sprites/spr_slime_king.png")
+    );
+    assert_eq!(track.kind, TRACK_ARTIFACT);
+    assert_eq!(
+        track.path,
+        project_dir.join("assets/music/bgm_sunken_vault.md")
+    );
+    assert!(track.path.is_file());
+    let history = ArtifactHistory::for_project(project_dir);
+    let versions = history.versions("spr_slime_king").unwrap();
+    assert_eq!(versions.len(), 2);
+    assert_eq!(versions[1].note, "a taller crown");
+    assert!(unknown.is_err());
+}
+
+#[test]
+fn test_disk_footprint() {
+    use vintage_game_generator::footprint::{
+        AssetChecklist, AssetKind, ChecklistItem, InsufficientSpace, ensure_disk_space,
+    };
+    use vintage_game_generator::wizard::config::{CharacterConcept, ProjectConfig};
+
+    let mut config = ProjectConfig::default();
+    config.features.dialogue_system = None;
+    let base = AssetChecklist::for_project(&config);
+    assert!(base.total_bytes() > 0);
+    assert!(base.required_bytes() > base.total_bytes());
+
+    // Every character adds a sprite beyond the minimum cast
+    config.ai_context.character_concepts = (0..10)
+        .map(|i| CharacterConcept {
+            name: format!("Hero {i}"),
+            role: "ally".to_string(),
+            description: String::new(),
+            abilities: Vec::new(),
+        })
+        .collect();
+    let cast = AssetChecklist::for_project(&config);
+    assert!(cast.total_bytes() > base.total_bytes());
+    assert!(!cast.items.iter().any(|item| item.name == "Portraits"));
+
+    let image = AssetKind::Image {
+        width: 64,
+        height: 32,
+    };
+    assert_eq!(
+        ChecklistItem::new("Icons", 3, image).bytes(),
+        3 * 64 * 32 * 2
+    );
+    let second = AssetKind::Audio { seconds: 1.0 }.bytes();
+    assert!(AssetKind::Audio { seconds: 2.0 }.bytes() > second);
+
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("not-created-yet");
+    ensure_disk_space(&AssetChecklist::default(), &project_dir)
+        .expect("An empty checklist always fits");
+    let huge = AssetChecklist {
+        items: vec![ChecklistItem::new("Everything", u32::MAX, image)],
+    };
+    if let Err(e) = ensure_disk_space(&huge, &project_dir) {
+        let error = e
+            .downcast_ref::<InsufficientSpace>()
+            .expect("Refused for lack of space");
+        assert!(error.available < error.required);
+        assert!(e.to_string().contains("choose another base directory"));
+    }
+}
+
+#[test]
+fn test_authentic_limits() {
+    use vintage_game_generator::metaprompts::DESIGN_STEPS;
+    use vintage_game_generator::vintage_games::limits::{LOOSE_LIMITS, era_of_games};
+    use vintage_game_generator::vintage_games::{AuthenticLimits, Era, Limit, Strictness};
+    use vintage_game_generator::wizard::config::ProjectConfig;
+    use vintage_game_generator::wizard::config_reload::affected_steps;
+
+    // The ends of the slider are the loose limits and the era's own
+    let loose = AuthenticLimits::new(Era::EarlyConsole, 0.0);
+    assert_eq!(loose.limits, LOOSE_LIMITS);
+    assert_eq!(loose.strictness(), Strictness::Loose);
+    let strict = AuthenticLimits::new(Era::EarlyConsole, 1.0);
+    assert_eq!(strict.limits, Era::EarlyConsole.hardware_limits());
+    assert_eq!(strict.strictness(), Strictness::Strict);
+    let faithful = AuthenticLimits::new(Era::EarlyConsole, 0.5);
+    assert_eq!(faithful.strictness(), Strictness::Faithful);
+    assert!(faithful.limits.palette_colors < loose.limits.palette_colors);
+    assert!(faithful.limits.palette_colors > strict.limits.palette_colors);
+    assert_eq!(AuthenticLimits::new(Era::EarlyConsole, 3.0), strict);
+
+    let rules = strict.rules(&[Limit::Palette, Limit::DialogueLength]);
+    assert!(rules.contains("never exceed"));
+    assert!(rules.contains("25 colors"));
+    assert!(rules.contains("48 characters"));
+    assert!(!rules.contains("sound channels"));
+
+    assert_eq!(
+        era_of_games(&[
+            "Balloon Fight",
+            "Desert Strike: Return to the Gulf",
+            "balloon fight"
+        ]),
+        Some(Era::EarlyConsole)
+    );
+    assert_eq!(era_of_games(&["No Such Game"]), None);
+
+    let mut config = ProjectConfig::default();
+    assert_eq!(config.reference_era(), Era::Peak16Bit);
+    config.visual_style.reference_games = vec!["Balloon Fight".to_string()];
+    assert_eq!(config.authentic_limits().era, Era::EarlyConsole);
+    config.authenticity.era = Some(Era::ArcadeGoldenAge);
+    assert_eq!(config.authentic_limits().era, Era::ArcadeGoldenAge);
+
+    // The limits reach every design step
+    let mut stricter = config.clone();
+    stricter.authenticity.level = 1.0;
+    assert_eq!(affected_steps(&config, &stricter), DESIGN_STEPS.to_vec());
+
+    let saved: ProjectConfig = toml::from_str(&toml::to_string(&stricter).unwrap()).unwrap();
+    assert_eq!(saved.authenticity, stricter.authenticity);
+}
+
+#[test]
+fn test_legacy_import() {
+    use vintage_game_generator::wizard::config::{ConfigManager, ProjectConfig};
+    use vintage_game_generator::wizard::journal;
+    use vintage_game_generator::wizard::legacy_import::{
+        IMPORT_JOURNAL_KIND, find_legacy_projects, import_legacy_project,
+    };
+
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let legacy_root = temp_dir.path().join("old");
+    let legacy_dir = legacy_root.join("star-quest");
+    std::fs::create_dir_all(legacy_dir.join("output/sprites")).unwrap();
+    std::fs::create_dir_all(legacy_dir.join("prompts")).unwrap();
+    std::fs::write(
+        legacy_dir.join("game.toml"),
+        r#"
+game_name = "Star Quest"
+genre = "Shoot 'em up"
+art_style = "Neon pixel art"
+reference_games = ["Gradius"]
+mechanics = ["Power-ups"]
+features = ["Boss rush"]
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        legacy_dir.join("conversation.json"),
+        r#"[{"role": "user", "content": "Make it fast"}]"#,
+    )
+    .unwrap();
+    std::fs::write(legacy_dir.join("prompts/design.md"), "prompt").unwrap();
+    std::fs::write(legacy_dir.join("output/design.md"), "# Star Quest").unwrap();
+    std::fs::write(legacy_dir.join("output/sprites/ship.png"), b"png").unwrap();
+    // Current projects are not legacy ones
+    ConfigManager::new(&legacy_root.join("current"), Some("project"))
+        .and_then(|mut manager| manager.save())
+        .unwrap();
+
+    assert_eq!(
+        find_legacy_projects(&legacy_root).unwrap(),
+        vec![legacy_dir.clone()]
+    );
+
+    let base_dir = temp_dir.path().join("projects");
+    let config_path =
+        import_legacy_project(&legacy_dir, &base_dir).expect("Failed to import legacy project");
+    let project_dir = config_path.parent().unwrap();
+    let config = ProjectConfig::load(&config_path).unwrap();
+    assert_eq!(config.basic_info.name, "Star Quest");
+    assert_eq!(config.basic_info.genre, "Shoot 'em up");
+    assert_eq!(config.visual_style.reference_games, vec!["Gradius"]);
+    assert_eq!(config.features.custom_features[0].name, "Boss rush");
+    assert_eq!(config.ai_context.conversation_history.len(), 1);
+    assert!(project_dir.join("design.md").is_file());
+    assert!(project_dir.join("assets/sprites/ship.png").is_file());
+    assert!(project_dir.join("prompts/generated/design.md").is_file());
+    // The legacy project is left as it was
+    assert!(legacy_dir.join("game.toml").is_file());
+
+    let entries = journal::read(project_dir).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].kind, IMPORT_JOURNAL_KIND);
+    assert!(
+        entries[0]
+            .details
+            .contains(&"output/sprites/ship.png -> assets/sprites/ship.png".to_string())
+    );
+}
+
+#[test]
+fn test_game_visuals() {
+    use vintage_games::visuals::{GameVisuals, describe_game};
+
+    let visuals = GameVisuals {
+        game_id: 1,
+        palette: &["#101820", "#3050a0"],
+        perspective: "side view",
+        ui_layout: &["score along the top edge"],
+    };
+    assert_eq!(
+        visuals.describe(),
+        "side view; palette #101820, #3050a0; interface: score along the top edge"
+    );
+
+    let without_layout = GameVisuals {
+        ui_layout: &[],
+        ..visuals
+    };
+    assert_eq!(
+        without_layout.describe(),
+        "side view; palette #101820, #3050a0"
+    );
+
+    // Every entry belongs to a timeline game and describes something
+    for visuals in vintage_games::GAME_VISUALS {
+        assert!(
+            vintage_games::games::TIMELINE_GAMES
+                .iter()
+                .any(|game| game.id == visuals.game_id)
+        );
+        assert!(!visuals.describe().is_empty());
+    }
+    assert_eq!(describe_game("Not a timeline game"), None);
+}
+
+#[test]
+fn test_game_sources() {
+    // Every game is attributed to the sources its data came from
+    for game in vintage_games::games::TIMELINE_GAMES {
+        assert!(!game.sources.is_empty(), "{} has no source", game.name);
+        assert!(
+            game.sources
+                .iter()
+                .all(|source| ["giantbomb", "igdb"].contains(source))
+        );
+    }
+
+    let game = &vintage_games::games::TIMELINE_GAMES[0];
+    let json = serde_json::to_value(game).unwrap();
+    assert_eq!(json["sources"], serde_json::json!(game.sources));
+}
+
+#[test]
+fn test_era_packs() {
+    use vintage_games::era_packs::{LoadedTimeline, load_era_packs};
+
+    let dir = TempDir::new().unwrap();
+    let embedded = &vintage_games::games::TIMELINE_GAMES[0];
+    let pack = serde_json::json!({
+        "format_version": 1,
+        "id": "atari",
+        "name": "Atari Era",
+        "start_year": 1977,
+        "end_year": 1983,
+        "created_at": "2024-01-01T00:00:00Z",
+        "games": [
+            {
+                "id": 9001,
+                "year": 1978,
+                "genre": "Shooter",
+                "name": "Space Invaders",
+                "deck": null,
+                "platforms": ["Arcade", "Atari 2600"],
+                "developer": "Taito",
+                "image_urls": { "original": "https://example.com/si.jpg" },
+                "site_url": "",
+                "sources": ["giantbomb"]
+            },
+            {
+                "id": embedded.id,
+                "year": embedded.year,
+                "genre": embedded.genre,
+                "name": embedded.name,
+                "image_urls": { "original": "" }
+            }
+        ]
+    });
+    std::fs::write(dir.path().join("atari.json"), pack.to_string()).unwrap();
+    std::fs::write(dir.path().join("old.json"), r#"{"format_version": 0}"#).unwrap();
+
+    // Packs of another format are skipped
+    let packs = load_era_packs(dir.path());
+    assert_eq!(packs.len(), 1);
+
+    // The pack's new game joins the embedded ones; the one it shares with
+    // them keeps its embedded entry
+    let timeline = LoadedTimeline::new(vintage_games::games::TIMELINE_GAMES, packs);
+    assert_eq!(
+        timeline.games.len(),
+        vintage_games::games::TIMELINE_GAMES.len() + 1
+    );
+    assert_eq!(timeline.packs[0].game_count, 1);
+    assert_eq!(timeline.span().unwrap().0, 1978);
+
+    let invaders = timeline.games.iter().find(|g| g.id == 9001).unwrap();
+    assert_eq!(invaders.name, "Space Invaders");
+    assert_eq!(invaders.platforms, ["Arcade", "Atari 2600"]);
+    assert_eq!(invaders.developer, Some("Taito"));
+
+    // The embedded timeline is part of every game list
+    assert!(
+        vintage_games::all_games()
+            .iter()
+            .any(|game| game.id == embedded.id)
+    );
+}
+
+#[test]
+fn test_game_database() {
+    use vintage_games::database::{GameDatabase, GameDatabaseFile, USER_GAME_ID_START};
+    use vintage_games::era_packs::{LoadedTimeline, PackGame};
+
+    let embedded = vintage_games::games::TIMELINE_GAMES;
+    let mut database = GameDatabase::from_timeline(LoadedTimeline::new(embedded, Vec::new()));
+    assert_eq!(database.len(), embedded.len());
+    assert_eq!(
+        database.games_by_year(embedded[0].year).len(),
+        embedded
+            .iter()
+            .filter(|g| g.year == embedded[0].year)
+            .count()
+    );
+
+    // A user's obscure favorite joins the timeline in year order
+    let id = database.next_user_game_id();
+    assert_eq!(id, USER_GAME_ID_START);
+    let game: PackGame = serde_json::from_value(serde_json::json!({
+        "id": id,
+        "year": 1986,
+        "genre": "Puzzle",
+        "name": "Quinty Quest",
+        "platforms": ["MSX"]
+    }))
+    .unwrap();
+    let added = database.add_game(game.clone()).unwrap();
+    assert_eq!(added.name, "Quinty Quest");
+    assert_eq!(database.get(id).unwrap().platforms, ["MSX"]);
+    assert_eq!(database.search_games("quinty").len(), 1);
+    assert!(database.games().windows(2).all(|w| w[0].year <= w[1].year));
+    assert_eq!(database.next_user_game_id(), id + 1);
+
+    // IDs can't be taken twice, and games need a name
+    assert!(database.add_game(game.clone()).is_err());
+    let unnamed = PackGame {
+        id: id + 1,
+        name: " ".to_string(),
+        ..game.clone()
+    };
+    assert!(database.add_game(unnamed).is_err());
+
+    // User games survive a restart through the user games file
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("user_games.json");
+    database.save_user_games(&path).unwrap();
+    let mut reloaded = GameDatabase::from_timeline(LoadedTimeline::new(embedded, Vec::new()));
+    assert_eq!(reloaded.load_user_games(&path).unwrap(), 1);
+    assert_eq!(reloaded.user_games()[0].name, "Quinty Quest");
+    assert_eq!(
+        reloaded
+            .load_user_games(dir.path().join("missing.json"))
+            .unwrap(),
+        0
+    );
+
+    // Only user games can be removed
+    assert!(database.remove_game(embedded[0].id).is_err());
+    assert_eq!(database.remove_game(id).unwrap().name, "Quinty Quest");
+    assert!(database.get(id).is_none());
+    assert_eq!(database.len(), embedded.len());
+
+    // A database file stands in for the embedded timeline
+    let file = GameDatabaseFile {
+        format_version: 1,
+        games: vec![game],
+    };
+    let database_path = dir.path().join("database.json");
+    std::fs::write(&database_path, serde_json::to_string(&file).unwrap()).unwrap();
+    let custom = GameDatabase::from_file(&database_path).unwrap();
+    assert!(custom.games().iter().any(|g| g.name == "Quinty Quest"));
+    assert!(custom.get(embedded[0].id).is_none());
+    assert!(custom.user_games().is_empty());
+}
+
+#[test]
+fn test_fuzzy_search() {
+    use vintage_games::SearchFilters;
+    use vintage_games::database::GameDatabase;
+    use vintage_games::era_packs::{LoadedTimeline, PackGame};
+
+    let mut database = GameDatabase::from_timeline(LoadedTimeline::new(&[], Vec::new()));
+    for (id, name, year, genre, platforms) in [
+        (
+            1,
+            "The Legend of Zelda",
+            1986,
+            "Action-Adventure",
+            vec!["NES", "Famicom Disk System"],
+        ),
+        (
+            2,
+            "Zelda II: The Adventure of Link",
+            1987,
+            "Action RPG",
+            vec!["NES"],
+        ),
+        (3, "Zaxxon", 1982, "Shooter", vec!["Arcade"]),
+        (4, "Castlevania", 1986, "Platformer", vec!["NES"]),
+        (
+            5,
+            "Link's Awakening",
+            1993,
+            "Action-Adventure",
+            vec!["Game Boy"],
+        ),
+    ] {
+        let game: PackGame = serde_json::from_value(serde_json::json!({
+            "id": id,
+            "year": year,
+            "genre": genre,
+            "name": name,
+            "platforms": platforms,
+        }))
+        .unwrap();
+        database.add_game(game).unwrap();
+    }
+    let names = |query: &str, filters: &SearchFilters| -> Vec<&str> {
+        database
+            .search(query, filters)
+            .iter()
+            .map(|hit| hit.game.name)
+            .collect()
+    };
+    let any = SearchFilters::default();
+
+    // Typos still find the game, and only games close to the query
+    assert_eq!(
+        names("zeda", &any),
+        ["The Legend of Zelda", "Zelda II: The Adventure of Link"]
+    );
+    assert_eq!(names("castelvania", &any), ["Castlevania"]);
+    assert!(names("xyzzy", &any).is_empty());
+
+    // Better matches rank first: a name starting with the query beats one
+    // containing it, which beats a match on the genre
+    assert_eq!(
+        names("zelda", &any),
+        ["Zelda II: The Adventure of Link", "The Legend of Zelda"]
+    );
+    let adventure = names("adventure", &any);
+    assert_eq!(adventure[0], "Zelda II: The Adventure of Link");
+    assert_eq!(adventure.len(), 3);
+
+    // Filters narrow the hits down
+    let nes_1986 = SearchFilters {
+        years: Some(1985..=1986),
+        platform: Some("nes".to_string()),
+        genre: None,
+    };
+    assert_eq!(names("", &nes_1986), ["The Legend of Zelda", "Castlevania"]);
+    let action_adventure = SearchFilters {
+        genre: Some("action-adventure".to_string()),
+        ..SearchFilters::default()
+    };
+    assert_eq!(names("link", &action_adventure), ["Link's Awakening"]);
+    assert_eq!(database.search_games("zeda").len(), 2);
+}
+
+#[test]
+fn test_timeline_facets() {
+    use vintage_games::era_packs::PackGame;
+    use vintage_games::{Facets, PlatformFamily};
+
+    assert_eq!(PlatformFamily::of("NES"), PlatformFamily::Nintendo);
+    assert_eq!(
+        PlatformFamily::of("Game Boy Color"),
+        PlatformFamily::Nintendo
+    );
+    assert_eq!(PlatformFamily::of("Genesis"), PlatformFamily::Sega);
+    assert_eq!(PlatformFamily::of("Sega 32X"), PlatformFamily::Sega);
+    assert_eq!(PlatformFamily::of("PC"), PlatformFamily::Computer);
+    assert_eq!(PlatformFamily::of("NEC PC-9801"), PlatformFamily::Computer);
+    assert_eq!(PlatformFamily::of("PC-FX"), PlatformFamily::Nec);
+    assert_eq!(PlatformFamily::of("Atari ST"), PlatformFamily::Computer);
+    assert_eq!(PlatformFamily::of("Atari 2600"), PlatformFamily::Atari);
+    assert_eq!(PlatformFamily::of("Vectrex"), PlatformFamily::Other);
+
+    let games: Vec<_> = [
+        (1, "Platformer", "Nintendo", vec!["NES"]),
+        (2, "Platformer", "Sega", vec!["Genesis", "Game Gear"]),
+        (3, "Shooter", "Konami", vec!["NES", "MSX"]),
+        (4, "Shooter", "Irem", vec!["Arcade"]),
+    ]
+    .into_iter()
+    .map(|(id, genre, developer, platforms)| {
+        let game: PackGame = serde_json::from_value(serde_json::json!({
+            "id": id,
+            "year": 1988,
+            "genre": genre,
+            "name": format!("Game {id}"),
+            "developer": developer,
+            "platforms": platforms,
+        }))
+        .unwrap();
+        game.into_timeline_game()
+    })
+    .collect();
+    let matching = |facets: &Facets| -> Vec<u32> {
+        games
+            .iter()
+            .filter(|game| facets.matches(game))
+            .map(|game| game.id)
+            .collect()
+    };
+
+    // Picks within a facet widen, picks across facets narrow
+    let mut facets = Facets::default();
+    assert_eq!(matching(&facets), [1, 2, 3, 4]);
+    facets.toggle_family(PlatformFamily::Nintendo);
+    facets.toggle_family(PlatformFamily::Sega);
+    assert_eq!(matching(&facets), [1, 2, 3]);
+    facets.toggle_genre("Shooter");
+    assert_eq!(matching(&facets), [3]);
+
+    // Each facet is counted over the games the other facets allow
+    let counts = facets.counts(&games);
+    assert_eq!(
+        counts.genres,
+        [("Platformer".to_string(), 2), ("Shooter".to_string(), 1)]
+    );
+    assert_eq!(
+        counts.families,
+        [
+            (PlatformFamily::Nintendo, 1),
+            (PlatformFamily::Arcade, 1),
+            (PlatformFamily::Computer, 1)
+        ]
+    );
+    assert_eq!(counts.developers, [("Konami".to_string(), 1)]);
+
+    // Toggling again unpicks
+    facets.toggle_genre("Shooter");
+    facets.toggle_developer("Sega");
+    assert_eq!(matching(&facets), [2]);
+    facets.clear();
+    assert!(facets.is_empty());
+}
+
+#[test]
+fn test_similar_games() {
+    use vintage_game_generator::wizard::steps::guided::SimilarGames;
+
+    let game = &vintage_games::games::TIMELINE_GAMES[0];
+    let similar = SimilarGames::for_game(game.id, 5);
+    assert_eq!(similar.game_id, game.id);
+    assert!(!similar.games.is_empty() && similar.games.len() <= 5);
+
+    // The game itself is left out and the closest games come first
+    assert!(similar.games.iter().all(|(other, _)| other.id != game.id));
+    assert!(similar.games.windows(2).all(|w| w[0].1 >= w[1].1));
+    assert!(
+        similar
+            .games
+            .iter()
+            .all(|(_, score)| (0.0..=1.0).contains(score))
+    );
+
+    assert!(SimilarGames::for_game(u32::MAX, 5).games.is_empty());
+}
+
+#[test]
+fn test_game_clusters() {
+    use vintage_game_generator::wizard::steps::guided::GameClusters;
+
+    let clusters = GameClusters::compute();
+    assert!(clusters.is_current());
+    assert!(!clusters.clusters.is_empty());
+    assert!(clusters.clusters.iter().all(|cluster| cluster.len() > 1));
+    assert!(
+        clusters
+            .clusters
+            .windows(2)
+            .all(|w| w[0].len() >= w[1].len())
+    );
+
+    // Each suggested blend pairs games of different clusters
+    let cluster_of = |id: u32| {
+        clusters
+            .clusters
+            .iter()
+            .position(|cluster| cluster.iter().any(|game| game.id == id))
+    };
+    for (a, b, compatibility) in &clusters.blends {
+        assert!(cluster_of(a.id).is_none() || cluster_of(a.id) != cluster_of(b.id));
+        assert!((0.0..=1.0).contains(compatibility));
+    }
+    assert!(clusters.blends.windows(2).all(|w| w[0].2 >= w[1].2));
+}
+
+#[test]
+fn test_weighted_blend() {
+    use vintage_game_generator::wizard::steps::guided::blend::metadata::calculate_complexity;
+    use vintage_game_generator::wizard::steps::guided::{GuidedModeState, create_blend};
+
+    // A Chrono Trigger-like and a Contra-like game
+    let base = &vintage_games::games::TIMELINE_GAMES[0];
+    let rpg: &'static vintage_games::TimelineGame =
+        Box::leak(Box::new(vintage_games::TimelineGame {
+            id: 9_000_001,
+            name: "Weighted RPG",
+            genre: "Role-Playing",
+            ..base.clone()
+        }));
+    let shooter: &'static vintage_games::TimelineGame =
+        Box::leak(Box::new(vintage_games::TimelineGame {
+            id: 9_000_002,
+            name: "Weighted Shooter",
+            genre: "Shooter",
+            ..base.clone()
+        }));
+
+    let mut state = GuidedModeState::default();
+    state.selected_games.insert(rpg.id, rpg);
+    state.selected_games.insert(shooter.id, shooter);
+    state.game_weights.insert(rpg.id, 70.0);
+    state.game_weights.insert(shooter.id, 30.0);
+
+    let shares = state.game_shares();
+    assert!((shares[&rpg.id] - 0.7).abs() < 1e-5);
+    assert!((shares[&shooter.id] - 0.3).abs() < 1e-5);
+
+    create_blend(&mut state);
+    let blend = state.blend_result.as_ref().unwrap();
+    let weights = &blend.blend_path.weights;
+    assert!((weights[&rpg.id.to_string()] - 0.7).abs() < 1e-5);
+    assert!((weights[&shooter.id.to_string()] - 0.3).abs() < 1e-5);
+
+    // The heavier game leads the name and pulls the complexity its way
+    let expected = 0.7 * calculate_complexity(rpg) + 0.3 * calculate_complexity(shooter);
+    assert!(blend.name.starts_with(rpg.name));
+    assert!((blend.complexity_score - expected).abs() < 1e-4);
+    assert!(blend.genres["Role-Playing"] > blend.genres["Shooter"]);
+    assert!(blend.description.contains("70% Weighted RPG"));
+
+    // A game at zero stays a source but adds none of its genre
+    state.game_weights.insert(shooter.id, 0.0);
+    create_blend(&mut state);
+    let blend = state.blend_result.as_ref().unwrap();
+    assert_eq!(blend.blend_path.games.len(), 2);
+    assert!(!blend.genres.contains_key("Shooter"));
+}
+
+#[test]
+fn test_conflict_resolution() {
+    use vintage_game_generator::metaprompts::parse_resolutions;
+    use vintage_game_generator::wizard::steps::guided::blend::export::{
+        export_to_toml, generate_ai_prompt,
+    };
+    use vintage_game_generator::wizard::steps::guided::blend::resolution::MAX_SUGGESTIONS;
+    use vintage_game_generator::wizard::steps::guided::{
+        GuidedModeState, create_blend, export_blend_to_config,
+    };
+
+    // An action game and a strategy game pull in different directions
+    let base = &vintage_games::games::TIMELINE_GAMES[0];
+    let action: &'static vintage_games::TimelineGame =
+        Box::leak(Box::new(vintage_games::TimelineGame {
+            id: 9_000_011,
+            name: "Conflict Action",
+            genre: "Action",
+            ..base.clone()
+        }));
+    let strategy: &'static vintage_games::TimelineGame =
+        Box::leak(Box::new(vintage_games::TimelineGame {
+            id: 9_000_012,
+            name: "Conflict Strategy",
+            genre: "Strategy",
+            ..base.clone()
+        }));
+
+    let mut state = GuidedModeState::default();
+    state.selected_games.insert(action.id, action);
+    state.selected_games.insert(strategy.id, strategy);
+    create_blend(&mut state);
+
+    // Every conflict is offered once, with two or three ways to settle it
+    let conflict_count = state.resolutions.choices.len();
+    assert!(conflict_count > 0);
+    assert!(state.resolutions.choices.iter().all(|choice| {
+        (2..=MAX_SUGGESTIONS).contains(&choice.suggestions.len()) && choice.chosen.is_none()
+    }));
+    assert!(!state.resolutions.is_resolved());
+
+    // The AI's ideas replace the analysis' for conflicts nothing was picked for
+    state.resolutions.choices[0].chosen = Some(1);
+    let picked = state.resolutions.choices[0].suggestions[1].clone();
+    let ai_ideas = vec![
+        "Turn-based overworld, real-time bosses".to_string(),
+        "Real-time squads you command between waves".to_string(),
+    ];
+    state
+        .resolutions
+        .apply_suggestions(vec![ai_ideas.clone(); conflict_count]);
+    assert_eq!(state.resolutions.choices[0].suggestions[1], picked);
+    for choice in state.resolutions.choices.iter_mut().skip(1) {
+        assert_eq!(choice.suggestions, ai_ideas);
+        choice.chosen = Some(0);
+    }
+    assert!(state.resolutions.is_resolved());
+
+    // Blending again keeps the picks of the conflicts that remain
+    create_blend(&mut state);
+    assert!(state.resolutions.is_resolved());
+
+    // The picks are exported and written into the blend design prompt
+    let export = export_blend_to_config(&state).unwrap();
+    assert_eq!(export.conflict_resolutions.len(), conflict_count);
+    assert_eq!(export.conflict_resolutions[0].resolution, picked);
+    assert!(
+        export_to_toml(&state)
+            .unwrap()
+            .contains("[[conflict_resolutions]]")
+    );
+    let prompt = generate_ai_prompt(&state).unwrap();
+    assert!(prompt.contains("DESIGN DECISIONS"));
+    assert!(prompt.contains(&picked));
+
+    assert_eq!(
+        parse_resolutions("1. \"Co-op raids\"\n\n- Pause to plan\n* Hybrid\nExtra", 3),
+        vec!["Co-op raids", "Pause to plan", "Hybrid"]
+    );
+}
+
+#[test]
+fn test_blend_explanation_context() {
+    use vintage_game_generator::wizard::steps::guided::blend::explain::{
+        blend_conversation_context, describe_blend,
+    };
+    use vintage_game_generator::wizard::steps::guided::{GuidedModeState, create_blend};
+
+    let base = &vintage_games::games::TIMELINE_GAMES[0];
+    let rpg: &'static vintage_games::TimelineGame =
+        Box::leak(Box::new(vintage_games::TimelineGame {
+            id: 9_000_021,
+            name: "Explained RPG",
+            genre: "Role-Playing",
+            ..base.clone()
+        }));
+    let platformer: &'static vintage_games::TimelineGame =
+        Box::leak(Box::new(vintage_games::TimelineGame {
+            id: 9_000_022,
+            name: "Explained Platformer",
+            genre: "Platform",
+            ..base.clone()
+        }));
+
+    let mut state = GuidedModeState::default();
+    assert!(blend_conversation_context(&state).is_none());
+    state.selected_games.insert(rpg.id, rpg);
+    state.selected_games.insert(platformer.id, platformer);
+    state.game_weights.insert(rpg.id, 75.0);
+    state.game_weights.insert(platformer.id, 25.0);
+    create_blend(&mut state);
+    let blend = state.blend_result.clone().unwrap();
+
+    // The conversation is told the concrete blend, synergy scores included
+    let description = describe_blend(&state).unwrap();
+    assert!(description.contains(&blend.name));
+    assert!(description.contains(&format!(
+        "- Explained RPG ({}, Role-Playing): 75% of the blend",
+        base.year
+    )));
+    for synergy in &blend.synergies {
+        assert!(description.contains(&format!("(strength {:.2})", synergy.strength)));
+    }
+
+    let context = blend_conversation_context(&state).unwrap();
+    assert_eq!(context.conversation_type, "blend_exploration");
+    let system_prompt = context.system_prompt.unwrap();
+    assert!(system_prompt.contains("blending classic game mechanics"));
+    assert!(system_prompt.ends_with(&description));
+
+    let concept = context.game_concept.unwrap();
+    assert_eq!(concept.title, blend.name);
+    assert_eq!(concept.genre, "Role-Playing");
+    let current = concept.current_blend.unwrap();
+    assert_eq!(current.selected_games.len(), 2);
+    assert!((current.blend_weights["Explained RPG"] - 0.75).abs() < 1e-5);
+    assert!((current.blend_weights["Explained Platformer"] - 0.25).abs() < 1e-5);
+    assert_eq!(current.dominant_attributes[0], "Role-Playing");
+
+    // A conversation that hasn't started has nothing to catch up on
+    assert!(!state.explanation.is_outdated(Some(&description)));
+}
+
+#[test]
+fn test_surprise_blend() {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use std::collections::HashSet;
+    use vintage_game_generator::vintage_games::surprise::{
+        SURPRISE_GAMES, draw_games, surprise_games,
+    };
+    use vintage_game_generator::wizard::steps::guided::{GuidedModeState, create_surprise_blend};
+
+    // A seed always draws the same games, two or three of them, all different
+    let ids = |seed| {
+        surprise_games(seed)
+            .iter()
+            .map(|g| g.id)
+            .collect::<Vec<_>>()
+    };
+    for seed in 0..5 {
+        let games = ids(seed);
+        assert_eq!(games, ids(seed));
+        assert!(SURPRISE_GAMES.contains(&games.len()));
+        assert_eq!(games.iter().collect::<HashSet<_>>().len(), games.len());
+    }
+    assert!((0..5).any(|seed| ids(seed) != ids(seed + 5)));
+
+    // Games come from different eras while the timeline has enough of them
+    let games = draw_games(&mut StdRng::seed_from_u64(7), 3);
+    assert_eq!(games.len(), 3);
+    let eras: HashSet<_> = games
+        .iter()
+        .map(|g| vintage_games::era_for_year(g.year))
+        .collect();
+    assert_eq!(eras.len(), 3);
+
+    // The pick replaces the selection and is blended at equal weights
+    let mut state = GuidedModeState::default();
+    state.game_weights.insert(1, 90.0);
+    assert!(create_surprise_blend(&mut state, 42));
+    let picked: HashSet<u32> = state.selected_games.keys().copied().collect();
+    assert_eq!(picked, ids(42).into_iter().collect());
+    assert!(state.game_weights.is_empty());
+    let blend = state.blend_result.as_ref().unwrap();
+    assert_eq!(blend.blend_path.games.len(), picked.len());
+}
+
+#[test]
+fn test_generation_seed() {
+    use vintage_game_generator::vintage_games::surprise::{project_surprise_seed, surprise_games};
+    use vintage_game_generator::wizard::config::ProjectConfig;
+
+    // Every project gets its own seed, and it survives project.toml
+    let config = ProjectConfig::default();
+    assert_ne!(
+        config.generation_seed,
+        ProjectConfig::default().generation_seed
+    );
+    let saved = toml::to_string_pretty(&config).expect("Failed to serialize");
+    let loaded: ProjectConfig = toml::from_str(&saved).expect("Failed to parse");
+    assert_eq!(loaded.generation_seed, config.generation_seed);
+
+    // A project file from before seeds gets the one a run used recorded,
+    // and keeps it on later runs
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("project.toml");
+    let legacy = saved
+        .lines()
+        .filter(|line| !line.starts_with("generation_seed"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    std::fs::write(&path, legacy).unwrap();
+    let first = ProjectConfig::load(&path).unwrap();
+    first.record_generation_seed(&path).unwrap();
+    let second = ProjectConfig::load(&path).unwrap();
+    assert_eq!(second.generation_seed, first.generation_seed);
+    assert_eq!(second.basic_info.name, config.basic_info.name);
+    second.record_generation_seed(&path).unwrap();
+    let recorded = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(recorded.matches("generation_seed").count(), 1);
+
+    // A project's surprises follow from its seed, one after the other
+    let seed = config.generation_seed;
+    assert_eq!(project_surprise_seed(seed, 0), seed);
+    let ids = |draws| -> Vec<u32> {
+        surprise_games(project_surprise_seed(seed, draws))
+            .iter()
+            .map(|game| game.id)
+            .collect()
+    };
+    assert_eq!(ids(0), ids(0));
+    assert!((0..5).any(|draws| ids(draws) != ids(draws + 1)));
+}
+
 // Run the tests with:
 // cargo test --package vintage_game_generator --test integration_tests
+
+#[test]
+fn test_tutorial_script() {
+    use vintage_game_generator::wizard::i18n::{LANGUAGES, Localizer};
+    use vintage_game_generator::wizard::steps::guided::{GuidedModeState, create_blend};
+    use vintage_game_generator::wizard::tutorial::{
+        TutorialScript, generate_sample_assets, tutorial_games,
+    };
+
+    let path = format!("{}/assets/wizard/tutorial.ron", env!("CARGO_MANIFEST_DIR"));
+    let script = TutorialScript::from_ron_file(&path).expect("Failed to load the tutorial");
+    let builtin = TutorialScript::default();
+    assert_eq!(script.steps.len(), builtin.steps.len());
+
+    // Callouts are in the catalogs and each points at a widget of its own
+    for language in LANGUAGES {
+        let localizer = Localizer::new(language.id);
+        for step in &script.steps {
+            for id in [&step.title_id, &step.body_id, &step.hotspot.label] {
+                assert_ne!(&localizer.t(id), id, "{id} is missing in {}", language.id);
+            }
+        }
+    }
+    let anchors: Vec<&str> = script.steps.iter().map(|s| s.hotspot.id.as_str()).collect();
+    let builtin_anchors: Vec<&str> = builtin
+        .steps
+        .iter()
+        .map(|s| s.hotspot.id.as_str())
+        .collect();
+    assert_eq!(anchors, builtin_anchors);
+    let mut distinct = anchors.clone();
+    distinct.sort();
+    distinct.dedup();
+    assert_eq!(distinct.len(), anchors.len());
+
+    // The sample project comes from the offline service
+    let mut guided = GuidedModeState::default();
+    for game in tutorial_games() {
+        guided.selected_games.insert(game.id, game);
+    }
+    create_blend(&mut guided);
+    let project = TempDir::new().unwrap();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let written = generate_sample_assets(project.path(), &guided, &runtime).unwrap();
+    assert_eq!(written.len(), 2);
+    let sprite = std::fs::read(&written[0]).unwrap();
+    assert!(image::load_from_memory(&sprite).is_ok());
+    let design = std::fs::read_to_string(&written[1]).unwrap();
+    assert!(design.starts_with("# Offline placeholder"));
+    assert!(design.contains(&guided.blend_result.unwrap().name));
+}
+
+#[test]
+fn test_suggestion_cache() {
+    use vintage_game_generator::wizard::steps::guided::GuidedModeState;
+    use vintage_game_generator::wizard::steps::guided::preferences::ranked_suggestions;
+
+    let games = vintage_games::all_games();
+    let mut state = GuidedModeState::default();
+    state.selected_games.insert(games[0].id, games[0]);
+
+    let ranked = ranked_suggestions(&mut state).to_vec();
+    assert_eq!(ranked.len(), games.len() - 1);
+    assert!(ranked.iter().all(|(game, _)| game.id != games[0].id));
+    assert!(ranked.windows(2).all(|w| w[0].1 >= w[1].1));
+    assert_eq!(state.ui_state.suggestions.metadata.len(), games.len());
+    assert_eq!(
+        state.ui_state.suggestions.revision,
+        vintage_games::database_revision()
+    );
+
+    // Nothing changed: the ranking is served as cached
+    let stale = |state: &mut GuidedModeState| {
+        state
+            .ui_state
+            .suggestions
+            .ranked
+            .as_mut()
+            .unwrap()
+            .truncate(1);
+    };
+    stale(&mut state);
+    assert_eq!(ranked_suggestions(&mut state).len(), 1);
+
+    // The selection changed
+    state.selected_games.insert(games[1].id, games[1]);
+    assert_eq!(ranked_suggestions(&mut state).len(), games.len() - 2);
+
+    // The ratings changed
+    stale(&mut state);
+    state.ui_state.suggestions.invalidate_ranking();
+    assert_eq!(ranked_suggestions(&mut state).len(), games.len() - 2);
+
+    // The database changed
+    stale(&mut state);
+    state.ui_state.suggestions.revision = state.ui_state.suggestions.revision.wrapping_add(1);
+    assert_eq!(ranked_suggestions(&mut state).len(), games.len() - 2);
+}
+
+#[test]
+fn test_timeline_stats() {
+    use vintage_game_generator::wizard::steps::guided::TimelineStats;
+
+    let base = &vintage_games::games::TIMELINE_GAMES[0];
+    let game = |id, year, genre, platforms| vintage_games::TimelineGame {
+        id,
+        year,
+        genre,
+        platforms,
+        ..base.clone()
+    };
+    let games = [
+        game(9_100_001, 1985, "Platformer", &["NES"]),
+        game(9_100_002, 1985, "Shooter", &["NES", "Arcade"]),
+        game(9_100_003, 1990, "Platformer", &["SNES"]),
+    ];
+    let stats = TimelineStats::from_games(&games);
+
+    assert_eq!(
+        stats.games_per_year.into_iter().collect::<Vec<_>>(),
+        [(1985, 2), (1990, 1)]
+    );
+    let genre = |name: &str| {
+        stats.genre_counts[name]
+            .iter()
+            .map(|(year, count)| (*year, *count))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(genre("Platformer"), [(1985, 1), (1990, 1)]);
+    assert_eq!(genre("Shooter"), [(1985, 1)]);
+    assert_eq!(stats.platform_lifecycles["NES"], (1985, 1985, 2));
+    assert_eq!(stats.platform_lifecycles["Arcade"], (1985, 1985, 1));
+    assert_eq!(stats.platform_lifecycles["SNES"], (1990, 1990, 1));
+
+    // Mechanic prevalence is a share of the year's games
+    assert!(!stats.mechanic_prevalence.is_empty());
+    assert!(stats.mechanic_prevalence.len() <= 6);
+    for years in stats.mechanic_prevalence.values() {
+        for (year, share) in years {
+            match year {
+                1985 => assert!(*share == 0.5 || *share == 1.0),
+                1990 => assert_eq!(*share, 1.0),
+                _ => panic!("no games in {year}"),
+            }
+        }
+    }
+
+    assert!(TimelineStats::from_timeline().is_current());
+}
+
+#[test]
+fn test_prompt_inspector_analysis() {
+    use vintage_ai_client::tokens::TokenCounter;
+    use vintage_game_generator::wizard::prompt_inspector::analyze_template;
+
+    let template = "You are designing {{ project.name }}.\n\
+        {% if project.setting %}Setting: {{ project.setting }}\n\n{% endif %}\
+        Inspirations:\n{% for game in project.games %}- {{ game }}\n{% endfor %}\
+        Rivals:\n{% for game in project.rivals %}- {{ game }}\n{% endfor %}\
+        Answer in JSON.";
+    let context = serde_json::json!({
+        "project": {
+            "name": "Star Quest",
+            "setting": "a drowned kingdom of coral towers",
+            "games": ["The Legend of Zelda", "Ys"],
+            "rivals": ["Ys"]
+        }
+    });
+    let counter = TokenCounter::new();
+    let analysis = analyze_template(template, &context, "project", "gpt-4o", &counter).unwrap();
+
+    let lines: Vec<&str> = analysis.prompt.lines().collect();
+    assert_eq!(lines[1], "Setting: a drowned kingdom of coral towers");
+    assert_eq!(lines[4], "- The Legend of Zelda");
+    assert_eq!(
+        analysis.total_tokens,
+        counter.count_tokens(&analysis.prompt, "gpt-4o").unwrap()
+    );
+
+    let section = |name: &str| {
+        analysis
+            .sections
+            .iter()
+            .find(|s| s.name == name)
+            .unwrap_or_else(|| panic!("no {name} section"))
+    };
+    assert_eq!(analysis.sections.len(), 4);
+    // The blank line after the setting disappears with it but isn't marked
+    assert_eq!(section("setting").lines, [1]);
+    assert_eq!(section("name").lines, [0]);
+    // A line both lists render stays once the other list still renders it
+    assert_eq!(section("rivals").lines, [7]);
+    assert_eq!(section("games").lines.len(), 2);
+    assert!(section("games").lines.contains(&4));
+
+    assert!(analysis.sections.iter().all(|s| s.tokens > 0));
+    assert!(
+        analysis
+            .sections
+            .windows(2)
+            .all(|pair| pair[0].tokens >= pair[1].tokens)
+    );
+    let attributed: usize = analysis.sections.iter().map(|s| s.tokens).sum();
+    assert_eq!(
+        analysis.template_tokens(),
+        analysis.total_tokens - attributed
+    );
+
+    // Without the root object every token belongs to the template
+    let analysis = analyze_template(template, &context, "missing", "gpt-4o", &counter).unwrap();
+    assert!(analysis.sections.is_empty());
+    assert_eq!(analysis.template_tokens(), analysis.total_tokens);
+
+    assert!(analyze_template("{% if %}", &context, "project", "gpt-4o", &counter).is_err());
+}
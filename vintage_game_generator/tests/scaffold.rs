@@ -0,0 +1,29 @@
+//! Tests for the sources copied into exported game projects
+//!
+//! The scaffold in `vintage_ai_client/scaffold/` only builds inside a
+//! generated game, so it is compiled here as modules to catch Bevy API drift,
+//! and its plugins are run headless for a frame.
+
+use bevy::prelude::*;
+
+/// The exported entry point with the plugin modules it declares
+#[allow(dead_code)]
+#[path = "../../vintage_ai_client/scaffold/main.rs"]
+mod exported_main;
+
+use exported_main::{game_data, speedrun};
+
+#[test]
+fn test_scaffold_plugins_run_headless() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        bevy::asset::AssetPlugin::default(),
+        bevy::input::InputPlugin,
+    ))
+    .add_plugins((game_data::GameDataPlugin, speedrun::SpeedrunPlugin));
+    app.update();
+
+    assert!(app.world().contains_resource::<game_data::GameData>());
+    assert!(!app.world().resource::<speedrun::SpeedrunTimer>().running);
+}